@@ -5,6 +5,8 @@
 //! - `Schema`, `FieldSchema`: DDL generation types
 //! - `HasSchema`: Trait for entity type introspection
 //! - `EntitySchema`, `FieldType`: Schema metadata types
+//! - `ValidationError`: Field constraint violations reported by `HasSchema::validate`
+//! - `TypedEntity`: A `StorageEntity` whose fields are checked against an `EntitySchema`
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -116,7 +118,8 @@ impl Schema {
                 col.push_str(" PRIMARY KEY");
             }
 
-            if !field.nullable {
+            // Computed fields are filled in after the row is written
+            if !field.nullable && field.computed.is_none() {
                 col.push_str(" NOT NULL");
             }
 
@@ -143,6 +146,11 @@ impl Schema {
             })
             .collect()
     }
+
+    /// Fields whose values are written by callers, i.e. everything but computed fields
+    pub fn stored_fields(&self) -> impl Iterator<Item = &FieldSchema> {
+        self.fields.iter().filter(|f| f.computed.is_none())
+    }
 }
 
 /// Schema for a single field in a table.
@@ -153,6 +161,11 @@ pub struct FieldSchema {
     pub nullable: bool,
     pub primary_key: bool,
     pub indexed: bool,
+    /// PRQL expression deriving this field from other fields of the same row
+    ///
+    /// Computed fields are never written by upserts; the storage layer keeps them
+    /// up to date when their source fields change.
+    pub computed: Option<String>,
 }
 
 impl FieldSchema {
@@ -163,6 +176,7 @@ impl FieldSchema {
             nullable: false,
             primary_key: false,
             indexed: false,
+            computed: None,
         }
     }
 
@@ -180,6 +194,11 @@ impl FieldSchema {
         self.indexed = true;
         self
     }
+
+    pub fn computed(mut self, expr: impl Into<String>) -> Self {
+        self.computed = Some(expr.into());
+        self
+    }
 }
 
 // =============================================================================
@@ -201,6 +220,134 @@ pub trait HasSchema {
     fn from_entity(entity: DynamicEntity) -> Result<Self>
     where
         Self: Sized;
+
+    /// Check field constraints on a (possibly partial) dynamic entity
+    ///
+    /// Only fields present in `entity` are checked, so this also works for
+    /// `create` parameters and single-field updates. `#[derive(Entity)]` generates
+    /// it from `#[validate(...)]` field attributes; by default nothing is checked.
+    fn validate_entity(_entity: &DynamicEntity) -> std::result::Result<(), Vec<ValidationError>> {
+        Ok(())
+    }
+
+    /// Check this entity's field constraints
+    fn validate(&self) -> std::result::Result<(), Vec<ValidationError>> {
+        Self::validate_entity(&self.to_entity())
+    }
+}
+
+// =============================================================================
+// Validation - Declarative field constraints
+// =============================================================================
+
+/// A single field constraint violation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationError {
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// All constraint violations of an entity, usable as an operation error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationErrors(pub Vec<ValidationError>);
+
+impl std::fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let messages: Vec<String> = self.0.iter().map(|e| e.to_string()).collect();
+        write!(f, "Validation failed: {}", messages.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// Check `#[validate(length(min = .., max = ..))]` on a field
+///
+/// Counts characters of strings and elements of arrays. Missing and null
+/// values are not checked.
+pub fn validate_length(
+    entity: &DynamicEntity,
+    field: &str,
+    min: Option<usize>,
+    max: Option<usize>,
+    errors: &mut Vec<ValidationError>,
+) {
+    let len = match entity.get(field) {
+        Some(Value::String(s)) => s.chars().count(),
+        Some(Value::Array(items)) => items.len(),
+        _ => return,
+    };
+    if let Some(min) = min {
+        if len < min {
+            errors.push(ValidationError::new(
+                field,
+                format!("must have at least {} characters", min),
+            ));
+            return;
+        }
+    }
+    if let Some(max) = max {
+        if len > max {
+            errors.push(ValidationError::new(
+                field,
+                format!("must have at most {} characters", max),
+            ));
+        }
+    }
+}
+
+/// Check `#[validate(range(min = .., max = ..))]` on a numeric field
+///
+/// Missing and null values are not checked.
+pub fn validate_range(
+    entity: &DynamicEntity,
+    field: &str,
+    min: Option<f64>,
+    max: Option<f64>,
+    errors: &mut Vec<ValidationError>,
+) {
+    let value = match entity.get(field) {
+        Some(Value::Integer(i)) => *i as f64,
+        Some(Value::Float(f)) => *f,
+        Some(Value::Null) | None => return,
+        Some(_) => {
+            errors.push(ValidationError::new(field, "must be a number"));
+            return;
+        }
+    };
+    if let Some(min) = min {
+        if value < min {
+            errors.push(ValidationError::new(
+                field,
+                format!("must be at least {}", min),
+            ));
+            return;
+        }
+    }
+    if let Some(max) = max {
+        if value > max {
+            errors.push(ValidationError::new(
+                field,
+                format!("must be at most {}", max),
+            ));
+        }
+    }
 }
 
 // =============================================================================
@@ -238,6 +385,35 @@ pub enum FieldType {
 }
 
 impl FieldType {
+    /// Whether a value of this field may be `value` (not considering nullability)
+    ///
+    /// Integers may be whole floats (as decoded from JSON numbers), booleans may be
+    /// SQLite integers, date-times and references may be plain strings, and JSON fields
+    /// may hold any value.
+    pub fn accepts(&self, value: &Value) -> bool {
+        match (self, value) {
+            (FieldType::String, Value::String(_)) => true,
+            (FieldType::Integer, Value::Integer(_)) => true,
+            (FieldType::Integer, Value::Float(f)) => f.fract() == 0.0,
+            (FieldType::Boolean, Value::Boolean(_) | Value::Integer(0 | 1)) => true,
+            (FieldType::DateTime, Value::DateTime(_) | Value::String(_)) => true,
+            (FieldType::Json, _) => true,
+            (FieldType::Reference(_), Value::Reference(_) | Value::String(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            FieldType::String => "a string".to_string(),
+            FieldType::Integer => "an integer".to_string(),
+            FieldType::Boolean => "a boolean".to_string(),
+            FieldType::DateTime => "a date-time".to_string(),
+            FieldType::Json => "JSON".to_string(),
+            FieldType::Reference(entity) => format!("a reference to {}", entity),
+        }
+    }
+
     /// Convert to SQLite type string
     pub fn to_sqlite_type(&self) -> &'static str {
         match self {
@@ -257,3 +433,228 @@ impl FieldType {
 
 /// Type alias for entity storage as HashMap
 pub type StorageEntity = HashMap<String, Value>;
+
+// =============================================================================
+// TypedEntity - StorageEntity checked against an EntitySchema
+// =============================================================================
+
+/// A field access that doesn't match an entity's schema
+///
+/// flutter_rust_bridge:ignore
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EntityFieldError {
+    #[error("{entity} has no field {field}")]
+    UnknownField { entity: String, field: String },
+
+    #[error("Field {field} of {entity} must be {expected}, got {actual}")]
+    WrongType {
+        entity: String,
+        field: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("Missing field {field} of {entity}")]
+    MissingField { entity: String, field: String },
+}
+
+/// A `StorageEntity` whose reads and writes are checked against an `EntitySchema`
+///
+/// A raw `StorageEntity` silently returns `None` for a misspelled field name and
+/// accepts values of any type. Through a `TypedEntity`, unknown fields, values of the
+/// wrong type and missing required fields are errors instead. Null is accepted for
+/// JSON fields and fields that aren't required.
+///
+/// flutter_rust_bridge:ignore
+#[derive(Debug, Clone)]
+pub struct TypedEntity<'schema> {
+    schema: &'schema EntitySchema,
+    fields: StorageEntity,
+}
+
+impl<'schema> TypedEntity<'schema> {
+    /// An entity without fields
+    pub fn new(schema: &'schema EntitySchema) -> Self {
+        Self {
+            schema,
+            fields: StorageEntity::new(),
+        }
+    }
+
+    /// Check all fields of `fields`, and that the required ones are present
+    pub fn try_from_fields(
+        schema: &'schema EntitySchema,
+        fields: StorageEntity,
+    ) -> std::result::Result<Self, EntityFieldError> {
+        for (name, value) in &fields {
+            check_field(schema, name, value)?;
+        }
+        let entity = Self { schema, fields };
+        entity.check_required()?;
+        Ok(entity)
+    }
+
+    /// Take and check the fields of the schema from `params`, ignoring other entries
+    ///
+    /// Operation parameters often carry a whole row, of which an operation only
+    /// uses some columns, so extra entries are not errors here.
+    pub fn from_params(
+        schema: &'schema EntitySchema,
+        params: &StorageEntity,
+    ) -> std::result::Result<Self, EntityFieldError> {
+        let mut fields = StorageEntity::new();
+        for field in &schema.fields {
+            if let Some(value) = params.get(&field.name) {
+                check_field(schema, &field.name, value)?;
+                fields.insert(field.name.clone(), value.clone());
+            }
+        }
+        let entity = Self { schema, fields };
+        entity.check_required()?;
+        Ok(entity)
+    }
+
+    pub fn schema(&self) -> &'schema EntitySchema {
+        self.schema
+    }
+
+    /// Value of a field of the schema (None if unset)
+    pub fn get(&self, name: &str) -> std::result::Result<Option<&Value>, EntityFieldError> {
+        field_schema(self.schema, name)?;
+        Ok(self.fields.get(name))
+    }
+
+    /// Set a field of the schema to a value of its type
+    pub fn set(
+        &mut self,
+        name: &str,
+        value: impl Into<Value>,
+    ) -> std::result::Result<(), EntityFieldError> {
+        let value = value.into();
+        check_field(self.schema, name, &value)?;
+        self.fields.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    pub fn as_storage(&self) -> &StorageEntity {
+        &self.fields
+    }
+
+    pub fn into_storage(self) -> StorageEntity {
+        self.fields
+    }
+
+    fn check_required(&self) -> std::result::Result<(), EntityFieldError> {
+        match self
+            .schema
+            .fields
+            .iter()
+            .find(|f| f.required && !self.fields.contains_key(&f.name))
+        {
+            Some(field) => Err(EntityFieldError::MissingField {
+                entity: self.schema.name.clone(),
+                field: field.name.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+fn field_schema<'a>(
+    schema: &'a EntitySchema,
+    name: &str,
+) -> std::result::Result<&'a EntityFieldSchema, EntityFieldError> {
+    schema
+        .fields
+        .iter()
+        .find(|f| f.name == name)
+        .ok_or_else(|| EntityFieldError::UnknownField {
+            entity: schema.name.clone(),
+            field: name.to_string(),
+        })
+}
+
+fn check_field(
+    schema: &EntitySchema,
+    name: &str,
+    value: &Value,
+) -> std::result::Result<(), EntityFieldError> {
+    let field = field_schema(schema, name)?;
+    let accepted = match value {
+        // Null is a JSON value, e.g. to clear a field through `set_field`
+        Value::Null => !field.required || matches!(field.field_type, FieldType::Json),
+        value => field.field_type.accepts(value),
+    };
+    if accepted {
+        return Ok(());
+    }
+    Err(EntityFieldError::WrongType {
+        entity: schema.name.clone(),
+        field: name.to_string(),
+        expected: field.field_type.describe(),
+        actual: value.to_json_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task_schema() -> EntitySchema {
+        let field = |name: &str, field_type: FieldType, required: bool| EntityFieldSchema {
+            name: name.to_string(),
+            field_type,
+            required,
+            indexed: false,
+        };
+        EntitySchema {
+            name: "tasks".to_string(),
+            fields: vec![
+                field("id", FieldType::String, true),
+                field("priority", FieldType::Integer, false),
+                field("completed", FieldType::Boolean, false),
+            ],
+            primary_key: "id".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_typed_entity_checks_fields() {
+        let schema = task_schema();
+        let mut task = TypedEntity::new(&schema);
+        task.set("id", "t1").unwrap();
+        task.set("priority", 3i64).unwrap();
+        assert_eq!(task.get("priority").unwrap(), Some(&Value::Integer(3)));
+        assert_eq!(task.get("completed").unwrap(), None);
+
+        assert!(matches!(
+            task.get("priorty"),
+            Err(EntityFieldError::UnknownField { .. })
+        ));
+        assert!(matches!(
+            task.set("priority", "high"),
+            Err(EntityFieldError::WrongType { .. })
+        ));
+        assert!(matches!(
+            task.set("id", Value::Null),
+            Err(EntityFieldError::WrongType { .. })
+        ));
+
+        // SQLite booleans are accepted, extra params are dropped
+        let params = HashMap::from([
+            ("id".to_string(), Value::String("t1".to_string())),
+            ("completed".to_string(), Value::Integer(1)),
+            ("content".to_string(), Value::String("Buy milk".to_string())),
+        ]);
+        let task = TypedEntity::from_params(&schema, &params).unwrap();
+        assert_eq!(task.as_storage().len(), 2);
+        assert!(matches!(
+            TypedEntity::try_from_fields(&schema, params),
+            Err(EntityFieldError::UnknownField { field, .. }) if field == "content"
+        ));
+        assert!(matches!(
+            TypedEntity::from_params(&schema, &HashMap::new()),
+            Err(EntityFieldError::MissingField { field, .. }) if field == "id"
+        ));
+    }
+}
@@ -0,0 +1,554 @@
+//! Locale-aware formatting of values at render time
+//!
+//! `format_date`, `format_number` and `relative_time` calls in render expressions
+//! are compiled into `RenderExpr::Format` nodes. Frontends evaluate the node's
+//! value against the row and turn it into text with `Format::apply`, passing the
+//! `RenderLocale` of the device, so every frontend shows the same text.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{parse_datetime, Value};
+
+/// Length of a formatted date
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateStyle {
+    /// `3/1/2024`, `01.03.2024`
+    Short,
+    /// `Mar 1, 2024`, `1. März 2024`
+    #[default]
+    Medium,
+    /// `Friday, March 1, 2024`, `Freitag, 1. März 2024`
+    Long,
+}
+
+/// A formatting function applied to a value when it is rendered
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Format {
+    /// `(format_date this.due_date style:"long" time:true)`
+    Date { style: DateStyle, time: bool },
+    /// `(format_number this.amount decimals:2)`: digits grouped by thousands, with
+    /// `decimals` fraction digits (as many as needed if None)
+    Number { decimals: Option<u32> },
+    /// `(relative_time this.updated_at)`: "in 3 days", "2 hours ago"
+    RelativeTime,
+}
+
+/// Language and time zone values are formatted for, provided by the frontend
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderLocale {
+    /// BCP 47 language tag, e.g. "en-US" or "de-DE"
+    pub tag: String,
+    /// Offset from UTC of the time zone dates are shown in, in minutes
+    pub utc_offset_minutes: i32,
+}
+
+impl Default for RenderLocale {
+    fn default() -> Self {
+        Self::new("en-US", 0)
+    }
+}
+
+/// Languages with built-in formatting rules; others are formatted as English
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+impl RenderLocale {
+    pub fn new(tag: impl Into<String>, utc_offset_minutes: i32) -> Self {
+        Self {
+            tag: tag.into(),
+            utc_offset_minutes,
+        }
+    }
+
+    /// Locale of a POSIX locale name such as `de_DE.UTF-8` (the `LANG` variable)
+    pub fn from_posix(locale: &str, utc_offset_minutes: i32) -> Self {
+        let tag = locale
+            .split(['.', '@'])
+            .next()
+            .filter(|tag| !tag.is_empty() && *tag != "C" && *tag != "POSIX")
+            .unwrap_or("en-US")
+            .replace('_', "-");
+        Self::new(tag, utc_offset_minutes)
+    }
+
+    fn language(&self) -> Language {
+        let language = self.tag.split('-').next().unwrap_or_default();
+        match language.to_ascii_lowercase().as_str() {
+            "de" => Language::German,
+            "fr" => Language::French,
+            "es" => Language::Spanish,
+            _ => Language::English,
+        }
+    }
+
+    /// Whether dates are written month first (`3/1/2024`)
+    fn month_first(&self) -> bool {
+        self.language() == Language::English && self.region_is_us()
+    }
+
+    /// Whether the tag has no region or the US one
+    fn region_is_us(&self) -> bool {
+        match self.tag.split('-').nth(1) {
+            None => true,
+            Some(region) => region.eq_ignore_ascii_case("US"),
+        }
+    }
+
+    /// Time zone dates are shown in
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn offset(&self) -> chrono::FixedOffset {
+        chrono::FixedOffset::east_opt(self.utc_offset_minutes * 60)
+            .unwrap_or_else(|| chrono::FixedOffset::east_opt(0).unwrap())
+    }
+}
+
+impl Format {
+    /// Text of `value` in `locale`; `now` is the reference of relative times
+    ///
+    /// Values that can't be formatted this way are shown as they are.
+    /// flutter_rust_bridge:ignore
+    pub fn apply(
+        &self,
+        value: &Value,
+        locale: &RenderLocale,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> String {
+        let formatted = match self {
+            Format::Date { style, time } => {
+                datetime_of(value).map(|dt| format_date(dt, *style, *time, locale))
+            }
+            Format::Number { decimals } => {
+                number_of(value).map(|n| format_number(n, *decimals, locale))
+            }
+            Format::RelativeTime => datetime_of(value).map(|dt| relative_time(dt, now, locale)),
+        };
+        formatted.unwrap_or_else(|| plain_text(value))
+    }
+}
+
+fn datetime_of(value: &Value) -> Option<chrono::DateTime<chrono::Utc>> {
+    match value {
+        Value::DateTime(s) | Value::String(s) => parse_datetime(s),
+        _ => None,
+    }
+}
+
+fn number_of(value: &Value) -> Option<f64> {
+    match value {
+        Value::Integer(_) | Value::Float(_) => value.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn plain_text(value: &Value) -> String {
+    match value {
+        Value::String(s) | Value::DateTime(s) | Value::Reference(s) | Value::Json(s) => s.clone(),
+        Value::Null => String::new(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Boolean(b) => b.to_string(),
+        Value::Array(_) | Value::Object(_) => value.to_json_string(),
+    }
+}
+
+const MONTHS_EN: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const MONTHS_DE: [&str; 12] = [
+    "Januar",
+    "Februar",
+    "März",
+    "April",
+    "Mai",
+    "Juni",
+    "Juli",
+    "August",
+    "September",
+    "Oktober",
+    "November",
+    "Dezember",
+];
+const MONTHS_FR: [&str; 12] = [
+    "janvier",
+    "février",
+    "mars",
+    "avril",
+    "mai",
+    "juin",
+    "juillet",
+    "août",
+    "septembre",
+    "octobre",
+    "novembre",
+    "décembre",
+];
+const MONTHS_ES: [&str; 12] = [
+    "enero",
+    "febrero",
+    "marzo",
+    "abril",
+    "mayo",
+    "junio",
+    "julio",
+    "agosto",
+    "septiembre",
+    "octubre",
+    "noviembre",
+    "diciembre",
+];
+
+/// Weekdays starting on Monday
+const WEEKDAYS_EN: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+const WEEKDAYS_DE: [&str; 7] = [
+    "Montag",
+    "Dienstag",
+    "Mittwoch",
+    "Donnerstag",
+    "Freitag",
+    "Samstag",
+    "Sonntag",
+];
+const WEEKDAYS_FR: [&str; 7] = [
+    "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+];
+const WEEKDAYS_ES: [&str; 7] = [
+    "lunes",
+    "martes",
+    "miércoles",
+    "jueves",
+    "viernes",
+    "sábado",
+    "domingo",
+];
+
+fn format_date(
+    dt: chrono::DateTime<chrono::Utc>,
+    style: DateStyle,
+    time: bool,
+    locale: &RenderLocale,
+) -> String {
+    use chrono::{Datelike, Timelike};
+
+    let local = dt.with_timezone(&locale.offset());
+    let (day, year) = (local.day(), local.year());
+    let month_index = local.month0() as usize;
+    let weekday_index = local.weekday().num_days_from_monday() as usize;
+    let (months, weekdays) = match locale.language() {
+        Language::English => (MONTHS_EN, WEEKDAYS_EN),
+        Language::German => (MONTHS_DE, WEEKDAYS_DE),
+        Language::French => (MONTHS_FR, WEEKDAYS_FR),
+        Language::Spanish => (MONTHS_ES, WEEKDAYS_ES),
+    };
+    let month = months[month_index];
+    // Abbreviations are the first three letters, e.g. "Mar", "mär"
+    let month_short: String = month.chars().take(3).collect();
+    let weekday = weekdays[weekday_index];
+    let month_first = locale.month_first();
+
+    let date = match (style, locale.language()) {
+        (DateStyle::Short, Language::English) if month_first => {
+            format!("{}/{}/{}", local.month(), day, year)
+        }
+        (DateStyle::Short, Language::German) => {
+            format!("{:02}.{:02}.{}", day, local.month(), year)
+        }
+        (DateStyle::Short, _) => format!("{:02}/{:02}/{}", day, local.month(), year),
+        (DateStyle::Medium, Language::English) if month_first => {
+            format!("{} {}, {}", month_short, day, year)
+        }
+        (DateStyle::Medium, Language::English) => format!("{} {} {}", day, month_short, year),
+        (DateStyle::Medium, Language::German) => format!("{}. {} {}", day, month, year),
+        (DateStyle::Medium, Language::French) => format!("{} {} {}", day, month, year),
+        (DateStyle::Medium, Language::Spanish) => format!("{} {} {}", day, month_short, year),
+        (DateStyle::Long, Language::English) if month_first => {
+            format!("{}, {} {}, {}", weekday, month, day, year)
+        }
+        (DateStyle::Long, Language::English) => {
+            format!("{}, {} {} {}", weekday, day, month, year)
+        }
+        (DateStyle::Long, Language::German) => {
+            format!("{}, {}. {} {}", weekday, day, month, year)
+        }
+        (DateStyle::Long, Language::French) => format!("{} {} {} {}", weekday, day, month, year),
+        (DateStyle::Long, Language::Spanish) => {
+            format!("{}, {} de {} de {}", weekday, day, month, year)
+        }
+    };
+    if !time {
+        return date;
+    }
+
+    let time = if locale.month_first() {
+        let (pm, hour) = local.hour12();
+        format!(
+            "{}:{:02} {}",
+            hour,
+            local.minute(),
+            if pm { "PM" } else { "AM" }
+        )
+    } else {
+        format!("{:02}:{:02}", local.hour(), local.minute())
+    };
+    match locale.language() {
+        Language::French => format!("{} {}", date, time),
+        _ => format!("{}, {}", date, time),
+    }
+}
+
+fn format_number(n: f64, decimals: Option<u32>, locale: &RenderLocale) -> String {
+    let (decimal_separator, group_separator) = match locale.language() {
+        Language::English => (".", ","),
+        Language::German | Language::Spanish => (",", "."),
+        // Narrow no-break space
+        Language::French => (",", "\u{202f}"),
+    };
+    let text = match decimals {
+        Some(decimals) => format!("{:.*}", decimals as usize, n.abs()),
+        None => n.abs().to_string(),
+    };
+    let (integer, fraction) = match text.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (text.as_str(), None),
+    };
+
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push_str(group_separator);
+        }
+        grouped.push(digit);
+    }
+    let sign = if n < 0.0 && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        "-"
+    } else {
+        ""
+    };
+    match fraction {
+        Some(fraction) => format!("{}{}{}{}", sign, grouped, decimal_separator, fraction),
+        None => format!("{}{}", sign, grouped),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TimeUnit {
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+fn relative_time(
+    dt: chrono::DateTime<chrono::Utc>,
+    now: chrono::DateTime<chrono::Utc>,
+    locale: &RenderLocale,
+) -> String {
+    let seconds = (dt - now).num_seconds();
+    let abs = seconds.unsigned_abs() as f64;
+    let language = locale.language();
+    if abs < 45.0 {
+        return match language {
+            Language::English => "now",
+            Language::German => "jetzt",
+            Language::French => "maintenant",
+            Language::Spanish => "ahora",
+        }
+        .to_string();
+    }
+
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+    const MONTH: f64 = 30.44 * DAY;
+    const YEAR: f64 = 365.25 * DAY;
+    let (count, unit) = if abs < 45.0 * MINUTE {
+        (abs / MINUTE, TimeUnit::Minute)
+    } else if abs < 22.0 * HOUR {
+        (abs / HOUR, TimeUnit::Hour)
+    } else if abs < 26.0 * DAY {
+        (abs / DAY, TimeUnit::Day)
+    } else if abs < 11.0 * MONTH {
+        (abs / MONTH, TimeUnit::Month)
+    } else {
+        (abs / YEAR, TimeUnit::Year)
+    };
+    let count = (count.round() as u64).max(1);
+    let unit = unit_name(language, unit, count != 1);
+
+    let future = seconds > 0;
+    match (language, future) {
+        (Language::English, true) => format!("in {} {}", count, unit),
+        (Language::English, false) => format!("{} {} ago", count, unit),
+        (Language::German, true) => format!("in {} {}", count, unit),
+        (Language::German, false) => format!("vor {} {}", count, unit),
+        (Language::French, true) => format!("dans {} {}", count, unit),
+        (Language::French, false) => format!("il y a {} {}", count, unit),
+        (Language::Spanish, true) => format!("dentro de {} {}", count, unit),
+        (Language::Spanish, false) => format!("hace {} {}", count, unit),
+    }
+}
+
+fn unit_name(language: Language, unit: TimeUnit, plural: bool) -> &'static str {
+    use TimeUnit::*;
+    match (language, unit, plural) {
+        (Language::English, Minute, false) => "minute",
+        (Language::English, Minute, true) => "minutes",
+        (Language::English, Hour, false) => "hour",
+        (Language::English, Hour, true) => "hours",
+        (Language::English, Day, false) => "day",
+        (Language::English, Day, true) => "days",
+        (Language::English, Month, false) => "month",
+        (Language::English, Month, true) => "months",
+        (Language::English, Year, false) => "year",
+        (Language::English, Year, true) => "years",
+        // Dative, as both "in" and "vor" take it
+        (Language::German, Minute, false) => "Minute",
+        (Language::German, Minute, true) => "Minuten",
+        (Language::German, Hour, false) => "Stunde",
+        (Language::German, Hour, true) => "Stunden",
+        (Language::German, Day, false) => "Tag",
+        (Language::German, Day, true) => "Tagen",
+        (Language::German, Month, false) => "Monat",
+        (Language::German, Month, true) => "Monaten",
+        (Language::German, Year, false) => "Jahr",
+        (Language::German, Year, true) => "Jahren",
+        (Language::French, Minute, false) => "minute",
+        (Language::French, Minute, true) => "minutes",
+        (Language::French, Hour, false) => "heure",
+        (Language::French, Hour, true) => "heures",
+        (Language::French, Day, false) => "jour",
+        (Language::French, Day, true) => "jours",
+        (Language::French, Month, _) => "mois",
+        (Language::French, Year, false) => "an",
+        (Language::French, Year, true) => "ans",
+        (Language::Spanish, Minute, false) => "minuto",
+        (Language::Spanish, Minute, true) => "minutos",
+        (Language::Spanish, Hour, false) => "hora",
+        (Language::Spanish, Hour, true) => "horas",
+        (Language::Spanish, Day, false) => "día",
+        (Language::Spanish, Day, true) => "días",
+        (Language::Spanish, Month, false) => "mes",
+        (Language::Spanish, Month, true) => "meses",
+        (Language::Spanish, Year, false) => "año",
+        (Language::Spanish, Year, true) => "años",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datetime(s: &str) -> Value {
+        Value::parse_datetime(s).unwrap()
+    }
+
+    fn now() -> chrono::DateTime<chrono::Utc> {
+        parse_datetime("2024-03-01T12:00:00Z").unwrap()
+    }
+
+    #[test]
+    fn test_format_date() {
+        let value = datetime("2024-03-01T08:30:00Z");
+        let date = |style, time, tag: &str| {
+            Format::Date { style, time }.apply(&value, &RenderLocale::new(tag, 0), now())
+        };
+        assert_eq!(date(DateStyle::Short, false, "en-US"), "3/1/2024");
+        assert_eq!(date(DateStyle::Short, false, "en-GB"), "01/03/2024");
+        assert_eq!(date(DateStyle::Short, false, "de-DE"), "01.03.2024");
+        assert_eq!(date(DateStyle::Medium, false, "en-US"), "Mar 1, 2024");
+        assert_eq!(date(DateStyle::Medium, false, "de-DE"), "1. März 2024");
+        assert_eq!(
+            date(DateStyle::Long, true, "en-US"),
+            "Friday, March 1, 2024, 8:30 AM"
+        );
+        assert_eq!(
+            date(DateStyle::Long, false, "es-ES"),
+            "viernes, 1 de marzo de 2024"
+        );
+        assert_eq!(date(DateStyle::Medium, true, "fr-FR"), "1 mars 2024 08:30");
+    }
+
+    #[test]
+    fn test_format_date_in_time_zone() {
+        let value = datetime("2024-03-01T23:30:00Z");
+        let format = Format::Date {
+            style: DateStyle::Short,
+            time: true,
+        };
+        // Already the next day two hours east of UTC
+        assert_eq!(
+            format.apply(&value, &RenderLocale::new("de-DE", 120), now()),
+            "02.03.2024, 01:30"
+        );
+    }
+
+    #[test]
+    fn test_format_number() {
+        let number = |n: f64, decimals, tag: &str| {
+            Format::Number { decimals }.apply(&Value::Float(n), &RenderLocale::new(tag, 0), now())
+        };
+        assert_eq!(number(1234567.891, Some(2), "en-US"), "1,234,567.89");
+        assert_eq!(number(1234567.891, Some(2), "de-DE"), "1.234.567,89");
+        assert_eq!(number(-1234.5, None, "fr-FR"), "-1\u{202f}234,5");
+        assert_eq!(number(999.0, Some(0), "en-US"), "999");
+        assert_eq!(number(-0.001, Some(2), "en-US"), "0.00");
+        assert_eq!(
+            Format::Number { decimals: None }.apply(
+                &Value::String("n/a".to_string()),
+                &RenderLocale::default(),
+                now()
+            ),
+            "n/a"
+        );
+    }
+
+    #[test]
+    fn test_relative_time() {
+        let relative = |s: &str, tag: &str| {
+            Format::RelativeTime.apply(&datetime(s), &RenderLocale::new(tag, 0), now())
+        };
+        assert_eq!(relative("2024-03-01T12:00:10Z", "en-US"), "now");
+        assert_eq!(relative("2024-03-04T12:00:00Z", "en-US"), "in 3 days");
+        assert_eq!(relative("2024-03-01T10:00:00Z", "en-US"), "2 hours ago");
+        assert_eq!(relative("2024-02-29T12:00:00Z", "de-DE"), "vor 1 Tag");
+        assert_eq!(relative("2024-02-01T12:00:00Z", "de-DE"), "vor 1 Monat");
+        assert_eq!(relative("2026-03-01T12:00:00Z", "fr-FR"), "dans 2 ans");
+        assert_eq!(relative("2024-03-01T11:55:00Z", "es-ES"), "hace 5 minutos");
+    }
+
+    #[test]
+    fn test_locale_from_posix() {
+        assert_eq!(RenderLocale::from_posix("de_DE.UTF-8", 60).tag, "de-DE");
+        assert_eq!(RenderLocale::from_posix("C", 0).tag, "en-US");
+        assert_eq!(RenderLocale::from_posix("", 0).tag, "en-US");
+    }
+}
@@ -3,8 +3,13 @@ use std::collections::HashMap;
 
 pub mod block;
 pub mod entity;
+pub mod format;
+pub mod pagination;
+pub mod presence;
+pub mod registry;
 pub mod render_types;
 pub mod streaming;
+pub mod text_delta;
 
 // Re-export block types
 pub use block::{
@@ -14,24 +19,45 @@ pub use block::{
 
 // Re-export entity types (for Entity derive macro)
 pub use entity::{
-    DynamicEntity, EntityFieldSchema, EntitySchema, FieldSchema, FieldType, HasSchema, Schema,
-    StorageEntity,
+    DynamicEntity, EntityFieldError, EntityFieldSchema, EntitySchema, FieldSchema, FieldType,
+    HasSchema, Schema, StorageEntity, TypedEntity, ValidationError, ValidationErrors,
+};
+
+// Re-export tree pagination types
+pub use pagination::{CursorLevel, TreeCursor, TREE_PARENT_COLUMN, TREE_SORT_COLUMN};
+
+// Re-export presence types
+pub use presence::Presence;
+
+// Re-export registration types (for Entity derive and operations_trait macros)
+pub use registry::{
+    collect_operations, inventory, registered_entities, registered_entity, registered_operations,
+    EntityRegistration, OperationsRegistration,
 };
 
 // Re-export render types
+pub use format::{DateStyle, Format, RenderLocale};
 pub use render_types::{
-    Arg, BinaryOperator, Operation, OperationDescriptor, OperationParam, OperationWiring,
-    ParamMapping, PreconditionChecker, RenderExpr, RenderSpec, RenderableItem, RowTemplate,
-    TypeHint,
+    Arg, BinaryOperator, BoardSpec, CalendarPeriod, CalendarSpec, EntityStates, FilterKind,
+    FilterSpec, FilterValue, GroupSpec, Operation, OperationDescriptor, OperationParam,
+    OperationWiring, ParamMapping, PreconditionChecker, PreconditionViolation, PresenceSpec,
+    RenderExpr, RenderSpec, RenderableItem, RowTemplate, SelectionSpec, SortKey, StateTransition,
+    Style, StyleRule, TypeHint, ViewState, WidgetArgType, WidgetParam, WidgetSpec,
+    CALENDAR_DAY_PARAM, CURRENT_IDEMPOTENCY_KEY, NAMED_COLORS, STYLE_ARG,
 };
 
 // Re-export streaming types
 pub use streaming::{
-    Batch, BatchMapChange, BatchMapChangeWithMetadata, BatchMetadata, BatchTraceContext,
-    BatchWithMetadata, BlockChange, Change, ChangeOrigin, MapChange, StreamPosition,
-    SyncTokenUpdate, WithMetadata, CHANGE_ORIGIN_COLUMN, CURRENT_TRACE_CONTEXT,
+    changed_columns, Batch, BatchMapChange, BatchMapChangeWithMetadata, BatchMetadata,
+    BatchTraceContext, BatchWithMetadata, BlockChange, Change, ChangeOrigin, ChangeSource,
+    MapChange, StreamPosition, SyncTokenUpdate, WindowChange, WindowChangeBatch, WithMetadata,
+    ARCHIVED_AT_COLUMN, CHANGE_ORIGIN_COLUMN, CURRENT_CHANGE_SOURCE, CURRENT_TRACE_CONTEXT,
+    DELETED_AT_COLUMN,
 };
 
+// Re-export text delta types
+pub use text_delta::{TextDelta, TextDeltaError};
+
 /// flutter_rust_bridge:non_opaque
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Number {
@@ -52,8 +78,9 @@ pub enum Value {
     Integer(i64),
     Float(f64),
     Boolean(bool),
-    // DateTime variant: stored as RFC3339 string for flutter_rust_bridge compatibility
-    // Use as_datetime() to get the parsed chrono::DateTime
+    // DateTime variant: stored as RFC3339 string for flutter_rust_bridge compatibility,
+    // normalized to UTC by its constructors (see from_datetime()) so that strings
+    // sort chronologically. Use as_datetime() to get the parsed chrono::DateTime
     DateTime(String),
     // Json variant: stored as String for flutter_rust_bridge compatibility
     // Use as_json_value() to get the parsed serde_json::Value
@@ -162,16 +189,105 @@ impl Value {
     /// flutter_rust_bridge:ignore
     pub fn as_datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
         match self {
-            Value::DateTime(s) => chrono::DateTime::parse_from_rfc3339(s)
-                .ok()
-                .map(|dt| dt.with_timezone(&chrono::Utc)),
+            Value::DateTime(s) => parse_datetime(s),
             _ => None,
         }
     }
 
     /// Create a Value from a chrono::DateTime
+    ///
+    /// The text is `DATETIME_FORMAT`: UTC with millisecond precision, e.g.
+    /// `2024-03-01T08:30:00.000Z`.
     pub fn from_datetime(dt: chrono::DateTime<chrono::Utc>) -> Self {
-        Value::DateTime(dt.to_rfc3339())
+        Value::DateTime(format_datetime(&dt))
+    }
+
+    /// Parse RFC3339 (any offset), `YYYY-MM-DD HH:MM:SS` (as written by SQLite,
+    /// in UTC) or `YYYY-MM-DD` (midnight UTC) into a normalized `DateTime`
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn parse_datetime(s: &str) -> Option<Self> {
+        parse_datetime(s).map(Value::from_datetime)
+    }
+
+    /// This value with a `DateTime` normalized to UTC; other values are unchanged
+    ///
+    /// Values built with `Value::DateTime(..)` directly may carry any RFC3339 offset.
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn normalized(&self) -> Self {
+        match self {
+            Value::DateTime(s) => Value::parse_datetime(s).unwrap_or_else(|| self.clone()),
+            other => other.clone(),
+        }
+    }
+
+    /// The datetime `days` calendar days later (earlier if negative)
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn add_days(&self, days: i64) -> Option<Self> {
+        let dt = self.as_datetime()?;
+        dt.checked_add_signed(chrono::Duration::days(days))
+            .map(Value::from_datetime)
+    }
+
+    /// Midnight starting the datetime's day, in time zone `tz`
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn start_of_day<Tz: chrono::TimeZone>(&self, tz: &Tz) -> Option<Self> {
+        let local = self.as_datetime()?.with_timezone(tz);
+        local_midnight(tz, local.date_naive())
+    }
+
+    /// Midnight of the first day of the datetime's month, in time zone `tz`
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn start_of_month<Tz: chrono::TimeZone>(&self, tz: &Tz) -> Option<Self> {
+        use chrono::Datelike;
+
+        let local = self.as_datetime()?.with_timezone(tz);
+        local_midnight(tz, local.date_naive().with_day(1)?)
+    }
+
+    /// Midnight of the Monday starting the datetime's week, in time zone `tz`
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn start_of_week<Tz: chrono::TimeZone>(&self, tz: &Tz) -> Option<Self> {
+        use chrono::Datelike;
+
+        let local = self.as_datetime()?.with_timezone(tz);
+        let monday = local.date_naive()
+            - chrono::Duration::days(local.weekday().num_days_from_monday() as i64);
+        local_midnight(tz, monday)
+    }
+
+    /// Order two values of comparable types
+    ///
+    /// Numbers compare numerically, datetimes chronologically (a `String` holding
+    /// a datetime compares with a `DateTime`), strings and references
+    /// lexicographically. Values of other type combinations are not comparable.
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn compare(&self, other: &Value) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => Some(a.cmp(b)),
+            (Value::Integer(_) | Value::Float(_), Value::Integer(_) | Value::Float(_)) => {
+                self.as_f64()?.partial_cmp(&other.as_f64()?)
+            }
+            (Value::Boolean(a), Value::Boolean(b)) => Some(a.cmp(b)),
+            (Value::DateTime(a), Value::DateTime(b) | Value::String(b))
+            | (Value::String(a), Value::DateTime(b)) => {
+                match (parse_datetime(a), parse_datetime(b)) {
+                    (Some(a), Some(b)) => Some(a.cmp(&b)),
+                    _ => Some(a.cmp(b)),
+                }
+            }
+            (Value::String(a) | Value::Reference(a), Value::String(b) | Value::Reference(b)) => {
+                Some(a.cmp(b))
+            }
+            (Value::Null, Value::Null) => Some(std::cmp::Ordering::Equal),
+            _ => None,
+        }
     }
 
     /// Get array value
@@ -207,6 +323,33 @@ impl Value {
     }
 }
 
+/// Midnight starting `date` in time zone `tz` (the earliest, if DST repeats it)
+fn local_midnight<Tz: chrono::TimeZone>(tz: &Tz, date: chrono::NaiveDate) -> Option<Value> {
+    let midnight = tz
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()?;
+    Some(Value::from_datetime(midnight.with_timezone(&chrono::Utc)))
+}
+
+/// Text format of `Value::DateTime`: RFC3339 in UTC with millisecond precision
+///
+/// Its strings have a fixed width, so they sort chronologically, also in SQL.
+pub fn format_datetime(dt: &chrono::DateTime<chrono::Utc>) -> String {
+    dt.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+/// Parse the datetime formats `Value::parse_datetime` accepts
+pub fn parse_datetime(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&chrono::Utc));
+    }
+    if let Ok(naive) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f") {
+        return Some(naive.and_utc());
+    }
+    let date = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?;
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+}
+
 impl From<bool> for Value {
     fn from(b: bool) -> Self {
         Value::Boolean(b)
@@ -249,6 +392,18 @@ impl From<&str> for Value {
     }
 }
 
+impl From<chrono::DateTime<chrono::Utc>> for Value {
+    fn from(dt: chrono::DateTime<chrono::Utc>) -> Self {
+        Value::from_datetime(dt)
+    }
+}
+
+impl From<chrono::DateTime<chrono::FixedOffset>> for Value {
+    fn from(dt: chrono::DateTime<chrono::FixedOffset>) -> Self {
+        Value::from_datetime(dt.with_timezone(&chrono::Utc))
+    }
+}
+
 impl<T> From<Vec<T>> for Value
 where
     T: Into<Value>,
@@ -339,6 +494,21 @@ impl TryFrom<Value> for String {
     }
 }
 
+impl TryFrom<Value> for chrono::DateTime<chrono::Utc> {
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    /// Accepts datetime text (see `Value::parse_datetime`) stored as `DateTime` or
+    /// `String` (as read back from SQL)
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::DateTime(s) | Value::String(s) => {
+                parse_datetime(&s).ok_or_else(|| format!("Invalid datetime '{}'", s).into())
+            }
+            _ => Err("Value is not a datetime".into()),
+        }
+    }
+}
+
 impl<T> TryFrom<Value> for Option<T>
 where
     T: TryFrom<Value, Error = Box<dyn std::error::Error + Send + Sync>>,
@@ -458,6 +628,260 @@ mod tests {
         let v = Value::Array(arr.clone());
         assert_eq!(v.as_array(), Some(&arr));
     }
+
+    #[test]
+    fn test_datetime_normalized_to_utc() {
+        let v = Value::parse_datetime("2024-03-01T09:30:00+01:00").unwrap();
+        assert_eq!(v, Value::DateTime("2024-03-01T08:30:00.000Z".to_string()));
+        assert_eq!(
+            Value::parse_datetime("2024-03-01 08:30:00"),
+            Some(v.clone())
+        );
+        assert_eq!(
+            Value::parse_datetime("2024-03-01"),
+            Some(Value::DateTime("2024-03-01T00:00:00.000Z".to_string()))
+        );
+        assert_eq!(
+            Value::DateTime("2024-03-01T10:30:00+02:00".to_string()).normalized(),
+            v
+        );
+        assert!(Value::parse_datetime("yesterday").is_none());
+    }
+
+    #[test]
+    fn test_datetime_ordering() {
+        use std::cmp::Ordering;
+
+        // Lexicographically "later", but an hour earlier
+        let a = Value::DateTime("2024-03-01T09:30:00+02:00".to_string());
+        let b = Value::DateTime("2024-03-01T08:00:00Z".to_string());
+        assert_eq!(a.compare(&b), Some(Ordering::Less));
+        assert_eq!(
+            b.compare(&Value::String("2024-03-01T08:00:00.000Z".to_string())),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            Value::Integer(2).compare(&Value::Float(1.5)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(a.compare(&Value::Integer(1)), None);
+    }
+
+    #[test]
+    fn test_datetime_arithmetic() {
+        // A Thursday
+        let v = Value::parse_datetime("2024-02-29T23:30:00Z").unwrap();
+        assert_eq!(v.add_days(1), Value::parse_datetime("2024-03-01T23:30:00Z"));
+        assert_eq!(
+            v.start_of_week(&chrono::Utc),
+            Value::parse_datetime("2024-02-26T00:00:00Z")
+        );
+        // Already Friday east of UTC; the week starts at Monday midnight there
+        let tz = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(
+            v.start_of_week(&tz),
+            Value::parse_datetime("2024-02-26T00:00:00+02:00")
+        );
+        assert_eq!(Value::Integer(1).add_days(1), None);
+
+        assert_eq!(
+            v.start_of_day(&tz),
+            Value::parse_datetime("2024-03-01T00:00:00+02:00")
+        );
+        assert_eq!(
+            v.start_of_month(&chrono::Utc),
+            Value::parse_datetime("2024-02-01T00:00:00Z")
+        );
+    }
+
+    fn descriptor(
+        name: &str,
+        params: &[&str],
+        param_mappings: Vec<ParamMapping>,
+    ) -> OperationDescriptor {
+        OperationDescriptor {
+            entity_name: "tasks".to_string(),
+            entity_short_name: "task".to_string(),
+            id_column: "id".to_string(),
+            name: name.to_string(),
+            display_name: name.to_string(),
+            description: String::new(),
+            required_params: params
+                .iter()
+                .map(|param| OperationParam {
+                    name: param.to_string(),
+                    type_hint: TypeHint::String,
+                    description: String::new(),
+                })
+                .collect(),
+            affected_fields: vec![],
+            param_mappings,
+            precondition: None,
+            simulation: None,
+        }
+    }
+
+    #[test]
+    fn test_calendar_buckets() {
+        let calendar = CalendarSpec {
+            column: "due_date".to_string(),
+            period: CalendarPeriod::Week,
+        };
+        let row =
+            |due: &str| HashMap::from([("due_date".to_string(), Value::String(due.to_string()))]);
+        let rows = [
+            row("2024-03-06T10:00:00Z"),
+            row("2024-02-29"),
+            HashMap::new(),
+            row("2024-03-04"),
+        ];
+        let monday = |date: &str| Value::parse_datetime(date).unwrap();
+        assert_eq!(
+            calendar.buckets(&rows, &chrono::Utc),
+            vec![
+                (monday("2024-02-26"), vec![1]),
+                (monday("2024-03-04"), vec![0, 3])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calendar_drop_operation() {
+        let calendar = CalendarSpec {
+            column: "due_date".to_string(),
+            period: CalendarPeriod::Day,
+        };
+        let day = Value::parse_datetime("2024-03-04").unwrap();
+        let set_due_date = descriptor(
+            "set_due_date",
+            &["id", "due_date"],
+            vec![ParamMapping {
+                from: CALENDAR_DAY_PARAM.to_string(),
+                provides: vec!["due_date".to_string()],
+                defaults: HashMap::new(),
+            }],
+        );
+        let set_field = descriptor("set_field", &["id", "field", "value"], vec![]);
+
+        let op = calendar
+            .drop_operation(&[set_field.clone(), set_due_date.clone()], "t1", &day)
+            .unwrap();
+        assert_eq!(op.op_name, "set_due_date");
+        assert_eq!(op.params["due_date"], day);
+
+        // A calendar of another column doesn't use set_due_date
+        let calendar = CalendarSpec {
+            column: "start_date".to_string(),
+            ..calendar
+        };
+        let op = calendar
+            .drop_operation(&[set_field, set_due_date], "t1", &day)
+            .unwrap();
+        assert_eq!(op.op_name, "set_field");
+        assert_eq!(op.params["field"], Value::String("start_date".to_string()));
+    }
+
+    #[test]
+    fn test_board_move_operation() {
+        let board = BoardSpec {
+            column: "status".to_string(),
+            lanes: vec![Value::String("todo".to_string())],
+        };
+        let done = Value::String("done".to_string());
+        let set_field = descriptor("set_field", &["id", "field", "value"], vec![]);
+        let set_status = descriptor("set_status", &["id", "status"], vec![]);
+        let move_to_section = descriptor(
+            "move_to_section",
+            &["id", "section"],
+            vec![ParamMapping {
+                from: "status".to_string(),
+                provides: vec!["section".to_string()],
+                defaults: HashMap::new(),
+            }],
+        );
+
+        let op = board
+            .move_operation(&[set_field.clone()], "t1", &done)
+            .unwrap();
+        assert_eq!(op.op_name, "set_field");
+        assert_eq!(op.params["field"], Value::String("status".to_string()));
+        assert_eq!(op.params["value"], done);
+
+        let op = board
+            .move_operation(&[set_field.clone(), set_status.clone()], "t1", &done)
+            .unwrap();
+        assert_eq!(op.op_name, "set_status");
+        assert_eq!(op.params["status"], done);
+
+        // Operations triggered by the lane column win
+        let op = board
+            .move_operation(&[set_field, set_status, move_to_section], "t1", &done)
+            .unwrap();
+        assert_eq!(op.op_name, "move_to_section");
+        assert_eq!(op.params["id"], Value::String("t1".to_string()));
+        assert_eq!(op.params["section"], done);
+
+        assert!(board.move_operation(&[], "t1", &done).is_none());
+
+        let rows = [
+            HashMap::from([("status".to_string(), done.clone())]),
+            HashMap::new(),
+        ];
+        assert_eq!(
+            board.lanes_for(&rows),
+            vec![Value::String("todo".to_string()), done, Value::Null]
+        );
+    }
+    #[test]
+    fn test_change_source_filter_and_badge() {
+        let source = ChangeSource::default()
+            .with_device_id("laptop")
+            .with_agent("mcp:assistant");
+        assert!(source.matches(&ChangeSource::default()));
+        assert!(source.matches(&ChangeSource::default().with_agent("mcp:assistant")));
+        assert!(!source.matches(&ChangeSource::default().with_frontend("tui")));
+
+        let tui = ChangeSource::default().with_frontend("tui");
+        let origin = ChangeOrigin::local_with_trace(None, None).with_source(source.clone());
+        assert_eq!(origin.badge(&tui), Some("mcp:assistant".to_string()));
+        let json = origin.to_json();
+        assert_eq!(ChangeOrigin::from_json(&json), Some(origin));
+
+        // Origins stored before sources existed still parse
+        let legacy =
+            ChangeOrigin::from_json(r#"{"Local":{"operation_id":null,"trace_id":null}}"#).unwrap();
+        assert_eq!(legacy.source(), None);
+        assert_eq!(legacy.badge(&tui), None);
+
+        let own = ChangeOrigin::local_with_trace(None, None).with_source(tui.clone());
+        assert_eq!(own.badge(&tui), None);
+        assert_eq!(
+            own.badge(&ChangeSource::default().with_frontend("flutter")),
+            Some("tui".to_string())
+        );
+
+        let synced = ChangeOrigin::remote_with_trace(None, None)
+            .with_source(ChangeSource::default().with_provider("todoist"))
+            .with_source(ChangeSource::default().with_device_id("laptop"));
+        assert_eq!(
+            synced.source().unwrap().provider.as_deref(),
+            Some("todoist")
+        );
+        assert_eq!(synced.badge(&tui), Some("todoist".to_string()));
+        assert_eq!(
+            ChangeOrigin::remote_with_trace(None, None).badge(&tui),
+            Some("remote".to_string())
+        );
+    }
+
+    #[test]
+    fn test_current_change_source_is_task_local() {
+        assert_eq!(ChangeSource::current(), None);
+        let cli = ChangeSource::default().with_frontend("cli");
+        let origin =
+            CURRENT_CHANGE_SOURCE.sync_scope(cli.clone(), ChangeOrigin::local_with_current_span);
+        assert_eq!(origin.source(), Some(&cli));
+    }
 }
 
 /// Structured error types for API operations.
@@ -483,4 +907,21 @@ pub enum ApiError {
 
     #[error("Internal error: {message}")]
     InternalError { message: String },
+
+    #[error("{entity} not found: {id}")]
+    NotFound { entity: String, id: String },
+
+    #[error("Remote error ({status}): {message}")]
+    RemoteError { status: u16, message: String },
+
+    #[error("Conflict: {message}")]
+    Conflict { message: String },
+
+    #[error("Precondition failed: {message}")]
+    PreconditionViolated {
+        operation: String,
+        clause: String,
+        message: String,
+        params: HashMap<String, Value>,
+    },
 }
@@ -0,0 +1,98 @@
+//! Presence of collaborators in a shared workspace
+//!
+//! Every client publishes what its user is looking at: the view, the entity the
+//! cursor is on and the selected entities. Clients receive each other's presence
+//! over a presence channel, so frontends can show collaborators' cursors on the rows
+//! of views that declare a `PresenceSpec`.
+
+use serde::{Deserialize, Serialize};
+
+/// What the user of a client is looking at
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Presence {
+    /// Identifies the client (one per running frontend)
+    pub client_id: String,
+    /// Name shown next to the client's cursor
+    pub user_name: String,
+    /// View the client shows (`view_id:` of its root widget)
+    #[serde(default)]
+    pub view_id: Option<String>,
+    /// Entity the cursor is on
+    #[serde(default)]
+    pub focused_id: Option<String>,
+    /// Selected entities
+    #[serde(default)]
+    pub selection: Vec<String>,
+    /// When the presence was published (Unix timestamp in milliseconds)
+    pub updated_at: i64,
+}
+
+impl Presence {
+    pub fn new(client_id: impl Into<String>, user_name: impl Into<String>) -> Self {
+        Self {
+            client_id: client_id.into(),
+            user_name: user_name.into(),
+            view_id: None,
+            focused_id: None,
+            selection: Vec::new(),
+            updated_at: 0,
+        }
+    }
+
+    pub fn with_view(mut self, view_id: impl Into<String>) -> Self {
+        self.view_id = Some(view_id.into());
+        self
+    }
+
+    pub fn with_focus(mut self, focused_id: impl Into<String>) -> Self {
+        self.focused_id = Some(focused_id.into());
+        self
+    }
+
+    pub fn with_selection(mut self, selection: Vec<String>) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Whether the cursor is on `id` or `id` is selected
+    pub fn is_on(&self, id: &str) -> bool {
+        self.focused_id.as_deref() == Some(id) || self.selection.iter().any(|s| s == id)
+    }
+
+    /// Message sent on presence channels
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Presence serializes to JSON")
+    }
+
+    /// Parse a message of `to_json`; `None` for anything else
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presence_round_trip() {
+        let presence = Presence::new("client-1", "Ada")
+            .with_view("inbox")
+            .with_focus("task-1")
+            .with_selection(vec!["task-2".to_string()]);
+        assert!(presence.is_on("task-1"));
+        assert!(presence.is_on("task-2"));
+        assert!(!presence.is_on("task-3"));
+
+        assert_eq!(Presence::from_json(&presence.to_json()), Some(presence));
+        assert_eq!(
+            Presence::from_json(r#"{"client_id": "c", "user_name": "Bo", "updated_at": 1}"#),
+            Some(Presence {
+                updated_at: 1,
+                ..Presence::new("c", "Bo")
+            })
+        );
+        assert_eq!(Presence::from_json("not presence"), None);
+    }
+}
@@ -0,0 +1,180 @@
+//! Entity and operation registrations collected at startup
+//!
+//! `#[derive(Entity)]` submits an `EntityRegistration` for every entity type and
+//! `#[operations_trait]` an `OperationsRegistration` for every operations trait.
+//! The registrations are gathered by `inventory` when the program starts, so
+//! entities and their operations are discovered without listing them by hand.
+//!
+//! An entity names the traits whose operations it supports in its attribute:
+//!
+//! ```ignore
+//! #[derive(Entity)]
+//! #[entity(name = "todoist_tasks", short_name = "task", operations(CrudOperations, TaskOperations))]
+//! pub struct TodoistTask { ... }
+//! ```
+//!
+//! The derive then implements `OperationRegistry` by collecting those traits'
+//! operations (see `collect_operations`).
+
+use crate::{OperationDescriptor, Schema};
+
+pub use inventory;
+
+/// An entity type declared with `#[derive(Entity)]`
+///
+/// flutter_rust_bridge:ignore
+#[derive(Debug)]
+pub struct EntityRegistration {
+    pub entity_name: &'static str,
+    pub short_name: Option<&'static str>,
+    pub primary_key: &'static str,
+    /// Table schema of the entity
+    pub schema: fn() -> Schema,
+    /// Names of the operations traits the entity supports (`operations(...)`)
+    pub operations: &'static [&'static str],
+}
+
+inventory::collect!(EntityRegistration);
+
+impl EntityRegistration {
+    /// Operations of all traits the entity supports, on the table named like the entity
+    pub fn operations(&self) -> Vec<OperationDescriptor> {
+        collect_operations(
+            self.operations,
+            self.entity_name,
+            self.short_name.unwrap_or(self.entity_name),
+            self.entity_name,
+            self.primary_key,
+        )
+    }
+}
+
+/// The operations of a trait declared with `#[operations_trait]`
+///
+/// flutter_rust_bridge:ignore
+#[derive(Debug)]
+pub struct OperationsRegistration {
+    /// Name of the trait (e.g. "CrudOperations")
+    pub trait_name: &'static str,
+    /// Descriptors of the trait's operations for (entity_name, short_name, table, id_column)
+    pub operations: fn(&str, &str, &str, &str) -> Vec<OperationDescriptor>,
+}
+
+inventory::collect!(OperationsRegistration);
+
+/// All entity types linked into the program, sorted by name
+pub fn registered_entities() -> Vec<&'static EntityRegistration> {
+    let mut entities: Vec<_> = inventory::iter::<EntityRegistration>.into_iter().collect();
+    entities.sort_by_key(|entity| entity.entity_name);
+    entities
+}
+
+/// The registration of the entity named `entity_name`
+pub fn registered_entity(entity_name: &str) -> Option<&'static EntityRegistration> {
+    inventory::iter::<EntityRegistration>
+        .into_iter()
+        .find(|entity| entity.entity_name == entity_name)
+}
+
+/// The registration of the operations trait named `trait_name`
+pub fn registered_operations(trait_name: &str) -> Option<&'static OperationsRegistration> {
+    inventory::iter::<OperationsRegistration>
+        .into_iter()
+        .find(|registration| registration.trait_name == trait_name)
+}
+
+/// Operations of the traits named in `traits`, in order
+///
+/// Traits that were not registered (e.g. a typo in `operations(...)`) are logged
+/// and skipped.
+pub fn collect_operations(
+    traits: &[&str],
+    entity_name: &str,
+    short_name: &str,
+    table: &str,
+    id_column: &str,
+) -> Vec<OperationDescriptor> {
+    traits
+        .iter()
+        .flat_map(|trait_name| match registered_operations(trait_name) {
+            Some(registration) => {
+                (registration.operations)(entity_name, short_name, table, id_column)
+            }
+            None => {
+                tracing::warn!(
+                    "Entity {} declares operations of {}, which is not an #[operations_trait]",
+                    entity_name,
+                    trait_name
+                );
+                Vec::new()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldSchema, OperationParam, TypeHint};
+
+    fn rename_operations(
+        entity_name: &str,
+        entity_short_name: &str,
+        table: &str,
+        id_column: &str,
+    ) -> Vec<OperationDescriptor> {
+        vec![OperationDescriptor {
+            entity_name: entity_name.to_string(),
+            entity_short_name: entity_short_name.to_string(),
+            id_column: id_column.to_string(),
+            name: format!("rename_in_{}", table),
+            display_name: "Rename".to_string(),
+            description: String::new(),
+            required_params: vec![OperationParam {
+                name: "name".to_string(),
+                type_hint: TypeHint::String,
+                description: String::new(),
+            }],
+            affected_fields: vec!["name".to_string()],
+            param_mappings: Vec::new(),
+            precondition: None,
+            simulation: None,
+        }]
+    }
+
+    fn notes_schema() -> Schema {
+        Schema::new("registry_test_notes", vec![FieldSchema::new("id", "TEXT")])
+    }
+
+    inventory::submit! {
+        OperationsRegistration {
+            trait_name: "RegistryTestRename",
+            operations: rename_operations,
+        }
+    }
+
+    inventory::submit! {
+        EntityRegistration {
+            entity_name: "registry_test_notes",
+            short_name: Some("note"),
+            primary_key: "id",
+            schema: notes_schema,
+            operations: &["RegistryTestRename", "RegistryTestMissing"],
+        }
+    }
+
+    #[test]
+    fn test_entity_operations_are_collected() {
+        let entity = registered_entity("registry_test_notes").unwrap();
+        assert!(registered_entities()
+            .iter()
+            .any(|e| e.entity_name == "registry_test_notes"));
+        assert_eq!((entity.schema)().table_name, "registry_test_notes");
+
+        // Unregistered traits are skipped
+        let operations = entity.operations();
+        assert_eq!(operations.len(), 1);
+        assert_eq!(operations[0].name, "rename_in_registry_test_notes");
+        assert_eq!(operations[0].entity_short_name, "note");
+    }
+}
@@ -2,13 +2,70 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::Value;
+use crate::{Format, Value};
 
+/// Evaluates an operation's `#[require(...)]` clauses
+///
+/// Returns the first violated clause, or an error if a parameter is missing.
+///
 /// flutter_rust_bridge:ignore
-pub type PreconditionChecker = dyn Fn(&HashMap<String, Box<dyn std::any::Any + Send + Sync>>) -> Result<bool, String>
+pub type PreconditionChecker = dyn Fn(
+        &HashMap<String, Box<dyn std::any::Any + Send + Sync>>,
+    ) -> Result<Option<PreconditionViolation>, String>
     + Send
     + Sync;
 
+/// Entities of one type by ID, each a map of field name to value
+///
+/// flutter_rust_bridge:ignore
+pub type EntityStates = HashMap<String, HashMap<String, Value>>;
+
+/// Pure model of an operation, attached with `#[simulate(...)]`
+///
+/// Given the entities before the operation and its parameters, returns the
+/// entities expected afterwards, or an error if the operation should fail.
+///
+/// flutter_rust_bridge:ignore
+pub type StateTransition =
+    dyn Fn(&EntityStates, &HashMap<String, Value>) -> Result<EntityStates, String> + Send + Sync;
+
+/// A `#[require(...)]` clause that failed for the given parameters
+///
+/// flutter_rust_bridge:ignore
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreconditionViolation {
+    pub operation: String,
+    /// Source of the failed clause, e.g. `priority <= 5`
+    pub clause: String,
+    /// Message given with the clause, e.g. `#[require(priority <= 5, "priority must be between 1 and 5")]`
+    pub message: Option<String>,
+    /// Parameters referenced by the clause with the values they had
+    pub params: Vec<(String, Value)>,
+}
+
+impl std::fmt::Display for PreconditionViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(message) = &self.message {
+            return write!(f, "{}", message);
+        }
+        let values: Vec<String> = self
+            .params
+            .iter()
+            .map(|(name, value)| {
+                let value = serde_json::to_string(value).unwrap_or_else(|_| format!("{:?}", value));
+                format!("{} = {}", name, value)
+            })
+            .collect();
+        write!(f, "{} requires {}", self.operation, self.clause)?;
+        if !values.is_empty() {
+            write!(f, " (got {})", values.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PreconditionViolation {}
+
 /// flutter_rust_bridge:non_opaque
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RenderSpec {
@@ -22,6 +79,430 @@ pub struct RenderSpec {
     /// Operations are wired based on each template's source entity.
     #[serde(default)]
     pub row_templates: Vec<RowTemplate>,
+    /// Multi-selection support of the root collection widget (None = not selectable)
+    #[serde(default)]
+    pub selection: Option<SelectionSpec>,
+    /// Row order declared with `sort_by:` on the root collection widget
+    #[serde(default)]
+    pub sort: Vec<SortKey>,
+    /// Section headers declared with `group_by:` on the root collection widget
+    #[serde(default)]
+    pub group_by: Option<GroupSpec>,
+    /// View whose UI state is persisted, declared with `view_id:"inbox"` on the root widget
+    #[serde(default)]
+    pub view_id: Option<String>,
+    /// Persisted UI state of `view_id`, filled in by the backend when the query is run
+    #[serde(default)]
+    pub view_state: ViewState,
+    /// Filters declared by `toggle_filter`/`date_filter` widgets anywhere in the tree
+    #[serde(default)]
+    pub filters: Vec<FilterSpec>,
+    /// Time zone date widgets show datetimes in, declared with
+    /// `timezone:"Europe/Berlin"` on the root widget (an IANA name, or "UTC").
+    /// None = the device's local time zone. Datetime values are always UTC.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Kanban lanes of a `board` root widget
+    #[serde(default)]
+    pub board: Option<BoardSpec>,
+    /// Day/week/month buckets of a `calendar` root widget
+    #[serde(default)]
+    pub calendar: Option<CalendarSpec>,
+    /// Collaborators' cursors, declared with `presence:this.id` on the root widget
+    #[serde(default)]
+    pub presence: Option<PresenceSpec>,
+}
+
+impl RenderSpec {
+    /// Order in which to show the rows: by group first, so that the rows of a
+    /// group are adjacent, then by `sort`
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn row_order(&self) -> Vec<SortKey> {
+        self.group_by
+            .iter()
+            .map(|group| SortKey {
+                column: group.column.clone(),
+                descending: group.descending,
+            })
+            .chain(self.sort.iter().cloned())
+            .collect()
+    }
+}
+
+/// Multi-selection configuration of a collection widget.
+///
+/// Enabled with `selectable:true` on the root widget, e.g.
+/// `render (list selectable:true item_template:(...))`. Optional arguments:
+/// `select_all:false` hides the select-all toggle, `selection_checkbox:false` hides
+/// the checkbox column, and `selection_id:"task_id"` picks the column identifying rows.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectionSpec {
+    /// Column whose values identify selected rows
+    pub id_column: String,
+    /// Render a checkbox column for toggling row selection
+    pub checkbox_column: bool,
+    /// Offer a select-all toggle
+    pub select_all: bool,
+}
+
+impl Default for SelectionSpec {
+    fn default() -> Self {
+        Self {
+            id_column: "id".to_string(),
+            checkbox_column: true,
+            select_all: true,
+        }
+    }
+}
+
+/// A column the rows of a collection widget are ordered by.
+///
+/// Written as `sort_by:[priority, -created_at]` on the root widget; a leading `-`
+/// orders by that column in descending order.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SortKey {
+    pub column: String,
+    pub descending: bool,
+}
+
+/// Grouping of a collection widget's rows under section headers.
+///
+/// Written as `group_by:project_id` (`group_by:-project_id` to list the groups in
+/// descending order) on the root widget. `group_label:project_name` shows another
+/// column of the group's rows in its header.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupSpec {
+    /// Rows with equal values in this column form a group
+    pub column: String,
+    pub descending: bool,
+    /// Column shown in the header of a group (`column` if None)
+    pub label_column: Option<String>,
+}
+
+/// Kanban layout of a `board` root widget.
+///
+/// Written as `render (board group_by:status lanes:["todo", "doing", "done"] item_template:(...))`:
+/// each row is a card in the lane of its `group_by:` column value. The `lanes:` are
+/// shown in the given order even when empty; values not listed get lanes after them.
+/// Moving a card to another lane runs the operation `move_operation` picks.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardSpec {
+    /// Column holding a card's lane (the `group_by:` column)
+    pub column: String,
+    /// Lanes declared with `lanes:`, in display order
+    pub lanes: Vec<Value>,
+}
+
+impl BoardSpec {
+    /// Lane of `row` (`Value::Null` if it has no value in `column`)
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn lane_of(&self, row: &HashMap<String, Value>) -> Value {
+        row.get(&self.column).cloned().unwrap_or(Value::Null)
+    }
+
+    /// Lanes to show for `rows`: the declared lanes, then the other values of
+    /// `column` in the order they first appear
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn lanes_for<'a>(
+        &self,
+        rows: impl IntoIterator<Item = &'a HashMap<String, Value>>,
+    ) -> Vec<Value> {
+        let mut lanes = self.lanes.clone();
+        for row in rows {
+            let lane = self.lane_of(row);
+            if !lanes.contains(&lane) {
+                lanes.push(lane);
+            }
+        }
+        lanes
+    }
+
+    /// Operation moving card `id` to `lane`, from the operations wired to the board
+    ///
+    /// Operations triggered by the lane column (`#[triggered_by(availability_of = "status")]`)
+    /// come first, then operations taking the column as a parameter (e.g.
+    /// `set_status(id, status)`), then `set_field` of the column. None if no
+    /// operation can be given all its parameters.
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn move_operation(
+        &self,
+        operations: &[OperationDescriptor],
+        id: &str,
+        lane: &Value,
+    ) -> Option<Operation> {
+        operation_setting(operations, &self.column, &self.column, id, lane)
+    }
+}
+
+/// Layout of a `calendar` root widget.
+///
+/// Written as `render (calendar date:this.due_date period:"week" item_template:(...))`:
+/// rows are shown in buckets of one day (the default), week or month by their
+/// `date:` column, in the time zone of the view. Dropping a row on a day runs the
+/// operation `drop_operation` picks.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarSpec {
+    /// Datetime column placing a row in the calendar (the `date:` column)
+    pub column: String,
+    /// Length of a bucket
+    pub period: CalendarPeriod,
+}
+
+/// Collaborators' cursors on the rows of a root widget.
+///
+/// Written as `render (list view_id:"inbox" presence:this.id item_template:(...))`:
+/// frontends mark each row whose `column` value a collaborator focuses or selected
+/// (see `Presence::is_on`), e.g. with the collaborator's name. With a `view_id:`,
+/// only collaborators showing the same view are marked.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresenceSpec {
+    /// Column holding the entity ids collaborators focus (the `presence:` column)
+    pub column: String,
+}
+
+/// Length of the buckets of a calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CalendarPeriod {
+    #[default]
+    Day,
+    /// Monday to Sunday
+    Week,
+    Month,
+}
+
+impl CalendarPeriod {
+    /// Parse a `period:` argument (`"day"`, `"week"` or `"month"`)
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "day" => Some(Self::Day),
+            "week" => Some(Self::Week),
+            "month" => Some(Self::Month),
+            _ => None,
+        }
+    }
+}
+
+/// Param calendar widgets provide for the day a row is dropped on
+///
+/// Operations declare what it sets with
+/// `#[triggered_by(availability_of = "calendar_day", providing = ["due_date"])]`.
+pub const CALENDAR_DAY_PARAM: &str = "calendar_day";
+
+impl CalendarSpec {
+    /// Start of the bucket `row` is shown in, in time zone `tz`; None without a date
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn bucket_of<Tz: chrono::TimeZone>(
+        &self,
+        row: &HashMap<String, Value>,
+        tz: &Tz,
+    ) -> Option<Value> {
+        let date = match row.get(&self.column)? {
+            Value::DateTime(s) | Value::String(s) => Value::parse_datetime(s)?,
+            _ => return None,
+        };
+        match self.period {
+            CalendarPeriod::Day => date.start_of_day(tz),
+            CalendarPeriod::Week => date.start_of_week(tz),
+            CalendarPeriod::Month => date.start_of_month(tz),
+        }
+    }
+
+    /// Buckets of `rows` in chronological order, each with the indices of its rows
+    ///
+    /// Rows without a date are left out.
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn buckets<Tz: chrono::TimeZone>(
+        &self,
+        rows: &[HashMap<String, Value>],
+        tz: &Tz,
+    ) -> Vec<(Value, Vec<usize>)> {
+        let mut buckets: Vec<(Value, Vec<usize>)> = Vec::new();
+        for (idx, row) in rows.iter().enumerate() {
+            let Some(bucket) = self.bucket_of(row, tz) else {
+                continue;
+            };
+            match buckets.iter_mut().find(|(start, _)| *start == bucket) {
+                Some((_, indices)) => indices.push(idx),
+                None => buckets.push((bucket, vec![idx])),
+            }
+        }
+        buckets.sort_by(|(a, _), (b, _)| a.compare(b).unwrap_or(std::cmp::Ordering::Equal));
+        buckets
+    }
+
+    /// Operation moving row `id` to `day`, from the operations wired to the calendar
+    ///
+    /// Operations triggered by `CALENDAR_DAY_PARAM` (e.g. `set_due_date`) come first,
+    /// then operations taking the date column as a parameter, then `set_field` of the
+    /// column. None if no operation can be given all its parameters.
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn drop_operation(
+        &self,
+        operations: &[OperationDescriptor],
+        id: &str,
+        day: &Value,
+    ) -> Option<Operation> {
+        operation_setting(operations, CALENDAR_DAY_PARAM, &self.column, id, day)
+    }
+}
+
+/// Operation setting `column` of row `id` to `value`, for widgets moving rows
+///
+/// Operations with a param mapping from `source` come first (when `source` is
+/// another param, only those providing `column`), then operations taking `column`
+/// as a parameter, then `set_field` of `column`.
+fn operation_setting(
+    operations: &[OperationDescriptor],
+    source: &str,
+    column: &str,
+    id: &str,
+    value: &Value,
+) -> Option<Operation> {
+    let triggered = operations.iter().filter(|op| {
+        op.param_mappings.iter().any(|mapping| {
+            mapping.from == source
+                && (source == column || mapping.provides.iter().any(|p| p == column))
+        })
+    });
+    let taking_column = operations
+        .iter()
+        .filter(|op| op.required_params.iter().any(|p| p.name == column));
+    let from_mappings = triggered
+        .chain(taking_column)
+        .find_map(|op| setting_params(op, source, column, id, value).map(|params| (op, params)));
+    if let Some((op, params)) = from_mappings {
+        return Some(Operation::new(
+            &op.entity_name,
+            &op.name,
+            &op.display_name,
+            params,
+        ));
+    }
+
+    let set_field = operations.iter().find(|op| op.name == "set_field")?;
+    Some(Operation::new(
+        &set_field.entity_name,
+        &set_field.name,
+        &set_field.display_name,
+        HashMap::from([
+            (set_field.id_column.clone(), Value::String(id.to_string())),
+            ("field".to_string(), Value::String(column.to_string())),
+            ("value".to_string(), value.clone()),
+        ]),
+    ))
+}
+
+/// Parameters of `op` for setting `column` of row `id` to `value`, if it gets all it requires
+fn setting_params(
+    op: &OperationDescriptor,
+    source: &str,
+    column: &str,
+    id: &str,
+    value: &Value,
+) -> Option<HashMap<String, Value>> {
+    let mapping = op
+        .param_mappings
+        .iter()
+        .find(|mapping| mapping.from == source);
+    op.required_params
+        .iter()
+        .map(|param| {
+            let param_value = if param.name == op.id_column {
+                Value::String(id.to_string())
+            } else if param.name == column {
+                value.clone()
+            } else {
+                let mapping = mapping.filter(|m| m.provides.contains(&param.name))?;
+                match mapping.defaults.get(&param.name) {
+                    Some(default) => default.clone(),
+                    None if mapping.provides.len() == 1 => value.clone(),
+                    None => return None,
+                }
+            };
+            Some((param.name.clone(), param_value))
+        })
+        .collect()
+}
+
+/// UI state of a view that survives restarts.
+///
+/// Frontends apply it when the view is shown (collapsing the listed tree nodes,
+/// selecting rows, scrolling to the anchor row) and report changes back to the
+/// backend, which stores them per `view_id` and entity id.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewState {
+    /// Ids of collapsed tree nodes
+    pub collapsed: Vec<String>,
+    /// Ids of selected rows
+    pub selected: Vec<String>,
+    /// Id of the first visible row
+    pub scroll_anchor: Option<String>,
+}
+
+/// A filter on the query's rows that a widget controls.
+///
+/// Declared with `(toggle_filter column:completed)` for boolean columns or
+/// `(date_filter column:due_date)` for date ranges; `name:` names the filter
+/// (defaults to the column) and `default:` sets the value applied until the user
+/// changes it, e.g. `(toggle_filter column:completed default:false)` hides completed
+/// rows until a "show completed" toggle clears the filter. Widgets report changes
+/// to the backend by name, which re-runs the query and streams the changed rows.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterSpec {
+    pub name: String,
+    pub column: String,
+    pub kind: FilterKind,
+    /// Value applied until a widget sets another one (None = rows are not filtered)
+    pub default: Option<FilterValue>,
+}
+
+/// Kind of values a filter takes
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilterKind {
+    Boolean,
+    DateRange,
+}
+
+/// Value of a filter, set by its widget
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilterValue {
+    /// Rows whose column has this value
+    Boolean(bool),
+    /// Rows whose column lies within the bounds (inclusive; RFC3339 or `YYYY-MM-DD`,
+    /// an open bound is None)
+    DateRange {
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+impl FilterValue {
+    pub fn kind(&self) -> FilterKind {
+        match self {
+            FilterValue::Boolean(_) => FilterKind::Boolean,
+            FilterValue::DateRange { .. } => FilterKind::DateRange,
+        }
+    }
 }
 
 /// Per-row UI template for heterogeneous data rendering.
@@ -71,6 +552,89 @@ pub struct OperationDescriptor {
     /// flutter_rust_bridge:opaque
     #[serde(skip_serializing, skip_deserializing)]
     pub precondition: Option<Arc<Box<PreconditionChecker>>>,
+
+    /// Expected effect of the operation, for simulation in property-based tests
+    /// flutter_rust_bridge:opaque
+    #[serde(skip_serializing, skip_deserializing)]
+    pub simulation: Option<Arc<Box<StateTransition>>>,
+}
+
+impl OperationDescriptor {
+    /// Whether the operation can be applied to each entity of a multi-selection
+    ///
+    /// True for operations addressing a single existing entity by its ID column
+    /// (e.g. `set_completion`, `indent`, `delete`), which frontends run once per
+    /// selected row.
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn accepts_multiple_ids(&self) -> bool {
+        self.name != "create"
+            && self
+                .required_params
+                .iter()
+                .any(|p| p.name == self.id_column)
+    }
+
+    /// Fill in absent params from the defaults of the param mappings providing them
+    ///
+    /// Mappings whose source is among `params` take precedence, e.g. a drop on a
+    /// `tree_position` that carries no `position` gets the default of the
+    /// `tree_position` mapping. Params that are present are left as they are.
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn apply_param_defaults(&self, params: &mut HashMap<String, Value>) {
+        let (triggered, others): (Vec<_>, Vec<_>) = self
+            .param_mappings
+            .iter()
+            .partition(|mapping| params.contains_key(&mapping.from));
+        for mapping in triggered.into_iter().chain(others) {
+            for (name, default) in &mapping.defaults {
+                if mapping.provides.contains(name) && !params.contains_key(name) {
+                    params.insert(name.clone(), default.clone());
+                }
+            }
+        }
+    }
+
+    /// Evaluate the operation's precondition against `params`
+    ///
+    /// Returns the violated clause, `None` if the precondition holds or there is
+    /// none, and an error if a parameter the precondition needs is missing.
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn check_precondition(
+        &self,
+        params: &HashMap<String, Value>,
+    ) -> Result<Option<PreconditionViolation>, String> {
+        let Some(precondition) = &self.precondition else {
+            return Ok(None);
+        };
+        let params: HashMap<String, Box<dyn std::any::Any + Send + Sync>> = params
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.clone(),
+                    Box::new(value.clone()) as Box<dyn std::any::Any + Send + Sync>,
+                )
+            })
+            .collect();
+        precondition(&params)
+    }
+
+    /// Entities expected after applying the operation with `params` to `entities`
+    ///
+    /// Returns `None` if the operation has no simulation.
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn simulate(
+        &self,
+        entities: &EntityStates,
+        params: &HashMap<String, Value>,
+    ) -> Option<Result<EntityStates, String>> {
+        self.simulation
+            .as_ref()
+            .map(|simulation| simulation(entities, params))
+    }
 }
 
 impl std::fmt::Debug for OperationDescriptor {
@@ -89,10 +653,17 @@ impl std::fmt::Debug for OperationDescriptor {
                 "precondition",
                 &self.precondition.as_ref().map(|_| "<closure>"),
             )
+            .field("simulation", &self.simulation.as_ref().map(|_| "<closure>"))
             .finish()
     }
 }
 
+tokio::task_local! {
+    /// Idempotency key of the operation being executed
+    /// Set by the operation dispatcher, read by providers via Operation::current_idempotency_key()
+    pub static CURRENT_IDEMPOTENCY_KEY: String;
+}
+
 /// An executable operation with all parameters
 ///
 /// Operations can be executed through the OperationProvider trait,
@@ -108,6 +679,13 @@ pub struct Operation {
     pub display_name: String,
     /// Operation parameters as key-value pairs
     pub params: HashMap<String, Value>,
+    /// UUID generated when the operation is created
+    ///
+    /// Executing the same operation again (e.g. retrying it after a crash) reuses
+    /// the key, so the dispatcher and remote providers can skip the duplicate.
+    /// Empty for operations logged before keys existed.
+    #[serde(default)]
+    pub idempotency_key: String,
 }
 
 impl Operation {
@@ -123,6 +701,7 @@ impl Operation {
             op_name: op_name.into(),
             display_name: display_name.into(),
             params,
+            idempotency_key: uuid::Uuid::new_v4().to_string(),
         }
     }
 
@@ -138,6 +717,7 @@ impl Operation {
             op_name: op_name.into(),
             display_name: display_name.into(),
             params: params.into_iter().collect(),
+            idempotency_key: uuid::Uuid::new_v4().to_string(),
         }
     }
 
@@ -146,6 +726,22 @@ impl Operation {
         self.entity_name = entity_name.into();
         self
     }
+
+    /// Set the idempotency key (e.g. to retry an operation created elsewhere)
+    pub fn with_idempotency_key(mut self, idempotency_key: impl Into<String>) -> Self {
+        self.idempotency_key = idempotency_key.into();
+        self
+    }
+
+    /// Idempotency key of the operation the current task is executing, if any
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn current_idempotency_key() -> Option<String> {
+        CURRENT_IDEMPOTENCY_KEY
+            .try_with(|key| key.clone())
+            .ok()
+            .filter(|key| !key.is_empty())
+    }
 }
 
 /// Type hints for operation parameters
@@ -299,6 +895,10 @@ pub struct OperationWiring {
 
     // Complete operation metadata (no duplication!)
     pub descriptor: OperationDescriptor,
+
+    /// Whether the operation can be applied to all selected entities at once
+    #[serde(default)]
+    pub accepts_multiple: bool,
 }
 
 /// flutter_rust_bridge:non_opaque
@@ -329,8 +929,177 @@ pub enum RenderExpr {
     Object {
         fields: HashMap<String, RenderExpr>,
     },
+    /// The `style:` argument of a widget, e.g. `style:(style color:"red" bold:true)`
+    Style {
+        rules: Vec<StyleRule>,
+    },
+    /// A value formatted for the frontend's locale when rendered, e.g.
+    /// `(format_date this.due_date style:"long")` (see `Format`)
+    Format {
+        format: Format,
+        value: Box<RenderExpr>,
+    },
+    /// Placeholder for a template that failed to compile, carrying the diagnostic
+    ///
+    /// Frontends render it as an inline error card, so the rest of the view still
+    /// renders when one row template of a multi-entity query has a typo.
+    Error {
+        message: String,
+    },
+}
+
+impl RenderExpr {
+    /// Selection configuration declared on this widget via `selectable:true`
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn selection(&self) -> Option<SelectionSpec> {
+        let RenderExpr::FunctionCall { args, .. } = self else {
+            return None;
+        };
+        let literal = |name: &str| {
+            args.iter()
+                .find(|arg| arg.name.as_deref() == Some(name))
+                .and_then(|arg| match &arg.value {
+                    RenderExpr::Literal { value } => Some(value),
+                    _ => None,
+                })
+        };
+        let flag = |name: &str, default: bool| match literal(name) {
+            Some(Value::Boolean(b)) => *b,
+            _ => default,
+        };
+
+        if !flag("selectable", false) {
+            return None;
+        }
+        let defaults = SelectionSpec::default();
+        Some(SelectionSpec {
+            id_column: literal("selection_id")
+                .and_then(|v| v.as_string())
+                .map(String::from)
+                .unwrap_or(defaults.id_column),
+            checkbox_column: flag("selection_checkbox", defaults.checkbox_column),
+            select_all: flag("select_all", defaults.select_all),
+        })
+    }
+}
+
+/// Colors that can be used by name in styles, with their RGB value
+///
+/// Frontends map these names to the same colors; other colors are given as `#rrggbb`.
+pub const NAMED_COLORS: &[(&str, &str)] = &[
+    ("red", "#EF4444"),
+    ("orange", "#F59E0B"),
+    ("yellow", "#EAB308"),
+    ("green", "#10B981"),
+    ("cyan", "#06B6D4"),
+    ("blue", "#3B82F6"),
+    ("purple", "#8B5CF6"),
+    ("gray", "#6B7280"),
+    ("grey", "#6B7280"),
+    ("white", "#FFFFFF"),
+    ("black", "#000000"),
+];
+
+/// Visual attributes of a widget
+///
+/// Unset attributes are left to the frontend's theme.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Style {
+    /// Text/foreground color: a name from `NAMED_COLORS` or `#rrggbb`
+    pub color: Option<String>,
+    /// Background color: a name from `NAMED_COLORS` or `#rrggbb`
+    pub background: Option<String>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underline: Option<bool>,
+    pub strikethrough: Option<bool>,
+    /// De-emphasized (e.g. completed items)
+    pub dim: Option<bool>,
+    /// Space around the widget in spacing units (a cell in terminals, 4px in Flutter)
+    pub padding: Option<u32>,
 }
 
+impl Style {
+    /// Apply the attributes set in `other` on top of this style
+    pub fn merge(&mut self, other: &Style) {
+        fn set<T: Clone>(target: &mut Option<T>, value: &Option<T>) {
+            if value.is_some() {
+                target.clone_from(value);
+            }
+        }
+        set(&mut self.color, &other.color);
+        set(&mut self.background, &other.background);
+        set(&mut self.bold, &other.bold);
+        set(&mut self.italic, &other.italic);
+        set(&mut self.underline, &other.underline);
+        set(&mut self.strikethrough, &other.strikethrough);
+        set(&mut self.dim, &other.dim);
+        set(&mut self.padding, &other.padding);
+    }
+
+    /// RGB value of a style color (`#rrggbb` or a name from `NAMED_COLORS`)
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn rgb(color: &str) -> Option<(u8, u8, u8)> {
+        let hex = match color.strip_prefix('#') {
+            Some(hex) => hex,
+            None => NAMED_COLORS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(color))?
+                .1
+                .strip_prefix('#')?,
+        };
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some((channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+/// A style that applies to rows for which `when` is true (to all rows if `None`)
+///
+/// Written as `(style dim:true when:this.completed)`; a list of styles
+/// (`style:[(style color:"blue"), (style dim:true when:this.completed)]`) applies
+/// the matching rules in order, later ones overriding earlier ones.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StyleRule {
+    pub style: Style,
+    pub when: Option<RenderExpr>,
+}
+
+impl RenderExpr {
+    /// The style declared on this widget for a row, given a predicate evaluating
+    /// `when` conditions against that row
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn resolve_style(&self, mut is_true: impl FnMut(&RenderExpr) -> bool) -> Option<Style> {
+        let RenderExpr::FunctionCall { args, .. } = self else {
+            return None;
+        };
+        let RenderExpr::Style { rules } = &args
+            .iter()
+            .find(|arg| arg.name.as_deref() == Some(STYLE_ARG))?
+            .value
+        else {
+            return None;
+        };
+        let mut style = Style::default();
+        for rule in rules {
+            if rule.when.as_ref().is_none_or(&mut is_true) {
+                style.merge(&rule.style);
+            }
+        }
+        Some(style)
+    }
+}
+
+/// Name of the widget argument holding its style
+pub const STYLE_ARG: &str = "style";
+
 /// flutter_rust_bridge:non_opaque
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Arg {
@@ -396,3 +1165,84 @@ impl RenderableItem {
         }
     }
 }
+
+/// Type of a widget argument, as declared by a frontend
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WidgetArgType {
+    /// Anything, including nested widgets
+    Any,
+    String,
+    Bool,
+    Number,
+    /// A nested widget call, e.g. `item_template:(text content)`
+    Widget,
+}
+
+/// A parameter of a widget
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WidgetParam {
+    pub name: String,
+    pub arg_type: WidgetArgType,
+    pub required: bool,
+}
+
+impl WidgetParam {
+    pub fn required(name: impl Into<String>, arg_type: WidgetArgType) -> Self {
+        Self {
+            name: name.into(),
+            arg_type,
+            required: true,
+        }
+    }
+
+    pub fn optional(name: impl Into<String>, arg_type: WidgetArgType) -> Self {
+        Self {
+            name: name.into(),
+            arg_type,
+            required: false,
+        }
+    }
+}
+
+/// A widget a frontend can render, with the arguments it accepts
+///
+/// Positional arguments fill `params` in order; further positional arguments are
+/// only accepted if `variadic` is set, and must be of that type
+/// (e.g. the children of `row`).
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WidgetSpec {
+    pub name: String,
+    pub params: Vec<WidgetParam>,
+    #[serde(default)]
+    pub variadic: Option<WidgetArgType>,
+}
+
+impl WidgetSpec {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            params: Vec::new(),
+            variadic: None,
+        }
+    }
+
+    /// flutter_rust_bridge:ignore
+    pub fn with_param(mut self, param: WidgetParam) -> Self {
+        self.params.push(param);
+        self
+    }
+
+    /// flutter_rust_bridge:ignore
+    pub fn with_variadic(mut self, arg_type: WidgetArgType) -> Self {
+        self.variadic = Some(arg_type);
+        self
+    }
+
+    pub fn param(&self, name: &str) -> Option<&WidgetParam> {
+        self.params.iter().find(|p| p.name == name)
+    }
+}
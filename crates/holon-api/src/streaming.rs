@@ -14,6 +14,10 @@ tokio::task_local! {
     /// Current trace context for the executing task
     /// Set at FFI boundary, read by BatchTraceContext::from_current_span()
     pub static CURRENT_TRACE_CONTEXT: BatchTraceContext;
+
+    /// Who is making changes in the executing task
+    /// Set around operation dispatch, read by ChangeOrigin::*_with_current_span()
+    pub static CURRENT_CHANGE_SOURCE: ChangeSource;
 }
 
 /// Position in the change stream to start watching from.
@@ -29,6 +33,98 @@ pub enum StreamPosition {
     Version(Vec<u8>),
 }
 
+/// Where a change came from, beyond local vs. remote.
+///
+/// Every field is optional; unset fields are unknown. Used to filter undo and the
+/// audit log by origin and to badge changes made outside the current frontend.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct ChangeSource {
+    /// Device that made the change (e.g. the P2P peer ID)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    /// Frontend the change was made in (e.g. "tui", "flutter", "cli")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frontend: Option<String>,
+    /// Sync provider the change arrived through (e.g. "todoist")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Agent or automation that made the change (e.g. "mcp:assistant")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+}
+
+impl ChangeSource {
+    /// flutter_rust_bridge:ignore
+    pub fn with_device_id(mut self, device_id: impl Into<String>) -> Self {
+        self.device_id = Some(device_id.into());
+        self
+    }
+
+    /// flutter_rust_bridge:ignore
+    pub fn with_frontend(mut self, frontend: impl Into<String>) -> Self {
+        self.frontend = Some(frontend.into());
+        self
+    }
+
+    /// flutter_rust_bridge:ignore
+    pub fn with_provider(mut self, provider: impl Into<String>) -> Self {
+        self.provider = Some(provider.into());
+        self
+    }
+
+    /// flutter_rust_bridge:ignore
+    pub fn with_agent(mut self, agent: impl Into<String>) -> Self {
+        self.agent = Some(agent.into());
+        self
+    }
+
+    /// Source of changes made by the current task, if one was set
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn current() -> Option<Self> {
+        CURRENT_CHANGE_SOURCE.try_with(|source| source.clone()).ok()
+    }
+
+    /// True if nothing is known about the source
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Fill fields that are unset here from `fallback`
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn or(self, fallback: &ChangeSource) -> Self {
+        Self {
+            device_id: self.device_id.or_else(|| fallback.device_id.clone()),
+            frontend: self.frontend.or_else(|| fallback.frontend.clone()),
+            provider: self.provider.or_else(|| fallback.provider.clone()),
+            agent: self.agent.or_else(|| fallback.agent.clone()),
+        }
+    }
+
+    /// True if every field set in `filter` has the same value here
+    ///
+    /// An empty filter matches every source.
+    pub fn matches(&self, filter: &ChangeSource) -> bool {
+        let field_matches =
+            |ours: &Option<String>, wanted: &Option<String>| wanted.is_none() || ours == wanted;
+        field_matches(&self.device_id, &filter.device_id)
+            && field_matches(&self.frontend, &filter.frontend)
+            && field_matches(&self.provider, &filter.provider)
+            && field_matches(&self.agent, &filter.agent)
+    }
+
+    /// Short human-readable label, most specific first (agent, provider, frontend, device)
+    pub fn label(&self) -> Option<String> {
+        self.agent
+            .clone()
+            .or_else(|| self.provider.clone())
+            .or_else(|| self.frontend.clone())
+            .or_else(|| self.device_id.clone())
+    }
+}
+
 /// Origin of a change event (local vs. remote).
 ///
 /// Used to prevent UI echo when local changes sync back via P2P.
@@ -43,6 +139,9 @@ pub enum ChangeOrigin {
         operation_id: Option<String>,
         /// Trace ID (32 hex chars) for distributed tracing
         trace_id: Option<String>,
+        /// Device, frontend, provider or agent that made the change
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source: Option<ChangeSource>,
     },
     /// Change received from P2P sync or external system
     Remote {
@@ -50,12 +149,21 @@ pub enum ChangeOrigin {
         operation_id: Option<String>,
         /// Trace ID (32 hex chars) for distributed tracing
         trace_id: Option<String>,
+        /// Device, frontend, provider or agent that made the change
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source: Option<ChangeSource>,
     },
 }
 
 /// Column name for change origin metadata stored in each row
 pub const CHANGE_ORIGIN_COLUMN: &str = "_change_origin";
 
+/// Column marking soft-deleted (trashed) rows: Unix timestamp in milliseconds, NULL if live
+pub const DELETED_AT_COLUMN: &str = "deleted_at";
+
+/// Column marking archived rows: Unix timestamp in milliseconds, NULL if not archived
+pub const ARCHIVED_AT_COLUMN: &str = "archived_at";
+
 impl ChangeOrigin {
     /// Create Local origin with trace context extracted from current OpenTelemetry span
     ///
@@ -65,6 +173,7 @@ impl ChangeOrigin {
         Self::Local {
             operation_id,
             trace_id,
+            source: ChangeSource::current(),
         }
     }
 
@@ -76,6 +185,7 @@ impl ChangeOrigin {
         Self::Remote {
             operation_id,
             trace_id,
+            source: ChangeSource::current(),
         }
     }
 
@@ -84,6 +194,7 @@ impl ChangeOrigin {
         Self::Local {
             operation_id,
             trace_id,
+            source: None,
         }
     }
 
@@ -92,6 +203,7 @@ impl ChangeOrigin {
         Self::Remote {
             operation_id,
             trace_id,
+            source: None,
         }
     }
 
@@ -100,6 +212,7 @@ impl ChangeOrigin {
         Self::Local {
             operation_id: Some(ctx.span_id.clone()),
             trace_id: Some(ctx.trace_id.clone()),
+            source: None,
         }
     }
 
@@ -108,6 +221,7 @@ impl ChangeOrigin {
         Self::Remote {
             operation_id: Some(ctx.span_id.clone()),
             trace_id: Some(ctx.trace_id.clone()),
+            source: None,
         }
     }
 
@@ -190,6 +304,62 @@ impl ChangeOrigin {
         matches!(self, Self::Local { .. })
     }
 
+    /// Get the structured source if known
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn source(&self) -> Option<&ChangeSource> {
+        match self {
+            Self::Local { source, .. } | Self::Remote { source, .. } => source.as_ref(),
+        }
+    }
+
+    /// Attach a source; fields it leaves unset are kept from the existing source
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn with_source(mut self, new_source: ChangeSource) -> Self {
+        match &mut self {
+            Self::Local { source, .. } | Self::Remote { source, .. } => {
+                *source = Some(match source.take() {
+                    Some(existing) => new_source.or(&existing),
+                    None => new_source,
+                });
+            }
+        }
+        self
+    }
+
+    /// Check if the change was made outside `own` (the source of the frontend asking):
+    /// by a sync provider, an agent, another frontend or device, or remotely
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn is_external_to(&self, own: &ChangeSource) -> bool {
+        let Some(source) = self.source() else {
+            return !self.is_local();
+        };
+        let differs = |theirs: &Option<String>, ours: &Option<String>| {
+            theirs.is_some() && ours.is_some() && theirs != ours
+        };
+        source.provider.is_some()
+            || source.agent.is_some()
+            || differs(&source.frontend, &own.frontend)
+            || differs(&source.device_id, &own.device_id)
+            || !self.is_local()
+    }
+
+    /// Badge text for changes made outside `own`, None for its own changes
+    ///
+    /// flutter_rust_bridge:ignore
+    pub fn badge(&self, own: &ChangeSource) -> Option<String> {
+        if !self.is_external_to(own) {
+            return None;
+        }
+        Some(
+            self.source()
+                .and_then(ChangeSource::label)
+                .unwrap_or_else(|| "remote".to_string()),
+        )
+    }
+
     /// Convert to BatchTraceContext if trace context is available
     ///
     /// flutter_rust_bridge:ignore
@@ -198,10 +368,12 @@ impl ChangeOrigin {
             Self::Local {
                 trace_id,
                 operation_id,
+                ..
             }
             | Self::Remote {
                 trace_id,
                 operation_id,
+                ..
             } => (trace_id.as_ref()?, operation_id.as_ref()?),
         };
         Some(BatchTraceContext {
@@ -245,6 +417,41 @@ pub enum Change<T> {
     },
     /// Block was deleted (tombstone set)
     Deleted { id: String, origin: ChangeOrigin },
+    /// Only some columns of an existing row changed
+    ///
+    /// `id` is the entity ID (not a ROWID); `columns` holds exactly the changed
+    /// columns with their new values. Lets frontends patch individual properties
+    /// instead of re-rendering the whole row.
+    ColumnChange {
+        id: String,
+        columns: HashMap<String, Value>,
+        origin: ChangeOrigin,
+    },
+}
+
+/// Columns whose value differs between two versions of a row, with their new values
+///
+/// Columns missing from `after` are reported as `Value::Null`. The `_change_origin`
+/// column is ignored since the origin travels separately with each change.
+///
+/// flutter_rust_bridge:ignore
+pub fn changed_columns(
+    before: &HashMap<String, Value>,
+    after: &HashMap<String, Value>,
+) -> HashMap<String, Value> {
+    let mut changed: HashMap<String, Value> = after
+        .iter()
+        .filter(|(name, value)| {
+            name.as_str() != CHANGE_ORIGIN_COLUMN && before.get(name.as_str()) != Some(*value)
+        })
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    for name in before.keys() {
+        if name != CHANGE_ORIGIN_COLUMN && !after.contains_key(name) {
+            changed.insert(name.clone(), Value::Null);
+        }
+    }
+    changed
 }
 
 /// Type alias for Change<HashMap<String, Value>>
@@ -281,6 +488,43 @@ pub type BatchMapChange = Batch<MapChange>;
 /// flutter_rust_bridge:non_opaque
 pub type BatchMapChangeWithMetadata = WithMetadata<BatchMapChange, BatchMetadata>;
 
+/// Change to the rows of a window over a query result
+///
+/// Indices are relative to the first row of the window. Applying the changes of
+/// each `WindowChangeBatch` in order keeps a copy of the window in sync with the
+/// backend.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WindowChange {
+    /// The window now holds `rows`, e.g. because it was moved
+    Reset { rows: Vec<HashMap<String, Value>> },
+    /// A row was inserted at `index`; the rows from `index` on move down
+    Inserted {
+        index: usize,
+        data: HashMap<String, Value>,
+    },
+    /// The row at `index` changed
+    Updated {
+        index: usize,
+        data: HashMap<String, Value>,
+    },
+    /// The row at `index` was removed; the rows after it move up
+    Removed { index: usize },
+}
+
+/// Changes to a window over a query result
+///
+/// Only sent when the window's rows or the total row count changed.
+/// flutter_rust_bridge:non_opaque
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowChangeBatch {
+    /// Index of the window's first row within the whole result
+    pub start: usize,
+    /// Number of rows of the whole result
+    pub total: usize,
+    pub changes: Vec<WindowChange>,
+}
+
 /// Generic wrapper for adding metadata to any type
 ///
 /// This allows you to add metadata to any type without modifying the original type.
@@ -346,6 +590,10 @@ pub struct BatchMetadata {
     pub trace_context: Option<BatchTraceContext>,
     /// Sync token to update atomically with the data changes
     pub sync_token: Option<SyncTokenUpdate>,
+    /// The batch holds every entity of the relation (e.g. a Todoist `full_sync`
+    /// response) instead of only the changes since the previous sync token
+    #[serde(default)]
+    pub full_snapshot: bool,
 }
 
 /// Trace context for batch metadata
@@ -0,0 +1,319 @@
+//! Fine-grained text edits
+//!
+//! A `TextDelta` replaces the text `deleted` at character offset `position` with
+//! `inserted`. Deltas record the removed text, so they are always invertible and
+//! can be checked against the document before being applied.
+//!
+//! Content edits made inline are sent as deltas (`apply_text_delta`) instead of
+//! whole-string `set_field` operations. This keeps undo at cursor granularity and
+//! lets concurrent edits be merged by transforming one delta against the other.
+
+use serde::{Deserialize, Serialize};
+
+/// A single replace/insert/delete at a character offset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextDelta {
+    /// Character (not byte) offset where the edit starts
+    pub position: usize,
+    /// Text removed at `position` (empty for pure inserts)
+    pub deleted: String,
+    /// Text inserted at `position` (empty for pure deletes)
+    pub inserted: String,
+}
+
+/// Error raised when a delta does not fit the text it is applied to
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TextDeltaError {
+    #[error("Delta position {position} exceeds text length {len}")]
+    OutOfBounds { position: usize, len: usize },
+
+    #[error("Delta expected to delete {expected:?} at {position}, found {found:?}")]
+    Mismatch {
+        position: usize,
+        expected: String,
+        found: String,
+    },
+
+    #[error("Invalid text delta: {0}")]
+    Parse(String),
+}
+
+impl TextDelta {
+    pub fn insert(position: usize, text: impl Into<String>) -> Self {
+        Self {
+            position,
+            deleted: String::new(),
+            inserted: text.into(),
+        }
+    }
+
+    pub fn delete(position: usize, text: impl Into<String>) -> Self {
+        Self {
+            position,
+            deleted: text.into(),
+            inserted: String::new(),
+        }
+    }
+
+    pub fn replace(
+        position: usize,
+        deleted: impl Into<String>,
+        inserted: impl Into<String>,
+    ) -> Self {
+        Self {
+            position,
+            deleted: deleted.into(),
+            inserted: inserted.into(),
+        }
+    }
+
+    /// Minimal delta turning `old` into `new` (common prefix/suffix trimmed)
+    ///
+    /// Returns None if the strings are equal.
+    pub fn diff(old: &str, new: &str) -> Option<Self> {
+        if old == new {
+            return None;
+        }
+        let old_chars: Vec<char> = old.chars().collect();
+        let new_chars: Vec<char> = new.chars().collect();
+
+        let prefix = old_chars
+            .iter()
+            .zip(new_chars.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let max_suffix = old_chars.len().min(new_chars.len()) - prefix;
+        let suffix = old_chars
+            .iter()
+            .rev()
+            .zip(new_chars.iter().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        Some(Self {
+            position: prefix,
+            deleted: old_chars[prefix..old_chars.len() - suffix].iter().collect(),
+            inserted: new_chars[prefix..new_chars.len() - suffix].iter().collect(),
+        })
+    }
+
+    pub fn is_noop(&self) -> bool {
+        self.deleted == self.inserted
+    }
+
+    /// Number of characters removed
+    pub fn deleted_len(&self) -> usize {
+        self.deleted.chars().count()
+    }
+
+    /// Number of characters inserted
+    pub fn inserted_len(&self) -> usize {
+        self.inserted.chars().count()
+    }
+
+    /// Offset just past the inserted text (the cursor position after the edit)
+    pub fn end_after(&self) -> usize {
+        self.position + self.inserted_len()
+    }
+
+    /// Apply the delta, verifying that the text to delete is present
+    pub fn apply(&self, text: &str) -> Result<String, TextDeltaError> {
+        let start = byte_offset(text, self.position).ok_or(TextDeltaError::OutOfBounds {
+            position: self.position,
+            len: text.chars().count(),
+        })?;
+        let end = start + self.deleted.len();
+        let found = text.get(start..end).unwrap_or(&text[start..]);
+        if found != self.deleted {
+            return Err(TextDeltaError::Mismatch {
+                position: self.position,
+                expected: self.deleted.clone(),
+                found: found.to_string(),
+            });
+        }
+
+        let mut result = String::with_capacity(text.len() + self.inserted.len());
+        result.push_str(&text[..start]);
+        result.push_str(&self.inserted);
+        result.push_str(&text[end..]);
+        Ok(result)
+    }
+
+    /// Delta that reverts this one
+    pub fn invert(&self) -> Self {
+        Self {
+            position: self.position,
+            deleted: self.inserted.clone(),
+            inserted: self.deleted.clone(),
+        }
+    }
+
+    /// Combine with a delta applied right after this one into a single delta
+    ///
+    /// Only contiguous edits are composed: continued typing at the cursor,
+    /// backspacing over (or past) what was just typed, and forward deletes at
+    /// the cursor. Returns None otherwise.
+    pub fn compose(&self, next: &TextDelta) -> Option<TextDelta> {
+        let end = self.end_after();
+
+        // Continued typing / forward delete at the cursor
+        if next.position == end {
+            let mut deleted = self.deleted.clone();
+            deleted.push_str(&next.deleted);
+            let mut inserted = self.inserted.clone();
+            inserted.push_str(&next.inserted);
+            return Some(Self::replace(self.position, deleted, inserted));
+        }
+
+        // Backspace ending at the cursor
+        let next_end = next.position + next.deleted_len();
+        if next_end == end && next.position < end {
+            if next.position >= self.position {
+                // Removes the tail of what was just inserted
+                let keep = next.position - self.position;
+                let mut inserted: String = self.inserted.chars().take(keep).collect();
+                inserted.push_str(&next.inserted);
+                return Some(Self::replace(self.position, self.deleted.clone(), inserted));
+            }
+            if next.deleted.ends_with(&self.inserted) {
+                // Removes everything just inserted and continues before it
+                let before_len = next.deleted.len() - self.inserted.len();
+                let mut deleted = next.deleted[..before_len].to_string();
+                deleted.push_str(&self.deleted);
+                return Some(Self::replace(next.position, deleted, next.inserted.clone()));
+            }
+        }
+
+        None
+    }
+
+    /// Rewrite this delta so it applies after a concurrent `other` delta
+    ///
+    /// Both deltas must have been made against the same text. Edits entirely
+    /// before or after `other` are shifted; inserts at the same position are
+    /// placed after `other`'s insert. Overlapping edits cannot be merged and
+    /// return None.
+    pub fn transform(&self, other: &TextDelta) -> Option<TextDelta> {
+        let self_end = self.position + self.deleted_len();
+        let other_end = other.position + other.deleted_len();
+        let shift = other.inserted_len() as isize - other.deleted_len() as isize;
+
+        if self.position >= other_end {
+            let mut shifted = self.clone();
+            shifted.position = (self.position as isize + shift) as usize;
+            return Some(shifted);
+        }
+        if self_end <= other.position {
+            return Some(self.clone());
+        }
+        None
+    }
+
+    /// Rebase a delta made against `base` onto the text as it is now
+    ///
+    /// Used when the field changed underneath an edit (e.g. a remote sync):
+    /// the concurrent change is recovered by diffing and this delta is transformed
+    /// over it.
+    pub fn rebase(&self, base: &str, current: &str) -> Option<TextDelta> {
+        match TextDelta::diff(base, current) {
+            None => Some(self.clone()),
+            Some(concurrent) => self.transform(&concurrent),
+        }
+    }
+
+    /// Serialize for use as an operation parameter
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Parse an operation parameter produced by `to_json`
+    pub fn from_json(json: &str) -> Result<Self, TextDeltaError> {
+        serde_json::from_str(json).map_err(|e| TextDeltaError::Parse(e.to_string()))
+    }
+}
+
+/// Byte offset of the character at `position`, or None if past the end
+fn byte_offset(text: &str, position: usize) -> Option<usize> {
+    let mut indices = text.char_indices().map(|(i, _)| i).chain([text.len()]);
+    indices.nth(position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_and_invert_roundtrip() {
+        let text = "héllo world";
+        let delta = TextDelta::replace(6, "world", "there");
+        let edited = delta.apply(text).unwrap();
+        assert_eq!(edited, "héllo there");
+        assert_eq!(delta.invert().apply(&edited).unwrap(), text);
+
+        let err = TextDelta::delete(0, "x").apply(text).unwrap_err();
+        assert!(matches!(err, TextDeltaError::Mismatch { .. }));
+        assert!(matches!(
+            TextDelta::insert(20, "!").apply(text),
+            Err(TextDeltaError::OutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn test_diff_is_minimal() {
+        let delta = TextDelta::diff("the quick fox", "the slow fox").unwrap();
+        assert_eq!(delta, TextDelta::replace(4, "quick", "slow"));
+        assert_eq!(TextDelta::diff("same", "same"), None);
+        // Repeated characters must not be counted twice by prefix and suffix
+        assert_eq!(
+            TextDelta::diff("aa", "aaa").unwrap().apply("aa").unwrap(),
+            "aaa"
+        );
+    }
+
+    #[test]
+    fn test_compose_typing_and_backspace() {
+        let typed = TextDelta::insert(3, "a")
+            .compose(&TextDelta::insert(4, "b"))
+            .unwrap();
+        assert_eq!(typed, TextDelta::insert(3, "ab"));
+
+        // Backspace over the last typed char
+        let corrected = typed.compose(&TextDelta::delete(4, "b")).unwrap();
+        assert_eq!(corrected, TextDelta::insert(3, "a"));
+
+        // Backspace past what was typed into existing text
+        let text = "xyz";
+        let first = TextDelta::insert(3, "a");
+        let second = TextDelta::delete(1, "yza");
+        let composed = first.compose(&second).unwrap();
+        let step = second.apply(&first.apply(text).unwrap()).unwrap();
+        assert_eq!(composed.apply(text).unwrap(), step);
+
+        // Edit elsewhere does not compose
+        assert_eq!(typed.compose(&TextDelta::insert(0, "z")), None);
+    }
+
+    #[test]
+    fn test_transform_concurrent_edits() {
+        let base = "hello world";
+        let local = TextDelta::insert(11, "!");
+        let remote = TextDelta::replace(0, "hello", "goodbye");
+
+        let local_after = local.transform(&remote).unwrap();
+        let remote_after = remote.transform(&local).unwrap();
+        let a = local_after.apply(&remote.apply(base).unwrap()).unwrap();
+        let b = remote_after.apply(&local.apply(base).unwrap()).unwrap();
+        assert_eq!(a, "goodbye world!");
+        assert_eq!(a, b);
+
+        // Overlapping edits conflict
+        let overlapping = TextDelta::delete(3, "lo w");
+        assert_eq!(overlapping.transform(&remote), None);
+
+        assert_eq!(
+            local.rebase(base, "goodbye world").unwrap(),
+            TextDelta::insert(13, "!")
+        );
+    }
+}
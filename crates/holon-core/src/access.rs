@@ -0,0 +1,54 @@
+//! When entities were last viewed and modified.
+//!
+//! An `EntityAccess` row records the last time an entity was opened in the UI
+//! and the last time a local operation changed it, for any entity of any
+//! datasource. The `recently_viewed` and `recently_modified` PRQL functions
+//! join against the `entity_access` table to build "Recent" views.
+
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+
+/// Access timestamps of one entity.
+///
+/// Table name: `entity_access`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Entity)]
+#[entity(name = "entity_access", short_name = "access")]
+pub struct EntityAccess {
+    /// Primary key: `"{entity_name}:{entity_id}"`
+    #[primary_key]
+    pub id: String,
+
+    /// Entity the row belongs to (e.g. `todoist_tasks`, `logseq_blocks`)
+    #[indexed]
+    pub entity_name: String,
+
+    /// The entity's own ID
+    #[indexed]
+    pub entity_id: String,
+
+    /// When the entity was last viewed (Unix timestamp in milliseconds)
+    pub last_viewed_at: Option<i64>,
+
+    /// When a local operation last changed the entity (Unix timestamp in milliseconds)
+    pub last_modified_at: Option<i64>,
+}
+
+impl EntityAccess {
+    /// Build the primary key for an entity's ID
+    pub fn key(entity_name: &str, entity_id: &str) -> String {
+        format!("{}:{}", entity_name, entity_id)
+    }
+
+    /// Row without any access recorded
+    pub fn new(entity_name: impl Into<String>, entity_id: impl Into<String>) -> Self {
+        let entity_name = entity_name.into();
+        let entity_id = entity_id.into();
+        Self {
+            id: Self::key(&entity_name, &entity_id),
+            entity_name,
+            entity_id,
+            last_viewed_at: None,
+            last_modified_at: None,
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Attachment entity.
+//!
+//! An `Attachment` is a file or URL attached to any entity (a task, a headline, a
+//! block). Attachments are added by `attach_file` (see `AttachmentOperations`) or
+//! imported from external sources such as org-mode `[[file:...]]` links. They live
+//! in the `attachments` table, so an entity's attachments are a plain join away and
+//! can be rendered with the `attachment_chip` widget.
+
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+
+/// Source of attachments added via `attach_file` in the app
+pub const LOCAL_ATTACHMENT_SOURCE: &str = "local";
+
+/// A file or URL attached to an entity.
+///
+/// Table name: `attachments`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Entity)]
+#[entity(name = "attachments", short_name = "attachment")]
+pub struct Attachment {
+    #[primary_key]
+    pub id: String,
+
+    /// Entity the attachment belongs to (e.g. `todoist_tasks`, `org_headlines`)
+    #[indexed]
+    pub entity_name: String,
+
+    /// ID of the entity the attachment belongs to
+    #[indexed]
+    pub entity_id: String,
+
+    /// Display name (the file name unless the source names it)
+    pub name: String,
+
+    /// Absolute path of a local file
+    pub path: Option<String>,
+
+    /// URL of a remote file
+    pub url: Option<String>,
+
+    pub mime_type: Option<String>,
+
+    /// File size in bytes, if known
+    pub size_bytes: Option<i64>,
+
+    /// Where the attachment came from: `local` or e.g. `org:<file id>` for imported ones
+    #[indexed]
+    pub source: String,
+
+    /// When the attachment was added (Unix timestamp in milliseconds)
+    pub created_at: i64,
+}
+
+impl Attachment {
+    /// Attachment of the local file at `path`
+    pub fn file(
+        entity_name: impl Into<String>,
+        entity_id: impl Into<String>,
+        path: impl Into<String>,
+        source: impl Into<String>,
+        created_at: i64,
+    ) -> Self {
+        let path = path.into();
+        let name = path
+            .rsplit(['/', '\\'])
+            .find(|part| !part.is_empty())
+            .unwrap_or(&path)
+            .to_string();
+        Self::new(
+            entity_name.into(),
+            entity_id.into(),
+            name,
+            Some(path),
+            None,
+            source.into(),
+            created_at,
+        )
+    }
+
+    /// Attachment of the remote file at `url`
+    pub fn url(
+        entity_name: impl Into<String>,
+        entity_id: impl Into<String>,
+        name: impl Into<String>,
+        url: impl Into<String>,
+        source: impl Into<String>,
+        created_at: i64,
+    ) -> Self {
+        Self::new(
+            entity_name.into(),
+            entity_id.into(),
+            name.into(),
+            None,
+            Some(url.into()),
+            source.into(),
+            created_at,
+        )
+    }
+
+    fn new(
+        entity_name: String,
+        entity_id: String,
+        name: String,
+        path: Option<String>,
+        url: Option<String>,
+        source: String,
+        created_at: i64,
+    ) -> Self {
+        // Attaching the same file to the same entity twice yields the same row
+        let location = path.as_deref().or(url.as_deref()).unwrap_or_default();
+        Self {
+            id: format!("{}:{}:{}", entity_name, entity_id, location),
+            mime_type: guess_mime_type(&name).map(str::to_string),
+            entity_name,
+            entity_id,
+            name,
+            path,
+            url,
+            size_bytes: None,
+            source,
+            created_at,
+        }
+    }
+
+    pub fn with_size(mut self, size_bytes: i64) -> Self {
+        self.size_bytes = Some(size_bytes);
+        self
+    }
+
+    /// Path or URL of the attached file
+    pub fn location(&self) -> &str {
+        self.path
+            .as_deref()
+            .or(self.url.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+/// MIME type of a file name's extension, for common types
+pub fn guess_mime_type(name: &str) -> Option<&'static str> {
+    let (_, extension) = name.rsplit_once('.')?;
+    let mime = match extension.to_ascii_lowercase().as_str() {
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "org" => "text/org",
+        "html" | "htm" => "text/html",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// Format a file size for display (`512 B`, `1.5 KB`, `12.0 MB`)
+pub fn format_size(size_bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if size_bytes < 1024 {
+        return format!("{} B", size_bytes.max(0));
+    }
+    let mut size = size_bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_attachment() {
+        let attachment = Attachment::file(
+            "todoist_tasks",
+            "t1",
+            "/home/me/Report.PDF",
+            LOCAL_ATTACHMENT_SOURCE,
+            0,
+        );
+        assert_eq!(attachment.name, "Report.PDF");
+        assert_eq!(attachment.mime_type.as_deref(), Some("application/pdf"));
+        assert_eq!(attachment.id, "todoist_tasks:t1:/home/me/Report.PDF");
+        assert_eq!(attachment.location(), "/home/me/Report.PDF");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(12 * 1024 * 1024), "12.0 MB");
+    }
+}
@@ -1,6 +1,9 @@
 //! Re-export datasource types for macro compatibility
 //!
 //! This module exists to match the path structure expected by the operations_trait macro:
-//! `#crate_path::core::datasource::UnknownOperationError`
+//! `#crate_path::core::datasource::UnknownOperationError` and
+//! `#crate_path::core::datasource::MaybeSendSync`
 
-pub use crate::{Result, UnknownOperationError};
+pub use crate::{
+    HolonError, HolonResult, MaybeSendSync, OperationProvider, Result, UnknownOperationError,
+};
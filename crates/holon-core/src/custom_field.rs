@@ -0,0 +1,263 @@
+//! User-defined fields on entities.
+//!
+//! A `CustomFieldDefinition` adds a typed field (e.g. "energy", "context") to an
+//! entity type without changing its table. Values live in the `custom_field_values`
+//! side table as JSON text, one row per entity and field, so queries read them with
+//! `json_extract` (see the `custom` PRQL function) and operations set them with
+//! `set_custom_field` (see `CrudOperations`).
+
+use std::fmt;
+
+use holon_api::Value;
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+
+/// Type of the values of a custom field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CustomFieldType {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    DateTime,
+}
+
+impl CustomFieldType {
+    /// Parse a type name as used by `define_custom_field` (e.g. `"integer"`)
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "string" | "text" => Ok(Self::String),
+            "integer" | "int" => Ok(Self::Integer),
+            "float" | "number" => Ok(Self::Float),
+            "boolean" | "bool" => Ok(Self::Boolean),
+            "datetime" | "date" => Ok(Self::DateTime),
+            _ => Err(format!(
+                "Unknown custom field type '{}' (expected string, integer, float, boolean or datetime)",
+                name
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::Boolean => "boolean",
+            Self::DateTime => "datetime",
+        }
+    }
+
+    /// JSON stored for `value`, or an error if it isn't of this type
+    ///
+    /// Integers are accepted for float fields and 0/1 for boolean fields; datetime
+    /// fields take any text `Value::parse_datetime` understands and store it in UTC.
+    pub fn encode(&self, value: &Value) -> Result<serde_json::Value, String> {
+        let encoded = match (self, value) {
+            (Self::String, Value::String(s)) => Some(serde_json::Value::from(s.as_str())),
+            (Self::Integer, Value::Integer(i)) => Some(serde_json::Value::from(*i)),
+            (Self::Float, Value::Float(_) | Value::Integer(_)) => {
+                value.as_f64().map(serde_json::Value::from)
+            }
+            (Self::Boolean, Value::Boolean(b)) => Some(serde_json::Value::from(*b)),
+            (Self::Boolean, Value::Integer(i @ (0 | 1))) => Some(serde_json::Value::from(*i == 1)),
+            (Self::DateTime, Value::DateTime(s) | Value::String(s)) => Value::parse_datetime(s)
+                .and_then(|parsed| parsed.as_datetime_string().map(serde_json::Value::from)),
+            _ => None,
+        };
+        encoded.ok_or_else(|| format!("Expected a {} value, got {:?}", self.as_str(), value))
+    }
+
+    /// Value of stored JSON text; `Value::Null` if it can't be read
+    pub fn decode(&self, json: &str) -> Value {
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(json) else {
+            return Value::Null;
+        };
+        match (self, json) {
+            (Self::DateTime, serde_json::Value::String(s)) => {
+                Value::parse_datetime(&s).unwrap_or(Value::String(s))
+            }
+            (Self::Float, serde_json::Value::Number(n)) => {
+                n.as_f64().map(Value::Float).unwrap_or(Value::Null)
+            }
+            (_, json) => Value::from_json_value(json),
+        }
+    }
+}
+
+impl fmt::Display for CustomFieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Definition of a user-defined field of an entity type.
+///
+/// Table name: `custom_field_definitions`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Entity)]
+#[entity(name = "custom_field_definitions", short_name = "custom_field")]
+pub struct CustomFieldDefinition {
+    /// Primary key: `"{entity_name}:{name}"`
+    #[primary_key]
+    pub id: String,
+
+    /// Entity the field belongs to (e.g. `todoist_tasks`)
+    #[indexed]
+    pub entity_name: String,
+
+    /// Field name, as passed to `custom` and `set_custom_field`
+    pub name: String,
+
+    /// Type of the field's values (see `CustomFieldType`)
+    pub field_type: String,
+
+    /// When the field was defined (Unix timestamp in milliseconds)
+    pub created_at: i64,
+}
+
+impl CustomFieldDefinition {
+    pub fn new(
+        entity_name: impl Into<String>,
+        name: impl Into<String>,
+        field_type: CustomFieldType,
+        created_at: i64,
+    ) -> Self {
+        let entity_name = entity_name.into();
+        let name = name.into();
+        Self {
+            id: Self::key(&entity_name, &name),
+            entity_name,
+            name,
+            field_type: field_type.as_str().to_string(),
+            created_at,
+        }
+    }
+
+    /// Build the primary key of field `name` of `entity_name`
+    pub fn key(entity_name: &str, name: &str) -> String {
+        format!("{}:{}", entity_name, name)
+    }
+
+    /// Parsed type of the field
+    pub fn field_type(&self) -> Result<CustomFieldType, String> {
+        CustomFieldType::parse(&self.field_type)
+    }
+}
+
+/// Value of a custom field of one entity.
+///
+/// Table name: `custom_field_values`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Entity)]
+#[entity(name = "custom_field_values", short_name = "custom_field_value")]
+pub struct CustomFieldValue {
+    /// Primary key: `"{entity_name}:{entity_id}:{field}"`
+    #[primary_key]
+    pub id: String,
+
+    /// Entity type of the entity (e.g. `todoist_tasks`)
+    #[indexed]
+    pub entity_name: String,
+
+    /// ID of the entity the value belongs to
+    #[indexed]
+    pub entity_id: String,
+
+    /// Name of the custom field
+    pub field: String,
+
+    /// The value as JSON text (e.g. `3`, `"home"`)
+    pub value: String,
+
+    /// When the value was last set (Unix timestamp in milliseconds)
+    pub updated_at: i64,
+}
+
+impl CustomFieldValue {
+    pub fn new(
+        entity_name: impl Into<String>,
+        entity_id: impl Into<String>,
+        field: impl Into<String>,
+        value: &serde_json::Value,
+        updated_at: i64,
+    ) -> Self {
+        let entity_name = entity_name.into();
+        let entity_id = entity_id.into();
+        let field = field.into();
+        Self {
+            id: Self::key(&entity_name, &entity_id, &field),
+            entity_name,
+            entity_id,
+            field,
+            value: value.to_string(),
+            updated_at,
+        }
+    }
+
+    /// Build the primary key of `field` of entity `entity_id`
+    pub fn key(entity_name: &str, entity_id: &str, field: &str) -> String {
+        format!("{}:{}:{}", entity_name, entity_id, field)
+    }
+}
+
+/// Whether `name` can be used as a custom field name (letters, digits and `_`)
+pub fn is_valid_field_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_checks_type() {
+        assert_eq!(
+            CustomFieldType::Integer.encode(&Value::Integer(3)),
+            Ok(serde_json::json!(3))
+        );
+        assert_eq!(
+            CustomFieldType::Float.encode(&Value::Integer(3)),
+            Ok(serde_json::json!(3.0))
+        );
+        assert_eq!(
+            CustomFieldType::Boolean.encode(&Value::Integer(1)),
+            Ok(serde_json::json!(true))
+        );
+        assert!(CustomFieldType::Integer
+            .encode(&Value::String("high".to_string()))
+            .is_err());
+        assert_eq!(
+            CustomFieldType::DateTime.encode(&Value::String("2024-03-01".to_string())),
+            Ok(serde_json::json!("2024-03-01T00:00:00.000Z"))
+        );
+    }
+
+    #[test]
+    fn test_decode_round_trips() {
+        for (field_type, value) in [
+            (CustomFieldType::String, Value::String("home".to_string())),
+            (CustomFieldType::Integer, Value::Integer(3)),
+            (CustomFieldType::Float, Value::Float(2.0)),
+            (CustomFieldType::Boolean, Value::Boolean(false)),
+        ] {
+            let json = field_type.encode(&value).unwrap().to_string();
+            assert_eq!(field_type.decode(&json), value);
+        }
+        assert_eq!(CustomFieldType::parse("INT"), Ok(CustomFieldType::Integer));
+        assert!(CustomFieldType::parse("color").is_err());
+    }
+
+    #[test]
+    fn test_keys() {
+        let definition =
+            CustomFieldDefinition::new("todoist_tasks", "energy", CustomFieldType::Integer, 0);
+        assert_eq!(definition.id, "todoist_tasks:energy");
+        assert_eq!(definition.field_type(), Ok(CustomFieldType::Integer));
+        assert_eq!(
+            CustomFieldValue::key("todoist_tasks", "t1", "energy"),
+            "todoist_tasks:t1:energy"
+        );
+        assert!(is_valid_field_name("energy_2"));
+        assert!(!is_valid_field_name("energy'"));
+    }
+}
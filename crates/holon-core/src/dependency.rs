@@ -0,0 +1,82 @@
+//! Task dependency entity.
+//!
+//! A `TaskDependency` says that one task blocks another: the blocked task can't be
+//! worked on until the blocker is completed. Dependencies are added by
+//! `add_dependency` (see `DependencyOperations`) and live in the `task_dependencies`
+//! table, together with whether the blocker is completed, so "what is blocked"
+//! is a plain query (see the `blocked_tasks` view).
+
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+
+/// One task blocking another of the same entity type.
+///
+/// Table name: `task_dependencies`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Entity)]
+#[entity(name = "task_dependencies", short_name = "dependency")]
+pub struct TaskDependency {
+    /// Primary key: `"{entity_name}:{task_id}>{blocks_id}"`
+    #[primary_key]
+    pub id: String,
+
+    /// Entity both tasks belong to (e.g. `todoist_tasks`, `org_headlines`)
+    #[indexed]
+    pub entity_name: String,
+
+    /// ID of the blocking task
+    #[indexed]
+    pub task_id: String,
+
+    /// ID of the blocked task
+    #[indexed]
+    pub blocks_id: String,
+
+    /// Whether the blocking task is completed (the dependency no longer blocks)
+    pub blocker_completed: bool,
+
+    /// When the dependency was added (Unix timestamp in milliseconds)
+    pub created_at: i64,
+}
+
+impl TaskDependency {
+    /// Dependency of `blocks_id` on `task_id`
+    pub fn new(
+        entity_name: impl Into<String>,
+        task_id: impl Into<String>,
+        blocks_id: impl Into<String>,
+        blocker_completed: bool,
+        created_at: i64,
+    ) -> Self {
+        let entity_name = entity_name.into();
+        let task_id = task_id.into();
+        let blocks_id = blocks_id.into();
+        Self {
+            id: Self::key(&entity_name, &task_id, &blocks_id),
+            entity_name,
+            task_id,
+            blocks_id,
+            blocker_completed,
+            created_at,
+        }
+    }
+
+    /// Build the primary key of the dependency of `blocks_id` on `task_id`
+    pub fn key(entity_name: &str, task_id: &str, blocks_id: &str) -> String {
+        format!("{}:{}>{}", entity_name, task_id, blocks_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key() {
+        let dependency = TaskDependency::new("todoist_tasks", "t1", "t2", false, 0);
+        assert_eq!(dependency.id, "todoist_tasks:t1>t2");
+        assert_eq!(
+            TaskDependency::key("todoist_tasks", "t1", "t2"),
+            dependency.id
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Structured errors of the core operation traits
+//!
+//! `CrudOperations`, `BlockOperations` and `TaskOperations` return `HolonError`, so
+//! callers can tell a missing entity from a rejected precondition or a failed
+//! remote call without parsing messages. Other code keeps using the boxed
+//! `Result`; a `HolonError` boxed on the way up can be recovered with
+//! `HolonError::find`. At the FFI boundary it converts into `holon_api::ApiError`.
+
+use holon_api::{ApiError, PreconditionViolation, ValidationErrors};
+
+pub type HolonResult<T> = std::result::Result<T, HolonError>;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum HolonError {
+    #[error("{entity} not found: {id}")]
+    NotFound { entity: String, id: String },
+
+    /// The entity is not in a state that allows the operation (e.g. outdenting a root block)
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+
+    /// A `#[require(...)]` clause of the operation rejected its parameters
+    #[error("Precondition failed: {0}")]
+    PreconditionViolated(PreconditionViolation),
+
+    /// The external system rejected the request; `status` is its HTTP status
+    #[error("Remote error ({status}): {message}")]
+    RemoteError { status: u16, message: String },
+
+    /// The entity was changed concurrently
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// The operation's parameters are invalid
+    #[error("Validation failed: {0}")]
+    Validation(String),
+
+    #[error("{0}")]
+    Unknown(String),
+}
+
+impl HolonError {
+    pub fn not_found(entity: &str, id: &str) -> Self {
+        Self::NotFound {
+            entity: entity.to_string(),
+            id: id.to_string(),
+        }
+    }
+
+    pub fn precondition(message: impl Into<String>) -> Self {
+        Self::PreconditionFailed(message.into())
+    }
+
+    pub fn remote(status: u16, message: impl Into<String>) -> Self {
+        Self::RemoteError {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::Validation(message.into())
+    }
+
+    /// The `HolonError` in `err` or its sources, if any
+    pub fn find<'a>(err: &'a (dyn std::error::Error + 'static)) -> Option<&'a HolonError> {
+        let mut current = Some(err);
+        while let Some(err) = current {
+            if let Some(holon_error) = err.downcast_ref::<HolonError>() {
+                return Some(holon_error);
+            }
+            current = err.source();
+        }
+        None
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::NotFound { .. })
+    }
+}
+
+impl From<String> for HolonError {
+    fn from(message: String) -> Self {
+        Self::Unknown(message)
+    }
+}
+
+impl From<&str> for HolonError {
+    fn from(message: &str) -> Self {
+        Self::Unknown(message.to_string())
+    }
+}
+
+/// Keeps a boxed `HolonError` intact; any other error becomes `Unknown`
+impl From<Box<dyn std::error::Error + Send + Sync>> for HolonError {
+    fn from(err: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        match err.downcast::<HolonError>() {
+            Ok(holon_error) => *holon_error,
+            Err(err) => Self::Unknown(err.to_string()),
+        }
+    }
+}
+
+impl From<anyhow::Error> for HolonError {
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast::<HolonError>() {
+            Ok(holon_error) => holon_error,
+            Err(err) => Self::Unknown(err.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for HolonError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Validation(err.to_string())
+    }
+}
+
+impl From<ValidationErrors> for HolonError {
+    fn from(errors: ValidationErrors) -> Self {
+        let messages: Vec<String> = errors.0.iter().map(|e| e.to_string()).collect();
+        Self::Validation(messages.join("; "))
+    }
+}
+
+impl From<PreconditionViolation> for HolonError {
+    fn from(violation: PreconditionViolation) -> Self {
+        Self::PreconditionViolated(violation)
+    }
+}
+
+impl From<HolonError> for ApiError {
+    fn from(err: HolonError) -> Self {
+        match err {
+            HolonError::NotFound { entity, id } => ApiError::NotFound { entity, id },
+            HolonError::PreconditionFailed(message) | HolonError::Validation(message) => {
+                ApiError::InvalidOperation { message }
+            }
+            HolonError::PreconditionViolated(violation) => ApiError::PreconditionViolated {
+                message: violation.to_string(),
+                operation: violation.operation,
+                clause: violation.clause,
+                params: violation.params.into_iter().collect(),
+            },
+            HolonError::RemoteError { status, message } => {
+                ApiError::RemoteError { status, message }
+            }
+            HolonError::Conflict(message) => ApiError::Conflict { message },
+            HolonError::Unknown(message) => ApiError::InternalError { message },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boxed_error_keeps_its_kind() {
+        let boxed: Box<dyn std::error::Error + Send + Sync> =
+            Box::new(HolonError::not_found("block", "b1"));
+        assert_eq!(
+            HolonError::find(boxed.as_ref()),
+            Some(&HolonError::not_found("block", "b1"))
+        );
+        assert!(HolonError::from(boxed).is_not_found());
+
+        let other: Box<dyn std::error::Error + Send + Sync> = "connection reset".into();
+        assert_eq!(
+            HolonError::from(other),
+            HolonError::Unknown("connection reset".to_string())
+        );
+    }
+
+    #[test]
+    fn test_converts_to_api_error() {
+        assert!(matches!(
+            ApiError::from(HolonError::remote(429, "Too many requests")),
+            ApiError::RemoteError { status: 429, .. }
+        ));
+        assert!(matches!(
+            ApiError::from(HolonError::precondition("Cannot outdent root block")),
+            ApiError::InvalidOperation { .. }
+        ));
+    }
+}
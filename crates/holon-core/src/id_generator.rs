@@ -0,0 +1,314 @@
+//! Pluggable ID generation for entities.
+//!
+//! Datasources create IDs through an `IdGenerator` chosen via `IdStrategy`, which
+//! each DI module exposes in its config (e.g. `OrgModeConfig::with_id_strategy`).
+//! UUIDv7 (the default) and ULID embed the creation time and sort lexicographically
+//! in creation order, which keeps newly created blocks in a stable order; NanoID
+//! gives short random IDs for sources that don't need ordering.
+//!
+//! `TempIdMap` tracks temporary IDs handed out for optimistic creates until the
+//! remote system assigns the real ID.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use uuid::Uuid;
+
+use crate::traits::MaybeSendSync;
+
+/// Generates IDs for newly created entities
+pub trait IdGenerator: MaybeSendSync {
+    /// Generate a new unique ID
+    fn generate(&self) -> String;
+
+    /// Whether IDs generated later compare greater (as strings) than earlier ones
+    fn is_sortable(&self) -> bool;
+}
+
+/// 122 random bits from the system RNG (via UUIDv4)
+fn random_u128() -> u128 {
+    Uuid::new_v4().as_u128()
+}
+
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis().max(0) as u64
+}
+
+/// Time-ordered UUIDv7 (`0190b6d2-...`), monotonic within the process
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidV7Generator;
+
+impl IdGenerator for UuidV7Generator {
+    fn generate(&self) -> String {
+        Uuid::now_v7().to_string()
+    }
+
+    fn is_sortable(&self) -> bool {
+        true
+    }
+}
+
+const CROCKFORD_BASE32: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// ULID (`01HQ3V5N8KX0Z6J4M2Y7C9D1EF`): 48-bit timestamp + 80 random bits
+///
+/// Monotonic: IDs generated within the same millisecond increment the random part.
+#[derive(Debug, Default)]
+pub struct UlidGenerator {
+    /// Timestamp and random part of the last generated ULID
+    last: Mutex<(u64, u128)>,
+}
+
+impl UlidGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IdGenerator for UlidGenerator {
+    fn generate(&self) -> String {
+        const RANDOM_MASK: u128 = (1 << 80) - 1;
+
+        let mut last = self.last.lock().unwrap();
+        let now = now_ms();
+        let (timestamp, random) = if now > last.0 {
+            (now, random_u128() & RANDOM_MASK)
+        } else if last.1 < RANDOM_MASK {
+            // Same millisecond (or clock went backwards): keep ordering by incrementing
+            (last.0, last.1 + 1)
+        } else {
+            (last.0 + 1, random_u128() & RANDOM_MASK)
+        };
+        *last = (timestamp, random);
+
+        let value = ((timestamp as u128 & ((1 << 48) - 1)) << 80) | random;
+        (0..26)
+            .rev()
+            .map(|i| CROCKFORD_BASE32[((value >> (i * 5)) & 0x1f) as usize] as char)
+            .collect()
+    }
+
+    fn is_sortable(&self) -> bool {
+        true
+    }
+}
+
+const NANOID_ALPHABET: &[u8; 64] =
+    b"_-0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// NanoID (`V1StGXR8_Z5jdHi6B-myT`): short, URL-safe, random (not sortable)
+#[derive(Debug, Clone, Copy)]
+pub struct NanoIdGenerator {
+    size: usize,
+}
+
+impl NanoIdGenerator {
+    /// Standard NanoID length (~126 bits of randomness)
+    pub const DEFAULT_SIZE: usize = 21;
+
+    pub fn new(size: usize) -> Self {
+        Self { size }
+    }
+}
+
+impl Default for NanoIdGenerator {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_SIZE)
+    }
+}
+
+impl IdGenerator for NanoIdGenerator {
+    fn generate(&self) -> String {
+        let mut id = String::with_capacity(self.size);
+        let mut bits = 0u128;
+        let mut available = 0;
+        for _ in 0..self.size {
+            if available < 6 {
+                bits = random_u128();
+                available = 122;
+            }
+            id.push(NANOID_ALPHABET[(bits & 0x3f) as usize] as char);
+            bits >>= 6;
+            available -= 6;
+        }
+        id
+    }
+
+    fn is_sortable(&self) -> bool {
+        false
+    }
+}
+
+/// Configurable choice of ID generator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdStrategy {
+    #[default]
+    UuidV7,
+    Ulid,
+    NanoId,
+}
+
+impl IdStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdStrategy::UuidV7 => "uuidv7",
+            IdStrategy::Ulid => "ulid",
+            IdStrategy::NanoId => "nanoid",
+        }
+    }
+
+    /// Create a generator for this strategy
+    pub fn generator(&self) -> Arc<dyn IdGenerator> {
+        match self {
+            IdStrategy::UuidV7 => Arc::new(UuidV7Generator),
+            IdStrategy::Ulid => Arc::new(UlidGenerator::new()),
+            IdStrategy::NanoId => Arc::new(NanoIdGenerator::default()),
+        }
+    }
+}
+
+impl FromStr for IdStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "uuidv7" | "uuid_v7" | "uuid" => Ok(IdStrategy::UuidV7),
+            "ulid" => Ok(IdStrategy::Ulid),
+            "nanoid" | "nano_id" => Ok(IdStrategy::NanoId),
+            other => Err(format!(
+                "Unknown ID strategy '{}' (expected uuidv7, ulid or nanoid)",
+                other
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for IdStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Shared generator for datasources without a configured strategy (UUIDv7)
+pub fn default_id_generator() -> Arc<dyn IdGenerator> {
+    static DEFAULT: OnceLock<Arc<dyn IdGenerator>> = OnceLock::new();
+    DEFAULT
+        .get_or_init(|| IdStrategy::default().generator())
+        .clone()
+}
+
+/// Mapping between temporary IDs of optimistic creates and the remote IDs replacing them
+///
+/// The temporary ID stays valid after the remote ID is known: `resolve` maps it to the
+/// remote ID (for commands sent to the remote system), and `local_id` maps the remote
+/// ID back (so UI state keyed by the temporary ID survives the replacement).
+#[derive(Default)]
+pub struct TempIdMap {
+    generator: Option<Arc<dyn IdGenerator>>,
+    /// temporary ID -> remote ID
+    to_remote: RwLock<HashMap<String, String>>,
+    /// remote ID -> temporary ID
+    to_temp: RwLock<HashMap<String, String>>,
+}
+
+impl TempIdMap {
+    pub fn new(generator: Arc<dyn IdGenerator>) -> Self {
+        Self {
+            generator: Some(generator),
+            ..Default::default()
+        }
+    }
+
+    /// Hand out a new temporary ID
+    pub fn new_temp_id(&self) -> String {
+        match &self.generator {
+            Some(generator) => generator.generate(),
+            None => default_id_generator().generate(),
+        }
+    }
+
+    /// Record that the remote system assigned `remote_id` to `temp_id`
+    pub fn register(&self, temp_id: &str, remote_id: &str) {
+        self.to_remote
+            .write()
+            .unwrap()
+            .insert(temp_id.to_string(), remote_id.to_string());
+        self.to_temp
+            .write()
+            .unwrap()
+            .insert(remote_id.to_string(), temp_id.to_string());
+    }
+
+    /// The remote ID for `id` if it is a mapped temporary ID, otherwise `id` itself
+    pub fn resolve(&self, id: &str) -> String {
+        self.to_remote
+            .read()
+            .unwrap()
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// The remote ID assigned to a temporary ID, if known yet
+    pub fn remote_id(&self, temp_id: &str) -> Option<String> {
+        self.to_remote.read().unwrap().get(temp_id).cloned()
+    }
+
+    /// The temporary ID a remote ID replaced, if it was created optimistically
+    pub fn local_id(&self, remote_id: &str) -> Option<String> {
+        self.to_temp.read().unwrap().get(remote_id).cloned()
+    }
+
+    /// Forget a mapping once nothing refers to the temporary ID anymore
+    pub fn remove(&self, temp_id: &str) {
+        if let Some(remote_id) = self.to_remote.write().unwrap().remove(temp_id) {
+            self.to_temp.write().unwrap().remove(&remote_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sortable_strategies_generate_increasing_ids() {
+        for strategy in [IdStrategy::UuidV7, IdStrategy::Ulid] {
+            let generator = strategy.generator();
+            assert!(generator.is_sortable());
+            let ids: Vec<String> = (0..100).map(|_| generator.generate()).collect();
+            let mut sorted = ids.clone();
+            sorted.sort();
+            sorted.dedup();
+            assert_eq!(ids, sorted, "{} IDs are not increasing", strategy);
+        }
+
+        let ulid = UlidGenerator::new().generate();
+        assert_eq!(ulid.len(), 26);
+        let nanoid = NanoIdGenerator::default().generate();
+        assert_eq!(nanoid.len(), 21);
+        assert!(nanoid
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-'));
+
+        assert_eq!("ULID".parse::<IdStrategy>(), Ok(IdStrategy::Ulid));
+        assert!("snowflake".parse::<IdStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_temp_id_map() {
+        let map = TempIdMap::new(Arc::new(UuidV7Generator));
+        let temp_id = map.new_temp_id();
+        assert_eq!(map.resolve(&temp_id), temp_id);
+        assert_eq!(map.remote_id(&temp_id), None);
+
+        map.register(&temp_id, "6Jf8VQXxpwv56VQ7");
+        assert_eq!(map.resolve(&temp_id), "6Jf8VQXxpwv56VQ7");
+        assert_eq!(map.local_id("6Jf8VQXxpwv56VQ7"), Some(temp_id.clone()));
+        assert_eq!(map.resolve("other"), "other");
+
+        map.remove(&temp_id);
+        assert_eq!(map.local_id("6Jf8VQXxpwv56VQ7"), None);
+    }
+}
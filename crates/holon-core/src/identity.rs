@@ -0,0 +1,60 @@
+//! Cross-datasource identity mapping.
+//!
+//! Each datasource has its own ID scheme: a Todoist task ID means nothing to
+//! Logseq. An `EntityIdentity` row says "this external ID of this entity refers
+//! to identity X"; rows sharing an `identity_id` describe the same real-world
+//! thing. The `resolve` PRQL function joins against the `entity_identities`
+//! table to translate IDs between entities.
+
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+
+/// One entity's ID for a shared identity.
+///
+/// Table name: `entity_identities`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Entity)]
+#[entity(name = "entity_identities", short_name = "identity")]
+pub struct EntityIdentity {
+    /// Primary key: `"{entity_name}:{external_id}"`
+    #[primary_key]
+    pub id: String,
+
+    /// Shared by all rows referring to the same thing
+    #[indexed]
+    pub identity_id: String,
+
+    /// Entity the ID belongs to (e.g. `todoist_tasks`, `logseq_blocks`)
+    #[indexed]
+    pub entity_name: String,
+
+    /// The entity's own ID
+    #[indexed]
+    pub external_id: String,
+
+    /// When the ID was linked (Unix timestamp in milliseconds)
+    pub linked_at: i64,
+}
+
+impl EntityIdentity {
+    /// Build the primary key for an entity's ID
+    pub fn key(entity_name: &str, external_id: &str) -> String {
+        format!("{}:{}", entity_name, external_id)
+    }
+
+    pub fn new(
+        identity_id: impl Into<String>,
+        entity_name: impl Into<String>,
+        external_id: impl Into<String>,
+        linked_at: i64,
+    ) -> Self {
+        let entity_name = entity_name.into();
+        let external_id = external_id.into();
+        Self {
+            id: Self::key(&entity_name, &external_id),
+            identity_id: identity_id.into(),
+            entity_name,
+            external_id,
+            linked_at,
+        }
+    }
+}
@@ -4,25 +4,65 @@
 //! - `CrudOperations`: Basic CRUD operations (create, update, delete)
 //! - `BlockOperations`: Block-specific operations (indent, outdent, move_block, etc.)
 //! - `TaskOperations`: Task-specific operations (set_completion, set_priority, set_due_date)
+//! - `TimeTrackingOperations`: Time tracking on tasks (clock_in, clock_out)
+//! - `AttachmentOperations`: Files attached to entities (attach_file, remove_attachment)
+//! - `DependencyOperations`: Tasks blocking other tasks (add_dependency, remove_dependency)
+//! - `CustomFieldOperations`: User-defined typed fields (define_custom_field, remove_custom_field)
+//! - `OperationProvider`: Executes operations by entity and operation name
+//! - `IdGenerator`: Pluggable ID generation (UUIDv7, ULID, NanoID)
+//! - `HolonError`: Structured errors returned by the operation traits
+//! - `testing::OperationSimulator`: Checks providers against operation simulations
 
+pub mod access;
+pub mod attachment;
 pub mod core;
+pub mod custom_field;
+pub mod dependency;
+pub mod error;
 pub mod fractional_index;
+pub mod id_generator;
+pub mod identity;
 pub mod operation_log;
 pub mod storage;
+pub mod testing;
+pub mod time_tracking;
 pub mod traits;
 pub mod undo;
+pub mod usage_stats;
+pub mod view_state;
 
-pub use operation_log::{OperationLogEntry, OperationStatus};
+pub use access::EntityAccess;
+pub use attachment::{format_size, guess_mime_type, Attachment, LOCAL_ATTACHMENT_SOURCE};
+pub use custom_field::{CustomFieldDefinition, CustomFieldType, CustomFieldValue};
+pub use dependency::TaskDependency;
+pub use error::{HolonError, HolonResult};
+pub use id_generator::{default_id_generator, IdGenerator, IdStrategy, TempIdMap};
+pub use identity::EntityIdentity;
+pub use operation_log::{
+    id_remapped_change, remap_operation_id, IdMappingService, OperationLogEntry, OperationStatus,
+};
+pub use time_tracking::{format_duration, TimeEntry, LOCAL_TIME_ENTRY_SOURCE};
+pub use traits::{
+    AttachmentOperations, BlockDataSourceHelpers, BlockEntity, BlockOperations, CrudOperations,
+    CustomFieldOperations, DataSource, DeletePolicy, DependencyOperations, MaybeSendSync,
+    MoveOperations, OperationLogOperations, OperationProvider, OperationRegistry, RenameOperations,
+    Result, TaskEntity, TaskOperations, TimeTrackingOperations, UndoAction, UnknownOperationError,
+};
+// Typed clients generated by #[operations_trait]
 pub use traits::{
-    BlockDataSourceHelpers, BlockEntity, BlockOperations, CrudOperations, DataSource,
-    MaybeSendSync, MoveOperations, OperationLogOperations, OperationRegistry, RenameOperations,
-    Result, TaskEntity, TaskOperations, UndoAction, UnknownOperationError,
+    AttachmentOperationsClient, BlockOperationsClient, CrudOperationsClient,
+    CustomFieldOperationsClient, DependencyOperationsClient, MoveOperationsClient,
+    RenameOperationsClient, TaskOperationsClient, TimeTrackingOperationsClient,
 };
 pub use undo::UndoStack;
+pub use usage_stats::OperationUsageEntry;
+pub use view_state::ViewStateEntry;
 
 // Re-export macro-generated operation dispatch functions
-#[cfg(not(target_arch = "wasm32"))]
 pub use traits::{
-    __operations_block_operations, __operations_crud_operations, __operations_move_operations,
+    __operations_attachment_operations, __operations_block_operations,
+    __operations_crud_operations, __operations_custom_field_operations,
+    __operations_dependency_operations, __operations_move_operations,
     __operations_rename_operations, __operations_task_operations,
+    __operations_time_tracking_operations,
 };
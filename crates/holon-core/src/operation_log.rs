@@ -2,11 +2,22 @@
 //!
 //! The `OperationLogEntry` entity stores executed operations with their inverses,
 //! enabling persistent undo/redo functionality and future offline sync support.
+//!
+//! `IdMappingService` keeps logged operations valid when an optimistic create
+//! receives its remote ID: operations queued against the temporary ID are
+//! rewritten, and a `Change` is emitted so frontends can swap the ID locally.
+
+use std::collections::HashMap;
+use std::sync::Arc;
 
 use holon_macros::Entity;
 use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
 
-use holon_api::Operation;
+use holon_api::{Change, ChangeOrigin, MapChange, Operation, Value};
+
+use crate::id_generator::TempIdMap;
+use crate::traits::{OperationLogOperations, Result};
 
 /// Status of an operation in the log.
 ///
@@ -21,6 +32,8 @@ pub enum OperationStatus {
     Undone,
     /// Operation was undone before sync completed (future use)
     Cancelled,
+    /// Operation's effects were discarded by restoring an earlier snapshot
+    RolledBack,
 }
 
 impl OperationStatus {
@@ -31,6 +44,7 @@ impl OperationStatus {
             OperationStatus::Synced => "synced",
             OperationStatus::Undone => "undone",
             OperationStatus::Cancelled => "cancelled",
+            OperationStatus::RolledBack => "rolled_back",
         }
     }
 
@@ -41,6 +55,7 @@ impl OperationStatus {
             "synced" => Some(OperationStatus::Synced),
             "undone" => Some(OperationStatus::Undone),
             "cancelled" => Some(OperationStatus::Cancelled),
+            "rolled_back" => Some(OperationStatus::RolledBack),
             _ => None,
         }
     }
@@ -136,13 +151,146 @@ impl OperationLogEntry {
     pub fn can_redo(&self) -> bool {
         matches!(self.get_status(), Some(OperationStatus::Undone))
     }
+
+    /// Replace a temporary entity ID with its remote ID in the operation and its inverse
+    ///
+    /// Returns whether the entry changed.
+    pub fn remap_id(&mut self, temp_id: &str, remote_id: &str) -> bool {
+        let mut changed = false;
+        if let Some(mut operation) = self.get_operation() {
+            if remap_operation_id(&mut operation, temp_id, remote_id) {
+                self.operation = serde_json::to_string(&operation).unwrap_or_default();
+                changed = true;
+            }
+        }
+        if let Some(mut inverse) = self.get_inverse() {
+            if remap_operation_id(&mut inverse, temp_id, remote_id) {
+                self.inverse = Some(serde_json::to_string(&inverse).unwrap_or_default());
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Replace string values for which `lookup` returns a new ID, including nested values
+fn remap_value(value: &mut Value, lookup: &dyn Fn(&str) -> Option<String>) -> bool {
+    match value {
+        Value::String(id) | Value::Reference(id) => match lookup(id) {
+            Some(new_id) => {
+                *id = new_id;
+                true
+            }
+            None => false,
+        },
+        Value::Array(items) => items
+            .iter_mut()
+            .fold(false, |changed, item| remap_value(item, lookup) || changed),
+        Value::Object(fields) => fields.values_mut().fold(false, |changed, field| {
+            remap_value(field, lookup) || changed
+        }),
+        _ => false,
+    }
+}
+
+fn remap_params(
+    params: &mut HashMap<String, Value>,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> bool {
+    params.values_mut().fold(false, |changed, value| {
+        remap_value(value, lookup) || changed
+    })
+}
+
+/// Replace `temp_id` with `remote_id` in an operation's parameters
+///
+/// Returns whether any parameter referenced the temporary ID.
+pub fn remap_operation_id(operation: &mut Operation, temp_id: &str, remote_id: &str) -> bool {
+    remap_params(&mut operation.params, &|id| {
+        (id == temp_id).then(|| remote_id.to_string())
+    })
+}
+
+/// Change announcing that the entity known by `temp_id` now has `remote_id`
+///
+/// Emitted as a `ColumnChange` of the `id` column, keyed by the temporary ID, so
+/// frontends holding rows under the temporary ID can swap it in place.
+pub fn id_remapped_change(temp_id: &str, remote_id: &str) -> MapChange {
+    Change::ColumnChange {
+        id: temp_id.to_string(),
+        columns: HashMap::from([("id".to_string(), Value::String(remote_id.to_string()))]),
+        origin: ChangeOrigin::Remote {
+            operation_id: None,
+            trace_id: None,
+            source: None,
+        },
+    }
+}
+
+/// Rewrites references to temporary IDs once optimistic creates complete
+///
+/// Operations executed while a create is in flight reference its temporary ID and
+/// would fail once the remote system assigns the real one. `complete_create` records
+/// the mapping, rewrites the operation log and notifies subscribers;
+/// `resolve_params` rewrites temporary IDs in operations issued afterwards (e.g.
+/// from UI state that still holds the temporary ID).
+pub struct IdMappingService {
+    temp_ids: Arc<TempIdMap>,
+    operation_log: Option<Arc<dyn OperationLogOperations>>,
+    changes: broadcast::Sender<MapChange>,
+}
+
+impl IdMappingService {
+    pub fn new(temp_ids: Arc<TempIdMap>) -> Self {
+        let (changes, _) = broadcast::channel(256);
+        Self {
+            temp_ids,
+            operation_log: None,
+            changes,
+        }
+    }
+
+    /// Also rewrite logged operations when a create completes
+    pub fn with_operation_log(mut self, operation_log: Arc<dyn OperationLogOperations>) -> Self {
+        self.operation_log = Some(operation_log);
+        self
+    }
+
+    /// The temporary ID mapping
+    pub fn temp_ids(&self) -> Arc<TempIdMap> {
+        self.temp_ids.clone()
+    }
+
+    /// Subscribe to ID changes (one `id_remapped_change` per completed create)
+    pub fn subscribe(&self) -> broadcast::Receiver<MapChange> {
+        self.changes.subscribe()
+    }
+
+    /// Record that the create of `temp_id` completed with `remote_id`
+    ///
+    /// Returns the number of rewritten operation log entries.
+    pub async fn complete_create(&self, temp_id: &str, remote_id: &str) -> Result<usize> {
+        self.temp_ids.register(temp_id, remote_id);
+        let rewritten = match &self.operation_log {
+            Some(operation_log) => operation_log.remap_id(temp_id, remote_id).await?,
+            None => 0,
+        };
+        // Sending only fails without subscribers, in which case nobody holds the temporary ID
+        let _ = self.changes.send(id_remapped_change(temp_id, remote_id));
+        Ok(rewritten)
+    }
+
+    /// Replace known temporary IDs in operation parameters with their remote IDs
+    ///
+    /// Returns whether any parameter was rewritten.
+    pub fn resolve_params(&self, params: &mut HashMap<String, Value>) -> bool {
+        remap_params(params, &|id| self.temp_ids.remote_id(id))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use holon_api::Value;
-    use std::collections::HashMap;
 
     #[test]
     fn test_operation_status_roundtrip() {
@@ -151,6 +299,7 @@ mod tests {
             OperationStatus::Synced,
             OperationStatus::Undone,
             OperationStatus::Cancelled,
+            OperationStatus::RolledBack,
         ] {
             let s = status.as_str();
             let parsed = OperationStatus::from_str(s).unwrap();
@@ -196,4 +345,82 @@ mod tests {
         let inv = entry.get_inverse().unwrap();
         assert_eq!(inv.display_name, "Mark as incomplete");
     }
+
+    #[test]
+    fn test_remap_id_rewrites_operation_and_inverse() {
+        let operation = Operation::new(
+            "todoist_tasks",
+            "move_block",
+            "Move task",
+            HashMap::from([
+                ("id".to_string(), Value::String("tmp-1".to_string())),
+                (
+                    "after".to_string(),
+                    Value::Array(vec![Value::String("tmp-1".to_string())]),
+                ),
+                ("parent_id".to_string(), Value::String("p1".to_string())),
+            ]),
+        );
+        let inverse = Operation::new(
+            "todoist_tasks",
+            "delete",
+            "Delete task",
+            HashMap::from([("id".to_string(), Value::String("tmp-1".to_string()))]),
+        );
+        let mut entry = OperationLogEntry::new(operation, Some(inverse));
+
+        assert!(entry.remap_id("tmp-1", "6Jf8VQXxpwv56VQ7"));
+        let op = entry.get_operation().unwrap();
+        assert_eq!(
+            op.params.get("id"),
+            Some(&Value::String("6Jf8VQXxpwv56VQ7".to_string()))
+        );
+        assert_eq!(
+            op.params.get("after"),
+            Some(&Value::Array(vec![Value::String(
+                "6Jf8VQXxpwv56VQ7".to_string()
+            )]))
+        );
+        assert_eq!(
+            op.params.get("parent_id"),
+            Some(&Value::String("p1".to_string()))
+        );
+        assert_eq!(
+            entry.get_inverse().unwrap().params.get("id"),
+            Some(&Value::String("6Jf8VQXxpwv56VQ7".to_string()))
+        );
+        assert!(!entry.remap_id("tmp-1", "6Jf8VQXxpwv56VQ7"));
+    }
+
+    #[test]
+    fn test_id_mapping_service_resolves_params() {
+        let service = IdMappingService::new(Arc::new(TempIdMap::default()));
+        service.temp_ids().register("tmp-1", "6Jf8VQXxpwv56VQ7");
+
+        let mut params = HashMap::from([
+            ("id".to_string(), Value::String("tmp-1".to_string())),
+            ("content".to_string(), Value::String("Buy milk".to_string())),
+        ]);
+        assert!(service.resolve_params(&mut params));
+        assert_eq!(
+            params.get("id"),
+            Some(&Value::String("6Jf8VQXxpwv56VQ7".to_string()))
+        );
+        assert_eq!(
+            params.get("content"),
+            Some(&Value::String("Buy milk".to_string()))
+        );
+        assert!(!service.resolve_params(&mut params));
+
+        match id_remapped_change("tmp-1", "6Jf8VQXxpwv56VQ7") {
+            Change::ColumnChange { id, columns, .. } => {
+                assert_eq!(id, "tmp-1");
+                assert_eq!(
+                    columns.get("id"),
+                    Some(&Value::String("6Jf8VQXxpwv56VQ7".to_string()))
+                );
+            }
+            other => panic!("Expected a column change, got {:?}", other),
+        }
+    }
 }
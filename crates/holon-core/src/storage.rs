@@ -1,3 +1,5 @@
+pub mod snapshot_differ;
 pub mod types;
 
+pub use snapshot_differ::SnapshotDiffer;
 pub use types::StorageEntity;
@@ -0,0 +1,192 @@
+//! Turn successive full snapshots into change streams
+//!
+//! Some providers can only return every entity on each poll. `SnapshotDiffer`
+//! remembers the previous snapshot and reports only what changed, so such
+//! providers feed the same `MapChange` pipeline as streaming ones:
+//!
+//! - rows with a new key become `Change::Created`
+//! - rows whose fields changed become `Change::ColumnChange` with just those fields
+//! - rows missing from the new snapshot become `Change::Deleted`
+
+use std::collections::{HashMap, HashSet};
+
+use holon_api::{changed_columns, ChangeOrigin, MapChange, Value};
+
+use super::types::StorageEntity;
+
+/// Diffs full entity snapshots keyed by a primary key column
+#[derive(Debug, Clone)]
+pub struct SnapshotDiffer {
+    key_column: String,
+    previous: HashMap<String, StorageEntity>,
+}
+
+impl SnapshotDiffer {
+    pub fn new(key_column: impl Into<String>) -> Self {
+        Self {
+            key_column: key_column.into(),
+            previous: HashMap::new(),
+        }
+    }
+
+    /// Start from a known snapshot (e.g. rows already in the cache) without emitting changes
+    pub fn with_baseline(mut self, snapshot: Vec<StorageEntity>) -> Self {
+        self.previous = self.index(snapshot);
+        self
+    }
+
+    /// Compare `snapshot` with the previous one and remember it
+    ///
+    /// Creates and updates follow the snapshot's row order; deletes come last,
+    /// sorted by key. Rows without a usable key (string or integer) are ignored.
+    pub fn diff(&mut self, snapshot: Vec<StorageEntity>, origin: &ChangeOrigin) -> Vec<MapChange> {
+        let mut changes = Vec::new();
+        let mut seen = HashSet::new();
+        let mut current = HashMap::with_capacity(snapshot.len());
+
+        for row in snapshot {
+            let Some(id) = self.key_of(&row) else {
+                continue;
+            };
+            if !seen.insert(id.clone()) {
+                // Keep the first row of a duplicated key
+                continue;
+            }
+            match self.previous.get(&id) {
+                None => changes.push(MapChange::Created {
+                    data: row.clone(),
+                    origin: origin.clone(),
+                }),
+                Some(before) => {
+                    let columns = changed_columns(before, &row);
+                    if !columns.is_empty() {
+                        changes.push(MapChange::ColumnChange {
+                            id: id.clone(),
+                            columns,
+                            origin: origin.clone(),
+                        });
+                    }
+                }
+            }
+            current.insert(id, row);
+        }
+
+        let mut deleted: Vec<&String> = self
+            .previous
+            .keys()
+            .filter(|id| !current.contains_key(*id))
+            .collect();
+        deleted.sort();
+        changes.extend(deleted.into_iter().map(|id| MapChange::Deleted {
+            id: id.clone(),
+            origin: origin.clone(),
+        }));
+
+        self.previous = current;
+        changes
+    }
+
+    /// Forget the previous snapshot, so the next one is reported as all creates
+    pub fn reset(&mut self) {
+        self.previous.clear();
+    }
+
+    /// Number of rows in the previous snapshot
+    pub fn len(&self) -> usize {
+        self.previous.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.previous.is_empty()
+    }
+
+    fn key_of(&self, row: &StorageEntity) -> Option<String> {
+        match row.get(&self.key_column)? {
+            Value::String(id) => Some(id.clone()),
+            Value::Integer(id) => Some(id.to_string()),
+            _ => None,
+        }
+    }
+
+    fn index(&self, snapshot: Vec<StorageEntity>) -> HashMap<String, StorageEntity> {
+        snapshot
+            .into_iter()
+            .filter_map(|row| Some((self.key_of(&row)?, row)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: &str, title: &str, done: bool) -> StorageEntity {
+        HashMap::from([
+            ("id".to_string(), Value::String(id.to_string())),
+            ("title".to_string(), Value::String(title.to_string())),
+            ("done".to_string(), Value::Boolean(done)),
+        ])
+    }
+
+    #[test]
+    fn test_diff_successive_snapshots() {
+        let origin = ChangeOrigin::Remote {
+            operation_id: None,
+            trace_id: None,
+            source: None,
+        };
+        let mut differ = SnapshotDiffer::new("id");
+
+        let first = differ.diff(vec![row("a", "A", false), row("b", "B", false)], &origin);
+        assert_eq!(first.len(), 2);
+        assert!(first
+            .iter()
+            .all(|change| matches!(change, MapChange::Created { .. })));
+
+        // Unchanged snapshot: nothing to report
+        assert!(differ
+            .diff(vec![row("b", "B", false), row("a", "A", false)], &origin)
+            .is_empty());
+
+        let changes = differ.diff(vec![row("b", "B", true), row("c", "C", false)], &origin);
+        assert_eq!(changes.len(), 3);
+        match &changes[0] {
+            MapChange::ColumnChange { id, columns, .. } => {
+                assert_eq!(id, "b");
+                assert_eq!(
+                    columns,
+                    &HashMap::from([("done".to_string(), Value::Boolean(true))])
+                );
+            }
+            other => panic!("Expected column change, got {:?}", other),
+        }
+        assert!(
+            matches!(&changes[1], MapChange::Created { data, .. } if data["id"] == Value::String("c".to_string()))
+        );
+        assert!(matches!(&changes[2], MapChange::Deleted { id, .. } if id == "a"));
+        assert_eq!(differ.len(), 2);
+    }
+
+    #[test]
+    fn test_baseline_and_integer_keys() {
+        let origin = ChangeOrigin::Remote {
+            operation_id: None,
+            trace_id: None,
+            source: None,
+        };
+        let with_int_id = |id: i64, title: &str| {
+            HashMap::from([
+                ("id".to_string(), Value::Integer(id)),
+                ("title".to_string(), Value::String(title.to_string())),
+            ])
+        };
+        let mut differ = SnapshotDiffer::new("id").with_baseline(vec![with_int_id(1, "One")]);
+
+        let changes = differ.diff(vec![with_int_id(1, "One")], &origin);
+        assert!(changes.is_empty());
+
+        differ.reset();
+        let changes = differ.diff(vec![with_int_id(1, "One")], &origin);
+        assert!(matches!(&changes[..], [MapChange::Created { .. }]));
+    }
+}
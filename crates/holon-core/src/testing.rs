@@ -0,0 +1,518 @@
+//! Simulation of operations for property-based tests
+//!
+//! Operations declared with `#[simulate(path)]` carry a pure model of their effect
+//! in `OperationDescriptor::simulation`. `OperationSimulator` keeps the entities
+//! these models expect next to a real provider: each step executes an operation on
+//! the provider, applies its model to the expected entities and compares them
+//! with the provider's actual entities, read through a `StateSource`.
+//!
+//! Steps are plain data, so any generator (e.g. a proptest strategy over
+//! `OperationSimulator::simulated_operations`) can drive the simulator.
+
+use std::collections::{BTreeSet, HashMap};
+
+use async_trait::async_trait;
+use holon_api::{EntityStates, OperationDescriptor, Value};
+
+use crate::traits::{MaybeSendSync, OperationProvider, Result};
+
+/// Model of `set_field`: the entity's `field` becomes `value`
+pub fn simulate_set_field(
+    entities: &EntityStates,
+    params: &HashMap<String, Value>,
+) -> std::result::Result<EntityStates, String> {
+    let field = string_param(params, "field")?;
+    let value = params
+        .get("value")
+        .cloned()
+        .ok_or("Missing 'value' parameter")?;
+    set_entity_field(entities, params, field, value)
+}
+
+/// Model of `delete`: the entity is gone
+pub fn simulate_delete(
+    entities: &EntityStates,
+    params: &HashMap<String, Value>,
+) -> std::result::Result<EntityStates, String> {
+    let id = string_param(params, "id")?;
+    let mut entities = entities.clone();
+    entities
+        .remove(id)
+        .ok_or_else(|| format!("Entity not found: {}", id))?;
+    Ok(entities)
+}
+
+/// Model of `set_completion`: the entity's `completed` field is set
+pub fn simulate_set_completion(
+    entities: &EntityStates,
+    params: &HashMap<String, Value>,
+) -> std::result::Result<EntityStates, String> {
+    let completed = params
+        .get("completed")
+        .cloned()
+        .ok_or("Missing 'completed' parameter")?;
+    set_entity_field(entities, params, "completed", completed)
+}
+
+/// Model of `set_priority`: the entity's `priority` field is set
+pub fn simulate_set_priority(
+    entities: &EntityStates,
+    params: &HashMap<String, Value>,
+) -> std::result::Result<EntityStates, String> {
+    let priority = params
+        .get("priority")
+        .cloned()
+        .ok_or("Missing 'priority' parameter")?;
+    set_entity_field(entities, params, "priority", priority)
+}
+
+fn set_entity_field(
+    entities: &EntityStates,
+    params: &HashMap<String, Value>,
+    field: &str,
+    value: Value,
+) -> std::result::Result<EntityStates, String> {
+    let id = string_param(params, "id")?;
+    let mut entities = entities.clone();
+    entities
+        .get_mut(id)
+        .ok_or_else(|| format!("Entity not found: {}", id))?
+        .insert(field.to_string(), value);
+    Ok(entities)
+}
+
+fn string_param<'a>(
+    params: &'a HashMap<String, Value>,
+    name: &str,
+) -> std::result::Result<&'a str, String> {
+    params
+        .get(name)
+        .and_then(|v| v.as_string())
+        .ok_or_else(|| format!("Missing '{}' parameter", name))
+}
+
+/// Reads the entities a provider actually holds
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait StateSource: MaybeSendSync {
+    async fn entities(&self, entity_name: &str) -> Result<EntityStates>;
+}
+
+/// One operation of a simulated sequence
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationStep {
+    pub entity_name: String,
+    pub op_name: String,
+    pub params: HashMap<String, Value>,
+}
+
+impl SimulationStep {
+    pub fn new(entity_name: &str, op_name: &str, params: HashMap<String, Value>) -> Self {
+        Self {
+            entity_name: entity_name.to_string(),
+            op_name: op_name.to_string(),
+            params,
+        }
+    }
+}
+
+/// An entity or field whose actual value differs from the expected one
+///
+/// `field` is None when the whole entity is missing or unexpected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateDifference {
+    pub entity_name: String,
+    pub id: String,
+    pub field: Option<String>,
+    pub expected: Option<Value>,
+    pub actual: Option<Value>,
+}
+
+impl std::fmt::Display for StateDifference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.field {
+            Some(field) => write!(
+                f,
+                "{} {}.{}: expected {:?}, got {:?}",
+                self.entity_name, self.id, field, self.expected, self.actual
+            ),
+            None if self.actual.is_none() => {
+                write!(f, "{} {}: expected to exist", self.entity_name, self.id)
+            }
+            None => write!(f, "{} {}: not expected to exist", self.entity_name, self.id),
+        }
+    }
+}
+
+/// Why a simulated sequence failed at `step`
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum SimulationError {
+    #[error("Step {step}: unknown operation {entity_name}.{op_name}")]
+    UnknownOperation {
+        step: usize,
+        entity_name: String,
+        op_name: String,
+    },
+
+    #[error("Step {step}: {entity_name}.{op_name} has no simulation")]
+    NotSimulated {
+        step: usize,
+        entity_name: String,
+        op_name: String,
+    },
+
+    /// The model accepted the operation but the provider failed it
+    #[error("Step {step}: provider failed: {error}")]
+    ProviderFailed { step: usize, error: String },
+
+    /// The model rejected the operation but the provider executed it
+    #[error("Step {step}: provider accepted an operation the model rejects: {reason}")]
+    UnexpectedSuccess { step: usize, reason: String },
+
+    #[error("Step {step}: reading actual state failed: {error}")]
+    StateUnavailable { step: usize, error: String },
+
+    #[error("Step {step}: state differs from the model: {}", format_differences(.differences))]
+    Mismatch {
+        step: usize,
+        differences: Vec<StateDifference>,
+    },
+}
+
+fn format_differences(differences: &[StateDifference]) -> String {
+    differences
+        .iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Compare expected and actual entities
+///
+/// Only fields of the expected entities are compared, so providers may hold
+/// fields the model doesn't track (e.g. timestamps).
+pub fn diff_states(
+    entity_name: &str,
+    expected: &EntityStates,
+    actual: &EntityStates,
+) -> Vec<StateDifference> {
+    let ids: BTreeSet<&String> = expected.keys().chain(actual.keys()).collect();
+    let mut differences = Vec::new();
+    for id in ids {
+        let difference = |field: Option<&str>, expected: Option<&Value>, actual: Option<&Value>| {
+            StateDifference {
+                entity_name: entity_name.to_string(),
+                id: id.clone(),
+                field: field.map(str::to_string),
+                expected: expected.cloned(),
+                actual: actual.cloned(),
+            }
+        };
+        match (expected.get(id), actual.get(id)) {
+            (Some(expected_fields), Some(actual_fields)) => {
+                let fields: BTreeSet<&String> = expected_fields.keys().collect();
+                for field in fields {
+                    let (expected, actual) = (expected_fields.get(field), actual_fields.get(field));
+                    if expected != actual {
+                        differences.push(difference(Some(field), expected, actual));
+                    }
+                }
+            }
+            (Some(_), None) => {
+                differences.push(difference(None, Some(&Value::Null), None));
+            }
+            (None, Some(_)) => differences.push(difference(None, None, Some(&Value::Null))),
+            (None, None) => {}
+        }
+    }
+    differences
+}
+
+/// Runs operations against a provider and checks them against their simulations
+pub struct OperationSimulator {
+    descriptors: HashMap<(String, String), OperationDescriptor>,
+    expected: HashMap<String, EntityStates>,
+    steps: usize,
+}
+
+impl OperationSimulator {
+    /// Simulator for `descriptors`, starting from the expected entities `initial`
+    /// (by entity name)
+    pub fn new(
+        descriptors: Vec<OperationDescriptor>,
+        initial: HashMap<String, EntityStates>,
+    ) -> Self {
+        Self {
+            descriptors: descriptors
+                .into_iter()
+                .map(|op| ((op.entity_name.clone(), op.name.clone()), op))
+                .collect(),
+            expected: initial,
+            steps: 0,
+        }
+    }
+
+    /// Simulator for the operations of `provider`
+    pub fn for_provider(
+        provider: &dyn OperationProvider,
+        initial: HashMap<String, EntityStates>,
+    ) -> Self {
+        Self::new(provider.operations(), initial)
+    }
+
+    /// Operations that can be simulated, e.g. to generate steps from
+    pub fn simulated_operations(&self) -> Vec<&OperationDescriptor> {
+        let mut operations: Vec<_> = self
+            .descriptors
+            .values()
+            .filter(|op| op.simulation.is_some())
+            .collect();
+        operations.sort_by(|a, b| (&a.entity_name, &a.name).cmp(&(&b.entity_name, &b.name)));
+        operations
+    }
+
+    /// Entities the model expects, by entity name
+    pub fn expected(&self, entity_name: &str) -> Option<&EntityStates> {
+        self.expected.get(entity_name)
+    }
+
+    /// Execute `step` on `provider` and compare the outcome with the model
+    ///
+    /// A step the model rejects must fail on the provider too; the expected
+    /// state is then unchanged.
+    pub async fn run_step(
+        &mut self,
+        provider: &dyn OperationProvider,
+        source: &dyn StateSource,
+        step: &SimulationStep,
+    ) -> std::result::Result<(), SimulationError> {
+        let index = self.steps;
+        self.steps += 1;
+        let descriptor = self
+            .descriptors
+            .get(&(step.entity_name.clone(), step.op_name.clone()))
+            .ok_or_else(|| SimulationError::UnknownOperation {
+                step: index,
+                entity_name: step.entity_name.clone(),
+                op_name: step.op_name.clone(),
+            })?;
+        let before = self
+            .expected
+            .get(&step.entity_name)
+            .cloned()
+            .unwrap_or_default();
+        let modelled = descriptor.simulate(&before, &step.params).ok_or_else(|| {
+            SimulationError::NotSimulated {
+                step: index,
+                entity_name: step.entity_name.clone(),
+                op_name: step.op_name.clone(),
+            }
+        })?;
+
+        let executed = provider
+            .execute_operation(&step.entity_name, &step.op_name, step.params.clone())
+            .await;
+        match (modelled, executed) {
+            (Ok(after), Ok(_)) => {
+                self.expected.insert(step.entity_name.clone(), after);
+            }
+            (Ok(_), Err(e)) => {
+                return Err(SimulationError::ProviderFailed {
+                    step: index,
+                    error: e.to_string(),
+                });
+            }
+            (Err(reason), Ok(_)) => {
+                return Err(SimulationError::UnexpectedSuccess {
+                    step: index,
+                    reason,
+                });
+            }
+            (Err(_), Err(_)) => {}
+        }
+
+        let actual = source.entities(&step.entity_name).await.map_err(|e| {
+            SimulationError::StateUnavailable {
+                step: index,
+                error: e.to_string(),
+            }
+        })?;
+        let expected = self
+            .expected
+            .get(&step.entity_name)
+            .cloned()
+            .unwrap_or_default();
+        let differences = diff_states(&step.entity_name, &expected, &actual);
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(SimulationError::Mismatch {
+                step: index,
+                differences,
+            })
+        }
+    }
+
+    /// Run `steps` in order, stopping at the first divergence
+    pub async fn run_sequence(
+        &mut self,
+        provider: &dyn OperationProvider,
+        source: &dyn StateSource,
+        steps: &[SimulationStep],
+    ) -> std::result::Result<(), SimulationError> {
+        for step in steps {
+            self.run_step(provider, source, step).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{__operations_crud_operations, UndoAction};
+    use std::sync::Mutex;
+
+    /// Provider keeping entities in memory; `buggy` ignores `set_field` on `title`
+    struct MemoryProvider {
+        entities: Mutex<EntityStates>,
+        buggy: bool,
+    }
+
+    impl MemoryProvider {
+        fn new(buggy: bool) -> Self {
+            Self {
+                entities: Mutex::new(initial_entities()),
+                buggy,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl OperationProvider for MemoryProvider {
+        fn operations(&self) -> Vec<OperationDescriptor> {
+            vec![
+                __operations_crud_operations::SET_FIELD_OP("notes", "note", "notes", "id"),
+                __operations_crud_operations::DELETE_OP("notes", "note", "notes", "id"),
+            ]
+        }
+
+        async fn execute_operation(
+            &self,
+            _entity_name: &str,
+            op_name: &str,
+            params: HashMap<String, Value>,
+        ) -> Result<UndoAction> {
+            let mut entities = self.entities.lock().unwrap();
+            let id = params.get("id").and_then(|v| v.as_string()).unwrap_or("");
+            let entity = entities.get_mut(id).ok_or("not found")?;
+            match op_name {
+                "set_field" => {
+                    let field = params.get("field").and_then(|v| v.as_string()).unwrap();
+                    if !(self.buggy && field == "title") {
+                        entity.insert(field.to_string(), params["value"].clone());
+                    }
+                }
+                "delete" => {
+                    entities.remove(id);
+                }
+                _ => return Err("unknown operation".into()),
+            }
+            Ok(UndoAction::Irreversible)
+        }
+    }
+
+    #[async_trait]
+    impl StateSource for MemoryProvider {
+        async fn entities(&self, _entity_name: &str) -> Result<EntityStates> {
+            Ok(self.entities.lock().unwrap().clone())
+        }
+    }
+
+    fn initial_entities() -> EntityStates {
+        HashMap::from([(
+            "n-1".to_string(),
+            HashMap::from([("title".to_string(), Value::String("First".to_string()))]),
+        )])
+    }
+
+    fn steps() -> Vec<SimulationStep> {
+        vec![
+            SimulationStep::new(
+                "notes",
+                "set_field",
+                HashMap::from([
+                    ("id".to_string(), Value::String("n-1".to_string())),
+                    ("field".to_string(), Value::String("title".to_string())),
+                    ("value".to_string(), Value::String("Renamed".to_string())),
+                ]),
+            ),
+            SimulationStep::new(
+                "notes",
+                "delete",
+                HashMap::from([("id".to_string(), Value::String("n-1".to_string()))]),
+            ),
+            // Rejected by both the model and the provider
+            SimulationStep::new(
+                "notes",
+                "delete",
+                HashMap::from([("id".to_string(), Value::String("n-1".to_string()))]),
+            ),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_sequence_matches_model() {
+        let provider = MemoryProvider::new(false);
+        let mut simulator = OperationSimulator::for_provider(
+            &provider,
+            HashMap::from([("notes".to_string(), initial_entities())]),
+        );
+        assert_eq!(simulator.simulated_operations().len(), 2);
+
+        simulator
+            .run_sequence(&provider, &provider, &steps())
+            .await
+            .unwrap();
+        assert!(simulator.expected("notes").unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_divergence_is_reported() {
+        let provider = MemoryProvider::new(true);
+        let mut simulator = OperationSimulator::for_provider(
+            &provider,
+            HashMap::from([("notes".to_string(), initial_entities())]),
+        );
+
+        let error = simulator
+            .run_sequence(&provider, &provider, &steps())
+            .await
+            .unwrap_err();
+        match error {
+            SimulationError::Mismatch { step, differences } => {
+                assert_eq!(step, 0);
+                assert_eq!(differences.len(), 1);
+                assert_eq!(differences[0].field.as_deref(), Some("title"));
+                assert_eq!(
+                    differences[0].actual,
+                    Some(Value::String("First".to_string()))
+                );
+            }
+            other => panic!("Expected a mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_models() {
+        let entities = initial_entities();
+        let params = HashMap::from([
+            ("id".to_string(), Value::String("n-1".to_string())),
+            ("completed".to_string(), Value::Boolean(true)),
+        ]);
+        let after = simulate_set_completion(&entities, &params).unwrap();
+        assert_eq!(after["n-1"]["completed"], Value::Boolean(true));
+        assert!(simulate_set_completion(&HashMap::new(), &params).is_err());
+        assert!(simulate_delete(&after, &params).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+//! Time tracking entity.
+//!
+//! A `TimeEntry` is one clocked interval on a task. Entries are created by
+//! `clock_in` and closed by `clock_out` (see `TimeTrackingOperations`), or imported
+//! from external sources such as org-mode `:LOGBOOK:` CLOCK lines. They live in the
+//! `time_entries` table so reports can be written as plain PRQL queries.
+
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+
+/// Source of entries created via `clock_in`/`clock_out` in the app
+pub const LOCAL_TIME_ENTRY_SOURCE: &str = "local";
+
+/// A clocked interval on a task.
+///
+/// Table name: `time_entries`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Entity)]
+#[entity(name = "time_entries", short_name = "time_entry")]
+pub struct TimeEntry {
+    #[primary_key]
+    pub id: String,
+
+    /// Entity the task belongs to (e.g. `todoist_tasks`, `org_headlines`)
+    #[indexed]
+    pub entity_name: String,
+
+    /// ID of the clocked task
+    #[indexed]
+    pub task_id: String,
+
+    /// Clock-in time (Unix timestamp in milliseconds)
+    #[indexed]
+    pub started_at: i64,
+
+    /// Clock-out time (Unix timestamp in milliseconds); None while running
+    pub ended_at: Option<i64>,
+
+    /// `ended_at - started_at`, stored for cheap aggregation in reports
+    pub duration_ms: Option<i64>,
+
+    /// Where the entry came from: `local` or e.g. `org:<file id>` for imported entries
+    #[indexed]
+    pub source: String,
+}
+
+impl TimeEntry {
+    /// Start a new running entry
+    pub fn start(
+        entity_name: impl Into<String>,
+        task_id: impl Into<String>,
+        started_at: i64,
+        source: impl Into<String>,
+    ) -> Self {
+        let entity_name = entity_name.into();
+        let task_id = task_id.into();
+        Self {
+            id: format!("{}:{}:{}", entity_name, task_id, started_at),
+            entity_name,
+            task_id,
+            started_at,
+            ended_at: None,
+            duration_ms: None,
+            source: source.into(),
+        }
+    }
+
+    /// Close the entry at `ended_at` (clamped so durations are never negative)
+    pub fn stop(&mut self, ended_at: i64) {
+        let ended_at = ended_at.max(self.started_at);
+        self.ended_at = Some(ended_at);
+        self.duration_ms = Some(ended_at - self.started_at);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.ended_at.is_none()
+    }
+
+    /// Elapsed time at `now` (the final duration once stopped)
+    pub fn elapsed_ms(&self, now: i64) -> i64 {
+        self.duration_ms
+            .unwrap_or_else(|| (now - self.started_at).max(0))
+    }
+}
+
+/// Format a duration as `H:MM` (the format used by org-mode clock reports)
+pub fn format_duration(duration_ms: i64) -> String {
+    let minutes = duration_ms.max(0) / 60_000;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_stop_and_duration() {
+        let mut entry = TimeEntry::start("todoist_tasks", "t1", 1_000, LOCAL_TIME_ENTRY_SOURCE);
+        assert!(entry.is_running());
+        assert_eq!(entry.elapsed_ms(61_000), 60_000);
+
+        entry.stop(500);
+        assert_eq!(entry.duration_ms, Some(0));
+        entry.stop(5_401_000);
+        assert_eq!(entry.duration_ms, Some(5_400_000));
+        assert_eq!(format_duration(entry.elapsed_ms(0)), "1:30");
+    }
+}
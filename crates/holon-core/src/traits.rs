@@ -8,9 +8,12 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
 
+use crate::error::{HolonError, HolonResult};
 use crate::fractional_index::{gen_key_between, gen_n_keys, MAX_SORT_KEY_LENGTH};
-use holon_api::{Operation, OperationDescriptor, Value};
+use crate::id_generator::{default_id_generator, IdGenerator};
+use holon_api::{Operation, OperationDescriptor, StorageEntity, Value};
 
 // Define Result type using Send + Sync for error
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
@@ -25,20 +28,81 @@ pub enum UndoAction {
     Undo(Operation),
     /// The operation cannot be undone (e.g., complex operations like split_block).
     Irreversible,
+    /// The operation is undone by several inverses, applied in reverse order.
+    ///
+    /// Operations made of several steps push each step's inverse as the step runs,
+    /// so undoing reverts the last step first.
+    Composite(Vec<UndoAction>),
 }
 
 impl UndoAction {
     /// Convert to Option<Operation> for backward compatibility
+    ///
+    /// Composites are only converted if they consist of a single operation.
     pub fn into_option(self) -> Option<Operation> {
         match self {
             UndoAction::Undo(op) => Some(op),
             UndoAction::Irreversible => None,
+            composite @ UndoAction::Composite(_) => {
+                if !composite.is_reversible() {
+                    return None;
+                }
+                let mut ops = composite.into_operations();
+                if ops.len() == 1 {
+                    ops.pop()
+                } else {
+                    None
+                }
+            }
         }
     }
 
     /// Check if this action is reversible
+    ///
+    /// A composite is reversible if it is non-empty and all of its parts are.
     pub fn is_reversible(&self) -> bool {
-        matches!(self, UndoAction::Undo(_))
+        match self {
+            UndoAction::Undo(_) => true,
+            UndoAction::Irreversible => false,
+            UndoAction::Composite(actions) => {
+                !actions.is_empty() && actions.iter().all(UndoAction::is_reversible)
+            }
+        }
+    }
+
+    /// Inverse operations in the order they must be executed
+    ///
+    /// Irreversible parts of a composite are skipped; check `is_reversible` first.
+    pub fn into_operations(self) -> Vec<Operation> {
+        match self {
+            UndoAction::Undo(op) => vec![op],
+            UndoAction::Irreversible => Vec::new(),
+            UndoAction::Composite(actions) => actions
+                .into_iter()
+                .rev()
+                .flat_map(UndoAction::into_operations)
+                .collect(),
+        }
+    }
+
+    /// Set the entity name of all inverse operations
+    ///
+    /// Operation implementations build inverses without knowing the entity name
+    /// they were dispatched under; providers fill it in with this.
+    pub fn with_entity_name(self, entity_name: &str) -> Self {
+        match self {
+            UndoAction::Undo(mut op) => {
+                op.entity_name = entity_name.to_string();
+                UndoAction::Undo(op)
+            }
+            UndoAction::Irreversible => UndoAction::Irreversible,
+            UndoAction::Composite(actions) => UndoAction::Composite(
+                actions
+                    .into_iter()
+                    .map(|action| action.with_entity_name(entity_name))
+                    .collect(),
+            ),
+        }
     }
 }
 
@@ -48,6 +112,12 @@ impl From<Operation> for UndoAction {
     }
 }
 
+impl From<Vec<UndoAction>> for UndoAction {
+    fn from(actions: Vec<UndoAction>) -> Self {
+        UndoAction::Composite(actions)
+    }
+}
+
 impl From<Option<Operation>> for UndoAction {
     fn from(opt: Option<Operation>) -> Self {
         match opt {
@@ -142,13 +212,68 @@ where
 {
     /// Set single field (returns inverse operation for undo)
     /// Note: affected_fields is determined dynamically based on the field parameter
-    async fn set_field(&self, id: &str, field: &str, value: Value) -> Result<UndoAction>;
+    #[holon_macros::simulate(crate::testing::simulate_set_field)]
+    async fn set_field(&self, id: &str, field: &str, value: Value) -> HolonResult<UndoAction>;
 
     /// Create new entity (returns new ID and inverse operation for undo)
-    async fn create(&self, fields: HashMap<String, Value>) -> Result<(String, UndoAction)>;
+    async fn create(&self, fields: HashMap<String, Value>) -> HolonResult<(String, UndoAction)>;
 
     /// Delete entity (returns inverse operation for undo)
-    async fn delete(&self, id: &str) -> Result<UndoAction>;
+    #[holon_macros::simulate(crate::testing::simulate_delete)]
+    async fn delete(&self, id: &str) -> HolonResult<UndoAction>;
+
+    /// Move entity to the trash (soft delete, returns inverse operation for undo)
+    ///
+    /// Trashed entities keep their data but are hidden from queries until restored
+    /// or purged. Implemented generically by caches that manage a `deleted_at` column.
+    async fn trash(&self, id: &str) -> HolonResult<UndoAction> {
+        Err(HolonError::precondition(format!(
+            "Cannot trash {}: soft delete is not supported",
+            id
+        )))
+    }
+
+    /// Restore entity from the trash (returns inverse operation for undo)
+    async fn restore(&self, id: &str) -> HolonResult<UndoAction> {
+        Err(HolonError::precondition(format!(
+            "Cannot restore {}: soft delete is not supported",
+            id
+        )))
+    }
+
+    /// Archive entity (returns inverse operation for undo)
+    ///
+    /// Archived entities keep their data but are hidden from queries unless they ask
+    /// for them with `include_archived`. Caches manage an `archived_at` column and
+    /// forward to their source, so sources with an archive of their own (e.g. Todoist
+    /// projects) archive the entity there too; other sources keep this default.
+    async fn archive(&self, id: &str) -> HolonResult<UndoAction> {
+        let _ = id;
+        Ok(UndoAction::Irreversible)
+    }
+
+    /// Unarchive entity (returns inverse operation for undo)
+    async fn unarchive(&self, id: &str) -> HolonResult<UndoAction> {
+        let _ = id;
+        Ok(UndoAction::Irreversible)
+    }
+
+    /// Set a user-defined field of an entity (returns inverse operation for undo)
+    ///
+    /// The field must be defined for the entity type (see `CustomFieldOperations`);
+    /// `Value::Null` clears it. Implemented generically by caches, which keep the
+    /// values in the `custom_field_values` table.
+    async fn set_custom_field(
+        &self,
+        id: &str,
+        field: &str,
+        value: Value,
+    ) -> HolonResult<UndoAction> {
+        Err(HolonError::precondition(format!(
+            "Cannot set {} of {} to {:?}: custom fields are not supported",
+            field, id, value
+        )))
+    }
 
     /// Get operations metadata (automatically delegates to entity type)
     fn operations(&self) -> Vec<OperationDescriptor>
@@ -163,7 +288,7 @@ where
 ///
 /// Entity types implement this trait to declare which operations they support.
 /// The implementation aggregates operations from all applicable traits:
-/// - `CrudOperations` operations (set_field, create, delete)
+/// - `CrudOperations` operations (set_field, create, delete, trash, restore, archive, unarchive)
 /// - `BlockOperations` operations (if entity implements `BlockEntity`)
 /// - `TaskOperations` operations (if entity implements `TaskEntity`)
 pub trait OperationRegistry: MaybeSendSync {
@@ -181,6 +306,121 @@ pub trait OperationRegistry: MaybeSendSync {
     }
 }
 
+/// Type-independent operation provider trait
+///
+/// Supports both local (cache-based) and external (API-based) providers.
+/// Operations are self-describing via OperationDescriptor metadata.
+///
+/// # Design
+/// - **OperationProvider = QueryableCache + dispatch layer**: Routes `execute_operation` to
+///   CRUD operations (create/set_field/delete) or custom operations
+/// - **Composite dispatcher pattern**: OperationDispatcher itself implements OperationProvider,
+///   allowing composition
+/// - **Provider registry discovery**: Providers expose introspection via `operations()`,
+///   allowing OperationDispatcher to discover all providers at runtime via DI
+///
+/// # Examples
+/// ```ignore
+/// // Individual cache:
+/// cache.execute_operation("todoist-task", "set_completion", params).await?;
+///
+/// // Composite dispatcher:
+/// dispatcher.execute_operation("todoist-task", "set_completion", params).await?;
+/// // → Routes to TodoistQueryableCache → set_field("completed", true)
+/// ```
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait OperationProvider: Send + Sync {
+    /// Get all operations this provider supports
+    fn operations(&self) -> Vec<OperationDescriptor>;
+
+    /// Find operations that can be executed with given arguments
+    ///
+    /// Filters to operations where required_params can be satisfied either:
+    /// 1. Directly from available_args, OR
+    /// 2. Via param_mappings (from other widgets like drop targets)
+    ///
+    /// Example:
+    /// ```
+    /// // Lineage: checkbox modifies "completed" field
+    /// // Available: ["id", "completed"]
+    /// let ops = provider.find_operations("todoist-task", &["id", "completed"]);
+    /// // Returns: ["set_field", "set_completion", "delete"]
+    /// // Also returns: ["move_block"] if it has param_mappings for parent_id
+    /// ```
+    fn find_operations(
+        &self,
+        entity_name: &str,
+        available_args: &[String],
+    ) -> Vec<OperationDescriptor> {
+        self.operations()
+            .into_iter()
+            .filter(|op| {
+                if op.entity_name != entity_name {
+                    return false;
+                }
+
+                // Check each required param
+                op.required_params.iter().all(|p| {
+                    // Param is directly available
+                    if available_args.contains(&p.name) {
+                        return true;
+                    }
+
+                    // Param can be provided via a param_mapping
+                    // (from another widget like drop target)
+                    op.param_mappings
+                        .iter()
+                        .any(|mapping| mapping.provides.contains(&p.name))
+                })
+            })
+            .collect()
+    }
+
+    /// Execute an operation
+    ///
+    /// - Individual caches: validate entity_name, dispatch to trait methods
+    /// - Composite dispatcher: route to correct registered provider
+    ///
+    /// Returns the UndoAction for undo support.
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        params: StorageEntity,
+    ) -> Result<UndoAction>;
+
+    /// Name identifying this provider in routing rules (e.g. "todoist", "todoist-fake")
+    ///
+    /// Empty for providers that routing rules can't address.
+    fn provider_name(&self) -> String {
+        String::new()
+    }
+
+    /// Priority among providers handling the same operation; the highest wins
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    /// Workspace this provider belongs to, if it serves only one
+    ///
+    /// Operations naming a `workspace` parameter are only routed to providers of
+    /// that workspace (or without one).
+    fn workspace(&self) -> Option<String> {
+        None
+    }
+
+    /// Get the last created entity ID (if any)
+    ///
+    /// This is used by GenericProviderState to track entity creation.
+    /// Providers that support this should override this method to return
+    /// the ID of the last entity created via execute_operation.
+    /// Default implementation returns None.
+    fn get_last_created_id(&self) -> Option<String> {
+        None
+    }
+}
+
 /// Read-only data access (from cache)
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -191,6 +431,13 @@ where
     async fn get_all(&self) -> Result<Vec<T>>;
     async fn get_by_id(&self, id: &str) -> Result<Option<T>>;
 
+    /// Generator for IDs of entities created by operations (e.g. `split_block`)
+    ///
+    /// Datasources with a configured `IdStrategy` override this.
+    fn id_generator(&self) -> Arc<dyn IdGenerator> {
+        default_id_generator()
+    }
+
     // Helper queries (default implementations)
     async fn get_children(&self, parent_id: &str) -> Result<Vec<T>>
     where
@@ -206,18 +453,33 @@ where
 
 /// Helper methods for block data source operations
 /// These are not operations themselves, but utilities used by operations
+///
+/// Implemented for every datasource with `CrudOperations` and `DataSource` (see the
+/// blanket impl below); datasources override the queries they can answer faster.
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 pub trait BlockDataSourceHelpers<T>: CrudOperations<T> + DataSource<T>
 where
     T: BlockEntity + MaybeSendSync + 'static,
 {
+    /// Get the parent of a block (`None` for root blocks)
+    async fn get_parent(&self, block_id: &str) -> Result<Option<T>> {
+        let block: T = self
+            .get_by_id(block_id)
+            .await?
+            .ok_or_else(|| HolonError::not_found("block", block_id))?;
+        match block.parent_id() {
+            Some(parent_id) => self.get_by_id(parent_id).await,
+            None => Ok(None),
+        }
+    }
+
     /// Get all siblings of a block, sorted by sort_key
     async fn get_siblings(&self, block_id: &str) -> Result<Vec<T>> {
         let block: T = self
             .get_by_id(block_id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Block not found"))?;
+            .ok_or_else(|| HolonError::not_found("block", block_id))?;
         let parent_id = block.parent_id();
 
         let siblings: Vec<T> = if let Some(pid) = parent_id {
@@ -240,7 +502,7 @@ where
         let block: T = self
             .get_by_id(block_id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Block not found"))?;
+            .ok_or_else(|| HolonError::not_found("block", block_id))?;
         let parent_id = block.parent_id();
 
         let siblings: Vec<T> = if let Some(pid) = parent_id {
@@ -261,7 +523,7 @@ where
         let block: T = self
             .get_by_id(block_id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Block not found"))?;
+            .ok_or_else(|| HolonError::not_found("block", block_id))?;
         let parent_id = block.parent_id();
 
         let siblings: Vec<T> = if let Some(pid) = parent_id {
@@ -354,6 +616,127 @@ where
 
         Ok(())
     }
+
+    /// All descendants of a block, parents before their children, siblings by sort_key
+    async fn get_descendants(&self, block_id: &str) -> Result<Vec<T>> {
+        let mut descendants = Vec::new();
+        let mut stack: Vec<T> = Vec::new();
+        let mut parent_id = block_id.to_string();
+        loop {
+            let mut children: Vec<T> = self.get_children(&parent_id).await?;
+            // Last child first, so the first child is popped first
+            children.sort_by(|a, b| b.sort_key().cmp(a.sort_key()));
+            stack.extend(children);
+            let Some(next) = stack.pop() else {
+                break;
+            };
+            parent_id = next.id().to_string();
+            descendants.push(next);
+        }
+        Ok(descendants)
+    }
+
+    /// Move a block to the trash where supported, otherwise delete it
+    ///
+    /// The returned inverse always restores the block: if `delete` is irreversible,
+    /// it recreates the block from its `BlockEntity` fields at its old sort_key.
+    async fn remove_block(&self, block: &T) -> Result<UndoAction> {
+        match self.trash(block.id()).await {
+            Ok(undo) => return Ok(undo),
+            // Soft delete is not supported by this datasource
+            Err(HolonError::PreconditionFailed(_)) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let undo = self.delete(block.id()).await?;
+        if undo.is_reversible() {
+            return Ok(undo);
+        }
+
+        use crate::__operations_crud_operations;
+
+        let fields = HashMap::from([
+            ("id".to_string(), Value::String(block.id().to_string())),
+            (
+                "parent_id".to_string(),
+                block
+                    .parent_id()
+                    .map(|pid| Value::String(pid.to_string()))
+                    .unwrap_or(Value::Null),
+            ),
+            (
+                "sort_key".to_string(),
+                Value::String(block.sort_key().to_string()),
+            ),
+            ("depth".to_string(), Value::Integer(block.depth())),
+            (
+                "content".to_string(),
+                Value::String(block.content().to_string()),
+            ),
+        ]);
+        Ok(UndoAction::Undo(__operations_crud_operations::create_op(
+            "", // Will be set by OperationProvider::execute_operation
+            fields,
+        )))
+    }
+}
+
+/// What `delete_block` does with the children of the deleted block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeletePolicy {
+    /// Refuse to delete a block that has children
+    #[default]
+    Fail,
+    /// Delete the block's whole subtree
+    Cascade,
+    /// Move the children to the block's parent, where the block was
+    Reparent,
+}
+
+impl DeletePolicy {
+    /// Parse the `policy` parameter of `delete_block` (`None` is `fail`)
+    pub fn parse(policy: Option<&str>) -> HolonResult<Self> {
+        match policy {
+            None | Some("fail") => Ok(Self::Fail),
+            Some("cascade") => Ok(Self::Cascade),
+            Some("reparent") => Ok(Self::Reparent),
+            Some(other) => Err(HolonError::validation(format!(
+                "Unknown delete policy '{}' (expected fail, cascade or reparent)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Inverse of moving a block: back under `parent_id`, at exactly `sort_key`
+///
+/// `move_block` restores the parent and the depths of the block's subtree, but
+/// generates a new sort key; the following `set_field` restores the old one, so
+/// the block ends up exactly where it was even if its old neighbours moved.
+fn restore_position(
+    id: &str,
+    parent_id: &str,
+    predecessor_id: Option<&str>,
+    sort_key: &str,
+) -> UndoAction {
+    use crate::{__operations_block_operations, __operations_crud_operations};
+
+    // Entity names will be set by OperationProvider::execute_operation.
+    // Composites are applied in reverse order: move first, then restore the key.
+    UndoAction::Composite(vec![
+        UndoAction::Undo(__operations_crud_operations::set_field_op(
+            "",
+            id,
+            "sort_key",
+            Value::String(sort_key.to_string()),
+        )),
+        UndoAction::Undo(__operations_block_operations::move_block_op(
+            "",
+            id,
+            parent_id,
+            predecessor_id,
+        )),
+    ])
 }
 
 /// Hierarchical structure operations (for any block-like entity)
@@ -361,6 +744,11 @@ where
 /// This trait provides operations for manipulating block hierarchies.
 /// It requires that the entity type implements `BlockEntity` and that
 /// the datasource implements `BlockDataSourceHelpers`.
+///
+/// All operations have default implementations: `indent`, `outdent`, `move_up`
+/// and `move_down` are derived from the helper queries and `move_block`, which is
+/// in turn built on `set_field`. A new datasource only implements the primitives
+/// (`CrudOperations` and `DataSource`) and gets this trait from the blanket impl.
 #[holon_macros::operations_trait]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -370,47 +758,25 @@ where
 {
     /// Move block under a new parent (increase indentation)
     #[holon_macros::affects("parent_id", "depth", "sort_key")]
-    async fn indent(&self, id: &str, parent_id: &str) -> Result<UndoAction> {
-        // Capture old state before mutation
+    async fn indent(&self, id: &str, parent_id: &str) -> HolonResult<UndoAction> {
         let block = self
             .get_by_id(id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Block not found"))?;
-        let old_parent_id = block
-            .parent_id()
-            .ok_or_else(|| anyhow::anyhow!("Cannot indent root block"))?
-            .to_string();
-        let old_predecessor = self.get_prev_sibling(id).await?;
-
-        // Query cache for current state (fast - no network)
-        let maybe_parent: Option<T> = self.get_by_id(parent_id).await?;
-        let parent: T = maybe_parent.ok_or_else(|| anyhow::anyhow!("Parent not found"))?;
-        let siblings: Vec<T> = self.get_children(parent_id).await?;
-
-        // Calculate new position via fractional indexing
-        let sort_key = gen_key_between(siblings.last().map(|s| s.sort_key()), None)
-            .map_err(|e| anyhow::anyhow!(e))?;
-
-        // Execute primitives (delegates to self.set_field)
-        self.set_field(id, "parent_id", Value::String(parent_id.to_string()))
-            .await?;
-        self.set_field(id, "depth", Value::Integer(parent.depth() + 1))
-            .await?;
-        self.set_field(id, "sort_key", Value::String(sort_key))
-            .await?;
-
-        // Return inverse operation using macro-generated helper
-        use crate::__operations_block_operations;
+            .ok_or_else(|| HolonError::not_found("block", id))?;
+        if block.parent_id().is_none() {
+            return Err(HolonError::precondition("Cannot indent root block"));
+        }
 
-        // Entity name will be set by OperationProvider when operation is executed
-        Ok(UndoAction::Undo(
-            __operations_block_operations::move_block_op(
-                "", // Will be set by OperationProvider::execute_operation
-                id,
-                &old_parent_id,
-                old_predecessor.as_ref().map(|p| p.id()),
-            ),
-        ))
+        // Append after the new parent's last child; move_block updates the depths
+        // of the whole subtree and its inverse restores the exact old position
+        let last_child = self
+            .get_children(parent_id)
+            .await?
+            .into_iter()
+            .filter(|child: &T| child.id() != id)
+            .max_by(|a: &T, b: &T| a.sort_key().cmp(b.sort_key()));
+        self.move_block(id, parent_id, last_child.as_ref().map(|c| c.id()))
+            .await
     }
 
     /// Move block to different position (reorder within same parent or different parent)
@@ -427,15 +793,16 @@ where
         id: &str,
         parent_id: &str,
         after_block_id: Option<&str>,
-    ) -> Result<UndoAction> {
+    ) -> HolonResult<UndoAction> {
         // Capture old state before mutation
         let maybe_block: Option<T> = self.get_by_id(id).await?;
-        let block: T = maybe_block.ok_or_else(|| anyhow::anyhow!("Block not found"))?;
+        let block: T = maybe_block.ok_or_else(|| HolonError::not_found("block", id))?;
         let old_parent_id = block
             .parent_id()
-            .ok_or_else(|| anyhow::anyhow!("Cannot move root block"))?
+            .ok_or_else(|| HolonError::precondition("Cannot move root block"))?
             .to_string();
         let old_predecessor = self.get_prev_sibling(id).await?;
+        let old_sort_key = block.sort_key().to_string();
         let old_depth = block.depth();
 
         // Query predecessor and successor sort_keys
@@ -447,8 +814,9 @@ where
         } else {
             // Insert after specific block
             let maybe_after_block: Option<T> = self.get_by_id(after_block_id.unwrap()).await?;
-            let after_block: T =
-                maybe_after_block.ok_or_else(|| anyhow::anyhow!("Reference block not found"))?;
+            let after_block: T = maybe_after_block.ok_or_else(|| {
+                HolonError::not_found("block", after_block_id.unwrap_or_default())
+            })?;
             let prev_key = Some(after_block.sort_key().to_string());
 
             // Find next sibling after the anchor block
@@ -473,8 +841,9 @@ where
                 (None, first_key)
             } else {
                 let maybe_after_block: Option<T> = self.get_by_id(after_block_id.unwrap()).await?;
-                let after_block: T = maybe_after_block
-                    .ok_or_else(|| anyhow::anyhow!("Reference block not found"))?;
+                let after_block: T = maybe_after_block.ok_or_else(|| {
+                    HolonError::not_found("block", after_block_id.unwrap_or_default())
+                })?;
                 let prev_key = Some(after_block.sort_key().to_string());
                 let next_sibling: Option<T> =
                     self.get_next_sibling(after_block_id.unwrap()).await?;
@@ -488,7 +857,7 @@ where
 
         // Calculate new depth based on parent
         let maybe_parent: Option<T> = self.get_by_id(parent_id).await?;
-        let parent: T = maybe_parent.ok_or_else(|| anyhow::anyhow!("Parent not found"))?;
+        let parent: T = maybe_parent.ok_or_else(|| HolonError::not_found("block", parent_id))?;
         let new_depth = parent.depth() + 1;
 
         // Calculate depth delta for recursive updates
@@ -507,58 +876,29 @@ where
             self.update_descendant_depths(id, depth_delta).await?;
         }
 
-        // Return inverse operation using macro-generated helper
-        use crate::__operations_block_operations;
-
-        // Entity name will be set by OperationProvider when operation is executed
-        Ok(UndoAction::Undo(
-            __operations_block_operations::move_block_op(
-                "", // Will be set by OperationProvider::execute_operation
-                id,
-                &old_parent_id,
-                old_predecessor.as_ref().map(|p| p.id()),
-            ),
+        Ok(restore_position(
+            id,
+            &old_parent_id,
+            old_predecessor.as_ref().map(|p| p.id()),
+            &old_sort_key,
         ))
     }
 
     /// Move block out to parent's level (decrease indentation)
     #[holon_macros::affects("parent_id", "depth", "sort_key")]
-    async fn outdent(&self, id: &str) -> Result<UndoAction> {
-        let maybe_block: Option<T> = self.get_by_id(id).await?;
-        let block: T = maybe_block.ok_or_else(|| anyhow::anyhow!("Block not found"))?;
-        let parent_id = block
-            .parent_id()
-            .ok_or_else(|| anyhow::anyhow!("Cannot outdent root block"))?;
-
-        let maybe_parent: Option<T> = self.get_by_id(parent_id).await?;
-        let parent: T = maybe_parent.ok_or_else(|| anyhow::anyhow!("Parent not found"))?;
-        let grandparent_id = parent
-            .parent_id()
-            .ok_or_else(|| anyhow::anyhow!("Cannot outdent: parent is already at root level"))?;
-
-        // Move to grandparent's children, after parent
-        // move_block returns the inverse, but we need to return the inverse of outdent
-        // which is indent. So we need to capture state before move_block
-        let block = self
-            .get_by_id(id)
+    async fn outdent(&self, id: &str) -> HolonResult<UndoAction> {
+        let parent: T = self
+            .get_parent(id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Block not found"))?;
-        let old_parent_id = block
-            .parent_id()
-            .ok_or_else(|| anyhow::anyhow!("Cannot outdent root block"))?
-            .to_string();
-
-        self.move_block(id, grandparent_id, Some(parent_id)).await?;
-
-        // Return inverse operation using macro-generated helper
-        use crate::__operations_block_operations;
-
-        // Entity name will be set by OperationProvider when operation is executed
-        Ok(UndoAction::Undo(__operations_block_operations::indent_op(
-            "", // Will be set by OperationProvider::execute_operation
-            id,
-            &old_parent_id,
-        )))
+            .ok_or_else(|| HolonError::precondition("Cannot outdent root block"))?;
+        let parent_id = parent.id();
+        let grandparent_id = parent.parent_id().ok_or_else(|| {
+            HolonError::precondition("Cannot outdent: parent is already at root level")
+        })?;
+
+        // Move to grandparent's children, after parent. The inverse of that move
+        // restores the old parent and sort key, so it is also the inverse of outdent.
+        self.move_block(id, grandparent_id, Some(parent_id)).await
     }
 
     /// Split a block at a given position
@@ -571,28 +911,25 @@ where
     /// * `id` - Block ID to split
     /// * `position` - Character position to split at (as i64, will be converted to usize)
     #[holon_macros::affects("content")]
-    async fn split_block(&self, id: &str, position: i64) -> Result<UndoAction> {
-        use uuid::Uuid;
-
+    async fn split_block(&self, id: &str, position: i64) -> HolonResult<UndoAction> {
         let maybe_block: Option<T> = self.get_by_id(id).await?;
-        let block: T = maybe_block.ok_or_else(|| anyhow::anyhow!("Block not found"))?;
+        let block: T = maybe_block.ok_or_else(|| HolonError::not_found("block", id))?;
 
         let content = block.content();
 
         // Convert i64 to usize (validate it's non-negative and fits in usize)
         if position < 0 {
-            return Err(anyhow::anyhow!("Position must be non-negative").into());
+            return Err(HolonError::validation("Position must be non-negative"));
         }
         let position = position as usize;
 
         // Validate offset is within bounds
         if position > content.len() {
-            return Err(anyhow::anyhow!(
+            return Err(HolonError::validation(format!(
                 "Split position {} exceeds content length {}",
                 position,
                 content.len()
-            )
-            .into());
+            )));
         }
 
         // Split content at cursor
@@ -606,7 +943,7 @@ where
         content_after = content_after.trim_start().to_string();
 
         // Generate new block ID
-        let new_block_id = Uuid::new_v4().to_string();
+        let new_block_id = self.id_generator().generate();
 
         // Get next sibling's sort_key to position new block correctly
         let next_sibling: Option<T> = self.get_next_sibling(id).await?;
@@ -650,86 +987,150 @@ where
         Ok(UndoAction::Irreversible)
     }
 
+    /// Apply a fine-grained text edit to the block content
+    ///
+    /// # Parameters
+    /// * `id` - Block ID to edit
+    /// * `delta` - JSON-encoded `holon_api::TextDelta` (character offsets)
+    ///
+    /// The inverse is the inverted delta, so undo restores cursor-level steps
+    /// instead of whole-content snapshots.
+    #[holon_macros::affects("content")]
+    async fn apply_text_delta(&self, id: &str, delta: String) -> HolonResult<UndoAction> {
+        use holon_api::TextDelta;
+
+        let text_delta =
+            TextDelta::from_json(&delta).map_err(|e| HolonError::validation(e.to_string()))?;
+        let block: T = self
+            .get_by_id(id)
+            .await?
+            .ok_or_else(|| HolonError::not_found("block", id))?;
+        let new_content = text_delta
+            .apply(block.content())
+            .map_err(|e| HolonError::validation(e.to_string()))?;
+
+        self.set_field(id, "content", Value::String(new_content))
+            .await?;
+
+        use crate::__operations_block_operations;
+
+        Ok(UndoAction::Undo(
+            __operations_block_operations::apply_text_delta_op(
+                "", // Will be set by OperationProvider::execute_operation
+                id,
+                text_delta.invert().to_json(),
+            ),
+        ))
+    }
+
+    /// Delete a block, handling its children according to `policy`
+    ///
+    /// # Parameters
+    /// * `id` - Block ID to delete
+    /// * `policy` - `fail` (default) refuses to delete a block with children,
+    ///   `cascade` deletes its whole subtree, `reparent` moves its children to the
+    ///   block's parent, at the block's position
+    ///
+    /// Blocks are moved to the trash where the datasource supports it. The inverse
+    /// is a single undo step restoring the whole subtree, including sort keys.
+    async fn delete_block(&self, id: &str, policy: Option<&str>) -> HolonResult<UndoAction> {
+        let policy = DeletePolicy::parse(policy)?;
+        let block: T = self
+            .get_by_id(id)
+            .await?
+            .ok_or_else(|| HolonError::not_found("block", id))?;
+        let mut children: Vec<T> = self.get_children(id).await?;
+        children.sort_by(|a, b| a.sort_key().cmp(b.sort_key()));
+
+        // Composites are applied in reverse order, so parents are restored before
+        // their children and children are moved back after the block is restored
+        let mut inverse = Vec::new();
+        match policy {
+            DeletePolicy::Fail if !children.is_empty() => {
+                return Err(HolonError::precondition(format!(
+                    "Cannot delete block {}: it has {} children (use policy cascade or reparent)",
+                    id,
+                    children.len()
+                )));
+            }
+            DeletePolicy::Fail => {}
+            DeletePolicy::Cascade => {
+                let descendants = self.get_descendants(id).await?;
+                for descendant in descendants.iter().rev() {
+                    inverse.push(self.remove_block(descendant).await?);
+                }
+            }
+            DeletePolicy::Reparent => {
+                let parent_id = block.parent_id().ok_or_else(|| {
+                    HolonError::precondition("Cannot reparent the children of a root block")
+                })?;
+                let mut after_block_id = id.to_string();
+                for child in &children {
+                    inverse.push(
+                        self.move_block(child.id(), parent_id, Some(&after_block_id))
+                            .await?,
+                    );
+                    after_block_id = child.id().to_string();
+                }
+            }
+        }
+        inverse.push(self.remove_block(&block).await?);
+
+        Ok(UndoAction::Composite(inverse))
+    }
+
     /// Move a block up (swap with previous sibling)
     #[holon_macros::affects("parent_id", "sort_key")]
-    async fn move_up(&self, id: &str) -> Result<UndoAction> {
+    async fn move_up(&self, id: &str) -> HolonResult<UndoAction> {
         // Capture old state
         let block = self
             .get_by_id(id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Block not found"))?;
+            .ok_or_else(|| HolonError::not_found("block", id))?;
         let parent_id = block
             .parent_id()
-            .ok_or_else(|| anyhow::anyhow!("Cannot move root block"))?
+            .ok_or_else(|| HolonError::precondition("Cannot move root block"))?
             .to_string();
-        let old_predecessor = self.get_prev_sibling(id).await?;
-        let next_sibling = self.get_next_sibling(id).await?;
 
         let prev_sibling: T = self
             .get_prev_sibling(id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Cannot move up: no previous sibling"))?;
+            .ok_or_else(|| HolonError::precondition("Cannot move up: no previous sibling"))?;
 
         // Get the sibling before prev_sibling
         let before_prev: Option<T> = self.get_prev_sibling(prev_sibling.id()).await?;
 
-        // Execute move
+        // Execute move; its inverse restores the exact old position
+        // (move_down would be relative, not absolute)
         if let Some(before_id) = before_prev {
-            self.move_block(id, &parent_id, Some(before_id.id()))
-                .await?;
+            self.move_block(id, &parent_id, Some(before_id.id())).await
         } else {
             // Move to beginning
-            self.move_block(id, &parent_id, None).await?;
+            self.move_block(id, &parent_id, None).await
         }
-
-        // Return inverse (move down - restore original position) using macro-generated helper
-        // Use move_block_op to restore exact old position (move_up_op is relative, not absolute)
-        use crate::__operations_block_operations;
-
-        Ok(UndoAction::Undo(
-            __operations_block_operations::move_block_op(
-                "", // Will be set by OperationProvider::execute_operation
-                id,
-                &parent_id,
-                old_predecessor.as_ref().map(|p| p.id()),
-            ),
-        ))
     }
 
     /// Move a block down (swap with next sibling)
     #[holon_macros::affects("parent_id", "sort_key")]
-    async fn move_down(&self, id: &str) -> Result<UndoAction> {
+    async fn move_down(&self, id: &str) -> HolonResult<UndoAction> {
         // Capture old state
         let block = self
             .get_by_id(id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Block not found"))?;
+            .ok_or_else(|| HolonError::not_found("block", id))?;
         let parent_id = block
             .parent_id()
-            .ok_or_else(|| anyhow::anyhow!("Cannot move root block"))?
+            .ok_or_else(|| HolonError::precondition("Cannot move root block"))?
             .to_string();
-        let old_predecessor = self.get_prev_sibling(id).await?;
 
         let next_sibling: T = self
             .get_next_sibling(id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Cannot move down: no next sibling"))?;
+            .ok_or_else(|| HolonError::precondition("Cannot move down: no next sibling"))?;
 
-        // Execute move after next_sibling
+        // Execute move after next_sibling; its inverse restores the exact old position
         self.move_block(id, &parent_id, Some(next_sibling.id()))
-            .await?;
-
-        // Return inverse (move up - restore original position) using macro-generated helper
-        use crate::__operations_block_operations;
-
-        Ok(UndoAction::Undo(
-            __operations_block_operations::move_block_op(
-                "", // Will be set by OperationProvider::execute_operation
-                id,
-                &parent_id,
-                old_predecessor.as_ref().map(|p| p.id()),
-            ),
-        ))
+            .await
     }
 }
 
@@ -789,7 +1190,8 @@ where
 {
     /// Toggle or set task completion status
     #[holon_macros::triggered_by(availability_of = "completed")]
-    async fn set_completion(&self, id: &str, completed: bool) -> Result<UndoAction> {
+    #[holon_macros::simulate(crate::testing::simulate_set_completion)]
+    async fn set_completion(&self, id: &str, completed: bool) -> HolonResult<UndoAction> {
         self.set_field(id, "completed", Value::Boolean(completed))
             .await
     }
@@ -797,14 +1199,20 @@ where
     /// Set task priority (1=highest, 4=lowest in Todoist)
     #[holon_macros::affects("priority")]
     #[holon_macros::triggered_by(availability_of = "priority")]
-    async fn set_priority(&self, id: &str, priority: i64) -> Result<UndoAction> {
+    #[holon_macros::simulate(crate::testing::simulate_set_priority)]
+    async fn set_priority(&self, id: &str, priority: i64) -> HolonResult<UndoAction> {
         self.set_field(id, "priority", Value::Integer(priority))
             .await
     }
 
     /// Set task due date
     #[holon_macros::affects("due_date")]
-    async fn set_due_date(&self, id: &str, due_date: Option<DateTime<Utc>>) -> Result<UndoAction> {
+    #[holon_macros::triggered_by(availability_of = "calendar_day", providing = ["due_date"])]
+    async fn set_due_date(
+        &self,
+        id: &str,
+        due_date: Option<DateTime<Utc>>,
+    ) -> HolonResult<UndoAction> {
         self.set_field(
             id,
             "due_date",
@@ -816,6 +1224,65 @@ where
     }
 }
 
+/// Time tracking operations (for any task-like entity)
+///
+/// Clocked intervals are recorded as `TimeEntry` rows in the `time_entries` table.
+/// At most one entry per task is running at a time.
+#[holon_macros::operations_trait]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait TimeTrackingOperations: MaybeSendSync {
+    /// Start tracking time on a task
+    async fn clock_in(&self, id: &str) -> Result<UndoAction>;
+
+    /// Stop tracking time on a task
+    async fn clock_out(&self, id: &str) -> Result<UndoAction>;
+}
+
+/// Attachment operations (for any entity that can carry files)
+///
+/// Attached files are recorded as `Attachment` rows in the `attachments` table.
+#[holon_macros::operations_trait]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait AttachmentOperations: MaybeSendSync {
+    /// Attach the file at `path` to an entity
+    async fn attach_file(&self, entity_id: &str, path: &str) -> Result<UndoAction>;
+
+    /// Remove an attachment (the file itself is left untouched)
+    async fn remove_attachment(&self, id: &str) -> Result<UndoAction>;
+}
+
+/// Dependency operations (for any task-like entity)
+///
+/// Dependencies are recorded as `TaskDependency` rows in the `task_dependencies`
+/// table. Adding a dependency that would make a task (indirectly) block itself fails.
+#[holon_macros::operations_trait]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait DependencyOperations: MaybeSendSync {
+    /// Make task `id` block task `blocks_id`
+    async fn add_dependency(&self, id: &str, blocks_id: &str) -> Result<UndoAction>;
+
+    /// Stop task `id` from blocking task `blocks_id`
+    async fn remove_dependency(&self, id: &str, blocks_id: &str) -> Result<UndoAction>;
+}
+
+/// Custom field operations (for any entity type)
+///
+/// Definitions are recorded as `CustomFieldDefinition` rows in the
+/// `custom_field_definitions` table; values are set with `set_custom_field`.
+#[holon_macros::operations_trait]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait CustomFieldOperations: MaybeSendSync {
+    /// Add field `name` of type `field_type` (e.g. "integer") to the entity type
+    async fn define_custom_field(&self, name: &str, field_type: &str) -> Result<UndoAction>;
+
+    /// Remove field `name` and all its values
+    async fn remove_custom_field(&self, name: &str) -> Result<UndoAction>;
+}
+
 // Blanket implementations: Automatically provide helper methods for any compatible type
 impl<T, D> BlockDataSourceHelpers<T> for D
 where
@@ -872,8 +1339,190 @@ pub trait OperationLogOperations: MaybeSendSync {
     /// Called when a new operation is executed to invalidate the redo history.
     async fn clear_redo_stack(&self) -> Result<()>;
 
+    /// Replace a temporary entity ID with its remote ID in logged operations.
+    ///
+    /// Called when an optimistic create completes, so queued operations and
+    /// inverses that still reference the temporary ID target the real entity.
+    /// Returns the number of rewritten log entries.
+    async fn remap_id(&self, temp_id: &str, remote_id: &str) -> Result<usize>;
+
     /// Get the maximum number of operations to retain.
     fn max_log_size(&self) -> usize {
         100
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delete_policy_defaults_to_fail() {
+        assert_eq!(DeletePolicy::parse(None).unwrap(), DeletePolicy::Fail);
+        assert_eq!(
+            DeletePolicy::parse(Some("cascade")).unwrap(),
+            DeletePolicy::Cascade
+        );
+        assert_eq!(
+            DeletePolicy::parse(Some("reparent")).unwrap(),
+            DeletePolicy::Reparent
+        );
+        assert!(matches!(
+            DeletePolicy::parse(Some("orphan")),
+            Err(HolonError::Validation(_))
+        ));
+    }
+
+    #[derive(Debug, Clone)]
+    struct TestBlock {
+        id: String,
+        parent_id: Option<String>,
+        sort_key: String,
+        depth: i64,
+    }
+
+    impl BlockEntity for TestBlock {
+        fn id(&self) -> &str {
+            &self.id
+        }
+
+        fn parent_id(&self) -> Option<&str> {
+            self.parent_id.as_deref()
+        }
+
+        fn sort_key(&self) -> &str {
+            &self.sort_key
+        }
+
+        fn depth(&self) -> i64 {
+            self.depth
+        }
+
+        fn content(&self) -> &str {
+            &self.id
+        }
+    }
+
+    /// Datasource implementing only the primitives; BlockOperations comes from the blanket impls
+    #[derive(Default)]
+    struct TestBlocks {
+        blocks: std::sync::Mutex<HashMap<String, TestBlock>>,
+    }
+
+    impl TestBlocks {
+        fn with_blocks(blocks: &[(&str, Option<&str>, &str, i64)]) -> Self {
+            let store = Self::default();
+            for (id, parent_id, sort_key, depth) in blocks {
+                store.blocks.lock().unwrap().insert(
+                    id.to_string(),
+                    TestBlock {
+                        id: id.to_string(),
+                        parent_id: parent_id.map(str::to_string),
+                        sort_key: sort_key.to_string(),
+                        depth: *depth,
+                    },
+                );
+            }
+            store
+        }
+
+        fn block(&self, id: &str) -> TestBlock {
+            self.blocks.lock().unwrap()[id].clone()
+        }
+
+        /// Children of `parent_id`, by sort_key
+        fn children(&self, parent_id: &str) -> Vec<String> {
+            let mut children: Vec<TestBlock> = self
+                .blocks
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|b| b.parent_id.as_deref() == Some(parent_id))
+                .cloned()
+                .collect();
+            children.sort_by(|a, b| a.sort_key.cmp(&b.sort_key));
+            children.into_iter().map(|b| b.id).collect()
+        }
+    }
+
+    #[async_trait]
+    impl DataSource<TestBlock> for TestBlocks {
+        async fn get_all(&self) -> Result<Vec<TestBlock>> {
+            Ok(self.blocks.lock().unwrap().values().cloned().collect())
+        }
+
+        async fn get_by_id(&self, id: &str) -> Result<Option<TestBlock>> {
+            Ok(self.blocks.lock().unwrap().get(id).cloned())
+        }
+    }
+
+    #[async_trait]
+    impl CrudOperations<TestBlock> for TestBlocks {
+        async fn set_field(&self, id: &str, field: &str, value: Value) -> HolonResult<UndoAction> {
+            let mut blocks = self.blocks.lock().unwrap();
+            let block = blocks
+                .get_mut(id)
+                .ok_or_else(|| HolonError::not_found("block", id))?;
+            match (field, value) {
+                ("parent_id", Value::String(parent_id)) => block.parent_id = Some(parent_id),
+                ("sort_key", Value::String(sort_key)) => block.sort_key = sort_key,
+                ("depth", Value::Integer(depth)) => block.depth = depth,
+                (field, value) => {
+                    return Err(HolonError::validation(format!(
+                        "Unexpected {} = {:?}",
+                        field, value
+                    )))
+                }
+            }
+            Ok(UndoAction::Irreversible)
+        }
+
+        async fn create(
+            &self,
+            _fields: HashMap<String, Value>,
+        ) -> HolonResult<(String, UndoAction)> {
+            Err(HolonError::precondition("create is not supported"))
+        }
+
+        async fn delete(&self, id: &str) -> HolonResult<UndoAction> {
+            self.blocks.lock().unwrap().remove(id);
+            Ok(UndoAction::Irreversible)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_block_operations_derived_from_primitives() {
+        let blocks = TestBlocks::with_blocks(&[
+            ("doc", None, "a0", 0),
+            ("a", Some("doc"), "a0", 1),
+            ("b", Some("doc"), "a1", 1),
+            ("b1", Some("b"), "a0", 2),
+            ("c", Some("doc"), "a2", 1),
+        ]);
+
+        // Indent appends after the new parent's children and moves the subtree along
+        let undo = blocks.indent("b", "a").await.unwrap();
+        assert!(undo.is_reversible());
+        assert_eq!(blocks.children("a"), vec!["b"]);
+        assert_eq!(blocks.block("b").depth, 2);
+        assert_eq!(blocks.block("b1").depth, 3);
+        assert_eq!(blocks.get_parent("b").await.unwrap().unwrap().id, "a");
+
+        // Outdent puts the block right after its old parent
+        blocks.outdent("b").await.unwrap();
+        assert_eq!(blocks.children("doc"), vec!["a", "b", "c"]);
+        assert_eq!(blocks.block("b1").depth, 2);
+
+        blocks.move_up("b").await.unwrap();
+        assert_eq!(blocks.children("doc"), vec!["b", "a", "c"]);
+        blocks.move_down("b").await.unwrap();
+        blocks.move_down("b").await.unwrap();
+        assert_eq!(blocks.children("doc"), vec!["a", "c", "b"]);
+
+        assert!(matches!(
+            blocks.indent("doc", "a").await,
+            Err(HolonError::PreconditionFailed(_))
+        ));
+        assert!(blocks.get_parent("doc").await.unwrap().is_none());
+    }
+}
@@ -2,21 +2,199 @@
 //!
 //! This module provides types and structures for implementing undo/redo
 //! functionality through inverse operations.
+//!
+//! Operations are grouped into undo steps by user intent:
+//! - `set_field` operations on the same entity and field within the coalesce window
+//!   (e.g. typing in a block) collapse into a single step
+//! - Consecutive `apply_text_delta` edits of the same block within the coalesce window
+//!   are composed into one delta per word, so undo steps back through a text edit
+//!   session at cursor granularity
+//! - Everything pushed between `begin_group()` and `end_group()` forms a single step
+//!
+//! Steps remember the `ChangeSource` of their operations, so an agent's or another
+//! frontend's changes can be undone without touching the user's own (`pop_for_undo_from`).
+//! Operations from different sources never share a step.
+
+use holon_api::{ChangeSource, Operation, TextDelta, Value};
+
+use crate::operation_log::remap_operation_id;
+
+/// Default window (ms) within which edits of the same field are coalesced
+pub const DEFAULT_COALESCE_WINDOW_MS: i64 = 1000;
+
+/// An executed operation and the operations reverting it (in execution order)
+///
+/// Most operations have a single inverse; those returning `UndoAction::Composite`
+/// have several.
+struct UndoRecord {
+    executed: Vec<Operation>,
+    inverse: Vec<Operation>,
+}
 
-use holon_api::Operation;
+impl UndoRecord {
+    fn single(executed: Operation, inverse: Operation) -> Self {
+        Self {
+            executed: vec![executed],
+            inverse: vec![inverse],
+        }
+    }
+
+    /// The (executed, inverse) pair, if this record is a plain one-to-one edit
+    fn as_pair(&self) -> Option<(&Operation, &Operation)> {
+        match (self.executed.as_slice(), self.inverse.as_slice()) {
+            ([executed], [inverse]) => Some((executed, inverse)),
+            _ => None,
+        }
+    }
+}
+
+/// A single undoable step
+///
+/// Holds records in execution order.
+struct UndoEntry {
+    ops: Vec<UndoRecord>,
+    /// `entity:id:field` for coalescable field edits
+    coalesce_key: Option<String>,
+    /// Timestamp (ms) of the last operation added to this entry
+    updated_at: i64,
+    /// Display name of an explicit group
+    display_name: Option<String>,
+    /// Who executed the operations (None if unknown)
+    source: Option<ChangeSource>,
+}
+
+impl UndoEntry {
+    /// Operations to execute to revert this entry, plus the entry that reverts *that*
+    ///
+    /// Inverses are executed in reverse order. The resulting entry holds the executed
+    /// inverses paired with the original operations as placeholders until the actual
+    /// new inverses are known (see `update_redo_top`/`update_undo_top`).
+    fn flip(self) -> (Vec<Operation>, UndoEntry) {
+        let ops: Vec<UndoRecord> = self
+            .ops
+            .into_iter()
+            .rev()
+            .map(|record| UndoRecord {
+                executed: record.inverse,
+                inverse: record.executed,
+            })
+            .collect();
+        let to_execute = ops
+            .iter()
+            .flat_map(|record| record.executed.iter().cloned())
+            .collect();
+        (
+            to_execute,
+            UndoEntry {
+                ops,
+                coalesce_key: None,
+                updated_at: self.updated_at,
+                display_name: self.display_name,
+                source: self.source,
+            },
+        )
+    }
+
+    fn display_name(&self) -> Option<&str> {
+        self.display_name.as_deref().or_else(|| {
+            self.ops
+                .last()
+                .and_then(|record| record.inverse.last())
+                .map(|inverse| inverse.display_name.as_str())
+        })
+    }
+
+    /// Replace the inverse of the record that executed the `index`-th operation
+    ///
+    /// Records executing several operations keep their placeholder, as the new
+    /// inverses of the individual operations don't add up to one inverse.
+    fn update_inverse(&mut self, index: usize, new_inverse: Vec<Operation>) {
+        if new_inverse.is_empty() {
+            return;
+        }
+        let mut start = 0;
+        for record in &mut self.ops {
+            let end = start + record.executed.len();
+            if index < end {
+                if record.executed.len() == 1 {
+                    record.inverse = new_inverse;
+                }
+                return;
+            }
+            start = end;
+        }
+    }
+}
+
+/// Key identifying edits that may be coalesced (same entity, row and field)
+fn coalesce_key(op: &Operation) -> Option<String> {
+    let id = op.params.get("id").and_then(Value::as_string)?;
+    match op.op_name.as_str() {
+        "set_field" => {
+            let field = op.params.get("field").and_then(Value::as_string)?;
+            Some(format!("{}:{}:{}", op.entity_name, id, field))
+        }
+        "apply_text_delta" => Some(format!("{}:{}:text_delta", op.entity_name, id)),
+        _ => None,
+    }
+}
+
+fn text_delta_param(op: &Operation) -> Option<TextDelta> {
+    let json = op.params.get("delta").and_then(Value::as_string)?;
+    TextDelta::from_json(json).ok()
+}
+
+/// Operation identical to `op` but carrying `delta`
+fn with_text_delta(op: &Operation, delta: &TextDelta) -> Operation {
+    let mut op = op.clone();
+    op.params
+        .insert("delta".to_string(), Value::String(delta.to_json()));
+    op
+}
+
+/// Compose two text-delta edits into one, unless `next` starts a new word
+///
+/// Returns the composed (original, inverse) pair.
+fn compose_text_edits(
+    (prev, prev_inverse): (&Operation, &Operation),
+    next: &Operation,
+) -> Option<UndoRecord> {
+    let prev_delta = text_delta_param(prev)?;
+    let next_delta = text_delta_param(next)?;
+
+    let starts_word = next_delta.inserted.starts_with(char::is_whitespace)
+        && !prev_delta.inserted.ends_with(char::is_whitespace);
+    if starts_word {
+        return None;
+    }
+
+    let composed = prev_delta.compose(&next_delta)?;
+    Some(UndoRecord::single(
+        with_text_delta(next, &composed),
+        with_text_delta(prev_inverse, &composed.invert()),
+    ))
+}
 
 /// Undo/redo history stack
 ///
-/// Maintains two stacks:
-/// - `undo`: (original_operation, inverse_operation) pairs for operations that can be undone
-/// - `redo`: (inverse_operation, new_inverse) pairs for operations that were undone and can be redone
+/// Maintains two stacks of undo steps:
+/// - `undo`: steps holding (original_operation, inverse_operation) pairs that can be undone
+/// - `redo`: steps holding (inverse_operation, new_inverse) pairs that were undone and can be redone
 pub struct UndoStack {
-    /// Stack of (original, inverse) operation pairs for undo
-    undo: Vec<(Operation, Operation)>,
-    /// Stack of (inverse, new_inverse) operation pairs for redo
-    redo: Vec<(Operation, Operation)>,
-    /// Maximum number of operations to keep in undo stack
+    /// Steps that can be undone
+    undo: Vec<UndoEntry>,
+    /// Steps that can be redone
+    redo: Vec<UndoEntry>,
+    /// Maximum number of steps to keep in undo stack
     max_size: usize,
+    /// Window (ms) for coalescing edits of the same field; 0 disables coalescing
+    coalesce_window_ms: i64,
+    /// Nesting depth of `begin_group` calls
+    group_depth: usize,
+    /// Display name for the currently open group
+    group_name: Option<String>,
+    /// Whether the top of `undo` belongs to the currently open group
+    group_started: bool,
 }
 
 impl UndoStack {
@@ -31,7 +209,50 @@ impl UndoStack {
             undo: Vec::new(),
             redo: Vec::new(),
             max_size,
+            coalesce_window_ms: DEFAULT_COALESCE_WINDOW_MS,
+            group_depth: 0,
+            group_name: None,
+            group_started: false,
+        }
+    }
+
+    /// Set the window (ms) within which edits of the same field are coalesced
+    ///
+    /// A window of 0 disables time-based coalescing.
+    pub fn set_coalesce_window_ms(&mut self, window_ms: i64) {
+        self.coalesce_window_ms = window_ms;
+    }
+
+    pub fn coalesce_window_ms(&self) -> i64 {
+        self.coalesce_window_ms
+    }
+
+    /// Start an explicit group: everything pushed until the matching `end_group()`
+    /// is undone and redone as a single step. Groups may be nested; only the
+    /// outermost group's display name is used.
+    pub fn begin_group(&mut self, display_name: Option<&str>) {
+        if self.group_depth == 0 {
+            self.group_name = display_name.map(|s| s.to_string());
+            self.group_started = false;
         }
+        self.group_depth += 1;
+    }
+
+    /// Close the innermost open group
+    pub fn end_group(&mut self) {
+        if self.group_depth == 0 {
+            return;
+        }
+        self.group_depth -= 1;
+        if self.group_depth == 0 {
+            self.group_name = None;
+            self.group_started = false;
+        }
+    }
+
+    /// Whether an explicit group is currently open
+    pub fn in_group(&self) -> bool {
+        self.group_depth > 0
     }
 
     /// Push an operation pair to the undo stack
@@ -39,11 +260,119 @@ impl UndoStack {
     /// When a new operation is executed, push (original, inverse) to undo stack
     /// and clear the redo stack.
     pub fn push(&mut self, original: Operation, inverse: Operation) {
+        self.push_at(original, inverse, chrono::Utc::now().timestamp_millis());
+    }
+
+    /// Push an operation undone by several inverses (see `UndoAction::Composite`)
+    ///
+    /// `inverses` are in execution order, i.e. as returned by `UndoAction::into_operations`.
+    pub fn push_composite(&mut self, original: Operation, inverses: Vec<Operation>) {
+        self.push_composite_at(original, inverses, chrono::Utc::now().timestamp_millis());
+    }
+
+    /// Push an operation pair executed at `timestamp_ms`, applying coalescing rules
+    pub fn push_at(&mut self, original: Operation, inverse: Operation, timestamp_ms: i64) {
+        self.push_composite_at(original, vec![inverse], timestamp_ms);
+    }
+
+    /// Push an operation with its inverses executed at `timestamp_ms`
+    ///
+    /// Only operations with a single inverse are coalesced.
+    pub fn push_composite_at(
+        &mut self,
+        original: Operation,
+        inverses: Vec<Operation>,
+        timestamp_ms: i64,
+    ) {
+        self.push_composite_from_at(original, inverses, None, timestamp_ms);
+    }
+
+    /// Push an operation with its inverses, remembering who executed it
+    pub fn push_composite_from(
+        &mut self,
+        original: Operation,
+        inverses: Vec<Operation>,
+        source: Option<ChangeSource>,
+    ) {
+        self.push_composite_from_at(
+            original,
+            inverses,
+            source,
+            chrono::Utc::now().timestamp_millis(),
+        );
+    }
+
+    /// Push an operation executed by `source` at `timestamp_ms`, applying coalescing
+    /// and grouping rules to steps from the same source only
+    pub fn push_composite_from_at(
+        &mut self,
+        original: Operation,
+        inverses: Vec<Operation>,
+        source: Option<ChangeSource>,
+        timestamp_ms: i64,
+    ) {
         // Clear redo stack when new operation is executed
         self.redo.clear();
 
+        let record = UndoRecord {
+            executed: vec![original],
+            inverse: inverses,
+        };
+
+        // Explicit group: append to the group's entry
+        if self.group_depth > 0 && self.group_started {
+            if let Some(top) = self.undo.last_mut().filter(|top| top.source == source) {
+                top.ops.push(record);
+                top.updated_at = timestamp_ms;
+                return;
+            }
+        }
+
+        // Coalesce with the previous edit of the same field: keep the oldest inverse
+        // (restores the value before the burst of edits) and the newest original.
+        // Text deltas are composed instead, as each one only covers part of the text.
+        let key = record
+            .as_pair()
+            .and_then(|(original, _)| coalesce_key(original));
+        if self.group_depth == 0 && self.coalesce_window_ms > 0 && key.is_some() {
+            if let Some(top) = self.undo.last_mut() {
+                if top.coalesce_key == key
+                    && top.source == source
+                    && top.ops.len() == 1
+                    && timestamp_ms - top.updated_at <= self.coalesce_window_ms
+                {
+                    let original = &record.executed[0];
+                    if original.op_name != "apply_text_delta" {
+                        top.ops[0].executed = record.executed;
+                        top.updated_at = timestamp_ms;
+                        return;
+                    }
+                    if let Some(composed) = top.ops[0]
+                        .as_pair()
+                        .and_then(|pair| compose_text_edits(pair, original))
+                    {
+                        top.ops[0] = composed;
+                        top.updated_at = timestamp_ms;
+                        return;
+                    }
+                }
+            }
+        }
+
         // Add to undo stack
-        self.undo.push((original, inverse));
+        let in_group = self.group_depth > 0;
+        self.undo.push(UndoEntry {
+            ops: vec![record],
+            coalesce_key: if in_group { None } else { key },
+            updated_at: timestamp_ms,
+            display_name: if in_group {
+                self.group_name.clone()
+            } else {
+                None
+            },
+            source,
+        });
+        self.group_started = in_group;
 
         // Trim if over max size
         if self.undo.len() > self.max_size {
@@ -51,26 +380,44 @@ impl UndoStack {
         }
     }
 
-    /// Pop an operation pair from undo stack for undo operation
+    /// Pop a step from undo stack for undo operation
+    ///
+    /// Returns the inverse operations that should be executed (in order) to undo.
+    /// Moves the step to redo stack. Closes any open group.
+    pub fn pop_for_undo(&mut self) -> Option<Vec<Operation>> {
+        self.close_groups();
+        let (to_execute, redo_entry) = self.undo.pop()?.flip();
+        // Move to redo stack (will be updated with new inverses after execution)
+        self.redo.push(redo_entry);
+        Some(to_execute)
+    }
+
+    /// Pop the newest step whose source matches `filter` (see `ChangeSource::matches`)
     ///
-    /// Returns the inverse operation that should be executed to undo.
-    /// Moves the pair to redo stack.
-    pub fn pop_for_undo(&mut self) -> Option<Operation> {
-        let (original, inverse) = self.undo.pop()?;
-        // Move to redo stack (will be updated with new inverse after execution)
-        self.redo.push((inverse.clone(), original));
-        Some(inverse)
+    /// Steps with an unknown source only match an empty filter. Like `pop_for_undo`,
+    /// returns the inverse operations to execute and moves the step to the redo stack;
+    /// newer steps from other sources stay where they are.
+    pub fn pop_for_undo_from(&mut self, filter: &ChangeSource) -> Option<Vec<Operation>> {
+        self.close_groups();
+        let index = self
+            .undo
+            .iter()
+            .rposition(|entry| entry.source.clone().unwrap_or_default().matches(filter))?;
+        let (to_execute, redo_entry) = self.undo.remove(index).flip();
+        self.redo.push(redo_entry);
+        Some(to_execute)
     }
 
-    /// Pop an operation pair from redo stack for redo operation
+    /// Pop a step from redo stack for redo operation
     ///
-    /// Returns the operation that should be executed to redo.
-    /// Moves the pair back to undo stack.
-    pub fn pop_for_redo(&mut self) -> Option<Operation> {
-        let (inverse, new_inverse) = self.redo.pop()?;
-        // Move back to undo stack (will be updated with new inverse after execution)
-        self.undo.push((inverse.clone(), new_inverse.clone()));
-        Some(new_inverse)
+    /// Returns the operations that should be executed (in order) to redo.
+    /// Moves the step back to undo stack.
+    pub fn pop_for_redo(&mut self) -> Option<Vec<Operation>> {
+        self.close_groups();
+        let (to_execute, undo_entry) = self.redo.pop()?.flip();
+        // Move back to undo stack (will be updated with new inverses after execution)
+        self.undo.push(undo_entry);
+        Some(to_execute)
     }
 
     /// Check if undo is available
@@ -88,41 +435,65 @@ impl UndoStack {
         self.redo.clear();
     }
 
-    /// Get the display name of the next undo operation (for UI)
+    /// Forget all undo and redo steps (e.g. after the database was replaced)
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group_depth = 0;
+        self.group_name = None;
+        self.group_started = false;
+    }
+
+    /// Get the display name of the next undo step (for UI)
     pub fn next_undo_display_name(&self) -> Option<&str> {
-        self.undo
-            .last()
-            .map(|(_, inverse)| inverse.display_name.as_str())
+        self.undo.last().and_then(UndoEntry::display_name)
     }
 
-    /// Get the display name of the next redo operation (for UI)
+    /// Get the display name of the next redo step (for UI)
     pub fn next_redo_display_name(&self) -> Option<&str> {
-        self.redo
-            .last()
-            .map(|(_, new_inverse)| new_inverse.display_name.as_str())
+        self.redo.last().and_then(UndoEntry::display_name)
+    }
+
+    /// Update the top of the redo stack with new inverse operations
+    ///
+    /// Called after executing the `index`-th operation returned by `pop_for_undo`
+    /// to update the redo stack with the inverse returned from execution (in
+    /// execution order, see `UndoAction::into_operations`).
+    pub fn update_redo_top(&mut self, index: usize, new_inverse: Vec<Operation>) {
+        if let Some(entry) = self.redo.last_mut() {
+            entry.update_inverse(index, new_inverse);
+        }
     }
 
-    /// Update the top of the redo stack with a new inverse operation
+    /// Update the top of the undo stack with new inverse operations
     ///
-    /// Called after executing an undo operation to update the redo stack
-    /// with the new inverse operation returned from execution.
-    pub fn update_redo_top(&mut self, new_inverse: Operation) {
-        if let Some((inverse, _original)) = self.redo.last_mut() {
-            // Update the second element (new_inverse) with the new inverse from execution
-            *self.redo.last_mut().unwrap() = (inverse.clone(), new_inverse);
+    /// Called after executing the `index`-th operation returned by `pop_for_redo`
+    /// to update the undo stack with the inverse returned from execution.
+    pub fn update_undo_top(&mut self, index: usize, new_inverse: Vec<Operation>) {
+        if let Some(entry) = self.undo.last_mut() {
+            entry.update_inverse(index, new_inverse);
         }
     }
 
-    /// Update the top of the undo stack with a new inverse operation
+    /// Replace a temporary entity ID with its remote ID in all undo and redo steps
     ///
-    /// Called after executing a redo operation to update the undo stack
-    /// with the new inverse operation returned from execution.
-    pub fn update_undo_top(&mut self, new_inverse: Operation) {
-        if let Some((_original, inverse)) = self.undo.last_mut() {
-            // Update the second element (inverse) with the new inverse from execution
-            *inverse = new_inverse;
+    /// Called when an optimistic create completes, so undoing or redoing steps
+    /// recorded against the temporary ID targets the real entity.
+    pub fn remap_id(&mut self, temp_id: &str, remote_id: &str) {
+        for entry in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            for record in entry.ops.iter_mut() {
+                for op in record.executed.iter_mut().chain(record.inverse.iter_mut()) {
+                    remap_operation_id(op, temp_id, remote_id);
+                }
+            }
         }
     }
+
+    fn close_groups(&mut self) {
+        self.group_depth = 0;
+        self.group_name = None;
+        self.group_started = false;
+    }
 }
 
 impl Default for UndoStack {
@@ -130,3 +501,253 @@ impl Default for UndoStack {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn set_field(id: &str, field: &str, value: &str) -> Operation {
+        Operation::new(
+            "blocks",
+            "set_field",
+            "Edit",
+            HashMap::from([
+                ("id".to_string(), Value::String(id.to_string())),
+                ("field".to_string(), Value::String(field.to_string())),
+                ("value".to_string(), Value::String(value.to_string())),
+            ]),
+        )
+    }
+
+    fn value_of(op: &Operation) -> &str {
+        op.params.get("value").and_then(Value::as_string).unwrap()
+    }
+
+    fn text_edit(id: &str, delta: &TextDelta) -> (Operation, Operation) {
+        let op = |delta: &TextDelta| {
+            Operation::new(
+                "blocks",
+                "apply_text_delta",
+                "Edit text",
+                HashMap::from([
+                    ("id".to_string(), Value::String(id.to_string())),
+                    ("delta".to_string(), Value::String(delta.to_json())),
+                ]),
+            )
+        };
+        (op(delta), op(&delta.invert()))
+    }
+
+    #[test]
+    fn test_typing_coalesces_into_one_step() {
+        let mut stack = UndoStack::new();
+        stack.push_at(
+            set_field("b1", "content", "h"),
+            set_field("b1", "content", ""),
+            0,
+        );
+        stack.push_at(
+            set_field("b1", "content", "he"),
+            set_field("b1", "content", "h"),
+            300,
+        );
+        stack.push_at(
+            set_field("b1", "content", "hey"),
+            set_field("b1", "content", "he"),
+            600,
+        );
+
+        let undo = stack.pop_for_undo().unwrap();
+        assert_eq!(undo.len(), 1);
+        assert_eq!(value_of(&undo[0]), "");
+        assert!(!stack.can_undo());
+
+        let redo = stack.pop_for_redo().unwrap();
+        assert_eq!(value_of(&redo[0]), "hey");
+    }
+
+    #[test]
+    fn test_coalescing_respects_window_and_field() {
+        let mut stack = UndoStack::new();
+        stack.push_at(
+            set_field("b1", "content", "a"),
+            set_field("b1", "content", ""),
+            0,
+        );
+        // Outside the window
+        stack.push_at(
+            set_field("b1", "content", "ab"),
+            set_field("b1", "content", "a"),
+            5000,
+        );
+        // Different field
+        stack.push_at(
+            set_field("b1", "title", "t"),
+            set_field("b1", "title", ""),
+            5100,
+        );
+
+        assert_eq!(value_of(&stack.pop_for_undo().unwrap()[0]), "");
+        assert_eq!(value_of(&stack.pop_for_undo().unwrap()[0]), "a");
+        assert_eq!(value_of(&stack.pop_for_undo().unwrap()[0]), "");
+        assert!(!stack.can_undo());
+    }
+
+    #[test]
+    fn test_text_deltas_compose_per_word() {
+        let mut stack = UndoStack::new();
+        let mut text = String::new();
+        for (i, c) in "hi yo".chars().enumerate() {
+            let delta = TextDelta::insert(i, c.to_string());
+            text = delta.apply(&text).unwrap();
+            let (original, inverse) = text_edit("b1", &delta);
+            stack.push_at(original, inverse, i as i64 * 100);
+        }
+        // Backspace the last character
+        let delta = TextDelta::delete(4, "o");
+        text = delta.apply(&text).unwrap();
+        let (original, inverse) = text_edit("b1", &delta);
+        stack.push_at(original, inverse, 600);
+        assert_eq!(text, "hi y");
+
+        // " y" is one step, "hi" another
+        let undo = stack.pop_for_undo().unwrap();
+        assert_eq!(undo.len(), 1);
+        text = text_delta_param(&undo[0]).unwrap().apply(&text).unwrap();
+        assert_eq!(text, "hi");
+
+        let undo = stack.pop_for_undo().unwrap();
+        text = text_delta_param(&undo[0]).unwrap().apply(&text).unwrap();
+        assert_eq!(text, "");
+        assert!(!stack.can_undo());
+
+        let redo = stack.pop_for_redo().unwrap();
+        text = text_delta_param(&redo[0]).unwrap().apply(&text).unwrap();
+        assert_eq!(text, "hi");
+    }
+
+    #[test]
+    fn test_explicit_group_undoes_in_reverse_order() {
+        let mut stack = UndoStack::new();
+        stack.set_coalesce_window_ms(0);
+        stack.begin_group(Some("Paste"));
+        stack.push_at(
+            set_field("b1", "content", "x"),
+            set_field("b1", "content", "1"),
+            0,
+        );
+        stack.begin_group(None);
+        stack.push_at(
+            set_field("b2", "content", "y"),
+            set_field("b2", "content", "2"),
+            10,
+        );
+        stack.end_group();
+        stack.push_at(
+            set_field("b3", "content", "z"),
+            set_field("b3", "content", "3"),
+            20,
+        );
+        stack.end_group();
+
+        assert_eq!(stack.next_undo_display_name(), Some("Paste"));
+
+        let undo = stack.pop_for_undo().unwrap();
+        let values: Vec<&str> = undo.iter().map(value_of).collect();
+        assert_eq!(values, vec!["3", "2", "1"]);
+
+        let redo = stack.pop_for_redo().unwrap();
+        let values: Vec<&str> = redo.iter().map(value_of).collect();
+        assert_eq!(values, vec!["x", "y", "z"]);
+
+        // The next push after the group starts a new step
+        stack.push_at(
+            set_field("b4", "content", "w"),
+            set_field("b4", "content", "4"),
+            30,
+        );
+        assert_eq!(stack.pop_for_undo().unwrap().len(), 1);
+        assert_eq!(stack.pop_for_undo().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_composite_inverse_undoes_and_redoes_as_one_step() {
+        let mut stack = UndoStack::new();
+        stack.push_composite_at(
+            set_field("b1", "parent_id", "p2"),
+            vec![
+                set_field("b1", "parent_id", "p1"),
+                set_field("b1", "sort_key", "a0"),
+            ],
+            0,
+        );
+        // Not coalesced with the composite step
+        stack.push_at(
+            set_field("b1", "parent_id", "p3"),
+            set_field("b1", "parent_id", "p2"),
+            100,
+        );
+        stack.pop_for_undo().unwrap();
+
+        let undo = stack.pop_for_undo().unwrap();
+        let values: Vec<&str> = undo.iter().map(value_of).collect();
+        assert_eq!(values, vec!["p1", "a0"]);
+
+        // Inverses of a multi-operation record keep their placeholder
+        stack.update_redo_top(0, vec![set_field("b1", "parent_id", "p2")]);
+        let redo = stack.pop_for_redo().unwrap();
+        assert_eq!(redo.len(), 1);
+        assert_eq!(value_of(&redo[0]), "p2");
+
+        stack.update_undo_top(
+            0,
+            vec![
+                set_field("b1", "parent_id", "p1"),
+                set_field("b1", "sort_key", "a1"),
+            ],
+        );
+        let undo = stack.pop_for_undo().unwrap();
+        let values: Vec<&str> = undo.iter().map(value_of).collect();
+        assert_eq!(values, vec!["p1", "a1"]);
+    }
+
+    #[test]
+    fn test_undo_filtered_by_source() {
+        let mut stack = UndoStack::new();
+        let user = ChangeSource::default().with_frontend("tui");
+        let agent = ChangeSource::default().with_agent("mcp:assistant");
+        stack.push_composite_from_at(
+            set_field("b1", "content", "user"),
+            vec![set_field("b1", "content", "1")],
+            Some(user.clone()),
+            0,
+        );
+        // Same field within the window, but another source: not coalesced
+        stack.push_composite_from_at(
+            set_field("b1", "content", "agent"),
+            vec![set_field("b1", "content", "user")],
+            Some(agent.clone()),
+            100,
+        );
+        stack.push_composite_from_at(
+            set_field("b2", "content", "user"),
+            vec![set_field("b2", "content", "2")],
+            Some(user.clone()),
+            200,
+        );
+
+        let undo = stack
+            .pop_for_undo_from(&ChangeSource::default().with_agent("mcp:assistant"))
+            .unwrap();
+        assert_eq!(value_of(&undo[0]), "user");
+        assert!(stack.pop_for_undo_from(&agent).is_none());
+
+        // The user's newest step is still on top
+        let undo = stack.pop_for_undo_from(&user).unwrap();
+        assert_eq!(value_of(&undo[0]), "2");
+        let undo = stack.pop_for_undo().unwrap();
+        assert_eq!(value_of(&undo[0]), "1");
+        assert!(!stack.can_undo());
+    }
+}
@@ -0,0 +1,126 @@
+//! Operation usage statistics entity.
+//!
+//! The `OperationUsageEntry` entity aggregates how often each operation is invoked,
+//! how long it takes and how often it fails. Only the entity and operation names are
+//! recorded - never parameters - so the table contains no user content.
+
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+
+/// Aggregated local usage statistics for a single operation.
+///
+/// One row exists per `(entity_name, op_name)` pair. Rows are updated in place
+/// every time the operation is executed.
+///
+/// Table name: `operation_usage`
+#[derive(Debug, Clone, Serialize, Deserialize, Entity)]
+#[entity(name = "operation_usage", short_name = "usage")]
+pub struct OperationUsageEntry {
+    /// Primary key: `"{entity_name}.{op_name}"`
+    #[primary_key]
+    pub id: String,
+
+    /// Entity the operation was dispatched to
+    #[indexed]
+    pub entity_name: String,
+
+    /// Operation name
+    pub op_name: String,
+
+    /// Number of times the operation was invoked (successful or not)
+    pub invocation_count: i64,
+
+    /// Number of invocations that returned an error
+    pub failure_count: i64,
+
+    /// Sum of all invocation latencies in milliseconds
+    pub total_latency_ms: i64,
+
+    /// Slowest observed invocation in milliseconds
+    pub max_latency_ms: i64,
+
+    /// When the operation was last invoked (Unix timestamp in milliseconds)
+    #[indexed]
+    pub last_invoked_at: i64,
+}
+
+impl OperationUsageEntry {
+    /// Build the primary key for an operation
+    pub fn key(entity_name: &str, op_name: &str) -> String {
+        format!("{}.{}", entity_name, op_name)
+    }
+
+    /// Create an empty entry for an operation
+    pub fn new(entity_name: impl Into<String>, op_name: impl Into<String>) -> Self {
+        let entity_name = entity_name.into();
+        let op_name = op_name.into();
+        Self {
+            id: Self::key(&entity_name, &op_name),
+            entity_name,
+            op_name,
+            invocation_count: 0,
+            failure_count: 0,
+            total_latency_ms: 0,
+            max_latency_ms: 0,
+            last_invoked_at: 0,
+        }
+    }
+
+    /// Fold a single invocation into this entry
+    pub fn record(&mut self, latency_ms: i64, success: bool, invoked_at: i64) {
+        self.invocation_count += 1;
+        if !success {
+            self.failure_count += 1;
+        }
+        self.total_latency_ms += latency_ms;
+        self.max_latency_ms = self.max_latency_ms.max(latency_ms);
+        self.last_invoked_at = self.last_invoked_at.max(invoked_at);
+    }
+
+    /// Average latency in milliseconds (0.0 if never invoked)
+    pub fn avg_latency_ms(&self) -> f64 {
+        if self.invocation_count == 0 {
+            0.0
+        } else {
+            self.total_latency_ms as f64 / self.invocation_count as f64
+        }
+    }
+
+    /// Fraction of invocations that failed, in `[0.0, 1.0]`
+    pub fn failure_rate(&self) -> f64 {
+        if self.invocation_count == 0 {
+            0.0
+        } else {
+            self.failure_count as f64 / self.invocation_count as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_aggregates_invocations() {
+        let mut entry = OperationUsageEntry::new("todoist-task", "set_completion");
+        assert_eq!(entry.id, "todoist-task.set_completion");
+
+        entry.record(10, true, 1_000);
+        entry.record(30, false, 2_000);
+
+        assert_eq!(entry.invocation_count, 2);
+        assert_eq!(entry.failure_count, 1);
+        assert_eq!(entry.total_latency_ms, 40);
+        assert_eq!(entry.max_latency_ms, 30);
+        assert_eq!(entry.last_invoked_at, 2_000);
+        assert_eq!(entry.avg_latency_ms(), 20.0);
+        assert_eq!(entry.failure_rate(), 0.5);
+    }
+
+    #[test]
+    fn test_empty_entry_rates() {
+        let entry = OperationUsageEntry::new("blocks", "indent");
+        assert_eq!(entry.avg_latency_ms(), 0.0);
+        assert_eq!(entry.failure_rate(), 0.0);
+    }
+}
@@ -0,0 +1,66 @@
+//! Persistent UI state of views.
+//!
+//! A `ViewStateEntry` holds the UI state of one entity in one view: whether its
+//! tree node is collapsed, whether it is selected, and whether it is the row the
+//! view was scrolled to. Entries are local to the device, like usage statistics,
+//! so collapsing a node never causes sync traffic.
+
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+
+/// UI state of an entity in a view.
+///
+/// Table name: `view_states`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Entity)]
+#[entity(name = "view_states", short_name = "view_state")]
+pub struct ViewStateEntry {
+    /// Primary key: `"{view_id}:{entity_id}"`
+    #[primary_key]
+    pub id: String,
+
+    /// View the state belongs to (the `view_id:` of its render expression)
+    #[indexed]
+    pub view_id: String,
+
+    /// Id of the row (e.g. block id)
+    pub entity_id: String,
+
+    /// Tree node is collapsed
+    pub collapsed: bool,
+
+    /// Row is selected
+    pub selected: bool,
+
+    /// Row was the first visible one, to restore the scroll position
+    pub scroll_anchor: bool,
+
+    /// Unix timestamp in milliseconds of the last change
+    pub updated_at: i64,
+}
+
+impl ViewStateEntry {
+    /// Build the primary key for an entity in a view
+    pub fn key(view_id: &str, entity_id: &str) -> String {
+        format!("{}:{}", view_id, entity_id)
+    }
+
+    /// Entry without any state
+    pub fn new(view_id: impl Into<String>, entity_id: impl Into<String>, updated_at: i64) -> Self {
+        let view_id = view_id.into();
+        let entity_id = entity_id.into();
+        Self {
+            id: Self::key(&view_id, &entity_id),
+            view_id,
+            entity_id,
+            collapsed: false,
+            selected: false,
+            scroll_anchor: false,
+            updated_at,
+        }
+    }
+
+    /// Whether the entry holds any state (entries without state aren't stored)
+    pub fn is_empty(&self) -> bool {
+        !(self.collapsed || self.selected || self.scroll_anchor)
+    }
+}
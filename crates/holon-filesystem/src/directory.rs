@@ -12,8 +12,8 @@ use tokio::sync::broadcast;
 use tokio_stream::Stream;
 
 use holon::core::datasource::{
-    CrudOperations, DataSource, OperationDescriptor, OperationProvider, OperationRegistry,
-    RenameOperations, Result, UndoAction,
+    CrudOperations, DataSource, HolonResult, OperationDescriptor, OperationProvider,
+    OperationRegistry, RenameOperations, Result, UndoAction,
 };
 use holon::storage::types::StorageEntity;
 use holon_api::streaming::ChangeNotifications;
@@ -25,7 +25,11 @@ pub const ROOT_ID: &str = "null";
 
 /// Directory - represents a folder in a directory hierarchy
 #[derive(Debug, Clone, Serialize, Deserialize, Entity)]
-#[entity(name = "directories", short_name = "dir")]
+#[entity(
+    name = "directories",
+    short_name = "dir",
+    operations(CrudOperations, BlockOperations, RenameOperations, MoveOperations)
+)]
 pub struct Directory {
     #[primary_key]
     #[indexed]
@@ -88,65 +92,6 @@ impl holon::core::datasource::BlockEntity for Directory {
     }
 }
 
-impl holon::core::datasource::OperationRegistry for Directory {
-    fn all_operations() -> Vec<holon::core::datasource::OperationDescriptor> {
-        let entity_name = Self::entity_name();
-        let short_name = Self::short_name().expect("Directory must have short_name");
-        let table = entity_name;
-        let id_column = "id";
-
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            use holon::core::datasource::{
-                __operations_block_operations, __operations_crud_operations,
-                __operations_move_operations, __operations_rename_operations,
-            };
-            __operations_crud_operations::crud_operations(entity_name, short_name, table, id_column)
-                .into_iter()
-                .chain(
-                    __operations_block_operations::block_operations(
-                        entity_name,
-                        short_name,
-                        table,
-                        id_column,
-                    )
-                    .into_iter(),
-                )
-                .chain(
-                    __operations_rename_operations::rename_operations(
-                        entity_name,
-                        short_name,
-                        table,
-                        id_column,
-                    )
-                    .into_iter(),
-                )
-                .chain(
-                    __operations_move_operations::move_operations(
-                        entity_name,
-                        short_name,
-                        table,
-                        id_column,
-                    )
-                    .into_iter(),
-                )
-                .collect()
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            Vec::new()
-        }
-    }
-
-    fn entity_name() -> &'static str {
-        "directories"
-    }
-
-    fn short_name() -> Option<&'static str> {
-        Directory::short_name()
-    }
-}
-
 /// Changes wrapped with metadata for atomic sync token updates
 pub type ChangesWithMetadata<T> = WithMetadata<Vec<Change<T>>, BatchMetadata>;
 
@@ -229,16 +174,16 @@ impl<P: DirectoryChangeProvider> DataSource<Directory> for DirectoryDataSource<P
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl<P: DirectoryChangeProvider> CrudOperations<Directory> for DirectoryDataSource<P> {
-    async fn set_field(&self, _id: &str, _field: &str, _value: Value) -> Result<UndoAction> {
+    async fn set_field(&self, _id: &str, _field: &str, _value: Value) -> HolonResult<UndoAction> {
         // Directory modifications not supported yet
         Err("Directory field updates not implemented".into())
     }
 
-    async fn create(&self, _fields: HashMap<String, Value>) -> Result<(String, UndoAction)> {
+    async fn create(&self, _fields: HashMap<String, Value>) -> HolonResult<(String, UndoAction)> {
         Err("Directory creation not implemented".into())
     }
 
-    async fn delete(&self, _id: &str) -> Result<UndoAction> {
+    async fn delete(&self, _id: &str) -> HolonResult<UndoAction> {
         Err("Directory deletion not implemented".into())
     }
 }
@@ -247,7 +192,7 @@ impl<P: DirectoryChangeProvider> CrudOperations<Directory> for DirectoryDataSour
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl<P: DirectoryChangeProvider> RenameOperations<Directory> for DirectoryDataSource<P> {
     async fn rename(&self, id: &str, name: String) -> Result<UndoAction> {
-        self.set_field(id, "name", Value::String(name)).await
+        Ok(self.set_field(id, "name", Value::String(name)).await?)
     }
 }
 
@@ -0,0 +1,242 @@
+//! Dependency Injection module for local iCalendar files
+//!
+//! This module provides DI registration for iCalendar services using ferrous-di.
+
+use ferrous_di::{DiResult, Lifetime, Resolver, ServiceCollection, ServiceModule};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::ical_datasource::{IcalEventDataSource, IcalOccurrenceDataSource};
+use crate::models::{CalendarEvent, CalendarOccurrence};
+use crate::IcalSyncProvider;
+use holon::core::datasource::{IdStrategy, OperationProvider, SyncTokenStore, SyncableProvider};
+use holon::core::queryable_cache::QueryableCache;
+use holon::storage::turso::TursoBackend;
+
+/// Configuration for iCalendar integration
+#[derive(Clone, Debug)]
+pub struct IcalConfig {
+    /// An .ics file, or a directory searched recursively for .ics files
+    pub path: PathBuf,
+    /// How often files are checked for external changes (None disables watching)
+    pub watch_interval: Option<Duration>,
+    /// Days before today recurring entries are expanded from
+    pub horizon_past_days: i64,
+    /// Days after today recurring entries are expanded to
+    pub horizon_future_days: i64,
+    /// How UIDs are generated for new entries and entries without one
+    pub id_strategy: IdStrategy,
+}
+
+impl IcalConfig {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            watch_interval: Some(Duration::from_secs(5)),
+            horizon_past_days: 30,
+            horizon_future_days: 365,
+            id_strategy: IdStrategy::default(),
+        }
+    }
+
+    pub fn with_watch_interval(mut self, interval: Option<Duration>) -> Self {
+        self.watch_interval = interval;
+        self
+    }
+
+    pub fn with_horizon_days(mut self, past: i64, future: i64) -> Self {
+        self.horizon_past_days = past;
+        self.horizon_future_days = future;
+        self
+    }
+
+    pub fn with_id_strategy(mut self, id_strategy: IdStrategy) -> Self {
+        self.id_strategy = id_strategy;
+        self
+    }
+}
+
+/// ServiceModule for iCalendar integration
+///
+/// Registers iCalendar services in the DI container:
+/// - `IcalSyncProvider` - Provider for syncing .ics files (and watching them)
+/// - `QueryableCache` for events and their occurrences
+pub struct IcalModule;
+
+impl ServiceModule for IcalModule {
+    fn register_services(self, services: &mut ServiceCollection) -> DiResult<()> {
+        use tracing::info;
+
+        info!("[IcalModule] register_services called");
+
+        // Register IcalSyncProvider as a factory
+        services.add_singleton_factory::<IcalSyncProvider, _>(|resolver| {
+            let config = resolver
+                .get::<IcalConfig>()
+                .unwrap_or_else(|e| panic!("[IcalModule] IcalConfig not found in DI: {}", e));
+            let token_store = resolver
+                .get_trait::<dyn SyncTokenStore>()
+                .unwrap_or_else(|e| panic!("[IcalModule] SyncTokenStore not found in DI: {:?}", e));
+
+            info!(
+                "[IcalModule] Creating IcalSyncProvider for: {}",
+                config.path.display()
+            );
+            IcalSyncProvider::new(config.path.clone(), token_store)
+                .with_id_generator(config.id_strategy.generator())
+                .with_horizon(
+                    chrono::Duration::days(config.horizon_past_days),
+                    chrono::Duration::days(config.horizon_future_days),
+                )
+        });
+
+        // Register SyncableProvider trait implementation
+        services.add_trait_factory::<dyn SyncableProvider, _>(Lifetime::Singleton, |resolver| {
+            let sync_provider = resolver.get_required::<IcalSyncProvider>();
+            sync_provider.clone() as Arc<dyn SyncableProvider>
+        });
+
+        // Register OperationProvider for sync operations
+        services.add_trait_factory::<dyn OperationProvider, _>(Lifetime::Singleton, |resolver| {
+            let sync_provider = resolver.get_required::<IcalSyncProvider>();
+            sync_provider.clone() as Arc<dyn OperationProvider>
+        });
+
+        // Register QueryableCache for CalendarEvent
+        services.add_singleton_factory::<QueryableCache<IcalEventDataSource, CalendarEvent>, _>(
+            |resolver| {
+                let backend = Resolver::get_required::<RwLock<TursoBackend>>(resolver);
+                let sync_provider = resolver.get_required::<IcalSyncProvider>();
+
+                #[cfg(not(target_arch = "wasm32"))]
+                let cache = std::thread::spawn(move || {
+                    let rt =
+                        tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+                    rt.block_on(async {
+                        let datasource = IcalEventDataSource::new(sync_provider);
+                        QueryableCache::new_with_backend(datasource, backend.clone())
+                            .await
+                            .expect("Failed to create QueryableCache<CalendarEvent>")
+                    })
+                })
+                .join()
+                .expect("Thread panicked while creating QueryableCache<CalendarEvent>");
+
+                #[cfg(target_arch = "wasm32")]
+                let cache = {
+                    let rt = tokio::runtime::Handle::current();
+                    rt.block_on(async {
+                        let datasource = IcalEventDataSource::new(sync_provider);
+                        QueryableCache::new_with_backend(datasource, backend.clone())
+                            .await
+                            .expect("Failed to create QueryableCache<CalendarEvent>")
+                    })
+                };
+
+                cache
+            },
+        );
+
+        // Register QueryableCache for CalendarOccurrence
+        services.add_singleton_factory::<
+            QueryableCache<IcalOccurrenceDataSource, CalendarOccurrence>,
+            _,
+        >(|resolver| {
+            let backend = Resolver::get_required::<RwLock<TursoBackend>>(resolver);
+            let sync_provider = resolver.get_required::<IcalSyncProvider>();
+
+            #[cfg(not(target_arch = "wasm32"))]
+            let cache = std::thread::spawn(move || {
+                let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+                rt.block_on(async {
+                    let datasource = IcalOccurrenceDataSource::new(sync_provider);
+                    QueryableCache::new_with_backend(datasource, backend.clone())
+                        .await
+                        .expect("Failed to create QueryableCache<CalendarOccurrence>")
+                })
+            })
+            .join()
+            .expect("Thread panicked while creating QueryableCache<CalendarOccurrence>");
+
+            #[cfg(target_arch = "wasm32")]
+            let cache = {
+                let rt = tokio::runtime::Handle::current();
+                rt.block_on(async {
+                    let datasource = IcalOccurrenceDataSource::new(sync_provider);
+                    QueryableCache::new_with_backend(datasource, backend.clone())
+                        .await
+                        .expect("Failed to create QueryableCache<CalendarOccurrence>")
+                })
+            };
+
+            cache
+        });
+
+        // Register event cache as OperationProvider and set up sequential stream processing
+        services.add_trait_factory::<dyn OperationProvider, _>(Lifetime::Singleton, |resolver| {
+            use tracing::error;
+
+            let event_cache =
+                resolver.get_required::<QueryableCache<IcalEventDataSource, CalendarEvent>>();
+            let occurrence_cache = resolver
+                .get_required::<QueryableCache<IcalOccurrenceDataSource, CalendarOccurrence>>();
+            let sync_provider = resolver.get_required::<IcalSyncProvider>();
+
+            let mut event_rx = sync_provider.subscribe_events();
+            let mut occurrence_rx = sync_provider.subscribe_occurrences();
+
+            // Events before occurrences, so occurrences never reference a missing event
+            let event_cache_clone = event_cache.clone();
+            tokio::spawn(async move {
+                let event_cache = event_cache_clone;
+                loop {
+                    match event_rx.recv().await {
+                        Ok(batch) => {
+                            let sync_token = batch.metadata.sync_token.as_ref();
+                            if let Err(e) = event_cache.apply_batch(&batch.inner, sync_token).await
+                            {
+                                error!("[Ical] Error applying event batch: {}", e);
+                                continue;
+                            }
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                            error!("[Ical] Event stream lagged by {} messages", n);
+                        }
+                    }
+
+                    match occurrence_rx.recv().await {
+                        Ok(batch) => {
+                            let sync_token = batch.metadata.sync_token.as_ref();
+                            if let Err(e) =
+                                occurrence_cache.apply_batch(&batch.inner, sync_token).await
+                            {
+                                error!("[Ical] Error applying occurrence batch: {}", e);
+                            }
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                            error!("[Ical] Occurrence stream lagged by {} messages", n);
+                        }
+                    }
+                }
+            });
+
+            // Pick up edits made by other calendar applications
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(interval) = resolver
+                .get::<IcalConfig>()
+                .ok()
+                .and_then(|config| config.watch_interval)
+            {
+                sync_provider.clone().spawn_watcher(interval);
+            }
+
+            event_cache
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,440 @@
+//! iCalendar datasource implementations
+//!
+//! `IcalEventDataSource` implements CrudOperations by rewriting the .ics file that
+//! contains the component, then syncing so the caches pick up the change. Occurrences
+//! are derived from events and are read-only.
+
+use async_trait::async_trait;
+use futures::stream;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio_stream::Stream;
+
+use holon::core::datasource::{
+    CrudOperations, DataSource, HolonError, HolonResult, OperationDescriptor, OperationProvider,
+    OperationRegistry, Result, StreamPosition as CoreStreamPosition, SyncableProvider, UndoAction,
+};
+use holon::storage::types::StorageEntity;
+use holon_api::streaming::ChangeNotifications;
+use holon_api::{ApiError, Change, HasSchema, StreamPosition, Value};
+
+use crate::ical_sync_provider::{read_calendars, write_calendars, IcalSyncProvider};
+use crate::ics::{Component, IcalTime, Property};
+use crate::models::{apply_field, component_name, CalendarEvent, CalendarOccurrence};
+
+/// Fields `create` writes to the new component (in property order)
+const WRITABLE_FIELDS: &[&str] = &[
+    "summary",
+    "description",
+    "location",
+    "start",
+    "end",
+    "due",
+    "status",
+    "completed",
+    "priority",
+    "rrule",
+    "categories",
+];
+
+/// DataSource for CalendarEvent with CRUD backed by the .ics files
+pub struct IcalEventDataSource {
+    provider: Arc<IcalSyncProvider>,
+}
+
+impl IcalEventDataSource {
+    pub fn new(provider: Arc<IcalSyncProvider>) -> Self {
+        Self { provider }
+    }
+
+    /// File containing the component with UID `id`, with its parsed calendars
+    fn locate(&self, id: &str) -> Result<(PathBuf, Vec<Component>)> {
+        for path in self.provider.ics_files() {
+            let calendars = read_calendars(&path)?;
+            if calendars
+                .iter()
+                .flat_map(|c| c.components.iter())
+                .any(|c| c.text("UID").as_deref() == Some(id))
+            {
+                return Ok((path, calendars));
+            }
+        }
+        Err(HolonError::not_found("calendar entry", id).into())
+    }
+
+    async fn sync(&self) -> Result<()> {
+        SyncableProvider::sync(&*self.provider, CoreStreamPosition::Beginning)
+            .await
+            .map_err(|e| format!("Failed to sync: {}", e))?;
+        Ok(())
+    }
+}
+
+/// The component with UID `id` that is not a modified instance (RECURRENCE-ID)
+fn master_mut<'a>(calendars: &'a mut [Component], id: &str) -> Option<&'a mut Component> {
+    calendars
+        .iter_mut()
+        .flat_map(|c| c.components.iter_mut())
+        .find(|c| c.text("UID").as_deref() == Some(id) && c.get("RECURRENCE-ID").is_none())
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ChangeNotifications<CalendarEvent> for IcalEventDataSource {
+    async fn watch_changes_since(
+        &self,
+        _position: StreamPosition,
+    ) -> Pin<Box<dyn Stream<Item = std::result::Result<Vec<Change<CalendarEvent>>, ApiError>> + Send>>
+    {
+        let rx = self.provider.subscribe_events();
+
+        let change_stream = stream::unfold(rx, |mut rx| async move {
+            match rx.recv().await {
+                Ok(batch) => Some((Ok(batch.inner), rx)),
+                Err(broadcast::error::RecvError::Lagged(n)) => Some((
+                    Err(ApiError::InternalError {
+                        message: format!("Stream lagged by {} messages", n),
+                    }),
+                    rx,
+                )),
+                Err(broadcast::error::RecvError::Closed) => None,
+            }
+        });
+
+        Box::pin(change_stream)
+    }
+
+    async fn get_current_version(&self) -> std::result::Result<Vec<u8>, ApiError> {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl DataSource<CalendarEvent> for IcalEventDataSource {
+    async fn get_all(&self) -> Result<Vec<CalendarEvent>> {
+        let mut events = Vec::new();
+        for path in self.provider.ics_files() {
+            let file_path = path.to_string_lossy().to_string();
+            events.extend(
+                read_calendars(&path)?
+                    .iter()
+                    .flat_map(|c| c.components.iter())
+                    .filter(|c| c.get("RECURRENCE-ID").is_none())
+                    .filter_map(|c| CalendarEvent::from_component(c, &file_path)),
+            );
+        }
+        Ok(events)
+    }
+
+    async fn get_by_id(&self, id: &str) -> Result<Option<CalendarEvent>> {
+        Ok(self.get_all().await?.into_iter().find(|e| e.id == id))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl CrudOperations<CalendarEvent> for IcalEventDataSource {
+    async fn set_field(&self, id: &str, field: &str, value: Value) -> HolonResult<UndoAction> {
+        tracing::info!(
+            "[IcalEventDataSource] set_field: id={}, field={}, value={:?}",
+            id,
+            field,
+            value
+        );
+
+        let guard = self.provider.lock_writes().await;
+        let (path, mut calendars) = self.locate(id)?;
+        let file_path = path.to_string_lossy().to_string();
+        let component = master_mut(&mut calendars, id)
+            .ok_or_else(|| HolonError::not_found("calendar entry", id))?;
+
+        // Capture old value for inverse operation
+        let old_value = CalendarEvent::from_component(component, &file_path)
+            .and_then(|event| event.to_entity().get(field).cloned())
+            .unwrap_or(Value::Null);
+
+        apply_field(component, field, &value)?;
+        component.set(IcalTime::Utc(chrono::Utc::now().naive_utc()).to_property("DTSTAMP", None));
+        write_calendars(&path, &calendars)?;
+        drop(guard);
+
+        self.sync().await?;
+
+        use holon::core::datasource::__operations_crud_operation_provider;
+        Ok(UndoAction::Undo(
+            __operations_crud_operation_provider::set_field_op(
+                "", // Will be set by OperationProvider
+                id, field, old_value,
+            ),
+        ))
+    }
+
+    async fn create(&self, fields: HashMap<String, Value>) -> HolonResult<(String, UndoAction)> {
+        let kind = match fields.get("kind").and_then(Value::as_string) {
+            Some(kind) => kind.to_string(),
+            // Entries with only a due date are todos
+            None if fields.contains_key("due") && !fields.contains_key("start") => {
+                "todo".to_string()
+            }
+            None => "event".to_string(),
+        };
+        let id = fields
+            .get("id")
+            .and_then(Value::as_string)
+            .map(str::to_string)
+            .unwrap_or_else(|| self.provider.id_generator().generate());
+        let path = fields
+            .get("file_path")
+            .and_then(Value::as_string)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.provider.default_file());
+
+        let mut component = Component::new(component_name(&kind)?);
+        component.properties.push(Property::text("UID", &id));
+        component
+            .properties
+            .push(IcalTime::Utc(chrono::Utc::now().naive_utc()).to_property("DTSTAMP", None));
+        for field in WRITABLE_FIELDS {
+            if let Some(value) = fields.get(*field) {
+                apply_field(&mut component, field, value)?;
+            }
+        }
+
+        let guard = self.provider.lock_writes().await;
+        let mut calendars = read_calendars(&path)?;
+        let calendar = calendars
+            .iter_mut()
+            .find(|c| c.name == "VCALENDAR")
+            .ok_or_else(|| format!("No VCALENDAR in {}", path.display()))?;
+        calendar.components.push(component);
+        write_calendars(&path, &calendars)?;
+        drop(guard);
+
+        self.sync().await?;
+
+        // Return inverse operation (delete)
+        use holon::core::datasource::__operations_crud_operation_provider;
+        let inverse = UndoAction::Undo(__operations_crud_operation_provider::delete_op(
+            "", // Will be set by OperationProvider
+            &id,
+        ));
+        Ok((id, inverse))
+    }
+
+    async fn delete(&self, id: &str) -> HolonResult<UndoAction> {
+        let guard = self.provider.lock_writes().await;
+        let (path, mut calendars) = self.locate(id)?;
+        let file_path = path.to_string_lossy().to_string();
+
+        // Capture entity for inverse operation (create)
+        let deleted = calendars
+            .iter()
+            .flat_map(|c| c.components.iter())
+            .filter(|c| c.get("RECURRENCE-ID").is_none())
+            .find_map(|c| {
+                CalendarEvent::from_component(c, &file_path).filter(|event| event.id == id)
+            });
+
+        // Modified instances share the UID and go with the series
+        for calendar in &mut calendars {
+            calendar
+                .components
+                .retain(|c| c.text("UID").as_deref() != Some(id));
+        }
+        write_calendars(&path, &calendars)?;
+        drop(guard);
+
+        self.sync().await?;
+
+        use holon::core::datasource::__operations_crud_operation_provider;
+        Ok(match deleted {
+            Some(event) => {
+                let create_fields: HashMap<String, Value> = event
+                    .to_entity()
+                    .fields
+                    .into_iter()
+                    .filter(|(_, value)| *value != Value::Null)
+                    .collect();
+                UndoAction::Undo(__operations_crud_operation_provider::create_op(
+                    "", // Will be set by OperationProvider
+                    create_fields,
+                ))
+            }
+            None => UndoAction::Irreversible,
+        })
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationProvider for IcalEventDataSource {
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        CalendarEvent::all_operations()
+    }
+
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        params: StorageEntity,
+    ) -> Result<UndoAction> {
+        use holon::core::datasource::__operations_crud_operation_provider;
+
+        if entity_name != "ical_events" {
+            return Err(
+                format!("Expected entity_name 'ical_events', got '{}'", entity_name).into(),
+            );
+        }
+
+        let result = __operations_crud_operation_provider::dispatch_operation::<_, CalendarEvent>(
+            self, op_name, &params,
+        )
+        .await?;
+        Ok(result.with_entity_name(entity_name))
+    }
+}
+
+/// DataSource for CalendarOccurrence (read-only, derived from events)
+pub struct IcalOccurrenceDataSource {
+    provider: Arc<IcalSyncProvider>,
+}
+
+impl IcalOccurrenceDataSource {
+    pub fn new(provider: Arc<IcalSyncProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ChangeNotifications<CalendarOccurrence> for IcalOccurrenceDataSource {
+    async fn watch_changes_since(
+        &self,
+        _position: StreamPosition,
+    ) -> Pin<
+        Box<
+            dyn Stream<Item = std::result::Result<Vec<Change<CalendarOccurrence>>, ApiError>>
+                + Send,
+        >,
+    > {
+        let rx = self.provider.subscribe_occurrences();
+
+        let change_stream = stream::unfold(rx, |mut rx| async move {
+            match rx.recv().await {
+                Ok(batch) => Some((Ok(batch.inner), rx)),
+                Err(broadcast::error::RecvError::Lagged(n)) => Some((
+                    Err(ApiError::InternalError {
+                        message: format!("Stream lagged by {} messages", n),
+                    }),
+                    rx,
+                )),
+                Err(broadcast::error::RecvError::Closed) => None,
+            }
+        });
+
+        Box::pin(change_stream)
+    }
+
+    async fn get_current_version(&self) -> std::result::Result<Vec<u8>, ApiError> {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl DataSource<CalendarOccurrence> for IcalOccurrenceDataSource {
+    async fn get_all(&self) -> Result<Vec<CalendarOccurrence>> {
+        // Occurrences only exist as expanded by the sync provider
+        Ok(vec![])
+    }
+
+    async fn get_by_id(&self, _id: &str) -> Result<Option<CalendarOccurrence>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holon::core::datasource::SyncTokenStore;
+    use std::sync::RwLock;
+    use tempfile::tempdir;
+
+    struct MockSyncTokenStore {
+        tokens: RwLock<HashMap<String, CoreStreamPosition>>,
+    }
+
+    #[async_trait]
+    impl SyncTokenStore for MockSyncTokenStore {
+        async fn load_token(&self, provider_name: &str) -> Result<Option<CoreStreamPosition>> {
+            Ok(self.tokens.read().unwrap().get(provider_name).cloned())
+        }
+        async fn save_token(
+            &self,
+            provider_name: &str,
+            position: CoreStreamPosition,
+        ) -> Result<()> {
+            self.tokens
+                .write()
+                .unwrap()
+                .insert(provider_name.to_string(), position);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_crud_rewrites_file_and_keeps_unknown_properties() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("cal.ics");
+        std::fs::write(
+            &file,
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:a\r\nSUMMARY:Old\r\nDTSTART:20240101T090000\r\nX-APPLE-COLOR:red\r\nBEGIN:VALARM\r\nTRIGGER:-PT5M\r\nEND:VALARM\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+        let token_store = Arc::new(MockSyncTokenStore {
+            tokens: RwLock::new(HashMap::new()),
+        });
+        let datasource = IcalEventDataSource::new(Arc::new(IcalSyncProvider::new(
+            dir.path().to_path_buf(),
+            token_store,
+        )));
+
+        let undo = datasource
+            .set_field("a", "summary", Value::String("New".to_string()))
+            .await
+            .unwrap();
+        let content = std::fs::read_to_string(&file).unwrap();
+        assert!(content.contains("SUMMARY:New\r\n"));
+        assert!(content.contains("X-APPLE-COLOR:red\r\n"));
+        assert!(content.contains("TRIGGER:-PT5M\r\n"));
+        match undo {
+            UndoAction::Undo(op) => {
+                assert_eq!(op.params.get("value"), Some(&Value::String("Old".into())))
+            }
+            other => panic!("set_field should be undoable, got {:?}", other),
+        }
+
+        let (id, _) = datasource
+            .create(HashMap::from([
+                ("summary".to_string(), Value::String("Pay rent".into())),
+                ("due".to_string(), Value::String("2024-02-01".into())),
+            ]))
+            .await
+            .unwrap();
+        // New entries go to holon.ics when the root is a directory
+        let created = datasource.get_by_id(&id).await.unwrap().unwrap();
+        assert_eq!(created.kind, "todo");
+        assert_eq!(created.due.as_deref(), Some("2024-02-01"));
+        assert!(created.file_path.ends_with("holon.ics"));
+
+        datasource.delete("a").await.unwrap();
+        assert!(datasource.get_by_id("a").await.unwrap().is_none());
+        assert!(!std::fs::read_to_string(&file)
+            .unwrap()
+            .contains("BEGIN:VEVENT"));
+    }
+}
@@ -0,0 +1,736 @@
+//! Stream-based IcalSyncProvider
+//!
+//! Scans a single .ics file or a directory of them and emits changes on two streams:
+//! - `ical_events` - one entity per VEVENT/VTODO (per UID)
+//! - `ical_occurrences` - recurring entries expanded within a horizon around today
+//!
+//! Like the org-mode provider, change detection uses file content hashes. Components
+//! without a UID get one assigned and written back, so IDs stay stable across syncs.
+//! `spawn_watcher` polls file modification times to pick up external edits.
+
+use async_trait::async_trait;
+use chrono::{Duration, Local, NaiveDate};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use walkdir::WalkDir;
+
+use holon::core::datasource::{
+    default_id_generator, generate_sync_operation, Change, ChangeOrigin, IdGenerator,
+    OperationDescriptor, OperationProvider, Result, StreamPosition, SyncTokenStore,
+    SyncableProvider, UndoAction,
+};
+use holon::storage::types::StorageEntity;
+use holon_api::{BatchMetadata, ChangeSource, SyncTokenUpdate, WithMetadata};
+
+use crate::ics::{self, Component, IcalTime, Property};
+use crate::models::{CalendarEvent, CalendarOccurrence};
+use crate::recurrence::RRule;
+
+pub type ChangesWithMetadata<T> = WithMetadata<Vec<Change<T>>, BatchMetadata>;
+
+/// File new entries are written to when the provider watches a directory
+pub const DEFAULT_FILE_NAME: &str = "holon.ics";
+
+/// Sync state stored as JSON in token store
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+struct SyncState {
+    /// Map of file paths to their content hashes
+    file_hashes: HashMap<String, String>,
+    /// Event IDs per file, to detect removed components
+    file_events: HashMap<String, Vec<String>>,
+    /// Occurrence IDs per file
+    file_occurrences: HashMap<String, Vec<String>>,
+    /// Day the occurrences were expanded on; the horizon moves with it
+    expanded_on: Option<NaiveDate>,
+}
+
+/// Stream-based provider for local iCalendar files
+pub struct IcalSyncProvider {
+    /// A single .ics file or a directory searched recursively for .ics files
+    root: PathBuf,
+    token_store: Arc<dyn SyncTokenStore>,
+    event_tx: broadcast::Sender<ChangesWithMetadata<CalendarEvent>>,
+    occurrence_tx: broadcast::Sender<ChangesWithMetadata<CalendarOccurrence>>,
+    id_generator: Arc<dyn IdGenerator>,
+    /// Occurrences are expanded from `today - past` to `today + future`
+    horizon_past: Duration,
+    horizon_future: Duration,
+    /// State of the last sync, ahead of the token store until the caches saved it
+    last_state: Mutex<Option<SyncState>>,
+    /// Serializes syncs so two scans never diff against the same old state
+    sync_lock: tokio::sync::Mutex<()>,
+    /// Serializes read-modify-write cycles on the files
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+impl IcalSyncProvider {
+    pub fn new(root: PathBuf, token_store: Arc<dyn SyncTokenStore>) -> Self {
+        Self {
+            root,
+            token_store,
+            event_tx: broadcast::channel(1000).0,
+            occurrence_tx: broadcast::channel(1000).0,
+            id_generator: default_id_generator(),
+            horizon_past: Duration::days(30),
+            horizon_future: Duration::days(365),
+            last_state: Mutex::new(None),
+            sync_lock: tokio::sync::Mutex::new(()),
+            write_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Expand recurring entries from `past` before to `future` after today
+    pub fn with_horizon(mut self, past: Duration, future: Duration) -> Self {
+        self.horizon_past = past;
+        self.horizon_future = future;
+        self
+    }
+
+    /// Generate UIDs for components without one with `id_generator`
+    pub fn with_id_generator(mut self, id_generator: Arc<dyn IdGenerator>) -> Self {
+        self.id_generator = id_generator;
+        self
+    }
+
+    pub fn id_generator(&self) -> Arc<dyn IdGenerator> {
+        self.id_generator.clone()
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ChangesWithMetadata<CalendarEvent>> {
+        self.event_tx.subscribe()
+    }
+
+    pub fn subscribe_occurrences(
+        &self,
+    ) -> broadcast::Receiver<ChangesWithMetadata<CalendarOccurrence>> {
+        self.occurrence_tx.subscribe()
+    }
+
+    /// The .ics files currently under the root
+    pub fn ics_files(&self) -> Vec<PathBuf> {
+        if self.root.is_file() {
+            return vec![self.root.clone()];
+        }
+        let mut files: Vec<PathBuf> = WalkDir::new(&self.root)
+            .follow_links(true)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("ics")))
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// File new entries go to when no `file_path` is given
+    pub fn default_file(&self) -> PathBuf {
+        if self
+            .root
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("ics"))
+        {
+            self.root.clone()
+        } else {
+            self.root.join(DEFAULT_FILE_NAME)
+        }
+    }
+
+    /// Wait for exclusive access to the files for a read-modify-write cycle
+    pub(crate) async fn lock_writes(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.write_lock.lock().await
+    }
+
+    /// Load sync state from memory or the token store
+    async fn load_state(&self) -> Result<SyncState> {
+        if let Some(state) = self.last_state.lock().unwrap().clone() {
+            return Ok(state);
+        }
+
+        let position = self
+            .token_store
+            .load_token(self.provider_name())
+            .await?
+            .unwrap_or(StreamPosition::Beginning);
+
+        match position {
+            StreamPosition::Beginning => Ok(SyncState::default()),
+            StreamPosition::Version(bytes) => {
+                let state: SyncState = serde_json::from_slice(&bytes)
+                    .map_err(|e| format!("Failed to parse sync state: {}", e))?;
+                Ok(state)
+            }
+        }
+    }
+
+    /// Scan all files and compute changes against `old_state`
+    fn scan_and_compute_changes(
+        &self,
+        old_state: &SyncState,
+        today: NaiveDate,
+    ) -> Result<(
+        SyncState,
+        Vec<Change<CalendarEvent>>,
+        Vec<Change<CalendarOccurrence>>,
+    )> {
+        let origin = ChangeOrigin::remote_with_current_span()
+            .with_source(ChangeSource::default().with_provider("ical"));
+        let mut new_state = SyncState {
+            expanded_on: Some(today),
+            ..SyncState::default()
+        };
+        let mut event_changes = Vec::new();
+        let mut occurrence_changes = Vec::new();
+        // All occurrences move with the horizon when the day changes
+        let reexpand = old_state.expanded_on != Some(today);
+
+        for path in self.ics_files() {
+            let file_key = path.to_string_lossy().to_string();
+            let mut content = match std::fs::read_to_string(&path) {
+                Ok(c) => c,
+                Err(e) => {
+                    tracing::warn!("Failed to read {}: {}", path.display(), e);
+                    carry_over(old_state, &mut new_state, &file_key);
+                    continue;
+                }
+            };
+
+            let mut content_hash = compute_content_hash(&content);
+            if !reexpand && old_state.file_hashes.get(&file_key) == Some(&content_hash) {
+                // Unchanged: carry the state over
+                carry_over(old_state, &mut new_state, &file_key);
+                continue;
+            }
+
+            let mut calendars = match ics::parse_components(&content) {
+                Ok(calendars) => calendars,
+                Err(e) => {
+                    // Keep the previous entities of a file that is mid-edit or broken
+                    tracing::warn!("Failed to parse {}: {}", path.display(), e);
+                    carry_over(old_state, &mut new_state, &file_key);
+                    continue;
+                }
+            };
+
+            // Write back UIDs for components that need them
+            if assign_missing_uids(&mut calendars, self.id_generator.as_ref()) {
+                content = ics::write_components(&calendars);
+                std::fs::write(&path, &content)
+                    .map_err(|e| format!("Failed to write UIDs to {}: {}", path.display(), e))?;
+                content_hash = compute_content_hash(&content);
+            }
+
+            let (events, occurrences) = self.expand_file(&calendars, &file_key, today);
+
+            let old_events: HashSet<&String> = old_state
+                .file_events
+                .get(&file_key)
+                .into_iter()
+                .flatten()
+                .collect();
+            let event_ids: Vec<String> = events.iter().map(|e| e.id.clone()).collect();
+            for event in events {
+                if old_events.contains(&event.id) {
+                    event_changes.push(Change::Updated {
+                        id: event.id.clone(),
+                        data: event,
+                        origin: origin.clone(),
+                    });
+                } else {
+                    event_changes.push(Change::Created {
+                        data: event,
+                        origin: origin.clone(),
+                    });
+                }
+            }
+            for id in old_events {
+                if !event_ids.contains(id) {
+                    event_changes.push(Change::Deleted {
+                        id: id.clone(),
+                        origin: origin.clone(),
+                    });
+                }
+            }
+
+            let old_occurrences: HashSet<&String> = old_state
+                .file_occurrences
+                .get(&file_key)
+                .into_iter()
+                .flatten()
+                .collect();
+            let occurrence_ids: Vec<String> = occurrences.iter().map(|o| o.id.clone()).collect();
+            for occurrence in occurrences {
+                occurrence_changes.push(Change::Updated {
+                    id: occurrence.id.clone(),
+                    data: occurrence,
+                    origin: origin.clone(),
+                });
+            }
+            for id in old_occurrences {
+                if !occurrence_ids.contains(id) {
+                    occurrence_changes.push(Change::Deleted {
+                        id: id.clone(),
+                        origin: origin.clone(),
+                    });
+                }
+            }
+
+            new_state.file_events.insert(file_key.clone(), event_ids);
+            new_state
+                .file_occurrences
+                .insert(file_key.clone(), occurrence_ids);
+            new_state.file_hashes.insert(file_key, content_hash);
+        }
+
+        // Entries of deleted files
+        for file_key in old_state.file_hashes.keys() {
+            if new_state.file_hashes.contains_key(file_key) {
+                continue;
+            }
+            for id in old_state.file_events.get(file_key).into_iter().flatten() {
+                event_changes.push(Change::Deleted {
+                    id: id.clone(),
+                    origin: origin.clone(),
+                });
+            }
+            for id in old_state
+                .file_occurrences
+                .get(file_key)
+                .into_iter()
+                .flatten()
+            {
+                occurrence_changes.push(Change::Deleted {
+                    id: id.clone(),
+                    origin: origin.clone(),
+                });
+            }
+        }
+
+        Ok((new_state, event_changes, occurrence_changes))
+    }
+
+    /// Events and their occurrences within the horizon around `today`
+    fn expand_file(
+        &self,
+        calendars: &[Component],
+        file_path: &str,
+        today: NaiveDate,
+    ) -> (Vec<CalendarEvent>, Vec<CalendarOccurrence>) {
+        let from = (today - self.horizon_past).and_time(chrono::NaiveTime::MIN);
+        let to = (today + self.horizon_future).and_time(chrono::NaiveTime::MIN);
+
+        let components: Vec<&Component> = calendars
+            .iter()
+            .flat_map(|calendar| calendar.components.iter())
+            .filter(|c| c.name == "VEVENT" || c.name == "VTODO")
+            .collect();
+        let (overrides, masters): (Vec<&Component>, Vec<&Component>) = components
+            .into_iter()
+            .partition(|c| c.get("RECURRENCE-ID").is_some());
+
+        let mut events = Vec::new();
+        let mut occurrences = Vec::new();
+        for master in masters {
+            let Some(event) = CalendarEvent::from_component(master, file_path) else {
+                continue;
+            };
+            let Some(start) = master
+                .get("DTSTART")
+                .or_else(|| master.get("DUE"))
+                .and_then(IcalTime::from_property)
+            else {
+                events.push(event);
+                continue;
+            };
+            let length = event
+                .end
+                .as_deref()
+                .and_then(IcalTime::from_iso)
+                .map(|end| end.naive() - start.naive());
+
+            let starts = match event.rrule.as_deref().map(str::parse::<RRule>) {
+                Some(Ok(rule)) => {
+                    rule.occurrences(start, &CalendarEvent::exdates(master), from, to)
+                }
+                Some(Err(e)) => {
+                    tracing::warn!(
+                        "[IcalSyncProvider] {}: {}; showing only the first occurrence",
+                        event.id,
+                        e
+                    );
+                    vec![start]
+                }
+                None => vec![start],
+            };
+            let mut expanded: Vec<CalendarOccurrence> = starts
+                .into_iter()
+                .filter(|s| event.rrule.is_none() || s.naive() >= from)
+                .map(|s| {
+                    let end = length.map(|length| s.with_naive(s.naive() + length));
+                    CalendarOccurrence::new(&event, s, end)
+                })
+                .collect();
+
+            // Modified instances (RECURRENCE-ID) replace the occurrence they override
+            for instance in overrides
+                .iter()
+                .filter(|o| o.text("UID").as_deref() == Some(event.id.as_str()))
+            {
+                let Some(recurrence_id) = instance
+                    .get("RECURRENCE-ID")
+                    .and_then(IcalTime::from_property)
+                else {
+                    continue;
+                };
+                expanded.retain(|o| o.start != recurrence_id.to_iso());
+                if instance
+                    .get("STATUS")
+                    .is_some_and(|s| s.value.eq_ignore_ascii_case("CANCELLED"))
+                {
+                    continue;
+                }
+                let Some(modified) = CalendarEvent::from_component(instance, file_path) else {
+                    continue;
+                };
+                let Some(instance_start) = modified.start.as_deref().and_then(IcalTime::from_iso)
+                else {
+                    continue;
+                };
+                if instance_start.naive() < from || instance_start.naive() >= to {
+                    continue;
+                }
+                let mut occurrence = CalendarOccurrence::new(
+                    &modified,
+                    instance_start,
+                    modified.end.as_deref().and_then(IcalTime::from_iso),
+                );
+                // Keep the ID of the occurrence it replaces so edits don't churn rows
+                occurrence.id = format!("{}/{}", event.id, recurrence_id.to_iso());
+                expanded.push(occurrence);
+            }
+
+            occurrences.extend(expanded);
+            events.push(event);
+        }
+        (events, occurrences)
+    }
+
+    /// Modification times and sizes of the .ics files, to notice external edits cheaply
+    fn fingerprint(&self) -> Vec<(PathBuf, Option<std::time::SystemTime>, u64)> {
+        self.ics_files()
+            .into_iter()
+            .map(|path| {
+                let metadata = std::fs::metadata(&path).ok();
+                let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+                let len = metadata.map(|m| m.len()).unwrap_or(0);
+                (path, modified, len)
+            })
+            .collect()
+    }
+
+    /// Poll the files every `interval` and sync when one was added, changed or
+    /// removed, or when the day changed (which moves the expansion horizon)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn_watcher(
+        self: Arc<Self>,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut last = (self.fingerprint(), Local::now().date_naive());
+            loop {
+                tokio::time::sleep(interval).await;
+                let current = (self.fingerprint(), Local::now().date_naive());
+                if current == last {
+                    continue;
+                }
+                tracing::debug!(
+                    "[IcalSyncProvider] Change detected under {}",
+                    self.root.display()
+                );
+                if let Err(e) = self.sync(StreamPosition::Beginning).await {
+                    tracing::error!("[IcalSyncProvider] Sync after file change failed: {}", e);
+                }
+                last = current;
+            }
+        })
+    }
+}
+
+/// Keep a file's previous hash and entity IDs (the file is unchanged or unreadable)
+fn carry_over(old_state: &SyncState, new_state: &mut SyncState, file_key: &str) {
+    if let Some(hash) = old_state.file_hashes.get(file_key) {
+        new_state
+            .file_hashes
+            .insert(file_key.to_string(), hash.clone());
+    }
+    if let Some(ids) = old_state.file_events.get(file_key) {
+        new_state
+            .file_events
+            .insert(file_key.to_string(), ids.clone());
+    }
+    if let Some(ids) = old_state.file_occurrences.get(file_key) {
+        new_state
+            .file_occurrences
+            .insert(file_key.to_string(), ids.clone());
+    }
+}
+
+fn compute_content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// Give every VEVENT/VTODO without UID a new one; returns whether any was assigned
+fn assign_missing_uids(calendars: &mut [Component], id_generator: &dyn IdGenerator) -> bool {
+    let mut assigned = false;
+    for component in calendars
+        .iter_mut()
+        .flat_map(|calendar| calendar.components.iter_mut())
+        .filter(|c| c.name == "VEVENT" || c.name == "VTODO")
+    {
+        if component.get("UID").is_none() {
+            component
+                .properties
+                .insert(0, Property::text("UID", &id_generator.generate()));
+            assigned = true;
+        }
+    }
+    assigned
+}
+
+/// Parse an .ics file; a missing file reads as an empty calendar
+pub(crate) fn read_calendars(path: &Path) -> Result<Vec<Component>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(ics::parse_components(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![ics::new_calendar()]),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e).into()),
+    }
+}
+
+pub(crate) fn write_calendars(path: &Path, calendars: &[Component]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(path, ics::write_components(calendars))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(())
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl SyncableProvider for IcalSyncProvider {
+    fn provider_name(&self) -> &str {
+        "ical"
+    }
+
+    #[tracing::instrument(name = "provider.ical.sync", skip(self, _position))]
+    async fn sync(&self, _position: StreamPosition) -> Result<StreamPosition> {
+        let _sync = self.sync_lock.lock().await;
+
+        let old_state = self.load_state().await?;
+        let (new_state, event_changes, occurrence_changes) =
+            self.scan_and_compute_changes(&old_state, Local::now().date_naive())?;
+
+        let state_bytes = serde_json::to_vec(&new_state)
+            .map_err(|e| format!("Failed to serialize sync state: {}", e))?;
+        let new_position = StreamPosition::Version(state_bytes);
+        *self.last_state.lock().unwrap() = Some(new_state);
+
+        let sync_token_update = SyncTokenUpdate {
+            provider_name: self.provider_name().to_string(),
+            position: new_position.clone(),
+        };
+        let trace_context = holon_api::BatchTraceContext::from_current_span();
+
+        tracing::info!(
+            "[IcalSyncProvider] Emitting {} event, {} occurrence changes",
+            event_changes.len(),
+            occurrence_changes.len()
+        );
+
+        let _ = self.event_tx.send(WithMetadata {
+            inner: event_changes,
+            metadata: BatchMetadata {
+                relation_name: "ical_events".to_string(),
+                trace_context: trace_context.clone(),
+                sync_token: Some(sync_token_update.clone()),
+                full_snapshot: false,
+            },
+        });
+        let _ = self.occurrence_tx.send(WithMetadata {
+            inner: occurrence_changes,
+            metadata: BatchMetadata {
+                relation_name: "ical_occurrences".to_string(),
+                trace_context,
+                sync_token: Some(sync_token_update),
+                full_snapshot: false,
+            },
+        });
+
+        Ok(new_position)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationProvider for IcalSyncProvider {
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        vec![generate_sync_operation(self.provider_name())]
+    }
+
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        _params: StorageEntity,
+    ) -> Result<UndoAction> {
+        let expected_entity_name = format!("{}.sync", self.provider_name());
+        if entity_name != expected_entity_name {
+            return Err(format!(
+                "Expected entity_name '{}', got '{}'",
+                expected_entity_name, entity_name
+            )
+            .into());
+        }
+
+        if op_name != "sync" {
+            return Err(format!("Expected op_name 'sync', got '{}'", op_name).into());
+        }
+
+        self.sync(StreamPosition::Beginning).await?;
+        Ok(UndoAction::Irreversible)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::RwLock;
+    use tempfile::tempdir;
+
+    /// Simple in-memory mock for SyncTokenStore
+    struct MockSyncTokenStore {
+        tokens: RwLock<HashMap<String, StreamPosition>>,
+    }
+
+    #[async_trait]
+    impl SyncTokenStore for MockSyncTokenStore {
+        async fn load_token(&self, provider_name: &str) -> Result<Option<StreamPosition>> {
+            Ok(self.tokens.read().unwrap().get(provider_name).cloned())
+        }
+        async fn save_token(&self, provider_name: &str, position: StreamPosition) -> Result<()> {
+            self.tokens
+                .write()
+                .unwrap()
+                .insert(provider_name.to_string(), position);
+            Ok(())
+        }
+    }
+
+    fn provider(root: PathBuf) -> IcalSyncProvider {
+        let token_store = Arc::new(MockSyncTokenStore {
+            tokens: RwLock::new(HashMap::new()),
+        });
+        IcalSyncProvider::new(root, token_store)
+    }
+
+    fn ids<T>(changes: &[Change<T>], f: impl Fn(&T) -> String) -> Vec<String> {
+        changes
+            .iter()
+            .map(|change| match change {
+                Change::Created { data, .. } => format!("+{}", f(data)),
+                Change::Updated { data, .. } => format!("~{}", f(data)),
+                Change::Deleted { id, .. } => format!("-{}", id),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_sync_expands_recurrences_and_assigns_uids() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("cal.ics");
+        let today = Local::now().date_naive();
+        let start = today.format("%Y%m%d");
+        std::fs::write(
+            &file,
+            format!(
+                "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:daily\r\nSUMMARY:Daily\r\nDTSTART:{start}T080000\r\nRRULE:FREQ=DAILY;COUNT=3\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nUID:daily\r\nRECURRENCE-ID:{start}T080000\r\nSUMMARY:Daily (moved)\r\nDTSTART:{start}T100000\r\nEND:VEVENT\r\nBEGIN:VTODO\r\nSUMMARY:No UID yet\r\nEND:VTODO\r\nEND:VCALENDAR\r\n"
+            ),
+        )
+        .unwrap();
+
+        let provider = provider(dir.path().to_path_buf());
+        let mut event_rx = provider.subscribe_events();
+        let mut occurrence_rx = provider.subscribe_occurrences();
+        provider.sync(StreamPosition::Beginning).await.unwrap();
+
+        let events = event_rx.try_recv().unwrap().inner;
+        assert_eq!(events.len(), 2);
+        assert!(std::fs::read_to_string(&file)
+            .unwrap()
+            .contains("BEGIN:VTODO\r\nUID:"));
+
+        let occurrences = occurrence_rx.try_recv().unwrap().inner;
+        let mut summaries = ids(&occurrences, |o| format!("{} {}", o.summary, o.start));
+        summaries.sort();
+        let day = |offset: i64| (today + Duration::days(offset)).format("%Y-%m-%d");
+        assert_eq!(
+            summaries,
+            vec![
+                format!("~Daily (moved) {}T10:00:00", day(0)),
+                format!("~Daily {}T08:00:00", day(1)),
+                format!("~Daily {}T08:00:00", day(2)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_removed_components_and_files_are_deleted() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("cal.ics");
+        std::fs::write(
+            &file,
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:a\r\nDTSTART:20240101T090000\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nUID:b\r\nDTSTART:20240102T090000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+
+        let provider = provider(dir.path().to_path_buf());
+        let mut event_rx = provider.subscribe_events();
+        provider.sync(StreamPosition::Beginning).await.unwrap();
+        assert_eq!(
+            ids(&event_rx.try_recv().unwrap().inner, |e| e.id.clone()),
+            vec!["+a", "+b"]
+        );
+
+        // Unchanged files produce no changes
+        provider.sync(StreamPosition::Beginning).await.unwrap();
+        assert!(event_rx.try_recv().unwrap().inner.is_empty());
+
+        std::fs::write(
+            &file,
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:a\r\nDTSTART:20240101T090000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+        provider.sync(StreamPosition::Beginning).await.unwrap();
+        assert_eq!(
+            ids(&event_rx.try_recv().unwrap().inner, |e| e.id.clone()),
+            vec!["~a", "-b"]
+        );
+
+        std::fs::remove_file(&file).unwrap();
+        provider.sync(StreamPosition::Beginning).await.unwrap();
+        assert_eq!(
+            ids(&event_rx.try_recv().unwrap().inner, |e| e.id.clone()),
+            vec!["-a"]
+        );
+    }
+}
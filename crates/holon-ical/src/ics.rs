@@ -0,0 +1,515 @@
+//! Reading and writing the iCalendar (RFC 5545) text format
+//!
+//! A file is parsed into a tree of `Component`s (`BEGIN:X` … `END:X`) holding their
+//! `Property` lines in file order. Properties and components we don't interpret
+//! (alarms, time zone definitions, vendor extensions) are kept as-is, so rewriting a
+//! file after an edit only changes the edited lines.
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+use holon::core::datasource::Result;
+
+/// Maximum line length in octets before folding (RFC 5545 §3.1)
+const FOLD_WIDTH: usize = 75;
+
+/// A content line: `NAME;PARAM=VALUE:value`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    /// Raw (still escaped) value
+    pub value: String,
+}
+
+impl Property {
+    pub fn new(name: &str, value: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            params: Vec::new(),
+            value: value.into(),
+        }
+    }
+
+    /// Text property with the value escaped
+    pub fn text(name: &str, text: &str) -> Self {
+        Self::new(name, escape_text(text))
+    }
+
+    pub fn with_param(mut self, name: &str, value: &str) -> Self {
+        self.params.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Unescaped value of a TEXT property
+    pub fn text_value(&self) -> String {
+        unescape_text(&self.value)
+    }
+
+    /// Parse an unfolded content line
+    pub fn parse(line: &str) -> Option<Self> {
+        // The value starts at the first ':' outside of a quoted parameter value
+        let mut in_quotes = false;
+        let colon = line.char_indices().find_map(|(i, c)| match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                None
+            }
+            ':' if !in_quotes => Some(i),
+            _ => None,
+        })?;
+        let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+        let mut parts = split_unquoted(head, ';').into_iter();
+        let name = parts.next()?.trim().to_ascii_uppercase();
+        if name.is_empty() {
+            return None;
+        }
+        let params = parts
+            .filter_map(|param| {
+                let (key, value) = param.split_once('=')?;
+                Some((key.trim().to_ascii_uppercase(), value.to_string()))
+            })
+            .collect();
+
+        Some(Self {
+            name,
+            params,
+            value: value.to_string(),
+        })
+    }
+
+    /// Content line without folding
+    pub fn to_line(&self) -> String {
+        let mut line = self.name.clone();
+        for (key, value) in &self.params {
+            line.push(';');
+            line.push_str(key);
+            line.push('=');
+            line.push_str(value);
+        }
+        line.push(':');
+        line.push_str(&self.value);
+        line
+    }
+}
+
+fn split_unquoted(s: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        } else if c == separator && !in_quotes {
+            parts.push(&s[start..i]);
+            start = i + 1;
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// `BEGIN:NAME` … `END:NAME` with its properties and nested components
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    pub name: String,
+    pub properties: Vec<Property>,
+    pub components: Vec<Component>,
+}
+
+impl Component {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            properties: Vec::new(),
+            components: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Property> {
+        self.properties.iter().filter(move |p| p.name == name)
+    }
+
+    /// Unescaped value of a TEXT property
+    pub fn text(&self, name: &str) -> Option<String> {
+        self.get(name).map(Property::text_value)
+    }
+
+    /// Replace the first property named like `property` (keeping its position) or append it
+    pub fn set(&mut self, property: Property) {
+        match self.properties.iter_mut().find(|p| p.name == property.name) {
+            Some(existing) => *existing = property,
+            None => self.properties.push(property),
+        }
+    }
+
+    /// Remove all properties named `name`
+    pub fn remove(&mut self, name: &str) {
+        self.properties.retain(|p| p.name != name);
+    }
+
+    /// Append the component's content lines (folded, CRLF-terminated) to `out`
+    pub fn write(&self, out: &mut String) {
+        push_line(out, &format!("BEGIN:{}", self.name));
+        for property in &self.properties {
+            push_line(out, &property.to_line());
+        }
+        for component in &self.components {
+            component.write(out);
+        }
+        push_line(out, &format!("END:{}", self.name));
+    }
+}
+
+fn push_line(out: &mut String, line: &str) {
+    out.push_str(&fold(line));
+    out.push_str("\r\n");
+}
+
+/// Join folded lines (a line starting with a space or tab continues the previous one)
+pub fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in content.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if raw.is_empty() => {}
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+/// Fold a content line into chunks of at most 75 octets, never splitting a UTF-8 character
+pub fn fold(line: &str) -> String {
+    if line.len() <= FOLD_WIDTH {
+        return line.to_string();
+    }
+    let mut folded = String::with_capacity(line.len() + line.len() / FOLD_WIDTH * 3);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > FOLD_WIDTH {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the continuation line's length
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+/// Parse the components of an iCalendar file (usually a single VCALENDAR)
+pub fn parse_components(content: &str) -> Result<Vec<Component>> {
+    let mut roots = Vec::new();
+    let mut stack: Vec<Component> = Vec::new();
+
+    for (index, line) in unfold(content).iter().enumerate() {
+        let property = Property::parse(line)
+            .ok_or_else(|| format!("Invalid iCalendar line {}: {}", index + 1, line))?;
+        match property.name.as_str() {
+            "BEGIN" => stack.push(Component::new(&property.value.to_ascii_uppercase())),
+            "END" => {
+                let component = stack.pop().ok_or_else(|| {
+                    format!("Unexpected END:{} at line {}", property.value, index + 1)
+                })?;
+                if !component.name.eq_ignore_ascii_case(&property.value) {
+                    return Err(format!(
+                        "END:{} at line {} does not match BEGIN:{}",
+                        property.value,
+                        index + 1,
+                        component.name
+                    )
+                    .into());
+                }
+                match stack.last_mut() {
+                    Some(parent) => parent.components.push(component),
+                    None => roots.push(component),
+                }
+            }
+            _ => match stack.last_mut() {
+                Some(component) => component.properties.push(property),
+                None => {
+                    return Err(
+                        format!("Property outside of a component at line {}", index + 1).into(),
+                    )
+                }
+            },
+        }
+    }
+
+    if let Some(open) = stack.last() {
+        return Err(format!("Missing END:{}", open.name).into());
+    }
+    Ok(roots)
+}
+
+/// Serialize components with CRLF line endings
+pub fn write_components(components: &[Component]) -> String {
+    let mut out = String::new();
+    for component in components {
+        component.write(&mut out);
+    }
+    out
+}
+
+/// Empty VCALENDAR for newly created files
+pub fn new_calendar() -> Component {
+    let mut calendar = Component::new("VCALENDAR");
+    calendar.properties.push(Property::new("VERSION", "2.0"));
+    calendar
+        .properties
+        .push(Property::new("PRODID", "-//holon//holon-ical//EN"));
+    calendar
+}
+
+/// Escape a TEXT value (RFC 5545 §3.3.11)
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+/// Value of DTSTART, DTEND, DUE, RECURRENCE-ID or EXDATE
+///
+/// Times with a TZID are kept as wall-clock times in that zone (`Local`), which is
+/// also what recurrence rules step through, so occurrences keep their local time
+/// across DST changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IcalTime {
+    /// All-day (`VALUE=DATE`)
+    Date(NaiveDate),
+    /// Floating time, or local time in the property's TZID
+    Local(NaiveDateTime),
+    /// UTC time (`...Z`)
+    Utc(NaiveDateTime),
+}
+
+impl IcalTime {
+    /// Parse an iCalendar value (`20240105`, `20240105T090000`, `20240105T090000Z`)
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Some(utc) = value.strip_suffix('Z') {
+            return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+                .ok()
+                .map(Self::Utc);
+        }
+        if value.contains('T') {
+            return NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+                .ok()
+                .map(Self::Local);
+        }
+        NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(Self::Date)
+    }
+
+    /// Parse a property (and its first value, for list-valued properties like EXDATE)
+    pub fn from_property(property: &Property) -> Option<Self> {
+        property.value.split(',').next().and_then(Self::parse)
+    }
+
+    /// Parse an ISO 8601 value as stored in entities (`2024-01-05`, `2024-01-05T09:00:00[Z]`)
+    pub fn from_iso(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Some(utc) = value.strip_suffix('Z') {
+            return parse_iso_date_time(utc).map(Self::Utc);
+        }
+        if let Ok(date_time) = chrono::DateTime::parse_from_rfc3339(value) {
+            return Some(Self::Utc(date_time.naive_utc()));
+        }
+        if value.contains('T') {
+            return parse_iso_date_time(value).map(Self::Local);
+        }
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .map(Self::Date)
+    }
+
+    /// ISO 8601 representation stored in entities
+    pub fn to_iso(&self) -> String {
+        match self {
+            Self::Date(date) => date.format("%Y-%m-%d").to_string(),
+            Self::Local(date_time) => date_time.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            Self::Utc(date_time) => date_time.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        }
+    }
+
+    /// iCalendar value representation
+    pub fn to_ical(&self) -> String {
+        match self {
+            Self::Date(date) => date.format("%Y%m%d").to_string(),
+            Self::Local(date_time) => date_time.format("%Y%m%dT%H%M%S").to_string(),
+            Self::Utc(date_time) => date_time.format("%Y%m%dT%H%M%SZ").to_string(),
+        }
+    }
+
+    /// Property carrying this time (`VALUE=DATE` for all-day values, `TZID` for local times)
+    pub fn to_property(&self, name: &str, tzid: Option<&str>) -> Property {
+        let property = Property::new(name, self.to_ical());
+        match (self, tzid) {
+            (Self::Date(_), _) => property.with_param("VALUE", "DATE"),
+            (Self::Local(_), Some(tzid)) => property.with_param("TZID", tzid),
+            _ => property,
+        }
+    }
+
+    pub fn is_date(&self) -> bool {
+        matches!(self, Self::Date(_))
+    }
+
+    /// Date-time (midnight for all-day values)
+    pub fn naive(&self) -> NaiveDateTime {
+        match self {
+            Self::Date(date) => date.and_time(NaiveTime::MIN),
+            Self::Local(date_time) | Self::Utc(date_time) => *date_time,
+        }
+    }
+
+    /// Same kind of value at another date-time
+    pub fn with_naive(&self, date_time: NaiveDateTime) -> Self {
+        match self {
+            Self::Date(_) => Self::Date(date_time.date()),
+            Self::Local(_) => Self::Local(date_time),
+            Self::Utc(_) => Self::Utc(date_time),
+        }
+    }
+}
+
+fn parse_iso_date_time(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+        .ok()
+}
+
+/// Parse a DURATION value (`PT1H30M`, `P1D`, `-PT15M`, `P2W`)
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let rest = rest.strip_prefix('P')?;
+
+    let mut seconds = 0i64;
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => number.push(c),
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                seconds += n * match (unit, in_time) {
+                    ('W', false) => 7 * 24 * 3600,
+                    ('D', false) => 24 * 3600,
+                    ('H', true) => 3600,
+                    ('M', true) => 60,
+                    ('S', true) => 1,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(Duration::seconds(sign * seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//test//EN\r\nBEGIN:VEVENT\r\nUID:abc\r\nDTSTART;TZID=Europe/Berlin:20240105T090000\r\nSUMMARY:Planning\\, weekly\r\nDESCRIPTION:A long description that is folded because it is longer than se\r\n venty-five octets\r\nBEGIN:VALARM\r\nACTION:DISPLAY\r\nTRIGGER:-PT15M\r\nEND:VALARM\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_and_write_round_trip() {
+        let components = parse_components(SAMPLE).unwrap();
+        assert_eq!(components.len(), 1);
+        let event = &components[0].components[0];
+        assert_eq!(event.name, "VEVENT");
+        assert_eq!(event.text("SUMMARY").as_deref(), Some("Planning, weekly"));
+        assert_eq!(
+            event.text("DESCRIPTION").as_deref(),
+            Some("A long description that is folded because it is longer than seventy-five octets")
+        );
+        let start = event.get("DTSTART").unwrap();
+        assert_eq!(start.param("tzid"), Some("Europe/Berlin"));
+        assert_eq!(
+            IcalTime::from_property(start)
+                .map(|t| t.to_iso())
+                .as_deref(),
+            Some("2024-01-05T09:00:00")
+        );
+        assert_eq!(event.components[0].name, "VALARM");
+
+        assert_eq!(write_components(&components), SAMPLE);
+    }
+
+    #[test]
+    fn test_times_and_durations() {
+        assert_eq!(
+            IcalTime::parse("20240105"),
+            Some(IcalTime::Date(NaiveDate::from_ymd_opt(2024, 1, 5).unwrap()))
+        );
+        let utc = IcalTime::parse("20240105T083000Z").unwrap();
+        assert_eq!(utc.to_iso(), "2024-01-05T08:30:00Z");
+        assert_eq!(IcalTime::from_iso("2024-01-05T08:30:00Z"), Some(utc));
+        assert_eq!(
+            IcalTime::from_iso("2024-01-05"),
+            IcalTime::parse("20240105")
+        );
+
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::days(7)));
+        assert_eq!(parse_duration("-PT15M"), Some(Duration::minutes(-15)));
+        assert_eq!(parse_duration("P1H"), None);
+    }
+
+    #[test]
+    fn test_fold_keeps_characters_intact() {
+        let line = format!("SUMMARY:{}", "ä".repeat(60));
+        let folded = fold(&line);
+        assert!(folded.split("\r\n").all(|part| part.len() <= FOLD_WIDTH));
+        assert_eq!(unfold(&folded), vec![line]);
+    }
+}
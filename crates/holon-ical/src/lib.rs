@@ -0,0 +1,23 @@
+//! Rusty Knowledge local calendar integration
+//!
+//! This crate reads and writes iCalendar (.ics) files for the holon PKM system.
+//! VEVENT and VTODO components become `CalendarEvent` entities that can be edited
+//! through the standard operation system; recurring entries are expanded into
+//! `CalendarOccurrence` rows so agenda queries can mix them with tasks.
+
+#[cfg(feature = "di")]
+pub mod di;
+pub mod ical_datasource;
+pub mod ical_sync_provider;
+pub mod ics;
+pub mod models;
+pub mod recurrence;
+
+// Re-export key types
+#[cfg(feature = "di")]
+pub use di::{IcalConfig, IcalModule};
+pub use ical_datasource::{IcalEventDataSource, IcalOccurrenceDataSource};
+pub use ical_sync_provider::IcalSyncProvider;
+pub use ics::{Component, IcalTime, Property};
+pub use models::{CalendarEvent, CalendarOccurrence};
+pub use recurrence::{Frequency, RRule};
@@ -0,0 +1,333 @@
+use holon_api::Value;
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+
+use holon::core::datasource::Result;
+
+use crate::ics::{parse_duration, Component, IcalTime, Property};
+
+/// A VEVENT or VTODO of an .ics file
+///
+/// Times are ISO 8601: `2024-01-05` for all-day values, `2024-01-05T09:00:00` for
+/// wall-clock times (in `timezone` if set) and `2024-01-05T08:00:00Z` for UTC.
+#[derive(Debug, Clone, Serialize, Deserialize, Entity)]
+#[entity(name = "ical_events", short_name = "event", operations(CrudOperations))]
+pub struct CalendarEvent {
+    /// The component's UID
+    #[primary_key]
+    #[indexed]
+    pub id: String,
+
+    /// Absolute path of the .ics file containing the component
+    #[indexed]
+    pub file_path: String,
+
+    /// "event" (VEVENT) or "todo" (VTODO)
+    pub kind: String,
+
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+
+    /// DTSTART
+    pub start: Option<String>,
+    /// DTEND, or DTSTART + DURATION
+    pub end: Option<String>,
+    /// DUE (todos only)
+    pub due: Option<String>,
+    pub all_day: bool,
+    /// TZID of DTSTART (or DUE)
+    pub timezone: Option<String>,
+
+    /// STATUS, e.g. CONFIRMED, TENTATIVE, NEEDS-ACTION, COMPLETED
+    pub status: Option<String>,
+    /// Todo is COMPLETED (always false for events)
+    pub completed: bool,
+    /// PRIORITY, 1 (highest) to 9 (lowest)
+    pub priority: Option<i64>,
+
+    /// RRULE value, e.g. "FREQ=WEEKLY;BYDAY=MO"
+    pub rrule: Option<String>,
+    /// CATEGORIES, comma-separated
+    pub categories: Option<String>,
+}
+
+impl CalendarEvent {
+    /// Entity for a VEVENT/VTODO, None for other components and components without UID
+    pub fn from_component(component: &Component, file_path: &str) -> Option<Self> {
+        let kind = match component.name.as_str() {
+            "VEVENT" => "event",
+            "VTODO" => "todo",
+            _ => return None,
+        };
+        let id = component.text("UID")?;
+
+        let start_property = component.get("DTSTART");
+        let start = start_property.and_then(IcalTime::from_property);
+        let due = component.get("DUE").and_then(IcalTime::from_property);
+        let end = component
+            .get("DTEND")
+            .and_then(IcalTime::from_property)
+            .or_else(|| {
+                let duration = parse_duration(&component.get("DURATION")?.value)?;
+                start.map(|start| start.with_naive(start.naive() + duration))
+            });
+        let timezone = start_property
+            .or_else(|| component.get("DUE"))
+            .and_then(|p| p.param("TZID"))
+            .map(str::to_string);
+        let status = component
+            .get("STATUS")
+            .map(|p| p.value.to_ascii_uppercase());
+
+        Some(Self {
+            id,
+            file_path: file_path.to_string(),
+            kind: kind.to_string(),
+            summary: component.text("SUMMARY").unwrap_or_default(),
+            description: component.text("DESCRIPTION"),
+            location: component.text("LOCATION"),
+            start: start.map(|t| t.to_iso()),
+            end: end.map(|t| t.to_iso()),
+            due: due.map(|t| t.to_iso()),
+            all_day: start.or(due).is_some_and(|t| t.is_date()),
+            timezone,
+            completed: kind == "todo" && status.as_deref() == Some("COMPLETED"),
+            status,
+            priority: component
+                .get("PRIORITY")
+                .and_then(|p| p.value.trim().parse().ok())
+                .filter(|p| *p > 0),
+            rrule: component.get("RRULE").map(|p| p.value.clone()),
+            categories: component
+                .get_all("CATEGORIES")
+                .map(Property::text_value)
+                .reduce(|a, b| format!("{},{}", a, b)),
+        })
+    }
+
+    /// Excluded start times of a recurring event (EXDATE)
+    pub fn exdates(component: &Component) -> Vec<IcalTime> {
+        component
+            .get_all("EXDATE")
+            .flat_map(|p| p.value.split(',').filter_map(IcalTime::parse))
+            .collect()
+    }
+}
+
+/// Component name for an entity kind
+pub fn component_name(kind: &str) -> Result<&'static str> {
+    match kind {
+        "event" => Ok("VEVENT"),
+        "todo" => Ok("VTODO"),
+        other => Err(format!("Unknown calendar entry kind '{}'", other).into()),
+    }
+}
+
+fn optional_string(field: &str, value: &Value) -> Result<Option<String>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::String(s) if s.is_empty() => Ok(None),
+        Value::String(s) | Value::DateTime(s) => Ok(Some(s.clone())),
+        other => Err(format!("Expected a string for '{}', got {:?}", field, other).into()),
+    }
+}
+
+fn set_text(component: &mut Component, name: &str, text: Option<String>) {
+    match text {
+        Some(text) => component.set(Property::text(name, &text)),
+        None => component.remove(name),
+    }
+}
+
+fn set_time(component: &mut Component, name: &str, field: &str, value: &Value) -> Result<()> {
+    match optional_string(field, value)? {
+        Some(iso) => {
+            let time = IcalTime::from_iso(&iso)
+                .ok_or_else(|| format!("Invalid date/time for '{}': {}", field, iso))?;
+            // Keep the zone of the value being replaced
+            let tzid = component
+                .get(name)
+                .and_then(|p| p.param("TZID"))
+                .map(str::to_string);
+            component.set(time.to_property(name, tzid.as_deref()));
+            if name == "DTEND" {
+                component.remove("DURATION");
+            }
+        }
+        None => component.remove(name),
+    }
+    Ok(())
+}
+
+/// Write an entity field to the corresponding property of `component`
+///
+/// Unknown properties of the component are left untouched.
+pub fn apply_field(component: &mut Component, field: &str, value: &Value) -> Result<()> {
+    match field {
+        "summary" => set_text(component, "SUMMARY", optional_string(field, value)?),
+        "description" => set_text(component, "DESCRIPTION", optional_string(field, value)?),
+        "location" => set_text(component, "LOCATION", optional_string(field, value)?),
+        "start" => set_time(component, "DTSTART", field, value)?,
+        "end" => set_time(component, "DTEND", field, value)?,
+        "due" => set_time(component, "DUE", field, value)?,
+        "status" => match optional_string(field, value)? {
+            Some(status) => component.set(Property::new("STATUS", status.to_ascii_uppercase())),
+            None => component.remove("STATUS"),
+        },
+        "completed" => {
+            let completed = value
+                .as_bool()
+                .or_else(|| value.as_i64().map(|i| i != 0))
+                .ok_or_else(|| format!("Expected a boolean for 'completed', got {:?}", value))?;
+            if completed {
+                let now = IcalTime::Utc(chrono::Utc::now().naive_utc());
+                component.set(Property::new("STATUS", "COMPLETED"));
+                component.set(now.to_property("COMPLETED", None));
+            } else {
+                component.set(Property::new("STATUS", "NEEDS-ACTION"));
+                component.remove("COMPLETED");
+            }
+        }
+        "priority" => match value {
+            Value::Null => component.remove("PRIORITY"),
+            value => {
+                let priority = value
+                    .as_i64()
+                    .filter(|p| (0..=9).contains(p))
+                    .ok_or_else(|| format!("Expected a priority from 0 to 9, got {:?}", value))?;
+                component.set(Property::new("PRIORITY", priority.to_string()));
+            }
+        },
+        "rrule" => match optional_string(field, value)? {
+            Some(rule) => {
+                rule.parse::<crate::recurrence::RRule>()?;
+                component.set(Property::new("RRULE", rule));
+            }
+            None => component.remove("RRULE"),
+        },
+        "categories" => {
+            component.remove("CATEGORIES");
+            if let Some(categories) = optional_string(field, value)? {
+                let escaped: Vec<String> = categories
+                    .split(',')
+                    .map(|c| crate::ics::escape_text(c.trim()))
+                    .collect();
+                component.set(Property::new("CATEGORIES", escaped.join(",")));
+            }
+        }
+        other => {
+            return Err(format!("Field '{}' of calendar entries is not writable", other).into())
+        }
+    }
+    Ok(())
+}
+
+/// One occurrence of an event or todo within the expansion horizon
+///
+/// Non-recurring entries have exactly one occurrence. This is the table agenda
+/// queries read from.
+#[derive(Debug, Clone, Serialize, Deserialize, Entity)]
+#[entity(name = "ical_occurrences", short_name = "occurrence")]
+pub struct CalendarOccurrence {
+    /// "{event_id}/{start}"
+    #[primary_key]
+    #[indexed]
+    pub id: String,
+
+    #[indexed]
+    #[reference(entity = "ical_events")]
+    pub event_id: String,
+
+    /// "event" or "todo"
+    pub kind: String,
+    pub summary: String,
+
+    /// Start (due date for todos without DTSTART), ISO 8601
+    #[indexed]
+    pub start: String,
+    pub end: Option<String>,
+    pub all_day: bool,
+    pub completed: bool,
+}
+
+impl CalendarOccurrence {
+    pub fn new(event: &CalendarEvent, start: IcalTime, end: Option<IcalTime>) -> Self {
+        let start = start.to_iso();
+        Self {
+            id: format!("{}/{}", event.id, start),
+            event_id: event.id.clone(),
+            kind: event.kind.clone(),
+            summary: event.summary.clone(),
+            start,
+            end: end.map(|t| t.to_iso()),
+            all_day: event.all_day,
+            completed: event.completed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ics::parse_components;
+
+    #[test]
+    fn test_event_from_component() {
+        let calendar = parse_components(
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:standup\nSUMMARY:Standup\nDTSTART;TZID=Europe/Berlin:20240108T093000\nDURATION:PT15M\nRRULE:FREQ=WEEKLY;BYDAY=MO,TU\nCATEGORIES:work,daily\nEND:VEVENT\nBEGIN:VTODO\nUID:taxes\nSUMMARY:File taxes\nDUE;VALUE=DATE:20240531\nSTATUS:COMPLETED\nEND:VTODO\nEND:VCALENDAR\n",
+        )
+        .unwrap();
+        let components = &calendar[0].components;
+
+        let event = CalendarEvent::from_component(&components[0], "/cal/work.ics").unwrap();
+        assert_eq!(event.kind, "event");
+        assert_eq!(event.start.as_deref(), Some("2024-01-08T09:30:00"));
+        assert_eq!(event.end.as_deref(), Some("2024-01-08T09:45:00"));
+        assert_eq!(event.timezone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(event.categories.as_deref(), Some("work,daily"));
+        assert!(!event.all_day);
+
+        let todo = CalendarEvent::from_component(&components[1], "/cal/work.ics").unwrap();
+        assert_eq!(todo.kind, "todo");
+        assert_eq!(todo.due.as_deref(), Some("2024-05-31"));
+        assert!(todo.all_day);
+        assert!(todo.completed);
+    }
+
+    #[test]
+    fn test_apply_field_keeps_timezone_and_unknown_properties() {
+        let mut component = Component::new("VEVENT");
+        component.properties.push(Property::new("UID", "a"));
+        component
+            .properties
+            .push(Property::new("DTSTART", "20240108T093000").with_param("TZID", "Europe/Berlin"));
+        component.properties.push(Property::new("X-CUSTOM", "kept"));
+
+        apply_field(
+            &mut component,
+            "start",
+            &Value::String("2024-01-09T10:00:00".to_string()),
+        )
+        .unwrap();
+        apply_field(
+            &mut component,
+            "summary",
+            &Value::String("Hi; there".to_string()),
+        )
+        .unwrap();
+
+        let start = component.get("DTSTART").unwrap();
+        assert_eq!(start.value, "20240109T100000");
+        assert_eq!(start.param("TZID"), Some("Europe/Berlin"));
+        assert_eq!(component.get("SUMMARY").unwrap().value, "Hi\\; there");
+        assert!(component.get("X-CUSTOM").is_some());
+
+        assert!(apply_field(
+            &mut component,
+            "rrule",
+            &Value::String("FREQ=SECONDLY".into())
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,415 @@
+//! Recurrence rule (RRULE) expansion
+//!
+//! Supports the rules calendar applications commonly write: `FREQ` DAILY, WEEKLY,
+//! MONTHLY or YEARLY with `INTERVAL`, `COUNT`, `UNTIL`, `BYDAY` (with ordinals such
+//! as `2MO` or `-1FR` for monthly and yearly rules), `BYMONTHDAY` and `BYMONTH`.
+//! Other parts (`BYSETPOS`, `BYWEEKNO`, sub-daily frequencies, ...) are rejected so
+//! that an event is never shown on the wrong days; such events only appear once.
+//!
+//! Occurrences are stepped in wall-clock time, so a weekly 09:00 meeting stays at
+//! 09:00 across DST changes.
+
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, Weekday};
+
+use crate::ics::IcalTime;
+
+/// Upper bound on candidate periods looked at while expanding one rule
+const MAX_PERIODS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// Parsed RRULE value
+#[derive(Debug, Clone, PartialEq)]
+pub struct RRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<IcalTime>,
+    /// Weekdays with an optional ordinal (`2MO` → `(Some(2), Mon)`)
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    /// Days of the month, negative values count from the end
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+}
+
+impl FromStr for RRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut frequency = None;
+        let mut rule = RRule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+        };
+
+        for part in value.trim().split(';').filter(|p| !p.is_empty()) {
+            let (key, val) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid RRULE part '{}'", part))?;
+            let invalid = || format!("Invalid {} in RRULE: '{}'", key, val);
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match val.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        other => return Err(format!("Unsupported RRULE frequency '{}'", other)),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = val.parse().ok().filter(|i| *i > 0).ok_or_else(invalid)?
+                }
+                "COUNT" => rule.count = Some(val.parse().map_err(|_| invalid())?),
+                "UNTIL" => rule.until = Some(IcalTime::parse(val).ok_or_else(invalid)?),
+                "BYDAY" => {
+                    rule.by_day = val
+                        .split(',')
+                        .map(|day| parse_by_day(day).ok_or_else(invalid))
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = val
+                        .split(',')
+                        .map(|day| {
+                            day.parse::<i32>()
+                                .ok()
+                                .filter(|d| *d != 0 && d.abs() <= 31)
+                                .ok_or_else(invalid)
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTH" => {
+                    rule.by_month = val
+                        .split(',')
+                        .map(|month| {
+                            month
+                                .parse::<u32>()
+                                .ok()
+                                .filter(|m| (1..=12).contains(m))
+                                .ok_or_else(invalid)
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                // Only affects weekly rules with an interval > 1 and BYDAY before the
+                // start's weekday; the Monday default is what nearly every client writes
+                "WKST" => {}
+                other => return Err(format!("Unsupported RRULE part '{}'", other)),
+            }
+        }
+
+        rule.frequency = frequency.ok_or_else(|| "RRULE without FREQ".to_string())?;
+        if rule.frequency == Frequency::Weekly && rule.by_day.iter().any(|(n, _)| n.is_some()) {
+            return Err(format!("Ordinal BYDAY in weekly RRULE: '{}'", value));
+        }
+        Ok(rule)
+    }
+}
+
+fn parse_by_day(value: &str) -> Option<(Option<i32>, Weekday)> {
+    let value = value.trim();
+    let split = value.len().checked_sub(2)?;
+    let weekday = match &value[split..] {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let ordinal = match &value[..split] {
+        "" => None,
+        n => Some(n.parse::<i32>().ok().filter(|n| *n != 0 && n.abs() <= 53)?),
+    };
+    Some((ordinal, weekday))
+}
+
+impl RRule {
+    /// Start times of the occurrences starting in `[from, to)`
+    ///
+    /// `start` is the DTSTART of the series and always its first occurrence. Excluded
+    /// dates still count towards `COUNT`, as RFC 5545 requires.
+    pub fn occurrences(
+        &self,
+        start: IcalTime,
+        exdates: &[IcalTime],
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Vec<IcalTime> {
+        let first = start.naive();
+        let time = first.time();
+        let until = self.until.map(|until| match until {
+            // An all-day UNTIL includes the whole day
+            IcalTime::Date(date) => date.and_time(chrono::NaiveTime::MIN) + Duration::days(1),
+            other => other.naive() + Duration::seconds(1),
+        });
+        let excluded: Vec<NaiveDateTime> = exdates.iter().map(IcalTime::naive).collect();
+
+        let mut occurrences = Vec::new();
+        let mut generated = 0u32;
+        let mut emit = |date_time: NaiveDateTime| -> bool {
+            if until.is_some_and(|until| date_time >= until) || date_time >= to {
+                return false;
+            }
+            if self.count.is_some_and(|count| generated >= count) {
+                return false;
+            }
+            generated += 1;
+            if date_time >= from && !excluded.contains(&date_time) {
+                occurrences.push(start.with_naive(date_time));
+            }
+            true
+        };
+
+        // DTSTART is the first occurrence even if it doesn't match the rule
+        if !emit(first) {
+            return occurrences;
+        }
+
+        for period in 0..MAX_PERIODS {
+            let mut candidates: Vec<NaiveDateTime> = self
+                .period_dates(first.date(), period as u32)
+                .into_iter()
+                .map(|date| date.and_time(time))
+                .filter(|date_time| *date_time > first)
+                .collect();
+            candidates.sort();
+            candidates.dedup();
+            for candidate in candidates {
+                if !emit(candidate) {
+                    return occurrences;
+                }
+            }
+            if self.period_start(first.date(), period as u32) >= Some(to.date()) {
+                break;
+            }
+        }
+        occurrences
+    }
+
+    /// First day of the `index`th period of the series (None once out of range)
+    fn period_start(&self, start: NaiveDate, index: u32) -> Option<NaiveDate> {
+        let step = index.checked_mul(self.interval)?;
+        match self.frequency {
+            Frequency::Daily => start.checked_add_signed(Duration::days(step as i64)),
+            Frequency::Weekly => {
+                let monday = start - Duration::days(start.weekday().num_days_from_monday() as i64);
+                monday.checked_add_signed(Duration::weeks(step as i64))
+            }
+            Frequency::Monthly => start.with_day(1)?.checked_add_months(Months::new(step)),
+            Frequency::Yearly => NaiveDate::from_ymd_opt(start.year() + step as i32, 1, 1),
+        }
+    }
+
+    /// Candidate dates within the `index`th period
+    fn period_dates(&self, start: NaiveDate, index: u32) -> Vec<NaiveDate> {
+        let Some(period) = self.period_start(start, index) else {
+            return Vec::new();
+        };
+        match self.frequency {
+            Frequency::Daily => Some(period)
+                .filter(|date| self.by_month.is_empty() || self.by_month.contains(&date.month()))
+                .filter(|date| {
+                    self.by_month_day.is_empty()
+                        || month_days(date, &self.by_month_day).contains(date)
+                })
+                .filter(|date| {
+                    self.by_day.is_empty()
+                        || self.by_day.iter().any(|(_, day)| *day == date.weekday())
+                })
+                .into_iter()
+                .collect(),
+            Frequency::Weekly => {
+                let weekdays: Vec<Weekday> = if self.by_day.is_empty() {
+                    vec![start.weekday()]
+                } else {
+                    self.by_day.iter().map(|(_, day)| *day).collect()
+                };
+                weekdays
+                    .into_iter()
+                    .map(|day| period + Duration::days(day.num_days_from_monday() as i64))
+                    .filter(|date| {
+                        self.by_month.is_empty() || self.by_month.contains(&date.month())
+                    })
+                    .collect()
+            }
+            Frequency::Monthly => {
+                if !self.by_month.is_empty() && !self.by_month.contains(&period.month()) {
+                    return Vec::new();
+                }
+                self.dates_in_month(period, start.day())
+            }
+            Frequency::Yearly => {
+                let months = if self.by_month.is_empty() {
+                    vec![start.month()]
+                } else {
+                    self.by_month.clone()
+                };
+                months
+                    .into_iter()
+                    .filter_map(|month| NaiveDate::from_ymd_opt(period.year(), month, 1))
+                    .flat_map(|month| self.dates_in_month(month, start.day()))
+                    .collect()
+            }
+        }
+    }
+
+    /// Dates matching BYMONTHDAY/BYDAY in the month starting at `month`, or `day` of that month
+    fn dates_in_month(&self, month: NaiveDate, day: u32) -> Vec<NaiveDate> {
+        let mut dates: Vec<NaiveDate> = if !self.by_month_day.is_empty() {
+            month_days(&month, &self.by_month_day)
+        } else if !self.by_day.is_empty() {
+            weekdays_in_month(month, &self.by_day)
+        } else {
+            // Months without that day (e.g. the 31st) are skipped
+            month.with_day(day).into_iter().collect()
+        };
+        if !self.by_month_day.is_empty() && !self.by_day.is_empty() {
+            let weekdays = weekdays_in_month(month, &self.by_day);
+            dates.retain(|date| weekdays.contains(date));
+        }
+        dates
+    }
+}
+
+fn days_in_month(date: &NaiveDate) -> u32 {
+    let first = date.with_day(1).expect("every month has a first day");
+    let next = first + Months::new(1);
+    (next - first).num_days() as u32
+}
+
+/// Dates of `days` (negative counting from the end) in the month of `date`
+fn month_days(date: &NaiveDate, days: &[i32]) -> Vec<NaiveDate> {
+    let length = days_in_month(date) as i32;
+    days.iter()
+        .filter_map(|day| {
+            let day = if *day < 0 { length + day + 1 } else { *day };
+            (day >= 1).then(|| date.with_day(day as u32)).flatten()
+        })
+        .collect()
+}
+
+/// Dates of the weekdays (all of them, or the nth / nth-from-last) in the month of `date`
+fn weekdays_in_month(date: NaiveDate, by_day: &[(Option<i32>, Weekday)]) -> Vec<NaiveDate> {
+    let all: Vec<NaiveDate> = (1..=days_in_month(&date))
+        .filter_map(|day| date.with_day(day))
+        .collect();
+    let mut dates = Vec::new();
+    for (ordinal, weekday) in by_day {
+        let matching: Vec<NaiveDate> = all
+            .iter()
+            .copied()
+            .filter(|d| d.weekday() == *weekday)
+            .collect();
+        match ordinal {
+            None => dates.extend(matching),
+            Some(n) if *n > 0 => dates.extend(matching.get(*n as usize - 1)),
+            Some(n) => dates.extend(
+                matching
+                    .len()
+                    .checked_sub(n.unsigned_abs() as usize)
+                    .map(|i| matching[i]),
+            ),
+        }
+    }
+    dates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(s: &str) -> IcalTime {
+        IcalTime::parse(s).unwrap()
+    }
+
+    fn dates(rule: &str, start: &str, from: &str, to: &str) -> Vec<String> {
+        let rule: RRule = rule.parse().unwrap();
+        rule.occurrences(local(start), &[], local(from).naive(), local(to).naive())
+            .iter()
+            .map(IcalTime::to_ical)
+            .collect()
+    }
+
+    #[test]
+    fn test_weekly_by_day_with_count() {
+        assert_eq!(
+            dates(
+                "FREQ=WEEKLY;BYDAY=MO,WE;COUNT=4",
+                "20240101T090000",
+                "20240101T000000",
+                "20250101T000000"
+            ),
+            vec![
+                "20240101T090000",
+                "20240103T090000",
+                "20240108T090000",
+                "20240110T090000"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monthly_ordinal_weekday_and_window() {
+        // Last Friday of each month, only those in February and March
+        assert_eq!(
+            dates(
+                "FREQ=MONTHLY;BYDAY=-1FR",
+                "20240126",
+                "20240201",
+                "20240401"
+            ),
+            vec!["20240223", "20240329"]
+        );
+        // The 31st only exists in some months
+        assert_eq!(
+            dates("FREQ=MONTHLY", "20240131", "20240101", "20240601"),
+            vec!["20240131", "20240331", "20240531"]
+        );
+    }
+
+    #[test]
+    fn test_until_and_exdates() {
+        let rule: RRule = "FREQ=DAILY;INTERVAL=2;UNTIL=20240107T090000Z"
+            .parse()
+            .unwrap();
+        let occurrences = rule.occurrences(
+            local("20240101T090000"),
+            &[local("20240103T090000")],
+            local("20240101T000000").naive(),
+            local("20240201T000000").naive(),
+        );
+        assert_eq!(
+            occurrences
+                .iter()
+                .map(IcalTime::to_ical)
+                .collect::<Vec<_>>(),
+            vec!["20240101T090000", "20240105T090000", "20240107T090000"]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_rules_are_rejected() {
+        assert!("FREQ=HOURLY".parse::<RRule>().is_err());
+        assert!("FREQ=MONTHLY;BYSETPOS=-1;BYDAY=MO,TU"
+            .parse::<RRule>()
+            .is_err());
+        assert!("FREQ=WEEKLY;BYDAY=2MO".parse::<RRule>().is_err());
+        assert!("INTERVAL=2".parse::<RRule>().is_err());
+    }
+}
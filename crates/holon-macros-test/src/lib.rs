@@ -22,13 +22,29 @@ where
 
     /// Set priority with range check
     #[require(priority >= 1)]
-    #[require(priority <= 5)]
+    #[require(priority <= 5, "priority must be between 1 and 5")]
     async fn set_priority(&self, _id: &str, priority: i64) -> Result<UndoAction>;
 
     /// Method without precondition
     async fn no_precondition(&self, id: &str) -> Result<UndoAction>;
 }
 
+// Test trait with param mapping defaults
+#[holon_macros::operations_trait]
+#[async_trait]
+pub trait TestMoveTrait: Send + Sync {
+    /// Move an item under a parent
+    #[holon_macros::triggered_by(availability_of = "tree_position", providing = ["parent_id", "position", "offset"], defaults = { "position" = "last", "offset" = -1 })]
+    #[holon_macros::triggered_by(availability_of = "parent_id")]
+    async fn move_item(
+        &self,
+        id: &str,
+        parent_id: &str,
+        position: &str,
+        offset: i64,
+    ) -> Result<UndoAction>;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -92,19 +108,14 @@ mod tests {
             result_valid.is_ok(),
             "Precondition should pass for valid input"
         );
-        assert_eq!(
-            result_valid.unwrap(),
-            true,
-            "Precondition should return true"
-        );
+        assert!(result_valid.unwrap().is_none(), "Precondition should hold");
 
         // Test invalid precondition
         let result_invalid = precondition(&params_invalid);
         assert!(result_invalid.is_ok(), "Precondition should not error");
-        assert_eq!(
-            result_invalid.unwrap(),
-            false,
-            "Precondition should return false for empty string"
+        assert!(
+            result_invalid.unwrap().is_some(),
+            "Precondition should fail for empty string"
         );
     }
 
@@ -129,10 +140,9 @@ mod tests {
             result.is_ok(),
             "Precondition should pass for valid priority"
         );
-        assert_eq!(
-            result.unwrap(),
-            true,
-            "Precondition should return true for priority 3"
+        assert!(
+            result.unwrap().is_none(),
+            "Precondition should hold for priority 3"
         );
 
         // Test invalid priority (too low)
@@ -145,11 +155,11 @@ mod tests {
 
         let result_low = precondition(&params_low);
         assert!(result_low.is_ok(), "Precondition should not error");
-        assert_eq!(
-            result_low.unwrap(),
-            false,
-            "Precondition should return false for priority 0"
-        );
+        let violation = result_low
+            .unwrap()
+            .expect("Precondition should fail for priority 0");
+        assert_eq!(violation.clause, "priority >= 1");
+        assert_eq!(violation.message, None);
 
         // Test invalid priority (too high)
         let mut params_high: HashMap<String, Box<dyn Any + Send + Sync>> = HashMap::new();
@@ -161,11 +171,16 @@ mod tests {
 
         let result_high = precondition(&params_high);
         assert!(result_high.is_ok(), "Precondition should not error");
+        let violation = result_high
+            .unwrap()
+            .expect("Precondition should fail for priority 6");
+        assert_eq!(violation.operation, "set_priority");
+        assert_eq!(violation.clause, "priority <= 5");
         assert_eq!(
-            result_high.unwrap(),
-            false,
-            "Precondition should return false for priority 6"
+            violation.params,
+            vec![("priority".to_string(), Value::Integer(6))]
         );
+        assert_eq!(violation.to_string(), "priority must be between 1 and 5");
     }
 
     #[test]
@@ -186,11 +201,7 @@ mod tests {
 
         let result_true = precondition(&params_true);
         assert!(result_true.is_ok(), "Precondition should pass for true");
-        assert_eq!(
-            result_true.unwrap(),
-            true,
-            "Precondition should return true"
-        );
+        assert!(result_true.unwrap().is_none(), "Precondition should hold");
 
         // Test with false value
         let mut params_false: HashMap<String, Box<dyn Any + Send + Sync>> = HashMap::new();
@@ -202,10 +213,9 @@ mod tests {
 
         let result_false = precondition(&params_false);
         assert!(result_false.is_ok(), "Precondition should pass for false");
-        assert_eq!(
-            result_false.unwrap(),
-            true,
-            "Precondition should return true for false (it's a valid bool)"
+        assert!(
+            result_false.unwrap().is_none(),
+            "Precondition should hold for false (it's a valid bool)"
         );
     }
 
@@ -251,10 +261,9 @@ mod tests {
             result_min.is_ok(),
             "Precondition should pass for priority 1 (lower bound)"
         );
-        assert_eq!(
-            result_min.unwrap(),
-            true,
-            "Precondition should return true for priority 1"
+        assert!(
+            result_min.unwrap().is_none(),
+            "Precondition should hold for priority 1"
         );
 
         let mut params_max: HashMap<String, Box<dyn Any + Send + Sync>> = HashMap::new();
@@ -269,10 +278,107 @@ mod tests {
             result_max.is_ok(),
             "Precondition should pass for priority 5 (upper bound)"
         );
+        assert!(
+            result_max.unwrap().is_none(),
+            "Precondition should hold for priority 5"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_dispatches_typed_operation() {
+        use holon::core::datasource::OperationProvider;
+        use holon_api::{Operation, OperationDescriptor, StorageEntity};
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Default)]
+        struct RecordingProvider {
+            executed: Mutex<Vec<Operation>>,
+        }
+
+        #[async_trait]
+        impl OperationProvider for RecordingProvider {
+            fn operations(&self) -> Vec<OperationDescriptor> {
+                vec![]
+            }
+
+            async fn execute_operation(
+                &self,
+                entity_name: &str,
+                op_name: &str,
+                params: StorageEntity,
+            ) -> Result<UndoAction> {
+                self.executed.lock().unwrap().push(Operation::new(
+                    entity_name,
+                    op_name,
+                    "",
+                    params,
+                ));
+                Ok(UndoAction::Irreversible)
+            }
+        }
+
+        let provider = Arc::new(RecordingProvider::default());
+        let client = TestTraitClient::new(provider.clone(), "test-entity");
+        client.set_priority("item-1", 3).await.unwrap();
+
+        let executed = provider.executed.lock().unwrap();
+        assert_eq!(executed.len(), 1);
+        assert_eq!(executed[0].entity_name, "test-entity");
+        assert_eq!(executed[0].op_name, "set_priority");
+        assert_eq!(executed[0].params.get("priority"), Some(&Value::Integer(3)));
+    }
+
+    #[test]
+    fn test_triggered_by_defaults() {
+        let ops = __operations_test_move_trait::test_move_trait(
+            "test-entity",
+            "item",
+            "test_table",
+            "id",
+        );
+        let mappings = &ops[0].param_mappings;
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(mappings[0].from, "tree_position");
+        assert_eq!(
+            mappings[0].provides,
+            vec!["parent_id", "position", "offset"]
+        );
+        assert_eq!(
+            mappings[0].defaults.get("position"),
+            Some(&Value::String("last".to_string()))
+        );
         assert_eq!(
-            result_max.unwrap(),
-            true,
-            "Precondition should return true for priority 5"
+            mappings[0].defaults.get("offset"),
+            Some(&Value::Integer(-1))
         );
+        assert!(mappings[1].defaults.is_empty());
+    }
+
+    #[derive(holon_macros::Entity)]
+    #[entity(name = "test_items", short_name = "item", operations(TestTrait))]
+    #[allow(dead_code)]
+    struct TestItem {
+        #[primary_key]
+        id: String,
+        priority: i64,
+    }
+
+    #[test]
+    fn test_entity_operations_are_registered() {
+        use holon::core::datasource::OperationRegistry;
+
+        let ops = <TestItem as OperationRegistry>::all_operations();
+        let names: Vec<&str> = ops.iter().map(|op| op.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["delete", "set_flag", "set_priority", "no_precondition"]
+        );
+        assert!(ops.iter().all(|op| op.entity_name == "test_items"));
+        assert!(ops.iter().all(|op| op.entity_short_name == "item"));
+
+        let registration = holon_api::registered_entity("test_items").unwrap();
+        assert_eq!(registration.primary_key, "id");
+        assert_eq!(registration.operations, &["TestTrait"]);
+        assert_eq!(registration.operations().len(), 4);
     }
 }
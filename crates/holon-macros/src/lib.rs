@@ -4,7 +4,10 @@ use syn::{
     Data, DeriveInput, Fields, FnArg, ItemFn, ItemTrait, Meta, Pat, Type, parse_macro_input,
 };
 
-#[proc_macro_derive(Entity, attributes(entity, primary_key, indexed, reference, lens))]
+#[proc_macro_derive(
+    Entity,
+    attributes(entity, primary_key, indexed, reference, lens, validate, computed)
+)]
 pub fn derive_entity(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
@@ -33,6 +36,7 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
     let mut to_entity_fields = Vec::new();
     let mut from_entity_fields = Vec::new();
     let mut schema_fields = Vec::new();
+    let mut validations = Vec::new();
 
     for field in fields {
         let field_name = field.ident.as_ref().unwrap();
@@ -82,6 +86,16 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
                 }
             });
 
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("validate")) {
+            validations.extend(parse_validate_attribute(attr, &field_name_str, &api_path));
+        }
+
+        let computed_expr = field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("computed"))
+            .map(|attr| parse_computed_attribute(attr, &field_name_str));
+
         if is_primary_key {
             primary_key_field = Some(field_name_str.clone());
         }
@@ -126,6 +140,10 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
                 field_schema_builder = quote! { #field_schema_builder.nullable() };
             }
 
+            if let Some(expr) = &computed_expr {
+                field_schema_builder = quote! { #field_schema_builder.computed(#expr) };
+            }
+
             schema_fields.push(field_schema_builder);
         }
 
@@ -161,7 +179,68 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
 
     let primary_key = primary_key_field.unwrap_or_else(|| "id".to_string());
 
+    let validate_fn = if validations.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            fn validate_entity(
+                entity: &#api_path::DynamicEntity,
+            ) -> std::result::Result<(), Vec<#api_path::ValidationError>> {
+                let mut errors = Vec::new();
+                #(#validations;)*
+                if errors.is_empty() { Ok(()) } else { Err(errors) }
+            }
+        }
+    };
+
+    // Implement OperationRegistry from the traits named in operations(...)
+    let operation_traits = &entity_attr.operations;
+    let operation_registry_impl = if operation_traits.is_empty() {
+        quote! {}
+    } else {
+        let pkg_name = std::env::var("CARGO_PKG_NAME").unwrap_or_default();
+        let datasource_path = if pkg_name == "holon" {
+            quote! { crate::core::datasource }
+        } else {
+            quote! { holon::core::datasource }
+        };
+        quote! {
+            impl #datasource_path::OperationRegistry for #name {
+                fn all_operations() -> Vec<#api_path::OperationDescriptor> {
+                    let entity_name = #entity_name;
+                    #api_path::collect_operations(
+                        &[#(#operation_traits),*],
+                        entity_name,
+                        #name::short_name().unwrap_or(entity_name),
+                        entity_name,
+                        #primary_key,
+                    )
+                }
+
+                fn entity_name() -> &'static str {
+                    #entity_name
+                }
+
+                fn short_name() -> Option<&'static str> {
+                    #name::short_name()
+                }
+            }
+        }
+    };
+
     let expanded = quote! {
+        #api_path::inventory::submit! {
+            #api_path::EntityRegistration {
+                entity_name: #entity_name,
+                short_name: #short_name_expr,
+                primary_key: #primary_key,
+                schema: <#name as #api_path::HasSchema>::schema,
+                operations: &[#(#operation_traits),*],
+            }
+        }
+
+        #operation_registry_impl
+
         impl #name {
             pub fn entity_schema() -> #api_path::EntitySchema {
                 #api_path::EntitySchema {
@@ -203,6 +282,8 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
                     #(#from_entity_fields),*
                 })
             }
+
+            #validate_fn
         }
     };
 
@@ -213,6 +294,8 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
 struct EntityAttribute {
     name: String,
     short_name: Option<String>,
+    /// Names of the operations traits from `operations(...)`
+    operations: Vec<String>,
 }
 
 fn extract_entity_attribute(attrs: &[syn::Attribute]) -> EntityAttribute {
@@ -264,14 +347,92 @@ fn extract_entity_attribute(attrs: &[syn::Attribute]) -> EntityAttribute {
                 None
             };
 
+            // Parse operations(TraitA, TraitB)
+            // (skipping "operations" inside names such as name = "operations")
+            let operations = tokens_str
+                .match_indices("operations")
+                .find_map(|(start, _)| {
+                    let after_key = tokens_str[start + 10..].trim_start(); // len("operations") = 10
+                    let list = after_key.strip_prefix('(')?;
+                    Some(list[..list.find(')')?].to_string())
+                })
+                .map(|list| {
+                    list.split(',')
+                        .map(|name| name.trim().to_string())
+                        .filter(|name| !name.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+
             if let Some(name) = name {
-                return EntityAttribute { name, short_name };
+                return EntityAttribute {
+                    name,
+                    short_name,
+                    operations,
+                };
             }
         }
     }
     panic!("Entity derive macro requires #[entity(name = \"...\")]");
 }
 
+/// Parse a field's `#[validate(length(min = .., max = ..), range(min = .., max = ..))]`
+/// into calls of the matching `holon_api::entity` check
+fn parse_validate_attribute(
+    attr: &syn::Attribute,
+    field_name: &str,
+    api_path: &proc_macro2::TokenStream,
+) -> Vec<proc_macro2::TokenStream> {
+    let mut checks = Vec::new();
+    attr.parse_nested_meta(|meta| {
+        let (check_fn, bound_type) = if meta.path.is_ident("length") {
+            (quote! { validate_length }, quote! { usize })
+        } else if meta.path.is_ident("range") {
+            (quote! { validate_range }, quote! { f64 })
+        } else {
+            return Err(meta.error("expected `length(...)` or `range(...)`"));
+        };
+
+        let mut min = quote! { None };
+        let mut max = quote! { None };
+        meta.parse_nested_meta(|bound| {
+            let expr: syn::Expr = bound.value()?.parse()?;
+            let value = quote! { Some((#expr) as #bound_type) };
+            if bound.path.is_ident("min") {
+                min = value;
+            } else if bound.path.is_ident("max") {
+                max = value;
+            } else {
+                return Err(bound.error("expected `min` or `max`"));
+            }
+            Ok(())
+        })?;
+
+        checks.push(quote! {
+            #api_path::entity::#check_fn(entity, #field_name, #min, #max, &mut errors)
+        });
+        Ok(())
+    })
+    .unwrap_or_else(|e| panic!("Invalid #[validate] on field {}: {}", field_name, e));
+    checks
+}
+
+/// Parse `#[computed(expr = "...")]` into the PRQL expression string
+fn parse_computed_attribute(attr: &syn::Attribute, field_name: &str) -> String {
+    let mut expr = None;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("expr") {
+            let value: syn::LitStr = meta.value()?.parse()?;
+            expr = Some(value.value());
+            Ok(())
+        } else {
+            Err(meta.error("expected `expr = \"...\"`"))
+        }
+    })
+    .unwrap_or_else(|e| panic!("Invalid #[computed] on field {}: {}", field_name, e));
+    expr.unwrap_or_else(|| panic!("#[computed] on field {} needs `expr = \"...\"`", field_name))
+}
+
 fn extract_entity_name(attrs: &[syn::Attribute]) -> String {
     extract_entity_attribute(attrs).name
 }
@@ -429,6 +590,7 @@ fn to_display_name(s: &str) -> String {
 /// - One function `fn OPERATION_NAME_OP() -> OperationDescriptor` per async method
 /// - One function `fn TRAIT_NAME_operations() -> Vec<OperationDescriptor>` returning all operations
 /// - A module `__operations_trait_name` (snake_case) containing all operations
+/// - An `OperationsRegistration`, so entities can list the trait in `#[entity(operations(...))]`
 ///
 /// Usage:
 /// ```rust
@@ -454,6 +616,7 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     let trait_name = &trait_def.ident;
     let operations_fn_name = format_ident!("{}", to_snake_case(&trait_name.to_string()));
+    let trait_name_str = trait_name.to_string();
     let operations_module_name =
         format_ident!("__operations_{}", to_snake_case(&trait_name.to_string()));
 
@@ -593,18 +756,30 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
             };
 
             // Extract and generate precondition if present
-            let precondition_field =
-                if let Some(precondition_tokens) = extract_require_precondition(&method.attrs) {
-                    let precondition_closure =
-                        generate_precondition_closure(method, &precondition_tokens, &crate_path);
-                    quote! {
-                        precondition: Some(#precondition_closure),
-                    }
-                } else {
-                    quote! {
-                        precondition: None,
-                    }
-                };
+            let require_clauses = extract_require_clauses(&method.attrs);
+            let precondition_field = if require_clauses.is_empty() {
+                quote! {
+                    precondition: None,
+                }
+            } else {
+                let precondition_closure =
+                    generate_precondition_closure(method, &require_clauses, &crate_path);
+                quote! {
+                    precondition: Some(#precondition_closure),
+                }
+            };
+
+            // Reference the state-transition function of #[simulate(path)] if present
+            let simulation_field = match extract_simulation(&method.attrs) {
+                Some(simulation) => quote! {
+                    simulation: Some(std::sync::Arc::new(
+                        Box::new(#simulation) as Box<holon_api::StateTransition>
+                    )),
+                },
+                None => quote! {
+                    simulation: None,
+                },
+            };
 
             // Extract affected fields from #[operation(affects = [...])] attribute
             let affected_fields = extract_affected_fields(&method.attrs);
@@ -632,11 +807,16 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
                             .iter()
                             .map(|s| quote! { #s.to_string() })
                             .collect();
+                        let defaults: Vec<_> = m
+                            .defaults
+                            .iter()
+                            .map(|(name, value)| quote! { (#name.to_string(), #value) })
+                            .collect();
                         quote! {
                             holon_api::ParamMapping {
                                 from: #from.to_string(),
                                 provides: vec![#(#provides),*],
-                                defaults: std::collections::HashMap::new(),
+                                defaults: std::collections::HashMap::from([#(#defaults),*]),
                             }
                         }
                     })
@@ -685,6 +865,7 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
                         affected_fields: #affected_fields_expr,
                         param_mappings: #param_mappings_expr,
                         #precondition_field
+                        #simulation_field
                     }
                 }
             }
@@ -840,6 +1021,45 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
         })
         .collect();
 
+    // Generate a typed client method for each operation, calling its *_op constructor
+    let client_name = format_ident!("{}Client", trait_name);
+    let client_methods: Vec<_> = methods
+        .iter()
+        .map(|method| {
+            let method_name = &method.sig.ident;
+            let op_fn_name = format_ident!("{}_op", method_name);
+            let doc_attrs = method.attrs.iter().filter(|attr| attr.path().is_ident("doc"));
+
+            let mut param_defs = Vec::new();
+            let mut param_idents = Vec::new();
+            for arg in method.sig.inputs.iter().skip(1) {
+                // Skip &self
+                if let FnArg::Typed(pat_type) = arg {
+                    let param_name_ident = match &*pat_type.pat {
+                        Pat::Ident(pat_ident) => pat_ident.ident.clone(),
+                        _ => syn::Ident::new(
+                            &extract_param_name(&pat_type.pat),
+                            proc_macro2::Span::call_site(),
+                        ),
+                    };
+                    let param_ty = &pat_type.ty;
+                    param_defs.push(quote! { #param_name_ident: #param_ty });
+                    param_idents.push(param_name_ident);
+                }
+            }
+
+            quote! {
+                #(#doc_attrs)*
+                pub async fn #method_name(&self, #(#param_defs),*) -> Result<#undo_action_path> {
+                    let operation = #op_fn_name(&self.entity_name, #(#param_idents),*);
+                    self.provider
+                        .execute_operation(&operation.entity_name, &operation.op_name, operation.params)
+                        .await
+                }
+            }
+        })
+        .collect();
+
     // Generate dispatch function code for each method
     let dispatch_cases: Vec<_> = methods.iter()
         .map(|method| {
@@ -849,6 +1069,8 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
             // Extract parameters and generate extraction code, building both lists together
             let mut param_extractions_code = Vec::new();
             let mut param_names_for_call = Vec::new();
+            // Schema of the params, checked in debug builds
+            let mut param_field_schemas = Vec::new();
 
             for arg in method.sig.inputs.iter().skip(1) {  // Skip &self
                 if let FnArg::Typed(pat_type) = arg {
@@ -896,6 +1118,27 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
                         false
                     };
 
+                    // A HashMap param receives all params rather than one entry
+                    if type_str_cleaned != "HashMap" {
+                        let field_type = match type_str_cleaned.as_str() {
+                            "String" | "&str" => quote! { holon_api::FieldType::String },
+                            "bool" => quote! { holon_api::FieldType::Boolean },
+                            "i64" | "i32" | "u64" | "u32" | "usize" => {
+                                quote! { holon_api::FieldType::Integer }
+                            }
+                            "DateTime" => quote! { holon_api::FieldType::DateTime },
+                            _ => quote! { holon_api::FieldType::Json },
+                        };
+                        param_field_schemas.push(quote! {
+                            holon_api::EntityFieldSchema {
+                                name: #param_name_str.to_string(),
+                                field_type: #field_type,
+                                required: #is_required,
+                                indexed: false,
+                            }
+                        });
+                    }
+
                     // Generate extraction code based on type
                     let extraction = if type_str_cleaned == "String" || type_str_cleaned == "&str" {
                         if is_optional {
@@ -1021,7 +1264,7 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
                     // Check if it's Result<T>
                     if let syn::Type::Path(type_path) = &**ty {
                         if let Some(segment) = type_path.path.segments.last() {
-                            if segment.ident == "Result" {
+                            if segment.ident == "Result" || segment.ident == "HolonResult" {
                                 if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
                                     if let Some(syn::GenericArgument::Type(inner_ty)) = args.args.first() {
                                         // Check inner type
@@ -1033,6 +1276,12 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
                                                     target.#method_name(#(#param_names_for_call),*).await.map(|_| #undo_action_path::Irreversible)
                                                 }
                                             }
+                                            syn::Type::Tuple(tuple) if tuple.elems.is_empty() => {
+                                                // Result<()> -> Result<UndoAction> (nothing to undo with)
+                                                quote! {
+                                                    target.#method_name(#(#param_names_for_call),*).await.map(|_| #undo_action_path::Irreversible)
+                                                }
+                                            }
                                             syn::Type::Tuple(tuple) if tuple.elems.len() == 2 => {
                                                 // Result<(String, UndoAction)> -> Result<UndoAction> (extract the UndoAction)
                                                 quote! {
@@ -1047,6 +1296,11 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
                                                         quote! {
                                                             target.#method_name(#(#param_names_for_call),*).await
                                                         }
+                                                    } else if seg.ident == "Vec" {
+                                                        // Result<Vec<UndoAction>> -> Result<UndoAction> (inverses of each step, undone last to first)
+                                                        quote! {
+                                                            target.#method_name(#(#param_names_for_call),*).await.map(#undo_action_path::Composite)
+                                                        }
                                                     } else if seg.ident == "Option" {
                                                         // Result<Option<Operation>> -> Result<UndoAction> (convert via Into)
                                                         quote! {
@@ -1103,10 +1357,22 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
                 }
             };
 
+            // Methods may return a structured HolonError; dispatch returns the boxed Result.
+            // Debug builds reject params of the wrong type (which extraction would read as
+            // missing, or silently drop if optional) with an error naming the field.
             quote! {
                 #method_name_str => {
+                    #[cfg(debug_assertions)]
+                    {
+                        let schema = holon_api::EntitySchema {
+                            name: #method_name_str.to_string(),
+                            fields: vec![#(#param_field_schemas),*],
+                            primary_key: "id".to_string(),
+                        };
+                        holon_api::TypedEntity::from_params(&schema, params)?;
+                    }
                     #(#param_extractions_code)*
-                    #return_handling
+                    (#return_handling).map_err(Into::into)
                 }
             }
         })
@@ -1122,6 +1388,10 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
         })
         .collect();
 
+    // Send + Sync on native only: on wasm32 operations run on a single thread and
+    // datasources (JS handles, `Rc` state) need not be Send
+    let maybe_send_sync = quote! { #crate_path::core::datasource::MaybeSendSync };
+
     // Generate the dispatch function differently based on whether trait has generics
     let dispatch_fn = if has_generics {
         quote! {
@@ -1131,8 +1401,8 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
                 params: &StorageEntity
             ) -> Result<#undo_action_path>
             where
-                DS: #trait_name<E> + Send + Sync,
-                E: Send + Sync + 'static,
+                DS: #trait_name<E> + #maybe_send_sync,
+                E: #maybe_send_sync + 'static,
                 #(#entity_constraints),*
             {
                 match op_name {
@@ -1152,7 +1422,7 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
                 params: &StorageEntity
             ) -> Result<#undo_action_path>
             where
-                DS: #trait_name + Send + Sync,
+                DS: #trait_name + #maybe_send_sync,
             {
                 match op_name {
                     #(#dispatch_cases),*
@@ -1169,6 +1439,8 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
         // Original trait (unchanged)
         #trait_def
 
+        pub use #operations_module_name::#client_name;
+
         // Generated operations module
         #[doc(hidden)]
         pub mod #operations_module_name {
@@ -1184,6 +1456,35 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
             // Operation constructor functions (*_op)
             #(#operation_constructor_fns)*
 
+            /// Typed client for the operations of this trait
+            ///
+            /// Each method builds the `Operation` with its `*_op` constructor and executes
+            /// it through the wrapped `OperationProvider` (usually the `OperationDispatcher`),
+            /// so callers get checked parameter types instead of building `StorageEntity` maps.
+            #[derive(Clone)]
+            pub struct #client_name {
+                provider: std::sync::Arc<dyn #crate_path::core::datasource::OperationProvider>,
+                entity_name: String,
+            }
+
+            impl #client_name {
+                pub fn new(
+                    provider: std::sync::Arc<dyn #crate_path::core::datasource::OperationProvider>,
+                    entity_name: impl Into<String>,
+                ) -> Self {
+                    Self {
+                        provider,
+                        entity_name: entity_name.into(),
+                    }
+                }
+
+                pub fn entity_name(&self) -> &str {
+                    &self.entity_name
+                }
+
+                #(#client_methods)*
+            }
+
             /// All operations for this trait
             ///
             /// Parameters:
@@ -1202,6 +1503,14 @@ pub fn operations_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
                 ]
             }
 
+            // Lets entities list this trait in `#[entity(operations(...))]`
+            holon_api::inventory::submit! {
+                holon_api::OperationsRegistration {
+                    trait_name: #trait_name_str,
+                    operations: #operations_fn_name,
+                }
+            }
+
             /// Dispatch operation to appropriate trait method
             ///
             /// Extracts parameters from StorageEntity and calls the appropriate trait method.
@@ -1257,12 +1566,15 @@ fn extract_doc_comments(attrs: &[syn::Attribute]) -> String {
     docs.join(" ")
 }
 
-/// Extract require precondition tokens from attributes
-///
-/// Returns the combined tokens from all #[require(...)] attributes,
-/// combined with && operator if multiple exist.
-fn extract_require_precondition(attrs: &[syn::Attribute]) -> Option<proc_macro2::TokenStream> {
-    let mut preconditions = Vec::new();
+/// A `#[require(condition)]` or `#[require(condition, "message")]` clause
+struct RequireClause {
+    condition: proc_macro2::TokenStream,
+    message: Option<String>,
+}
+
+/// Extract the clauses of all #[require(...)] attributes, in declaration order
+fn extract_require_clauses(attrs: &[syn::Attribute]) -> Vec<RequireClause> {
+    let mut clauses = Vec::new();
 
     for attr in attrs {
         // Check if this is a require attribute (either #[require(...)] or #[holon_macros::require(...)])
@@ -1273,28 +1585,71 @@ fn extract_require_precondition(attrs: &[syn::Attribute]) -> Option<proc_macro2:
 
         if is_require {
             if let Meta::List(meta_list) = &attr.meta {
-                preconditions.push(meta_list.tokens.clone());
+                clauses.push(parse_require_clause(meta_list.tokens.clone()));
             }
         }
     }
 
-    if preconditions.is_empty() {
-        None
-    } else if preconditions.len() == 1 {
-        Some(preconditions[0].clone())
-    } else {
-        // Combine multiple preconditions with &&
-        let mut combined = preconditions[0].clone();
-        for prec in preconditions.iter().skip(1) {
-            combined = quote! { (#combined) && (#prec) };
+    clauses
+}
+
+/// Split a trailing `, "message"` off a require clause
+///
+/// Commas inside the condition are nested in groups (calls, closures), so only a
+/// top-level comma followed by a single string literal starts a message.
+fn parse_require_clause(tokens: proc_macro2::TokenStream) -> RequireClause {
+    use proc_macro2::TokenTree;
+
+    let trees: Vec<TokenTree> = tokens.clone().into_iter().collect();
+    if let [
+        condition @ ..,
+        TokenTree::Punct(comma),
+        TokenTree::Literal(literal),
+    ] = trees.as_slice()
+    {
+        if comma.as_char() == ',' {
+            if let Ok(syn::Lit::Str(message)) = syn::parse2::<syn::Lit>(quote! { #literal }) {
+                return RequireClause {
+                    condition: condition.iter().cloned().collect(),
+                    message: Some(message.value()),
+                };
+            }
         }
-        Some(combined)
+    }
+
+    RequireClause {
+        condition: tokens,
+        message: None,
     }
 }
 
+/// Whether `ident` occurs anywhere in `tokens`, including nested groups
+fn tokens_mention(tokens: &proc_macro2::TokenStream, ident: &str) -> bool {
+    tokens.clone().into_iter().any(|tree| match tree {
+        proc_macro2::TokenTree::Ident(i) => i == ident,
+        proc_macro2::TokenTree::Group(group) => tokens_mention(&group.stream(), ident),
+        _ => false,
+    })
+}
+
 /// Extract affected fields from #[affects(...)] or #[operation(affects = [...])] attribute
 ///
 /// Returns a Vec<String> of field names, or empty vec if not found.
+/// Extract the function path of a #[simulate(path::to::function)] attribute
+fn extract_simulation(attrs: &[syn::Attribute]) -> Option<syn::Path> {
+    attrs.iter().find_map(|attr| {
+        let is_simulate_attr = attr.path().is_ident("simulate")
+            || (attr.path().segments.len() == 2
+                && attr.path().segments[0].ident == "holon_macros"
+                && attr.path().segments[1].ident == "simulate");
+        if is_simulate_attr {
+            attr.parse_args::<syn::Path>().ok()
+        } else {
+            None
+        }
+    })
+}
+
 fn extract_affected_fields(attrs: &[syn::Attribute]) -> Vec<String> {
     for attr in attrs {
         // Check if this is an affects attribute
@@ -1366,6 +1721,9 @@ struct ParsedParamMapping {
     /// What required params this provides (e.g., ["parent_id", "after_block_id"])
     /// If empty/not specified, defaults to [availability_of] (identity mapping)
     providing: Vec<String>,
+    /// Values of provided params the source may not carry (e.g., "position" = "last"),
+    /// as `holon_api::Value` expressions
+    defaults: Vec<(String, proc_macro2::TokenStream)>,
 }
 
 /// Extract param_mappings from method attributes.
@@ -1376,6 +1734,9 @@ struct ParsedParamMapping {
 /// If `providing` is not specified, it defaults to `[availability_of]` (identity mapping),
 /// which is useful for declaring intent without transformation.
 ///
+/// `defaults = { "position" = "last", "count" = 1 }` gives values for provided params
+/// the source doesn't carry; values are string, integer, float or bool literals.
+///
 /// Returns a Vec of ParsedParamMapping.
 fn extract_param_mappings(attrs: &[syn::Attribute]) -> Vec<ParsedParamMapping> {
     let mut mappings = Vec::new();
@@ -1436,6 +1797,30 @@ fn extract_param_mappings(attrs: &[syn::Attribute]) -> Vec<ParsedParamMapping> {
                     }
                 }
 
+                // Extract "defaults" map (optional): { "name" = value, ... }
+                let mut default_values = Vec::new();
+                if let Some(start) = tokens_str.find("defaults") {
+                    let after_key = &tokens_str[start + 8..]; // len("defaults") = 8
+                    let trimmed = after_key.trim_start();
+                    if let Some(brace_start) = trimmed.find('{') {
+                        if let Some(brace_end) = trimmed.find('}') {
+                            let map_content = &trimmed[brace_start + 1..brace_end];
+                            for entry in map_content.split(',') {
+                                let Some((name, value)) = entry.split_once('=') else {
+                                    continue;
+                                };
+                                let name = name.trim();
+                                if name.starts_with('"') && name.ends_with('"') && name.len() > 1 {
+                                    if let Some(value) = default_value_expr(value.trim()) {
+                                        default_values
+                                            .push((name[1..name.len() - 1].to_string(), value));
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
                 if let Some(availability_of) = availability_of_value {
                     // If providing is empty, default to identity mapping [availability_of]
                     let providing = if providing_values.is_empty() {
@@ -1447,6 +1832,7 @@ fn extract_param_mappings(attrs: &[syn::Attribute]) -> Vec<ParsedParamMapping> {
                     mappings.push(ParsedParamMapping {
                         availability_of,
                         providing,
+                        defaults: default_values,
                     });
                 }
             }
@@ -1456,13 +1842,37 @@ fn extract_param_mappings(attrs: &[syn::Attribute]) -> Vec<ParsedParamMapping> {
     mappings
 }
 
+/// `holon_api::Value` expression of a literal in `#[triggered_by(defaults = {...})]`
+fn default_value_expr(literal: &str) -> Option<proc_macro2::TokenStream> {
+    if literal.len() > 1 && literal.starts_with('"') && literal.ends_with('"') {
+        let value = &literal[1..literal.len() - 1];
+        return Some(quote! { holon_api::Value::String(#value.to_string()) });
+    }
+    match literal {
+        "true" => return Some(quote! { holon_api::Value::Boolean(true) }),
+        "false" => return Some(quote! { holon_api::Value::Boolean(false) }),
+        _ => {}
+    }
+    // Negative numbers are tokenized as `- 1`
+    let number = literal.replace(' ', "");
+    if let Ok(value) = number.parse::<i64>() {
+        return Some(quote! { holon_api::Value::Integer(#value) });
+    }
+    number
+        .parse::<f64>()
+        .ok()
+        .map(|value| quote! { holon_api::Value::Float(#value) })
+}
+
 /// Generate precondition closure code for a method
 ///
 /// Creates a closure that extracts parameters from HashMap<String, Box<dyn Any>>,
-/// converts them to the appropriate types, and evaluates the precondition expression.
+/// converts them to the appropriate types, and evaluates the require clauses in
+/// order. The first failing clause is returned as a `PreconditionViolation` with
+/// the values of the parameters it mentions.
 fn generate_precondition_closure(
     method: &syn::TraitItemFn,
-    precondition_tokens: &proc_macro2::TokenStream,
+    clauses: &[RequireClause],
     crate_path: &proc_macro2::TokenStream,
 ) -> proc_macro2::TokenStream {
     // Generate parameter extraction and type conversion code
@@ -1602,6 +2012,54 @@ fn generate_precondition_closure(
         }
     }
 
+    let operation_name = method.sig.ident.to_string();
+    let param_names: Vec<String> = method
+        .sig
+        .inputs
+        .iter()
+        .skip(1)
+        .filter_map(|arg| match arg {
+            FnArg::Typed(pat_type) => Some(extract_param_name(&pat_type.pat)),
+            _ => None,
+        })
+        .collect();
+
+    let clause_checks: Vec<_> = clauses
+        .iter()
+        .map(|clause| {
+            let condition = &clause.condition;
+            let clause_str = condition.to_string();
+            let message = match &clause.message {
+                Some(message) => quote! { Some(#message.to_string()) },
+                None => quote! { None },
+            };
+            // Report the raw values of the parameters the clause mentions
+            let mentioned_params = param_names
+                .iter()
+                .filter(|name| tokens_mention(condition, name))
+                .map(|name| {
+                    quote! {
+                        (
+                            #name.to_string(),
+                            params.get(#name)
+                                .and_then(|any_val| any_val.downcast_ref::<holon_api::Value>().cloned())
+                                .unwrap_or(holon_api::Value::Null),
+                        )
+                    }
+                });
+            quote! {
+                if !(#condition) {
+                    return Ok(Some(holon_api::PreconditionViolation {
+                        operation: #operation_name.to_string(),
+                        clause: #clause_str.to_string(),
+                        message: #message,
+                        params: vec![#(#mentioned_params),*],
+                    }));
+                }
+            }
+        })
+        .collect();
+
     // Generate the closure that wraps everything
     quote! {
         {
@@ -1609,9 +2067,10 @@ fn generate_precondition_closure(
             use std::any::Any;
             use std::collections::HashMap;
 
-            Arc::new(Box::new(move |params: &HashMap<String, Box<dyn Any + Send + Sync>>| -> std::result::Result<bool, String> {
+            Arc::new(Box::new(move |params: &HashMap<String, Box<dyn Any + Send + Sync>>| -> std::result::Result<Option<holon_api::PreconditionViolation>, String> {
                 #(#param_declarations)*
-                Ok(#precondition_tokens)
+                #(#clause_checks)*
+                Ok(None)
             }) as Box<holon_api::PreconditionChecker>)
         }
     }
@@ -1772,6 +2231,21 @@ pub fn affects(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// Pass-through attribute for #[simulate(path)] - allows Rust to accept the attribute
+/// The actual parsing is done by extract_simulation() in the operations_trait macro.
+///
+/// `path` names a pure function modelling the operation's effect
+/// (see `holon_api::StateTransition`); it becomes the descriptor's `simulation`:
+/// ```rust
+/// #[simulate(crate::testing::simulate_delete)]
+/// async fn delete(&self, id: &str) -> Result<UndoAction>
+/// ```
+#[proc_macro_attribute]
+pub fn simulate(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    // Pass through unchanged - this just allows Rust to accept the attribute
+    item
+}
+
 /// Pass-through attribute for #[triggered_by(...)] - allows Rust to accept the attribute
 /// The actual parsing is done by extract_param_mappings() in the operations_trait macro.
 ///
@@ -1788,6 +2262,10 @@ pub fn affects(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// // Identity case: completed triggers and provides itself
 /// #[triggered_by(availability_of = "completed")]
 /// async fn set_completion(&self, id: &str, completed: bool) -> Result<()>
+///
+/// // Defaults: a drop on a parent provides no position, so it defaults to "last"
+/// #[triggered_by(availability_of = "drop_target", providing = ["parent_id", "position"], defaults = { "position" = "last" })]
+/// async fn move_to(&self, id: &str, parent_id: &str, position: &str) -> Result<()>
 /// ```
 #[proc_macro_attribute]
 pub fn triggered_by(_attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -1909,15 +2387,15 @@ mod tests {
             async fn delete(&self, id: &str) -> Result<()>;
         };
 
-        let result = extract_require_precondition(&method.attrs);
-        assert!(result.is_some(), "Should extract precondition");
-        let tokens = result.unwrap();
-        let code = quote! { #tokens }.to_string();
+        let clauses = extract_require_clauses(&method.attrs);
+        assert_eq!(clauses.len(), 1, "Should extract precondition");
+        let code = clauses[0].condition.to_string();
         // The code might have extra formatting, so check for key parts
         assert!(
             code.contains("id") && code.contains("len"),
             "Should contain the precondition code"
         );
+        assert!(clauses[0].message.is_none());
     }
 
     #[test]
@@ -1925,23 +2403,43 @@ mod tests {
         // Create a method with multiple require attributes
         let method: TraitItemFn = parse_quote! {
             #[require(priority >= 1)]
-            #[require(priority <= 5)]
+            #[require(priority <= 5, "priority must be between 1 and 5")]
             async fn set_priority(&self, id: &str, priority: i64) -> Result<()>;
         };
 
-        let result = extract_require_precondition(&method.attrs);
-        assert!(result.is_some(), "Should extract combined preconditions");
-        let tokens = result.unwrap();
-        let code = quote! { #tokens }.to_string();
-        assert!(
-            code.contains("priority >= 1"),
-            "Should contain first precondition"
+        let clauses = extract_require_clauses(&method.attrs);
+        assert_eq!(clauses.len(), 2, "Should keep each clause separately");
+        assert_eq!(clauses[0].condition.to_string(), "priority >= 1");
+        assert_eq!(clauses[1].condition.to_string(), "priority <= 5");
+        assert_eq!(
+            clauses[1].message.as_deref(),
+            Some("priority must be between 1 and 5")
         );
-        assert!(
-            code.contains("priority <= 5"),
-            "Should contain second precondition"
+    }
+
+    #[test]
+    fn test_require_clause_keeps_nested_commas() {
+        let method: TraitItemFn = parse_quote! {
+            #[require(["a", "b"].contains(&kind.as_str()))]
+            async fn set_kind(&self, id: &str, kind: String) -> Result<()>;
+        };
+
+        let clauses = extract_require_clauses(&method.attrs);
+        assert!(clauses[0].message.is_none());
+        assert!(tokens_mention(&clauses[0].condition, "kind"));
+        assert!(!tokens_mention(&clauses[0].condition, "id"));
+    }
+
+    #[test]
+    fn test_parse_computed_attribute() {
+        let field: syn::Field = parse_quote! {
+            #[computed(expr = "priority * 10 - due_in_days")]
+            urgency: Option<i64>
+        };
+        assert_eq!(
+            parse_computed_attribute(&field.attrs[0], "urgency"),
+            "priority * 10 - due_in_days"
         );
-        assert!(code.contains("&&"), "Should combine with &&");
     }
 
     #[test]
@@ -1951,8 +2449,29 @@ mod tests {
             async fn no_precondition(&self, id: &str) -> Result<()>;
         };
 
-        let result = extract_require_precondition(&method.attrs);
-        assert!(result.is_none(), "Should return None when no precondition");
+        let clauses = extract_require_clauses(&method.attrs);
+        assert!(
+            clauses.is_empty(),
+            "Should return no clauses when no precondition"
+        );
+    }
+
+    #[test]
+    fn test_extract_simulation() {
+        let method: TraitItemFn = parse_quote! {
+            #[holon_macros::simulate(crate::testing::simulate_delete)]
+            async fn delete(&self, id: &str) -> Result<()>;
+        };
+        let simulation = extract_simulation(&method.attrs).expect("Should extract the path");
+        assert_eq!(
+            quote!(#simulation).to_string(),
+            "crate :: testing :: simulate_delete"
+        );
+
+        let method: TraitItemFn = parse_quote! {
+            async fn delete(&self, id: &str) -> Result<()>;
+        };
+        assert!(extract_simulation(&method.attrs).is_none());
     }
 
     #[test]
@@ -1963,10 +2482,9 @@ mod tests {
             async fn delete(&self, id: &str) -> Result<()>;
         };
 
-        let precondition_tokens = extract_require_precondition(&method.attrs).unwrap();
+        let clauses = extract_require_clauses(&method.attrs);
         let crate_path = quote! { crate };
-        let closure_code =
-            generate_precondition_closure(&method, &precondition_tokens, &crate_path);
+        let closure_code = generate_precondition_closure(&method, &clauses, &crate_path);
 
         // Verify the generated code compiles (by checking it has expected structure)
         let code_str = quote! { #closure_code }.to_string();
@@ -1981,6 +2499,10 @@ mod tests {
         );
         assert!(code_str.contains("params"), "Should extract from params");
         assert!(code_str.contains("id"), "Should reference parameter name");
+        assert!(
+            code_str.contains("PreconditionViolation"),
+            "Should report the failed clause"
+        );
     }
 
     #[test]
@@ -1991,10 +2513,9 @@ mod tests {
             async fn set_flag(&self, id: &str, value: bool) -> Result<()>;
         };
 
-        let precondition_tokens = extract_require_precondition(&method.attrs).unwrap();
+        let clauses = extract_require_clauses(&method.attrs);
         let crate_path = quote! { crate };
-        let closure_code =
-            generate_precondition_closure(&method, &precondition_tokens, &crate_path);
+        let closure_code = generate_precondition_closure(&method, &clauses, &crate_path);
 
         let code_str = quote! { #closure_code }.to_string();
         assert!(code_str.contains("as_bool"), "Should convert to bool");
@@ -2012,10 +2533,9 @@ mod tests {
             async fn set_priority(&self, id: &str, priority: i64) -> Result<()>;
         };
 
-        let precondition_tokens = extract_require_precondition(&method.attrs).unwrap();
+        let clauses = extract_require_clauses(&method.attrs);
         let crate_path = quote! { crate };
-        let closure_code =
-            generate_precondition_closure(&method, &precondition_tokens, &crate_path);
+        let closure_code = generate_precondition_closure(&method, &clauses, &crate_path);
 
         let code_str = quote! { #closure_code }.to_string();
         assert!(code_str.contains("as_i64"), "Should convert to i64");
@@ -2029,6 +2549,9 @@ mod tests {
 /// No-op proc macro for #[require(...)] attribute
 /// This allows the attribute to be recognized by Rust's parser
 /// The actual processing is done by the operations_trait macro
+///
+/// An optional message is shown when the clause fails:
+/// `#[require(priority <= 5, "priority must be between 1 and 5")]`
 #[proc_macro_attribute]
 pub fn require(_attr: TokenStream, item: TokenStream) -> TokenStream {
     // Just return the item unchanged - the operations_trait macro will process the require attributes
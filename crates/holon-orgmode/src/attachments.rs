@@ -0,0 +1,141 @@
+//! Org-mode `file:` links as attachments
+//!
+//! A headline's `[[file:report.pdf]]` and `[[file:report.pdf][Q3 report]]` links
+//! are imported as `Attachment`s of that headline, so they show up alongside
+//! attachments of other sources. Relative paths resolve against the org file's
+//! directory; search options (`file:notes.org::*Heading`) are dropped.
+
+use std::path::Path;
+
+use holon::core::attachments::Attachment;
+
+use crate::clock::clock_source;
+
+/// Source of attachments imported from an org file's links
+pub fn attachment_source(file_id: &str) -> String {
+    clock_source(file_id)
+}
+
+/// A parsed `[[file:...]]` link
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLink {
+    /// Link target as written, without the `file:` prefix or search option
+    pub path: String,
+    /// Link description, if any
+    pub description: Option<String>,
+}
+
+/// All `file:` links in a section, in document order
+pub fn parse_file_links(section: &str) -> Vec<FileLink> {
+    let mut links = Vec::new();
+    let mut rest = section;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("]]") else {
+            break;
+        };
+        let link = &rest[..end];
+        rest = &rest[end + 2..];
+
+        let (target, description) = match link.split_once("][") {
+            Some((target, description)) => (target, Some(description.trim().to_string())),
+            None => (link, None),
+        };
+        let Some(path) = target.strip_prefix("file:") else {
+            continue;
+        };
+        let path = path.split("::").next().unwrap_or_default().trim();
+        if path.is_empty() {
+            continue;
+        }
+        links.push(FileLink {
+            path: path.to_string(),
+            description: description.filter(|d| !d.is_empty()),
+        });
+    }
+    links
+}
+
+/// Absolute path of a link target, relative to the org file at `org_path`
+pub fn resolve_link_path(link_path: &str, org_path: &Path) -> String {
+    if let Some(home_relative) = link_path.strip_prefix("~/") {
+        if let Some(home) = std::env::var_os("HOME") {
+            return Path::new(&home)
+                .join(home_relative)
+                .to_string_lossy()
+                .to_string();
+        }
+    }
+    let path = Path::new(link_path);
+    if path.is_absolute() {
+        return link_path.to_string();
+    }
+    org_path
+        .parent()
+        .map(|dir| dir.join(path))
+        .unwrap_or_else(|| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Attachments for a headline's `file:` links
+pub fn file_attachments(
+    headline_id: &str,
+    file_id: &str,
+    org_path: &Path,
+    section: &str,
+    created_at: i64,
+) -> Vec<Attachment> {
+    let source = attachment_source(file_id);
+    parse_file_links(section)
+        .into_iter()
+        .map(|link| {
+            let path = resolve_link_path(&link.path, org_path);
+            let mut attachment =
+                Attachment::file("org_headlines", headline_id, path, &source, created_at);
+            if let Some(description) = link.description {
+                attachment.name = description;
+            }
+            attachment
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_links() {
+        let section = "See [[file:report.pdf][Q3 report]] and [[file:~/img.png]].\n\
+                       Not a file: [[https://example.com][site]] [[file:notes.org::*Todo]]\n";
+        let links = parse_file_links(section);
+        assert_eq!(links.len(), 3);
+        assert_eq!(links[0].path, "report.pdf");
+        assert_eq!(links[0].description.as_deref(), Some("Q3 report"));
+        assert_eq!(links[1].path, "~/img.png");
+        assert_eq!(links[1].description, None);
+        assert_eq!(links[2].path, "notes.org");
+    }
+
+    #[test]
+    fn test_file_attachments_resolve_relative_paths() {
+        let attachments = file_attachments(
+            "h1",
+            "f1",
+            Path::new("/org/projects/plan.org"),
+            "[[file:docs/spec.pdf][Spec]] [[file:/tmp/a.txt]]",
+            0,
+        );
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(
+            attachments[0].path.as_deref(),
+            Some("/org/projects/docs/spec.pdf")
+        );
+        assert_eq!(attachments[0].name, "Spec");
+        assert_eq!(attachments[0].mime_type.as_deref(), Some("application/pdf"));
+        assert_eq!(attachments[0].source, "org:f1");
+        assert_eq!(attachments[1].path.as_deref(), Some("/tmp/a.txt"));
+        assert_eq!(attachments[1].name, "a.txt");
+    }
+}
@@ -0,0 +1,272 @@
+//! Org-mode CLOCK lines in `:LOGBOOK:` drawers
+//!
+//! Clocking in adds an open `CLOCK: [2024-01-15 Mon 09:00]` line to the headline's
+//! logbook (creating the drawer if needed); clocking out closes it as
+//! `CLOCK: [2024-01-15 Mon 09:00]--[2024-01-15 Mon 10:30] =>  1:30`, exactly as
+//! Emacs does. Timestamps are minute-precision local time.
+
+use anyhow::{bail, Result};
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone};
+
+use crate::writer::{HeadlineSpan, TextEdit};
+use holon::core::time_tracking::{format_duration, TimeEntry};
+
+/// A parsed CLOCK line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockLine {
+    /// Unix timestamp in milliseconds
+    pub started_at: i64,
+    /// Unix timestamp in milliseconds; None for a running clock
+    pub ended_at: Option<i64>,
+}
+
+/// Source of time entries imported from an org file's clocks
+pub fn clock_source(file_id: &str) -> String {
+    format!("org:{}", file_id)
+}
+
+/// Format a timestamp as an inactive org timestamp (`[2024-01-15 Mon 09:00]`)
+pub fn format_org_timestamp(timestamp_ms: i64) -> String {
+    match Local.timestamp_millis_opt(timestamp_ms).earliest() {
+        Some(time) => time.format("[%Y-%m-%d %a %H:%M]").to_string(),
+        None => String::from("[1970-01-01 Thu 00:00]"),
+    }
+}
+
+/// Parse an org timestamp (`[2024-01-15 Mon 09:00]`), ignoring the day name
+pub fn parse_org_timestamp(text: &str) -> Option<i64> {
+    let inner = text.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let mut parts = inner.split_whitespace();
+    let date = NaiveDate::parse_from_str(parts.next()?, "%Y-%m-%d").ok()?;
+    let time = NaiveTime::parse_from_str(parts.last()?, "%H:%M").ok()?;
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|t| t.timestamp_millis())
+}
+
+/// Parse a `CLOCK:` line (open or closed)
+pub fn parse_clock_line(line: &str) -> Option<ClockLine> {
+    let rest = line.trim().strip_prefix("CLOCK:")?.trim();
+    let (range, _duration) = rest.split_once("=>").unwrap_or((rest, ""));
+    let range = range.trim();
+    match range.split_once("--") {
+        Some((start, end)) => Some(ClockLine {
+            started_at: parse_org_timestamp(start)?,
+            ended_at: Some(parse_org_timestamp(end)?),
+        }),
+        None => Some(ClockLine {
+            started_at: parse_org_timestamp(range)?,
+            ended_at: None,
+        }),
+    }
+}
+
+/// Format a CLOCK line (without indentation or newline)
+pub fn format_clock_line(started_at: i64, ended_at: Option<i64>) -> String {
+    match ended_at {
+        Some(ended_at) => format!(
+            "CLOCK: {}--{} => {:>5}",
+            format_org_timestamp(started_at),
+            format_org_timestamp(ended_at),
+            format_duration(ended_at - started_at)
+        ),
+        None => format!("CLOCK: {}", format_org_timestamp(started_at)),
+    }
+}
+
+/// All CLOCK lines in a section, in document order
+pub fn parse_clocks(section: &str) -> Vec<ClockLine> {
+    section.lines().filter_map(parse_clock_line).collect()
+}
+
+/// Time entries for a headline's clocks
+pub fn clock_entries(headline_id: &str, file_id: &str, section: &str) -> Vec<TimeEntry> {
+    let source = clock_source(file_id);
+    parse_clocks(section)
+        .into_iter()
+        .map(|clock| {
+            let mut entry =
+                TimeEntry::start("org_headlines", headline_id, clock.started_at, &source);
+            if let Some(ended_at) = clock.ended_at {
+                entry.stop(ended_at);
+            }
+            entry
+        })
+        .collect()
+}
+
+/// Byte range (within `content`) of the headline's running CLOCK line
+fn open_clock_line(content: &str, span: &HeadlineSpan) -> Option<(usize, usize)> {
+    let mut pos = span.line_end;
+    for line in content[span.line_end..span.section_end].split_inclusive('\n') {
+        let text = line.trim_end_matches(['\n', '\r']);
+        if parse_clock_line(text).is_some_and(|clock| clock.ended_at.is_none()) {
+            return Some((pos, pos + text.len()));
+        }
+        pos += line.len();
+    }
+    None
+}
+
+/// Where a new `:LOGBOOK:` drawer goes: after planning lines and the property drawer
+fn logbook_position(content: &str, span: &HeadlineSpan) -> usize {
+    let mut pos = span.line_end;
+    let mut in_properties = false;
+    for (i, line) in content[span.line_end..span.section_end]
+        .split_inclusive('\n')
+        .enumerate()
+    {
+        let trimmed = line.trim();
+        // The first chunk is the newline ending the headline line
+        let skip = (i == 0 && trimmed.is_empty())
+            || in_properties
+            || trimmed == ":PROPERTIES:"
+            || trimmed.starts_with("SCHEDULED:")
+            || trimmed.starts_with("DEADLINE:")
+            || trimmed.starts_with("CLOSED:");
+        if !skip {
+            break;
+        }
+        if trimmed == ":PROPERTIES:" {
+            in_properties = true;
+        } else if trimmed == ":END:" {
+            in_properties = false;
+        }
+        pos += line.len();
+    }
+    pos
+}
+
+/// Edit adding a running clock to a headline's logbook
+pub fn clock_in_edit(content: &str, span: &HeadlineSpan, now: i64) -> Result<TextEdit> {
+    if open_clock_line(content, span).is_some() {
+        bail!("Headline is already clocked in");
+    }
+    let clock = format_clock_line(now, None);
+
+    // Newest clocks go first, right after an existing :LOGBOOK: line
+    let mut pos = span.line_end;
+    for line in content[span.line_end..span.section_end].split_inclusive('\n') {
+        pos += line.len();
+        if line.trim() == ":LOGBOOK:" {
+            let prefix = if line.ends_with('\n') { "" } else { "\n" };
+            return Ok(TextEdit::insert(pos, format!("{}{}\n", prefix, clock)));
+        }
+    }
+
+    // Otherwise create the drawer right after planning lines and the property drawer
+    let pos = logbook_position(content, span);
+    let prefix = if content[..pos].ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    Ok(TextEdit::insert(
+        pos,
+        format!("{}:LOGBOOK:\n{}\n:END:\n", prefix, clock),
+    ))
+}
+
+/// Edit closing a headline's running clock
+pub fn clock_out_edit(content: &str, span: &HeadlineSpan, now: i64) -> Result<TextEdit> {
+    let Some((start, end)) = open_clock_line(content, span) else {
+        bail!("Headline is not clocked in");
+    };
+    let line = &content[start..end];
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let clock = parse_clock_line(line).expect("open clock line parses");
+    let ended_at = now.max(clock.started_at);
+    Ok(TextEdit::replace(
+        content,
+        start,
+        end,
+        format!(
+            "{}{}",
+            indent,
+            format_clock_line(clock.started_at, Some(ended_at))
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::{apply_edits, headline_spans};
+
+    fn local_ms(date: &str, time: &str) -> i64 {
+        parse_org_timestamp(&format!("[{} Mon {}]", date, time)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_format_clock_lines() {
+        let start = local_ms("2024-01-15", "09:00");
+        let end = local_ms("2024-01-15", "10:30");
+
+        let closed = format_clock_line(start, Some(end));
+        assert_eq!(
+            closed,
+            "CLOCK: [2024-01-15 Mon 09:00]--[2024-01-15 Mon 10:30] =>  1:30"
+        );
+        assert_eq!(
+            parse_clock_line(&format!("  {}", closed)),
+            Some(ClockLine {
+                started_at: start,
+                ended_at: Some(end)
+            })
+        );
+        assert_eq!(
+            parse_clock_line("CLOCK: [2024-01-15 Mon 09:00]"),
+            Some(ClockLine {
+                started_at: start,
+                ended_at: None
+            })
+        );
+        assert_eq!(parse_clock_line("CLOSED: [2024-01-15 Mon 09:00]"), None);
+    }
+
+    #[test]
+    fn test_clock_in_and_out_round_trip() {
+        let content = "* TODO Write report\nSCHEDULED: <2024-01-15 Mon>\n:PROPERTIES:\n:ID: h1\n:END:\nBody text\n* Next\n";
+        let start = local_ms("2024-01-15", "09:00");
+        let end = local_ms("2024-01-15", "10:30");
+
+        let spans = headline_spans(content);
+        let edit = clock_in_edit(content, &spans[0], start).unwrap();
+        let clocked_in = apply_edits(content, &[edit]).unwrap();
+        assert_eq!(
+            clocked_in,
+            "* TODO Write report\nSCHEDULED: <2024-01-15 Mon>\n:PROPERTIES:\n:ID: h1\n:END:\n:LOGBOOK:\nCLOCK: [2024-01-15 Mon 09:00]\n:END:\nBody text\n* Next\n"
+        );
+
+        let spans = headline_spans(&clocked_in);
+        assert!(clock_in_edit(&clocked_in, &spans[0], end).is_err());
+        assert!(clock_out_edit(&clocked_in, &spans[1], end).is_err());
+        let edit = clock_out_edit(&clocked_in, &spans[0], end).unwrap();
+        let clocked_out = apply_edits(&clocked_in, &[edit]).unwrap();
+
+        // A second clock goes on top of the existing logbook
+        let spans = headline_spans(&clocked_out);
+        let edit = clock_in_edit(&clocked_out, &spans[0], end).unwrap();
+        let clocked_again = apply_edits(&clocked_out, &[edit]).unwrap();
+        let spans = headline_spans(&clocked_again);
+        let section = &clocked_again[spans[0].byte_start..spans[0].section_end];
+        assert_eq!(
+            parse_clocks(section),
+            vec![
+                ClockLine {
+                    started_at: end,
+                    ended_at: None
+                },
+                ClockLine {
+                    started_at: start,
+                    ended_at: Some(end)
+                },
+            ]
+        );
+        assert_eq!(
+            clock_entries("h1", "f1", section)[1].duration_ms,
+            Some(90 * 60_000)
+        );
+    }
+}
@@ -9,11 +9,16 @@ use tokio::sync::RwLock;
 
 use holon_filesystem::{directory::Directory, directory::DirectoryDataSource};
 
+use crate::execution::BlockRunners;
+use crate::git::GitVersioning;
 use crate::models::{OrgFile, OrgHeadline};
 use crate::orgmode_datasource::{OrgFileDataSource, OrgHeadlineDataSource};
 use crate::OrgModeSyncProvider;
-use holon::core::datasource::{OperationProvider, SyncTokenStore, SyncableProvider};
+use holon::core::attachments::AttachmentStore;
+use holon::core::datasource::{IdStrategy, OperationProvider, SyncTokenStore, SyncableProvider};
 use holon::core::queryable_cache::QueryableCache;
+use holon::core::time_tracking::TimeEntryStore;
+use holon::storage::archival::{ArchivalRule, ArchivalRuleProvider};
 use holon::storage::turso::TursoBackend;
 
 /// Configuration for OrgMode integration
@@ -21,11 +26,37 @@ use holon::storage::turso::TursoBackend;
 pub struct OrgModeConfig {
     /// Root directory containing .org files
     pub root_directory: PathBuf,
+    /// Commit file write-backs to a git repository at the root directory
+    pub git_versioning: bool,
+    /// How IDs are generated for headlines without an `:ID:` property
+    pub id_strategy: IdStrategy,
+    /// Runners of source blocks by language, for `execute_block`
+    pub block_runners: BlockRunners,
 }
 
 impl OrgModeConfig {
     pub fn new(root_directory: PathBuf) -> Self {
-        Self { root_directory }
+        Self {
+            root_directory,
+            git_versioning: false,
+            id_strategy: IdStrategy::default(),
+            block_runners: BlockRunners::default(),
+        }
+    }
+
+    pub fn with_git_versioning(mut self, enabled: bool) -> Self {
+        self.git_versioning = enabled;
+        self
+    }
+
+    pub fn with_id_strategy(mut self, id_strategy: IdStrategy) -> Self {
+        self.id_strategy = id_strategy;
+        self
+    }
+
+    pub fn with_block_runners(mut self, block_runners: BlockRunners) -> Self {
+        self.block_runners = block_runners;
+        self
     }
 }
 
@@ -83,7 +114,27 @@ impl ServiceModule for OrgModeModule {
             if root_dir.exists() {
                 println!("[OrgModeModule] Directory is_dir: {}", root_dir.is_dir());
             }
-            OrgModeSyncProvider::new(root_dir, token_store)
+            let mut provider = OrgModeSyncProvider::new(root_dir.clone(), token_store)
+                .with_id_generator(config.id_strategy.generator())
+                .with_block_runners(config.block_runners.clone());
+            // Import :LOGBOOK: clocks when time tracking storage is registered
+            if let Ok(time_entries) = resolver.get::<TimeEntryStore>() {
+                provider = provider.with_time_entries(time_entries);
+            }
+            // Import file links when attachment storage is registered
+            if let Ok(attachments) = resolver.get::<AttachmentStore>() {
+                provider = provider.with_attachments(attachments);
+            }
+            if !config.git_versioning {
+                return provider;
+            }
+            match GitVersioning::open_or_init(root_dir) {
+                Ok(git) => provider.with_git_versioning(Arc::new(git)),
+                Err(e) => {
+                    tracing::warn!("[OrgModeModule] Git versioning disabled: {}", e);
+                    provider
+                }
+            }
         });
 
         // Register SyncableProvider trait implementation
@@ -329,6 +380,15 @@ impl ServiceModule for OrgModeModule {
             headline_cache
         });
 
+        // Archive headlines tagged ARCHIVE, as Org does
+        // Tags aren't written back, so a headline stays archived until its tag is removed from the file
+        services.add_trait_factory::<dyn ArchivalRuleProvider, _>(Lifetime::Singleton, |_| {
+            Arc::new(vec![ArchivalRule::new(
+                "org_headlines",
+                "',' || tags || ',' GLOB '*,ARCHIVE,*'",
+            )]) as Arc<dyn ArchivalRuleProvider>
+        });
+
         Ok(())
     }
 }
@@ -0,0 +1,197 @@
+//! Execution of source blocks
+//!
+//! A source block is run by the `BlockRunner` configured for its language: the
+//! block's source is piped to the runner's stdin, and its stdout becomes the block's
+//! `#+RESULTS:` (stderr and the exit status when it fails). The output so far is
+//! reported after every line, so it can be shown while the block is running.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+
+use holon_api::BlockResult;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+
+/// How long a block may run before it is killed, unless configured otherwise
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Command running a source block, reading the block's source from stdin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockRunner {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl BlockRunner {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn with_arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    async fn run<F>(&self, source: &str, on_output: &mut F) -> std::io::Result<BlockResult>
+    where
+        F: FnMut(&str) + Send,
+    {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        // Feed the source and drain stderr while reading stdout, so no pipe fills up
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let write_source = async move { stdin.write_all(source.as_bytes()).await };
+        let mut stderr = child.stderr.take().expect("stderr is piped");
+        let read_errors = async move {
+            let mut errors = String::new();
+            stderr.read_to_string(&mut errors).await.map(|_| errors)
+        };
+        let mut lines = BufReader::new(child.stdout.take().expect("stdout is piped")).lines();
+        let read_output = async {
+            let mut output = String::new();
+            while let Some(line) = lines.next_line().await? {
+                if !output.is_empty() {
+                    output.push('\n');
+                }
+                output.push_str(&line);
+                on_output(&output);
+            }
+            Ok::<_, std::io::Error>(output)
+        };
+
+        let (written, output, errors) = tokio::join!(write_source, read_output, read_errors);
+        // Runners may exit without reading all of their input
+        if let Err(e) = written {
+            if e.kind() != std::io::ErrorKind::BrokenPipe {
+                return Err(e);
+            }
+        }
+        let (output, errors) = (output?, errors?);
+        let status = child.wait().await?;
+
+        if status.success() {
+            return Ok(BlockResult::text(output));
+        }
+        let mut message = format!("{} failed ({})", self.program, status);
+        if !errors.trim().is_empty() {
+            message.push('\n');
+            message.push_str(errors.trim_end());
+        }
+        Ok(BlockResult::error(message))
+    }
+}
+
+/// Runners of source blocks by language
+#[derive(Debug, Clone)]
+pub struct BlockRunners {
+    runners: HashMap<String, BlockRunner>,
+    timeout: Duration,
+}
+
+impl Default for BlockRunners {
+    /// Shell (`sh`, `shell`, `bash`) and Python (`python`) blocks
+    fn default() -> Self {
+        Self::none()
+            .with_runner("sh", BlockRunner::new("sh"))
+            .with_runner("shell", BlockRunner::new("sh"))
+            .with_runner("bash", BlockRunner::new("bash"))
+            .with_runner("python", BlockRunner::new("python3").with_arg("-"))
+    }
+}
+
+impl BlockRunners {
+    /// No runners, so no block can be executed
+    pub fn none() -> Self {
+        Self {
+            runners: HashMap::new(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Run blocks of `language` (case-insensitive) with `runner`
+    pub fn with_runner(mut self, language: impl Into<String>, runner: BlockRunner) -> Self {
+        self.runners
+            .insert(language.into().to_ascii_lowercase(), runner);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn runner(&self, language: &str) -> Option<&BlockRunner> {
+        self.runners.get(&language.to_ascii_lowercase())
+    }
+
+    /// Run `source` with the runner for `language`
+    ///
+    /// `on_output` is called with the output so far after every line. Failures
+    /// (no runner, the runner failing or timing out) are returned as error results.
+    pub async fn run<F>(&self, language: &str, source: &str, mut on_output: F) -> BlockResult
+    where
+        F: FnMut(&str) + Send,
+    {
+        let Some(runner) = self.runner(language) else {
+            return BlockResult::error(format!("No runner for {} blocks", language));
+        };
+        match tokio::time::timeout(self.timeout, runner.run(source, &mut on_output)).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => BlockResult::error(format!("Failed to run {}: {}", runner.program, e)),
+            Err(_) => BlockResult::error(format!(
+                "{} timed out after {}s",
+                runner.program,
+                self.timeout.as_secs()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holon_api::ResultOutput;
+
+    #[tokio::test]
+    async fn test_run_shell_block_streams_output() {
+        let runners = BlockRunners::default();
+        let mut partial = Vec::new();
+        let result = runners
+            .run("SH", "echo one\necho two", |output| {
+                partial.push(output.to_string())
+            })
+            .await;
+
+        assert_eq!(result.output, BlockResult::text("one\ntwo").output);
+        assert_eq!(partial, vec!["one", "one\ntwo"]);
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_error_results() {
+        let runners = BlockRunners::default().with_timeout(Duration::from_millis(200));
+
+        let result = runners.run("sh", "echo boom >&2; exit 3", |_| {}).await;
+        match result.output {
+            ResultOutput::Error { message } => {
+                assert!(message.starts_with("sh failed"));
+                assert!(message.ends_with("boom"));
+            }
+            other => panic!("Expected an error, got {:?}", other),
+        }
+
+        let result = runners.run("sh", "sleep 5", |_| {}).await;
+        assert!(matches!(result.output, ResultOutput::Error { .. }));
+        let result = runners.run("cobol", "", |_| {}).await;
+        assert!(matches!(result.output, ResultOutput::Error { .. }));
+    }
+}
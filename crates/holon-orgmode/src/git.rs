@@ -0,0 +1,320 @@
+//! Optional git versioning of the org directory
+//!
+//! When enabled, every write-back to an .org file is recorded and committed to a
+//! git repository at the org root once the write has been flushed and synced.
+//! Commit messages summarize the operations that caused the write, so `git log`
+//! doubles as a durable, file-level history that outlives the in-app history.
+//!
+//! Uses the `git` command line client, so no libgit2 build is required.
+
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Author used when the repository has no `user.name`/`user.email` configured
+const FALLBACK_AUTHOR_NAME: &str = "holon";
+const FALLBACK_AUTHOR_EMAIL: &str = "holon@localhost";
+
+/// A single write waiting to be committed
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingWrite {
+    /// Path relative to the repository root
+    path: PathBuf,
+    summary: String,
+}
+
+/// One commit touching a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRevision {
+    pub commit: String,
+    /// Commit time (Unix timestamp in seconds)
+    pub timestamp: i64,
+    /// First line of the commit message
+    pub summary: String,
+}
+
+/// Commits org file writes to a git repository at the org root
+pub struct GitVersioning {
+    repo_root: PathBuf,
+    pending: Mutex<Vec<PendingWrite>>,
+}
+
+impl GitVersioning {
+    /// Open the repository at `repo_root`, running `git init` if there is none
+    pub fn open_or_init(repo_root: impl Into<PathBuf>) -> Result<Self> {
+        let versioning = Self {
+            repo_root: repo_root.into(),
+            pending: Mutex::new(Vec::new()),
+        };
+        if !versioning.repo_root.join(".git").exists() {
+            versioning.git(&["init", "--quiet"])?;
+        }
+        Ok(versioning)
+    }
+
+    pub fn repo_root(&self) -> &Path {
+        &self.repo_root
+    }
+
+    /// Remember that `file` was written because of `summary` (e.g. "update_todo: Buy milk")
+    pub fn record_write(&self, file: &Path, summary: impl Into<String>) -> Result<()> {
+        let path = self.relative_path(file)?;
+        self.pending.lock().unwrap().push(PendingWrite {
+            path,
+            summary: summary.into(),
+        });
+        Ok(())
+    }
+
+    /// Number of recorded writes not yet committed
+    pub fn pending_count(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    /// Commit all recorded writes with a structured message
+    ///
+    /// Returns the new commit hash, or None if nothing was pending or the
+    /// files are unchanged compared to HEAD.
+    pub fn commit_pending(&self) -> Result<Option<String>> {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        if pending.is_empty() {
+            return Ok(None);
+        }
+
+        let mut paths: Vec<&Path> = pending.iter().map(|w| w.path.as_path()).collect();
+        paths.sort();
+        paths.dedup();
+
+        let mut add_args = vec!["add", "--"];
+        let path_strs: Vec<String> = paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        add_args.extend(path_strs.iter().map(String::as_str));
+        self.git(&add_args)?;
+
+        let mut diff_args = vec!["diff", "--cached", "--quiet", "--"];
+        diff_args.extend(path_strs.iter().map(String::as_str));
+        if self.git_status(&diff_args)? {
+            return Ok(None);
+        }
+
+        let message = commit_message(&pending, paths.len());
+        let mut commit_args = vec![
+            "commit",
+            "--quiet",
+            "--no-verify",
+            "-m",
+            message.as_str(),
+            "--",
+        ];
+        commit_args.extend(path_strs.iter().map(String::as_str));
+        self.git_as_author(&commit_args)?;
+
+        Ok(Some(self.git(&["rev-parse", "HEAD"])?.trim().to_string()))
+    }
+
+    /// Commits that touched `file`, newest first
+    pub fn file_history(&self, file: &Path, limit: usize) -> Result<Vec<FileRevision>> {
+        let path = self.relative_path(file)?.to_string_lossy().to_string();
+        let limit = format!("--max-count={}", limit);
+        // An unborn HEAD (no commits yet) has no history
+        if !self.git_status(&["rev-parse", "--verify", "--quiet", "HEAD"])? {
+            return Ok(Vec::new());
+        }
+        let output = self.git(&[
+            "log",
+            limit.as_str(),
+            "--format=%H%x1f%ct%x1f%s",
+            "--",
+            path.as_str(),
+        ])?;
+
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\u{1f}');
+                Some(FileRevision {
+                    commit: parts.next()?.to_string(),
+                    timestamp: parts.next()?.parse().ok()?,
+                    summary: parts.next().unwrap_or_default().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    /// Content of `file` as of `commit`
+    pub fn file_at_revision(&self, file: &Path, commit: &str) -> Result<String> {
+        let path = self.relative_path(file)?;
+        let object = format!("{}:{}", commit, path.to_string_lossy());
+        self.git(&["show", object.as_str()])
+    }
+
+    /// Hash of the latest commit touching `file`, if any
+    pub fn latest_revision(&self, file: &Path) -> Result<Option<String>> {
+        Ok(self
+            .file_history(file, 1)?
+            .into_iter()
+            .next()
+            .map(|r| r.commit))
+    }
+
+    /// Overwrite `file` with its content as of `commit` and commit the restore
+    pub fn restore_file(&self, file: &Path, commit: &str) -> Result<Option<String>> {
+        let content = self.file_at_revision(file, commit)?;
+        let absolute = self.repo_root.join(self.relative_path(file)?);
+        std::fs::write(&absolute, content)
+            .with_context(|| format!("Failed to write file: {}", absolute.display()))?;
+        let short = &commit[..commit.len().min(8)];
+        self.record_write(file, format!("restore_file_version: {}", short))?;
+        self.commit_pending()
+    }
+
+    fn relative_path(&self, file: &Path) -> Result<PathBuf> {
+        let relative = if file.is_absolute() {
+            let root = self
+                .repo_root
+                .canonicalize()
+                .unwrap_or_else(|_| self.repo_root.clone());
+            let file = canonicalize_lenient(file);
+            file.strip_prefix(&root)
+                .with_context(|| {
+                    format!(
+                        "{} is outside of the versioned directory {}",
+                        file.display(),
+                        root.display()
+                    )
+                })?
+                .to_path_buf()
+        } else {
+            file.to_path_buf()
+        };
+        Ok(relative)
+    }
+
+    /// Run git and return stdout, failing on a non-zero exit
+    fn git(&self, args: &[&str]) -> Result<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.repo_root)
+            .args(args)
+            .output()
+            .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Run git and report whether it exited successfully
+    fn git_status(&self, args: &[&str]) -> Result<bool> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.repo_root)
+            .args(args)
+            .output()
+            .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+        Ok(output.status.success())
+    }
+
+    /// Run git, supplying a fallback identity if the repository has none
+    fn git_as_author(&self, args: &[&str]) -> Result<String> {
+        let has_identity = self.git(&["config", "user.email"]).is_ok();
+        if has_identity {
+            return self.git(args);
+        }
+        let name = format!("user.name={}", FALLBACK_AUTHOR_NAME);
+        let email = format!("user.email={}", FALLBACK_AUTHOR_EMAIL);
+        let mut full_args = vec!["-c", name.as_str(), "-c", email.as_str()];
+        full_args.extend_from_slice(args);
+        self.git(&full_args)
+    }
+}
+
+/// Canonicalize a path that may not exist yet via its parent directory
+fn canonicalize_lenient(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+    match (path.parent().map(Path::canonicalize), path.file_name()) {
+        (Some(Ok(parent)), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Subject summarizes the change; the body lists every operation and its file
+fn commit_message(pending: &[PendingWrite], file_count: usize) -> String {
+    let subject = if pending.len() == 1 {
+        pending[0].summary.clone()
+    } else {
+        format!(
+            "Update {} file(s) ({} operations)",
+            file_count,
+            pending.len()
+        )
+    };
+    let body: Vec<String> = pending
+        .iter()
+        .map(|w| format!("- {} ({})", w.summary, w.path.display()))
+        .collect();
+    format!("{}\n\n{}\n", subject, body.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_message_lists_operations() {
+        let pending = vec![
+            PendingWrite {
+                path: PathBuf::from("a.org"),
+                summary: "update_todo: Buy milk".to_string(),
+            },
+            PendingWrite {
+                path: PathBuf::from("a.org"),
+                summary: "update_priority: Buy milk".to_string(),
+            },
+        ];
+        assert_eq!(
+            commit_message(&pending, 1),
+            "Update 1 file(s) (2 operations)\n\n- update_todo: Buy milk (a.org)\n- update_priority: Buy milk (a.org)\n"
+        );
+        assert!(commit_message(&pending[..1], 1).starts_with("update_todo: Buy milk\n"));
+    }
+
+    #[test]
+    fn test_commit_history_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = GitVersioning::open_or_init(dir.path()).unwrap();
+        let file = dir.path().join("notes.org");
+        assert!(git.file_history(&file, 10).unwrap().is_empty());
+
+        std::fs::write(&file, "* TODO Buy milk\n").unwrap();
+        git.record_write(&file, "create: Buy milk").unwrap();
+        let first = git.commit_pending().unwrap().unwrap();
+
+        std::fs::write(&file, "* DONE Buy milk\n").unwrap();
+        git.record_write(&file, "update_todo: Buy milk").unwrap();
+        git.commit_pending().unwrap().unwrap();
+
+        // Nothing changed on disk: no empty commit
+        git.record_write(&file, "update_todo: Buy milk").unwrap();
+        assert_eq!(git.commit_pending().unwrap(), None);
+        assert_eq!(git.pending_count(), 0);
+
+        let history = git.file_history(&file, 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].summary, "update_todo: Buy milk");
+        assert_eq!(history[1].commit, first);
+
+        git.restore_file(&file, &first).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "* TODO Buy milk\n");
+        assert_eq!(git.file_history(&file, 10).unwrap().len(), 3);
+    }
+}
@@ -4,30 +4,42 @@
 //! It parses org-mode files into structured entities (Directory, OrgFile, OrgHeadline)
 //! that can be queried and modified through the standard operation system.
 
+pub mod attachments;
+pub mod clock;
 #[cfg(feature = "di")]
 pub mod di;
+pub mod execution;
+pub mod git;
 pub mod models;
 pub mod orgmode_datasource;
 pub mod orgmode_sync_provider;
 pub mod parser;
+pub mod timestamp;
 pub mod writer;
 
 // Re-export key types
+pub use attachments::{parse_file_links, FileLink};
+pub use clock::{clock_in_edit, clock_out_edit, parse_clock_line, ClockLine};
 #[cfg(feature = "di")]
 pub use di::{OrgModeConfig, OrgModeModule};
-pub use models::{OrgFile, OrgHeadline};
+pub use execution::{BlockRunner, BlockRunners};
+pub use git::{FileRevision, GitVersioning};
+pub use models::{parse_source_block_id, source_block_id, OrgFile, OrgHeadline};
 // Re-export Directory and ROOT_ID from holon-filesystem for convenience
 pub use holon_filesystem::directory::{Directory, ROOT_ID};
 pub use orgmode_datasource::{OrgFileDataSource, OrgHeadlineDataSource};
 // Re-export DirectoryDataSource from holon-filesystem
 pub use holon_filesystem::directory::DirectoryDataSource;
 pub use orgmode_sync_provider::OrgModeSyncProvider;
-pub use parser::{parse_org_file, ParseResult};
+pub use parser::{parse_org_file, parse_org_file_with_ids, ParseResult};
+pub use timestamp::OrgTimestamp;
 pub use writer::{
-    delete_source_block, format_api_source_block, format_block_result, format_header_args,
-    format_header_args_from_values, format_org_source_block, insert_api_source_block,
-    insert_source_block, update_api_source_block, update_source_block, value_to_header_arg_string,
-    write_id_properties,
+    apply_edits, delete_source_block, format_api_source_block, format_block_result,
+    format_header_args, format_header_args_from_values, format_org_source_block, headline_spans,
+    insert_api_source_block, insert_source_block, parse_block_result, planning_edit,
+    planning_timestamp, results_edit, results_span, source_block_ranges, update_api_source_block,
+    update_source_block, value_to_header_arg_string, write_edits, write_id_properties,
+    HeadlineSpan, TextEdit, WritePlan,
 };
 
 // Re-export orgize for direct access if needed
@@ -2,6 +2,8 @@ use holon_macros::Entity;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::timestamp::OrgTimestamp;
+
 /// Re-export Directory and ROOT_ID from holon-filesystem
 pub use holon_filesystem::directory::{Directory, ROOT_ID};
 
@@ -15,7 +17,11 @@ pub fn is_done_keyword(keyword: &str) -> bool {
 
 /// OrgFile - represents a .org file
 #[derive(Debug, Clone, Serialize, Deserialize, Entity)]
-#[entity(name = "org_files", short_name = "file")]
+#[entity(
+    name = "org_files",
+    short_name = "file",
+    operations(CrudOperations, BlockOperations)
+)]
 pub struct OrgFile {
     #[primary_key]
     #[indexed]
@@ -120,54 +126,13 @@ impl holon::core::datasource::BlockEntity for OrgFile {
     }
 }
 
-impl holon::core::datasource::OperationRegistry for OrgFile {
-    fn all_operations() -> Vec<holon::core::datasource::OperationDescriptor> {
-        let entity_name = Self::entity_name();
-        let short_name = Self::short_name().expect("OrgFile must have short_name");
-        let table = entity_name;
-        let id_column = "id";
-
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            use holon::core::datasource::{
-                __operations_crud_operation_provider, __operations_mutable_block_data_source,
-            };
-            __operations_crud_operation_provider::crud_operations(
-                entity_name,
-                short_name,
-                table,
-                id_column,
-            )
-            .into_iter()
-            .chain(
-                __operations_mutable_block_data_source::block_operations(
-                    entity_name,
-                    short_name,
-                    table,
-                    id_column,
-                )
-                .into_iter(),
-            )
-            .collect()
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            Vec::new()
-        }
-    }
-
-    fn entity_name() -> &'static str {
-        "org_files"
-    }
-
-    fn short_name() -> Option<&'static str> {
-        OrgFile::short_name()
-    }
-}
-
 /// OrgHeadline - represents a headline within an org file
 #[derive(Debug, Clone, Serialize, Deserialize, Entity)]
-#[entity(name = "org_headlines", short_name = "headline")]
+#[entity(
+    name = "org_headlines",
+    short_name = "headline",
+    operations(CrudOperations, BlockOperations, TaskOperations)
+)]
 pub struct OrgHeadline {
     #[primary_key]
     #[indexed]
@@ -208,12 +173,30 @@ pub struct OrgHeadline {
     /// Comma-separated tags
     pub tags: Option<String>,
 
-    /// SCHEDULED timestamp (ISO 8601)
+    /// SCHEDULED timestamp as written in the file (e.g. `<2024-01-15 Mon 10:00 +1w>`)
     pub scheduled: Option<String>,
 
-    /// DEADLINE timestamp (ISO 8601)
+    /// Start of the SCHEDULED timestamp
+    pub scheduled_at: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Repeater of the SCHEDULED timestamp (e.g. `+1w`, `.+1d`)
+    pub scheduled_repeater: Option<String>,
+
+    /// DEADLINE timestamp as written in the file
     pub deadline: Option<String>,
 
+    /// Start of the DEADLINE timestamp
+    pub deadline_at: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Repeater of the DEADLINE timestamp
+    pub deadline_repeater: Option<String>,
+
+    /// First active timestamp in the title or body (an appointment)
+    pub timestamp: Option<String>,
+
+    /// Start of `timestamp`
+    pub timestamp_at: Option<chrono::DateTime<chrono::Utc>>,
+
     /// JSON-serialized property drawer
     pub properties: Option<String>,
 
@@ -249,12 +232,50 @@ impl OrgHeadline {
             priority: None,
             tags: None,
             scheduled: None,
+            scheduled_at: None,
+            scheduled_repeater: None,
             deadline: None,
+            deadline_at: None,
+            deadline_repeater: None,
+            timestamp: None,
+            timestamp_at: None,
             properties: None,
             source_blocks: None,
         }
     }
 
+    /// Set the SCHEDULED timestamp text and the columns parsed from it
+    pub fn set_scheduled(&mut self, text: Option<String>) {
+        let timestamp = text.as_deref().and_then(OrgTimestamp::parse);
+        self.scheduled_at = timestamp.and_then(|t| t.start_utc());
+        self.scheduled_repeater = timestamp.and_then(|t| t.repeater).map(|r| r.to_string());
+        self.scheduled = text;
+    }
+
+    /// Set the DEADLINE timestamp text and the columns parsed from it
+    pub fn set_deadline(&mut self, text: Option<String>) {
+        let timestamp = text.as_deref().and_then(OrgTimestamp::parse);
+        self.deadline_at = timestamp.and_then(|t| t.start_utc());
+        self.deadline_repeater = timestamp.and_then(|t| t.repeater).map(|r| r.to_string());
+        self.deadline = text;
+    }
+
+    /// Set the plain active timestamp and its start
+    pub fn set_timestamp(&mut self, timestamp: Option<OrgTimestamp>) {
+        self.timestamp_at = timestamp.and_then(|t| t.start_utc());
+        self.timestamp = timestamp.map(|t| t.to_string());
+    }
+
+    /// The parsed SCHEDULED timestamp
+    pub fn scheduled_timestamp(&self) -> Option<OrgTimestamp> {
+        self.scheduled.as_deref().and_then(OrgTimestamp::parse)
+    }
+
+    /// The parsed DEADLINE timestamp
+    pub fn deadline_timestamp(&self) -> Option<OrgTimestamp> {
+        self.deadline.as_deref().and_then(OrgTimestamp::parse)
+    }
+
     /// Get parsed source blocks from the serialized JSON
     pub fn get_source_blocks(&self) -> Vec<OrgSourceBlock> {
         self.source_blocks
@@ -336,6 +357,10 @@ pub struct OrgSourceBlock {
 
     /// Byte offset where this block ends
     pub byte_end: i64,
+
+    /// Output of the last execution (`#+RESULTS:` after the block)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<holon_api::BlockResult>,
 }
 
 impl OrgSourceBlock {
@@ -348,6 +373,7 @@ impl OrgSourceBlock {
             header_args: HashMap::new(),
             byte_start,
             byte_end,
+            results: None,
         }
     }
 
@@ -402,11 +428,25 @@ impl OrgSourceBlock {
             source: self.source.clone(),
             name: self.name.clone(),
             header_args,
-            results: None,
+            results: self.results.clone(),
         }
     }
 }
 
+/// Separator between the headline ID and the index in source block IDs
+const SOURCE_BLOCK_ID_SEPARATOR: &str = "::src::";
+
+/// ID of the `index`-th source block of a headline (`<headline_id>::src::<index>`)
+pub fn source_block_id(headline_id: &str, index: usize) -> String {
+    format!("{}{}{}", headline_id, SOURCE_BLOCK_ID_SEPARATOR, index)
+}
+
+/// Headline ID and index of a source block ID
+pub fn parse_source_block_id(id: &str) -> Option<(&str, usize)> {
+    let (headline_id, index) = id.rsplit_once(SOURCE_BLOCK_ID_SEPARATOR)?;
+    Some((headline_id, index.parse().ok()?))
+}
+
 /// Parsed section content with both text and source blocks
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ParsedSectionContent {
@@ -466,66 +506,7 @@ impl holon::core::datasource::TaskEntity for OrgHeadline {
     }
 
     fn due_date(&self) -> Option<chrono::DateTime<chrono::Utc>> {
-        self.deadline.as_ref().and_then(|d| {
-            chrono::DateTime::parse_from_rfc3339(d)
-                .ok()
-                .map(|dt| dt.with_timezone(&chrono::Utc))
-        })
-    }
-}
-
-impl holon::core::datasource::OperationRegistry for OrgHeadline {
-    fn all_operations() -> Vec<holon::core::datasource::OperationDescriptor> {
-        let entity_name = Self::entity_name();
-        let short_name = Self::short_name().expect("OrgHeadline must have short_name");
-        let table = entity_name;
-        let id_column = "id";
-
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            use holon::core::datasource::{
-                __operations_crud_operation_provider, __operations_mutable_block_data_source,
-                __operations_mutable_task_data_source,
-            };
-            __operations_crud_operation_provider::crud_operations(
-                entity_name,
-                short_name,
-                table,
-                id_column,
-            )
-            .into_iter()
-            .chain(
-                __operations_mutable_block_data_source::block_operations(
-                    entity_name,
-                    short_name,
-                    table,
-                    id_column,
-                )
-                .into_iter(),
-            )
-            .chain(
-                __operations_mutable_task_data_source::task_operations(
-                    entity_name,
-                    short_name,
-                    table,
-                    id_column,
-                )
-                .into_iter(),
-            )
-            .collect()
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            Vec::new()
-        }
-    }
-
-    fn entity_name() -> &'static str {
-        "org_headlines"
-    }
-
-    fn short_name() -> Option<&'static str> {
-        OrgHeadline::short_name()
+        self.deadline_at
     }
 }
 
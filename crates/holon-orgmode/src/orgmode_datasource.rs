@@ -4,25 +4,149 @@
 //! Directory, OrgFile, and OrgHeadline entities.
 
 use async_trait::async_trait;
+use chrono::{NaiveDate, NaiveTime};
 use futures::stream;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tokio_stream::Stream;
 
 use holon::core::datasource::{
-    CrudOperations, DataSource, OperationDescriptor, OperationProvider, OperationRegistry, Result,
-    StreamPosition as CoreStreamPosition, UndoAction,
+    __operations_time_tracking_operations, CrudOperations, DataSource, HolonError, HolonResult,
+    IdGenerator, OperationDescriptor, OperationProvider, OperationRegistry, Result,
+    StreamPosition as CoreStreamPosition, TimeTrackingOperations, UndoAction,
 };
 use holon::storage::types::StorageEntity;
 use holon_api::streaming::ChangeNotifications;
-use holon_api::{ApiError, Change, StreamPosition};
+use holon_api::{ApiError, BlockResult, Change, ChangeOrigin, StreamPosition};
 use holon_api::{Operation, Value};
+use holon_filesystem::directory::DirectoryChangeProvider;
 
-use crate::models::{OrgFile, OrgHeadline};
+use crate::clock;
+use crate::git::FileRevision;
+use crate::models::{parse_source_block_id, OrgFile, OrgHeadline, OrgSourceBlock};
 use crate::orgmode_sync_provider::OrgModeSyncProvider;
-use crate::writer;
+use crate::timestamp::OrgTimestamp;
+use crate::writer::{self, HeadlineSpan, SubtreePosition, TextEdit};
+
+/// Params identifying a headline for inverse operations
+///
+/// `byte_start` is where the headline starts once the operation is applied, so the
+/// inverse still finds it if its ID can't be found.
+fn headline_params(id: &str, file_path: &str, byte_start: usize) -> HashMap<String, Value> {
+    HashMap::from([
+        ("id".to_string(), Value::String(id.to_string())),
+        (
+            "file_path".to_string(),
+            Value::String(file_path.to_string()),
+        ),
+        ("byte_start".to_string(), Value::Integer(byte_start as i64)),
+    ])
+}
+
+/// Operation moving a headline back to `position` (used as the inverse of moves)
+///
+/// Irreversible if the headline it was positioned by has no ID.
+fn move_back_op(
+    id: &str,
+    file_path: &str,
+    byte_start: usize,
+    spans: &[HeadlineSpan],
+    position: SubtreePosition,
+) -> UndoAction {
+    let byte_start = byte_start as i64;
+    let operation = match position {
+        SubtreePosition::After(sibling) => spans[sibling].id.as_deref().map(|after_id| {
+            __operations_org_headline_operations::move_headline_after_op(
+                "org_headlines",
+                id,
+                file_path,
+                byte_start,
+                after_id,
+            )
+        }),
+        SubtreePosition::FirstChildOf(Some(parent)) => {
+            spans[parent].id.as_deref().map(|parent_id| {
+                __operations_org_headline_operations::move_headline_under_op(
+                    "org_headlines",
+                    id,
+                    file_path,
+                    byte_start,
+                    Some(parent_id),
+                )
+            })
+        }
+        SubtreePosition::FirstChildOf(None) => Some(
+            __operations_org_headline_operations::move_headline_under_op(
+                "org_headlines",
+                id,
+                file_path,
+                byte_start,
+                None,
+            ),
+        ),
+    };
+    UndoAction::from(operation)
+}
+
+/// Timestamp text for setting a planning entry to `value`: org timestamp text, an
+/// ISO date, an RFC 3339 date-time or null. Repeater and warning delay of `current`
+/// are kept.
+fn planning_timestamp(value: &Value, current: Option<OrgTimestamp>) -> Result<Option<String>> {
+    let text = match value {
+        Value::Null => return Ok(None),
+        Value::String(text) | Value::DateTime(text) => text.trim(),
+        other => return Err(format!("Invalid timestamp: {:?}", other).into()),
+    };
+    if let Some(timestamp) = OrgTimestamp::parse(text) {
+        return Ok(Some(timestamp.to_string()));
+    }
+
+    let (date, time) = match NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        Ok(date) => (date, None),
+        Err(_) => {
+            let local = chrono::DateTime::parse_from_rfc3339(text)
+                .map_err(|e| format!("Invalid timestamp '{}': {}", text, e))?
+                .with_timezone(&chrono::Local);
+            // Midnight means a whole day, as for timestamps without a time
+            let time = Some(local.time()).filter(|time| *time != NaiveTime::MIN);
+            (local.date_naive(), time)
+        }
+    };
+    let timestamp = match current {
+        Some(current) => current.rescheduled(date, time),
+        None => OrgTimestamp::new(date, time),
+    };
+    Ok(Some(timestamp.to_string()))
+}
+
+/// Clock operation on a headline (used as the inverse of clock_in/clock_out)
+fn clock_op(id: &str, op_name: &str, display_name: &str) -> Operation {
+    Operation::new(
+        "org_headlines",
+        op_name,
+        display_name,
+        HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+    )
+}
+
+/// Operation setting a source block's results (used as the inverse of execute_block)
+fn block_results_op(id: &str, results: Option<&BlockResult>) -> Operation {
+    let results = results
+        .and_then(|results| serde_json::to_string(results).ok())
+        .map_or(Value::Null, Value::String);
+    Operation::new(
+        "org_headlines",
+        "set_block_results",
+        "Restore block results",
+        HashMap::from([
+            ("id".to_string(), Value::String(id.to_string())),
+            ("results".to_string(), results),
+        ]),
+    )
+}
 
 /// OrgHeadline-specific operations for file write-back
 ///
@@ -61,6 +185,73 @@ pub trait OrgHeadlineOperations: Send + Sync {
         byte_end: i64,
         content: &str,
     ) -> Result<UndoAction>;
+
+    /// Demote a headline and its subtree by one level in the file
+    #[holon_macros::affects("depth", "parent_id")]
+    async fn indent_headline(
+        &self,
+        id: &str,
+        file_path: &str,
+        byte_start: i64,
+    ) -> Result<UndoAction>;
+
+    /// Promote a headline and its subtree by one level in the file
+    #[holon_macros::affects("depth", "parent_id")]
+    async fn outdent_headline(
+        &self,
+        id: &str,
+        file_path: &str,
+        byte_start: i64,
+    ) -> Result<UndoAction>;
+
+    /// Move a headline's subtree to directly after another headline in the same file
+    #[holon_macros::affects("depth", "parent_id")]
+    async fn move_headline_after(
+        &self,
+        id: &str,
+        file_path: &str,
+        byte_start: i64,
+        after_id: &str,
+    ) -> Result<UndoAction>;
+
+    /// Move a headline's subtree to become the first child of another headline in the
+    /// same file, or without `parent_id`, the first headline of the file
+    #[holon_macros::affects("depth", "parent_id")]
+    async fn move_headline_under(
+        &self,
+        id: &str,
+        file_path: &str,
+        byte_start: i64,
+        parent_id: Option<&str>,
+    ) -> Result<UndoAction>;
+}
+
+/// Execution of the source blocks in headlines (see `crate::execution`)
+///
+/// Source blocks are identified by `<headline_id>::src::<index>` (see
+/// `models::source_block_id`).
+#[holon_macros::operations_trait]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait SourceBlockOperations: Send + Sync {
+    /// Run a source block and write its output as the block's #+RESULTS:
+    ///
+    /// The output is emitted as headline changes while the block is running.
+    #[holon_macros::affects("source_blocks")]
+    async fn execute_block(&self, id: &str) -> Result<UndoAction>;
+
+    /// Replace a source block's #+RESULTS: (a `BlockResult` as JSON; null removes them)
+    #[holon_macros::affects("source_blocks")]
+    async fn set_block_results(&self, id: &str, results: Option<&str>) -> Result<UndoAction>;
+}
+
+/// OrgFile version operations backed by git versioning of the org directory
+#[holon_macros::operations_trait]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait OrgFileVersionOperations: Send + Sync {
+    /// Restore a file to its content as of a previous commit
+    async fn restore_file_version(&self, id: &str, path: &str, commit: &str) -> Result<UndoAction>;
 }
 
 // DirectoryDataSource is now imported from holon-filesystem
@@ -75,6 +266,62 @@ impl OrgFileDataSource {
     pub fn new(provider: Arc<OrgModeSyncProvider>) -> Self {
         Self { provider }
     }
+
+    /// Commits that touched the file at `path`, newest first
+    ///
+    /// Empty if git versioning is not enabled.
+    pub fn file_history(&self, path: &str, limit: usize) -> Result<Vec<FileRevision>> {
+        match self.provider.git_versioning() {
+            Some(git) => Ok(git
+                .file_history(Path::new(path), limit)
+                .map_err(|e| format!("Failed to read history of {}: {}", path, e))?),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OrgFileVersionOperations for OrgFileDataSource {
+    async fn restore_file_version(&self, id: &str, path: &str, commit: &str) -> Result<UndoAction> {
+        use tracing::info;
+
+        info!(
+            "[OrgFileDataSource] restore_file_version: id={}, path={}, commit={}",
+            id, path, commit
+        );
+
+        let git = self
+            .provider
+            .git_versioning()
+            .ok_or_else(|| "Git versioning is not enabled for this org directory".to_string())?;
+        let file = Path::new(path);
+        let previous = git
+            .latest_revision(file)
+            .map_err(|e| format!("Failed to read history of {}: {}", path, e))?;
+        git.restore_file(file, commit)
+            .map_err(|e| format!("Failed to restore {} to {}: {}", path, commit, e))?;
+
+        use holon::core::datasource::SyncableProvider;
+        SyncableProvider::sync(&*self.provider, CoreStreamPosition::Beginning)
+            .await
+            .map_err(|e| format!("Failed to sync: {}", e))?;
+
+        // Undo restores the version that was current before
+        Ok(match previous {
+            Some(previous) => UndoAction::Undo(Operation::new(
+                "org_files",
+                "restore_file_version",
+                "Restore file version",
+                HashMap::from([
+                    ("id".to_string(), Value::String(id.to_string())),
+                    ("path".to_string(), Value::String(path.to_string())),
+                    ("commit".to_string(), Value::String(previous)),
+                ]),
+            )),
+            None => UndoAction::Irreversible,
+        })
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -123,15 +370,15 @@ impl DataSource<OrgFile> for OrgFileDataSource {
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl CrudOperations<OrgFile> for OrgFileDataSource {
-    async fn set_field(&self, _id: &str, _field: &str, _value: Value) -> Result<UndoAction> {
+    async fn set_field(&self, _id: &str, _field: &str, _value: Value) -> HolonResult<UndoAction> {
         Err("File field updates not implemented".into())
     }
 
-    async fn create(&self, _fields: HashMap<String, Value>) -> Result<(String, UndoAction)> {
+    async fn create(&self, _fields: HashMap<String, Value>) -> HolonResult<(String, UndoAction)> {
         Err("File creation not implemented".into())
     }
 
-    async fn delete(&self, _id: &str) -> Result<UndoAction> {
+    async fn delete(&self, _id: &str) -> HolonResult<UndoAction> {
         Err("File deletion not implemented".into())
     }
 }
@@ -140,19 +387,43 @@ impl CrudOperations<OrgFile> for OrgFileDataSource {
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl OperationProvider for OrgFileDataSource {
     fn operations(&self) -> Vec<OperationDescriptor> {
+        let entity_name = OrgFile::entity_name();
+        let short_name = OrgFile::short_name().expect("OrgFile must have short_name");
+
         OrgFile::all_operations()
+            .into_iter()
+            .chain(
+                __operations_org_file_version_operations::org_file_version_operations(
+                    entity_name,
+                    short_name,
+                    entity_name,
+                    "id",
+                ),
+            )
+            .collect()
     }
 
     async fn execute_operation(
         &self,
         entity_name: &str,
-        _op_name: &str,
-        _params: StorageEntity,
+        op_name: &str,
+        params: StorageEntity,
     ) -> Result<UndoAction> {
+        use holon::core::datasource::UnknownOperationError;
+
         if entity_name != "org_files" {
             return Err(format!("Expected entity_name 'org_files', got '{}'", entity_name).into());
         }
-        Ok(UndoAction::Irreversible)
+
+        match __operations_org_file_version_operations::dispatch_operation(self, op_name, &params)
+            .await
+        {
+            Ok(op) => Ok(op),
+            Err(err) if UnknownOperationError::is_unknown(err.as_ref()) => {
+                Ok(UndoAction::Irreversible)
+            }
+            Err(err) => Err(err),
+        }
     }
 }
 
@@ -208,38 +479,162 @@ impl DataSource<OrgHeadline> for OrgHeadlineDataSource {
     async fn get_by_id(&self, _id: &str) -> Result<Option<OrgHeadline>> {
         Ok(None)
     }
+
+    fn id_generator(&self) -> Arc<dyn IdGenerator> {
+        self.provider.id_generator()
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl CrudOperations<OrgHeadline> for OrgHeadlineDataSource {
-    async fn set_field(&self, id: &str, field: &str, value: Value) -> Result<UndoAction> {
-        use tracing::{info, warn};
+    async fn set_field(&self, id: &str, field: &str, value: Value) -> HolonResult<UndoAction> {
+        use tracing::info;
 
         info!(
             "[OrgHeadlineDataSource] set_field: id={}, field={}, value={:?}",
             id, field, value
         );
 
-        // For now, log and acknowledge - full implementation requires file path lookup
-        // In production, we'd query the database to get file_path and byte_start
         match field {
-            "todo_keyword" | "priority" | "title" | "content" | "tags" | "scheduled"
-            | "deadline" => {
-                warn!(
-                    "[OrgHeadlineDataSource] Field '{}' update acknowledged but write-back requires file_path lookup (not implemented)",
-                    field
-                );
+            "todo_keyword" | "priority" | "title" | "content" => {
+                let path = self
+                    .find_headline_file(id)
+                    .await
+                    .ok_or_else(|| HolonError::not_found("headline", id))?;
+                let file_path = path.to_string_lossy().to_string();
+                let text = match &value {
+                    Value::Null => None,
+                    Value::Integer(p) => match p {
+                        3 => Some("A".to_string()),
+                        2 => Some("B".to_string()),
+                        1 => Some("C".to_string()),
+                        _ => None,
+                    },
+                    other => other.as_string().map(|s| s.to_string()),
+                };
+                let field = field.to_string();
+                let operation = format!("set_field({})", field);
+
+                self.edit_headline(&operation, &file_path, id, None, |content, spans, index| {
+                    let span = &spans[index];
+                    let edit = match field.as_str() {
+                        "todo_keyword" => writer::todo_keyword_edit(content, span, text.as_deref()),
+                        "priority" => writer::priority_edit(
+                            content,
+                            span,
+                            text.as_deref().and_then(|p| p.chars().next()),
+                        ),
+                        "title" => writer::title_edit(content, span, text.as_deref().unwrap_or("")),
+                        _ => writer::content_edit(content, span, text.as_deref().unwrap_or("")),
+                    };
+                    edit.map(|edit| vec![edit])
+                        .map_err(|e| format!("Failed to update {}: {}", field, e).into())
+                })
+                .await?;
                 Ok(UndoAction::Irreversible)
             }
-            "depth" | "parent_id" | "byte_start" | "byte_end" | "file_path" | "file_id" => {
-                Err(format!("Field '{}' cannot be set directly", field).into())
+            "scheduled" | "deadline" | "due_date" => {
+                let path = self
+                    .find_headline_file(id)
+                    .await
+                    .ok_or_else(|| HolonError::not_found("headline", id))?;
+                let file_path = path.to_string_lossy().to_string();
+                // The due date of a headline is its DEADLINE
+                let (field, keyword) = match field {
+                    "scheduled" => ("scheduled", "SCHEDULED"),
+                    _ => ("deadline", "DEADLINE"),
+                };
+                let mut old_value = Value::Null;
+
+                self.edit_headline(
+                    &format!("set_field({})", field),
+                    &file_path,
+                    id,
+                    None,
+                    |content, spans, index| {
+                        let current = writer::planning_timestamp(content, &spans[index], keyword);
+                        if let Some(current) = current {
+                            old_value = Value::String(current.to_string());
+                        }
+                        let timestamp =
+                            planning_timestamp(&value, current.and_then(OrgTimestamp::parse))?;
+                        Ok(writer::planning_edit(
+                            content,
+                            &spans[index],
+                            keyword,
+                            timestamp.as_deref(),
+                        )
+                        .into_iter()
+                        .collect())
+                    },
+                )
+                .await?;
+
+                use holon::core::datasource::__operations_crud_operation_provider;
+                Ok(UndoAction::Undo(
+                    __operations_crud_operation_provider::set_field_op(
+                        "", // Will be set by OperationProvider
+                        id, field, old_value,
+                    ),
+                ))
             }
-            _ => Err(format!("Unknown field '{}'", field).into()),
+            "tags" => {
+                let path = self
+                    .find_headline_file(id)
+                    .await
+                    .ok_or_else(|| HolonError::not_found("headline", id))?;
+                let file_path = path.to_string_lossy().to_string();
+                // Tags are comma-separated, as in the headline row
+                let tags: Vec<String> = match &value {
+                    Value::Null => Vec::new(),
+                    other => other
+                        .as_string()
+                        .ok_or_else(|| {
+                            HolonError::validation(format!("Invalid tags: {:?}", other))
+                        })?
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(String::from)
+                        .collect(),
+                };
+                let mut old_value = Value::Null;
+
+                self.edit_headline(
+                    "set_field(tags)",
+                    &file_path,
+                    id,
+                    None,
+                    |content, spans, index| {
+                        let current = writer::headline_tags(content, &spans[index]);
+                        if !current.is_empty() {
+                            old_value = Value::String(current.join(","));
+                        }
+                        let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+                        writer::tags_edit(content, &spans[index], &tags)
+                            .map(|edit| edit.into_iter().collect())
+                            .map_err(|e| format!("Failed to update tags: {}", e).into())
+                    },
+                )
+                .await?;
+
+                use holon::core::datasource::__operations_crud_operation_provider;
+                Ok(UndoAction::Undo(
+                    __operations_crud_operation_provider::set_field_op(
+                        "", // Will be set by OperationProvider
+                        id, "tags", old_value,
+                    ),
+                ))
+            }
+            "depth" | "parent_id" | "byte_start" | "byte_end" | "file_path" | "file_id" => Err(
+                HolonError::validation(format!("Field '{}' cannot be set directly", field)),
+            ),
+            _ => Err(HolonError::validation(format!("Unknown field '{}'", field))),
         }
     }
 
-    async fn create(&self, fields: HashMap<String, Value>) -> Result<(String, UndoAction)> {
+    async fn create(&self, fields: HashMap<String, Value>) -> HolonResult<(String, UndoAction)> {
         use tracing::info;
 
         let title = fields
@@ -267,7 +662,7 @@ impl CrudOperations<OrgHeadline> for OrgHeadlineDataSource {
         Err("Headline creation not implemented".into())
     }
 
-    async fn delete(&self, id: &str) -> Result<UndoAction> {
+    async fn delete(&self, id: &str) -> HolonResult<UndoAction> {
         use tracing::info;
 
         info!(
@@ -286,20 +681,38 @@ impl CrudOperations<OrgHeadline> for OrgHeadlineDataSource {
 }
 
 impl OrgHeadlineDataSource {
-    /// Helper to modify a file and sync afterwards
-    async fn modify_file<F>(&self, file_path: &str, transform: F) -> Result<()>
+    /// Helper to apply span-based edits to a headline and sync afterwards
+    ///
+    /// The headline is located by ID (falling back to `byte_start`, if given), `build`
+    /// computes the edits, and only those edits are written back to the file. With git
+    /// versioning enabled, the write is committed as `operation` once synced.
+    async fn edit_headline<F>(
+        &self,
+        operation: &str,
+        file_path: &str,
+        id: &str,
+        byte_start: Option<usize>,
+        build: F,
+    ) -> Result<()>
     where
-        F: FnOnce(&str) -> Result<String>,
+        F: FnOnce(&str, &[HeadlineSpan], usize) -> Result<Vec<TextEdit>>,
     {
         // Read file
         let content = std::fs::read_to_string(file_path)
             .map_err(|e| format!("Failed to read file: {}", e))?;
 
-        // Apply transformation
-        let new_content = transform(&content)?;
-
-        // Write back
-        std::fs::write(file_path, new_content)
+        // Locate headline and compute edits
+        let spans = writer::headline_spans(&content);
+        let index = writer::find_headline(&spans, id, byte_start)
+            .ok_or_else(|| format!("Headline '{}' not found in {}", id, file_path))?;
+        let edits = build(&content, &spans, index)?;
+        let headline = content[spans[index].byte_start..spans[index].line_end]
+            .trim_start_matches('*')
+            .trim()
+            .to_string();
+
+        // Write back only the edited spans
+        writer::write_edits(Path::new(file_path), &content, edits)
             .map_err(|e| format!("Failed to write file: {}", e))?;
 
         // Trigger sync to update database
@@ -308,8 +721,116 @@ impl OrgHeadlineDataSource {
             .await
             .map_err(|e| format!("Failed to sync: {}", e))?;
 
+        if let Some(git) = self.provider.git_versioning() {
+            let summary = format!("{}: {}", operation, headline);
+            if let Err(e) = git
+                .record_write(Path::new(file_path), summary)
+                .and_then(|_| git.commit_pending())
+            {
+                tracing::warn!(
+                    "[OrgHeadlineDataSource] Failed to commit {}: {}",
+                    file_path,
+                    e
+                );
+            }
+        }
+
         Ok(())
     }
+
+    /// Apply a clock edit to the :LOGBOOK: of the headline with the given ID
+    async fn edit_clock<F>(&self, operation: &str, id: &str, build: F) -> Result<()>
+    where
+        F: FnOnce(&str, &HeadlineSpan, i64) -> anyhow::Result<TextEdit>,
+    {
+        let file_path = self
+            .find_headline_file(id)
+            .await
+            .ok_or_else(|| format!("Headline '{}' not found", id))?;
+        let file_path = file_path.to_string_lossy().to_string();
+        let now = chrono::Utc::now().timestamp_millis();
+
+        self.edit_headline(operation, &file_path, id, None, |content, spans, index| {
+            build(content, &spans[index], now)
+                .map(|edit| vec![edit])
+                .map_err(|e| format!("Failed to {}: {}", operation, e).into())
+        })
+        .await
+    }
+
+    /// The source block with the given ID, with its file, headline and index
+    async fn find_source_block(
+        &self,
+        id: &str,
+    ) -> Result<(PathBuf, OrgHeadline, usize, OrgSourceBlock)> {
+        let (headline_id, index) =
+            parse_source_block_id(id).ok_or_else(|| format!("Invalid source block ID '{}'", id))?;
+        let file_path = self
+            .find_headline_file(headline_id)
+            .await
+            .ok_or_else(|| format!("Headline '{}' not found", headline_id))?;
+        let headline = self
+            .provider
+            .parse_file(&file_path)?
+            .headlines
+            .into_iter()
+            .find(|headline| headline.id == headline_id)
+            .ok_or_else(|| format!("Headline '{}' not found", headline_id))?;
+        let block = headline
+            .get_source_blocks()
+            .into_iter()
+            .nth(index)
+            .ok_or_else(|| format!("Headline '{}' has no source block {}", headline_id, index))?;
+        Ok((file_path, headline, index, block))
+    }
+
+    /// Write the #+RESULTS: of the `index`-th source block of a headline
+    async fn write_block_results(
+        &self,
+        operation: &str,
+        file_path: &Path,
+        headline_id: &str,
+        index: usize,
+        results: Option<&BlockResult>,
+        name: Option<&str>,
+    ) -> Result<()> {
+        let file_path = file_path.to_string_lossy().to_string();
+        self.edit_headline(
+            operation,
+            &file_path,
+            headline_id,
+            None,
+            |content, spans, i| {
+                let (_, block_end) = *writer::source_block_ranges(content, &spans[i])
+                    .get(index)
+                    .ok_or_else(|| {
+                        format!("Source block {} of '{}' not found", index, headline_id)
+                    })?;
+                Ok(writer::results_edit(content, block_end, results, name)
+                    .into_iter()
+                    .collect())
+            },
+        )
+        .await
+    }
+
+    /// Find the .org file containing the headline with the given ID
+    ///
+    /// This is the file the last sync found the headline in (the `file_path` of its
+    /// row). Headlines the last sync didn't see are looked up again after a sync.
+    async fn find_headline_file(&self, id: &str) -> Option<PathBuf> {
+        if let Some(path) = self.provider.headline_file(id) {
+            return Some(path);
+        }
+
+        use holon::core::datasource::SyncableProvider;
+        if let Err(e) = SyncableProvider::sync(&*self.provider, CoreStreamPosition::Beginning).await
+        {
+            tracing::warn!("[OrgHeadlineDataSource] Failed to sync: {}", e);
+            return None;
+        }
+        self.provider.headline_file(id)
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -332,10 +853,17 @@ impl OrgHeadlineOperations for OrgHeadlineDataSource {
         let byte_start = byte_start as usize;
         let keyword_owned = todo_keyword.map(|s| s.to_string());
 
-        self.modify_file(file_path, |content| {
-            writer::update_todo_keyword(content, byte_start, keyword_owned.as_deref())
-                .map_err(|e| format!("Failed to update TODO keyword: {}", e).into())
-        })
+        self.edit_headline(
+            "update_todo",
+            file_path,
+            id,
+            Some(byte_start),
+            |content, spans, index| {
+                writer::todo_keyword_edit(content, &spans[index], keyword_owned.as_deref())
+                    .map(|edit| vec![edit])
+                    .map_err(|e| format!("Failed to update TODO keyword: {}", e).into())
+            },
+        )
         .await?;
 
         info!("[OrgHeadlineDataSource] update_todo completed successfully");
@@ -366,10 +894,17 @@ impl OrgHeadlineOperations for OrgHeadlineDataSource {
 
         let byte_start = byte_start as usize;
 
-        self.modify_file(file_path, |content| {
-            writer::update_priority(content, byte_start, priority_char)
-                .map_err(|e| format!("Failed to update priority: {}", e).into())
-        })
+        self.edit_headline(
+            "update_priority",
+            file_path,
+            id,
+            Some(byte_start),
+            |content, spans, index| {
+                writer::priority_edit(content, &spans[index], priority_char)
+                    .map(|edit| vec![edit])
+                    .map_err(|e| format!("Failed to update priority: {}", e).into())
+            },
+        )
         .await?;
 
         info!("[OrgHeadlineDataSource] update_priority completed successfully");
@@ -392,18 +927,264 @@ impl OrgHeadlineOperations for OrgHeadlineDataSource {
         );
 
         let byte_start = byte_start as usize;
-        let byte_end = byte_end as usize;
-        let new_content = content.to_string();
 
-        self.modify_file(file_path, |file_content| {
-            writer::update_content(file_content, byte_start, byte_end, |_| new_content.clone())
-                .map_err(|e| format!("Failed to update content: {}", e).into())
-        })
+        self.edit_headline(
+            "update_content",
+            file_path,
+            id,
+            Some(byte_start),
+            |file_content, spans, index| {
+                writer::content_edit(file_content, &spans[index], content)
+                    .map(|edit| vec![edit])
+                    .map_err(|e| format!("Failed to update content: {}", e).into())
+            },
+        )
         .await?;
 
         info!("[OrgHeadlineDataSource] update_content completed successfully");
         Ok(UndoAction::Irreversible)
     }
+
+    async fn indent_headline(
+        &self,
+        id: &str,
+        file_path: &str,
+        byte_start: i64,
+    ) -> Result<UndoAction> {
+        use tracing::info;
+
+        info!(
+            "[OrgHeadlineDataSource] indent_headline: id={}, file={}",
+            id, file_path
+        );
+
+        // Shifting levels doesn't move the headline line
+        let mut headline_start = byte_start as usize;
+        self.edit_headline(
+            "indent_headline",
+            file_path,
+            id,
+            Some(byte_start as usize),
+            |content, spans, index| {
+                headline_start = spans[index].byte_start;
+                writer::shift_level_edits(content, spans, index, 1)
+                    .map_err(|e| format!("Failed to indent headline: {}", e).into())
+            },
+        )
+        .await?;
+
+        Ok(UndoAction::Undo(Operation::new(
+            "org_headlines",
+            "outdent_headline",
+            "Outdent",
+            headline_params(id, file_path, headline_start),
+        )))
+    }
+
+    async fn outdent_headline(
+        &self,
+        id: &str,
+        file_path: &str,
+        byte_start: i64,
+    ) -> Result<UndoAction> {
+        use tracing::info;
+
+        info!(
+            "[OrgHeadlineDataSource] outdent_headline: id={}, file={}",
+            id, file_path
+        );
+
+        let mut headline_start = byte_start as usize;
+        self.edit_headline(
+            "outdent_headline",
+            file_path,
+            id,
+            Some(byte_start as usize),
+            |content, spans, index| {
+                headline_start = spans[index].byte_start;
+                writer::shift_level_edits(content, spans, index, -1)
+                    .map_err(|e| format!("Failed to outdent headline: {}", e).into())
+            },
+        )
+        .await?;
+
+        Ok(UndoAction::Undo(Operation::new(
+            "org_headlines",
+            "indent_headline",
+            "Indent",
+            headline_params(id, file_path, headline_start),
+        )))
+    }
+
+    async fn move_headline_after(
+        &self,
+        id: &str,
+        file_path: &str,
+        byte_start: i64,
+        after_id: &str,
+    ) -> Result<UndoAction> {
+        use tracing::info;
+
+        info!(
+            "[OrgHeadlineDataSource] move_headline_after: id={}, after_id={}, file={}",
+            id, after_id, file_path
+        );
+
+        let mut undo_action = UndoAction::Irreversible;
+        self.edit_headline(
+            "move_headline_after",
+            file_path,
+            id,
+            Some(byte_start as usize),
+            |content, spans, index| {
+                let after_index = spans
+                    .iter()
+                    .position(|s| s.id.as_deref() == Some(after_id))
+                    .ok_or_else(|| format!("Headline '{}' not found in {}", after_id, file_path))?;
+                let edits = writer::move_subtree_edits(content, spans, index, after_index)
+                    .map_err(|e| format!("Failed to move headline: {}", e))?;
+                if let Some(moved_start) = writer::moved_subtree_start(&edits) {
+                    let position = writer::subtree_position(spans, index);
+                    undo_action = move_back_op(id, file_path, moved_start, spans, position);
+                }
+                Ok(edits)
+            },
+        )
+        .await?;
+
+        Ok(undo_action)
+    }
+
+    async fn move_headline_under(
+        &self,
+        id: &str,
+        file_path: &str,
+        byte_start: i64,
+        parent_id: Option<&str>,
+    ) -> Result<UndoAction> {
+        use tracing::info;
+
+        info!(
+            "[OrgHeadlineDataSource] move_headline_under: id={}, parent_id={:?}, file={}",
+            id, parent_id, file_path
+        );
+
+        let mut undo_action = UndoAction::Irreversible;
+        self.edit_headline(
+            "move_headline_under",
+            file_path,
+            id,
+            Some(byte_start as usize),
+            |content, spans, index| {
+                let parent_index = parent_id
+                    .map(|parent_id| {
+                        spans
+                            .iter()
+                            .position(|s| s.id.as_deref() == Some(parent_id))
+                            .ok_or_else(|| {
+                                format!("Headline '{}' not found in {}", parent_id, file_path)
+                            })
+                    })
+                    .transpose()?;
+                let edits = writer::move_subtree_under_edits(content, spans, index, parent_index)
+                    .map_err(|e| format!("Failed to move headline: {}", e))?;
+                if let Some(moved_start) = writer::moved_subtree_start(&edits) {
+                    let position = writer::subtree_position(spans, index);
+                    undo_action = move_back_op(id, file_path, moved_start, spans, position);
+                }
+                Ok(edits)
+            },
+        )
+        .await?;
+
+        Ok(undo_action)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl TimeTrackingOperations for OrgHeadlineDataSource {
+    async fn clock_in(&self, id: &str) -> Result<UndoAction> {
+        self.edit_clock("clock_in", id, clock::clock_in_edit)
+            .await?;
+        Ok(UndoAction::Undo(clock_op(id, "clock_out", "Clock out")))
+    }
+
+    async fn clock_out(&self, id: &str) -> Result<UndoAction> {
+        self.edit_clock("clock_out", id, clock::clock_out_edit)
+            .await?;
+        Ok(UndoAction::Undo(clock_op(id, "clock_in", "Clock in")))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl SourceBlockOperations for OrgHeadlineDataSource {
+    async fn execute_block(&self, id: &str) -> Result<UndoAction> {
+        let (file_path, headline, index, block) = self.find_source_block(id).await?;
+        let language = block.language.clone().unwrap_or_default();
+        tracing::info!(
+            "[OrgHeadlineDataSource] execute_block: id={}, language={}",
+            id,
+            language
+        );
+
+        // Stream the output so far as updates of the headline
+        let origin = ChangeOrigin::local_with_current_span();
+        let result = self
+            .provider
+            .block_runners()
+            .run(&language, &block.source, |output| {
+                let mut headline = headline.clone();
+                let mut blocks = headline.get_source_blocks();
+                blocks[index].results = Some(BlockResult::text(output));
+                headline.set_source_blocks(blocks);
+                self.provider.emit_headline_changes(vec![Change::Updated {
+                    id: headline.id.clone(),
+                    data: headline,
+                    origin: origin.clone(),
+                }]);
+            })
+            .await;
+
+        self.write_block_results(
+            "execute_block",
+            &file_path,
+            &headline.id,
+            index,
+            Some(&result),
+            block.name.as_deref(),
+        )
+        .await?;
+
+        Ok(UndoAction::Undo(block_results_op(
+            id,
+            block.results.as_ref(),
+        )))
+    }
+
+    async fn set_block_results(&self, id: &str, results: Option<&str>) -> Result<UndoAction> {
+        let results: Option<BlockResult> = results
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(|e| format!("Invalid block results: {}", e))?;
+        let (file_path, headline, index, block) = self.find_source_block(id).await?;
+
+        self.write_block_results(
+            "set_block_results",
+            &file_path,
+            &headline.id,
+            index,
+            results.as_ref(),
+            block.name.as_deref(),
+        )
+        .await?;
+
+        Ok(UndoAction::Undo(block_results_op(
+            id,
+            block.results.as_ref(),
+        )))
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -426,6 +1207,22 @@ impl OperationProvider for OrgHeadlineDataSource {
                 )
                 .into_iter(),
             )
+            .chain(
+                __operations_time_tracking_operations::time_tracking_operations(
+                    entity_name,
+                    short_name,
+                    entity_name,
+                    id_column,
+                ),
+            )
+            .chain(
+                __operations_source_block_operations::source_block_operations(
+                    entity_name,
+                    short_name,
+                    entity_name,
+                    id_column,
+                ),
+            )
             .collect()
     }
 
@@ -436,8 +1233,8 @@ impl OperationProvider for OrgHeadlineDataSource {
         params: StorageEntity,
     ) -> Result<UndoAction> {
         use holon::core::datasource::{
-            UnknownOperationError, __operations_crud_operation_provider,
-            __operations_mutable_block_data_source, __operations_mutable_task_data_source,
+            __operations_crud_operation_provider, __operations_mutable_block_data_source,
+            __operations_mutable_task_data_source, UnknownOperationError,
         };
 
         if entity_name != "org_headlines" {
@@ -459,6 +1256,29 @@ impl OperationProvider for OrgHeadlineDataSource {
             }
         }
 
+        // Try clock_in/clock_out (written to the headline's :LOGBOOK:)
+        match __operations_time_tracking_operations::dispatch_operation(self, op_name, &params)
+            .await
+        {
+            Ok(op) => return Ok(op),
+            Err(err) => {
+                if !UnknownOperationError::is_unknown(err.as_ref()) {
+                    return Err(err);
+                }
+            }
+        }
+
+        // Try execute_block/set_block_results (written to the block's #+RESULTS:)
+        match __operations_source_block_operations::dispatch_operation(self, op_name, &params).await
+        {
+            Ok(op) => return Ok(op),
+            Err(err) => {
+                if !UnknownOperationError::is_unknown(err.as_ref()) {
+                    return Err(err);
+                }
+            }
+        }
+
         // Try CRUD operations
         match __operations_crud_operation_provider::dispatch_operation::<_, OrgHeadline>(
             self, op_name, &params,
@@ -526,4 +1346,161 @@ mod tests {
             "Should have move_block operation"
         );
     }
+
+    #[test]
+    fn test_planning_timestamp_keeps_repeater() {
+        let current = OrgTimestamp::parse("<2024-01-15 Mon 10:00 +1w -2d>");
+
+        let moved = planning_timestamp(&Value::String("2024-01-22".to_string()), current).unwrap();
+        assert_eq!(moved.as_deref(), Some("<2024-01-22 Mon +1w -2d>"));
+
+        let text = planning_timestamp(&Value::String("<2024-02-01 Thu>".to_string()), current);
+        assert_eq!(text.unwrap().as_deref(), Some("<2024-02-01 Thu>"));
+        assert_eq!(planning_timestamp(&Value::Null, current).unwrap(), None);
+        assert!(planning_timestamp(&Value::Integer(3), current).is_err());
+    }
+
+    /// Simple in-memory mock for SyncTokenStore
+    struct MockSyncTokenStore {
+        tokens: std::sync::RwLock<HashMap<String, CoreStreamPosition>>,
+    }
+
+    #[async_trait]
+    impl holon::core::datasource::SyncTokenStore for MockSyncTokenStore {
+        async fn load_token(&self, provider_name: &str) -> Result<Option<CoreStreamPosition>> {
+            Ok(self.tokens.read().unwrap().get(provider_name).cloned())
+        }
+        async fn save_token(
+            &self,
+            provider_name: &str,
+            position: CoreStreamPosition,
+        ) -> Result<()> {
+            self.tokens
+                .write()
+                .unwrap()
+                .insert(provider_name.to_string(), position);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_block_writes_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.org");
+        let original = "* Run\n:PROPERTIES:\n:ID: run\n:END:\n#+BEGIN_SRC sh\necho hi\n#+END_SRC\n";
+        std::fs::write(&path, original).unwrap();
+        let token_store = Arc::new(MockSyncTokenStore {
+            tokens: std::sync::RwLock::new(HashMap::new()),
+        });
+        let provider = Arc::new(OrgModeSyncProvider::new(
+            dir.path().to_path_buf(),
+            token_store,
+        ));
+        let mut headline_rx = provider.subscribe_headlines();
+        let datasource = OrgHeadlineDataSource::new(provider);
+
+        let undo = datasource.execute_block("run::src::0").await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("{}#+RESULTS:\n: hi\n", original)
+        );
+
+        // The output was streamed before the results were written
+        match &headline_rx.try_recv().unwrap().inner[0] {
+            Change::Updated { id, data, .. } => {
+                assert_eq!(id, "run");
+                let results = data.get_source_blocks()[0].results.clone().unwrap();
+                assert_eq!(results.output, BlockResult::text("hi").output);
+            }
+            other => panic!("Expected an update of the headline, got {:?}", other),
+        }
+
+        // Undoing restores the missing results
+        let UndoAction::Undo(inverse) = undo else {
+            panic!("execute_block should be undoable");
+        };
+        assert_eq!(inverse.op_name, "set_block_results");
+        assert_eq!(inverse.params["results"], Value::Null);
+        datasource
+            .set_block_results("run::src::0", None)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+
+        assert!(datasource.execute_block("run::src::1").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_move_and_tags_are_undoable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.org");
+        let original = "* A\n:PROPERTIES:\n:ID: a\n:END:\n** B\n:PROPERTIES:\n:ID: b\n:END:\n* C :home:\n:PROPERTIES:\n:ID: c\n:END:\n";
+        std::fs::write(&path, original).unwrap();
+        let token_store = Arc::new(MockSyncTokenStore {
+            tokens: std::sync::RwLock::new(HashMap::new()),
+        });
+        let provider = Arc::new(OrgModeSyncProvider::new(
+            dir.path().to_path_buf(),
+            token_store,
+        ));
+        let datasource = OrgHeadlineDataSource::new(provider);
+        let file_path = path.to_string_lossy().to_string();
+
+        // Moving the first child away and back puts it under its parent again
+        let undo = datasource
+            .move_headline_after("b", &file_path, 0, "c")
+            .await
+            .unwrap();
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .ends_with(":ID: c\n:END:\n* B\n:PROPERTIES:\n:ID: b\n:END:\n"));
+        let UndoAction::Undo(inverse) = undo else {
+            panic!("move_headline_after should be undoable");
+        };
+        assert_eq!(inverse.op_name, "move_headline_under");
+        assert_eq!(inverse.params["parent_id"], Value::String("a".to_string()));
+        datasource
+            .execute_operation("org_headlines", &inverse.op_name, inverse.params)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+
+        // Tags are written to the headline line; the file is found via the last sync
+        let undo = datasource
+            .set_field("c", "tags", Value::String("home, work".to_string()))
+            .await
+            .unwrap();
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("\n* C :home:work:\n"));
+        let UndoAction::Undo(inverse) = undo else {
+            panic!("Setting tags should be undoable");
+        };
+        assert_eq!(inverse.params["value"], Value::String("home".to_string()));
+
+        assert!(datasource
+            .set_field("missing", "tags", Value::Null)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_file_operations_include_restore_version() {
+        let ops = __operations_org_file_version_operations::org_file_version_operations(
+            "org_files",
+            "file",
+            "org_files",
+            "id",
+        );
+        let restore = ops
+            .iter()
+            .find(|op| op.name == "restore_file_version")
+            .expect("Should have restore_file_version operation");
+        let param_names: Vec<&str> = restore
+            .required_params
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert!(param_names.contains(&"commit"));
+    }
 }
@@ -8,28 +8,36 @@
 
 use async_trait::async_trait;
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 use tokio::sync::broadcast;
 use walkdir::WalkDir;
 
+use holon::core::attachments::AttachmentStore;
 use holon::core::datasource::{
-    generate_sync_operation, Change, ChangeOrigin, OperationDescriptor, OperationProvider, Result,
-    StreamPosition, SyncTokenStore, SyncableProvider, UndoAction,
+    default_id_generator, generate_sync_operation, Change, ChangeOrigin, IdGenerator,
+    OperationDescriptor, OperationProvider, Result, StreamPosition, SyncTokenStore,
+    SyncableProvider, UndoAction,
 };
+use holon::core::time_tracking::TimeEntryStore;
 use holon::storage::types::StorageEntity;
-use holon_api::{BatchMetadata, Operation, SyncTokenUpdate, WithMetadata};
+use holon_api::{BatchMetadata, ChangeSource, Operation, SyncTokenUpdate, WithMetadata};
 
 use holon_filesystem::{
     directory::{ChangesWithMetadata, DirectoryChangeProvider},
     directory::{Directory, ROOT_ID},
 };
 
+use crate::attachments::attachment_source;
+use crate::clock::clock_source;
+use crate::execution::BlockRunners;
+use crate::git::GitVersioning;
 use crate::models::{OrgFile, OrgHeadline};
 use crate::parser::{
-    compute_content_hash, generate_directory_id, generate_file_id, parse_org_file,
+    compute_content_hash, generate_directory_id, generate_file_id, parse_org_file_with_ids,
+    ParseResult,
 };
-use crate::writer::write_id_properties;
+use crate::writer::{headline_spans, write_id_properties};
 
 /// Sync state stored as JSON in token store
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
@@ -47,6 +55,13 @@ pub struct OrgModeSyncProvider {
     directory_tx: broadcast::Sender<ChangesWithMetadata<Directory>>,
     file_tx: broadcast::Sender<ChangesWithMetadata<OrgFile>>,
     headline_tx: broadcast::Sender<ChangesWithMetadata<OrgHeadline>>,
+    git: Option<Arc<GitVersioning>>,
+    time_entries: Option<Arc<TimeEntryStore>>,
+    attachments: Option<Arc<AttachmentStore>>,
+    id_generator: Arc<dyn IdGenerator>,
+    block_runners: BlockRunners,
+    /// File of every headline, by ID, as of the last sync
+    headline_files: RwLock<HashMap<String, PathBuf>>,
 }
 
 impl OrgModeSyncProvider {
@@ -57,9 +72,58 @@ impl OrgModeSyncProvider {
             directory_tx: broadcast::channel(1000).0,
             file_tx: broadcast::channel(1000).0,
             headline_tx: broadcast::channel(1000).0,
+            git: None,
+            time_entries: None,
+            attachments: None,
+            id_generator: default_id_generator(),
+            block_runners: BlockRunners::default(),
+            headline_files: RwLock::new(HashMap::new()),
         }
     }
 
+    /// Commit every file write-back to a git repository at the root directory
+    pub fn with_git_versioning(mut self, git: Arc<GitVersioning>) -> Self {
+        self.git = Some(git);
+        self
+    }
+
+    /// Import `:LOGBOOK:` CLOCK lines into the time_entries table on sync
+    pub fn with_time_entries(mut self, store: Arc<TimeEntryStore>) -> Self {
+        self.time_entries = Some(store);
+        self
+    }
+
+    /// Import `[[file:...]]` links into the attachments table on sync
+    pub fn with_attachments(mut self, store: Arc<AttachmentStore>) -> Self {
+        self.attachments = Some(store);
+        self
+    }
+
+    /// Generate IDs for headlines without an `:ID:` property with `id_generator`
+    pub fn with_id_generator(mut self, id_generator: Arc<dyn IdGenerator>) -> Self {
+        self.id_generator = id_generator;
+        self
+    }
+
+    /// Run source blocks with `block_runners` (see `crate::execution`)
+    pub fn with_block_runners(mut self, block_runners: BlockRunners) -> Self {
+        self.block_runners = block_runners;
+        self
+    }
+
+    pub fn block_runners(&self) -> &BlockRunners {
+        &self.block_runners
+    }
+
+    pub fn id_generator(&self) -> Arc<dyn IdGenerator> {
+        self.id_generator.clone()
+    }
+
+    /// Git versioning of the root directory, if enabled
+    pub fn git_versioning(&self) -> Option<&Arc<GitVersioning>> {
+        self.git.as_ref()
+    }
+
     pub fn subscribe_directories(&self) -> broadcast::Receiver<ChangesWithMetadata<Directory>> {
         self.directory_tx.subscribe()
     }
@@ -72,6 +136,59 @@ impl OrgModeSyncProvider {
         self.headline_tx.subscribe()
     }
 
+    /// Emit headline changes outside of a sync, e.g. the output of a running source block
+    pub fn emit_headline_changes(&self, changes: Vec<Change<OrgHeadline>>) {
+        let _ = self.headline_tx.send(WithMetadata {
+            inner: changes,
+            metadata: BatchMetadata {
+                relation_name: "org_headlines".to_string(),
+                trace_context: holon_api::BatchTraceContext::from_current_span(),
+                sync_token: None,
+                full_snapshot: false,
+            },
+        });
+    }
+
+    /// The .org file the last sync found the headline with the given ID in
+    pub fn headline_file(&self, id: &str) -> Option<PathBuf> {
+        self.headline_files.read().unwrap().get(id).cloned()
+    }
+
+    /// Parse the .org file at `path` (within the root directory) as a sync does
+    pub fn parse_file(&self, path: &Path) -> Result<ParseResult> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let (parent_id, parent_depth) = self.file_parent(path);
+        Ok(parse_org_file_with_ids(
+            path,
+            &content,
+            &parent_id,
+            parent_depth,
+            self.id_generator.as_ref(),
+        )?)
+    }
+
+    /// ID and depth of the directory containing the file at `path`
+    fn file_parent(&self, path: &Path) -> (String, i64) {
+        let parent_id = path
+            .parent()
+            .map(|p| {
+                if p == self.root_directory {
+                    ROOT_ID.to_string()
+                } else {
+                    generate_directory_id(p, &self.root_directory)
+                }
+            })
+            .unwrap_or_else(|| ROOT_ID.to_string());
+
+        let parent_depth = path
+            .strip_prefix(&self.root_directory)
+            .map(|p| p.components().count() as i64 - 1)
+            .unwrap_or(0);
+
+        (parent_id, parent_depth)
+    }
+
     /// Load sync state from token store
     async fn load_state(&self) -> Result<SyncState> {
         let position = self
@@ -100,11 +217,13 @@ impl OrgModeSyncProvider {
         Vec<Change<OrgFile>>,
         Vec<Change<OrgHeadline>>,
     )> {
-        let origin = ChangeOrigin::remote_with_current_span();
+        let origin = ChangeOrigin::remote_with_current_span()
+            .with_source(ChangeSource::default().with_provider("orgmode"));
         let mut new_state = SyncState::default();
         let mut dir_changes = Vec::new();
         let mut file_changes = Vec::new();
         let mut headline_changes = Vec::new();
+        let mut headline_files = HashMap::new();
 
         // Track what we've seen to detect deletions
         let mut seen_dirs: HashMap<String, bool> = HashMap::new();
@@ -183,29 +302,52 @@ impl OrgModeSyncProvider {
                     .unwrap_or(true); // New file = changed
 
                 if file_changed {
-                    let parent_id = path
-                        .parent()
-                        .map(|p| {
-                            if p == self.root_directory {
-                                ROOT_ID.to_string()
-                            } else {
-                                generate_directory_id(p, &self.root_directory)
-                            }
-                        })
-                        .unwrap_or_else(|| ROOT_ID.to_string());
-
-                    let parent_depth = path
-                        .strip_prefix(&self.root_directory)
-                        .map(|p| p.components().count() as i64 - 1)
-                        .unwrap_or(0);
-
-                    let parse_result = parse_org_file(path, &content, &parent_id, parent_depth)?;
+                    let (parent_id, parent_depth) = self.file_parent(path);
+                    let parse_result = parse_org_file_with_ids(
+                        path,
+                        &content,
+                        &parent_id,
+                        parent_depth,
+                        self.id_generator.as_ref(),
+                    )?;
 
                     // Write back IDs for headlines that need them
                     if !parse_result.headlines_needing_ids.is_empty() {
                         write_id_properties(path, &parse_result.headlines_needing_ids)?;
                     }
 
+                    if let Some(store) = &self.time_entries {
+                        store
+                            .replace_source_entries(
+                                &clock_source(&file_id),
+                                &parse_result.clock_entries,
+                            )
+                            .await?;
+                    }
+
+                    if let Some(store) = &self.attachments {
+                        let attachments: Vec<_> = parse_result
+                            .attachments
+                            .iter()
+                            .cloned()
+                            .map(|attachment| {
+                                let size = attachment
+                                    .path
+                                    .as_deref()
+                                    .and_then(|path| std::fs::metadata(path).ok())
+                                    .filter(|metadata| metadata.is_file())
+                                    .map(|metadata| metadata.len() as i64);
+                                match size {
+                                    Some(size) => attachment.with_size(size),
+                                    None => attachment,
+                                }
+                            })
+                            .collect();
+                        store
+                            .replace_source_attachments(&attachment_source(&file_id), &attachments)
+                            .await?;
+                    }
+
                     // Emit file change
                     let is_new = !old_state.file_hashes.contains_key(&file_id);
                     if is_new {
@@ -223,12 +365,18 @@ impl OrgModeSyncProvider {
 
                     // Emit headline changes (for simplicity, treat all as Updated)
                     for headline in parse_result.headlines {
+                        headline_files.insert(headline.id.clone(), path.to_path_buf());
                         headline_changes.push(Change::Updated {
                             id: headline.id.clone(),
                             data: headline,
                             origin: origin.clone(),
                         });
                     }
+                } else {
+                    // Unchanged files have all their IDs written already
+                    for id in headline_spans(&content).into_iter().filter_map(|s| s.id) {
+                        headline_files.insert(id, path.to_path_buf());
+                    }
                 }
 
                 new_state.file_hashes.insert(file_id, content_hash);
@@ -240,6 +388,7 @@ impl OrgModeSyncProvider {
             entry_count,
             org_file_count
         );
+        *self.headline_files.write().unwrap() = headline_files;
 
         // Detect deleted directories
         for old_dir_id in old_state.known_dirs.keys() {
@@ -258,6 +407,16 @@ impl OrgModeSyncProvider {
                     id: old_file_id.clone(),
                     origin: origin.clone(),
                 });
+                if let Some(store) = &self.time_entries {
+                    store
+                        .replace_source_entries(&clock_source(old_file_id), &[])
+                        .await?;
+                }
+                if let Some(store) = &self.attachments {
+                    store
+                        .replace_source_attachments(&attachment_source(old_file_id), &[])
+                        .await?;
+                }
                 // Note: Headlines from deleted files should be cleaned up
                 // In production, we'd track headline IDs per file
             }
@@ -326,18 +485,21 @@ impl SyncableProvider for OrgModeSyncProvider {
             relation_name: "directories".to_string(),
             trace_context: trace_context.clone(),
             sync_token: Some(sync_token_update.clone()),
+            full_snapshot: false,
         };
 
         let file_metadata = BatchMetadata {
             relation_name: "org_files".to_string(),
             trace_context: trace_context.clone(),
             sync_token: Some(sync_token_update.clone()),
+            full_snapshot: false,
         };
 
         let headline_metadata = BatchMetadata {
             relation_name: "org_headlines".to_string(),
             trace_context,
             sync_token: Some(sync_token_update),
+            full_snapshot: false,
         };
 
         // Log stats
@@ -402,7 +564,6 @@ impl OperationProvider for OrgModeSyncProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::RwLock;
     use tempfile::tempdir;
 
     /// Simple in-memory mock for SyncTokenStore
@@ -465,4 +626,26 @@ mod tests {
         let headline_batch = headline_rx.try_recv().unwrap();
         assert_eq!(headline_batch.inner.len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_sync_indexes_headline_files() {
+        let dir = tempdir().unwrap();
+        let org_file = dir.path().join("test.org");
+        std::fs::write(&org_file, "* Headline\n:PROPERTIES:\n:ID: h1\n:END:\n").unwrap();
+
+        let token_store = Arc::new(MockSyncTokenStore::new());
+        let provider = OrgModeSyncProvider::new(dir.path().to_path_buf(), token_store.clone());
+        assert_eq!(provider.headline_file("h1"), None);
+
+        let position = provider.sync(StreamPosition::Beginning).await.unwrap();
+        assert_eq!(provider.headline_file("h1"), Some(org_file.clone()));
+
+        // Unchanged files are not parsed again, but stay indexed
+        token_store
+            .save_token(provider.provider_name(), position)
+            .await
+            .unwrap();
+        provider.sync(StreamPosition::Beginning).await.unwrap();
+        assert_eq!(provider.headline_file("h1"), Some(org_file));
+    }
 }
@@ -1,12 +1,18 @@
+use crate::attachments;
+use crate::clock;
 use crate::models::{OrgFile, OrgHeadline, OrgSourceBlock};
+use crate::timestamp::OrgTimestamp;
+use crate::writer::{self, headline_spans};
 use anyhow::Result;
 use chrono::Utc;
+use holon::core::attachments::Attachment;
+use holon::core::datasource::{default_id_generator, IdGenerator};
+use holon::core::time_tracking::TimeEntry;
 use orgize::ast::{Headline, SourceBlock};
 use orgize::rowan::ast::AstNode;
 use orgize::{Org, ParseConfig, SyntaxKind};
 use sha2::{Digest, Sha256};
 use std::path::Path;
-use uuid::Uuid;
 
 /// Generate a directory ID from its path (ID is the relative path from root)
 pub fn generate_directory_id(path: &Path, root_directory: &Path) -> String {
@@ -43,6 +49,10 @@ pub struct ParseResult {
     pub headlines: Vec<OrgHeadline>,
     /// Headlines that need :ID: property added (id, byte_start for insertion)
     pub headlines_needing_ids: Vec<(String, i64)>,
+    /// Time entries from the headlines' `:LOGBOOK:` CLOCK lines
+    pub clock_entries: Vec<TimeEntry>,
+    /// Attachments from the headlines' `[[file:...]]` links
+    pub attachments: Vec<Attachment>,
 }
 
 /// Parse TODO keywords from file content (#+TODO: or #+SEQ_TODO: lines)
@@ -91,6 +101,23 @@ pub fn parse_org_file(
     content: &str,
     parent_dir_id: &str,
     parent_depth: i64,
+) -> Result<ParseResult> {
+    parse_org_file_with_ids(
+        path,
+        content,
+        parent_dir_id,
+        parent_depth,
+        default_id_generator().as_ref(),
+    )
+}
+
+/// Parse an org file, generating missing headline IDs with `id_generator`
+pub fn parse_org_file_with_ids(
+    path: &Path,
+    content: &str,
+    parent_dir_id: &str,
+    parent_depth: i64,
+    id_generator: &dyn IdGenerator,
 ) -> Result<ParseResult> {
     let file_id = generate_file_id(path);
     let file_name = path
@@ -147,10 +174,38 @@ pub fn parse_org_file(
         &mut headlines_needing_ids,
     )?;
 
+    // Extract CLOCK lines and file links from each headline's own section
+    let spans = headline_spans(content);
+    let sections: Vec<(&str, &str)> = headlines
+        .iter()
+        .filter_map(|headline| {
+            let span = spans
+                .iter()
+                .find(|s| s.byte_start as i64 == headline.byte_start)?;
+            Some((
+                headline.id.as_str(),
+                &content[span.line_end..span.section_end],
+            ))
+        })
+        .collect();
+    let clock_entries = sections
+        .iter()
+        .flat_map(|(headline_id, section)| clock::clock_entries(headline_id, &file_id, section))
+        .collect();
+    let created_at = Utc::now().timestamp_millis();
+    let attachments = sections
+        .iter()
+        .flat_map(|(headline_id, section)| {
+            attachments::file_attachments(headline_id, &file_id, path, section, created_at)
+        })
+        .collect();
+
     Ok(ParseResult {
         file,
         headlines,
         headlines_needing_ids,
+        clock_entries,
+        attachments,
     })
 }
 
@@ -187,7 +242,7 @@ fn process_headlines(
         let byte_end = u32::from(range.end()) as i64;
 
         // Extract :ID: property if exists
-        let (id, needs_write) = extract_or_generate_id(&headline);
+        let (id, needs_write) = extract_or_generate_id(&headline, id_generator);
         if needs_write {
             needs_id.push((id.clone(), byte_start));
         }
@@ -244,8 +299,14 @@ fn process_headlines(
         org_headline.todo_keyword = todo_keyword;
         org_headline.priority = priority;
         org_headline.tags = tags;
-        org_headline.scheduled = scheduled;
-        org_headline.deadline = deadline;
+        org_headline.set_scheduled(scheduled);
+        org_headline.set_deadline(deadline);
+        org_headline.set_timestamp(OrgTimestamp::find_active(&org_headline.title).or_else(|| {
+            org_headline
+                .content
+                .as_deref()
+                .and_then(OrgTimestamp::find_active)
+        }));
         org_headline.properties = properties;
         org_headline.set_source_blocks(source_blocks);
 
@@ -266,9 +327,9 @@ fn process_headlines(
     Ok(())
 }
 
-/// Extract :ID: property from headline, or generate a new UUID
+/// Extract :ID: property from headline, or generate a new ID
 /// Returns (id, needs_write_back)
-fn extract_or_generate_id(headline: &Headline) -> (String, bool) {
+fn extract_or_generate_id(headline: &Headline, id_generator: &dyn IdGenerator) -> (String, bool) {
     if let Some(drawer) = headline.properties() {
         // Use get() method to look up ID property
         if let Some(id_token) = drawer.get("ID") {
@@ -278,8 +339,8 @@ fn extract_or_generate_id(headline: &Headline) -> (String, bool) {
             }
         }
     }
-    // Generate new UUID
-    (Uuid::new_v4().to_string(), true)
+    // Generate new ID
+    (id_generator.generate(), true)
 }
 
 /// Extract SCHEDULED and DEADLINE timestamps from headline
@@ -372,8 +433,17 @@ fn extract_section_content(headline: &Headline) -> (Option<String>, Vec<OrgSourc
                     org_source_block.header_args = OrgSourceBlock::parse_header_args(&params);
                 }
 
-                source_blocks.push(org_source_block);
+                // Attach the block's #+RESULTS: and keep them out of the text
                 last_end = block_end;
+                let section_text = section_syntax.to_string();
+                let relative_end = (block_end - section_start) as usize;
+                if let Some((start, end)) = writer::results_span(&section_text, relative_end) {
+                    org_source_block.results =
+                        writer::parse_block_result(&section_text[start..end]);
+                    last_end = section_start + end as i64;
+                }
+
+                source_blocks.push(org_source_block);
             }
         }
     }
@@ -483,6 +553,26 @@ mod tests {
         assert_eq!(h.tags, Some("work,urgent".to_string()));
     }
 
+    #[test]
+    fn test_parse_planning_timestamps() {
+        let content = "* TODO Review\nSCHEDULED: <2024-01-15 Mon 10:00 .+1w> DEADLINE: <2024-01-19 Fri -2d>\nBring <2024-01-16 Tue 14:00-15:00> notes";
+        let path = PathBuf::from("/test/file.org");
+
+        let result = parse_org_file(&path, content, ROOT_ID, 0).unwrap();
+
+        let h = &result.headlines[0];
+        assert_eq!(h.scheduled.as_deref(), Some("<2024-01-15 Mon 10:00 .+1w>"));
+        assert_eq!(h.scheduled_repeater.as_deref(), Some(".+1w"));
+        assert_eq!(
+            h.scheduled_at,
+            h.scheduled_timestamp().and_then(|t| t.start_utc())
+        );
+        assert!(h.scheduled_at.is_some());
+        assert!(h.deadline_at.is_some());
+        assert_eq!(h.deadline_repeater, None);
+        assert_eq!(h.timestamp.as_deref(), Some("<2024-01-16 Tue 14:00-15:00>"));
+    }
+
     #[test]
     fn test_parse_title_and_todo_keywords() {
         let content = "#+TITLE: My Document\n#+TODO: TODO INPROGRESS | DONE CANCELLED\n* Task";
@@ -522,6 +612,39 @@ mod tests {
         assert!(result.headlines_needing_ids.is_empty());
     }
 
+    #[test]
+    fn test_generated_ids_use_configured_strategy() {
+        use holon::core::datasource::IdStrategy;
+
+        let content = "* First\n* Second";
+        let path = PathBuf::from("/test/file.org");
+        let ulid = IdStrategy::Ulid.generator();
+
+        let result = parse_org_file_with_ids(&path, content, ROOT_ID, 0, ulid.as_ref()).unwrap();
+
+        assert_eq!(result.headlines_needing_ids.len(), 2);
+        let (first, second) = (&result.headlines[0].id, &result.headlines[1].id);
+        assert_eq!(first.len(), 26);
+        assert!(first < second, "ULIDs should sort in document order");
+    }
+
+    #[test]
+    fn test_parse_logbook_clocks() {
+        let content = "* Task\n:PROPERTIES:\n:ID: task-1\n:END:\n:LOGBOOK:\nCLOCK: [2024-01-15 Mon 09:00]--[2024-01-15 Mon 10:30] =>  1:30\n:END:\n** Subtask\n:LOGBOOK:\nCLOCK: [2024-01-15 Mon 11:00]\n:END:";
+        let path = PathBuf::from("/test/file.org");
+
+        let result = parse_org_file(&path, content, ROOT_ID, 0).unwrap();
+
+        assert_eq!(result.clock_entries.len(), 2);
+        let closed = &result.clock_entries[0];
+        assert_eq!(closed.task_id, "task-1");
+        assert_eq!(closed.entity_name, "org_headlines");
+        assert_eq!(closed.duration_ms, Some(90 * 60 * 1000));
+        assert_eq!(closed.source, format!("org:{}", result.file.id));
+        assert_eq!(result.clock_entries[1].task_id, result.headlines[1].id);
+        assert!(result.clock_entries[1].is_running());
+    }
+
     #[test]
     fn test_headlines_without_id_need_writeback() {
         let content = "* Headline without ID";
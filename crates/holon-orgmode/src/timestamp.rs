@@ -0,0 +1,424 @@
+//! Org-mode timestamps as used by SCHEDULED/DEADLINE and in headline text
+//!
+//! `OrgTimestamp` parses every form of a single timestamp or range:
+//!
+//! ```text
+//! <2024-01-15 Mon>                      date
+//! <2024-01-15 Mon 10:00>                date and time
+//! <2024-01-15 Mon 10:00-11:30>          time range
+//! <2024-01-15 Mon>--<2024-01-17 Wed>    date range
+//! <2024-01-15 Mon 10:00 .+1w -2d>       repeater and warning delay
+//! [2024-01-15 Mon]                      inactive
+//! ```
+//!
+//! and formats back to the same text, so a rescheduled task keeps its repeater and
+//! warning delay. Times are local; `start_utc` converts them for typed columns.
+
+use std::fmt;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+/// Unit of a repeater or delay interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntervalUnit {
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl IntervalUnit {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'h' => Some(Self::Hour),
+            'd' => Some(Self::Day),
+            'w' => Some(Self::Week),
+            'm' => Some(Self::Month),
+            'y' => Some(Self::Year),
+            _ => None,
+        }
+    }
+
+    fn as_char(self) -> char {
+        match self {
+            Self::Hour => 'h',
+            Self::Day => 'd',
+            Self::Week => 'w',
+            Self::Month => 'm',
+            Self::Year => 'y',
+        }
+    }
+}
+
+/// An interval such as `1w`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interval {
+    pub value: u32,
+    pub unit: IntervalUnit,
+}
+
+impl Interval {
+    fn parse(text: &str) -> Option<Self> {
+        let unit = IntervalUnit::from_char(text.chars().last()?)?;
+        let value = text[..text.len() - 1].parse().ok()?;
+        Some(Self { value, unit })
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.value, self.unit.as_char())
+    }
+}
+
+/// How the next occurrence of a repeating timestamp is computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeaterKind {
+    /// `+1w`: shift by one interval
+    Cumulate,
+    /// `++1w`: shift by whole intervals until in the future
+    CatchUp,
+    /// `.+1w`: one interval after completion
+    Restart,
+}
+
+impl RepeaterKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::Cumulate => "+",
+            Self::CatchUp => "++",
+            Self::Restart => ".+",
+        }
+    }
+}
+
+/// A repeater such as `.+1w`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repeater {
+    pub kind: RepeaterKind,
+    pub interval: Interval,
+}
+
+impl Repeater {
+    fn parse(text: &str) -> Option<Self> {
+        let (kind, rest) = if let Some(rest) = text.strip_prefix("++") {
+            (RepeaterKind::CatchUp, rest)
+        } else if let Some(rest) = text.strip_prefix(".+") {
+            (RepeaterKind::Restart, rest)
+        } else {
+            (RepeaterKind::Cumulate, text.strip_prefix('+')?)
+        };
+        Some(Self {
+            kind,
+            interval: Interval::parse(rest)?,
+        })
+    }
+}
+
+impl fmt::Display for Repeater {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.kind.prefix(), self.interval)
+    }
+}
+
+/// A warning delay such as `-2d` (`--2d` applies to the first occurrence only)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delay {
+    pub first_only: bool,
+    pub interval: Interval,
+}
+
+impl Delay {
+    fn parse(text: &str) -> Option<Self> {
+        let (first_only, rest) = match text.strip_prefix("--") {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('-')?),
+        };
+        Some(Self {
+            first_only,
+            interval: Interval::parse(rest)?,
+        })
+    }
+}
+
+impl fmt::Display for Delay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = if self.first_only { "--" } else { "-" };
+        write!(f, "{}{}", prefix, self.interval)
+    }
+}
+
+/// End of a timestamp range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampEnd {
+    /// `<2024-01-15 Mon 10:00-11:30>`
+    Time(NaiveTime),
+    /// `<2024-01-15 Mon>--<2024-01-17 Wed>`
+    Date {
+        date: NaiveDate,
+        time: Option<NaiveTime>,
+    },
+}
+
+/// A parsed org timestamp or range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrgTimestamp {
+    /// `<...>` (shows up in the agenda) rather than `[...]`
+    pub active: bool,
+    pub date: NaiveDate,
+    pub time: Option<NaiveTime>,
+    pub end: Option<TimestampEnd>,
+    pub repeater: Option<Repeater>,
+    pub delay: Option<Delay>,
+}
+
+/// The parts of a single `<...>` or `[...]`
+struct Part {
+    active: bool,
+    date: NaiveDate,
+    time: Option<NaiveTime>,
+    end_time: Option<NaiveTime>,
+    repeater: Option<Repeater>,
+    delay: Option<Delay>,
+}
+
+impl Part {
+    fn parse(text: &str) -> Option<Self> {
+        let (active, inner) = if let Some(inner) = text.strip_prefix('<') {
+            (true, inner.strip_suffix('>')?)
+        } else {
+            (false, text.strip_prefix('[')?.strip_suffix(']')?)
+        };
+
+        let mut tokens = inner.split_whitespace();
+        let mut part = Self {
+            active,
+            date: NaiveDate::parse_from_str(tokens.next()?, "%Y-%m-%d").ok()?,
+            time: None,
+            end_time: None,
+            repeater: None,
+            delay: None,
+        };
+        for token in tokens {
+            if token.starts_with(|c: char| c.is_ascii_digit()) {
+                let (start, end) = match token.split_once('-') {
+                    Some((start, end)) => (start, Some(end)),
+                    None => (token, None),
+                };
+                part.time = Some(parse_time(start)?);
+                part.end_time = match end {
+                    Some(end) => Some(parse_time(end)?),
+                    None => None,
+                };
+            } else if token.starts_with(['+', '.']) {
+                part.repeater = Some(Repeater::parse(token)?);
+            } else if token.starts_with('-') {
+                part.delay = Some(Delay::parse(token)?);
+            } else if !token.chars().all(char::is_alphabetic) {
+                // Anything but the day name
+                return None;
+            }
+        }
+        Some(part)
+    }
+}
+
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(text, "%H:%M").ok()
+}
+
+impl OrgTimestamp {
+    /// A plain active timestamp without repeater or delay
+    pub fn new(date: NaiveDate, time: Option<NaiveTime>) -> Self {
+        Self {
+            active: true,
+            date,
+            time,
+            end: None,
+            repeater: None,
+            delay: None,
+        }
+    }
+
+    /// Parse a timestamp or range; surrounding whitespace is ignored
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let close = text.find(['>', ']'])?;
+        let (first, second) = match text[close + 1..].strip_prefix("--") {
+            Some(second) => (&text[..=close], Some(second)),
+            None => (text, None),
+        };
+
+        let start = Part::parse(first)?;
+        let end = match second {
+            Some(second) => {
+                let end = Part::parse(second)?;
+                if end.active != start.active || start.end_time.is_some() {
+                    return None;
+                }
+                Some(TimestampEnd::Date {
+                    date: end.date,
+                    time: end.time,
+                })
+            }
+            None => start.end_time.map(TimestampEnd::Time),
+        };
+        Some(Self {
+            active: start.active,
+            date: start.date,
+            time: start.time,
+            end,
+            repeater: start.repeater,
+            delay: start.delay,
+        })
+    }
+
+    /// The first active timestamp in `text` (e.g. a headline title or body)
+    pub fn find_active(text: &str) -> Option<Self> {
+        let mut rest = text;
+        while let Some(open) = rest.find('<') {
+            rest = &rest[open..];
+            let close = rest.find('>')?;
+            // Include a following `--<...>` range end
+            let end = match rest[close + 1..].strip_prefix("--<") {
+                Some(tail) => tail.find('>').map(|c| close + 4 + c).unwrap_or(close),
+                None => close,
+            };
+            if let Some(timestamp) = Self::parse(&rest[..=end]) {
+                return Some(timestamp);
+            }
+            rest = &rest[1..];
+        }
+        None
+    }
+
+    /// Start as local date and time (midnight for a date without time)
+    pub fn start(&self) -> NaiveDateTime {
+        self.date.and_time(self.time.unwrap_or(NaiveTime::MIN))
+    }
+
+    /// Start as a UTC instant, interpreting the timestamp in the local time zone
+    pub fn start_utc(&self) -> Option<DateTime<Utc>> {
+        Local
+            .from_local_datetime(&self.start())
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+
+    /// The same timestamp moved to `date` and `time`, keeping repeater, delay and
+    /// the length of a range
+    pub fn rescheduled(&self, date: NaiveDate, time: Option<NaiveTime>) -> Self {
+        let end = match (self.end, time) {
+            (Some(TimestampEnd::Time(end)), Some(new_time)) => self
+                .time
+                .map(|old_time| TimestampEnd::Time(new_time + (end - old_time))),
+            (Some(TimestampEnd::Time(_)), None) => None,
+            (
+                Some(TimestampEnd::Date {
+                    date: end_date,
+                    time: end_time,
+                }),
+                _,
+            ) => Some(TimestampEnd::Date {
+                date: date + (end_date - self.date),
+                time: end_time,
+            }),
+            (None, _) => None,
+        };
+        Self {
+            date,
+            time,
+            end,
+            ..*self
+        }
+    }
+}
+
+impl fmt::Display for OrgTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (open, close) = if self.active { ('<', '>') } else { ('[', ']') };
+        write!(f, "{}{}", open, self.date.format("%Y-%m-%d %a"))?;
+        if let Some(time) = self.time {
+            write!(f, " {}", time.format("%H:%M"))?;
+            if let Some(TimestampEnd::Time(end)) = self.end {
+                write!(f, "-{}", end.format("%H:%M"))?;
+            }
+        }
+        if let Some(repeater) = self.repeater {
+            write!(f, " {}", repeater)?;
+        }
+        if let Some(delay) = self.delay {
+            write!(f, " {}", delay)?;
+        }
+        write!(f, "{}", close)?;
+        if let Some(TimestampEnd::Date { date, time }) = self.end {
+            write!(f, "--{}{}", open, date.format("%Y-%m-%d %a"))?;
+            if let Some(time) = time {
+                write!(f, " {}", time.format("%H:%M"))?;
+            }
+            write!(f, "{}", close)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for text in [
+            "<2024-01-15 Mon>",
+            "<2024-01-15 Mon 10:00>",
+            "<2024-01-15 Mon 10:00-11:30>",
+            "<2024-01-15 Mon>--<2024-01-17 Wed>",
+            "<2024-01-15 Mon 09:00>--<2024-01-17 Wed 18:00>",
+            "<2024-01-15 Mon 10:00 .+1w -2d>",
+            "<2024-01-15 Mon ++1m --3d>",
+            "[2024-01-15 Mon +1y]",
+        ] {
+            let timestamp = OrgTimestamp::parse(text).unwrap();
+            assert_eq!(timestamp.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_parse_parts() {
+        let timestamp = OrgTimestamp::parse("<2024-01-15 Mon 10:00 .+1w -2d>").unwrap();
+        assert!(timestamp.active);
+        assert_eq!(
+            timestamp.date,
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()
+        );
+        assert_eq!(timestamp.time, NaiveTime::from_hms_opt(10, 0, 0));
+        assert_eq!(timestamp.repeater.unwrap().to_string(), ".+1w");
+        assert_eq!(timestamp.delay.unwrap().interval.unit, IntervalUnit::Day);
+
+        assert!(OrgTimestamp::parse("<2024-13-01 Mon>").is_none());
+        assert!(OrgTimestamp::parse("<2024-01-15 Mon>--[2024-01-16 Tue]").is_none());
+        assert!(OrgTimestamp::parse("2024-01-15").is_none());
+    }
+
+    #[test]
+    fn test_rescheduled_keeps_repeater_and_range_length() {
+        let timestamp = OrgTimestamp::parse("<2024-01-15 Mon 10:00-11:30 +1w -2d>").unwrap();
+        let moved = timestamp.rescheduled(
+            NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+            NaiveTime::from_hms_opt(14, 0, 0),
+        );
+        assert_eq!(moved.to_string(), "<2024-02-01 Thu 14:00-15:30 +1w -2d>");
+
+        let range = OrgTimestamp::parse("<2024-01-15 Mon>--<2024-01-17 Wed>").unwrap();
+        let moved = range.rescheduled(NaiveDate::from_ymd_opt(2024, 1, 20).unwrap(), None);
+        assert_eq!(moved.to_string(), "<2024-01-20 Sat>--<2024-01-22 Mon>");
+    }
+
+    #[test]
+    fn test_find_active() {
+        let found = OrgTimestamp::find_active("Meeting [2024-01-01 Mon] at <2024-01-15 Mon 10:00>");
+        assert_eq!(found.unwrap().to_string(), "<2024-01-15 Mon 10:00>");
+        assert!(OrgTimestamp::find_active("if a < b then c > d").is_none());
+    }
+}
@@ -5,6 +5,8 @@
 //! - Updating headline content
 //! - Creating and deleting headlines
 //! - Writing and updating source blocks (#+BEGIN_SRC ... #+END_SRC)
+//! - Reading and replacing the #+RESULTS: of source blocks
+//! - Span-based incremental edits that preserve concurrent changes to the file
 
 use anyhow::{Context, Result};
 use std::collections::HashMap;
@@ -456,21 +458,23 @@ pub fn format_block_result(result: &BlockResult, name: Option<&str>) -> String {
             }
         }
         ResultOutput::Table { headers, rows } => {
-            output.push('|');
-            for header in headers {
-                output.push(' ');
-                output.push_str(header);
-                output.push_str(" |");
-            }
-            output.push('\n');
+            if !headers.is_empty() {
+                output.push('|');
+                for header in headers {
+                    output.push(' ');
+                    output.push_str(header);
+                    output.push_str(" |");
+                }
+                output.push('\n');
 
-            output.push('|');
-            for _ in headers {
-                output.push_str("---+");
+                output.push('|');
+                for _ in headers {
+                    output.push_str("---+");
+                }
+                output.pop();
+                output.push('|');
+                output.push('\n');
             }
-            output.pop();
-            output.push('|');
-            output.push('\n');
 
             for row in rows {
                 output.push('|');
@@ -638,6 +642,848 @@ pub fn update_api_source_block(
     Ok(result)
 }
 
+// =============================================================================
+// Source Block Results
+// =============================================================================
+
+/// Byte ranges of the source blocks in a headline's own section, from the start of
+/// the `#+BEGIN_SRC` line to the end of the `#+END_SRC` line
+pub fn source_block_ranges(content: &str, span: &HeadlineSpan) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut begin = None;
+    let mut pos = span.line_end;
+    for line in content[span.line_end..span.section_end].split_inclusive('\n') {
+        let text = line.trim_end_matches(['\n', '\r']);
+        let directive = text.trim_start().to_ascii_lowercase();
+        match begin {
+            None if directive.starts_with("#+begin_src") => begin = Some(pos),
+            Some(start) if directive.starts_with("#+end_src") => {
+                ranges.push((start, pos + text.len()));
+                begin = None;
+            }
+            _ => {}
+        }
+        pos += line.len();
+    }
+    ranges
+}
+
+/// Byte range of the `#+RESULTS:` of a source block ending at `block_end`
+///
+/// The results may follow after blank lines and span fixed-width (`: `) lines, a
+/// table or an error block.
+pub fn results_span(content: &str, block_end: usize) -> Option<(usize, usize)> {
+    let mut start = None;
+    let mut end = block_end;
+    let mut in_error = false;
+    let mut pos = block_end;
+    for line in content[block_end..].split_inclusive('\n') {
+        let text = line.trim_end_matches(['\n', '\r']);
+        let trimmed = text.trim();
+        match start {
+            None if trimmed.is_empty() => {}
+            None if trimmed.to_ascii_lowercase().starts_with("#+results") => start = Some(pos),
+            None => return None,
+            Some(_) if headline_level(text).is_some() => break,
+            Some(_) if in_error => in_error = !trimmed.eq_ignore_ascii_case("#+end_error"),
+            Some(_) if trimmed.eq_ignore_ascii_case("#+begin_error") => in_error = true,
+            Some(_) if trimmed == ":" || trimmed.starts_with(": ") || trimmed.starts_with('|') => {}
+            Some(_) => break,
+        }
+        if start.is_some() {
+            end = pos + text.len();
+        }
+        pos += line.len();
+    }
+    start.map(|start| (start, end))
+}
+
+/// Parse a `#+RESULTS:` block as written by `format_block_result`
+///
+/// The execution time isn't kept in the file, so `executed_at` is 0.
+pub fn parse_block_result(text: &str) -> Option<BlockResult> {
+    let mut lines = text.lines().map(str::trim);
+    if !lines.next()?.to_ascii_lowercase().starts_with("#+results") {
+        return None;
+    }
+    let body: Vec<&str> = lines.collect();
+
+    let output = match body.first() {
+        Some(first) if first.eq_ignore_ascii_case("#+begin_error") => ResultOutput::Error {
+            message: body[1..]
+                .iter()
+                .take_while(|line| !line.eq_ignore_ascii_case("#+end_error"))
+                .copied()
+                .collect::<Vec<_>>()
+                .join("\n"),
+        },
+        Some(first) if first.starts_with('|') => {
+            let is_separator = |line: &&str| line.starts_with("|-");
+            let cells = |line: &str| -> Vec<String> {
+                line.trim_matches('|')
+                    .split('|')
+                    .map(|cell| cell.trim().to_string())
+                    .collect()
+            };
+            let (headers, rows) = if body.get(1).is_some_and(is_separator) {
+                (cells(first), &body[2..])
+            } else {
+                (Vec::new(), &body[..])
+            };
+            ResultOutput::Table {
+                headers,
+                rows: rows
+                    .iter()
+                    .filter(|line| !is_separator(line))
+                    .map(|line| cells(line).into_iter().map(Value::String).collect())
+                    .collect(),
+            }
+        }
+        _ => ResultOutput::Text {
+            content: body
+                .iter()
+                .map(|line| {
+                    let line = line.strip_prefix(':').unwrap_or(line);
+                    line.strip_prefix(' ').unwrap_or(line)
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        },
+    };
+
+    Some(BlockResult {
+        output,
+        executed_at: 0,
+    })
+}
+
+/// Edit replacing the results of the source block ending at `block_end`
+///
+/// `None` removes the results. Returns `None` if there is nothing to change.
+pub fn results_edit(
+    content: &str,
+    block_end: usize,
+    result: Option<&BlockResult>,
+    name: Option<&str>,
+) -> Option<TextEdit> {
+    match (results_span(content, block_end), result) {
+        (Some((start, end)), Some(result)) => Some(TextEdit::replace(
+            content,
+            start,
+            end,
+            format_block_result(result, name),
+        )),
+        (Some((_, end)), None) => Some(TextEdit::replace(content, block_end, end, "")),
+        (None, Some(result)) => Some(TextEdit::insert(
+            block_end,
+            format!("\n{}", format_block_result(result, name)),
+        )),
+        (None, None) => None,
+    }
+}
+
+// =============================================================================
+// Span-based Incremental Edits
+// =============================================================================
+//
+// Instead of re-rendering a whole file, operations compute a list of `TextEdit`s
+// against the content they were derived from. `write_edits` re-reads the file right
+// before writing and rebases the edits onto whatever is on disk, so concurrent
+// changes to unrelated parts of the file survive.
+
+/// Above this fraction of the file being touched, edits are applied as a full rewrite
+const REWRITE_THRESHOLD: f64 = 0.5;
+
+/// Above this number of edits, they are applied as a full rewrite
+const MAX_INCREMENTAL_EDITS: usize = 256;
+
+/// Replace the bytes `start..end` (which must currently read `expected`) with `replacement`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub expected: String,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    /// Create an edit replacing `content[start..end]`, capturing the current text
+    pub fn replace(
+        content: &str,
+        start: usize,
+        end: usize,
+        replacement: impl Into<String>,
+    ) -> Self {
+        Self {
+            start,
+            end,
+            expected: content[start..end].to_string(),
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Create an edit inserting `text` at `pos`
+    pub fn insert(pos: usize, text: impl Into<String>) -> Self {
+        Self {
+            start: pos,
+            end: pos,
+            expected: String::new(),
+            replacement: text.into(),
+        }
+    }
+
+    /// Number of bytes this edit touches
+    fn touched_len(&self) -> usize {
+        self.expected.len().max(self.replacement.len())
+    }
+}
+
+/// Byte ranges of a headline within a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadlineSpan {
+    /// Value of the :ID: property, if present
+    pub id: Option<String>,
+    /// Number of leading stars
+    pub level: usize,
+    /// Start of the headline line
+    pub byte_start: usize,
+    /// End of the headline line (excluding the newline)
+    pub line_end: usize,
+    /// Start of the next headline (of any level) or EOF
+    pub section_end: usize,
+    /// End of this headline's subtree (next headline with level <= this one, or EOF)
+    pub subtree_end: usize,
+}
+
+/// Headline level if `line` is a headline (`*`s followed by a space)
+fn headline_level(line: &str) -> Option<usize> {
+    let level = line.find(|c: char| c != '*').unwrap_or(line.len());
+    if level > 0 && line[level..].starts_with(' ') {
+        Some(level)
+    } else {
+        None
+    }
+}
+
+/// Find the :ID: property in a section (property drawer may follow planning lines)
+fn section_id(section: &str) -> Option<String> {
+    let mut in_drawer = false;
+    for line in section.lines().skip(1) {
+        let trimmed = line.trim();
+        if in_drawer {
+            if trimmed == ":END:" {
+                return None;
+            }
+            if let Some(value) = trimmed.strip_prefix(":ID:") {
+                return Some(value.trim().to_string());
+            }
+        } else if trimmed == ":PROPERTIES:" {
+            in_drawer = true;
+        } else if !(trimmed.starts_with("SCHEDULED:")
+            || trimmed.starts_with("DEADLINE:")
+            || trimmed.starts_with("CLOSED:"))
+        {
+            return None;
+        }
+    }
+    None
+}
+
+/// Compute the spans of all headlines in `content`, in document order
+pub fn headline_spans(content: &str) -> Vec<HeadlineSpan> {
+    let mut starts = Vec::new();
+    let mut pos = 0;
+    for line in content.split_inclusive('\n') {
+        let text = line.trim_end_matches(['\n', '\r']);
+        if let Some(level) = headline_level(text) {
+            starts.push((pos, pos + text.len(), level));
+        }
+        pos += line.len();
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &(byte_start, line_end, level))| {
+            let section_end = starts.get(i + 1).map(|s| s.0).unwrap_or(content.len());
+            let subtree_end = starts[i + 1..]
+                .iter()
+                .find(|s| s.2 <= level)
+                .map(|s| s.0)
+                .unwrap_or(content.len());
+            HeadlineSpan {
+                id: section_id(&content[byte_start..section_end]),
+                level,
+                byte_start,
+                line_end,
+                section_end,
+                subtree_end,
+            }
+        })
+        .collect()
+}
+
+/// Find a headline by ID, falling back to an ID-less headline starting at `byte_start_hint`.
+///
+/// Stored byte offsets go stale as soon as the file is edited elsewhere, so the ID wins.
+/// Without a hint, only the ID is matched.
+pub fn find_headline(
+    spans: &[HeadlineSpan],
+    id: &str,
+    byte_start_hint: Option<usize>,
+) -> Option<usize> {
+    spans
+        .iter()
+        .position(|s| s.id.as_deref() == Some(id))
+        .or_else(|| {
+            let hint = byte_start_hint?;
+            spans
+                .iter()
+                .position(|s| s.byte_start == hint && s.id.is_none())
+        })
+}
+
+/// Edit changing a headline's TODO keyword (touches only the headline line)
+pub fn todo_keyword_edit(
+    content: &str,
+    span: &HeadlineSpan,
+    keyword: Option<&str>,
+) -> Result<TextEdit> {
+    let line = &content[span.byte_start..span.line_end];
+    let new_line = update_todo_keyword(line, 0, keyword)?;
+    Ok(TextEdit::replace(
+        content,
+        span.byte_start,
+        span.line_end,
+        new_line,
+    ))
+}
+
+/// Edit changing a headline's priority (touches only the headline line)
+pub fn priority_edit(
+    content: &str,
+    span: &HeadlineSpan,
+    priority: Option<char>,
+) -> Result<TextEdit> {
+    let line = &content[span.byte_start..span.line_end];
+    let new_line = update_priority(line, 0, priority)?;
+    Ok(TextEdit::replace(
+        content,
+        span.byte_start,
+        span.line_end,
+        new_line,
+    ))
+}
+
+/// Edit changing a headline's title, keeping keyword, priority and tags
+pub fn title_edit(content: &str, span: &HeadlineSpan, title: &str) -> Result<TextEdit> {
+    let line = &content[span.byte_start..span.line_end];
+    let stars = &line[..span.level];
+    let (todo, rest) = extract_todo_keyword(&line[span.level..]);
+    let (priority, rest) = extract_priority(rest);
+    let rest = rest.trim_end();
+
+    // Trailing `:tag1:tag2:` token
+    let tags = rest
+        .rsplit_once(char::is_whitespace)
+        .map(|(_, last)| last)
+        .filter(|last| last.len() > 1 && last.starts_with(':') && last.ends_with(':'));
+
+    let mut new_line = stars.to_string();
+    if let Some(kw) = todo {
+        new_line.push(' ');
+        new_line.push_str(kw);
+    }
+    if let Some(p) = priority {
+        new_line.push_str(" [#");
+        new_line.push(p);
+        new_line.push(']');
+    }
+    new_line.push(' ');
+    new_line.push_str(title.trim());
+    if let Some(tags) = tags {
+        new_line.push(' ');
+        new_line.push_str(tags);
+    }
+
+    Ok(TextEdit::replace(
+        content,
+        span.byte_start,
+        span.line_end,
+        new_line,
+    ))
+}
+
+/// Byte range of the trailing `:tag1:tag2:` token of a headline line, if any
+fn tags_range(content: &str, span: &HeadlineSpan) -> Option<std::ops::Range<usize>> {
+    let line = content[span.byte_start..span.line_end].trim_end();
+    let (_, last) = line.rsplit_once(char::is_whitespace)?;
+    if last.len() > 1 && last.starts_with(':') && last.ends_with(':') {
+        let start = span.byte_start + line.len() - last.len();
+        Some(start..start + last.len())
+    } else {
+        None
+    }
+}
+
+/// Tags of a headline, from the trailing `:tag1:tag2:` token of its line
+pub fn headline_tags(content: &str, span: &HeadlineSpan) -> Vec<String> {
+    tags_range(content, span)
+        .map(|range| {
+            content[range]
+                .split(':')
+                .filter(|tag| !tag.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Edit setting a headline's tags (touches only the trailing tags token).
+///
+/// An empty list removes the token. Returns `None` if there is nothing to change.
+pub fn tags_edit(content: &str, span: &HeadlineSpan, tags: &[&str]) -> Result<Option<TextEdit>> {
+    if let Some(tag) = tags
+        .iter()
+        .find(|tag| tag.is_empty() || tag.contains(|c: char| c == ':' || c.is_whitespace()))
+    {
+        anyhow::bail!("Invalid tag '{}'", tag);
+    }
+    let token = format!(":{}:", tags.join(":"));
+
+    Ok(match (tags_range(content, span), tags.is_empty()) {
+        (Some(range), false) => Some(TextEdit::replace(content, range.start, range.end, token)),
+        (Some(range), true) => {
+            // Also drop the whitespace before the token
+            let start = span.byte_start + content[span.byte_start..range.start].trim_end().len();
+            Some(TextEdit::replace(content, start, span.line_end, ""))
+        }
+        (None, false) => {
+            let line_end =
+                span.byte_start + content[span.byte_start..span.line_end].trim_end().len();
+            Some(TextEdit::replace(
+                content,
+                line_end,
+                span.line_end,
+                format!(" {}", token),
+            ))
+        }
+        (None, true) => None,
+    })
+}
+
+/// Edit replacing a headline's section body (after property drawer and planning)
+pub fn content_edit(content: &str, span: &HeadlineSpan, body: &str) -> Result<TextEdit> {
+    let after_headline = (span.line_end + 1).min(span.section_end);
+    let body_start = find_section_body_start(content, after_headline).min(span.section_end);
+
+    let mut replacement = String::new();
+    if !body.trim().is_empty() {
+        replacement.push_str(body.trim());
+        replacement.push('\n');
+    }
+    Ok(TextEdit::replace(
+        content,
+        body_start,
+        span.section_end,
+        replacement,
+    ))
+}
+
+/// Byte range of the line right after the headline line (empty at the section end)
+fn planning_line(content: &str, span: &HeadlineSpan) -> (usize, usize) {
+    let start = (span.line_end + 1).min(span.section_end);
+    let end = content[start..span.section_end]
+        .find('\n')
+        .map(|i| start + i)
+        .unwrap_or(span.section_end);
+    (start, end)
+}
+
+fn is_planning_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("SCHEDULED:")
+        || trimmed.starts_with("DEADLINE:")
+        || trimmed.starts_with("CLOSED:")
+}
+
+/// Byte ranges of the `KEYWORD:` entry and of its timestamp (including a `--<...>` range end)
+fn planning_entry(
+    content: &str,
+    span: &HeadlineSpan,
+    keyword: &str,
+) -> Option<(usize, std::ops::Range<usize>)> {
+    let (line_start, line_end) = planning_line(content, span);
+    let line = &content[line_start..line_end];
+    if !is_planning_line(line) {
+        return None;
+    }
+    let marker = format!("{}:", keyword);
+    let entry_start = line_start + line.find(&marker)?;
+    let after_marker = entry_start + marker.len();
+    let ts_start = line_end - content[after_marker..line_end].trim_start().len();
+    let closing = |from: usize| {
+        content[from..line_end]
+            .find(['>', ']'])
+            .map(|i| from + i + 1)
+    };
+    let mut ts_end = closing(ts_start)?;
+    if content[ts_end..line_end].starts_with("--") {
+        ts_end = closing(ts_end).unwrap_or(ts_end);
+    }
+    Some((entry_start, ts_start..ts_end))
+}
+
+/// The timestamp text of a headline's `SCHEDULED`, `DEADLINE` or `CLOSED` entry
+pub fn planning_timestamp<'a>(
+    content: &'a str,
+    span: &HeadlineSpan,
+    keyword: &str,
+) -> Option<&'a str> {
+    planning_entry(content, span, keyword).map(|(_, ts)| &content[ts])
+}
+
+/// Edit setting (or with `None`, removing) one entry of a headline's planning line.
+///
+/// Only that entry's text changes, so the other entries keep their exact text. A
+/// missing planning line is inserted right after the headline line; a planning line
+/// left empty is removed. Returns `None` if there is nothing to change.
+pub fn planning_edit(
+    content: &str,
+    span: &HeadlineSpan,
+    keyword: &str,
+    timestamp: Option<&str>,
+) -> Option<TextEdit> {
+    let (line_start, line_end) = planning_line(content, span);
+    let has_planning = is_planning_line(&content[line_start..line_end]);
+
+    match (planning_entry(content, span, keyword), timestamp) {
+        (Some((_, ts)), Some(timestamp)) => {
+            Some(TextEdit::replace(content, ts.start, ts.end, timestamp))
+        }
+        (Some((entry_start, ts)), None) => {
+            let before = &content[line_start..entry_start];
+            let after = &content[ts.end..line_end];
+            if before.trim().is_empty() && after.trim().is_empty() {
+                // Remove the whole line together with the newline before it
+                return Some(TextEdit::replace(content, span.line_end, line_end, ""));
+            }
+            let (start, end) = if after.trim().is_empty() {
+                // Last entry: also drop the whitespace before it
+                (line_start + before.trim_end().len(), line_end)
+            } else {
+                (entry_start, line_end - after.trim_start().len())
+            };
+            Some(TextEdit::replace(content, start, end, ""))
+        }
+        (None, Some(timestamp)) if has_planning => Some(TextEdit::insert(
+            line_end,
+            format!(" {}: {}", keyword, timestamp),
+        )),
+        (None, Some(timestamp)) => Some(TextEdit::insert(
+            span.line_end,
+            format!("\n{}: {}", keyword, timestamp),
+        )),
+        (None, None) => None,
+    }
+}
+
+/// Indices of a headline and all its descendants
+fn subtree_indices(spans: &[HeadlineSpan], index: usize) -> std::ops::Range<usize> {
+    let end = spans[index + 1..]
+        .iter()
+        .position(|s| s.level <= spans[index].level)
+        .map(|p| index + 1 + p)
+        .unwrap_or(spans.len());
+    index..end
+}
+
+/// Edits shifting a headline and its subtree by `delta` levels (indent > 0, outdent < 0).
+///
+/// Only the leading stars of each affected headline line are touched.
+pub fn shift_level_edits(
+    content: &str,
+    spans: &[HeadlineSpan],
+    index: usize,
+    delta: i64,
+) -> Result<Vec<TextEdit>> {
+    let subtree = subtree_indices(spans, index);
+    if spans[index].level as i64 + delta < 1 {
+        anyhow::bail!("Cannot outdent a top-level headline");
+    }
+
+    Ok(spans[subtree]
+        .iter()
+        .map(|s| {
+            let new_level = (s.level as i64 + delta) as usize;
+            TextEdit::replace(
+                content,
+                s.byte_start,
+                s.byte_start + s.level,
+                "*".repeat(new_level),
+            )
+        })
+        .collect())
+}
+
+/// Edits moving a headline's subtree to directly after the subtree of `after_index`,
+/// re-leveling it to become a sibling of that headline.
+pub fn move_subtree_edits(
+    content: &str,
+    spans: &[HeadlineSpan],
+    index: usize,
+    after_index: usize,
+) -> Result<Vec<TextEdit>> {
+    let target = &spans[after_index];
+    if subtree_indices(spans, index).contains(&after_index) {
+        anyhow::bail!("Cannot move a headline into its own subtree");
+    }
+    Ok(relocate_subtree_edits(
+        content,
+        spans,
+        index,
+        target.subtree_end,
+        target.level,
+    ))
+}
+
+/// Edits moving a headline's subtree to become the first child of `parent_index`,
+/// or with `None`, the first headline of the file.
+pub fn move_subtree_under_edits(
+    content: &str,
+    spans: &[HeadlineSpan],
+    index: usize,
+    parent_index: Option<usize>,
+) -> Result<Vec<TextEdit>> {
+    let (insert_pos, level) = match parent_index {
+        Some(parent_index) => {
+            if subtree_indices(spans, index).contains(&parent_index) {
+                anyhow::bail!("Cannot move a headline into its own subtree");
+            }
+            let parent = &spans[parent_index];
+            (parent.section_end, parent.level + 1)
+        }
+        None => (spans[0].byte_start, 1),
+    };
+    Ok(relocate_subtree_edits(
+        content, spans, index, insert_pos, level,
+    ))
+}
+
+/// Where a headline sits among its siblings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubtreePosition {
+    /// Directly after the subtree of its previous sibling
+    After(usize),
+    /// First child of its parent, or without a parent, first headline of the file
+    FirstChildOf(Option<usize>),
+}
+
+/// Position of a headline, to move it back there with `move_subtree_edits` or
+/// `move_subtree_under_edits`
+pub fn subtree_position(spans: &[HeadlineSpan], index: usize) -> SubtreePosition {
+    let level = spans[index].level;
+    match spans[..index].iter().rposition(|s| s.level <= level) {
+        Some(i) if spans[i].level == level => SubtreePosition::After(i),
+        parent => SubtreePosition::FirstChildOf(parent),
+    }
+}
+
+/// Start of the moved subtree once the edits of `move_subtree_edits` or
+/// `move_subtree_under_edits` are applied
+pub fn moved_subtree_start(edits: &[TextEdit]) -> Option<usize> {
+    let [insert, remove] = edits else {
+        return None;
+    };
+    let newline = insert.replacement.len() - insert.replacement.trim_start_matches('\n').len();
+    let start = insert.start + newline;
+    Some(if remove.end <= insert.start {
+        start - (remove.end - remove.start)
+    } else {
+        start
+    })
+}
+
+/// Edits moving a headline's subtree to `insert_pos` (outside of it), re-leveled so
+/// the headline gets `level`
+fn relocate_subtree_edits(
+    content: &str,
+    spans: &[HeadlineSpan],
+    index: usize,
+    insert_pos: usize,
+    level: usize,
+) -> Vec<TextEdit> {
+    let source = &spans[index];
+
+    // Re-level every headline line in the moved text
+    let delta = level as i64 - source.level as i64;
+    let mut moved = String::with_capacity(source.subtree_end - source.byte_start);
+    for s in &spans[subtree_indices(spans, index)] {
+        let new_level = (s.level as i64 + delta).max(1) as usize;
+        let section_end = s.section_end.min(source.subtree_end);
+        moved.push_str(&"*".repeat(new_level));
+        moved.push_str(&content[s.byte_start + s.level..section_end]);
+    }
+    if !moved.ends_with('\n') {
+        moved.push('\n');
+    }
+
+    if insert_pos == content.len() && !content.is_empty() && !content.ends_with('\n') {
+        moved.insert(0, '\n');
+    }
+
+    vec![
+        TextEdit::insert(insert_pos, moved),
+        TextEdit::replace(content, source.byte_start, source.subtree_end, ""),
+    ]
+}
+
+/// Apply edits to `content`, verifying each edit still matches its expected text
+pub fn apply_edits(content: &str, edits: &[TextEdit]) -> Result<String> {
+    let mut sorted: Vec<&TextEdit> = edits.iter().collect();
+    sorted.sort_by_key(|e| (e.start, e.end));
+
+    for pair in sorted.windows(2) {
+        if pair[0].end > pair[1].start {
+            anyhow::bail!(
+                "Overlapping edits at {}..{} and {}..{}",
+                pair[0].start,
+                pair[0].end,
+                pair[1].start,
+                pair[1].end
+            );
+        }
+    }
+
+    let mut result = content.to_string();
+    for edit in sorted.into_iter().rev() {
+        if result.get(edit.start..edit.end) != Some(edit.expected.as_str()) {
+            anyhow::bail!(
+                "Stale edit at {}..{}: file content changed",
+                edit.start,
+                edit.end
+            );
+        }
+        result.replace_range(edit.start..edit.end, &edit.replacement);
+    }
+    Ok(result)
+}
+
+/// How a set of edits will be written back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WritePlan {
+    /// Apply edits in place on the current file content
+    Incremental(Vec<TextEdit>),
+    /// Replace the whole file (structure changed too much for targeted edits)
+    Rewrite(String),
+}
+
+/// Decide between incremental edits and a full rewrite
+pub fn plan_write(content: &str, edits: Vec<TextEdit>) -> Result<WritePlan> {
+    let touched: usize = edits.iter().map(TextEdit::touched_len).sum();
+    let drastic = edits.len() > MAX_INCREMENTAL_EDITS
+        || (!content.is_empty() && touched as f64 > content.len() as f64 * REWRITE_THRESHOLD);
+
+    if drastic {
+        Ok(WritePlan::Rewrite(apply_edits(content, &edits)?))
+    } else {
+        Ok(WritePlan::Incremental(edits))
+    }
+}
+
+/// Rebase edits computed against `base` onto `current`.
+///
+/// The region that differs between the two is located via common prefix/suffix.
+/// Edits before it are kept, edits after it are shifted, and edits overlapping it
+/// are reported as a conflict.
+pub fn rebase_edits(base: &str, current: &str, edits: Vec<TextEdit>) -> Result<Vec<TextEdit>> {
+    if base == current {
+        return Ok(edits);
+    }
+
+    let mut prefix = base
+        .bytes()
+        .zip(current.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !base.is_char_boundary(prefix) || !current.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+
+    let max_suffix = base.len().min(current.len()) - prefix;
+    let mut suffix = base
+        .bytes()
+        .rev()
+        .zip(current.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !base.is_char_boundary(base.len() - suffix)
+        || !current.is_char_boundary(current.len() - suffix)
+    {
+        suffix -= 1;
+    }
+
+    let changed_end = base.len() - suffix;
+    let delta = current.len() as i64 - base.len() as i64;
+
+    edits
+        .into_iter()
+        .map(|edit| {
+            if edit.end <= prefix {
+                Ok(edit)
+            } else if edit.start >= changed_end {
+                Ok(TextEdit {
+                    start: (edit.start as i64 + delta) as usize,
+                    end: (edit.end as i64 + delta) as usize,
+                    ..edit
+                })
+            } else {
+                anyhow::bail!(
+                    "Edit at {}..{} conflicts with a concurrent change to the file",
+                    edit.start,
+                    edit.end
+                )
+            }
+        })
+        .collect()
+}
+
+/// Write edits computed against `base` to `path`.
+///
+/// The file is re-read right before writing and the edits are rebased onto it, so
+/// concurrent changes elsewhere in the file are preserved. A full rewrite is only
+/// performed if the file is unchanged since `base` was read.
+pub fn write_edits(path: &Path, base: &str, edits: Vec<TextEdit>) -> Result<()> {
+    if edits.is_empty() {
+        return Ok(());
+    }
+
+    let plan = plan_write(base, edits)?;
+    let current = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+
+    let new_content = match plan {
+        WritePlan::Incremental(edits) => {
+            let edits = rebase_edits(base, &current, edits)?;
+            apply_edits(&current, &edits)?
+        }
+        WritePlan::Rewrite(new_content) => {
+            if current != base {
+                anyhow::bail!(
+                    "File {} changed on disk; refusing to rewrite it",
+                    path.display()
+                );
+            }
+            new_content
+        }
+    };
+
+    fs::write(path, new_content)
+        .with_context(|| format!("Failed to write file: {}", path.display()))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -856,6 +1702,46 @@ mod tests {
         assert!(output.contains("#+end_error"));
     }
 
+    #[test]
+    fn test_block_results_edits_round_trip() {
+        let content = "* Run\n#+BEGIN_SRC sh\necho hi\n#+END_SRC\nAfter\n* Next\n";
+        let spans = headline_spans(content);
+        let (_, block_end) = source_block_ranges(content, &spans[0])[0];
+        assert_eq!(results_span(content, block_end), None);
+
+        // Inserted after the block, then replaced in place
+        let edit = results_edit(content, block_end, Some(&BlockResult::text("hi")), None);
+        let content = apply_edits(content, &[edit.unwrap()]).unwrap();
+        assert_eq!(
+            content,
+            "* Run\n#+BEGIN_SRC sh\necho hi\n#+END_SRC\n#+RESULTS:\n: hi\nAfter\n* Next\n"
+        );
+        let table = BlockResult::table(
+            vec!["a".to_string()],
+            vec![vec![Value::String("1".to_string())]],
+        );
+        let edit = results_edit(&content, block_end, Some(&table), None);
+        let content = apply_edits(&content, &[edit.unwrap()]).unwrap();
+        let (start, end) = results_span(&content, block_end).unwrap();
+        assert_eq!(
+            parse_block_result(&content[start..end]).unwrap().output,
+            table.output
+        );
+
+        let error = BlockResult::error("exit status 1\nboom");
+        let text = format_block_result(&error, Some("run"));
+        assert_eq!(parse_block_result(&text).unwrap().output, error.output);
+
+        // Removing the results restores the original text
+        let edit = results_edit(&content, block_end, None, None);
+        let content = apply_edits(&content, &[edit.unwrap()]).unwrap();
+        assert_eq!(
+            content,
+            "* Run\n#+BEGIN_SRC sh\necho hi\n#+END_SRC\nAfter\n* Next\n"
+        );
+        assert_eq!(results_edit(&content, block_end, None, None), None);
+    }
+
     #[test]
     fn test_insert_source_block() {
         let content = "* Headline\nSome text\n";
@@ -935,4 +1821,198 @@ mod tests {
         assert_eq!(value_to_header_arg_string(&Value::Boolean(false)), "no");
         assert_eq!(value_to_header_arg_string(&Value::Null), "");
     }
+
+    const SPAN_DOC: &str = "* TODO Parent :work:\n:PROPERTIES:\n:ID: p\n:END:\nBody\n** Child\n:PROPERTIES:\n:ID: c\n:END:\n* Sibling\n:PROPERTIES:\n:ID: s\n:END:\n";
+
+    #[test]
+    fn test_headline_spans() {
+        let spans = headline_spans(SPAN_DOC);
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].id.as_deref(), Some("p"));
+        assert_eq!(spans[1].level, 2);
+        assert_eq!(spans[0].section_end, spans[1].byte_start);
+        assert_eq!(spans[0].subtree_end, spans[2].byte_start);
+        assert_eq!(spans[2].subtree_end, SPAN_DOC.len());
+
+        // ID wins over a stale byte offset
+        assert_eq!(find_headline(&spans, "s", Some(0)), Some(2));
+        assert_eq!(find_headline(&spans, "missing", Some(0)), None);
+    }
+
+    #[test]
+    fn test_field_edits_touch_only_headline_line() {
+        let spans = headline_spans(SPAN_DOC);
+
+        let edit = todo_keyword_edit(SPAN_DOC, &spans[0], Some("DONE")).unwrap();
+        assert_eq!(edit.end, spans[0].line_end);
+        let result = apply_edits(SPAN_DOC, &[edit]).unwrap();
+        assert!(result.starts_with("* DONE Parent :work:\n"));
+
+        let edit = title_edit(SPAN_DOC, &spans[0], "Renamed").unwrap();
+        let result = apply_edits(SPAN_DOC, &[edit]).unwrap();
+        assert!(result.starts_with("* TODO Renamed :work:\n"));
+
+        let edit = content_edit(SPAN_DOC, &spans[0], "New body").unwrap();
+        let result = apply_edits(SPAN_DOC, &[edit]).unwrap();
+        assert!(result.contains(":END:\nNew body\n** Child"));
+    }
+
+    #[test]
+    fn test_planning_edits() {
+        let doc =
+            "* TODO Task\nSCHEDULED: <2024-01-15 Mon +1w>  DEADLINE: <2024-01-19 Fri -2d>\nBody\n";
+        let span = &headline_spans(doc)[0];
+        assert_eq!(
+            planning_timestamp(doc, span, "DEADLINE"),
+            Some("<2024-01-19 Fri -2d>")
+        );
+
+        let edit = planning_edit(doc, span, "SCHEDULED", Some("<2024-01-22 Mon +1w>")).unwrap();
+        let result = apply_edits(doc, &[edit]).unwrap();
+        assert!(
+            result.contains("SCHEDULED: <2024-01-22 Mon +1w>  DEADLINE: <2024-01-19 Fri -2d>\n")
+        );
+
+        let edit = planning_edit(doc, span, "DEADLINE", None).unwrap();
+        let result = apply_edits(doc, &[edit]).unwrap();
+        assert!(result.contains("SCHEDULED: <2024-01-15 Mon +1w>\nBody"));
+
+        let edit = planning_edit(doc, span, "SCHEDULED", None).unwrap();
+        let result = apply_edits(doc, &[edit]).unwrap();
+        assert!(result.contains("\nDEADLINE: <2024-01-19 Fri -2d>\nBody"));
+
+        let plain = "* Task\nBody\n";
+        let span = &headline_spans(plain)[0];
+        assert!(planning_edit(plain, span, "DEADLINE", None).is_none());
+        let edit = planning_edit(plain, span, "DEADLINE", Some("<2024-01-19 Fri>")).unwrap();
+        let result = apply_edits(plain, &[edit]).unwrap();
+        assert_eq!(result, "* Task\nDEADLINE: <2024-01-19 Fri>\nBody\n");
+        let span = &headline_spans(&result)[0];
+        let edit = planning_edit(&result, span, "DEADLINE", None).unwrap();
+        assert_eq!(apply_edits(&result, &[edit]).unwrap(), plain);
+    }
+
+    #[test]
+    fn test_shift_level_edits() {
+        let spans = headline_spans(SPAN_DOC);
+        let edits = shift_level_edits(SPAN_DOC, &spans, 0, 1).unwrap();
+        assert_eq!(edits.len(), 2);
+        let result = apply_edits(SPAN_DOC, &edits).unwrap();
+        assert!(result.starts_with("** TODO Parent"));
+        assert!(result.contains("*** Child"));
+        assert!(result.contains("\n* Sibling"));
+
+        assert!(shift_level_edits(SPAN_DOC, &spans, 0, -1).is_err());
+    }
+
+    #[test]
+    fn test_move_subtree_edits() {
+        let spans = headline_spans(SPAN_DOC);
+        let edits = move_subtree_edits(SPAN_DOC, &spans, 0, 2).unwrap();
+        let result = apply_edits(SPAN_DOC, &edits).unwrap();
+        assert!(result.starts_with("* Sibling"));
+        assert!(result.contains(":ID: s\n:END:\n* TODO Parent :work:"));
+        assert!(result.ends_with(":ID: c\n:END:\n"));
+
+        // Moving into own subtree is rejected
+        assert!(move_subtree_edits(SPAN_DOC, &spans, 0, 1).is_err());
+    }
+
+    #[test]
+    fn test_move_subtree_back_to_position() {
+        let spans = headline_spans(SPAN_DOC);
+        assert_eq!(
+            subtree_position(&spans, 0),
+            SubtreePosition::FirstChildOf(None)
+        );
+        assert_eq!(
+            subtree_position(&spans, 1),
+            SubtreePosition::FirstChildOf(Some(0))
+        );
+        assert_eq!(subtree_position(&spans, 2), SubtreePosition::After(0));
+
+        // Move the child to the end of the file, then back under its parent
+        let edits = move_subtree_edits(SPAN_DOC, &spans, 1, 2).unwrap();
+        let moved = apply_edits(SPAN_DOC, &edits).unwrap();
+        let start = moved_subtree_start(&edits).unwrap();
+        assert!(moved[start..].starts_with("* Child\n:PROPERTIES:\n:ID: c"));
+
+        let spans = headline_spans(&moved);
+        let index = find_headline(&spans, "c", Some(start)).unwrap();
+        let edits = move_subtree_under_edits(&moved, &spans, index, Some(0)).unwrap();
+        let restored = apply_edits(&moved, &edits).unwrap();
+        assert_eq!(restored, SPAN_DOC);
+        assert_eq!(
+            moved_subtree_start(&edits),
+            Some(headline_spans(SPAN_DOC)[1].byte_start)
+        );
+
+        // Move the sibling to the top of the file
+        let spans = headline_spans(SPAN_DOC);
+        let edits = move_subtree_under_edits(SPAN_DOC, &spans, 2, None).unwrap();
+        let result = apply_edits(SPAN_DOC, &edits).unwrap();
+        assert!(result.starts_with("* Sibling\n"));
+        assert_eq!(moved_subtree_start(&edits), Some(0));
+
+        // Moving into own subtree is rejected
+        assert!(move_subtree_under_edits(SPAN_DOC, &spans, 0, Some(1)).is_err());
+    }
+
+    #[test]
+    fn test_tags_edit() {
+        let spans = headline_spans(SPAN_DOC);
+        assert_eq!(headline_tags(SPAN_DOC, &spans[0]), vec!["work".to_string()]);
+        assert!(headline_tags(SPAN_DOC, &spans[1]).is_empty());
+
+        let edit = tags_edit(SPAN_DOC, &spans[0], &["work", "urgent"])
+            .unwrap()
+            .unwrap();
+        let result = apply_edits(SPAN_DOC, &[edit]).unwrap();
+        assert!(result.starts_with("* TODO Parent :work:urgent:\n"));
+
+        let edit = tags_edit(SPAN_DOC, &spans[0], &[]).unwrap().unwrap();
+        let result = apply_edits(SPAN_DOC, &[edit]).unwrap();
+        assert!(result.starts_with("* TODO Parent\n"));
+
+        let edit = tags_edit(SPAN_DOC, &spans[1], &["home"]).unwrap().unwrap();
+        let result = apply_edits(SPAN_DOC, &[edit]).unwrap();
+        assert!(result.contains("\n** Child :home:\n"));
+
+        assert!(tags_edit(SPAN_DOC, &spans[1], &[]).unwrap().is_none());
+        assert!(tags_edit(SPAN_DOC, &spans[1], &["two words"]).is_err());
+    }
+
+    #[test]
+    fn test_rebase_preserves_concurrent_changes() {
+        let spans = headline_spans(SPAN_DOC);
+        let edit = todo_keyword_edit(SPAN_DOC, &spans[2], Some("TODO")).unwrap();
+
+        // Someone edited the parent body in the meantime
+        let current = SPAN_DOC.replace("Body\n", "Body edited elsewhere\n");
+        let rebased = rebase_edits(SPAN_DOC, &current, vec![edit.clone()]).unwrap();
+        let result = apply_edits(&current, &rebased).unwrap();
+        assert!(result.contains("Body edited elsewhere"));
+        assert!(result.contains("* TODO Sibling"));
+
+        // A concurrent change to the same line conflicts
+        let current = SPAN_DOC.replace("* Sibling", "* Sibling renamed");
+        assert!(rebase_edits(SPAN_DOC, &current, vec![edit]).is_err());
+    }
+
+    #[test]
+    fn test_plan_write_falls_back_to_rewrite() {
+        let content = "* A\n";
+        let edit = TextEdit::replace(content, 0, content.len(), "* Completely different\n");
+        assert!(matches!(
+            plan_write(content, vec![edit]).unwrap(),
+            WritePlan::Rewrite(_)
+        ));
+
+        let spans = headline_spans(SPAN_DOC);
+        let edit = todo_keyword_edit(SPAN_DOC, &spans[1], Some("TODO")).unwrap();
+        assert!(matches!(
+            plan_write(SPAN_DOC, vec![edit]).unwrap(),
+            WritePlan::Incremental(_)
+        ));
+    }
 }
@@ -182,3 +182,33 @@ from projects
     assert!(source_blocks[0].source.contains("from tasks"));
     assert!(source_blocks[1].source.contains("from projects"));
 }
+
+#[test]
+fn test_source_block_results_roundtrip() {
+    let original = r#"* Script
+#+NAME: greet
+#+BEGIN_SRC sh
+echo hello
+#+END_SRC
+
+#+RESULTS: greet
+: hello
+: world
+
+After the results.
+"#;
+
+    let path = PathBuf::from("/test/file.org");
+    let result = parse_org_file(&path, original, ROOT_ID, 0).unwrap();
+
+    let headline = &result.headlines[0];
+    let block = &headline.get_source_blocks()[0];
+    let results = block.results.clone().expect("results should be parsed");
+    assert_eq!(results.output, BlockResult::text("hello\nworld").output);
+    let content = headline.content.as_deref().unwrap_or_default();
+    assert!(content.contains("After the results."));
+    assert!(!content.contains("RESULTS") && !content.contains(": hello"));
+
+    let formatted = format_api_source_block(&block.to_api_source_block());
+    assert!(formatted.ends_with("#+END_SRC\n#+RESULTS: greet\n: hello\n: world"));
+}
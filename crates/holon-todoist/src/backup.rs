@@ -0,0 +1,628 @@
+//! Offline import of Todoist backups
+//!
+//! Reads Todoist's backup files without calling the API and writes their projects,
+//! sections and tasks straight into the local `todoist_*` tables, so large accounts
+//! can be used offline right away:
+//!
+//! - CSV backups: one `<Project name> [<project id>].csv` per project (the files of
+//!   the backup zip, once extracted). `section` rows start a section, `INDENT` gives
+//!   the task hierarchy and `@labels` are moved from the content into `labels`. CSV
+//!   rows carry no ids, so tasks and sections get ids from their project and row.
+//! - JSON backups in the shape of a Sync API full sync (`projects`, `sections`,
+//!   `items`), optionally with the `completed` history as returned by
+//!   `completed/get_all`.
+//!
+//! Everything is upserted by id: importing the same backup again changes nothing, and
+//! a later API sync updates the imported rows in place.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use holon::core::datasource::Result;
+use holon::storage::turso::TursoBackend;
+use holon_api::{HasSchema, Value};
+
+use crate::converters::str_to_date;
+use crate::models::{
+    TodoistProject, TodoistProjectApiResponse, TodoistSection, TodoistSectionApiResponse,
+    TodoistTask, TodoistTaskApiResponse,
+};
+
+/// Projects, sections and tasks read from a backup
+#[derive(Debug, Clone, Default)]
+pub struct TodoistBackup {
+    pub projects: Vec<TodoistProject>,
+    pub sections: Vec<TodoistSection>,
+    pub tasks: Vec<TodoistTask>,
+}
+
+/// Number of entities written by an import
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackupImportSummary {
+    pub projects: usize,
+    pub sections: usize,
+    pub tasks: usize,
+    /// Completed tasks among `tasks`
+    pub completed: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonBackup {
+    #[serde(default)]
+    projects: Vec<TodoistProjectApiResponse>,
+    #[serde(default)]
+    sections: Vec<TodoistSectionApiResponse>,
+    #[serde(default)]
+    items: Vec<TodoistTaskApiResponse>,
+    #[serde(default)]
+    completed: CompletedHistory,
+}
+
+/// `completed` either as a list or as the `completed/get_all` response
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum CompletedHistory {
+    List(Vec<CompletedItem>),
+    Response { items: Vec<CompletedItem> },
+}
+
+impl Default for CompletedHistory {
+    fn default() -> Self {
+        CompletedHistory::List(Vec::new())
+    }
+}
+
+impl CompletedHistory {
+    fn into_items(self) -> Vec<CompletedItem> {
+        match self {
+            CompletedHistory::List(items) | CompletedHistory::Response { items } => items,
+        }
+    }
+}
+
+/// An entry of the completed history
+#[derive(Debug, Deserialize)]
+struct CompletedItem {
+    task_id: String,
+    content: String,
+    project_id: String,
+    #[serde(default)]
+    section_id: Option<String>,
+    #[serde(default)]
+    completed_at: Option<String>,
+    /// Full task, if requested with `annotate_items`
+    #[serde(default)]
+    item_object: Option<TodoistTaskApiResponse>,
+}
+
+impl CompletedItem {
+    fn into_task(self) -> TodoistTask {
+        let mut task = match self.item_object {
+            Some(item) => TodoistTask::from(item),
+            None => {
+                let mut task = TodoistTask::new(self.task_id, self.content, self.project_id);
+                task.section_id = self.section_id;
+                task
+            }
+        };
+        task.completed = true;
+        task.completed_at = task.completed_at.or(self.completed_at);
+        task
+    }
+}
+
+impl TodoistBackup {
+    /// Read a JSON backup
+    ///
+    /// Completed tasks that are also listed as active items (recurring tasks) keep
+    /// their active state.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let backup: JsonBackup = serde_json::from_str(json)
+            .map_err(|e| format!("Invalid Todoist JSON backup: {}", e))?;
+
+        let mut tasks: Vec<TodoistTask> = backup
+            .items
+            .into_iter()
+            .filter(|item| !item.is_deleted.unwrap_or(false))
+            .map(TodoistTask::from)
+            .collect();
+        let mut known: HashSet<String> = tasks.iter().map(|t| t.id.clone()).collect();
+        for item in backup.completed.into_items() {
+            let task = item.into_task();
+            if known.insert(task.id.clone()) {
+                tasks.push(task);
+            }
+        }
+
+        Ok(Self {
+            projects: backup
+                .projects
+                .into_iter()
+                .filter(|p| !p.is_deleted.unwrap_or(false))
+                .map(TodoistProject::from)
+                .collect(),
+            sections: backup
+                .sections
+                .into_iter()
+                .filter(|s| !s.is_deleted.unwrap_or(false))
+                .map(TodoistSection::from)
+                .collect(),
+            tasks,
+        })
+    }
+
+    /// Read the CSV backup of one project
+    ///
+    /// `file_name` is the name of the CSV file in the backup, which carries the
+    /// project's name and id.
+    pub fn from_csv(file_name: &str, csv: &str) -> Result<Self> {
+        let (project_name, project_id) = project_from_file_name(file_name);
+        let rows = parse_csv(csv)?;
+        let Some((header, rows)) = rows.split_first() else {
+            return Err(format!("Empty Todoist CSV backup: {}", file_name).into());
+        };
+        let columns: HashMap<String, usize> = header
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.trim().to_uppercase(), i))
+            .collect();
+        if !columns.contains_key("TYPE") || !columns.contains_key("CONTENT") {
+            return Err(format!("Not a Todoist CSV backup: {}", file_name).into());
+        }
+
+        let mut project = TodoistProject {
+            id: project_id.clone(),
+            name: project_name,
+            color: None,
+            parent_id: None,
+            sort_order: None,
+            is_archived: None,
+            is_favorite: None,
+            view_style: None,
+            shared: None,
+            sync_id: None,
+            created_at: None,
+            updated_at: None,
+            inbox_project: None,
+        };
+        let mut backup = Self::default();
+        let mut section_id: Option<String> = None;
+        // IDs of the tasks that are parents of the next task, by indent level
+        let mut parents: Vec<String> = Vec::new();
+
+        for (index, row) in rows.iter().enumerate() {
+            let cell = |column: &str| {
+                columns
+                    .get(column)
+                    .and_then(|&i| row.get(i))
+                    .map(|s| s.trim())
+                    .unwrap_or_default()
+            };
+            // Record number in the file (the header is record 1)
+            let row_number = index + 2;
+
+            match cell("TYPE").to_lowercase().as_str() {
+                "section" if !cell("CONTENT").is_empty() => {
+                    let section = TodoistSection {
+                        id: format!("csv-{}-s{}", project_id, row_number),
+                        name: cell("CONTENT").to_string(),
+                        project_id: project_id.clone(),
+                        sort_order: Some(backup.sections.len() as i32 + 1),
+                        is_archived: None,
+                        created_at: None,
+                    };
+                    section_id = Some(section.id.clone());
+                    parents.clear();
+                    backup.sections.push(section);
+                }
+                "task" => {
+                    let (content, labels) = split_labels(cell("CONTENT"));
+                    if content.is_empty() {
+                        continue;
+                    }
+                    let indent = cell("INDENT").parse::<usize>().unwrap_or(1).max(1);
+                    parents.truncate(indent - 1);
+
+                    let mut task = TodoistTask::new(
+                        format!("csv-{}-{}", project_id, row_number),
+                        content,
+                        project_id.clone(),
+                    );
+                    task.description = Some(cell("DESCRIPTION"))
+                        .filter(|d| !d.is_empty())
+                        .map(str::to_string);
+                    task.section_id = section_id.clone();
+                    task.parent_id = parents.last().cloned();
+                    task.priority = csv_priority(cell("PRIORITY"));
+                    task.due_date = Some(cell("DATE"))
+                        .filter(|d| str_to_date(d).is_some())
+                        .map(str::to_string);
+                    task.labels = (!labels.is_empty()).then(|| labels.join(","));
+
+                    parents.push(task.id.clone());
+                    backup.tasks.push(task);
+                }
+                "meta" => {
+                    if let Some(view_style) = cell("CONTENT").strip_prefix("view_style=") {
+                        project.view_style = Some(view_style.to_string());
+                    }
+                }
+                // Comments (`note`) and empty rows
+                _ => {}
+            }
+        }
+
+        backup.projects.push(project);
+        Ok(backup)
+    }
+
+    /// Read a `.json` backup, a project `.csv` or a directory of project CSVs
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_path(path: &std::path::Path) -> Result<Self> {
+        let read = |path: &std::path::Path| {
+            std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+        };
+        let extension = |path: &std::path::Path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .map(str::to_lowercase)
+        };
+
+        if path.is_dir() {
+            let mut files: Vec<_> = std::fs::read_dir(path)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| extension(p).as_deref() == Some("csv"))
+                .collect();
+            files.sort();
+
+            let mut backup = Self::default();
+            for file in files {
+                let file_name = file
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or_default();
+                backup.merge(Self::from_csv(file_name, &read(&file)?)?);
+            }
+            return Ok(backup);
+        }
+
+        match extension(path).as_deref() {
+            Some("json") => Self::from_json(&read(path)?),
+            Some("csv") => {
+                let file_name = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or_default();
+                Self::from_csv(file_name, &read(path)?)
+            }
+            _ => Err(format!(
+                "Unknown Todoist backup format (expected .json, .csv or a directory): {}",
+                path.display()
+            )
+            .into()),
+        }
+    }
+
+    /// Add the entities of `other`
+    pub fn merge(&mut self, other: Self) {
+        self.projects.extend(other.projects);
+        self.sections.extend(other.sections);
+        self.tasks.extend(other.tasks);
+    }
+}
+
+/// Writes backups into the local Todoist tables
+pub struct TodoistBackupImporter {
+    backend: Arc<RwLock<TursoBackend>>,
+}
+
+impl TodoistBackupImporter {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self { backend }
+    }
+
+    /// Create the Todoist tables if they don't exist yet
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let backend = self.backend.read().await;
+        for schema in [
+            TodoistProject::schema(),
+            TodoistSection::schema(),
+            TodoistTask::schema(),
+        ] {
+            backend
+                .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create {} table: {}", schema.table_name, e))?;
+            for index_sql in schema.to_index_sql() {
+                backend
+                    .execute_sql(&index_sql, HashMap::new())
+                    .await
+                    .map_err(|e| format!("Failed to create index: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Upsert all entities of `backup`
+    pub async fn import(&self, backup: &TodoistBackup) -> Result<BackupImportSummary> {
+        self.initialize_schema().await?;
+
+        let backend = self.backend.read().await;
+        for project in &backup.projects {
+            upsert(&backend, project).await?;
+        }
+        for section in &backup.sections {
+            upsert(&backend, section).await?;
+        }
+        for task in &backup.tasks {
+            upsert(&backend, task).await?;
+        }
+
+        let summary = BackupImportSummary {
+            projects: backup.projects.len(),
+            sections: backup.sections.len(),
+            tasks: backup.tasks.len(),
+            completed: backup.tasks.iter().filter(|t| t.completed).count(),
+        };
+        info!("[TodoistBackupImporter] Imported backup: {:?}", summary);
+        Ok(summary)
+    }
+
+    /// Read the backup at `path` (see `TodoistBackup::from_path`) and import it
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn import_path(&self, path: &std::path::Path) -> Result<BackupImportSummary> {
+        let backup = TodoistBackup::from_path(path)?;
+        self.import(&backup).await
+    }
+}
+
+/// Insert `item`, or update all of its columns if a row with its id exists
+async fn upsert<T: HasSchema>(backend: &TursoBackend, item: &T) -> Result<()> {
+    let schema = T::schema();
+    let mut entity = item.to_entity();
+    let columns: Vec<String> = schema.stored_fields().map(|f| f.name.clone()).collect();
+    let id_column = schema
+        .fields
+        .iter()
+        .find(|f| f.primary_key)
+        .map(|f| f.name.clone())
+        .unwrap_or_else(|| "id".to_string());
+
+    let params: HashMap<String, Value> = columns
+        .iter()
+        .map(|c| (c.clone(), entity.remove(c).unwrap_or(Value::Null)))
+        .collect();
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT({}) DO UPDATE SET {}",
+        schema.table_name,
+        columns.join(", "),
+        columns
+            .iter()
+            .map(|c| format!("${}", c))
+            .collect::<Vec<_>>()
+            .join(", "),
+        id_column,
+        columns
+            .iter()
+            .filter(|c| **c != id_column)
+            .map(|c| format!("{} = excluded.{}", c, c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    backend
+        .execute_sql(&sql, params)
+        .await
+        .map_err(|e| format!("Failed to import into {}: {}", schema.table_name, e))?;
+    Ok(())
+}
+
+/// Project name and id from a backup file name like `Work [2203306141].csv`
+///
+/// Files without an id get one derived from the name.
+fn project_from_file_name(file_name: &str) -> (String, String) {
+    let stem = file_name
+        .strip_suffix(".csv")
+        .or_else(|| file_name.strip_suffix(".CSV"))
+        .unwrap_or(file_name);
+    let named_id = stem
+        .rsplit_once(" [")
+        .and_then(|(name, rest)| rest.strip_suffix(']').map(|id| (name, id)));
+    if let Some((name, id)) = named_id {
+        return (name.trim().to_string(), id.to_string());
+    }
+    let name = stem.trim().to_string();
+    let id = format!(
+        "csv-{}",
+        name.to_lowercase().replace(char::is_whitespace, "-")
+    );
+    (name, id)
+}
+
+/// CSV `PRIORITY` (1 = highest, as in the app) to the API's priority (4 = highest)
+fn csv_priority(priority: &str) -> i32 {
+    match priority.parse::<i32>() {
+        Ok(p @ 1..=4) => 5 - p,
+        _ => 1,
+    }
+}
+
+/// Split `@label`s off task content
+fn split_labels(content: &str) -> (String, Vec<String>) {
+    let mut labels = Vec::new();
+    let mut words = Vec::new();
+    for word in content.split_whitespace() {
+        match word.strip_prefix('@') {
+            Some(label) if !label.is_empty() => labels.push(label.to_string()),
+            _ => words.push(word),
+        }
+    }
+    (words.join(" "), labels)
+}
+
+/// Parse CSV (RFC 4180: quoted fields may contain commas, quotes and newlines)
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("Unterminated quoted field in CSV".into());
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV_BACKUP: &str =
+        "TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,AUTHOR,RESPONSIBLE,DATE,DATE_LANG,TIMEZONE\r
+meta,view_style=board,,,,,,,,\r
+task,Plan trip @travel,\"Flights, hotel\nand \"\"stuff\"\"\",1,1,Me,,2025-03-01,en,UTC\r
+task,Book flights,,4,2,Me,,tomorrow,en,UTC\r
+,,,,,,,,,\r
+section,Later,,,,,,,,\r
+task,Renew passport @admin @travel,,2,1,Me,,,en,UTC\r
+note,Remember the photos,,,,,,,,\r
+";
+
+    #[test]
+    fn test_from_csv() {
+        let backup = TodoistBackup::from_csv("Personal [2203306141].csv", CSV_BACKUP).unwrap();
+
+        let project = &backup.projects[0];
+        assert_eq!(
+            (project.id.as_str(), project.name.as_str()),
+            ("2203306141", "Personal")
+        );
+        assert_eq!(project.view_style.as_deref(), Some("board"));
+
+        assert_eq!(backup.sections.len(), 1);
+        assert_eq!(backup.sections[0].name, "Later");
+
+        let tasks = &backup.tasks;
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[0].content, "Plan trip");
+        assert_eq!(
+            tasks[0].description.as_deref(),
+            Some("Flights, hotel\nand \"stuff\"")
+        );
+        assert_eq!(tasks[0].priority, 4);
+        assert_eq!(tasks[0].due_date.as_deref(), Some("2025-03-01"));
+        assert_eq!(tasks[0].labels.as_deref(), Some("travel"));
+
+        assert_eq!(tasks[1].parent_id.as_deref(), Some(tasks[0].id.as_str()));
+        assert_eq!(tasks[1].priority, 1);
+        assert_eq!(tasks[1].due_date, None);
+
+        assert_eq!(tasks[2].parent_id, None);
+        assert_eq!(tasks[2].section_id, Some(backup.sections[0].id.clone()));
+        assert_eq!(tasks[2].labels.as_deref(), Some("admin,travel"));
+    }
+
+    #[test]
+    fn test_from_json_with_completed_history() {
+        let json = r#"{
+            "projects": [{"id": "p1", "name": "Inbox", "inbox_project": true}],
+            "sections": [{"id": "s1", "name": "Errands", "project_id": "p1", "section_order": 1}],
+            "items": [
+                {"id": "t1", "content": "Water plants", "project_id": "p1", "priority": 2,
+                 "labels": ["home"]},
+                {"id": "t2", "content": "Gone", "project_id": "p1", "is_deleted": true}
+            ],
+            "completed": {"items": [
+                {"task_id": "t1", "content": "Water plants", "project_id": "p1",
+                 "completed_at": "2025-01-01T10:00:00Z"},
+                {"task_id": "t3", "content": "Buy milk", "project_id": "p1", "section_id": "s1",
+                 "completed_at": "2025-01-02T10:00:00Z"}
+            ]}
+        }"#;
+        let backup = TodoistBackup::from_json(json).unwrap();
+
+        assert_eq!(backup.projects.len(), 1);
+        assert_eq!(backup.sections[0].sort_order, Some(1));
+        let ids: Vec<_> = backup.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["t1", "t3"]);
+        assert!(!backup.tasks[0].completed);
+        assert_eq!(backup.tasks[0].labels.as_deref(), Some("home"));
+        assert!(backup.tasks[1].completed);
+        assert_eq!(backup.tasks[1].section_id.as_deref(), Some("s1"));
+        assert_eq!(
+            backup.tasks[1].completed_at.as_deref(),
+            Some("2025-01-02T10:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_project_from_file_name() {
+        assert_eq!(
+            project_from_file_name("Work [ab12] [99].csv"),
+            ("Work [ab12]".to_string(), "99".to_string())
+        );
+        assert_eq!(
+            project_from_file_name("Side Project.csv"),
+            ("Side Project".to_string(), "csv-side-project".to_string())
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_import_is_idempotent() {
+        let backend = Arc::new(RwLock::new(TursoBackend::new_in_memory().await.unwrap()));
+        let importer = TodoistBackupImporter::new(backend.clone());
+        let backup = TodoistBackup::from_csv("Personal [1].csv", CSV_BACKUP).unwrap();
+
+        let summary = importer.import(&backup).await.unwrap();
+        assert_eq!(
+            summary,
+            BackupImportSummary {
+                projects: 1,
+                sections: 1,
+                tasks: 3,
+                completed: 0,
+            }
+        );
+        importer.import(&backup).await.unwrap();
+
+        let rows = backend
+            .read()
+            .await
+            .execute_sql("SELECT COUNT(*) AS n FROM todoist_tasks", HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(rows[0].get("n"), Some(&Value::Integer(3)));
+    }
+}
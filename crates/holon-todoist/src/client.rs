@@ -2,18 +2,48 @@ use super::models::{
     CommandResponse, CreateTaskRequest, SyncCommand, SyncResponse, TodoistTaskApiResponse,
     UpdateTaskRequest,
 };
+use super::rate_limit::{parse_retry_after, RateLimited, RateLimiter, DEFAULT_RETRY_AFTER};
+use holon::core::datasource::{HolonError, IdGenerator, TempIdMap};
 use reqwest::header::HeaderMap;
 use serde_json::json;
-use tracing::{debug, error, info};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
 const BASE_URL: &str = "https://app.todoist.com/api/v1";
 
+/// How long a request may take (generous for slow networks)
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often a request throttled with HTTP 429 is retried before giving up
+const MAX_RATE_LIMIT_RETRIES: usize = 3;
+
+/// `item_update` args of one task waiting for a request slot
+struct PendingUpdate {
+    args: serde_json::Value,
+    /// Callers whose updates were merged into `args`
+    waiters: Vec<oneshot::Sender<std::result::Result<(), String>>>,
+}
+
 pub struct TodoistClient {
     default_headers: HeaderMap,
     client: reqwest::Client,
+    /// Sync API temp IDs of created items/projects and the real IDs Todoist assigned
+    temp_ids: Arc<TempIdMap>,
+    /// Quota for partial syncs and commands
+    partial_syncs: RateLimiter,
+    /// Quota for full syncs
+    full_syncs: RateLimiter,
+    /// Task updates queued behind the rate limiter, by task ID
+    ///
+    /// Updates to a task arriving while an earlier one still waits for a slot are
+    /// merged into it and sent as a single `item_update` command.
+    pending_updates: Mutex<HashMap<String, PendingUpdate>>,
 }
 
 impl TodoistClient {
@@ -26,20 +56,33 @@ impl TodoistClient {
                 .expect("Invalid API key format"),
         );
 
-        // Create client with 30 second timeout (increased for slow networks)
-        let mut builder = reqwest::Client::builder();
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            builder = builder.timeout(std::time::Duration::from_secs(30));
-        }
-        let client = builder.build().expect("Failed to create HTTP client");
+        // On wasm32 reqwest sends requests with the browser's fetch API, whose
+        // client builder has no timeout; requests set `REQUEST_TIMEOUT` instead
+        let client = reqwest::Client::builder()
+            .build()
+            .expect("Failed to create HTTP client");
 
         Self {
             default_headers: headers,
             client,
+            temp_ids: Arc::new(TempIdMap::default()),
+            partial_syncs: RateLimiter::partial_syncs(),
+            full_syncs: RateLimiter::full_syncs(),
+            pending_updates: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Generate Sync API temp IDs with `id_generator`
+    pub fn with_id_generator(mut self, id_generator: Arc<dyn IdGenerator>) -> Self {
+        self.temp_ids = Arc::new(TempIdMap::new(id_generator));
+        self
+    }
+
+    /// Mapping from temp IDs of created items/projects to their Todoist IDs
+    pub fn temp_ids(&self) -> Arc<TempIdMap> {
+        self.temp_ids.clone()
+    }
+
     /// Helper to create better error messages from reqwest errors
     fn format_reqwest_error(e: reqwest::Error, url: &str, operation: &str) -> String {
         // Check error type first and provide specific guidance
@@ -115,22 +158,82 @@ impl TodoistClient {
             .map_err(|e| format!("Failed to read response body from {}: {}", url, e))?;
 
         if !status.is_success() {
-            return Err(format!(
-                "HTTP {} error from {}: {}",
-                status.as_u16(),
-                url,
-                if response_text.len() > 500 {
-                    format!("{}... (truncated)", &response_text[..500])
-                } else {
-                    response_text
-                }
-            )
-            .into());
+            let body = if response_text.len() > 500 {
+                format!("{}... (truncated)", &response_text[..500])
+            } else {
+                response_text
+            };
+            return Err(HolonError::remote(status.as_u16(), format!("{}: {}", url, body)).into());
         }
 
         Ok(response_text)
     }
 
+    /// POST `body` to the Sync API, retrying (within limits) after HTTP 429
+    ///
+    /// Callers acquire a slot from the `full_sync` or partial sync limiter first,
+    /// so they can keep batching work while they wait for it.
+    async fn post_sync(
+        &self,
+        headers: HeaderMap,
+        body: &serde_json::Value,
+        full_sync: bool,
+        operation: &str,
+    ) -> Result<String> {
+        let url = format!("{}/sync", BASE_URL);
+        let limiter = if full_sync {
+            &self.full_syncs
+        } else {
+            &self.partial_syncs
+        };
+
+        let mut attempt = 0;
+        loop {
+            let response = self
+                .client
+                .post(&url)
+                .timeout(REQUEST_TIMEOUT)
+                .headers(headers.clone())
+                .json(body)
+                .send()
+                .await
+                .map_err(|e| Self::format_reqwest_error(e, &url, operation))?;
+
+            if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return Self::handle_response(response, &url).await;
+            }
+
+            let header = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let response_text = response.text().await.unwrap_or_default();
+            let retry_after =
+                parse_retry_after(header.as_deref(), &response_text).unwrap_or(DEFAULT_RETRY_AFTER);
+
+            // Todoist throttles per user, so hold back every kind of request
+            self.partial_syncs.pause(retry_after);
+            self.full_syncs.pause(retry_after);
+
+            if attempt == MAX_RATE_LIMIT_RETRIES {
+                error!(
+                    "[TodoistClient] Still rate limited after {} retries: {}",
+                    attempt, url
+                );
+                return Err(RateLimited { url, retry_after }.into());
+            }
+            attempt += 1;
+            warn!(
+                "[TodoistClient] Rate limited (HTTP 429), retrying in {}s (attempt {}/{})",
+                retry_after.as_secs(),
+                attempt,
+                MAX_RATE_LIMIT_RETRIES
+            );
+            limiter.acquire().await;
+        }
+    }
+
     /// Parse command response from various formats the Sync API can return
     /// Handles:
     /// 1. Direct array: [CommandResponse, ...]
@@ -247,7 +350,12 @@ impl TodoistClient {
 
     /// Execute a sync command and return the command response
     async fn execute_command(&self, command: SyncCommand) -> Result<CommandResponse> {
-        let url = format!("{}/sync", BASE_URL);
+        self.partial_syncs.acquire().await;
+        self.send_command(command).await
+    }
+
+    /// Send a sync command once a partial sync slot has been acquired
+    async fn send_command(&self, command: SyncCommand) -> Result<CommandResponse> {
         let command_uuid = command.uuid.clone();
 
         let body = serde_json::json!({
@@ -290,24 +398,14 @@ impl TodoistClient {
             headers = injector.headers;
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .json(&body)
-            .send()
+        let response_text = self
+            .post_sync(headers, &body, false, "send command request")
             .await
             .map_err(|e| {
-                let error_msg = Self::format_reqwest_error(e, &url, "send command request");
-                error!("[TodoistClient] Command execution failed: {}", error_msg);
-                error_msg
+                error!("[TodoistClient] Command execution failed: {}", e);
+                e
             })?;
 
-        let response_text = Self::handle_response(response, &url).await.map_err(|e| {
-            error!("[TodoistClient] Failed to handle response: {}", e);
-            e
-        })?;
-
         debug!(
             "[TodoistClient] Command response received: uuid={}, response_length={}",
             command_uuid,
@@ -367,7 +465,6 @@ impl TodoistClient {
     /// - `sync_token`: Token from previous sync, or None for full sync (use "*" for full sync)
     /// - Returns: SyncResponse with items and new sync_token
     pub async fn sync_items(&self, sync_token: Option<&str>) -> Result<SyncResponse> {
-        let url = format!("{}/sync", BASE_URL);
         let sync_token = sync_token.unwrap_or("*");
 
         // Sync API expects JSON body (not form-urlencoded)
@@ -416,24 +513,20 @@ impl TodoistClient {
             headers = injector.headers;
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .json(&body)
-            .send()
+        let full_sync = sync_token == "*";
+        if full_sync {
+            self.full_syncs.acquire().await;
+        } else {
+            self.partial_syncs.acquire().await;
+        }
+        let response_text = self
+            .post_sync(headers, &body, full_sync, "send sync request")
             .await
             .map_err(|e| {
-                let error_msg = Self::format_reqwest_error(e, &url, "send sync request");
-                error!("[TodoistClient] Sync request failed: {}", error_msg);
-                error_msg
+                error!("[TodoistClient] Sync request failed: {}", e);
+                e
             })?;
 
-        let response_text = Self::handle_response(response, &url).await.map_err(|e| {
-            error!("[TodoistClient] Failed to handle sync response: {}", e);
-            e
-        })?;
-
         debug!(
             "[TodoistClient] Sync response received: length={}",
             response_text.len()
@@ -524,7 +617,7 @@ impl TodoistClient {
         &self,
         request: &CreateTaskRequest<'_>,
     ) -> Result<TodoistTaskApiResponse> {
-        let temp_id = Uuid::new_v4().to_string();
+        let temp_id = self.temp_ids.new_temp_id();
 
         let mut args = json!({
             "content": request.content,
@@ -546,9 +639,14 @@ impl TodoistClient {
             args["parent_id"] = json!(parent_id);
         }
 
+        // Todoist ignores a command whose uuid it already processed, so retrying the
+        // operation that created the task doesn't create it twice
         let command = SyncCommand {
             command_type: "item_add".to_string(),
-            uuid: Uuid::new_v4().to_string(),
+            uuid: request
+                .request_id
+                .map(str::to_string)
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
             temp_id: Some(temp_id.clone()),
             args,
         };
@@ -557,6 +655,7 @@ impl TodoistClient {
 
         // Extract the created item ID from temp_id_mapping
         let item_id = Self::extract_temp_id(&cmd_result, &temp_id);
+        self.temp_ids.register(&temp_id, &item_id);
 
         // Fetch the created task via sync
         let sync_response = self.sync_items(None).await?;
@@ -590,15 +689,68 @@ impl TodoistClient {
             args["parent_id"] = json!(parent_id);
         }
 
-        let command = SyncCommand {
-            command_type: "item_update".to_string(),
-            uuid: Uuid::new_v4().to_string(),
-            temp_id: None,
-            args,
+        let (done, merged) = oneshot::channel();
+        let first = {
+            let mut pending = self.pending_updates.lock().unwrap();
+            match pending.get_mut(task_id) {
+                Some(update) => {
+                    for (key, value) in args.as_object().into_iter().flatten() {
+                        update.args[key] = value.clone();
+                    }
+                    update.waiters.push(done);
+                    false
+                }
+                None => {
+                    pending.insert(
+                        task_id.to_string(),
+                        PendingUpdate {
+                            args,
+                            waiters: vec![done],
+                        },
+                    );
+                    true
+                }
+            }
         };
 
-        self.execute_command(command).await?;
-        Ok(())
+        // The first caller sends the update once a slot is free; updates arriving
+        // in the meantime are merged into it and wait for its result
+        if first {
+            self.partial_syncs.acquire().await;
+            let update = self
+                .pending_updates
+                .lock()
+                .unwrap()
+                .remove(task_id)
+                .expect("pending update is only removed by its first caller");
+            if update.waiters.len() > 1 {
+                info!(
+                    "[TodoistClient] Coalesced {} updates of task {} into one command",
+                    update.waiters.len(),
+                    task_id
+                );
+            }
+
+            let command = SyncCommand {
+                command_type: "item_update".to_string(),
+                uuid: Uuid::new_v4().to_string(),
+                temp_id: None,
+                args: update.args,
+            };
+            let result = self
+                .send_command(command)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string());
+            for waiter in update.waiters {
+                let _ = waiter.send(result.clone());
+            }
+        }
+
+        merged
+            .await
+            .map_err(|_| format!("Update of task {} was dropped", task_id))?
+            .map_err(Into::into)
     }
 
     pub async fn move_task(
@@ -670,7 +822,7 @@ impl TodoistClient {
 
     /// Create a project using the Sync API
     pub async fn create_project(&self, name: &str) -> Result<String> {
-        let temp_id = Uuid::new_v4().to_string();
+        let temp_id = self.temp_ids.new_temp_id();
 
         let command = SyncCommand {
             command_type: "project_add".to_string(),
@@ -685,6 +837,7 @@ impl TodoistClient {
 
         // Extract the created project ID from temp_id_mapping
         let project_id = Self::extract_temp_id(&cmd_result, &temp_id);
+        self.temp_ids.register(&temp_id, &project_id);
 
         Ok(project_id)
     }
@@ -774,29 +927,31 @@ impl TodoistClient {
         Ok(())
     }
 
-    /// Sync projects using the Sync API
+    /// Sync projects and their sections using the Sync API
+    ///
+    /// The response carries both `projects` and `sections` arrays.
     pub async fn sync_projects(&self, sync_token: Option<&str>) -> Result<serde_json::Value> {
-        let url = format!("{}/sync", BASE_URL);
         let sync_token = sync_token.unwrap_or("*");
 
         let body = serde_json::json!({
-            "resource_types": ["projects"],
+            "resource_types": ["projects", "sections"],
             "sync_token": sync_token,
         });
 
-        let response = self
-            .client
-            .post(&url)
-            .headers(self.default_headers.clone())
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| {
-                let error_msg = Self::format_reqwest_error(e, &url, "send sync projects request");
-                error_msg
-            })?;
-
-        let response_text = Self::handle_response(response, &url).await?;
+        let full_sync = sync_token == "*";
+        if full_sync {
+            self.full_syncs.acquire().await;
+        } else {
+            self.partial_syncs.acquire().await;
+        }
+        let response_text = self
+            .post_sync(
+                self.default_headers.clone(),
+                &body,
+                full_sync,
+                "send sync projects request",
+            )
+            .await?;
         let sync_resp: serde_json::Value = serde_json::from_str(&response_text)?;
         Ok(sync_resp)
     }
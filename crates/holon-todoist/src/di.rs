@@ -7,23 +7,41 @@ use ferrous_di::{DiResult, Lifetime, ServiceCollection, ServiceModule};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use crate::models::{TodoistProject, TodoistTask};
-use crate::todoist_datasource::{TodoistProjectDataSource, TodoistTaskDataSource};
+use crate::models::{TodoistProject, TodoistSection, TodoistTask};
+use crate::todoist_datasource::{
+    TodoistProjectDataSource, TodoistSectionDataSource, TodoistTaskDataSource,
+};
 use crate::TodoistClient;
 use crate::TodoistSyncProvider;
-use holon::core::datasource::{OperationProvider, SyncTokenStore, SyncableProvider};
+use holon::core::attachments::{AttachmentProvider, AttachmentStore};
+use holon::core::custom_fields::{CustomFieldProvider, CustomFieldStore};
+use holon::core::datasource::{IdStrategy, OperationProvider, SyncTokenStore, SyncableProvider};
+use holon::core::dependencies::{DependencyProvider, DependencyStore};
 use holon::core::queryable_cache::QueryableCache;
+use holon::core::time_tracking::{TimeEntryStore, TimeTrackingProvider};
+use holon::storage::archival::{ArchivalRule, ArchivalRuleProvider};
 use holon::storage::turso::TursoBackend;
+use holon::sync::conflicts::{MergeStrategy, SyncReconciler};
 
-/// Configuration for Todoist API key
+/// Configuration for Todoist integration
 #[derive(Clone, Debug)]
 pub struct TodoistConfig {
     pub api_key: Option<String>,
+    /// How temp IDs for items/projects created via the Sync API are generated
+    pub id_strategy: IdStrategy,
 }
 
 impl TodoistConfig {
     pub fn new(api_key: Option<String>) -> Self {
-        Self { api_key }
+        Self {
+            api_key,
+            id_strategy: IdStrategy::default(),
+        }
+    }
+
+    pub fn with_id_strategy(mut self, id_strategy: IdStrategy) -> Self {
+        self.id_strategy = id_strategy;
+        self
     }
 }
 
@@ -85,7 +103,9 @@ impl ServiceModule for TodoistModule {
             if let Some(api_key) = &config.api_key {
                 println!("[TodoistModule] API key found in TodoistConfig, setting up Todoist integration");
                 info!("[TodoistModule] API key found in TodoistConfig, setting up Todoist integration");
-                TodoistSyncProvider::new(TodoistClient::new(api_key), token_store)
+                let client =
+                    TodoistClient::new(api_key).with_id_generator(config.id_strategy.generator());
+                TodoistSyncProvider::new(client, token_store)
             } else {
                 // TodoistConfig registered but no API key - this is a configuration error
                 let msg = "[TodoistModule] ERROR: TodoistConfig registered but no API key provided. Either provide an API key in TodoistConfig or don't register TodoistModule.";
@@ -182,8 +202,15 @@ impl ServiceModule for TodoistModule {
                 })
             };
 
+            // Remote changes of tasks with pending local edits go through the reconciler;
+            // labels added on either side are kept
+            let reconciler = resolver.get_required::<SyncReconciler>();
+            reconciler
+                .strategies()
+                .register("todoist_tasks", "labels", MergeStrategy::union());
+
             println!("[TodoistModule] QueryableCache<TodoistTask> factory completed successfully");
-            cache
+            cache.with_reconciler(reconciler)
         });
 
         // Register QueryableCache for TodoistProject
@@ -254,6 +281,44 @@ impl ServiceModule for TodoistModule {
             cache
         });
 
+        // Register QueryableCache for TodoistSection
+        // This creates the todoist_sections table, which the hierarchy query groups tasks by
+        services
+            .add_singleton_factory::<QueryableCache<TodoistSectionDataSource, TodoistSection>, _>(
+                |resolver| {
+                    use ferrous_di::Resolver;
+
+                    let backend = Resolver::get_required::<RwLock<TursoBackend>>(resolver);
+                    let sync_provider = resolver.get_required::<TodoistSyncProvider>();
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        std::thread::spawn(move || {
+                            let rt = tokio::runtime::Runtime::new()
+                                .expect("Failed to create tokio runtime");
+                            rt.block_on(async {
+                                let datasource = TodoistSectionDataSource::new(sync_provider);
+                                QueryableCache::new_with_backend(datasource, backend)
+                                    .await
+                                    .expect("Failed to create QueryableCache<TodoistSection>")
+                            })
+                        })
+                        .join()
+                        .expect("Thread panicked while creating QueryableCache<TodoistSection>")
+                    }
+                    #[cfg(target_arch = "wasm32")]
+                    {
+                        let rt = tokio::runtime::Handle::current();
+                        rt.block_on(async {
+                            let datasource = TodoistSectionDataSource::new(sync_provider);
+                            QueryableCache::new_with_backend(datasource, backend)
+                                .await
+                                .expect("Failed to create QueryableCache<TodoistSection>")
+                        })
+                    }
+                },
+            );
+
         // Register QueryableCache as OperationProvider so it can be discovered by OperationDispatcher
         // This enables operations like set_field to work on todoist_tasks
         // The cache will be created when OperationModule collects providers (during BackendEngine creation)
@@ -272,6 +337,10 @@ impl ServiceModule for TodoistModule {
             let project_cache =
                 resolver.get_required::<QueryableCache<TodoistProjectDataSource, TodoistProject>>();
 
+            // Get the section cache (creates it if needed) - this triggers todoist_sections table creation
+            let section_cache =
+                resolver.get_required::<QueryableCache<TodoistSectionDataSource, TodoistSection>>();
+
             // Get sync provider to subscribe to its streams
             let sync_provider = resolver.get_required::<TodoistSyncProvider>();
 
@@ -291,6 +360,10 @@ impl ServiceModule for TodoistModule {
             project_cache.ingest_stream_with_metadata(project_rx);
             info!("[Todoist] Project stream subscription complete!");
 
+            // Subscribe section cache to sync provider's section stream with metadata
+            let section_rx = sync_provider.subscribe_sections();
+            section_cache.ingest_stream_with_metadata(section_rx);
+
             task_cache
         });
 
@@ -307,6 +380,47 @@ impl ServiceModule for TodoistModule {
             resolver.get_required::<TodoistProjectDataSource>()
         });
 
+        // Register local time tracking (clock_in/clock_out) for todoist_tasks
+        // Todoist has no time tracking API, so entries are kept in time_entries only
+        services.add_trait_factory::<dyn OperationProvider, _>(Lifetime::Singleton, |resolver| {
+            let store = resolver.get_required::<TimeEntryStore>();
+            Arc::new(TimeTrackingProvider::new(store, "todoist_tasks", "task"))
+                as Arc<dyn OperationProvider>
+        });
+
+        // Register task dependencies (add_dependency/remove_dependency) for todoist_tasks
+        // Todoist has no dependency API, so dependencies are kept in task_dependencies only
+        services.add_trait_factory::<dyn OperationProvider, _>(Lifetime::Singleton, |resolver| {
+            let store = resolver.get_required::<DependencyStore>();
+            Arc::new(DependencyProvider::new(store, "todoist_tasks", "task"))
+                as Arc<dyn OperationProvider>
+        });
+
+        // Register custom field definitions (define_custom_field/remove_custom_field) for todoist_tasks
+        // Todoist has no custom fields, so definitions and values are kept locally
+        services.add_trait_factory::<dyn OperationProvider, _>(Lifetime::Singleton, |resolver| {
+            let store = resolver.get_required::<CustomFieldStore>();
+            Arc::new(CustomFieldProvider::new(store, "todoist_tasks", "task"))
+                as Arc<dyn OperationProvider>
+        });
+
+        // Register local attachments (attach_file/remove_attachment) for todoist_tasks
+        // The Todoist client has no uploads API, so files are only referenced locally
+        services.add_trait_factory::<dyn OperationProvider, _>(Lifetime::Singleton, |resolver| {
+            let store = resolver.get_required::<AttachmentStore>();
+            Arc::new(AttachmentProvider::new(store, "todoist_tasks", "task"))
+                as Arc<dyn OperationProvider>
+        });
+
+        // Archive projects that are archived in Todoist
+        // Archiving a project in holon archives it in Todoist, so the rule never undoes an unarchive
+        services.add_trait_factory::<dyn ArchivalRuleProvider, _>(Lifetime::Singleton, |_| {
+            Arc::new(vec![ArchivalRule::new(
+                "todoist_projects",
+                "is_archived = 1",
+            )]) as Arc<dyn ArchivalRuleProvider>
+        });
+
         Ok(())
     }
 }
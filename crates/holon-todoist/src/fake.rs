@@ -6,7 +6,10 @@
 //! - Simulates external API behavior for testing/offline mode
 
 use async_trait::async_trait;
-use holon::core::datasource::{CrudOperations, DataSource, Operation, Result, UndoAction};
+use holon::core::datasource::{
+    default_id_generator, CrudOperations, DataSource, HolonError, HolonResult, IdGenerator,
+    Operation, Result, UndoAction,
+};
 use holon_api::streaming::ChangeNotifications;
 use holon_api::Value;
 use holon_api::{ApiError, Change, ChangeOrigin, StreamPosition};
@@ -34,6 +37,8 @@ pub struct TodoistTaskFake {
     change_tx: broadcast::Sender<Vec<Change<TodoistTask>>>,
     /// Version counter for tracking changes
     version: Arc<AtomicU64>,
+    /// Generator for IDs of created tasks
+    id_generator: Arc<dyn IdGenerator>,
 }
 
 impl TodoistTaskFake {
@@ -45,9 +50,16 @@ impl TodoistTaskFake {
             read_source,
             change_tx,
             version: Arc::new(AtomicU64::new(0)),
+            id_generator: default_id_generator(),
         }
     }
 
+    /// Generate IDs of created tasks with `id_generator`
+    pub fn with_id_generator(mut self, id_generator: Arc<dyn IdGenerator>) -> Self {
+        self.id_generator = id_generator;
+        self
+    }
+
     /// Get a receiver for the change stream (batches)
     pub fn subscribe(&self) -> broadcast::Receiver<Vec<Change<TodoistTask>>> {
         self.change_tx.subscribe()
@@ -86,6 +98,7 @@ impl ChangeNotifications<TodoistTask> for TodoistTaskFake {
                             origin: ChangeOrigin::Remote {
                                 operation_id: None,
                                 trace_id: None,
+                                source: None,
                             },
                         })
                         .collect(),
@@ -211,15 +224,19 @@ impl DataSource<TodoistTask> for TodoistTaskFake {
     async fn get_by_id(&self, id: &str) -> Result<Option<TodoistTask>> {
         self.read_source.get_by_id(id).await
     }
+
+    fn id_generator(&self) -> Arc<dyn IdGenerator> {
+        self.id_generator.clone()
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl CrudOperations<TodoistTask> for TodoistTaskFake {
-    async fn set_field(&self, id: &str, field: &str, value: Value) -> Result<UndoAction> {
+    async fn set_field(&self, id: &str, field: &str, value: Value) -> HolonResult<UndoAction> {
         // Read current task from read_source
         let task = self.read_source.get_by_id(id).await?;
-        let mut task = task.ok_or_else(|| anyhow::anyhow!("Task not found: {}", id))?;
+        let mut task = task.ok_or_else(|| HolonError::not_found("task", id))?;
 
         // Capture old value for inverse operation
         let old_value = match field {
@@ -295,6 +312,7 @@ impl CrudOperations<TodoistTask> for TodoistTaskFake {
             origin: ChangeOrigin::Local {
                 operation_id: None,
                 trace_id: None,
+                source: None,
             },
         });
 
@@ -308,9 +326,9 @@ impl CrudOperations<TodoistTask> for TodoistTaskFake {
         ))
     }
 
-    async fn create(&self, fields: HashMap<String, Value>) -> Result<(String, UndoAction)> {
+    async fn create(&self, fields: HashMap<String, Value>) -> HolonResult<(String, UndoAction)> {
         // Generate ID
-        let id = format!("fake-{}", uuid::Uuid::new_v4());
+        let id = format!("fake-{}", self.id_generator.generate());
 
         // Build task from fields
         let mut task = TodoistTask::new(
@@ -361,6 +379,7 @@ impl CrudOperations<TodoistTask> for TodoistTaskFake {
             origin: ChangeOrigin::Local {
                 operation_id: None,
                 trace_id: None,
+                source: None,
             },
         });
 
@@ -373,13 +392,13 @@ impl CrudOperations<TodoistTask> for TodoistTaskFake {
         Ok((id, inverse))
     }
 
-    async fn delete(&self, id: &str) -> Result<UndoAction> {
+    async fn delete(&self, id: &str) -> HolonResult<UndoAction> {
         // Capture entity for inverse operation (create)
         let task = self
             .read_source
             .get_by_id(id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Task not found: {}", id))?;
+            .ok_or_else(|| HolonError::not_found("task", id))?;
 
         // Emit change - no DB write, the change stream will update the cache
         self.emit_change(Change::Deleted {
@@ -387,6 +406,7 @@ impl CrudOperations<TodoistTask> for TodoistTaskFake {
             origin: ChangeOrigin::Local {
                 operation_id: None,
                 trace_id: None,
+                source: None,
             },
         });
 
@@ -450,6 +470,8 @@ mod tests {
                 Change::Deleted { id, .. } => {
                     tasks.remove(&id);
                 }
+                // Task sources only emit whole-task changes
+                Change::ColumnChange { .. } => {}
             }
         }
     }
@@ -53,6 +53,15 @@ impl OperationProvider for TodoistFakeOperationProvider {
         crate::todoist_datasource::operations_with_param_mappings()
     }
 
+    fn provider_name(&self) -> String {
+        "todoist-fake".to_string()
+    }
+
+    /// The real Todoist provider wins when both are registered
+    fn priority(&self) -> i32 {
+        -1
+    }
+
     async fn execute_operation(
         &self,
         entity_name: &str,
@@ -82,13 +91,7 @@ impl OperationProvider for TodoistFakeOperationProvider {
             // Store the ID for GenericProviderState to retrieve
             *self.last_created_id.lock().unwrap() = Some(id.clone());
             // Return inverse operation with entity_name set
-            return Ok(match inverse {
-                UndoAction::Undo(mut op) => {
-                    op.entity_name = entity_name.to_string();
-                    UndoAction::Undo(op)
-                }
-                UndoAction::Irreversible => UndoAction::Irreversible,
-            });
+            return Ok(inverse.with_entity_name(entity_name));
         }
 
         // Try dispatching to each trait module in order
@@ -101,13 +104,7 @@ impl OperationProvider for TodoistFakeOperationProvider {
         .await
         {
             Ok(inverse) => {
-                return Ok(match inverse {
-                    UndoAction::Undo(mut op) => {
-                        op.entity_name = entity_name.to_string();
-                        UndoAction::Undo(op)
-                    }
-                    UndoAction::Irreversible => UndoAction::Irreversible,
-                });
+                return Ok(inverse.with_entity_name(entity_name));
             }
             Err(err) => {
                 if !UnknownOperationError::is_unknown(err.as_ref()) {
@@ -124,13 +121,7 @@ impl OperationProvider for TodoistFakeOperationProvider {
         .await
         {
             Ok(inverse) => {
-                return Ok(match inverse {
-                    UndoAction::Undo(mut op) => {
-                        op.entity_name = entity_name.to_string();
-                        UndoAction::Undo(op)
-                    }
-                    UndoAction::Irreversible => UndoAction::Irreversible,
-                });
+                return Ok(inverse.with_entity_name(entity_name));
             }
             Err(err) => {
                 if !UnknownOperationError::is_unknown(err.as_ref()) {
@@ -145,13 +136,7 @@ impl OperationProvider for TodoistFakeOperationProvider {
             &params,
         )
         .await?;
-        Ok(match result {
-            UndoAction::Undo(mut op) => {
-                op.entity_name = entity_name.to_string();
-                UndoAction::Undo(op)
-            }
-            UndoAction::Irreversible => UndoAction::Irreversible,
-        })
+        Ok(result.with_entity_name(entity_name))
     }
 
     fn get_last_created_id(&self) -> Option<String> {
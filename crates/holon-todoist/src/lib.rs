@@ -4,14 +4,17 @@
 //!
 //! ## Stream-Based DataSource Implementation
 //! - `client` - TodoistClient (HTTP client)
+//! - `rate_limit` - RateLimiter keeping TodoistClient within Todoist's request limits
 //! - `provider` - TodoistProvider (underlying API provider)
 //! - `todoist_sync_provider` - Stream-based TodoistSyncProvider with builder pattern
-//! - `datasource` - TodoistTaskDataSource and TodoistProjectDataSource for DataSource trait
+//! - `datasource` - TodoistTaskDataSource, TodoistProjectDataSource and TodoistSectionDataSource for DataSource trait
 //! - `todoist_datasource` - Stream-based TodoistTaskDataSource
 //! - `fake` - TodoistTaskFake for optimistic updates
 //! - `models` - API models
 //! - `converters` - Type converters
+//! - `backup` - Offline import of Todoist backup files (CSV/JSON)
 
+pub mod backup;
 pub mod client;
 pub mod converters;
 pub mod datasource;
@@ -20,6 +23,7 @@ pub mod di;
 pub mod fake;
 pub mod models;
 pub mod queries;
+pub mod rate_limit;
 pub mod todoist_datasource;
 pub mod todoist_sync_provider;
 
@@ -43,6 +47,7 @@ mod stream_integration_test;
 #[cfg(test)]
 mod operations_demo;
 
+pub use backup::{BackupImportSummary, TodoistBackup, TodoistBackupImporter};
 pub use client::TodoistClient;
 pub use converters::*;
 pub use di::{TodoistConfig, TodoistModule};
@@ -52,4 +57,5 @@ pub use fake::*;
 pub use fake_wrapper::TodoistFakeOperationProvider;
 pub use models::*;
 pub use provider_wrapper::TodoistOperationProvider;
+pub use rate_limit::{RateLimited, RateLimiter};
 pub use todoist_sync_provider::TodoistSyncProvider;
@@ -2,12 +2,17 @@ use holon_macros::Entity;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Entity)]
-#[entity(name = "todoist_tasks", short_name = "task")]
+#[entity(
+    name = "todoist_tasks",
+    short_name = "task",
+    operations(CrudOperations, BlockOperations, TaskOperations)
+)]
 pub struct TodoistTask {
     #[primary_key]
     #[indexed]
     pub id: String,
 
+    #[validate(length(min = 1))]
     pub content: String,
 
     pub description: Option<String>,
@@ -22,6 +27,7 @@ pub struct TodoistTask {
     #[indexed]
     pub completed: bool,
 
+    #[validate(range(min = 1, max = 4))]
     pub priority: i32,
 
     pub due_date: Option<String>,
@@ -61,6 +67,14 @@ impl TodoistTask {
             is_deleted: Some(false),
         }
     }
+
+    /// Parent in the Todoist hierarchy: the parent task, else the section, else the project
+    pub fn tree_parent_id(&self) -> &str {
+        self.parent_id
+            .as_deref()
+            .or(self.section_id.as_deref())
+            .unwrap_or(&self.project_id)
+    }
 }
 
 // Implement BlockEntity trait for TodoistTask
@@ -114,66 +128,6 @@ impl holon::core::datasource::TaskEntity for TodoistTask {
     }
 }
 
-// Implement OperationRegistry to expose all operations for TodoistTask
-// Since TodoistTask implements both BlockEntity and TaskEntity,
-// it gets operations from all three traits: CrudOperations, BlockOperations, TaskOperations
-impl holon::core::datasource::OperationRegistry for TodoistTask {
-    fn all_operations() -> Vec<holon::core::datasource::OperationDescriptor> {
-        let entity_name = Self::entity_name();
-        let short_name = Self::short_name().expect("TodoistTask must have short_name");
-        let table = entity_name; // Use entity_name as table name - no mapping needed!
-        let id_column = "id";
-
-        // Aggregate operations from all applicable traits
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            use holon::core::datasource::{
-                __operations_crud_operation_provider, __operations_mutable_block_data_source,
-                __operations_mutable_task_data_source,
-            };
-            __operations_crud_operation_provider::crud_operations(
-                entity_name,
-                short_name,
-                table,
-                id_column,
-            )
-            .into_iter()
-            .chain(
-                __operations_mutable_block_data_source::block_operations(
-                    entity_name,
-                    short_name,
-                    table,
-                    id_column,
-                )
-                .into_iter(),
-            )
-            .chain(
-                __operations_mutable_task_data_source::task_operations(
-                    entity_name,
-                    short_name,
-                    table,
-                    id_column,
-                )
-                .into_iter(),
-            )
-            .collect()
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            // Operations macros not available on WASM
-            Vec::new()
-        }
-    }
-
-    fn entity_name() -> &'static str {
-        "todoist_tasks"
-    }
-
-    fn short_name() -> Option<&'static str> {
-        TodoistTask::short_name()
-    }
-}
-
 #[derive(Debug, Deserialize)]
 pub struct TodoistTaskApiResponse {
     pub id: String,
@@ -215,6 +169,9 @@ pub struct CreateTaskRequest<'a> {
     pub priority: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_id: Option<&'a str>,
+    /// Idempotency key of the operation creating the task, sent as the command uuid
+    #[serde(skip)]
+    pub request_id: Option<&'a str>,
 }
 
 #[derive(Debug, Default)]
@@ -395,3 +352,55 @@ impl From<TodoistProjectApiResponse> for TodoistProject {
         }
     }
 }
+
+/// Todoist Section model
+///
+/// Sections are synced together with projects and filled in by backup imports.
+#[derive(Debug, Clone, Serialize, Deserialize, Entity)]
+#[entity(name = "todoist_sections", short_name = "section")]
+pub struct TodoistSection {
+    #[primary_key]
+    #[indexed]
+    pub id: String,
+
+    pub name: String,
+
+    #[indexed]
+    pub project_id: String,
+
+    /// Sort order (renamed from `section_order` for consistency with projects)
+    pub sort_order: Option<i32>,
+
+    pub is_archived: Option<bool>,
+
+    pub created_at: Option<String>,
+}
+
+/// Todoist Section API response structure
+#[derive(Debug, Deserialize)]
+pub struct TodoistSectionApiResponse {
+    pub id: String,
+    pub name: String,
+    pub project_id: String,
+    #[serde(default)]
+    pub section_order: Option<i32>,
+    #[serde(default)]
+    pub is_archived: Option<bool>,
+    #[serde(default)]
+    pub added_at: Option<String>,
+    #[serde(default)]
+    pub is_deleted: Option<bool>,
+}
+
+impl From<TodoistSectionApiResponse> for TodoistSection {
+    fn from(api: TodoistSectionApiResponse) -> Self {
+        TodoistSection {
+            id: api.id,
+            name: api.name,
+            project_id: api.project_id,
+            sort_order: api.section_order,
+            is_archived: api.is_archived,
+            created_at: api.added_at,
+        }
+    }
+}
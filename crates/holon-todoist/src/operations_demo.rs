@@ -38,10 +38,11 @@ mod tests {
         let ops = TodoistTask::all_operations();
 
         // Should have operations from all three traits:
-        // - CrudOperations: set_field, create, delete (3 ops)
+        // - CrudOperations: set_field, create, delete, trash, restore, set_custom_field, archive,
+        //   unarchive (8 ops)
         // - BlockOperations: indent, move_block, outdent (3 ops)
         // - TaskOperations: set_completion, set_priority, set_due_date (3 ops)
-        assert_eq!(ops.len(), 9, "TodoistTask should have 9 operations total");
+        assert_eq!(ops.len(), 14, "TodoistTask should have 14 operations total");
 
         // Check for presence of operations from each trait
         let op_names: Vec<String> = ops.iter().map(|op| op.name.clone()).collect();
@@ -50,6 +51,11 @@ mod tests {
         assert!(op_names.contains(&"set_field".to_string()));
         assert!(op_names.contains(&"create".to_string()));
         assert!(op_names.contains(&"delete".to_string()));
+        assert!(op_names.contains(&"trash".to_string()));
+        assert!(op_names.contains(&"restore".to_string()));
+        assert!(op_names.contains(&"set_custom_field".to_string()));
+        assert!(op_names.contains(&"archive".to_string()));
+        assert!(op_names.contains(&"unarchive".to_string()));
 
         // BlockOperations operations
         assert!(op_names.contains(&"indent".to_string()));
@@ -80,7 +86,7 @@ mod tests {
         let ops = cache.operations();
 
         // Should delegate to TodoistTask::all_operations()
-        assert_eq!(ops.len(), 9, "Cache should expose all 9 operations");
+        assert_eq!(ops.len(), 14, "Cache should expose all 14 operations");
 
         // Verify a few operation details
         let set_field_op = ops.iter().find(|op| op.name == "set_field").unwrap();
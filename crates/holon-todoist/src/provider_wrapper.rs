@@ -54,6 +54,10 @@ impl OperationProvider for TodoistOperationProvider {
         crate::todoist_datasource::operations_with_param_mappings()
     }
 
+    fn provider_name(&self) -> String {
+        "todoist".to_string()
+    }
+
     async fn execute_operation(
         &self,
         entity_name: &str,
@@ -99,13 +103,7 @@ impl OperationProvider for TodoistOperationProvider {
                 id
             );
             // Return the inverse operation (if any) with entity_name set
-            return Ok(match inverse {
-                UndoAction::Undo(mut op) => {
-                    op.entity_name = entity_name.to_string();
-                    UndoAction::Undo(op)
-                }
-                UndoAction::Irreversible => UndoAction::Irreversible,
-            });
+            return Ok(inverse.with_entity_name(entity_name));
         }
 
         // Try dispatching to each trait module in order
@@ -124,13 +122,7 @@ impl OperationProvider for TodoistOperationProvider {
                     op_name
                 );
                 // Set entity_name on the inverse operation if present
-                return Ok(match inverse {
-                    UndoAction::Undo(mut op) => {
-                        op.entity_name = entity_name.to_string();
-                        UndoAction::Undo(op)
-                    }
-                    UndoAction::Irreversible => UndoAction::Irreversible,
-                });
+                return Ok(inverse.with_entity_name(entity_name));
             }
             Err(err) => {
                 if !UnknownOperationError::is_unknown(err.as_ref()) {
@@ -157,13 +149,7 @@ impl OperationProvider for TodoistOperationProvider {
                     op_name
                 );
                 // Set entity_name on the inverse operation if present
-                return Ok(match inverse {
-                    UndoAction::Undo(mut op) => {
-                        op.entity_name = entity_name.to_string();
-                        UndoAction::Undo(op)
-                    }
-                    UndoAction::Irreversible => UndoAction::Irreversible,
-                });
+                return Ok(inverse.with_entity_name(entity_name));
             }
             Err(err) => {
                 if !UnknownOperationError::is_unknown(err.as_ref()) {
@@ -200,13 +186,7 @@ impl OperationProvider for TodoistOperationProvider {
         }
 
         // Set entity_name on the inverse operation if present
-        result.map(|inverse| match inverse {
-            UndoAction::Undo(mut op) => {
-                op.entity_name = entity_name.to_string();
-                UndoAction::Undo(op)
-            }
-            UndoAction::Irreversible => UndoAction::Irreversible,
-        })
+        result.map(|inverse| inverse.with_entity_name(entity_name))
     }
 
     fn get_last_created_id(&self) -> Option<String> {
@@ -1,23 +1,28 @@
 //! PRQL query templates for Todoist hierarchy
 //!
-//! This module provides PRQL CTE definitions that unify tasks and projects
-//! into a single hierarchy suitable for outliner-style rendering.
+//! This module provides PRQL CTE definitions that unify tasks, sections and
+//! projects into a single hierarchy suitable for outliner-style rendering.
 //!
 //! The hierarchy uses `node_type` column containing the full entity name
-//! (e.g., "todoist_tasks", "todoist_projects") for operation routing.
+//! (e.g., "todoist_tasks", "todoist_sections", "todoist_projects") for operation routing.
 
 /// PRQL CTE that defines the unified Todoist hierarchy
 ///
-/// This CTE combines `todoist_projects` and `todoist_tasks` into a single
-/// hierarchical structure where:
+/// This CTE combines `todoist_projects`, `todoist_sections` and `todoist_tasks` into
+/// a single hierarchical structure where:
 /// - Projects can be parents of other projects (sub-projects)
-/// - Projects can be parents of tasks (top-level tasks)
+/// - Projects can be parents of sections and tasks (top-level tasks)
+/// - Sections can be parents of tasks
 /// - Tasks can be parents of tasks (subtasks)
 ///
 /// The `parent_id` column is computed to create this unified hierarchy:
 /// - For projects: `parent_id` points to parent project (or NULL for root)
+/// - For sections: points to `project_id` (the containing project)
 /// - For tasks with `parent_id`: points to parent task
-/// - For tasks without `parent_id`: points to `project_id` (the containing project)
+/// - For tasks in a section: points to `section_id`
+/// - Otherwise: points to `project_id` (the containing project)
+///
+/// Moving a task under any of these nodes with `move_block` moves it in Todoist.
 ///
 /// The `node_type` column contains the full entity name for operation routing.
 ///
@@ -47,8 +52,27 @@ pub const TODOIST_HIERARCHY_CTE: &str = r#"let todoist_hierarchy = (
         priority = null,
         due_date = null,
         project_id = null,
+        section_id = null,
         is_favorite
     }
+    append (
+        from todoist_sections
+        filter (is_archived == null || is_archived == false)
+        select {
+            id,
+            parent_id = project_id,
+            content = name,
+            node_type = "todoist_sections",
+            sort_order = sort_order ?? 0,
+            color = null,
+            completed = null,
+            priority = null,
+            due_date = null,
+            project_id,
+            section_id = null,
+            is_favorite = null
+        }
+    )
     append (
         from todoist_tasks
         filter (is_deleted == null || is_deleted == false)
@@ -56,6 +80,7 @@ pub const TODOIST_HIERARCHY_CTE: &str = r#"let todoist_hierarchy = (
             id,
             parent_id = case [
                 parent_id != null => parent_id,
+                section_id != null => section_id,
                 true => project_id
             ],
             content,
@@ -66,6 +91,7 @@ pub const TODOIST_HIERARCHY_CTE: &str = r#"let todoist_hierarchy = (
             priority,
             due_date,
             project_id,
+            section_id,
             is_favorite = null
         }
     )
@@ -96,6 +122,9 @@ pub const ENTITY_TODOIST_TASKS: &str = "todoist_tasks";
 /// Entity name for Todoist projects (matches the value in node_type column)
 pub const ENTITY_TODOIST_PROJECTS: &str = "todoist_projects";
 
+/// Entity name for Todoist sections (matches the value in node_type column)
+pub const ENTITY_TODOIST_SECTIONS: &str = "todoist_sections";
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,6 +184,11 @@ render (text content:this.content)
             "Expected todoist_tasks in SQL: {}",
             sql
         );
+        assert!(
+            sql.contains("todoist_sections"),
+            "Expected todoist_sections in SQL: {}",
+            sql
+        );
     }
 
     #[test]
@@ -180,5 +214,10 @@ render (text content:this.node_type)
             "Expected 'todoist_tasks' literal in SQL: {}",
             sql
         );
+        assert!(
+            sql.contains("'todoist_sections'") || sql.contains("\"todoist_sections\""),
+            "Expected 'todoist_sections' literal in SQL: {}",
+            sql
+        );
     }
 }
@@ -0,0 +1,208 @@
+//! Client-side rate limiting for the Todoist Sync API
+//!
+//! Todoist allows each user 1000 partial sync requests and 100 full sync requests
+//! per 15 minutes and answers HTTP 429 (with a `retry_after` hint) beyond that.
+//! `RateLimiter` keeps a sliding window of recent requests and makes callers wait,
+//! in arrival order, for a free slot. When Todoist throttles anyway, the limiter is
+//! paused for the advertised duration; `RateLimited` is surfaced once retries run out.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Window Todoist counts requests in
+pub const SYNC_WINDOW: Duration = Duration::from_secs(15 * 60);
+/// Partial (incremental) sync requests allowed per window, including commands
+pub const PARTIAL_SYNC_LIMIT: usize = 1000;
+/// Full sync requests (`sync_token: "*"` with resource types) allowed per window
+pub const FULL_SYNC_LIMIT: usize = 100;
+/// Wait used when a 429 response carries no retry hint
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Sliding-window limiter for requests against one Todoist quota
+pub struct RateLimiter {
+    limit: usize,
+    window: Duration,
+    state: Mutex<WindowState>,
+    /// Held while waiting for a slot so waiters are served in arrival order
+    queue: tokio::sync::Mutex<()>,
+}
+
+#[derive(Default)]
+struct WindowState {
+    /// Start times of the requests inside the window, oldest first
+    sent: VecDeque<Instant>,
+    /// No requests before this instant (set after a 429 response)
+    paused_until: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            state: Mutex::new(WindowState::default()),
+            queue: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Limiter for partial syncs and commands
+    pub fn partial_syncs() -> Self {
+        Self::new(PARTIAL_SYNC_LIMIT, SYNC_WINDOW)
+    }
+
+    /// Limiter for full syncs
+    pub fn full_syncs() -> Self {
+        Self::new(FULL_SYNC_LIMIT, SYNC_WINDOW)
+    }
+
+    /// Reserve a slot for a request sent at `now`, or return how long to wait for one
+    pub fn try_acquire_at(&self, now: Instant) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(until) = state.paused_until {
+            if until > now {
+                return Err(until - now);
+            }
+            state.paused_until = None;
+        }
+
+        while state
+            .sent
+            .front()
+            .is_some_and(|sent| now.saturating_duration_since(*sent) >= self.window)
+        {
+            state.sent.pop_front();
+        }
+
+        match state.sent.front() {
+            Some(oldest) if state.sent.len() >= self.limit => {
+                Err(self.window - now.saturating_duration_since(*oldest))
+            }
+            _ => {
+                state.sent.push_back(now);
+                Ok(())
+            }
+        }
+    }
+
+    /// Wait until a request may be sent, queueing behind earlier callers
+    ///
+    /// On WASM there is no timer, so requests are never delayed.
+    pub async fn acquire(&self) {
+        let _turn = self.queue.lock().await;
+        #[cfg(not(target_arch = "wasm32"))]
+        while let Err(wait) = self.try_acquire_at(Instant::now()) {
+            tracing::debug!("[RateLimiter] Waiting {:?} for a request slot", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Hold back all requests until `duration` after `now`
+    pub fn pause_at(&self, now: Instant, duration: Duration) {
+        let until = now + duration;
+        let mut state = self.state.lock().unwrap();
+        if state.paused_until.is_none_or(|paused| paused < until) {
+            state.paused_until = Some(until);
+        }
+    }
+
+    /// Hold back all requests for `duration` (Todoist answered 429)
+    pub fn pause(&self, duration: Duration) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.pause_at(Instant::now(), duration);
+        #[cfg(target_arch = "wasm32")]
+        let _ = duration;
+    }
+}
+
+/// How long Todoist asks us to back off, from the `Retry-After` header or the
+/// `error_extra.retry_after` field of the error body (both in seconds)
+pub fn parse_retry_after(header: Option<&str>, body: &str) -> Option<Duration> {
+    header
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .or_else(|| {
+            serde_json::from_str::<serde_json::Value>(body)
+                .ok()?
+                .pointer("/error_extra/retry_after")?
+                .as_u64()
+        })
+        .map(Duration::from_secs)
+}
+
+/// Todoist kept answering HTTP 429 (Too Many Requests) after retrying
+#[derive(Debug, Clone)]
+pub struct RateLimited {
+    pub url: String,
+    pub retry_after: Duration,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Rate limited by Todoist at {}: retry after {}s",
+            self.url,
+            self.retry_after.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sliding_window() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(10));
+        let start = Instant::now();
+
+        assert_eq!(limiter.try_acquire_at(start), Ok(()));
+        assert_eq!(
+            limiter.try_acquire_at(start + Duration::from_secs(4)),
+            Ok(())
+        );
+        assert_eq!(
+            limiter.try_acquire_at(start + Duration::from_secs(5)),
+            Err(Duration::from_secs(5))
+        );
+        // The first request left the window
+        assert_eq!(
+            limiter.try_acquire_at(start + Duration::from_secs(10)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_pause_after_429() {
+        let limiter = RateLimiter::new(10, Duration::from_secs(10));
+        let start = Instant::now();
+
+        limiter.pause_at(start, Duration::from_secs(30));
+        assert_eq!(
+            limiter.try_acquire_at(start + Duration::from_secs(20)),
+            Err(Duration::from_secs(10))
+        );
+        assert_eq!(
+            limiter.try_acquire_at(start + Duration::from_secs(30)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(
+            parse_retry_after(Some("12"), ""),
+            Some(Duration::from_secs(12))
+        );
+        assert_eq!(
+            parse_retry_after(
+                None,
+                r#"{"error_tag":"LIMITS_REACHED","error_extra":{"retry_after":7}}"#
+            ),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(parse_retry_after(None, "Too Many Requests"), None);
+    }
+}
@@ -46,6 +46,8 @@ mod tests {
                 Change::Deleted { id, .. } => {
                     tasks.remove(&id);
                 }
+                // Task sources only emit whole-task changes
+                Change::ColumnChange { .. } => {}
             }
         }
     }
@@ -120,7 +122,7 @@ mod tests {
                 assert_eq!(data.id, id);
                 assert_eq!(data.content, "Test Task");
             }
-            Change::Updated { .. } | Change::Deleted { .. } => {
+            Change::Updated { .. } | Change::Deleted { .. } | Change::ColumnChange { .. } => {
                 panic!("Expected Created, got {:?}", changes[0])
             }
         }
@@ -8,10 +8,10 @@
 
 use async_trait::async_trait;
 use holon::core::datasource::{
-    CrudOperations, DataSource, Operation, OperationDescriptor, OperationProvider,
-    OperationRegistry, Result, UndoAction, UnknownOperationError,
-    __operations_crud_operation_provider, __operations_mutable_block_data_source,
-    __operations_mutable_task_data_source,
+    __operations_crud_operation_provider, __operations_move_operations,
+    __operations_mutable_block_data_source, __operations_mutable_task_data_source, CrudOperations,
+    DataSource, HolonError, HolonResult, MoveOperations, Operation, OperationDescriptor,
+    OperationProvider, OperationRegistry, Result, UndoAction, UnknownOperationError,
 };
 use holon::storage::types::StorageEntity;
 use holon_api::streaming::ChangeNotifications;
@@ -22,10 +22,11 @@ use std::pin::Pin;
 use std::sync::Arc;
 
 use crate::models::{
-    CreateTaskRequest, TodoistProject, TodoistProjectApiResponse, TodoistTask, UpdateTaskRequest,
+    CreateTaskRequest, TodoistProject, TodoistProjectApiResponse, TodoistSection,
+    TodoistSectionApiResponse, TodoistTask, UpdateTaskRequest,
 };
 
-use super::todoist_sync_provider::TodoistSyncProvider;
+use super::todoist_sync_provider::{TaskParent, TodoistSyncProvider};
 use tokio::sync::broadcast;
 use tokio_stream::Stream;
 use tracing::{debug, error, info};
@@ -62,6 +63,20 @@ impl TodoistTaskDataSource {
     pub fn new(provider: Arc<TodoistSyncProvider>) -> Self {
         Self { provider }
     }
+
+    /// Move a task under `parent_id`, which may be a task, section or project
+    async fn move_to_parent(&self, id: &str, parent_id: &str) -> Result<()> {
+        let client = &self.provider.client;
+        match self.provider.task_parent(parent_id) {
+            TaskParent::Task(task_id) => client.move_task(id, Some(task_id), None, None).await,
+            TaskParent::Section(section_id) => {
+                client.move_task(id, None, None, Some(section_id)).await
+            }
+            TaskParent::Project(project_id) => {
+                client.move_task(id, None, Some(project_id), None).await
+            }
+        }
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -136,6 +151,48 @@ impl TodoistTaskOperations for TodoistTaskDataSource {
     }
 }
 
+/// Moves along the Todoist hierarchy, where a task's parent is a task, section or project
+///
+/// `move_block` on tasks is routed here as well. `after_id` is not sent: Todoist
+/// decides where the moved task goes among its new siblings.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl MoveOperations<TodoistTask> for TodoistTaskDataSource {
+    async fn move_entity(
+        &self,
+        id: &str,
+        parent_id: &str,
+        _after_id: Option<&str>,
+    ) -> Result<UndoAction> {
+        info!(
+            "[TodoistTaskDataSource] move_entity: task {} -> parent {}",
+            id, parent_id
+        );
+
+        let old_task = <TodoistTaskDataSource as DataSource<TodoistTask>>::get_by_id(self, id)
+            .await?
+            .ok_or_else(|| HolonError::not_found("task", id))?;
+        let old_parent_id = old_task.tree_parent_id().to_string();
+
+        self.move_to_parent(id, parent_id).await?;
+
+        // Sync so the moved task's parent_id/section_id/project_id arrive in the cache
+        use holon::core::datasource::{StreamPosition, SyncableProvider};
+        if let Err(e) = self.provider.sync(StreamPosition::Beginning).await {
+            error!("[TodoistTaskDataSource] Post-move sync failed: {}", e);
+        }
+
+        Ok(UndoAction::Undo(
+            __operations_move_operations::move_entity_op(
+                "", // Will be set by OperationProvider
+                id,
+                &old_parent_id,
+                None,
+            ),
+        ))
+    }
+}
+
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl ChangeNotifications<TodoistTask> for TodoistTaskDataSource {
@@ -214,7 +271,7 @@ impl holon::core::datasource::DataSource<TodoistTask> for TodoistTaskDataSource
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl CrudOperations<TodoistTask> for TodoistTaskDataSource {
-    async fn set_field(&self, id: &str, field: &str, value: Value) -> Result<UndoAction> {
+    async fn set_field(&self, id: &str, field: &str, value: Value) -> HolonResult<UndoAction> {
         use tracing::{debug, error, info};
 
         info!(
@@ -239,10 +296,11 @@ impl CrudOperations<TodoistTask> for TodoistTaskDataSource {
                     .as_ref()
                     .map(|d| Value::String(d.clone()))
                     .or(Some(Value::Null)),
-                "parent_id" => task
-                    .parent_id
+                "parent_id" => Some(Value::String(task.tree_parent_id().to_string())),
+                "section_id" => task
+                    .section_id
                     .as_ref()
-                    .map(|p| Value::String(p.clone()))
+                    .map(|s| Value::String(s.clone()))
                     .or(Some(Value::Null)),
                 _ => None,
             })
@@ -326,16 +384,13 @@ impl CrudOperations<TodoistTask> for TodoistTaskDataSource {
                 let current =
                     <TodoistTaskDataSource as DataSource<TodoistTask>>::get_by_id(self, id)
                         .await?
-                        .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+                        .ok_or_else(|| HolonError::not_found("task", id))?;
                 let project_id = current.project_id.as_str();
                 let section_id = current.section_id.as_deref();
                 match value {
                     Value::String(s) => {
                         debug!("[TodoistTaskDataSource] Moving task to parent: {}", s);
-                        self.provider
-                            .client
-                            .move_task(id, Some(&s), Some(project_id), section_id)
-                            .await
+                        self.move_to_parent(id, &s).await
                     }
                     Value::Null => {
                         debug!("[TodoistTaskDataSource] Removing parent from task");
@@ -346,7 +401,32 @@ impl CrudOperations<TodoistTask> for TodoistTaskDataSource {
                     }
                     _ => {
                         error!("[TodoistTaskDataSource] Invalid value type for parent_id");
-                        return Err("Invalid value type for parent_id".into());
+                        return Err(HolonError::validation("Invalid value type for parent_id"));
+                    }
+                }
+            }
+            "section_id" => {
+                debug!("[TodoistTaskDataSource] Updating section_id field");
+                match value {
+                    Value::String(s) => {
+                        self.provider
+                            .client
+                            .move_task(id, None, None, Some(&s))
+                            .await
+                    }
+                    Value::Null => {
+                        let current =
+                            <TodoistTaskDataSource as DataSource<TodoistTask>>::get_by_id(self, id)
+                                .await?
+                                .ok_or_else(|| HolonError::not_found("task", id))?;
+                        self.provider
+                            .client
+                            .move_task(id, None, Some(&current.project_id), None)
+                            .await
+                    }
+                    _ => {
+                        error!("[TodoistTaskDataSource] Invalid value type for section_id");
+                        return Err(HolonError::validation("Invalid value type for section_id"));
                     }
                 }
             }
@@ -362,7 +442,10 @@ impl CrudOperations<TodoistTask> for TodoistTaskDataSource {
             }
             _ => {
                 error!("[TodoistTaskDataSource] Field '{}' not supported", field);
-                return Err(format!("Field {} not supported", field).into());
+                return Err(HolonError::validation(format!(
+                    "Field {} not supported",
+                    field
+                )));
             }
         };
 
@@ -389,16 +472,16 @@ impl CrudOperations<TodoistTask> for TodoistTaskDataSource {
         }
 
         // Return inverse operation
-        result.map(|_| {
-            use holon::core::datasource::__operations_crud_operation_provider;
-            UndoAction::Undo(__operations_crud_operation_provider::set_field_op(
+        result?;
+        Ok(UndoAction::Undo(
+            __operations_crud_operation_provider::set_field_op(
                 "", // Will be set by OperationProvider
                 id, field, old_value,
-            ))
-        })
+            ),
+        ))
     }
 
-    async fn create(&self, fields: HashMap<String, Value>) -> Result<(String, UndoAction)> {
+    async fn create(&self, fields: HashMap<String, Value>) -> HolonResult<(String, UndoAction)> {
         let content = fields
             .get("content")
             .and_then(|v| v.as_string().map(|s| s.to_string()))
@@ -419,6 +502,7 @@ impl CrudOperations<TodoistTask> for TodoistTaskDataSource {
         let parent_id = fields
             .get("parent_id")
             .and_then(|v| v.as_string().map(|s| s.to_string()));
+        let idempotency_key = Operation::current_idempotency_key();
 
         let request = CreateTaskRequest {
             content: &content,
@@ -427,6 +511,7 @@ impl CrudOperations<TodoistTask> for TodoistTaskDataSource {
             due_string: due_string.as_deref(),
             priority,
             parent_id: parent_id.as_deref(),
+            request_id: idempotency_key.as_deref(),
         };
 
         let created_task_api = self.provider.client.create_task(&request).await?;
@@ -442,7 +527,7 @@ impl CrudOperations<TodoistTask> for TodoistTaskDataSource {
         Ok((task_id, inverse))
     }
 
-    async fn delete(&self, id: &str) -> Result<UndoAction> {
+    async fn delete(&self, id: &str) -> HolonResult<UndoAction> {
         // Capture entity for inverse operation (create)
         let old_task =
             <TodoistTaskDataSource as DataSource<TodoistTask>>::get_by_id(self, id).await?;
@@ -503,6 +588,15 @@ pub fn operations_with_param_mappings() -> Vec<OperationDescriptor> {
             )
             .into_iter(),
         )
+        .chain(
+            __operations_move_operations::move_operations(
+                entity_name,
+                short_name,
+                table,
+                id_column,
+            )
+            .into_iter(),
+        )
         .collect()
 }
 
@@ -534,13 +628,7 @@ impl OperationProvider for TodoistTaskDataSource {
         match __operations_todoist_task_operations::dispatch_operation(self, op_name, &params).await
         {
             Ok(inverse) => {
-                return Ok(match inverse {
-                    UndoAction::Undo(mut op) => {
-                        op.entity_name = entity_name.to_string();
-                        UndoAction::Undo(op)
-                    }
-                    UndoAction::Irreversible => UndoAction::Irreversible,
-                });
+                return Ok(inverse.with_entity_name(entity_name));
             }
             Err(err) => {
                 if !UnknownOperationError::is_unknown(err.as_ref()) {
@@ -556,13 +644,37 @@ impl OperationProvider for TodoistTaskDataSource {
         .await
         {
             Ok(inverse) => {
-                return Ok(match inverse {
-                    UndoAction::Undo(mut op) => {
-                        op.entity_name = entity_name.to_string();
-                        UndoAction::Undo(op)
-                    }
-                    UndoAction::Irreversible => UndoAction::Irreversible,
-                });
+                return Ok(inverse.with_entity_name(entity_name));
+            }
+            Err(err) => {
+                if !UnknownOperationError::is_unknown(err.as_ref()) {
+                    return Err(err);
+                }
+            }
+        }
+
+        // Move operations; move_block is handled as move_entity, since the generic
+        // BlockOperations::move_block only knows tasks as parents, not sections or projects
+        let renamed_params;
+        let (move_op_name, move_params) = if op_name == "move_block" {
+            let mut move_params = params.clone();
+            if let Some(after_block_id) = move_params.remove("after_block_id") {
+                move_params.insert("after_id".to_string(), after_block_id);
+            }
+            renamed_params = move_params;
+            ("move_entity", &renamed_params)
+        } else {
+            (op_name, &params)
+        };
+        match __operations_move_operations::dispatch_operation::<_, TodoistTask>(
+            self,
+            move_op_name,
+            move_params,
+        )
+        .await
+        {
+            Ok(inverse) => {
+                return Ok(inverse.with_entity_name(entity_name));
             }
             Err(err) => {
                 if !UnknownOperationError::is_unknown(err.as_ref()) {
@@ -571,20 +683,14 @@ impl OperationProvider for TodoistTaskDataSource {
             }
         }
 
-        // Block operations (move_block, indent, outdent, etc.)
+        // Block operations (indent, outdent, etc.)
         match __operations_mutable_block_data_source::dispatch_operation::<_, TodoistTask>(
             self, op_name, &params,
         )
         .await
         {
             Ok(inverse) => {
-                return Ok(match inverse {
-                    UndoAction::Undo(mut op) => {
-                        op.entity_name = entity_name.to_string();
-                        UndoAction::Undo(op)
-                    }
-                    UndoAction::Irreversible => UndoAction::Irreversible,
-                });
+                return Ok(inverse.with_entity_name(entity_name));
             }
             Err(err) => {
                 if !UnknownOperationError::is_unknown(err.as_ref()) {
@@ -598,13 +704,7 @@ impl OperationProvider for TodoistTaskDataSource {
             self, op_name, &params,
         )
         .await?;
-        Ok(match result {
-            UndoAction::Undo(mut op) => {
-                op.entity_name = entity_name.to_string();
-                UndoAction::Undo(op)
-            }
-            UndoAction::Irreversible => UndoAction::Irreversible,
-        })
+        Ok(result.with_entity_name(entity_name))
     }
 }
 
@@ -714,7 +814,7 @@ impl holon::core::datasource::DataSource<TodoistProject> for TodoistProjectDataS
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl CrudOperations<TodoistProject> for TodoistProjectDataSource {
-    async fn set_field(&self, _id: &str, field: &str, value: Value) -> Result<UndoAction> {
+    async fn set_field(&self, _id: &str, field: &str, value: Value) -> HolonResult<UndoAction> {
         match field {
             "name" => {
                 if let Value::String(_name) = value {
@@ -732,7 +832,7 @@ impl CrudOperations<TodoistProject> for TodoistProjectDataSource {
         Ok(UndoAction::Irreversible)
     }
 
-    async fn create(&self, fields: HashMap<String, Value>) -> Result<(String, UndoAction)> {
+    async fn create(&self, fields: HashMap<String, Value>) -> HolonResult<(String, UndoAction)> {
         let name = fields
             .get("name")
             .and_then(|v| v.as_string().map(|s| s.to_string()))
@@ -762,10 +862,21 @@ impl CrudOperations<TodoistProject> for TodoistProjectDataSource {
         Ok((project_id, UndoAction::Irreversible))
     }
 
-    async fn delete(&self, id: &str) -> Result<UndoAction> {
+    async fn delete(&self, id: &str) -> HolonResult<UndoAction> {
         self.provider.client.delete_project(id).await?;
         Ok(UndoAction::Irreversible)
     }
+
+    // Archiving a project in holon archives it in Todoist as well
+    async fn archive(&self, id: &str) -> HolonResult<UndoAction> {
+        self.archive_project(id).await?;
+        Ok(UndoAction::Irreversible)
+    }
+
+    async fn unarchive(&self, id: &str) -> HolonResult<UndoAction> {
+        self.unarchive_project(id).await?;
+        Ok(UndoAction::Irreversible)
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -804,6 +915,7 @@ impl OperationProvider for TodoistProjectDataSource {
                     },
                 ],
                 precondition: None,
+                simulation: None,
             },
             OperationDescriptor {
                 entity_name: "todoist_projects".to_string(),
@@ -820,6 +932,7 @@ impl OperationProvider for TodoistProjectDataSource {
                 affected_fields: vec!["is_archived".to_string()],
                 param_mappings: vec![],
                 precondition: None,
+                simulation: None,
             },
             OperationDescriptor {
                 entity_name: "todoist_projects".to_string(),
@@ -836,6 +949,7 @@ impl OperationProvider for TodoistProjectDataSource {
                 affected_fields: vec!["is_archived".to_string()],
                 param_mappings: vec![],
                 precondition: None,
+                simulation: None,
             },
         ]
     }
@@ -861,11 +975,19 @@ impl OperationProvider for TodoistProjectDataSource {
                 Ok(UndoAction::Irreversible)
             }
             "archive" => {
-                self.archive_project(&params).await?;
+                let id = params
+                    .get("id")
+                    .and_then(|v| v.as_string())
+                    .ok_or_else(|| "archive requires 'id' parameter")?;
+                self.archive_project(id).await?;
                 Ok(UndoAction::Irreversible)
             }
             "unarchive" => {
-                self.unarchive_project(&params).await?;
+                let id = params
+                    .get("id")
+                    .and_then(|v| v.as_string())
+                    .ok_or_else(|| "unarchive requires 'id' parameter")?;
+                self.unarchive_project(id).await?;
                 Ok(UndoAction::Irreversible)
             }
             _ => Err(format!("Unknown operation '{}' for todoist_projects", op_name).into()),
@@ -901,12 +1023,7 @@ impl TodoistProjectDataSource {
     }
 
     /// Archive a project and its descendants
-    async fn archive_project(&self, params: &StorageEntity) -> Result<()> {
-        let id = params
-            .get("id")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| "archive requires 'id' parameter")?;
-
+    async fn archive_project(&self, id: &str) -> Result<()> {
         debug!("[TodoistProjectDataSource] Archiving project {}", id);
 
         self.provider.client.archive_project(id).await?;
@@ -921,12 +1038,7 @@ impl TodoistProjectDataSource {
     }
 
     /// Unarchive a project
-    async fn unarchive_project(&self, params: &StorageEntity) -> Result<()> {
-        let id = params
-            .get("id")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| "unarchive requires 'id' parameter")?;
-
+    async fn unarchive_project(&self, id: &str) -> Result<()> {
         debug!("[TodoistProjectDataSource] Unarchiving project {}", id);
 
         self.provider.client.unarchive_project(id).await?;
@@ -944,6 +1056,47 @@ impl TodoistProjectDataSource {
     }
 }
 
+/// DataSource for TodoistSection
+///
+/// Read-only: sections arrive via the sync provider's section stream and are
+/// changed in Todoist itself.
+pub struct TodoistSectionDataSource {
+    provider: Arc<TodoistSyncProvider>,
+}
+
+impl TodoistSectionDataSource {
+    pub fn new(provider: Arc<TodoistSyncProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl holon::core::datasource::DataSource<TodoistSection> for TodoistSectionDataSource {
+    async fn get_all(&self) -> Result<Vec<TodoistSection>> {
+        let sync_resp = self.provider.client.sync_projects(None).await?;
+        let sections_array = sync_resp
+            .get("sections")
+            .and_then(|s| s.as_array())
+            .ok_or_else(|| "No sections array in response".to_string())?;
+
+        Ok(sections_array
+            .iter()
+            .filter_map(|s| {
+                serde_json::from_value::<TodoistSectionApiResponse>(s.clone())
+                    .ok()
+                    .filter(|api| !api.is_deleted.unwrap_or(false))
+                    .map(TodoistSection::from)
+            })
+            .collect())
+    }
+
+    async fn get_by_id(&self, id: &str) -> Result<Option<TodoistSection>> {
+        let all_sections = self.get_all().await?;
+        Ok(all_sections.into_iter().find(|s| s.id == id))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1011,4 +1164,14 @@ mod tests {
             param_names
         );
     }
+
+    #[test]
+    fn test_operations_with_param_mappings_includes_move_entity() {
+        let ops = operations_with_param_mappings();
+        let move_entity = ops
+            .iter()
+            .find(|op| op.name == "move_entity")
+            .expect("move_entity operation should exist");
+        assert_eq!(move_entity.entity_name, "todoist_tasks");
+    }
 }
@@ -2,10 +2,16 @@
 //!
 //! This sync provider polls the Todoist API and emits changes on typed streams.
 //! Architecture:
-//! - ONE sync() call → multiple typed streams (tasks, projects)
+//! - ONE sync() call → multiple typed streams (tasks, projects, sections)
 //! - Builder pattern for registering caches
 //! - Fire-and-forget operations - updates arrive via streams
 //! - Sync tokens are included in batch metadata for atomic updates
+//! - Incremental sync: only the first sync (or one after the server discarded the
+//!   token) is a `full_sync`, which is flagged as a full snapshot so the caches emit
+//!   only the rows that actually changed
+//!
+//! Batch assembly and change counting come from `holon::sync::http_provider`, the
+//! generic toolkit for REST-backed providers with a single resource.
 
 use async_trait::async_trait;
 use tokio::sync::broadcast;
@@ -15,21 +21,40 @@ use holon::core::datasource::{
     StreamPosition, SyncTokenStore, SyncableProvider, UndoAction,
 };
 use holon::storage::types::StorageEntity;
-use holon_api::{BatchMetadata, SyncTokenUpdate, WithMetadata};
-use std::sync::Arc;
+use holon::sync::http_provider::{sync_batch, ChangeCounts};
+use holon_api::ChangeSource;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 use crate::client::TodoistClient;
 use crate::models::{
-    SyncResponse, TodoistProject, TodoistProjectApiResponse, TodoistTask, TodoistTaskApiResponse,
+    SyncResponse, TodoistProject, TodoistProjectApiResponse, TodoistSection,
+    TodoistSectionApiResponse, TodoistTask, TodoistTaskApiResponse,
 };
 
-/// Changes wrapped with metadata for atomic sync token updates
-pub type ChangesWithMetadata<T> = WithMetadata<Vec<Change<T>>, BatchMetadata>;
+pub use holon::sync::http_provider::ChangesWithMetadata;
+
+/// What the tree parent of a task is
+///
+/// In the Todoist hierarchy a task sits under its parent task, else its section,
+/// else its project; moving a task to a new tree parent needs to know which.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskParent<'a> {
+    Task(&'a str),
+    Section(&'a str),
+    Project(&'a str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContainerKind {
+    Project,
+    Section,
+}
 
 /// Stream-based TodoistSyncProvider that polls API and emits changes on typed streams
 ///
 /// Architecture:
-/// - sync() makes ONE API call → splits into task and project changes → emits on separate streams
+/// - sync() makes ONE API call → splits into task, project and section changes → emits on separate streams
 /// - Builder pattern for registering caches
 /// - Sync token is included in batch metadata for atomic updates in QueryableCache
 pub struct TodoistSyncProvider {
@@ -37,6 +62,9 @@ pub struct TodoistSyncProvider {
     token_store: Arc<dyn SyncTokenStore>,
     task_tx: broadcast::Sender<ChangesWithMetadata<TodoistTask>>,
     project_tx: broadcast::Sender<ChangesWithMetadata<TodoistProject>>,
+    section_tx: broadcast::Sender<ChangesWithMetadata<TodoistSection>>,
+    /// Projects and sections seen by syncs, to tell them apart from tasks
+    containers: Mutex<HashMap<String, ContainerKind>>,
 }
 
 impl TodoistSyncProvider {
@@ -46,6 +74,8 @@ impl TodoistSyncProvider {
             token_store,
             task_tx: broadcast::channel(1000).0,
             project_tx: broadcast::channel(1000).0,
+            section_tx: broadcast::channel(1000).0,
+            containers: Mutex::new(HashMap::new()),
         }
     }
 
@@ -58,6 +88,38 @@ impl TodoistSyncProvider {
     pub fn subscribe_projects(&self) -> broadcast::Receiver<ChangesWithMetadata<TodoistProject>> {
         self.project_tx.subscribe()
     }
+
+    /// Get a receiver for section changes (for testing or manual wiring)
+    pub fn subscribe_sections(&self) -> broadcast::Receiver<ChangesWithMetadata<TodoistSection>> {
+        self.section_tx.subscribe()
+    }
+
+    /// Resolve a tree parent id to the task, section or project it refers to
+    ///
+    /// Ids of projects and sections not seen by a sync yet resolve to tasks.
+    pub fn task_parent<'a>(&self, parent_id: &'a str) -> TaskParent<'a> {
+        match self.containers.lock().unwrap().get(parent_id) {
+            Some(ContainerKind::Project) => TaskParent::Project(parent_id),
+            Some(ContainerKind::Section) => TaskParent::Section(parent_id),
+            None => TaskParent::Task(parent_id),
+        }
+    }
+
+    fn remember_containers<T>(&self, changes: &[Change<T>], kind: ContainerKind) {
+        let mut containers = self.containers.lock().unwrap();
+        for change in changes {
+            match change {
+                Change::Updated { id, .. } | Change::ColumnChange { id, .. } => {
+                    containers.insert(id.clone(), kind);
+                }
+                Change::Deleted { id, .. } => {
+                    containers.remove(id);
+                }
+                // Syncs emit created entities as Updated, since Todoist doesn't tell them apart
+                Change::Created { .. } => {}
+            }
+        }
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -71,9 +133,10 @@ impl SyncableProvider for TodoistSyncProvider {
     ///
     /// This method:
     /// 1. Loads current token from token store
-    /// 2. Calls sync_items() API (returns tasks + projects in one response)
-    /// 3. Splits response into task and project changes
-    /// 4. Emits changes on separate typed streams
+    /// 2. Calls sync_items() and sync_projects() (the latter returns projects + sections)
+    /// 3. Splits responses into task, project and section changes
+    /// 4. Emits changes on separate typed streams, flagging `full_sync` responses
+    ///    as full snapshots
     /// 5. Saves new token to token store
     /// 6. Returns the new stream position
     #[tracing::instrument(name = "provider.todoist.sync", skip(self, _position))]
@@ -104,47 +167,47 @@ impl SyncableProvider for TodoistSyncProvider {
             // Also fetch projects (using same sync token for consistency)
             let project_response = self.client.sync_projects(token_str).await?;
 
+            // The server answers with a full_sync when there is no token or it no longer
+            // accepts ours; such responses list every entity instead of deltas
+            let tasks_full_sync = response.full_sync.unwrap_or(token_str.is_none());
+            let projects_full_sync = project_response
+                .get("full_sync")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(token_str.is_none());
+            info!(
+                "[TodoistSyncProvider] Sync response: tasks full_sync={}, projects full_sync={}",
+                tasks_full_sync, projects_full_sync
+            );
+
             // Split and emit on separate typed streams
             let task_changes = compute_task_changes(&response);
             let project_changes = compute_project_changes(&project_response);
+            let section_changes = compute_section_changes(&project_response);
 
-            let task_count = task_changes.len();
-            let project_count = project_changes.len();
-
-            // Count change types for tasks
-            let mut task_created = 0;
-            let mut task_updated = 0;
-            let mut task_deleted = 0;
-            for change in &task_changes {
-                match change {
-                    holon_api::Change::Created { .. } => task_created += 1,
-                    holon_api::Change::Updated { .. } => task_updated += 1,
-                    holon_api::Change::Deleted { .. } => task_deleted += 1,
-                }
+            // A full sync lists every project and section, so forget the ones it lacks
+            if projects_full_sync {
+                self.containers.lock().unwrap().clear();
             }
+            self.remember_containers(&project_changes, ContainerKind::Project);
+            self.remember_containers(&section_changes, ContainerKind::Section);
 
-            // Count change types for projects
-            let mut project_created = 0;
-            let mut project_updated = 0;
-            let mut project_deleted = 0;
-            for change in &project_changes {
-                match change {
-                    holon_api::Change::Created { .. } => project_created += 1,
-                    holon_api::Change::Updated { .. } => project_updated += 1,
-                    holon_api::Change::Deleted { .. } => project_deleted += 1,
-                }
-            }
+            let task_count = task_changes.len();
+            let project_count = project_changes.len();
+            let section_count = section_changes.len();
+            let task_counts = ChangeCounts::of(&task_changes);
+            let project_counts = ChangeCounts::of(&project_changes);
 
             // Record OpenTelemetry attributes on the span
             use tracing::Span;
             Span::current().record("sync.task_count", task_count);
-            Span::current().record("sync.task_created", task_created);
-            Span::current().record("sync.task_updated", task_updated);
-            Span::current().record("sync.task_deleted", task_deleted);
+            Span::current().record("sync.task_created", task_counts.created);
+            Span::current().record("sync.task_updated", task_counts.updated);
+            Span::current().record("sync.task_deleted", task_counts.deleted);
             Span::current().record("sync.project_count", project_count);
-            Span::current().record("sync.project_created", project_created);
-            Span::current().record("sync.project_updated", project_updated);
-            Span::current().record("sync.project_deleted", project_deleted);
+            Span::current().record("sync.project_created", project_counts.created);
+            Span::current().record("sync.project_updated", project_counts.updated);
+            Span::current().record("sync.project_deleted", project_counts.deleted);
+            Span::current().record("sync.section_count", section_count);
 
             // Determine new position from sync token
             let new_position = match response.sync_token {
@@ -152,54 +215,41 @@ impl SyncableProvider for TodoistSyncProvider {
                 None => StreamPosition::Beginning, // Fallback - shouldn't happen
             };
 
-            // Create sync token update to be saved atomically with data
-            let sync_token_update = SyncTokenUpdate {
-                provider_name: self.provider_name().to_string(),
-                position: new_position.clone(),
-            };
-
-            // Extract trace context from current span for propagation through broadcast channel
-            let trace_context = holon_api::BatchTraceContext::from_current_span();
-            tracing::info!(
-                "[TodoistSyncProvider] Extracted trace_context: {:?}",
-                trace_context
+            // Wrap changes with metadata carrying the sync token (saved atomically with
+            // the data) and the current span's trace context
+            let task_batch = sync_batch(
+                self.provider_name(),
+                "todoist_tasks",
+                task_changes,
+                new_position.clone(),
+                tasks_full_sync,
+            );
+            let project_batch = sync_batch(
+                self.provider_name(),
+                "todoist_projects",
+                project_changes,
+                new_position.clone(),
+                projects_full_sync,
+            );
+            let section_batch = sync_batch(
+                self.provider_name(),
+                "todoist_sections",
+                section_changes,
+                new_position.clone(),
+                projects_full_sync,
             );
-
-            // Create metadata with sync token for atomic updates
-            let task_metadata = BatchMetadata {
-                relation_name: "todoist_tasks".to_string(),
-                trace_context: trace_context.clone(),
-                sync_token: Some(sync_token_update.clone()),
-            };
-
-            let project_metadata = BatchMetadata {
-                relation_name: "todoist_projects".to_string(),
-                trace_context,
-                sync_token: Some(sync_token_update),
-            };
-
-            // Wrap changes with metadata
-            let task_batch = WithMetadata {
-                inner: task_changes,
-                metadata: task_metadata,
-            };
-
-            let project_batch = WithMetadata {
-                inner: project_changes,
-                metadata: project_metadata,
-            };
 
             // Emit changes (fire-and-forget - ignore errors if no receivers)
             info!(
                 "[TodoistSyncProvider] Emitting {} task changes (created={}, updated={}, deleted={}) and {} project changes (created={}, updated={}, deleted={})",
                 task_count,
-                task_created,
-                task_updated,
-                task_deleted,
+                task_counts.created,
+                task_counts.updated,
+                task_counts.deleted,
                 project_count,
-                project_created,
-                project_updated,
-                project_deleted
+                project_counts.created,
+                project_counts.updated,
+                project_counts.deleted
             );
             let send_result = self.task_tx.send(task_batch);
             if send_result.is_err() {
@@ -208,11 +258,12 @@ impl SyncableProvider for TodoistSyncProvider {
                 );
             }
             let _ = self.project_tx.send(project_batch);
+            let _ = self.section_tx.send(section_batch);
 
             // Log sync completion
             info!(
-                "[TodoistSyncProvider] Sync completed successfully: {} task changes, {} project changes",
-                task_count, project_count
+                "[TodoistSyncProvider] Sync completed successfully: {} task changes, {} project changes, {} section changes",
+                task_count, project_count, section_count
             );
 
             // NOTE: Sync token is NOT saved here anymore - it will be saved atomically
@@ -261,6 +312,12 @@ impl OperationProvider for TodoistSyncProvider {
     }
 }
 
+/// Origin of changes received from Todoist, with trace context of the current span
+fn sync_origin() -> ChangeOrigin {
+    ChangeOrigin::remote_with_current_span()
+        .with_source(ChangeSource::default().with_provider("todoist"))
+}
+
 /// Compute task changes from sync response
 ///
 /// Converts API responses to Change<TodoistTask> enum variants.
@@ -268,7 +325,7 @@ impl OperationProvider for TodoistSyncProvider {
 fn compute_task_changes(response: &SyncResponse) -> Vec<Change<TodoistTask>> {
     // Capture trace context ONCE for all changes in this batch
     // This ensures all changes in the sync batch share the same trace context
-    let origin = ChangeOrigin::remote_with_current_span();
+    let origin = sync_origin();
 
     response
         .items
@@ -322,7 +379,7 @@ fn compute_task_changes(response: &SyncResponse) -> Vec<Change<TodoistTask>> {
 /// Handles both updates and deletions.
 fn compute_project_changes(response: &serde_json::Value) -> Vec<Change<TodoistProject>> {
     // Capture trace context ONCE for all changes in this batch
-    let origin = ChangeOrigin::remote_with_current_span();
+    let origin = sync_origin();
 
     // Extract projects array from response
     let projects_array = match response.get("projects").and_then(|p| p.as_array()) {
@@ -364,3 +421,80 @@ fn compute_project_changes(response: &serde_json::Value) -> Vec<Change<TodoistPr
         })
         .collect()
 }
+
+/// Compute section changes from sync_projects() response
+///
+/// Sections come in the same response as projects; handles updates and deletions.
+fn compute_section_changes(response: &serde_json::Value) -> Vec<Change<TodoistSection>> {
+    let origin = sync_origin();
+
+    let Some(sections_array) = response.get("sections").and_then(|s| s.as_array()) else {
+        return vec![];
+    };
+
+    sections_array
+        .iter()
+        .filter_map(|section_json| {
+            match serde_json::from_value::<TodoistSectionApiResponse>(section_json.clone()) {
+                Ok(api_section) if api_section.is_deleted.unwrap_or(false) => {
+                    Some(Change::Deleted {
+                        id: api_section.id,
+                        origin: origin.clone(),
+                    })
+                }
+                Ok(api_section) => {
+                    let section = TodoistSection::from(api_section);
+                    Some(Change::Updated {
+                        id: section.id.clone(),
+                        data: section,
+                        origin: origin.clone(),
+                    })
+                }
+                Err(e) => {
+                    tracing::warn!("[compute_section_changes] Failed to parse section: {}", e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Simple in-memory mock for SyncTokenStore
+    struct MockSyncTokenStore;
+
+    #[async_trait]
+    impl SyncTokenStore for MockSyncTokenStore {
+        async fn load_token(&self, _provider_name: &str) -> Result<Option<StreamPosition>> {
+            Ok(None)
+        }
+        async fn save_token(&self, _provider_name: &str, _position: StreamPosition) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_task_parent_resolves_synced_containers() {
+        let provider =
+            TodoistSyncProvider::new(TodoistClient::new("key"), Arc::new(MockSyncTokenStore));
+        let response = serde_json::json!({
+            "projects": [{"id": "p1", "name": "Work"}],
+            "sections": [
+                {"id": "s1", "name": "Next", "project_id": "p1"},
+                {"id": "s2", "name": "Gone", "project_id": "p1", "is_deleted": true}
+            ]
+        });
+        let sections = compute_section_changes(&response);
+        assert_eq!(sections.len(), 2);
+        provider.remember_containers(&compute_project_changes(&response), ContainerKind::Project);
+        provider.remember_containers(&sections, ContainerKind::Section);
+
+        assert_eq!(provider.task_parent("p1"), TaskParent::Project("p1"));
+        assert_eq!(provider.task_parent("s1"), TaskParent::Section("s1"));
+        assert_eq!(provider.task_parent("s2"), TaskParent::Task("s2"));
+        assert_eq!(provider.task_parent("t1"), TaskParent::Task("t1"));
+    }
+}
@@ -0,0 +1,220 @@
+//! Benchmarks of the query pipeline: compiling queries in the engine, dispatching
+//! operations and processing change streams
+//!
+//! Run with `cargo bench -p holon`; `scripts/check-bench-budget.sh` compares the
+//! results with the budget in `scripts/bench-budget.txt`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use criterion::{BatchSize, Criterion, black_box, criterion_group, criterion_main};
+use holon::api::backend_engine::BackendEngine;
+use holon::api::operation_dispatcher::OperationDispatcher;
+use holon::api::{Window, WindowedResult};
+use holon::core::datasource::{OperationProvider, Result, UndoAction};
+use holon::core::transform::{ChangeOriginTransformer, TransformPipeline};
+use holon::storage::turso::TursoBackend;
+use holon::storage::types::StorageEntity;
+use holon_api::{ChangeOrigin, MapChange, OperationDescriptor, SortKey, Value, changed_columns};
+use tokio::runtime::Runtime;
+use tokio::sync::RwLock;
+
+const ROWS: usize = 10_000;
+
+const QUERY: &str = r#"
+from blocks
+filter completed == false
+select {id, content, parent_id, depth, sort_key, completed}
+render (list sort_by:[parent_id, sort_key] item_template:(row (checkbox checked:this.completed) (editable_text content:this.content)))
+"#;
+
+/// Provider whose operations do nothing, so only the dispatch itself is measured
+struct NoopProvider {
+    entity_name: String,
+}
+
+#[async_trait]
+impl OperationProvider for NoopProvider {
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        (0..10)
+            .map(|i| OperationDescriptor {
+                entity_name: self.entity_name.clone(),
+                entity_short_name: self.entity_name.clone(),
+                id_column: "id".to_string(),
+                name: format!("op{}", i),
+                display_name: format!("Op {}", i),
+                description: String::new(),
+                required_params: vec![],
+                affected_fields: vec![],
+                param_mappings: vec![],
+                precondition: None,
+                simulation: None,
+            })
+            .collect()
+    }
+
+    async fn execute_operation(
+        &self,
+        _entity_name: &str,
+        _op_name: &str,
+        _params: StorageEntity,
+    ) -> Result<UndoAction> {
+        Ok(UndoAction::Irreversible)
+    }
+}
+
+fn block(i: usize) -> HashMap<String, Value> {
+    HashMap::from([
+        ("id".to_string(), Value::String(format!("block-{}", i))),
+        (
+            "parent_id".to_string(),
+            Value::String(format!("page-{}", i % 50)),
+        ),
+        ("sort_key".to_string(), Value::String(format!("a{:05}", i))),
+        ("content".to_string(), Value::String(format!("Block {}", i))),
+        ("completed".to_string(), Value::Boolean(i % 3 == 0)),
+    ])
+}
+
+fn origin() -> ChangeOrigin {
+    ChangeOrigin::Remote {
+        operation_id: None,
+        trace_id: None,
+        source: None,
+    }
+}
+
+fn engine_compile(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let engine = runtime.block_on(async {
+        let backend = Arc::new(RwLock::new(TursoBackend::new_in_memory().await.unwrap()));
+        let pipeline = Arc::new(
+            TransformPipeline::empty().with_transformer(Arc::new(ChangeOriginTransformer)),
+        );
+        BackendEngine::from_dependencies(
+            backend,
+            Arc::new(OperationDispatcher::new(vec![])),
+            pipeline,
+        )
+        .unwrap()
+    });
+
+    let mut group = c.benchmark_group("engine");
+    group.bench_function("compile_query", |b| {
+        b.iter(|| engine.compile_query(black_box(QUERY.to_string())).unwrap())
+    });
+    group.bench_function("compile_query_cached", |b| {
+        let params = HashMap::new();
+        b.iter(|| {
+            engine
+                .compile_query_cached(black_box(QUERY), &params)
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn operation_dispatch(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let providers: Vec<Arc<dyn OperationProvider>> = (0..20)
+        .map(|i| {
+            Arc::new(NoopProvider {
+                entity_name: format!("entity{}", i),
+            }) as Arc<dyn OperationProvider>
+        })
+        .collect();
+    let dispatcher = OperationDispatcher::new(providers);
+    let params: StorageEntity =
+        HashMap::from([("id".to_string(), Value::String("block-1".to_string()))]);
+
+    c.bench_function("operation_dispatch/execute_operation", |b| {
+        b.to_async(&runtime).iter(|| async {
+            dispatcher
+                .execute_operation("entity19", "op9", params.clone())
+                .await
+                .unwrap()
+        })
+    });
+}
+
+fn change_stream(c: &mut Criterion) {
+    let rows: Vec<_> = (0..ROWS).map(block).collect();
+    let created: Vec<MapChange> = rows
+        .iter()
+        .map(|row| MapChange::Created {
+            data: row.clone(),
+            origin: origin(),
+        })
+        .collect();
+    let toggled: Vec<MapChange> = (0..ROWS)
+        .map(|i| MapChange::ColumnChange {
+            id: format!("block-{}", i),
+            columns: HashMap::from([("completed".to_string(), Value::Boolean(i % 3 != 0))]),
+            origin: origin(),
+        })
+        .collect();
+    let order = vec![
+        SortKey {
+            column: "parent_id".to_string(),
+            descending: false,
+        },
+        SortKey {
+            column: "sort_key".to_string(),
+            descending: false,
+        },
+    ];
+    let window = Window::new(0, 100);
+
+    let mut group = c.benchmark_group("change_stream");
+    group.sample_size(10);
+    group.bench_function("window_10k_created", |b| {
+        b.iter_batched(
+            || (WindowedResult::new(vec![], order.clone()), created.clone()),
+            |(mut result, changes)| {
+                for change in changes {
+                    black_box(result.apply(change, window));
+                }
+                result
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("window_10k_column_changes", |b| {
+        b.iter_batched(
+            || {
+                (
+                    WindowedResult::new(rows.clone(), order.clone()),
+                    toggled.clone(),
+                )
+            },
+            |(mut result, changes)| {
+                for change in changes {
+                    black_box(result.apply(change, window));
+                }
+                result
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("changed_columns_10k", |b| {
+        let updated: Vec<_> = rows
+            .iter()
+            .map(|row| {
+                let mut row = row.clone();
+                row.insert("content".to_string(), Value::String("edited".to_string()));
+                row
+            })
+            .collect();
+        b.iter(|| {
+            rows.iter()
+                .zip(&updated)
+                .map(|(before, after)| changed_columns(before, after).len())
+                .sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, engine_compile, operation_dispatch, change_stream);
+criterion_main!(benches);
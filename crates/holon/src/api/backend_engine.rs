@@ -1,5 +1,5 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -13,14 +13,84 @@ enum AvailableColumns {
     Selected(Vec<String>),
 }
 
+use crate::api::diagnostics::{Diagnostics, provider_diagnostics};
+use crate::api::live_search::{LiveSearchSource, LiveSearches};
 use crate::api::operation_dispatcher::OperationDispatcher;
+use crate::api::query_cache::{CompiledQuery, QueryCache, QueryCacheConfig};
+use crate::api::query_filters::{FilteredQueries, FilteredQuerySource};
+use crate::api::query_plan::{QueryPlan, QueryPlanStep, indexed_columns, suggest_indexes};
+use crate::api::result_window::{Window, WindowChangeStream, WindowedQueries, WindowedResult};
+use crate::api::view_loader::{ViewDefinition, ViewEvent, ViewLoader};
+use crate::core::access::{ACCESS_ENTITY, EntityAccess, EntityAccessStore};
+use crate::core::annotations::AnnotationStore;
+use crate::core::automation::AutomationRules;
 use crate::core::datasource::OperationProvider;
+use crate::core::identities::EntityIdentityStore;
+use crate::core::log_buffer::{LogBuffer, LogFilter, LogRecord};
+use crate::core::notifications::NotificationSink;
+use crate::core::operation_log::{AuditExportFormat, AuditLogEntry, OperationLogStore};
+use crate::core::presence::PresenceTracker;
 use crate::core::transform::TransformPipeline;
+use crate::core::usage_stats::OperationUsageStore;
+use crate::core::view_state::{VIEW_STATE_FLUSH_INTERVAL, ViewStateStore};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::export::{
+    ExportFormat, ExportSummary, QUERY_EXPORT_PROGRESS_INTERVAL, QueryExportFormat,
+    QueryExportProgress, QueryResultWriter,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::import::{
+    ColumnMapping, ImportProgress, ImportRowError, ImportSummary, LogseqImporter, OutlineImporter,
+    TABLE_IMPORT_BATCH_SIZE, TabularData, TabularImportProgress, TabularImportSummary,
+    TabularPreview,
+};
+use crate::references::{Backlink, BacklinkIndex, EmbedResolver, Tag, TagIndex};
+use crate::reminders::ReminderScheduler;
+use crate::storage::archival::{ArchivalConfig, apply_archival_rules};
+use crate::storage::computed::ComputedField;
+use crate::storage::maintenance::{MaintenanceScheduler, MaintenanceStatus};
+use crate::storage::replica::{ReplicaStatus, ReplicaSyncer};
+use crate::storage::rollups::Rollup;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::schema::EntitySchema;
+use crate::storage::snapshot_store::{RestoreSummary, SnapshotInfo, SnapshotStore};
+use crate::storage::soft_delete::{SoftDeleteTables, TrashConfig};
+use crate::storage::stats::StatsSource;
 use crate::storage::turso::{RowChangeStream, TursoBackend};
 use crate::storage::types::StorageEntity;
-use holon_api::{Operation, OperationDescriptor, Value};
-use holon_core::{UndoAction, UndoStack};
-use query_render::RenderSpec;
+use crate::storage::urgency::UrgencyScoring;
+use crate::sync::blob::{SyncBlobLog, SyncImportSummary};
+use crate::sync::conflicts::{SyncConflict, SyncReconciler};
+use crate::sync::dirty::{DirtyEntity, ProviderDirtyStatus, SyncDirtyStore};
+use crate::sync::health::{SyncHealthReport, SyncHealthStore};
+use crate::sync::orchestrator::SyncProgress;
+use crate::sync::scheduler::{SyncAllSummary, SyncScheduler, SyncStatus};
+use holon_api::{
+    CURRENT_CHANGE_SOURCE, ChangeSource, DELETED_AT_COLUMN, FilterValue, MapChange, Operation,
+    OperationDescriptor, Presence, TreeCursor, Value,
+};
+use holon_core::{
+    HolonError, IdMappingService, OperationLogEntry, OperationUsageEntry, UndoAction, UndoStack,
+};
+use prqlc::ir::pl::TableExternRef;
+use prqlc::ir::rq::RelationKind;
+use query_render::{RenderSpec, ViewState, WidgetRegistry, WidgetSpec};
+
+/// Schema of the `blocks` table (shared with the importers)
+pub(crate) const BLOCKS_TABLE_SQL: &str = r#"
+    CREATE TABLE IF NOT EXISTS blocks (
+        id TEXT PRIMARY KEY,
+        parent_id TEXT,
+        depth INTEGER NOT NULL DEFAULT 0,
+        sort_key TEXT NOT NULL,
+        content TEXT NOT NULL,
+        collapsed INTEGER NOT NULL DEFAULT 0,
+        completed INTEGER NOT NULL DEFAULT 0,
+        block_type TEXT NOT NULL DEFAULT 'text',
+        created_at TEXT NOT NULL DEFAULT (datetime('now')),
+        updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+    )
+"#;
 
 /// Main render engine managing database, query compilation, and operations
 pub struct BackendEngine {
@@ -29,6 +99,40 @@ pub struct BackendEngine {
     transform_pipeline: Arc<TransformPipeline>, // Pipeline for AST transformations
     table_to_entity_map: Arc<RwLock<HashMap<String, String>>>, // Maps table names to entity names
     undo_stack: Arc<RwLock<UndoStack>>,   // Undo/redo history
+    usage_stats: Option<Arc<OperationUsageStore>>, // Local operation usage statistics
+    operation_log: Option<Arc<OperationLogStore>>, // Persistent operation history
+    id_mapping: Option<Arc<IdMappingService>>, // Temporary IDs of optimistic creates
+    sync_health: Option<Arc<SyncHealthStore>>, // Sync attempt tracking and health reports
+    sync_dirty: Option<Arc<SyncDirtyStore>>, // Unsynced local changes per entity/provider
+    sync_reconciler: Option<Arc<SyncReconciler>>, // Unresolved field-level sync conflicts
+    sync_scheduler: Option<Arc<SyncScheduler>>, // Periodic syncs of registered providers
+    query_cache: Arc<QueryCache>,         // Compiled queries and recent results
+    windowed_queries: Arc<WindowedQueries>, // Queries whose rows are sent a window at a time
+    filtered_queries: Arc<FilteredQueries>, // Queries re-run when their filter widgets change
+    live_searches: Arc<LiveSearches>,     // Search queries re-run as the user types
+    soft_delete_tables: SoftDeleteTables, // Tables whose trashed rows queries hide
+    trash_config: TrashConfig,            // Retention of trashed entities
+    archival_config: ArchivalConfig,      // Rules of the archival job
+    embed_resolver: EmbedResolver,        // Resolves ((block-id)) embeds in query results
+    backlinks: Option<Arc<BacklinkIndex>>, // References between blocks
+    tags: Option<Arc<TagIndex>>,          // Tags extracted from content
+    automation_rules: Option<Arc<AutomationRules>>, // Operations run when entities change
+    annotations: Option<Arc<AnnotationStore>>, // Comments on any entity
+    sync_blobs: Option<Arc<SyncBlobLog>>, // Encrypted device-to-device operation log
+    log_buffer: Option<LogBuffer>,        // Recent log events for in-app log viewers
+    identities: Option<Arc<EntityIdentityStore>>, // IDs of the same thing across datasources
+    maintenance: Option<Arc<MaintenanceScheduler>>, // WAL checkpoints, vacuum and ANALYZE while idle
+    snapshots: Option<Arc<SnapshotStore>>,          // Periodic snapshots for point-in-time restore
+    replica: Option<Arc<ReplicaSyncer>>, // Sync with the remote database this one replicates
+    view_states: Option<Arc<ViewStateStore>>, // Collapsed nodes, selection and scroll position of views
+    view_loader: Option<Arc<ViewLoader>>,     // Views defined by .prql files
+    presence: Option<Arc<PresenceTracker>>,   // What collaborators on the presence channel look at
+    reminders: Option<Arc<ReminderScheduler>>, // Fires due reminders as notifications
+    dependencies: Option<Arc<DependencyStore>>, // Blocks/blocked-by relationships between tasks
+    entity_access: Option<Arc<EntityAccessStore>>, // When entities were last viewed and modified
+    change_source: ChangeSource,              // Frontend/agent making changes through this engine
+    widgets: std::sync::RwLock<Option<WidgetRegistry>>, // Widgets the frontend renders (None = unchecked)
+    entity_schemas: std::sync::RwLock<HashMap<String, holon_api::EntitySchema>>, // Declared fields, for index advice
     // CDC connection kept alive for streaming
     // CRITICAL: This must stay alive for CDC callbacks to work
     // The callback closure captures the channel sender, which closes the stream if dropped
@@ -49,16 +153,329 @@ impl BackendEngine {
         // Operations are now provided via OperationProvider implementations
         // No legacy operations need to be registered
 
+        let soft_delete_tables = backend
+            .try_read()
+            .map_err(|_| anyhow::anyhow!("Backend is locked during BackendEngine construction"))?
+            .soft_delete_tables();
+
         Ok(Self {
             backend,
             dispatcher,
             transform_pipeline,
             table_to_entity_map: Arc::new(RwLock::new(HashMap::new())),
             undo_stack: Arc::new(RwLock::new(UndoStack::default())),
+            usage_stats: None,
+            operation_log: None,
+            id_mapping: None,
+            sync_health: None,
+            sync_dirty: None,
+            sync_reconciler: None,
+            sync_scheduler: None,
+            query_cache: Arc::new(QueryCache::new()),
+            windowed_queries: Arc::new(WindowedQueries::default()),
+            filtered_queries: Arc::new(FilteredQueries::default()),
+            live_searches: Arc::new(LiveSearches::default()),
+            soft_delete_tables,
+            trash_config: TrashConfig::default(),
+            archival_config: ArchivalConfig::default(),
+            embed_resolver: EmbedResolver::default(),
+            backlinks: None,
+            tags: None,
+            automation_rules: None,
+            annotations: None,
+            sync_blobs: None,
+            log_buffer: None,
+            identities: None,
+            maintenance: None,
+            snapshots: None,
+            replica: None,
+            view_states: None,
+            view_loader: None,
+            presence: None,
+            reminders: None,
+            dependencies: None,
+            entity_access: None,
+            change_source: ChangeSource::default(),
+            widgets: std::sync::RwLock::new(None),
+            entity_schemas: std::sync::RwLock::new(HashMap::new()),
             _cdc_conn: Arc::new(tokio::sync::Mutex::new(None)),
         })
     }
 
+    /// Attach a usage statistics store
+    ///
+    /// When attached, every operation executed via `execute_operation` is recorded
+    /// (entity, operation name, latency, success) in the local database.
+    pub fn with_usage_stats(mut self, usage_stats: Arc<OperationUsageStore>) -> Self {
+        self.usage_stats = Some(usage_stats);
+        self
+    }
+
+    /// Attach the persistent operation log
+    ///
+    /// When attached, `operation_history` returns the logged operations, and every
+    /// dispatched operation (including undo/redo and failures) is added to the audit trail.
+    pub fn with_operation_log(mut self, operation_log: Arc<OperationLogStore>) -> Self {
+        self.operation_log = Some(operation_log);
+        self
+    }
+
+    /// Attach the temporary ID mapping service
+    ///
+    /// When attached, known temporary IDs in operation parameters are replaced with
+    /// their remote IDs before dispatch, and `complete_create` rewrites logged operations.
+    pub fn with_id_mapping(mut self, id_mapping: Arc<IdMappingService>) -> Self {
+        self.id_mapping = Some(id_mapping);
+        self
+    }
+
+    /// Attach a sync health store
+    ///
+    /// When attached, every `sync` operation executed via `execute_operation` is recorded
+    /// as a success or a classified failure for the recurring sync health report.
+    pub fn with_sync_health(mut self, sync_health: Arc<SyncHealthStore>) -> Self {
+        self.sync_health = Some(sync_health);
+        self
+    }
+
+    /// Attach an unsynced-changes store
+    ///
+    /// When attached, a successful `sync` operation acknowledges the provider's pending
+    /// local changes, clearing their dirty state.
+    pub fn with_sync_dirty(mut self, sync_dirty: Arc<SyncDirtyStore>) -> Self {
+        self.sync_dirty = Some(sync_dirty);
+        self
+    }
+
+    /// Attach the reconciler whose unresolved conflicts `sync_conflicts` lists
+    pub fn with_sync_reconciler(mut self, sync_reconciler: Arc<SyncReconciler>) -> Self {
+        self.sync_reconciler = Some(sync_reconciler);
+        self
+    }
+
+    /// Attach a sync scheduler
+    ///
+    /// The scheduler only runs once `start_sync_scheduler` is called.
+    pub fn with_sync_scheduler(mut self, sync_scheduler: Arc<SyncScheduler>) -> Self {
+        self.sync_scheduler = Some(sync_scheduler);
+        self
+    }
+
+    /// Set the retention of trashed entities used by `start_trash_purge`
+    pub fn with_trash_config(mut self, trash_config: TrashConfig) -> Self {
+        self.trash_config = trash_config;
+        self
+    }
+
+    /// Set the rules applied by `start_archival` and `run_archival`
+    pub fn with_archival_config(mut self, archival_config: ArchivalConfig) -> Self {
+        self.archival_config = archival_config;
+        self
+    }
+
+    /// Attach a backlink index
+    ///
+    /// The index only follows content changes once `start_backlink_index` is called.
+    pub fn with_backlinks(mut self, backlinks: Arc<BacklinkIndex>) -> Self {
+        self.backlinks = Some(backlinks);
+        self
+    }
+
+    /// Attach a tag index
+    ///
+    /// Binds the index's `add_tag`/`remove_tag` operations to this engine's dispatcher.
+    /// The index only follows content changes once `start_tag_index` is called.
+    pub fn with_tags(mut self, tags: Arc<TagIndex>) -> Self {
+        tags.bind_dispatcher(&self.dispatcher);
+        self.tags = Some(tags);
+        self
+    }
+
+    /// Attach automation rules
+    ///
+    /// Binds the rules to this engine's dispatcher, which executes their actions.
+    /// Rules only fire once `start_automation_rules` is called.
+    pub fn with_automation_rules(mut self, automation_rules: Arc<AutomationRules>) -> Self {
+        automation_rules.bind_dispatcher(&self.dispatcher);
+        self.automation_rules = Some(automation_rules);
+        self
+    }
+
+    /// Attach the annotation store
+    ///
+    /// Rows of recreated tables get their comments back once `start_annotations` is called.
+    pub fn with_annotations(mut self, annotations: Arc<AnnotationStore>) -> Self {
+        self.annotations = Some(annotations);
+        self
+    }
+
+    /// Attach the encrypted sync log
+    ///
+    /// Binds the log to this engine's dispatcher so imported operations can be applied.
+    pub fn with_sync_blobs(mut self, sync_blobs: Arc<SyncBlobLog>) -> Self {
+        sync_blobs.bind_dispatcher(&self.dispatcher);
+        self.sync_blobs = Some(sync_blobs);
+        self
+    }
+
+    /// Attach the ring buffer of recent log events served by `recent_logs`
+    ///
+    /// The buffer only fills if its `layer()` is installed in the tracing subscriber.
+    pub fn with_log_buffer(mut self, log_buffer: LogBuffer) -> Self {
+        self.log_buffer = Some(log_buffer);
+        self
+    }
+
+    /// Attach the identity mapping used by the `resolve` query step
+    pub fn with_identities(mut self, identities: Arc<EntityIdentityStore>) -> Self {
+        self.identities = Some(identities);
+        self
+    }
+
+    /// Attach the database maintenance scheduler
+    ///
+    /// Scheduled runs only happen once `start_maintenance` is called; `run_maintenance`
+    /// works either way.
+    pub fn with_maintenance(mut self, maintenance: Arc<MaintenanceScheduler>) -> Self {
+        self.maintenance = Some(maintenance);
+        self
+    }
+
+    /// Attach the store persisting UI state of views declared with `view_id:`
+    ///
+    /// Changes are written in batches; call `start_view_state_flush` to also write
+    /// them periodically.
+    pub fn with_view_states(mut self, view_states: Arc<ViewStateStore>) -> Self {
+        self.view_states = Some(view_states);
+        self
+    }
+
+    /// Make the database an embedded replica of a remote database
+    ///
+    /// Operations are refused until the replica is bootstrapped; syncs only run once
+    /// `start_replica_sync` is called (or through `sync_replica`).
+    pub fn with_replica(mut self, replica: Arc<ReplicaSyncer>) -> Self {
+        self.replica = Some(replica);
+        self
+    }
+
+    /// Attach the tracker exchanging presence with collaborators
+    ///
+    /// Presence from other clients is only received once `start_presence` is called.
+    pub fn with_presence(mut self, presence: Arc<PresenceTracker>) -> Self {
+        self.presence = Some(presence);
+        self
+    }
+
+    /// Attach the reminder scheduler
+    ///
+    /// Reminders only fire once `start_reminders` is called.
+    pub fn with_reminders(mut self, reminders: Arc<ReminderScheduler>) -> Self {
+        self.reminders = Some(reminders);
+        self
+    }
+
+    /// Attach the snapshot store
+    ///
+    /// Snapshots are only taken periodically once `start_snapshots` is called.
+    pub fn with_snapshots(mut self, snapshots: Arc<SnapshotStore>) -> Self {
+        self.snapshots = Some(snapshots);
+        self
+    }
+
+    /// Attach the task dependency store
+    pub fn with_dependencies(mut self, dependencies: Arc<DependencyStore>) -> Self {
+        self.dependencies = Some(dependencies);
+        self
+    }
+
+    /// Attach the loader of views defined by `.prql` files
+    ///
+    /// Call `start_view_loader` to load the views and keep them up to date.
+    pub fn with_view_loader(mut self, view_loader: Arc<ViewLoader>) -> Self {
+        self.view_loader = Some(view_loader);
+        self
+    }
+
+    /// Attach the store recording when entities were last viewed and modified
+    ///
+    /// Successful operations on an entity then record a modification; views are
+    /// recorded with `record_entity_viewed`.
+    pub fn with_entity_access(mut self, entity_access: Arc<EntityAccessStore>) -> Self {
+        self.entity_access = Some(entity_access);
+        self
+    }
+
+    /// Set who makes changes through this engine (e.g. the frontend)
+    ///
+    /// Operations are attributed to this source in the undo stack and audit log,
+    /// and changes they make carry it in their `ChangeOrigin`. A source set for the
+    /// calling task via `CURRENT_CHANGE_SOURCE` takes precedence field by field.
+    pub fn with_change_source(mut self, change_source: ChangeSource) -> Self {
+        self.change_source = change_source;
+        self
+    }
+
+    /// Replace the resolver of `((block-id))` embeds (e.g. to add embed source tables)
+    pub fn with_embed_resolver(mut self, embed_resolver: EmbedResolver) -> Self {
+        self.embed_resolver = embed_resolver;
+        self
+    }
+
+    /// Replace the query cache with one using the given configuration
+    pub fn with_query_cache(mut self, config: QueryCacheConfig) -> Self {
+        self.query_cache = Arc::new(QueryCache::with_config(config));
+        self
+    }
+
+    /// Get the query cache (for stats and manual invalidation)
+    pub fn query_cache(&self) -> Arc<QueryCache> {
+        self.query_cache.clone()
+    }
+
+    /// Lock an encrypted database
+    ///
+    /// Cached query results are dropped and, until `unlock`, queries and operations fail
+    /// and watched queries receive no changes.
+    pub async fn lock(&self) -> Result<()> {
+        self.backend
+            .read()
+            .await
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Failed to lock database: {}", e))?;
+        self.query_cache.invalidate_all_rows();
+        Ok(())
+    }
+
+    /// Unlock an encrypted database with its passphrase
+    ///
+    /// Changes made while locked (e.g. by sync) aren't replayed to watched queries;
+    /// frontends should re-run their queries after unlocking.
+    pub async fn unlock(&self, passphrase: &str) -> Result<()> {
+        self.backend
+            .read()
+            .await
+            .unlock(passphrase)
+            .map_err(|e| anyhow::anyhow!("Failed to unlock database: {}", e))
+    }
+
+    pub async fn is_locked(&self) -> bool {
+        self.backend.read().await.database_lock().is_locked()
+    }
+
+    /// Change the passphrase of an encrypted database
+    pub async fn rotate_passphrase(
+        &self,
+        old_passphrase: &str,
+        new_passphrase: &str,
+    ) -> Result<()> {
+        self.backend
+            .read()
+            .await
+            .rotate_passphrase(old_passphrase, new_passphrase)
+            .map_err(|e| anyhow::anyhow!("Failed to rotate passphrase: {}", e))
+    }
+
     /// Compile a PRQL query with render() into SQL and UI specification
     ///
     /// Automatically infers operation wirings from PRQL lineage analysis.
@@ -73,9 +490,116 @@ impl BackendEngine {
     /// 6. Replaces placeholder operations with real OperationDescriptors
     /// 7. For UNION queries with row_templates, wires operations per-template using entity_name
     pub fn compile_query(&self, prql: String) -> Result<(String, RenderSpec)> {
+        let compiled = self.compile(&prql, &HashMap::new(), &HashMap::new())?;
+        Ok((compiled.sql, compiled.render_spec))
+    }
+
+    /// Register the widgets the frontend can render
+    ///
+    /// Replaces previously registered widgets. From now on, queries using other
+    /// widgets, or passing arguments a widget doesn't declare, fail to compile.
+    pub fn register_widgets(&self, widgets: Vec<WidgetSpec>) {
+        *self.widgets.write().unwrap() = Some(WidgetRegistry::new(widgets));
+        // Compiled queries were not checked against these widgets
+        self.query_cache.clear();
+    }
+
+    /// Compile a PRQL query, reusing the cached result for the same (source, params)
+    pub fn compile_query_cached(
+        &self,
+        prql: &str,
+        params: &HashMap<String, Value>,
+    ) -> Result<CompiledQuery> {
+        if let Some(compiled) = self.query_cache.get_compiled(prql, params) {
+            return Ok(compiled);
+        }
+        let compiled = self.compile(prql, &HashMap::new(), params)?;
+        self.query_cache
+            .insert_compiled(prql, params, compiled.clone());
+        Ok(compiled)
+    }
+
+    /// Register the schema of an entity's table (e.g. `Task::entity_schema()`)
+    ///
+    /// `explain_query` points out columns the schema declares as indexed but that
+    /// have no index in the database.
+    pub fn register_entity_schema(&self, schema: holon_api::EntitySchema) {
+        self.entity_schemas
+            .write()
+            .unwrap()
+            .insert(schema.name.clone(), schema);
+    }
+
+    /// SQLite's query plan of a PRQL query, mapped back to the query's source, with
+    /// suggestions of indexes that would likely make it faster (see `api::query_plan`)
+    pub async fn explain_query(&self, prql: &str) -> Result<QueryPlan> {
+        let compiled = self.compile_query_cached(prql, &HashMap::new())?;
+        let usages = query_render::query_usages(prql)?;
+
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                &format!("EXPLAIN QUERY PLAN {}", compiled.sql),
+                HashMap::new(),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to explain query: {}", e))?;
+        let steps: Vec<QueryPlanStep> = rows
+            .iter()
+            .map(|row| QueryPlanStep::from_row(row, &usages))
+            .collect();
+
+        let tables: HashSet<&str> = usages.iter().map(|usage| usage.table.as_str()).collect();
+        let mut existing = HashMap::new();
+        for table in tables {
+            let columns = indexed_columns(&backend, table)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to read indexes of {}: {}", table, e))?;
+            existing.insert(table.to_string(), columns);
+        }
+        let declared: HashMap<String, HashSet<String>> = self
+            .entity_schemas
+            .read()
+            .unwrap()
+            .values()
+            .map(|schema| {
+                let indexed = schema
+                    .fields
+                    .iter()
+                    .filter(|field| field.indexed)
+                    .map(|field| field.name.clone())
+                    .collect();
+                (schema.name.clone(), indexed)
+            })
+            .collect();
+
+        let suggestions = suggest_indexes(&steps, &usages, &declared, &existing);
+        Ok(QueryPlan {
+            sql: compiled.sql,
+            steps,
+            suggestions,
+        })
+    }
+
+    /// Compile a PRQL query; `filter_values` override the defaults of its filter widgets
+    ///
+    /// `params` give the cursors of `limit_after` steps; the SQL binds the others.
+    fn compile(
+        &self,
+        prql: &str,
+        filter_values: &HashMap<String, Option<FilterValue>>,
+        params: &HashMap<String, Value>,
+    ) -> Result<CompiledQuery> {
         // Step 1: Parse query to RQ AST with placeholder operations
-        // This gives us the RQ AST before SQL generation
-        let parsed = query_render::parse_query_render_to_rq(&prql)?;
+        // This gives us the RQ AST before SQL generation (trashed rows, rows failing the filters and rows hidden by row security removed)
+        let parsed = query_render::parse_query_render_to_rq_with_row_filters(
+            prql,
+            &self.soft_delete_tables.table_names(),
+            self.widgets.read().unwrap().as_ref(),
+            filter_values,
+            params,
+            &self.dispatcher.row_filters(),
+        )?;
         let mut render_spec = parsed.render_spec;
         let all_selected_columns = parsed.available_columns;
 
@@ -85,8 +609,20 @@ impl BackendEngine {
         // Step 3: Generate SQL from the transformed RQ
         let sql = query_render::ParsedQueryRender::to_sql_from_rq(&transformed_rq)?;
 
+        // Source tables for lineage-based cache invalidation
+        let source_tables: Vec<String> = transformed_rq
+            .tables
+            .iter()
+            .filter_map(|decl| match &decl.relation.kind {
+                RelationKind::ExternRef(TableExternRef::LocalTable(ident)) => {
+                    Some(ident.name.clone())
+                }
+                _ => None,
+            })
+            .collect();
+
         // Step 4: Extract table name from query (needed for entity lookup)
-        let table_name = self.extract_table_name_from_prql(&prql)?;
+        let table_name = self.extract_table_name_from_prql(prql)?;
 
         // Step 5: Walk the tree and enhance operations with real descriptors from dispatcher
         // Pass all selected columns as context for operation filtering
@@ -130,7 +666,11 @@ impl BackendEngine {
             )?;
         }
 
-        Ok((sql, render_spec))
+        Ok(CompiledQuery {
+            sql,
+            render_spec,
+            source_tables,
+        })
     }
 
     /// Extract table name from PRQL query string
@@ -238,6 +778,7 @@ impl BackendEngine {
                         new_operations.push(query_render::OperationWiring {
                             widget_type: name.clone(),
                             modified_param: String::new(), // Will be filled by lineage if needed
+                            accepts_multiple: op_desc.accepts_multiple_ids(),
                             descriptor: op_desc,
                         });
                     }
@@ -362,6 +903,11 @@ impl BackendEngine {
                     }
                 }
             }
+            query_render::RenderExpr::Format { value, .. } => {
+                if let AvailableColumns::All = self.collect_columns_from_expr(value, columns) {
+                    return AvailableColumns::All;
+                }
+            }
             _ => {} // Literal - no columns
         }
         AvailableColumns::Selected(columns.clone())
@@ -371,16 +917,23 @@ impl BackendEngine {
     ///
     /// Supports parameter binding by replacing `$param_name` placeholders with actual values.
     /// Parameters are bound safely using SQL parameter binding to prevent SQL injection.
+    /// Rows whose content embeds other blocks (`((block-id))`) get the resolved embeds
+    /// in their `_embeds` column.
     pub async fn execute_query(
         &self,
         sql: String,
         params: HashMap<String, Value>,
     ) -> Result<Vec<HashMap<String, Value>>> {
         let backend = self.backend.read().await;
-        backend
+        let mut rows = backend
             .execute_sql(&sql, params)
             .await
-            .map_err(|e| anyhow::anyhow!("SQL execution failed: {}", e))
+            .map_err(|e| anyhow::anyhow!("SQL execution failed: {}", e))?;
+        self.embed_resolver
+            .resolve_rows(&backend, &mut rows)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to resolve embeds: {}", e))?;
+        Ok(rows)
     }
 
     /// Watch a query for changes via CDC streaming
@@ -576,7 +1129,12 @@ impl BackendEngine {
         // by the view_name we just created to avoid mixing events from different queries
         use tokio_stream::StreamExt;
         let view_name_for_filter = view_name.clone();
+        // Nothing reaches the UI while the database is locked
+        let database_lock = backend.database_lock();
         let filtered_stream = stream.filter(move |batch| {
+            if database_lock.is_locked() {
+                return false;
+            }
             let matches = batch.metadata.relation_name == view_name_for_filter;
             if !matches {
                 tracing::debug!(
@@ -611,6 +1169,7 @@ impl BackendEngine {
     ///
     /// This combines `compile_query`, `execute_query`, and `watch_query` into a single call.
     /// Returns the render specification, current table data, and a stream of ongoing changes.
+    /// Compilation and (within the row TTL) the current data are served from the query cache.
     ///
     /// # Returns
     /// A tuple containing:
@@ -634,13 +1193,260 @@ impl BackendEngine {
             }
         );
 
-        let (sql, render_spec) = self.compile_query(prql)?;
-        let current_data = self.execute_query(sql.clone(), params.clone()).await?;
-        let change_stream = self.watch_query(sql, params).await?;
+        // Don't serve cached rows of a locked database
+        self.backend
+            .read()
+            .await
+            .database_lock()
+            .check()
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let compiled = self.compile_query_cached(&prql, &params)?;
+        let now = chrono::Utc::now().timestamp_millis();
+        let current_data = match self.query_cache.get_rows(&prql, &params, now) {
+            Some(rows) => rows,
+            None => {
+                let rows = self
+                    .execute_query(compiled.sql.clone(), params.clone())
+                    .await?;
+                self.query_cache
+                    .insert_rows(&prql, &params, rows.clone(), now);
+                rows
+            }
+        };
+        let change_stream = self.watch_query(compiled.sql, params).await?;
+        let render_spec = self.load_view_state(compiled.render_spec).await?;
 
         Ok((render_spec, current_data, change_stream))
     }
 
+    /// Compile a PRQL query and watch a window of its result
+    ///
+    /// Unlike `query_and_watch`, only the rows of `window` are sent to the frontend,
+    /// so lists over huge results can be virtualized. The returned stream starts with
+    /// the window's rows and then carries changes with indices relative to the window;
+    /// move the window with `set_query_window` as the user scrolls. Rows are ordered by
+    /// the query's `sort_by`/`group_by`.
+    ///
+    /// # Returns
+    /// A tuple containing:
+    /// - `RenderSpec`: UI rendering specification from the PRQL query
+    /// - `u64`: Id of the windowed query, for `set_query_window`/`close_windowed_query`
+    /// - `WindowChangeStream`: The window's rows followed by changes to them
+    pub async fn query_windowed(
+        &self,
+        prql: String,
+        params: HashMap<String, Value>,
+        window: Window,
+    ) -> Result<(RenderSpec, u64, WindowChangeStream)> {
+        let (render_spec, rows, mut changes) = self.query_and_watch(prql, params).await?;
+        let result = WindowedResult::new(rows, render_spec.row_order());
+        let (id, stream) = self.windowed_queries.open(result, window).await;
+
+        let queries = Arc::clone(&self.windowed_queries);
+        tokio::spawn(async move {
+            use tokio_stream::StreamExt;
+            while let Some(batch) = changes.next().await {
+                let changes = batch.inner.items.into_iter().map(|c| c.change);
+                if !queries.apply(id, changes).await {
+                    break;
+                }
+            }
+            queries.close(id);
+        });
+
+        Ok((render_spec, id, stream))
+    }
+
+    /// Move the window of a query opened with `query_windowed`
+    ///
+    /// The query's stream gets the rows of the new window; changes sent after them
+    /// are relative to it.
+    pub async fn set_query_window(&self, query_id: u64, window: Window) -> Result<()> {
+        self.windowed_queries.set_window(query_id, window).await
+    }
+
+    /// Stop watching a query opened with `query_windowed`; its stream ends
+    pub fn close_windowed_query(&self, query_id: u64) -> bool {
+        self.windowed_queries.close(query_id)
+    }
+
+    /// Read a page of a tree query with a `limit_after <n> sortkey:@cursor` step
+    ///
+    /// `cursor` is the token returned for the previous page (`None` for the first);
+    /// it is passed to the query as the `cursor` parameter. Pages are read once, not
+    /// watched: rows are ordered and limited, which materialized views can't do.
+    ///
+    /// # Returns
+    /// A tuple containing:
+    /// - `RenderSpec`: UI rendering specification from the PRQL query
+    /// - `Vec<Entity>`: The rows of the page
+    /// - `Option<String>`: Cursor token of the next page (after the page's last row,
+    ///   by its `parent_id` and `sort_key`), `None` if the page is empty
+    pub async fn query_page(
+        &self,
+        prql: String,
+        mut params: HashMap<String, Value>,
+        cursor: Option<String>,
+    ) -> Result<(RenderSpec, Vec<HashMap<String, Value>>, Option<String>)> {
+        let mut next = match &cursor {
+            Some(token) => TreeCursor::from_token(token)
+                .map_err(|e| anyhow::anyhow!("Invalid tree cursor: {}", e))?,
+            None => TreeCursor::default(),
+        };
+        params.insert(
+            "cursor".to_string(),
+            cursor.map(Value::String).unwrap_or(Value::Null),
+        );
+
+        let compiled = self.compile_query_cached(&prql, &params)?;
+        let rows = self.execute_query(compiled.sql, params).await?;
+        let render_spec = self.load_view_state(compiled.render_spec).await?;
+
+        let next = rows.last().map(|row| {
+            next.advance_past(row);
+            next.to_token()
+        });
+        Ok((render_spec, rows, next))
+    }
+
+    /// Compile a PRQL query and watch its result, re-running it when its filters change
+    ///
+    /// Like `query_and_watch`, but filter widgets of the query (`toggle_filter`,
+    /// `date_filter`, listed in `RenderSpec::filters`) can be set with
+    /// `set_query_filter`: the query is recompiled with the new filter values and the
+    /// rows leaving, entering or changing in its result are sent through the returned
+    /// stream, which then carries the changes of the re-filtered query.
+    ///
+    /// # Returns
+    /// A tuple containing:
+    /// - `RenderSpec`: UI rendering specification from the PRQL query
+    /// - `Vec<Entity>`: Current query results, with the filters' defaults applied
+    /// - `u64`: Id of the filtered query, for `set_query_filter`/`close_filtered_query`
+    /// - `RowChangeStream`: Stream of ongoing changes to the query results
+    pub async fn query_filtered(
+        &self,
+        prql: String,
+        params: HashMap<String, Value>,
+    ) -> Result<(
+        RenderSpec,
+        Vec<HashMap<String, Value>>,
+        u64,
+        RowChangeStream,
+    )> {
+        let (render_spec, rows, changes) =
+            self.query_and_watch(prql.clone(), params.clone()).await?;
+        let source = FilteredQuerySource {
+            prql,
+            params,
+            filter_values: HashMap::new(),
+        };
+        let (id, stream) = self.filtered_queries.open(source, &rows, changes).await;
+        Ok((render_spec, rows, id, stream))
+    }
+
+    /// Set a filter of a query opened with `query_filtered`
+    ///
+    /// `value` of `None` turns the filter off; its default no longer applies either.
+    pub async fn set_query_filter(
+        &self,
+        query_id: u64,
+        name: &str,
+        value: Option<FilterValue>,
+    ) -> Result<()> {
+        let source = self.filtered_queries.source(query_id).await?;
+        let mut filter_values = source.filter_values;
+        filter_values.insert(name.to_string(), value);
+
+        let compiled = self.compile(&source.prql, &filter_values, &source.params)?;
+        let rows = self
+            .execute_query(compiled.sql.clone(), source.params.clone())
+            .await?;
+        let changes = self.watch_query(compiled.sql, source.params).await?;
+        self.filtered_queries
+            .refilter(query_id, filter_values, rows, changes)
+            .await
+    }
+
+    /// Stop watching a query opened with `query_filtered`; its stream ends
+    pub fn close_filtered_query(&self, query_id: u64) -> bool {
+        self.filtered_queries.close(query_id)
+    }
+
+    /// Compile a search query and run it, for search-as-you-type with `live_search`
+    ///
+    /// `text_param` names the parameter bound to the search text, e.g. `search` for
+    /// `filter (content ~= $search)`; it defaults to the empty string if `params`
+    /// doesn't set it.
+    ///
+    /// # Returns
+    /// A tuple containing:
+    /// - `RenderSpec`: UI rendering specification from the PRQL query
+    /// - `Vec<Entity>`: Current search results
+    /// - `u64`: Id of the search, for `live_search`/`close_live_search`
+    /// - `RowChangeStream`: Rows entering, leaving or changing in the result as the text changes
+    pub async fn open_live_search(
+        &self,
+        prql: String,
+        mut params: HashMap<String, Value>,
+        text_param: &str,
+    ) -> Result<(
+        RenderSpec,
+        Vec<HashMap<String, Value>>,
+        u64,
+        RowChangeStream,
+    )> {
+        params
+            .entry(text_param.to_string())
+            .or_insert_with(|| Value::String(String::new()));
+        let compiled = self.compile_query_cached(&prql, &params)?;
+        let rows = self
+            .execute_query(compiled.sql.clone(), params.clone())
+            .await?;
+        let render_spec = self.load_view_state(compiled.render_spec).await?;
+
+        let source = LiveSearchSource {
+            sql: compiled.sql,
+            params,
+            text_param: text_param.to_string(),
+        };
+        let (id, stream) = self.live_searches.open(source, &rows);
+        Ok((render_spec, rows, id, stream))
+    }
+
+    /// Re-run a search opened with `open_live_search` for new text
+    ///
+    /// Runs once typing pauses; a newer call cancels a pending or running one, so
+    /// only the result of the latest text is sent.
+    pub async fn live_search(&self, query_id: u64, text: &str) -> Result<()> {
+        self.live_searches
+            .search(query_id, text, |sql, params| {
+                self.execute_query(sql, params)
+            })
+            .await
+    }
+
+    /// Stop a search opened with `open_live_search`; its stream ends
+    pub fn close_live_search(&self, query_id: u64) -> bool {
+        self.live_searches.close(query_id)
+    }
+
+    /// Resolve a drop position in a windowed query's rows into `move_block` params
+    ///
+    /// `drop_index` is the row index in the whole result the dragged row is dropped
+    /// at. Returns the `parent_id` and `after_block_id` that operations triggered by
+    /// `tree_position` (see `ParamMapping`) take, so frontends don't have to work out
+    /// the tree structure of the rendered rows themselves.
+    pub async fn resolve_tree_position(
+        &self,
+        query_id: u64,
+        drop_index: usize,
+    ) -> Result<HashMap<String, Value>> {
+        self.windowed_queries
+            .tree_position(query_id, drop_index)
+            .await
+    }
+
     /// Execute a block operation
     ///
     /// This method provides a clean interface for executing operations without exposing
@@ -675,8 +1481,24 @@ impl BackendEngine {
         op_name: &str,
         params: StorageEntity,
     ) -> Result<()> {
-        use tracing::info;
+        self.execute_operation_with_key(entity_name, op_name, params, None)
+            .await
+    }
+
+    /// Execute an operation under `idempotency_key`, e.g. one derived from a request ID
+    ///
+    /// If an operation with the same key already succeeded, nothing is executed (see
+    /// `OperationDispatcher::execute`), so a retried request isn't applied twice.
+    /// Without a key, the operation gets a fresh one, as with `execute_operation`.
+    pub async fn execute_operation_with_key(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        mut params: StorageEntity,
+        idempotency_key: Option<String>,
+    ) -> Result<()> {
         use tracing::Instrument;
+        use tracing::info;
 
         // Create tracing span that will be bridged to OpenTelemetry
         // Use .instrument() to maintain context across async boundaries
@@ -686,35 +1508,117 @@ impl BackendEngine {
             "operation.entity" = entity_name,
             "operation.name" = op_name
         );
+        let source = self.current_change_source();
 
-        async {
+        let execute = async {
             info!(
                 "[BackendEngine] execute_operation: entity={}, op={}, params={:?}",
                 entity_name, op_name, params
             );
 
-            // Build original operation for undo stack
-            let original_op = Operation::new(
+            if let Some(key) = &idempotency_key
+                && self.dispatcher.is_completed(key).await
+            {
+                info!(
+                    "[BackendEngine] Skipping operation executed before: entity={}, op={}, key={}",
+                    entity_name, op_name, key
+                );
+                return Ok(());
+            }
+
+            // Changes made before the replica is downloaded from its remote would be lost
+            if let Some(replica) = &self.replica {
+                replica
+                    .check_writable()
+                    .map_err(|e| anyhow::anyhow!("{}", e))?;
+            }
+
+            if let Some(id_mapping) = &self.id_mapping
+                && id_mapping.resolve_params(&mut params)
+            {
+                debug!(
+                    "[BackendEngine] Resolved temporary IDs in params: {:?}",
+                    params
+                );
+            }
+
+            // Build original operation for undo stack; providers see its idempotency key
+            let mut original_op = Operation::new(
                 entity_name,
                 op_name,
                 "", // display_name will be set from OperationDescriptor if needed
                 params.clone(),
             );
+            if let Some(key) = idempotency_key {
+                original_op = original_op.with_idempotency_key(key);
+            }
+
+            let entity_id = params.get("id").and_then(|id| id.as_string_owned());
 
             // Execute via dispatcher using entity_name
             // Span context will be propagated via tracing-opentelemetry bridge
-            let inverse_result = self.dispatcher
-                .execute_operation(entity_name, op_name, params)
+            let started_at = std::time::Instant::now();
+            let inverse_result = match self.scheduled_sync_all(entity_name, op_name).await {
+                Some(result) => result,
+                None => self.dispatcher.execute(original_op.clone()).await,
+            };
+
+            self.record_audit(&original_op, &inverse_result, started_at, &source)
                 .await;
 
+            if let Some(usage_stats) = &self.usage_stats {
+                let latency_ms = started_at.elapsed().as_millis() as i64;
+                if let Err(e) = usage_stats
+                    .record(entity_name, op_name, latency_ms, inverse_result.is_ok())
+                    .await
+                {
+                    tracing::warn!("[BackendEngine] Failed to record operation usage: {}", e);
+                }
+            }
+
+            if let Some(sync_dirty) = &self.sync_dirty
+                && op_name == "sync"
+                && inverse_result.is_ok()
+            {
+                let provider_name = entity_name.strip_suffix(".sync").unwrap_or(entity_name);
+                let synced_at =
+                    chrono::Utc::now().timestamp_millis() - started_at.elapsed().as_millis() as i64;
+                if let Err(e) = sync_dirty
+                    .mark_provider_synced(provider_name, synced_at)
+                    .await
+                {
+                    tracing::warn!(
+                        "[BackendEngine] Failed to acknowledge synced changes: {}",
+                        e
+                    );
+                }
+            }
+
+            if let Some(sync_health) = &self.sync_health
+                && op_name == "sync"
+            {
+                let provider_name = entity_name.strip_suffix(".sync").unwrap_or(entity_name);
+                let recorded = match &inverse_result {
+                    Ok(_) => sync_health.record_success(provider_name, 0, 0).await,
+                    Err(e) => {
+                        sync_health
+                            .record_failure(provider_name, &e.to_string())
+                            .await
+                    }
+                };
+                if let Err(e) = recorded {
+                    tracing::warn!("[BackendEngine] Failed to record sync health: {}", e);
+                }
+            }
+
             match &inverse_result {
-                Ok(UndoAction::Undo(_)) => {
+                Ok(action) if action.is_reversible() => {
                     info!(
                         "[BackendEngine] execute_operation succeeded: entity={}, op={} (inverse operation available)",
                         entity_name, op_name
                     );
                 }
-                Ok(UndoAction::Irreversible) => {
+                Ok(_) => {
                     info!(
                         "[BackendEngine] execute_operation succeeded: entity={}, op={} (no inverse operation)",
                         entity_name, op_name
@@ -723,108 +1627,1607 @@ impl BackendEngine {
                 Err(e) => {
                     tracing::error!(
                         "[BackendEngine] Operation '{}' on entity '{}' failed: {}",
-                        op_name, entity_name, e
+                        op_name,
+                        entity_name,
+                        e
                     );
                 }
             }
 
-            // If operation succeeded and has an inverse, push to undo stack
-            if let Ok(UndoAction::Undo(inverse_op)) = &inverse_result {
-                let mut undo_stack = self.undo_stack.write().await;
-                undo_stack.push(original_op, inverse_op.clone());
+            if inverse_result.is_ok() {
+                self.invalidate_cached_rows(entity_name, op_name).await;
             }
 
-            inverse_result.map(|_| ()).map_err(|e| {
-                anyhow::anyhow!(
-                    "Operation '{}' on entity '{}' failed: {}",
-                    op_name,
-                    entity_name,
-                    e
+            if let (Some(entity_access), Some(entity_id)) = (&self.entity_access, &entity_id)
+                && inverse_result.is_ok()
+            {
+                let now = chrono::Utc::now().timestamp_millis();
+                match entity_access
+                    .record_modified(entity_name, entity_id, now)
+                    .await
+                {
+                    Ok(()) => self.query_cache.invalidate_table(ACCESS_ENTITY),
+                    Err(e) => {
+                        tracing::warn!(
+                            "[BackendEngine] Failed to record entity modification: {}",
+                            e
+                        )
+                    }
+                }
+            }
+
+            // The web target's database lives in memory; save it shortly after changes
+            #[cfg(target_arch = "wasm32")]
+            if inverse_result.is_ok() {
+                TursoBackend::schedule_persist(self.backend.clone()).await;
+            }
+
+            // If operation succeeded and has an inverse, push to undo stack
+            if let Ok(action) = &inverse_result {
+                if action.is_reversible() {
+                    let mut undo_stack = self.undo_stack.write().await;
+                    undo_stack.push_composite_from(
+                        original_op,
+                        action.clone().into_operations(),
+                        Some(source.clone()),
+                    );
+                }
+            }
+
+            inverse_result
+                .map(|_| ())
+                .map_err(|e| operation_error(op_name, entity_name, e))
+        };
+        CURRENT_CHANGE_SOURCE
+            .scope(source.clone(), execute.instrument(span))
+            .await
+    }
+
+    /// Source of changes made by the calling task: its `CURRENT_CHANGE_SOURCE`,
+    /// completed by the engine's source and this device's sync ID
+    fn current_change_source(&self) -> ChangeSource {
+        let mut source = ChangeSource::current()
+            .unwrap_or_default()
+            .or(&self.change_source);
+        if source.device_id.is_none()
+            && let Some(sync_blobs) = &self.sync_blobs
+        {
+            let device_id = sync_blobs.device_id();
+            if !device_id.is_empty() {
+                source.device_id = Some(device_id);
+            }
+        }
+        source
+    }
+
+    /// Undo the last undo step
+    ///
+    /// Executes the inverse operations of the step (a single operation, a coalesced
+    /// burst of field edits, or an explicit group) and pushes the step to the redo stack.
+    /// Returns true if a step was undone, false if the undo stack is empty.
+    pub async fn undo(&self) -> Result<bool> {
+        // Pop the inverse operations from undo stack (automatically moves to redo stack)
+        let inverse_ops = {
+            let mut undo_stack = self.undo_stack.write().await;
+            undo_stack
+                .pop_for_undo()
+                .ok_or_else(|| anyhow::anyhow!("Nothing to undo"))?
+        };
+        self.execute_undo_step(inverse_ops).await
+    }
+
+    /// Undo the last step made by a matching source (see `ChangeSource::matches`)
+    ///
+    /// E.g. `ChangeSource::default().with_agent("mcp:assistant")` reverts the last change
+    /// of that assistant, even if the user made changes after it. Returns an error if no
+    /// step of the source is left.
+    pub async fn undo_from(&self, filter: &ChangeSource) -> Result<bool> {
+        let inverse_ops = {
+            let mut undo_stack = self.undo_stack.write().await;
+            undo_stack
+                .pop_for_undo_from(filter)
+                .ok_or_else(|| anyhow::anyhow!("Nothing to undo from {:?}", filter))?
+        };
+        self.execute_undo_step(inverse_ops).await
+    }
+
+    /// Execute the inverse operations of a step popped for undo
+    async fn execute_undo_step(&self, inverse_ops: Vec<Operation>) -> Result<bool> {
+        let source = self.current_change_source();
+        for (index, inverse_op) in inverse_ops.into_iter().enumerate() {
+            // Execute the inverse operation. It's a new operation rather than a retry,
+            // since a step can be undone again after it was redone.
+            let inverse_op = inverse_op.with_idempotency_key(uuid::Uuid::new_v4().to_string());
+            let started_at = std::time::Instant::now();
+            let result = CURRENT_CHANGE_SOURCE
+                .scope(source.clone(), self.dispatcher.execute(inverse_op.clone()))
+                .await;
+            self.record_audit(&inverse_op, &result, started_at, &source)
+                .await;
+            let new_inverse =
+                result.map_err(|e| anyhow::anyhow!("Failed to execute undo operation: {}", e))?;
+            self.invalidate_cached_rows(&inverse_op.entity_name, &inverse_op.op_name)
+                .await;
+
+            // Update the redo stack with the new inverse operations
+            // The UndoStack already moved the step to the redo stack,
+            // but we need to update it with the new inverse we got from execution
+            if new_inverse.is_reversible() {
+                let mut undo_stack = self.undo_stack.write().await;
+                undo_stack.update_redo_top(index, new_inverse.into_operations());
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Redo the last undone step
+    ///
+    /// Executes the operations of the last undone step and pushes it back to the undo stack.
+    /// Returns true if a step was redone, false if the redo stack is empty.
+    pub async fn redo(&self) -> Result<bool> {
+        // Pop the operations to redo from redo stack (automatically moves back to undo stack)
+        let operations_to_redo = {
+            let mut undo_stack = self.undo_stack.write().await;
+            undo_stack
+                .pop_for_redo()
+                .ok_or_else(|| anyhow::anyhow!("Nothing to redo"))?
+        };
+
+        let source = self.current_change_source();
+        for (index, operation_to_redo) in operations_to_redo.into_iter().enumerate() {
+            // Execute the operation to redo, as a new operation (see `execute_undo_step`)
+            let operation_to_redo =
+                operation_to_redo.with_idempotency_key(uuid::Uuid::new_v4().to_string());
+            let started_at = std::time::Instant::now();
+            let result = CURRENT_CHANGE_SOURCE
+                .scope(
+                    source.clone(),
+                    self.dispatcher.execute(operation_to_redo.clone()),
                 )
-            })
+                .await;
+            self.record_audit(&operation_to_redo, &result, started_at, &source)
+                .await;
+            let new_inverse =
+                result.map_err(|e| anyhow::anyhow!("Failed to execute redo operation: {}", e))?;
+            self.invalidate_cached_rows(&operation_to_redo.entity_name, &operation_to_redo.op_name)
+                .await;
+
+            // Update the undo stack with the new inverse operations
+            // The UndoStack already moved the step back to the undo stack,
+            // but we need to update it with the new inverse we got from execution
+            if new_inverse.is_reversible() {
+                let mut undo_stack = self.undo_stack.write().await;
+                undo_stack.update_undo_top(index, new_inverse.into_operations());
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Record a dispatched operation in the audit trail of the operation log, if attached
+    async fn record_audit<E: std::fmt::Display>(
+        &self,
+        operation: &Operation,
+        result: &std::result::Result<UndoAction, E>,
+        started_at: std::time::Instant,
+        source: &ChangeSource,
+    ) {
+        let Some(operation_log) = &self.operation_log else {
+            return;
+        };
+        let error = result.as_ref().err().map(|e| e.to_string());
+        let outcome = match result {
+            Ok(undo_action) => Ok(undo_action),
+            Err(_) => Err(error.as_deref().unwrap_or_default()),
+        };
+        let duration_ms = started_at.elapsed().as_millis() as i64;
+        if let Err(e) = operation_log
+            .record_audit(operation, outcome, duration_ms, Some(source))
+            .await
+        {
+            tracing::warn!("[BackendEngine] Failed to record audit log entry: {}", e);
+        }
+    }
+
+    /// Drop cached query rows that may be affected by an operation on `entity_name`
+    ///
+    /// Sync operations write to tables we can't name up front, so they drop all rows.
+    async fn invalidate_cached_rows(&self, entity_name: &str, op_name: &str) {
+        if op_name == "sync" {
+            self.query_cache.invalidate_all_rows();
+            return;
+        }
+        self.query_cache.invalidate_table(entity_name);
+        let table_to_entity = self.table_to_entity_map.read().await;
+        for (table, entity) in table_to_entity.iter() {
+            if entity == entity_name {
+                self.query_cache.invalidate_table(table);
+            }
+        }
+    }
+
+    /// Start an undo group
+    ///
+    /// All operations executed until the matching `end_undo_group` are undone as one step.
+    /// Frontends use this for multi-operation user actions (paste, drag of several blocks).
+    pub async fn begin_undo_group(&self, display_name: Option<&str>) {
+        self.undo_stack.write().await.begin_group(display_name);
+    }
+
+    /// Close the innermost undo group
+    pub async fn end_undo_group(&self) {
+        self.undo_stack.write().await.end_group();
+    }
+
+    /// Execute an operation once per selected entity, undone as a single step
+    ///
+    /// Each element of `rows` holds the parameters for one entity (typically its row
+    /// data with batch-wide values like `completed` applied). Stops at the first
+    /// failure; operations executed before it stay applied. Returns the number of
+    /// executed operations.
+    pub async fn execute_batch_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        display_name: &str,
+        rows: Vec<StorageEntity>,
+    ) -> Result<usize> {
+        let total = rows.len();
+        let mut executed = 0;
+        let mut failure = None;
+
+        self.begin_undo_group(Some(display_name)).await;
+        for params in rows {
+            match self.execute_operation(entity_name, op_name, params).await {
+                Ok(()) => executed += 1,
+                Err(e) => {
+                    failure = Some(e);
+                    break;
+                }
+            }
+        }
+        self.end_undo_group().await;
+
+        match failure {
+            Some(e) => Err(e.context(format!(
+                "Batch '{}' stopped after {} of {} entities",
+                op_name, executed, total
+            ))),
+            None => Ok(executed),
+        }
+    }
+
+    /// Set the window (ms) within which edits of the same field collapse into one undo step
+    pub async fn set_undo_coalesce_window_ms(&self, window_ms: i64) {
+        self.undo_stack
+            .write()
+            .await
+            .set_coalesce_window_ms(window_ms);
+    }
+
+    /// Check if undo is available
+    pub async fn can_undo(&self) -> bool {
+        self.undo_stack.read().await.can_undo()
+    }
+
+    /// Check if redo is available
+    pub async fn can_redo(&self) -> bool {
+        self.undo_stack.read().await.can_redo()
+    }
+
+    /// Record that an optimistic create completed and the entity got its remote ID
+    ///
+    /// Rewrites undo/redo steps and logged operations that reference `temp_id`, and
+    /// emits an ID change to `subscribe_id_changes` subscribers. Returns the number of
+    /// rewritten operation log entries.
+    pub async fn complete_create(&self, temp_id: &str, remote_id: &str) -> Result<usize> {
+        self.undo_stack.write().await.remap_id(temp_id, remote_id);
+        match &self.id_mapping {
+            Some(id_mapping) => id_mapping
+                .complete_create(temp_id, remote_id)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to remap {}: {}", temp_id, e)),
+            None => Ok(0),
+        }
+    }
+
+    /// Subscribe to ID changes of completed optimistic creates
+    ///
+    /// Each change is a `ColumnChange` keyed by the temporary ID with the remote ID in
+    /// its `id` column. Returns None if no ID mapping service is attached.
+    pub fn subscribe_id_changes(&self) -> Option<tokio::sync::broadcast::Receiver<MapChange>> {
+        self.id_mapping
+            .as_ref()
+            .map(|id_mapping| id_mapping.subscribe())
+    }
+
+    /// Get the most recent operations from the operation log, newest first
+    ///
+    /// Returns an empty list if no operation log is attached.
+    pub async fn operation_history(&self, limit: usize) -> Result<Vec<OperationLogEntry>> {
+        match &self.operation_log {
+            Some(operation_log) => operation_log
+                .recent_operations(limit)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load operation history: {}", e)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Audit entries created in `[since, until)` (Unix ms), oldest first
+    ///
+    /// Returns an empty list if no operation log is attached. The same data is
+    /// queryable as the `operation_audit_log` table.
+    pub async fn audit_log(
+        &self,
+        since: Option<i64>,
+        until: Option<i64>,
+    ) -> Result<Vec<AuditLogEntry>> {
+        match &self.operation_log {
+            Some(operation_log) => operation_log
+                .audit_entries(since, until)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load audit log: {}", e)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Audit entries created in `[since, until)` (Unix ms) by a matching source
+    /// (see `ChangeSource::matches`), oldest first
+    pub async fn audit_log_from(
+        &self,
+        filter: &ChangeSource,
+        since: Option<i64>,
+        until: Option<i64>,
+    ) -> Result<Vec<AuditLogEntry>> {
+        match &self.operation_log {
+            Some(operation_log) => operation_log
+                .audit_entries_from(filter, since, until)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load audit log: {}", e)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Export audit entries created in `[since, until)` as JSONL or CSV
+    ///
+    /// Returns the number of exported entries.
+    pub async fn export_audit_log(
+        &self,
+        format: AuditExportFormat,
+        since: Option<i64>,
+        until: Option<i64>,
+        writer: &mut (dyn std::io::Write + Send),
+    ) -> Result<usize> {
+        let operation_log = self
+            .operation_log
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No operation log attached"))?;
+        operation_log
+            .export_audit_log(format, since, until, writer)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to export audit log: {}", e))
+    }
+
+    /// Get local operation usage statistics, most frequently used first
+    ///
+    /// Returns an empty list if no usage store is attached or statistics are switched off.
+    pub async fn operation_usage_stats(&self) -> Result<Vec<OperationUsageEntry>> {
+        match &self.usage_stats {
+            Some(usage_stats) => usage_stats
+                .all_stats()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load operation usage: {}", e)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Get the `limit` most frequently used operations (e.g., for keybinding suggestions)
+    pub async fn top_operations(&self, limit: usize) -> Result<Vec<OperationUsageEntry>> {
+        match &self.usage_stats {
+            Some(usage_stats) => usage_stats
+                .top_operations(limit)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load operation usage: {}", e)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Turn usage statistics recording on or off
+    ///
+    /// Fails to turn it on when `HOLON_DISABLE_USAGE_STATS` switched it off at startup.
+    pub fn set_usage_stats_enabled(&self, enabled: bool) -> Result<()> {
+        if let Some(usage_stats) = &self.usage_stats {
+            usage_stats
+                .set_enabled(enabled)
+                .map_err(|e| anyhow::anyhow!("Failed to change usage statistics: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Delete all recorded usage statistics
+    pub async fn clear_usage_stats(&self) -> Result<()> {
+        if let Some(usage_stats) = &self.usage_stats {
+            usage_stats
+                .clear()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to clear operation usage: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Get the most recent sync health report, if any
+    pub async fn latest_sync_health_report(&self) -> Result<Option<SyncHealthReport>> {
+        match &self.sync_health {
+            Some(sync_health) => sync_health
+                .latest_report()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load sync health report: {}", e)),
+            None => Ok(None),
+        }
+    }
+
+    /// Generate a sync health report now, regardless of when the last one was generated
+    pub async fn generate_sync_health_report(&self) -> Result<Option<SyncHealthReport>> {
+        match &self.sync_health {
+            Some(sync_health) => sync_health
+                .generate_report(chrono::Utc::now().timestamp_millis())
+                .await
+                .map(Some)
+                .map_err(|e| anyhow::anyhow!("Failed to generate sync health report: {}", e)),
+            None => Ok(None),
+        }
+    }
+
+    /// Unsynced local changes per provider
+    pub async fn unsynced_changes_summary(&self) -> Result<Vec<ProviderDirtyStatus>> {
+        match &self.sync_dirty {
+            Some(sync_dirty) => sync_dirty
+                .summary()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to summarize unsynced changes: {}", e)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Entities with unsynced local changes, optionally restricted to one provider
+    ///
+    /// The same data is queryable as the `sync_dirty` table (join on `entity_id`).
+    pub async fn unsynced_entities(&self, provider_name: Option<&str>) -> Result<Vec<DirtyEntity>> {
+        match &self.sync_dirty {
+            Some(sync_dirty) => sync_dirty
+                .dirty_entities(provider_name)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load unsynced entities: {}", e)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Whether an entity has local changes its provider hasn't acknowledged yet
+    pub async fn has_unsynced_changes(&self, entity_name: &str, entity_id: &str) -> Result<bool> {
+        match &self.sync_dirty {
+            Some(sync_dirty) => sync_dirty
+                .is_dirty(entity_name, entity_id)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to check unsynced changes: {}", e)),
+            None => Ok(false),
+        }
+    }
+
+    /// Sync conflicts no merge strategy resolved, optionally restricted to one entity type
+    ///
+    /// The same data is queryable as the `sync_conflicts` table (join on `entity_id`).
+    pub async fn sync_conflicts(&self, entity_name: Option<&str>) -> Result<Vec<SyncConflict>> {
+        match &self.sync_reconciler {
+            Some(sync_reconciler) => sync_reconciler
+                .conflicts(entity_name)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load sync conflicts: {}", e)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Resolve a sync conflict by keeping the local value or taking the remote one
+    ///
+    /// Taking the remote value sets the field through the entity's `set_field`
+    /// operation, so it is undoable and synced like any other change.
+    pub async fn resolve_sync_conflict(&self, conflict_id: &str, use_remote: bool) -> Result<()> {
+        let sync_reconciler = self
+            .sync_reconciler
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Sync conflict resolution is not configured"))?;
+        let conflict = sync_reconciler
+            .conflict(conflict_id)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load sync conflict: {}", e))?
+            .ok_or_else(|| anyhow::anyhow!("Sync conflict '{}' not found", conflict_id))?;
+
+        if use_remote {
+            let params = HashMap::from([
+                ("id".to_string(), Value::String(conflict.entity_id.clone())),
+                ("field".to_string(), Value::String(conflict.field.clone())),
+                ("value".to_string(), conflict.remote()),
+            ]);
+            self.execute_operation(&conflict.entity_name, "set_field", params)
+                .await?;
+        }
+
+        sync_reconciler
+            .dismiss(conflict_id)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to dismiss sync conflict: {}", e))
+    }
+
+    /// Start generating recurring (weekly) sync health reports in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_sync_health_reports(&self) {
+        if let Some(sync_health) = &self.sync_health {
+            sync_health.clone().spawn_recurring_reports();
+        }
+    }
+
+    /// Start syncing all registered providers periodically in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_sync_scheduler(&self) {
+        if let Some(sync_scheduler) = &self.sync_scheduler {
+            sync_scheduler.clone().spawn();
+        }
+    }
+
+    /// Start checkpointing, vacuuming and analyzing the database in idle periods
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_maintenance(&self) {
+        if let Some(maintenance) = &self.maintenance {
+            maintenance.clone().spawn();
+        }
+    }
+
+    /// Write pending view state changes every few seconds
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_view_state_flush(&self) {
+        if let Some(view_states) = &self.view_states {
+            view_states.clone().spawn_flush(VIEW_STATE_FLUSH_INTERVAL);
+        }
+    }
+
+    /// Track due dates and fire reminders in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_reminders(&self) {
+        if let Some(reminders) = &self.reminders {
+            reminders.clone().spawn();
+        }
+    }
+
+    /// Show notifications of background subsystems (reminders, sync health
+    /// reports) through `sink`, e.g. natively in a frontend
+    pub fn add_notification_sink(&self, sink: Arc<dyn NotificationSink>) {
+        if let Some(reminders) = &self.reminders {
+            reminders.add_notification_sink(sink.clone());
+        }
+        if let Some(sync_health) = &self.sync_health {
+            sync_health.add_notification_sink(sink);
+        }
+    }
+
+    /// Tasks whose last open blocker gets completed from now on
+    pub fn subscribe_actionable(&self) -> Result<tokio::sync::broadcast::Receiver<TaskActionable>> {
+        Ok(self.require_dependencies()?.subscribe())
+    }
+
+    /// Whether a task has blockers that aren't completed
+    pub async fn is_blocked(&self, entity_name: &str, task_id: &str) -> Result<bool> {
+        self.require_dependencies()?
+            .is_blocked(entity_name, task_id)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to read dependencies: {}", e))
+    }
+
+    /// Load the view files and recompile them whenever they change
+    ///
+    /// The directory is polled until the engine is dropped. Changes are sent to
+    /// `subscribe_views` subscribers, so frontends can re-run an open view.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_view_loader(self: &Arc<Self>) {
+        let Some(view_loader) = self.view_loader.clone() else {
+            return;
+        };
+        let engine = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(view_loader.config().poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let Some(engine) = engine.upgrade() else {
+                    break;
+                };
+                if let Err(e) = engine.reload_views() {
+                    debug!("[ViewLoader] {}", e);
+                }
+            }
+        });
+    }
+
+    /// Recompile changed view files now
+    pub fn reload_views(&self) -> Result<Vec<ViewEvent>> {
+        self.require_view_loader()?.reload(|prql| {
+            let (_, render_spec) = self.compile_query(prql.to_string())?;
+            Ok(render_spec)
+        })
+    }
+
+    /// Views with a valid definition, by name
+    pub fn views(&self) -> Result<Vec<ViewDefinition>> {
+        Ok(self.require_view_loader()?.views())
+    }
+
+    /// Last valid definition of the view defined by `<name>.prql`
+    pub fn view(&self, name: &str) -> Result<ViewDefinition> {
+        self.require_view_loader()?
+            .view(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown view: {}", name))
+    }
+
+    /// Changes to the views from now on
+    pub fn subscribe_views(&self) -> Result<tokio::sync::broadcast::Receiver<ViewEvent>> {
+        Ok(self.require_view_loader()?.subscribe())
+    }
+
+    /// Take snapshots of the workspace in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_snapshots(&self) {
+        if let Some(snapshots) = &self.snapshots {
+            snapshots.clone().spawn();
+        }
+    }
+
+    /// Snapshot the workspace now
+    pub async fn take_snapshot(&self) -> Result<SnapshotInfo> {
+        self.require_snapshots()?
+            .take_snapshot(chrono::Utc::now().timestamp_millis())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to take snapshot: {}", e))
+    }
+
+    /// Snapshots that can be restored, newest first
+    pub fn snapshots(&self) -> Result<Vec<SnapshotInfo>> {
+        self.require_snapshots()?
+            .snapshots()
+            .map_err(|e| anyhow::anyhow!("Failed to list snapshots: {}", e))
+    }
+
+    /// Roll the workspace back to the snapshot taken at `created_at`
+    ///
+    /// Operations logged after the snapshot stay in the history, marked as rolled
+    /// back; the undo/redo stacks are cleared since they refer to discarded state.
+    pub async fn restore_snapshot(&self, created_at: i64) -> Result<RestoreSummary> {
+        let summary = self
+            .require_snapshots()?
+            .restore(created_at)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to restore snapshot: {}", e))?;
+        self.undo_stack.write().await.clear();
+        self.query_cache.invalidate_all_rows();
+        Ok(summary)
+    }
+
+    /// Run database maintenance now, e.g. from an "optimize database" action
+    ///
+    /// Fails if maintenance is not configured or already running.
+    pub async fn run_maintenance(&self) -> Result<MaintenanceStatus> {
+        let maintenance = self
+            .maintenance
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Database maintenance is not configured"))?;
+        maintenance
+            .run()
+            .await
+            .map_err(|e| anyhow::anyhow!("Database maintenance failed: {}", e))
+    }
+
+    /// State of database maintenance (`running` while the database is being optimized)
+    pub fn maintenance_status(&self) -> Option<MaintenanceStatus> {
+        self.maintenance
+            .as_ref()
+            .map(|maintenance| maintenance.status())
+    }
+
+    /// Bootstrap the database replica and sync it with its remote periodically
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_replica_sync(&self) {
+        if let Some(replica) = &self.replica {
+            // Cached query results may be stale after every pull
+            let mut status = replica.subscribe_status();
+            let query_cache = self.query_cache.clone();
+            tokio::spawn(async move {
+                let mut syncs = status.borrow().syncs;
+                while status.changed().await.is_ok() {
+                    let current = status.borrow_and_update().syncs;
+                    if current != syncs {
+                        syncs = current;
+                        query_cache.invalidate_all_rows();
+                    }
+                }
+            });
+            replica.clone().spawn();
+        }
+    }
+
+    /// Sync the database replica with its remote now
+    pub async fn sync_replica(&self) -> Result<ReplicaStatus> {
+        let replica = self
+            .replica
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Remote replica is not configured"))?;
+        let status = replica
+            .sync()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to sync replica: {}", e))?;
+        self.query_cache.invalidate_all_rows();
+        #[cfg(target_arch = "wasm32")]
+        TursoBackend::schedule_persist(self.backend.clone()).await;
+        Ok(status)
+    }
+
+    /// Replication state of the database, if it is a remote replica
+    pub fn replica_status(&self) -> Option<ReplicaStatus> {
+        self.replica.as_ref().map(|replica| replica.status())
+    }
+
+    /// Cancel running syncs and stop the sync scheduler, e.g. when the app exits
+    pub fn shutdown_sync(&self) {
+        if let Some(sync_scheduler) = &self.sync_scheduler {
+            sync_scheduler.shutdown();
+        }
+    }
+
+    /// Sync all scheduled providers concurrently and wait for them
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn sync_all(&self) -> Result<SyncAllSummary> {
+        let sync_scheduler = self
+            .sync_scheduler
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Sync scheduler is not configured"))?;
+        Ok(sync_scheduler.sync_all().await)
+    }
+
+    /// Run the wildcard `sync` operation through the scheduler, so providers sync
+    /// concurrently instead of one after another in the dispatcher
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn scheduled_sync_all(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+    ) -> Option<crate::core::datasource::Result<UndoAction>> {
+        let sync_scheduler = self.sync_scheduler.as_ref()?;
+        if entity_name != "*" || op_name != "sync" || sync_scheduler.provider_names().is_empty() {
+            return None;
+        }
+        Some(sync_scheduler.sync_all().await.into_result())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn scheduled_sync_all(
+        &self,
+        _entity_name: &str,
+        _op_name: &str,
+    ) -> Option<crate::core::datasource::Result<UndoAction>> {
+        None
+    }
+
+    /// Purge expired trash periodically in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_trash_purge(self: &Arc<Self>) {
+        let engine = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                match engine.purge_trash(engine.trash_config.retention).await {
+                    Ok(0) => {}
+                    Ok(purged) => info!("[BackendEngine] Purged {} trashed entities", purged),
+                    Err(e) => tracing::warn!("[BackendEngine] Trash purge failed: {}", e),
+                }
+                tokio::time::sleep(engine.trash_config.purge_interval).await;
+            }
+        });
+    }
+
+    /// Permanently delete entities that have been in the trash longer than `older_than`
+    ///
+    /// Purged entities are deleted through the dispatcher, so the deletion reaches their
+    /// source like any other `delete`. Returns the number of purged entities.
+    pub async fn purge_trash(&self, older_than: std::time::Duration) -> Result<usize> {
+        let cutoff = chrono::Utc::now().timestamp_millis() - older_than.as_millis() as i64;
+        let operations = self.dispatcher.operations();
+        let mut purged = 0;
+        let purge_source = ChangeSource::default()
+            .with_agent("purge_trash")
+            .or(&self.current_change_source());
+
+        for (table_name, entity_name) in self.soft_delete_tables.entries() {
+            let id_column = operations
+                .iter()
+                .find(|op| op.entity_name == entity_name && op.name == "delete")
+                .map(|op| op.id_column.as_str())
+                .unwrap_or("id");
+            let rows = {
+                let backend = self.backend.read().await;
+                backend
+                    .execute_sql(
+                        &format!(
+                            "SELECT {} AS id FROM {} WHERE {} IS NOT NULL AND {} < $cutoff",
+                            id_column, table_name, DELETED_AT_COLUMN, DELETED_AT_COLUMN
+                        ),
+                        HashMap::from([("cutoff".to_string(), Value::Integer(cutoff))]),
+                    )
+                    .await
+                    .map_err(|e| {
+                        anyhow::anyhow!("Failed to load trashed rows of {}: {}", table_name, e)
+                    })?
+            };
+
+            for row in rows {
+                let Some(id) = row.get("id").and_then(|v| v.as_string_owned()) else {
+                    continue;
+                };
+                let params = HashMap::from([("id".to_string(), Value::String(id.clone()))]);
+                let operation = Operation::new(&entity_name, "delete", "", params);
+                let started_at = std::time::Instant::now();
+                let result = CURRENT_CHANGE_SOURCE
+                    .scope(
+                        purge_source.clone(),
+                        self.dispatcher.execute(operation.clone()),
+                    )
+                    .await;
+                self.record_audit(&operation, &result, started_at, &purge_source)
+                    .await;
+                match result {
+                    Ok(_) => purged += 1,
+                    Err(e) => tracing::warn!(
+                        "[BackendEngine] Failed to purge trashed {} {}: {}",
+                        entity_name,
+                        id,
+                        e
+                    ),
+                }
+            }
+        }
+
+        if purged > 0 {
+            self.query_cache.invalidate_all_rows();
+        }
+        Ok(purged)
+    }
+
+    /// Apply the archival rules periodically in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_archival(self: &Arc<Self>) {
+        let engine = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                match engine.run_archival().await {
+                    Ok(0) => {}
+                    Ok(archived) => info!("[BackendEngine] Archived {} entities", archived),
+                    Err(e) => tracing::warn!("[BackendEngine] Archival failed: {}", e),
+                }
+                tokio::time::sleep(engine.archival_config.interval).await;
+            }
+        });
+    }
+
+    /// Archive the entities matching the archival rules now
+    ///
+    /// Returns the number of archived entities (see `storage::archival`).
+    pub async fn run_archival(&self) -> Result<usize> {
+        if self.archival_config.rules.is_empty() {
+            return Ok(0);
+        }
+        let archived = apply_archival_rules(
+            &self.backend,
+            &self.archival_config.rules,
+            chrono::Utc::now(),
+        )
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        if archived > 0 {
+            self.query_cache.invalidate_all_rows();
+        }
+        Ok(archived)
+    }
+
+    /// Receive webhooks at `config.addr` and execute the operations they map to
+    ///
+    /// Returns the bound address (see `webhooks`).
+    #[cfg(all(feature = "webhooks", not(target_arch = "wasm32")))]
+    pub async fn start_webhooks(
+        self: &Arc<Self>,
+        config: crate::webhooks::WebhookConfig,
+    ) -> Result<std::net::SocketAddr> {
+        let listener = tokio::net::TcpListener::bind(config.addr)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to bind webhook listener: {}", e))?;
+        let addr = listener.local_addr()?;
+        let receiver = Arc::new(crate::webhooks::WebhookReceiver::new(
+            Arc::clone(self),
+            config.endpoints,
+        ));
+        tokio::spawn(async move {
+            if let Err(e) = receiver.serve(listener).await {
+                tracing::warn!("[BackendEngine] Webhook listener failed: {}", e);
+            }
+        });
+        info!("[BackendEngine] Receiving webhooks at {}", addr);
+        Ok(addr)
+    }
+
+    /// Rebuild the backlink index and keep it up to date in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_backlink_index(&self) {
+        if let Some(backlinks) = &self.backlinks {
+            backlinks.clone().spawn();
+        }
+    }
+
+    /// Entities whose content refers to `target_id` (a block ID or wiki-link page name)
+    ///
+    /// The same data is queryable as the `backlinks` table.
+    pub async fn backlinks(&self, target_id: &str) -> Result<Vec<Backlink>> {
+        match &self.backlinks {
+            Some(backlinks) => backlinks
+                .backlinks_to(target_id)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load backlinks: {}", e)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Register a derived column, e.g. one computed by a closure
+    ///
+    /// Fields declared with `#[computed(expr = "...")]` are registered automatically
+    /// when their entity's table is created. Register before `start_computed_fields`.
+    pub async fn register_computed_field(&self, field: ComputedField) -> Result<()> {
+        self.backend
+            .read()
+            .await
+            .computed_fields()
+            .register(field)
+            .map_err(|e| anyhow::anyhow!("Failed to register computed field: {}", e))
+    }
+
+    /// Recompute all computed fields and keep them up to date in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn start_computed_fields(&self) {
+        let computed_fields = self.backend.read().await.computed_fields();
+        computed_fields.spawn(self.backend.clone());
+    }
+
+    /// Maintain child and completion counts of a tree table
+    ///
+    /// Tables of entities with `parent_id` and `completed` fields are registered
+    /// automatically when created. Register before `start_rollups`.
+    pub async fn register_rollup(&self, rollup: Rollup) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .rollups()
+            .register(&backend, rollup)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to register rollup: {}", e))
+    }
+
+    /// Recount all rollups and keep them up to date in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn start_rollups(&self) {
+        let rollups = self.backend.read().await.rollups();
+        rollups.spawn(self.backend.clone());
+    }
+
+    /// Maintain the `urgency` column of a task table
+    ///
+    /// Tables of entities with `due_date`, `priority` and `completed` fields are
+    /// registered automatically when created. Register before `start_urgency_scores`.
+    pub async fn register_urgency_scoring(&self, scoring: UrgencyScoring) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .urgency_scores()
+            .register(&backend, scoring)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to register urgency scoring: {}", e))
+    }
+
+    /// Score all tasks and keep their urgency up to date in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn start_urgency_scores(&self) {
+        let urgency_scores = self.backend.read().await.urgency_scores();
+        urgency_scores.spawn(self.backend.clone());
+    }
+
+    /// Maintain the daily statistics (`stats_daily`, `stats_project_daily`) of a task table
+    ///
+    /// Tables of entities with `created_at`, `completed` and `completed_at` fields are
+    /// registered automatically when created. Register before `start_stats`.
+    pub async fn register_stats_source(&self, source: StatsSource) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .stats()
+            .register(&backend, source)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to register statistics source: {}", e))
+    }
+
+    /// Aggregate all tasks and keep the daily statistics up to date in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn start_stats(&self) {
+        let stats = self.backend.read().await.stats();
+        stats.spawn(self.backend.clone());
+    }
+
+    /// Rebuild the tag index and keep it up to date in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_tag_index(&self) {
+        if let Some(tags) = &self.tags {
+            tags.clone().spawn();
+        }
+    }
+
+    /// Evaluate automation rules on changes of the watched entities in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_automation_rules(&self) {
+        if let Some(automation_rules) = &self.automation_rules {
+            automation_rules.clone().spawn();
+        }
+    }
+
+    /// Store the comments of all annotated entities in their rows
+    pub async fn start_annotations(&self) {
+        if let Some(annotations) = &self.annotations {
+            match annotations.refresh_all().await {
+                Ok(refreshed) => debug!(
+                    "[BackendEngine] Refreshed comments of {} entities",
+                    refreshed
+                ),
+                Err(e) => tracing::warn!("[BackendEngine] Failed to refresh comments: {}", e),
+            }
         }
-        .instrument(span)
-        .await
     }
 
-    /// Undo the last operation
+    /// Entities tagged with `tag` (with or without leading `#`)
     ///
-    /// Executes the inverse operation from the undo stack and pushes it to the redo stack.
-    /// Returns true if an operation was undone, false if the undo stack is empty.
-    pub async fn undo(&self) -> Result<bool> {
-        // Pop the inverse operation from undo stack (automatically moves to redo stack)
-        let inverse_op = {
-            let mut undo_stack = self.undo_stack.write().await;
-            undo_stack
-                .pop_for_undo()
-                .ok_or_else(|| anyhow::anyhow!("Nothing to undo"))?
-        };
+    /// The same data is queryable as the `tags` table.
+    pub async fn tagged(&self, tag: &str) -> Result<Vec<Tag>> {
+        match &self.tags {
+            Some(tags) => tags
+                .tagged(tag)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load tagged entities: {}", e)),
+            None => Ok(Vec::new()),
+        }
+    }
 
-        // Execute the inverse operation
-        let new_inverse = self
-            .dispatcher
-            .execute_operation(
-                &inverse_op.entity_name,
-                &inverse_op.op_name,
-                inverse_op.params.clone(),
-            )
+    /// Bytes to append to this device's sync blob (see `sync::blob`)
+    ///
+    /// `blob` is the blob's current content, empty if there is none yet.
+    pub async fn export_sync_blob(&self, blob: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+        self.require_sync_blobs()?
+            .append_local(blob, passphrase)
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to execute undo operation: {}", e))?;
+            .map_err(|e| anyhow::anyhow!("Failed to export sync blob: {}", e))
+    }
 
-        // Update the redo stack with the new inverse operation
-        // The UndoStack already moved (inverse, original) to redo stack,
-        // but we need to update it with the new inverse we got from execution
-        if let UndoAction::Undo(new_inverse_op) = new_inverse {
-            let mut undo_stack = self.undo_stack.write().await;
-            undo_stack.update_redo_top(new_inverse_op);
+    /// Merge another device's sync blob and apply the operations not seen before
+    pub async fn import_sync_blob(
+        &self,
+        blob: &[u8],
+        passphrase: &str,
+    ) -> Result<SyncImportSummary> {
+        let summary = self
+            .require_sync_blobs()?
+            .import_blob(blob, passphrase)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to import sync blob: {}", e))?;
+        if summary.applied > 0 {
+            self.query_cache.invalidate_all_rows();
         }
+        Ok(summary)
+    }
 
-        Ok(true)
+    /// Import a Logseq graph export (`.json` or `.edn`) as blocks
+    ///
+    /// Re-importing the same graph updates the previously imported blocks.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn import_logseq(
+        &self,
+        path: &std::path::Path,
+        progress: impl FnMut(&ImportProgress),
+    ) -> Result<ImportSummary> {
+        let summary = LogseqImporter::new(self.backend.clone())
+            .import_file(path, progress)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to import Logseq graph: {}", e))?;
+        if summary.created + summary.updated > 0 {
+            self.query_cache.invalidate_all_rows();
+        }
+        Ok(summary)
     }
 
-    /// Redo the last undone operation
+    /// Export all block trees below `path` as Markdown or Org files, one per root block
     ///
-    /// Executes the inverse of the last undone operation and pushes it back to the undo stack.
-    /// Returns true if an operation was redone, false if the redo stack is empty.
-    pub async fn redo(&self) -> Result<bool> {
-        // Pop the operation to redo from redo stack (automatically moves back to undo stack)
-        let operation_to_redo = {
-            let mut undo_stack = self.undo_stack.write().await;
-            undo_stack
-                .pop_for_redo()
-                .ok_or_else(|| anyhow::anyhow!("Nothing to redo"))?
+    /// Block ids are written along with the blocks, so `import_workspace` on the same
+    /// directory updates the exported blocks instead of duplicating them.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn export_workspace(
+        &self,
+        path: &std::path::Path,
+        format: ExportFormat,
+    ) -> Result<ExportSummary> {
+        std::fs::create_dir_all(path)
+            .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", path.display(), e))?;
+        crate::export::export_workspace(self, path, format).await
+    }
+
+    /// Write the results of a PRQL query to `path` as CSV or JSONL
+    ///
+    /// Rows are streamed from the database cursor into the file, so memory use doesn't
+    /// grow with the result. `progress` is called every `QUERY_EXPORT_PROGRESS_INTERVAL`
+    /// rows and at the end. Setting `cancel` stops the export and removes the partial file.
+    /// Returns the number of exported rows.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn export_query_results(
+        &self,
+        prql: &str,
+        path: &std::path::Path,
+        format: QueryExportFormat,
+        cancel: &std::sync::atomic::AtomicBool,
+        mut progress: impl FnMut(&QueryExportProgress),
+    ) -> Result<usize> {
+        use std::sync::atomic::Ordering;
+
+        let compiled = self.compile_query_cached(prql, &HashMap::new())?;
+        let file = std::fs::File::create(path)
+            .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", path.display(), e))?;
+        let mut writer = QueryResultWriter::new(std::io::BufWriter::new(file), format);
+
+        let mut cancelled = false;
+        let result = self
+            .backend
+            .read()
+            .await
+            .for_each_row(&compiled.sql, HashMap::new(), |columns, values| {
+                if cancel.load(Ordering::Relaxed) {
+                    cancelled = true;
+                    return Ok(false);
+                }
+                writer.write_row(columns, &values)?;
+                if writer.rows() % QUERY_EXPORT_PROGRESS_INTERVAL == 0 {
+                    progress(&QueryExportProgress {
+                        rows: writer.rows(),
+                    });
+                }
+                Ok(true)
+            })
+            .await;
+        let rows = match result {
+            Ok(columns) if !cancelled => writer.finish(&columns).map_err(anyhow::Error::from),
+            Ok(_) => Err(anyhow::anyhow!(
+                "Export cancelled after {} rows",
+                writer.rows()
+            )),
+            Err(e) => Err(anyhow::anyhow!("Failed to export query results: {}", e)),
+        };
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(e) => {
+                let _ = std::fs::remove_file(path);
+                return Err(e);
+            }
+        };
+
+        progress(&QueryExportProgress { rows });
+        info!(
+            "[BackendEngine] Exported {} rows to {}",
+            rows,
+            path.display()
+        );
+        Ok(rows)
+    }
+
+    /// Import the Markdown/Org files in `path` (as written by `export_workspace`)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn import_workspace(
+        &self,
+        path: &std::path::Path,
+        progress: impl FnMut(&ImportProgress),
+    ) -> Result<ImportSummary> {
+        let summary = OutlineImporter::new(self.backend.clone())
+            .import_dir(path, progress)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to import workspace: {}", e))?;
+        if summary.created + summary.updated > 0 {
+            self.query_cache.invalidate_all_rows();
+        }
+        Ok(summary)
+    }
+
+    /// Read a CSV/JSON file and suggest how its columns map to `schema`
+    ///
+    /// Frontends show the preview so the user can adjust the mapping before `import_table`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn preview_table_import(
+        &self,
+        path: &std::path::Path,
+        schema: &EntitySchema,
+    ) -> Result<TabularPreview> {
+        let data = TabularData::read_file(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Ok(data.preview(schema))
+    }
+
+    /// Create an entity of `schema` for each row of a CSV/JSON file
+    ///
+    /// Without a `mapping`, columns are mapped by name (see `ColumnMapping::suggest`).
+    /// Entities are created through the entity's `create` operation in batches of
+    /// `TABLE_IMPORT_BATCH_SIZE`, reporting progress after each batch; the whole import
+    /// is undone as one step. Rows that fail validation or creation are skipped and
+    /// listed in the summary.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn import_table(
+        &self,
+        path: &std::path::Path,
+        schema: &EntitySchema,
+        mapping: Option<ColumnMapping>,
+        mut progress: impl FnMut(&TabularImportProgress),
+    ) -> Result<TabularImportSummary> {
+        let data = TabularData::read_file(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let mapping = mapping.unwrap_or_else(|| ColumnMapping::suggest(&data.headers, schema));
+        let (entities, mut errors) = data
+            .to_entities(schema, &mapping)
+            .map_err(|e| anyhow::anyhow!("Invalid column mapping: {}", e))?;
+
+        let mut state = TabularImportProgress {
+            processed: errors.len(),
+            total: data.len(),
+            created: 0,
+            failed: errors.len(),
         };
+        self.begin_undo_group(Some(&format!("Import {}", schema.name)))
+            .await;
+        for batch in entities.chunks(TABLE_IMPORT_BATCH_SIZE) {
+            for (row, entity) in batch {
+                match self
+                    .execute_operation(&schema.name, "create", entity.clone())
+                    .await
+                {
+                    Ok(()) => state.created += 1,
+                    Err(e) => {
+                        state.failed += 1;
+                        errors.push(ImportRowError {
+                            row: *row,
+                            field: None,
+                            message: e.to_string(),
+                        });
+                    }
+                }
+            }
+            state.processed += batch.len();
+            progress(&state);
+        }
+        self.end_undo_group().await;
+
+        errors.sort_by_key(|error| error.row);
+        info!(
+            "[BackendEngine] Imported {} of {} rows into {}",
+            state.created, state.total, schema.name
+        );
+        Ok(TabularImportSummary {
+            rows: state.total,
+            created: state.created,
+            errors,
+        })
+    }
 
-        // Execute the operation to redo
-        let new_inverse = self
-            .dispatcher
-            .execute_operation(
-                &operation_to_redo.entity_name,
-                &operation_to_redo.op_name,
-                operation_to_redo.params.clone(),
+    /// Record that two entities' IDs refer to the same thing (see `resolve` in queries)
+    ///
+    /// Returns the shared identity ID; linking IDs of two existing identities merges them.
+    pub async fn link_identities(
+        &self,
+        entity_name: &str,
+        external_id: &str,
+        other_entity_name: &str,
+        other_external_id: &str,
+    ) -> Result<String> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let identity_id = self
+            .require_identities()?
+            .link(
+                entity_name,
+                external_id,
+                other_entity_name,
+                other_external_id,
+                now,
             )
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to execute redo operation: {}", e))?;
+            .map_err(|e| anyhow::anyhow!("Failed to link identities: {}", e))?;
+        self.query_cache.invalidate_all_rows();
+        Ok(identity_id)
+    }
 
-        // Update the undo stack with the new inverse operation
-        // The UndoStack already moved (inverse, operation_to_redo) back to undo stack,
-        // but we need to update it with the new inverse we got from execution
-        if let UndoAction::Undo(new_inverse_op) = new_inverse {
-            let mut undo_stack = self.undo_stack.write().await;
-            undo_stack.update_undo_top(new_inverse_op);
+    /// Remove an entity's ID from the identity it was linked to
+    pub async fn unlink_identity(&self, entity_name: &str, external_id: &str) -> Result<()> {
+        self.require_identities()?
+            .unlink(entity_name, external_id)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to unlink identity: {}", e))?;
+        self.query_cache.invalidate_all_rows();
+        Ok(())
+    }
+
+    /// IDs in `target_entity` linked to `external_id` of `entity_name`
+    pub async fn resolve_identity(
+        &self,
+        entity_name: &str,
+        external_id: &str,
+        target_entity: &str,
+    ) -> Result<Vec<String>> {
+        self.require_identities()?
+            .resolve(entity_name, external_id, target_entity)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to resolve identity: {}", e))
+    }
+
+    /// Record that the frontend opened an entity, for `recently_viewed`
+    pub async fn record_entity_viewed(&self, entity_name: &str, entity_id: &str) -> Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.require_entity_access()?
+            .record_viewed(entity_name, entity_id, now)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to record entity view: {}", e))?;
+        self.query_cache.invalidate_table(ACCESS_ENTITY);
+        Ok(())
+    }
+
+    /// The `limit` most recently viewed entities of all datasources, newest first
+    pub async fn recently_viewed(&self, limit: usize) -> Result<Vec<EntityAccess>> {
+        self.require_entity_access()?
+            .recently_viewed(limit)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load recently viewed entities: {}", e))
+    }
+
+    /// The `limit` most recently modified entities of all datasources, newest first
+    pub async fn recently_modified(&self, limit: usize) -> Result<Vec<EntityAccess>> {
+        self.require_entity_access()?
+            .recently_modified(limit)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load recently modified entities: {}", e))
+    }
+
+    /// Persisted UI state of a view (see `view_id:` in render expressions)
+    pub async fn view_state(&self, view_id: &str) -> Result<ViewState> {
+        self.require_view_states()?
+            .get(view_id)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load view state: {}", e))
+    }
+
+    /// Record that a tree node of a view was collapsed or expanded
+    pub async fn set_view_collapsed(
+        &self,
+        view_id: &str,
+        entity_id: &str,
+        collapsed: bool,
+    ) -> Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.require_view_states()?
+            .set_collapsed(view_id, entity_id, collapsed, now)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to save view state: {}", e))
+    }
+
+    /// Record the selected rows of a view
+    pub async fn set_view_selection(&self, view_id: &str, entity_ids: &[String]) -> Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.require_view_states()?
+            .set_selection(view_id, entity_ids, now)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to save view state: {}", e))
+    }
+
+    /// Record the first visible row of a view (`None` when scrolled to the top)
+    pub async fn set_view_scroll_anchor(
+        &self,
+        view_id: &str,
+        entity_id: Option<&str>,
+    ) -> Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.require_view_states()?
+            .set_scroll_anchor(view_id, entity_id, now)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to save view state: {}", e))
+    }
+
+    /// Write pending view state changes now, e.g. before the app exits
+    pub async fn flush_view_states(&self) -> Result<()> {
+        if let Some(view_states) = &self.view_states {
+            view_states
+                .flush()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to save view state: {}", e))?;
         }
+        Ok(())
+    }
 
-        Ok(true)
+    /// Receive collaborators' presence in the background (see `core::presence`)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_presence(&self) {
+        if let Some(presence) = &self.presence {
+            presence.clone().spawn();
+        }
     }
 
-    /// Check if undo is available
-    pub async fn can_undo(&self) -> bool {
-        self.undo_stack.read().await.can_undo()
+    /// Tell collaborators what this client shows: the view, the row the cursor is
+    /// on and the selected rows
+    pub async fn publish_presence(
+        &self,
+        view_id: Option<&str>,
+        focused_id: Option<&str>,
+        selection: &[String],
+    ) -> Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.require_presence()?
+            .publish(view_id, focused_id, selection, now)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to publish presence: {}", e))
     }
 
-    /// Check if redo is available
-    pub async fn can_redo(&self) -> bool {
-        self.undo_stack.read().await.can_redo()
+    /// Presence of the collaborators showing `view_id` (all collaborators if None)
+    ///
+    /// Frontends mark the rows collaborators are on in views declaring `presence:`.
+    /// The same data can be queried as the `presence` table.
+    pub fn presence(&self, view_id: Option<&str>) -> Result<Vec<Presence>> {
+        Ok(self.require_presence()?.peers(view_id))
+    }
+
+    /// Fill in the persisted state of the view a render spec declares
+    async fn load_view_state(&self, mut render_spec: RenderSpec) -> Result<RenderSpec> {
+        if let (Some(view_id), Some(view_states)) = (&render_spec.view_id, &self.view_states) {
+            render_spec.view_state = view_states
+                .get(view_id)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load view state: {}", e))?;
+        }
+        Ok(render_spec)
+    }
+
+    fn require_view_states(&self) -> Result<&Arc<ViewStateStore>> {
+        self.view_states
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("View state is not configured"))
+    }
+
+    fn require_presence(&self) -> Result<&Arc<PresenceTracker>> {
+        self.presence
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Presence is not configured"))
+    }
+
+    fn require_view_loader(&self) -> Result<&Arc<ViewLoader>> {
+        self.view_loader
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("View files are not configured"))
+    }
+
+    fn require_snapshots(&self) -> Result<&Arc<SnapshotStore>> {
+        self.snapshots
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Snapshots are not configured"))
+    }
+
+    fn require_dependencies(&self) -> Result<&Arc<DependencyStore>> {
+        self.dependencies
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Task dependencies are not configured"))
+    }
+
+    fn require_entity_access(&self) -> Result<&Arc<EntityAccessStore>> {
+        self.entity_access
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Entity access tracking is not configured"))
+    }
+
+    fn require_identities(&self) -> Result<&Arc<EntityIdentityStore>> {
+        self.identities
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Identity mapping is not configured"))
+    }
+
+    fn require_sync_blobs(&self) -> Result<&Arc<SyncBlobLog>> {
+        self.sync_blobs
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Encrypted sync is not configured"))
+    }
+
+    /// The sync scheduler, for pausing/resuming providers and toggling offline mode
+    pub fn sync_scheduler(&self) -> Option<Arc<SyncScheduler>> {
+        self.sync_scheduler.clone()
+    }
+
+    /// Scheduling status of every provider
+    ///
+    /// The same data is queryable as the `sync_status` table.
+    pub fn sync_statuses(&self) -> Vec<SyncStatus> {
+        self.sync_scheduler
+            .as_ref()
+            .map(|sync_scheduler| sync_scheduler.statuses())
+            .unwrap_or_default()
+    }
+
+    /// Aggregated progress of the syncs currently queued or running
+    pub fn sync_progress(&self) -> Option<SyncProgress> {
+        self.sync_scheduler
+            .as_ref()
+            .map(|sync_scheduler| sync_scheduler.progress())
+    }
+
+    /// Up to `limit` recent log events matching `filter`, newest first
+    ///
+    /// Empty when no log buffer is attached.
+    pub fn recent_logs(&self, filter: &LogFilter, limit: usize) -> Vec<LogRecord> {
+        self.log_buffer
+            .as_ref()
+            .map(|log_buffer| log_buffer.recent(filter, limit))
+            .unwrap_or_default()
+    }
+
+    /// Health check of the database, sync providers and query cache
+    ///
+    /// A failing integrity check is reported in `Diagnostics::integrity` rather than
+    /// as an error, so the report stays available when the database is damaged.
+    pub async fn diagnostics(&self) -> Result<Diagnostics> {
+        let (integrity, integrity_ok, schema_version) = {
+            let backend = self.backend.read().await;
+            let (integrity, integrity_ok) = match backend
+                .execute_sql("PRAGMA quick_check", HashMap::new())
+                .await
+            {
+                Ok(rows) => {
+                    let problems: Vec<String> = rows
+                        .iter()
+                        .flat_map(|row| row.values())
+                        .filter_map(|value| value.as_string_owned())
+                        .collect();
+                    let ok = problems.iter().all(|problem| problem == "ok");
+                    let integrity = if ok {
+                        "ok".to_string()
+                    } else {
+                        problems.join("\n")
+                    };
+                    (integrity, ok)
+                }
+                Err(e) => (format!("Integrity check failed: {}", e), false),
+            };
+            let schema_version = backend
+                .execute_sql("PRAGMA schema_version", HashMap::new())
+                .await
+                .ok()
+                .and_then(|rows| {
+                    rows.first()
+                        .and_then(|row| row.values().find_map(|value| value.as_i64()))
+                });
+            (integrity, integrity_ok, schema_version)
+        };
+
+        Ok(Diagnostics {
+            integrity,
+            integrity_ok,
+            schema_version,
+            providers: provider_diagnostics(
+                self.unsynced_changes_summary().await?,
+                self.sync_statuses(),
+            ),
+            query_cache: self.query_cache.stats(),
+            replica: self.replica_status(),
+            generated_at: chrono::Utc::now().timestamp_millis(),
+        })
     }
 
     /// Register a custom OperationProvider
@@ -855,6 +3258,11 @@ impl BackendEngine {
             .collect()
     }
 
+    /// Operations of all registered entities
+    pub async fn all_operations(&self) -> Vec<OperationDescriptor> {
+        self.dispatcher.operations()
+    }
+
     pub async fn has_operation(&self, entity_name: &str, op_name: &str) -> bool {
         self.dispatcher
             .operations()
@@ -934,23 +3342,7 @@ impl BackendEngine {
         let db_exists = db_path.exists();
 
         if !db_exists {
-            // Create blocks table schema
-            let create_table_sql = r#"
-                CREATE TABLE IF NOT EXISTS blocks (
-                    id TEXT PRIMARY KEY,
-                    parent_id TEXT,
-                    depth INTEGER NOT NULL DEFAULT 0,
-                    sort_key TEXT NOT NULL,
-                    content TEXT NOT NULL,
-                    collapsed INTEGER NOT NULL DEFAULT 0,
-                    completed INTEGER NOT NULL DEFAULT 0,
-                    block_type TEXT NOT NULL DEFAULT 'text',
-                    created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
-                )
-            "#;
-
-            self.execute_query(create_table_sql.to_string(), HashMap::new())
+            self.execute_query(BLOCKS_TABLE_SQL.to_string(), HashMap::new())
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to create blocks table: {}", e))?;
 
@@ -993,6 +3385,25 @@ impl BackendEngine {
     }
 }
 
+/// Error of a failed operation
+///
+/// A structured `HolonError` stays downcastable, so frontends can convert it into
+/// an `ApiError`.
+fn operation_error(
+    op_name: &str,
+    entity_name: &str,
+    err: Box<dyn std::error::Error + Send + Sync>,
+) -> anyhow::Error {
+    let message = format!(
+        "Operation '{}' on entity '{}' failed: {}",
+        op_name, entity_name, err
+    );
+    match err.downcast::<HolonError>() {
+        Ok(holon_error) => anyhow::Error::new(*holon_error).context(message),
+        Err(_) => anyhow::anyhow!(message),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1050,6 +3461,7 @@ mod tests {
                 affected_fields: vec![],
                 param_mappings: vec![],
                 precondition: None,
+                simulation: None,
             },
             OperationDescriptor {
                 entity_name: entity_name.to_string(),
@@ -1062,6 +3474,7 @@ mod tests {
                 affected_fields: vec![],
                 param_mappings: vec![],
                 precondition: None,
+                simulation: None,
             },
             OperationDescriptor {
                 entity_name: entity_name.to_string(),
@@ -1074,6 +3487,7 @@ mod tests {
                 affected_fields: vec![],
                 param_mappings: vec![],
                 precondition: None,
+                simulation: None,
             },
         ]
     }
@@ -1239,6 +3653,24 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_registered_widgets_are_checked() {
+        let engine = create_test_engine().await.unwrap();
+        let prql = "from blocks\nrender (txt content)".to_string();
+        assert!(engine.compile_query(prql.clone()).is_ok());
+
+        engine.register_widgets(vec![WidgetSpec::new("text").with_param(
+            holon_api::WidgetParam::required("content", holon_api::WidgetArgType::String),
+        )]);
+        let error = engine.compile_query(prql).unwrap_err().to_string();
+        assert!(error.contains("unknown widget `txt`"), "{}", error);
+        assert!(
+            engine
+                .compile_query("from blocks\nrender (text content)".to_string())
+                .is_ok()
+        );
+    }
+
     #[tokio::test]
     async fn test_execute_query_with_parameters() {
         let engine = create_test_engine().await.unwrap();
@@ -1380,6 +3812,99 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_execute_batch_operation() {
+        let temp_engine = create_test_engine().await.unwrap();
+        let provider = Arc::new(SqlOperationProvider::new(
+            temp_engine.backend.clone(),
+            "blocks".to_string(),
+            "blocks".to_string(),
+        ));
+        let engine = create_test_engine_with_providers(":memory:".into(), |module| {
+            module.with_operation_provider(provider)
+        })
+        .await
+        .unwrap();
+
+        {
+            let backend = engine.backend.write().await;
+            let conn = backend.get_connection().unwrap();
+            conn.execute(
+                "CREATE TABLE blocks (id TEXT PRIMARY KEY, content TEXT, completed BOOLEAN)",
+                (),
+            )
+            .await
+            .unwrap();
+            conn.execute(
+                "INSERT INTO blocks (id, content, completed) VALUES ('b1', 'A', 0), ('b2', 'B', 0), ('b3', 'C', 0)",
+                (),
+            )
+            .await
+            .unwrap();
+        }
+
+        let complete = |id: &str| {
+            HashMap::from([
+                ("id".to_string(), Value::String(id.to_string())),
+                ("field".to_string(), Value::String("completed".to_string())),
+                ("value".to_string(), Value::Boolean(true)),
+            ])
+        };
+        let executed = engine
+            .execute_batch_operation(
+                "blocks",
+                "set_field",
+                "Complete all",
+                vec![complete("b1"), complete("b3")],
+            )
+            .await
+            .unwrap();
+        assert_eq!(executed, 2);
+
+        let results = engine
+            .execute_query(
+                "SELECT id FROM blocks WHERE completed = 1 ORDER BY id".to_string(),
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        let ids: Vec<&str> = results
+            .iter()
+            .filter_map(|r| r.get("id")?.as_string())
+            .collect();
+        assert_eq!(ids, vec!["b1", "b3"]);
+
+        // A failing entity stops the batch
+        let mut missing_field = complete("b2");
+        missing_field.remove("field");
+        let result = engine
+            .execute_batch_operation(
+                "blocks",
+                "set_field",
+                "Complete all",
+                vec![missing_field, complete("b2")],
+            )
+            .await;
+        assert!(result.unwrap_err().to_string().contains("after 0 of 2"));
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics() {
+        let engine = create_test_engine().await.unwrap();
+        engine
+            .compile_query_cached(
+                "from blocks\nrender (list item_template:(text content:this.content))",
+                &HashMap::new(),
+            )
+            .unwrap();
+
+        let diagnostics = engine.diagnostics().await.unwrap();
+        assert!(diagnostics.integrity_ok, "{}", diagnostics.integrity);
+        assert!(diagnostics.providers.is_empty());
+        assert_eq!(diagnostics.query_cache.entries, 1);
+        assert!(!diagnostics.summary_lines().is_empty());
+    }
+
     #[tokio::test]
     async fn test_execute_operation_failure() {
         let engine = create_test_engine().await.unwrap();
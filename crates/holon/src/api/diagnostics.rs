@@ -0,0 +1,212 @@
+//! Backend health check for debug screens and settings pages
+//!
+//! `BackendEngine::diagnostics` gathers the database integrity, schema version,
+//! per-provider sync state, query cache counters and remote replica state into one
+//! `Diagnostics` report.
+
+use std::collections::BTreeMap;
+
+use crate::api::query_cache::QueryCacheStats;
+use crate::storage::replica::ReplicaStatus;
+use crate::sync::dirty::ProviderDirtyStatus;
+use crate::sync::scheduler::SyncStatus;
+
+/// Snapshot of the backend's health
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostics {
+    /// Output of `PRAGMA quick_check`: "ok", the problems found, or why the check failed
+    pub integrity: String,
+    pub integrity_ok: bool,
+    /// SQLite schema cookie (`PRAGMA schema_version`), bumped by every schema change
+    pub schema_version: Option<i64>,
+    /// Sync state per provider, sorted by provider name
+    pub providers: Vec<ProviderDiagnostics>,
+    pub query_cache: QueryCacheStats,
+    /// Replication state, if the database is a remote replica
+    pub replica: Option<ReplicaStatus>,
+    /// Unix timestamp in milliseconds
+    pub generated_at: i64,
+}
+
+/// Sync state of one provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderDiagnostics {
+    pub provider_name: String,
+    /// Logged operations the provider hasn't acknowledged yet
+    pub pending_operations: i64,
+    /// Entities with unsynced local changes
+    pub dirty_entities: i64,
+    /// Last successful sync (Unix timestamp in milliseconds)
+    pub last_sync_at: Option<i64>,
+    /// Error of the last failed sync, if it failed after the last success
+    pub last_error: Option<String>,
+}
+
+impl ProviderDiagnostics {
+    fn new(provider_name: &str) -> Self {
+        Self {
+            provider_name: provider_name.to_string(),
+            pending_operations: 0,
+            dirty_entities: 0,
+            last_sync_at: None,
+            last_error: None,
+        }
+    }
+}
+
+/// Merge unsynced change counts and scheduler statuses into one entry per provider
+pub(crate) fn provider_diagnostics(
+    dirty: Vec<ProviderDirtyStatus>,
+    statuses: Vec<SyncStatus>,
+) -> Vec<ProviderDiagnostics> {
+    let mut providers: BTreeMap<String, ProviderDiagnostics> = BTreeMap::new();
+    for status in dirty {
+        let entry = providers
+            .entry(status.provider_name.clone())
+            .or_insert_with(|| ProviderDiagnostics::new(&status.provider_name));
+        entry.pending_operations = status.pending_operations;
+        entry.dirty_entities = status.dirty_entities;
+    }
+    for status in statuses {
+        let entry = providers
+            .entry(status.provider_name.clone())
+            .or_insert_with(|| ProviderDiagnostics::new(&status.provider_name));
+        entry.last_sync_at = status.last_success_at;
+        let failed_since_success = match (status.last_error_at, status.last_success_at) {
+            (Some(error_at), Some(success_at)) => error_at > success_at,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if failed_since_success {
+            entry.last_error = status.last_error;
+        }
+    }
+    providers.into_values().collect()
+}
+
+impl Diagnostics {
+    /// Plain-text report, one line per item (for the TUI debug screen and logs)
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "Database integrity: {}",
+                if self.integrity_ok {
+                    "ok".to_string()
+                } else {
+                    self.integrity.clone()
+                }
+            ),
+            format!(
+                "Schema version: {}",
+                self.schema_version
+                    .map(|version| version.to_string())
+                    .unwrap_or_else(|| "unknown".to_string())
+            ),
+            format!(
+                "Query cache: {} entries, {} hits, {} misses",
+                self.query_cache.entries, self.query_cache.hits, self.query_cache.misses
+            ),
+        ];
+
+        if let Some(replica) = &self.replica {
+            let state = if !replica.bootstrapped {
+                "bootstrapping"
+            } else if replica.syncing {
+                "syncing"
+            } else {
+                "ready"
+            };
+            let mut line = format!(
+                "Replica of {}: {}, last sync {}",
+                replica.sync_url,
+                state,
+                format_timestamp(replica.last_sync_at)
+            );
+            if let Some(error) = &replica.last_error {
+                line.push_str(&format!(
+                    ", {} failed syncs, last error: {}",
+                    replica.consecutive_failures, error
+                ));
+            }
+            lines.push(line);
+        }
+
+        if self.providers.is_empty() {
+            lines.push("Sync: no providers".to_string());
+        }
+        for provider in &self.providers {
+            let last_sync = format_timestamp(provider.last_sync_at);
+            let mut line = format!(
+                "Sync {}: {} pending operations, {} unsynced entities, last sync {}",
+                provider.provider_name,
+                provider.pending_operations,
+                provider.dirty_entities,
+                last_sync
+            );
+            if let Some(error) = &provider.last_error {
+                line.push_str(&format!(", last error: {}", error));
+            }
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+fn format_timestamp(timestamp: Option<i64>) -> String {
+    timestamp
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| "never".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_diagnostics_merges_by_provider() {
+        let dirty = vec![ProviderDirtyStatus {
+            provider_name: "todoist".to_string(),
+            dirty_entities: 2,
+            pending_operations: 3,
+            oldest_pending_at: Some(10),
+        }];
+        let statuses = vec![
+            SyncStatus {
+                provider_name: "todoist".to_string(),
+                state: "idle".to_string(),
+                in_progress: false,
+                paused: false,
+                last_started_at: Some(200),
+                last_success_at: Some(100),
+                last_error_at: Some(200),
+                last_error: Some("timeout".to_string()),
+                consecutive_failures: 1,
+                next_run_at: None,
+                interval_ms: 60_000,
+            },
+            SyncStatus {
+                provider_name: "orgmode".to_string(),
+                state: "idle".to_string(),
+                in_progress: false,
+                paused: false,
+                last_started_at: Some(300),
+                last_success_at: Some(300),
+                last_error_at: Some(50),
+                last_error: Some("old failure".to_string()),
+                consecutive_failures: 0,
+                next_run_at: None,
+                interval_ms: 60_000,
+            },
+        ];
+
+        let providers = provider_diagnostics(dirty, statuses);
+        assert_eq!(providers.len(), 2);
+        assert_eq!(providers[0].provider_name, "orgmode");
+        assert_eq!(providers[0].last_error, None);
+        assert_eq!(providers[1].pending_operations, 3);
+        assert_eq!(providers[1].dirty_entities, 2);
+        assert_eq!(providers[1].last_sync_at, Some(100));
+        assert_eq!(providers[1].last_error.as_deref(), Some("timeout"));
+    }
+}
@@ -0,0 +1,257 @@
+//! Search-as-you-type queries
+//!
+//! A live search is a query with a text parameter (e.g. `filter content ~= $search`)
+//! that is re-run on every keystroke. `LiveSearches::search` waits until typing
+//! pauses, drops executions superseded by newer text, and sends only the rows
+//! that entered, left or changed in the result through the search's stream, so
+//! the frontend never reloads the whole result.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use holon_api::{BatchWithMetadata, Value};
+use tokio::sync::{mpsc, watch};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::api::query_filters::{batch, result_diff};
+use crate::api::result_window::row_id;
+use crate::storage::turso::{RowChange, RowChangeStream};
+
+type Row = HashMap<String, Value>;
+
+/// How long typing has to pause before a search runs
+pub const DEFAULT_SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// What a live search runs: compiled SQL, its parameters and the text parameter
+#[derive(Debug, Clone)]
+pub struct LiveSearchSource {
+    pub sql: String,
+    pub params: HashMap<String, Value>,
+    /// Parameter bound to the search text (without `$`)
+    pub text_param: String,
+}
+
+struct LiveSearch {
+    source: LiveSearchSource,
+    /// Bumped by every `search`; executions of older generations are dropped
+    generation: watch::Sender<u64>,
+    /// Rows the frontend has, by id
+    rows: tokio::sync::Mutex<HashMap<String, Row>>,
+    tx: mpsc::Sender<BatchWithMetadata<RowChange>>,
+}
+
+/// Live searches of a `BackendEngine`, by id
+pub struct LiveSearches {
+    debounce: Duration,
+    next_id: AtomicU64,
+    searches: Mutex<HashMap<u64, Arc<LiveSearch>>>,
+}
+
+impl Default for LiveSearches {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEARCH_DEBOUNCE)
+    }
+}
+
+impl LiveSearches {
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            next_id: AtomicU64::new(0),
+            searches: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start a live search whose current result is `rows`
+    ///
+    /// Returns the search's id and the stream of its result changes.
+    pub fn open(&self, source: LiveSearchSource, rows: &[Row]) -> (u64, RowChangeStream) {
+        let (tx, rx) = mpsc::channel(64);
+        let search = LiveSearch {
+            source,
+            generation: watch::Sender::new(0),
+            rows: tokio::sync::Mutex::new(
+                rows.iter()
+                    .filter_map(|row| Some((row_id(row)?, row.clone())))
+                    .collect(),
+            ),
+            tx,
+        };
+
+        let id = self.next_id.fetch_add(1, AtomicOrdering::Relaxed);
+        self.searches.lock().unwrap().insert(id, Arc::new(search));
+        (id, ReceiverStream::new(rx))
+    }
+
+    /// Re-run search `id` for `text` once typing pauses
+    ///
+    /// `execute` runs the search's SQL with the given parameters. Returns once
+    /// the result difference was sent, or early if a newer `search` call (or
+    /// `close`) superseded this one, cancelling its execution.
+    pub async fn search<F, Fut>(&self, id: u64, text: &str, execute: F) -> Result<()>
+    where
+        F: FnOnce(String, HashMap<String, Value>) -> Fut,
+        Fut: Future<Output = Result<Vec<Row>>>,
+    {
+        let search = self
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown live search: {}", id))?;
+        search.generation.send_modify(|generation| *generation += 1);
+        let generation = *search.generation.borrow();
+        // Subscribed after the bump, so `changed` fires for newer searches only
+        let mut newer = search.generation.subscribe();
+
+        let mut params = search.source.params.clone();
+        params.insert(
+            search.source.text_param.clone(),
+            Value::String(text.to_string()),
+        );
+        let rows = tokio::select! {
+            rows = async {
+                tokio::time::sleep(self.debounce).await;
+                execute(search.source.sql.clone(), params).await
+            } => rows?,
+            _ = newer.changed() => return Ok(()),
+        };
+
+        let mut current = search.rows.lock().await;
+        if *search.generation.borrow() != generation {
+            return Ok(());
+        }
+        let diff = result_diff(&current, &rows);
+        *current = rows
+            .into_iter()
+            .filter_map(|row| Some((row_id(&row)?, row)))
+            .collect();
+        if !diff.is_empty()
+            && search
+                .tx
+                .send(batch(format!("live_search_{}", id), diff))
+                .await
+                .is_err()
+        {
+            drop(current);
+            self.close(id);
+        }
+        Ok(())
+    }
+
+    /// What search `id` runs
+    pub fn source(&self, id: u64) -> Option<LiveSearchSource> {
+        self.get(id).map(|search| search.source.clone())
+    }
+
+    /// Stop search `id`, cancelling a pending execution; its stream ends
+    pub fn close(&self, id: u64) -> bool {
+        match self.searches.lock().unwrap().remove(&id) {
+            Some(search) => {
+                search.generation.send_modify(|generation| *generation += 1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Number of open searches
+    pub fn len(&self) -> usize {
+        self.searches.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, id: u64) -> Option<Arc<LiveSearch>> {
+        self.searches.lock().unwrap().get(&id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holon_api::Change;
+    use tokio_stream::StreamExt;
+
+    fn row(id: &str) -> Row {
+        HashMap::from([
+            ("id".to_string(), Value::String(id.to_string())),
+            (
+                "content".to_string(),
+                Value::String(format!("block {}", id)),
+            ),
+        ])
+    }
+
+    /// Rows whose id starts with the search text
+    async fn execute(_sql: String, params: HashMap<String, Value>) -> Result<Vec<Row>> {
+        let text = params["search"].as_string_owned().unwrap_or_default();
+        Ok(["apple", "apricot", "banana"]
+            .into_iter()
+            .filter(|id| id.starts_with(&text))
+            .map(row)
+            .collect())
+    }
+
+    fn source() -> LiveSearchSource {
+        LiveSearchSource {
+            sql: "SELECT * FROM blocks WHERE id LIKE $search || '%'".to_string(),
+            params: HashMap::new(),
+            text_param: "search".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_sends_additions_and_removals() {
+        let searches = LiveSearches::new(Duration::ZERO);
+        let initial = vec![row("apple"), row("apricot"), row("banana")];
+        let (id, mut stream) = searches.open(source(), &initial);
+
+        searches.search(id, "ap", execute).await.unwrap();
+        let batch = stream.next().await.unwrap();
+        let changes: Vec<_> = batch.inner.items.into_iter().map(|c| c.change).collect();
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], Change::Deleted { id, .. } if id == "banana"));
+
+        searches.search(id, "b", execute).await.unwrap();
+        let batch = stream.next().await.unwrap();
+        assert_eq!(batch.inner.items.len(), 3);
+
+        assert!(searches.close(id));
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_newer_search_cancels_pending_one() {
+        let searches = Arc::new(LiveSearches::new(Duration::from_millis(50)));
+        let (id, mut stream) = searches.open(source(), &[row("apple"), row("banana")]);
+
+        let pending = {
+            let searches = Arc::clone(&searches);
+            tokio::spawn(async move { searches.search(id, "b", execute).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        searches.search(id, "a", execute).await.unwrap();
+        pending.await.unwrap().unwrap();
+
+        // Only the result of "a" arrives: banana removed, apricot added
+        let batch = stream.next().await.unwrap();
+        let mut changes: Vec<_> = batch
+            .inner
+            .items
+            .into_iter()
+            .map(|c| match c.change {
+                Change::Created { data, .. } => format!("+{}", row_id(&data).unwrap()),
+                Change::Deleted { id, .. } => format!("-{}", id),
+                other => panic!("unexpected change {:?}", other),
+            })
+            .collect();
+        changes.sort();
+        assert_eq!(changes, vec!["+apricot", "-banana"]);
+        searches.close(id);
+        assert!(stream.next().await.is_none());
+    }
+}
@@ -558,6 +558,7 @@ impl ChangeNotifications<Block> for LoroBackend {
                             origin: ChangeOrigin::Remote {
                                 operation_id: None,
                                 trace_id: None,
+                                source: None,
                             },
                         });
                     }
@@ -894,6 +895,7 @@ impl CoreOperations for LoroBackend {
             origin: ChangeOrigin::Local {
                 operation_id: None,
                 trace_id: None,
+                source: None,
             },
         });
 
@@ -948,6 +950,7 @@ impl CoreOperations for LoroBackend {
             origin: ChangeOrigin::Local {
                 operation_id: None,
                 trace_id: None,
+                source: None,
             },
         });
 
@@ -990,6 +993,7 @@ impl CoreOperations for LoroBackend {
             origin: ChangeOrigin::Local {
                 operation_id: None,
                 trace_id: None,
+                source: None,
             },
         });
 
@@ -1064,6 +1068,7 @@ impl CoreOperations for LoroBackend {
             origin: ChangeOrigin::Local {
                 operation_id: None,
                 trace_id: None,
+                source: None,
             },
         });
 
@@ -1186,6 +1191,7 @@ impl CoreOperations for LoroBackend {
                 origin: ChangeOrigin::Local {
                     operation_id: None,
                     trace_id: None,
+                    source: None,
                 },
             });
         }
@@ -1236,6 +1242,7 @@ impl CoreOperations for LoroBackend {
                 origin: ChangeOrigin::Local {
                     operation_id: None,
                     trace_id: None,
+                    source: None,
                 },
             });
         }
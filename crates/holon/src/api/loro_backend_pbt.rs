@@ -665,6 +665,8 @@ mod stateful_tests {
                                 false
                             }
                         }
+                        // Block backends only emit whole-row updates
+                        Change::ColumnChange { .. } => false,
                     };
 
                     assert!(
@@ -399,6 +399,7 @@ impl CoreOperations for MemoryBackend {
                 origin: ChangeOrigin::Local {
                     operation_id: None,
                     trace_id: None,
+                    source: None,
                 },
             },
         );
@@ -450,6 +451,7 @@ impl CoreOperations for MemoryBackend {
                 origin: ChangeOrigin::Local {
                     operation_id: None,
                     trace_id: None,
+                    source: None,
                 },
             },
         );
@@ -492,6 +494,7 @@ impl CoreOperations for MemoryBackend {
                 origin: ChangeOrigin::Local {
                     operation_id: None,
                     trace_id: None,
+                    source: None,
                 },
             },
         );
@@ -624,6 +627,7 @@ impl CoreOperations for MemoryBackend {
                 origin: ChangeOrigin::Local {
                     operation_id: None,
                     trace_id: None,
+                    source: None,
                 },
             },
         );
@@ -731,6 +735,7 @@ impl CoreOperations for MemoryBackend {
                     origin: ChangeOrigin::Local {
                         operation_id: None,
                         trace_id: None,
+                        source: None,
                     },
                 },
             );
@@ -779,6 +784,7 @@ impl CoreOperations for MemoryBackend {
                     origin: ChangeOrigin::Local {
                         operation_id: None,
                         trace_id: None,
+                        source: None,
                     },
                 },
             );
@@ -829,6 +835,7 @@ impl ChangeNotifications<Block> for MemoryBackend {
                                 origin: ChangeOrigin::Remote {
                                     operation_id: None,
                                     trace_id: None,
+                                    source: None,
                                 },
                             })
                         } else {
@@ -24,8 +24,15 @@ pub mod repository;
 pub mod types;
 
 pub mod backend_engine;
+pub mod diagnostics;
+pub mod live_search;
 pub mod operation_dispatcher;
+pub mod query_cache;
+pub mod query_filters;
+pub mod query_plan;
+pub mod result_window;
 pub mod ui_types;
+pub mod view_loader;
 
 #[cfg(test)]
 mod tests;
@@ -46,8 +53,13 @@ pub use holon_api::{
 
 // Re-export render engine types for FFI
 pub use backend_engine::BackendEngine;
-pub use operation_dispatcher::OperationDispatcher;
+pub use diagnostics::{Diagnostics, ProviderDiagnostics};
+pub use operation_dispatcher::{OperationDispatcher, OperationRouting, RoutingRule};
+pub use query_cache::{CompiledQuery, QueryCache, QueryCacheConfig, QueryCacheStats};
+pub use query_plan::{IndexSuggestion, QueryPlan, QueryPlanStep};
+pub use result_window::{Window, WindowChangeStream, WindowedQueries, WindowedResult};
 pub use ui_types::{CursorPosition, UiState};
+pub use view_loader::{ViewDefinition, ViewEvent, ViewLoader, ViewLoaderConfig};
 
 // Re-export OperationDescriptor and OperationParam for FRB type generation
 pub use holon_api::{OperationDescriptor, OperationParam};
@@ -5,16 +5,118 @@
 //!
 //! This implements the Composite Pattern - both individual caches (QueryableCache<T>)
 //! and the dispatcher implement OperationProvider, allowing recursive composition.
+//!
+//! # Provider resolution
+//!
+//! When several providers handle the same entity and operation (e.g. the fake and
+//! the real Todoist provider, or one provider per workspace), the provider is chosen
+//! in this order:
+//!
+//! 1. The first `RoutingRule` of the dispatcher's `OperationRouting` matching the
+//!    operation names the provider (by `OperationProvider::provider_name`).
+//! 2. Otherwise, if the operation has a `workspace` parameter, providers of other
+//!    workspaces are left out.
+//! 3. The provider with the highest `OperationProvider::priority` wins; a tie
+//!    between the highest is an error, since the dispatch would be ambiguous.
+//!
+//! With `RowSecurity`, operations on rows hidden by the row filters are rejected
+//! before they reach a provider.
 
 use async_trait::async_trait;
 use ferrous_di::{DiResult, Resolver, ServiceCollection, ServiceModule};
-use std::collections::HashSet;
-use std::sync::Arc;
-use tracing::{error, info};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
 
-use crate::core::datasource::{OperationObserver, OperationProvider, Result, UndoAction};
+use crate::core::datasource::{
+    HolonError, OperationObserver, OperationProvider, Result, UndoAction,
+};
+use crate::core::operation_log::OperationLogStore;
+use crate::core::row_security::{RowSecurity, RowSecurityConfig};
+use crate::storage::turso::TursoBackend;
 use crate::storage::types::StorageEntity;
-use holon_api::{Operation, OperationDescriptor};
+use holon_api::{CURRENT_IDEMPOTENCY_KEY, Operation, OperationDescriptor};
+
+/// Parameter naming the workspace an operation targets
+pub const WORKSPACE_PARAM: &str = "workspace";
+
+/// Parameter naming where an operation comes from (e.g. "ui", "import", "sync")
+pub const ORIGIN_PARAM: &str = "origin";
+
+/// Routes matching operations to a named provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutingRule {
+    /// Entity name, or a pattern with one `*` wildcard (e.g. "todoist_*", "*")
+    pub entity_pattern: String,
+    /// Only operations with this `workspace` parameter
+    pub workspace: Option<String>,
+    /// Only operations with this `origin` parameter
+    pub origin: Option<String>,
+    /// `provider_name` of the provider to route to
+    pub provider: String,
+}
+
+impl RoutingRule {
+    /// Route all operations on entities matching `entity_pattern` to `provider`
+    pub fn new(entity_pattern: impl Into<String>, provider: impl Into<String>) -> Self {
+        Self {
+            entity_pattern: entity_pattern.into(),
+            workspace: None,
+            origin: None,
+            provider: provider.into(),
+        }
+    }
+
+    pub fn with_workspace(mut self, workspace: impl Into<String>) -> Self {
+        self.workspace = Some(workspace.into());
+        self
+    }
+
+    pub fn with_origin(mut self, origin: impl Into<String>) -> Self {
+        self.origin = Some(origin.into());
+        self
+    }
+
+    fn matches(&self, entity_name: &str, params: &StorageEntity) -> bool {
+        let param_matches = |name: &str, expected: &Option<String>| match expected {
+            Some(expected) => {
+                params.get(name).and_then(|v| v.as_string()) == Some(expected.as_str())
+            }
+            None => true,
+        };
+        matches_pattern(&self.entity_pattern, entity_name)
+            && param_matches(WORKSPACE_PARAM, &self.workspace)
+            && param_matches(ORIGIN_PARAM, &self.origin)
+    }
+}
+
+/// Routing rules of an `OperationDispatcher`, tried in order
+///
+/// Register an instance in the DI container to configure the dispatcher.
+#[derive(Debug, Clone, Default)]
+pub struct OperationRouting {
+    pub rules: Vec<RoutingRule>,
+}
+
+impl OperationRouting {
+    pub fn with_rule(mut self, rule: RoutingRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+}
+
+/// Whether `name` matches `pattern`, which may contain one `*` wildcard
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+        }
+        None => pattern == name,
+    }
+}
 
 /// Composite dispatcher that aggregates multiple OperationProvider instances
 ///
@@ -29,6 +131,44 @@ pub struct OperationDispatcher {
     providers: Vec<Arc<dyn OperationProvider>>,
     /// List of operation observers (notified after execution)
     observers: Vec<Arc<dyn OperationObserver>>,
+    /// Undo actions of recently succeeded operations, by idempotency key
+    completed: Mutex<CompletedOperations>,
+    /// Persisted operations, to recognise retries across restarts
+    operation_log: Option<Arc<OperationLogStore>>,
+    /// Rules choosing between providers handling the same operation
+    routing: OperationRouting,
+    /// Row filters operations must pass (see `RowSecurity`)
+    row_security: Option<Arc<RowSecurity>>,
+}
+
+/// Number of succeeded operations remembered for deduplicating retries
+const COMPLETED_OPERATIONS_CAPACITY: usize = 1024;
+
+/// Bounded map of idempotency key to undo action, evicting the oldest entry
+#[derive(Default)]
+struct CompletedOperations {
+    undo_actions: HashMap<String, UndoAction>,
+    order: VecDeque<String>,
+}
+
+impl CompletedOperations {
+    fn get(&self, key: &str) -> Option<UndoAction> {
+        self.undo_actions.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: String, undo_action: UndoAction) {
+        if key.is_empty() {
+            return;
+        }
+        if self.undo_actions.insert(key.clone(), undo_action).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > COMPLETED_OPERATIONS_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.undo_actions.remove(&oldest);
+            }
+        }
+    }
 }
 
 impl OperationDispatcher {
@@ -46,6 +186,10 @@ impl OperationDispatcher {
         Self {
             providers,
             observers: Vec::new(),
+            completed: Mutex::new(CompletedOperations::default()),
+            operation_log: None,
+            routing: OperationRouting::default(),
+            row_security: None,
         }
     }
 
@@ -57,9 +201,42 @@ impl OperationDispatcher {
         Self {
             providers,
             observers,
+            completed: Mutex::new(CompletedOperations::default()),
+            operation_log: None,
+            routing: OperationRouting::default(),
+            row_security: None,
         }
     }
 
+    /// Choose between providers handling the same operation with `routing`
+    pub fn with_routing(mut self, routing: OperationRouting) -> Self {
+        self.routing = routing;
+        self
+    }
+
+    /// Also skip retries of operations found in `operation_log`
+    ///
+    /// The in-memory dedupe cache is empty after a restart; the operation log
+    /// still has the idempotency keys of the operations executed before it.
+    pub fn with_operation_log(mut self, operation_log: Arc<OperationLogStore>) -> Self {
+        self.operation_log = Some(operation_log);
+        self
+    }
+
+    /// Reject operations on rows hidden by the row filters of `row_security`
+    pub fn with_row_security(mut self, row_security: Arc<RowSecurity>) -> Self {
+        self.row_security = Some(row_security);
+        self
+    }
+
+    /// Row filters to compile into every query, by table
+    pub fn row_filters(&self) -> HashMap<String, String> {
+        self.row_security
+            .as_ref()
+            .map(|row_security| row_security.query_filters().clone())
+            .unwrap_or_default()
+    }
+
     /// Add an observer to this dispatcher
     pub fn add_observer(&mut self, observer: Arc<dyn OperationObserver>) {
         self.observers.push(observer);
@@ -101,6 +278,19 @@ impl OperationDispatcher {
         entity_names.into_iter().collect()
     }
 
+    /// Entities declaring operations (`#[entity(operations(...))]`) without a provider
+    ///
+    /// These are linked into the program but their datasource was not registered,
+    /// e.g. because an optional integration is not configured.
+    pub fn unserved_entities(&self) -> Vec<&'static str> {
+        holon_api::registered_entities()
+            .into_iter()
+            .filter(|entity| !entity.operations.is_empty())
+            .map(|entity| entity.entity_name)
+            .filter(|entity_name| !self.has_provider(entity_name))
+            .collect()
+    }
+
     /// Get the number of registered providers
     pub fn provider_count(&self) -> usize {
         self.providers.len()
@@ -110,100 +300,156 @@ impl OperationDispatcher {
     pub fn providers(&self) -> Vec<Arc<dyn OperationProvider>> {
         self.providers.clone()
     }
-}
 
-impl Default for OperationDispatcher {
-    fn default() -> Self {
-        Self::new(Vec::new())
-    }
-}
-
-#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
-#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
-impl OperationProvider for OperationDispatcher {
-    /// Get all operations from all registered providers
+    /// The provider executing `op_name` on `entity_name` (see the module docs)
     ///
-    /// Aggregates operations from all providers and includes wildcard operations.
-    fn operations(&self) -> Vec<OperationDescriptor> {
-        let mut ops: Vec<OperationDescriptor> = self
+    /// Fails if no provider handles the operation, if the matching routing rule
+    /// names a provider that doesn't, or if the choice is ambiguous.
+    pub fn resolve_provider(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        params: &StorageEntity,
+    ) -> Result<Arc<dyn OperationProvider>> {
+        let candidates: Vec<_> = self
             .providers
             .iter()
-            .flat_map(|provider| provider.operations())
+            .filter(|provider| {
+                provider
+                    .operations()
+                    .iter()
+                    .any(|op| op.entity_name == entity_name && op.name == op_name)
+            })
             .collect();
+        if candidates.is_empty() {
+            return Err(format!("No provider registered for entity: {}", entity_name).into());
+        }
+        if let [provider] = candidates.as_slice() {
+            return Ok(Arc::clone(*provider));
+        }
 
-        // Add wildcard sync operation if any provider has a "sync" operation
-        let has_sync_ops = ops.iter().any(|op| op.name == "sync");
-        if has_sync_ops {
-            ops.push(OperationDescriptor {
-                entity_name: "*".to_string(),
-                entity_short_name: "all".to_string(), // Wildcard operations affect all entities
-                id_column: String::new(),             // Wildcard operations don't need an ID column
-                name: "sync".to_string(),
-                display_name: "Sync".to_string(),
-                description: "Sync registered syncable providers".to_string(),
-                required_params: vec![],
-                affected_fields: vec![], // Wildcard operations don't affect specific fields
-                param_mappings: vec![],
-                precondition: None,
-            });
+        if let Some(rule) = self
+            .routing
+            .rules
+            .iter()
+            .find(|rule| rule.matches(entity_name, params))
+        {
+            return candidates
+                .iter()
+                .find(|provider| provider.provider_name() == rule.provider)
+                .map(|provider| Arc::clone(*provider))
+                .ok_or_else(|| {
+                    format!(
+                        "Routing rule for '{}' names provider '{}', which doesn't handle {}.{}",
+                        rule.entity_pattern, rule.provider, entity_name, op_name
+                    )
+                    .into()
+                });
         }
 
-        ops
+        let workspace = params.get(WORKSPACE_PARAM).and_then(|v| v.as_string());
+        let candidates: Vec<_> = candidates
+            .into_iter()
+            .filter(|provider| match (workspace, provider.workspace()) {
+                (Some(workspace), Some(provider_workspace)) => provider_workspace == workspace,
+                _ => true,
+            })
+            .collect();
+
+        let highest = candidates
+            .iter()
+            .map(|provider| provider.priority())
+            .max()
+            .ok_or_else(|| {
+                format!(
+                    "No provider of workspace '{}' handles {}.{}",
+                    workspace.unwrap_or_default(),
+                    entity_name,
+                    op_name
+                )
+            })?;
+        let mut winners = candidates
+            .into_iter()
+            .filter(|provider| provider.priority() == highest);
+        let winner = winners.next().cloned();
+        let tied: Vec<_> = winners.map(|provider| provider.provider_name()).collect();
+        match winner {
+            Some(winner) if tied.is_empty() => Ok(winner),
+            Some(winner) => Err(format!(
+                "Ambiguous dispatch of {}.{}: providers {:?} and {:?} have priority {}; \
+                 add a routing rule or change a priority",
+                entity_name,
+                op_name,
+                winner.provider_name(),
+                tied,
+                highest
+            )
+            .into()),
+            None => Err(format!("No provider registered for entity: {}", entity_name).into()),
+        }
     }
 
-    /// Find operations that can be executed with given arguments
+    /// Execute an operation, skipping it if one with the same idempotency key succeeded
     ///
-    /// Filters operations based on entity_name and available_args.
-    ///
-    /// Special handling for generic operations:
-    /// - `set_field`: Only requires "id" to be available (field and value are runtime parameters)
-    /// - Other operations: Require all parameters to be in available_args
-    fn find_operations(
-        &self,
-        entity_name: &str,
-        available_args: &[String],
-    ) -> Vec<OperationDescriptor> {
-        // Filter operations from all providers
-        self.operations()
-            .into_iter()
-            .filter(|op| {
-                if op.entity_name != entity_name {
-                    return false;
-                }
+    /// Retrying an operation (e.g. replaying it from the operation log) returns the
+    /// first execution's undo action instead of applying it twice. Recent operations
+    /// are remembered in memory; older ones and those executed before a restart are
+    /// looked up in the operation log, if one is attached. Providers read the key via
+    /// `Operation::current_idempotency_key()` to deduplicate remote requests.
+    pub async fn execute(&self, operation: Operation) -> Result<UndoAction> {
+        let key = operation.idempotency_key.clone();
+        if let Some(undo_action) = self.completed_undo_action(&key).await {
+            info!(
+                "[OperationDispatcher] Skipping duplicate operation: entity={}, op={}, key={}",
+                operation.entity_name, operation.op_name, key
+            );
+            return Ok(undo_action);
+        }
 
-                // Special case: set_field is a generic operation that can update any field
-                // It only needs "id" from the query columns; "field" and "value" are runtime parameters
-                if op.name == "set_field" {
-                    // Only require "id" to be available
-                    return op
-                        .required_params
-                        .iter()
-                        .any(|p| p.name == "id" && available_args.contains(&p.name));
-                }
+        let result = CURRENT_IDEMPOTENCY_KEY
+            .scope(key.clone(), self.dispatch(&operation))
+            .await;
+        if let Ok(undo_action) = &result {
+            self.completed
+                .lock()
+                .unwrap()
+                .insert(key, undo_action.clone());
+        }
+        result
+    }
 
-                // For other operations, a param is considered available if:
-                // 1. It's directly in available_args, OR
-                // 2. It has a param_mapping that can provide it at runtime
-                op.required_params.iter().all(|p| {
-                    // Direct availability
-                    if available_args.contains(&p.name) {
-                        return true;
-                    }
-                    // Can be provided via param_mapping at runtime
-                    op.param_mappings
-                        .iter()
-                        .any(|m| m.provides.contains(&p.name))
-                })
-            })
-            .collect()
+    /// Whether an operation with this idempotency key already succeeded
+    pub async fn is_completed(&self, idempotency_key: &str) -> bool {
+        self.completed_undo_action(idempotency_key).await.is_some()
     }
 
-    /// Execute an operation by routing to the correct provider
+    /// Undo action of the operation that already succeeded under `key`, if any
+    async fn completed_undo_action(&self, key: &str) -> Option<UndoAction> {
+        if key.is_empty() {
+            return None;
+        }
+        let cached = self.completed.lock().unwrap().get(key);
+        if cached.is_some() {
+            return cached;
+        }
+
+        let operation_log = self.operation_log.as_ref()?;
+        match operation_log.find_by_idempotency_key(key).await {
+            Ok(entry) => entry.map(|entry| UndoAction::from(entry.get_inverse())),
+            Err(e) => {
+                warn!(
+                    "[OperationDispatcher] Failed to look up idempotency key {} in operation log: {}",
+                    key, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Route an operation to the correct provider
     ///
-    /// # Arguments
-    /// * `entity_name` - Entity identifier (e.g., "todoist-task" or "*" for wildcard)
-    /// * `op_name` - Operation name (e.g., "set_completion" or "sync")
-    /// * `params` - Operation parameters as StorageEntity
+    /// The operation's entity_name may be "*" to run it on all providers that
+    /// have an operation named op_name.
     ///
     /// # Returns
     /// Result indicating success or failure
@@ -212,15 +458,14 @@ impl OperationProvider for OperationDispatcher {
     /// Returns an error if:
     /// - No provider is registered for the entity_name (or wildcard matches no providers)
     /// - The provider's execute_operation returns an error
-    async fn execute_operation(
-        &self,
-        entity_name: &str,
-        op_name: &str,
-        params: StorageEntity,
-    ) -> Result<UndoAction> {
+    async fn dispatch(&self, operation: &Operation) -> Result<UndoAction> {
         use tracing::Instrument;
         use tracing::{debug, info};
 
+        let entity_name = operation.entity_name.as_str();
+        let op_name = operation.op_name.as_str();
+        let params = operation.params.clone();
+
         // Create tracing span that will be bridged to OpenTelemetry
         // Use .instrument() to maintain context across async boundaries
         let span = tracing::span!(
@@ -323,42 +568,57 @@ impl OperationProvider for OperationDispatcher {
             }
         } else {
             // Regular operation - route to specific provider
-            let available_ops: Vec<_> = self.providers.iter().flat_map(|p| p.operations()).collect();
-            let matching_ops: Vec<_> = available_ops
-                .iter()
-                .filter(|op| op.entity_name == entity_name && op.name == op_name)
-                .collect();
+            let provider = match self.resolve_provider(entity_name, op_name, &params) {
+                Ok(provider) => provider,
+                Err(e) => {
+                    // Log all available entity names for debugging
+                    error!(
+                        "[OperationDispatcher] Cannot route entity: '{}' (operation: '{}'): {}. Available entities: {:?}",
+                        entity_name, op_name, e, self.registered_entities()
+                    );
+                    return Err(e);
+                }
+            };
+            let descriptor = provider
+                .operations()
+                .into_iter()
+                .find(|op| op.entity_name == entity_name && op.name == op_name)
+                .ok_or_else(|| format!("No provider registered for entity: {}", entity_name))?;
 
-            debug!(
-                "[OperationDispatcher] Found {} matching operations for entity={}, op={}",
-                matching_ops.len(), entity_name, op_name
-            );
+            // Params a triggering source doesn't carry get its mapping's defaults
+            let mut params = params;
+            descriptor.apply_param_defaults(&mut params);
 
-            if matching_ops.is_empty() {
-                // Log all available entity names for debugging
-                let entity_names: std::collections::HashSet<_> =
-                    available_ops.iter().map(|op| &op.entity_name).collect();
-                error!(
-                    "[OperationDispatcher] No provider registered for entity: '{}' (operation: '{}'). Available entities: {:?}",
-                    entity_name, op_name, entity_names
-                );
-                return Err(format!("No provider registered for entity: {}", entity_name).into());
+            // Reject parameters violating a #[require(...)] clause before the provider runs.
+            // Missing parameters are left to the provider, which may derive them.
+            match descriptor.check_precondition(&params) {
+                Ok(Some(violation)) => {
+                    info!(
+                        "[OperationDispatcher] Precondition failed: entity={}, op={}: {}",
+                        entity_name, op_name, violation
+                    );
+                    return Err(Box::new(HolonError::from(violation)));
+                }
+                Ok(None) => {}
+                Err(e) => debug!(
+                    "[OperationDispatcher] Precondition not checked: entity={}, op={}: {}",
+                    entity_name, op_name, e
+                ),
             }
 
-            let provider = self
-                .providers
-                .iter()
-                .find(|provider| {
-                    provider
-                        .operations()
-                        .iter()
-                        .any(|op| op.entity_name == entity_name && op.name == op_name)
-                })
-                .ok_or_else(|| format!("No provider registered for entity: {}", entity_name))?;
+            if let Some(row_security) = &self.row_security {
+                if let Err(e) = row_security.check_operation(&descriptor, &params).await {
+                    info!(
+                        "[OperationDispatcher] Row filter rejected operation: entity={}, op={}: {}",
+                        entity_name, op_name, e
+                    );
+                    return Err(Box::new(e));
+                }
+            }
 
             info!(
-                "[OperationDispatcher] Routing operation to provider: entity={}, op={}",
-                entity_name, op_name
+                "[OperationDispatcher] Routing operation to provider: entity={}, op={}, provider={:?}",
+                entity_name, op_name, provider.provider_name()
             );
 
             // Clone params before execution for observer notification
@@ -370,31 +630,23 @@ impl OperationProvider for OperationDispatcher {
                 .await?;
 
             // Set entity_name on the inverse operation if present
-            let result = match undo_action {
-                UndoAction::Undo(mut op) => {
-                    op.entity_name = entity_name.to_string();
-                    UndoAction::Undo(op)
-                }
-                UndoAction::Irreversible => UndoAction::Irreversible,
-            };
+            let result = undo_action.with_entity_name(entity_name);
 
-            match &result {
-                UndoAction::Undo(_) => {
-                    info!(
-                        "[OperationDispatcher] Provider execution succeeded: entity={}, op={} (inverse operation available)",
-                        entity_name, op_name
-                    );
-                }
-                UndoAction::Irreversible => {
-                    info!(
-                        "[OperationDispatcher] Provider execution succeeded: entity={}, op={} (no inverse operation)",
-                        entity_name, op_name
-                    );
-                }
+            if result.is_reversible() {
+                info!(
+                    "[OperationDispatcher] Provider execution succeeded: entity={}, op={} (inverse operation available)",
+                    entity_name, op_name
+                );
+            } else {
+                info!(
+                    "[OperationDispatcher] Provider execution succeeded: entity={}, op={} (no inverse operation)",
+                    entity_name, op_name
+                );
             }
 
             // Notify observers of successful execution
-            let executed_operation = Operation::new(entity_name, op_name, "", params_for_observer);
+            let executed_operation = Operation::new(entity_name, op_name, "", params_for_observer)
+                .with_idempotency_key(operation.idempotency_key.clone());
             self.notify_observers(entity_name, &executed_operation, &result).await;
 
             Ok(result)
@@ -405,6 +657,108 @@ impl OperationProvider for OperationDispatcher {
     }
 }
 
+impl Default for OperationDispatcher {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationProvider for OperationDispatcher {
+    /// Get all operations from all registered providers
+    ///
+    /// Aggregates operations from all providers and includes wildcard operations.
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        let mut ops: Vec<OperationDescriptor> = self
+            .providers
+            .iter()
+            .flat_map(|provider| provider.operations())
+            .collect();
+
+        // Add wildcard sync operation if any provider has a "sync" operation
+        let has_sync_ops = ops.iter().any(|op| op.name == "sync");
+        if has_sync_ops {
+            ops.push(OperationDescriptor {
+                entity_name: "*".to_string(),
+                entity_short_name: "all".to_string(), // Wildcard operations affect all entities
+                id_column: String::new(),             // Wildcard operations don't need an ID column
+                name: "sync".to_string(),
+                display_name: "Sync".to_string(),
+                description: "Sync registered syncable providers".to_string(),
+                required_params: vec![],
+                affected_fields: vec![], // Wildcard operations don't affect specific fields
+                param_mappings: vec![],
+                precondition: None,
+                simulation: None,
+            });
+        }
+
+        ops
+    }
+
+    /// Find operations that can be executed with given arguments
+    ///
+    /// Filters operations based on entity_name and available_args.
+    ///
+    /// Special handling for generic operations:
+    /// - `set_field`: Only requires "id" to be available (field and value are runtime parameters)
+    /// - Other operations: Require all parameters to be in available_args
+    fn find_operations(
+        &self,
+        entity_name: &str,
+        available_args: &[String],
+    ) -> Vec<OperationDescriptor> {
+        // Filter operations from all providers
+        self.operations()
+            .into_iter()
+            .filter(|op| {
+                if op.entity_name != entity_name {
+                    return false;
+                }
+
+                // Special case: set_field is a generic operation that can update any field
+                // It only needs "id" from the query columns; "field" and "value" are runtime parameters
+                if op.name == "set_field" {
+                    // Only require "id" to be available
+                    return op
+                        .required_params
+                        .iter()
+                        .any(|p| p.name == "id" && available_args.contains(&p.name));
+                }
+
+                // For other operations, a param is considered available if:
+                // 1. It's directly in available_args, OR
+                // 2. It has a param_mapping that can provide it at runtime
+                op.required_params.iter().all(|p| {
+                    // Direct availability
+                    if available_args.contains(&p.name) {
+                        return true;
+                    }
+                    // Can be provided via param_mapping at runtime
+                    op.param_mappings
+                        .iter()
+                        .any(|m| m.provides.contains(&p.name))
+                })
+            })
+            .collect()
+    }
+
+    /// Execute an operation by routing to the correct provider
+    ///
+    /// Each call is a new operation with its own idempotency key; use
+    /// `OperationDispatcher::execute` to retry an existing one.
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        params: StorageEntity,
+    ) -> Result<UndoAction> {
+        self.execute(Operation::new(entity_name, op_name, "", params))
+            .await
+    }
+}
+
 pub struct OperationModule;
 
 impl ServiceModule for OperationModule {
@@ -427,7 +781,29 @@ impl ServiceModule for OperationModule {
                 observers.len()
             );
 
-            OperationDispatcher::with_observers(providers, observers)
+            let routing = r
+                .get::<OperationRouting>()
+                .map(|routing| (*routing).clone())
+                .unwrap_or_default();
+
+            let mut dispatcher =
+                OperationDispatcher::with_observers(providers, observers).with_routing(routing);
+            if let Ok(operation_log) = r.get::<OperationLogStore>() {
+                dispatcher = dispatcher.with_operation_log(operation_log);
+            }
+            if let Ok(config) = r.get::<RowSecurityConfig>() {
+                let backend = r.get_required::<RwLock<TursoBackend>>();
+                dispatcher = dispatcher
+                    .with_row_security(Arc::new(RowSecurity::new(backend, (*config).clone())));
+            }
+            let unserved = dispatcher.unserved_entities();
+            if !unserved.is_empty() {
+                info!(
+                    "[OperationModule] No operation provider for entities: {}",
+                    unserved.join(", ")
+                );
+            }
+            dispatcher
         });
         Ok(())
     }
@@ -436,6 +812,8 @@ impl ServiceModule for OperationModule {
 #[cfg(test)]
 mod tests {
     use self::super::*;
+    use crate::core::operation_log::OperationLogObserver;
+    use crate::storage::test_support::memory_backend;
 
     // Mock OperationProvider for testing
     struct MockProvider {
@@ -482,6 +860,7 @@ mod tests {
             affected_fields: vec![],
             param_mappings: vec![],
             precondition: None,
+            simulation: None,
         }
     }
 
@@ -543,10 +922,326 @@ mod tests {
             .execute_operation("entity2", "test_op", params)
             .await;
         assert!(result.is_err());
-        assert!(result
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("No provider registered")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_operation_checks_precondition() {
+        let mut operation = create_test_operation("entity1", "test_op");
+        operation.precondition = Some(Arc::new(Box::new(
+            |params: &std::collections::HashMap<String, Box<dyn std::any::Any + Send + Sync>>| {
+                let priority = params
+                    .get("priority")
+                    .and_then(|v| v.downcast_ref::<holon_api::Value>())
+                    .and_then(|v| v.as_i64())
+                    .ok_or("Missing parameter: priority")?;
+                Ok((priority > 5).then(|| holon_api::PreconditionViolation {
+                    operation: "test_op".to_string(),
+                    clause: "priority <= 5".to_string(),
+                    message: None,
+                    params: vec![("priority".to_string(), holon_api::Value::Integer(priority))],
+                }))
+            },
+        ) as Box<holon_api::PreconditionChecker>));
+        let dispatcher = OperationDispatcher::new(vec![Arc::new(MockProvider {
+            entity_name: "entity1".to_string(),
+            operations_list: vec![operation],
+        })]);
+
+        let params = StorageEntity::from([("priority".to_string(), holon_api::Value::Integer(6))]);
+        let err = dispatcher
+            .execute_operation("entity1", "test_op", params)
+            .await
+            .unwrap_err();
+        match HolonError::from(err) {
+            HolonError::PreconditionViolated(violation) => {
+                assert_eq!(violation.clause, "priority <= 5");
+                assert_eq!(
+                    violation.to_string(),
+                    "test_op requires priority <= 5 (got priority = 6)"
+                );
+            }
+            other => panic!("Expected a precondition violation, got {:?}", other),
+        }
+
+        let params = StorageEntity::from([("priority".to_string(), holon_api::Value::Integer(3))]);
+        assert!(
+            dispatcher
+                .execute_operation("entity1", "test_op", params)
+                .await
+                .is_ok()
+        );
+    }
+
+    /// Provider that records the idempotency key of every execution
+    struct RecordingProvider {
+        keys: Mutex<Vec<Option<String>>>,
+    }
+
+    #[async_trait]
+    impl OperationProvider for RecordingProvider {
+        fn operations(&self) -> Vec<OperationDescriptor> {
+            vec![create_test_operation("entity1", "test_op")]
+        }
+
+        async fn execute_operation(
+            &self,
+            _entity_name: &str,
+            _op_name: &str,
+            _params: StorageEntity,
+        ) -> Result<UndoAction> {
+            self.keys
+                .lock()
+                .unwrap()
+                .push(Operation::current_idempotency_key());
+            Ok(UndoAction::Irreversible)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_skips_retried_operation() {
+        let provider = Arc::new(RecordingProvider {
+            keys: Mutex::new(Vec::new()),
+        });
+        let dispatcher = OperationDispatcher::new(vec![provider.clone()]);
+
+        let operation = Operation::new("entity1", "test_op", "", StorageEntity::new());
+        dispatcher.execute(operation.clone()).await.unwrap();
+        dispatcher.execute(operation.clone()).await.unwrap();
+        assert_eq!(
+            *provider.keys.lock().unwrap(),
+            vec![Some(operation.idempotency_key.clone())]
+        );
+
+        // A new operation with the same parameters gets its own key and runs again
+        dispatcher
+            .execute_operation("entity1", "test_op", StorageEntity::new())
+            .await
+            .unwrap();
+        assert_eq!(provider.keys.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_skips_operation_logged_before_restart() {
+        let operation_log = Arc::new(OperationLogStore::new(memory_backend().await));
+        operation_log.initialize_schema().await.unwrap();
+        let provider = Arc::new(RecordingProvider {
+            keys: Mutex::new(Vec::new()),
+        });
+
+        let operation = Operation::new("entity1", "test_op", "", StorageEntity::new());
+        let dispatcher = OperationDispatcher::with_observers(
+            vec![provider.clone()],
+            vec![Arc::new(OperationLogObserver::new(operation_log.clone()))],
+        )
+        .with_operation_log(operation_log.clone());
+        dispatcher.execute(operation.clone()).await.unwrap();
+
+        // A fresh dispatcher remembers nothing, but finds the key in the log
+        let restarted =
+            OperationDispatcher::new(vec![provider.clone()]).with_operation_log(operation_log);
+        restarted.execute(operation.clone()).await.unwrap();
+        assert_eq!(
+            *provider.keys.lock().unwrap(),
+            vec![Some(operation.idempotency_key.clone())]
+        );
+
+        // Operations that were never logged still run
+        restarted
+            .execute_operation("entity1", "test_op", StorageEntity::new())
+            .await
+            .unwrap();
+        assert_eq!(provider.keys.lock().unwrap().len(), 2);
+    }
+
+    /// Provider of `entity1.move_to`, recording the params of every execution
+    struct MoveProvider {
+        params: Mutex<Vec<StorageEntity>>,
+    }
+
+    #[async_trait]
+    impl OperationProvider for MoveProvider {
+        fn operations(&self) -> Vec<OperationDescriptor> {
+            let mut operation = create_test_operation("entity1", "move_to");
+            operation.param_mappings = vec![holon_api::ParamMapping {
+                from: "tree_position".to_string(),
+                provides: vec!["parent_id".to_string(), "position".to_string()],
+                defaults: HashMap::from([(
+                    "position".to_string(),
+                    holon_api::Value::String("last".to_string()),
+                )]),
+            }];
+            vec![operation]
+        }
+
+        async fn execute_operation(
+            &self,
+            _entity_name: &str,
+            _op_name: &str,
+            params: StorageEntity,
+        ) -> Result<UndoAction> {
+            self.params.lock().unwrap().push(params);
+            Ok(UndoAction::Irreversible)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_applies_param_defaults() {
+        let provider = Arc::new(MoveProvider {
+            params: Mutex::new(Vec::new()),
+        });
+        let dispatcher = OperationDispatcher::new(vec![provider.clone()]);
+        let position = |value: &str| {
+            StorageEntity::from([
+                ("id".to_string(), holon_api::Value::from("a")),
+                ("parent_id".to_string(), holon_api::Value::from("b")),
+                ("position".to_string(), holon_api::Value::from(value)),
+            ])
+        };
+
+        let mut params = position("first");
+        dispatcher
+            .execute_operation("entity1", "move_to", params.clone())
+            .await
+            .unwrap();
+        params.remove("position");
+        dispatcher
+            .execute_operation("entity1", "move_to", params)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *provider.params.lock().unwrap(),
+            vec![position("first"), position("last")]
+        );
+    }
+
+    /// Provider of `entity1.test_op` with routing metadata
+    struct NamedProvider {
+        name: &'static str,
+        priority: i32,
+        workspace: Option<&'static str>,
+    }
+
+    #[async_trait]
+    impl OperationProvider for NamedProvider {
+        fn operations(&self) -> Vec<OperationDescriptor> {
+            vec![create_test_operation("entity1", "test_op")]
+        }
+
+        async fn execute_operation(
+            &self,
+            _entity_name: &str,
+            _op_name: &str,
+            _params: StorageEntity,
+        ) -> Result<UndoAction> {
+            Ok(UndoAction::Irreversible)
+        }
+
+        fn provider_name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn priority(&self) -> i32 {
+            self.priority
+        }
+
+        fn workspace(&self) -> Option<String> {
+            self.workspace.map(str::to_string)
+        }
+    }
+
+    fn named(
+        name: &'static str,
+        priority: i32,
+        workspace: Option<&'static str>,
+    ) -> Arc<dyn OperationProvider> {
+        Arc::new(NamedProvider {
+            name,
+            priority,
+            workspace,
+        })
+    }
+
+    fn resolved(dispatcher: &OperationDispatcher, params: &[(&str, &str)]) -> Result<String> {
+        let params = params
+            .iter()
+            .map(|(k, v)| (k.to_string(), holon_api::Value::String(v.to_string())))
+            .collect();
+        dispatcher
+            .resolve_provider("entity1", "test_op", &params)
+            .map(|provider| provider.provider_name())
+    }
+
+    #[test]
+    fn test_resolve_provider_by_priority() {
+        let dispatcher = OperationDispatcher::new(vec![
+            named("todoist-fake", -1, None),
+            named("todoist", 0, None),
+        ]);
+        assert_eq!(resolved(&dispatcher, &[]).unwrap(), "todoist");
+
+        let dispatcher =
+            OperationDispatcher::new(vec![named("first", 0, None), named("second", 0, None)]);
+        let err = resolved(&dispatcher, &[]).unwrap_err().to_string();
+        assert!(
+            err.contains("Ambiguous dispatch of entity1.test_op"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_resolve_provider_by_routing_rule() {
+        let dispatcher = OperationDispatcher::new(vec![
+            named("todoist-fake", -1, None),
+            named("todoist", 0, None),
+        ])
+        .with_routing(
+            OperationRouting::default()
+                .with_rule(RoutingRule::new("entity*", "todoist-fake").with_origin("test"))
+                .with_rule(RoutingRule::new("other_entity", "todoist-fake"))
+                .with_rule(RoutingRule::new("*", "missing").with_workspace("personal")),
+        );
+
+        assert_eq!(resolved(&dispatcher, &[]).unwrap(), "todoist");
+        assert_eq!(
+            resolved(&dispatcher, &[(ORIGIN_PARAM, "test")]).unwrap(),
+            "todoist-fake"
+        );
+        let err = resolved(&dispatcher, &[(WORKSPACE_PARAM, "personal")])
             .unwrap_err()
-            .to_string()
-            .contains("No provider registered"));
+            .to_string();
+        assert!(err.contains("names provider 'missing'"), "{}", err);
+    }
+
+    #[test]
+    fn test_resolve_provider_by_workspace() {
+        let dispatcher = OperationDispatcher::new(vec![
+            named("work", 0, Some("work")),
+            named("personal", 0, Some("personal")),
+        ]);
+        assert_eq!(
+            resolved(&dispatcher, &[(WORKSPACE_PARAM, "personal")]).unwrap(),
+            "personal"
+        );
+        assert!(resolved(&dispatcher, &[]).is_err());
+        assert!(resolved(&dispatcher, &[(WORKSPACE_PARAM, "family")]).is_err());
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("*", "todoist_tasks"));
+        assert!(matches_pattern("todoist_*", "todoist_tasks"));
+        assert!(matches_pattern("*_tasks", "todoist_tasks"));
+        assert!(!matches_pattern("todoist_*", "logseq_blocks"));
+        assert!(!matches_pattern("ab*ba", "aba"));
+        assert!(matches_pattern("todoist_tasks", "todoist_tasks"));
     }
 
     #[tokio::test]
@@ -0,0 +1,340 @@
+//! Query result caching
+//!
+//! Views are mounted far more often than their PRQL changes. `QueryCache` keeps the
+//! compiled SQL and `RenderSpec` per (PRQL source, params) and, optionally, the last
+//! result rows.
+//!
+//! Invalidation is lineage-based: every entry remembers the source tables of its
+//! query, and a write to a table drops the cached rows of all queries reading it.
+//! Compiled SQL/RenderSpec don't depend on table contents and stay cached until they
+//! are evicted (LRU, bounded by `max_entries`) or the cache is cleared.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use holon_api::Value;
+use query_render::RenderSpec;
+
+/// Cache tuning knobs
+#[derive(Debug, Clone)]
+pub struct QueryCacheConfig {
+    /// Maximum number of cached queries; the least recently used entry is evicted
+    pub max_entries: usize,
+    /// How long (ms) cached result rows stay valid; 0 disables row caching
+    pub rows_ttl_ms: i64,
+}
+
+impl Default for QueryCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 128,
+            rows_ttl_ms: 5_000,
+        }
+    }
+}
+
+/// Compiled form of a query
+#[derive(Debug, Clone)]
+pub struct CompiledQuery {
+    pub sql: String,
+    pub render_spec: RenderSpec,
+    /// Tables the query reads from (from the compiled query's table declarations)
+    pub source_tables: Vec<String>,
+}
+
+struct CacheEntry {
+    compiled: CompiledQuery,
+    rows: Option<Vec<HashMap<String, Value>>>,
+    rows_cached_at: i64,
+    last_used: u64,
+}
+
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    /// Monotonic counter used as LRU clock
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/// Hit/miss counters and current size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueryCacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// LRU cache of compiled queries and their last results
+pub struct QueryCache {
+    config: QueryCacheConfig,
+    state: Mutex<CacheState>,
+}
+
+impl QueryCache {
+    pub fn new() -> Self {
+        Self::with_config(QueryCacheConfig::default())
+    }
+
+    pub fn with_config(config: QueryCacheConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(CacheState {
+                entries: HashMap::new(),
+                tick: 0,
+                hits: 0,
+                misses: 0,
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &QueryCacheConfig {
+        &self.config
+    }
+
+    /// Cache key for a query and its parameters (parameter order independent)
+    pub fn cache_key(prql: &str, params: &HashMap<String, Value>) -> String {
+        let mut sorted: Vec<(&String, &Value)> = params.iter().collect();
+        sorted.sort_by(|a, b| a.0.cmp(b.0));
+        let params_json = serde_json::to_string(&sorted).unwrap_or_default();
+        format!("{}\u{0}{}", prql, params_json)
+    }
+
+    /// Look up the compiled form of a query
+    pub fn get_compiled(
+        &self,
+        prql: &str,
+        params: &HashMap<String, Value>,
+    ) -> Option<CompiledQuery> {
+        let key = Self::cache_key(prql, params);
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+        match state.entries.get_mut(&key) {
+            Some(entry) => {
+                entry.last_used = tick;
+                let compiled = entry.compiled.clone();
+                state.hits += 1;
+                Some(compiled)
+            }
+            None => {
+                state.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store the compiled form of a query, evicting the least recently used entry if full
+    pub fn insert_compiled(
+        &self,
+        prql: &str,
+        params: &HashMap<String, Value>,
+        compiled: CompiledQuery,
+    ) {
+        if self.config.max_entries == 0 {
+            return;
+        }
+        let key = Self::cache_key(prql, params);
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.config.max_entries {
+            let lru_key = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(lru_key) = lru_key {
+                state.entries.remove(&lru_key);
+            }
+        }
+
+        state.entries.insert(
+            key,
+            CacheEntry {
+                compiled,
+                rows: None,
+                rows_cached_at: 0,
+                last_used: tick,
+            },
+        );
+    }
+
+    /// Cached result rows, if present and younger than the TTL at `now_ms`
+    pub fn get_rows(
+        &self,
+        prql: &str,
+        params: &HashMap<String, Value>,
+        now_ms: i64,
+    ) -> Option<Vec<HashMap<String, Value>>> {
+        if self.config.rows_ttl_ms <= 0 {
+            return None;
+        }
+        let key = Self::cache_key(prql, params);
+        let mut state = self.state.lock().unwrap();
+        let entry = state.entries.get_mut(&key)?;
+        if now_ms - entry.rows_cached_at > self.config.rows_ttl_ms {
+            entry.rows = None;
+        }
+        entry.rows.clone()
+    }
+
+    /// Remember the result rows of a compiled query at `now_ms`
+    pub fn insert_rows(
+        &self,
+        prql: &str,
+        params: &HashMap<String, Value>,
+        rows: Vec<HashMap<String, Value>>,
+        now_ms: i64,
+    ) {
+        if self.config.rows_ttl_ms <= 0 {
+            return;
+        }
+        let key = Self::cache_key(prql, params);
+        let mut state = self.state.lock().unwrap();
+        if let Some(entry) = state.entries.get_mut(&key) {
+            entry.rows = Some(rows);
+            entry.rows_cached_at = now_ms;
+        }
+    }
+
+    /// Drop cached rows of every query that reads `table`
+    ///
+    /// Returns the number of entries whose rows were dropped.
+    pub fn invalidate_table(&self, table: &str) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let mut invalidated = 0;
+        for entry in state.entries.values_mut() {
+            if entry.rows.is_some() && entry.compiled.source_tables.iter().any(|t| t == table) {
+                entry.rows = None;
+                invalidated += 1;
+            }
+        }
+        invalidated
+    }
+
+    /// Drop all cached rows (e.g. after a sync wrote to unknown tables)
+    pub fn invalidate_all_rows(&self) {
+        let mut state = self.state.lock().unwrap();
+        for entry in state.entries.values_mut() {
+            entry.rows = None;
+        }
+    }
+
+    /// Drop everything, including compiled queries (e.g. when operations change)
+    pub fn clear(&self) {
+        self.state.lock().unwrap().entries.clear();
+    }
+
+    pub fn stats(&self) -> QueryCacheStats {
+        let state = self.state.lock().unwrap();
+        QueryCacheStats {
+            entries: state.entries.len(),
+            hits: state.hits,
+            misses: state.misses,
+        }
+    }
+}
+
+impl Default for QueryCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compiled(sql: &str, tables: &[&str]) -> CompiledQuery {
+        CompiledQuery {
+            sql: sql.to_string(),
+            render_spec: RenderSpec {
+                root: holon_api::RenderExpr::ColumnRef {
+                    name: "content".to_string(),
+                },
+                nested_queries: vec![],
+                operations: HashMap::new(),
+                row_templates: vec![],
+                selection: None,
+                sort: vec![],
+                group_by: None,
+                view_id: None,
+                view_state: Default::default(),
+                filters: vec![],
+                timezone: None,
+                board: None,
+                calendar: None,
+                presence: None,
+            },
+            source_tables: tables.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    fn row(id: &str) -> HashMap<String, Value> {
+        HashMap::from([("id".to_string(), Value::String(id.to_string()))])
+    }
+
+    #[test]
+    fn test_key_includes_params() {
+        let cache = QueryCache::new();
+        let params = HashMap::from([("parent".to_string(), Value::String("a".to_string()))]);
+        cache.insert_compiled("from blocks", &params, compiled("SELECT 1", &["blocks"]));
+
+        assert!(cache.get_compiled("from blocks", &params).is_some());
+        assert!(cache.get_compiled("from blocks", &HashMap::new()).is_none());
+        assert_eq!(
+            cache.stats(),
+            QueryCacheStats {
+                entries: 1,
+                hits: 1,
+                misses: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = QueryCache::with_config(QueryCacheConfig {
+            max_entries: 2,
+            rows_ttl_ms: 0,
+        });
+        let params = HashMap::new();
+        cache.insert_compiled("a", &params, compiled("A", &[]));
+        cache.insert_compiled("b", &params, compiled("B", &[]));
+        // Touch "a" so "b" becomes least recently used
+        cache.get_compiled("a", &params);
+        cache.insert_compiled("c", &params, compiled("C", &[]));
+
+        assert!(cache.get_compiled("a", &params).is_some());
+        assert!(cache.get_compiled("b", &params).is_none());
+        assert!(cache.get_compiled("c", &params).is_some());
+    }
+
+    #[test]
+    fn test_rows_ttl_and_lineage_invalidation() {
+        let cache = QueryCache::with_config(QueryCacheConfig {
+            max_entries: 10,
+            rows_ttl_ms: 1000,
+        });
+        let params = HashMap::new();
+        cache.insert_compiled("q1", &params, compiled("Q1", &["blocks"]));
+        cache.insert_compiled("q2", &params, compiled("Q2", &["todoist_tasks"]));
+        cache.insert_rows("q1", &params, vec![row("b1")], 0);
+        cache.insert_rows("q2", &params, vec![row("t1")], 0);
+
+        assert_eq!(cache.get_rows("q1", &params, 500), Some(vec![row("b1")]));
+        // Expired
+        assert_eq!(cache.get_rows("q1", &params, 1500), None);
+
+        // Writes to blocks don't affect the tasks query
+        cache.insert_rows("q1", &params, vec![row("b1")], 1500);
+        assert_eq!(cache.invalidate_table("blocks"), 1);
+        assert_eq!(cache.get_rows("q1", &params, 1600), None);
+        assert_eq!(cache.get_rows("q2", &params, 900), Some(vec![row("t1")]));
+
+        // Compiled form survives row invalidation
+        assert!(cache.get_compiled("q1", &params).is_some());
+    }
+}
@@ -0,0 +1,352 @@
+//! Queries filtered by widgets
+//!
+//! Filter widgets (`toggle_filter`, `date_filter`, see `FilterSpec`) report the
+//! values the user picks instead of the frontend submitting a new query. The backend
+//! recompiles the query with the new filter values, re-runs it, and sends the rows
+//! that left, entered or changed in the result through the query's existing change
+//! stream, which then carries the changes of the re-filtered query.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use holon_api::{
+    Batch, BatchMetadata, BatchWithMetadata, Change, ChangeOrigin, FilterValue, Value,
+};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::api::result_window::row_id;
+use crate::storage::turso::{RowChange, RowChangeStream};
+
+type Row = HashMap<String, Value>;
+
+/// What a filtered query was opened with, to recompile it
+#[derive(Debug, Clone)]
+pub struct FilteredQuerySource {
+    pub prql: String,
+    pub params: HashMap<String, Value>,
+    /// Values set by filter widgets so far (see `parse_query_render_to_rq_with_filters`)
+    pub filter_values: HashMap<String, Option<FilterValue>>,
+}
+
+struct FilteredQuery {
+    source: FilteredQuerySource,
+    /// Rows the frontend has, by id
+    rows: HashMap<String, Row>,
+    tx: mpsc::Sender<BatchWithMetadata<RowChange>>,
+}
+
+impl FilteredQuery {
+    fn track(&mut self, change: &Change<Row>) {
+        match change {
+            Change::Created { data, .. } | Change::Updated { data, .. } => {
+                if let Some(id) = row_id(data) {
+                    self.rows.insert(id, data.clone());
+                }
+            }
+            Change::ColumnChange { id, columns, .. } => {
+                if let Some(row) = self.rows.get_mut(id) {
+                    row.extend(columns.clone());
+                }
+            }
+            Change::Deleted { id, .. } => {
+                self.rows.remove(id);
+            }
+        }
+    }
+}
+
+struct Entry {
+    query: Arc<tokio::sync::Mutex<FilteredQuery>>,
+    /// Forwards the change stream of the query's current SQL
+    forward: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Entry {
+    fn replace_forward(&self, forward: Option<JoinHandle<()>>) {
+        if let Some(old) = std::mem::replace(&mut *self.forward.lock().unwrap(), forward) {
+            old.abort();
+        }
+    }
+}
+
+/// Filtered queries of a `BackendEngine`, by id
+#[derive(Default)]
+pub struct FilteredQueries {
+    next_id: AtomicU64,
+    queries: Mutex<HashMap<u64, Arc<Entry>>>,
+}
+
+impl FilteredQueries {
+    /// Start forwarding `changes` of a query whose current result is `rows`
+    ///
+    /// Returns the query's id and its stream.
+    pub async fn open(
+        &self,
+        source: FilteredQuerySource,
+        rows: &[Row],
+        changes: RowChangeStream,
+    ) -> (u64, RowChangeStream) {
+        let (tx, rx) = mpsc::channel(1024);
+        let query = Arc::new(tokio::sync::Mutex::new(FilteredQuery {
+            source,
+            rows: rows
+                .iter()
+                .filter_map(|row| Some((row_id(row)?, row.clone())))
+                .collect(),
+            tx,
+        }));
+        let entry = Entry {
+            forward: Mutex::new(Some(forward(Arc::clone(&query), changes))),
+            query,
+        };
+
+        let id = self.next_id.fetch_add(1, AtomicOrdering::Relaxed);
+        self.queries.lock().unwrap().insert(id, Arc::new(entry));
+        (id, ReceiverStream::new(rx))
+    }
+
+    /// What query `id` was opened with, including its current filter values
+    pub async fn source(&self, id: u64) -> Result<FilteredQuerySource> {
+        let entry = self
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown filtered query: {}", id))?;
+        let query = entry.query.lock().await;
+        Ok(query.source.clone())
+    }
+
+    /// Replace the result of query `id` after its filters changed
+    ///
+    /// Sends the difference between the previous and the new result, then forwards
+    /// `changes` (the change stream of the re-filtered query) instead of the old one.
+    pub async fn refilter(
+        &self,
+        id: u64,
+        filter_values: HashMap<String, Option<FilterValue>>,
+        rows: Vec<Row>,
+        changes: RowChangeStream,
+    ) -> Result<()> {
+        let entry = self
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown filtered query: {}", id))?;
+        let mut query = entry.query.lock().await;
+        // Changes of the old SQL don't apply to the new result
+        entry.replace_forward(None);
+        query.source.filter_values = filter_values;
+
+        let diff = result_diff(&query.rows, &rows);
+        query.rows = rows
+            .into_iter()
+            .filter_map(|row| Some((row_id(&row)?, row)))
+            .collect();
+        if !diff.is_empty()
+            && query
+                .tx
+                .send(batch(format!("filtered_query_{}", id), diff))
+                .await
+                .is_err()
+        {
+            drop(query);
+            self.close(id);
+            return Ok(());
+        }
+        entry.replace_forward(Some(forward(Arc::clone(&entry.query), changes)));
+        Ok(())
+    }
+
+    /// Stop watching query `id`; its stream ends
+    pub fn close(&self, id: u64) -> bool {
+        match self.queries.lock().unwrap().remove(&id) {
+            Some(entry) => {
+                entry.replace_forward(None);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Number of watched queries
+    pub fn len(&self) -> usize {
+        self.queries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, id: u64) -> Option<Arc<Entry>> {
+        self.queries.lock().unwrap().get(&id).cloned()
+    }
+}
+
+/// Forward the batches of `changes` to the query's stream, tracking its rows
+fn forward(
+    query: Arc<tokio::sync::Mutex<FilteredQuery>>,
+    mut changes: RowChangeStream,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Some(batch) = changes.next().await {
+            let mut query = query.lock().await;
+            for item in &batch.inner.items {
+                query.track(&item.change);
+            }
+            if query.tx.send(batch).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+/// Changes turning `old` into `new`, matching rows by id
+pub(crate) fn result_diff(old: &HashMap<String, Row>, new: &[Row]) -> Vec<Change<Row>> {
+    let origin = ChangeOrigin::Local {
+        operation_id: None,
+        trace_id: None,
+        source: None,
+    };
+    let mut seen = std::collections::HashSet::new();
+    let mut changes = Vec::new();
+    for row in new {
+        let Some(id) = row_id(row) else {
+            continue;
+        };
+        match old.get(&id) {
+            None => changes.push(Change::Created {
+                data: row.clone(),
+                origin: origin.clone(),
+            }),
+            Some(previous) if !same_row(previous, row) => changes.push(Change::Updated {
+                id: id.clone(),
+                data: row.clone(),
+                origin: origin.clone(),
+            }),
+            Some(_) => {}
+        }
+        seen.insert(id);
+    }
+    changes.extend(
+        old.keys()
+            .filter(|id| !seen.contains(*id))
+            .map(|id| Change::Deleted {
+                id: id.clone(),
+                origin: origin.clone(),
+            }),
+    );
+    changes
+}
+
+/// Rows with equal columns, ignoring the `_rowid` CDC adds to the rows it reports
+fn same_row(a: &Row, b: &Row) -> bool {
+    let columns = |row: &Row| row.keys().filter(|k| k.as_str() != "_rowid").count();
+    columns(a) == columns(b)
+        && a.iter()
+            .filter(|(k, _)| k.as_str() != "_rowid")
+            .all(|(k, v)| b.get(k) == Some(v))
+}
+
+/// A batch of `changes` from the query named `relation_name`
+pub(crate) fn batch(
+    relation_name: String,
+    changes: Vec<Change<Row>>,
+) -> BatchWithMetadata<RowChange> {
+    BatchWithMetadata {
+        inner: Batch {
+            items: changes
+                .into_iter()
+                .map(|change| RowChange {
+                    relation_name: relation_name.clone(),
+                    change,
+                })
+                .collect(),
+        },
+        metadata: BatchMetadata {
+            relation_name,
+            trace_context: None,
+            sync_token: None,
+            full_snapshot: false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: &str, done: bool) -> Row {
+        HashMap::from([
+            ("id".to_string(), Value::String(id.to_string())),
+            ("completed".to_string(), Value::Boolean(done)),
+        ])
+    }
+
+    fn source() -> FilteredQuerySource {
+        FilteredQuerySource {
+            prql: "from tasks".to_string(),
+            params: HashMap::new(),
+            filter_values: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refilter_sends_result_difference() {
+        let queries = FilteredQueries::default();
+        let (_changes_tx, changes_rx) = mpsc::channel(1);
+        let initial = vec![row("a", false), row("b", false)];
+        let (id, mut stream) = queries
+            .open(source(), &initial, ReceiverStream::new(changes_rx))
+            .await;
+
+        // "Show completed": `b` changed meanwhile and `c` is completed
+        let (_changes_tx, changes_rx) = mpsc::channel(1);
+        let values = HashMap::from([("completed".to_string(), None)]);
+        queries
+            .refilter(
+                id,
+                values.clone(),
+                vec![row("a", false), row("b", true), row("c", true)],
+                ReceiverStream::new(changes_rx),
+            )
+            .await
+            .unwrap();
+
+        let batch = stream.next().await.unwrap();
+        let changes: Vec<_> = batch.inner.items.into_iter().map(|c| c.change).collect();
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(&changes[0], Change::Updated { id, .. } if id == "b"));
+        assert!(
+            matches!(&changes[1], Change::Created { data, .. } if row_id(data).as_deref() == Some("c"))
+        );
+        assert_eq!(queries.source(id).await.unwrap().filter_values, values);
+
+        // Hiding completed rows again deletes them
+        let (_changes_tx, changes_rx) = mpsc::channel(1);
+        queries
+            .refilter(
+                id,
+                HashMap::new(),
+                vec![row("a", false)],
+                ReceiverStream::new(changes_rx),
+            )
+            .await
+            .unwrap();
+        let batch = stream.next().await.unwrap();
+        let mut deleted: Vec<_> = batch
+            .inner
+            .items
+            .into_iter()
+            .filter_map(|c| match c.change {
+                Change::Deleted { id, .. } => Some(id),
+                _ => None,
+            })
+            .collect();
+        deleted.sort();
+        assert_eq!(deleted, vec!["b".to_string(), "c".to_string()]);
+
+        assert!(queries.close(id));
+        assert!(queries.is_empty());
+    }
+}
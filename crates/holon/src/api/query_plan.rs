@@ -0,0 +1,335 @@
+//! Query plan inspection and index advice for slow views
+//!
+//! `BackendEngine::explain_query` runs `EXPLAIN QUERY PLAN` on a compiled PRQL query.
+//! Each step of the plan that reads a table points at the `from`/`join` of that table
+//! in the PRQL source (see `query_render::query_usages`).
+//!
+//! `suggest_indexes` is a heuristic: it proposes an index for each column a fully
+//! scanned table is filtered or joined by, and for the sort/group columns when SQLite
+//! has to sort in a temporary B-tree, unless the database already has an index
+//! starting with the column. Columns the table's `EntitySchema` declares as indexed
+//! are pointed out as missing their index.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use holon_api::Value;
+use query_render::{QueryUsage, Span, UsageKind};
+
+use crate::storage::turso::TursoBackend;
+use crate::storage::types::{Result, StorageEntity};
+
+/// One step of SQLite's query plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryPlanStep {
+    pub id: i64,
+    /// `id` of the enclosing step (0 at the top level)
+    pub parent: i64,
+    /// As printed by SQLite, e.g. "SEARCH tasks USING INDEX idx_tasks_due_date (due_date>?)"
+    pub detail: String,
+    /// Table (or CTE) the step reads
+    pub table: Option<String>,
+    /// Index the step reads the table through
+    pub index: Option<String>,
+    /// The step reads every row of the table
+    pub full_scan: bool,
+    /// `from`/`join` of `table` in the PRQL source
+    pub span: Option<Span>,
+}
+
+impl QueryPlanStep {
+    /// Step of an `EXPLAIN QUERY PLAN` result row (columns `id`, `parent`, `detail`)
+    pub fn from_row(row: &StorageEntity, usages: &[QueryUsage]) -> Self {
+        let id = row.get("id").and_then(Value::as_i64).unwrap_or_default();
+        let parent = row
+            .get("parent")
+            .and_then(Value::as_i64)
+            .unwrap_or_default();
+        let detail = row
+            .get("detail")
+            .and_then(|detail| detail.as_string())
+            .unwrap_or_default()
+            .to_string();
+
+        let mut words = detail.split_whitespace();
+        let operation = words.next().unwrap_or_default();
+        let table = match (operation, words.next(), words.next()) {
+            ("SCAN", Some("CONSTANT"), Some("ROW")) => None,
+            ("SCAN" | "SEARCH", Some(table), _) => Some(table.to_string()),
+            _ => None,
+        };
+        let index = ["USING INDEX ", "USING COVERING INDEX "]
+            .iter()
+            .find_map(|marker| detail.split_once(marker))
+            .and_then(|(_, rest)| rest.split_whitespace().next())
+            .map(str::to_string);
+        let full_scan = operation == "SCAN" && table.is_some() && !detail.contains(" USING ");
+        let span = table.as_deref().and_then(|table| {
+            usages
+                .iter()
+                .find(|usage| usage.column.is_none() && usage.table == table)
+                .and_then(|usage| usage.span)
+        });
+
+        Self {
+            id,
+            parent,
+            detail,
+            table,
+            index,
+            full_scan,
+            span,
+        }
+    }
+}
+
+/// Index that would likely speed up a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexSuggestion {
+    pub table: String,
+    pub column: String,
+    pub reason: String,
+    /// Statement creating the index
+    pub sql: String,
+    /// Where the query uses the column
+    pub span: Option<Span>,
+}
+
+/// Query plan of a PRQL query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryPlan {
+    /// The compiled SQL the plan is for
+    pub sql: String,
+    pub steps: Vec<QueryPlanStep>,
+    pub suggestions: Vec<IndexSuggestion>,
+}
+
+impl QueryPlan {
+    /// Plain-text plan, indented by nesting, followed by the suggestions
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut depths: HashMap<i64, usize> = HashMap::new();
+        let mut lines = Vec::new();
+        for step in &self.steps {
+            let depth = depths.get(&step.parent).map_or(0, |depth| depth + 1);
+            depths.insert(step.id, depth);
+            let mut line = format!("{}{}", "  ".repeat(depth), step.detail);
+            if let Some(span) = step.span {
+                line.push_str(&format!(" (line {})", span.line));
+            }
+            lines.push(line);
+        }
+        for suggestion in &self.suggestions {
+            lines.push(format!(
+                "Suggestion: {} ({})",
+                suggestion.sql, suggestion.reason
+            ));
+        }
+        lines
+    }
+}
+
+/// Indexes for the columns `usages` read from tables the plan scans or sorts slowly
+///
+/// `declared` are the indexed columns of the tables' `EntitySchema`s, `existing` the
+/// first columns of the database's indexes (including primary keys), both by table.
+pub fn suggest_indexes(
+    steps: &[QueryPlanStep],
+    usages: &[QueryUsage],
+    declared: &HashMap<String, HashSet<String>>,
+    existing: &HashMap<String, HashSet<String>>,
+) -> Vec<IndexSuggestion> {
+    let scanned: HashSet<&str> = steps
+        .iter()
+        .filter(|step| step.full_scan)
+        .filter_map(|step| step.table.as_deref())
+        .collect();
+    let temp_sort = |clause: &str| {
+        steps
+            .iter()
+            .any(|step| step.detail.contains("TEMP B-TREE") && step.detail.contains(clause))
+    };
+    let sorts_in_memory = temp_sort("ORDER BY");
+    let groups_in_memory = temp_sort("GROUP BY");
+
+    let mut seen = BTreeSet::new();
+    let mut suggestions = Vec::new();
+    for usage in usages {
+        let Some(column) = &usage.column else {
+            continue;
+        };
+        let table = usage.table.as_str();
+        let slow = match usage.kind {
+            UsageKind::Filter | UsageKind::Join => scanned.contains(table),
+            UsageKind::Sort => sorts_in_memory,
+            UsageKind::Group => groups_in_memory,
+            UsageKind::From => false,
+        };
+        let has_index = existing
+            .get(table)
+            .is_some_and(|columns| columns.contains(column));
+        if !slow || has_index || !seen.insert((table, column.as_str())) {
+            continue;
+        }
+
+        let usage_description = match usage.kind {
+            UsageKind::Filter => {
+                format!("`{}` is scanned in full to filter by `{}`", table, column)
+            }
+            UsageKind::Join => format!("`{}` is scanned in full to join on `{}`", table, column),
+            UsageKind::Sort => format!("rows are sorted by `{}` in memory", column),
+            UsageKind::Group => format!("rows are grouped by `{}` in memory", column),
+            UsageKind::From => unreachable!(),
+        };
+        let declared_indexed = declared
+            .get(table)
+            .is_some_and(|columns| columns.contains(column));
+        let reason = if declared_indexed {
+            format!(
+                "{}; its schema declares it indexed, but the index is missing",
+                usage_description
+            )
+        } else {
+            usage_description
+        };
+
+        suggestions.push(IndexSuggestion {
+            table: table.to_string(),
+            column: column.clone(),
+            reason,
+            sql: format!(
+                "CREATE INDEX IF NOT EXISTS idx_{}_{} ON {} ({})",
+                table, column, table, column
+            ),
+            span: usage.span,
+        });
+    }
+    suggestions
+}
+
+/// First columns of the indexes of `table`, plus its primary key
+pub(crate) async fn indexed_columns(
+    backend: &TursoBackend,
+    table: &str,
+) -> Result<HashSet<String>> {
+    let params = HashMap::from([("table".to_string(), Value::String(table.to_string()))]);
+    let indexes = backend
+        .execute_sql(
+            "SELECT sql FROM sqlite_master WHERE type = 'index' AND tbl_name = $table AND sql IS NOT NULL",
+            params,
+        )
+        .await?;
+    let mut columns: HashSet<String> = indexes
+        .iter()
+        .filter_map(|row| row.get("sql").and_then(|sql| sql.as_string()))
+        .filter_map(first_index_column)
+        .collect();
+
+    let table_info = backend
+        .execute_sql(&format!("PRAGMA table_info({})", table), HashMap::new())
+        .await?;
+    columns.extend(table_info.iter().filter_map(|row| {
+        let primary_key = row.get("pk").and_then(Value::as_i64).unwrap_or_default() > 0;
+        primary_key
+            .then(|| row.get("name").and_then(|name| name.as_string_owned()))
+            .flatten()
+    }));
+    Ok(columns)
+}
+
+/// First column of a `CREATE INDEX … ON table (column, …)` statement
+fn first_index_column(sql: &str) -> Option<String> {
+    let (_, columns) = sql.split_once('(')?;
+    let column = columns
+        .split([',', ')'])
+        .next()?
+        .split_whitespace()
+        .next()?;
+    Some(column.trim_matches(['"', '`', '[', ']']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan_row(id: i64, parent: i64, detail: &str) -> StorageEntity {
+        HashMap::from([
+            ("id".to_string(), Value::Integer(id)),
+            ("parent".to_string(), Value::Integer(parent)),
+            ("detail".to_string(), Value::String(detail.to_string())),
+        ])
+    }
+
+    fn usage(kind: UsageKind, table: &str, column: Option<&str>) -> QueryUsage {
+        QueryUsage {
+            kind,
+            table: table.to_string(),
+            column: column.map(str::to_string),
+            span: Some(Span::new("from tasks", 5, 10)),
+        }
+    }
+
+    #[test]
+    fn test_plan_steps_and_suggestions() {
+        let usages = vec![
+            usage(UsageKind::From, "tasks", None),
+            usage(UsageKind::Join, "projects", None),
+            usage(UsageKind::Join, "projects", Some("id")),
+            usage(UsageKind::Filter, "tasks", Some("completed")),
+            usage(UsageKind::Filter, "tasks", Some("project_id")),
+            usage(UsageKind::Sort, "tasks", Some("due_date")),
+        ];
+        let steps: Vec<QueryPlanStep> = [
+            plan_row(2, 0, "SCAN tasks"),
+            plan_row(
+                5,
+                0,
+                "SEARCH projects USING INDEX sqlite_autoindex_projects_1 (id=?)",
+            ),
+            plan_row(9, 0, "USE TEMP B-TREE FOR ORDER BY"),
+        ]
+        .iter()
+        .map(|row| QueryPlanStep::from_row(row, &usages))
+        .collect();
+
+        assert!(steps[0].full_scan);
+        assert_eq!(steps[0].table.as_deref(), Some("tasks"));
+        assert_eq!(steps[0].span.map(|span| span.start), Some(5));
+        assert!(!steps[1].full_scan);
+        assert_eq!(
+            steps[1].index.as_deref(),
+            Some("sqlite_autoindex_projects_1")
+        );
+        assert_eq!(steps[2].table, None);
+
+        let declared = HashMap::from([(
+            "tasks".to_string(),
+            HashSet::from(["completed".to_string()]),
+        )]);
+        let existing = HashMap::from([
+            (
+                "tasks".to_string(),
+                HashSet::from(["project_id".to_string()]),
+            ),
+            ("projects".to_string(), HashSet::from(["id".to_string()])),
+        ]);
+        let suggestions = suggest_indexes(&steps, &usages, &declared, &existing);
+        let columns: Vec<&str> = suggestions.iter().map(|s| s.column.as_str()).collect();
+        assert_eq!(columns, vec!["completed", "due_date"]);
+        assert_eq!(
+            suggestions[0].sql,
+            "CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks (completed)"
+        );
+        assert!(suggestions[0].reason.contains("index is missing"));
+        assert_eq!(
+            suggestions[1].reason,
+            "rows are sorted by `due_date` in memory"
+        );
+    }
+
+    #[test]
+    fn test_first_index_column() {
+        assert_eq!(
+            first_index_column("CREATE INDEX idx_tasks_due ON tasks (\"due_date\" DESC, id)"),
+            Some("due_date".to_string())
+        );
+    }
+}
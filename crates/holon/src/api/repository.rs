@@ -38,7 +38,7 @@ use holon_api::streaming::ChangeNotifications;
 
 use super::types::{NewBlock, Traversal};
 use async_trait::async_trait;
-use holon_api::{ApiError, Block};
+use holon_api::{ApiError, Block, TextDelta};
 
 /// Core CRUD and batch operations for block documents.
 ///
@@ -201,6 +201,40 @@ pub trait CoreOperations: Send + Sync {
         content: holon_api::BlockContent,
     ) -> Result<(), ApiError>;
 
+    /// Apply a fine-grained text edit to a text block.
+    ///
+    /// Frontends editing content inline send deltas instead of whole strings so
+    /// concurrent edits from other peers touching other parts of the text are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Block ID to edit
+    /// * `delta` - Edit at character offsets of the current content
+    ///
+    /// # Returns
+    ///
+    /// The inverse delta (for undo).
+    ///
+    /// # Errors
+    ///
+    /// * `ApiError::BlockNotFound` - Block doesn't exist
+    /// * `ApiError::InvalidOperation` - Not a text block, or the delta doesn't match its content
+    async fn apply_text_delta(&self, id: &str, delta: &TextDelta) -> Result<TextDelta, ApiError> {
+        let block = self.get_block(id).await?;
+        let raw = block
+            .content
+            .as_text()
+            .ok_or_else(|| ApiError::InvalidOperation {
+                message: format!("Block {} is not a text block", id),
+            })?;
+        let new_raw = delta.apply(raw).map_err(|e| ApiError::InvalidOperation {
+            message: e.to_string(),
+        })?;
+        self.update_block(id, holon_api::BlockContent::text(new_raw))
+            .await?;
+        Ok(delta.invert())
+    }
+
     /// Delete a block (tombstone).
     ///
     /// Sets `deleted_at` timestamp but keeps block in CRDT for consistency.
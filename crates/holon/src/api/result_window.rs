@@ -0,0 +1,587 @@
+//! Windowed query results
+//!
+//! Sending every row of a query with tens of thousands of results to a frontend is
+//! slow, and most of them are never on screen. A windowed query keeps the ordered
+//! result in the backend and sends the frontend only the rows of the range it shows
+//! (its window). Changes from the query's CDC stream are applied to the whole result
+//! and translated into `WindowChange`s with indices relative to the window; a change
+//! to a row outside the window only updates the total row count.
+//!
+//! Rows are ordered by the render spec's `sort_by`/`group_by` (`RenderSpec::row_order`).
+//! Rows of unordered queries keep the order of the initial result and new rows are
+//! appended.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use holon_api::{Change, MapChange, SortKey, Value, WindowChange, WindowChangeBatch};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+type Row = HashMap<String, Value>;
+
+/// Stream of changes to a window, starting with the window's rows
+pub type WindowChangeStream = ReceiverStream<WindowChangeBatch>;
+
+/// Range of result rows a frontend shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Window {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Window {
+    pub fn new(start: usize, len: usize) -> Self {
+        Self { start, len }
+    }
+
+    fn end(&self) -> usize {
+        self.start.saturating_add(self.len)
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        index >= self.start && index < self.end()
+    }
+}
+
+/// Whole, ordered result of a query
+#[derive(Debug, Clone, Default)]
+pub struct WindowedResult {
+    order: Vec<SortKey>,
+    rows: Vec<Row>,
+}
+
+impl WindowedResult {
+    pub fn new(mut rows: Vec<Row>, order: Vec<SortKey>) -> Self {
+        if !order.is_empty() {
+            rows.sort_by(|a, b| compare_rows(a, b, &order));
+        }
+        Self { order, rows }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Rows of `window`, fewer if it reaches past the end of the result
+    pub fn window(&self, window: Window) -> Vec<Row> {
+        self.rows
+            .iter()
+            .skip(window.start)
+            .take(window.len)
+            .cloned()
+            .collect()
+    }
+
+    /// Params of the `tree_position` a row dropped at `index` ends up in
+    ///
+    /// `index` is a row index of the whole result (not of a window); dropping there
+    /// places the row before the row currently at `index`, as its sibling. Dropping
+    /// past the last row places it after the last row. Returns `parent_id` and
+    /// `after_block_id` (NULL for the first child), the params `move_block` takes
+    /// from a `tree_position`, or `None` if the result is empty or has no `parent_id` column.
+    pub fn tree_position(&self, index: usize) -> Option<Row> {
+        let (parent_id, after_block_id) = match self.rows.get(index) {
+            Some(target) => {
+                let parent_id = target.get("parent_id")?.clone();
+                let after_block_id = self.rows[..index]
+                    .iter()
+                    .rev()
+                    .take_while(|row| !is_row(row, &parent_id))
+                    .find(|row| row.get("parent_id") == Some(&parent_id))
+                    .and_then(|row| row.get("id").cloned());
+                (parent_id, after_block_id)
+            }
+            None => {
+                let last = self.rows.last()?;
+                (last.get("parent_id")?.clone(), last.get("id").cloned())
+            }
+        };
+        Some(HashMap::from([
+            ("parent_id".to_string(), parent_id),
+            (
+                "after_block_id".to_string(),
+                after_block_id.unwrap_or(Value::Null),
+            ),
+        ]))
+    }
+
+    /// Apply a change to the result, returning its effect on `window`
+    ///
+    /// Rows are identified by their `id` column. A created or updated row that is
+    /// unknown is inserted; a row whose sort columns changed moves to its new position.
+    pub fn apply(&mut self, change: MapChange, window: Window) -> Vec<WindowChange> {
+        let mut out = Vec::new();
+        match change {
+            Change::Created { data, .. } | Change::Updated { data, .. } => {
+                match row_id(&data).and_then(|id| self.position(&id)) {
+                    Some(index) => self.replace(index, data, window, &mut out),
+                    None => self.insert(data, window, &mut out),
+                }
+            }
+            Change::ColumnChange { id, columns, .. } => {
+                if let Some(index) = self.position(&id) {
+                    let mut data = self.rows[index].clone();
+                    data.extend(columns);
+                    self.replace(index, data, window, &mut out);
+                }
+            }
+            Change::Deleted { id, .. } => {
+                if let Some(index) = self.position(&id) {
+                    self.remove(index, window, &mut out);
+                }
+            }
+        }
+        out
+    }
+
+    fn position(&self, id: &str) -> Option<usize> {
+        self.rows.iter().position(|row| has_id(row, id))
+    }
+
+    fn insert(&mut self, data: Row, window: Window, out: &mut Vec<WindowChange>) {
+        let index = if self.order.is_empty() {
+            self.rows.len()
+        } else {
+            self.rows
+                .partition_point(|row| compare_rows(row, &data, &self.order) != Ordering::Greater)
+        };
+        self.rows.insert(index, data);
+
+        if window.len == 0 || index >= window.end() {
+            return;
+        }
+        // The row now at the window's start moved in from before it if the insert
+        // was above the window
+        let shown = index.max(window.start);
+        let Some(row) = self.rows.get(shown) else {
+            return;
+        };
+        let held_before = (self.rows.len() - 1)
+            .saturating_sub(window.start)
+            .min(window.len);
+        if held_before == window.len {
+            out.push(WindowChange::Removed {
+                index: window.len - 1,
+            });
+        }
+        out.push(WindowChange::Inserted {
+            index: shown - window.start,
+            data: row.clone(),
+        });
+    }
+
+    fn remove(&mut self, index: usize, window: Window, out: &mut Vec<WindowChange>) {
+        self.rows.remove(index);
+
+        if window.len == 0 || index >= window.end() || window.start > self.rows.len() {
+            return;
+        }
+        out.push(WindowChange::Removed {
+            index: index.saturating_sub(window.start),
+        });
+        // The row after the window moves into its last slot
+        if let Some(row) = self.rows.get(window.end() - 1) {
+            out.push(WindowChange::Inserted {
+                index: window.len - 1,
+                data: row.clone(),
+            });
+        }
+    }
+
+    fn replace(&mut self, index: usize, data: Row, window: Window, out: &mut Vec<WindowChange>) {
+        let in_place = self.order.is_empty()
+            || (index.checked_sub(1).is_none_or(|prev| {
+                compare_rows(&self.rows[prev], &data, &self.order) != Ordering::Greater
+            }) && self
+                .rows
+                .get(index + 1)
+                .is_none_or(|next| compare_rows(&data, next, &self.order) != Ordering::Greater));
+        if !in_place {
+            self.remove(index, window, out);
+            self.insert(data, window, out);
+            return;
+        }
+
+        self.rows[index] = data;
+        if window.contains(index) {
+            out.push(WindowChange::Updated {
+                index: index - window.start,
+                data: self.rows[index].clone(),
+            });
+        }
+    }
+}
+
+/// Like `row_id(row) == Some(id)`, without allocating for string ids
+fn has_id(row: &Row, id: &str) -> bool {
+    match row.get("id") {
+        Some(Value::String(row_id) | Value::Reference(row_id)) => row_id == id,
+        Some(Value::Integer(row_id)) => row_id.to_string() == id,
+        _ => false,
+    }
+}
+
+/// Whether `row` is the row with id `id` (as stored in another row's column)
+fn is_row(row: &Row, id: &Value) -> bool {
+    match id {
+        Value::String(id) | Value::Reference(id) => has_id(row, id),
+        Value::Integer(id) => has_id(row, &id.to_string()),
+        _ => false,
+    }
+}
+
+pub(crate) fn row_id(row: &Row) -> Option<String> {
+    match row.get("id")? {
+        Value::String(id) | Value::Reference(id) => Some(id.clone()),
+        Value::Integer(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Order of two rows by `keys`, NULLs (and missing columns) first like SQLite
+fn compare_rows(a: &Row, b: &Row, keys: &[SortKey]) -> Ordering {
+    for key in keys {
+        let ordering = compare_values(
+            a.get(&key.column).unwrap_or(&Value::Null),
+            b.get(&key.column).unwrap_or(&Value::Null),
+        );
+        let ordering = if key.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Less,
+        (_, Value::Null) => Ordering::Greater,
+        _ => a
+            .compare(b)
+            .unwrap_or_else(|| a.to_json_string().cmp(&b.to_json_string())),
+    }
+}
+
+/// A windowed query a frontend watches
+struct WatchedQuery {
+    result: WindowedResult,
+    window: Window,
+    tx: mpsc::Sender<WindowChangeBatch>,
+}
+
+impl WatchedQuery {
+    /// Send the rows of the current window, replacing whatever the frontend holds
+    async fn send_window(&self) -> bool {
+        let rows = self.result.window(self.window);
+        self.send(vec![WindowChange::Reset { rows }]).await
+    }
+
+    async fn send(&self, changes: Vec<WindowChange>) -> bool {
+        let batch = WindowChangeBatch {
+            start: self.window.start,
+            total: self.result.len(),
+            changes,
+        };
+        self.tx.send(batch).await.is_ok()
+    }
+}
+
+/// Windowed queries of a `BackendEngine`, by id
+///
+/// Changes to a query's window are sent in order on its stream, whether they come
+/// from the database or from moving the window, so a frontend can apply them as
+/// they arrive.
+#[derive(Default)]
+pub struct WindowedQueries {
+    next_id: AtomicU64,
+    queries: Mutex<HashMap<u64, Arc<tokio::sync::Mutex<WatchedQuery>>>>,
+}
+
+impl WindowedQueries {
+    /// Start watching `window` of `result`
+    ///
+    /// Returns the query's id and its stream, which starts with the window's rows.
+    pub async fn open(&self, result: WindowedResult, window: Window) -> (u64, WindowChangeStream) {
+        let (tx, rx) = mpsc::channel(1024);
+        let query = WatchedQuery { result, window, tx };
+        query.send_window().await;
+
+        let id = self.next_id.fetch_add(1, AtomicOrdering::Relaxed);
+        self.queries
+            .lock()
+            .unwrap()
+            .insert(id, Arc::new(tokio::sync::Mutex::new(query)));
+        (id, ReceiverStream::new(rx))
+    }
+
+    /// Move the window of query `id`; its stream gets the rows of the new window
+    pub async fn set_window(&self, id: u64, window: Window) -> Result<()> {
+        let query = self
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown windowed query: {}", id))?;
+        let mut query = query.lock().await;
+        query.window = window;
+        if !query.send_window().await {
+            self.close(id);
+        }
+        Ok(())
+    }
+
+    /// Stop watching query `id`; its stream ends
+    pub fn close(&self, id: u64) -> bool {
+        self.queries.lock().unwrap().remove(&id).is_some()
+    }
+
+    /// Params of the `tree_position` a row dropped at `index` of query `id` ends up in
+    ///
+    /// See `WindowedResult::tree_position`.
+    pub async fn tree_position(&self, id: u64, index: usize) -> Result<Row> {
+        let query = self
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown windowed query: {}", id))?;
+        let query = query.lock().await;
+        query.result.tree_position(index).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Windowed query {} has no tree position at row {}: result is empty or has no parent_id column",
+                id,
+                index
+            )
+        })
+    }
+
+    /// Number of watched queries
+    pub fn len(&self) -> usize {
+        self.queries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Apply changes to the result of query `id`, sending their effect on its window
+    ///
+    /// Nothing is sent if neither the window nor the total row count changed.
+    /// Returns false once the query is closed or its stream was dropped.
+    pub async fn apply(&self, id: u64, changes: impl IntoIterator<Item = MapChange>) -> bool {
+        let Some(query) = self.get(id) else {
+            return false;
+        };
+        let mut query = query.lock().await;
+        let total = query.result.len();
+        let window = query.window;
+        let window_changes: Vec<WindowChange> = changes
+            .into_iter()
+            .flat_map(|change| query.result.apply(change, window))
+            .collect();
+        if window_changes.is_empty() && query.result.len() == total {
+            return true;
+        }
+        if !query.send(window_changes).await {
+            self.close(id);
+            return false;
+        }
+        true
+    }
+
+    fn get(&self, id: u64) -> Option<Arc<tokio::sync::Mutex<WatchedQuery>>> {
+        self.queries.lock().unwrap().get(&id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holon_api::ChangeOrigin;
+
+    fn row(id: &str, rank: i64) -> Row {
+        HashMap::from([
+            ("id".to_string(), Value::String(id.to_string())),
+            ("rank".to_string(), Value::Integer(rank)),
+        ])
+    }
+
+    fn by_rank() -> Vec<SortKey> {
+        vec![SortKey {
+            column: "rank".to_string(),
+            descending: false,
+        }]
+    }
+
+    fn ids(rows: &[Row]) -> Vec<String> {
+        rows.iter().filter_map(row_id).collect()
+    }
+
+    /// Apply window changes to a frontend's copy of the window
+    fn mirror(copy: &mut Vec<Row>, changes: Vec<WindowChange>) {
+        for change in changes {
+            match change {
+                WindowChange::Reset { rows } => *copy = rows,
+                WindowChange::Inserted { index, data } => copy.insert(index, data),
+                WindowChange::Updated { index, data } => copy[index] = data,
+                WindowChange::Removed { index } => {
+                    copy.remove(index);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_window_follows_changes() {
+        let origin = ChangeOrigin::Remote {
+            operation_id: None,
+            trace_id: None,
+            source: None,
+        };
+        let changes = vec![
+            MapChange::Created {
+                data: row("new-first", 0),
+                origin: origin.clone(),
+            },
+            MapChange::Created {
+                data: row("new-mid", 35),
+                origin: origin.clone(),
+            },
+            MapChange::Deleted {
+                id: "r2".to_string(),
+                origin: origin.clone(),
+            },
+            MapChange::ColumnChange {
+                id: "r4".to_string(),
+                columns: HashMap::from([("rank".to_string(), Value::Integer(75))]),
+                origin: origin.clone(),
+            },
+            MapChange::ColumnChange {
+                id: "r6".to_string(),
+                columns: HashMap::from([("rank".to_string(), Value::Integer(5))]),
+                origin: origin.clone(),
+            },
+            MapChange::Deleted {
+                id: "new-first".to_string(),
+                origin: origin.clone(),
+            },
+            MapChange::Created {
+                data: row("new-last", 100),
+                origin,
+            },
+        ];
+
+        // Every window over the result must stay equal to the frontend's copy
+        for start in 0..12 {
+            for len in 0..5 {
+                let window = Window::new(start, len);
+                let initial = (1..=9).map(|i| row(&format!("r{}", i), i * 10)).collect();
+                let mut result = WindowedResult::new(initial, by_rank());
+                let mut copy = result.window(window);
+                for change in changes.clone() {
+                    mirror(&mut copy, result.apply(change, window));
+                    assert_eq!(
+                        ids(&copy),
+                        ids(&result.window(window)),
+                        "window {:?}",
+                        window
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_changes_outside_window() {
+        let initial = (1..=5).map(|i| row(&format!("r{}", i), i)).collect();
+        let mut result = WindowedResult::new(initial, vec![]);
+        let window = Window::new(0, 2);
+        let change = MapChange::ColumnChange {
+            id: "r4".to_string(),
+            columns: HashMap::from([("rank".to_string(), Value::Integer(0))]),
+            origin: ChangeOrigin::Remote {
+                operation_id: None,
+                trace_id: None,
+                source: None,
+            },
+        };
+        // Unordered results keep rows in place
+        assert!(result.apply(change, window).is_empty());
+        assert_eq!(ids(&result.window(Window::new(3, 1))), vec!["r4"]);
+    }
+
+    #[test]
+    fn test_tree_position_of_drop_index() {
+        let node = |id: &str, parent_id: &str| {
+            HashMap::from([
+                ("id".to_string(), Value::String(id.to_string())),
+                (
+                    "parent_id".to_string(),
+                    Value::String(parent_id.to_string()),
+                ),
+            ])
+        };
+        // Flattened tree: a, its children a1 and a2, then b
+        let result = WindowedResult::new(
+            vec![
+                node("a", "root"),
+                node("a1", "a"),
+                node("a2", "a"),
+                node("b", "root"),
+            ],
+            vec![],
+        );
+        let position = |index: usize| {
+            let params = result.tree_position(index).unwrap();
+            (
+                params["parent_id"].clone(),
+                params["after_block_id"].clone(),
+            )
+        };
+        let id = |id: &str| Value::String(id.to_string());
+
+        assert_eq!(position(0), (id("root"), Value::Null));
+        assert_eq!(position(1), (id("a"), Value::Null));
+        assert_eq!(position(2), (id("a"), id("a1")));
+        assert_eq!(position(3), (id("root"), id("a")));
+        assert_eq!(position(4), (id("root"), id("b")));
+
+        assert!(
+            WindowedResult::new(vec![row("r1", 1)], vec![])
+                .tree_position(0)
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_carries_window_changes() {
+        use tokio_stream::StreamExt;
+
+        let queries = WindowedQueries::default();
+        let initial = (1..=3).map(|i| row(&format!("r{}", i), i)).collect();
+        let (id, mut stream) = queries
+            .open(WindowedResult::new(initial, by_rank()), Window::new(0, 2))
+            .await;
+        let first = stream.next().await.unwrap();
+        assert_eq!((first.start, first.total), (0, 3));
+        assert!(matches!(&first.changes[..], [WindowChange::Reset { rows }] if rows.len() == 2));
+
+        queries.set_window(id, Window::new(2, 2)).await.unwrap();
+        let moved = stream.next().await.unwrap();
+        assert!(
+            matches!(&moved.changes[..], [WindowChange::Reset { rows }] if ids(rows) == vec!["r3"])
+        );
+
+        assert!(queries.close(id));
+        assert!(stream.next().await.is_none());
+        assert!(queries.set_window(id, Window::new(0, 1)).await.is_err());
+    }
+}
@@ -94,6 +94,7 @@ fn test_block_change_serialization() {
             origin: ChangeOrigin::Local {
                 operation_id: None,
                 trace_id: None,
+                source: None,
             },
         },
         Change::Updated {
@@ -108,6 +109,7 @@ fn test_block_change_serialization() {
             origin: ChangeOrigin::Remote {
                 operation_id: None,
                 trace_id: None,
+                source: None,
             },
         },
         Change::Deleted {
@@ -115,6 +117,7 @@ fn test_block_change_serialization() {
             origin: ChangeOrigin::Local {
                 operation_id: None,
                 trace_id: None,
+                source: None,
             },
         },
     ];
@@ -133,6 +136,7 @@ fn test_change_origin_clone() {
     let origin = ChangeOrigin::Local {
         operation_id: None,
         trace_id: None,
+        source: None,
     };
     let cloned = origin.clone();
 
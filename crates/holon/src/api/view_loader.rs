@@ -0,0 +1,354 @@
+//! View definitions loaded from `.prql` files
+//!
+//! A `ViewLoader` keeps the views of a directory: every `<name>.prql` file in it
+//! is a view called `<name>`. `reload` compiles new and changed files (with the
+//! engine's `compile_query`, so widgets and operations are validated and wired as
+//! for any other query) and tells subscribers what changed. A file that fails to
+//! compile keeps its last valid definition, so a typo while editing doesn't take
+//! the view away.
+//!
+//! `BackendEngine::start_view_loader` polls the directory, so views can be edited
+//! while the app runs.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+
+use anyhow::Result;
+use holon_api::RenderSpec;
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+/// How often the view directory is checked for changes
+pub const DEFAULT_VIEW_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// File extension of view definitions
+pub const VIEW_FILE_EXTENSION: &str = "prql";
+
+/// Where view definitions are loaded from
+#[derive(Debug, Clone)]
+pub struct ViewLoaderConfig {
+    pub directory: PathBuf,
+    pub poll_interval: Duration,
+}
+
+impl ViewLoaderConfig {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            poll_interval: DEFAULT_VIEW_POLL_INTERVAL,
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+}
+
+/// A compiled view
+#[derive(Debug, Clone)]
+pub struct ViewDefinition {
+    /// File name without extension
+    pub name: String,
+    pub path: PathBuf,
+    pub prql: String,
+    pub render_spec: RenderSpec,
+}
+
+/// Change to the views of a `ViewLoader`
+#[derive(Debug, Clone)]
+pub enum ViewEvent {
+    /// A view was added or its file changed and compiled
+    Updated(ViewDefinition),
+    /// A view's file changed but does not compile; the last valid definition stays
+    Failed { name: String, error: String },
+    /// A view's file was deleted
+    Removed { name: String },
+}
+
+impl ViewEvent {
+    pub fn view_name(&self) -> &str {
+        match self {
+            ViewEvent::Updated(view) => &view.name,
+            ViewEvent::Failed { name, .. } | ViewEvent::Removed { name } => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LoadedView {
+    prql: String,
+    /// Last valid definition
+    definition: Option<ViewDefinition>,
+    /// Error of the current file contents
+    error: Option<String>,
+}
+
+/// Views defined by the `.prql` files of a directory
+pub struct ViewLoader {
+    config: ViewLoaderConfig,
+    views: RwLock<HashMap<String, LoadedView>>,
+    events: broadcast::Sender<ViewEvent>,
+}
+
+impl ViewLoader {
+    pub fn new(config: ViewLoaderConfig) -> Self {
+        let (events, _) = broadcast::channel(64);
+        Self {
+            config,
+            views: RwLock::new(HashMap::new()),
+            events,
+        }
+    }
+
+    pub fn config(&self) -> &ViewLoaderConfig {
+        &self.config
+    }
+
+    /// Compile new and changed view files and forget deleted ones
+    ///
+    /// Returns the changes, which are also sent to subscribers. Files whose
+    /// contents are unchanged are not recompiled.
+    pub fn reload<F>(&self, compile: F) -> Result<Vec<ViewEvent>>
+    where
+        F: Fn(&str) -> Result<RenderSpec>,
+    {
+        let files = view_files(&self.config.directory)?;
+        let mut events = Vec::new();
+        {
+            let mut views = self.views.write().unwrap();
+            let removed: Vec<String> = views
+                .keys()
+                .filter(|name| !files.contains_key(*name))
+                .cloned()
+                .collect();
+            for name in removed {
+                views.remove(&name);
+                events.push(ViewEvent::Removed { name });
+            }
+
+            for (name, path) in files {
+                // Compared by contents: modification times can be too coarse
+                // to tell apart two saves in quick succession
+                let prql = match std::fs::read_to_string(&path) {
+                    Ok(prql) => prql,
+                    // Deleted or replaced while we were looking; picked up next time
+                    Err(e) => {
+                        debug!("[ViewLoader] Skipping {}: {}", path.display(), e);
+                        continue;
+                    }
+                };
+                if views.get(&name).is_some_and(|view| view.prql == prql) {
+                    continue;
+                }
+
+                let previous = views.get(&name).and_then(|view| view.definition.clone());
+                let event = match compile(&prql) {
+                    Ok(render_spec) => {
+                        let definition = ViewDefinition {
+                            name: name.clone(),
+                            path,
+                            prql: prql.clone(),
+                            render_spec,
+                        };
+                        views.insert(
+                            name,
+                            LoadedView {
+                                prql,
+                                definition: Some(definition.clone()),
+                                error: None,
+                            },
+                        );
+                        ViewEvent::Updated(definition)
+                    }
+                    Err(e) => {
+                        let error = format!("{:#}", e);
+                        warn!("[ViewLoader] View '{}' does not compile: {}", name, error);
+                        views.insert(
+                            name.clone(),
+                            LoadedView {
+                                prql,
+                                definition: previous,
+                                error: Some(error.clone()),
+                            },
+                        );
+                        ViewEvent::Failed { name, error }
+                    }
+                };
+                events.push(event);
+            }
+        }
+
+        for event in &events {
+            // No receivers is fine: nobody is watching views right now
+            let _ = self.events.send(event.clone());
+        }
+        Ok(events)
+    }
+
+    /// Last valid definition of view `name`
+    pub fn view(&self, name: &str) -> Option<ViewDefinition> {
+        self.views
+            .read()
+            .unwrap()
+            .get(name)
+            .and_then(|view| view.definition.clone())
+    }
+
+    /// All views with a valid definition, by name
+    pub fn views(&self) -> Vec<ViewDefinition> {
+        let mut views: Vec<_> = self
+            .views
+            .read()
+            .unwrap()
+            .values()
+            .filter_map(|view| view.definition.clone())
+            .collect();
+        views.sort_by(|a, b| a.name.cmp(&b.name));
+        views
+    }
+
+    /// Compile errors of views whose current file is invalid, by view name
+    pub fn errors(&self) -> HashMap<String, String> {
+        self.views
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|(name, view)| Some((name.clone(), view.error.clone()?)))
+            .collect()
+    }
+
+    /// Changes from the next `reload` on
+    pub fn subscribe(&self) -> broadcast::Receiver<ViewEvent> {
+        self.events.subscribe()
+    }
+}
+
+/// `.prql` files of `directory`, by view name
+fn view_files(directory: &Path) -> Result<HashMap<String, PathBuf>> {
+    let entries = std::fs::read_dir(directory).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to read view directory {}: {}",
+            directory.display(),
+            e
+        )
+    })?;
+    let mut files = HashMap::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|e| e.to_str()) != Some(VIEW_FILE_EXTENSION)
+        {
+            continue;
+        }
+        if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+            files.insert(name.to_string(), path.clone());
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holon_api::{RenderExpr, ViewState};
+
+    /// Accepts any source without "broken", naming the root widget after the source
+    fn compile(prql: &str) -> Result<RenderSpec> {
+        if prql.contains("broken") {
+            anyhow::bail!("unexpected token");
+        }
+        Ok(RenderSpec {
+            root: RenderExpr::FunctionCall {
+                name: prql.trim().to_string(),
+                args: vec![],
+                operations: vec![],
+            },
+            nested_queries: vec![],
+            operations: HashMap::new(),
+            row_templates: vec![],
+            selection: None,
+            sort: vec![],
+            group_by: None,
+            view_id: None,
+            view_state: ViewState::default(),
+            filters: vec![],
+            timezone: None,
+            board: None,
+            calendar: None,
+            presence: None,
+        })
+    }
+
+    fn root_name(view: &ViewDefinition) -> &str {
+        match &view.render_spec.root {
+            RenderExpr::FunctionCall { name, .. } => name,
+            _ => "",
+        }
+    }
+
+    #[test]
+    fn test_reload_reports_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("inbox.prql"), "list").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a view").unwrap();
+        let loader = ViewLoader::new(ViewLoaderConfig::new(dir.path()));
+        let mut events = loader.subscribe();
+
+        let changes = loader.reload(compile).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], ViewEvent::Updated(view) if view.name == "inbox"));
+        assert!(matches!(events.try_recv(), Ok(ViewEvent::Updated(_))));
+
+        // Nothing changed
+        assert!(loader.reload(compile).unwrap().is_empty());
+
+        std::fs::write(dir.path().join("inbox.prql"), "tree").unwrap();
+        std::fs::write(dir.path().join("today.prql"), "table").unwrap();
+        let mut names: Vec<_> = loader
+            .reload(compile)
+            .unwrap()
+            .iter()
+            .map(|event| event.view_name().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["inbox", "today"]);
+        assert_eq!(root_name(&loader.view("inbox").unwrap()), "tree");
+
+        std::fs::remove_file(dir.path().join("today.prql")).unwrap();
+        let changes = loader.reload(compile).unwrap();
+        assert!(matches!(&changes[..], [ViewEvent::Removed { name }] if name == "today"));
+        assert_eq!(loader.views().len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_file_keeps_last_valid_definition() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("inbox.prql");
+        std::fs::write(&path, "list").unwrap();
+        let loader = ViewLoader::new(ViewLoaderConfig::new(dir.path()));
+        loader.reload(compile).unwrap();
+
+        std::fs::write(&path, "broken").unwrap();
+        let changes = loader.reload(compile).unwrap();
+        assert!(matches!(
+            &changes[..],
+            [ViewEvent::Failed { name, error }] if name == "inbox" && error == "unexpected token"
+        ));
+        assert_eq!(root_name(&loader.view("inbox").unwrap()), "list");
+        assert_eq!(loader.errors().len(), 1);
+
+        std::fs::write(&path, "tree").unwrap();
+        loader.reload(compile).unwrap();
+        assert_eq!(root_name(&loader.view("inbox").unwrap()), "tree");
+        assert!(loader.errors().is_empty());
+    }
+
+    #[test]
+    fn test_missing_directory_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = ViewLoader::new(ViewLoaderConfig::new(dir.path().join("missing")));
+        assert!(loader.reload(compile).is_err());
+    }
+}
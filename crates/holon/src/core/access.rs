@@ -0,0 +1,216 @@
+//! When entities were last viewed and modified.
+//!
+//! `EntityAccessStore` keeps the `entity_access` table: `BackendEngine` records a
+//! modification for every successful local operation on an entity, and a view
+//! whenever the frontend opens one (`record_entity_viewed`). Queries of any
+//! datasource turn into "Recent" views with the `recently_viewed` and
+//! `recently_modified` PRQL steps:
+//!
+//! ```prql
+//! from todoist_tasks
+//! recently_viewed 20
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::storage::turso::TursoBackend;
+use holon_api::{DynamicEntity, HasSchema, Value};
+pub use holon_core::EntityAccess;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Entity name of the access table (also hardcoded in the recency steps)
+pub const ACCESS_ENTITY: &str = "entity_access";
+
+/// Persistent access timestamps backed by TursoBackend
+pub struct EntityAccessStore {
+    backend: Arc<RwLock<TursoBackend>>,
+}
+
+impl EntityAccessStore {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self { backend }
+    }
+
+    /// Initialize the entity_access table schema
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = EntityAccess::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create entity_access table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        info!("Entity access schema initialized");
+        Ok(())
+    }
+
+    /// Record that an entity was viewed at `now`
+    pub async fn record_viewed(&self, entity_name: &str, entity_id: &str, now: i64) -> Result<()> {
+        self.record("last_viewed_at", entity_name, entity_id, now)
+            .await
+    }
+
+    /// Record that a local operation changed an entity at `now`
+    pub async fn record_modified(
+        &self,
+        entity_name: &str,
+        entity_id: &str,
+        now: i64,
+    ) -> Result<()> {
+        self.record("last_modified_at", entity_name, entity_id, now)
+            .await
+    }
+
+    /// Access timestamps of an entity, if it was ever viewed or modified
+    pub async fn get(&self, entity_name: &str, entity_id: &str) -> Result<Option<EntityAccess>> {
+        Ok(self
+            .query(
+                "SELECT * FROM entity_access WHERE id = $id",
+                HashMap::from([(
+                    "id".to_string(),
+                    Value::String(EntityAccess::key(entity_name, entity_id)),
+                )]),
+            )
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// The `limit` most recently viewed entities of all datasources, newest first
+    pub async fn recently_viewed(&self, limit: usize) -> Result<Vec<EntityAccess>> {
+        self.query(
+            "SELECT * FROM entity_access WHERE last_viewed_at IS NOT NULL ORDER BY last_viewed_at DESC LIMIT $limit",
+            HashMap::from([("limit".to_string(), Value::Integer(limit as i64))]),
+        )
+        .await
+    }
+
+    /// The `limit` most recently modified entities of all datasources, newest first
+    pub async fn recently_modified(&self, limit: usize) -> Result<Vec<EntityAccess>> {
+        self.query(
+            "SELECT * FROM entity_access WHERE last_modified_at IS NOT NULL ORDER BY last_modified_at DESC LIMIT $limit",
+            HashMap::from([("limit".to_string(), Value::Integer(limit as i64))]),
+        )
+        .await
+    }
+
+    /// Set `column` of an entity's row to `now`, creating the row if needed
+    async fn record(
+        &self,
+        column: &str,
+        entity_name: &str,
+        entity_id: &str,
+        now: i64,
+    ) -> Result<()> {
+        let sql = format!(
+            "INSERT INTO entity_access (id, entity_name, entity_id, {column})
+            VALUES ($id, $entity_name, $entity_id, $now)
+            ON CONFLICT(id) DO UPDATE SET {column} = MAX(COALESCE({column}, 0), excluded.{column})"
+        );
+        let params = HashMap::from([
+            (
+                "id".to_string(),
+                Value::String(EntityAccess::key(entity_name, entity_id)),
+            ),
+            (
+                "entity_name".to_string(),
+                Value::String(entity_name.to_string()),
+            ),
+            (
+                "entity_id".to_string(),
+                Value::String(entity_id.to_string()),
+            ),
+            ("now".to_string(), Value::Integer(now)),
+        ]);
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(&sql, params)
+            .await
+            .map_err(|e| format!("Failed to record entity access: {}", e))?;
+
+        debug!("Recorded {} of {}:{}", column, entity_name, entity_id);
+        Ok(())
+    }
+
+    async fn query(&self, sql: &str, params: HashMap<String, Value>) -> Result<Vec<EntityAccess>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to query entity access: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(ACCESS_ENTITY);
+                entity.fields = row;
+                EntityAccess::from_entity(entity)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    async fn create_store() -> EntityAccessStore {
+        let store = EntityAccessStore::new(memory_backend().await);
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        store
+    }
+
+    #[tokio::test]
+    async fn test_recently_viewed_and_modified() {
+        let store = create_store().await;
+
+        store.record_viewed("todoist_tasks", "1", 10).await.unwrap();
+        store
+            .record_viewed("logseq_blocks", "b-1", 20)
+            .await
+            .unwrap();
+        store
+            .record_modified("todoist_tasks", "2", 30)
+            .await
+            .unwrap();
+        // Viewing again moves the entity to the front; older times are ignored
+        store.record_viewed("todoist_tasks", "1", 40).await.unwrap();
+        store.record_viewed("todoist_tasks", "1", 5).await.unwrap();
+
+        let viewed = store.recently_viewed(10).await.unwrap();
+        let ids: Vec<_> = viewed.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["todoist_tasks:1", "logseq_blocks:b-1"]);
+        assert_eq!(viewed[0].last_viewed_at, Some(40));
+        assert_eq!(viewed[0].last_modified_at, None);
+
+        let modified = store.recently_modified(10).await.unwrap();
+        assert_eq!(modified.len(), 1);
+        assert_eq!(modified[0].entity_id, "2");
+
+        store
+            .record_modified("todoist_tasks", "1", 50)
+            .await
+            .unwrap();
+        let access = store.get("todoist_tasks", "1").await.unwrap().unwrap();
+        assert_eq!(access.last_viewed_at, Some(40));
+        assert_eq!(access.last_modified_at, Some(50));
+        assert_eq!(store.recently_viewed(1).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,649 @@
+//! Comments on any entity
+//!
+//! An `Annotation` is a comment on one row of any entity table (a block, a task, a
+//! headline): its author, text, creation time and whether it is resolved. Annotations
+//! live in the `annotations` table and are edited through the `annotations` operations.
+//!
+//! Like progress rollups, the comments of a row are also stored in the row itself:
+//! `comment_count`, `open_comment_count` and `_comments` (the JSON array of its
+//! annotations, oldest first). So `(comments this.id)` renders them from the row like
+//! any other column, and commenting updates the views showing the row through CDC.
+//!
+//! The text of annotations is indexed by the `BacklinkIndex`, so a comment linking a
+//! block shows up in the block's linked references.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::core::datasource::{OperationProvider, Result, UndoAction};
+use crate::storage::turso::TursoBackend;
+use crate::storage::types::StorageEntity;
+use holon_api::{
+    ChangeSource, DynamicEntity, HasSchema, Operation, OperationDescriptor, OperationParam,
+    TypeHint, Value,
+};
+
+/// Entity name of the annotations table
+pub const ANNOTATIONS_ENTITY: &str = "annotations";
+
+/// Number of annotations of a row
+pub const COMMENT_COUNT_COLUMN: &str = "comment_count";
+
+/// Number of unresolved annotations of a row
+pub const OPEN_COMMENT_COUNT_COLUMN: &str = "open_comment_count";
+
+/// JSON array of the annotations of a row, oldest first (NULL without annotations)
+pub const COMMENTS_COLUMN: &str = "_comments";
+
+/// Author of annotations made without an agent or device (e.g. in a local frontend)
+const DEFAULT_AUTHOR: &str = "me";
+
+/// A comment on an entity
+///
+/// Table name: `annotations`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Entity)]
+#[entity(name = "annotations", short_name = "comment")]
+pub struct Annotation {
+    #[primary_key]
+    pub id: String,
+    /// Table of the annotated entity (e.g. "blocks", "todoist_tasks")
+    #[indexed]
+    pub entity_name: String,
+    #[indexed]
+    pub entity_id: String,
+    pub author: String,
+    pub text: String,
+    /// When the annotation was made (Unix timestamp in milliseconds)
+    pub created_at: i64,
+    pub resolved: bool,
+}
+
+impl Annotation {
+    pub fn new(
+        entity_name: impl Into<String>,
+        entity_id: impl Into<String>,
+        author: impl Into<String>,
+        text: impl Into<String>,
+        created_at: i64,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            entity_name: entity_name.into(),
+            entity_id: entity_id.into(),
+            author: author.into(),
+            text: text.into(),
+            created_at,
+            resolved: false,
+        }
+    }
+}
+
+/// Parse the `_comments` column of an annotated row
+pub fn parse_comments(json: &str) -> Vec<Annotation> {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+/// Persistent annotations backed by TursoBackend
+pub struct AnnotationStore {
+    backend: Arc<RwLock<TursoBackend>>,
+}
+
+impl AnnotationStore {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self { backend }
+    }
+
+    /// Initialize the annotations table schema
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = Annotation::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create annotations table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        info!("Annotations schema initialized");
+        Ok(())
+    }
+
+    /// Look up an annotation by ID
+    pub async fn get(&self, id: &str) -> Result<Option<Annotation>> {
+        Ok(self
+            .query(
+                "SELECT * FROM annotations WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+            )
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// All annotations of an entity, oldest first
+    pub async fn annotations_for_entity(
+        &self,
+        entity_name: &str,
+        entity_id: &str,
+    ) -> Result<Vec<Annotation>> {
+        let params = HashMap::from([
+            (
+                "entity_name".to_string(),
+                Value::String(entity_name.to_string()),
+            ),
+            (
+                "entity_id".to_string(),
+                Value::String(entity_id.to_string()),
+            ),
+        ]);
+        self.query(
+            "SELECT * FROM annotations WHERE entity_name = $entity_name AND entity_id = $entity_id ORDER BY created_at",
+            params,
+        )
+        .await
+    }
+
+    /// Store an annotation (replacing the one with the same ID) and update the annotated row
+    pub async fn save(&self, annotation: &Annotation) -> Result<()> {
+        if !is_table_name(&annotation.entity_name) {
+            return Err(format!("Invalid entity name: {}", annotation.entity_name).into());
+        }
+        let sql = "INSERT INTO annotations
+                (id, entity_name, entity_id, author, text, created_at, resolved)
+            VALUES ($id, $entity_name, $entity_id, $author, $text, $created_at, $resolved)
+            ON CONFLICT(id) DO UPDATE SET
+                author = excluded.author,
+                text = excluded.text,
+                resolved = excluded.resolved";
+        {
+            let backend = self.backend.read().await;
+            backend
+                .execute_sql(sql, annotation.to_entity().fields)
+                .await
+                .map_err(|e| format!("Failed to save annotation: {}", e))?;
+        }
+        self.refresh_entity(&annotation.entity_name, &annotation.entity_id)
+            .await?;
+        Ok(())
+    }
+
+    /// Delete an annotation, returning it
+    pub async fn delete(&self, id: &str) -> Result<Option<Annotation>> {
+        let Some(annotation) = self.get(id).await? else {
+            return Ok(None);
+        };
+        {
+            let backend = self.backend.read().await;
+            backend
+                .execute_sql(
+                    "DELETE FROM annotations WHERE id = $id",
+                    HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+                )
+                .await
+                .map_err(|e| format!("Failed to delete annotation: {}", e))?;
+        }
+        self.refresh_entity(&annotation.entity_name, &annotation.entity_id)
+            .await?;
+        debug!("Deleted annotation {}", id);
+        Ok(Some(annotation))
+    }
+
+    /// Replace the text of an annotation. Returns the previous text.
+    pub async fn set_text(&self, id: &str, text: &str) -> Result<String> {
+        let mut annotation = self.require(id).await?;
+        let previous = std::mem::replace(&mut annotation.text, text.to_string());
+        self.save(&annotation).await?;
+        Ok(previous)
+    }
+
+    /// Resolve or reopen an annotation. Returns the previous value.
+    pub async fn set_resolved(&self, id: &str, resolved: bool) -> Result<bool> {
+        let mut annotation = self.require(id).await?;
+        let previous = annotation.resolved;
+        annotation.resolved = resolved;
+        self.save(&annotation).await?;
+        Ok(previous)
+    }
+
+    /// Store the comment counts and comments of an entity in its row
+    ///
+    /// Adds the columns to the entity's table if missing. Returns false if the table
+    /// or row doesn't exist (yet).
+    pub async fn refresh_entity(&self, entity_name: &str, entity_id: &str) -> Result<bool> {
+        if !self.prepare_table(entity_name).await? {
+            return Ok(false);
+        }
+        let annotations = self.annotations_for_entity(entity_name, entity_id).await?;
+        let open = annotations.iter().filter(|a| !a.resolved).count();
+        let comments = match annotations.is_empty() {
+            true => Value::Null,
+            false => Value::String(
+                serde_json::to_string(&annotations)
+                    .map_err(|e| format!("Failed to serialize annotations: {}", e))?,
+            ),
+        };
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                &format!(
+                    "UPDATE {} SET {} = $count, {} = $open, {} = $comments WHERE id = $id",
+                    entity_name, COMMENT_COUNT_COLUMN, OPEN_COMMENT_COUNT_COLUMN, COMMENTS_COLUMN
+                ),
+                HashMap::from([
+                    (
+                        "count".to_string(),
+                        Value::Integer(annotations.len() as i64),
+                    ),
+                    ("open".to_string(), Value::Integer(open as i64)),
+                    ("comments".to_string(), comments),
+                    ("id".to_string(), Value::String(entity_id.to_string())),
+                ]),
+            )
+            .await
+            .map_err(|e| format!("Failed to update comments of {}: {}", entity_id, e))?;
+        Ok(true)
+    }
+
+    /// Store the comments of every annotated entity in its row
+    ///
+    /// Run on start, since tables can be recreated (e.g. by a full sync) without them.
+    /// Returns the number of updated entities.
+    pub async fn refresh_all(&self) -> Result<usize> {
+        let rows = {
+            let backend = self.backend.read().await;
+            backend
+                .execute_sql(
+                    "SELECT DISTINCT entity_name, entity_id FROM annotations",
+                    HashMap::new(),
+                )
+                .await
+                .map_err(|e| format!("Failed to query annotations: {}", e))?
+        };
+        let mut refreshed = 0;
+        for row in rows {
+            let (Some(entity_name), Some(entity_id)) = (
+                row.get("entity_name").and_then(|v| v.as_string_owned()),
+                row.get("entity_id").and_then(|v| v.as_string_owned()),
+            ) else {
+                continue;
+            };
+            if self.refresh_entity(&entity_name, &entity_id).await? {
+                refreshed += 1;
+            }
+        }
+        Ok(refreshed)
+    }
+
+    /// Add the comment columns to `table` if missing. Returns false if the table doesn't exist.
+    async fn prepare_table(&self, table: &str) -> Result<bool> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = $name",
+                HashMap::from([("name".to_string(), Value::String(table.to_string()))]),
+            )
+            .await
+            .map_err(|e| format!("Failed to look up table {}: {}", table, e))?;
+        let Some(create_sql) = rows
+            .first()
+            .and_then(|row| row.get("sql"))
+            .and_then(|sql| sql.as_string_owned())
+        else {
+            return Ok(false);
+        };
+        for (column, sql_type) in [
+            (COMMENT_COUNT_COLUMN, "INTEGER"),
+            (OPEN_COMMENT_COUNT_COLUMN, "INTEGER"),
+            (COMMENTS_COLUMN, "TEXT"),
+        ] {
+            if !create_sql.contains(column) {
+                backend
+                    .execute_sql(
+                        &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, sql_type),
+                        HashMap::new(),
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to add {} to {}: {}", column, table, e))?;
+            }
+        }
+        Ok(true)
+    }
+
+    async fn require(&self, id: &str) -> Result<Annotation> {
+        self.get(id)
+            .await?
+            .ok_or_else(|| format!("Annotation not found: {}", id).into())
+    }
+
+    async fn query(&self, sql: &str, params: HashMap<String, Value>) -> Result<Vec<Annotation>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to query annotations: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(ANNOTATIONS_ENTITY);
+                entity.fields = row;
+                Annotation::from_entity(entity)
+            })
+            .collect()
+    }
+}
+
+/// Entity names end up in SQL, so only plain identifiers are accepted
+fn is_table_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Author of an annotation made by the calling task: its agent or device
+fn current_author() -> String {
+    ChangeSource::current()
+        .and_then(|source| source.agent.or(source.device_id))
+        .unwrap_or_else(|| DEFAULT_AUTHOR.to_string())
+}
+
+fn annotation_operation(
+    name: &str,
+    display_name: &str,
+    description: &str,
+    required_params: Vec<OperationParam>,
+) -> OperationDescriptor {
+    OperationDescriptor {
+        entity_name: ANNOTATIONS_ENTITY.to_string(),
+        entity_short_name: "comment".to_string(),
+        id_column: "id".to_string(),
+        name: name.to_string(),
+        display_name: display_name.to_string(),
+        description: description.to_string(),
+        required_params,
+        affected_fields: vec![],
+        param_mappings: vec![],
+        precondition: None,
+        simulation: None,
+    }
+}
+
+fn string_param(name: &str, description: &str) -> OperationParam {
+    OperationParam {
+        name: name.to_string(),
+        type_hint: TypeHint::String,
+        description: description.to_string(),
+    }
+}
+
+fn annotation_op(op_name: &str, display_name: &str, params: StorageEntity) -> Operation {
+    Operation::new(ANNOTATIONS_ENTITY, op_name, display_name, params)
+}
+
+fn id_params(id: &str) -> StorageEntity {
+    HashMap::from([("id".to_string(), Value::String(id.to_string()))])
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationProvider for AnnotationStore {
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        let id = || string_param("id", "Annotation ID");
+        vec![
+            annotation_operation(
+                "create",
+                "Add comment",
+                "Comment on an entity (the author defaults to the agent or device making the change)",
+                vec![
+                    string_param("entity_name", "Table of the commented entity"),
+                    string_param("entity_id", "ID of the commented entity"),
+                    string_param("text", "Text of the comment"),
+                ],
+            ),
+            annotation_operation(
+                "set_text",
+                "Edit comment",
+                "Replace the text of a comment",
+                vec![id(), string_param("text", "New text")],
+            ),
+            annotation_operation(
+                "resolve",
+                "Resolve comment",
+                "Mark the comment as resolved",
+                vec![id()],
+            ),
+            annotation_operation(
+                "unresolve",
+                "Reopen comment",
+                "Mark the comment as open again",
+                vec![id()],
+            ),
+            annotation_operation("delete", "Delete comment", "Delete the comment", vec![id()]),
+        ]
+    }
+
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        params: StorageEntity,
+    ) -> Result<UndoAction> {
+        if entity_name != ANNOTATIONS_ENTITY {
+            return Err(format!(
+                "Expected entity_name '{}', got '{}'",
+                ANNOTATIONS_ENTITY, entity_name
+            )
+            .into());
+        }
+
+        let string = |name: &str| {
+            params
+                .get(name)
+                .and_then(|v| v.as_string())
+                .ok_or_else(|| format!("Missing '{}' parameter", name))
+        };
+
+        if op_name == "create" {
+            let mut annotation = Annotation::new(
+                string("entity_name")?,
+                string("entity_id")?,
+                params
+                    .get("author")
+                    .and_then(|v| v.as_string_owned())
+                    .unwrap_or_else(current_author),
+                string("text")?,
+                params
+                    .get("created_at")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+            );
+            // Undoing a delete recreates the annotation with its ID and state
+            if let Some(id) = params.get("id").and_then(|v| v.as_string_owned()) {
+                annotation.id = id;
+            }
+            annotation.resolved = params
+                .get("resolved")
+                .and_then(|v| v.as_bool().or_else(|| v.as_i64().map(|i| i != 0)))
+                .unwrap_or(false);
+            self.save(&annotation).await?;
+            return Ok(UndoAction::Undo(annotation_op(
+                "delete",
+                "Delete comment",
+                id_params(&annotation.id),
+            )));
+        }
+
+        let id = string("id")?;
+        match op_name {
+            "set_text" => {
+                let previous = self.set_text(id, string("text")?).await?;
+                let mut undo_params = id_params(id);
+                undo_params.insert("text".to_string(), Value::String(previous));
+                Ok(UndoAction::Undo(annotation_op(
+                    "set_text",
+                    "Edit comment",
+                    undo_params,
+                )))
+            }
+            "resolve" | "unresolve" => {
+                let previous = self.set_resolved(id, op_name == "resolve").await?;
+                let (undo_name, display_name) = if previous {
+                    ("resolve", "Resolve comment")
+                } else {
+                    ("unresolve", "Reopen comment")
+                };
+                Ok(UndoAction::Undo(annotation_op(
+                    undo_name,
+                    display_name,
+                    id_params(id),
+                )))
+            }
+            "delete" => {
+                let annotation = self
+                    .delete(id)
+                    .await?
+                    .ok_or_else(|| format!("Annotation not found: {}", id))?;
+                Ok(UndoAction::Undo(annotation_op(
+                    "create",
+                    "Add comment",
+                    annotation.to_entity().fields,
+                )))
+            }
+            _ => Err(format!("Unknown operation: {}", op_name).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    #[tokio::test]
+    async fn test_comments_are_stored_in_the_annotated_row() {
+        let backend = memory_backend().await;
+        backend
+            .read()
+            .await
+            .execute_sql(
+                "CREATE TABLE tasks (id TEXT PRIMARY KEY, content TEXT)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+            .read()
+            .await
+            .execute_sql(
+                "INSERT INTO tasks (id, content) VALUES ('t1', 'Write report')",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        let store = AnnotationStore::new(backend);
+        store.initialize_schema().await.unwrap();
+
+        let row = || async {
+            let backend = store.backend.read().await;
+            backend
+                .execute_sql("SELECT * FROM tasks WHERE id = 't1'", HashMap::new())
+                .await
+                .unwrap()
+                .remove(0)
+        };
+        let create = |text: &str, created_at: i64| {
+            HashMap::from([
+                (
+                    "entity_name".to_string(),
+                    Value::String("tasks".to_string()),
+                ),
+                ("entity_id".to_string(), Value::String("t1".to_string())),
+                ("text".to_string(), Value::String(text.to_string())),
+                ("created_at".to_string(), Value::Integer(created_at)),
+            ])
+        };
+
+        store
+            .execute_operation(ANNOTATIONS_ENTITY, "create", create("Needs numbers", 1))
+            .await
+            .unwrap();
+        let undo = store
+            .execute_operation(ANNOTATIONS_ENTITY, "create", create("See [[Q3]]", 2))
+            .await
+            .unwrap();
+        let UndoAction::Undo(undo) = undo else {
+            panic!("Expected an undo operation");
+        };
+
+        let mut first = store.annotations_for_entity("tasks", "t1").await.unwrap()[0].clone();
+        assert_eq!(first.author, DEFAULT_AUTHOR);
+        store
+            .execute_operation(ANNOTATIONS_ENTITY, "resolve", id_params(&first.id))
+            .await
+            .unwrap();
+        first.resolved = true;
+
+        let task = row().await;
+        assert_eq!(task.get(COMMENT_COUNT_COLUMN), Some(&Value::Integer(2)));
+        assert_eq!(
+            task.get(OPEN_COMMENT_COUNT_COLUMN),
+            Some(&Value::Integer(1))
+        );
+        let comments = parse_comments(&task[COMMENTS_COLUMN].as_string_owned().unwrap());
+        assert_eq!(
+            comments
+                .iter()
+                .map(|c| (c.text.as_str(), c.resolved))
+                .collect::<Vec<_>>(),
+            vec![("Needs numbers", true), ("See [[Q3]]", false)]
+        );
+
+        // Deleting and re-creating from the undo keeps the annotation's ID and state
+        store
+            .execute_operation(ANNOTATIONS_ENTITY, undo.op_name.as_str(), undo.params)
+            .await
+            .unwrap();
+        let UndoAction::Undo(redo) = store
+            .execute_operation(ANNOTATIONS_ENTITY, "delete", id_params(&first.id))
+            .await
+            .unwrap()
+        else {
+            panic!("Expected an undo operation");
+        };
+        assert_eq!(row().await.get(COMMENTS_COLUMN), Some(&Value::Null));
+        store
+            .execute_operation(ANNOTATIONS_ENTITY, "create", redo.params)
+            .await
+            .unwrap();
+        assert_eq!(store.get(&first.id).await.unwrap(), Some(first));
+        assert_eq!(
+            row().await.get(OPEN_COMMENT_COUNT_COLUMN),
+            Some(&Value::Integer(0))
+        );
+
+        let invalid = HashMap::from([
+            (
+                "entity_name".to_string(),
+                Value::String("tasks; DROP TABLE tasks".to_string()),
+            ),
+            ("entity_id".to_string(), Value::String("t1".to_string())),
+            ("text".to_string(), Value::String("x".to_string())),
+        ]);
+        assert!(
+            store
+                .execute_operation(ANNOTATIONS_ENTITY, "create", invalid)
+                .await
+                .is_err()
+        );
+    }
+}
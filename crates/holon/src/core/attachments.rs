@@ -0,0 +1,389 @@
+//! Attachment storage and operations.
+//!
+//! `AttachmentStore` keeps file metadata in the `attachments` table, so an entity's
+//! attachments are a plain PRQL join (e.g. `from attachments | filter entity_id == ...`)
+//! and render uniformly through the `attachment_chip` widget.
+//!
+//! `AttachmentProvider` exposes `attach_file`/`remove_attachment` for one entity type
+//! (e.g. `todoist_tasks`) by recording attachments locally. Sources that reference
+//! files themselves (like org-mode `[[file:...]]` links) import them via
+//! `replace_source_attachments`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::core::datasource::{
+    __operations_attachment_operations, AttachmentOperations, OperationProvider, Result, UndoAction,
+};
+use crate::storage::turso::TursoBackend;
+use crate::storage::types::StorageEntity;
+use holon_api::{DynamicEntity, HasSchema, Operation, OperationDescriptor, Value};
+pub use holon_core::{Attachment, LOCAL_ATTACHMENT_SOURCE, format_size, guess_mime_type};
+
+/// Entity name of the attachments table
+pub const ATTACHMENTS_ENTITY: &str = "attachments";
+
+/// Persistent attachment metadata backed by TursoBackend
+pub struct AttachmentStore {
+    backend: Arc<RwLock<TursoBackend>>,
+}
+
+impl AttachmentStore {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self { backend }
+    }
+
+    /// Initialize the attachments table schema
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = Attachment::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create attachments table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        info!("Attachments schema initialized");
+        Ok(())
+    }
+
+    /// Attach a local file to an entity, recording its current size
+    ///
+    /// Fails if `path` is not a readable file. Attaching the same file twice
+    /// refreshes the existing attachment.
+    pub async fn attach_file(
+        &self,
+        entity_name: &str,
+        entity_id: &str,
+        path: &str,
+        now: i64,
+    ) -> Result<Attachment> {
+        let metadata =
+            std::fs::metadata(path).map_err(|e| format!("Cannot attach {}: {}", path, e))?;
+        if !metadata.is_file() {
+            return Err(format!("Cannot attach {}: not a file", path).into());
+        }
+
+        let attachment =
+            Attachment::file(entity_name, entity_id, path, LOCAL_ATTACHMENT_SOURCE, now)
+                .with_size(metadata.len() as i64);
+        self.save(&attachment).await?;
+        debug!("Attached {} to {}", path, entity_id);
+        Ok(attachment)
+    }
+
+    /// Look up an attachment by ID
+    pub async fn get(&self, id: &str) -> Result<Option<Attachment>> {
+        Ok(self
+            .query(
+                "SELECT * FROM attachments WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+            )
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// Delete an attachment, returning it
+    pub async fn remove(&self, id: &str) -> Result<Attachment> {
+        let attachment = self
+            .get(id)
+            .await?
+            .ok_or_else(|| format!("Attachment {} not found", id))?;
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "DELETE FROM attachments WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+            )
+            .await
+            .map_err(|e| format!("Failed to delete attachment: {}", e))?;
+        debug!("Removed attachment {}", id);
+        Ok(attachment)
+    }
+
+    /// All attachments of an entity, oldest first
+    pub async fn attachments_for_entity(
+        &self,
+        entity_name: &str,
+        entity_id: &str,
+    ) -> Result<Vec<Attachment>> {
+        let params = HashMap::from([
+            (
+                "entity_name".to_string(),
+                Value::String(entity_name.to_string()),
+            ),
+            (
+                "entity_id".to_string(),
+                Value::String(entity_id.to_string()),
+            ),
+        ]);
+        self.query(
+            "SELECT * FROM attachments WHERE entity_name = $entity_name AND entity_id = $entity_id ORDER BY created_at",
+            params,
+        )
+        .await
+    }
+
+    /// Replace all attachments of an external source (e.g. one org file's file links)
+    pub async fn replace_source_attachments(
+        &self,
+        source: &str,
+        attachments: &[Attachment],
+    ) -> Result<()> {
+        {
+            let backend = self.backend.read().await;
+            backend
+                .execute_sql(
+                    "DELETE FROM attachments WHERE source = $source",
+                    HashMap::from([("source".to_string(), Value::String(source.to_string()))]),
+                )
+                .await
+                .map_err(|e| format!("Failed to delete attachments: {}", e))?;
+        }
+        for attachment in attachments {
+            self.save(attachment).await?;
+        }
+        debug!("Imported {} attachments from {}", attachments.len(), source);
+        Ok(())
+    }
+
+    async fn save(&self, attachment: &Attachment) -> Result<()> {
+        let sql = "INSERT INTO attachments
+                (id, entity_name, entity_id, name, path, url, mime_type, size_bytes, source, created_at)
+            VALUES ($id, $entity_name, $entity_id, $name, $path, $url, $mime_type, $size_bytes, $source, $created_at)
+            ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                mime_type = excluded.mime_type,
+                size_bytes = excluded.size_bytes,
+                source = excluded.source";
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(sql, attachment.to_entity().fields)
+            .await
+            .map_err(|e| format!("Failed to save attachment: {}", e))?;
+        Ok(())
+    }
+
+    async fn query(&self, sql: &str, params: HashMap<String, Value>) -> Result<Vec<Attachment>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to query attachments: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(ATTACHMENTS_ENTITY);
+                entity.fields = row;
+                Attachment::from_entity(entity)
+            })
+            .collect()
+    }
+}
+
+/// Local `attach_file`/`remove_attachment` operations for one entity type
+pub struct AttachmentProvider {
+    store: Arc<AttachmentStore>,
+    entity_name: String,
+    short_name: String,
+}
+
+impl AttachmentProvider {
+    pub fn new(
+        store: Arc<AttachmentStore>,
+        entity_name: impl Into<String>,
+        short_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            store,
+            entity_name: entity_name.into(),
+            short_name: short_name.into(),
+        }
+    }
+
+    fn inverse(
+        &self,
+        op_name: &str,
+        display_name: &str,
+        params: HashMap<String, Value>,
+    ) -> UndoAction {
+        UndoAction::Undo(Operation::new(
+            &self.entity_name,
+            op_name,
+            display_name,
+            params,
+        ))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl AttachmentOperations for AttachmentProvider {
+    async fn attach_file(&self, entity_id: &str, path: &str) -> Result<UndoAction> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let attachment = self
+            .store
+            .attach_file(&self.entity_name, entity_id, path, now)
+            .await?;
+        Ok(self.inverse(
+            "remove_attachment",
+            "Remove attachment",
+            HashMap::from([("id".to_string(), Value::String(attachment.id))]),
+        ))
+    }
+
+    async fn remove_attachment(&self, id: &str) -> Result<UndoAction> {
+        let attachment = self
+            .store
+            .get(id)
+            .await?
+            .ok_or_else(|| format!("Attachment {} not found", id))?;
+        if attachment.source != LOCAL_ATTACHMENT_SOURCE {
+            return Err(format!(
+                "Attachment {} comes from {}; remove it there",
+                id, attachment.source
+            )
+            .into());
+        }
+        let path = attachment
+            .path
+            .clone()
+            .ok_or_else(|| format!("Attachment {} has no local path", id))?;
+
+        self.store.remove(id).await?;
+        Ok(self.inverse(
+            "attach_file",
+            "Attach file",
+            HashMap::from([
+                ("entity_id".to_string(), Value::String(attachment.entity_id)),
+                ("path".to_string(), Value::String(path)),
+            ]),
+        ))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationProvider for AttachmentProvider {
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        __operations_attachment_operations::attachment_operations(
+            &self.entity_name,
+            &self.short_name,
+            &self.entity_name,
+            "id",
+        )
+    }
+
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        params: StorageEntity,
+    ) -> Result<UndoAction> {
+        if entity_name != self.entity_name {
+            return Err(format!(
+                "Expected entity_name '{}', got '{}'",
+                self.entity_name, entity_name
+            )
+            .into());
+        }
+        __operations_attachment_operations::dispatch_operation(self, op_name, &params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    async fn create_store() -> AttachmentStore {
+        let store = AttachmentStore::new(memory_backend().await);
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        store
+    }
+
+    #[tokio::test]
+    async fn test_attach_and_remove_file() {
+        let store = Arc::new(create_store().await);
+        let provider = AttachmentProvider::new(store.clone(), "todoist_tasks", "task");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "hello").unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        provider.attach_file("t1", &path).await.unwrap();
+        assert!(provider.attach_file("t1", "/does/not/exist").await.is_err());
+
+        let attachments = store
+            .attachments_for_entity("todoist_tasks", "t1")
+            .await
+            .unwrap();
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].name, "notes.txt");
+        assert_eq!(attachments[0].size_bytes, Some(5));
+        assert_eq!(attachments[0].mime_type.as_deref(), Some("text/plain"));
+
+        let undo = provider
+            .remove_attachment(&attachments[0].id)
+            .await
+            .unwrap();
+        match undo {
+            UndoAction::Undo(op) => {
+                assert_eq!(op.op_name, "attach_file");
+                assert_eq!(op.params.get("path"), Some(&Value::String(path)));
+            }
+            other => panic!("Expected an undo operation, got {:?}", other),
+        }
+        assert!(
+            store
+                .attachments_for_entity("todoist_tasks", "t1")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_imported_attachments_are_replaced_not_removed() {
+        let store = Arc::new(create_store().await);
+        let provider = AttachmentProvider::new(store.clone(), "org_headlines", "headline");
+
+        let linked = Attachment::file("org_headlines", "h1", "/org/plan.pdf", "org:file-1", 0);
+        store
+            .replace_source_attachments("org:file-1", &[linked.clone()])
+            .await
+            .unwrap();
+        store
+            .replace_source_attachments("org:file-1", &[linked.clone()])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store
+                .attachments_for_entity("org_headlines", "h1")
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(provider.remove_attachment(&linked.id).await.is_err());
+    }
+}
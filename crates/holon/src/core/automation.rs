@@ -0,0 +1,984 @@
+//! Automation rules
+//!
+//! An `AutomationRule` watches one field of an entity table. When a row is created
+//! with, or changed to, a value satisfying the rule's condition, the rule executes its
+//! actions: operations whose parameters are templates over the changed row.
+//!
+//! ```text
+//! when todoist_tasks.priority >= 4
+//!   -> reminders.create(entity_name: "todoist_tasks", entity_id: "{{id}}",
+//!                       title: "Urgent: {{content}}", remind_at: ...)
+//! ```
+//!
+//! Rules are rows of the `automation_rules` table, edited at runtime through the
+//! `automation_rules` operations and synced like other data. `AutomationRules`
+//! follows the CDC stream of the watched tables (and of the rules themselves) and
+//! dispatches the actions with the `ChangeSource` agent `rule:<id>`.
+//!
+//! Loop protection: changes attributed to a rule never trigger rules, and a rule only
+//! fires when the watched value actually changes, so an echo of the same value (e.g.
+//! a sync round trip of a rule's own write) does not fire it again.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, RwLock as StdRwLock, Weak};
+
+use async_trait::async_trait;
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, info, warn};
+
+use crate::api::operation_dispatcher::OperationDispatcher;
+use crate::core::datasource::{OperationProvider, Result, UndoAction};
+use crate::references::content_source;
+use crate::storage::turso::{ChangeData, RowChange, TursoBackend};
+use crate::storage::types::StorageEntity;
+use holon_api::{
+    BatchWithMetadata, CURRENT_CHANGE_SOURCE, ChangeSource, DynamicEntity, HasSchema, Operation,
+    OperationDescriptor, OperationParam, TypeHint, Value,
+};
+
+/// Entity name used for rule operations
+pub const AUTOMATION_RULES_ENTITY: &str = "automation_rules";
+
+/// Prefix of the `ChangeSource` agent of changes made by rules (`rule:<id>`)
+pub const RULE_AGENT_PREFIX: &str = "rule:";
+
+/// Prefix of the materialized views over the watched tables
+const SOURCE_VIEW_PREFIX: &str = "rules_src_";
+
+/// Materialized view over the rules, to pick up edits
+const RULES_VIEW: &str = "rules_watch";
+
+/// Fields of a rule that `set_field` may change
+const EDITABLE_FIELDS: &[&str] = &[
+    "name",
+    "entity_name",
+    "field",
+    "operator",
+    "value",
+    "actions",
+];
+
+/// A rule executing operations when a field of an entity changes
+///
+/// Table name: `automation_rules`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Entity)]
+#[entity(name = "automation_rules", short_name = "rule")]
+pub struct AutomationRule {
+    #[primary_key]
+    pub id: String,
+    pub name: String,
+    /// Table whose rows are watched (e.g. "todoist_tasks")
+    #[indexed]
+    pub entity_name: String,
+    /// Column whose changes trigger the rule
+    pub field: String,
+    /// `==`, `!=`, `<`, `<=`, `>`, `>=`, `contains`, or `changed` (any new value)
+    pub operator: String,
+    /// JSON of the value the new value is compared with (unused by `changed`)
+    pub value: Option<String>,
+    /// JSON array of `RuleAction`s
+    pub actions: String,
+    /// Disabled rules are kept but never fire
+    pub enabled: bool,
+    /// When the rule was created (Unix timestamp in milliseconds)
+    pub created_at: i64,
+}
+
+impl AutomationRule {
+    /// Check the operator, value and actions, so that broken rules are rejected on edit
+    pub fn validate(&self) -> Result<()> {
+        if !matches!(
+            self.operator.as_str(),
+            "==" | "!=" | "<" | "<=" | ">" | ">=" | "contains" | "changed"
+        ) {
+            return Err(format!("Unknown rule operator: {}", self.operator).into());
+        }
+        if self.operator != "changed" && self.expected()?.is_none() {
+            return Err(format!("Operator {} needs a value", self.operator).into());
+        }
+        if self.parsed_actions()?.is_empty() {
+            return Err("A rule needs at least one action".into());
+        }
+        Ok(())
+    }
+
+    /// The value compared with, parsed from JSON
+    pub fn expected(&self) -> Result<Option<Value>> {
+        self.value
+            .as_deref()
+            .map(|json| {
+                serde_json::from_str::<JsonValue>(json)
+                    .map(Value::from_json_value)
+                    .map_err(|e| format!("Invalid rule value {}: {}", json, e).into())
+            })
+            .transpose()
+    }
+
+    pub fn parsed_actions(&self) -> Result<Vec<RuleAction>> {
+        serde_json::from_str(&self.actions)
+            .map_err(|e| format!("Invalid actions of rule {}: {}", self.id, e).into())
+    }
+
+    /// Whether a change of the watched field from `old` (None for a new row) to `new` fires the rule
+    pub fn fires(&self, old: Option<&Value>, new: &Value) -> bool {
+        if !self.enabled || old == Some(new) {
+            return false;
+        }
+        if self.operator == "changed" {
+            return true;
+        }
+        let Ok(Some(expected)) = self.expected() else {
+            return false;
+        };
+        // SQLite stores booleans as integers
+        let new = match (&expected, new) {
+            (Value::Boolean(_), Value::Integer(i)) => Value::Boolean(*i != 0),
+            _ => new.clone(),
+        };
+        let ordering = new.compare(&expected);
+        match self.operator.as_str() {
+            "==" => ordering == Some(std::cmp::Ordering::Equal),
+            "!=" => ordering != Some(std::cmp::Ordering::Equal),
+            "<" => ordering == Some(std::cmp::Ordering::Less),
+            "<=" => ordering.is_some_and(|o| o.is_le()),
+            ">" => ordering == Some(std::cmp::Ordering::Greater),
+            ">=" => ordering.is_some_and(|o| o.is_ge()),
+            "contains" => match (&new, &expected) {
+                (Value::String(new), Value::String(expected)) => new.contains(expected.as_str()),
+                (Value::Array(items), expected) => items.contains(expected),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+/// An operation executed when a rule fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleAction {
+    pub entity_name: String,
+    pub op_name: String,
+    /// Parameters; strings are templates over the changed row (see `render_row_template`)
+    #[serde(default)]
+    pub params: HashMap<String, JsonValue>,
+}
+
+impl RuleAction {
+    /// Parameters of the operation for the changed `row`
+    pub fn render(&self, row: &HashMap<String, Value>) -> Result<StorageEntity> {
+        self.params
+            .iter()
+            .map(|(name, param)| -> Result<(String, Value)> {
+                let value = match param {
+                    JsonValue::String(template) => render_row_template(template, row)?,
+                    other => Value::from_json_value(other.clone()),
+                };
+                Ok((name.clone(), value))
+            })
+            .collect()
+    }
+}
+
+/// Render a parameter template over a row
+///
+/// `{{column}}` placeholders are replaced by the row's values. A template that is a
+/// single placeholder keeps the value's type; others render to a string. A placeholder
+/// naming a column the row doesn't have is an error.
+pub fn render_row_template(template: &str, row: &HashMap<String, Value>) -> Result<Value> {
+    let lookup = |column: &str| {
+        row.get(column)
+            .ok_or_else(|| format!("No column {} for `{}`", column, template))
+    };
+
+    if let Some(column) = template
+        .strip_prefix("{{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+        .filter(|column| !column.contains("{{"))
+    {
+        return Ok(lookup(column.trim())?.clone());
+    }
+
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        match lookup(rest[start + 2..start + end].trim())? {
+            Value::String(s) => rendered.push_str(s),
+            Value::Null => {}
+            other => rendered.push_str(&other.to_json_string()),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(Value::String(rendered))
+}
+
+/// Storage, operations and evaluation of automation rules
+pub struct AutomationRules {
+    backend: Arc<RwLock<TursoBackend>>,
+    /// Routes the operations of fired rules
+    dispatcher: StdRwLock<Weak<OperationDispatcher>>,
+    /// Last seen values of the watched fields, by (table, id)
+    seen: Mutex<HashMap<(String, String), HashMap<String, Value>>>,
+}
+
+impl AutomationRules {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self {
+            backend,
+            dispatcher: StdRwLock::new(Weak::new()),
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set the dispatcher executing the actions of fired rules
+    ///
+    /// Only a weak reference is kept since the dispatcher owns the rules as a provider.
+    pub fn bind_dispatcher(&self, dispatcher: &Arc<OperationDispatcher>) {
+        *self.dispatcher.write().unwrap() = Arc::downgrade(dispatcher);
+    }
+
+    /// Initialize the automation_rules table.
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = AutomationRule::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create automation_rules table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        info!("Automation rules schema initialized");
+        Ok(())
+    }
+
+    pub async fn rules(&self) -> Result<Vec<AutomationRule>> {
+        self.query(
+            "SELECT * FROM automation_rules ORDER BY created_at",
+            HashMap::new(),
+        )
+        .await
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<AutomationRule>> {
+        Ok(self
+            .query(
+                "SELECT * FROM automation_rules WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+            )
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// Validate and store a rule (replacing the rule with the same ID)
+    pub async fn save(&self, rule: &AutomationRule) -> Result<()> {
+        rule.validate()?;
+        let sql = "INSERT INTO automation_rules
+                (id, name, entity_name, field, operator, value, actions, enabled, created_at)
+            VALUES ($id, $name, $entity_name, $field, $operator, $value, $actions, $enabled, $created_at)
+            ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                entity_name = excluded.entity_name,
+                field = excluded.field,
+                operator = excluded.operator,
+                value = excluded.value,
+                actions = excluded.actions,
+                enabled = excluded.enabled";
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(sql, rule.to_entity().fields)
+            .await
+            .map_err(|e| format!("Failed to save automation rule: {}", e))?;
+        Ok(())
+    }
+
+    /// Delete a rule, returning it
+    pub async fn delete(&self, id: &str) -> Result<Option<AutomationRule>> {
+        let Some(rule) = self.get(id).await? else {
+            return Ok(None);
+        };
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "DELETE FROM automation_rules WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+            )
+            .await
+            .map_err(|e| format!("Failed to delete automation rule: {}", e))?;
+        Ok(Some(rule))
+    }
+
+    /// Enable or disable a rule. Returns the previous value.
+    pub async fn set_enabled(&self, id: &str, enabled: bool) -> Result<bool> {
+        let mut rule = self.require(id).await?;
+        let previous = rule.enabled;
+        rule.enabled = enabled;
+        self.save(&rule).await?;
+        Ok(previous)
+    }
+
+    /// Change one of the `EDITABLE_FIELDS` of a rule. Returns the previous value.
+    pub async fn set_field(&self, id: &str, field: &str, value: &Value) -> Result<Value> {
+        let mut rule = self.require(id).await?;
+        if !EDITABLE_FIELDS.contains(&field) {
+            return Err(format!("Field {} of automation rules is not editable", field).into());
+        }
+        let mut fields = rule.to_entity().fields;
+        let previous = fields.insert(field.to_string(), value.clone());
+        let mut entity = DynamicEntity::new(AUTOMATION_RULES_ENTITY);
+        entity.fields = fields;
+        rule = AutomationRule::from_entity(entity)?;
+        self.save(&rule).await?;
+        Ok(previous.unwrap_or(Value::Null))
+    }
+
+    /// Apply a CDC batch of one of the watched tables, executing the actions of fired rules
+    ///
+    /// Returns the number of executed actions. Failing actions are logged and skipped.
+    pub async fn apply_batch(&self, batch: &BatchWithMetadata<RowChange>) -> Result<usize> {
+        let Some(table) = batch
+            .metadata
+            .relation_name
+            .strip_prefix(SOURCE_VIEW_PREFIX)
+        else {
+            return Ok(0);
+        };
+        let rules: Vec<AutomationRule> = self
+            .rules()
+            .await?
+            .into_iter()
+            .filter(|rule| rule.enabled && rule.entity_name == table)
+            .collect();
+
+        let mut executed = 0;
+        for row_change in &batch.inner.items {
+            let Some(id) = content_source::changed_entity_id(&row_change.change) else {
+                continue;
+            };
+            let id = id.to_string();
+            let (row, origin) = match &row_change.change {
+                ChangeData::Created { data, origin } | ChangeData::Updated { data, origin, .. } => {
+                    (data.clone(), origin)
+                }
+                ChangeData::ColumnChange { origin, .. } => {
+                    let Some(row) = self.load_row(table, &id).await? else {
+                        continue;
+                    };
+                    (row, origin)
+                }
+                ChangeData::Deleted { .. } => {
+                    self.seen.lock().await.remove(&(table.to_string(), id));
+                    continue;
+                }
+            };
+
+            let previous = self.remember(table, &id, &rules, &row).await;
+            let by_rule = origin
+                .source()
+                .and_then(|source| source.agent.as_deref())
+                .is_some_and(|agent| agent.starts_with(RULE_AGENT_PREFIX));
+            if by_rule {
+                continue;
+            }
+
+            for rule in &rules {
+                let new = row.get(&rule.field).unwrap_or(&Value::Null);
+                let old = previous
+                    .as_ref()
+                    .map(|previous| previous.get(&rule.field).unwrap_or(&Value::Null));
+                if rule.fires(old, new) {
+                    executed += self.execute_actions(rule, &row).await;
+                }
+            }
+        }
+        Ok(executed)
+    }
+
+    /// Follow the watched tables and execute fired rules in the background
+    ///
+    /// The watched tables are re-subscribed whenever edits to the rules change them.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self: Arc<Self>) {
+        use std::ops::ControlFlow;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        tokio::spawn(async move {
+            loop {
+                let tables = match self.watched_tables().await {
+                    Ok(tables) => tables,
+                    Err(e) => {
+                        warn!("[AutomationRules] Failed to load rules: {}", e);
+                        return;
+                    }
+                };
+                let tables_changed = AtomicBool::new(false);
+                let watched = async {
+                    let views = self.watch(&tables).await?;
+                    info!("[AutomationRules] Watching {:?}", tables);
+                    let (this, tables, tables_changed) = (&self, &tables, &tables_changed);
+                    content_source::follow_views(&self.backend, &views, move |batch| {
+                        let rules = this.clone();
+                        async move {
+                            if batch.metadata.relation_name == RULES_VIEW {
+                                match rules.watched_tables().await {
+                                    Ok(new_tables) if new_tables != *tables => {
+                                        // Subscribe again to the new set of tables
+                                        tables_changed.store(true, Ordering::Relaxed);
+                                        return ControlFlow::Break(());
+                                    }
+                                    Ok(_) => {}
+                                    Err(e) => {
+                                        warn!("[AutomationRules] Failed to load rules: {}", e)
+                                    }
+                                }
+                            } else if let Err(e) = rules.apply_batch(&batch).await {
+                                warn!("[AutomationRules] Failed to evaluate rules: {}", e);
+                            }
+                            ControlFlow::Continue(())
+                        }
+                    })
+                    .await
+                };
+                if let Err(e) = watched.await {
+                    warn!("[AutomationRules] Failed to watch tables: {}", e);
+                    return;
+                }
+                if !tables_changed.load(Ordering::Relaxed) {
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Existing tables watched by enabled rules
+    async fn watched_tables(&self) -> Result<BTreeSet<String>> {
+        let existing = {
+            let backend = self.backend.read().await;
+            content_source::existing_tables(&backend).await?
+        };
+        Ok(self
+            .rules()
+            .await?
+            .into_iter()
+            .filter(|rule| rule.enabled && existing.contains(&rule.entity_name))
+            .map(|rule| rule.entity_name)
+            .collect())
+    }
+
+    /// Record the current values of the watched tables, returning the materialized views
+    /// `(name, select_sql)` over them and over the rules
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn watch(&self, tables: &BTreeSet<String>) -> Result<Vec<(String, String)>> {
+        let rules = self.rules().await?;
+        let mut seen = HashMap::new();
+        {
+            let backend = self.backend.read().await;
+            for table in tables {
+                let fields: BTreeSet<&str> = rules
+                    .iter()
+                    .filter(|rule| rule.entity_name == *table)
+                    .map(|rule| rule.field.as_str())
+                    .collect();
+                let rows = backend
+                    .execute_sql(&format!("SELECT * FROM {}", table), HashMap::new())
+                    .await
+                    .map_err(|e| format!("Failed to read {}: {}", table, e))?;
+                for mut row in rows {
+                    let Some(id) = row.get("id").and_then(|v| v.as_string_owned()) else {
+                        continue;
+                    };
+                    row.retain(|column, _| fields.contains(column.as_str()));
+                    seen.insert((table.clone(), id), row);
+                }
+            }
+        }
+        *self.seen.lock().await = seen;
+
+        let mut views: Vec<(String, String)> = tables
+            .iter()
+            .map(|table| {
+                (
+                    format!("{}{}", SOURCE_VIEW_PREFIX, table),
+                    format!("SELECT * FROM {}", table),
+                )
+            })
+            .collect();
+        views.push((
+            RULES_VIEW.to_string(),
+            format!("SELECT * FROM {}", AUTOMATION_RULES_ENTITY),
+        ));
+        Ok(views)
+    }
+
+    /// Remember the watched values of a row, returning the previously seen ones
+    ///
+    /// None if the row wasn't seen before (it is new).
+    async fn remember(
+        &self,
+        table: &str,
+        id: &str,
+        rules: &[AutomationRule],
+        row: &HashMap<String, Value>,
+    ) -> Option<HashMap<String, Value>> {
+        let values: HashMap<String, Value> = rules
+            .iter()
+            .map(|rule| {
+                (
+                    rule.field.clone(),
+                    row.get(&rule.field).cloned().unwrap_or(Value::Null),
+                )
+            })
+            .collect();
+        self.seen
+            .lock()
+            .await
+            .insert((table.to_string(), id.to_string()), values)
+    }
+
+    /// Execute the actions of a fired rule, returning how many succeeded
+    async fn execute_actions(&self, rule: &AutomationRule, row: &HashMap<String, Value>) -> usize {
+        let Some(dispatcher) = self.dispatcher.read().unwrap().upgrade() else {
+            warn!(
+                "[AutomationRules] Rule {} fired, but rules are not bound to a dispatcher",
+                rule.id
+            );
+            return 0;
+        };
+        let actions = match rule.parsed_actions() {
+            Ok(actions) => actions,
+            Err(e) => {
+                warn!("[AutomationRules] {}", e);
+                return 0;
+            }
+        };
+
+        let source =
+            ChangeSource::default().with_agent(format!("{}{}", RULE_AGENT_PREFIX, rule.id));
+        let mut executed = 0;
+        for action in actions {
+            let result = match action.render(row) {
+                Ok(params) => CURRENT_CHANGE_SOURCE
+                    .scope(
+                        source.clone(),
+                        dispatcher.execute_operation(&action.entity_name, &action.op_name, params),
+                    )
+                    .await
+                    .map(|_| ()),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(()) => executed += 1,
+                Err(e) => warn!(
+                    "[AutomationRules] Action {}.{} of rule {} failed: {}",
+                    action.entity_name, action.op_name, rule.id, e
+                ),
+            }
+        }
+        debug!("Rule {} executed {} actions", rule.id, executed);
+        executed
+    }
+
+    async fn load_row(&self, table: &str, id: &str) -> Result<Option<HashMap<String, Value>>> {
+        let backend = self.backend.read().await;
+        let select_sql = format!("SELECT * FROM {}", table);
+        Ok(content_source::load_view_row(&backend, &select_sql, id)
+            .await
+            .map_err(|e| format!("Failed to read {} {}: {}", table, id, e))?)
+    }
+
+    async fn require(&self, id: &str) -> Result<AutomationRule> {
+        self.get(id)
+            .await?
+            .ok_or_else(|| format!("Automation rule not found: {}", id).into())
+    }
+
+    async fn query(
+        &self,
+        sql: &str,
+        params: HashMap<String, Value>,
+    ) -> Result<Vec<AutomationRule>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to query automation rules: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(AUTOMATION_RULES_ENTITY);
+                entity.fields = row;
+                AutomationRule::from_entity(entity)
+            })
+            .collect()
+    }
+}
+
+fn rule_operation(
+    name: &str,
+    display_name: &str,
+    description: &str,
+    extra_params: Vec<OperationParam>,
+) -> OperationDescriptor {
+    let mut required_params = vec![string_param("id", "Rule ID")];
+    required_params.extend(extra_params);
+    OperationDescriptor {
+        entity_name: AUTOMATION_RULES_ENTITY.to_string(),
+        entity_short_name: "rule".to_string(),
+        id_column: "id".to_string(),
+        name: name.to_string(),
+        display_name: display_name.to_string(),
+        description: description.to_string(),
+        required_params,
+        affected_fields: vec![],
+        param_mappings: vec![],
+        precondition: None,
+        simulation: None,
+    }
+}
+
+fn string_param(name: &str, description: &str) -> OperationParam {
+    OperationParam {
+        name: name.to_string(),
+        type_hint: TypeHint::String,
+        description: description.to_string(),
+    }
+}
+
+fn rule_op(op_name: &str, display_name: &str, params: StorageEntity) -> Operation {
+    Operation::new(AUTOMATION_RULES_ENTITY, op_name, display_name, params)
+}
+
+fn id_params(id: &str) -> StorageEntity {
+    HashMap::from([("id".to_string(), Value::String(id.to_string()))])
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationProvider for AutomationRules {
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        vec![
+            rule_operation(
+                "create",
+                "Add rule",
+                "Execute operations when a field of an entity changes (value is optional JSON)",
+                vec![
+                    string_param("name", "Name of the rule"),
+                    string_param("entity_name", "Watched table, e.g. todoist_tasks"),
+                    string_param("field", "Watched column"),
+                    string_param("operator", "==, !=, <, <=, >, >=, contains or changed"),
+                    string_param(
+                        "actions",
+                        "JSON array of {entity_name, op_name, params}; {{column}} in params refers to the changed row",
+                    ),
+                ],
+            ),
+            rule_operation(
+                "set_field",
+                "Edit rule",
+                "Change the name, condition or actions of a rule",
+                vec![
+                    string_param("field", "Field to change"),
+                    string_param("value", "New value"),
+                ],
+            ),
+            rule_operation("enable", "Enable rule", "Let the rule fire again", vec![]),
+            rule_operation(
+                "disable",
+                "Disable rule",
+                "Keep the rule, but stop it from firing",
+                vec![],
+            ),
+            rule_operation("delete", "Delete rule", "Delete the rule", vec![]),
+        ]
+    }
+
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        params: StorageEntity,
+    ) -> Result<UndoAction> {
+        if entity_name != AUTOMATION_RULES_ENTITY {
+            return Err(format!(
+                "Expected entity_name '{}', got '{}'",
+                AUTOMATION_RULES_ENTITY, entity_name
+            )
+            .into());
+        }
+
+        let string = |name: &str| {
+            params
+                .get(name)
+                .and_then(|v| v.as_string())
+                .ok_or_else(|| format!("Missing '{}' parameter", name))
+        };
+
+        if op_name == "create" {
+            // Undoing a delete recreates the rule with its ID
+            let rule = AutomationRule {
+                id: params
+                    .get("id")
+                    .and_then(|v| v.as_string_owned())
+                    .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                name: string("name")?.to_string(),
+                entity_name: string("entity_name")?.to_string(),
+                field: string("field")?.to_string(),
+                operator: string("operator")?.to_string(),
+                value: params.get("value").and_then(|v| v.as_string_owned()),
+                actions: string("actions")?.to_string(),
+                enabled: params
+                    .get("enabled")
+                    .and_then(|v| v.as_bool().or_else(|| v.as_i64().map(|i| i != 0)))
+                    .unwrap_or(true),
+                created_at: chrono::Utc::now().timestamp_millis(),
+            };
+            self.save(&rule).await?;
+            return Ok(UndoAction::Undo(rule_op(
+                "delete",
+                "Delete rule",
+                id_params(&rule.id),
+            )));
+        }
+
+        let id = string("id")?;
+        match op_name {
+            "set_field" => {
+                let field = string("field")?;
+                let value = params.get("value").cloned().unwrap_or(Value::Null);
+                let previous = self.set_field(id, field, &value).await?;
+                let mut undo_params = id_params(id);
+                undo_params.insert("field".to_string(), Value::String(field.to_string()));
+                undo_params.insert("value".to_string(), previous);
+                Ok(UndoAction::Undo(rule_op(
+                    "set_field",
+                    "Edit rule",
+                    undo_params,
+                )))
+            }
+            "enable" | "disable" => {
+                let previous = self.set_enabled(id, op_name == "enable").await?;
+                let (undo_name, display_name) = if previous {
+                    ("enable", "Enable rule")
+                } else {
+                    ("disable", "Disable rule")
+                };
+                Ok(UndoAction::Undo(rule_op(
+                    undo_name,
+                    display_name,
+                    id_params(id),
+                )))
+            }
+            "delete" => {
+                let rule = self
+                    .delete(id)
+                    .await?
+                    .ok_or_else(|| format!("Automation rule not found: {}", id))?;
+                Ok(UndoAction::Undo(rule_op(
+                    "create",
+                    "Add rule",
+                    rule.to_entity().fields,
+                )))
+            }
+            _ => Err(format!("Unknown operation: {}", op_name).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+    use holon_api::ChangeOrigin;
+    use std::sync::Mutex as StdMutex;
+
+    /// Provider of `journal.append`, recording the params of every execution
+    struct JournalProvider {
+        entries: StdMutex<Vec<StorageEntity>>,
+    }
+
+    #[async_trait]
+    impl OperationProvider for JournalProvider {
+        fn operations(&self) -> Vec<OperationDescriptor> {
+            let mut operation = rule_operation("append", "Append", "", vec![]);
+            operation.entity_name = "journal".to_string();
+            vec![operation]
+        }
+
+        async fn execute_operation(
+            &self,
+            _entity_name: &str,
+            _op_name: &str,
+            params: StorageEntity,
+        ) -> Result<UndoAction> {
+            self.entries.lock().unwrap().push(params);
+            Ok(UndoAction::Irreversible)
+        }
+    }
+
+    fn batch(table: &str, changes: Vec<ChangeData>) -> BatchWithMetadata<RowChange> {
+        let relation_name = format!("{}{}", SOURCE_VIEW_PREFIX, table);
+        BatchWithMetadata {
+            inner: holon_api::Batch {
+                items: changes
+                    .into_iter()
+                    .map(|change| RowChange {
+                        relation_name: relation_name.clone(),
+                        change,
+                    })
+                    .collect(),
+            },
+            metadata: holon_api::BatchMetadata {
+                relation_name,
+                trace_context: None,
+                sync_token: None,
+                full_snapshot: false,
+            },
+        }
+    }
+
+    fn task(id: &str, status: &str, origin: ChangeOrigin) -> ChangeData {
+        ChangeData::Updated {
+            id: "1".to_string(),
+            data: HashMap::from([
+                ("id".to_string(), Value::String(id.to_string())),
+                ("content".to_string(), Value::String("Ship it".to_string())),
+                ("status".to_string(), Value::String(status.to_string())),
+            ]),
+            origin,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rule_fires_on_change_with_loop_protection() {
+        let rules = AutomationRules::new(memory_backend().await);
+        rules.initialize_schema().await.unwrap();
+        let journal = Arc::new(JournalProvider {
+            entries: StdMutex::new(Vec::new()),
+        });
+        let dispatcher = Arc::new(OperationDispatcher::new(vec![journal.clone()]));
+        rules.bind_dispatcher(&dispatcher);
+
+        let undo = rules
+            .execute_operation(
+                AUTOMATION_RULES_ENTITY,
+                "create",
+                HashMap::from([
+                    (
+                        "name".to_string(),
+                        Value::String("Log done tasks".to_string()),
+                    ),
+                    (
+                        "entity_name".to_string(),
+                        Value::String("tasks".to_string()),
+                    ),
+                    ("field".to_string(), Value::String("status".to_string())),
+                    ("operator".to_string(), Value::String("==".to_string())),
+                    ("value".to_string(), Value::String("\"done\"".to_string())),
+                    (
+                        "actions".to_string(),
+                        Value::String(
+                            r#"[{"entity_name": "journal", "op_name": "append",
+                                 "params": {"text": "Done: {{content}}", "task": "{{id}}"}}]"#
+                                .to_string(),
+                        ),
+                    ),
+                ]),
+            )
+            .await
+            .unwrap();
+        let UndoAction::Undo(delete) = undo else {
+            panic!("create must be undoable");
+        };
+        let id = delete.params["id"].as_string_owned().unwrap();
+
+        let user = ChangeOrigin::local_with_trace(None, None);
+        // New row that doesn't match, then the change to "done"
+        let fired = rules
+            .apply_batch(&batch(
+                "tasks",
+                vec![
+                    task("t1", "open", user.clone()),
+                    task("t1", "done", user.clone()),
+                ],
+            ))
+            .await
+            .unwrap();
+        assert_eq!(fired, 1);
+        assert_eq!(
+            journal.entries.lock().unwrap()[0]["text"],
+            Value::String("Done: Ship it".to_string())
+        );
+
+        // An echo of the same value doesn't fire again
+        let fired = rules
+            .apply_batch(&batch("tasks", vec![task("t1", "done", user.clone())]))
+            .await
+            .unwrap();
+        assert_eq!(fired, 0);
+
+        // Changes made by rules never trigger rules
+        let by_rule = ChangeOrigin::Local {
+            operation_id: None,
+            trace_id: None,
+            source: Some(ChangeSource::default().with_agent("rule:other")),
+        };
+        let fired = rules
+            .apply_batch(&batch(
+                "tasks",
+                vec![
+                    task("t2", "open", user.clone()),
+                    task("t2", "done", by_rule),
+                ],
+            ))
+            .await
+            .unwrap();
+        assert_eq!(fired, 0);
+
+        // Disabled rules don't fire
+        rules
+            .execute_operation(AUTOMATION_RULES_ENTITY, "disable", id_params(&id))
+            .await
+            .unwrap();
+        let fired = rules
+            .apply_batch(&batch(
+                "tasks",
+                vec![task("t3", "open", user.clone()), task("t3", "done", user)],
+            ))
+            .await
+            .unwrap();
+        assert_eq!(fired, 0);
+        assert_eq!(journal.entries.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_render_row_template() {
+        let row = HashMap::from([
+            ("id".to_string(), Value::String("t1".to_string())),
+            ("priority".to_string(), Value::Integer(4)),
+        ]);
+        assert_eq!(
+            render_row_template("{{priority}}", &row).unwrap(),
+            Value::Integer(4)
+        );
+        assert_eq!(
+            render_row_template("{{id}} has priority {{ priority }}", &row).unwrap(),
+            Value::String("t1 has priority 4".to_string())
+        );
+        assert!(render_row_template("{{due_date}}", &row).is_err());
+    }
+}
@@ -0,0 +1,484 @@
+//! User-defined fields on entities (e.g. "energy" or "context" on tasks)
+//!
+//! `CustomFieldStore` keeps `CustomFieldDefinition` rows in the
+//! `custom_field_definitions` table and their values, as JSON text, in the
+//! `custom_field_values` side table. Queries read a value with the `custom` PRQL
+//! function (`filter (custom this "energy") > 2`), which compiles into a
+//! `json_extract` subquery on that table.
+//!
+//! `CustomFieldProvider` exposes `define_custom_field`/`remove_custom_field` for
+//! one entity type; values are set through `set_custom_field` on the entity's
+//! cache (see `QueryableCache`).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::core::datasource::{
+    __operations_custom_field_operations, CustomFieldOperations, OperationProvider, Result,
+    UndoAction,
+};
+use crate::storage::turso::TursoBackend;
+use crate::storage::types::StorageEntity;
+use holon_api::{DynamicEntity, HasSchema, Operation, OperationDescriptor, Value};
+use holon_core::custom_field::is_valid_field_name;
+pub use holon_core::{CustomFieldDefinition, CustomFieldType, CustomFieldValue};
+
+/// Entity name of the custom field definitions table
+pub const CUSTOM_FIELD_DEFINITIONS_ENTITY: &str = "custom_field_definitions";
+
+/// Entity name of the custom field values table
+pub const CUSTOM_FIELD_VALUES_ENTITY: &str = "custom_field_values";
+
+/// Persistent custom field definitions and values backed by TursoBackend
+pub struct CustomFieldStore {
+    backend: Arc<RwLock<TursoBackend>>,
+}
+
+impl CustomFieldStore {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self { backend }
+    }
+
+    /// Initialize the custom_field_definitions and custom_field_values tables
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let backend = self.backend.read().await;
+        for schema in [CustomFieldDefinition::schema(), CustomFieldValue::schema()] {
+            backend
+                .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create {} table: {}", schema.table_name, e))?;
+
+            for index_sql in schema.to_index_sql() {
+                backend
+                    .execute_sql(&index_sql, HashMap::new())
+                    .await
+                    .map_err(|e| format!("Failed to create index: {}", e))?;
+            }
+        }
+
+        info!("Custom fields schema initialized");
+        Ok(())
+    }
+
+    /// Add field `name` of `field_type` to `entity_name`
+    pub async fn define_field(
+        &self,
+        entity_name: &str,
+        name: &str,
+        field_type: CustomFieldType,
+        now: i64,
+    ) -> Result<CustomFieldDefinition> {
+        if !is_valid_field_name(name) {
+            return Err(format!(
+                "Invalid custom field name '{}': use letters, digits and '_'",
+                name
+            )
+            .into());
+        }
+        if self.definition(entity_name, name).await?.is_some() {
+            return Err(format!("Field {} of {} is already defined", name, entity_name).into());
+        }
+
+        let definition = CustomFieldDefinition::new(entity_name, name, field_type, now);
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "INSERT INTO custom_field_definitions (id, entity_name, name, field_type, created_at)
+                VALUES ($id, $entity_name, $name, $field_type, $created_at)",
+                definition.to_entity().fields,
+            )
+            .await
+            .map_err(|e| format!("Failed to save custom field definition: {}", e))?;
+        debug!("Defined custom field {}", definition.id);
+        Ok(definition)
+    }
+
+    /// Remove field `name` of `entity_name`, together with its values
+    pub async fn remove_field(
+        &self,
+        entity_name: &str,
+        name: &str,
+    ) -> Result<CustomFieldDefinition> {
+        let definition = self
+            .definition(entity_name, name)
+            .await?
+            .ok_or_else(|| format!("Field {} of {} is not defined", name, entity_name))?;
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "DELETE FROM custom_field_values WHERE entity_name = $entity_name AND field = $field",
+                HashMap::from([
+                    (
+                        "entity_name".to_string(),
+                        Value::String(entity_name.to_string()),
+                    ),
+                    ("field".to_string(), Value::String(name.to_string())),
+                ]),
+            )
+            .await
+            .map_err(|e| format!("Failed to delete custom field values: {}", e))?;
+        backend
+            .execute_sql(
+                "DELETE FROM custom_field_definitions WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(definition.id.clone()))]),
+            )
+            .await
+            .map_err(|e| format!("Failed to delete custom field definition: {}", e))?;
+        debug!("Removed custom field {}", definition.id);
+        Ok(definition)
+    }
+
+    /// Fields defined for `entity_name`, oldest first
+    pub async fn definitions(&self, entity_name: &str) -> Result<Vec<CustomFieldDefinition>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT * FROM custom_field_definitions WHERE entity_name = $entity_name ORDER BY created_at",
+                HashMap::from([(
+                    "entity_name".to_string(),
+                    Value::String(entity_name.to_string()),
+                )]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query custom field definitions: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(CUSTOM_FIELD_DEFINITIONS_ENTITY);
+                entity.fields = row;
+                CustomFieldDefinition::from_entity(entity)
+            })
+            .collect()
+    }
+
+    /// Definition of field `name` of `entity_name`, if any
+    pub async fn definition(
+        &self,
+        entity_name: &str,
+        name: &str,
+    ) -> Result<Option<CustomFieldDefinition>> {
+        Ok(self
+            .definitions(entity_name)
+            .await?
+            .into_iter()
+            .find(|definition| definition.name == name))
+    }
+
+    /// Set field `field` of entity `entity_id`; `Value::Null` clears it
+    ///
+    /// Fails if the field isn't defined or `value` isn't of its type. Returns the
+    /// previous value (`Value::Null` if the field wasn't set).
+    pub async fn set_value(
+        &self,
+        entity_name: &str,
+        entity_id: &str,
+        field: &str,
+        value: &Value,
+        now: i64,
+    ) -> Result<Value> {
+        let definition = self
+            .definition(entity_name, field)
+            .await?
+            .ok_or_else(|| format!("Field {} of {} is not defined", field, entity_name))?;
+        let field_type = definition.field_type()?;
+        let previous = self.get_value(entity_name, entity_id, field).await?;
+
+        let backend = self.backend.read().await;
+        if value.is_null() {
+            backend
+                .execute_sql(
+                    "DELETE FROM custom_field_values WHERE id = $id",
+                    HashMap::from([(
+                        "id".to_string(),
+                        Value::String(CustomFieldValue::key(entity_name, entity_id, field)),
+                    )]),
+                )
+                .await
+                .map_err(|e| format!("Failed to clear custom field: {}", e))?;
+        } else {
+            let json = field_type.encode(value)?;
+            let stored = CustomFieldValue::new(entity_name, entity_id, field, &json, now);
+            backend
+                .execute_sql(
+                    "INSERT INTO custom_field_values (id, entity_name, entity_id, field, value, updated_at)
+                    VALUES ($id, $entity_name, $entity_id, $field, $value, $updated_at)
+                    ON CONFLICT(id) DO UPDATE SET
+                        value = excluded.value,
+                        updated_at = excluded.updated_at",
+                    stored.to_entity().fields,
+                )
+                .await
+                .map_err(|e| format!("Failed to save custom field: {}", e))?;
+        }
+        debug!("Set {} of {} to {:?}", field, entity_id, value);
+        Ok(previous)
+    }
+
+    /// Value of field `field` of entity `entity_id` (`Value::Null` if not set)
+    pub async fn get_value(
+        &self,
+        entity_name: &str,
+        entity_id: &str,
+        field: &str,
+    ) -> Result<Value> {
+        let Some(definition) = self.definition(entity_name, field).await? else {
+            return Ok(Value::Null);
+        };
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT value FROM custom_field_values WHERE id = $id",
+                HashMap::from([(
+                    "id".to_string(),
+                    Value::String(CustomFieldValue::key(entity_name, entity_id, field)),
+                )]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query custom field: {}", e))?;
+
+        let json = rows
+            .first()
+            .and_then(|row| row.get("value"))
+            .and_then(|value| value.as_string());
+        Ok(match json {
+            Some(json) => definition.field_type()?.decode(json),
+            None => Value::Null,
+        })
+    }
+}
+
+/// `define_custom_field`/`remove_custom_field` operations for one entity type
+pub struct CustomFieldProvider {
+    store: Arc<CustomFieldStore>,
+    entity_name: String,
+    short_name: String,
+}
+
+impl CustomFieldProvider {
+    pub fn new(
+        store: Arc<CustomFieldStore>,
+        entity_name: impl Into<String>,
+        short_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            store,
+            entity_name: entity_name.into(),
+            short_name: short_name.into(),
+        }
+    }
+
+    fn inverse(&self, op_name: &str, display_name: &str, params: StorageEntity) -> UndoAction {
+        UndoAction::Undo(Operation::new(
+            &self.entity_name,
+            op_name,
+            display_name,
+            params,
+        ))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl CustomFieldOperations for CustomFieldProvider {
+    async fn define_custom_field(&self, name: &str, field_type: &str) -> Result<UndoAction> {
+        let field_type = CustomFieldType::parse(field_type)?;
+        let now = chrono::Utc::now().timestamp_millis();
+        self.store
+            .define_field(&self.entity_name, name, field_type, now)
+            .await?;
+        Ok(self.inverse(
+            "remove_custom_field",
+            "Remove custom field",
+            HashMap::from([("name".to_string(), Value::String(name.to_string()))]),
+        ))
+    }
+
+    // Removing a field also drops its values, which the undo doesn't bring back
+    async fn remove_custom_field(&self, name: &str) -> Result<UndoAction> {
+        let definition = self.store.remove_field(&self.entity_name, name).await?;
+        Ok(self.inverse(
+            "define_custom_field",
+            "Define custom field",
+            HashMap::from([
+                ("name".to_string(), Value::String(name.to_string())),
+                (
+                    "field_type".to_string(),
+                    Value::String(definition.field_type),
+                ),
+            ]),
+        ))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationProvider for CustomFieldProvider {
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        __operations_custom_field_operations::custom_field_operations(
+            &self.entity_name,
+            &self.short_name,
+            &self.entity_name,
+            "id",
+        )
+    }
+
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        params: StorageEntity,
+    ) -> Result<UndoAction> {
+        if entity_name != self.entity_name {
+            return Err(format!(
+                "Expected entity_name '{}', got '{}'",
+                self.entity_name, entity_name
+            )
+            .into());
+        }
+        __operations_custom_field_operations::dispatch_operation(self, op_name, &params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    async fn create_store() -> Arc<CustomFieldStore> {
+        let store = CustomFieldStore::new(memory_backend().await);
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        Arc::new(store)
+    }
+
+    #[tokio::test]
+    async fn test_values_are_typed() {
+        let store = create_store().await;
+        let provider = CustomFieldProvider::new(store.clone(), "todoist_tasks", "task");
+        provider
+            .define_custom_field("energy", "integer")
+            .await
+            .unwrap();
+        assert!(
+            provider
+                .define_custom_field("energy", "string")
+                .await
+                .is_err()
+        );
+        assert!(provider.define_custom_field("mood", "color").await.is_err());
+        assert!(provider.define_custom_field("a b", "string").await.is_err());
+
+        let previous = store
+            .set_value("todoist_tasks", "t1", "energy", &Value::Integer(3), 0)
+            .await
+            .unwrap();
+        assert_eq!(previous, Value::Null);
+        assert!(
+            store
+                .set_value(
+                    "todoist_tasks",
+                    "t1",
+                    "energy",
+                    &Value::String("high".to_string()),
+                    0
+                )
+                .await
+                .is_err()
+        );
+        assert!(
+            store
+                .set_value("todoist_tasks", "t1", "context", &Value::Integer(1), 0)
+                .await
+                .is_err()
+        );
+
+        let previous = store
+            .set_value("todoist_tasks", "t1", "energy", &Value::Integer(5), 1)
+            .await
+            .unwrap();
+        assert_eq!(previous, Value::Integer(3));
+        assert_eq!(
+            store
+                .get_value("todoist_tasks", "t1", "energy")
+                .await
+                .unwrap(),
+            Value::Integer(5)
+        );
+
+        // Stored as JSON so queries can read it with json_extract
+        let rows = store
+            .backend
+            .read()
+            .await
+            .execute_sql(
+                "SELECT json_extract(value, '$') AS energy FROM custom_field_values WHERE entity_id = 't1'",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows[0].get("energy"), Some(&Value::Integer(5)));
+
+        store
+            .set_value("todoist_tasks", "t1", "energy", &Value::Null, 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            store
+                .get_value("todoist_tasks", "t1", "energy")
+                .await
+                .unwrap(),
+            Value::Null
+        );
+    }
+
+    #[tokio::test]
+    async fn test_removing_field_drops_values() {
+        let store = create_store().await;
+        let provider = CustomFieldProvider::new(store.clone(), "todoist_tasks", "task");
+        provider
+            .define_custom_field("context", "string")
+            .await
+            .unwrap();
+        store
+            .set_value(
+                "todoist_tasks",
+                "t1",
+                "context",
+                &Value::String("home".to_string()),
+                0,
+            )
+            .await
+            .unwrap();
+
+        let undo = provider.remove_custom_field("context").await.unwrap();
+        match undo {
+            UndoAction::Undo(op) => {
+                assert_eq!(op.op_name, "define_custom_field");
+                assert_eq!(
+                    op.params.get("field_type"),
+                    Some(&Value::String("string".to_string()))
+                );
+            }
+            other => panic!("Expected an undo operation, got {:?}", other),
+        }
+        assert!(store.definitions("todoist_tasks").await.unwrap().is_empty());
+
+        provider
+            .define_custom_field("context", "string")
+            .await
+            .unwrap();
+        assert_eq!(
+            store
+                .get_value("todoist_tasks", "t1", "context")
+                .await
+                .unwrap(),
+            Value::Null
+        );
+    }
+}
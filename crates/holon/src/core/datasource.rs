@@ -21,28 +21,38 @@ use holon_api::Value;
 
 // Re-export core traits from holon-core
 pub use holon_core::{
-    BlockDataSourceHelpers, BlockEntity, BlockOperations, CrudOperations, DataSource,
-    MaybeSendSync, MoveOperations, OperationRegistry, RenameOperations, Result, TaskEntity,
-    TaskOperations, UndoAction, UnknownOperationError,
+    AttachmentOperations, BlockDataSourceHelpers, BlockEntity, BlockOperations, CrudOperations,
+    CustomFieldOperations, DataSource, DeletePolicy, DependencyOperations, HolonError, HolonResult,
+    MaybeSendSync, MoveOperations, OperationProvider, OperationRegistry, RenameOperations, Result,
+    TaskEntity, TaskOperations, TimeTrackingOperations, UndoAction, UnknownOperationError,
 };
 
+// Re-export typed operation clients
+pub use holon_core::{
+    AttachmentOperationsClient, BlockOperationsClient, CrudOperationsClient,
+    CustomFieldOperationsClient, DependencyOperationsClient, MoveOperationsClient,
+    RenameOperationsClient, TaskOperationsClient, TimeTrackingOperationsClient,
+};
+
+// Re-export ID generation for datasource configuration
+pub use holon_core::{IdGenerator, IdStrategy, TempIdMap, default_id_generator};
+
 // Re-export undo types for external crates
 pub use holon_api::Operation;
 pub use holon_core::undo::UndoStack;
 
 // Re-export macro-generated operation dispatch functions from holon-core
-#[cfg(not(target_arch = "wasm32"))]
 pub use holon_core::{
-    __operations_block_operations, __operations_crud_operations, __operations_move_operations,
+    __operations_attachment_operations, __operations_block_operations,
+    __operations_crud_operations, __operations_custom_field_operations,
+    __operations_dependency_operations, __operations_move_operations,
     __operations_rename_operations, __operations_task_operations,
+    __operations_time_tracking_operations,
 };
 
 // Backwards compatibility aliases for old module names
-#[cfg(not(target_arch = "wasm32"))]
 pub use __operations_block_operations as __operations_mutable_block_data_source;
-#[cfg(not(target_arch = "wasm32"))]
 pub use __operations_crud_operations as __operations_crud_operation_provider;
-#[cfg(not(target_arch = "wasm32"))]
 pub use __operations_task_operations as __operations_mutable_task_data_source;
 
 // Re-export OperationDescriptor and OperationParam from holon-api
@@ -68,119 +78,14 @@ pub struct ParamDescriptor {
 // All traits (DataSource, BlockDataSourceHelpers, BlockOperations, TaskOperations)
 // and their blanket implementations are now defined in holon-core and re-exported above.
 
-// Note: BlockOperations is defined in holon-core, so we can't provide blanket impls here.
-// Types that implement BlockDataSourceHelpers<T> should manually implement BlockOperations<T>
-// using the helper functions below, or use the provided implementations via a wrapper type.
-//
-// For convenience, we provide a helper module with default implementations that can be used
-// by types implementing BlockDataSourceHelpers<T>.
-pub mod mutable_block_impls {
-    use super::*;
-
-    // Helper functions that can be used by BlockOperations implementations
-    // These are kept here for reference, but types should implement BlockOperations
-    // methods directly using BlockDataSourceHelpers methods.
-}
+// BlockOperations (indent, outdent, move_up, move_down, ...) is derived by blanket impls
+// in holon-core for every datasource implementing CrudOperations<T> and DataSource<T>
+// with T: BlockEntity, so datasources only implement those primitives.
 
 // All trait implementations are now in holon-core.
 
 // TaskOperations has default implementations in holon-core, so no blanket impl needed here.
 
-/// Type-independent operation provider trait
-///
-/// Supports both local (cache-based) and external (API-based) providers.
-/// Operations are self-describing via OperationDescriptor metadata.
-///
-/// # Design
-/// - **OperationProvider = QueryableCache + dispatch layer**: Routes `execute_operation` to
-///   CRUD operations (create/set_field/delete) or custom operations
-/// - **Composite dispatcher pattern**: OperationDispatcher itself implements OperationProvider,
-///   allowing composition
-/// - **Provider registry discovery**: Providers expose introspection via `operations()`,
-///   allowing OperationDispatcher to discover all providers at runtime via DI
-///
-/// # Examples
-/// ```ignore
-/// // Individual cache:
-/// cache.execute_operation("todoist-task", "set_completion", params).await?;
-///
-/// // Composite dispatcher:
-/// dispatcher.execute_operation("todoist-task", "set_completion", params).await?;
-/// // → Routes to TodoistQueryableCache → set_field("completed", true)
-/// ```
-#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
-#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
-pub trait OperationProvider: Send + Sync {
-    /// Get all operations this provider supports
-    fn operations(&self) -> Vec<OperationDescriptor>;
-
-    /// Find operations that can be executed with given arguments
-    ///
-    /// Filters to operations where required_params can be satisfied either:
-    /// 1. Directly from available_args, OR
-    /// 2. Via param_mappings (from other widgets like drop targets)
-    ///
-    /// Example:
-    /// ```
-    /// // Lineage: checkbox modifies "completed" field
-    /// // Available: ["id", "completed"]
-    /// let ops = provider.find_operations("todoist-task", &["id", "completed"]);
-    /// // Returns: ["set_field", "set_completion", "delete"]
-    /// // Also returns: ["move_block"] if it has param_mappings for parent_id
-    /// ```
-    fn find_operations(
-        &self,
-        entity_name: &str,
-        available_args: &[String],
-    ) -> Vec<OperationDescriptor> {
-        self.operations()
-            .into_iter()
-            .filter(|op| {
-                if op.entity_name != entity_name {
-                    return false;
-                }
-
-                // Check each required param
-                op.required_params.iter().all(|p| {
-                    // Param is directly available
-                    if available_args.contains(&p.name) {
-                        return true;
-                    }
-
-                    // Param can be provided via a param_mapping
-                    // (from another widget like drop target)
-                    op.param_mappings
-                        .iter()
-                        .any(|mapping| mapping.provides.contains(&p.name))
-                })
-            })
-            .collect()
-    }
-
-    /// Execute an operation
-    ///
-    /// - Individual caches: validate entity_name, dispatch to trait methods
-    /// - Composite dispatcher: route to correct registered provider
-    ///
-    /// Returns the UndoAction for undo support.
-    async fn execute_operation(
-        &self,
-        entity_name: &str,
-        op_name: &str,
-        params: StorageEntity,
-    ) -> Result<UndoAction>;
-
-    /// Get the last created entity ID (if any)
-    ///
-    /// This is used by GenericProviderState to track entity creation.
-    /// Providers that support this should override this method to return
-    /// the ID of the last entity created via execute_operation.
-    /// Default implementation returns None.
-    fn get_last_created_id(&self) -> Option<String> {
-        None
-    }
-}
-
 /// Observer for operation execution events
 ///
 /// Observers are notified after an operation is successfully executed.
@@ -307,5 +212,6 @@ pub fn generate_sync_operation(provider_name: &str) -> OperationDescriptor {
         affected_fields: vec![], // Sync operations don't affect specific fields
         param_mappings: vec![],
         precondition: None,
+        simulation: None,
     }
 }
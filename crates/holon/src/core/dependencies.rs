@@ -0,0 +1,597 @@
+//! Dependencies between tasks (blocks / blocked-by)
+//!
+//! `DependencyStore` keeps `TaskDependency` rows in the `task_dependencies` table
+//! and the `blocked_tasks` view on top of it, so "what can't I work on yet" is a
+//! plain PRQL query (`from blocked_tasks`). Each row remembers whether its blocker
+//! is completed; the store observes completion operations of all entities to keep
+//! that flag current, and announces tasks whose last open blocker was completed
+//! (see `subscribe`).
+//!
+//! `DependencyProvider` exposes `add_dependency`/`remove_dependency` for one task
+//! entity type (e.g. `todoist_tasks`). Both tasks of a dependency belong to that
+//! entity type.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::{RwLock, broadcast};
+use tracing::{debug, info, warn};
+
+use crate::core::datasource::{
+    __operations_dependency_operations, DependencyOperations, OperationObserver, OperationProvider,
+    Result, UndoAction,
+};
+use crate::storage::turso::TursoBackend;
+use crate::storage::types::StorageEntity;
+use holon_api::{DynamicEntity, HasSchema, Operation, OperationDescriptor, Value};
+pub use holon_core::TaskDependency;
+
+/// Entity name of the dependencies table
+pub const TASK_DEPENDENCIES_ENTITY: &str = "task_dependencies";
+
+/// View of the tasks with open blockers: `entity_name`, `id`, `open_blockers`
+pub const BLOCKED_TASKS_VIEW: &str = "blocked_tasks";
+
+const BLOCKED_TASKS_VIEW_SQL: &str = "CREATE VIEW IF NOT EXISTS blocked_tasks AS
+    SELECT entity_name, blocks_id AS id, COUNT(*) AS open_blockers
+    FROM task_dependencies
+    WHERE blocker_completed = 0
+    GROUP BY entity_name, blocks_id";
+
+/// A task whose last open blocker was just completed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskActionable {
+    pub entity_name: String,
+    pub task_id: String,
+}
+
+/// Persistent task dependencies backed by TursoBackend
+pub struct DependencyStore {
+    backend: Arc<RwLock<TursoBackend>>,
+    actionable: broadcast::Sender<TaskActionable>,
+}
+
+impl DependencyStore {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        let (actionable, _) = broadcast::channel(64);
+        Self {
+            backend,
+            actionable,
+        }
+    }
+
+    /// Initialize the task_dependencies table and the blocked_tasks view
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = TaskDependency::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create task_dependencies table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        backend
+            .execute_sql(BLOCKED_TASKS_VIEW_SQL, HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create blocked_tasks view: {}", e))?;
+
+        info!("Task dependencies schema initialized");
+        Ok(())
+    }
+
+    /// Make `task_id` block `blocks_id`
+    ///
+    /// Fails if the dependency exists or would close a cycle (`blocks_id` already
+    /// blocks `task_id`, directly or through other tasks).
+    pub async fn add_dependency(
+        &self,
+        entity_name: &str,
+        task_id: &str,
+        blocks_id: &str,
+        now: i64,
+    ) -> Result<TaskDependency> {
+        if task_id == blocks_id {
+            return Err(format!("Task {} can't block itself", task_id).into());
+        }
+        if self.get(entity_name, task_id, blocks_id).await?.is_some() {
+            return Err(format!("Task {} already blocks {}", task_id, blocks_id).into());
+        }
+        if let Some(path) = self
+            .dependency_path(entity_name, blocks_id, task_id)
+            .await?
+        {
+            return Err(format!(
+                "Dependency would create a cycle: {} blocks {}",
+                path.join(" → "),
+                blocks_id
+            )
+            .into());
+        }
+
+        let completed = self.is_completed(entity_name, task_id).await;
+        let dependency = TaskDependency::new(entity_name, task_id, blocks_id, completed, now);
+        self.save(&dependency).await?;
+        debug!("Added dependency {}", dependency.id);
+        Ok(dependency)
+    }
+
+    /// Stop `task_id` from blocking `blocks_id`
+    pub async fn remove_dependency(
+        &self,
+        entity_name: &str,
+        task_id: &str,
+        blocks_id: &str,
+    ) -> Result<TaskDependency> {
+        let dependency = self
+            .get(entity_name, task_id, blocks_id)
+            .await?
+            .ok_or_else(|| format!("Task {} does not block {}", task_id, blocks_id))?;
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "DELETE FROM task_dependencies WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(dependency.id.clone()))]),
+            )
+            .await
+            .map_err(|e| format!("Failed to delete dependency: {}", e))?;
+        debug!("Removed dependency {}", dependency.id);
+        Ok(dependency)
+    }
+
+    /// The dependency of `blocks_id` on `task_id`, if any
+    pub async fn get(
+        &self,
+        entity_name: &str,
+        task_id: &str,
+        blocks_id: &str,
+    ) -> Result<Option<TaskDependency>> {
+        let id = TaskDependency::key(entity_name, task_id, blocks_id);
+        Ok(self
+            .query(
+                "SELECT * FROM task_dependencies WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(id))]),
+            )
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// Dependencies on the tasks blocking `task_id`
+    pub async fn blocked_by(
+        &self,
+        entity_name: &str,
+        task_id: &str,
+    ) -> Result<Vec<TaskDependency>> {
+        self.query(
+            "SELECT * FROM task_dependencies WHERE entity_name = $entity_name AND blocks_id = $task_id ORDER BY created_at",
+            task_params(entity_name, task_id),
+        )
+        .await
+    }
+
+    /// Dependencies of the tasks `task_id` blocks
+    pub async fn blocks(&self, entity_name: &str, task_id: &str) -> Result<Vec<TaskDependency>> {
+        self.query(
+            "SELECT * FROM task_dependencies WHERE entity_name = $entity_name AND task_id = $task_id ORDER BY created_at",
+            task_params(entity_name, task_id),
+        )
+        .await
+    }
+
+    /// Whether `task_id` has blockers that aren't completed
+    pub async fn is_blocked(&self, entity_name: &str, task_id: &str) -> Result<bool> {
+        Ok(self
+            .blocked_by(entity_name, task_id)
+            .await?
+            .iter()
+            .any(|dependency| !dependency.blocker_completed))
+    }
+
+    /// Record that `task_id` was completed (or reopened)
+    ///
+    /// Returns the tasks it blocked that have no open blockers left; they are
+    /// also sent to subscribers.
+    pub async fn set_completed(
+        &self,
+        entity_name: &str,
+        task_id: &str,
+        completed: bool,
+    ) -> Result<Vec<TaskActionable>> {
+        let mut actionable = Vec::new();
+        for mut dependency in self.blocks(entity_name, task_id).await? {
+            if dependency.blocker_completed == completed {
+                continue;
+            }
+            dependency.blocker_completed = completed;
+            self.save(&dependency).await?;
+            if completed && !self.is_blocked(entity_name, &dependency.blocks_id).await? {
+                actionable.push(TaskActionable {
+                    entity_name: entity_name.to_string(),
+                    task_id: dependency.blocks_id,
+                });
+            }
+        }
+        for task in &actionable {
+            debug!("Task {} is now actionable", task.task_id);
+            // No receivers is fine: nobody is listening right now
+            let _ = self.actionable.send(task.clone());
+        }
+        Ok(actionable)
+    }
+
+    /// Tasks that become actionable from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<TaskActionable> {
+        self.actionable.subscribe()
+    }
+
+    /// Chain of tasks through which `from` blocks `to`, if it does
+    async fn dependency_path(
+        &self,
+        entity_name: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Option<Vec<String>>> {
+        let mut previous: HashMap<String, String> = HashMap::new();
+        let mut seen = HashSet::from([from.to_string()]);
+        let mut queue = VecDeque::from([from.to_string()]);
+        while let Some(task_id) = queue.pop_front() {
+            if task_id == to {
+                let mut path = vec![task_id];
+                while let Some(before) = previous.get(path.last().unwrap()) {
+                    path.push(before.clone());
+                }
+                path.reverse();
+                return Ok(Some(path));
+            }
+            for dependency in self.blocks(entity_name, &task_id).await? {
+                if seen.insert(dependency.blocks_id.clone()) {
+                    previous.insert(dependency.blocks_id.clone(), task_id.clone());
+                    queue.push_back(dependency.blocks_id);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Current `completed` column of a task; false if the entity has none
+    async fn is_completed(&self, entity_name: &str, task_id: &str) -> bool {
+        if !entity_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return false;
+        }
+        let sql = format!("SELECT completed FROM {} WHERE id = $id", entity_name);
+        let backend = self.backend.read().await;
+        match backend
+            .execute_sql(
+                &sql,
+                HashMap::from([("id".to_string(), Value::String(task_id.to_string()))]),
+            )
+            .await
+        {
+            Ok(rows) => rows
+                .first()
+                .and_then(|row| row.get("completed"))
+                .and_then(completed_value)
+                .unwrap_or(false),
+            Err(e) => {
+                debug!("Can't tell whether {} is completed: {}", task_id, e);
+                false
+            }
+        }
+    }
+
+    async fn save(&self, dependency: &TaskDependency) -> Result<()> {
+        let sql = "INSERT INTO task_dependencies
+                (id, entity_name, task_id, blocks_id, blocker_completed, created_at)
+            VALUES ($id, $entity_name, $task_id, $blocks_id, $blocker_completed, $created_at)
+            ON CONFLICT(id) DO UPDATE SET
+                blocker_completed = excluded.blocker_completed";
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(sql, dependency.to_entity().fields)
+            .await
+            .map_err(|e| format!("Failed to save dependency: {}", e))?;
+        Ok(())
+    }
+
+    async fn query(
+        &self,
+        sql: &str,
+        params: HashMap<String, Value>,
+    ) -> Result<Vec<TaskDependency>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to query dependencies: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(TASK_DEPENDENCIES_ENTITY);
+                entity.fields = row;
+                TaskDependency::from_entity(entity)
+            })
+            .collect()
+    }
+}
+
+fn task_params(entity_name: &str, task_id: &str) -> HashMap<String, Value> {
+    HashMap::from([
+        (
+            "entity_name".to_string(),
+            Value::String(entity_name.to_string()),
+        ),
+        ("task_id".to_string(), Value::String(task_id.to_string())),
+    ])
+}
+
+/// A `completed` value as stored (0/1) or passed to operations (bool)
+fn completed_value(value: &Value) -> Option<bool> {
+    match value {
+        Value::Boolean(completed) => Some(*completed),
+        Value::Integer(completed) => Some(*completed != 0),
+        _ => None,
+    }
+}
+
+/// Task and new completion state of a `set_completion` or `set_field completed` operation
+fn completion_change(operation: &Operation) -> Option<(String, bool)> {
+    let completed = match operation.op_name.as_str() {
+        "set_completion" => operation.params.get("completed")?,
+        "set_field" if operation.params.get("field")?.as_string() == Some("completed") => {
+            operation.params.get("value")?
+        }
+        _ => return None,
+    };
+    let id = operation.params.get("id")?.as_string()?;
+    Some((id.to_string(), completed_value(completed)?))
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationObserver for DependencyStore {
+    fn entity_filter(&self) -> &str {
+        "*"
+    }
+
+    async fn on_operation_executed(&self, operation: &Operation, _undo_action: &UndoAction) {
+        let Some((task_id, completed)) = completion_change(operation) else {
+            return;
+        };
+        if let Err(e) = self
+            .set_completed(&operation.entity_name, &task_id, completed)
+            .await
+        {
+            warn!("Failed to update dependencies of {}: {}", task_id, e);
+        }
+    }
+}
+
+/// `add_dependency`/`remove_dependency` operations for one task entity type
+pub struct DependencyProvider {
+    store: Arc<DependencyStore>,
+    entity_name: String,
+    short_name: String,
+}
+
+impl DependencyProvider {
+    pub fn new(
+        store: Arc<DependencyStore>,
+        entity_name: impl Into<String>,
+        short_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            store,
+            entity_name: entity_name.into(),
+            short_name: short_name.into(),
+        }
+    }
+
+    fn inverse(&self, op_name: &str, display_name: &str, id: &str, blocks_id: &str) -> UndoAction {
+        UndoAction::Undo(Operation::new(
+            &self.entity_name,
+            op_name,
+            display_name,
+            HashMap::from([
+                ("id".to_string(), Value::String(id.to_string())),
+                (
+                    "blocks_id".to_string(),
+                    Value::String(blocks_id.to_string()),
+                ),
+            ]),
+        ))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl DependencyOperations for DependencyProvider {
+    async fn add_dependency(&self, id: &str, blocks_id: &str) -> Result<UndoAction> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.store
+            .add_dependency(&self.entity_name, id, blocks_id, now)
+            .await?;
+        Ok(self.inverse("remove_dependency", "Remove dependency", id, blocks_id))
+    }
+
+    async fn remove_dependency(&self, id: &str, blocks_id: &str) -> Result<UndoAction> {
+        self.store
+            .remove_dependency(&self.entity_name, id, blocks_id)
+            .await?;
+        Ok(self.inverse("add_dependency", "Add dependency", id, blocks_id))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationProvider for DependencyProvider {
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        __operations_dependency_operations::dependency_operations(
+            &self.entity_name,
+            &self.short_name,
+            &self.entity_name,
+            "id",
+        )
+    }
+
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        params: StorageEntity,
+    ) -> Result<UndoAction> {
+        if entity_name != self.entity_name {
+            return Err(format!(
+                "Expected entity_name '{}', got '{}'",
+                self.entity_name, entity_name
+            )
+            .into());
+        }
+        __operations_dependency_operations::dispatch_operation(self, op_name, &params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    async fn create_store() -> Arc<DependencyStore> {
+        let store = DependencyStore::new(memory_backend().await);
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        Arc::new(store)
+    }
+
+    fn set_completion(id: &str, completed: bool) -> Operation {
+        Operation::new(
+            "todoist_tasks",
+            "set_completion",
+            "Complete",
+            HashMap::from([
+                ("id".to_string(), Value::String(id.to_string())),
+                ("completed".to_string(), Value::Boolean(completed)),
+            ]),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_cycles_are_rejected() {
+        let store = create_store().await;
+        let provider = DependencyProvider::new(store.clone(), "todoist_tasks", "task");
+
+        provider.add_dependency("a", "b").await.unwrap();
+        provider.add_dependency("b", "c").await.unwrap();
+        assert!(provider.add_dependency("a", "b").await.is_err());
+        assert!(provider.add_dependency("a", "a").await.is_err());
+        let error = provider.add_dependency("c", "a").await.unwrap_err();
+        assert!(error.to_string().contains("a → b → c"), "{}", error);
+
+        // Other entity types have their own graph
+        store
+            .add_dependency("org_headlines", "c", "a", 0)
+            .await
+            .unwrap();
+
+        let undo = provider.remove_dependency("b", "c").await.unwrap();
+        match undo {
+            UndoAction::Undo(op) => assert_eq!(op.op_name, "add_dependency"),
+            other => panic!("Expected an undo operation, got {:?}", other),
+        }
+        provider.add_dependency("c", "a").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_completing_last_blocker_makes_task_actionable() {
+        let store = create_store().await;
+        store
+            .add_dependency("todoist_tasks", "a", "c", 0)
+            .await
+            .unwrap();
+        store
+            .add_dependency("todoist_tasks", "b", "c", 0)
+            .await
+            .unwrap();
+        let mut actionable = store.subscribe();
+
+        let blocked = store
+            .backend
+            .read()
+            .await
+            .execute_sql("SELECT * FROM blocked_tasks", HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(blocked.len(), 1);
+        assert_eq!(blocked[0].get("id"), Some(&Value::String("c".to_string())));
+        assert_eq!(blocked[0].get("open_blockers"), Some(&Value::Integer(2)));
+
+        store
+            .on_operation_executed(&set_completion("a", true), &UndoAction::Irreversible)
+            .await;
+        assert!(store.is_blocked("todoist_tasks", "c").await.unwrap());
+        assert!(actionable.try_recv().is_err());
+
+        store
+            .on_operation_executed(&set_completion("b", true), &UndoAction::Irreversible)
+            .await;
+        assert!(!store.is_blocked("todoist_tasks", "c").await.unwrap());
+        assert_eq!(
+            actionable.try_recv().unwrap(),
+            TaskActionable {
+                entity_name: "todoist_tasks".to_string(),
+                task_id: "c".to_string(),
+            }
+        );
+
+        // Reopening a blocker blocks the task again
+        store
+            .on_operation_executed(&set_completion("b", false), &UndoAction::Irreversible)
+            .await;
+        assert!(store.is_blocked("todoist_tasks", "c").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_completed_blocker_does_not_block() {
+        let store = create_store().await;
+        store
+            .backend
+            .read()
+            .await
+            .execute_sql(
+                "CREATE TABLE todoist_tasks (id TEXT PRIMARY KEY, completed INTEGER)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        store
+            .backend
+            .read()
+            .await
+            .execute_sql(
+                "INSERT INTO todoist_tasks (id, completed) VALUES ('a', 1)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+
+        let dependency = store
+            .add_dependency("todoist_tasks", "a", "b", 0)
+            .await
+            .unwrap();
+        assert!(dependency.blocker_completed);
+        assert!(!store.is_blocked("todoist_tasks", "b").await.unwrap());
+    }
+}
@@ -0,0 +1,310 @@
+//! Identity mapping between datasources.
+//!
+//! `EntityIdentityStore` keeps the `entity_identities` table, which records which
+//! IDs of different entities (e.g. a `todoist_tasks` ID and a `logseq_blocks` ID)
+//! refer to the same thing. Queries translate IDs with the `resolve` PRQL step:
+//!
+//! ```prql
+//! from todoist_tasks
+//! resolve logseq_blocks id
+//! join logseq_blocks (this.logseq_blocks_id == that.id)
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::storage::turso::TursoBackend;
+use holon_api::{DynamicEntity, HasSchema, Value};
+pub use holon_core::EntityIdentity;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Entity name of the identity mapping table (also hardcoded in the `resolve` step)
+pub const IDENTITIES_ENTITY: &str = "entity_identities";
+
+/// Persistent identity mapping backed by TursoBackend
+pub struct EntityIdentityStore {
+    backend: Arc<RwLock<TursoBackend>>,
+}
+
+impl EntityIdentityStore {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self { backend }
+    }
+
+    /// Initialize the entity_identities table schema
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = EntityIdentity::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create entity_identities table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        info!("Entity identities schema initialized");
+        Ok(())
+    }
+
+    /// Record that two entities' IDs refer to the same thing, returning the shared identity
+    ///
+    /// If both IDs are already linked to different identities, the identities are merged.
+    pub async fn link(
+        &self,
+        entity_name: &str,
+        external_id: &str,
+        other_entity_name: &str,
+        other_external_id: &str,
+        now: i64,
+    ) -> Result<String> {
+        let existing = self.identity_of(entity_name, external_id).await?;
+        let other = self
+            .identity_of(other_entity_name, other_external_id)
+            .await?;
+
+        let identity_id = match (&existing, &other) {
+            (Some(existing), Some(other)) => {
+                if existing.identity_id != other.identity_id {
+                    self.merge(&other.identity_id, &existing.identity_id)
+                        .await?;
+                }
+                existing.identity_id.clone()
+            }
+            (Some(existing), None) => existing.identity_id.clone(),
+            (None, Some(other)) => other.identity_id.clone(),
+            (None, None) => EntityIdentity::key(entity_name, external_id),
+        };
+
+        if existing.is_none() {
+            self.save(&EntityIdentity::new(
+                &identity_id,
+                entity_name,
+                external_id,
+                now,
+            ))
+            .await?;
+        }
+        if other.is_none() {
+            self.save(&EntityIdentity::new(
+                &identity_id,
+                other_entity_name,
+                other_external_id,
+                now,
+            ))
+            .await?;
+        }
+        debug!(
+            "Linked {}:{} and {}:{} as {}",
+            entity_name, external_id, other_entity_name, other_external_id, identity_id
+        );
+        Ok(identity_id)
+    }
+
+    /// Remove an entity's ID from its identity
+    pub async fn unlink(&self, entity_name: &str, external_id: &str) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "DELETE FROM entity_identities WHERE id = $id",
+                HashMap::from([(
+                    "id".to_string(),
+                    Value::String(EntityIdentity::key(entity_name, external_id)),
+                )]),
+            )
+            .await
+            .map_err(|e| format!("Failed to unlink identity: {}", e))?;
+        Ok(())
+    }
+
+    /// The identity an entity's ID is linked to, if any
+    pub async fn identity_of(
+        &self,
+        entity_name: &str,
+        external_id: &str,
+    ) -> Result<Option<EntityIdentity>> {
+        Ok(self
+            .query(
+                "SELECT * FROM entity_identities WHERE id = $id",
+                HashMap::from([(
+                    "id".to_string(),
+                    Value::String(EntityIdentity::key(entity_name, external_id)),
+                )]),
+            )
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// IDs in `target_entity` of the thing `external_id` of `entity_name` refers to
+    pub async fn resolve(
+        &self,
+        entity_name: &str,
+        external_id: &str,
+        target_entity: &str,
+    ) -> Result<Vec<String>> {
+        let Some(identity) = self.identity_of(entity_name, external_id).await? else {
+            return Ok(Vec::new());
+        };
+        let params = HashMap::from([
+            (
+                "identity_id".to_string(),
+                Value::String(identity.identity_id),
+            ),
+            (
+                "entity_name".to_string(),
+                Value::String(target_entity.to_string()),
+            ),
+        ]);
+        Ok(self
+            .query(
+                "SELECT * FROM entity_identities WHERE identity_id = $identity_id AND entity_name = $entity_name ORDER BY external_id",
+                params,
+            )
+            .await?
+            .into_iter()
+            .map(|identity| identity.external_id)
+            .collect())
+    }
+
+    /// Move all IDs of identity `from` to identity `into`
+    async fn merge(&self, from: &str, into: &str) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "UPDATE entity_identities SET identity_id = $into WHERE identity_id = $from",
+                HashMap::from([
+                    ("from".to_string(), Value::String(from.to_string())),
+                    ("into".to_string(), Value::String(into.to_string())),
+                ]),
+            )
+            .await
+            .map_err(|e| format!("Failed to merge identities: {}", e))?;
+        Ok(())
+    }
+
+    async fn save(&self, identity: &EntityIdentity) -> Result<()> {
+        let sql =
+            "INSERT INTO entity_identities (id, identity_id, entity_name, external_id, linked_at)
+            VALUES ($id, $identity_id, $entity_name, $external_id, $linked_at)
+            ON CONFLICT(id) DO UPDATE SET
+                identity_id = excluded.identity_id,
+                linked_at = excluded.linked_at";
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(sql, identity.to_entity().fields)
+            .await
+            .map_err(|e| format!("Failed to save identity: {}", e))?;
+        Ok(())
+    }
+
+    async fn query(
+        &self,
+        sql: &str,
+        params: HashMap<String, Value>,
+    ) -> Result<Vec<EntityIdentity>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to query identities: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(IDENTITIES_ENTITY);
+                entity.fields = row;
+                EntityIdentity::from_entity(entity)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    async fn create_store() -> EntityIdentityStore {
+        let store = EntityIdentityStore::new(memory_backend().await);
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        store
+    }
+
+    #[tokio::test]
+    async fn test_link_and_resolve() {
+        let store = create_store().await;
+
+        store
+            .link("todoist_tasks", "123", "logseq_blocks", "b-1", 0)
+            .await
+            .unwrap();
+        assert_eq!(
+            store
+                .resolve("todoist_tasks", "123", "logseq_blocks")
+                .await
+                .unwrap(),
+            vec!["b-1".to_string()]
+        );
+        assert_eq!(
+            store
+                .resolve("logseq_blocks", "b-1", "todoist_tasks")
+                .await
+                .unwrap(),
+            vec!["123".to_string()]
+        );
+
+        store.unlink("logseq_blocks", "b-1").await.unwrap();
+        assert!(
+            store
+                .resolve("todoist_tasks", "123", "logseq_blocks")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_link_merges_identities() {
+        let store = create_store().await;
+
+        store
+            .link("todoist_tasks", "123", "logseq_blocks", "b-1", 0)
+            .await
+            .unwrap();
+        store
+            .link("org_headlines", "h-1", "calendar_events", "e-1", 0)
+            .await
+            .unwrap();
+        let identity = store
+            .link("logseq_blocks", "b-1", "org_headlines", "h-1", 0)
+            .await
+            .unwrap();
+
+        for (entity_name, external_id) in [
+            ("todoist_tasks", "123"),
+            ("logseq_blocks", "b-1"),
+            ("org_headlines", "h-1"),
+            ("calendar_events", "e-1"),
+        ] {
+            let linked = store
+                .identity_of(entity_name, external_id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(linked.identity_id, identity);
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! In-memory capture of recent log events for in-app log viewers.
+//!
+//! `LogBuffer` keeps the last N tracing events in a ring buffer. Frontends install
+//! its `layer()` next to their other tracing layers and register the buffer in the
+//! DI container; `BackendEngine::recent_logs` then serves the captured events.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Number of events kept when no capacity is configured
+pub const DEFAULT_LOG_CAPACITY: usize = 1000;
+
+/// A captured log event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    /// Unix timestamp in milliseconds
+    pub timestamp: i64,
+    /// `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`
+    pub level: String,
+    /// Module path the event was logged from (e.g. `holon::sync::scheduler`)
+    pub target: String,
+    pub message: String,
+    /// Structured fields of the event, formatted with `Debug`
+    pub fields: HashMap<String, String>,
+}
+
+/// Which events `LogBuffer::recent` returns
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogFilter {
+    /// Least severe level to include (e.g. `warn` includes warnings and errors)
+    pub min_level: Option<String>,
+    /// Target prefix (e.g. `holon_todoist`)
+    pub target: Option<String>,
+    /// Case-insensitive substring of the message or a field value
+    pub contains: Option<String>,
+}
+
+impl LogFilter {
+    fn matches(&self, record: &LogRecord) -> bool {
+        if let Some(min_level) = self
+            .min_level
+            .as_deref()
+            .and_then(|l| Level::from_str(l).ok())
+        {
+            // Less verbose levels compare lower (ERROR < WARN < ... < TRACE)
+            match Level::from_str(&record.level) {
+                Ok(level) if level <= min_level => {}
+                _ => return false,
+            }
+        }
+        if let Some(target) = &self.target {
+            if !record.target.starts_with(target.as_str()) {
+                return false;
+            }
+        }
+        if let Some(needle) = &self.contains {
+            let needle = needle.to_lowercase();
+            let found = record.message.to_lowercase().contains(&needle)
+                || record
+                    .fields
+                    .values()
+                    .any(|value| value.to_lowercase().contains(&needle));
+            if !found {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Ring buffer of the most recent log events
+#[derive(Clone)]
+pub struct LogBuffer {
+    records: Arc<Mutex<VecDeque<LogRecord>>>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Tracing layer that captures events into this buffer
+    pub fn layer(&self) -> LogBufferLayer {
+        LogBufferLayer {
+            buffer: self.clone(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn push(&self, record: LogRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut records = self.records.lock().unwrap();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Up to `limit` matching events, newest first
+    pub fn recent(&self, filter: &LogFilter, limit: usize) -> Vec<LogRecord> {
+        let records = self.records.lock().unwrap();
+        records
+            .iter()
+            .rev()
+            .filter(|record| filter.matches(record))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.records.lock().unwrap().clear();
+    }
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOG_CAPACITY)
+    }
+}
+
+impl fmt::Debug for LogBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogBuffer")
+            .field("capacity", &self.capacity)
+            .field("len", &self.records.lock().unwrap().len())
+            .finish()
+    }
+}
+
+/// Tracing layer feeding a `LogBuffer` (see `LogBuffer::layer`)
+pub struct LogBufferLayer {
+    buffer: LogBuffer,
+}
+
+impl<S: Subscriber> Layer<S> for LogBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        self.buffer.push(LogRecord {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+        });
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: HashMap<String, String>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields
+                .insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_captures_recent_events() {
+        let buffer = LogBuffer::new(2);
+        let subscriber = tracing_subscriber::registry().with(buffer.layer());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("first");
+            tracing::warn!(provider = "todoist", "sync failed");
+            tracing::debug!("third");
+        });
+
+        let all = buffer.recent(&LogFilter::default(), 10);
+        assert_eq!(all.len(), 2, "oldest event is evicted");
+        assert_eq!(all[0].message, "third");
+        assert_eq!(all[1].message, "sync failed");
+        assert_eq!(all[1].level, "WARN");
+        assert_eq!(
+            all[1].fields.get("provider").map(String::as_str),
+            Some("todoist")
+        );
+
+        let warnings = buffer.recent(
+            &LogFilter {
+                min_level: Some("warn".to_string()),
+                ..Default::default()
+            },
+            10,
+        );
+        assert_eq!(warnings.len(), 1);
+
+        let by_field = buffer.recent(
+            &LogFilter {
+                contains: Some("TODOIST".to_string()),
+                ..Default::default()
+            },
+            10,
+        );
+        assert_eq!(by_field.len(), 1);
+        assert_eq!(buffer.recent(&LogFilter::default(), 1).len(), 1);
+    }
+}
@@ -1,26 +1,54 @@
+pub mod access;
+pub mod annotations;
+pub mod attachments;
+pub mod automation;
+pub mod custom_fields;
 pub mod datasource;
+pub mod dependencies;
+pub mod identities;
+pub mod log_buffer;
+pub mod notifications;
 pub mod operation_log;
+pub mod presence;
 pub mod queryable_cache;
+pub mod row_security;
 pub mod stream_cache;
+pub mod time_tracking;
 pub mod traits;
 pub mod transform;
 pub mod unified_query;
 pub mod updates;
+pub mod usage_stats;
+pub mod view_state;
 
 #[cfg(test)]
 mod test_macro;
 
+pub use access::EntityAccessStore;
+pub use annotations::{Annotation, AnnotationStore};
+pub use attachments::{AttachmentProvider, AttachmentStore};
+pub use automation::{AutomationRule, AutomationRules, RuleAction};
+pub use custom_fields::{CustomFieldProvider, CustomFieldStore};
 pub use datasource::{DataSource, StreamProvider};
+pub use dependencies::{DependencyProvider, DependencyStore, TaskActionable};
+pub use identities::EntityIdentityStore;
+pub use log_buffer::{LogBuffer, LogFilter, LogRecord};
+pub use notifications::{LoggingNotificationSink, Notification, NotificationSink};
 // Re-export DynamicEntity from holon_api (single source of truth)
 pub use holon_api::DynamicEntity;
 pub use operation_log::{OperationLogObserver, OperationLogStore};
+pub use presence::{LocalPresenceChannel, PresenceChannel, PresenceConfig, PresenceTracker};
 pub use queryable_cache::QueryableCache;
+pub use row_security::{RowSecurity, RowSecurityConfig};
 pub use stream_cache::QueryableCache as StreamCache;
+pub use time_tracking::{TimeEntryStore, TimeTrackingProvider};
 pub use traits::{
     And, FieldSchema, HasSchema, Lens, Not, Or, Predicate, Queryable, Schema, SqlPredicate,
 };
 pub use transform::{AstTransformer, ChangeOriginTransformer, TransformPhase, TransformPipeline};
 pub use unified_query::UnifiedQuery;
 pub use updates::{FieldChange, Updates};
+pub use usage_stats::{OperationUsageStore, UsageStatsConfig};
+pub use view_state::ViewStateStore;
 
 // MaybeSendSync is now defined in holon-core and re-exported via datasource module
@@ -0,0 +1,78 @@
+//! Platform-agnostic notifications
+//!
+//! Background subsystems (sync health reports, reminders, ...) raise `Notification`s
+//! through a `NotificationSink`. Frontends implement the sink to show them natively
+//! (TUI status line, Flutter local notifications); `LoggingNotificationSink` is the
+//! fallback when no frontend sink is attached.
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// How urgently a notification should be surfaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NotificationSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// A notification to show to the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    pub severity: NotificationSeverity,
+    /// Entity the notification refers to, e.g. `("sync_health_reports", report_id)`
+    pub entity: Option<(String, String)>,
+}
+
+impl Notification {
+    pub fn new(
+        title: impl Into<String>,
+        body: impl Into<String>,
+        severity: NotificationSeverity,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            body: body.into(),
+            severity,
+            entity: None,
+        }
+    }
+
+    /// Link the notification to an entity so frontends can navigate to it
+    pub fn with_entity(mut self, entity_name: impl Into<String>, id: impl Into<String>) -> Self {
+        self.entity = Some((entity_name.into(), id.into()));
+        self
+    }
+}
+
+/// Receives notifications raised by background subsystems
+///
+/// Implementations must not block; forward to a channel or UI event loop instead.
+pub trait NotificationSink: Send + Sync {
+    fn notify(&self, notification: Notification);
+}
+
+/// Sink that writes notifications to the tracing log
+#[derive(Debug, Default, Clone)]
+pub struct LoggingNotificationSink;
+
+impl NotificationSink for LoggingNotificationSink {
+    fn notify(&self, notification: Notification) {
+        match notification.severity {
+            NotificationSeverity::Info => {
+                info!(
+                    "[Notification] {}: {}",
+                    notification.title, notification.body
+                )
+            }
+            NotificationSeverity::Warning | NotificationSeverity::Critical => {
+                warn!(
+                    "[Notification] {}: {}",
+                    notification.title, notification.body
+                )
+            }
+        }
+    }
+}
@@ -2,36 +2,165 @@
 //!
 //! This module provides `OperationLogStore`, which implements the
 //! `OperationLogOperations` trait for persistent operation logging.
+//!
+//! Besides the bounded undo/redo log in the `operations` table, the store keeps a
+//! durable audit trail in `operation_audit_log`: one `AuditLogEntry` per dispatched
+//! operation (including failed ones), pruned by an `AuditRetention` policy,
+//! filterable by the `ChangeSource` that executed it and exportable as JSONL or CSV.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tokio::sync::RwLock;
 use tracing::{debug, info};
 
 use crate::storage::turso::TursoBackend;
-use holon_api::{HasSchema, Operation, Value};
-use holon_core::{OperationLogEntry, OperationLogOperations, OperationStatus, UndoAction};
+use holon_api::{ChangeSource, DynamicEntity, HasSchema, Operation, Value};
+pub use holon_core::{IdMappingService, OperationLogEntry, OperationStatus};
+use holon_core::{OperationLogOperations, UndoAction};
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
+/// Entity name of the audit trail
+pub const AUDIT_LOG_ENTITY: &str = "operation_audit_log";
+
+/// Retention is enforced once per this many audit entries
+const AUDIT_RETENTION_CHECK_INTERVAL: i64 = 100;
+
+/// One dispatched operation in the audit trail.
+///
+/// Parameters are stored as a SHA-256 digest only, so the trail can be shared
+/// without leaking content; the undo action is stored in full.
+///
+/// Table name: `operation_audit_log`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Entity)]
+#[entity(name = "operation_audit_log", short_name = "audit")]
+pub struct AuditLogEntry {
+    /// Primary key (auto-incremented)
+    #[primary_key]
+    pub id: i64,
+    /// When the operation was dispatched (Unix timestamp in milliseconds)
+    #[indexed]
+    pub created_at: i64,
+    /// Who executed the operation (see `OperationLogStore::with_actor`)
+    pub actor: String,
+    /// Device, frontend, provider or agent that executed it (`ChangeSource` as JSON)
+    pub change_source: Option<String>,
+    #[indexed]
+    pub entity_name: String,
+    pub op_name: String,
+    /// Hex SHA-256 of the parameters as canonical (key-sorted) JSON
+    pub params_digest: String,
+    /// "ok" or "error"
+    pub result: String,
+    pub error: Option<String>,
+    /// The inverse operation (serialized as JSON, None if not undoable or failed)
+    pub undo_action: Option<String>,
+    pub duration_ms: i64,
+}
+
+impl AuditLogEntry {
+    pub fn is_success(&self) -> bool {
+        self.result == "ok"
+    }
+
+    /// The source that executed the operation, if recorded
+    pub fn source(&self) -> Option<ChangeSource> {
+        self.change_source
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+    }
+}
+
+/// Hex SHA-256 digest of operation parameters, independent of map ordering
+pub fn params_digest(params: &HashMap<String, Value>) -> String {
+    let sorted: BTreeMap<&String, &Value> = params.iter().collect();
+    let json = serde_json::to_string(&sorted).unwrap_or_default();
+    hex::encode(Sha256::digest(json.as_bytes()))
+}
+
+/// How long audit entries are kept
+#[derive(Clone, Debug)]
+pub struct AuditRetention {
+    /// Entries older than this are deleted (None keeps them forever)
+    pub max_age: Option<Duration>,
+    /// Only the newest entries are kept beyond this count (None for no limit)
+    pub max_entries: Option<usize>,
+}
+
+impl Default for AuditRetention {
+    fn default() -> Self {
+        Self {
+            max_age: Some(Duration::from_secs(365 * 24 * 60 * 60)),
+            max_entries: None,
+        }
+    }
+}
+
+impl AuditRetention {
+    /// Keep all entries
+    pub fn unlimited() -> Self {
+        Self {
+            max_age: None,
+            max_entries: None,
+        }
+    }
+
+    pub fn with_max_age(mut self, max_age: Option<Duration>) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    pub fn with_max_entries(mut self, max_entries: Option<usize>) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+}
+
+/// Who is recorded as the actor of audit entries, if not the OS user
+///
+/// Register one in the DI container to attribute all operations of a process, e.g.
+/// those an assistant executes through the MCP server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditActor(pub String);
+
+impl AuditActor {
+    pub fn new(actor: impl Into<String>) -> Self {
+        Self(actor.into())
+    }
+}
+
+/// Export format of the audit trail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditExportFormat {
+    /// One JSON object per line
+    Jsonl,
+    /// RFC 4180 CSV with a header row
+    Csv,
+}
+
 /// Persistent operation log store backed by TursoBackend.
 ///
 /// Stores operations in the `operations` table and provides
-/// undo/redo candidate queries.
+/// undo/redo candidate queries. Every dispatched operation is additionally
+/// recorded in the `operation_audit_log` table via `record_audit`.
 pub struct OperationLogStore {
     backend: Arc<RwLock<TursoBackend>>,
     max_log_size: usize,
+    audit_retention: AuditRetention,
+    actor: String,
 }
 
 impl OperationLogStore {
     /// Create a new operation log store.
     pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
-        Self {
-            backend,
-            max_log_size: 100,
-        }
+        Self::with_max_size(backend, 100)
     }
 
     /// Create a new operation log store with custom max size.
@@ -39,37 +168,131 @@ impl OperationLogStore {
         Self {
             backend,
             max_log_size,
+            audit_retention: AuditRetention::default(),
+            actor: default_actor(),
         }
     }
 
+    /// Set the retention policy of the audit trail.
+    pub fn with_audit_retention(mut self, audit_retention: AuditRetention) -> Self {
+        self.audit_retention = audit_retention;
+        self
+    }
+
+    /// Set the actor recorded in audit entries (defaults to the OS user name).
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = actor.into();
+        self
+    }
+
     /// Initialize the operations table schema.
     ///
     /// Creates the table and indexes if they don't exist.
     pub async fn initialize_schema(&self) -> Result<()> {
-        let schema = OperationLogEntry::schema();
-        let create_table_sql = schema.to_create_table_sql();
-        let index_sqls = schema.to_index_sql();
-
         let backend = self.backend.read().await;
 
-        debug!("Creating operations table: {}", create_table_sql);
-        backend
-            .execute_sql(&create_table_sql, HashMap::new())
-            .await
-            .map_err(|e| format!("Failed to create operations table: {}", e))?;
+        for schema in [OperationLogEntry::schema(), AuditLogEntry::schema()] {
+            let create_table_sql = schema.to_create_table_sql();
+            debug!("Creating {} table: {}", schema.table_name, create_table_sql);
+            backend
+                .execute_sql(&create_table_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create {} table: {}", schema.table_name, e))?;
+
+            for index_sql in schema.to_index_sql() {
+                debug!("Creating index: {}", index_sql);
+                backend
+                    .execute_sql(&index_sql, HashMap::new())
+                    .await
+                    .map_err(|e| format!("Failed to create index: {}", e))?;
+            }
+        }
 
-        for index_sql in index_sqls {
-            debug!("Creating index: {}", index_sql);
+        // Audit tables created before change sources were recorded lack the column
+        let rows = backend
+            .execute_sql(
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'operation_audit_log'",
+                HashMap::new(),
+            )
+            .await
+            .map_err(|e| format!("Failed to read audit log schema: {}", e))?;
+        let has_change_source = rows
+            .first()
+            .and_then(|row| row.get("sql"))
+            .and_then(|sql| sql.as_string())
+            .is_some_and(|sql| sql.contains("change_source"));
+        if !has_change_source {
             backend
-                .execute_sql(&index_sql, HashMap::new())
+                .execute_sql(
+                    "ALTER TABLE operation_audit_log ADD COLUMN change_source TEXT",
+                    HashMap::new(),
+                )
                 .await
-                .map_err(|e| format!("Failed to create index: {}", e))?;
+                .map_err(|e| format!("Failed to add change_source column: {}", e))?;
         }
 
         info!("Operation log schema initialized");
         Ok(())
     }
 
+    /// Get the most recent logged operations, newest first.
+    ///
+    /// Includes undone and cancelled operations so a history view can show them.
+    pub async fn recent_operations(&self, limit: usize) -> Result<Vec<OperationLogEntry>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT * FROM operations ORDER BY id DESC LIMIT $limit",
+                HashMap::from([("limit".to_string(), Value::Integer(limit as i64))]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query operations: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new("operations");
+                entity.fields = row;
+                OperationLogEntry::from_entity(entity)
+            })
+            .collect()
+    }
+
+    /// Find the logged operation that was executed under `idempotency_key`.
+    ///
+    /// Lets a dispatcher recognise a retry of an operation it completed before a
+    /// restart. Only operations still in the bounded log can be found.
+    pub async fn find_by_idempotency_key(
+        &self,
+        idempotency_key: &str,
+    ) -> Result<Option<OperationLogEntry>> {
+        let backend = self.backend.read().await;
+
+        // LIKE narrows down candidates; the deserialized operation must match exactly
+        let rows = backend
+            .execute_sql(
+                "SELECT * FROM operations WHERE operation LIKE $pattern ORDER BY id DESC",
+                HashMap::from([(
+                    "pattern".to_string(),
+                    Value::String(format!("%{}%", idempotency_key)),
+                )]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query operations: {}", e))?;
+
+        for row in rows {
+            let mut entity = DynamicEntity::new("operations");
+            entity.fields = row;
+            let entry = OperationLogEntry::from_entity(entity)?;
+            if entry
+                .get_operation()
+                .is_some_and(|operation| operation.idempotency_key == idempotency_key)
+            {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+
     /// Trim old operations if we're over the max size.
     async fn trim_if_needed(&self) -> Result<()> {
         let backend = self.backend.read().await;
@@ -111,6 +334,241 @@ impl OperationLogStore {
     }
 }
 
+impl OperationLogStore {
+    /// Record a dispatched operation in the audit trail.
+    ///
+    /// `result` is the undo action of a successful operation or the error message
+    /// of a failed one. `source` is who executed it, if known.
+    pub async fn record_audit(
+        &self,
+        operation: &Operation,
+        result: std::result::Result<&UndoAction, &str>,
+        duration_ms: i64,
+        source: Option<&ChangeSource>,
+    ) -> Result<i64> {
+        let entry = AuditLogEntry {
+            id: 0,
+            created_at: chrono::Utc::now().timestamp_millis(),
+            actor: self.actor.clone(),
+            change_source: source
+                .filter(|source| !source.is_empty())
+                .and_then(|source| serde_json::to_string(source).ok()),
+            entity_name: operation.entity_name.clone(),
+            op_name: operation.op_name.clone(),
+            params_digest: params_digest(&operation.params),
+            result: if result.is_ok() { "ok" } else { "error" }.to_string(),
+            error: result.err().map(String::from),
+            undo_action: match result {
+                Ok(UndoAction::Undo(inverse)) => serde_json::to_string(inverse).ok(),
+                _ => None,
+            },
+            duration_ms,
+        };
+
+        let backend = self.backend.read().await;
+        let mut params = entry.to_entity().fields;
+        params.remove("id");
+        let columns: Vec<String> = params.keys().cloned().collect();
+        let sql = format!(
+            "INSERT INTO operation_audit_log ({}) VALUES ({})",
+            columns.join(", "),
+            columns
+                .iter()
+                .map(|c| format!("${}", c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        backend
+            .execute_sql(&sql, params)
+            .await
+            .map_err(|e| format!("Failed to insert audit log entry: {}", e))?;
+
+        let id = backend
+            .execute_sql("SELECT last_insert_rowid() as id", HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to get last insert ID: {}", e))?
+            .first()
+            .and_then(|row| row.get("id"))
+            .and_then(|v| v.as_i64())
+            .ok_or("Failed to get inserted audit entry ID")?;
+        drop(backend);
+
+        if id % AUDIT_RETENTION_CHECK_INTERVAL == 0 {
+            self.apply_audit_retention(entry.created_at).await?;
+        }
+        Ok(id)
+    }
+
+    /// Audit entries created in `[since, until)`, oldest first.
+    pub async fn audit_entries(
+        &self,
+        since: Option<i64>,
+        until: Option<i64>,
+    ) -> Result<Vec<AuditLogEntry>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT * FROM operation_audit_log
+                 WHERE created_at >= $since AND created_at < $until
+                 ORDER BY id ASC",
+                HashMap::from([
+                    (
+                        "since".to_string(),
+                        Value::Integer(since.unwrap_or(i64::MIN)),
+                    ),
+                    (
+                        "until".to_string(),
+                        Value::Integer(until.unwrap_or(i64::MAX)),
+                    ),
+                ]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query audit log: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(AUDIT_LOG_ENTITY);
+                entity.fields = row;
+                AuditLogEntry::from_entity(entity)
+            })
+            .collect()
+    }
+
+    /// Audit entries created in `[since, until)` whose source matches `filter`
+    /// (see `ChangeSource::matches`), oldest first.
+    ///
+    /// Entries without a recorded source only match an empty filter.
+    pub async fn audit_entries_from(
+        &self,
+        filter: &ChangeSource,
+        since: Option<i64>,
+        until: Option<i64>,
+    ) -> Result<Vec<AuditLogEntry>> {
+        let mut entries = self.audit_entries(since, until).await?;
+        entries.retain(|entry| entry.source().unwrap_or_default().matches(filter));
+        Ok(entries)
+    }
+
+    /// Delete audit entries outside the retention policy.
+    ///
+    /// Returns the number of deleted entries.
+    pub async fn apply_audit_retention(&self, now: i64) -> Result<usize> {
+        let backend = self.backend.read().await;
+        let count = |rows: Vec<HashMap<String, Value>>| {
+            rows.first()
+                .and_then(|row| row.get("count"))
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0) as usize
+        };
+        let count_sql = "SELECT COUNT(*) as count FROM operation_audit_log";
+        let before = count(
+            backend
+                .execute_sql(count_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to count audit entries: {}", e))?,
+        );
+
+        if let Some(max_age) = self.audit_retention.max_age {
+            let cutoff = now - max_age.as_millis() as i64;
+            backend
+                .execute_sql(
+                    "DELETE FROM operation_audit_log WHERE created_at < $cutoff",
+                    HashMap::from([("cutoff".to_string(), Value::Integer(cutoff))]),
+                )
+                .await
+                .map_err(|e| format!("Failed to prune old audit entries: {}", e))?;
+        }
+
+        if let Some(max_entries) = self.audit_retention.max_entries {
+            backend
+                .execute_sql(
+                    "DELETE FROM operation_audit_log WHERE id NOT IN (
+                        SELECT id FROM operation_audit_log ORDER BY id DESC LIMIT $max_entries
+                    )",
+                    HashMap::from([(
+                        "max_entries".to_string(),
+                        Value::Integer(max_entries as i64),
+                    )]),
+                )
+                .await
+                .map_err(|e| format!("Failed to trim audit entries: {}", e))?;
+        }
+
+        let after = count(
+            backend
+                .execute_sql(count_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to count audit entries: {}", e))?,
+        );
+        let deleted = before.saturating_sub(after);
+        if deleted > 0 {
+            debug!("Pruned {} audit log entries", deleted);
+        }
+        Ok(deleted)
+    }
+
+    /// Write audit entries created in `[since, until)` to `writer`.
+    ///
+    /// Returns the number of exported entries.
+    pub async fn export_audit_log(
+        &self,
+        format: AuditExportFormat,
+        since: Option<i64>,
+        until: Option<i64>,
+        writer: &mut (dyn Write + Send),
+    ) -> Result<usize> {
+        let entries = self.audit_entries(since, until).await?;
+        match format {
+            AuditExportFormat::Jsonl => {
+                for entry in &entries {
+                    serde_json::to_writer(&mut *writer, entry)?;
+                    writer.write_all(b"\n")?;
+                }
+            }
+            AuditExportFormat::Csv => {
+                writeln!(
+                    writer,
+                    "id,created_at,actor,change_source,entity_name,op_name,params_digest,result,error,undo_action,duration_ms"
+                )?;
+                for entry in &entries {
+                    writeln!(
+                        writer,
+                        "{},{},{},{},{},{},{},{},{},{},{}",
+                        entry.id,
+                        entry.created_at,
+                        csv_field(&entry.actor),
+                        csv_field(entry.change_source.as_deref().unwrap_or("")),
+                        csv_field(&entry.entity_name),
+                        csv_field(&entry.op_name),
+                        entry.params_digest,
+                        entry.result,
+                        csv_field(entry.error.as_deref().unwrap_or("")),
+                        csv_field(entry.undo_action.as_deref().unwrap_or("")),
+                        entry.duration_ms
+                    )?;
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(entries.len())
+    }
+}
+
+/// Quote a CSV field if it contains separators, quotes or line breaks
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn default_actor() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "local".to_string())
+}
+
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl OperationLogOperations for OperationLogStore {
@@ -243,6 +701,55 @@ impl OperationLogOperations for OperationLogStore {
         Ok(())
     }
 
+    async fn remap_id(&self, temp_id: &str, remote_id: &str) -> Result<usize> {
+        let backend = self.backend.read().await;
+
+        // LIKE narrows down candidates; OperationLogEntry::remap_id matches exact values
+        let rows = backend
+            .execute_sql(
+                "SELECT * FROM operations WHERE operation LIKE $pattern OR inverse LIKE $pattern",
+                HashMap::from([(
+                    "pattern".to_string(),
+                    Value::String(format!("%{}%", temp_id)),
+                )]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query operations: {}", e))?;
+
+        let mut rewritten = 0;
+        for row in rows {
+            let mut entity = DynamicEntity::new("operations");
+            entity.fields = row;
+            let mut entry = OperationLogEntry::from_entity(entity)?;
+            if !entry.remap_id(temp_id, remote_id) {
+                continue;
+            }
+
+            let params = HashMap::from([
+                ("id".to_string(), Value::Integer(entry.id)),
+                ("operation".to_string(), Value::String(entry.operation)),
+                (
+                    "inverse".to_string(),
+                    entry.inverse.map(Value::String).unwrap_or(Value::Null),
+                ),
+            ]);
+            backend
+                .execute_sql(
+                    "UPDATE operations SET operation = $operation, inverse = $inverse WHERE id = $id",
+                    params,
+                )
+                .await
+                .map_err(|e| format!("Failed to remap operation {}: {}", entry.id, e))?;
+            rewritten += 1;
+        }
+
+        debug!(
+            "Remapped {} -> {} in {} logged operations",
+            temp_id, remote_id, rewritten
+        );
+        Ok(rewritten)
+    }
+
     fn max_log_size(&self) -> usize {
         self.max_log_size
     }
@@ -346,6 +853,85 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_recent_operations_newest_first() {
+        let backend = TursoBackend::new_in_memory()
+            .await
+            .expect("Failed to create backend");
+        let store = OperationLogStore::new(Arc::new(RwLock::new(backend)));
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+
+        for name in ["Op 1", "Op 2", "Op 3"] {
+            let op = Operation::new("test", "op", name, HashMap::new());
+            store
+                .log_operation(op, UndoAction::Irreversible)
+                .await
+                .unwrap();
+        }
+
+        let recent = store.recent_operations(2).await.unwrap();
+        let names: Vec<&str> = recent.iter().map(|e| e.display_name.as_str()).collect();
+        assert_eq!(names, vec!["Op 3", "Op 2"]);
+        assert_eq!(recent[0].inverse, None);
+        assert_eq!(recent[0].get_operation().unwrap().op_name, "op");
+    }
+
+    #[tokio::test]
+    async fn test_remap_id_rewrites_queued_operations() {
+        let backend = TursoBackend::new_in_memory()
+            .await
+            .expect("Failed to create backend");
+        let store = OperationLogStore::new(Arc::new(RwLock::new(backend)));
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+
+        let task_op = |op_name: &str, id: &str| {
+            Operation::new(
+                "todoist_tasks",
+                op_name,
+                op_name,
+                HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+            )
+        };
+        store
+            .log_operation(
+                task_op("create", "tmp-1"),
+                UndoAction::Undo(task_op("delete", "tmp-1")),
+            )
+            .await
+            .unwrap();
+        store
+            .log_operation(task_op("set_completion", "tmp-1"), UndoAction::Irreversible)
+            .await
+            .unwrap();
+        store
+            .log_operation(task_op("set_completion", "other"), UndoAction::Irreversible)
+            .await
+            .unwrap();
+
+        assert_eq!(store.remap_id("tmp-1", "remote-1").await.unwrap(), 2);
+
+        let ids: Vec<String> = store
+            .recent_operations(10)
+            .await
+            .unwrap()
+            .iter()
+            .flat_map(|entry| entry.get_operation().into_iter().chain(entry.get_inverse()))
+            .filter_map(|op| {
+                op.params
+                    .get("id")
+                    .and_then(|v| v.as_string())
+                    .map(String::from)
+            })
+            .collect();
+        assert_eq!(ids, vec!["other", "remote-1", "remote-1", "remote-1"]);
+    }
+
     #[tokio::test]
     async fn test_mark_undone_and_redone() {
         let backend = TursoBackend::new_in_memory()
@@ -489,4 +1075,132 @@ mod tests {
 
         assert_eq!(count, 5);
     }
+
+    #[test]
+    fn test_params_digest_ignores_key_order() {
+        let a = HashMap::from([
+            ("id".to_string(), Value::String("1".to_string())),
+            ("content".to_string(), Value::String("Buy milk".to_string())),
+        ]);
+        let mut b = HashMap::new();
+        b.insert("content".to_string(), Value::String("Buy milk".to_string()));
+        b.insert("id".to_string(), Value::String("1".to_string()));
+        assert_eq!(params_digest(&a), params_digest(&b));
+        assert_eq!(params_digest(&a).len(), 64);
+        assert_ne!(params_digest(&a), params_digest(&HashMap::new()));
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_records_and_exports() {
+        let backend = TursoBackend::new_in_memory()
+            .await
+            .expect("Failed to create backend");
+        let store = OperationLogStore::new(Arc::new(RwLock::new(backend))).with_actor("alice");
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+
+        let op = Operation::new(
+            "todoist_tasks",
+            "set_field",
+            "",
+            HashMap::from([("id".to_string(), Value::String("1".to_string()))]),
+        );
+        let inverse = Operation::new("todoist_tasks", "set_field", "", HashMap::new());
+        let agent = ChangeSource::default()
+            .with_frontend("cli")
+            .with_agent("mcp:assistant");
+        store
+            .record_audit(&op, Ok(&UndoAction::Undo(inverse)), 12, Some(&agent))
+            .await
+            .unwrap();
+        store
+            .record_audit(&op, Err("HTTP 500, \"server error\""), 30, None)
+            .await
+            .unwrap();
+
+        let entries = store.audit_entries(None, None).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].actor, "alice");
+        assert_eq!(entries[0].source(), Some(agent));
+        assert_eq!(entries[1].source(), None);
+
+        let from_agent = store
+            .audit_entries_from(
+                &ChangeSource::default().with_agent("mcp:assistant"),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(from_agent, entries[..1]);
+        assert!(entries[0].is_success());
+        assert!(entries[0].undo_action.is_some());
+        assert_eq!(entries[1].result, "error");
+        assert_eq!(entries[1].params_digest, params_digest(&op.params));
+
+        let mut jsonl = Vec::new();
+        let exported = store
+            .export_audit_log(AuditExportFormat::Jsonl, None, None, &mut jsonl)
+            .await
+            .unwrap();
+        assert_eq!(exported, 2);
+        let parsed: Vec<AuditLogEntry> = String::from_utf8(jsonl)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(parsed, entries);
+
+        let mut csv = Vec::new();
+        store
+            .export_audit_log(AuditExportFormat::Csv, None, None, &mut csv)
+            .await
+            .unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("id,created_at,actor,change_source"));
+        assert!(lines[2].contains(",error,\"HTTP 500, \"\"server error\"\"\","));
+    }
+
+    #[tokio::test]
+    async fn test_audit_retention() {
+        let backend = TursoBackend::new_in_memory()
+            .await
+            .expect("Failed to create backend");
+        let store = OperationLogStore::new(Arc::new(RwLock::new(backend)))
+            .with_audit_retention(AuditRetention::unlimited().with_max_entries(Some(3)));
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+
+        let op = Operation::new("test", "op", "", HashMap::new());
+        for _ in 0..5 {
+            store
+                .record_audit(&op, Ok(&UndoAction::Irreversible), 1, None)
+                .await
+                .unwrap();
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        assert_eq!(store.apply_audit_retention(now).await.unwrap(), 2);
+        let ids: Vec<i64> = store
+            .audit_entries(None, None)
+            .await
+            .unwrap()
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, vec![3, 4, 5]);
+
+        // A max age prunes everything created before the cutoff
+        let store = store.with_audit_retention(
+            AuditRetention::unlimited().with_max_age(Some(Duration::from_secs(60))),
+        );
+        let later = now + 2 * 60 * 1000;
+        assert_eq!(store.apply_audit_retention(later).await.unwrap(), 3);
+    }
 }
@@ -0,0 +1,527 @@
+//! Presence of collaborators in a shared workspace.
+//!
+//! Every client publishes its `Presence` (view, focused entity and selection) on a
+//! `PresenceChannel`: `LocalPresenceChannel` connects the clients of one process,
+//! `MulticastPresenceChannel` the clients on a local network. `PresenceTracker`
+//! collects what it receives into the `presence` table, so presence can be queried
+//! like any other table (`from presence | filter view_id == "inbox"`).
+//!
+//! Presence is transient: the table is cleared when the tracker starts, clients
+//! republish their presence every `PRESENCE_HEARTBEAT` while idle, and clients not
+//! heard from within the tracker's timeout are dropped.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::{RwLock, broadcast};
+use tracing::{debug, info};
+
+use crate::storage::turso::TursoBackend;
+use holon_api::{Presence, Value};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Table holding the presence of all clients
+pub const PRESENCE_TABLE: &str = "presence";
+
+/// Interval at which clients republish their presence
+pub const PRESENCE_HEARTBEAT: Duration = Duration::from_secs(10);
+
+/// Time after which a client that stopped publishing is dropped, unless configured otherwise
+pub const DEFAULT_PRESENCE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Presence messages buffered per subscriber
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Channel on which clients exchange their presence
+#[async_trait]
+pub trait PresenceChannel: Send + Sync {
+    /// Send `presence` to all clients on the channel (possibly including this one)
+    async fn publish(&self, presence: &Presence) -> Result<()>;
+
+    /// Presence received from now on
+    fn subscribe(&self) -> broadcast::Receiver<Presence>;
+}
+
+/// Channel between clients of the same process (e.g. windows, or tests)
+#[derive(Clone)]
+pub struct LocalPresenceChannel {
+    sender: broadcast::Sender<Presence>,
+}
+
+impl Default for LocalPresenceChannel {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+}
+
+#[async_trait]
+impl PresenceChannel for LocalPresenceChannel {
+    async fn publish(&self, presence: &Presence) -> Result<()> {
+        // Nobody listening is not an error
+        let _ = self.sender.send(presence.clone());
+        Ok(())
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<Presence> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use multicast::MulticastPresenceChannel;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod multicast {
+    use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use socket2::{Domain, Protocol, Socket, Type};
+    use tokio::net::UdpSocket;
+    use tokio::sync::broadcast;
+    use tracing::warn;
+
+    use super::{CHANNEL_CAPACITY, PresenceChannel, Result};
+    use holon_api::Presence;
+
+    /// Largest presence message, so that it fits into one datagram
+    const MAX_MESSAGE_SIZE: usize = 8 * 1024;
+
+    /// Channel between the clients on a local network, over UDP multicast
+    ///
+    /// Every client joins the same multicast group; several clients on one machine
+    /// can share the group's port.
+    pub struct MulticastPresenceChannel {
+        socket: Arc<UdpSocket>,
+        group: SocketAddrV4,
+        sender: broadcast::Sender<Presence>,
+        receiving: tokio::task::JoinHandle<()>,
+    }
+
+    impl MulticastPresenceChannel {
+        /// Group joined by default (an organization-local multicast address)
+        pub const DEFAULT_GROUP: SocketAddrV4 =
+            SocketAddrV4::new(Ipv4Addr::new(239, 255, 72, 79), 47479);
+
+        /// Join `group` and start receiving presence; must be called within a tokio runtime
+        pub fn join(group: SocketAddrV4) -> Result<Self> {
+            let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+            socket.set_reuse_address(true)?;
+            socket.set_nonblocking(true)?;
+            socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, group.port())).into())?;
+            socket.join_multicast_v4(group.ip(), &Ipv4Addr::UNSPECIFIED)?;
+            // Other clients on this machine receive our presence too
+            socket.set_multicast_loop_v4(true)?;
+            let socket = Arc::new(UdpSocket::from_std(socket.into())?);
+
+            let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+            let receiving = tokio::spawn(receive(socket.clone(), sender.clone()));
+            Ok(Self {
+                socket,
+                group,
+                sender,
+                receiving,
+            })
+        }
+    }
+
+    impl Drop for MulticastPresenceChannel {
+        fn drop(&mut self) {
+            self.receiving.abort();
+        }
+    }
+
+    #[async_trait]
+    impl PresenceChannel for MulticastPresenceChannel {
+        async fn publish(&self, presence: &Presence) -> Result<()> {
+            let message = presence.to_json();
+            if message.len() > MAX_MESSAGE_SIZE {
+                return Err(format!(
+                    "Presence of {} is too large to publish ({} bytes)",
+                    presence.client_id,
+                    message.len()
+                )
+                .into());
+            }
+            self.socket
+                .send_to(message.as_bytes(), self.group)
+                .await
+                .map_err(|e| format!("Failed to publish presence: {}", e))?;
+            Ok(())
+        }
+
+        fn subscribe(&self) -> broadcast::Receiver<Presence> {
+            self.sender.subscribe()
+        }
+    }
+
+    /// Forward the presence messages arriving on `socket`, ignoring anything else
+    async fn receive(socket: Arc<UdpSocket>, sender: broadcast::Sender<Presence>) {
+        let mut buffer = vec![0u8; MAX_MESSAGE_SIZE];
+        loop {
+            match socket.recv_from(&mut buffer).await {
+                Ok((len, _)) => {
+                    if let Some(presence) = std::str::from_utf8(&buffer[..len])
+                        .ok()
+                        .and_then(Presence::from_json)
+                    {
+                        let _ = sender.send(presence);
+                    }
+                }
+                Err(e) => {
+                    warn!("[Presence] Stopped receiving presence: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Who this client is and which channel it publishes on
+#[derive(Clone)]
+pub struct PresenceConfig {
+    /// Name shown next to this client's cursor on other clients
+    pub user_name: String,
+    pub channel: Arc<dyn PresenceChannel>,
+    /// Time after which a client that stopped publishing is dropped
+    pub timeout: Duration,
+}
+
+impl PresenceConfig {
+    pub fn new(user_name: impl Into<String>, channel: Arc<dyn PresenceChannel>) -> Self {
+        Self {
+            user_name: user_name.into(),
+            channel,
+            timeout: DEFAULT_PRESENCE_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Presence of this client and the others on its channel, kept in the `presence` table
+pub struct PresenceTracker {
+    backend: Arc<RwLock<TursoBackend>>,
+    config: PresenceConfig,
+    client_id: String,
+    /// Latest presence of every client, including this one, by client id
+    clients: StdMutex<HashMap<String, Presence>>,
+}
+
+impl PresenceTracker {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>, config: PresenceConfig) -> Self {
+        Self {
+            backend,
+            config,
+            client_id: uuid::Uuid::new_v4().to_string(),
+            clients: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    /// Identifies this client on the channel; new for every tracker
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    /// Create the presence table, dropping presence left from a previous run
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "CREATE TABLE IF NOT EXISTS presence (
+                    id TEXT PRIMARY KEY,
+                    user_name TEXT NOT NULL,
+                    view_id TEXT,
+                    focused_id TEXT,
+                    selection TEXT NOT NULL,
+                    updated_at INTEGER NOT NULL
+                )",
+                HashMap::new(),
+            )
+            .await
+            .map_err(|e| format!("Failed to create presence table: {}", e))?;
+        backend
+            .execute_sql("DELETE FROM presence", HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to clear presence table: {}", e))?;
+
+        info!("Presence schema initialized");
+        Ok(())
+    }
+
+    /// Publish what this client shows: the view, the focused entity and the selection
+    pub async fn publish(
+        &self,
+        view_id: Option<&str>,
+        focused_id: Option<&str>,
+        selection: &[String],
+        now: i64,
+    ) -> Result<()> {
+        let presence = Presence {
+            client_id: self.client_id.clone(),
+            user_name: self.config.user_name.clone(),
+            view_id: view_id.map(str::to_string),
+            focused_id: focused_id.map(str::to_string),
+            selection: selection.to_vec(),
+            updated_at: now,
+        };
+        self.receive(presence.clone()).await?;
+        self.config.channel.publish(&presence).await
+    }
+
+    /// Record presence received from the channel
+    ///
+    /// Presence older than what is known of the client (e.g. reordered datagrams)
+    /// is ignored.
+    pub async fn receive(&self, presence: Presence) -> Result<()> {
+        {
+            let mut clients = self.clients.lock().unwrap();
+            if let Some(known) = clients.get(&presence.client_id) {
+                if known.updated_at > presence.updated_at || *known == presence {
+                    return Ok(());
+                }
+            }
+            clients.insert(presence.client_id.clone(), presence.clone());
+        }
+
+        let selection = serde_json::to_string(&presence.selection)?;
+        let optional = |value: Option<String>| value.map(Value::String).unwrap_or(Value::Null);
+        let params = HashMap::from([
+            ("id".to_string(), Value::String(presence.client_id)),
+            ("user_name".to_string(), Value::String(presence.user_name)),
+            ("view_id".to_string(), optional(presence.view_id)),
+            ("focused_id".to_string(), optional(presence.focused_id)),
+            ("selection".to_string(), Value::String(selection)),
+            (
+                "updated_at".to_string(),
+                Value::Integer(presence.updated_at),
+            ),
+        ]);
+        self.backend
+            .read()
+            .await
+            .execute_sql(
+                "INSERT INTO presence (id, user_name, view_id, focused_id, selection, updated_at)
+                VALUES ($id, $user_name, $view_id, $focused_id, $selection, $updated_at)
+                ON CONFLICT(id) DO UPDATE SET
+                    user_name = excluded.user_name,
+                    view_id = excluded.view_id,
+                    focused_id = excluded.focused_id,
+                    selection = excluded.selection,
+                    updated_at = excluded.updated_at",
+                params,
+            )
+            .await
+            .map_err(|e| format!("Failed to save presence: {}", e))?;
+        Ok(())
+    }
+
+    /// Drop the other clients not heard from within the timeout; returns how many
+    pub async fn expire(&self, now: i64) -> Result<usize> {
+        let cutoff = now - self.config.timeout.as_millis() as i64;
+        let expired: Vec<String> = {
+            let mut clients = self.clients.lock().unwrap();
+            let expired: Vec<String> = clients
+                .values()
+                .filter(|p| p.client_id != self.client_id && p.updated_at < cutoff)
+                .map(|p| p.client_id.clone())
+                .collect();
+            for client_id in &expired {
+                clients.remove(client_id);
+            }
+            expired
+        };
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        let mut params = HashMap::new();
+        let placeholders: Vec<String> = expired
+            .iter()
+            .enumerate()
+            .map(|(i, client_id)| {
+                params.insert(format!("id_{}", i), Value::String(client_id.clone()));
+                format!("$id_{}", i)
+            })
+            .collect();
+        self.backend
+            .read()
+            .await
+            .execute_sql(
+                &format!(
+                    "DELETE FROM presence WHERE id IN ({})",
+                    placeholders.join(", ")
+                ),
+                params,
+            )
+            .await
+            .map_err(|e| format!("Failed to drop presence: {}", e))?;
+        debug!("Dropped the presence of {} clients", expired.len());
+        Ok(expired.len())
+    }
+
+    /// Presence of the other clients, showing `view_id` if given, by user name
+    pub fn peers(&self, view_id: Option<&str>) -> Vec<Presence> {
+        let mut peers: Vec<Presence> = self
+            .clients
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|p| p.client_id != self.client_id)
+            .filter(|p| view_id.is_none() || p.view_id.as_deref() == view_id)
+            .cloned()
+            .collect();
+        peers.sort_by(|a, b| (&a.user_name, &a.client_id).cmp(&(&b.user_name, &b.client_id)));
+        peers
+    }
+
+    /// Receive presence from the channel, republish this client's presence every
+    /// `PRESENCE_HEARTBEAT` and drop clients that stopped publishing
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let mut received = self.config.channel.subscribe();
+        let tracker = Arc::downgrade(&self);
+        drop(self);
+        tokio::spawn(async move {
+            if let Some(tracker) = tracker.upgrade() {
+                if let Err(e) = tracker.initialize_schema().await {
+                    tracing::warn!("[Presence] {}", e);
+                    return;
+                }
+            }
+            let mut heartbeat = tokio::time::interval(PRESENCE_HEARTBEAT);
+            loop {
+                let message = tokio::select! {
+                    message = received.recv() => Some(message),
+                    _ = heartbeat.tick() => None,
+                };
+                let Some(tracker) = tracker.upgrade() else {
+                    break;
+                };
+                let result = match message {
+                    Some(Ok(presence)) => tracker.receive(presence).await,
+                    Some(Err(broadcast::error::RecvError::Lagged(skipped))) => {
+                        debug!("[Presence] Skipped {} presence messages", skipped);
+                        Ok(())
+                    }
+                    Some(Err(broadcast::error::RecvError::Closed)) => break,
+                    None => {
+                        tracker
+                            .heartbeat(chrono::Utc::now().timestamp_millis())
+                            .await
+                    }
+                };
+                if let Err(e) = result {
+                    tracing::warn!("[Presence] {}", e);
+                }
+            }
+        })
+    }
+
+    /// Republish this client's presence and drop clients that stopped publishing
+    async fn heartbeat(&self, now: i64) -> Result<()> {
+        let own = self.clients.lock().unwrap().get(&self.client_id).cloned();
+        if let Some(presence) = own {
+            self.publish(
+                presence.view_id.as_deref(),
+                presence.focused_id.as_deref(),
+                &presence.selection,
+                now,
+            )
+            .await?;
+        }
+        self.expire(now).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    async fn tracker(channel: &LocalPresenceChannel, user_name: &str) -> PresenceTracker {
+        let backend = memory_backend().await;
+        let tracker = PresenceTracker::new(
+            backend,
+            PresenceConfig::new(user_name, Arc::new(channel.clone()))
+                .with_timeout(Duration::from_secs(30)),
+        );
+        tracker.initialize_schema().await.unwrap();
+        tracker
+    }
+
+    async fn stored_focus(tracker: &PresenceTracker) -> Vec<(String, Value)> {
+        let rows = tracker
+            .backend
+            .read()
+            .await
+            .execute_sql(
+                "SELECT user_name, focused_id FROM presence ORDER BY user_name",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        rows.into_iter()
+            .map(|row| {
+                (
+                    row["user_name"].as_string().unwrap().to_string(),
+                    row["focused_id"].clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_presence_is_shared_and_expires() {
+        let channel = LocalPresenceChannel::default();
+        let ada = tracker(&channel, "Ada").await;
+        let bo = tracker(&channel, "Bo").await;
+        let mut received = channel.subscribe();
+
+        ada.publish(Some("inbox"), Some("task-1"), &[], 1_000)
+            .await
+            .unwrap();
+        bo.receive(received.recv().await.unwrap()).await.unwrap();
+        bo.publish(Some("outline"), None, &["block-1".to_string()], 2_000)
+            .await
+            .unwrap();
+        ada.receive(received.recv().await.unwrap()).await.unwrap();
+
+        let peers = bo.peers(None);
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].user_name, "Ada");
+        assert!(peers[0].is_on("task-1"));
+        assert!(bo.peers(Some("outline")).is_empty());
+        assert_eq!(ada.peers(Some("outline"))[0].selection, vec!["block-1"]);
+        assert_eq!(
+            stored_focus(&bo).await,
+            vec![
+                ("Ada".to_string(), Value::String("task-1".to_string())),
+                ("Bo".to_string(), Value::Null),
+            ]
+        );
+
+        // Older presence doesn't override newer
+        let mut stale = peers[0].clone();
+        stale.focused_id = Some("task-0".to_string());
+        stale.updated_at = 500;
+        bo.receive(stale).await.unwrap();
+        assert!(bo.peers(None)[0].is_on("task-1"));
+
+        // Ada stopped publishing; Bo's own presence stays
+        assert_eq!(bo.expire(31_500).await.unwrap(), 1);
+        assert!(bo.peers(None).is_empty());
+        assert_eq!(
+            stored_focus(&bo).await,
+            vec![("Bo".to_string(), Value::Null)]
+        );
+    }
+}
@@ -4,23 +4,27 @@ use std::marker::PhantomData;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{RwLock, broadcast};
 use tokio_stream::Stream;
 use tracing;
 
+use super::custom_fields::CustomFieldStore;
 use super::datasource::{
-    CrudOperations, DataSource, OperationDescriptor, OperationProvider, OperationRegistry,
-    UndoAction,
+    CrudOperations, DataSource, HolonResult, OperationDescriptor, OperationProvider,
+    OperationRegistry, UndoAction,
 };
 use super::traits::{HasSchema, Predicate, Queryable, Result, Schema};
 use crate::storage::turso::TursoBackend;
 use crate::storage::types::StorageEntity;
-use holon_api::streaming::ChangeNotifications;
+use crate::sync::conflicts::SyncReconciler;
 use holon_api::DynamicEntity;
-use holon_api::{ApiError, Change, StreamPosition};
+use holon_api::streaming::ChangeNotifications;
 use holon_api::{
-    BatchMetadata, ChangeOrigin, SyncTokenUpdate, Value, WithMetadata, CHANGE_ORIGIN_COLUMN,
+    ARCHIVED_AT_COLUMN, BatchMetadata, CHANGE_ORIGIN_COLUMN, ChangeOrigin, DELETED_AT_COLUMN,
+    SyncTokenUpdate, Value, WithMetadata,
 };
+use holon_api::{ApiError, Change, StreamPosition, ValidationErrors};
+use holon_core::__operations_crud_operations;
 
 pub struct QueryableCache<S, T>
 where
@@ -33,6 +37,8 @@ where
     // CRITICAL: This must stay alive for CDC callbacks to work
     // The callback closure captures the channel sender, which closes the stream if dropped
     _cdc_conn: Option<Arc<tokio::sync::Mutex<turso::Connection>>>,
+    // Resolves conflicts of ingested remote changes with pending local changes
+    reconciler: Option<Arc<SyncReconciler>>,
     _phantom: PhantomData<T>,
 }
 
@@ -49,6 +55,7 @@ where
             source: Arc::new(source),
             backend,
             _cdc_conn: None, // Will be initialized when watch_changes_since is called
+            reconciler: None,
             _phantom: PhantomData,
         };
 
@@ -56,6 +63,12 @@ where
         Ok(cache)
     }
 
+    /// Reconcile ingested remote changes with pending local changes before applying them
+    pub fn with_reconciler(mut self, reconciler: Arc<SyncReconciler>) -> Self {
+        self.reconciler = Some(reconciler);
+        self
+    }
+
     // Keep old methods for backward compatibility during transition
     #[allow(dead_code)]
     pub async fn new(source: S) -> Result<Self> {
@@ -134,6 +147,30 @@ where
                 .map_err(|e| format!("Failed to create index: {}", e))?;
         }
 
+        backend
+            .enable_soft_delete(table_name, T::entity_name())
+            .await
+            .map_err(|e| format!("Failed to enable soft delete: {}", e))?;
+        backend
+            .computed_fields()
+            .register_schema(&schema)
+            .map_err(|e| format!("Failed to register computed fields: {}", e))?;
+        backend
+            .rollups()
+            .register_schema(&backend, &schema)
+            .await
+            .map_err(|e| format!("Failed to register rollups: {}", e))?;
+        backend
+            .urgency_scores()
+            .register_schema(&backend, &schema)
+            .await
+            .map_err(|e| format!("Failed to register urgency scoring: {}", e))?;
+        backend
+            .stats()
+            .register_schema(&backend, &schema)
+            .await
+            .map_err(|e| format!("Failed to register statistics: {}", e))?;
+
         let autocommit_final = conn.is_autocommit().unwrap_or(true);
         tracing::debug!(
             "[QueryableCache] initialize_schema completed for '{}'. Autocommit: {}",
@@ -174,7 +211,7 @@ where
         let mut placeholders = Vec::new();
         let mut values = Vec::new();
 
-        for field in &schema.fields {
+        for field in schema.stored_fields() {
             if let Some(value) = entity.fields.get(&field.name) {
                 columns.push(field.name.clone());
                 placeholders.push("?");
@@ -284,6 +321,38 @@ where
         Ok(())
     }
 
+    /// Set (trash, archive) or clear (restore, unarchive) a storage-managed marker
+    /// column of a row, i.e. `deleted_at` or `archived_at`
+    async fn set_marker(&self, column: &str, id: &str, at: Option<i64>) -> Result<()> {
+        let backend = self.backend.read().await;
+        let conn = backend
+            .get_connection()
+            .map_err(|e| format!("Failed to get connection: {}", e))?;
+
+        let schema = T::schema();
+        let id_field = schema
+            .fields
+            .iter()
+            .find(|f| f.primary_key)
+            .map(|f| f.name.as_str())
+            .unwrap_or("id");
+
+        let sql = format!(
+            "UPDATE {} SET {} = ? WHERE {} = ?",
+            schema.table_name, column, id_field
+        );
+        let at = at.map(turso::Value::Integer).unwrap_or(turso::Value::Null);
+        let updated = conn
+            .execute(&sql, [at, turso::Value::Text(id.to_string())])
+            .await
+            .map_err(|e| format!("Failed to update {}: {}", column, e))?;
+
+        if updated == 0 {
+            return Err(format!("Entity not found in {}: {}", schema.table_name, id).into());
+        }
+        Ok(())
+    }
+
     /// Wire up stream ingestion from a broadcast receiver (spawns background task)
     ///
     /// This method subscribes to a broadcast channel and updates the local cache
@@ -423,6 +492,7 @@ where
         T: Clone + Send + Sync + 'static,
     {
         let backend = Arc::clone(&self.backend);
+        let reconciler = self.reconciler.clone();
         let schema = T::schema();
         let table_name = schema.table_name.clone();
         let id_field = schema
@@ -441,8 +511,50 @@ where
             loop {
                 match rx.recv().await {
                     Ok(batch_with_metadata) => {
-                        let changes = &batch_with_metadata.inner;
                         let sync_token = batch_with_metadata.metadata.sync_token.clone();
+                        let mut changes = batch_with_metadata.inner;
+
+                        // A full snapshot only touches rows that actually changed
+                        if batch_with_metadata.metadata.full_snapshot {
+                            match Self::snapshot_to_deltas(
+                                &backend,
+                                &table_name,
+                                &id_field,
+                                &changes,
+                            )
+                            .await
+                            {
+                                Ok(deltas) => {
+                                    tracing::info!(
+                                        "[QueryableCache] Full snapshot of {} entities for table {} reduced to {} changes",
+                                        changes.len(),
+                                        table_name,
+                                        deltas.len()
+                                    );
+                                    changes = deltas;
+                                }
+                                Err(e) => {
+                                    tracing::warn!(
+                                        "[QueryableCache] Failed to diff full snapshot for table {}, applying it as-is: {}",
+                                        table_name,
+                                        e
+                                    );
+                                }
+                            }
+                        }
+                        if let Some(reconciler) = &reconciler {
+                            match reconciler.reconcile(changes.clone()).await {
+                                Ok(reconciled) => changes = reconciled,
+                                Err(e) => {
+                                    tracing::warn!(
+                                        "[QueryableCache] Failed to reconcile changes for table {}, applying them as-is: {}",
+                                        table_name,
+                                        e
+                                    );
+                                }
+                            }
+                        }
+                        let changes = &changes;
                         let change_count = changes.len();
 
                         tracing::info!(
@@ -504,6 +616,76 @@ where
         });
     }
 
+    /// Reduce a full snapshot of the relation to the changes it implies
+    ///
+    /// Entities identical to their cached row are dropped, so unchanged rows are not
+    /// rewritten (and produce no CDC events). Cached rows missing from the snapshot
+    /// become `Deleted` changes.
+    async fn snapshot_to_deltas(
+        backend: &Arc<RwLock<TursoBackend>>,
+        table_name: &str,
+        id_field: &str,
+        snapshot: &[Change<T>],
+    ) -> Result<Vec<Change<T>>>
+    where
+        T: HasSchema + Clone,
+    {
+        let rows = backend
+            .read()
+            .await
+            .execute_sql(&format!("SELECT * FROM {}", table_name), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to read {} for snapshot diff: {}", table_name, e))?;
+        let mut cached: HashMap<String, StorageEntity> = rows
+            .into_iter()
+            .filter_map(|row| {
+                let id = row.get(id_field)?.as_string()?.to_string();
+                Some((id, row))
+            })
+            .collect();
+
+        let schema = T::schema();
+        let mut deltas = Vec::new();
+        let mut delete_origin = None;
+        for change in snapshot {
+            match change {
+                Change::Created { data, origin } | Change::Updated { data, origin, .. } => {
+                    delete_origin.get_or_insert_with(|| origin.clone());
+                    let entity = data.to_entity();
+                    let unchanged = entity
+                        .get_string(id_field)
+                        .and_then(|id| cached.remove(&id))
+                        .is_some_and(|row| {
+                            schema.stored_fields().all(|field| {
+                                stored_value(row.get(&field.name))
+                                    == stored_value(entity.get(&field.name))
+                            })
+                        });
+                    if !unchanged {
+                        deltas.push(change.clone());
+                    }
+                }
+                Change::Deleted { id, .. } => {
+                    if cached.remove(id).is_some() {
+                        deltas.push(change.clone());
+                    }
+                }
+                Change::ColumnChange { .. } => deltas.push(change.clone()),
+            }
+        }
+
+        let delete_origin = delete_origin.unwrap_or(ChangeOrigin::Remote {
+            operation_id: None,
+            trace_id: None,
+            source: None,
+        });
+        deltas.extend(cached.into_keys().map(|id| Change::Deleted {
+            id,
+            origin: delete_origin.clone(),
+        }));
+        Ok(deltas)
+    }
+
     // Helper method for applying a batch of changes to cache in a single transaction
     // This reduces database lock contention by processing all changes atomically
     // Includes retry logic with exponential backoff for "database is locked" errors
@@ -688,8 +870,7 @@ where
         // Build SQL templates and prepare statements ONCE before the loop
         let schema = T::schema();
         let columns: Vec<String> = schema
-            .fields
-            .iter()
+            .stored_fields()
             .map(|f| f.name.clone())
             .chain(std::iter::once(CHANGE_ORIGIN_COLUMN.to_string()))
             .collect();
@@ -724,7 +905,7 @@ where
 
                     // Extract values in the same order as columns
                     let mut values: Vec<turso::Value> = Vec::with_capacity(columns.len());
-                    for field in &schema.fields {
+                    for field in schema.stored_fields() {
                         let libsql_value = match entity.fields.get(&field.name) {
                             Some(Value::String(s)) => turso::Value::Text(s.clone()),
                             Some(Value::Integer(i)) => turso::Value::Integer(*i),
@@ -797,6 +978,40 @@ where
                         }
                     }
                 }
+                Change::ColumnChange {
+                    id,
+                    columns: changed,
+                    origin,
+                } => {
+                    let Some((sql, values)) = generate_column_update_sql(
+                        &schema,
+                        table_name,
+                        id_field,
+                        id,
+                        changed,
+                        Some(origin),
+                    ) else {
+                        continue;
+                    };
+
+                    match conn.prepare(&sql).await {
+                        Ok(mut stmt) => match stmt.execute(turso::params_from_iter(values)).await {
+                            Ok(_) => {
+                                ops_executed += 1;
+                            }
+                            Err(e) => {
+                                error_count += 1;
+                                last_error = Some(e.to_string());
+                                tracing::error!("[TX] Error in batch column update execute: {}", e);
+                            }
+                        },
+                        Err(e) => {
+                            error_count += 1;
+                            last_error = Some(e.to_string());
+                            tracing::error!("[TX] Error in batch column update prepare: {}", e);
+                        }
+                    }
+                }
             }
         }
 
@@ -963,7 +1178,7 @@ where
                     let mut placeholders = Vec::new();
                     let mut values = Vec::new();
 
-                    for field in &schema.fields {
+                    for field in schema.stored_fields() {
                         if let Some(value) = entity.fields.get(&field.name) {
                             columns.push(field.name.clone());
                             placeholders.push("?");
@@ -1012,6 +1227,24 @@ where
                         tracing::error!("[QueryableCache] Error in batch delete: {}", e);
                     }
                 }
+                Change::ColumnChange { id, columns, .. } => {
+                    let Some((sql, values)) = generate_column_update_sql(
+                        &T::schema(),
+                        table_name,
+                        id_field,
+                        id,
+                        columns,
+                        None,
+                    ) else {
+                        continue;
+                    };
+
+                    if let Err(e) = conn.execute(&sql, turso::params_from_iter(values)).await {
+                        error_count += 1;
+                        last_error = Some(e.to_string());
+                        tracing::error!("[QueryableCache] Error in batch column update: {}", e);
+                    }
+                }
             }
         }
 
@@ -1089,7 +1322,7 @@ where
     S: DataSource<T> + CrudOperations<T>,
     T: HasSchema + Send + Sync + 'static,
 {
-    async fn set_field(&self, id: &str, field: &str, value: Value) -> Result<UndoAction> {
+    async fn set_field(&self, id: &str, field: &str, value: Value) -> HolonResult<UndoAction> {
         let expected_value = value.clone();
         tracing::info!(
             "[QueryableCache] set_field request: entity={} field={} value={:?}",
@@ -1098,6 +1331,10 @@ where
             expected_value
         );
 
+        let mut partial = DynamicEntity::new(T::schema().table_name);
+        partial.set(field, value.clone());
+        T::validate_entity(&partial).map_err(ValidationErrors)?;
+
         // Source now returns the undo action
         let undo_action = self.source.set_field(id, field, value).await?;
 
@@ -1129,7 +1366,13 @@ where
         Ok(undo_action)
     }
 
-    async fn create(&self, fields: HashMap<String, Value>) -> Result<(String, UndoAction)> {
+    async fn create(&self, fields: HashMap<String, Value>) -> HolonResult<(String, UndoAction)> {
+        let candidate = DynamicEntity {
+            type_name: T::schema().table_name,
+            fields: fields.clone(),
+        };
+        T::validate_entity(&candidate).map_err(ValidationErrors)?;
+
         // Source now returns (id, undo_action)
         let (id, undo_action) = self.source.create(fields).await?;
         // Update cache if we have the item
@@ -1139,12 +1382,84 @@ where
         Ok((id, undo_action))
     }
 
-    async fn delete(&self, id: &str) -> Result<UndoAction> {
+    async fn delete(&self, id: &str) -> HolonResult<UndoAction> {
         // Source now returns the undo action
         let undo_action = self.source.delete(id).await?;
         let _ = self.delete_from_cache(id).await;
         Ok(undo_action)
     }
+
+    // Trash is storage-managed: the source keeps the entity until it is purged
+    async fn trash(&self, id: &str) -> HolonResult<UndoAction> {
+        self.set_marker(
+            DELETED_AT_COLUMN,
+            id,
+            Some(chrono::Utc::now().timestamp_millis()),
+        )
+        .await?;
+        Ok(UndoAction::Undo(__operations_crud_operations::restore_op(
+            "", // Will be set by OperationProvider
+            id,
+        )))
+    }
+
+    async fn restore(&self, id: &str) -> HolonResult<UndoAction> {
+        self.set_marker(DELETED_AT_COLUMN, id, None).await?;
+        Ok(UndoAction::Undo(__operations_crud_operations::trash_op(
+            "", // Will be set by OperationProvider
+            id,
+        )))
+    }
+
+    // Archival is storage-managed too; sources with an archive of their own are told first
+    async fn archive(&self, id: &str) -> HolonResult<UndoAction> {
+        self.source.archive(id).await?;
+        self.set_marker(
+            ARCHIVED_AT_COLUMN,
+            id,
+            Some(chrono::Utc::now().timestamp_millis()),
+        )
+        .await?;
+        Ok(UndoAction::Undo(
+            __operations_crud_operations::unarchive_op(
+                "", // Will be set by OperationProvider
+                id,
+            ),
+        ))
+    }
+
+    async fn unarchive(&self, id: &str) -> HolonResult<UndoAction> {
+        self.source.unarchive(id).await?;
+        self.set_marker(ARCHIVED_AT_COLUMN, id, None).await?;
+        Ok(UndoAction::Undo(__operations_crud_operations::archive_op(
+            "", // Will be set by OperationProvider
+            id,
+        )))
+    }
+
+    // Custom fields live in the custom_field_values side table, not in the source
+    async fn set_custom_field(
+        &self,
+        id: &str,
+        field: &str,
+        value: Value,
+    ) -> HolonResult<UndoAction> {
+        let previous = CustomFieldStore::new(self.backend.clone())
+            .set_value(
+                &T::schema().table_name,
+                id,
+                field,
+                &value,
+                chrono::Utc::now().timestamp_millis(),
+            )
+            .await?;
+        Ok(UndoAction::Undo(
+            __operations_crud_operations::set_custom_field_op(
+                "", // Will be set by OperationProvider
+                id, field, previous,
+            ),
+        ))
+    }
 }
 
 // Implement OperationProvider for QueryableCache
@@ -1194,25 +1509,13 @@ where
                 // set_field returns UndoAction
                 let undo_action = self.set_field(&id, &field, value).await?;
                 // Set entity_name on the inverse operation if present
-                Ok(match undo_action {
-                    UndoAction::Undo(mut op) => {
-                        op.entity_name = entity_name.to_string();
-                        UndoAction::Undo(op)
-                    }
-                    UndoAction::Irreversible => UndoAction::Irreversible,
-                })
+                Ok(undo_action.with_entity_name(entity_name))
             }
             "create" => {
                 // Create expects fields as params (excluding id which is generated)
                 let (_id, undo_action) = self.create(params).await?;
                 // Set entity_name on the inverse operation if present
-                Ok(match undo_action {
-                    UndoAction::Undo(mut op) => {
-                        op.entity_name = entity_name.to_string();
-                        UndoAction::Undo(op)
-                    }
-                    UndoAction::Irreversible => UndoAction::Irreversible,
-                })
+                Ok(undo_action.with_entity_name(entity_name))
             }
             "delete" => {
                 let id = params
@@ -1222,13 +1525,47 @@ where
                 // delete returns UndoAction
                 let undo_action = self.delete(&id).await?;
                 // Set entity_name on the inverse operation if present
-                Ok(match undo_action {
-                    UndoAction::Undo(mut op) => {
-                        op.entity_name = entity_name.to_string();
-                        UndoAction::Undo(op)
-                    }
-                    UndoAction::Irreversible => UndoAction::Irreversible,
-                })
+                Ok(undo_action.with_entity_name(entity_name))
+            }
+            "trash" | "restore" => {
+                let id = params
+                    .get("id")
+                    .and_then(|v| v.as_string())
+                    .ok_or_else(|| "Missing 'id' parameter".to_string())?;
+                let undo_action = if op_name == "trash" {
+                    self.trash(&id).await?
+                } else {
+                    self.restore(&id).await?
+                };
+                // Set entity_name on the inverse operation if present
+                Ok(undo_action.with_entity_name(entity_name))
+            }
+            "archive" | "unarchive" => {
+                let id = params
+                    .get("id")
+                    .and_then(|v| v.as_string())
+                    .ok_or_else(|| "Missing 'id' parameter".to_string())?;
+                let undo_action = if op_name == "archive" {
+                    self.archive(&id).await?
+                } else {
+                    self.unarchive(&id).await?
+                };
+                // Set entity_name on the inverse operation if present
+                Ok(undo_action.with_entity_name(entity_name))
+            }
+            "set_custom_field" => {
+                let id = params
+                    .get("id")
+                    .and_then(|v| v.as_string())
+                    .ok_or_else(|| "Missing 'id' parameter".to_string())?;
+                let field = params
+                    .get("field")
+                    .and_then(|v| v.as_string())
+                    .ok_or_else(|| "Missing 'field' parameter".to_string())?;
+                let value = params.get("value").cloned().unwrap_or(Value::Null);
+                let undo_action = self.set_custom_field(&id, &field, value).await?;
+                // Set entity_name on the inverse operation if present
+                Ok(undo_action.with_entity_name(entity_name))
             }
             _ => {
                 let refresh_id = params
@@ -1416,7 +1753,9 @@ where
                 for row_change in &batch.inner.items {
                     match &row_change.change {
                         ChangeData::Created { .. } => created_count += 1,
-                        ChangeData::Updated { .. } => updated_count += 1,
+                        ChangeData::Updated { .. } | ChangeData::ColumnChange { .. } => {
+                            updated_count += 1
+                        }
                         ChangeData::Deleted { .. } => deleted_count += 1,
                     }
                 }
@@ -1485,6 +1824,16 @@ where
                                 origin,
                             }
                         }
+                        // Column changes already carry the entity ID
+                        ChangeData::ColumnChange {
+                            id,
+                            columns,
+                            origin,
+                        } => Change::ColumnChange {
+                            id,
+                            columns,
+                            origin,
+                        },
                     };
                     results.push(result);
                 }
@@ -1501,6 +1850,15 @@ where
     }
 }
 
+/// A value as the batch upsert stores it (booleans as integers, other non-scalars as NULL)
+pub(crate) fn stored_value(value: Option<&Value>) -> Value {
+    match value {
+        Some(Value::Boolean(b)) => Value::Integer(i64::from(*b)),
+        Some(v @ (Value::String(_) | Value::Integer(_) | Value::Float(_))) => v.clone(),
+        _ => Value::Null,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1686,6 +2044,89 @@ mod tests {
         assert_eq!(retrieved, Some(task));
     }
 
+    #[tokio::test]
+    async fn test_trash_and_restore() {
+        let source = InMemoryDataSource::new();
+        let cache = QueryableCache::with_database(source, ":memory:")
+            .await
+            .unwrap();
+
+        let mut fields = HashMap::new();
+        fields.insert("id".to_string(), Value::String("1".to_string()));
+        fields.insert("title".to_string(), Value::String("Task".to_string()));
+        fields.insert("priority".to_string(), Value::Integer(1));
+        cache.create(fields).await.unwrap();
+
+        let deleted_at = |cache: &QueryableCache<InMemoryDataSource, TestTask>| {
+            let backend = cache.backend.clone();
+            async move {
+                let rows = backend
+                    .read()
+                    .await
+                    .execute_sql(
+                        "SELECT deleted_at FROM test_tasks WHERE id = '1'",
+                        HashMap::new(),
+                    )
+                    .await
+                    .unwrap();
+                rows[0].get("deleted_at").cloned().unwrap_or(Value::Null)
+            }
+        };
+
+        let undo = cache.trash("1").await.unwrap();
+        assert!(matches!(undo, UndoAction::Undo(ref op) if op.op_name == "restore"));
+        assert!(matches!(deleted_at(&cache).await, Value::Integer(_)));
+        // Trash keeps the entity in the source
+        assert!(cache.source.get_by_id("1").await.unwrap().is_some());
+
+        let undo = cache.restore("1").await.unwrap();
+        assert!(matches!(undo, UndoAction::Undo(ref op) if op.op_name == "trash"));
+        assert_eq!(deleted_at(&cache).await, Value::Null);
+
+        assert!(cache.trash("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_archive_and_unarchive() {
+        let source = InMemoryDataSource::new();
+        let cache = QueryableCache::with_database(source, ":memory:")
+            .await
+            .unwrap();
+
+        let mut fields = HashMap::new();
+        fields.insert("id".to_string(), Value::String("1".to_string()));
+        fields.insert("title".to_string(), Value::String("Task".to_string()));
+        fields.insert("priority".to_string(), Value::Integer(1));
+        cache.create(fields).await.unwrap();
+
+        let archived_at = |cache: &QueryableCache<InMemoryDataSource, TestTask>| {
+            let backend = cache.backend.clone();
+            async move {
+                let rows = backend
+                    .read()
+                    .await
+                    .execute_sql(
+                        "SELECT archived_at FROM test_tasks WHERE id = '1'",
+                        HashMap::new(),
+                    )
+                    .await
+                    .unwrap();
+                rows[0].get("archived_at").cloned().unwrap_or(Value::Null)
+            }
+        };
+
+        let undo = cache.archive("1").await.unwrap();
+        assert!(matches!(undo, UndoAction::Undo(ref op) if op.op_name == "unarchive"));
+        assert!(matches!(archived_at(&cache).await, Value::Integer(_)));
+        assert!(cache.source.get_by_id("1").await.unwrap().is_some());
+
+        let undo = cache.unarchive("1").await.unwrap();
+        assert!(matches!(undo, UndoAction::Undo(ref op) if op.op_name == "archive"));
+        assert_eq!(archived_at(&cache).await, Value::Null);
+
+        assert!(cache.archive("missing").await.is_err());
+    }
+
     #[tokio::test]
     async fn test_sync() {
         let source = InMemoryDataSource::new();
@@ -1773,6 +2214,50 @@ mod tests {
         let deleted = cache.get_by_id("1").await.unwrap();
         assert!(deleted.is_none());
     }
+
+    #[tokio::test]
+    async fn test_full_snapshot_reduced_to_deltas() {
+        let cache = QueryableCache::with_database(InMemoryDataSource::new(), ":memory:")
+            .await
+            .unwrap();
+        let origin = ChangeOrigin::Remote {
+            operation_id: None,
+            trace_id: None,
+            source: None,
+        };
+        let task = |id: &str, title: &str| TestTask {
+            id: id.to_string(),
+            title: title.to_string(),
+            priority: 1,
+        };
+        let updated = |task: TestTask| Change::Updated {
+            id: task.id.clone(),
+            data: task,
+            origin: origin.clone(),
+        };
+
+        cache
+            .apply_batch(
+                &[updated(task("1", "Same")), updated(task("2", "Gone"))],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let snapshot = [updated(task("1", "Same")), updated(task("3", "New"))];
+        let deltas = QueryableCache::<InMemoryDataSource, TestTask>::snapshot_to_deltas(
+            &cache.backend,
+            "test_tasks",
+            "id",
+            &snapshot,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(deltas.len(), 2);
+        assert!(matches!(&deltas[0], Change::Updated { id, .. } if id == "3"));
+        assert!(matches!(&deltas[1], Change::Deleted { id, .. } if id == "2"));
+    }
 }
 
 /// Generate CREATE TABLE SQL with automatic `_change_origin` column
@@ -1790,7 +2275,8 @@ fn generate_create_table_sql_with_change_origin(schema: &Schema) -> String {
             col.push_str(" PRIMARY KEY");
         }
 
-        if !field.nullable {
+        // Computed fields are filled in after the row is written
+        if !field.nullable && field.computed.is_none() {
             col.push_str(" NOT NULL");
         }
 
@@ -1806,3 +2292,51 @@ fn generate_create_table_sql_with_change_origin(schema: &Schema) -> String {
         columns.join(",\n  ")
     )
 }
+
+/// Build an UPDATE touching only the changed columns of a row
+///
+/// Columns that aren't part of the schema are skipped. Returns None if nothing
+/// is left to update.
+fn generate_column_update_sql(
+    schema: &Schema,
+    table_name: &str,
+    id_field: &str,
+    id: &str,
+    columns: &HashMap<String, Value>,
+    origin: Option<&ChangeOrigin>,
+) -> Option<(String, Vec<turso::Value>)> {
+    let mut assignments = Vec::new();
+    let mut values = Vec::new();
+
+    for field in schema.stored_fields() {
+        if field.name == id_field {
+            continue;
+        }
+        if let Some(value) = columns.get(&field.name) {
+            assignments.push(format!("{} = ?", field.name));
+            values.push(match value {
+                Value::String(s) => turso::Value::Text(s.clone()),
+                Value::Integer(i) => turso::Value::Integer(*i),
+                Value::Float(f) => turso::Value::Real(*f),
+                Value::Boolean(b) => turso::Value::Integer(if *b { 1 } else { 0 }),
+                _ => turso::Value::Null,
+            });
+        }
+    }
+    if assignments.is_empty() {
+        return None;
+    }
+    if let Some(origin) = origin {
+        assignments.push(format!("{} = ?", CHANGE_ORIGIN_COLUMN));
+        values.push(turso::Value::Text(origin.to_json()));
+    }
+    values.push(turso::Value::Text(id.to_string()));
+
+    let sql = format!(
+        "UPDATE {} SET {} WHERE {} = ?",
+        table_name,
+        assignments.join(", "),
+        id_field
+    );
+    Some((sql, values))
+}
@@ -0,0 +1,396 @@
+//! Row-level security for deployments shared by several people (e.g. a household)
+//!
+//! A `RowSecurityConfig` attaches a row filter to the tables of registered
+//! datasources, e.g. `assignee == @current_user` for `todoist_tasks`, where
+//! `@current_user` is the user the engine runs for. The query compiler adds the
+//! filter to every read of the table, and the dispatcher checks operations with
+//! `RowSecurity::check_operation` before they run:
+//!
+//! - an operation on a row the filter hides fails as if the row didn't exist
+//! - `create` fails unless the new row passes the filter
+//! - a write fails if the row wouldn't pass the filter afterwards (e.g. reassigning
+//!   a task to someone else), judged by the fields the operation's params set
+//!
+//! Only a configuration with the admin capability bypasses the filters.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use crate::core::datasource::{HolonError, HolonResult};
+use crate::storage::turso::TursoBackend;
+use crate::storage::types::StorageEntity;
+use holon_api::{OperationDescriptor, Value};
+
+/// Placeholder of row filters for the current user's name
+pub const CURRENT_USER: &str = "@current_user";
+
+/// Row filters of the tables and the user they are evaluated for
+#[derive(Clone, Debug)]
+pub struct RowSecurityConfig {
+    /// Name `@current_user` refers to
+    pub current_user: String,
+    /// PRQL filter expression by table, e.g. `assignee == @current_user`
+    pub filters: HashMap<String, String>,
+    /// Admin capability: bypass all row filters
+    pub admin: bool,
+}
+
+impl RowSecurityConfig {
+    pub fn new(current_user: impl Into<String>) -> Self {
+        Self {
+            current_user: current_user.into(),
+            filters: HashMap::new(),
+            admin: false,
+        }
+    }
+
+    /// Only show and change rows of `table` passing `filter`
+    pub fn with_filter(mut self, table: impl Into<String>, filter: impl Into<String>) -> Self {
+        self.filters.insert(table.into(), filter.into());
+        self
+    }
+
+    pub fn with_admin_capability(mut self) -> Self {
+        self.admin = true;
+        self
+    }
+
+    /// The filters with `@current_user` replaced by the user's name; none for admins
+    pub fn query_filters(&self) -> HashMap<String, String> {
+        if self.admin {
+            return HashMap::new();
+        }
+        // A PRQL string literal (PRQL strings escape like Rust's)
+        let user = format!("{:?}", self.current_user);
+        self.filters
+            .iter()
+            .map(|(table, filter)| (table.clone(), replace_current_user(filter, &user)))
+            .collect()
+    }
+}
+
+/// Replace `@current_user` (but not e.g. `@current_users`) in `filter`
+fn replace_current_user(filter: &str, user: &str) -> String {
+    let mut result = String::with_capacity(filter.len());
+    let mut rest = filter;
+    while let Some(start) = rest.find(CURRENT_USER) {
+        let after = &rest[start + CURRENT_USER.len()..];
+        let is_whole = !after
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_');
+        result.push_str(&rest[..start]);
+        result.push_str(if is_whole { user } else { CURRENT_USER });
+        rest = after;
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Enforces the row filters of a `RowSecurityConfig` on operations
+pub struct RowSecurity {
+    backend: Arc<RwLock<TursoBackend>>,
+    config: RowSecurityConfig,
+    query_filters: HashMap<String, String>,
+}
+
+impl RowSecurity {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>, config: RowSecurityConfig) -> Self {
+        let query_filters = config.query_filters();
+        Self {
+            backend,
+            config,
+            query_filters,
+        }
+    }
+
+    pub fn config(&self) -> &RowSecurityConfig {
+        &self.config
+    }
+
+    /// Row filters to compile into queries, by table (see `RowSecurityConfig::query_filters`)
+    pub fn query_filters(&self) -> &HashMap<String, String> {
+        &self.query_filters
+    }
+
+    /// Reject an operation on a row the current user may not see, or that would
+    /// move a row out of the user's sight
+    ///
+    /// Operations are checked by their `id` parameter and the row with the fields
+    /// they set (see `written_fields`); `create` by the fields of the new row.
+    /// Operations without either (e.g. `sync`) aren't restricted.
+    pub async fn check_operation(
+        &self,
+        descriptor: &OperationDescriptor,
+        params: &StorageEntity,
+    ) -> HolonResult<()> {
+        let table = descriptor.entity_name.as_str();
+        let Some(filter) = self.query_filters.get(table) else {
+            return Ok(());
+        };
+
+        if descriptor.name == "create" {
+            return self.check_new_row(table, filter, params).await;
+        }
+        let Some(id) = params.get("id").filter(|id| !id.is_null()) else {
+            return Ok(());
+        };
+
+        let prql = format!(
+            "from {}\nfilter ({})\nselect {{row_id = {}}}",
+            table, filter, descriptor.id_column
+        );
+        let sql = format!(
+            "SELECT 1 FROM (\n{}\n) WHERE row_id = $id",
+            compile_filter(table, filter, &prql)?
+        );
+        let id_param = HashMap::from([("id".to_string(), id.clone())]);
+        let visible = !self.query(&sql, id_param.clone()).await?.is_empty();
+        if !visible {
+            return Err(HolonError::not_found(
+                &descriptor.entity_short_name,
+                &id.as_string_owned().unwrap_or_else(|| id.to_json_string()),
+            ));
+        }
+
+        let written = written_fields(descriptor, params);
+        if written.is_empty() {
+            return Ok(());
+        }
+        let Some(mut row) = self
+            .query(
+                &format!(
+                    "SELECT * FROM {} WHERE {} = $id",
+                    table, descriptor.id_column
+                ),
+                id_param,
+            )
+            .await?
+            .into_iter()
+            .next()
+        else {
+            return Ok(());
+        };
+        row.extend(written);
+        match self.passes_filter(table, filter, &row).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(HolonError::precondition(format!(
+                "Changed {} row must still pass the row filter `{}`",
+                table, self.config.filters[table]
+            ))),
+            Err(e) => Err(HolonError::precondition(format!(
+                "Changed {} row must still pass the row filter `{}`: {}",
+                table, self.config.filters[table], e
+            ))),
+        }
+    }
+
+    async fn check_new_row(
+        &self,
+        table: &str,
+        filter: &str,
+        params: &StorageEntity,
+    ) -> HolonResult<()> {
+        match self.passes_filter(table, filter, params).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(HolonError::precondition(format!(
+                "New {} row must pass the row filter `{}`",
+                table, self.config.filters[table]
+            ))),
+            Err(e) => Err(HolonError::precondition(format!(
+                "New {} row must pass the row filter `{}`: {}",
+                table, self.config.filters[table], e
+            ))),
+        }
+    }
+
+    /// Evaluate the filter on the fields of `row`, in a CTE shadowing the table
+    async fn passes_filter(
+        &self,
+        table: &str,
+        filter: &str,
+        row: &StorageEntity,
+    ) -> HolonResult<bool> {
+        let prql = format!("from {}\nfilter ({})", table, filter);
+        let mut columns = Vec::new();
+        let mut values = HashMap::new();
+        for (index, (field, value)) in row.iter().enumerate() {
+            columns.push(format!("$p{} AS \"{}\"", index, field.replace('"', "\"\"")));
+            values.insert(format!("p{}", index), value.clone());
+        }
+        let sql = format!(
+            "WITH {} AS (SELECT {})\nSELECT 1 FROM (\n{}\n)",
+            table,
+            columns.join(", "),
+            compile_filter(table, filter, &prql)?
+        );
+        Ok(!self.query(&sql, values).await?.is_empty())
+    }
+
+    async fn query(
+        &self,
+        sql: &str,
+        params: HashMap<String, Value>,
+    ) -> HolonResult<Vec<StorageEntity>> {
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| HolonError::Unknown(format!("Failed to check row filter: {}", e)))
+    }
+}
+
+/// Fields an operation sets, with their new values, as far as its params tell
+///
+/// `set_field` names the field in its params, `update` takes any fields; other
+/// operations set the params that are among their `affected_fields`.
+fn written_fields(descriptor: &OperationDescriptor, params: &StorageEntity) -> StorageEntity {
+    match descriptor.name.as_str() {
+        "set_field" => params
+            .get("field")
+            .and_then(|field| field.as_string_owned())
+            .map(|field| {
+                let value = params.get("value").cloned().unwrap_or(Value::Null);
+                HashMap::from([(field, value)])
+            })
+            .unwrap_or_default(),
+        "update" => params
+            .iter()
+            .filter(|(name, _)| name.as_str() != "id" && **name != descriptor.id_column)
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+        _ => params
+            .iter()
+            .filter(|(name, _)| descriptor.affected_fields.contains(name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+    }
+}
+
+fn compile_filter(table: &str, filter: &str, prql: &str) -> HolonResult<String> {
+    prqlc::compile(prql, &prqlc::Options::default()).map_err(|e| {
+        HolonError::Unknown(format!(
+            "Invalid row filter of {}: {}: {}",
+            table, filter, e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    fn descriptor(name: &str) -> OperationDescriptor {
+        OperationDescriptor {
+            entity_name: "tasks".to_string(),
+            entity_short_name: "task".to_string(),
+            id_column: "id".to_string(),
+            name: name.to_string(),
+            display_name: name.to_string(),
+            description: String::new(),
+            required_params: vec![],
+            affected_fields: vec![],
+            param_mappings: vec![],
+            precondition: None,
+            simulation: None,
+        }
+    }
+
+    async fn create_security(config: RowSecurityConfig) -> RowSecurity {
+        let backend = memory_backend().await;
+        backend
+            .read()
+            .await
+            .execute_sql(
+                "CREATE TABLE tasks (id TEXT PRIMARY KEY, assignee TEXT)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+            .read()
+            .await
+            .execute_sql(
+                "INSERT INTO tasks (id, assignee) VALUES ('a', 'alice'), ('b', 'bob')",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        RowSecurity::new(backend, config)
+    }
+
+    fn params(fields: &[(&str, &str)]) -> StorageEntity {
+        fields
+            .iter()
+            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn test_query_filters_replace_current_user() {
+        let config = RowSecurityConfig::new("al\"ice")
+            .with_filter("tasks", "assignee == @current_user || @current_users == 1");
+        assert_eq!(
+            config.query_filters()["tasks"],
+            r#"assignee == "al\"ice" || @current_users == 1"#
+        );
+        assert!(config.with_admin_capability().query_filters().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_operations_on_hidden_rows_are_rejected() {
+        let config =
+            RowSecurityConfig::new("alice").with_filter("tasks", "assignee == @current_user");
+        let security = create_security(config.clone()).await;
+
+        let set_field = descriptor("set_field");
+        assert!(
+            security
+                .check_operation(&set_field, &params(&[("id", "a")]))
+                .await
+                .is_ok()
+        );
+        let err = security
+            .check_operation(&set_field, &params(&[("id", "b")]))
+            .await
+            .unwrap_err();
+        assert!(err.is_not_found(), "{}", err);
+
+        // A visible row can't be changed into a hidden one
+        let reassign = params(&[("id", "a"), ("field", "assignee"), ("value", "bob")]);
+        assert!(
+            security
+                .check_operation(&set_field, &reassign)
+                .await
+                .is_err()
+        );
+        let keep = params(&[("id", "a"), ("field", "assignee"), ("value", "alice")]);
+        assert!(security.check_operation(&set_field, &keep).await.is_ok());
+
+        let create = descriptor("create");
+        assert!(
+            security
+                .check_operation(&create, &params(&[("id", "c"), ("assignee", "alice")]))
+                .await
+                .is_ok()
+        );
+        assert!(
+            security
+                .check_operation(&create, &params(&[("id", "c"), ("assignee", "bob")]))
+                .await
+                .is_err()
+        );
+
+        // Admins see every row
+        let security = create_security(config.with_admin_capability()).await;
+        assert!(
+            security
+                .check_operation(&set_field, &params(&[("id", "b")]))
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -15,13 +15,13 @@ use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{RwLock, broadcast};
 
-use crate::core::datasource::{CrudOperations, DataSource, Result, UndoAction};
+use crate::core::datasource::{CrudOperations, DataSource, HolonResult, Result, UndoAction};
 use crate::storage::backend::StorageBackend;
 use crate::storage::types::StorageEntity;
-use holon_api::streaming::ChangeNotifications;
 use holon_api::Value;
+use holon_api::streaming::ChangeNotifications;
 use holon_api::{ApiError, Change, StreamPosition};
 use tokio_stream::{Stream, StreamExt};
 
@@ -105,6 +105,13 @@ where
                                             eprintln!("Error ingesting delete: {}", e);
                                         }
                                     }
+                                    Change::ColumnChange { id, columns, .. } => {
+                                        let mut db_guard = db.write().await;
+                                        if let Err(e) = db_guard.update(&table, &id, columns).await
+                                        {
+                                            eprintln!("Error ingesting column change: {}", e);
+                                        }
+                                    }
                                 }
                             }
                         }
@@ -158,6 +165,12 @@ where
                                         eprintln!("Error ingesting delete: {}", e);
                                     }
                                 }
+                                Change::ColumnChange { id, columns, .. } => {
+                                    let mut db_guard = db.write().await;
+                                    if let Err(e) = db_guard.update(&table, &id, columns).await {
+                                        eprintln!("Error ingesting column change: {}", e);
+                                    }
+                                }
                             }
                         }
                     }
@@ -307,6 +320,14 @@ where
                                             eprintln!("Error deleting from cache: {}", e);
                                         }
                                     }
+                                    Change::ColumnChange { id, columns, .. } => {
+                                        let mut db_guard = db.write().await;
+                                        if let Err(e) =
+                                            db_guard.update(&table, id, columns.clone()).await
+                                        {
+                                            eprintln!("Error patching cache: {}", e);
+                                        }
+                                    }
                                 }
                             }
                         });
@@ -407,17 +428,17 @@ impl<T> CrudOperations<T> for QueryableCache<T>
 where
     T: Send + Sync + 'static,
 {
-    async fn set_field(&self, id: &str, field: &str, value: Value) -> Result<UndoAction> {
+    async fn set_field(&self, id: &str, field: &str, value: Value) -> HolonResult<UndoAction> {
         // Delegate to datasource - update arrives via stream
         self.datasource.set_field(id, field, value).await
     }
 
-    async fn create(&self, fields: HashMap<String, Value>) -> Result<(String, UndoAction)> {
+    async fn create(&self, fields: HashMap<String, Value>) -> HolonResult<(String, UndoAction)> {
         // Delegate to datasource - full entity arrives via stream
         self.datasource.create(fields).await
     }
 
-    async fn delete(&self, id: &str) -> Result<UndoAction> {
+    async fn delete(&self, id: &str) -> HolonResult<UndoAction> {
         // Delegate to datasource - deletion confirmed via stream
         self.datasource.delete(id).await
     }
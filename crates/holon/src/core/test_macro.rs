@@ -7,8 +7,10 @@ pub struct TestItem {
     #[primary_key]
     #[indexed]
     pub id: String,
+    #[validate(length(min = 1, max = 20))]
     pub title: String,
     #[indexed]
+    #[validate(range(min = 0))]
     pub priority: i64,
     pub completed: bool,
     pub optional_field: Option<String>,
@@ -118,4 +120,31 @@ mod tests {
         assert_eq!(original.completed, restored.completed);
         assert_eq!(original.optional_field, restored.optional_field);
     }
+
+    #[test]
+    fn test_validate_constraints() {
+        let mut item = TestItem {
+            id: "1".to_string(),
+            title: "Test".to_string(),
+            priority: 5,
+            completed: false,
+            optional_field: None,
+            derived_field: vec![],
+        };
+        assert!(item.validate().is_ok());
+
+        item.title = String::new();
+        item.priority = -1;
+        let errors = item.validate().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["title", "priority"]);
+
+        // Partial entities only check the fields they contain
+        let mut partial = DynamicEntity::new("test_items");
+        partial.set("title", "x".repeat(21));
+        let errors = TestItem::validate_entity(&partial).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "title");
+        assert!(TestItem::validate_entity(&DynamicEntity::new("test_items")).is_ok());
+    }
 }
@@ -0,0 +1,361 @@
+//! Time tracking storage and operations.
+//!
+//! `TimeEntryStore` keeps clocked intervals in the `time_entries` table, so time
+//! reports are plain PRQL queries (e.g. `from time_entries | group task_id (...)`).
+//!
+//! `TimeTrackingProvider` exposes `clock_in`/`clock_out` for one task entity type
+//! (e.g. `todoist_tasks`) by recording entries locally. Providers that can store
+//! clocks themselves (like org-mode LOGBOOK drawers) implement
+//! `TimeTrackingOperations` directly and import their entries via
+//! `replace_source_entries`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::core::datasource::{
+    __operations_time_tracking_operations, OperationProvider, Result, TimeTrackingOperations,
+    UndoAction,
+};
+use crate::storage::turso::TursoBackend;
+use crate::storage::types::StorageEntity;
+use holon_api::{DynamicEntity, HasSchema, Operation, OperationDescriptor, Value};
+pub use holon_core::{LOCAL_TIME_ENTRY_SOURCE, TimeEntry, format_duration};
+
+/// Entity name of the time entries table
+pub const TIME_ENTRIES_ENTITY: &str = "time_entries";
+
+/// Persistent time entries backed by TursoBackend
+pub struct TimeEntryStore {
+    backend: Arc<RwLock<TursoBackend>>,
+}
+
+impl TimeEntryStore {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self { backend }
+    }
+
+    /// Initialize the time_entries table schema
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = TimeEntry::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create time_entries table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        info!("Time entries schema initialized");
+        Ok(())
+    }
+
+    /// Start a local entry for a task
+    ///
+    /// Like org-mode, only one clock runs at a time: any other running local
+    /// entry is clocked out at `now`. Fails if the task is already clocked in.
+    pub async fn clock_in(&self, entity_name: &str, task_id: &str, now: i64) -> Result<TimeEntry> {
+        if self.running_entry(entity_name, task_id).await?.is_some() {
+            return Err(format!("Task {} is already clocked in", task_id).into());
+        }
+        for mut running in self.running_entries().await? {
+            if running.source == LOCAL_TIME_ENTRY_SOURCE {
+                running.stop(now);
+                self.save(&running).await?;
+            }
+        }
+
+        let entry = TimeEntry::start(entity_name, task_id, now, LOCAL_TIME_ENTRY_SOURCE);
+        self.save(&entry).await?;
+        debug!("Clocked in {}", entry.id);
+        Ok(entry)
+    }
+
+    /// Stop the running entry of a task
+    pub async fn clock_out(&self, entity_name: &str, task_id: &str, now: i64) -> Result<TimeEntry> {
+        let mut entry = self
+            .running_entry(entity_name, task_id)
+            .await?
+            .ok_or_else(|| format!("Task {} is not clocked in", task_id))?;
+        entry.stop(now);
+        self.save(&entry).await?;
+        debug!("Clocked out {}", entry.id);
+        Ok(entry)
+    }
+
+    /// The running entry of a task, if any
+    pub async fn running_entry(
+        &self,
+        entity_name: &str,
+        task_id: &str,
+    ) -> Result<Option<TimeEntry>> {
+        let params = HashMap::from([
+            (
+                "entity_name".to_string(),
+                Value::String(entity_name.to_string()),
+            ),
+            ("task_id".to_string(), Value::String(task_id.to_string())),
+        ]);
+        Ok(self
+            .query(
+                "SELECT * FROM time_entries WHERE entity_name = $entity_name AND task_id = $task_id AND ended_at IS NULL",
+                params,
+            )
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// All running entries, oldest first
+    pub async fn running_entries(&self) -> Result<Vec<TimeEntry>> {
+        self.query(
+            "SELECT * FROM time_entries WHERE ended_at IS NULL ORDER BY started_at",
+            HashMap::new(),
+        )
+        .await
+    }
+
+    /// All entries of a task, oldest first
+    pub async fn entries_for_task(
+        &self,
+        entity_name: &str,
+        task_id: &str,
+    ) -> Result<Vec<TimeEntry>> {
+        let params = HashMap::from([
+            (
+                "entity_name".to_string(),
+                Value::String(entity_name.to_string()),
+            ),
+            ("task_id".to_string(), Value::String(task_id.to_string())),
+        ]);
+        self.query(
+            "SELECT * FROM time_entries WHERE entity_name = $entity_name AND task_id = $task_id ORDER BY started_at",
+            params,
+        )
+        .await
+    }
+
+    /// Total tracked time of a task at `now`, including a running entry
+    pub async fn total_ms_for_task(
+        &self,
+        entity_name: &str,
+        task_id: &str,
+        now: i64,
+    ) -> Result<i64> {
+        Ok(self
+            .entries_for_task(entity_name, task_id)
+            .await?
+            .iter()
+            .map(|entry| entry.elapsed_ms(now))
+            .sum())
+    }
+
+    /// Replace all entries of an external source (e.g. one org file's LOGBOOK clocks)
+    pub async fn replace_source_entries(&self, source: &str, entries: &[TimeEntry]) -> Result<()> {
+        {
+            let backend = self.backend.read().await;
+            backend
+                .execute_sql(
+                    "DELETE FROM time_entries WHERE source = $source",
+                    HashMap::from([("source".to_string(), Value::String(source.to_string()))]),
+                )
+                .await
+                .map_err(|e| format!("Failed to delete time entries: {}", e))?;
+        }
+        for entry in entries {
+            self.save(entry).await?;
+        }
+        debug!("Imported {} time entries from {}", entries.len(), source);
+        Ok(())
+    }
+
+    async fn save(&self, entry: &TimeEntry) -> Result<()> {
+        let sql = "INSERT INTO time_entries
+                (id, entity_name, task_id, started_at, ended_at, duration_ms, source)
+            VALUES ($id, $entity_name, $task_id, $started_at, $ended_at, $duration_ms, $source)
+            ON CONFLICT(id) DO UPDATE SET
+                ended_at = excluded.ended_at,
+                duration_ms = excluded.duration_ms,
+                source = excluded.source";
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(sql, entry.to_entity().fields)
+            .await
+            .map_err(|e| format!("Failed to save time entry: {}", e))?;
+        Ok(())
+    }
+
+    async fn query(&self, sql: &str, params: HashMap<String, Value>) -> Result<Vec<TimeEntry>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to query time entries: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(TIME_ENTRIES_ENTITY);
+                entity.fields = row;
+                TimeEntry::from_entity(entity)
+            })
+            .collect()
+    }
+}
+
+/// Local `clock_in`/`clock_out` operations for one task entity type
+pub struct TimeTrackingProvider {
+    store: Arc<TimeEntryStore>,
+    entity_name: String,
+    short_name: String,
+}
+
+impl TimeTrackingProvider {
+    pub fn new(
+        store: Arc<TimeEntryStore>,
+        entity_name: impl Into<String>,
+        short_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            store,
+            entity_name: entity_name.into(),
+            short_name: short_name.into(),
+        }
+    }
+
+    fn inverse(&self, op_name: &str, display_name: &str, id: &str) -> UndoAction {
+        UndoAction::Undo(Operation::new(
+            &self.entity_name,
+            op_name,
+            display_name,
+            HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+        ))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl TimeTrackingOperations for TimeTrackingProvider {
+    async fn clock_in(&self, id: &str) -> Result<UndoAction> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.store.clock_in(&self.entity_name, id, now).await?;
+        Ok(self.inverse("clock_out", "Clock out", id))
+    }
+
+    async fn clock_out(&self, id: &str) -> Result<UndoAction> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.store.clock_out(&self.entity_name, id, now).await?;
+        Ok(self.inverse("clock_in", "Clock in", id))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationProvider for TimeTrackingProvider {
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        __operations_time_tracking_operations::time_tracking_operations(
+            &self.entity_name,
+            &self.short_name,
+            &self.entity_name,
+            "id",
+        )
+    }
+
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        params: StorageEntity,
+    ) -> Result<UndoAction> {
+        if entity_name != self.entity_name {
+            return Err(format!(
+                "Expected entity_name '{}', got '{}'",
+                self.entity_name, entity_name
+            )
+            .into());
+        }
+        __operations_time_tracking_operations::dispatch_operation(self, op_name, &params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    async fn create_store() -> TimeEntryStore {
+        let store = TimeEntryStore::new(memory_backend().await);
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        store
+    }
+
+    #[tokio::test]
+    async fn test_clock_in_out_and_totals() {
+        let store = create_store().await;
+
+        store.clock_in("todoist_tasks", "t1", 0).await.unwrap();
+        assert!(store.clock_in("todoist_tasks", "t1", 10).await.is_err());
+
+        // Clocking into another task stops the first clock
+        store.clock_in("todoist_tasks", "t2", 60_000).await.unwrap();
+        let running = store.running_entries().await.unwrap();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].task_id, "t2");
+
+        let stopped = store
+            .clock_out("todoist_tasks", "t2", 180_000)
+            .await
+            .unwrap();
+        assert_eq!(stopped.duration_ms, Some(120_000));
+        assert!(
+            store
+                .clock_out("todoist_tasks", "t2", 200_000)
+                .await
+                .is_err()
+        );
+
+        assert_eq!(
+            store
+                .total_ms_for_task("todoist_tasks", "t1", 999_999)
+                .await
+                .unwrap(),
+            60_000
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replace_source_entries_keeps_local_entries() {
+        let store = create_store().await;
+        store.clock_in("org_headlines", "h1", 0).await.unwrap();
+
+        let mut imported = TimeEntry::start("org_headlines", "h1", 1_000, "org:file-1");
+        imported.stop(61_000);
+        store
+            .replace_source_entries("org:file-1", &[imported.clone()])
+            .await
+            .unwrap();
+        store
+            .replace_source_entries("org:file-1", &[imported])
+            .await
+            .unwrap();
+
+        let entries = store.entries_for_task("org_headlines", "h1").await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries.iter().filter(|e| e.source == "org:file-1").count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! Local operation usage statistics.
+//!
+//! This module provides `OperationUsageStore`, which aggregates invocation counts,
+//! latencies and failure rates per operation in the `operation_usage` table.
+//!
+//! Statistics never leave the device: they are written to the profile's own
+//! database and only exposed through local APIs (usage dashboards, keybinding
+//! suggestions). Recording can be switched off entirely via `UsageStatsConfig`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::storage::turso::TursoBackend;
+use holon_api::{DynamicEntity, HasSchema, Value};
+use holon_core::OperationUsageEntry;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Environment variable that disables usage statistics when set to `1`/`true`/`off`
+pub const DISABLE_USAGE_STATS_ENV: &str = "HOLON_DISABLE_USAGE_STATS";
+
+/// Configuration for local usage statistics
+#[derive(Clone, Debug)]
+pub struct UsageStatsConfig {
+    /// Hard off switch. When false, nothing is recorded and the table is never touched.
+    pub enabled: bool,
+}
+
+impl UsageStatsConfig {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    /// Read configuration from the environment (enabled unless `HOLON_DISABLE_USAGE_STATS` is set)
+    pub fn from_env() -> Self {
+        let disabled = std::env::var(DISABLE_USAGE_STATS_ENV)
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "off"))
+            .unwrap_or(false);
+        Self { enabled: !disabled }
+    }
+}
+
+impl Default for UsageStatsConfig {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+/// Persistent per-profile operation usage statistics backed by TursoBackend.
+pub struct OperationUsageStore {
+    backend: Arc<RwLock<TursoBackend>>,
+    config: UsageStatsConfig,
+    enabled: AtomicBool,
+}
+
+impl OperationUsageStore {
+    /// Create a new usage store.
+    pub fn new(backend: Arc<RwLock<TursoBackend>>, config: UsageStatsConfig) -> Self {
+        Self {
+            backend,
+            enabled: AtomicBool::new(config.enabled),
+            config,
+        }
+    }
+
+    /// Whether statistics are currently being recorded
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Turn recording on or off at runtime.
+    ///
+    /// Disabling does not delete existing statistics; use `clear()` for that.
+    /// Fails to enable recording that `UsageStatsConfig` switched off, since the
+    /// table was never created then.
+    pub fn set_enabled(&self, enabled: bool) -> Result<()> {
+        if enabled && !self.config.enabled {
+            return Err(format!(
+                "Operation usage statistics are disabled by {}",
+                DISABLE_USAGE_STATS_ENV
+            )
+            .into());
+        }
+        self.enabled.store(enabled, Ordering::SeqCst);
+        info!(
+            "Operation usage statistics {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+        Ok(())
+    }
+
+    /// Initialize the operation_usage table schema.
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = OperationUsageEntry::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create operation_usage table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        info!("Operation usage schema initialized");
+        Ok(())
+    }
+
+    /// Record a single operation invocation.
+    ///
+    /// No-op when statistics are disabled.
+    pub async fn record(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        latency_ms: i64,
+        success: bool,
+    ) -> Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let failure = if success { 0 } else { 1 };
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let sql = "INSERT INTO operation_usage
+                (id, entity_name, op_name, invocation_count, failure_count, total_latency_ms, max_latency_ms, last_invoked_at)
+            VALUES ($id, $entity_name, $op_name, 1, $failure, $latency, $latency, $now)
+            ON CONFLICT(id) DO UPDATE SET
+                invocation_count = invocation_count + 1,
+                failure_count = failure_count + excluded.failure_count,
+                total_latency_ms = total_latency_ms + excluded.total_latency_ms,
+                max_latency_ms = MAX(max_latency_ms, excluded.max_latency_ms),
+                last_invoked_at = excluded.last_invoked_at";
+
+        let mut params = HashMap::new();
+        params.insert(
+            "id".to_string(),
+            Value::String(OperationUsageEntry::key(entity_name, op_name)),
+        );
+        params.insert(
+            "entity_name".to_string(),
+            Value::String(entity_name.to_string()),
+        );
+        params.insert("op_name".to_string(), Value::String(op_name.to_string()));
+        params.insert("failure".to_string(), Value::Integer(failure));
+        params.insert("latency".to_string(), Value::Integer(latency_ms));
+        params.insert("now".to_string(), Value::Integer(now));
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to record operation usage: {}", e))?;
+
+        debug!(
+            "Recorded usage for {}.{} ({}ms, success={})",
+            entity_name, op_name, latency_ms, success
+        );
+        Ok(())
+    }
+
+    /// Get statistics for all operations, most frequently used first.
+    ///
+    /// Empty when `UsageStatsConfig` switched statistics off.
+    pub async fn all_stats(&self) -> Result<Vec<OperationUsageEntry>> {
+        self.query_stats(
+            "SELECT * FROM operation_usage ORDER BY invocation_count DESC, last_invoked_at DESC",
+            HashMap::new(),
+        )
+        .await
+    }
+
+    /// Get the `limit` most frequently used operations.
+    ///
+    /// Intended for prioritizing keybinding suggestions.
+    pub async fn top_operations(&self, limit: usize) -> Result<Vec<OperationUsageEntry>> {
+        let mut params = HashMap::new();
+        params.insert("limit".to_string(), Value::Integer(limit as i64));
+        self.query_stats(
+            "SELECT * FROM operation_usage ORDER BY invocation_count DESC, last_invoked_at DESC LIMIT $limit",
+            params,
+        )
+        .await
+    }
+
+    /// Delete all recorded statistics.
+    ///
+    /// No-op when `UsageStatsConfig` switched statistics off.
+    pub async fn clear(&self) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql("DELETE FROM operation_usage", HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to clear operation usage: {}", e))?;
+        info!("Cleared operation usage statistics");
+        Ok(())
+    }
+
+    async fn query_stats(
+        &self,
+        sql: &str,
+        params: HashMap<String, Value>,
+    ) -> Result<Vec<OperationUsageEntry>> {
+        // Without the hard off switch's table there's nothing to query
+        if !self.config.enabled {
+            return Ok(Vec::new());
+        }
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to query operation usage: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new("operation_usage");
+                entity.fields = row;
+                OperationUsageEntry::from_entity(entity)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    async fn create_store(config: UsageStatsConfig) -> OperationUsageStore {
+        let store = OperationUsageStore::new(memory_backend().await, config);
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        store
+    }
+
+    #[tokio::test]
+    async fn test_record_aggregates_per_operation() {
+        let store = create_store(UsageStatsConfig::default()).await;
+
+        store.record("blocks", "indent", 10, true).await.unwrap();
+        store.record("blocks", "indent", 30, false).await.unwrap();
+        store.record("blocks", "outdent", 5, true).await.unwrap();
+
+        let stats = store.all_stats().await.unwrap();
+        assert_eq!(stats.len(), 2);
+
+        let indent = &stats[0];
+        assert_eq!(indent.op_name, "indent");
+        assert_eq!(indent.invocation_count, 2);
+        assert_eq!(indent.failure_count, 1);
+        assert_eq!(indent.max_latency_ms, 30);
+        assert_eq!(indent.avg_latency_ms(), 20.0);
+
+        let top = store.top_operations(1).await.unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].op_name, "indent");
+    }
+
+    #[tokio::test]
+    async fn test_disabled_store_records_nothing() {
+        let store = create_store(UsageStatsConfig::default()).await;
+
+        store.set_enabled(false).unwrap();
+        store.record("blocks", "indent", 10, true).await.unwrap();
+        assert!(store.all_stats().await.unwrap().is_empty());
+
+        store.set_enabled(true).unwrap();
+        store.record("blocks", "indent", 10, true).await.unwrap();
+        assert_eq!(store.all_stats().await.unwrap().len(), 1);
+
+        store.clear().await.unwrap();
+        assert!(store.all_stats().await.unwrap().is_empty());
+
+        // The hard off switch never creates the table and can't be overridden at runtime
+        let store = OperationUsageStore::new(memory_backend().await, UsageStatsConfig::new(false));
+        store.record("blocks", "indent", 10, true).await.unwrap();
+        assert!(store.all_stats().await.unwrap().is_empty());
+        assert!(store.top_operations(1).await.unwrap().is_empty());
+        store.clear().await.unwrap();
+        assert!(store.set_enabled(true).is_err());
+        assert!(!store.is_enabled());
+    }
+}
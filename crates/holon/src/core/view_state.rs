@@ -0,0 +1,443 @@
+//! Persistent per-view UI state.
+//!
+//! `ViewStateStore` keeps the `view_states` table: which tree nodes of a view are
+//! collapsed, which rows are selected and which row the view was scrolled to, keyed
+//! by view id and entity id. Views are named with `view_id:` on the root widget of
+//! their render expression; `BackendEngine` puts the stored state into the
+//! `RenderSpec` of such queries.
+//!
+//! Changes arrive on every click or scroll, so they are collected in memory and
+//! written in batches: when `VIEW_STATE_BATCH_SIZE` entries are pending, on
+//! `flush`, and periodically once `spawn_flush` was called. Reads see pending
+//! changes immediately.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::storage::turso::TursoBackend;
+use holon_api::{DynamicEntity, HasSchema, Value, ViewState};
+pub use holon_core::ViewStateEntry;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Entity name of the view state table
+pub const VIEW_STATES_ENTITY: &str = "view_states";
+
+/// Number of pending changes that triggers a write
+pub const VIEW_STATE_BATCH_SIZE: usize = 50;
+
+/// Interval at which `BackendEngine::start_view_state_flush` writes pending changes
+pub const VIEW_STATE_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+const COLUMNS: [&str; 7] = [
+    "id",
+    "view_id",
+    "entity_id",
+    "collapsed",
+    "selected",
+    "scroll_anchor",
+    "updated_at",
+];
+
+/// Persistent view state backed by TursoBackend
+pub struct ViewStateStore {
+    backend: Arc<RwLock<TursoBackend>>,
+    /// Changed entries not yet written, by id
+    pending: StdMutex<HashMap<String, ViewStateEntry>>,
+}
+
+impl ViewStateStore {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self {
+            backend,
+            pending: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    /// Initialize the view_states table schema
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = ViewStateEntry::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create view_states table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        info!("View state schema initialized");
+        Ok(())
+    }
+
+    /// UI state of `view_id`, including changes that weren't written yet
+    pub async fn get(&self, view_id: &str) -> Result<ViewState> {
+        let mut entries: HashMap<String, ViewStateEntry> = self
+            .query(
+                "SELECT * FROM view_states WHERE view_id = $view_id",
+                HashMap::from([("view_id".to_string(), Value::String(view_id.to_string()))]),
+            )
+            .await?
+            .into_iter()
+            .map(|entry| (entry.id.clone(), entry))
+            .collect();
+        for entry in self.pending.lock().unwrap().values() {
+            if entry.view_id == view_id {
+                entries.insert(entry.id.clone(), entry.clone());
+            }
+        }
+
+        let mut entries: Vec<ViewStateEntry> = entries.into_values().collect();
+        entries.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+        let ids = |flag: fn(&ViewStateEntry) -> bool| -> Vec<String> {
+            entries
+                .iter()
+                .filter(|entry| flag(entry))
+                .map(|entry| entry.entity_id.clone())
+                .collect()
+        };
+        Ok(ViewState {
+            collapsed: ids(|entry| entry.collapsed),
+            selected: ids(|entry| entry.selected),
+            scroll_anchor: ids(|entry| entry.scroll_anchor).into_iter().next(),
+        })
+    }
+
+    /// Collapse or expand a tree node
+    pub async fn set_collapsed(
+        &self,
+        view_id: &str,
+        entity_id: &str,
+        collapsed: bool,
+        now: i64,
+    ) -> Result<()> {
+        let mut entry = self.entry(view_id, entity_id, now).await?;
+        entry.collapsed = collapsed;
+        self.stage(vec![entry]).await
+    }
+
+    /// Replace the selected rows of a view
+    pub async fn set_selection(
+        &self,
+        view_id: &str,
+        entity_ids: &[String],
+        now: i64,
+    ) -> Result<()> {
+        let current = self.get(view_id).await?;
+        let mut changed = Vec::new();
+        for entity_id in current
+            .selected
+            .iter()
+            .filter(|id| !entity_ids.contains(id))
+        {
+            let mut entry = self.entry(view_id, entity_id, now).await?;
+            entry.selected = false;
+            changed.push(entry);
+        }
+        for entity_id in entity_ids
+            .iter()
+            .filter(|id| !current.selected.contains(id))
+        {
+            let mut entry = self.entry(view_id, entity_id, now).await?;
+            entry.selected = true;
+            changed.push(entry);
+        }
+        self.stage(changed).await
+    }
+
+    /// Remember the first visible row of a view (`None` for the top)
+    pub async fn set_scroll_anchor(
+        &self,
+        view_id: &str,
+        entity_id: Option<&str>,
+        now: i64,
+    ) -> Result<()> {
+        let current = self.get(view_id).await?.scroll_anchor;
+        if current.as_deref() == entity_id {
+            return Ok(());
+        }
+        let mut changed = Vec::new();
+        if let Some(previous) = current {
+            let mut entry = self.entry(view_id, &previous, now).await?;
+            entry.scroll_anchor = false;
+            changed.push(entry);
+        }
+        if let Some(entity_id) = entity_id {
+            let mut entry = self.entry(view_id, entity_id, now).await?;
+            entry.scroll_anchor = true;
+            changed.push(entry);
+        }
+        self.stage(changed).await
+    }
+
+    /// Number of changes not yet written
+    pub fn pending_count(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    /// Write all pending changes; returns the number of written entries
+    ///
+    /// Entries without state are deleted. If the write fails, the changes stay pending.
+    pub async fn flush(&self) -> Result<usize> {
+        // Entries stay pending (and visible to `get`) until they are written
+        let entries: Vec<ViewStateEntry> = self.pending.lock().unwrap().values().cloned().collect();
+        if entries.is_empty() {
+            return Ok(0);
+        }
+
+        self.write(&entries).await?;
+        let mut pending = self.pending.lock().unwrap();
+        for entry in &entries {
+            // Keep changes made while writing
+            if pending.get(&entry.id) == Some(entry) {
+                pending.remove(&entry.id);
+            }
+        }
+        debug!("Wrote {} view state changes", entries.len());
+        Ok(entries.len())
+    }
+
+    /// Write pending changes every `interval`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn_flush(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.flush().await {
+                    tracing::warn!("Failed to write view state: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Current entry of an entity: pending, stored or new
+    async fn entry(&self, view_id: &str, entity_id: &str, now: i64) -> Result<ViewStateEntry> {
+        let id = ViewStateEntry::key(view_id, entity_id);
+        if let Some(entry) = self.pending.lock().unwrap().get(&id) {
+            return Ok(ViewStateEntry {
+                updated_at: now,
+                ..entry.clone()
+            });
+        }
+        let stored = self
+            .query(
+                "SELECT * FROM view_states WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(id))]),
+            )
+            .await?
+            .into_iter()
+            .next();
+        Ok(match stored {
+            Some(entry) => ViewStateEntry {
+                updated_at: now,
+                ..entry
+            },
+            None => ViewStateEntry::new(view_id, entity_id, now),
+        })
+    }
+
+    async fn stage(&self, entries: Vec<ViewStateEntry>) -> Result<()> {
+        let pending_count = {
+            let mut pending = self.pending.lock().unwrap();
+            for entry in entries {
+                pending.insert(entry.id.clone(), entry);
+            }
+            pending.len()
+        };
+        if pending_count >= VIEW_STATE_BATCH_SIZE {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Upsert entries with state and delete the others, one statement each
+    async fn write(&self, entries: &[ViewStateEntry]) -> Result<()> {
+        let (stored, cleared): (Vec<&ViewStateEntry>, Vec<&ViewStateEntry>) =
+            entries.iter().partition(|entry| !entry.is_empty());
+        let backend = self.backend.read().await;
+
+        if !stored.is_empty() {
+            let mut params = HashMap::new();
+            let mut rows = Vec::with_capacity(stored.len());
+            for (i, entry) in stored.iter().enumerate() {
+                let mut fields = entry.to_entity().fields;
+                let placeholders: Vec<String> = COLUMNS
+                    .iter()
+                    .map(|column| {
+                        let name = format!("{}_{}", column, i);
+                        params.insert(name.clone(), fields.remove(*column).unwrap_or(Value::Null));
+                        format!("${}", name)
+                    })
+                    .collect();
+                rows.push(format!("({})", placeholders.join(", ")));
+            }
+            let sql = format!(
+                "INSERT INTO view_states ({})
+                VALUES {}
+                ON CONFLICT(id) DO UPDATE SET
+                    collapsed = excluded.collapsed,
+                    selected = excluded.selected,
+                    scroll_anchor = excluded.scroll_anchor,
+                    updated_at = excluded.updated_at",
+                COLUMNS.join(", "),
+                rows.join(", ")
+            );
+            backend
+                .execute_sql(&sql, params)
+                .await
+                .map_err(|e| format!("Failed to save view state: {}", e))?;
+        }
+
+        if !cleared.is_empty() {
+            let mut params = HashMap::new();
+            let placeholders: Vec<String> = cleared
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| {
+                    params.insert(format!("id_{}", i), Value::String(entry.id.clone()));
+                    format!("$id_{}", i)
+                })
+                .collect();
+            backend
+                .execute_sql(
+                    &format!(
+                        "DELETE FROM view_states WHERE id IN ({})",
+                        placeholders.join(", ")
+                    ),
+                    params,
+                )
+                .await
+                .map_err(|e| format!("Failed to clear view state: {}", e))?;
+        }
+        Ok(())
+    }
+
+    async fn query(
+        &self,
+        sql: &str,
+        params: HashMap<String, Value>,
+    ) -> Result<Vec<ViewStateEntry>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to query view state: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(VIEW_STATES_ENTITY);
+                entity.fields = row;
+                ViewStateEntry::from_entity(entity)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    async fn create_store() -> ViewStateStore {
+        let store = ViewStateStore::new(memory_backend().await);
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        store
+    }
+
+    #[tokio::test]
+    async fn test_state_round_trips_through_flush() {
+        let store = create_store().await;
+
+        store
+            .set_collapsed("outline", "b-2", true, 0)
+            .await
+            .unwrap();
+        store
+            .set_collapsed("outline", "b-1", true, 0)
+            .await
+            .unwrap();
+        store
+            .set_selection("outline", &["b-3".to_string()], 0)
+            .await
+            .unwrap();
+        store
+            .set_scroll_anchor("outline", Some("b-1"), 0)
+            .await
+            .unwrap();
+        store.set_collapsed("inbox", "b-1", true, 0).await.unwrap();
+
+        // Pending changes are visible before they are written
+        let expected = ViewState {
+            collapsed: vec!["b-1".to_string(), "b-2".to_string()],
+            selected: vec!["b-3".to_string()],
+            scroll_anchor: Some("b-1".to_string()),
+        };
+        assert_eq!(store.get("outline").await.unwrap(), expected);
+        assert_eq!(store.flush().await.unwrap(), 4);
+        assert_eq!(store.pending_count(), 0);
+
+        let reopened = ViewStateStore::new(store.backend.clone());
+        assert_eq!(reopened.get("outline").await.unwrap(), expected);
+
+        // Clearing the last flag of an entry deletes it; other flags are kept
+        reopened
+            .set_collapsed("outline", "b-2", false, 1)
+            .await
+            .unwrap();
+        reopened
+            .set_collapsed("outline", "b-1", false, 1)
+            .await
+            .unwrap();
+        reopened.set_selection("outline", &[], 1).await.unwrap();
+        reopened.flush().await.unwrap();
+        let state = reopened.get("outline").await.unwrap();
+        assert_eq!(
+            state,
+            ViewState {
+                collapsed: vec![],
+                selected: vec![],
+                scroll_anchor: Some("b-1".to_string()),
+            }
+        );
+        let rows = reopened
+            .query("SELECT * FROM view_states", HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2, "b-1 of outline (anchor) and b-1 of inbox");
+    }
+
+    #[tokio::test]
+    async fn test_batch_is_written_when_full() {
+        let store = create_store().await;
+        for i in 0..VIEW_STATE_BATCH_SIZE - 1 {
+            store
+                .set_collapsed("outline", &format!("b-{}", i), true, 0)
+                .await
+                .unwrap();
+        }
+        assert_eq!(store.pending_count(), VIEW_STATE_BATCH_SIZE - 1);
+
+        store
+            .set_collapsed("outline", "last", true, 0)
+            .await
+            .unwrap();
+        assert_eq!(store.pending_count(), 0);
+        assert_eq!(
+            store.get("outline").await.unwrap().collapsed.len(),
+            VIEW_STATE_BATCH_SIZE
+        );
+    }
+}
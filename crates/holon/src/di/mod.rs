@@ -14,12 +14,46 @@ use tokio::sync::RwLock;
 
 use crate::api::backend_engine::BackendEngine;
 use crate::api::operation_dispatcher::{OperationDispatcher, OperationModule};
-use crate::core::datasource::{OperationObserver, SyncTokenStore};
-use crate::core::operation_log::{OperationLogObserver, OperationLogStore};
+use crate::api::view_loader::{ViewLoader, ViewLoaderConfig};
+use crate::core::access::EntityAccessStore;
+use crate::core::annotations::AnnotationStore;
+use crate::core::attachments::AttachmentStore;
+use crate::core::automation::AutomationRules;
+use crate::core::custom_fields::CustomFieldStore;
+use crate::core::datasource::{
+    OperationObserver, OperationProvider, SyncTokenStore, SyncableProvider, TempIdMap,
+};
+use crate::core::dependencies::DependencyStore;
+use crate::core::identities::EntityIdentityStore;
+use crate::core::log_buffer::LogBuffer;
+use crate::core::notifications::LoggingNotificationSink;
+use crate::core::operation_log::{
+    AuditActor, AuditRetention, IdMappingService, OperationLogObserver, OperationLogStore,
+};
+use crate::core::presence::{PresenceConfig, PresenceTracker};
+use crate::core::time_tracking::TimeEntryStore;
 use crate::core::transform::{AstTransformer, TransformPipeline};
 use crate::core::transform::{ColumnPreservationTransformer, JsonAggregationTransformer};
+use crate::core::usage_stats::{OperationUsageStore, UsageStatsConfig};
+use crate::core::view_state::ViewStateStore;
+use crate::references::{BacklinkIndex, TagIndex};
+use crate::reminders::{ReminderConfig, ReminderScheduler, ReminderStore};
+use crate::storage::archival::{ArchivalConfig, ArchivalRuleProvider};
+use crate::storage::encryption::EncryptionConfig;
+use crate::storage::maintenance::{MaintenanceConfig, MaintenanceScheduler};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::replica::{ReplicaConfig, ReplicaSyncer};
+use crate::storage::snapshot_store::{SnapshotConfig, SnapshotStore};
+use crate::storage::soft_delete::TrashConfig;
 use crate::storage::sync_token_store::DatabaseSyncTokenStore;
 use crate::storage::turso::TursoBackend;
+use crate::sync::blob::SyncBlobLog;
+use crate::sync::conflicts::{MergeStrategies, SyncReconciler};
+use crate::sync::dirty::SyncDirtyStore;
+use crate::sync::health::{SyncHealthConfig, SyncHealthStore};
+use crate::sync::scheduler::{SyncScheduler, SyncSchedulerConfig};
+use holon_api::ChangeSource;
+use holon_core::OperationLogOperations;
 
 /// Configuration for database path
 #[derive(Clone, Debug)]
@@ -33,6 +67,43 @@ impl DatabasePathConfig {
     }
 }
 
+/// Open the database, encrypted if an `EncryptionConfig` is given
+async fn open_backend(
+    db_path: PathBuf,
+    encryption: Option<EncryptionConfig>,
+) -> crate::storage::types::Result<TursoBackend> {
+    match encryption {
+        Some(config) => TursoBackend::new_encrypted(db_path, &config).await,
+        None => TursoBackend::new(db_path).await,
+    }
+}
+
+/// Run an async initializer to completion from a synchronous DI factory
+///
+/// Natively the future runs on a fresh runtime in its own thread, which avoids the
+/// "runtime within runtime" panic when the container is built from async code.
+/// WASM can't spawn threads, so there it blocks on the current runtime instead.
+fn block_on_in_thread<F, Fut, T>(make_future: F) -> T
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = T>,
+    T: Send + 'static,
+{
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+            rt.block_on(make_future())
+        })
+        .join()
+        .expect("Thread panicked while running DI initializer")
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        tokio::runtime::Handle::current().block_on(make_future())
+    }
+}
+
 /// Shared setup function for creating BackendEngine with DI
 ///
 /// This function sets up the DI container and returns a BackendEngine.
@@ -80,7 +151,8 @@ where
 ///
 /// This registers:
 /// - `DatabasePathConfig` (singleton) - Database path configuration
-/// - `RwLock<TursoBackend>` (singleton) - Database backend (wrapped in RwLock for BackendEngine)
+/// - `RwLock<TursoBackend>` (singleton) - Database backend (wrapped in RwLock for BackendEngine),
+///   encrypted if an `EncryptionConfig` is registered
 /// - `OperationDispatcher` (singleton) - Operation dispatcher
 /// - `BackendEngine` (singleton) - Render engine (no longer wrapped in RwLock)
 ///
@@ -97,30 +169,14 @@ pub fn register_core_services(services: &mut ServiceCollection, db_path: PathBuf
     // Register Arc<RwLock<TursoBackend>> as singleton factory with blocking async initialization
     // This matches what BackendEngine::from_dependencies expects
     let db_path_clone = db_path.clone();
-    services.add_singleton_factory::<RwLock<TursoBackend>, _>(move |_resolver| {
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            // Create backend in a new thread with its own runtime to avoid "runtime within runtime" error
-            let db_path_for_thread = db_path_clone.clone();
-            let backend = std::thread::spawn(move || {
-                let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
-                rt.block_on(TursoBackend::new(db_path_for_thread))
-                    .expect("Failed to create TursoBackend")
-            })
-            .join()
-            .expect("Thread panicked while creating TursoBackend");
-            RwLock::new(backend)
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            // On WASM, we can't spawn threads, so we need to use the current runtime
-            // This assumes we're already in an async context with a runtime
-            let rt = tokio::runtime::Handle::current();
-            let backend = rt
-                .block_on(TursoBackend::new(db_path_clone.clone()))
-                .expect("Failed to create TursoBackend");
-            RwLock::new(backend)
-        }
+    services.add_singleton_factory::<RwLock<TursoBackend>, _>(move |resolver| {
+        // Optional encryption at rest (unencrypted unless an EncryptionConfig is registered)
+        let encryption = resolver.get::<EncryptionConfig>().map(|c| (*c).clone());
+
+        let db_path_for_thread = db_path_clone.clone();
+        let backend = block_on_in_thread(move || open_backend(db_path_for_thread, encryption))
+            .expect("Failed to create TursoBackend");
+        RwLock::new(backend)
     });
 
     // Register DatabaseSyncTokenStore as SyncTokenStore implementation
@@ -129,36 +185,15 @@ pub fn register_core_services(services: &mut ServiceCollection, db_path: PathBuf
         let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
         let backend = backend_arc.clone();
 
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            // Initialize sync_states table in a new thread with its own runtime
-            let backend_for_init = backend.clone();
-            std::thread::spawn(move || {
-                let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
-                rt.block_on(async {
-                    let token_store = DatabaseSyncTokenStore::new(backend_for_init);
-                    token_store
-                        .initialize_sync_state_table()
-                        .await
-                        .expect("Failed to initialize sync_states table");
-                });
-            })
-            .join()
-            .expect("Thread panicked while initializing sync_states table");
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            // On WASM, use current runtime
-            let rt = tokio::runtime::Handle::current();
-            let backend_for_init = backend.clone();
-            rt.block_on(async {
-                let token_store = DatabaseSyncTokenStore::new(backend_for_init);
-                token_store
-                    .initialize_sync_state_table()
-                    .await
-                    .expect("Failed to initialize sync_states table");
-            });
-        }
+        // Initialize sync_states table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let token_store = DatabaseSyncTokenStore::new(backend_for_init);
+            token_store
+                .initialize_sync_state_table()
+                .await
+                .expect("Failed to initialize sync_states table");
+        });
 
         Arc::new(DatabaseSyncTokenStore::new(backend)) as Arc<dyn SyncTokenStore>
     });
@@ -167,45 +202,470 @@ pub fn register_core_services(services: &mut ServiceCollection, db_path: PathBuf
     services.add_singleton_factory::<OperationLogStore, _>(move |resolver| {
         let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
         let backend = backend_arc.clone();
-
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            // Initialize operations table in a new thread with its own runtime
-            let backend_for_init = backend.clone();
-            std::thread::spawn(move || {
-                let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
-                rt.block_on(async {
-                    let store = OperationLogStore::new(backend_for_init);
-                    store
-                        .initialize_schema()
-                        .await
-                        .expect("Failed to initialize operations table");
-                });
-            })
-            .join()
-            .expect("Thread panicked while initializing operations table");
+        let audit_retention = resolver
+            .get::<AuditRetention>()
+            .map(|retention| (*retention).clone())
+            .unwrap_or_default();
+        let actor = resolver.get::<AuditActor>().map(|actor| actor.0.clone());
+
+        // Initialize operations table
+        let backend_for_init = backend.clone();
+        let retention_for_init = audit_retention.clone();
+        block_on_in_thread(move || async move {
+            let store =
+                OperationLogStore::new(backend_for_init).with_audit_retention(retention_for_init);
+            store
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize operations table");
+            store
+                .apply_audit_retention(chrono::Utc::now().timestamp_millis())
+                .await
+                .expect("Failed to apply audit log retention");
+        });
+
+        let store = OperationLogStore::new(backend).with_audit_retention(audit_retention);
+        match actor {
+            Some(actor) => store.with_actor(actor),
+            None => store,
         }
-        #[cfg(target_arch = "wasm32")]
-        {
-            // On WASM, use current runtime
-            let rt = tokio::runtime::Handle::current();
+    });
+
+    // Register OperationLogObserver as OperationObserver for persistent undo/redo
+    services.add_trait_factory::<dyn OperationObserver, _>(Lifetime::Singleton, move |resolver| {
+        let store = resolver.get_required::<OperationLogStore>();
+        Arc::new(OperationLogObserver::new(store)) as Arc<dyn OperationObserver>
+    });
+
+    // Register IdMappingService to rewrite temporary IDs once optimistic creates complete
+    services.add_singleton_factory::<IdMappingService, _>(move |resolver| {
+        let operation_log = resolver.get_required::<OperationLogStore>();
+        IdMappingService::new(Arc::new(TempIdMap::default()))
+            .with_operation_log(operation_log as Arc<dyn OperationLogOperations>)
+    });
+
+    // Register OperationUsageStore for local operation usage statistics
+    // Honors the HOLON_DISABLE_USAGE_STATS hard off switch: when disabled the table is never created
+    services.add_singleton_factory::<OperationUsageStore, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+        let config = UsageStatsConfig::from_env();
+
+        if config.enabled {
+            // Initialize operation_usage table
             let backend_for_init = backend.clone();
-            rt.block_on(async {
-                let store = OperationLogStore::new(backend_for_init);
+            let config_for_init = config.clone();
+            block_on_in_thread(move || async move {
+                let store = OperationUsageStore::new(backend_for_init, config_for_init);
                 store
                     .initialize_schema()
                     .await
-                    .expect("Failed to initialize operations table");
+                    .expect("Failed to initialize operation_usage table");
             });
         }
 
-        OperationLogStore::new(backend)
+        OperationUsageStore::new(backend, config)
     });
 
-    // Register OperationLogObserver as OperationObserver for persistent undo/redo
-    services.add_trait_factory::<dyn OperationObserver, _>(Lifetime::Singleton, move |resolver| {
-        let store = resolver.get_required::<OperationLogStore>();
-        Arc::new(OperationLogObserver::new(store)) as Arc<dyn OperationObserver>
+    // Register SyncHealthStore for sync attempt tracking and weekly health reports
+    services.add_singleton_factory::<SyncHealthStore, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+
+        // Initialize sync health tables
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let store = SyncHealthStore::new(backend_for_init, SyncHealthConfig::default());
+            store
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize sync health tables");
+        });
+
+        let store = SyncHealthStore::new(backend, SyncHealthConfig::default());
+        store.add_notification_sink(Arc::new(LoggingNotificationSink));
+        store
+    });
+
+    // Register SyncDirtyStore for unsynced-changes indicators
+    services.add_singleton_factory::<SyncDirtyStore, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+        // The dirty state is derived from the operations table
+        let _operation_log = resolver.get_required::<OperationLogStore>();
+
+        // Initialize sync_dirty table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let store = SyncDirtyStore::new(backend_for_init);
+            store
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize sync_dirty table");
+            store
+                .rebuild()
+                .await
+                .expect("Failed to rebuild sync_dirty table");
+        });
+
+        SyncDirtyStore::new(backend)
+    });
+
+    // Register SyncDirtyStore as OperationObserver to mark changed entities dirty
+    services.add_trait_factory::<dyn OperationObserver, _>(Lifetime::Singleton, |resolver| {
+        resolver.get_required::<SyncDirtyStore>() as Arc<dyn OperationObserver>
+    });
+
+    // Register MergeStrategies for field-level sync conflicts (providers register theirs)
+    services.add_singleton_factory::<MergeStrategies, _>(|_resolver| MergeStrategies::default());
+
+    // Register SyncReconciler applying merge strategies to ingested remote changes
+    services.add_singleton_factory::<SyncReconciler, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+        let sync_dirty = resolver.get_required::<SyncDirtyStore>();
+        let strategies = resolver.get_required::<MergeStrategies>();
+
+        // Initialize sync_conflicts table
+        let reconciler =
+            SyncReconciler::new(backend.clone(), sync_dirty.clone(), strategies.clone());
+        block_on_in_thread(move || async move {
+            reconciler
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize sync_conflicts table");
+        });
+
+        SyncReconciler::new(backend, sync_dirty, strategies)
+    });
+
+    // Register MaintenanceScheduler for WAL checkpoints, vacuum and ANALYZE while idle
+    services.add_singleton_factory::<MaintenanceScheduler, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+        let config = resolver
+            .get::<MaintenanceConfig>()
+            .map(|config| (*config).clone())
+            .unwrap_or_default();
+        MaintenanceScheduler::new(backend, config)
+    });
+
+    // Register MaintenanceScheduler as OperationObserver to detect idle periods
+    services.add_trait_factory::<dyn OperationObserver, _>(Lifetime::Singleton, |resolver| {
+        resolver.get_required::<MaintenanceScheduler>() as Arc<dyn OperationObserver>
+    });
+
+    // Register SyncScheduler for periodic syncs of all registered SyncableProviders
+    services.add_singleton_factory::<SyncScheduler, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+        let config = resolver
+            .get::<SyncSchedulerConfig>()
+            .map(|config| (*config).clone())
+            .unwrap_or_default();
+        let sync_health = resolver.get_required::<SyncHealthStore>();
+        let sync_dirty = resolver.get_required::<SyncDirtyStore>();
+        let providers = resolver
+            .get_all_trait::<dyn SyncableProvider>()
+            .unwrap_or_else(|_| vec![]);
+
+        // Initialize sync_status table
+        let backend_for_init = backend.clone();
+        let config_for_init = config.clone();
+        block_on_in_thread(move || async move {
+            let scheduler = SyncScheduler::new(backend_for_init, config_for_init);
+            scheduler
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize sync_status table");
+        });
+
+        let scheduler = SyncScheduler::new(backend, config)
+            .with_sync_health(sync_health)
+            .with_sync_dirty(sync_dirty);
+        for provider in providers {
+            scheduler.register(provider);
+        }
+        scheduler
+    });
+
+    // Register ReminderStore for deadline reminders (escalation, snooze, dismiss)
+    services.add_singleton_factory::<ReminderStore, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+        let config = resolver
+            .get::<ReminderConfig>()
+            .map(|config| (*config).clone())
+            .unwrap_or_default();
+
+        // Initialize reminders table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let store = ReminderStore::new(backend_for_init);
+            store
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize reminders table");
+        });
+
+        ReminderStore::with_policy(backend, config.policy)
+    });
+
+    // Register ReminderScheduler to fire due reminders (deadlines and explicit ones)
+    services.add_singleton_factory::<ReminderScheduler, _>(move |resolver| {
+        let store = resolver.get_required::<ReminderStore>();
+        let config = resolver
+            .get::<ReminderConfig>()
+            .map(|config| (*config).clone())
+            .unwrap_or_default();
+        let scheduler = ReminderScheduler::new(store, config);
+        scheduler.add_notification_sink(Arc::new(LoggingNotificationSink));
+        scheduler
+    });
+
+    // Register ReminderStore as OperationProvider for snooze/dismiss operations
+    services.add_trait_factory::<dyn OperationProvider, _>(Lifetime::Singleton, |resolver| {
+        resolver.get_required::<ReminderStore>() as Arc<dyn OperationProvider>
+    });
+
+    // Register BacklinkIndex for "Linked references" queries
+    services.add_singleton_factory::<BacklinkIndex, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+
+        // Initialize backlinks table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let index = BacklinkIndex::new(backend_for_init);
+            index
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize backlinks table");
+        });
+
+        // The index is (re)built when the engine starts it, once source tables exist
+        BacklinkIndex::new(backend)
+    });
+
+    // Register TagIndex for tag-based views
+    services.add_singleton_factory::<TagIndex, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+
+        // Initialize tags table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let index = TagIndex::new(backend_for_init);
+            index
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize tags table");
+        });
+
+        // The index is (re)built when the engine starts it, once source tables exist
+        TagIndex::new(backend)
+    });
+
+    // Register TagIndex as OperationProvider for add_tag/remove_tag operations
+    services.add_trait_factory::<dyn OperationProvider, _>(Lifetime::Singleton, |resolver| {
+        resolver.get_required::<TagIndex>() as Arc<dyn OperationProvider>
+    });
+
+    // Register AutomationRules for operations run when entities change
+    services.add_singleton_factory::<AutomationRules, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+
+        // Initialize automation_rules table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let rules = AutomationRules::new(backend_for_init);
+            rules
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize automation_rules table");
+        });
+
+        // Rules only fire once the engine starts them
+        AutomationRules::new(backend)
+    });
+
+    // Register AutomationRules as OperationProvider for editing and enabling rules
+    services.add_trait_factory::<dyn OperationProvider, _>(Lifetime::Singleton, |resolver| {
+        resolver.get_required::<AutomationRules>() as Arc<dyn OperationProvider>
+    });
+
+    // Register AnnotationStore for comments on any entity
+    services.add_singleton_factory::<AnnotationStore, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+
+        // Initialize annotations table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let store = AnnotationStore::new(backend_for_init);
+            store
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize annotations table");
+        });
+
+        AnnotationStore::new(backend)
+    });
+
+    // Register AnnotationStore as OperationProvider for adding, editing and resolving comments
+    services.add_trait_factory::<dyn OperationProvider, _>(Lifetime::Singleton, |resolver| {
+        resolver.get_required::<AnnotationStore>() as Arc<dyn OperationProvider>
+    });
+
+    // Register SyncBlobLog for end-to-end encrypted device-to-device sync
+    services.add_singleton_factory::<SyncBlobLog, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let log = SyncBlobLog::new(backend_arc.clone());
+
+        // Initialization loads this device's ID into the log, so it runs on the instance itself
+        block_on_in_thread(move || async move {
+            log.initialize_schema()
+                .await
+                .expect("Failed to initialize sync_blob_log table");
+            log
+        })
+    });
+
+    // Register SyncBlobLog as OperationObserver to record local operations for sync
+    services.add_trait_factory::<dyn OperationObserver, _>(Lifetime::Singleton, |resolver| {
+        resolver.get_required::<SyncBlobLog>() as Arc<dyn OperationObserver>
+    });
+
+    // Register TimeEntryStore for clock-in/clock-out time tracking
+    services.add_singleton_factory::<TimeEntryStore, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+
+        // Initialize time_entries table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let store = TimeEntryStore::new(backend_for_init);
+            store
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize time_entries table");
+        });
+
+        TimeEntryStore::new(backend)
+    });
+
+    // Register DependencyStore for blocks/blocked-by relationships between tasks
+    services.add_singleton_factory::<DependencyStore, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+
+        // Initialize task_dependencies table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let store = DependencyStore::new(backend_for_init);
+            store
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize task_dependencies table");
+        });
+
+        DependencyStore::new(backend)
+    });
+
+    // Register DependencyStore as OperationObserver to follow task completion
+    services.add_trait_factory::<dyn OperationObserver, _>(Lifetime::Singleton, |resolver| {
+        resolver.get_required::<DependencyStore>() as Arc<dyn OperationObserver>
+    });
+
+    // Register CustomFieldStore for user-defined fields on any entity
+    services.add_singleton_factory::<CustomFieldStore, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+
+        // Initialize custom field tables
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let store = CustomFieldStore::new(backend_for_init);
+            store
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize custom field tables");
+        });
+
+        CustomFieldStore::new(backend)
+    });
+
+    // Register AttachmentStore for file attachments on any entity
+    services.add_singleton_factory::<AttachmentStore, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+
+        // Initialize attachments table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let store = AttachmentStore::new(backend_for_init);
+            store
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize attachments table");
+        });
+
+        AttachmentStore::new(backend)
+    });
+
+    // Register EntityIdentityStore mapping IDs across datasources (used by `resolve`)
+    services.add_singleton_factory::<EntityIdentityStore, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+
+        // Initialize entity_identities table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let store = EntityIdentityStore::new(backend_for_init);
+            store
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize entity_identities table");
+        });
+
+        EntityIdentityStore::new(backend)
+    });
+
+    // Register EntityAccessStore recording when entities were viewed and modified (used by `recently_viewed`)
+    services.add_singleton_factory::<EntityAccessStore, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+
+        // Initialize entity_access table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let store = EntityAccessStore::new(backend_for_init);
+            store
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize entity_access table");
+        });
+
+        EntityAccessStore::new(backend)
+    });
+
+    // Register ViewStateStore persisting collapsed nodes, selection and scroll position of views
+    services.add_singleton_factory::<ViewStateStore, _>(move |resolver| {
+        let backend_arc = resolver.get_required::<RwLock<TursoBackend>>();
+        let backend = backend_arc.clone();
+
+        // Initialize view_states table
+        let backend_for_init = backend.clone();
+        block_on_in_thread(move || async move {
+            let store = ViewStateStore::new(backend_for_init);
+            store
+                .initialize_schema()
+                .await
+                .expect("Failed to initialize view_states table");
+        });
+
+        ViewStateStore::new(backend)
     });
 
     // Register OperationModule to collect providers from DI and create OperationDispatcher
@@ -252,57 +712,166 @@ pub fn register_core_services(services: &mut ServiceCollection, db_path: PathBuf
         // Get transform pipeline
         let transform_pipeline = resolver.get_required::<TransformPipeline>();
 
-        let db_path_config: Arc<DatabasePathConfig> = resolver.get_required::<DatabasePathConfig>();
-        let db_path_for_thread = db_path_config.path.clone();
+        // Get usage statistics store
+        let usage_stats = resolver.get_required::<OperationUsageStore>();
+
+        // Get operation log store
+        let operation_log = resolver.get_required::<OperationLogStore>();
+
+        // Get temporary ID mapping service
+        let id_mapping = resolver.get_required::<IdMappingService>();
+
+        // Get sync health store
+        let sync_health = resolver.get_required::<SyncHealthStore>();
+
+        // Get unsynced-changes store
+        let sync_dirty = resolver.get_required::<SyncDirtyStore>();
+
+        // Get sync scheduler
+        let sync_scheduler = resolver.get_required::<SyncScheduler>();
+
+        // Get backlink index
+        let backlinks = resolver.get_required::<BacklinkIndex>();
+
+        // Get tag index
+        let tags = resolver.get_required::<TagIndex>();
+
+        // Get automation rules
+        let automation_rules = resolver.get_required::<AutomationRules>();
+
+        // Get comments on entities
+        let annotations = resolver.get_required::<AnnotationStore>();
 
+        // Get encrypted sync log
+        let sync_blobs = resolver.get_required::<SyncBlobLog>();
+
+        // Get cross-datasource identity mapping
+        let identities = resolver.get_required::<EntityIdentityStore>();
+
+        // Get database maintenance scheduler
+        let maintenance = resolver.get_required::<MaintenanceScheduler>();
+
+        // Get reminder scheduler
+        let reminders = resolver.get_required::<ReminderScheduler>();
+
+        // Get task dependencies
+        let dependencies = resolver.get_required::<DependencyStore>();
+
+        // Get persisted UI state of views
+        let view_states = resolver.get_required::<ViewStateStore>();
+
+        // Get entity access timestamps
+        let entity_access = resolver.get_required::<EntityAccessStore>();
+
+        // Get sync conflict reconciler
+        let sync_reconciler = resolver.get_required::<SyncReconciler>();
+
+        // Optional trash retention (defaults to 30 days)
+        let trash_config = resolver
+            .get::<TrashConfig>()
+            .map(|c| (*c).clone())
+            .unwrap_or_default();
+
+        // Optional archival rules (none by default), plus the rules providers contribute
+        // for the archive of their source
+        let archival_config = resolver
+            .get::<ArchivalConfig>()
+            .map(|c| (*c).clone())
+            .unwrap_or_default();
+        let archival_config = resolver
+            .get_all_trait::<dyn ArchivalRuleProvider>()
+            .unwrap_or_else(|_| vec![])
+            .iter()
+            .fold(archival_config, |config, provider| {
+                config.with_rules(provider.archival_rules())
+            });
+
+        // Optional attribution of changes (registered by frontends, e.g. with their name)
+        let change_source = resolver
+            .get::<ChangeSource>()
+            .map(|source| (*source).clone())
+            .unwrap_or_default();
+
+        // Optional capture of recent log events (registered by frontends that install its layer)
+        let log_buffer = resolver.get::<LogBuffer>().map(|b| (*b).clone());
+
+        // Optional views defined by .prql files (registered by frontends that have a view directory)
+        let view_loader = resolver
+            .get::<ViewLoaderConfig>()
+            .map(|config| Arc::new(ViewLoader::new((*config).clone())));
+
+        // Optional periodic snapshots (registered by frontends that have a snapshot directory)
+        let snapshots = resolver
+            .get::<SnapshotConfig>()
+            .map(|config| Arc::new(SnapshotStore::new(backend.clone(), (*config).clone())));
+
+        // Optional presence of collaborators (registered by frontends that join a presence channel)
+        let presence = resolver
+            .get::<PresenceConfig>()
+            .map(|config| Arc::new(PresenceTracker::new(backend.clone(), (*config).clone())));
+
+        // Optional remote replica (registered by frontends that configure a sync URL)
         #[cfg(not(target_arch = "wasm32"))]
-        {
-            // Create engine in a new thread with its own runtime to avoid "runtime within runtime" error
-            let backend_clone = backend.clone();
-            let dispatcher_clone = dispatcher.clone();
-            let pipeline_clone = transform_pipeline.clone();
-            std::thread::spawn(move || {
-                let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
-
-                rt.block_on(async {
-                    let engine = BackendEngine::from_dependencies(
-                        backend_clone,
-                        dispatcher_clone,
-                        pipeline_clone,
-                    )
-                    .expect("Failed to create BackendEngine");
-
-                    // Initialize database schema and sample data if needed
-                    engine
-                        .initialize_database_if_needed(&db_path_for_thread)
-                        .await
-                        .expect("Failed to initialize database");
-
-                    engine
-                })
-            })
-            .join()
-            .expect("Thread panicked while creating BackendEngine")
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            // On WASM, we can't spawn threads, so we need to use the current runtime
-            // This assumes we're already in an async context with a runtime
-            let rt = tokio::runtime::Handle::current();
-            rt.block_on(async {
-                let engine =
-                    BackendEngine::from_dependencies(backend, dispatcher, transform_pipeline)
-                        .expect("Failed to create BackendEngine");
-
-                // Initialize database schema and sample data if needed
-                engine
-                    .initialize_database_if_needed(&db_path_for_thread)
-                    .await
-                    .expect("Failed to initialize database");
+        let replica_config = resolver
+            .get::<ReplicaConfig>()
+            .map(|config| (*config).clone());
 
-                engine
-            })
-        }
+        let db_path_config: Arc<DatabasePathConfig> = resolver.get_required::<DatabasePathConfig>();
+        let db_path_for_thread = db_path_config.path.clone();
+
+        block_on_in_thread(move || async move {
+            let mut engine =
+                BackendEngine::from_dependencies(backend, dispatcher, transform_pipeline)
+                    .expect("Failed to create BackendEngine")
+                    .with_usage_stats(usage_stats)
+                    .with_operation_log(operation_log)
+                    .with_id_mapping(id_mapping)
+                    .with_sync_health(sync_health)
+                    .with_sync_dirty(sync_dirty)
+                    .with_sync_scheduler(sync_scheduler)
+                    .with_trash_config(trash_config)
+                    .with_archival_config(archival_config)
+                    .with_backlinks(backlinks)
+                    .with_tags(tags)
+                    .with_automation_rules(automation_rules)
+                    .with_annotations(annotations)
+                    .with_sync_blobs(sync_blobs)
+                    .with_identities(identities)
+                    .with_maintenance(maintenance)
+                    .with_reminders(reminders)
+                    .with_dependencies(dependencies)
+                    .with_view_states(view_states)
+                    .with_entity_access(entity_access)
+                    .with_sync_reconciler(sync_reconciler)
+                    .with_change_source(change_source);
+            if let Some(log_buffer) = log_buffer {
+                engine = engine.with_log_buffer(log_buffer);
+            }
+            if let Some(view_loader) = view_loader {
+                engine = engine.with_view_loader(view_loader);
+            }
+            if let Some(snapshots) = snapshots {
+                engine = engine.with_snapshots(snapshots);
+            }
+            if let Some(presence) = presence {
+                engine = engine.with_presence(presence);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(config) = replica_config {
+                match ReplicaSyncer::open(&db_path_for_thread, config).await {
+                    Ok(replica) => engine = engine.with_replica(Arc::new(replica)),
+                    Err(e) => tracing::warn!("Failed to open database replica: {}", e),
+                }
+            }
+
+            // Initialize database schema and sample data if needed
+            engine
+                .initialize_database_if_needed(&db_path_for_thread)
+                .await
+                .expect("Failed to initialize database");
+
+            engine
+        })
     });
 
     Ok(())
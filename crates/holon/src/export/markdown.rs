@@ -0,0 +1,185 @@
+//! Markdown rendering of query results
+//!
+//! Rows with `id`/`parent_id` columns (e.g. blocks) render as a nested bullet
+//! outline ordered by `sort_key`; any other result set renders as a table.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use holon_api::Value;
+
+type Row = HashMap<String, Value>;
+
+/// Plain-text form of a value for Markdown output
+pub fn value_to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) | Value::DateTime(s) | Value::Json(s) | Value::Reference(s) => s.clone(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Boolean(b) => b.to_string(),
+        Value::Array(_) | Value::Object(_) => serde_json::to_string(value).unwrap_or_default(),
+        Value::Null => String::new(),
+    }
+}
+
+fn row_text(row: &Row, column: &str) -> String {
+    row.get(column).map(value_to_text).unwrap_or_default()
+}
+
+fn is_completed(row: &Row) -> bool {
+    match row.get("completed") {
+        Some(Value::Boolean(b)) => *b,
+        Some(Value::Integer(i)) => *i != 0,
+        _ => false,
+    }
+}
+
+/// Whether rows carry the hierarchy columns needed for `render_outline`
+pub fn is_outline(rows: &[Row]) -> bool {
+    rows.first()
+        .is_some_and(|row| row.contains_key("id") && row.contains_key("parent_id"))
+}
+
+/// Render hierarchical rows as a nested bullet list
+///
+/// Rows whose parent is not part of the result are top-level items. Siblings are
+/// ordered by `sort_key` (then `id`); completed rows get a checked task marker.
+pub fn render_outline(rows: &[Row]) -> String {
+    let ids: HashSet<String> = rows.iter().map(|row| row_text(row, "id")).collect();
+    let mut children: HashMap<Option<String>, Vec<&Row>> = HashMap::new();
+    for row in rows {
+        let parent = Some(row_text(row, "parent_id")).filter(|p| ids.contains(p));
+        children.entry(parent).or_default().push(row);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|row| (row_text(row, "sort_key"), row_text(row, "id")));
+    }
+
+    let mut out = String::new();
+    write_outline_level(&children, None, 0, &mut out);
+    out
+}
+
+fn write_outline_level(
+    children: &HashMap<Option<String>, Vec<&Row>>,
+    parent: Option<String>,
+    depth: usize,
+    out: &mut String,
+) {
+    let Some(siblings) = children.get(&parent) else {
+        return;
+    };
+    for row in siblings {
+        let indent = "  ".repeat(depth);
+        let marker = if is_completed(row) { "- [x] " } else { "- " };
+        let mut lines = row_text(row, "content")
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        out.push_str(&format!("{}{}{}\n", indent, marker, lines[0]));
+        // Continuation lines of multi-line content stay inside the list item
+        for line in &lines[1..] {
+            out.push_str(&format!("{}  {}\n", indent, line));
+        }
+        write_outline_level(children, Some(row_text(row, "id")), depth + 1, out);
+    }
+}
+
+/// Render rows as a Markdown table
+///
+/// Columns are the union of all row keys in alphabetical order, except internal
+/// columns starting with `_` (e.g. `_change_origin`).
+pub fn render_table(rows: &[Row]) -> String {
+    let columns: Vec<&String> = rows
+        .iter()
+        .flat_map(|row| row.keys())
+        .filter(|key| !key.starts_with('_'))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    if columns.is_empty() {
+        return String::new();
+    }
+
+    let cell = |text: String| text.replace('|', "\\|").replace('\n', "<br>");
+    let mut out = String::new();
+    out.push_str(&format!(
+        "| {} |\n",
+        columns
+            .iter()
+            .map(|c| cell(c.to_string()))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    ));
+    out.push_str(&format!("|{}\n", " --- |".repeat(columns.len())));
+    for row in rows {
+        let cells: Vec<String> = columns.iter().map(|c| cell(row_text(row, c))).collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
+
+/// Render a complete document: optional title heading followed by the rows
+pub fn render_document(title: Option<&str>, rows: &[Row]) -> String {
+    let mut out = String::new();
+    if let Some(title) = title {
+        out.push_str(&format!("# {}\n\n", title));
+    }
+    if is_outline(rows) {
+        out.push_str(&render_outline(rows));
+    } else {
+        out.push_str(&render_table(rows));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: &str, parent: Option<&str>, sort_key: &str, content: &str) -> Row {
+        HashMap::from([
+            ("id".to_string(), Value::String(id.to_string())),
+            (
+                "parent_id".to_string(),
+                parent.map_or(Value::Null, |p| Value::String(p.to_string())),
+            ),
+            ("sort_key".to_string(), Value::String(sort_key.to_string())),
+            ("content".to_string(), Value::String(content.to_string())),
+        ])
+    }
+
+    #[test]
+    fn test_outline_nesting_and_order() {
+        let mut done = block("c2", Some("r1"), "a0", "First child");
+        done.insert("completed".to_string(), Value::Integer(1));
+        let rows = vec![
+            block("r2", None, "a1", "Second root"),
+            block("c1", Some("r1"), "a1", "Second child\ncontinued"),
+            block("r1", None, "a0", "First root"),
+            done,
+        ];
+
+        assert_eq!(
+            render_outline(&rows),
+            "- First root\n  - [x] First child\n  - Second child\n    continued\n- Second root\n"
+        );
+    }
+
+    #[test]
+    fn test_table_skips_internal_columns_and_escapes() {
+        let rows = vec![HashMap::from([
+            ("content".to_string(), Value::String("a|b".to_string())),
+            ("priority".to_string(), Value::Integer(2)),
+            ("_change_origin".to_string(), Value::Null),
+        ])];
+
+        assert!(!is_outline(&rows));
+        assert_eq!(
+            render_document(Some("Tasks"), &rows),
+            "# Tasks\n\n| content | priority |\n| --- | --- |\n| a\\|b | 2 |\n"
+        );
+    }
+}
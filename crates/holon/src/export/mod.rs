@@ -0,0 +1,23 @@
+//! Plain-text exports
+//!
+//! - `markdown`: render query results and block outlines as Markdown
+//! - `outline`: Markdown/Org outline files that parse back into the same blocks
+//! - `query_results`: stream query results into CSV or JSONL files
+//! - `scheduler`: keep a Markdown directory mirror of configured queries/subtrees up to date
+//! - `workspace`: export all block trees as outline files
+
+pub mod markdown;
+pub mod outline;
+pub mod query_results;
+pub mod scheduler;
+pub mod workspace;
+
+pub use outline::{ExportFormat, OutlineBlock};
+pub use query_results::{
+    QUERY_EXPORT_PROGRESS_INTERVAL, QueryExportFormat, QueryExportProgress, QueryResultWriter,
+};
+pub use scheduler::{
+    ExportSource, ExportSummary, ExportTarget, ExportTrigger, MarkdownExportConfig,
+    MarkdownExporter,
+};
+pub use workspace::export_workspace;
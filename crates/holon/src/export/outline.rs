@@ -0,0 +1,544 @@
+//! Outline files
+//!
+//! The Markdown and Org files of the workspace export. A file holds one root block
+//! and its descendants. Everything a block has besides its content and completion
+//! state is written as a property, so the files parse back into the same blocks:
+//!
+//! ```text
+//! ---                          :PROPERTIES:
+//! id: inbox                    :ID: inbox
+//! title: Inbox                 :END:
+//! ---                          #+TITLE: Inbox
+//!
+//! - [x] Buy milk               * DONE Buy milk
+//!   id:: b1                    :PROPERTIES:
+//!   store:: corner shop        :ID: b1
+//!                              :store: corner shop
+//!                              :END:
+//! ```
+//!
+//! Content lines that would otherwise read as markup are escaped with a leading `,`.
+//! Property values that aren't plain one-line strings are written as JSON.
+
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+/// Format of outline files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Org,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Org => "org",
+        }
+    }
+
+    /// Format of the outline file at `path`, by file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "md" | "markdown" => Some(ExportFormat::Markdown),
+            "org" => Some(ExportFormat::Org),
+            _ => None,
+        }
+    }
+}
+
+const ID_KEY: &str = "id";
+const TITLE_KEY: &str = "title";
+const COMPLETED_KEY: &str = "completed";
+const COLLAPSED_KEY: &str = "collapsed";
+const BLOCK_TYPE_KEY: &str = "block-type";
+const DEFAULT_BLOCK_TYPE: &str = "text";
+
+/// A block and its descendants as stored in an outline file
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineBlock {
+    /// `None` for blocks written by hand without an id
+    pub id: Option<String>,
+    pub content: String,
+    pub collapsed: bool,
+    pub completed: bool,
+    pub block_type: String,
+    pub properties: Map<String, Value>,
+    pub children: Vec<OutlineBlock>,
+}
+
+impl OutlineBlock {
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            id: None,
+            content: content.into(),
+            collapsed: false,
+            completed: false,
+            block_type: DEFAULT_BLOCK_TYPE.to_string(),
+            properties: Map::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Properties to write (block columns first), with encoded values
+    fn property_lines(&self) -> Vec<(String, String)> {
+        let mut lines = Vec::new();
+        if let Some(id) = &self.id {
+            lines.push((ID_KEY.to_string(), id.clone()));
+        }
+        if self.block_type != DEFAULT_BLOCK_TYPE {
+            lines.push((BLOCK_TYPE_KEY.to_string(), self.block_type.clone()));
+        }
+        if self.collapsed {
+            lines.push((COLLAPSED_KEY.to_string(), "true".to_string()));
+        }
+        for (key, value) in &self.properties {
+            if !is_reserved_key(key) {
+                lines.push((key.clone(), encode_value(value)));
+            }
+        }
+        lines
+    }
+
+    /// Apply a property read from a file
+    fn set_property(&mut self, key: &str, raw: &str) {
+        match key {
+            ID_KEY => self.id = Some(raw.to_string()),
+            BLOCK_TYPE_KEY => self.block_type = raw.to_string(),
+            COLLAPSED_KEY => self.collapsed = raw == "true",
+            _ => {
+                self.properties.insert(key.to_string(), decode_value(raw));
+            }
+        }
+    }
+}
+
+fn is_reserved_key(key: &str) -> bool {
+    matches!(
+        key,
+        ID_KEY | TITLE_KEY | COMPLETED_KEY | COLLAPSED_KEY | BLOCK_TYPE_KEY
+    )
+}
+
+/// Property value as written to a file: plain one-line strings as-is, anything else as JSON
+pub fn encode_value(value: &Value) -> String {
+    match value {
+        Value::String(s)
+            if !s.contains('\n') && s.trim() == s && serde_json::from_str::<Value>(s).is_err() =>
+        {
+            s.clone()
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Inverse of `encode_value`
+pub fn decode_value(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+fn decode_string(raw: &str) -> String {
+    match decode_value(raw) {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+/// `key:: value` (Markdown block property)
+fn markdown_property(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once("::")?;
+    let valid_key = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    (valid_key && (value.is_empty() || value.starts_with(' '))).then_some((key, value.trim()))
+}
+
+fn escape(line: &str, needs_escape: bool) -> String {
+    if needs_escape {
+        format!(",{}", line)
+    } else {
+        line.to_string()
+    }
+}
+
+fn unescape(line: &str) -> &str {
+    line.strip_prefix(',').unwrap_or(line)
+}
+
+fn content_lines(content: &str) -> Vec<&str> {
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() { vec![""] } else { lines }
+}
+
+/// Render `root` and its descendants in `format`
+pub fn render(root: &OutlineBlock, format: ExportFormat) -> String {
+    let mut out = String::new();
+    match format {
+        ExportFormat::Markdown => {
+            out.push_str("---\n");
+            if let Some(id) = &root.id {
+                out.push_str(&format!("{}: {}\n", ID_KEY, id));
+            }
+            out.push_str(&format!(
+                "{}: {}\n",
+                TITLE_KEY,
+                encode_value(&Value::String(root.content.clone()))
+            ));
+            if root.completed {
+                out.push_str(&format!("{}: true\n", COMPLETED_KEY));
+            }
+            for (key, value) in root
+                .property_lines()
+                .into_iter()
+                .filter(|(key, _)| key != ID_KEY)
+            {
+                out.push_str(&format!("{}: {}\n", key, value));
+            }
+            out.push_str("---\n\n");
+            for child in &root.children {
+                write_markdown_item(child, 0, &mut out);
+            }
+        }
+        ExportFormat::Org => {
+            let mut properties = root.property_lines();
+            if root.completed {
+                properties.push((COMPLETED_KEY.to_string(), "true".to_string()));
+            }
+            write_org_drawer(&properties, &mut out);
+            out.push_str(&format!(
+                "#+TITLE: {}\n\n",
+                encode_value(&Value::String(root.content.clone()))
+            ));
+            for child in &root.children {
+                write_org_headline(child, 1, &mut out);
+            }
+        }
+    }
+    out
+}
+
+fn markdown_needs_escape(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with('-')
+        || trimmed.starts_with(',')
+        || trimmed.starts_with("[x]")
+        || trimmed.starts_with("[ ]")
+        || markdown_property(trimmed).is_some()
+}
+
+fn write_markdown_item(block: &OutlineBlock, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    let lines = content_lines(&block.content);
+    let marker = if block.completed { "- [x] " } else { "- " };
+    out.push_str(&format!(
+        "{}{}{}\n",
+        indent,
+        marker,
+        escape(lines[0], markdown_needs_escape(lines[0]))
+    ));
+    for line in &lines[1..] {
+        out.push_str(&format!(
+            "{}  {}\n",
+            indent,
+            escape(line, markdown_needs_escape(line))
+        ));
+    }
+    for (key, value) in block.property_lines() {
+        out.push_str(&format!("{}  {}:: {}\n", indent, key, value));
+    }
+    for child in &block.children {
+        write_markdown_item(child, depth + 1, out);
+    }
+}
+
+fn org_needs_escape(line: &str, headline: bool) -> bool {
+    line.starts_with('*')
+        || line.starts_with(',')
+        || line.starts_with(':')
+        || line.starts_with("#+")
+        || (headline && (line.starts_with("DONE ") || line.starts_with("TODO ")))
+}
+
+fn write_org_drawer(properties: &[(String, String)], out: &mut String) {
+    if properties.is_empty() {
+        return;
+    }
+    out.push_str(":PROPERTIES:\n");
+    for (key, value) in properties {
+        let key = if key == ID_KEY { "ID" } else { key.as_str() };
+        out.push_str(&format!(":{}: {}\n", key, value));
+    }
+    out.push_str(":END:\n");
+}
+
+fn write_org_headline(block: &OutlineBlock, level: usize, out: &mut String) {
+    let lines = content_lines(&block.content);
+    let keyword = if block.completed { "DONE " } else { "" };
+    out.push_str(&format!(
+        "{} {}{}\n",
+        "*".repeat(level),
+        keyword,
+        escape(lines[0], org_needs_escape(lines[0], true))
+    ));
+    write_org_drawer(&block.property_lines(), out);
+    for line in &lines[1..] {
+        out.push_str(&escape(line, org_needs_escape(line, false)));
+        out.push('\n');
+    }
+    for child in &block.children {
+        write_org_headline(child, level + 1, out);
+    }
+}
+
+/// Parse an outline file written by `render` (or by hand in the same format)
+///
+/// The root block is described by the front matter (Markdown) or the properties
+/// drawer and `#+TITLE:` before the first headline (Org).
+pub fn parse(text: &str, format: ExportFormat) -> OutlineBlock {
+    match format {
+        ExportFormat::Markdown => parse_markdown(text),
+        ExportFormat::Org => parse_org(text),
+    }
+}
+
+/// Blocks in document order with their depth below the root, assembled into a tree
+fn build_tree(mut root: OutlineBlock, items: Vec<(usize, OutlineBlock)>) -> OutlineBlock {
+    fn close(stack: &mut Vec<(usize, OutlineBlock)>, root: &mut OutlineBlock) {
+        if let Some((_, block)) = stack.pop() {
+            match stack.last_mut() {
+                Some((_, parent)) => parent.children.push(block),
+                None => root.children.push(block),
+            }
+        }
+    }
+
+    let mut stack: Vec<(usize, OutlineBlock)> = Vec::new();
+    for (depth, block) in items {
+        while stack.last().is_some_and(|(d, _)| *d >= depth) {
+            close(&mut stack, &mut root);
+        }
+        stack.push((depth, block));
+    }
+    while !stack.is_empty() {
+        close(&mut stack, &mut root);
+    }
+    root
+}
+
+fn parse_markdown(text: &str) -> OutlineBlock {
+    let mut root = OutlineBlock::new("");
+    let mut lines = text.lines().map(|l| l.trim_end_matches('\r')).peekable();
+
+    if lines.peek() == Some(&"---") {
+        lines.next();
+        for line in lines.by_ref() {
+            if line == "---" {
+                break;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                TITLE_KEY => root.content = decode_string(value),
+                COMPLETED_KEY => root.completed = value == "true",
+                _ => root.set_property(key, value),
+            }
+        }
+    }
+
+    // (indent of the item's "- ", block) for every item, in document order
+    let mut items: Vec<(usize, OutlineBlock)> = Vec::new();
+    let mut content: Vec<String> = Vec::new();
+    for line in lines {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed == "-" || trimmed.starts_with("- ") {
+            if let Some((_, block)) = items.last_mut() {
+                block.content = std::mem::take(&mut content).join("\n");
+            }
+            let mut first = trimmed.strip_prefix('-').unwrap_or_default();
+            first = first.strip_prefix(' ').unwrap_or(first);
+            let mut block = OutlineBlock::new("");
+            if let Some(rest) = first.strip_prefix("[x]") {
+                block.completed = true;
+                first = rest.strip_prefix(' ').unwrap_or(rest);
+            }
+            content.push(unescape(first).to_string());
+            items.push((indent, block));
+            continue;
+        }
+
+        let Some((item_indent, block)) = items.last_mut() else {
+            continue;
+        };
+        // Continuation lines are indented two spaces past the item's "- "
+        let body = line.get(*item_indent + 2..).unwrap_or_default();
+        match markdown_property(body) {
+            Some((key, value)) if body == trimmed => block.set_property(key, value),
+            _ => content.push(unescape(body).to_string()),
+        }
+    }
+    if let Some((_, block)) = items.last_mut() {
+        block.content = content.join("\n");
+    }
+
+    let items = items
+        .into_iter()
+        .map(|(indent, block)| (indent / 2, block))
+        .collect();
+    build_tree(root, items)
+}
+
+fn parse_org(text: &str) -> OutlineBlock {
+    let mut root = OutlineBlock::new("");
+    let mut items: Vec<(usize, OutlineBlock)> = Vec::new();
+    let mut content: Vec<String> = Vec::new();
+    let mut in_drawer = false;
+    // Whether the drawer may still start (only directly after the headline)
+    let mut drawer_allowed = true;
+
+    for line in text.lines().map(|l| l.trim_end_matches('\r')) {
+        let stars = line.chars().take_while(|c| *c == '*').count();
+        if stars > 0 && (line.len() == stars || line[stars..].starts_with(' ')) {
+            if let Some((_, block)) = items.last_mut() {
+                block.content = std::mem::take(&mut content).join("\n");
+            }
+            let mut title = line.get(stars + 1..).unwrap_or_default();
+            let mut block = OutlineBlock::new("");
+            if let Some(rest) = title.strip_prefix("DONE ") {
+                block.completed = true;
+                title = rest;
+            }
+            content.push(unescape(title).to_string());
+            items.push((stars - 1, block));
+            in_drawer = false;
+            drawer_allowed = true;
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if in_drawer {
+            if trimmed == ":END:" {
+                in_drawer = false;
+                continue;
+            }
+            let Some((key, value)) = trimmed
+                .strip_prefix(':')
+                .and_then(|rest| rest.split_once(':'))
+            else {
+                continue;
+            };
+            let key = if key.eq_ignore_ascii_case(ID_KEY) {
+                ID_KEY
+            } else {
+                key
+            };
+            let value = value.trim();
+            match items.last_mut() {
+                Some((_, block)) => block.set_property(key, value),
+                None if key == COMPLETED_KEY => root.completed = value == "true",
+                None => root.set_property(key, value),
+            }
+            continue;
+        }
+        if drawer_allowed && trimmed == ":PROPERTIES:" {
+            in_drawer = true;
+            continue;
+        }
+        drawer_allowed = false;
+
+        if items.is_empty() {
+            if let Some(title) = line.strip_prefix("#+TITLE:") {
+                root.content = decode_string(title.trim());
+            }
+            continue;
+        }
+        content.push(unescape(line).to_string());
+    }
+    if let Some((_, block)) = items.last_mut() {
+        block.content = content.join("\n");
+    }
+
+    build_tree(root, items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> OutlineBlock {
+        let mut root = OutlineBlock::new("Inbox");
+        root.id = Some("inbox".to_string());
+        root.properties
+            .insert("area".to_string(), Value::String("home".to_string()));
+
+        let mut first = OutlineBlock::new("Buy milk\n- not a child\nstore:: not a property");
+        first.id = Some("b1".to_string());
+        first.completed = true;
+        first.collapsed = true;
+        first.properties.insert("count".to_string(), Value::from(2));
+        first
+            .properties
+            .insert("code".to_string(), Value::String("42".to_string()));
+
+        let mut nested = OutlineBlock::new("* DONE tricky, [x] line");
+        nested.id = Some("b2".to_string());
+        nested.block_type = "heading".to_string();
+        first.children.push(nested);
+
+        let mut second = OutlineBlock::new("");
+        second.id = Some("b3".to_string());
+        root.children = vec![first, second];
+        root
+    }
+
+    #[test]
+    fn test_markdown_round_trip() {
+        let root = sample();
+        let markdown = render(&root, ExportFormat::Markdown);
+        assert!(markdown.starts_with("---\nid: inbox\ntitle: Inbox\narea: home\n---\n\n"));
+        assert!(markdown.contains("- [x] Buy milk\n  ,- not a child\n"));
+        assert_eq!(parse(&markdown, ExportFormat::Markdown), root);
+    }
+
+    #[test]
+    fn test_org_round_trip() {
+        let root = sample();
+        let org = render(&root, ExportFormat::Org);
+        assert!(org.starts_with(":PROPERTIES:\n:ID: inbox\n:area: home\n:END:\n#+TITLE: Inbox\n"));
+        assert!(org.contains("\n** ,* DONE tricky, [x] line\n:PROPERTIES:\n:ID: b2\n"));
+        assert_eq!(parse(&org, ExportFormat::Org), root);
+    }
+
+    #[test]
+    fn test_parse_hand_written_markdown() {
+        let root = parse(
+            "- Top\n  - Child\n    more\n- [x] Done\n",
+            ExportFormat::Markdown,
+        );
+        assert_eq!(root.id, None);
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.children[0].children[0].content, "Child\nmore");
+        assert!(root.children[1].completed);
+    }
+
+    #[test]
+    fn test_value_encoding() {
+        for value in [
+            Value::String("plain".to_string()),
+            Value::String("42".to_string()),
+            Value::String(" padded".to_string()),
+            Value::String("two\nlines".to_string()),
+            Value::from(42),
+            Value::Bool(true),
+            serde_json::json!(["a", "b"]),
+        ] {
+            assert_eq!(decode_value(&encode_value(&value)), value);
+        }
+        assert_eq!(encode_value(&Value::String("plain".to_string())), "plain");
+    }
+}
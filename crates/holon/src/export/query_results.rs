@@ -0,0 +1,191 @@
+//! Query results as CSV or JSONL
+//!
+//! `QueryResultWriter` writes rows as the database cursor yields them (see
+//! `TursoBackend::for_each_row`), so exporting a large result needs constant memory.
+//! Internal columns, prefixed with `_` (like `_change_origin`), are left out.
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use holon_api::Value;
+
+use crate::core::operation_log::csv_field;
+
+/// Rows between two progress reports of an export
+pub const QUERY_EXPORT_PROGRESS_INTERVAL: usize = 1000;
+
+/// File format of exported query results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryExportFormat {
+    /// RFC 4180 CSV with a header row
+    Csv,
+    /// One JSON object per row and line
+    Jsonl,
+}
+
+impl QueryExportFormat {
+    /// Format of a file by its extension (`.csv`, `.jsonl` or `.ndjson`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "jsonl" | "ndjson" => Some(Self::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+/// Progress of a running export
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryExportProgress {
+    /// Rows written so far
+    pub rows: usize,
+}
+
+/// Writes query result rows one at a time
+pub struct QueryResultWriter<W: Write> {
+    writer: W,
+    format: QueryExportFormat,
+    /// Indices of the exported columns, known with the first row
+    exported: Option<Vec<usize>>,
+    rows: usize,
+}
+
+impl<W: Write> QueryResultWriter<W> {
+    pub fn new(writer: W, format: QueryExportFormat) -> Self {
+        Self {
+            writer,
+            format,
+            exported: None,
+            rows: 0,
+        }
+    }
+
+    /// Number of rows written
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Write a row, given the result's columns and the row's values in column order
+    pub fn write_row(&mut self, columns: &[String], values: &[Value]) -> io::Result<()> {
+        if self.exported.is_none() {
+            self.start(columns)?;
+        }
+        let exported = self.exported.as_deref().unwrap_or_default();
+        match self.format {
+            QueryExportFormat::Csv => {
+                let fields: Vec<String> = exported
+                    .iter()
+                    .map(|&idx| csv_field(&csv_value(values.get(idx).unwrap_or(&Value::Null))))
+                    .collect();
+                writeln!(self.writer, "{}", fields.join(","))?;
+            }
+            QueryExportFormat::Jsonl => {
+                // Written field by field to keep the column order
+                self.writer.write_all(b"{")?;
+                for (i, &idx) in exported.iter().enumerate() {
+                    if i > 0 {
+                        self.writer.write_all(b",")?;
+                    }
+                    serde_json::to_writer(&mut self.writer, &columns[idx])?;
+                    self.writer.write_all(b":")?;
+                    let value = values.get(idx).cloned().unwrap_or(Value::Null);
+                    serde_json::to_writer(&mut self.writer, &serde_json::Value::from(value))?;
+                }
+                self.writer.write_all(b"}\n")?;
+            }
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Flush the output, writing the CSV header if there were no rows
+    ///
+    /// Returns the number of written rows.
+    pub fn finish(mut self, columns: &[String]) -> io::Result<usize> {
+        if self.exported.is_none() {
+            self.start(columns)?;
+        }
+        self.writer.flush()?;
+        Ok(self.rows)
+    }
+
+    fn start(&mut self, columns: &[String]) -> io::Result<()> {
+        let exported: Vec<usize> = (0..columns.len())
+            .filter(|&idx| !columns[idx].starts_with('_'))
+            .collect();
+        if self.format == QueryExportFormat::Csv {
+            let header: Vec<String> = exported
+                .iter()
+                .map(|&idx| csv_field(&columns[idx]))
+                .collect();
+            writeln!(self.writer, "{}", header.join(","))?;
+        }
+        self.exported = Some(exported);
+        Ok(())
+    }
+}
+
+/// Text of a value in a CSV cell (empty for NULL, JSON for arrays and objects)
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::String(s) | Value::DateTime(s) | Value::Json(s) | Value::Reference(s) => s.clone(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Boolean(b) => b.to_string(),
+        Value::Array(_) | Value::Object(_) => value.to_json_string(),
+        Value::Null => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(format: QueryExportFormat, rows: &[Vec<Value>]) -> String {
+        let columns: Vec<String> = ["id", "content", "_change_origin", "tags"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let mut output = Vec::new();
+        let mut writer = QueryResultWriter::new(&mut output, format);
+        for row in rows {
+            writer.write_row(&columns, row).unwrap();
+        }
+        assert_eq!(writer.finish(&columns).unwrap(), rows.len());
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_export_rows() {
+        let rows = vec![
+            vec![
+                Value::Integer(1),
+                Value::String("Buy milk, eggs".to_string()),
+                Value::String("{}".to_string()),
+                Value::Array(vec![Value::String("home".to_string())]),
+            ],
+            vec![
+                Value::Integer(2),
+                Value::String("Say \"hi\"".to_string()),
+                Value::Null,
+                Value::Null,
+            ],
+        ];
+
+        assert_eq!(
+            export(QueryExportFormat::Csv, &rows),
+            "id,content,tags\n1,\"Buy milk, eggs\",\"[\"\"home\"\"]\"\n2,\"Say \"\"hi\"\"\",\n"
+        );
+        assert_eq!(
+            export(QueryExportFormat::Jsonl, &rows),
+            "{\"id\":1,\"content\":\"Buy milk, eggs\",\"tags\":[\"home\"]}\n\
+             {\"id\":2,\"content\":\"Say \\\"hi\\\"\",\"tags\":null}\n"
+        );
+        // An empty CSV result still has its header
+        assert_eq!(export(QueryExportFormat::Csv, &[]), "id,content,tags\n");
+        assert_eq!(
+            QueryExportFormat::from_path(Path::new("/tmp/tasks.NDJSON")),
+            Some(QueryExportFormat::Jsonl)
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Scheduled Markdown exports
+//!
+//! A `MarkdownExporter` renders a set of `ExportTarget`s (PRQL queries or block
+//! subtrees) into files below an output directory. It runs either on a fixed
+//! interval or whenever the exported data changes (debounced), so the directory
+//! is a continuously up-to-date plain-text mirror that can be grepped or
+//! committed to git. Files are only rewritten when their content changed.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use holon_api::Value;
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+use tracing::{debug, info, warn};
+
+use super::markdown::render_document;
+use crate::api::backend_engine::BackendEngine;
+
+/// What a target exports
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportSource {
+    /// Result rows of a PRQL query (as used by views)
+    Query {
+        prql: String,
+        params: HashMap<String, Value>,
+    },
+    /// A block and all of its descendants
+    Subtree { root_id: String },
+}
+
+/// A single exported file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportTarget {
+    /// File path relative to the output directory (e.g. `projects/inbox.md`)
+    pub path: PathBuf,
+    pub source: ExportSource,
+    /// Document heading; subtrees default to the root block's content
+    pub title: Option<String>,
+}
+
+/// When exports run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportTrigger {
+    /// Export everything every `every_ms`
+    Interval { every_ms: u64 },
+    /// Export after the exported data changed, once no change arrived for `debounce_ms`
+    OnChange { debounce_ms: u64 },
+}
+
+/// Configuration of the Markdown mirror
+#[derive(Debug, Clone)]
+pub struct MarkdownExportConfig {
+    pub output_dir: PathBuf,
+    pub targets: Vec<ExportTarget>,
+    pub trigger: ExportTrigger,
+}
+
+impl MarkdownExportConfig {
+    pub fn new(output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            output_dir: output_dir.into(),
+            targets: Vec::new(),
+            trigger: ExportTrigger::OnChange { debounce_ms: 2_000 },
+        }
+    }
+
+    pub fn with_target(mut self, target: ExportTarget) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    pub fn with_trigger(mut self, trigger: ExportTrigger) -> Self {
+        self.trigger = trigger;
+        self
+    }
+}
+
+/// Outcome of one export run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportSummary {
+    /// Files whose content changed and were rewritten
+    pub written: Vec<PathBuf>,
+    /// Files that were already up to date
+    pub unchanged: usize,
+    /// Targets that failed, with their error
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Renders export targets to a Markdown directory tree
+pub struct MarkdownExporter {
+    engine: Arc<BackendEngine>,
+    config: MarkdownExportConfig,
+}
+
+impl MarkdownExporter {
+    pub fn new(engine: Arc<BackendEngine>, config: MarkdownExportConfig) -> Result<Self> {
+        for target in &config.targets {
+            validate_relative_path(&target.path)?;
+        }
+        Ok(Self { engine, config })
+    }
+
+    pub fn config(&self) -> &MarkdownExportConfig {
+        &self.config
+    }
+
+    /// Render all targets and write the files that changed
+    ///
+    /// A failing target doesn't stop the others; failures are reported in the summary.
+    pub async fn export_all(&self) -> ExportSummary {
+        let mut summary = ExportSummary::default();
+        for target in &self.config.targets {
+            let result = match self.render_target(target).await {
+                Ok(markdown) => {
+                    write_if_changed(&self.config.output_dir.join(&target.path), &markdown)
+                }
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(true) => summary.written.push(target.path.clone()),
+                Ok(false) => summary.unchanged += 1,
+                Err(e) => {
+                    warn!("Markdown export of {} failed: {}", target.path.display(), e);
+                    summary.failed.push((target.path.clone(), e.to_string()));
+                }
+            }
+        }
+        debug!(
+            "Markdown export: {} written, {} unchanged, {} failed",
+            summary.written.len(),
+            summary.unchanged,
+            summary.failed.len()
+        );
+        summary
+    }
+
+    /// Render a single target to Markdown
+    pub async fn render_target(&self, target: &ExportTarget) -> Result<String> {
+        match &target.source {
+            ExportSource::Query { prql, params } => {
+                let compiled = self.engine.compile_query_cached(prql, params)?;
+                let rows = self
+                    .engine
+                    .execute_query(compiled.sql, params.clone())
+                    .await?;
+                Ok(render_document(target.title.as_deref(), &rows))
+            }
+            ExportSource::Subtree { root_id } => {
+                let (root, descendants) = self.load_subtree(root_id).await?;
+                let title = target
+                    .title
+                    .clone()
+                    .or_else(|| root.get("content").and_then(|v| v.as_string_owned()));
+                Ok(render_document(title.as_deref(), &descendants))
+            }
+        }
+    }
+
+    /// Load a block and its descendants (breadth-first, one query per level)
+    async fn load_subtree(
+        &self,
+        root_id: &str,
+    ) -> Result<(HashMap<String, Value>, Vec<HashMap<String, Value>>)> {
+        let params = HashMap::from([("id".to_string(), Value::String(root_id.to_string()))]);
+        let root = self
+            .engine
+            .execute_query("SELECT * FROM blocks WHERE id = $id".to_string(), params)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Block {} not found", root_id))?;
+
+        let mut descendants = Vec::new();
+        let mut seen = HashSet::from([root_id.to_string()]);
+        let mut queue = VecDeque::from([root_id.to_string()]);
+        while let Some(parent_id) = queue.pop_front() {
+            let params = HashMap::from([("parent_id".to_string(), Value::String(parent_id))]);
+            let children = self
+                .engine
+                .execute_query(
+                    "SELECT * FROM blocks WHERE parent_id = $parent_id".to_string(),
+                    params,
+                )
+                .await?;
+            for child in children {
+                if let Some(id) = child.get("id").and_then(|v| v.as_string_owned())
+                    && seen.insert(id.clone())
+                {
+                    queue.push_back(id);
+                    descendants.push(child);
+                }
+            }
+        }
+        Ok((root, descendants))
+    }
+
+    /// Run exports in the background according to the configured trigger
+    ///
+    /// An initial export runs immediately.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            match self.config.trigger {
+                ExportTrigger::Interval { every_ms } => {
+                    let mut interval =
+                        tokio::time::interval(std::time::Duration::from_millis(every_ms.max(1)));
+                    loop {
+                        interval.tick().await;
+                        self.export_all().await;
+                    }
+                }
+                ExportTrigger::OnChange { debounce_ms } => {
+                    self.run_on_change(std::time::Duration::from_millis(debounce_ms))
+                        .await;
+                }
+            }
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn run_on_change(&self, debounce: std::time::Duration) {
+        let (tx, mut rx) = mpsc::unbounded_channel::<()>();
+        for sql in self.watched_queries() {
+            match self.engine.watch_query(sql, HashMap::new()).await {
+                Ok(mut stream) => {
+                    let tx = tx.clone();
+                    tokio::spawn(async move {
+                        while stream.next().await.is_some() {
+                            if tx.send(()).is_err() {
+                                break;
+                            }
+                        }
+                    });
+                }
+                Err(e) => warn!("Markdown export can't watch for changes: {}", e),
+            }
+        }
+        drop(tx);
+
+        self.export_all().await;
+        while rx.recv().await.is_some() {
+            // Wait until changes stop arriving for the debounce period
+            while let Ok(Some(())) = tokio::time::timeout(debounce, rx.recv()).await {}
+            let summary = self.export_all().await;
+            if !summary.written.is_empty() {
+                info!("Markdown export updated {} file(s)", summary.written.len());
+            }
+        }
+    }
+
+    /// SQL of the queries whose changes trigger an export
+    fn watched_queries(&self) -> Vec<String> {
+        let mut queries = Vec::new();
+        for target in &self.config.targets {
+            let sql = match &target.source {
+                ExportSource::Query { prql, params } => {
+                    match self.engine.compile_query_cached(prql, params) {
+                        Ok(compiled) => compiled.sql,
+                        Err(e) => {
+                            warn!(
+                                "Can't compile export query for {}: {}",
+                                target.path.display(),
+                                e
+                            );
+                            continue;
+                        }
+                    }
+                }
+                ExportSource::Subtree { .. } => "SELECT * FROM blocks".to_string(),
+            };
+            if !queries.contains(&sql) {
+                queries.push(sql);
+            }
+        }
+        queries
+    }
+}
+
+/// Export paths must stay inside the output directory
+fn validate_relative_path(path: &Path) -> Result<()> {
+    let escapes = path
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if escapes || path.as_os_str().is_empty() {
+        return Err(anyhow!(
+            "Export path must be relative to the output directory: {}",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Write `content` unless the file already holds it; returns whether it was written
+pub(super) fn write_if_changed(path: &Path, content: &str) -> Result<bool> {
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_must_stay_inside_output_dir() {
+        assert!(validate_relative_path(Path::new("projects/inbox.md")).is_ok());
+        assert!(validate_relative_path(Path::new("../inbox.md")).is_err());
+        assert!(validate_relative_path(Path::new("/tmp/inbox.md")).is_err());
+        assert!(validate_relative_path(Path::new("")).is_err());
+    }
+
+    #[test]
+    fn test_write_if_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/notes.md");
+
+        assert!(write_if_changed(&path, "- a\n").unwrap());
+        assert!(!write_if_changed(&path, "- a\n").unwrap());
+        assert!(write_if_changed(&path, "- b\n").unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "- b\n");
+    }
+}
@@ -0,0 +1,213 @@
+//! Workspace export
+//!
+//! Writes every root block of the `blocks` table with its descendants to a file of
+//! its own (see `outline` for the file format), named after the root's content.
+//! Since block ids are part of the files, importing them again
+//! (`import::OutlineImporter`) updates the exported blocks instead of duplicating them.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use holon_api::Value;
+use serde_json::Map;
+
+use super::outline::{ExportFormat, OutlineBlock, render};
+use super::scheduler::{ExportSummary, write_if_changed};
+use crate::api::backend_engine::BackendEngine;
+
+type Row = HashMap<String, Value>;
+
+/// Write all block trees below `dir`, one file per root block
+///
+/// Files are only rewritten when their content changed.
+pub async fn export_workspace(
+    engine: &BackendEngine,
+    dir: &Path,
+    format: ExportFormat,
+) -> Result<ExportSummary> {
+    let rows = engine
+        .execute_query("SELECT * FROM blocks".to_string(), HashMap::new())
+        .await?;
+
+    let mut summary = ExportSummary::default();
+    let mut used_names = HashSet::new();
+    for root in outline_trees(&rows) {
+        let path = PathBuf::from(file_name(&root, format, &mut used_names));
+        match write_if_changed(&dir.join(&path), &render(&root, format)) {
+            Ok(true) => summary.written.push(path),
+            Ok(false) => summary.unchanged += 1,
+            Err(e) => summary.failed.push((path, e.to_string())),
+        }
+    }
+    Ok(summary)
+}
+
+/// Block rows assembled into one tree per root block
+///
+/// Rows whose parent is missing are roots; siblings are ordered by `sort_key`.
+pub fn outline_trees(rows: &[Row]) -> Vec<OutlineBlock> {
+    let text = |row: &Row, column: &str| {
+        row.get(column)
+            .and_then(|v| v.as_string_owned())
+            .unwrap_or_default()
+    };
+    let ids: HashSet<String> = rows.iter().map(|row| text(row, "id")).collect();
+    let mut children: HashMap<Option<String>, Vec<&Row>> = HashMap::new();
+    for row in rows {
+        let parent = Some(text(row, "parent_id")).filter(|p| ids.contains(p));
+        children.entry(parent).or_default().push(row);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|row| (text(row, "sort_key"), text(row, "id")));
+    }
+
+    let mut visited = HashSet::new();
+    children
+        .get(&None)
+        .map(|roots| {
+            roots
+                .iter()
+                .filter_map(|row| outline_block(row, &children, &mut visited))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn outline_block(
+    row: &Row,
+    children: &HashMap<Option<String>, Vec<&Row>>,
+    visited: &mut HashSet<String>,
+) -> Option<OutlineBlock> {
+    let id = row.get("id").and_then(|v| v.as_string_owned())?;
+    // Guard against parent_id cycles
+    if !visited.insert(id.clone()) {
+        return None;
+    }
+
+    let flag = |column: &str| match row.get(column) {
+        Some(Value::Boolean(b)) => *b,
+        Some(Value::Integer(i)) => *i != 0,
+        _ => false,
+    };
+    let mut block = OutlineBlock::new(
+        row.get("content")
+            .and_then(|v| v.as_string_owned())
+            .unwrap_or_default(),
+    );
+    block.collapsed = flag("collapsed");
+    block.completed = flag("completed");
+    if let Some(block_type) = row.get("block_type").and_then(|v| v.as_string_owned()) {
+        block.block_type = block_type;
+    }
+    block.properties = row
+        .get("properties")
+        .and_then(|v| v.as_string())
+        .and_then(|json| serde_json::from_str::<Map<String, serde_json::Value>>(json).ok())
+        .unwrap_or_default();
+    block.children = children
+        .get(&Some(id.clone()))
+        .map(|rows| {
+            rows.iter()
+                .filter_map(|row| outline_block(row, children, visited))
+                .collect()
+        })
+        .unwrap_or_default();
+    block.id = Some(id);
+    Some(block)
+}
+
+/// File name from the first line of the root's content, unique within the export
+fn file_name(root: &OutlineBlock, format: ExportFormat, used: &mut HashSet<String>) -> String {
+    let title = root.content.lines().next().unwrap_or_default();
+    let mut slug = slugify(title);
+    if slug.is_empty() {
+        slug = slugify(root.id.as_deref().unwrap_or_default());
+    }
+    if slug.is_empty() {
+        slug = "untitled".to_string();
+    }
+
+    let mut name = format!("{}.{}", slug, format.extension());
+    let mut n = 2;
+    while !used.insert(name.clone()) {
+        name = format!("{}-{}.{}", slug, n, format.extension());
+        n += 1;
+    }
+    name
+}
+
+/// Lowercase alphanumeric words joined by `-`
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').chars().take(80).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: &str, parent: Option<&str>, sort_key: &str, content: &str) -> Row {
+        HashMap::from([
+            ("id".to_string(), Value::String(id.to_string())),
+            (
+                "parent_id".to_string(),
+                parent.map_or(Value::Null, |p| Value::String(p.to_string())),
+            ),
+            ("sort_key".to_string(), Value::String(sort_key.to_string())),
+            ("content".to_string(), Value::String(content.to_string())),
+            ("block_type".to_string(), Value::String("text".to_string())),
+        ])
+    }
+
+    #[test]
+    fn test_outline_trees() {
+        let mut child = block("c1", Some("r1"), "a1", "Child");
+        child.insert("completed".to_string(), Value::Integer(1));
+        child.insert(
+            "properties".to_string(),
+            Value::String(r#"{"owner":"alice"}"#.to_string()),
+        );
+        let rows = vec![
+            block("r2", None, "a1", "Second"),
+            child,
+            block("c0", Some("r1"), "a0", "First child"),
+            block("r1", None, "a0", "First"),
+        ];
+
+        let trees = outline_trees(&rows);
+        let roots: Vec<_> = trees.iter().map(|t| t.content.as_str()).collect();
+        assert_eq!(roots, vec!["First", "Second"]);
+        let children = &trees[0].children;
+        assert_eq!(children[0].id.as_deref(), Some("c0"));
+        assert!(children[1].completed);
+        assert_eq!(
+            children[1].properties.get("owner"),
+            Some(&serde_json::Value::String("alice".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_file_names_are_unique() {
+        let mut used = HashSet::new();
+        let root = OutlineBlock::new("Project: Q3 / plans!");
+        assert_eq!(
+            file_name(&root, ExportFormat::Markdown, &mut used),
+            "project-q3-plans.md"
+        );
+        assert_eq!(
+            file_name(&root, ExportFormat::Markdown, &mut used),
+            "project-q3-plans-2.md"
+        );
+        let mut unnamed = OutlineBlock::new("");
+        unnamed.id = Some("b-1".to_string());
+        assert_eq!(file_name(&unnamed, ExportFormat::Org, &mut used), "b-1.org");
+    }
+}
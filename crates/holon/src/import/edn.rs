@@ -0,0 +1,289 @@
+//! Minimal EDN reader
+//!
+//! Reads the subset of EDN found in exports (maps, vectors, lists, sets, strings,
+//! keywords, numbers, tagged literals) into a `serde_json::Value`:
+//!
+//! - keywords and symbols become strings without the leading `:`
+//! - lists and sets become arrays
+//! - tagged literals (`#uuid "…"`, `#inst "…"`) become their tagged value
+//! - map keys that aren't strings/keywords are written as their JSON text
+
+use serde_json::{Map, Number, Value};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Parse a single EDN value from `input`
+pub fn parse(input: &str) -> Result<Value> {
+    let mut reader = Reader { input, pos: 0 };
+    let value = reader
+        .read()?
+        .ok_or_else(|| "EDN input is empty".to_string())?;
+    reader.skip_whitespace();
+    if reader.pos < input.len() {
+        return Err(format!("Unexpected trailing EDN input at byte {}", reader.pos).into());
+    }
+    Ok(value)
+}
+
+struct Reader<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// Skip whitespace, commas and `;` comments
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == ',' {
+                self.bump();
+            } else if c == ';' {
+                while let Some(c) = self.bump() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Read the next value; `None` at the end of the input or of a collection
+    fn read(&mut self) -> Result<Option<Value>> {
+        loop {
+            self.skip_whitespace();
+            let Some(c) = self.peek() else {
+                return Ok(None);
+            };
+            let value = match c {
+                ')' | ']' | '}' => return Ok(None),
+                '{' => {
+                    self.bump();
+                    self.read_map()?
+                }
+                '[' => {
+                    self.bump();
+                    Value::Array(self.read_seq(']')?)
+                }
+                '(' => {
+                    self.bump();
+                    Value::Array(self.read_seq(')')?)
+                }
+                '"' => {
+                    self.bump();
+                    Value::String(self.read_string()?)
+                }
+                '#' => {
+                    self.bump();
+                    match self.peek() {
+                        Some('{') => {
+                            self.bump();
+                            Value::Array(self.read_seq('}')?)
+                        }
+                        Some('_') => {
+                            self.bump();
+                            self.read_required()?;
+                            continue;
+                        }
+                        _ => {
+                            // Tagged literal: keep the tagged value
+                            self.read_token();
+                            self.read_required()?
+                        }
+                    }
+                }
+                '\\' => {
+                    self.bump();
+                    Value::String(self.read_char()?)
+                }
+                _ => self.read_atom()?,
+            };
+            return Ok(Some(value));
+        }
+    }
+
+    fn read_required(&mut self) -> Result<Value> {
+        let pos = self.pos;
+        self.read()?
+            .ok_or_else(|| format!("Expected an EDN value at byte {}", pos).into())
+    }
+
+    fn read_seq(&mut self, close: char) -> Result<Vec<Value>> {
+        let mut items = Vec::new();
+        while let Some(item) = self.read()? {
+            items.push(item);
+        }
+        self.expect(close)?;
+        Ok(items)
+    }
+
+    fn read_map(&mut self) -> Result<Value> {
+        let mut map = Map::new();
+        while let Some(key) = self.read()? {
+            let key = match key {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+            let value = self.read_required()?;
+            map.insert(key, value);
+        }
+        self.expect('}')?;
+        Ok(Value::Object(map))
+    }
+
+    fn expect(&mut self, close: char) -> Result<()> {
+        match self.bump() {
+            Some(c) if c == close => Ok(()),
+            Some(c) => Err(format!(
+                "Expected '{}' but found '{}' at byte {}",
+                close, c, self.pos
+            )
+            .into()),
+            None => Err(format!("Unterminated EDN collection, expected '{}'", close).into()),
+        }
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(out),
+                Some('\\') => match self.bump() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('u') => {
+                        let end = self.pos + 4;
+                        let hex = self
+                            .input
+                            .get(self.pos..end)
+                            .ok_or("Truncated \\u escape in EDN string")?;
+                        let code = u32::from_str_radix(hex, 16)
+                            .map_err(|e| format!("Invalid \\u escape in EDN string: {}", e))?;
+                        out.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                        self.pos = end;
+                    }
+                    Some(c) => out.push(c),
+                    None => break,
+                },
+                Some(c) => out.push(c),
+                None => break,
+            }
+        }
+        Err("Unterminated EDN string".into())
+    }
+
+    fn read_char(&mut self) -> Result<String> {
+        let token = self.read_token().to_string();
+        let c = match token.as_str() {
+            "newline" => "\n".to_string(),
+            "space" => " ".to_string(),
+            "tab" => "\t".to_string(),
+            "return" => "\r".to_string(),
+            "" => {
+                return self
+                    .bump()
+                    .map(String::from)
+                    .ok_or_else(|| "Invalid EDN character".into());
+            }
+            _ => token,
+        };
+        Ok(c)
+    }
+
+    /// Symbol-like token up to the next delimiter
+    fn read_token(&mut self) -> &str {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || matches!(c, ',' | ';' | '"' | '(' | ')' | '[' | ']' | '{' | '}')
+            {
+                break;
+            }
+            self.bump();
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn read_atom(&mut self) -> Result<Value> {
+        let pos = self.pos;
+        let token = self.read_token();
+        if token.is_empty() {
+            return Err(format!("Unexpected EDN input at byte {}", pos).into());
+        }
+        if let Some(keyword) = token.strip_prefix(':') {
+            return Ok(Value::String(keyword.to_string()));
+        }
+        let value = match token {
+            "nil" => Value::Null,
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => parse_number(token).unwrap_or_else(|| Value::String(token.to_string())),
+        };
+        Ok(value)
+    }
+}
+
+fn parse_number(token: &str) -> Option<Value> {
+    let first = token.trim_start_matches(['+', '-']).chars().next()?;
+    if !first.is_ascii_digit() {
+        return None;
+    }
+    let digits = token.trim_end_matches(['N', 'M']);
+    if let Ok(i) = digits.parse::<i64>() {
+        return Some(Value::Number(i.into()));
+    }
+    digits
+        .parse::<f64>()
+        .ok()
+        .and_then(Number::from_f64)
+        .map(Value::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_export_subset() {
+        let edn = r#"
+            ; Logseq export
+            {:version 1,
+             :blocks ({:block/id #uuid "6512b0e4-0000-4000-8000-000000000001"
+                       :block/page-name "journal"
+                       :block/properties {:tags #{"a" "b"} :rating 4.5 :done? false}
+                       :block/children [{:block/content "Line \"one\"\n" :block/children []}]
+                       #_ :ignored #_ 42})}
+        "#;
+        assert_eq!(
+            parse(edn).unwrap(),
+            json!({
+                "version": 1,
+                "blocks": [{
+                    "block/id": "6512b0e4-0000-4000-8000-000000000001",
+                    "block/page-name": "journal",
+                    "block/properties": {"tags": ["a", "b"], "rating": 4.5, "done?": false},
+                    "block/children": [{"block/content": "Line \"one\"\n", "block/children": []}]
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("{:a 1").is_err());
+        assert!(parse("\"open").is_err());
+        assert!(parse("[1] 2").is_err());
+        assert!(parse("  ").is_err());
+    }
+}
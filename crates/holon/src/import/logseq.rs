@@ -0,0 +1,542 @@
+//! Logseq graph import
+//!
+//! Reads the graph export of Logseq ("Export graph" as JSON or EDN): a list of pages,
+//! each with a tree of blocks carrying a uuid, their content and properties.
+//!
+//! - every page becomes a root block (`block_type = 'page'`), its blocks become the
+//!   page block's descendants in their original order
+//! - blocks keep their Logseq uuid as id, so `((uuid))` references keep working and
+//!   re-importing updates blocks in place
+//! - `key:: value` property lines are moved out of the content into the `properties`
+//!   column; `collapsed:: true` sets `collapsed`
+//! - a leading task marker (`TODO`, `DONE`, …) is moved into the `marker` property;
+//!   `DONE` blocks are `completed`
+//!
+//! JSON exports are read incrementally, one page at a time, on a blocking thread.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Value};
+use tokio::sync::{RwLock, mpsc};
+use tracing::info;
+
+use crate::core::datasource::Result;
+use crate::import::{BlockWriter, ImportProgress, ImportSummary, ImportedBlock, ImportedPage};
+use crate::storage::fractional_index::gen_key_between;
+use crate::storage::turso::TursoBackend;
+
+/// Pages parsed ahead of the writer
+const PAGE_BUFFER: usize = 16;
+
+/// Task markers Logseq recognizes at the start of a block
+const TASK_MARKERS: &[&str] = &[
+    "TODO",
+    "DOING",
+    "DONE",
+    "LATER",
+    "NOW",
+    "WAITING",
+    "WAIT",
+    "CANCELED",
+    "CANCELLED",
+    "IN-PROGRESS",
+];
+
+/// Format of a Logseq export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogseqFormat {
+    Json,
+    Edn,
+}
+
+impl LogseqFormat {
+    /// Format of the export at `path`, by file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "edn" => Some(Self::Edn),
+            _ => None,
+        }
+    }
+}
+
+/// Imports Logseq graph exports into the `blocks` table
+pub struct LogseqImporter {
+    writer: BlockWriter,
+}
+
+impl LogseqImporter {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self {
+            writer: BlockWriter::new(backend),
+        }
+    }
+
+    /// Import the export at `path`, calling `progress` after each page
+    pub async fn import_file(
+        &self,
+        path: &Path,
+        progress: impl FnMut(&ImportProgress),
+    ) -> Result<ImportSummary> {
+        let format = LogseqFormat::from_path(path).ok_or_else(|| {
+            format!(
+                "Unknown Logseq export format (expected .json or .edn): {}",
+                path.display()
+            )
+        })?;
+
+        let (tx, rx) = mpsc::channel(PAGE_BUFFER);
+        let path_buf = path.to_path_buf();
+        let reader = tokio::task::spawn_blocking(move || {
+            if let Err(e) = read_pages(&path_buf, format, &tx) {
+                // Fails only if the importer already stopped
+                let _ = tx.blocking_send(Err(e));
+            }
+        });
+
+        let summary = self.import_pages(rx, progress).await;
+        reader
+            .await
+            .map_err(|e| format!("Logseq export reader failed: {}", e))?;
+        let summary = summary?;
+        info!(
+            "Imported {} Logseq pages from {}: {:?}",
+            summary.pages,
+            path.display(),
+            summary
+        );
+        Ok(summary)
+    }
+
+    /// Import an export held in memory
+    pub async fn import_str(
+        &self,
+        export: &str,
+        format: LogseqFormat,
+        progress: impl FnMut(&ImportProgress),
+    ) -> Result<ImportSummary> {
+        let pages = export_pages(parse_export(export, format)?)?;
+        let (tx, rx) = mpsc::channel(pages.len().max(1));
+        for page in pages {
+            tx.send(Ok(page))
+                .await
+                .map_err(|_| "Logseq import stopped".to_string())?;
+        }
+        drop(tx);
+        self.import_pages(rx, progress).await
+    }
+
+    async fn import_pages(
+        &self,
+        mut pages: mpsc::Receiver<Result<Value>>,
+        mut progress: impl FnMut(&ImportProgress),
+    ) -> Result<ImportSummary> {
+        self.writer.initialize_schema().await?;
+
+        let mut summary = ImportSummary::default();
+        let mut state = ImportProgress::default();
+        let mut prev_page_key: Option<String> = None;
+        while let Some(page) = pages.recv().await {
+            let page = map_page(&page?, prev_page_key.as_deref())?;
+            self.writer.write_page(&page, &mut summary).await?;
+
+            prev_page_key = page.blocks.first().map(|b| b.sort_key.clone());
+            state.pages += 1;
+            state.blocks += page.blocks.len();
+            state.current_page = page.title;
+            progress(&state);
+        }
+        Ok(summary)
+    }
+}
+
+/// Map one exported page to its blocks
+///
+/// `prev_page_key` is the sort key of the previously imported page, so pages keep
+/// their export order.
+pub fn map_page(page: &Value, prev_page_key: Option<&str>) -> Result<ImportedPage> {
+    let title = ["original-name", "page-name", "title", "name", "content"]
+        .iter()
+        .find_map(|key| field(page, key).and_then(Value::as_str))
+        .map(str::to_string)
+        .ok_or_else(|| "Logseq page without a name".to_string())?;
+    let page_id = field(page, "id")
+        .or_else(|| field(page, "uuid"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("logseq-page:{}", title.to_lowercase()));
+
+    let mut properties = properties_of(page);
+    let collapsed = take_collapsed(&mut properties);
+    let mut blocks = vec![ImportedBlock {
+        id: page_id.clone(),
+        parent_id: None,
+        depth: 0,
+        sort_key: gen_key_between(prev_page_key, None)?,
+        content: title.clone(),
+        collapsed,
+        completed: false,
+        block_type: "page".to_string(),
+        properties,
+    }];
+    map_children(page, &page_id, 1, &mut blocks)?;
+
+    Ok(ImportedPage { title, blocks })
+}
+
+fn map_children(
+    parent: &Value,
+    parent_id: &str,
+    depth: i64,
+    blocks: &mut Vec<ImportedBlock>,
+) -> Result<()> {
+    let Some(children) = field(parent, "children").and_then(Value::as_array) else {
+        return Ok(());
+    };
+
+    let mut prev_key: Option<String> = None;
+    for (index, child) in children.iter().enumerate() {
+        // Blocks without uuid get an id from their position
+        let id = field(child, "id")
+            .or_else(|| field(child, "uuid"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{}/{}", parent_id, index));
+        let raw_content = field(child, "content")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+
+        let (content, mut properties) = split_properties(raw_content);
+        properties.extend(properties_of(child));
+        let collapsed = take_collapsed(&mut properties);
+        let (content, marker) = split_marker(&content);
+        let completed = marker.as_deref() == Some("DONE");
+        if let Some(marker) = marker {
+            properties.insert("marker".to_string(), Value::String(marker));
+        }
+        let block_type = if is_heading(&content, &properties) {
+            "heading"
+        } else {
+            "text"
+        };
+
+        let sort_key = gen_key_between(prev_key.as_deref(), None)?;
+        prev_key = Some(sort_key.clone());
+        blocks.push(ImportedBlock {
+            id: id.clone(),
+            parent_id: Some(parent_id.to_string()),
+            depth,
+            sort_key,
+            content,
+            collapsed,
+            completed,
+            block_type: block_type.to_string(),
+            properties,
+        });
+        map_children(child, &id, depth + 1, blocks)?;
+    }
+    Ok(())
+}
+
+/// Field of an exported page/block, with or without the `block/` namespace of EDN exports
+fn field<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    let object = value.as_object()?;
+    object
+        .get(name)
+        .or_else(|| object.get(&format!("block/{}", name)))
+}
+
+fn properties_of(value: &Value) -> Map<String, Value> {
+    field(value, "properties")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn take_collapsed(properties: &mut Map<String, Value>) -> bool {
+    match properties.remove("collapsed") {
+        Some(Value::Bool(b)) => b,
+        Some(Value::String(s)) => s == "true",
+        _ => false,
+    }
+}
+
+/// Split `key:: value` lines off `content`
+pub fn split_properties(content: &str) -> (String, Map<String, Value>) {
+    let mut properties = Map::new();
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        match line.trim().split_once(":: ") {
+            Some((key, value)) if is_property_key(key) => {
+                properties.insert(key.to_lowercase(), Value::String(value.trim().to_string()));
+            }
+            _ => lines.push(line),
+        }
+    }
+    (lines.join("\n").trim_end().to_string(), properties)
+}
+
+fn is_property_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Split a leading task marker off `content`
+fn split_marker(content: &str) -> (String, Option<String>) {
+    let (first, rest) = content.split_once(' ').unwrap_or((content, ""));
+    if TASK_MARKERS.contains(&first) {
+        (rest.trim_start().to_string(), Some(first.to_string()))
+    } else {
+        (content.to_string(), None)
+    }
+}
+
+fn is_heading(content: &str, properties: &Map<String, Value>) -> bool {
+    let hashes = content.chars().take_while(|c| *c == '#').count();
+    let markdown_heading = (1..=6).contains(&hashes) && content[hashes..].starts_with(' ');
+    markdown_heading
+        || properties
+            .get("heading")
+            .is_some_and(|h| *h != Value::Bool(false) && *h != "false")
+}
+
+fn parse_export(export: &str, format: LogseqFormat) -> Result<Value> {
+    match format {
+        LogseqFormat::Json => serde_json::from_str(export)
+            .map_err(|e| format!("Invalid Logseq JSON export: {}", e).into()),
+        LogseqFormat::Edn => crate::import::edn::parse(export),
+    }
+}
+
+/// Pages of a parsed export (`{"blocks": [...]}` or a bare list of pages)
+fn export_pages(export: Value) -> Result<Vec<Value>> {
+    match export {
+        Value::Array(pages) => Ok(pages),
+        Value::Object(mut object) => match object.remove("blocks") {
+            Some(Value::Array(pages)) => Ok(pages),
+            _ => Err("Logseq export without a list of pages".into()),
+        },
+        _ => Err("Logseq export must be a map or a list of pages".into()),
+    }
+}
+
+/// Send the pages of the export at `path` to `tx`
+fn read_pages(path: &Path, format: LogseqFormat, tx: &mpsc::Sender<Result<Value>>) -> Result<()> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    match format {
+        LogseqFormat::Json => {
+            let mut deserializer =
+                serde_json::Deserializer::from_reader(std::io::BufReader::new(file));
+            deserializer
+                .deserialize_any(PagesVisitor { tx })
+                .map_err(|e| format!("Invalid Logseq JSON export: {}", e))?;
+            deserializer
+                .end()
+                .map_err(|e| format!("Invalid Logseq JSON export: {}", e))?;
+        }
+        LogseqFormat::Edn => {
+            let export = std::io::read_to_string(file)?;
+            for page in export_pages(crate::import::edn::parse(&export)?)? {
+                if tx.blocking_send(Ok(page)).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Streams the pages of a JSON export without materializing the whole export
+#[derive(Clone, Copy)]
+struct PagesVisitor<'a> {
+    tx: &'a mpsc::Sender<Result<Value>>,
+}
+
+impl<'de> Visitor<'de> for PagesVisitor<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a Logseq export")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "blocks" {
+                map.next_value_seed(self)?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while let Some(page) = seq.next_element::<Value>()? {
+            if self.tx.blocking_send(Ok(page)).is_err() {
+                return Err(de::Error::custom("import stopped"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for PagesVisitor<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+    use serde_json::json;
+
+    const JSON_EXPORT: &str = r###"{
+        "version": 1,
+        "blocks": [
+            {
+                "id": "page-1",
+                "page-name": "project",
+                "properties": {"type": "project"},
+                "children": [
+                    {
+                        "id": "b1",
+                        "content": "DONE Write outline\nowner:: alice\ncollapsed:: true",
+                        "properties": {"owner": "alice", "collapsed": true},
+                        "children": [{"id": "b1a", "content": "## Details", "children": []}]
+                    },
+                    {"id": "b2", "content": "Second"}
+                ]
+            },
+            {"page-name": "empty"}
+        ]
+    }"###;
+
+    #[test]
+    fn test_map_page() {
+        let export: Value = serde_json::from_str(JSON_EXPORT).unwrap();
+        let pages = export_pages(export).unwrap();
+        let page = map_page(&pages[0], None).unwrap();
+
+        assert_eq!(page.title, "project");
+        let ids: Vec<_> = page.blocks.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(ids, vec!["page-1", "b1", "b1a", "b2"]);
+
+        let root = &page.blocks[0];
+        assert_eq!(root.block_type, "page");
+        assert_eq!(root.properties.get("type"), Some(&json!("project")));
+
+        let b1 = &page.blocks[1];
+        assert_eq!(b1.parent_id.as_deref(), Some("page-1"));
+        assert_eq!(b1.content, "Write outline");
+        assert!(b1.completed && b1.collapsed);
+        assert_eq!(b1.properties.get("marker"), Some(&json!("DONE")));
+        assert_eq!(b1.properties.get("owner"), Some(&json!("alice")));
+        assert!(!b1.properties.contains_key("collapsed"));
+
+        let b1a = &page.blocks[2];
+        assert_eq!((b1a.parent_id.as_deref(), b1a.depth), (Some("b1"), 2));
+        assert_eq!(b1a.block_type, "heading");
+        assert!(b1.sort_key < page.blocks[3].sort_key);
+
+        let empty = map_page(&pages[1], Some(&root.sort_key)).unwrap();
+        assert_eq!(empty.blocks.len(), 1);
+        assert_eq!(empty.blocks[0].id, "logseq-page:empty");
+        assert!(empty.blocks[0].sort_key > root.sort_key);
+    }
+
+    #[test]
+    fn test_split_properties_and_marker() {
+        let (content, properties) = split_properties("Title\nid:: 123\nnot a:: property");
+        assert_eq!(content, "Title\nnot a:: property");
+        assert_eq!(properties.get("id"), Some(&json!("123")));
+        assert_eq!(
+            split_marker("LATER call bob"),
+            ("call bob".to_string(), Some("LATER".to_string()))
+        );
+        assert_eq!(split_marker("TODOs"), ("TODOs".to_string(), None));
+    }
+
+    #[tokio::test]
+    async fn test_import_is_idempotent() {
+        let backend = memory_backend().await;
+        let importer = LogseqImporter::new(backend);
+
+        let mut reported = Vec::new();
+        let summary = importer
+            .import_str(JSON_EXPORT, LogseqFormat::Json, |p| {
+                reported.push(p.clone())
+            })
+            .await
+            .unwrap();
+        assert_eq!((summary.pages, summary.created, summary.updated), (2, 5, 0));
+        assert_eq!(reported.len(), 2);
+        assert_eq!(reported[1].blocks, 5);
+        assert_eq!(reported[1].current_page, "empty");
+
+        let again = importer
+            .import_str(JSON_EXPORT, LogseqFormat::Json, |_| {})
+            .await
+            .unwrap();
+        assert_eq!((again.created, again.updated, again.unchanged), (0, 0, 5));
+
+        let edited = JSON_EXPORT.replace("\"Second\"", "\"Second, edited\"");
+        let edited = importer
+            .import_str(&edited, LogseqFormat::Json, |_| {})
+            .await
+            .unwrap();
+        assert_eq!((edited.created, edited.updated), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_import_edn_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.edn");
+        std::fs::write(
+            &path,
+            r#"{:version 1
+                :blocks ({:block/id #uuid "00000000-0000-4000-8000-000000000001"
+                          :block/page-name "inbox"
+                          :block/children [{:block/id #uuid "00000000-0000-4000-8000-000000000002"
+                                            :block/content "TODO reply"
+                                            :block/children []}]})}"#,
+        )
+        .unwrap();
+
+        let backend = memory_backend().await;
+        let summary = LogseqImporter::new(backend.clone())
+            .import_file(&path, |_| {})
+            .await
+            .unwrap();
+        assert_eq!((summary.pages, summary.created), (1, 2));
+
+        let rows = backend
+            .read()
+            .await
+            .execute_sql(
+                "SELECT parent_id, content FROM blocks WHERE id = '00000000-0000-4000-8000-000000000002'",
+                Default::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            rows[0].get("parent_id").and_then(|v| v.as_string()),
+            Some("00000000-0000-4000-8000-000000000001")
+        );
+        assert_eq!(
+            rows[0].get("content").and_then(|v| v.as_string()),
+            Some("reply")
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Importers
+//!
+//! - `logseq`: import a Logseq graph export (JSON or EDN) as blocks
+//! - `outline`: import Markdown/Org files written by the workspace export
+//! - `edn`: the EDN reader used for EDN exports
+//! - `tabular`: import CSV/JSON rows as entities of any schema
+//!
+//! Importers map their source into pages of `ImportedBlock`s and hand them to the
+//! `BlockWriter` one page at a time, so large exports are never held in memory as a
+//! whole. Imported blocks keep the id they have in the source: importing the same
+//! export again updates the existing blocks in place instead of adding copies, and
+//! blocks that didn't change aren't written at all.
+
+pub mod edn;
+pub mod logseq;
+pub mod outline;
+pub mod tabular;
+
+pub use logseq::{LogseqFormat, LogseqImporter};
+pub use outline::OutlineImporter;
+pub use tabular::{
+    ColumnMapping, ImportRowError, TABLE_IMPORT_BATCH_SIZE, TabularData, TabularFormat,
+    TabularImportProgress, TabularImportSummary, TabularPreview,
+};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::Map;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::api::backend_engine::BLOCKS_TABLE_SQL;
+use crate::core::datasource::Result;
+use crate::storage::fractional_index::gen_key_between;
+use crate::storage::turso::TursoBackend;
+use holon_api::Value;
+
+/// Column holding the (JSON-encoded) properties of imported blocks
+pub const PROPERTIES_COLUMN: &str = "properties";
+
+/// A block in the shape of a `blocks` row
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedBlock {
+    pub id: String,
+    pub parent_id: Option<String>,
+    pub depth: i64,
+    pub sort_key: String,
+    pub content: String,
+    pub collapsed: bool,
+    pub completed: bool,
+    pub block_type: String,
+    pub properties: Map<String, serde_json::Value>,
+}
+
+impl ImportedBlock {
+    fn to_row(&self) -> HashMap<String, Value> {
+        let properties = if self.properties.is_empty() {
+            Value::Null
+        } else {
+            Value::String(serde_json::Value::Object(self.properties.clone()).to_string())
+        };
+        HashMap::from([
+            ("id".to_string(), Value::String(self.id.clone())),
+            (
+                "parent_id".to_string(),
+                self.parent_id.clone().map_or(Value::Null, Value::String),
+            ),
+            ("depth".to_string(), Value::Integer(self.depth)),
+            ("sort_key".to_string(), Value::String(self.sort_key.clone())),
+            ("content".to_string(), Value::String(self.content.clone())),
+            (
+                "collapsed".to_string(),
+                Value::Integer(i64::from(self.collapsed)),
+            ),
+            (
+                "completed".to_string(),
+                Value::Integer(i64::from(self.completed)),
+            ),
+            (
+                "block_type".to_string(),
+                Value::String(self.block_type.clone()),
+            ),
+            (PROPERTIES_COLUMN.to_string(), properties),
+        ])
+    }
+}
+
+/// A page of the source with all of its blocks
+///
+/// `blocks` starts with the page's root block and lists parents before children.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedPage {
+    pub title: String,
+    pub blocks: Vec<ImportedBlock>,
+}
+
+/// Progress reported after each imported page
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportProgress {
+    /// Pages imported so far
+    pub pages: usize,
+    /// Blocks (including page blocks) imported so far
+    pub blocks: usize,
+    /// Title of the page that was just imported
+    pub current_page: String,
+}
+
+/// Result of an import
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub pages: usize,
+    /// Blocks that didn't exist yet
+    pub created: usize,
+    /// Existing blocks that changed
+    pub updated: usize,
+    /// Existing blocks that were already up to date
+    pub unchanged: usize,
+}
+
+impl ImportSummary {
+    pub fn blocks(&self) -> usize {
+        self.created + self.updated + self.unchanged
+    }
+}
+
+/// Writes imported pages to the `blocks` table
+pub struct BlockWriter {
+    backend: Arc<RwLock<TursoBackend>>,
+}
+
+impl BlockWriter {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self { backend }
+    }
+
+    /// Create the blocks table if needed and add the `properties` column if missing
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(BLOCKS_TABLE_SQL, HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create blocks table: {}", e))?;
+
+        let rows = backend
+            .execute_sql(
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'blocks'",
+                HashMap::new(),
+            )
+            .await?;
+        let has_properties = rows
+            .first()
+            .and_then(|row| row.get("sql"))
+            .and_then(|sql| sql.as_string())
+            .is_some_and(|sql| sql.contains(PROPERTIES_COLUMN));
+        if !has_properties {
+            backend
+                .execute_sql(
+                    &format!("ALTER TABLE blocks ADD COLUMN {} TEXT", PROPERTIES_COLUMN),
+                    HashMap::new(),
+                )
+                .await
+                .map_err(|e| format!("Failed to add properties column: {}", e))?;
+        }
+
+        info!("Import schema initialized");
+        Ok(())
+    }
+
+    /// Insert or update the blocks of `page`, counting them in `summary`
+    pub async fn write_page(&self, page: &ImportedPage, summary: &mut ImportSummary) -> Result<()> {
+        let backend = self.backend.read().await;
+        let upsert_sql = format!(
+            "INSERT INTO blocks (id, parent_id, depth, sort_key, content, collapsed, completed, block_type, {properties})
+            VALUES ($id, $parent_id, $depth, $sort_key, $content, $collapsed, $completed, $block_type, ${properties})
+            ON CONFLICT(id) DO UPDATE SET
+                parent_id = excluded.parent_id,
+                depth = excluded.depth,
+                sort_key = excluded.sort_key,
+                content = excluded.content,
+                collapsed = excluded.collapsed,
+                completed = excluded.completed,
+                block_type = excluded.block_type,
+                {properties} = excluded.{properties},
+                updated_at = datetime('now')",
+            properties = PROPERTIES_COLUMN
+        );
+        let select_sql = format!(
+            "SELECT parent_id, depth, sort_key, content, collapsed, completed, block_type, {}
+            FROM blocks WHERE id = $id",
+            PROPERTIES_COLUMN
+        );
+
+        let mut existing = HashMap::new();
+        for block in &page.blocks {
+            let rows = backend
+                .execute_sql(
+                    &select_sql,
+                    HashMap::from([("id".to_string(), Value::String(block.id.clone()))]),
+                )
+                .await
+                .map_err(|e| format!("Failed to load block {}: {}", block.id, e))?;
+            if let Some(row) = rows.into_iter().next() {
+                existing.insert(block.id.clone(), row);
+            }
+        }
+
+        let mut blocks = page.blocks.clone();
+        keep_sort_keys(&mut blocks, &existing)?;
+
+        for block in &blocks {
+            let row = block.to_row();
+            match existing.get(&block.id) {
+                Some(existing) if existing.iter().all(|(k, v)| row.get(k) == Some(v)) => {
+                    summary.unchanged += 1;
+                    continue;
+                }
+                Some(_) => summary.updated += 1,
+                None => summary.created += 1,
+            }
+
+            backend
+                .execute_sql(&upsert_sql, row)
+                .await
+                .map_err(|e| format!("Failed to write block {}: {}", block.id, e))?;
+        }
+
+        summary.pages += 1;
+        Ok(())
+    }
+}
+
+/// Keep the stored sort keys of re-imported blocks where possible
+///
+/// Sources that don't carry sort keys (like outline files) generate fresh ones on
+/// every import, which would rewrite every block. For each group of siblings whose
+/// already stored blocks (under the same parent) are still in the same relative
+/// order, the stored keys are kept and new blocks get keys between their neighbors.
+fn keep_sort_keys(
+    blocks: &mut [ImportedBlock],
+    existing: &HashMap<String, HashMap<String, Value>>,
+) -> Result<()> {
+    let mut groups: HashMap<Option<String>, Vec<usize>> = HashMap::new();
+    for (i, block) in blocks.iter().enumerate() {
+        groups.entry(block.parent_id.clone()).or_default().push(i);
+    }
+
+    for (parent_id, indices) in groups {
+        let stored: Vec<Option<String>> = indices
+            .iter()
+            .map(|&i| {
+                let row = existing.get(&blocks[i].id)?;
+                let stored_parent = row.get("parent_id").and_then(|v| v.as_string_owned());
+                if stored_parent != parent_id {
+                    return None;
+                }
+                row.get("sort_key").and_then(|v| v.as_string_owned())
+            })
+            .collect();
+        let kept: Vec<&String> = stored.iter().flatten().collect();
+        if kept.is_empty() || kept.windows(2).any(|w| w[0] >= w[1]) {
+            continue;
+        }
+
+        let mut prev: Option<String> = None;
+        for (n, &i) in indices.iter().enumerate() {
+            let key = match &stored[n] {
+                Some(key) => key.clone(),
+                None => {
+                    let next = stored[n + 1..].iter().flatten().next();
+                    gen_key_between(prev.as_deref(), next.map(String::as_str))?
+                }
+            };
+            blocks[i].sort_key = key.clone();
+            prev = Some(key);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,498 @@
+//! Tabular (CSV/JSON) import for any entity
+//!
+//! Reads a CSV file (first record = headers) or a JSON array of objects, maps its
+//! columns to the fields of a target `EntitySchema` and converts each cell to the
+//! field's `FieldType`. Frontends show the `TabularPreview` (headers, sample rows and
+//! a suggested `ColumnMapping`) so the user can adjust the mapping before importing;
+//! `BackendEngine::import_table` then creates the entities through the entity's
+//! `create` operation.
+//!
+//! Rows with values that don't fit their field are skipped and reported as
+//! `ImportRowError`s; the other rows are still imported.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::core::datasource::Result;
+use crate::storage::schema::{EntitySchema, FieldType};
+use crate::storage::types::StorageEntity;
+use holon_api::Value;
+
+/// Number of rows in `TabularPreview::sample_rows`
+pub const PREVIEW_ROWS: usize = 5;
+
+/// Rows created between two progress reports of `BackendEngine::import_table`
+pub const TABLE_IMPORT_BATCH_SIZE: usize = 100;
+
+/// Source file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabularFormat {
+    Csv,
+    /// A JSON array of objects
+    Json,
+}
+
+impl TabularFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "csv" => Some(TabularFormat::Csv),
+            "json" => Some(TabularFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Rows of a CSV or JSON source
+///
+/// CSV cells are strings; JSON cells keep their JSON type. Missing cells are `Null`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TabularData {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+impl TabularData {
+    pub fn read_file(path: &Path) -> Result<Self> {
+        let format = TabularFormat::from_path(path).ok_or_else(|| {
+            format!(
+                "Unknown table format (expected .csv or .json): {}",
+                path.display()
+            )
+        })?;
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&text, format)
+    }
+
+    pub fn parse(text: &str, format: TabularFormat) -> Result<Self> {
+        match format {
+            TabularFormat::Csv => Self::parse_csv(text),
+            TabularFormat::Json => Self::parse_json(text),
+        }
+    }
+
+    fn parse_csv(text: &str) -> Result<Self> {
+        let mut records = parse_csv_records(text.trim_start_matches('\u{feff}'))?.into_iter();
+        let Some(headers) = records.next() else {
+            return Ok(Self::default());
+        };
+        let headers: Vec<String> = headers.into_iter().map(|h| h.trim().to_string()).collect();
+        let rows = records
+            .map(|record| {
+                let mut row: Vec<serde_json::Value> =
+                    record.into_iter().map(serde_json::Value::String).collect();
+                row.resize(headers.len(), serde_json::Value::Null);
+                row
+            })
+            .collect();
+        Ok(Self { headers, rows })
+    }
+
+    fn parse_json(text: &str) -> Result<Self> {
+        let value: serde_json::Value =
+            serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+        let serde_json::Value::Array(items) = value else {
+            return Err("Expected a JSON array of objects".into());
+        };
+
+        let mut headers: Vec<String> = Vec::new();
+        let mut objects = Vec::with_capacity(items.len());
+        for (index, item) in items.into_iter().enumerate() {
+            let serde_json::Value::Object(object) = item else {
+                return Err(format!("Item {} is not a JSON object", index + 1).into());
+            };
+            for key in object.keys() {
+                if !headers.contains(key) {
+                    headers.push(key.clone());
+                }
+            }
+            objects.push(object);
+        }
+        let rows = objects
+            .into_iter()
+            .map(|mut object| {
+                headers
+                    .iter()
+                    .map(|header| object.remove(header).unwrap_or(serde_json::Value::Null))
+                    .collect()
+            })
+            .collect();
+        Ok(Self { headers, rows })
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Headers, first rows and a suggested mapping to `schema`, for the mapping dialog
+    pub fn preview(&self, schema: &EntitySchema) -> TabularPreview {
+        TabularPreview {
+            headers: self.headers.clone(),
+            sample_rows: self.rows.iter().take(PREVIEW_ROWS).cloned().collect(),
+            total_rows: self.rows.len(),
+            suggested_mapping: ColumnMapping::suggest(&self.headers, schema),
+        }
+    }
+
+    /// Convert the rows to entities of `schema`
+    ///
+    /// Returns the entities with their row number (1-based, excluding the header)
+    /// and the rows that couldn't be converted.
+    pub fn to_entities(
+        &self,
+        schema: &EntitySchema,
+        mapping: &ColumnMapping,
+    ) -> Result<(Vec<(usize, StorageEntity)>, Vec<ImportRowError>)> {
+        let columns = mapping.resolve(&self.headers, schema)?;
+        let mut entities = Vec::with_capacity(self.rows.len());
+        let mut errors = Vec::new();
+        for (index, row) in self.rows.iter().enumerate() {
+            let row_number = index + 1;
+            match convert_row(row, &columns, schema) {
+                Ok(entity) => entities.push((row_number, entity)),
+                Err((field, message)) => errors.push(ImportRowError {
+                    row: row_number,
+                    field,
+                    message,
+                }),
+            }
+        }
+        Ok((entities, errors))
+    }
+}
+
+/// What the mapping dialog shows before an import
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabularPreview {
+    pub headers: Vec<String>,
+    /// Up to `PREVIEW_ROWS` rows
+    pub sample_rows: Vec<Vec<serde_json::Value>>,
+    pub total_rows: usize,
+    pub suggested_mapping: ColumnMapping,
+}
+
+/// Which source column fills which entity field
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnMapping {
+    /// Source column name -> field name
+    pub columns: HashMap<String, String>,
+}
+
+impl ColumnMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fill `field` from the source column `column`
+    pub fn map(mut self, column: impl Into<String>, field: impl Into<String>) -> Self {
+        self.columns.insert(column.into(), field.into());
+        self
+    }
+
+    /// Map columns whose names match a field, ignoring case, spaces, dashes and underscores
+    pub fn suggest(headers: &[String], schema: &EntitySchema) -> Self {
+        let columns = headers
+            .iter()
+            .filter_map(|header| {
+                let normalized = normalize_name(header);
+                schema
+                    .fields
+                    .iter()
+                    .find(|field| normalize_name(&field.name) == normalized)
+                    .map(|field| (header.clone(), field.name.clone()))
+            })
+            .collect();
+        Self { columns }
+    }
+
+    /// (column index, field index) pairs, validated against `headers` and `schema`
+    fn resolve(&self, headers: &[String], schema: &EntitySchema) -> Result<Vec<(usize, usize)>> {
+        let mut columns = Vec::with_capacity(self.columns.len());
+        for (column, field) in &self.columns {
+            let column_index = headers
+                .iter()
+                .position(|header| header == column)
+                .ok_or_else(|| format!("Unknown source column: {}", column))?;
+            let field_index = schema
+                .fields
+                .iter()
+                .position(|f| &f.name == field)
+                .ok_or_else(|| format!("Unknown field of {}: {}", schema.name, field))?;
+            if columns.iter().any(|&(_, f)| f == field_index) {
+                return Err(format!("Field {} is mapped from more than one column", field).into());
+            }
+            columns.push((column_index, field_index));
+        }
+
+        // Primary keys may be generated by the entity's `create`
+        let missing: Vec<&str> = schema
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(index, field)| {
+                field.required
+                    && field.name != schema.primary_key
+                    && !columns.iter().any(|&(_, f)| f == *index)
+            })
+            .map(|(_, field)| field.name.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("Required fields are not mapped: {}", missing.join(", ")).into());
+        }
+        Ok(columns)
+    }
+}
+
+/// A source row that couldn't be imported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportRowError {
+    /// Row number (1-based, excluding the header)
+    pub row: usize,
+    /// Field whose value was rejected, if the error concerns a single field
+    pub field: Option<String>,
+    pub message: String,
+}
+
+/// Progress reported after each batch of created entities
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TabularImportProgress {
+    /// Rows handled so far, created or not
+    pub processed: usize,
+    pub total: usize,
+    pub created: usize,
+    pub failed: usize,
+}
+
+/// Result of a tabular import
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TabularImportSummary {
+    pub rows: usize,
+    pub created: usize,
+    /// Rows that failed validation or whose `create` failed, in row order
+    pub errors: Vec<ImportRowError>,
+}
+
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn convert_row(
+    row: &[serde_json::Value],
+    columns: &[(usize, usize)],
+    schema: &EntitySchema,
+) -> std::result::Result<StorageEntity, (Option<String>, String)> {
+    let mut entity = StorageEntity::new();
+    for &(column, field_index) in columns {
+        let field = &schema.fields[field_index];
+        let cell = row.get(column).unwrap_or(&serde_json::Value::Null);
+        let is_empty = match cell {
+            serde_json::Value::Null => true,
+            serde_json::Value::String(s) => s.trim().is_empty(),
+            _ => false,
+        };
+        if is_empty {
+            if field.required && field.name != schema.primary_key {
+                return Err((Some(field.name.clone()), "Value is required".to_string()));
+            }
+            continue;
+        }
+        let value = convert_value(cell, &field.field_type)
+            .map_err(|message| (Some(field.name.clone()), message))?;
+        entity.insert(field.name.clone(), value);
+    }
+    Ok(entity)
+}
+
+/// Convert a non-empty cell to a value of `field_type`
+pub fn convert_value(
+    cell: &serde_json::Value,
+    field_type: &FieldType,
+) -> std::result::Result<Value, String> {
+    use serde_json::Value as Json;
+
+    match (field_type, cell) {
+        (FieldType::String | FieldType::Reference(_), Json::String(s)) => {
+            Ok(Value::String(s.clone()))
+        }
+        (FieldType::String | FieldType::Reference(_), Json::Number(n)) => {
+            Ok(Value::String(n.to_string()))
+        }
+        (FieldType::String, Json::Bool(b)) => Ok(Value::String(b.to_string())),
+        (FieldType::Integer, Json::Number(n)) => n
+            .as_i64()
+            .map(Value::Integer)
+            .ok_or_else(|| format!("Not an integer: {}", n)),
+        (FieldType::Integer, Json::String(s)) => s
+            .trim()
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| format!("Not an integer: {}", s)),
+        (FieldType::Boolean, Json::Bool(b)) => Ok(Value::Boolean(*b)),
+        (FieldType::Boolean, Json::Number(n)) => match n.as_i64() {
+            Some(0) => Ok(Value::Boolean(false)),
+            Some(1) => Ok(Value::Boolean(true)),
+            _ => Err(format!("Not a boolean: {}", n)),
+        },
+        (FieldType::Boolean, Json::String(s)) => match s.trim().to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" | "x" => Ok(Value::Boolean(true)),
+            "false" | "no" | "n" | "0" => Ok(Value::Boolean(false)),
+            _ => Err(format!("Not a boolean: {}", s)),
+        },
+        (FieldType::DateTime, Json::String(s)) => Value::parse_datetime(s.trim())
+            .ok_or_else(|| format!("Not a date or RFC 3339 timestamp: {}", s)),
+        (FieldType::Json, Json::String(s)) => serde_json::from_str::<Json>(s)
+            .map(Value::from)
+            .map_err(|e| format!("Invalid JSON: {}", e)),
+        (FieldType::Json, other) => Ok(Value::from(other.clone())),
+        (field_type, other) => Err(format!("Expected {:?}, got {}", field_type, other)),
+    }
+}
+
+/// Split CSV text into records (RFC 4180: quoted fields may contain commas,
+/// newlines and `""` for a quote); blank lines are skipped
+fn parse_csv_records(text: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                line += 1;
+                record.push(std::mem::take(&mut field));
+                if !(record.len() == 1 && record[0].is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!("Unterminated quoted field at line {}", line).into());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::schema::FieldSchema;
+
+    fn contacts_schema() -> EntitySchema {
+        let field = |name: &str, field_type: FieldType, required: bool| FieldSchema {
+            name: name.to_string(),
+            field_type,
+            required,
+            indexed: false,
+        };
+        EntitySchema {
+            name: "contacts".to_string(),
+            fields: vec![
+                field("id", FieldType::String, true),
+                field("full_name", FieldType::String, true),
+                field("age", FieldType::Integer, false),
+                field("active", FieldType::Boolean, false),
+                field("born_on", FieldType::DateTime, false),
+            ],
+            primary_key: "id".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_csv_import_converts_and_reports_bad_rows() {
+        let csv = "\u{feff}Full Name,Age,Active,Born On,Notes\r\n\
+                   \"Doe, Jane\",42,yes,1982-03-04,\"said \"\"hi\"\"\nthen left\"\r\n\
+                   \r\n\
+                   Bob,forty,no,,\r\n\
+                   ,7,true,,\r\n";
+        let data = TabularData::parse(csv, TabularFormat::Csv).unwrap();
+        assert_eq!(data.headers.len(), 5);
+        assert_eq!(data.len(), 3);
+        assert_eq!(data.rows[0][4], serde_json::json!("said \"hi\"\nthen left"));
+
+        let schema = contacts_schema();
+        let preview = data.preview(&schema);
+        let mapping = preview.suggested_mapping;
+        assert_eq!(mapping.columns.len(), 4, "Notes has no matching field");
+        assert_eq!(mapping.columns["Born On"], "born_on");
+
+        let (entities, errors) = data.to_entities(&schema, &mapping).unwrap();
+        assert_eq!(entities.len(), 1);
+        let (row, jane) = &entities[0];
+        assert_eq!(*row, 1);
+        assert_eq!(jane["full_name"], Value::String("Doe, Jane".to_string()));
+        assert_eq!(jane["age"], Value::Integer(42));
+        assert_eq!(jane["active"], Value::Boolean(true));
+        assert_eq!(
+            jane["born_on"],
+            Value::DateTime("1982-03-04T00:00:00.000Z".to_string())
+        );
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            (errors[0].row, errors[0].field.as_deref()),
+            (2, Some("age"))
+        );
+        assert_eq!(
+            (errors[1].row, errors[1].field.as_deref()),
+            (3, Some("full_name"))
+        );
+    }
+
+    #[test]
+    fn test_json_import_and_mapping_validation() {
+        let json = r#"[{"name": "Ann", "years": 30}, {"name": "Ben", "extra": [1]}]"#;
+        let data = TabularData::parse(json, TabularFormat::Json).unwrap();
+        assert_eq!(data.headers, vec!["name", "years", "extra"]);
+        assert_eq!(data.rows[1][1], serde_json::Value::Null);
+
+        let schema = contacts_schema();
+        // full_name is required but not mapped
+        assert!(
+            data.to_entities(&schema, &ColumnMapping::new().map("years", "age"))
+                .is_err()
+        );
+
+        let mapping = ColumnMapping::new()
+            .map("name", "full_name")
+            .map("years", "age");
+        let (entities, errors) = data.to_entities(&schema, &mapping).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(entities[0].1["age"], Value::Integer(30));
+        assert!(!entities[1].1.contains_key("age"));
+    }
+}
@@ -2,13 +2,20 @@ pub mod adapter;
 pub mod api;
 pub mod core;
 pub mod di;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod export;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod import;
 pub mod operations;
 pub mod references;
+pub mod reminders;
 pub mod storage;
 pub mod sync;
 pub mod tasks;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod testing;
+#[cfg(all(feature = "webhooks", not(target_arch = "wasm32")))]
+pub mod webhooks;
 
 // Re-export query-render types for FFI
 pub use query_render::types::{Arg, BinaryOperator, RenderExpr, RenderSpec};
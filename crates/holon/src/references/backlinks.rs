@@ -0,0 +1,414 @@
+//! Backlink index
+//!
+//! Scans block/headline content for references to other entities — `((block-id))`,
+//! org `[[id:block-id]]` links and `[[wiki-links]]` — and keeps them in the
+//! `backlinks` table, so a "Linked references" panel is a plain query:
+//!
+//! ```text
+//! from backlinks | filter target_id == @id | render (list item_template:(text source_content))
+//! ```
+//!
+//! The index is rebuilt on start and then updated incrementally from the CDC change
+//! stream of one materialized view per content source.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::references::content_source::{self, ContentSource};
+use crate::references::embed::parse_embeds;
+use crate::storage::turso::{ChangeData, RowChange, TursoBackend};
+use holon_api::{BatchWithMetadata, DynamicEntity, HasSchema, Value};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Prefix of the materialized views the index watches (one per content source)
+const SOURCE_VIEW_PREFIX: &str = "backlinks_src_";
+
+/// A reference from a source block's content to a target
+///
+/// Table name: `backlinks`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Entity)]
+#[entity(name = "backlinks", short_name = "backlink")]
+pub struct Backlink {
+    /// `{source_table}:{source_id}:{kind}:{target_id}`
+    #[primary_key]
+    pub id: String,
+    #[indexed]
+    pub source_table: String,
+    #[indexed]
+    pub source_id: String,
+    /// Block ID (`block_ref`) or page name (`wiki_link`) the source refers to
+    #[indexed]
+    pub target_id: String,
+    /// "block_ref" or "wiki_link"
+    pub kind: String,
+    /// Content of the referencing block
+    pub source_content: String,
+    /// When the source was last indexed (Unix timestamp in milliseconds)
+    pub updated_at: i64,
+}
+
+/// Kind of a reference found in block content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LinkKind {
+    /// `((block-id))` or org `[[id:block-id]]`
+    BlockRef,
+    /// `[[Page name]]`
+    WikiLink,
+}
+
+impl LinkKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkKind::BlockRef => "block_ref",
+            LinkKind::WikiLink => "wiki_link",
+        }
+    }
+}
+
+/// Find all (deduplicated) references in `content`
+///
+/// Org links with a description (`[[target][description]]`) refer to `target`.
+pub fn parse_links(content: &str) -> Vec<(LinkKind, String)> {
+    let mut links = BTreeSet::new();
+    for embed in parse_embeds(content) {
+        links.insert((LinkKind::BlockRef, embed.block_id));
+    }
+
+    let mut offset = 0;
+    while let Some(start) = content[offset..].find("[[") {
+        let inner_start = offset + start + 2;
+        let Some(len) = content[inner_start..].find("]]") else {
+            break;
+        };
+        let inner = &content[inner_start..inner_start + len];
+        let target = inner.split("][").next().unwrap_or_default().trim();
+        if !target.is_empty() && !target.contains(['[', ']', '\n']) {
+            match target.strip_prefix("id:") {
+                Some(id) => links.insert((LinkKind::BlockRef, id.to_string())),
+                None => links.insert((LinkKind::WikiLink, target.to_string())),
+            };
+        }
+        offset = inner_start + len + 2;
+    }
+
+    links.into_iter().collect()
+}
+
+/// Maintains the `backlinks` table
+pub struct BacklinkIndex {
+    backend: Arc<RwLock<TursoBackend>>,
+    sources: Vec<ContentSource>,
+}
+
+impl BacklinkIndex {
+    /// Index the `blocks` and `org_headlines` tables and the text of annotations
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self {
+            backend,
+            sources: vec![
+                ContentSource::new("blocks", "id", "content"),
+                ContentSource::new("org_headlines", "id", "content"),
+                ContentSource::new("annotations", "id", "text"),
+            ],
+        }
+    }
+
+    /// Also index the content of `table`
+    pub fn with_source(mut self, source: ContentSource) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Initialize the backlinks table.
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = Backlink::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create backlinks table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        info!("Backlinks schema initialized");
+        Ok(())
+    }
+
+    /// Entities referring to `target_id`
+    pub async fn backlinks_to(&self, target_id: &str) -> Result<Vec<Backlink>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT * FROM backlinks WHERE target_id = $target_id ORDER BY source_table, source_id",
+                HashMap::from([(
+                    "target_id".to_string(),
+                    Value::String(target_id.to_string()),
+                )]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query backlinks: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new("backlinks");
+                entity.fields = row;
+                Backlink::from_entity(entity)
+            })
+            .collect()
+    }
+
+    /// Recompute the backlinks table from the content of all (existing) sources
+    pub async fn rebuild(&self) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql("DELETE FROM backlinks", HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to clear backlinks: {}", e))?;
+        drop(backend);
+
+        for source in self.existing_sources().await? {
+            let backend = self.backend.read().await;
+            let rows = backend
+                .execute_sql(&source_select_sql(&backend, source), HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to scan {}: {}", source.table, e))?;
+            drop(backend);
+
+            for row in rows {
+                let Some(source_id) = row.get("id").and_then(|v| v.as_string()) else {
+                    continue;
+                };
+                let content = row.get("content").and_then(|v| v.as_string());
+                self.index_source(&source.table, source_id, content).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the backlinks of one source entity (`None` content removes them)
+    pub async fn index_source(
+        &self,
+        source_table: &str,
+        source_id: &str,
+        content: Option<&str>,
+    ) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "DELETE FROM backlinks WHERE source_table = $source_table AND source_id = $source_id",
+                HashMap::from([
+                    (
+                        "source_table".to_string(),
+                        Value::String(source_table.to_string()),
+                    ),
+                    ("source_id".to_string(), Value::String(source_id.to_string())),
+                ]),
+            )
+            .await
+            .map_err(|e| format!("Failed to clear backlinks of {}: {}", source_id, e))?;
+
+        let Some(content) = content else {
+            return Ok(());
+        };
+        let updated_at = chrono::Utc::now().timestamp_millis();
+        let sql = "INSERT INTO backlinks (id, source_table, source_id, target_id, kind, source_content, updated_at)
+            VALUES ($id, $source_table, $source_id, $target_id, $kind, $source_content, $updated_at)";
+        for (kind, target_id) in parse_links(content) {
+            // Self-references are not backlinks
+            if target_id == source_id {
+                continue;
+            }
+            let backlink = Backlink {
+                id: format!(
+                    "{}:{}:{}:{}",
+                    source_table,
+                    source_id,
+                    kind.as_str(),
+                    target_id
+                ),
+                source_table: source_table.to_string(),
+                source_id: source_id.to_string(),
+                target_id,
+                kind: kind.as_str().to_string(),
+                source_content: content.to_string(),
+                updated_at,
+            };
+            backend
+                .execute_sql(sql, backlink.to_entity().fields)
+                .await
+                .map_err(|e| format!("Failed to insert backlink: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Apply a CDC batch of one of the source views
+    ///
+    /// Batches of other relations are ignored.
+    pub async fn apply_batch(&self, batch: &BatchWithMetadata<RowChange>) -> Result<()> {
+        let Some(source_table) = batch
+            .metadata
+            .relation_name
+            .strip_prefix(SOURCE_VIEW_PREFIX)
+        else {
+            return Ok(());
+        };
+
+        for row_change in &batch.inner.items {
+            let Some(source_id) = content_source::changed_entity_id(&row_change.change) else {
+                continue;
+            };
+            match &row_change.change {
+                ChangeData::Created { data, .. } | ChangeData::Updated { data, .. } => {
+                    let content = data.get("content").and_then(|v| v.as_string());
+                    self.index_source(source_table, source_id, content).await?;
+                }
+                ChangeData::ColumnChange { columns, .. } => {
+                    if let Some(content) = columns.get("content") {
+                        self.index_source(source_table, source_id, content.as_string())
+                            .await?;
+                    }
+                }
+                ChangeData::Deleted { .. } => {
+                    self.index_source(source_table, source_id, None).await?;
+                }
+            }
+        }
+        debug!(
+            "Applied {} content changes of {} to backlinks",
+            batch.inner.items.len(),
+            source_table
+        );
+        Ok(())
+    }
+
+    /// Rebuild the index, then keep it up to date from the change stream in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self: Arc<Self>) {
+        use std::ops::ControlFlow;
+
+        tokio::spawn(async move {
+            if let Err(e) = self.rebuild().await {
+                tracing::warn!("[BacklinkIndex] Failed to rebuild backlinks: {}", e);
+            }
+
+            let watched = async {
+                let views = self.source_views().await?;
+                content_source::follow_views(&self.backend, &views, |batch| {
+                    let index = self.clone();
+                    async move {
+                        if let Err(e) = index.apply_batch(&batch).await {
+                            tracing::warn!("[BacklinkIndex] Failed to update backlinks: {}", e);
+                        }
+                        ControlFlow::Continue(())
+                    }
+                })
+                .await
+            };
+            if let Err(e) = watched.await {
+                tracing::warn!("[BacklinkIndex] Failed to watch content sources: {}", e);
+            }
+        });
+    }
+
+    /// A materialized view `(name, select_sql)` per existing source
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn source_views(&self) -> Result<Vec<(String, String)>> {
+        let sources = self.existing_sources().await?;
+        let backend = self.backend.read().await;
+        Ok(sources
+            .into_iter()
+            .map(|source| {
+                (
+                    format!("{}{}", SOURCE_VIEW_PREFIX, source.table),
+                    source_select_sql(&backend, source),
+                )
+            })
+            .collect())
+    }
+
+    async fn existing_sources(&self) -> Result<Vec<&ContentSource>> {
+        let backend = self.backend.read().await;
+        let tables = content_source::existing_tables(&backend).await?;
+        Ok(self
+            .sources
+            .iter()
+            .filter(|source| tables.contains(&source.table))
+            .collect())
+    }
+}
+
+/// `SELECT id, content` of a source; trashed rows have no backlinks
+fn source_select_sql(backend: &TursoBackend, source: &ContentSource) -> String {
+    format!(
+        "SELECT {} AS id, {} AS content FROM {}{}",
+        source.id_column,
+        source.content_column,
+        source.table,
+        content_source::live_rows_filter(backend, &source.table)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    #[test]
+    fn test_parse_links() {
+        let links = parse_links(
+            "See ((block-1)), [[Project X]] and [[id:abc][the abc headline]]. Again [[Project X]].",
+        );
+        assert_eq!(
+            links,
+            vec![
+                (LinkKind::BlockRef, "abc".to_string()),
+                (LinkKind::BlockRef, "block-1".to_string()),
+                (LinkKind::WikiLink, "Project X".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_links_ignores_malformed() {
+        assert!(parse_links("[[]] [[unterminated ((  ))").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_index_source_replaces_links() {
+        let backend = memory_backend().await;
+        let index = BacklinkIndex::new(backend);
+        index.initialize_schema().await.unwrap();
+
+        index
+            .index_source("blocks", "a", Some("links ((b)) and [[Page]]"))
+            .await
+            .unwrap();
+        assert_eq!(index.backlinks_to("b").await.unwrap().len(), 1);
+        assert_eq!(index.backlinks_to("Page").await.unwrap().len(), 1);
+
+        index
+            .index_source("blocks", "a", Some("now only ((c))"))
+            .await
+            .unwrap();
+        assert!(index.backlinks_to("b").await.unwrap().is_empty());
+        let backlinks = index.backlinks_to("c").await.unwrap();
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].source_id, "a");
+        assert_eq!(backlinks[0].kind, "block_ref");
+
+        index.index_source("blocks", "a", None).await.unwrap();
+        assert!(index.backlinks_to("c").await.unwrap().is_empty());
+    }
+}
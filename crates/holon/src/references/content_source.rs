@@ -0,0 +1,143 @@
+//! Tables of blocks with text content
+//!
+//! Embeds are looked up in these tables; content-derived indexes (backlinks, tags)
+//! scan them on rebuild and follow their changes through one materialized view each
+//! (`follow_views`, shared with the other derived tables and the automation rules).
+
+use std::collections::{HashMap, HashSet};
+
+use crate::storage::turso::{ChangeData, TursoBackend};
+use crate::storage::types::StorageEntity;
+use holon_api::{DELETED_AT_COLUMN, Value};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// A table of blocks with text content
+#[derive(Debug, Clone)]
+pub struct ContentSource {
+    pub table: String,
+    pub id_column: String,
+    pub content_column: String,
+}
+
+impl ContentSource {
+    pub fn new(
+        table: impl Into<String>,
+        id_column: impl Into<String>,
+        content_column: impl Into<String>,
+    ) -> Self {
+        Self {
+            table: table.into(),
+            id_column: id_column.into(),
+            content_column: content_column.into(),
+        }
+    }
+}
+
+/// ` WHERE deleted_at IS NULL` if `table` has soft delete enabled, empty otherwise
+pub(crate) fn live_rows_filter(backend: &TursoBackend, table: &str) -> String {
+    if backend.soft_delete_tables().contains(table) {
+        format!(" WHERE {} IS NULL", DELETED_AT_COLUMN)
+    } else {
+        String::new()
+    }
+}
+
+/// Names of all tables in the database
+pub(crate) async fn existing_tables(backend: &TursoBackend) -> Result<HashSet<String>> {
+    let rows = backend
+        .execute_sql(
+            "SELECT name FROM sqlite_master WHERE type = 'table'",
+            HashMap::new(),
+        )
+        .await
+        .map_err(|e| format!("Failed to list tables: {}", e))?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| row.get("name").and_then(|v| v.as_string_owned()))
+        .collect())
+}
+
+/// Entity ID of the row a view change is about
+///
+/// `Created` and `Updated` carry the ROWID as their `id`; the entity ID is the `id`
+/// column of their row data.
+pub(crate) fn changed_entity_id(change: &ChangeData) -> Option<&str> {
+    match change {
+        ChangeData::Created { data, .. } | ChangeData::Updated { data, .. } => {
+            data.get("id").and_then(|v| v.as_string())
+        }
+        ChangeData::ColumnChange { id, .. } | ChangeData::Deleted { id, .. } => Some(id),
+    }
+}
+
+/// Row `id` of a view's `select_sql`, e.g. after a `ColumnChange` that only carries
+/// the changed columns
+///
+/// None if the row no longer matches the view (deleted or trashed).
+pub(crate) async fn load_view_row(
+    backend: &TursoBackend,
+    select_sql: &str,
+    id: &str,
+) -> crate::storage::types::Result<Option<StorageEntity>> {
+    let rows = backend
+        .execute_sql(
+            &format!("SELECT * FROM ({}) WHERE id = $id", select_sql),
+            HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+        )
+        .await?;
+    Ok(rows.into_iter().next())
+}
+
+/// (Re)create materialized views `(name, select_sql)` and hand each batch of their
+/// changes to `on_batch`, until the stream ends or `on_batch` breaks
+///
+/// Batches of all views arrive in one stream; filter by relation name.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn follow_views<F, Fut>(
+    backend: &tokio::sync::RwLock<TursoBackend>,
+    views: &[(String, String)],
+    mut on_batch: F,
+) -> Result<()>
+where
+    F: FnMut(holon_api::BatchWithMetadata<crate::storage::turso::RowChange>) -> Fut,
+    Fut: std::future::Future<Output = std::ops::ControlFlow<()>>,
+{
+    use tokio_stream::StreamExt;
+
+    // The CDC connection must outlive the stream for changes to keep coming
+    let (_cdc_conn, mut stream) = {
+        let backend = backend.read().await;
+        watch_views(&backend, views).await?
+    };
+    while let Some(batch) = stream.next().await {
+        if on_batch(batch).await.is_break() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn watch_views(
+    backend: &TursoBackend,
+    views: &[(String, String)],
+) -> Result<(turso::Connection, crate::storage::turso::RowChangeStream)> {
+    for (view_name, select_sql) in views {
+        backend
+            .execute_sql(
+                &format!("DROP VIEW IF EXISTS {}", view_name),
+                HashMap::new(),
+            )
+            .await
+            .map_err(|e| format!("Failed to drop view {}: {}", view_name, e))?;
+        backend
+            .execute_sql(
+                &format!("CREATE MATERIALIZED VIEW {} AS {}", view_name, select_sql),
+                HashMap::new(),
+            )
+            .await
+            .map_err(|e| format!("Failed to create view {}: {}", view_name, e))?;
+    }
+    Ok(backend.row_changes()?)
+}
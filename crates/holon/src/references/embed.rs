@@ -0,0 +1,315 @@
+//! Block transclusion via `((block-id))` embed references
+//!
+//! Query results whose content embeds another block get an `_embeds` column holding
+//! the embedded blocks as nested `embed(...)` RenderExprs (JSON-encoded). Embeds are
+//! resolved recursively; a block that (transitively) embeds itself is rendered as
+//! `embed(block_id:..., cycle:true)` instead of being expanded again.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use holon_api::{Arg, DELETED_AT_COLUMN, RenderExpr, Value};
+
+use crate::references::content_source::ContentSource;
+use crate::storage::turso::TursoBackend;
+use crate::storage::{Result, StorageError};
+
+/// Row column holding the resolved embeds of the row's content
+pub const EMBEDS_COLUMN: &str = "_embeds";
+
+/// A `((block-id))` occurrence in block content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbedReference {
+    pub block_id: String,
+    /// Byte range of the whole `((block-id))` token
+    pub span: Range<usize>,
+}
+
+/// Find all `((block-id))` embeds in `content`
+///
+/// Block ids can't contain whitespace or parentheses, so `(( not an embed ))` is ignored.
+pub fn parse_embeds(content: &str) -> Vec<EmbedReference> {
+    let mut embeds = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = content[offset..].find("((") {
+        let start = offset + start;
+        let id_start = start + 2;
+        let id_len = content[id_start..]
+            .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .unwrap_or(content.len() - id_start);
+        let id_end = id_start + id_len;
+        if id_len > 0 && content[id_end..].starts_with("))") {
+            embeds.push(EmbedReference {
+                block_id: content[id_start..id_end].to_string(),
+                span: start..id_end + 2,
+            });
+            offset = id_end + 2;
+        } else {
+            offset = start + 1;
+        }
+    }
+    embeds
+}
+
+/// Resolves embed references in query results
+#[derive(Debug, Clone)]
+pub struct EmbedResolver {
+    sources: Vec<ContentSource>,
+    /// Maximum nesting of embeds; deeper embeds are left unexpanded
+    max_depth: usize,
+}
+
+impl Default for EmbedResolver {
+    fn default() -> Self {
+        Self {
+            sources: vec![ContentSource::new("blocks", "id", "content")],
+            max_depth: 8,
+        }
+    }
+}
+
+impl EmbedResolver {
+    /// Also look up embedded blocks in `table`
+    pub fn with_source(
+        mut self,
+        table: impl Into<String>,
+        id_column: impl Into<String>,
+        content_column: impl Into<String>,
+    ) -> Self {
+        self.sources
+            .push(ContentSource::new(table, id_column, content_column));
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Add the `_embeds` column to every row whose content contains embeds
+    pub async fn resolve_rows(
+        &self,
+        backend: &TursoBackend,
+        rows: &mut [HashMap<String, Value>],
+    ) -> Result<()> {
+        for row in rows.iter_mut() {
+            let Some(content) = self.row_content(row) else {
+                continue;
+            };
+            let embeds = parse_embeds(&content);
+            if embeds.is_empty() {
+                continue;
+            }
+
+            // The row itself is the root of the embed chain
+            let mut visited = HashSet::new();
+            if let Some(id) = row.get("id").and_then(|v| v.as_string()) {
+                visited.insert(id.to_string());
+            }
+            let mut items = Vec::with_capacity(embeds.len());
+            for embed in embeds {
+                items.push(
+                    self.resolve_embed(backend, &embed.block_id, &mut visited, 1)
+                        .await?,
+                );
+            }
+            let json = serde_json::to_string(&items)
+                .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+            row.insert(EMBEDS_COLUMN.to_string(), Value::Json(json));
+        }
+        Ok(())
+    }
+
+    fn row_content(&self, row: &HashMap<String, Value>) -> Option<String> {
+        self.sources.iter().find_map(|source| {
+            row.get(&source.content_column)
+                .and_then(|v| v.as_string())
+                .filter(|content| content.contains("(("))
+                .map(|content| content.to_string())
+        })
+    }
+
+    /// Resolve one embed into an `embed(block_id:, content:, embeds:)` RenderExpr
+    ///
+    /// `visited` holds the blocks on the current embed chain.
+    fn resolve_embed<'a>(
+        &'a self,
+        backend: &'a TursoBackend,
+        block_id: &'a str,
+        visited: &'a mut HashSet<String>,
+        depth: usize,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<RenderExpr>> + Send + 'a>> {
+        Box::pin(async move {
+            if visited.contains(block_id) {
+                return Ok(embed_expr(
+                    block_id,
+                    vec![named("cycle", bool_literal(true))],
+                ));
+            }
+            let Some(content) = self.load_content(backend, block_id).await? else {
+                return Ok(embed_expr(
+                    block_id,
+                    vec![named("missing", bool_literal(true))],
+                ));
+            };
+
+            let mut nested = Vec::new();
+            if depth < self.max_depth {
+                visited.insert(block_id.to_string());
+                for embed in parse_embeds(&content) {
+                    nested.push(
+                        self.resolve_embed(backend, &embed.block_id, visited, depth + 1)
+                            .await?,
+                    );
+                }
+                visited.remove(block_id);
+            }
+
+            Ok(embed_expr(
+                block_id,
+                vec![
+                    named(
+                        "content",
+                        RenderExpr::Literal {
+                            value: Value::String(content),
+                        },
+                    ),
+                    named("embeds", RenderExpr::Array { items: nested }),
+                ],
+            ))
+        })
+    }
+
+    async fn load_content(&self, backend: &TursoBackend, block_id: &str) -> Result<Option<String>> {
+        let soft_delete_tables = backend.soft_delete_tables();
+        for source in &self.sources {
+            let mut sql = format!(
+                "SELECT {} AS content FROM {} WHERE {} = $id",
+                source.content_column, source.table, source.id_column
+            );
+            if soft_delete_tables.contains(&source.table) {
+                sql.push_str(&format!(" AND {} IS NULL", DELETED_AT_COLUMN));
+            }
+            let params = HashMap::from([("id".to_string(), Value::String(block_id.to_string()))]);
+            let rows = match backend.execute_sql(&sql, params).await {
+                Ok(rows) => rows,
+                Err(e) => {
+                    tracing::debug!(
+                        "[EmbedResolver] Skipping source {} for {}: {}",
+                        source.table,
+                        block_id,
+                        e
+                    );
+                    continue;
+                }
+            };
+            if let Some(content) = rows
+                .first()
+                .and_then(|row| row.get("content"))
+                .and_then(|v| v.as_string_owned())
+            {
+                return Ok(Some(content));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn embed_expr(block_id: &str, mut args: Vec<Arg>) -> RenderExpr {
+    args.insert(
+        0,
+        named(
+            "block_id",
+            RenderExpr::Literal {
+                value: Value::String(block_id.to_string()),
+            },
+        ),
+    );
+    RenderExpr::FunctionCall {
+        name: "embed".to_string(),
+        args,
+        operations: vec![],
+    }
+}
+
+fn named(name: &str, value: RenderExpr) -> Arg {
+    Arg {
+        name: Some(name.to_string()),
+        value,
+    }
+}
+
+fn bool_literal(value: bool) -> RenderExpr {
+    RenderExpr::Literal {
+        value: Value::Boolean(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_embeds() {
+        let embeds = parse_embeds("See ((block-1)) and ((abc_2)).");
+        assert_eq!(
+            embeds,
+            vec![
+                EmbedReference {
+                    block_id: "block-1".to_string(),
+                    span: 4..15,
+                },
+                EmbedReference {
+                    block_id: "abc_2".to_string(),
+                    span: 20..29,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_embeds_ignores_malformed() {
+        assert!(parse_embeds("(( spaced )) (()) ((open").is_empty());
+        // Nested parentheses: only the innermost well-formed token counts
+        let embeds = parse_embeds("(((inner)))");
+        assert_eq!(embeds.len(), 1);
+        assert_eq!(embeds[0].block_id, "inner");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_rows_detects_cycles() {
+        let backend = TursoBackend::new_in_memory().await.unwrap();
+        backend
+            .execute_sql(
+                "CREATE TABLE blocks (id TEXT PRIMARY KEY, content TEXT)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+            .execute_sql(
+                "INSERT INTO blocks (id, content) VALUES ('a', 'A embeds ((b))'), ('b', 'B embeds ((a))')",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+
+        let mut rows = vec![HashMap::from([
+            ("id".to_string(), Value::String("a".to_string())),
+            (
+                "content".to_string(),
+                Value::String("A embeds ((b))".to_string()),
+            ),
+        ])];
+        EmbedResolver::default()
+            .resolve_rows(&backend, &mut rows)
+            .await
+            .unwrap();
+
+        let embeds: serde_json::Value = rows[0][EMBEDS_COLUMN].as_json_value().unwrap();
+        let json = embeds.to_string();
+        // a -> b -> a stops at the cycle instead of recursing
+        assert!(json.contains("B embeds ((a))"));
+        assert!(json.contains("cycle"));
+    }
+}
@@ -1,7 +1,15 @@
+pub mod backlinks;
 pub mod block_reference;
+pub mod content_source;
+pub mod embed;
 pub mod resolver;
+pub mod tags;
 pub mod view_config;
 
+pub use backlinks::*;
 pub use block_reference::*;
+pub use content_source::ContentSource;
+pub use embed::*;
 pub use resolver::*;
+pub use tags::*;
 pub use view_config::*;
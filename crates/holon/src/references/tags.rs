@@ -0,0 +1,772 @@
+//! Tags
+//!
+//! `#tags` in block content and org `:tag1:tag2:` groups (or a comma-separated tag
+//! column such as `org_headlines.tags`) are extracted into the normalized `tags`
+//! table, one row per (source entity, tag). Tag-based views join on it:
+//!
+//! ```text
+//! from todoist_tasks | join tags (==source_id) | filter tags.tag == "work"
+//! ```
+//!
+//! Like the backlink index, the table is rebuilt on start and then updated from the
+//! CDC change stream. `add_tag`/`remove_tag` operations rewrite the underlying
+//! content through the owning entity's `set_field`, so the change reaches its source.
+
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+use std::sync::{Arc, RwLock as StdRwLock, Weak};
+
+use async_trait::async_trait;
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::api::operation_dispatcher::OperationDispatcher;
+use crate::core::datasource::{OperationProvider, Result, UndoAction};
+use crate::references::content_source;
+use crate::storage::turso::{ChangeData, RowChange, TursoBackend};
+use crate::storage::types::StorageEntity;
+use holon_api::{
+    BatchWithMetadata, DynamicEntity, HasSchema, Operation, OperationDescriptor, OperationParam,
+    TypeHint, Value,
+};
+
+/// Prefix of the materialized views the index watches (one per tag source)
+const SOURCE_VIEW_PREFIX: &str = "tags_src_";
+
+/// A tag of a source entity
+///
+/// Table name: `tags`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Entity)]
+#[entity(name = "tags", short_name = "tag")]
+pub struct Tag {
+    /// `{source_table}:{source_id}:{tag}`
+    #[primary_key]
+    pub id: String,
+    /// Normalized tag (lowercase, without `#`)
+    #[indexed]
+    pub tag: String,
+    #[indexed]
+    pub source_table: String,
+    /// ID of the tagged entity (join key for queries)
+    #[indexed]
+    pub source_id: String,
+    /// Entity name of the tagged entity (for `add_tag`/`remove_tag`)
+    pub entity_name: String,
+    /// When the source was last indexed (Unix timestamp in milliseconds)
+    pub updated_at: i64,
+}
+
+/// Normalize a tag: strip a leading `#`, lowercase
+///
+/// Returns `None` for tags that can't be written as `#tag`.
+pub fn normalize_tag(raw: &str) -> Option<String> {
+    let tag = raw.trim();
+    let tag = tag.strip_prefix('#').unwrap_or(tag);
+    if tag.is_empty() || !tag.chars().all(is_tag_char) {
+        return None;
+    }
+    Some(tag.to_lowercase())
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || c == '/'
+}
+
+/// `#tag` occurrences in `content` (byte range of `#tag`, normalized tag)
+///
+/// A `#` only starts a tag at the start of the content or after whitespace or `(`,
+/// so `a#b`, `##` and markdown headings (`# Title`) are not tags.
+fn hashtag_spans(content: &str) -> Vec<(Range<usize>, String)> {
+    let mut spans = Vec::new();
+    let mut prev: Option<char> = None;
+    for (i, c) in content.char_indices() {
+        if c == '#' && prev.is_none_or(|p| p.is_whitespace() || p == '(') {
+            let len = content[i + 1..]
+                .find(|c: char| !is_tag_char(c))
+                .unwrap_or(content.len() - i - 1);
+            if let Some(tag) = normalize_tag(&content[i + 1..i + 1 + len]) {
+                spans.push((i..i + 1 + len, tag));
+            }
+        }
+        prev = Some(c);
+    }
+    spans
+}
+
+/// Org tag group `:tag1:tag2:` at the end of `line` (byte range within the line, tags)
+fn org_tag_group(line: &str) -> Option<(Range<usize>, Vec<String>)> {
+    let trimmed = line.trim_end();
+    let start = trimmed.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let group = &trimmed[start..];
+    if group.len() < 3 || !group.starts_with(':') || !group.ends_with(':') {
+        return None;
+    }
+    let tags: Option<Vec<String>> = group[1..group.len() - 1]
+        .split(':')
+        .map(normalize_tag)
+        .collect();
+    tags.map(|tags| (start..trimmed.len(), tags))
+}
+
+/// All (deduplicated, normalized) tags in `content`
+pub fn parse_tags(content: &str) -> Vec<String> {
+    let mut tags: BTreeSet<String> = hashtag_spans(content)
+        .into_iter()
+        .map(|(_, tag)| tag)
+        .collect();
+    for line in content.lines() {
+        if let Some((_, group)) = org_tag_group(line) {
+            tags.extend(group);
+        }
+    }
+    tags.into_iter().collect()
+}
+
+/// Tags of a comma-separated tag column value
+pub fn parse_tag_list(list: &str) -> Vec<String> {
+    let tags: BTreeSet<String> = list.split(',').filter_map(normalize_tag).collect();
+    tags.into_iter().collect()
+}
+
+/// `content` with `#tag` appended, or `None` if it already has the tag
+pub fn add_tag_to_content(content: &str, tag: &str) -> Option<String> {
+    if parse_tags(content).iter().any(|t| t == tag) {
+        return None;
+    }
+    let content = content.trim_end();
+    Some(if content.is_empty() {
+        format!("#{}", tag)
+    } else {
+        format!("{} #{}", content, tag)
+    })
+}
+
+/// `content` without any `#tag` or org `:tag:` occurrence, or `None` if it doesn't have the tag
+pub fn remove_tag_from_content(content: &str, tag: &str) -> Option<String> {
+    if !parse_tags(content).iter().any(|t| t == tag) {
+        return None;
+    }
+
+    let lines: Vec<String> = content
+        .split('\n')
+        .map(|line| {
+            let mut line = line.to_string();
+            let original_len = line.len();
+            if let Some((range, group)) = org_tag_group(&line) {
+                let kept: Vec<&String> = group.iter().filter(|t| *t != tag).collect();
+                if kept.len() != group.len() {
+                    let replacement = if kept.is_empty() {
+                        String::new()
+                    } else {
+                        let kept: Vec<&str> = kept.iter().map(|t| t.as_str()).collect();
+                        format!(":{}:", kept.join(":"))
+                    };
+                    line.replace_range(range, &replacement);
+                }
+            }
+            // Remove back to front so earlier spans stay valid
+            for (range, found) in hashtag_spans(&line).into_iter().rev() {
+                if found == tag {
+                    let start = if line[..range.start].ends_with(' ') {
+                        range.start - 1
+                    } else {
+                        range.start
+                    };
+                    line.replace_range(start..range.end, "");
+                }
+            }
+            if line.len() != original_len {
+                line.truncate(line.trim_end().len());
+            }
+            line
+        })
+        .collect();
+    Some(lines.join("\n"))
+}
+
+/// A table whose entities carry tags
+#[derive(Debug, Clone)]
+pub struct TagSource {
+    pub table: String,
+    /// Entity name the `add_tag`/`remove_tag` operations are registered for
+    pub entity_name: String,
+    pub id_column: String,
+    /// Text column scanned for `#tags` and org tag groups
+    pub content_column: String,
+    /// Comma-separated tag column; when set, `add_tag` writes here instead of the content
+    pub list_column: Option<String>,
+}
+
+impl TagSource {
+    pub fn new(
+        table: impl Into<String>,
+        entity_name: impl Into<String>,
+        id_column: impl Into<String>,
+        content_column: impl Into<String>,
+    ) -> Self {
+        Self {
+            table: table.into(),
+            entity_name: entity_name.into(),
+            id_column: id_column.into(),
+            content_column: content_column.into(),
+            list_column: None,
+        }
+    }
+
+    pub fn with_list_column(mut self, list_column: impl Into<String>) -> Self {
+        self.list_column = Some(list_column.into());
+        self
+    }
+
+    /// `SELECT id, content[, tag_list]`; trashed rows have no tags
+    fn select_sql(&self, backend: &TursoBackend) -> String {
+        let list = self
+            .list_column
+            .as_ref()
+            .map(|column| format!(", {} AS tag_list", column))
+            .unwrap_or_default();
+        format!(
+            "SELECT {} AS id, {} AS content{} FROM {}{}",
+            self.id_column,
+            self.content_column,
+            list,
+            self.table,
+            content_source::live_rows_filter(backend, &self.table)
+        )
+    }
+}
+
+/// Maintains the `tags` table and provides the `add_tag`/`remove_tag` operations
+pub struct TagIndex {
+    backend: Arc<RwLock<TursoBackend>>,
+    sources: Vec<TagSource>,
+    /// Routes the `set_field` operations that rewrite tagged content
+    dispatcher: StdRwLock<Weak<OperationDispatcher>>,
+}
+
+impl TagIndex {
+    /// Index `blocks`, `todoist_tasks` and `org_headlines` (title and `tags` column)
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self {
+            backend,
+            sources: vec![
+                TagSource::new("blocks", "blocks", "id", "content"),
+                TagSource::new("todoist_tasks", "todoist_tasks", "id", "content"),
+                TagSource::new("org_headlines", "org_headlines", "id", "title")
+                    .with_list_column("tags"),
+            ],
+            dispatcher: StdRwLock::new(Weak::new()),
+        }
+    }
+
+    /// Also index the tags of `source`
+    pub fn with_source(mut self, source: TagSource) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Set the dispatcher used to rewrite tagged content
+    ///
+    /// Only a weak reference is kept since the dispatcher owns this index as a provider.
+    pub fn bind_dispatcher(&self, dispatcher: &Arc<OperationDispatcher>) {
+        *self.dispatcher.write().unwrap() = Arc::downgrade(dispatcher);
+    }
+
+    /// Initialize the tags table.
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = Tag::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create tags table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        info!("Tags schema initialized");
+        Ok(())
+    }
+
+    /// All tags with the number of tagged entities, most used first
+    pub async fn tag_counts(&self) -> Result<Vec<(String, i64)>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT tag, COUNT(*) AS count FROM tags GROUP BY tag ORDER BY count DESC, tag ASC",
+                HashMap::new(),
+            )
+            .await
+            .map_err(|e| format!("Failed to query tags: {}", e))?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let tag = row.get("tag").and_then(|v| v.as_string_owned())?;
+                let count = row.get("count").and_then(|v| v.as_i64()).unwrap_or(0);
+                Some((tag, count))
+            })
+            .collect())
+    }
+
+    /// Entities tagged with `tag`
+    pub async fn tagged(&self, tag: &str) -> Result<Vec<Tag>> {
+        let tag = normalize_tag(tag).ok_or_else(|| format!("Invalid tag: {}", tag))?;
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT * FROM tags WHERE tag = $tag ORDER BY source_table, source_id",
+                HashMap::from([("tag".to_string(), Value::String(tag))]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query tags: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new("tags");
+                entity.fields = row;
+                Tag::from_entity(entity)
+            })
+            .collect()
+    }
+
+    /// Recompute the tags table from all (existing) sources
+    pub async fn rebuild(&self) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql("DELETE FROM tags", HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to clear tags: {}", e))?;
+        drop(backend);
+
+        for source in self.existing_sources().await? {
+            let backend = self.backend.read().await;
+            let rows = backend
+                .execute_sql(&source.select_sql(&backend), HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to scan {}: {}", source.table, e))?;
+            drop(backend);
+
+            for row in rows {
+                let Some(source_id) = row.get("id").and_then(|v| v.as_string()) else {
+                    continue;
+                };
+                let tags = row_tags(&row);
+                self.index_source(source, source_id, &tags).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the tags of one source entity (an empty list removes them)
+    pub async fn index_source(
+        &self,
+        source: &TagSource,
+        source_id: &str,
+        tags: &[String],
+    ) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "DELETE FROM tags WHERE source_table = $source_table AND source_id = $source_id",
+                HashMap::from([
+                    (
+                        "source_table".to_string(),
+                        Value::String(source.table.clone()),
+                    ),
+                    (
+                        "source_id".to_string(),
+                        Value::String(source_id.to_string()),
+                    ),
+                ]),
+            )
+            .await
+            .map_err(|e| format!("Failed to clear tags of {}: {}", source_id, e))?;
+
+        let updated_at = chrono::Utc::now().timestamp_millis();
+        let sql = "INSERT INTO tags (id, tag, source_table, source_id, entity_name, updated_at)
+            VALUES ($id, $tag, $source_table, $source_id, $entity_name, $updated_at)";
+        for tag in tags {
+            let row = Tag {
+                id: format!("{}:{}:{}", source.table, source_id, tag),
+                tag: tag.clone(),
+                source_table: source.table.clone(),
+                source_id: source_id.to_string(),
+                entity_name: source.entity_name.clone(),
+                updated_at,
+            };
+            backend
+                .execute_sql(sql, row.to_entity().fields)
+                .await
+                .map_err(|e| format!("Failed to insert tag: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Apply a CDC batch of one of the source views
+    ///
+    /// Batches of other relations are ignored.
+    pub async fn apply_batch(&self, batch: &BatchWithMetadata<RowChange>) -> Result<()> {
+        let Some(source) = batch
+            .metadata
+            .relation_name
+            .strip_prefix(SOURCE_VIEW_PREFIX)
+            .and_then(|table| self.sources.iter().find(|source| source.table == table))
+        else {
+            return Ok(());
+        };
+
+        for row_change in &batch.inner.items {
+            let Some(source_id) = content_source::changed_entity_id(&row_change.change) else {
+                continue;
+            };
+            match &row_change.change {
+                ChangeData::Created { data, .. } | ChangeData::Updated { data, .. } => {
+                    self.index_source(source, source_id, &row_tags(data))
+                        .await?;
+                }
+                ChangeData::ColumnChange { columns, .. } => {
+                    if !columns.contains_key("content") && !columns.contains_key("tag_list") {
+                        continue;
+                    }
+                    let tags = self.load_tags(source, source_id).await?;
+                    self.index_source(source, source_id, &tags).await?;
+                }
+                ChangeData::Deleted { .. } => {
+                    self.index_source(source, source_id, &[]).await?;
+                }
+            }
+        }
+        debug!(
+            "Applied {} tag source changes of {}",
+            batch.inner.items.len(),
+            source.table
+        );
+        Ok(())
+    }
+
+    /// Rebuild the index, then keep it up to date from the change stream in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self: Arc<Self>) {
+        use std::ops::ControlFlow;
+
+        tokio::spawn(async move {
+            if let Err(e) = self.rebuild().await {
+                tracing::warn!("[TagIndex] Failed to rebuild tags: {}", e);
+            }
+
+            let watched = async {
+                let views = self.source_views().await?;
+                content_source::follow_views(&self.backend, &views, |batch| {
+                    let index = self.clone();
+                    async move {
+                        if let Err(e) = index.apply_batch(&batch).await {
+                            tracing::warn!("[TagIndex] Failed to update tags: {}", e);
+                        }
+                        ControlFlow::Continue(())
+                    }
+                })
+                .await
+            };
+            if let Err(e) = watched.await {
+                tracing::warn!("[TagIndex] Failed to watch tag sources: {}", e);
+            }
+        });
+    }
+
+    /// Add `tag` to an entity, writing the source's tag column or appending `#tag`
+    ///
+    /// Returns the inverse `remove_tag`, or `Irreversible` if the entity already had the tag.
+    pub async fn add_tag(&self, entity_name: &str, id: &str, tag: &str) -> Result<UndoAction> {
+        let source = self.source_for_entity(entity_name)?;
+        let tag = normalize_tag(tag).ok_or_else(|| format!("Invalid tag: {}", tag))?;
+        let (content, tag_list) = self.load_row(source, id).await?;
+
+        let changed = match &source.list_column {
+            Some(list_column) => {
+                let mut tags = parse_tag_list(tag_list.as_deref().unwrap_or_default());
+                let has_tag = tags.contains(&tag) || parse_tags(&content).contains(&tag);
+                if !has_tag {
+                    tags.push(tag.clone());
+                    self.set_field(entity_name, id, list_column, tags.join(","))
+                        .await?;
+                }
+                !has_tag
+            }
+            None => match add_tag_to_content(&content, &tag) {
+                Some(new_content) => {
+                    self.set_field(entity_name, id, &source.content_column, new_content)
+                        .await?;
+                    true
+                }
+                None => false,
+            },
+        };
+
+        Ok(if changed {
+            UndoAction::Undo(tag_operation(entity_name, "remove_tag", id, &tag))
+        } else {
+            UndoAction::Irreversible
+        })
+    }
+
+    /// Remove `tag` from an entity's content and tag column
+    ///
+    /// Returns the inverse `add_tag`, or `Irreversible` if the entity didn't have the tag.
+    pub async fn remove_tag(&self, entity_name: &str, id: &str, tag: &str) -> Result<UndoAction> {
+        let source = self.source_for_entity(entity_name)?;
+        let tag = normalize_tag(tag).ok_or_else(|| format!("Invalid tag: {}", tag))?;
+        let (content, tag_list) = self.load_row(source, id).await?;
+
+        let mut changed = false;
+        if let Some(new_content) = remove_tag_from_content(&content, &tag) {
+            self.set_field(entity_name, id, &source.content_column, new_content)
+                .await?;
+            changed = true;
+        }
+        if let (Some(list_column), Some(tag_list)) = (&source.list_column, tag_list) {
+            let tags = parse_tag_list(&tag_list);
+            if tags.contains(&tag) {
+                let kept: Vec<String> = tags.into_iter().filter(|t| *t != tag).collect();
+                self.set_field(entity_name, id, list_column, kept.join(","))
+                    .await?;
+                changed = true;
+            }
+        }
+
+        Ok(if changed {
+            UndoAction::Undo(tag_operation(entity_name, "add_tag", id, &tag))
+        } else {
+            UndoAction::Irreversible
+        })
+    }
+
+    fn source_for_entity(&self, entity_name: &str) -> Result<&TagSource> {
+        self.sources
+            .iter()
+            .find(|source| source.entity_name == entity_name)
+            .ok_or_else(|| format!("Entity '{}' has no tag source", entity_name).into())
+    }
+
+    /// Current (content, tag list) of an entity
+    async fn load_row(&self, source: &TagSource, id: &str) -> Result<(String, Option<String>)> {
+        let backend = self.backend.read().await;
+        let row = content_source::load_view_row(&backend, &source.select_sql(&backend), id)
+            .await
+            .map_err(|e| format!("Failed to load {} {}: {}", source.table, id, e))?
+            .ok_or_else(|| format!("Entity not found in {}: {}", source.table, id))?;
+        Ok((
+            row.get("content")
+                .and_then(|v| v.as_string_owned())
+                .unwrap_or_default(),
+            row.get("tag_list").and_then(|v| v.as_string_owned()),
+        ))
+    }
+
+    async fn load_tags(&self, source: &TagSource, id: &str) -> Result<Vec<String>> {
+        let (content, tag_list) = match self.load_row(source, id).await {
+            Ok(row) => row,
+            // Trashed or already deleted
+            Err(_) => return Ok(Vec::new()),
+        };
+        let mut row = HashMap::from([("content".to_string(), Value::String(content))]);
+        if let Some(tag_list) = tag_list {
+            row.insert("tag_list".to_string(), Value::String(tag_list));
+        }
+        Ok(row_tags(&row))
+    }
+
+    async fn set_field(
+        &self,
+        entity_name: &str,
+        id: &str,
+        field: &str,
+        value: String,
+    ) -> Result<()> {
+        let dispatcher = self
+            .dispatcher
+            .read()
+            .unwrap()
+            .upgrade()
+            .ok_or_else(|| "Tag operations are not bound to a dispatcher".to_string())?;
+        dispatcher
+            .execute_operation(
+                entity_name,
+                "set_field",
+                HashMap::from([
+                    ("id".to_string(), Value::String(id.to_string())),
+                    ("field".to_string(), Value::String(field.to_string())),
+                    ("value".to_string(), Value::String(value)),
+                ]),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// A materialized view `(name, select_sql)` per existing source
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn source_views(&self) -> Result<Vec<(String, String)>> {
+        let sources = self.existing_sources().await?;
+        let backend = self.backend.read().await;
+        Ok(sources
+            .into_iter()
+            .map(|source| {
+                (
+                    format!("{}{}", SOURCE_VIEW_PREFIX, source.table),
+                    source.select_sql(&backend),
+                )
+            })
+            .collect())
+    }
+
+    async fn existing_sources(&self) -> Result<Vec<&TagSource>> {
+        let backend = self.backend.read().await;
+        let tables = content_source::existing_tables(&backend).await?;
+        Ok(self
+            .sources
+            .iter()
+            .filter(|source| tables.contains(&source.table))
+            .collect())
+    }
+}
+
+/// Tags of a source row (`content` and optional `tag_list` columns)
+fn row_tags(row: &HashMap<String, Value>) -> Vec<String> {
+    let mut tags: BTreeSet<String> = row
+        .get("content")
+        .and_then(|v| v.as_string())
+        .map(parse_tags)
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    if let Some(tag_list) = row.get("tag_list").and_then(|v| v.as_string()) {
+        tags.extend(parse_tag_list(tag_list));
+    }
+    tags.into_iter().collect()
+}
+
+fn tag_operation(entity_name: &str, op_name: &str, id: &str, tag: &str) -> Operation {
+    Operation::new(
+        entity_name,
+        op_name,
+        "",
+        HashMap::from([
+            ("id".to_string(), Value::String(id.to_string())),
+            ("tag".to_string(), Value::String(tag.to_string())),
+        ]),
+    )
+}
+
+fn tag_descriptor(source: &TagSource, name: &str, display_name: &str) -> OperationDescriptor {
+    OperationDescriptor {
+        entity_name: source.entity_name.clone(),
+        entity_short_name: source.entity_name.clone(),
+        id_column: source.id_column.clone(),
+        name: name.to_string(),
+        display_name: display_name.to_string(),
+        description: format!("{} (rewrites {})", display_name, source.content_column),
+        required_params: vec![
+            OperationParam {
+                name: "id".to_string(),
+                type_hint: TypeHint::String,
+                description: "ID of the tagged entity".to_string(),
+            },
+            OperationParam {
+                name: "tag".to_string(),
+                type_hint: TypeHint::String,
+                description: "Tag, with or without leading #".to_string(),
+            },
+        ],
+        affected_fields: vec![],
+        param_mappings: vec![],
+        precondition: None,
+        simulation: None,
+    }
+}
+
+#[async_trait]
+impl OperationProvider for TagIndex {
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        self.sources
+            .iter()
+            .flat_map(|source| {
+                [
+                    tag_descriptor(source, "add_tag", "Add tag"),
+                    tag_descriptor(source, "remove_tag", "Remove tag"),
+                ]
+            })
+            .collect()
+    }
+
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        params: StorageEntity,
+    ) -> Result<UndoAction> {
+        let id = params
+            .get("id")
+            .and_then(|v| v.as_string())
+            .ok_or_else(|| "Missing 'id' parameter".to_string())?;
+        let tag = params
+            .get("tag")
+            .and_then(|v| v.as_string())
+            .ok_or_else(|| "Missing 'tag' parameter".to_string())?;
+
+        match op_name {
+            "add_tag" => self.add_tag(entity_name, id, tag).await,
+            "remove_tag" => self.remove_tag(entity_name, id, tag).await,
+            _ => Err(format!("Unknown tag operation: {}", op_name).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(
+            parse_tags("# Heading with #Work and (#home/garden), not a#b or ##x"),
+            vec!["home/garden".to_string(), "work".to_string()]
+        );
+        assert_eq!(
+            parse_tags("Write report   :work:urgent:\nbody #Work"),
+            vec!["urgent".to_string(), "work".to_string()]
+        );
+        assert_eq!(
+            parse_tag_list("Work, errands,,"),
+            vec!["errands".to_string(), "work".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_rewrite_content() {
+        assert_eq!(
+            add_tag_to_content("Buy milk ", "errands").as_deref(),
+            Some("Buy milk #errands")
+        );
+        assert_eq!(add_tag_to_content("Buy milk #Errands", "errands"), None);
+
+        assert_eq!(
+            remove_tag_from_content("Buy #errands milk #errands", "errands").as_deref(),
+            Some("Buy milk")
+        );
+        assert_eq!(
+            remove_tag_from_content("Report :work:urgent:", "work").as_deref(),
+            Some("Report :urgent:")
+        );
+        assert_eq!(
+            remove_tag_from_content("Report :work:", "work").as_deref(),
+            Some("Report")
+        );
+        assert_eq!(remove_tag_from_content("No tags", "work"), None);
+    }
+}
@@ -0,0 +1,186 @@
+//! Escalation policies for deadline reminders
+//!
+//! An `EscalationPolicy` is an ordered list of steps relative to a deadline.
+//! Each step is a "stage" of the reminder; a reminder advances one stage every
+//! time it fires. The final `OverdueEvery` step repeats indefinitely.
+
+use serde::{Deserialize, Serialize};
+
+const MINUTE_MS: i64 = 60 * 1000;
+const HOUR_MS: i64 = 60 * MINUTE_MS;
+const DAY_MS: i64 = 24 * HOUR_MS;
+
+/// A single step in an escalation policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EscalationStep {
+    /// Fire once, `offset_ms` before the deadline
+    BeforeDue { offset_ms: i64 },
+    /// Fire at the deadline and then every `interval_ms` while overdue
+    OverdueEvery { interval_ms: i64 },
+}
+
+/// Ordered escalation steps for a deadline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EscalationPolicy {
+    steps: Vec<EscalationStep>,
+}
+
+impl EscalationPolicy {
+    /// Create a policy from explicit steps
+    ///
+    /// `BeforeDue` steps are sorted so the earliest reminder comes first.
+    /// Only the first `OverdueEvery` step is kept and always placed last.
+    pub fn new(steps: Vec<EscalationStep>) -> Self {
+        let mut before: Vec<i64> = steps
+            .iter()
+            .filter_map(|s| match s {
+                EscalationStep::BeforeDue { offset_ms } => Some(*offset_ms),
+                _ => None,
+            })
+            .collect();
+        before.sort_by(|a, b| b.cmp(a));
+        before.dedup();
+
+        let overdue = steps.iter().find_map(|s| match s {
+            EscalationStep::OverdueEvery { interval_ms } if *interval_ms > 0 => {
+                Some(EscalationStep::OverdueEvery {
+                    interval_ms: *interval_ms,
+                })
+            }
+            _ => None,
+        });
+
+        let mut steps: Vec<EscalationStep> = before
+            .into_iter()
+            .map(|offset_ms| EscalationStep::BeforeDue { offset_ms })
+            .collect();
+        steps.extend(overdue);
+        Self { steps }
+    }
+
+    /// Default deadline policy: T-1 day, T-1 hour, then daily while overdue
+    pub fn deadline_default() -> Self {
+        Self::new(vec![
+            EscalationStep::BeforeDue { offset_ms: DAY_MS },
+            EscalationStep::BeforeDue { offset_ms: HOUR_MS },
+            EscalationStep::OverdueEvery {
+                interval_ms: DAY_MS,
+            },
+        ])
+    }
+
+    pub fn steps(&self) -> &[EscalationStep] {
+        &self.steps
+    }
+
+    /// When the given stage fires for a deadline, or None if the policy is exhausted
+    pub fn fire_time(&self, due_at: i64, stage: i64) -> Option<i64> {
+        if stage < 0 {
+            return None;
+        }
+        let stage = stage as usize;
+        if let Some(step) = self.steps.get(stage) {
+            return Some(match step {
+                EscalationStep::BeforeDue { offset_ms } => due_at - offset_ms,
+                EscalationStep::OverdueEvery { .. } => due_at,
+            });
+        }
+        // Past the end: repeat the overdue step if there is one
+        match self.steps.last() {
+            Some(EscalationStep::OverdueEvery { interval_ms }) => {
+                let repeats = (stage - (self.steps.len() - 1)) as i64;
+                Some(due_at + repeats * interval_ms)
+            }
+            _ => None,
+        }
+    }
+
+    /// First stage that fires strictly after `now`
+    pub fn next_stage_after(&self, due_at: i64, now: i64) -> i64 {
+        let mut stage = 0;
+        while let Some(time) = self.fire_time(due_at, stage) {
+            if time > now {
+                return stage;
+            }
+            // Jump directly to the right repetition for long-overdue deadlines
+            if let Some(EscalationStep::OverdueEvery { interval_ms }) = self.steps.last() {
+                let overdue_stage = (self.steps.len() - 1) as i64;
+                if stage >= overdue_stage {
+                    let elapsed = now - due_at;
+                    return overdue_stage + elapsed / interval_ms + 1;
+                }
+            }
+            stage += 1;
+        }
+        stage
+    }
+
+    /// Stage a newly tracked deadline should start at
+    ///
+    /// If some stages already passed, the most recent one fires immediately
+    /// instead of replaying every missed reminder.
+    pub fn initial_stage(&self, due_at: i64, now: i64) -> i64 {
+        (self.next_stage_after(due_at, now) - 1).max(0)
+    }
+}
+
+impl Default for EscalationPolicy {
+    fn default() -> Self {
+        Self::deadline_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy_fire_times() {
+        let policy = EscalationPolicy::deadline_default();
+        let due = 10 * DAY_MS;
+
+        assert_eq!(policy.fire_time(due, 0), Some(due - DAY_MS));
+        assert_eq!(policy.fire_time(due, 1), Some(due - HOUR_MS));
+        assert_eq!(policy.fire_time(due, 2), Some(due));
+        assert_eq!(policy.fire_time(due, 3), Some(due + DAY_MS));
+        assert_eq!(policy.fire_time(due, 5), Some(due + 3 * DAY_MS));
+    }
+
+    #[test]
+    fn test_steps_are_normalized() {
+        let policy = EscalationPolicy::new(vec![
+            EscalationStep::OverdueEvery {
+                interval_ms: DAY_MS,
+            },
+            EscalationStep::BeforeDue { offset_ms: HOUR_MS },
+            EscalationStep::BeforeDue { offset_ms: DAY_MS },
+        ]);
+        assert_eq!(policy, EscalationPolicy::deadline_default());
+    }
+
+    #[test]
+    fn test_policy_without_overdue_is_exhausted() {
+        let policy = EscalationPolicy::new(vec![EscalationStep::BeforeDue { offset_ms: HOUR_MS }]);
+        assert_eq!(policy.fire_time(DAY_MS, 1), None);
+        assert_eq!(policy.next_stage_after(DAY_MS, DAY_MS), 1);
+    }
+
+    #[test]
+    fn test_next_and_initial_stage() {
+        let policy = EscalationPolicy::deadline_default();
+        let due = 10 * DAY_MS;
+
+        // Well before the deadline: nothing has fired yet
+        assert_eq!(policy.next_stage_after(due, due - 2 * DAY_MS), 0);
+        assert_eq!(policy.initial_stage(due, due - 2 * DAY_MS), 0);
+
+        // Two hours before: T-1 day passed, T-1 hour is next
+        assert_eq!(policy.next_stage_after(due, due - 2 * HOUR_MS), 1);
+        assert_eq!(policy.initial_stage(due, due - 2 * HOUR_MS), 0);
+
+        // Three and a half days overdue: skip to the next daily repetition
+        let now = due + 3 * DAY_MS + 12 * HOUR_MS;
+        let next = policy.next_stage_after(due, now);
+        assert_eq!(policy.fire_time(due, next), Some(due + 4 * DAY_MS));
+    }
+}
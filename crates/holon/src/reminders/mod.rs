@@ -0,0 +1,16 @@
+//! Deadline reminders
+//!
+//! - `escalation`: escalation policies (T-1 day, T-1 hour, overdue daily, ...)
+//! - `store`: persistent reminder state with snooze/dismiss operations
+//! - `sources`: tables whose due dates become reminders
+//! - `scheduler`: fires due reminders as notifications
+
+pub mod escalation;
+pub mod scheduler;
+pub mod sources;
+pub mod store;
+
+pub use escalation::{EscalationPolicy, EscalationStep};
+pub use scheduler::{ReminderConfig, ReminderScheduler};
+pub use sources::{DeadlineSource, default_deadline_sources};
+pub use store::{REMINDERS_ENTITY, Reminder, ReminderStore};
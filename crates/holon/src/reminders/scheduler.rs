@@ -0,0 +1,314 @@
+//! Firing reminders on time
+//!
+//! `ReminderScheduler` refreshes the deadline reminders from their sources and
+//! raises a `Notification` through every attached `NotificationSink` when a
+//! reminder is due. It sleeps until the next reminder, but never longer than
+//! `ReminderConfig::check_interval`: timers don't advance while the device is
+//! suspended, so checking the wall clock regularly catches up on reminders that
+//! came due during sleep. A reminder that missed several escalation stages fires
+//! once and continues with the next future stage.
+
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::Duration;
+
+use tracing::{debug, warn};
+
+use super::escalation::EscalationPolicy;
+use super::sources::{DeadlineSource, default_deadline_sources};
+use super::store::{REMINDERS_ENTITY, Reminder, ReminderStore};
+use crate::core::datasource::Result;
+use crate::core::notifications::{Notification, NotificationSeverity, NotificationSink};
+
+const MINUTE_MS: i64 = 60 * 1000;
+const HOUR_MS: i64 = 60 * MINUTE_MS;
+
+/// Configuration of deadline reminders
+#[derive(Debug, Clone)]
+pub struct ReminderConfig {
+    pub policy: EscalationPolicy,
+    pub sources: Vec<DeadlineSource>,
+    /// Longest time between two checks for due reminders
+    pub check_interval: Duration,
+}
+
+impl Default for ReminderConfig {
+    fn default() -> Self {
+        Self {
+            policy: EscalationPolicy::default(),
+            sources: default_deadline_sources(),
+            check_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl ReminderConfig {
+    pub fn with_policy(mut self, policy: EscalationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_source(mut self, source: DeadlineSource) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    pub fn with_check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+}
+
+/// Fires due reminders as notifications
+pub struct ReminderScheduler {
+    store: Arc<ReminderStore>,
+    config: ReminderConfig,
+    sinks: StdRwLock<Vec<Arc<dyn NotificationSink>>>,
+}
+
+impl ReminderScheduler {
+    pub fn new(store: Arc<ReminderStore>, config: ReminderConfig) -> Self {
+        Self {
+            store,
+            config,
+            sinks: StdRwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn store(&self) -> &Arc<ReminderStore> {
+        &self.store
+    }
+
+    /// Add a sink that receives reminder notifications
+    pub fn add_notification_sink(&self, sink: Arc<dyn NotificationSink>) {
+        self.sinks.write().unwrap().push(sink);
+    }
+
+    /// Update the deadline reminders from all sources
+    pub async fn refresh_deadlines(&self) {
+        for source in &self.config.sources {
+            if let Err(e) = self.store.refresh_deadlines(source).await {
+                // Usually the source's datasource isn't configured
+                debug!("[Reminders] Skipping {}: {}", source.table, e);
+            }
+        }
+    }
+
+    /// Notify about all reminders due at `now` and advance them
+    pub async fn fire_due(&self, now: i64) -> Result<Vec<Reminder>> {
+        let mut fired = Vec::new();
+        for reminder in self.store.due_reminders(now).await? {
+            self.notify(&reminder, now);
+            if let Some(reminder) = self.store.mark_fired(&reminder.id, now).await? {
+                fired.push(reminder);
+            }
+        }
+        Ok(fired)
+    }
+
+    /// Refresh deadlines and fire reminders until the scheduler is dropped
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let scheduler = Arc::downgrade(&self);
+        drop(self);
+        tokio::spawn(async move {
+            loop {
+                let Some(this) = scheduler.upgrade() else {
+                    break;
+                };
+                this.refresh_deadlines().await;
+                let now = chrono::Utc::now().timestamp_millis();
+                if let Err(e) = this.fire_due(now).await {
+                    warn!("[Reminders] Failed to fire reminders: {}", e);
+                }
+                let delay = this.next_delay(now).await;
+                drop(this);
+                tokio::time::sleep(delay).await;
+            }
+        })
+    }
+
+    /// Time until the next reminder, at most `check_interval`
+    async fn next_delay(&self, now: i64) -> Duration {
+        let max = self.config.check_interval;
+        match self.store.next_fire_at().await {
+            // At least a second, so a reminder that fails to advance doesn't spin
+            Ok(Some(at)) => max.min(Duration::from_millis((at - now).max(1000) as u64)),
+            _ => max,
+        }
+    }
+
+    fn notify(&self, reminder: &Reminder, now: i64) {
+        let (severity, body) = if reminder.explicit {
+            (NotificationSeverity::Info, "Reminder".to_string())
+        } else if reminder.is_overdue(now) {
+            (
+                NotificationSeverity::Warning,
+                format!("Overdue by {}", describe_duration(now - reminder.due_at)),
+            )
+        } else {
+            (
+                NotificationSeverity::Info,
+                format!("Due in {}", describe_duration(reminder.due_at - now)),
+            )
+        };
+        // Linked to the reminder (not its entity) so it can be snoozed or dismissed
+        let notification = Notification::new(reminder.title.clone(), body, severity)
+            .with_entity(REMINDERS_ENTITY, reminder.id.clone());
+        for sink in self.sinks.read().unwrap().iter() {
+            sink.notify(notification.clone());
+        }
+        debug!(
+            "[Reminders] Fired {} at stage {}",
+            reminder.id, reminder.stage
+        );
+    }
+}
+
+/// Rough human-readable length of `ms`, e.g. "2 days" or "45 minutes"
+fn describe_duration(ms: i64) -> String {
+    let (count, unit) = if ms >= 24 * HOUR_MS {
+        (ms / (24 * HOUR_MS), "day")
+    } else if ms >= HOUR_MS {
+        (ms / HOUR_MS, "hour")
+    } else {
+        ((ms / MINUTE_MS).max(1), "minute")
+    };
+    format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+    use crate::storage::turso::TursoBackend;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use tokio::sync::RwLock;
+
+    #[derive(Default)]
+    struct CollectingSink {
+        notifications: Mutex<Vec<Notification>>,
+    }
+
+    impl NotificationSink for CollectingSink {
+        fn notify(&self, notification: Notification) {
+            self.notifications.lock().unwrap().push(notification);
+        }
+    }
+
+    type Backend = Arc<RwLock<TursoBackend>>;
+
+    async fn create_scheduler() -> (Arc<ReminderScheduler>, Arc<CollectingSink>, Backend) {
+        let backend = memory_backend().await;
+        backend
+            .read()
+            .await
+            .execute_sql(
+                "CREATE TABLE todoist_tasks (id TEXT PRIMARY KEY, content TEXT, due_date TEXT, completed INTEGER)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        let store = Arc::new(ReminderStore::new(backend.clone()));
+        store.initialize_schema().await.unwrap();
+
+        let config = ReminderConfig::default();
+        let scheduler = Arc::new(ReminderScheduler::new(store, config));
+        let sink = Arc::new(CollectingSink::default());
+        scheduler.add_notification_sink(sink.clone());
+        (scheduler, sink, backend)
+    }
+
+    async fn add_task(backend: &Backend, id: &str, due_date: &str, completed: bool) {
+        backend
+            .read()
+            .await
+            .execute_sql(
+                "INSERT OR REPLACE INTO todoist_tasks (id, content, due_date, completed) VALUES ($id, $content, $due, $completed)",
+                HashMap::from([
+                    ("id".to_string(), holon_api::Value::String(id.to_string())),
+                    (
+                        "content".to_string(),
+                        holon_api::Value::String(format!("Task {}", id)),
+                    ),
+                    (
+                        "due".to_string(),
+                        holon_api::Value::String(due_date.to_string()),
+                    ),
+                    ("completed".to_string(), holon_api::Value::Boolean(completed)),
+                ]),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_deadlines_are_derived_from_due_dates() {
+        let (scheduler, _, backend) = create_scheduler().await;
+        let store = scheduler.store();
+        add_task(&backend, "t1", "2030-01-01", false).await;
+        add_task(&backend, "t2", "2030-01-01", true).await;
+
+        scheduler.refresh_deadlines().await;
+        assert!(store.get("todoist_tasks:t1").await.unwrap().is_some());
+        assert!(store.get("todoist_tasks:t2").await.unwrap().is_none());
+
+        // Completing the task removes its reminder, explicit reminders stay
+        store
+            .add_reminder("todoist_tasks", "t1", "Call back", 0)
+            .await
+            .unwrap();
+        add_task(&backend, "t1", "2030-01-01", true).await;
+        scheduler.refresh_deadlines().await;
+        assert!(store.get("todoist_tasks:t1").await.unwrap().is_none());
+        assert!(store.get("todoist_tasks:t1@0").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_missed_reminders_fire_once_after_sleep() {
+        let (scheduler, sink, _) = create_scheduler().await;
+        let store = scheduler.store();
+        let now = chrono::Utc::now().timestamp_millis();
+
+        // Both the T-1 day and T-1 hour stages passed while asleep
+        let reminder = store
+            .upsert_deadline("todoist_tasks", "t1", "File taxes", now - 10 * MINUTE_MS)
+            .await
+            .unwrap();
+        store
+            .add_reminder("todoist_tasks", "t2", "Call back", now - MINUTE_MS)
+            .await
+            .unwrap();
+
+        let fired = scheduler.fire_due(now).await.unwrap();
+        assert_eq!(fired.len(), 2);
+        let notifications = sink.notifications.lock().unwrap().clone();
+        assert_eq!(notifications.len(), 2);
+        let overdue = notifications
+            .iter()
+            .find(|n| n.title == "File taxes")
+            .unwrap();
+        assert_eq!(overdue.severity, NotificationSeverity::Warning);
+        assert_eq!(overdue.body, "Overdue by 10 minutes");
+        assert_eq!(
+            overdue.entity,
+            Some((REMINDERS_ENTITY.to_string(), reminder.id.clone()))
+        );
+
+        // Nothing fires again right away; the explicit reminder never again
+        assert!(scheduler.fire_due(now).await.unwrap().is_empty());
+        let next = store.next_fire_at().await.unwrap().unwrap();
+        assert!(next > now);
+        assert_eq!(
+            store.get(&reminder.id).await.unwrap().unwrap().next_fire_at,
+            next
+        );
+    }
+
+    #[test]
+    fn test_describe_duration() {
+        assert_eq!(describe_duration(30 * 1000), "1 minute");
+        assert_eq!(describe_duration(90 * MINUTE_MS), "1 hour");
+        assert_eq!(describe_duration(50 * HOUR_MS), "2 days");
+    }
+}
@@ -0,0 +1,130 @@
+//! Where deadline reminders come from
+//!
+//! A `DeadlineSource` names a table whose rows have a due date. The reminder
+//! scheduler periodically reads all sources and keeps one reminder per row with
+//! a due date (`ReminderStore::refresh_deadlines`). Sources whose table does not
+//! exist (e.g. Todoist isn't configured) are skipped.
+
+use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc};
+use holon_api::Value;
+
+/// A table with due dates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadlineSource {
+    /// Entity name reminders refer to (for navigation and operations)
+    pub entity_name: String,
+    pub table: String,
+    pub id_column: String,
+    /// Column shown as the reminder's title
+    pub title_column: String,
+    /// Unix ms, RFC 3339 or `YYYY-MM-DD` (midnight UTC)
+    pub due_column: String,
+    /// SQL condition rows must meet to be reminded of, e.g. `completed = 0`
+    pub filter: Option<String>,
+}
+
+impl DeadlineSource {
+    /// Source for `entity_name` stored in the table of the same name
+    pub fn new(entity_name: &str, title_column: &str, due_column: &str) -> Self {
+        Self {
+            entity_name: entity_name.to_string(),
+            table: entity_name.to_string(),
+            id_column: "id".to_string(),
+            title_column: title_column.to_string(),
+            due_column: due_column.to_string(),
+            filter: None,
+        }
+    }
+
+    pub fn with_table(mut self, table: &str) -> Self {
+        self.table = table.to_string();
+        self
+    }
+
+    pub fn with_id_column(mut self, id_column: &str) -> Self {
+        self.id_column = id_column.to_string();
+        self
+    }
+
+    pub fn with_filter(mut self, filter: &str) -> Self {
+        self.filter = Some(filter.to_string());
+        self
+    }
+
+    /// Query returning `id`, `title` and `due` of the rows with a due date
+    pub fn select_sql(&self) -> String {
+        let mut sql = format!(
+            "SELECT CAST({} AS TEXT) AS id, {} AS title, {} AS due FROM {} WHERE {} IS NOT NULL",
+            self.id_column, self.title_column, self.due_column, self.table, self.due_column
+        );
+        if let Some(filter) = &self.filter {
+            sql.push_str(&format!(" AND ({})", filter));
+        }
+        sql
+    }
+}
+
+/// Due dates of the built-in datasources
+pub fn default_deadline_sources() -> Vec<DeadlineSource> {
+    vec![
+        DeadlineSource::new("todoist_tasks", "content", "due_date").with_filter("completed = 0"),
+        DeadlineSource::new("org_headlines", "title", "deadline_at")
+            .with_filter("todo_keyword IS NULL OR todo_keyword NOT IN ('DONE', 'CANCELLED')"),
+    ]
+}
+
+/// Parse a due date column value into Unix milliseconds
+pub fn parse_due(value: &Value) -> Option<i64> {
+    match value {
+        Value::Integer(ms) => Some(*ms),
+        Value::String(s) | Value::DateTime(s) => {
+            let s = s.trim();
+            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
+                return Some(dt.timestamp_millis());
+            }
+            if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
+                return Some(Utc.from_utc_datetime(&dt).timestamp_millis());
+            }
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|dt| Utc.from_utc_datetime(&dt).timestamp_millis())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_due() {
+        assert_eq!(parse_due(&Value::Integer(42)), Some(42));
+        assert_eq!(
+            parse_due(&Value::String("1970-01-02".to_string())),
+            Some(86_400_000)
+        );
+        assert_eq!(
+            parse_due(&Value::String("1970-01-01T00:00:01Z".to_string())),
+            Some(1000)
+        );
+        assert_eq!(
+            parse_due(&Value::String("1970-01-01T01:00:00".to_string())),
+            Some(3_600_000)
+        );
+        assert_eq!(parse_due(&Value::String("someday".to_string())), None);
+        assert_eq!(parse_due(&Value::Null), None);
+    }
+
+    #[test]
+    fn test_select_sql() {
+        let source = DeadlineSource::new("todoist_tasks", "content", "due_date")
+            .with_filter("completed = 0");
+        assert_eq!(
+            source.select_sql(),
+            "SELECT CAST(id AS TEXT) AS id, content AS title, due_date AS due \
+             FROM todoist_tasks WHERE due_date IS NOT NULL AND (completed = 0)"
+        );
+    }
+}
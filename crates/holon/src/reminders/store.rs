@@ -0,0 +1,765 @@
+//! Persistent reminder state
+//!
+//! `ReminderStore` keeps one `Reminder` row per tracked deadline in the `reminders`
+//! table, plus explicit reminders the user set for a point in time. Deadlines are
+//! derived from the due dates of other entities (see `DeadlineSource`); explicit
+//! reminders fire once. Escalation stage, snooze and dismiss state live in storage
+//! (not in any provider), so they survive restarts and sync between devices along
+//! with the rest of the database.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use super::escalation::EscalationPolicy;
+use super::sources::{DeadlineSource, parse_due};
+use crate::core::datasource::{OperationProvider, Result, UndoAction};
+use crate::storage::turso::TursoBackend;
+use crate::storage::types::StorageEntity;
+use holon_api::{
+    DynamicEntity, HasSchema, Operation, OperationDescriptor, OperationParam, TypeHint, Value,
+};
+
+/// Entity name used for reminder operations
+pub const REMINDERS_ENTITY: &str = "reminders";
+
+/// A reminder for a single deadline
+///
+/// Table name: `reminders`
+#[derive(Debug, Clone, Serialize, Deserialize, Entity)]
+#[entity(name = "reminders", short_name = "reminder")]
+pub struct Reminder {
+    /// Primary key: `"{entity_name}:{entity_id}"`
+    #[primary_key]
+    pub id: String,
+    /// Entity the deadline belongs to (e.g., "todoist_tasks")
+    pub entity_name: String,
+    /// ID of the entity within its entity type
+    pub entity_id: String,
+    /// Human-readable title shown in the notification
+    pub title: String,
+    /// Deadline (Unix timestamp in milliseconds)
+    pub due_at: i64,
+    /// Current escalation stage (index into the escalation policy)
+    pub stage: i64,
+    /// When the reminder fires next, taking snooze into account (Unix ms)
+    #[indexed]
+    pub next_fire_at: i64,
+    /// If set, the reminder is snoozed until this time (Unix ms)
+    pub snoozed_until: Option<i64>,
+    /// Dismissed reminders never fire again until their deadline changes
+    pub dismissed: bool,
+    /// When the reminder last fired (Unix ms)
+    pub last_fired_at: Option<i64>,
+    /// Set by the user for `due_at` (fires once) rather than derived from a deadline
+    pub explicit: bool,
+}
+
+impl Reminder {
+    /// Build the primary key for an entity's reminder
+    pub fn key(entity_name: &str, entity_id: &str) -> String {
+        format!("{}:{}", entity_name, entity_id)
+    }
+
+    /// Build the primary key of an explicit reminder at `remind_at`
+    pub fn explicit_key(entity_name: &str, entity_id: &str, remind_at: i64) -> String {
+        format!("{}:{}@{}", entity_name, entity_id, remind_at)
+    }
+
+    /// Whether the deadline has passed at `now`
+    pub fn is_overdue(&self, now: i64) -> bool {
+        !self.explicit && self.due_at <= now
+    }
+}
+
+/// Storage and operations for deadline reminders
+pub struct ReminderStore {
+    backend: Arc<RwLock<TursoBackend>>,
+    policy: EscalationPolicy,
+}
+
+impl ReminderStore {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self::with_policy(backend, EscalationPolicy::default())
+    }
+
+    pub fn with_policy(backend: Arc<RwLock<TursoBackend>>, policy: EscalationPolicy) -> Self {
+        Self { backend, policy }
+    }
+
+    pub fn policy(&self) -> &EscalationPolicy {
+        &self.policy
+    }
+
+    /// Initialize the reminders table schema.
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = Reminder::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create reminders table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        // Tables created before explicit reminders existed lack the column
+        let rows = backend
+            .execute_sql(
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'reminders'",
+                HashMap::new(),
+            )
+            .await
+            .map_err(|e| format!("Failed to read reminders schema: {}", e))?;
+        let has_explicit = rows
+            .first()
+            .and_then(|row| row.get("sql"))
+            .and_then(|sql| sql.as_string())
+            .is_some_and(|sql| sql.contains("explicit"));
+        if !has_explicit {
+            backend
+                .execute_sql(
+                    "ALTER TABLE reminders ADD COLUMN explicit INTEGER NOT NULL DEFAULT 0",
+                    HashMap::new(),
+                )
+                .await
+                .map_err(|e| format!("Failed to add explicit column: {}", e))?;
+        }
+
+        info!("Reminders schema initialized");
+        Ok(())
+    }
+
+    /// Start tracking (or update) the deadline of an entity.
+    ///
+    /// If the deadline moved, escalation restarts and snooze/dismiss are reset.
+    pub async fn upsert_deadline(
+        &self,
+        entity_name: &str,
+        entity_id: &str,
+        title: &str,
+        due_at: i64,
+    ) -> Result<Reminder> {
+        let id = Reminder::key(entity_name, entity_id);
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let reminder = match self.get(&id).await? {
+            Some(mut existing) if existing.due_at == due_at => {
+                existing.title = title.to_string();
+                existing
+            }
+            _ => {
+                let stage = self.policy.initial_stage(due_at, now);
+                let mut reminder = Reminder {
+                    id,
+                    entity_name: entity_name.to_string(),
+                    entity_id: entity_id.to_string(),
+                    title: title.to_string(),
+                    due_at,
+                    stage,
+                    next_fire_at: 0,
+                    snoozed_until: None,
+                    dismissed: false,
+                    last_fired_at: None,
+                    explicit: false,
+                };
+                self.refresh_next_fire(&mut reminder);
+                reminder
+            }
+        };
+
+        self.save(&reminder).await?;
+        Ok(reminder)
+    }
+
+    /// Remind about an entity once, at `remind_at`
+    pub async fn add_reminder(
+        &self,
+        entity_name: &str,
+        entity_id: &str,
+        title: &str,
+        remind_at: i64,
+    ) -> Result<Reminder> {
+        let mut reminder = Reminder {
+            id: Reminder::explicit_key(entity_name, entity_id, remind_at),
+            entity_name: entity_name.to_string(),
+            entity_id: entity_id.to_string(),
+            title: title.to_string(),
+            due_at: remind_at,
+            stage: 0,
+            next_fire_at: 0,
+            snoozed_until: None,
+            dismissed: false,
+            last_fired_at: None,
+            explicit: true,
+        };
+        self.refresh_next_fire(&mut reminder);
+        self.save(&reminder).await?;
+        Ok(reminder)
+    }
+
+    /// Delete a reminder, returning it
+    pub async fn delete(&self, id: &str) -> Result<Option<Reminder>> {
+        let Some(reminder) = self.get(id).await? else {
+            return Ok(None);
+        };
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "DELETE FROM reminders WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+            )
+            .await
+            .map_err(|e| format!("Failed to delete reminder: {}", e))?;
+        Ok(Some(reminder))
+    }
+
+    /// Track the due dates of `source`'s entities
+    ///
+    /// Entities that gained or moved a due date get a (restarted) reminder;
+    /// deadline reminders of entities that no longer have one (or are done) are
+    /// removed. Explicit reminders are left alone. Returns the number of
+    /// entities with a deadline.
+    pub async fn refresh_deadlines(&self, source: &DeadlineSource) -> Result<usize> {
+        let rows = {
+            let backend = self.backend.read().await;
+            backend
+                .execute_sql(&source.select_sql(), HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to read due dates of {}: {}", source.table, e))?
+        };
+
+        let mut tracked = std::collections::HashSet::new();
+        for row in rows {
+            let (Some(entity_id), Some(due_at)) = (
+                row.get("id").and_then(Value::as_string_owned),
+                row.get("due").and_then(parse_due),
+            ) else {
+                continue;
+            };
+            let title = row
+                .get("title")
+                .and_then(Value::as_string_owned)
+                .unwrap_or_else(|| entity_id.clone());
+            let reminder = self
+                .upsert_deadline(&source.entity_name, &entity_id, &title, due_at)
+                .await?;
+            tracked.insert(reminder.id);
+        }
+
+        let stale: Vec<Reminder> = self
+            .query(
+                "SELECT * FROM reminders WHERE entity_name = $entity_name AND explicit = 0",
+                HashMap::from([(
+                    "entity_name".to_string(),
+                    Value::String(source.entity_name.clone()),
+                )]),
+            )
+            .await?
+            .into_iter()
+            .filter(|reminder| !tracked.contains(&reminder.id))
+            .collect();
+        for reminder in stale {
+            self.remove(&reminder.entity_name, &reminder.entity_id)
+                .await?;
+        }
+        Ok(tracked.len())
+    }
+
+    /// Stop tracking an entity's deadline (e.g., when it is completed or deleted)
+    pub async fn remove(&self, entity_name: &str, entity_id: &str) -> Result<()> {
+        let mut params = HashMap::new();
+        params.insert(
+            "id".to_string(),
+            Value::String(Reminder::key(entity_name, entity_id)),
+        );
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql("DELETE FROM reminders WHERE id = $id", params)
+            .await
+            .map_err(|e| format!("Failed to remove reminder: {}", e))?;
+        Ok(())
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<Reminder>> {
+        let mut params = HashMap::new();
+        params.insert("id".to_string(), Value::String(id.to_string()));
+        Ok(self
+            .query("SELECT * FROM reminders WHERE id = $id", params)
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// Reminders that should fire at `now`
+    pub async fn due_reminders(&self, now: i64) -> Result<Vec<Reminder>> {
+        let mut params = HashMap::new();
+        params.insert("now".to_string(), Value::Integer(now));
+        self.query(
+            "SELECT * FROM reminders WHERE dismissed = 0 AND next_fire_at <= $now ORDER BY next_fire_at ASC",
+            params,
+        )
+        .await
+    }
+
+    /// When the next reminder fires, if any
+    pub async fn next_fire_at(&self) -> Result<Option<i64>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT MIN(next_fire_at) AS next_fire_at FROM reminders WHERE dismissed = 0",
+                HashMap::new(),
+            )
+            .await
+            .map_err(|e| format!("Failed to query reminders: {}", e))?;
+        Ok(rows
+            .first()
+            .and_then(|row| row.get("next_fire_at"))
+            .and_then(|v| v.as_i64())
+            .filter(|at| *at != i64::MAX))
+    }
+
+    /// Record that a reminder fired and advance it to the next escalation stage
+    ///
+    /// Stages whose time passed while the reminder could not fire (e.g. while
+    /// the device was asleep) are skipped.
+    pub async fn mark_fired(&self, id: &str, now: i64) -> Result<Option<Reminder>> {
+        let Some(mut reminder) = self.get(id).await? else {
+            return Ok(None);
+        };
+        reminder.stage = if reminder.explicit {
+            1
+        } else {
+            self.policy.next_stage_after(reminder.due_at, now)
+        };
+        reminder.snoozed_until = None;
+        reminder.last_fired_at = Some(now);
+        self.refresh_next_fire(&mut reminder);
+        self.save(&reminder).await?;
+        Ok(Some(reminder))
+    }
+
+    /// Snooze a reminder until the given time (None clears the snooze).
+    ///
+    /// Returns the previous snooze value.
+    pub async fn set_snoozed_until(
+        &self,
+        id: &str,
+        snoozed_until: Option<i64>,
+    ) -> Result<Option<i64>> {
+        let mut reminder = self.require(id).await?;
+        let previous = reminder.snoozed_until;
+        reminder.snoozed_until = snoozed_until;
+        self.refresh_next_fire(&mut reminder);
+        self.save(&reminder).await?;
+        debug!("Reminder {} snoozed until {:?}", id, snoozed_until);
+        Ok(previous)
+    }
+
+    /// Set the dismissed flag. Returns the previous value.
+    pub async fn set_dismissed(&self, id: &str, dismissed: bool) -> Result<bool> {
+        let mut reminder = self.require(id).await?;
+        let previous = reminder.dismissed;
+        reminder.dismissed = dismissed;
+        self.save(&reminder).await?;
+        Ok(previous)
+    }
+
+    fn refresh_next_fire(&self, reminder: &mut Reminder) {
+        reminder.next_fire_at = match reminder.snoozed_until {
+            Some(until) => until,
+            // Explicit reminders fire once
+            None if reminder.explicit => {
+                if reminder.stage == 0 {
+                    reminder.due_at
+                } else {
+                    i64::MAX
+                }
+            }
+            // An exhausted policy never fires again
+            None => self
+                .policy
+                .fire_time(reminder.due_at, reminder.stage)
+                .unwrap_or(i64::MAX),
+        };
+    }
+
+    async fn require(&self, id: &str) -> Result<Reminder> {
+        self.get(id)
+            .await?
+            .ok_or_else(|| format!("Reminder not found: {}", id).into())
+    }
+
+    async fn save(&self, reminder: &Reminder) -> Result<()> {
+        let sql = "INSERT INTO reminders
+                (id, entity_name, entity_id, title, due_at, stage, next_fire_at, snoozed_until, dismissed, last_fired_at, explicit)
+            VALUES ($id, $entity_name, $entity_id, $title, $due_at, $stage, $next_fire_at, $snoozed_until, $dismissed, $last_fired_at, $explicit)
+            ON CONFLICT(id) DO UPDATE SET
+                title = excluded.title,
+                due_at = excluded.due_at,
+                stage = excluded.stage,
+                next_fire_at = excluded.next_fire_at,
+                snoozed_until = excluded.snoozed_until,
+                dismissed = excluded.dismissed,
+                last_fired_at = excluded.last_fired_at";
+
+        let params: HashMap<String, Value> = reminder.to_entity().fields;
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to save reminder: {}", e))?;
+        Ok(())
+    }
+
+    async fn query(&self, sql: &str, params: HashMap<String, Value>) -> Result<Vec<Reminder>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to query reminders: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(REMINDERS_ENTITY);
+                entity.fields = row;
+                Reminder::from_entity(entity)
+            })
+            .collect()
+    }
+}
+
+fn reminder_operation(
+    name: &str,
+    display_name: &str,
+    description: &str,
+    extra_params: Vec<OperationParam>,
+) -> OperationDescriptor {
+    let mut required_params = vec![OperationParam {
+        name: "id".to_string(),
+        type_hint: TypeHint::String,
+        description: "Reminder ID".to_string(),
+    }];
+    required_params.extend(extra_params);
+    reminder_operation_with_params(name, display_name, description, required_params)
+}
+
+fn reminder_operation_with_params(
+    name: &str,
+    display_name: &str,
+    description: &str,
+    required_params: Vec<OperationParam>,
+) -> OperationDescriptor {
+    OperationDescriptor {
+        entity_name: REMINDERS_ENTITY.to_string(),
+        entity_short_name: "reminder".to_string(),
+        id_column: "id".to_string(),
+        name: name.to_string(),
+        display_name: display_name.to_string(),
+        description: description.to_string(),
+        required_params,
+        affected_fields: vec![],
+        param_mappings: vec![],
+        precondition: None,
+        simulation: None,
+    }
+}
+
+fn set_snoozed_until_op(id: &str, snoozed_until: Option<i64>) -> Operation {
+    Operation::new(
+        REMINDERS_ENTITY,
+        "set_snoozed_until",
+        "Change snooze",
+        HashMap::from([
+            ("id".to_string(), Value::String(id.to_string())),
+            (
+                "snoozed_until".to_string(),
+                snoozed_until.map(Value::Integer).unwrap_or(Value::Null),
+            ),
+        ]),
+    )
+}
+
+fn create_op(reminder: &Reminder) -> Operation {
+    Operation::new(
+        REMINDERS_ENTITY,
+        "create",
+        "Add reminder",
+        HashMap::from([
+            (
+                "entity_name".to_string(),
+                Value::String(reminder.entity_name.clone()),
+            ),
+            (
+                "entity_id".to_string(),
+                Value::String(reminder.entity_id.clone()),
+            ),
+            ("title".to_string(), Value::String(reminder.title.clone())),
+            ("remind_at".to_string(), Value::Integer(reminder.due_at)),
+        ]),
+    )
+}
+
+fn delete_op(id: &str) -> Operation {
+    Operation::new(
+        REMINDERS_ENTITY,
+        "delete",
+        "Delete reminder",
+        HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+    )
+}
+
+fn string_param(name: &str, description: &str) -> OperationParam {
+    OperationParam {
+        name: name.to_string(),
+        type_hint: TypeHint::String,
+        description: description.to_string(),
+    }
+}
+
+fn set_dismissed_op(id: &str, dismissed: bool) -> Operation {
+    let (op_name, display_name) = if dismissed {
+        ("dismiss", "Dismiss reminder")
+    } else {
+        ("undismiss", "Restore reminder")
+    };
+    Operation::new(
+        REMINDERS_ENTITY,
+        op_name,
+        display_name,
+        HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+    )
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationProvider for ReminderStore {
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        vec![
+            reminder_operation(
+                "snooze",
+                "Snooze",
+                "Snooze the reminder for a number of minutes",
+                vec![OperationParam {
+                    name: "minutes".to_string(),
+                    type_hint: TypeHint::Number,
+                    description: "Minutes to snooze for".to_string(),
+                }],
+            ),
+            reminder_operation(
+                "set_snoozed_until",
+                "Change snooze",
+                "Snooze the reminder until a timestamp (null clears the snooze)",
+                vec![OperationParam {
+                    name: "snoozed_until".to_string(),
+                    type_hint: TypeHint::Number,
+                    description: "Unix timestamp in milliseconds".to_string(),
+                }],
+            ),
+            reminder_operation(
+                "dismiss",
+                "Dismiss",
+                "Stop reminding about this deadline",
+                vec![],
+            ),
+            reminder_operation(
+                "undismiss",
+                "Restore reminder",
+                "Resume reminding about this deadline",
+                vec![],
+            ),
+            reminder_operation_with_params(
+                "create",
+                "Add reminder",
+                "Remind about an entity once, at a point in time",
+                vec![
+                    string_param("entity_name", "Entity type, e.g. todoist_tasks"),
+                    string_param("entity_id", "ID of the entity"),
+                    string_param("title", "Text of the notification"),
+                    OperationParam {
+                        name: "remind_at".to_string(),
+                        type_hint: TypeHint::Number,
+                        description: "Unix timestamp in milliseconds".to_string(),
+                    },
+                ],
+            ),
+            reminder_operation(
+                "delete",
+                "Delete reminder",
+                "Delete a reminder added with create",
+                vec![],
+            ),
+        ]
+    }
+
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        params: StorageEntity,
+    ) -> Result<UndoAction> {
+        if entity_name != REMINDERS_ENTITY {
+            return Err(format!(
+                "Expected entity_name '{}', got '{}'",
+                REMINDERS_ENTITY, entity_name
+            )
+            .into());
+        }
+
+        if op_name == "create" {
+            let string = |name: &str| {
+                params
+                    .get(name)
+                    .and_then(|v| v.as_string())
+                    .ok_or_else(|| format!("Missing '{}' parameter", name))
+            };
+            let remind_at = params
+                .get("remind_at")
+                .and_then(|v| v.as_i64())
+                .ok_or_else(|| "Missing 'remind_at' parameter".to_string())?;
+            let reminder = self
+                .add_reminder(
+                    string("entity_name")?,
+                    string("entity_id")?,
+                    string("title")?,
+                    remind_at,
+                )
+                .await?;
+            return Ok(UndoAction::Undo(delete_op(&reminder.id)));
+        }
+
+        let id = params
+            .get("id")
+            .and_then(|v| v.as_string())
+            .ok_or_else(|| "Missing 'id' parameter".to_string())?;
+
+        match op_name {
+            "snooze" => {
+                let minutes = params
+                    .get("minutes")
+                    .and_then(|v| v.as_i64())
+                    .ok_or_else(|| "Missing 'minutes' parameter".to_string())?;
+                let until = chrono::Utc::now().timestamp_millis() + minutes * 60 * 1000;
+                let previous = self.set_snoozed_until(id, Some(until)).await?;
+                Ok(UndoAction::Undo(set_snoozed_until_op(id, previous)))
+            }
+            "set_snoozed_until" => {
+                let until = params.get("snoozed_until").and_then(|v| v.as_i64());
+                let previous = self.set_snoozed_until(id, until).await?;
+                Ok(UndoAction::Undo(set_snoozed_until_op(id, previous)))
+            }
+            "dismiss" | "undismiss" => {
+                let previous = self.set_dismissed(id, op_name == "dismiss").await?;
+                Ok(UndoAction::Undo(set_dismissed_op(id, previous)))
+            }
+            "delete" => {
+                let reminder = self.require(id).await?;
+                if !reminder.explicit {
+                    return Err(
+                        format!("Reminder {} follows a due date; dismiss it instead", id).into(),
+                    );
+                }
+                self.delete(id).await?;
+                Ok(UndoAction::Undo(create_op(&reminder)))
+            }
+            _ => Err(format!("Unknown operation: {}", op_name).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    const HOUR_MS: i64 = 60 * 60 * 1000;
+
+    async fn create_store() -> ReminderStore {
+        let store = ReminderStore::new(memory_backend().await);
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        store
+    }
+
+    #[tokio::test]
+    async fn test_escalation_and_snooze_persist() {
+        let store = create_store().await;
+        let due = chrono::Utc::now().timestamp_millis() + 48 * HOUR_MS;
+
+        let reminder = store
+            .upsert_deadline("todoist_tasks", "t1", "File taxes", due)
+            .await
+            .unwrap();
+        assert_eq!(reminder.stage, 0);
+        assert_eq!(reminder.next_fire_at, due - 24 * HOUR_MS);
+
+        // T-1 day fires, next is T-1 hour
+        let fired = store
+            .mark_fired(&reminder.id, due - 24 * HOUR_MS)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fired.stage, 1);
+        assert_eq!(fired.next_fire_at, due - HOUR_MS);
+
+        // Snooze via operation, then undo restores the escalation schedule
+        let undo = store
+            .execute_operation(
+                REMINDERS_ENTITY,
+                "set_snoozed_until",
+                HashMap::from([
+                    ("id".to_string(), Value::String(reminder.id.clone())),
+                    ("snoozed_until".to_string(), Value::Integer(due + HOUR_MS)),
+                ]),
+            )
+            .await
+            .unwrap();
+        let snoozed = store.get(&reminder.id).await.unwrap().unwrap();
+        assert_eq!(snoozed.next_fire_at, due + HOUR_MS);
+
+        let UndoAction::Undo(inverse) = undo else {
+            panic!("snooze should be undoable");
+        };
+        store
+            .execute_operation(REMINDERS_ENTITY, &inverse.op_name, inverse.params)
+            .await
+            .unwrap();
+        let restored = store.get(&reminder.id).await.unwrap().unwrap();
+        assert_eq!(restored.snoozed_until, None);
+        assert_eq!(restored.next_fire_at, due - HOUR_MS);
+    }
+
+    #[tokio::test]
+    async fn test_dismissed_reminders_do_not_fire() {
+        let store = create_store().await;
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let reminder = store
+            .upsert_deadline("todoist_tasks", "t2", "Overdue", now - HOUR_MS)
+            .await
+            .unwrap();
+        assert_eq!(store.due_reminders(now).await.unwrap().len(), 1);
+
+        store.set_dismissed(&reminder.id, true).await.unwrap();
+        assert!(store.due_reminders(now).await.unwrap().is_empty());
+
+        // Moving the deadline resets dismissal
+        store
+            .upsert_deadline("todoist_tasks", "t2", "Overdue", now - 2 * HOUR_MS)
+            .await
+            .unwrap();
+        assert_eq!(store.due_reminders(now).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,238 @@
+//! Automatic archival of entities
+//!
+//! Tables registered for soft delete also carry a storage-managed `archived_at`
+//! column. The `archive` operation sets it, `unarchive` clears it, and compiled
+//! queries hide archived rows unless the query contains an `include_archived` step.
+//!
+//! `ArchivalRule`s archive rows automatically, e.g. completed tasks whose
+//! `completed_at` is older than 90 days. The archival job applies the rules of the
+//! `ArchivalConfig` and those contributed by providers through
+//! `ArchivalRuleProvider`, which is how the archive of a source (Todoist's
+//! `is_archived`, Org's `ARCHIVE` tag) is respected. Rules only archive: a row that
+//! still matches a rule after `unarchive` is archived again by the next run.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use tokio::sync::RwLock;
+
+use crate::storage::turso::TursoBackend;
+use holon_api::{ARCHIVED_AT_COLUMN, DELETED_AT_COLUMN};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Rows of a table that the archival job archives
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArchivalRule {
+    /// Table of the rows, which must be registered for soft delete
+    pub table: String,
+    /// SQL condition on the table's columns, e.g. `completed = 1`
+    pub condition: String,
+    /// Only archive rows whose column (an RFC 3339 timestamp) is older than the duration
+    pub older_than: Option<(String, Duration)>,
+}
+
+impl ArchivalRule {
+    pub fn new(table: impl Into<String>, condition: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            condition: condition.into(),
+            older_than: None,
+        }
+    }
+
+    pub fn older_than(mut self, column: impl Into<String>, age: Duration) -> Self {
+        self.older_than = Some((column.into(), age));
+        self
+    }
+
+    /// `UPDATE` archiving the live rows matching the rule
+    ///
+    /// Binds the archival time and, for rules with an age, the cutoff timestamp.
+    fn archive_sql(&self) -> String {
+        let mut sql = format!(
+            "UPDATE {table} SET {archived} = ? WHERE {archived} IS NULL AND {deleted} IS NULL AND ({condition})",
+            table = self.table,
+            archived = ARCHIVED_AT_COLUMN,
+            deleted = DELETED_AT_COLUMN,
+            condition = self.condition,
+        );
+        if let Some((column, _)) = &self.older_than {
+            sql.push_str(&format!(" AND {} < ?", column));
+        }
+        sql
+    }
+
+    fn params(&self, now: DateTime<Utc>) -> Vec<turso::Value> {
+        let mut params = vec![turso::Value::Integer(now.timestamp_millis())];
+        if let Some((_, age)) = &self.older_than {
+            let cutoff = now - chrono::Duration::milliseconds(age.as_millis() as i64);
+            params.push(turso::Value::Text(
+                cutoff.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ));
+        }
+        params
+    }
+}
+
+/// Archival rules contributed by a provider for the archive of its source
+///
+/// Registered as `dyn ArchivalRuleProvider` trait services; the engine collects
+/// the rules of all of them.
+pub trait ArchivalRuleProvider: Send + Sync {
+    fn archival_rules(&self) -> Vec<ArchivalRule>;
+}
+
+impl ArchivalRuleProvider for Vec<ArchivalRule> {
+    fn archival_rules(&self) -> Vec<ArchivalRule> {
+        self.clone()
+    }
+}
+
+/// Configuration of the archival job
+#[derive(Clone, Debug)]
+pub struct ArchivalConfig {
+    /// Rules applied by the job (besides those of providers)
+    pub rules: Vec<ArchivalRule>,
+    /// How often the archival job runs
+    pub interval: Duration,
+}
+
+impl Default for ArchivalConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            interval: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl ArchivalConfig {
+    pub fn with_rule(mut self, rule: ArchivalRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn with_rules(mut self, rules: impl IntoIterator<Item = ArchivalRule>) -> Self {
+        self.rules.extend(rules);
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+/// Archive the rows matching `rules` at `now`, returning the number of archived rows
+///
+/// A failing rule (e.g. of a table that doesn't exist yet) doesn't stop the others;
+/// its error is returned after all rules ran.
+pub async fn apply_archival_rules(
+    backend: &Arc<RwLock<TursoBackend>>,
+    rules: &[ArchivalRule],
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    let backend = backend.read().await;
+    let conn = backend
+        .get_connection()
+        .map_err(|e| format!("Failed to get connection: {}", e))?;
+
+    let mut archived = 0;
+    let mut errors = Vec::new();
+    for rule in rules {
+        match conn.execute(&rule.archive_sql(), rule.params(now)).await {
+            Ok(count) => archived += count as usize,
+            Err(e) => errors.push(format!("{} ({}): {}", rule.table, rule.condition, e)),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(format!("Failed to apply archival rules: {}", errors.join("; ")).into());
+    }
+    Ok(archived)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_rules_archive_matching_rows() {
+        let backend = memory_backend().await;
+        backend
+            .read()
+            .await
+            .execute_sql(
+                "CREATE TABLE tasks (id TEXT PRIMARY KEY, completed INTEGER, completed_at TEXT)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+            .read()
+            .await
+            .enable_soft_delete("tasks", "task")
+            .await
+            .unwrap();
+        backend
+            .read()
+            .await
+            .execute_sql(
+                "INSERT INTO tasks (id, completed, completed_at) VALUES
+                 ('old', 1, '2026-01-01T10:00:00Z'),
+                 ('recent', 1, '2026-06-01T10:00:00Z'),
+                 ('open', 0, NULL)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+
+        let rules = vec![
+            ArchivalRule::new("tasks", "completed = 1")
+                .older_than("completed_at", Duration::from_secs(90 * 24 * 60 * 60)),
+        ];
+        let now = "2026-06-15T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(
+            apply_archival_rules(&backend, &rules, now).await.unwrap(),
+            1
+        );
+        // Archived rows aren't archived again
+        assert_eq!(
+            apply_archival_rules(&backend, &rules, now).await.unwrap(),
+            0
+        );
+
+        let rows = backend
+            .read()
+            .await
+            .execute_sql(
+                "SELECT id FROM tasks WHERE archived_at IS NOT NULL",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get("id"), Some(&holon_api::Value::from("old")));
+
+        // A broken rule is reported without stopping the others
+        let rules = vec![
+            ArchivalRule::new("missing", "1 = 1"),
+            ArchivalRule::new("tasks", "completed = 0"),
+        ];
+        assert!(apply_archival_rules(&backend, &rules, now).await.is_err());
+        let rows = backend
+            .read()
+            .await
+            .execute_sql(
+                "SELECT id FROM tasks WHERE archived_at IS NOT NULL",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+    }
+}
@@ -0,0 +1,546 @@
+//! Computed (derived) entity fields
+//!
+//! A computed field is a column derived from other columns of the same row, either by
+//! a PRQL expression (`#[computed(expr = "...")]` on an Entity field) or by a closure.
+//! Each table with computed fields is watched through a materialized view over its id
+//! and the columns the computations depend on. When one of those changes, the row's
+//! computed fields are recomputed and written back. Unchanged values are not written,
+//! so computed fields may depend on other computed fields as long as there is no cycle.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, RwLock};
+
+use holon_api::{BatchWithMetadata, Schema, Value};
+
+use crate::references::content_source;
+use crate::storage::turso::{ChangeData, RowChange, TursoBackend};
+use crate::storage::types::{Result, StorageEntity, StorageError};
+
+/// Prefix of the materialized views that report dependency changes
+pub const COMPUTED_SOURCE_VIEW_PREFIX: &str = "computed_src_";
+
+/// Closure deriving a field from the row it belongs to
+pub type ComputeFn = Arc<dyn Fn(&StorageEntity) -> Value + Send + Sync>;
+
+#[derive(Clone)]
+enum Computation {
+    Prql(String),
+    Closure(ComputeFn),
+}
+
+/// Definition of a derived column
+#[derive(Clone)]
+pub struct ComputedField {
+    pub table: String,
+    pub column: String,
+    pub id_column: String,
+    /// Columns of the same row the value is derived from
+    pub dependencies: Vec<String>,
+    computation: Computation,
+}
+
+impl std::fmt::Debug for ComputedField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let computation = match &self.computation {
+            Computation::Prql(expr) => expr.as_str(),
+            Computation::Closure(_) => "<closure>",
+        };
+        f.debug_struct("ComputedField")
+            .field("table", &self.table)
+            .field("column", &self.column)
+            .field("id_column", &self.id_column)
+            .field("dependencies", &self.dependencies)
+            .field("computation", &computation)
+            .finish()
+    }
+}
+
+impl ComputedField {
+    /// Derive `column` with a PRQL expression over other columns of `table`
+    pub fn from_expr(
+        table: impl Into<String>,
+        column: impl Into<String>,
+        expr: impl Into<String>,
+        dependencies: Vec<String>,
+    ) -> Self {
+        Self {
+            table: table.into(),
+            column: column.into(),
+            id_column: "id".to_string(),
+            dependencies,
+            computation: Computation::Prql(expr.into()),
+        }
+    }
+
+    /// Derive `column` with a closure receiving the whole row
+    pub fn from_fn(
+        table: impl Into<String>,
+        column: impl Into<String>,
+        dependencies: Vec<String>,
+        compute: impl Fn(&StorageEntity) -> Value + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            table: table.into(),
+            column: column.into(),
+            id_column: "id".to_string(),
+            dependencies,
+            computation: Computation::Closure(Arc::new(compute)),
+        }
+    }
+
+    pub fn with_id_column(mut self, id_column: impl Into<String>) -> Self {
+        self.id_column = id_column.into();
+        self
+    }
+
+    /// The computed fields declared in `schema`
+    ///
+    /// Dependencies are the schema fields the expression mentions.
+    pub fn from_schema(schema: &Schema) -> Vec<Self> {
+        let id_column = schema
+            .fields
+            .iter()
+            .find(|f| f.primary_key)
+            .map(|f| f.name.as_str())
+            .unwrap_or("id");
+        schema
+            .fields
+            .iter()
+            .filter_map(|field| {
+                let expr = field.computed.as_ref()?;
+                let identifiers = expression_identifiers(expr);
+                let dependencies = schema
+                    .fields
+                    .iter()
+                    .filter(|f| f.name != field.name && identifiers.contains(f.name.as_str()))
+                    .map(|f| f.name.clone())
+                    .collect();
+                Some(
+                    Self::from_expr(&schema.table_name, &field.name, expr, dependencies)
+                        .with_id_column(id_column),
+                )
+            })
+            .collect()
+    }
+
+    /// `SELECT computed_value, computed_current` for the row `$id`
+    fn compile(&self) -> Result<Option<String>> {
+        let Computation::Prql(expr) = &self.computation else {
+            return Ok(None);
+        };
+        let prql = format!(
+            "from {}\nselect {{computed_id = {}, computed_current = {}, computed_value = ({})}}",
+            self.table, self.id_column, self.column, expr
+        );
+        let sql = prqlc::compile(&prql, &prqlc::Options::default()).map_err(|e| {
+            StorageError::SchemaError(format!(
+                "Invalid expression for computed field {}.{}: {}",
+                self.table, self.column, e
+            ))
+        })?;
+        Ok(Some(format!(
+            "SELECT computed_value, computed_current FROM (\n{}\n) WHERE computed_id = $id",
+            sql
+        )))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RegisteredField {
+    field: ComputedField,
+    /// Compiled select for PRQL fields
+    select_sql: Option<String>,
+}
+
+/// Registry of computed fields by table
+#[derive(Clone, Debug, Default)]
+pub struct ComputedFields {
+    tables: Arc<RwLock<HashMap<String, Vec<RegisteredField>>>>,
+}
+
+impl ComputedFields {
+    /// Register a computed field, replacing an earlier definition of the same column
+    ///
+    /// PRQL expressions are compiled here, so invalid expressions fail registration.
+    pub fn register(&self, field: ComputedField) -> Result<()> {
+        let select_sql = field.compile()?;
+        let mut tables = self.tables.write().unwrap();
+        let fields = tables.entry(field.table.clone()).or_default();
+        fields.retain(|registered| registered.field.column != field.column);
+        fields.push(RegisteredField { field, select_sql });
+        Ok(())
+    }
+
+    /// Register all computed fields declared in `schema`
+    pub fn register_schema(&self, schema: &Schema) -> Result<()> {
+        for field in ComputedField::from_schema(schema) {
+            self.register(field)?;
+        }
+        Ok(())
+    }
+
+    pub fn table_names(&self) -> Vec<String> {
+        self.tables.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Computed fields of `table`
+    pub fn fields(&self, table: &str) -> Vec<ComputedField> {
+        self.registered(table)
+            .into_iter()
+            .map(|registered| registered.field)
+            .collect()
+    }
+
+    /// `SELECT id, <dependencies>` of `table`, or None if nothing is derived from its columns
+    pub fn source_select_sql(&self, table: &str) -> Option<String> {
+        let fields = self.registered(table);
+        let id_column = &fields.first()?.field.id_column;
+        let dependencies: BTreeSet<&str> = fields
+            .iter()
+            .flat_map(|registered| registered.field.dependencies.iter())
+            .map(|dependency| dependency.as_str())
+            .filter(|dependency| *dependency != id_column.as_str())
+            .collect();
+        if dependencies.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "SELECT {} AS id, {} FROM {}",
+            id_column,
+            dependencies.into_iter().collect::<Vec<_>>().join(", "),
+            table
+        ))
+    }
+
+    /// Recompute the computed fields of one row
+    ///
+    /// Returns the number of fields whose value changed.
+    pub async fn recompute_row(
+        &self,
+        backend: &TursoBackend,
+        table: &str,
+        id: &str,
+    ) -> Result<usize> {
+        let params = HashMap::from([("id".to_string(), Value::String(id.to_string()))]);
+        let mut changed = 0;
+
+        for registered in self.registered(table) {
+            let field = &registered.field;
+            let (value, current) = match (&registered.select_sql, &field.computation) {
+                (Some(sql), _) => {
+                    let rows = backend.execute_sql(sql, params.clone()).await?;
+                    let Some(result) = rows.into_iter().next() else {
+                        return Ok(changed);
+                    };
+                    (
+                        result.get("computed_value").cloned().unwrap_or(Value::Null),
+                        result
+                            .get("computed_current")
+                            .cloned()
+                            .unwrap_or(Value::Null),
+                    )
+                }
+                (None, Computation::Closure(compute)) => {
+                    // Reload the row: an earlier field of this table may have changed it
+                    let sql = format!("SELECT * FROM {} WHERE {} = $id", table, field.id_column);
+                    let Some(loaded) = backend.execute_sql(&sql, params.clone()).await?.pop()
+                    else {
+                        return Ok(changed);
+                    };
+                    (
+                        compute(&loaded),
+                        loaded.get(&field.column).cloned().unwrap_or(Value::Null),
+                    )
+                }
+                (None, Computation::Prql(_)) => continue,
+            };
+
+            let value = stored_value(value);
+            if value == stored_value(current) {
+                continue;
+            }
+            backend
+                .execute_sql(
+                    &format!(
+                        "UPDATE {} SET {} = $value WHERE {} = $id",
+                        table, field.column, field.id_column
+                    ),
+                    HashMap::from([
+                        ("value".to_string(), value),
+                        ("id".to_string(), Value::String(id.to_string())),
+                    ]),
+                )
+                .await?;
+            changed += 1;
+        }
+        Ok(changed)
+    }
+
+    /// Recompute the computed fields of every row of every registered table
+    pub async fn recompute_all(&self, backend: &TursoBackend) -> Result<usize> {
+        let mut changed = 0;
+        for table in self.table_names() {
+            let Some(id_column) = self
+                .registered(&table)
+                .first()
+                .map(|registered| registered.field.id_column.clone())
+            else {
+                continue;
+            };
+            let rows = backend
+                .execute_sql(
+                    &format!("SELECT {} AS id FROM {}", id_column, table),
+                    HashMap::new(),
+                )
+                .await?;
+            for id in rows
+                .iter()
+                .filter_map(|row| row.get("id").and_then(|v| v.as_string_owned()))
+            {
+                changed += self.recompute_row(backend, &table, &id).await?;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Recompute the rows touched by a change batch of a `computed_src_` view
+    pub async fn apply_batch(
+        &self,
+        backend: &TursoBackend,
+        batch: &BatchWithMetadata<RowChange>,
+    ) -> Result<usize> {
+        let Some(table) = batch
+            .metadata
+            .relation_name
+            .strip_prefix(COMPUTED_SOURCE_VIEW_PREFIX)
+        else {
+            return Ok(0);
+        };
+
+        let mut changed = 0;
+        for row_change in &batch.inner.items {
+            if matches!(row_change.change, ChangeData::Deleted { .. }) {
+                continue;
+            }
+            if let Some(id) = content_source::changed_entity_id(&row_change.change) {
+                changed += self.recompute_row(backend, table, id).await?;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Recompute all rows, then follow dependency changes in the background
+    ///
+    /// Only fields registered before this is called are watched.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self, backend: Arc<tokio::sync::RwLock<TursoBackend>>) {
+        use std::ops::ControlFlow;
+
+        tokio::spawn(async move {
+            let views = {
+                let backend = backend.read().await;
+                match self.recompute_all(&backend).await {
+                    Ok(0) => {}
+                    Ok(changed) => {
+                        tracing::debug!("[ComputedFields] Recomputed {} fields", changed)
+                    }
+                    Err(e) => tracing::warn!("[ComputedFields] Failed to recompute fields: {}", e),
+                }
+                self.table_names()
+                    .into_iter()
+                    .filter_map(|table| {
+                        let sql = self.source_select_sql(&table)?;
+                        Some((format!("{}{}", COMPUTED_SOURCE_VIEW_PREFIX, table), sql))
+                    })
+                    .collect::<Vec<_>>()
+            };
+            if views.is_empty() {
+                return;
+            }
+
+            let watched = content_source::follow_views(&backend, &views, |batch| {
+                let (fields, backend) = (self.clone(), backend.clone());
+                async move {
+                    let backend = backend.read().await;
+                    if let Err(e) = fields.apply_batch(&backend, &batch).await {
+                        tracing::warn!("[ComputedFields] Failed to recompute fields: {}", e);
+                    }
+                    ControlFlow::Continue(())
+                }
+            })
+            .await;
+            if let Err(e) = watched {
+                tracing::warn!("[ComputedFields] Failed to watch dependencies: {}", e);
+            }
+        });
+    }
+
+    fn registered(&self, table: &str) -> Vec<RegisteredField> {
+        self.tables
+            .read()
+            .unwrap()
+            .get(table)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Normalize a value to what SQLite stores, so recomputed and stored values compare equal
+fn stored_value(value: Value) -> Value {
+    match value {
+        Value::Boolean(b) => Value::Integer(b as i64),
+        other => other,
+    }
+}
+
+/// Identifiers mentioned in a PRQL expression
+fn expression_identifiers(expr: &str) -> BTreeSet<&str> {
+    expr.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|token| token.starts_with(|c: char| c.is_alphabetic() || c == '_'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holon_api::FieldSchema;
+
+    async fn tasks_backend() -> TursoBackend {
+        let backend = TursoBackend::new_in_memory().await.unwrap();
+        backend
+            .execute_sql(
+                "CREATE TABLE tasks (id TEXT PRIMARY KEY, priority INTEGER, due_in_days INTEGER, urgency INTEGER)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+            .execute_sql(
+                "INSERT INTO tasks (id, priority, due_in_days) VALUES ('a', 3, 1), ('b', 1, 10)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+    }
+
+    async fn urgency(backend: &TursoBackend, id: &str) -> Value {
+        let rows = backend
+            .execute_sql(
+                "SELECT urgency FROM tasks WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+            )
+            .await
+            .unwrap();
+        rows[0]["urgency"].clone()
+    }
+
+    #[test]
+    fn test_from_schema_derives_dependencies() {
+        let schema = Schema::new(
+            "tasks",
+            vec![
+                FieldSchema::new("id", "TEXT").primary_key(),
+                FieldSchema::new("priority", "INTEGER"),
+                FieldSchema::new("due_in_days", "INTEGER"),
+                FieldSchema::new("title", "TEXT"),
+                FieldSchema::new("urgency", "INTEGER")
+                    .nullable()
+                    .computed("priority * 10 - due_in_days"),
+            ],
+        );
+        let fields = ComputedField::from_schema(&schema);
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].column, "urgency");
+        assert_eq!(fields[0].dependencies, vec!["priority", "due_in_days"]);
+        assert!(
+            !schema
+                .to_create_table_sql()
+                .contains("urgency INTEGER NOT NULL")
+        );
+        assert_eq!(schema.stored_fields().count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_recompute_prql_expression() {
+        let backend = tasks_backend().await;
+        let computed = ComputedFields::default();
+        computed
+            .register(ComputedField::from_expr(
+                "tasks",
+                "urgency",
+                "priority * 10 - due_in_days",
+                vec!["priority".to_string(), "due_in_days".to_string()],
+            ))
+            .unwrap();
+
+        assert_eq!(computed.recompute_all(&backend).await.unwrap(), 2);
+        assert_eq!(urgency(&backend, "a").await, Value::Integer(29));
+        assert_eq!(urgency(&backend, "b").await, Value::Integer(0));
+
+        // Nothing changed, nothing is written
+        assert_eq!(
+            computed
+                .recompute_row(&backend, "tasks", "a")
+                .await
+                .unwrap(),
+            0
+        );
+
+        backend
+            .execute_sql(
+                "UPDATE tasks SET priority = 4 WHERE id = 'b'",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            computed
+                .recompute_row(&backend, "tasks", "b")
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(urgency(&backend, "b").await, Value::Integer(30));
+    }
+
+    #[tokio::test]
+    async fn test_recompute_closure() {
+        let backend = tasks_backend().await;
+        let computed = ComputedFields::default();
+        computed
+            .register(ComputedField::from_fn(
+                "tasks",
+                "urgency",
+                vec!["priority".to_string()],
+                |row| match row.get("priority") {
+                    Some(Value::Integer(p)) => Value::Integer(p * p),
+                    _ => Value::Null,
+                },
+            ))
+            .unwrap();
+
+        computed
+            .recompute_row(&backend, "tasks", "a")
+            .await
+            .unwrap();
+        assert_eq!(urgency(&backend, "a").await, Value::Integer(9));
+        assert_eq!(
+            computed.source_select_sql("tasks").unwrap(),
+            "SELECT id AS id, priority FROM tasks"
+        );
+    }
+
+    #[test]
+    fn test_invalid_expression_fails_registration() {
+        let computed = ComputedFields::default();
+        let result = computed.register(ComputedField::from_expr(
+            "tasks",
+            "urgency",
+            "priority * (",
+            vec![],
+        ));
+        assert!(result.is_err());
+        assert!(computed.fields("tasks").is_empty());
+    }
+}
@@ -0,0 +1,302 @@
+//! Encryption at rest
+//!
+//! An encrypted database is encrypted by Turso with a random data key. The data key is
+//! stored next to the database (`<db>.key`), wrapped with a key derived from the user's
+//! passphrase (Argon2id + XChaCha20-Poly1305). Rotating the passphrase therefore only
+//! rewraps the data key and never re-encrypts the database.
+//!
+//! Locking keeps the database file open but refuses every connection until it is
+//! unlocked with the passphrase again.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::storage::types::{Result, StorageError};
+
+/// Cipher used for new encrypted databases
+pub const DEFAULT_CIPHER: &str = "aegis256";
+
+const KEY_FILE_VERSION: u32 = 1;
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Configuration of an encrypted database
+#[derive(Clone)]
+pub struct EncryptionConfig {
+    passphrase: String,
+    cipher: String,
+}
+
+impl EncryptionConfig {
+    pub fn new(passphrase: impl Into<String>) -> Self {
+        Self {
+            passphrase: passphrase.into(),
+            cipher: DEFAULT_CIPHER.to_string(),
+        }
+    }
+
+    /// Cipher for newly created databases (existing ones keep the cipher in their key file)
+    pub fn with_cipher(mut self, cipher: impl Into<String>) -> Self {
+        self.cipher = cipher.into();
+        self
+    }
+
+    pub fn passphrase(&self) -> &str {
+        &self.passphrase
+    }
+
+    pub fn cipher(&self) -> &str {
+        &self.cipher
+    }
+}
+
+impl std::fmt::Debug for EncryptionConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptionConfig")
+            .field("passphrase", &"<redacted>")
+            .field("cipher", &self.cipher)
+            .finish()
+    }
+}
+
+/// The key the database is encrypted with; zeroed when dropped
+pub struct DataKey([u8; KEY_LEN]);
+
+impl DataKey {
+    fn generate() -> Self {
+        let mut key = [0u8; KEY_LEN];
+        rand::thread_rng().fill_bytes(&mut key);
+        Self(key)
+    }
+
+    /// Hex encoding as expected by Turso's `hexkey`
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// SHA-256 of the key, to check an unwrapped key without keeping the key around
+    pub fn fingerprint(&self) -> String {
+        hex::encode(Sha256::digest(self.0))
+    }
+
+    /// XChaCha20-Poly1305 keyed with this key
+    pub(crate) fn aead(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(&self.0.into())
+    }
+}
+
+impl Drop for DataKey {
+    fn drop(&mut self) {
+        self.0.fill(0);
+    }
+}
+
+/// The wrapped data key of an encrypted database, stored as JSON in `<db>.key`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyFile {
+    pub version: u32,
+    pub cipher: String,
+    pub kdf: String,
+    pub salt: String,
+    pub nonce: String,
+    pub wrapped_key: String,
+}
+
+impl KeyFile {
+    /// Path of the key file of the database at `db_path`
+    pub fn path_for(db_path: &Path) -> PathBuf {
+        let mut path = db_path.as_os_str().to_owned();
+        path.push(".key");
+        PathBuf::from(path)
+    }
+
+    /// Generate a new data key and wrap it with `passphrase`
+    pub fn generate(passphrase: &str, cipher: &str) -> Result<(Self, DataKey)> {
+        let key = DataKey::generate();
+        let key_file = Self::wrap(&key, passphrase, cipher)?;
+        Ok((key_file, key))
+    }
+
+    /// Unwrap the data key; fails if the passphrase is wrong
+    pub fn unwrap_key(&self, passphrase: &str) -> Result<DataKey> {
+        if self.version != KEY_FILE_VERSION {
+            return Err(StorageError::DatabaseError(format!(
+                "Unsupported key file version {}",
+                self.version
+            )));
+        }
+        let salt = decode_hex("salt", &self.salt)?;
+        let nonce = decode_hex("nonce", &self.nonce)?;
+        let wrapped_key = decode_hex("wrapped_key", &self.wrapped_key)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(StorageError::DatabaseError(
+                "Corrupt key file: invalid nonce".to_string(),
+            ));
+        }
+
+        let kek = derive_key(passphrase, &salt)?;
+        let mut plain = kek
+            .aead()
+            .decrypt(XNonce::from_slice(&nonce), wrapped_key.as_slice())
+            .map_err(|_| StorageError::DatabaseError("Wrong passphrase".to_string()))?;
+        let key = <[u8; KEY_LEN]>::try_from(plain.as_slice())
+            .map(DataKey)
+            .map_err(|_| {
+                StorageError::DatabaseError("Corrupt key file: invalid key length".to_string())
+            });
+        plain.fill(0);
+        key
+    }
+
+    /// Wrap the same data key with `new_passphrase`
+    pub fn rewrap(&self, old_passphrase: &str, new_passphrase: &str) -> Result<Self> {
+        let key = self.unwrap_key(old_passphrase)?;
+        Self::wrap(&key, new_passphrase, &self.cipher)
+    }
+
+    /// Load the key file at `path`, or None if there is none
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| StorageError::SerializationError(e.to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the key file atomically, so a crash never leaves a torn key behind
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_vec_pretty(self)
+            .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        std::fs::write(&tmp_path, json)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    fn wrap(key: &DataKey, passphrase: &str, cipher: &str) -> Result<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let kek = derive_key(passphrase, &salt)?;
+        let wrapped_key = kek
+            .aead()
+            .encrypt(XNonce::from_slice(&nonce), key.0.as_slice())
+            .map_err(|e| StorageError::DatabaseError(format!("Failed to wrap key: {}", e)))?;
+
+        Ok(Self {
+            version: KEY_FILE_VERSION,
+            cipher: cipher.to_string(),
+            kdf: "argon2id".to_string(),
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            wrapped_key: hex::encode(wrapped_key),
+        })
+    }
+}
+
+/// Derive a key from a passphrase with Argon2id
+pub(crate) fn derive_key(passphrase: &str, salt: &[u8]) -> Result<DataKey> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| StorageError::DatabaseError(format!("Key derivation failed: {}", e)))?;
+    Ok(DataKey(key))
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>> {
+    hex::decode(value)
+        .map_err(|e| StorageError::DatabaseError(format!("Corrupt key file: {}: {}", field, e)))
+}
+
+/// Locked/unlocked state of a database
+///
+/// Cheap to clone; all clones share the same state.
+#[derive(Clone, Debug, Default)]
+pub struct DatabaseLock {
+    locked: Arc<AtomicBool>,
+}
+
+impl DatabaseLock {
+    pub fn lock(&self) {
+        self.locked.store(true, Ordering::SeqCst);
+    }
+
+    pub fn unlock(&self) {
+        self.locked.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::SeqCst)
+    }
+
+    /// `Err(StorageError::Locked)` while locked
+    pub fn check(&self) -> Result<()> {
+        if self.is_locked() {
+            Err(StorageError::Locked)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_file_roundtrip() {
+        let (key_file, key) = KeyFile::generate("correct horse", DEFAULT_CIPHER).unwrap();
+        let unwrapped = key_file.unwrap_key("correct horse").unwrap();
+        assert_eq!(unwrapped.to_hex(), key.to_hex());
+        assert!(key_file.unwrap_key("wrong").is_err());
+    }
+
+    #[test]
+    fn test_rewrap_keeps_data_key() {
+        let (key_file, key) = KeyFile::generate("old", DEFAULT_CIPHER).unwrap();
+        let rotated = key_file.rewrap("old", "new").unwrap();
+        assert_ne!(rotated.wrapped_key, key_file.wrapped_key);
+        assert!(rotated.unwrap_key("old").is_err());
+        assert_eq!(
+            rotated.unwrap_key("new").unwrap().fingerprint(),
+            key.fingerprint()
+        );
+    }
+
+    #[test]
+    fn test_key_file_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = KeyFile::path_for(&dir.path().join("holon.db"));
+        assert!(path.ends_with("holon.db.key"));
+        assert_eq!(KeyFile::load(&path).unwrap(), None);
+
+        let (key_file, _) = KeyFile::generate("secret", DEFAULT_CIPHER).unwrap();
+        key_file.save(&path).unwrap();
+        assert_eq!(KeyFile::load(&path).unwrap(), Some(key_file));
+    }
+
+    #[test]
+    fn test_database_lock() {
+        let lock = DatabaseLock::default();
+        assert!(lock.check().is_ok());
+        lock.clone().lock();
+        assert!(matches!(lock.check(), Err(StorageError::Locked)));
+        lock.unlock();
+        assert!(!lock.is_locked());
+    }
+}
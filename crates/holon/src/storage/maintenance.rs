@@ -0,0 +1,303 @@
+//! Background database maintenance
+//!
+//! Long-running instances accumulate WAL frames and free pages, and the query
+//! planner's statistics go stale as tables grow. `MaintenanceScheduler` checkpoints
+//! the WAL, reclaims free pages with `PRAGMA incremental_vacuum` and refreshes the
+//! statistics with `ANALYZE`.
+//!
+//! Maintenance runs while the app is idle: the scheduler observes every dispatched
+//! operation and only starts a run when at most `max_idle_operations` ran within
+//! `idle_window`, and the last run is at least `min_interval` ago. Frontends can
+//! show its `MaintenanceStatus` ("optimizing database") and start a run manually.
+//!
+//! `incremental_vacuum` only reclaims pages of databases with
+//! `auto_vacuum = INCREMENTAL`; on other databases it does nothing.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::{Mutex, RwLock, watch};
+use tracing::{debug, info, warn};
+
+use crate::core::datasource::{OperationObserver, UndoAction};
+use crate::storage::turso::TursoBackend;
+use holon_api::Operation;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Configuration of background database maintenance
+#[derive(Clone, Debug)]
+pub struct MaintenanceConfig {
+    /// How often the scheduler checks whether a run is due
+    pub check_interval: Duration,
+    /// Minimum time between the end of a run and the start of the next one
+    pub min_interval: Duration,
+    /// Period over which dispatched operations are counted to detect idleness
+    pub idle_window: Duration,
+    /// The app counts as idle while at most this many operations ran in `idle_window`
+    pub max_idle_operations: usize,
+    /// Free pages reclaimed per run by `PRAGMA incremental_vacuum`
+    pub vacuum_pages: u32,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(60),
+            min_interval: Duration::from_secs(6 * 60 * 60),
+            idle_window: Duration::from_secs(2 * 60),
+            max_idle_operations: 0,
+            vacuum_pages: 1000,
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    pub fn with_check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    pub fn with_idle_window(mut self, idle_window: Duration, max_operations: usize) -> Self {
+        self.idle_window = idle_window;
+        self.max_idle_operations = max_operations;
+        self
+    }
+
+    pub fn with_vacuum_pages(mut self, vacuum_pages: u32) -> Self {
+        self.vacuum_pages = vacuum_pages;
+        self
+    }
+}
+
+/// State of database maintenance, for "optimizing database" indicators
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaintenanceStatus {
+    /// A run is in progress
+    pub running: bool,
+    /// Timestamps are Unix timestamps in milliseconds
+    pub last_started_at: Option<i64>,
+    pub last_finished_at: Option<i64>,
+    pub last_duration_ms: Option<i64>,
+    /// Failed steps of the last run (`"<statement>: <error>"`); the other steps still ran
+    pub last_errors: Vec<String>,
+    /// Completed runs since the app started
+    pub runs: u64,
+}
+
+/// Runs database maintenance during idle periods
+pub struct MaintenanceScheduler {
+    backend: Arc<RwLock<TursoBackend>>,
+    config: MaintenanceConfig,
+    /// Timestamps (ms) of operations dispatched within the idle window
+    activity: StdMutex<VecDeque<i64>>,
+    status: watch::Sender<MaintenanceStatus>,
+    /// Held while a run is in progress
+    run_lock: Mutex<()>,
+}
+
+impl MaintenanceScheduler {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>, config: MaintenanceConfig) -> Self {
+        Self {
+            backend,
+            config,
+            activity: StdMutex::new(VecDeque::new()),
+            status: watch::channel(MaintenanceStatus::default()).0,
+            run_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn config(&self) -> &MaintenanceConfig {
+        &self.config
+    }
+
+    pub fn status(&self) -> MaintenanceStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Receive every status change, e.g. to show "optimizing database" while a run is in progress
+    pub fn subscribe_status(&self) -> watch::Receiver<MaintenanceStatus> {
+        self.status.subscribe()
+    }
+
+    /// Record an operation dispatched at `now` (Unix timestamp in milliseconds)
+    pub fn record_activity(&self, now: i64) {
+        let mut activity = self.activity.lock().unwrap();
+        activity.push_back(now);
+        Self::prune(&mut activity, now, &self.config);
+    }
+
+    /// Number of operations dispatched within the idle window before `now`
+    pub fn recent_operations(&self, now: i64) -> usize {
+        let mut activity = self.activity.lock().unwrap();
+        Self::prune(&mut activity, now, &self.config);
+        activity.len()
+    }
+
+    pub fn is_idle(&self, now: i64) -> bool {
+        self.recent_operations(now) <= self.config.max_idle_operations
+    }
+
+    /// Whether a scheduled run should start at `now`
+    pub fn is_due(&self, now: i64) -> bool {
+        let status = self.status();
+        if status.running || !self.is_idle(now) {
+            return false;
+        }
+        status.last_finished_at.is_none_or(|finished_at| {
+            now - finished_at >= self.config.min_interval.as_millis() as i64
+        })
+    }
+
+    /// Run maintenance now, regardless of activity
+    ///
+    /// Fails if a run is already in progress. Returns the status after the run;
+    /// failed steps are reported in `last_errors` instead of failing the run.
+    pub async fn run(&self) -> Result<MaintenanceStatus> {
+        let _running = self
+            .run_lock
+            .try_lock()
+            .map_err(|_| "Database maintenance is already running")?;
+
+        let started_at = chrono::Utc::now().timestamp_millis();
+        self.status.send_modify(|status| {
+            status.running = true;
+            status.last_started_at = Some(started_at);
+        });
+        info!("[Maintenance] Optimizing database");
+
+        let mut errors = Vec::new();
+        {
+            let backend = self.backend.read().await;
+            for statement in self.statements() {
+                match backend.execute_sql(&statement, HashMap::new()).await {
+                    Ok(_) => debug!("[Maintenance] {} done", statement),
+                    Err(e) => {
+                        warn!("[Maintenance] {} failed: {}", statement, e);
+                        errors.push(format!("{}: {}", statement, e));
+                    }
+                }
+            }
+        }
+
+        let finished_at = chrono::Utc::now().timestamp_millis();
+        self.status.send_modify(|status| {
+            status.running = false;
+            status.last_finished_at = Some(finished_at);
+            status.last_duration_ms = Some(finished_at - started_at);
+            status.last_errors = errors;
+            status.runs += 1;
+        });
+        info!(
+            "[Maintenance] Database optimized in {} ms",
+            finished_at - started_at
+        );
+        Ok(self.status())
+    }
+
+    /// Run maintenance if it is due at `now`; returns the status after the run
+    pub async fn run_if_due(&self, now: i64) -> Option<MaintenanceStatus> {
+        if !self.is_due(now) {
+            return None;
+        }
+        match self.run().await {
+            Ok(status) => Some(status),
+            Err(e) => {
+                debug!("[Maintenance] Skipped scheduled run: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Check every `check_interval` whether maintenance is due and run it
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.check_interval);
+            // The first tick completes immediately; give the app time to start up
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                self.run_if_due(chrono::Utc::now().timestamp_millis()).await;
+            }
+        })
+    }
+
+    fn statements(&self) -> Vec<String> {
+        vec![
+            "PRAGMA wal_checkpoint(TRUNCATE)".to_string(),
+            format!("PRAGMA incremental_vacuum({})", self.config.vacuum_pages),
+            "ANALYZE".to_string(),
+        ]
+    }
+
+    fn prune(activity: &mut VecDeque<i64>, now: i64, config: &MaintenanceConfig) {
+        let cutoff = now - config.idle_window.as_millis() as i64;
+        while activity.front().is_some_and(|&at| at <= cutoff) {
+            activity.pop_front();
+        }
+    }
+}
+
+/// Counts dispatched operations to detect idle periods
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationObserver for MaintenanceScheduler {
+    fn entity_filter(&self) -> &str {
+        "*"
+    }
+
+    async fn on_operation_executed(&self, _operation: &Operation, _undo_action: &UndoAction) {
+        self.record_activity(chrono::Utc::now().timestamp_millis());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    async fn create_scheduler(config: MaintenanceConfig) -> MaintenanceScheduler {
+        MaintenanceScheduler::new(memory_backend().await, config)
+    }
+
+    #[tokio::test]
+    async fn test_due_only_when_idle_and_interval_passed() {
+        let config = MaintenanceConfig::default()
+            .with_idle_window(Duration::from_secs(60), 1)
+            .with_min_interval(Duration::from_secs(3600));
+        let scheduler = create_scheduler(config).await;
+
+        scheduler.record_activity(1_000);
+        scheduler.record_activity(2_000);
+        assert!(!scheduler.is_due(30_000), "two operations within a minute");
+        // The first operation left the window
+        assert!(scheduler.is_due(61_500));
+
+        let status = scheduler.run().await.unwrap();
+        assert!(!status.running);
+        assert_eq!(status.runs, 1);
+        let finished_at = status.last_finished_at.unwrap();
+        assert!(!scheduler.is_due(finished_at + 60_000));
+        assert!(scheduler.is_due(finished_at + 3_600_000));
+    }
+
+    #[tokio::test]
+    async fn test_status_changes_are_published() {
+        let scheduler = create_scheduler(MaintenanceConfig::default()).await;
+        let mut status = scheduler.subscribe_status();
+
+        scheduler.run().await.unwrap();
+        assert!(status.has_changed().unwrap());
+        let latest = status.borrow_and_update().clone();
+        assert_eq!(latest.runs, 1);
+        assert!(latest.last_duration_ms.is_some());
+    }
+}
@@ -1,19 +1,47 @@
+pub mod archival;
 pub mod backend;
 pub mod command_sourcing;
+pub mod computed;
+pub mod encryption;
 pub mod fractional_index;
+pub mod maintenance;
+#[cfg(target_arch = "wasm32")]
+pub mod opfs;
+pub mod pagination;
+pub mod replica;
+pub mod rollups;
 pub mod schema;
+pub mod snapshot;
+pub mod snapshot_store;
+pub mod soft_delete;
+pub mod stats;
 pub mod sync_token_store;
 pub mod task_datasource;
 pub mod turso;
 pub mod types;
+pub mod urgency;
 
+#[cfg(test)]
+pub(crate) mod test_support;
 #[cfg(test)]
 pub mod turso_repro_test;
 
+pub use archival::*;
 pub use backend::*;
 pub use command_sourcing::*;
+pub use computed::*;
+pub use encryption::*;
 pub use fractional_index::*;
+pub use maintenance::*;
+pub use pagination::*;
+pub use replica::*;
+pub use rollups::*;
 pub use schema::*;
+pub use snapshot::*;
+pub use snapshot_store::*;
+pub use soft_delete::*;
+pub use stats::*;
 pub use sync_token_store::*;
 pub use task_datasource::*;
 pub use types::*;
+pub use urgency::*;
@@ -0,0 +1,139 @@
+//! Database persistence for the web target.
+//!
+//! turso has no file system IO in the browser, so on wasm32 `TursoBackend` runs
+//! the database on `MemoryIO` and persists it as a `DatabaseSnapshot` JSON file
+//! in the Origin Private File System (OPFS). `TursoBackend::open` restores the
+//! snapshot of its database path, `TursoBackend::schedule_persist` writes it back
+//! shortly after changes.
+//!
+//! OPFS is available in windows and workers alike, and unlike `localStorage`
+//! it has no small size limit.
+
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    DomException, File, FileSystemDirectoryHandle, FileSystemFileHandle, FileSystemGetFileOptions,
+    FileSystemWritableFileStream, Navigator,
+};
+
+use crate::storage::types::{Result, StorageError};
+
+/// Snapshot file of one database in the origin's private file system
+#[derive(Debug, Clone)]
+pub struct OpfsFile {
+    name: String,
+}
+
+impl OpfsFile {
+    /// Snapshot file for the database at `db_path`
+    pub fn for_database(db_path: &str) -> Self {
+        let name: String = db_path
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Self {
+            name: format!("{}.snapshot.json", name),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Contents of the file, or `None` if it was never written
+    pub async fn read(&self) -> Result<Option<String>> {
+        let root = root_directory().await?;
+        let handle = match JsFuture::from(root.get_file_handle(&self.name)).await {
+            Ok(handle) => handle.unchecked_into::<FileSystemFileHandle>(),
+            Err(e) if is_not_found(&e) => return Ok(None),
+            Err(e) => return Err(js_error("open", &self.name, e)),
+        };
+
+        let file: File = JsFuture::from(handle.get_file())
+            .await
+            .map_err(|e| js_error("read", &self.name, e))?
+            .unchecked_into();
+        let text = JsFuture::from(file.text())
+            .await
+            .map_err(|e| js_error("read", &self.name, e))?;
+        Ok(text.as_string())
+    }
+
+    /// Replace the file's contents
+    pub async fn write(&self, contents: &str) -> Result<()> {
+        let root = root_directory().await?;
+        let options = FileSystemGetFileOptions::new();
+        options.set_create(true);
+        let handle: FileSystemFileHandle =
+            JsFuture::from(root.get_file_handle_with_options(&self.name, &options))
+                .await
+                .map_err(|e| js_error("create", &self.name, e))?
+                .unchecked_into();
+
+        // Writes go to a swap file and replace the file atomically on close
+        let stream: FileSystemWritableFileStream = JsFuture::from(handle.create_writable())
+            .await
+            .map_err(|e| js_error("write", &self.name, e))?
+            .unchecked_into();
+        let written = stream
+            .write_with_str(contents)
+            .map_err(|e| js_error("write", &self.name, e))?;
+        JsFuture::from(written)
+            .await
+            .map_err(|e| js_error("write", &self.name, e))?;
+        JsFuture::from(stream.close())
+            .await
+            .map_err(|e| js_error("write", &self.name, e))?;
+        Ok(())
+    }
+}
+
+/// Wait `duration` on the JS event loop; tokio's timer doesn't run in the browser
+pub async fn sleep(duration: std::time::Duration) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let set_timeout = js_sys::Reflect::get(&js_sys::global(), &"setTimeout".into())
+            .ok()
+            .and_then(|f| f.dyn_into::<js_sys::Function>().ok());
+        let scheduled = set_timeout.is_some_and(|set_timeout| {
+            set_timeout
+                .call2(
+                    &JsValue::NULL,
+                    &resolve,
+                    &JsValue::from_f64(duration.as_millis() as f64),
+                )
+                .is_ok()
+        });
+        if !scheduled {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
+/// Root of the origin private file system
+async fn root_directory() -> Result<FileSystemDirectoryHandle> {
+    // `navigator` is a `WorkerNavigator` in workers; both have `storage`
+    let navigator: Navigator = js_sys::Reflect::get(&js_sys::global(), &"navigator".into())
+        .map_err(|e| js_error("access", "navigator", e))?
+        .unchecked_into();
+    let root = JsFuture::from(navigator.storage().get_directory())
+        .await
+        .map_err(|e| js_error("open", "origin private file system", e))?;
+    Ok(root.unchecked_into())
+}
+
+fn is_not_found(error: &JsValue) -> bool {
+    error
+        .dyn_ref::<DomException>()
+        .is_some_and(|e| e.name() == "NotFoundError")
+}
+
+fn js_error(action: &str, target: &str, error: JsValue) -> StorageError {
+    StorageError::BackendError(format!("Failed to {} {}: {:?}", action, target, error))
+}
@@ -0,0 +1,245 @@
+//! Keyset pagination of tree tables
+//!
+//! Reads a table whose rows form a tree (a parent column) page by page in
+//! depth-first order, siblings ordered by their sort key, after a
+//! `holon_api::TreeCursor`. Each page is a single indexed query on the parent and
+//! sort key columns, however deep into the tree it starts. Queries compiled from
+//! PRQL page with `limit_after <n> sortkey:@cursor` instead (see
+//! `BackendEngine::query_page`).
+
+use std::collections::HashMap;
+
+use holon_api::{TREE_PARENT_COLUMN, TREE_SORT_COLUMN, TreeCursor, Value};
+
+use crate::storage::turso::TursoBackend;
+use crate::storage::types::{Result, StorageEntity, StorageError};
+
+/// Pagination of a tree table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreePagination {
+    pub table: String,
+    pub id_column: String,
+    /// Column holding the ID of a row's parent
+    pub parent_column: String,
+    /// Column ordering the children of a parent
+    pub sort_column: String,
+}
+
+impl TreePagination {
+    /// Pagination of `table` with the default `id`, `parent_id` and `sort_key` columns
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            id_column: "id".to_string(),
+            parent_column: TREE_PARENT_COLUMN.to_string(),
+            sort_column: TREE_SORT_COLUMN.to_string(),
+        }
+    }
+
+    pub fn with_id_column(mut self, id_column: impl Into<String>) -> Self {
+        self.id_column = id_column.into();
+        self
+    }
+
+    pub fn with_parent_column(mut self, parent_column: impl Into<String>) -> Self {
+        self.parent_column = parent_column.into();
+        self
+    }
+
+    pub fn with_sort_column(mut self, sort_column: impl Into<String>) -> Self {
+        self.sort_column = sort_column.into();
+        self
+    }
+
+    /// SQL and parameters selecting the first `limit` rows after `cursor`
+    ///
+    /// Rows after the cursor's deepest level come first, then those after the level
+    /// above it, and so on; siblings are ordered by sort key.
+    pub fn page_sql(&self, cursor: &TreeCursor, limit: usize) -> (String, HashMap<String, Value>) {
+        let mut params = HashMap::new();
+        let mut conditions = Vec::new();
+        let mut levels = Vec::new();
+        for (index, level) in cursor.levels.iter().rev().enumerate() {
+            let parent_condition = match &level.parent_id {
+                Some(parent_id) => {
+                    params.insert(
+                        format!("parent_{}", index),
+                        Value::String(parent_id.clone()),
+                    );
+                    format!("{} = $parent_{}", self.parent_column, index)
+                }
+                None => format!("{} IS NULL", self.parent_column),
+            };
+            params.insert(
+                format!("sort_key_{}", index),
+                Value::String(level.sort_key.clone()),
+            );
+            conditions.push(format!(
+                "({} AND {} > $sort_key_{})",
+                parent_condition, self.sort_column, index
+            ));
+            levels.push(format!("WHEN {} THEN {}", parent_condition, index));
+        }
+        if conditions.is_empty() {
+            // A cursor without levels has nothing after it
+            conditions.push("0".to_string());
+            levels.push("WHEN 1 THEN 0".to_string());
+        }
+
+        let sql = format!(
+            "SELECT * FROM {table} WHERE {conditions} ORDER BY CASE {levels} END, {sort} LIMIT {limit}",
+            table = self.table,
+            conditions = conditions.join(" OR "),
+            levels = levels.join(" "),
+            sort = self.sort_column,
+        );
+        (sql, params)
+    }
+
+    /// The first `limit` rows after `cursor`, and the cursor after them
+    pub async fn page(
+        &self,
+        backend: &TursoBackend,
+        cursor: &TreeCursor,
+        limit: usize,
+    ) -> Result<(Vec<StorageEntity>, TreeCursor)> {
+        let (sql, params) = self.page_sql(cursor, limit);
+        let rows = backend.execute_sql(&sql, params).await?;
+        let mut next = cursor.clone();
+        for row in &rows {
+            next.advance(
+                row.get(&self.parent_column).and_then(Value::as_string),
+                row.get(&self.sort_column)
+                    .and_then(Value::as_string)
+                    .unwrap_or_default(),
+            );
+        }
+        Ok((rows, next))
+    }
+
+    /// The cursor right after the row `id`, from its parent chain
+    ///
+    /// The next page starts with the row's children, e.g. to continue reading below
+    /// a row the user scrolled to.
+    pub async fn cursor_after(&self, backend: &TursoBackend, id: &str) -> Result<TreeCursor> {
+        let sql = format!(
+            "SELECT {} AS parent_id, {} AS sort_key FROM {} WHERE {} = $id",
+            self.parent_column, self.sort_column, self.table, self.id_column
+        );
+        let mut levels = Vec::new();
+        let mut current = Some(id.to_string());
+        while let Some(row_id) = current {
+            let rows = backend
+                .execute_sql(
+                    &sql,
+                    HashMap::from([("id".to_string(), Value::String(row_id.clone()))]),
+                )
+                .await?;
+            let row = rows.first().ok_or_else(|| StorageError::NotFound {
+                entity: self.table.clone(),
+                id: row_id.clone(),
+            })?;
+            let parent_id = row.get("parent_id").and_then(Value::as_string_owned);
+            if parent_id.as_deref() == Some(row_id.as_str()) || levels.len() > MAX_TREE_DEPTH {
+                return Err(StorageError::QueryError(format!(
+                    "Cycle in the parent chain of {} in {}",
+                    id, self.table
+                )));
+            }
+            levels.push(holon_api::CursorLevel {
+                parent_id: parent_id.clone(),
+                sort_key: row
+                    .get("sort_key")
+                    .and_then(Value::as_string_owned)
+                    .unwrap_or_default(),
+            });
+            current = parent_id;
+        }
+        levels.reverse();
+
+        Ok(TreeCursor { levels }.descend(id))
+    }
+}
+
+/// Deepest parent chain `cursor_after` follows before assuming a cycle
+const MAX_TREE_DEPTH: usize = 10_000;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// root
+    /// ├─ a
+    /// │  ├─ a1
+    /// │  └─ a2
+    /// └─ b
+    ///    └─ b1
+    async fn tree_backend() -> TursoBackend {
+        let backend = TursoBackend::new_in_memory().await.unwrap();
+        backend
+            .execute_sql(
+                "CREATE TABLE blocks (id TEXT PRIMARY KEY, parent_id TEXT, sort_key TEXT)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+            .execute_sql(
+                "INSERT INTO blocks (id, parent_id, sort_key) VALUES \
+                 ('root', NULL, 'a0'), ('a', 'root', 'a0'), ('b', 'root', 'a1'), \
+                 ('a1', 'a', 'a0'), ('a2', 'a', 'a1'), ('b1', 'b', 'a0')",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+    }
+
+    fn ids(rows: &[StorageEntity]) -> Vec<&str> {
+        rows.iter()
+            .map(|row| row["id"].as_string().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_pages_follow_the_parent_chain() {
+        let backend = tree_backend().await;
+        let pagination = TreePagination::new("blocks");
+
+        // Expanding a and reading two of its children
+        let cursor = TreeCursor::children_of(Some("root"));
+        let (rows, cursor) = pagination.page(&backend, &cursor, 1).await.unwrap();
+        assert_eq!(ids(&rows), vec!["a"]);
+        let (rows, cursor) = pagination
+            .page(&backend, &cursor.descend("a"), 1)
+            .await
+            .unwrap();
+        assert_eq!(ids(&rows), vec!["a1"]);
+
+        // The rest of a's children come before a's siblings
+        let (rows, cursor) = pagination.page(&backend, &cursor, 10).await.unwrap();
+        assert_eq!(ids(&rows), vec!["a2", "b"]);
+        let (rows, _) = pagination.page(&backend, &cursor, 10).await.unwrap();
+        assert!(rows.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cursor_after_row() {
+        let backend = tree_backend().await;
+        let pagination = TreePagination::new("blocks");
+
+        let cursor = pagination.cursor_after(&backend, "a1").await.unwrap();
+        let (rows, _) = pagination.page(&backend, &cursor, 10).await.unwrap();
+        assert_eq!(ids(&rows), vec!["a2", "b"]);
+
+        // Continuing below b reads its children first
+        let cursor = pagination.cursor_after(&backend, "b").await.unwrap();
+        let (rows, _) = pagination.page(&backend, &cursor, 10).await.unwrap();
+        assert_eq!(ids(&rows), vec!["b1"]);
+
+        assert!(matches!(
+            pagination.cursor_after(&backend, "missing").await,
+            Err(StorageError::NotFound { .. })
+        ));
+    }
+}
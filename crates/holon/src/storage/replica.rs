@@ -0,0 +1,378 @@
+//! Remote replica sync
+//!
+//! With a `ReplicaConfig`, the local database is an embedded replica of a remote
+//! libSQL/Turso database: `ReplicaSyncer` pushes local changes to the remote and pulls
+//! remote changes on a schedule, through the database's embedded replica sync API
+//! (`ReplicaClient`; `TursoReplicaClient` with feature `remote-replica`).
+//!
+//! A new local replica is bootstrapped from the remote on the first sync. Until that
+//! succeeds, `check_writable` refuses local writes, so they can't be lost to or
+//! conflict with the downloaded database. `ReplicaStatus` is part of the diagnostics.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::{Mutex, watch};
+use tracing::{debug, info, warn};
+
+use crate::storage::types::{Result, StorageError};
+
+/// Time between two scheduled syncs of a replica
+pub const DEFAULT_REPLICA_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Remote database the local database replicates
+#[derive(Clone)]
+pub struct ReplicaConfig {
+    sync_url: String,
+    auth_token: String,
+    sync_interval: Duration,
+}
+
+impl ReplicaConfig {
+    pub fn new(sync_url: impl Into<String>, auth_token: impl Into<String>) -> Self {
+        Self {
+            sync_url: sync_url.into(),
+            auth_token: auth_token.into(),
+            sync_interval: DEFAULT_REPLICA_SYNC_INTERVAL,
+        }
+    }
+
+    pub fn with_sync_interval(mut self, sync_interval: Duration) -> Self {
+        self.sync_interval = sync_interval;
+        self
+    }
+
+    pub fn sync_url(&self) -> &str {
+        &self.sync_url
+    }
+
+    pub fn auth_token(&self) -> &str {
+        &self.auth_token
+    }
+
+    pub fn sync_interval(&self) -> Duration {
+        self.sync_interval
+    }
+}
+
+impl std::fmt::Debug for ReplicaConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReplicaConfig")
+            .field("sync_url", &self.sync_url)
+            .field("auth_token", &"<redacted>")
+            .field("sync_interval", &self.sync_interval)
+            .finish()
+    }
+}
+
+/// Embedded replica sync API of the database
+#[async_trait]
+pub trait ReplicaClient: Send + Sync {
+    /// Whether the local replica has never been synced with the remote
+    async fn needs_bootstrap(&self) -> Result<bool>;
+
+    /// Download the remote database into the new local replica
+    async fn bootstrap(&self) -> Result<()>;
+
+    /// Send local changes to the remote
+    async fn push(&self) -> Result<()>;
+
+    /// Apply remote changes to the local replica
+    async fn pull(&self) -> Result<()>;
+}
+
+/// Replication state, for diagnostics and sync indicators
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplicaStatus {
+    pub sync_url: String,
+    /// The local replica was downloaded from the remote; local writes are refused until then
+    pub bootstrapped: bool,
+    /// A sync is in progress
+    pub syncing: bool,
+    /// Timestamps are Unix timestamps in milliseconds
+    pub last_sync_at: Option<i64>,
+    pub last_error_at: Option<i64>,
+    /// Error of the last sync, if it failed
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+    /// Successful syncs since the app started
+    pub syncs: u64,
+}
+
+/// Keeps the local database in sync with its remote
+pub struct ReplicaSyncer {
+    client: Arc<dyn ReplicaClient>,
+    config: ReplicaConfig,
+    status: watch::Sender<ReplicaStatus>,
+    /// Held while a sync is in progress
+    sync_lock: Mutex<()>,
+}
+
+impl ReplicaSyncer {
+    pub async fn new(client: Arc<dyn ReplicaClient>, config: ReplicaConfig) -> Result<Self> {
+        let bootstrapped = !client.needs_bootstrap().await?;
+        let status = ReplicaStatus {
+            sync_url: config.sync_url.clone(),
+            bootstrapped,
+            ..Default::default()
+        };
+        Ok(Self {
+            client,
+            config,
+            status: watch::channel(status).0,
+            sync_lock: Mutex::new(()),
+        })
+    }
+
+    /// Open the database file at `db_path` as an embedded replica of `config`'s remote
+    #[cfg(feature = "remote-replica")]
+    pub async fn open(db_path: &std::path::Path, config: ReplicaConfig) -> Result<Self> {
+        let client = TursoReplicaClient::open(db_path, &config).await?;
+        Self::new(Arc::new(client), config).await
+    }
+
+    /// Remote replicas need feature `remote-replica`
+    #[cfg(not(feature = "remote-replica"))]
+    pub async fn open(_db_path: &std::path::Path, _config: ReplicaConfig) -> Result<Self> {
+        Err(StorageError::BackendError(
+            "Remote replicas are not supported by this build (feature `remote-replica`)"
+                .to_string(),
+        ))
+    }
+
+    pub fn config(&self) -> &ReplicaConfig {
+        &self.config
+    }
+
+    pub fn status(&self) -> ReplicaStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Receive every status change, e.g. to show a sync indicator
+    pub fn subscribe_status(&self) -> watch::Receiver<ReplicaStatus> {
+        self.status.subscribe()
+    }
+
+    /// `Err(StorageError::ReplicaBootstrapping)` until the replica is bootstrapped
+    pub fn check_writable(&self) -> Result<()> {
+        if self.status.borrow().bootstrapped {
+            Ok(())
+        } else {
+            Err(StorageError::ReplicaBootstrapping)
+        }
+    }
+
+    /// Sync with the remote now: bootstrap a new replica, otherwise push local changes
+    /// and pull remote ones
+    ///
+    /// Fails if a sync is already in progress. Returns the status after the sync.
+    pub async fn sync(&self) -> Result<ReplicaStatus> {
+        let _syncing = self.sync_lock.try_lock().map_err(|_| {
+            StorageError::BackendError("Replica sync is already running".to_string())
+        })?;
+        self.status.send_modify(|status| status.syncing = true);
+
+        let bootstrapping = !self.status.borrow().bootstrapped;
+        let result = if bootstrapping {
+            info!(
+                "[ReplicaSyncer] Bootstrapping replica from {}",
+                self.config.sync_url
+            );
+            self.client.bootstrap().await
+        } else {
+            match self.client.push().await {
+                Ok(()) => self.client.pull().await,
+                Err(e) => Err(e),
+            }
+        };
+
+        let now = chrono::Utc::now().timestamp_millis();
+        self.status.send_modify(|status| {
+            status.syncing = false;
+            match &result {
+                Ok(()) => {
+                    status.bootstrapped = true;
+                    status.last_sync_at = Some(now);
+                    status.last_error = None;
+                    status.consecutive_failures = 0;
+                    status.syncs += 1;
+                }
+                Err(e) => {
+                    status.last_error_at = Some(now);
+                    status.last_error = Some(e.to_string());
+                    status.consecutive_failures += 1;
+                }
+            }
+        });
+        match result {
+            Ok(()) if bootstrapping => info!("[ReplicaSyncer] Replica bootstrapped"),
+            Ok(()) => debug!("[ReplicaSyncer] Synced with {}", self.config.sync_url),
+            Err(e) => {
+                warn!("[ReplicaSyncer] Sync failed: {}", e);
+                return Err(e);
+            }
+        }
+        Ok(self.status())
+    }
+
+    /// Sync right away (bootstrapping a new replica) and then every `sync_interval`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.sync_interval);
+            loop {
+                interval.tick().await;
+                // Failures are recorded in the status and retried on the next tick
+                let _ = self.sync().await;
+            }
+        })
+    }
+}
+
+/// `ReplicaClient` on the sync engine of the Turso bindings
+#[cfg(feature = "remote-replica")]
+pub struct TursoReplicaClient {
+    db: turso::sync::Database,
+}
+
+#[cfg(feature = "remote-replica")]
+impl TursoReplicaClient {
+    pub async fn open(db_path: &std::path::Path, config: &ReplicaConfig) -> Result<Self> {
+        let db_path = db_path
+            .to_str()
+            .ok_or_else(|| StorageError::DatabaseError("Invalid path".to_string()))?;
+        let db = turso::sync::Builder::new_remote(db_path)
+            .with_remote_url(config.sync_url())
+            .with_auth_token(config.auth_token())
+            .bootstrap_if_empty(false)
+            .build()
+            .await
+            .map_err(|e| StorageError::DatabaseError(e.to_string()))?;
+        Ok(Self { db })
+    }
+}
+
+#[cfg(feature = "remote-replica")]
+#[async_trait]
+impl ReplicaClient for TursoReplicaClient {
+    async fn needs_bootstrap(&self) -> Result<bool> {
+        let stats = self
+            .db
+            .stats()
+            .await
+            .map_err(|e| StorageError::DatabaseError(e.to_string()))?;
+        Ok(stats.last_pull_unix_time.is_none())
+    }
+
+    async fn bootstrap(&self) -> Result<()> {
+        self.pull().await
+    }
+
+    async fn push(&self) -> Result<()> {
+        self.db
+            .push()
+            .await
+            .map_err(|e| StorageError::DatabaseError(format!("Replica push failed: {}", e)))
+    }
+
+    async fn pull(&self) -> Result<()> {
+        self.db
+            .pull()
+            .await
+            .map(|_| ())
+            .map_err(|e| StorageError::DatabaseError(format!("Replica pull failed: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex as StdMutex;
+
+    /// Records the calls and fails while `failing` is set
+    #[derive(Default)]
+    struct FakeClient {
+        needs_bootstrap: bool,
+        failing: StdMutex<bool>,
+        calls: StdMutex<Vec<&'static str>>,
+    }
+
+    impl FakeClient {
+        fn call(&self, name: &'static str) -> Result<()> {
+            self.calls.lock().unwrap().push(name);
+            if *self.failing.lock().unwrap() {
+                Err(StorageError::BackendError("offline".to_string()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ReplicaClient for FakeClient {
+        async fn needs_bootstrap(&self) -> Result<bool> {
+            Ok(self.needs_bootstrap)
+        }
+
+        async fn bootstrap(&self) -> Result<()> {
+            self.call("bootstrap")
+        }
+
+        async fn push(&self) -> Result<()> {
+            self.call("push")
+        }
+
+        async fn pull(&self) -> Result<()> {
+            self.call("pull")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_writes_refused_until_bootstrapped() {
+        let client = Arc::new(FakeClient {
+            needs_bootstrap: true,
+            failing: StdMutex::new(true),
+            ..Default::default()
+        });
+        let syncer = ReplicaSyncer::new(client.clone(), ReplicaConfig::new("libsql://db", "t"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            syncer.check_writable(),
+            Err(StorageError::ReplicaBootstrapping)
+        ));
+
+        assert!(syncer.sync().await.is_err());
+        let status = syncer.status();
+        assert!(!status.bootstrapped);
+        assert_eq!(status.consecutive_failures, 1);
+        assert_eq!(status.last_error.as_deref(), Some("Backend error: offline"));
+        assert!(syncer.check_writable().is_err());
+
+        *client.failing.lock().unwrap() = false;
+        let status = syncer.sync().await.unwrap();
+        assert!(status.bootstrapped);
+        assert_eq!(status.consecutive_failures, 0);
+        assert_eq!(status.last_error, None);
+        assert!(syncer.check_writable().is_ok());
+
+        syncer.sync().await.unwrap();
+        assert_eq!(
+            *client.calls.lock().unwrap(),
+            vec!["bootstrap", "bootstrap", "push", "pull"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_existing_replica_is_writable() {
+        let syncer = ReplicaSyncer::new(
+            Arc::new(FakeClient::default()),
+            ReplicaConfig::new("libsql://db", "secret"),
+        )
+        .await
+        .unwrap();
+        assert!(syncer.check_writable().is_ok());
+        assert!(!format!("{:?}", syncer.config()).contains("secret"));
+    }
+}
@@ -0,0 +1,528 @@
+//! Progress rollups of tree entities
+//!
+//! A rollup adds `child_count`, `completed_count` and `percent_done` columns to a
+//! table whose rows form a tree (a parent column) and can be completed. They count
+//! the direct children of each row and how many of them are completed, and are
+//! stored in the table itself, so `(progress value:this.percent_done max:100)` reads
+//! them like any other column instead of aggregating the tree in every query.
+//!
+//! Tables of entities with `parent_id` and `completed` fields get a rollup when
+//! their schema is initialized. The counts are maintained incrementally: a
+//! materialized view over each table's id, parent and completion reports changes,
+//! and only the changed rows and their old and new parents are recounted.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use holon_api::{BatchWithMetadata, DELETED_AT_COLUMN, Schema, Value};
+
+use crate::references::content_source;
+use crate::storage::turso::{ChangeData, RowChange, TursoBackend};
+use crate::storage::types::Result;
+
+/// Prefix of the materialized views that report tree changes
+pub const ROLLUP_SOURCE_VIEW_PREFIX: &str = "rollup_src_";
+
+/// Number of direct children of a row
+pub const CHILD_COUNT_COLUMN: &str = "child_count";
+
+/// Number of completed direct children of a row
+pub const COMPLETED_COUNT_COLUMN: &str = "completed_count";
+
+/// Completed share of the direct children in percent (0-100); NULL without children
+pub const PERCENT_DONE_COLUMN: &str = "percent_done";
+
+const ROLLUP_COLUMNS: [&str; 3] = [
+    CHILD_COUNT_COLUMN,
+    COMPLETED_COUNT_COLUMN,
+    PERCENT_DONE_COLUMN,
+];
+
+/// Definition of the rollup of a tree table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rollup {
+    pub table: String,
+    pub id_column: String,
+    /// Column holding the ID of a row's parent
+    pub parent_column: String,
+    /// Boolean column marking a row completed
+    pub completed_column: String,
+}
+
+impl Rollup {
+    /// Rollup of `table` with the default `id`, `parent_id` and `completed` columns
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            id_column: "id".to_string(),
+            parent_column: "parent_id".to_string(),
+            completed_column: "completed".to_string(),
+        }
+    }
+
+    pub fn with_id_column(mut self, id_column: impl Into<String>) -> Self {
+        self.id_column = id_column.into();
+        self
+    }
+
+    pub fn with_parent_column(mut self, parent_column: impl Into<String>) -> Self {
+        self.parent_column = parent_column.into();
+        self
+    }
+
+    pub fn with_completed_column(mut self, completed_column: impl Into<String>) -> Self {
+        self.completed_column = completed_column.into();
+        self
+    }
+
+    /// The rollup of a schema with `parent_id` and `completed` fields, if it has them
+    pub fn from_schema(schema: &Schema) -> Option<Self> {
+        let has_field = |name: &str| schema.fields.iter().any(|f| f.name == name);
+        if !has_field("parent_id") || !has_field("completed") {
+            return None;
+        }
+        let id_column = schema
+            .fields
+            .iter()
+            .find(|f| f.primary_key)
+            .map(|f| f.name.as_str())
+            .unwrap_or("id");
+        Some(Self::new(&schema.table_name).with_id_column(id_column))
+    }
+
+    /// `SELECT id, parent_id, completed` of the table, watched for changes
+    fn source_select_sql(&self) -> String {
+        format!(
+            "SELECT {} AS id, {} AS parent_id, {} AS completed FROM {}",
+            self.id_column, self.parent_column, self.completed_column, self.table
+        )
+    }
+}
+
+/// Registry of rollups by table, with the parent of each row seen so far
+#[derive(Clone, Debug, Default)]
+pub struct Rollups {
+    tables: Arc<RwLock<HashMap<String, Rollup>>>,
+    /// Parent of each row by table and row ID, to recount the old parent after a move
+    parents: Arc<RwLock<HashMap<(String, String), Option<String>>>>,
+}
+
+impl Rollups {
+    /// Register a rollup, adding its columns to the table if missing
+    pub async fn register(&self, backend: &TursoBackend, rollup: Rollup) -> Result<()> {
+        let rows = backend
+            .execute_sql(
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = $name",
+                HashMap::from([("name".to_string(), Value::String(rollup.table.clone()))]),
+            )
+            .await?;
+        let create_sql = rows
+            .first()
+            .and_then(|row| row.get("sql"))
+            .and_then(|sql| sql.as_string_owned())
+            .unwrap_or_default();
+        for column in ROLLUP_COLUMNS {
+            if !create_sql.contains(column) {
+                backend
+                    .execute_sql(
+                        &format!("ALTER TABLE {} ADD COLUMN {} INTEGER", rollup.table, column),
+                        HashMap::new(),
+                    )
+                    .await?;
+            }
+        }
+
+        self.tables
+            .write()
+            .unwrap()
+            .insert(rollup.table.clone(), rollup);
+        Ok(())
+    }
+
+    /// Register the rollup of `schema`, if it describes a tree of completable rows
+    pub async fn register_schema(&self, backend: &TursoBackend, schema: &Schema) -> Result<()> {
+        match Rollup::from_schema(schema) {
+            Some(rollup) => self.register(backend, rollup).await,
+            None => Ok(()),
+        }
+    }
+
+    pub fn table_names(&self) -> Vec<String> {
+        self.tables.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Rollup of `table`, if registered
+    pub fn rollup(&self, table: &str) -> Option<Rollup> {
+        self.tables.read().unwrap().get(table).cloned()
+    }
+
+    /// Recount the children of one row
+    ///
+    /// Returns whether its rollup columns changed.
+    pub async fn recount_row(&self, backend: &TursoBackend, table: &str, id: &str) -> Result<bool> {
+        let Some(rollup) = self.rollup(table) else {
+            return Ok(false);
+        };
+        let params = HashMap::from([("id".to_string(), Value::String(id.to_string()))]);
+        let not_deleted = if backend.soft_delete_tables().contains(table) {
+            format!(" AND {} IS NULL", DELETED_AT_COLUMN)
+        } else {
+            String::new()
+        };
+        let counts = backend
+            .execute_sql(
+                &format!(
+                    "SELECT COUNT(*) AS child_count, \
+                     COALESCE(SUM(CASE WHEN {} THEN 1 ELSE 0 END), 0) AS completed_count \
+                     FROM {} WHERE {} = $id{}",
+                    rollup.completed_column, table, rollup.parent_column, not_deleted
+                ),
+                params.clone(),
+            )
+            .await?;
+        let count = |column: &str| {
+            counts
+                .first()
+                .and_then(|row| row.get(column))
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0)
+        };
+        let (child_count, completed_count) =
+            (count(CHILD_COUNT_COLUMN), count(COMPLETED_COUNT_COLUMN));
+        let percent_done = match child_count {
+            0 => Value::Null,
+            _ => Value::Integer(completed_count * 100 / child_count),
+        };
+        let values = [
+            Value::Integer(child_count),
+            Value::Integer(completed_count),
+            percent_done,
+        ];
+
+        let current = backend
+            .execute_sql(
+                &format!(
+                    "SELECT {} FROM {} WHERE {} = $id",
+                    ROLLUP_COLUMNS.join(", "),
+                    table,
+                    rollup.id_column
+                ),
+                params,
+            )
+            .await?;
+        let Some(current) = current.first() else {
+            return Ok(false);
+        };
+        if ROLLUP_COLUMNS
+            .iter()
+            .zip(&values)
+            .all(|(column, value)| current.get(*column).unwrap_or(&Value::Null) == value)
+        {
+            return Ok(false);
+        }
+
+        let mut params: HashMap<String, Value> = ROLLUP_COLUMNS
+            .iter()
+            .map(|column| column.to_string())
+            .zip(values)
+            .collect();
+        params.insert("id".to_string(), Value::String(id.to_string()));
+        backend
+            .execute_sql(
+                &format!(
+                    "UPDATE {} SET {} WHERE {} = $id",
+                    table,
+                    ROLLUP_COLUMNS
+                        .iter()
+                        .map(|column| format!("{} = ${}", column, column))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    rollup.id_column
+                ),
+                params,
+            )
+            .await?;
+        Ok(true)
+    }
+
+    /// Recount every row of every registered table
+    ///
+    /// Returns the number of rows whose rollup changed.
+    pub async fn recount_all(&self, backend: &TursoBackend) -> Result<usize> {
+        let mut changed = 0;
+        for table in self.table_names() {
+            let Some(rollup) = self.rollup(&table) else {
+                continue;
+            };
+            let rows = backend
+                .execute_sql(&rollup.source_select_sql(), HashMap::new())
+                .await?;
+            for row in rows {
+                let Some(id) = row.get("id").and_then(|v| v.as_string_owned()) else {
+                    continue;
+                };
+                self.set_parent(&table, &id, parent_of(&row));
+                if self.recount_row(backend, &table, &id).await? {
+                    changed += 1;
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Recount the rows touched by a change batch of a `rollup_src_` view
+    ///
+    /// A changed row is recounted along with its old and new parent.
+    pub async fn apply_batch(
+        &self,
+        backend: &TursoBackend,
+        batch: &BatchWithMetadata<RowChange>,
+    ) -> Result<usize> {
+        let Some(table) = batch
+            .metadata
+            .relation_name
+            .strip_prefix(ROLLUP_SOURCE_VIEW_PREFIX)
+        else {
+            return Ok(0);
+        };
+
+        let mut touched: Vec<String> = Vec::new();
+        for row_change in &batch.inner.items {
+            let Some(id) = content_source::changed_entity_id(&row_change.change) else {
+                continue;
+            };
+            let id = id.to_string();
+            let parent = match &row_change.change {
+                ChangeData::Created { data, .. } | ChangeData::Updated { data, .. } => {
+                    Some(parent_of(data))
+                }
+                ChangeData::ColumnChange { columns, .. } => match columns.get("parent_id") {
+                    Some(_) => Some(parent_of(columns)),
+                    None => self.parent(table, &id),
+                },
+                ChangeData::Deleted { .. } => None,
+            };
+
+            let old_parent = self.parent(table, &id).flatten();
+            match &parent {
+                Some(parent) => self.set_parent(table, &id, parent.clone()),
+                None => {
+                    self.parents
+                        .write()
+                        .unwrap()
+                        .remove(&(table.to_string(), id.clone()));
+                }
+            }
+            touched.extend(old_parent);
+            touched.extend(parent.flatten());
+            touched.push(id);
+        }
+
+        touched.sort();
+        touched.dedup();
+        let mut changed = 0;
+        for id in touched {
+            if self.recount_row(backend, table, &id).await? {
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Recount all rows, then follow tree changes in the background
+    ///
+    /// Only rollups registered before this is called are watched.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self, backend: Arc<tokio::sync::RwLock<TursoBackend>>) {
+        use std::ops::ControlFlow;
+
+        tokio::spawn(async move {
+            let views = {
+                let backend = backend.read().await;
+                match self.recount_all(&backend).await {
+                    Ok(0) => {}
+                    Ok(changed) => tracing::debug!("[Rollups] Recounted {} rows", changed),
+                    Err(e) => tracing::warn!("[Rollups] Failed to recount rollups: {}", e),
+                }
+                self.table_names()
+                    .into_iter()
+                    .filter_map(|table| {
+                        let rollup = self.rollup(&table)?;
+                        Some((
+                            format!("{}{}", ROLLUP_SOURCE_VIEW_PREFIX, table),
+                            rollup.source_select_sql(),
+                        ))
+                    })
+                    .collect::<Vec<_>>()
+            };
+            if views.is_empty() {
+                return;
+            }
+
+            let watched = content_source::follow_views(&backend, &views, |batch| {
+                let (rollups, backend) = (self.clone(), backend.clone());
+                async move {
+                    let backend = backend.read().await;
+                    if let Err(e) = rollups.apply_batch(&backend, &batch).await {
+                        tracing::warn!("[Rollups] Failed to update rollups: {}", e);
+                    }
+                    ControlFlow::Continue(())
+                }
+            })
+            .await;
+            if let Err(e) = watched {
+                tracing::warn!("[Rollups] Failed to watch trees: {}", e);
+            }
+        });
+    }
+
+    /// Last seen parent of a row: None if unknown, Some(None) for a root
+    fn parent(&self, table: &str, id: &str) -> Option<Option<String>> {
+        self.parents
+            .read()
+            .unwrap()
+            .get(&(table.to_string(), id.to_string()))
+            .cloned()
+    }
+
+    fn set_parent(&self, table: &str, id: &str, parent: Option<String>) {
+        self.parents
+            .write()
+            .unwrap()
+            .insert((table.to_string(), id.to_string()), parent);
+    }
+}
+
+/// Parent ID in a row of a `rollup_src_` view (None for roots)
+fn parent_of(row: &HashMap<String, Value>) -> Option<String> {
+    row.get("parent_id")
+        .and_then(|v| v.as_string_owned())
+        .filter(|parent| !parent.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn tree_backend() -> TursoBackend {
+        let backend = TursoBackend::new_in_memory().await.unwrap();
+        backend
+            .execute_sql(
+                "CREATE TABLE tasks (id TEXT PRIMARY KEY, parent_id TEXT, completed INTEGER)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+            .execute_sql(
+                "INSERT INTO tasks (id, parent_id, completed) VALUES \
+                 ('root', NULL, 0), ('a', 'root', 1), ('b', 'root', 0), ('c', 'root', 1), ('a1', 'a', 0)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+    }
+
+    async fn rollup_of(backend: &TursoBackend, id: &str) -> (Value, Value, Value) {
+        let rows = backend
+            .execute_sql(
+                "SELECT child_count, completed_count, percent_done FROM tasks WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+            )
+            .await
+            .unwrap();
+        (
+            rows[0]["child_count"].clone(),
+            rows[0]["completed_count"].clone(),
+            rows[0]["percent_done"].clone(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_recount_all() {
+        let backend = tree_backend().await;
+        let rollups = Rollups::default();
+        rollups
+            .register(&backend, Rollup::new("tasks"))
+            .await
+            .unwrap();
+
+        // Every row gets counts, only rows with children a percentage
+        assert_eq!(rollups.recount_all(&backend).await.unwrap(), 5);
+        assert_eq!(
+            rollup_of(&backend, "root").await,
+            (Value::Integer(3), Value::Integer(2), Value::Integer(66))
+        );
+        assert_eq!(
+            rollup_of(&backend, "a").await,
+            (Value::Integer(1), Value::Integer(0), Value::Integer(0))
+        );
+        assert_eq!(
+            rollup_of(&backend, "b").await,
+            (Value::Integer(0), Value::Integer(0), Value::Null)
+        );
+
+        // Nothing changed, nothing is written
+        assert!(
+            !rollups
+                .recount_row(&backend, "tasks", "root")
+                .await
+                .unwrap()
+        );
+
+        // Registering again keeps the columns
+        rollups
+            .register(&backend, Rollup::new("tasks"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_move_recounts_old_parent() {
+        let backend = tree_backend().await;
+        let rollups = Rollups::default();
+        rollups
+            .register(&backend, Rollup::new("tasks"))
+            .await
+            .unwrap();
+        rollups.recount_all(&backend).await.unwrap();
+
+        // "c" moves from "root" to "a"
+        backend
+            .execute_sql(
+                "UPDATE tasks SET parent_id = 'a' WHERE id = 'c'",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        let change = RowChange {
+            relation_name: "rollup_src_tasks".to_string(),
+            change: ChangeData::ColumnChange {
+                id: "c".to_string(),
+                columns: HashMap::from([("parent_id".to_string(), Value::String("a".to_string()))]),
+                origin: holon_api::ChangeOrigin::remote_with_trace(None, None),
+            },
+        };
+        let batch = BatchWithMetadata {
+            inner: holon_api::Batch {
+                items: vec![change],
+            },
+            metadata: holon_api::BatchMetadata {
+                relation_name: "rollup_src_tasks".to_string(),
+                trace_context: None,
+                sync_token: None,
+                full_snapshot: false,
+            },
+        };
+        assert_eq!(rollups.apply_batch(&backend, &batch).await.unwrap(), 2);
+        assert_eq!(
+            rollup_of(&backend, "root").await,
+            (Value::Integer(2), Value::Integer(1), Value::Integer(50))
+        );
+        assert_eq!(
+            rollup_of(&backend, "a").await,
+            (Value::Integer(2), Value::Integer(1), Value::Integer(50))
+        );
+    }
+}
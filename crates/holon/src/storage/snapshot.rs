@@ -0,0 +1,294 @@
+//! Portable copies of a database's tables and rows.
+//!
+//! A `DatabaseSnapshot` holds the schema SQL and all rows of the user tables of a
+//! `TursoBackend`. The web target keeps its database in memory and persists it
+//! as a snapshot (see `storage::opfs`); restoring a snapshot into an empty
+//! backend recreates the tables, their rows and then indexes and triggers.
+//!
+//! Views are not part of a snapshot: `watch_query` recreates the materialized
+//! views it needs, and turso's internal tables belong to them.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::turso::TursoBackend;
+use crate::storage::types::{Result, StorageEntity, StorageError};
+use holon_api::Value;
+
+/// Tables that are never copied (SQLite's own and turso's view state)
+const INTERNAL_TABLE_PATTERNS: [&str; 2] = ["sqlite_%", "__turso_internal%"];
+
+/// Rows of one table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableSnapshot {
+    pub name: String,
+    pub rows: Vec<StorageEntity>,
+}
+
+/// Schema and contents of a database's user tables
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseSnapshot {
+    /// `CREATE TABLE` statements
+    pub tables_sql: Vec<String>,
+    /// `CREATE INDEX` and `CREATE TRIGGER` statements, run after the rows are inserted
+    pub indexes_sql: Vec<String>,
+    pub tables: Vec<TableSnapshot>,
+}
+
+impl DatabaseSnapshot {
+    /// Copy the schema and rows of all user tables of `backend`
+    pub async fn capture(backend: &TursoBackend) -> Result<Self> {
+        let entries = backend
+            .execute_sql(
+                &format!(
+                    "SELECT type, name, sql FROM sqlite_master \
+                     WHERE type IN ('table', 'index', 'trigger') AND sql IS NOT NULL AND {} \
+                     ORDER BY name",
+                    user_objects_filter()
+                ),
+                HashMap::new(),
+            )
+            .await?;
+
+        let mut snapshot = Self::default();
+        for entry in entries {
+            let (Some(kind), Some(name), Some(sql)) = (
+                entry.get("type").and_then(Value::as_string),
+                entry.get("name").and_then(Value::as_string),
+                entry.get("sql").and_then(Value::as_string),
+            ) else {
+                continue;
+            };
+
+            if kind == "table" {
+                let rows = backend
+                    .execute_sql(&format!("SELECT * FROM \"{}\"", name), HashMap::new())
+                    .await?;
+                snapshot.tables_sql.push(sql.to_string());
+                snapshot.tables.push(TableSnapshot {
+                    name: name.to_string(),
+                    rows,
+                });
+            } else {
+                snapshot.indexes_sql.push(sql.to_string());
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Recreate the snapshot's tables and rows in `backend`
+    ///
+    /// Tables that already exist keep their schema; rows are inserted with
+    /// `INSERT OR REPLACE`, so restoring into a non-empty database overwrites
+    /// rows with the same primary key.
+    pub async fn restore(&self, backend: &TursoBackend) -> Result<()> {
+        for sql in &self.tables_sql {
+            backend
+                .execute_sql(&if_not_exists(sql), HashMap::new())
+                .await?;
+        }
+
+        for table in &self.tables {
+            for row in &table.rows {
+                let mut columns: Vec<_> = row.keys().cloned().collect();
+                columns.sort();
+                let sql = format!(
+                    "INSERT OR REPLACE INTO \"{}\" ({}) VALUES ({})",
+                    table.name,
+                    columns
+                        .iter()
+                        .map(|c| format!("\"{}\"", c))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    columns
+                        .iter()
+                        .map(|c| format!("${}", c))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                backend.execute_sql(&sql, row.clone()).await?;
+            }
+        }
+
+        for sql in &self.indexes_sql {
+            backend
+                .execute_sql(&if_not_exists(sql), HashMap::new())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Make `backend`'s tables hold exactly the snapshot's rows
+    ///
+    /// Rows of all user tables are deleted before the snapshot is restored, except
+    /// those of `keep_tables`, which are neither cleared nor restored. Tables
+    /// created after the snapshot are left empty.
+    pub async fn replace(&self, backend: &TursoBackend, keep_tables: &[&str]) -> Result<()> {
+        let tables = backend
+            .execute_sql(
+                &format!(
+                    "SELECT name FROM sqlite_master WHERE type = 'table' AND {}",
+                    user_objects_filter()
+                ),
+                HashMap::new(),
+            )
+            .await?;
+        for name in tables
+            .iter()
+            .filter_map(|row| row.get("name").and_then(Value::as_string))
+            .filter(|name| !keep_tables.contains(name))
+        {
+            backend
+                .execute_sql(&format!("DELETE FROM \"{}\"", name), HashMap::new())
+                .await?;
+        }
+
+        let mut snapshot = self.clone();
+        snapshot
+            .tables
+            .retain(|table| !keep_tables.contains(&table.name.as_str()));
+        snapshot.restore(backend).await
+    }
+
+    /// Serialize to JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| StorageError::SerializationError(e.to_string()))
+    }
+
+    /// Deserialize from JSON written by `to_json`
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| StorageError::SerializationError(e.to_string()))
+    }
+}
+
+/// `sqlite_master` condition excluding internal tables
+fn user_objects_filter() -> String {
+    INTERNAL_TABLE_PATTERNS
+        .iter()
+        .map(|pattern| format!("name NOT LIKE '{}'", pattern))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// Make a `CREATE TABLE/INDEX/TRIGGER` statement idempotent
+fn if_not_exists(sql: &str) -> String {
+    for create in [
+        "CREATE TABLE ",
+        "CREATE INDEX ",
+        "CREATE UNIQUE INDEX ",
+        "CREATE TRIGGER ",
+    ] {
+        if let Some(rest) = sql.strip_prefix(create) {
+            if rest.starts_with("IF NOT EXISTS") {
+                break;
+            }
+            return format!("{}IF NOT EXISTS {}", create, rest);
+        }
+    }
+    sql.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() {
+        let source = TursoBackend::new_in_memory()
+            .await
+            .expect("Failed to create backend");
+        source
+            .execute_sql(
+                "CREATE TABLE notes (id TEXT PRIMARY KEY, title TEXT, position INTEGER)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        source
+            .execute_sql(
+                "CREATE INDEX idx_notes_title ON notes (title)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        source
+            .execute_sql(
+                "INSERT INTO notes (id, title, position) VALUES ('n-1', 'First', 1), ('n-2', NULL, 2)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+
+        let snapshot = DatabaseSnapshot::capture(&source).await.unwrap();
+        assert_eq!(snapshot.tables.len(), 1);
+        assert_eq!(snapshot.indexes_sql.len(), 1);
+        let snapshot = DatabaseSnapshot::from_json(&snapshot.to_json().unwrap()).unwrap();
+
+        let target = TursoBackend::new_in_memory()
+            .await
+            .expect("Failed to create backend");
+        snapshot.restore(&target).await.unwrap();
+        // Restoring twice is harmless
+        snapshot.restore(&target).await.unwrap();
+
+        let rows = target
+            .execute_sql("SELECT * FROM notes ORDER BY position", HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get("title"), Some(&Value::String("First".into())));
+        assert_eq!(rows[1].get("position"), Some(&Value::Integer(2)));
+    }
+
+    #[tokio::test]
+    async fn test_replace_keeps_listed_tables() {
+        let backend = TursoBackend::new_in_memory()
+            .await
+            .expect("Failed to create backend");
+        for sql in [
+            "CREATE TABLE notes (id TEXT PRIMARY KEY)",
+            "CREATE TABLE history (id TEXT PRIMARY KEY)",
+            "INSERT INTO notes (id) VALUES ('n-1')",
+            "INSERT INTO history (id) VALUES ('h-1')",
+        ] {
+            backend.execute_sql(sql, HashMap::new()).await.unwrap();
+        }
+        let snapshot = DatabaseSnapshot::capture(&backend).await.unwrap();
+
+        for sql in [
+            "INSERT INTO notes (id) VALUES ('n-2')",
+            "INSERT INTO history (id) VALUES ('h-2')",
+        ] {
+            backend.execute_sql(sql, HashMap::new()).await.unwrap();
+        }
+        snapshot.replace(&backend, &["history"]).await.unwrap();
+
+        let notes = backend
+            .execute_sql("SELECT id FROM notes", HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(notes.len(), 1);
+        let history = backend
+            .execute_sql("SELECT id FROM history", HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 2);
+    }
+
+    #[test]
+    fn test_if_not_exists() {
+        assert_eq!(
+            if_not_exists("CREATE TABLE t (id TEXT)"),
+            "CREATE TABLE IF NOT EXISTS t (id TEXT)"
+        );
+        assert_eq!(
+            if_not_exists("CREATE TABLE IF NOT EXISTS t (id TEXT)"),
+            "CREATE TABLE IF NOT EXISTS t (id TEXT)"
+        );
+        assert_eq!(
+            if_not_exists("CREATE UNIQUE INDEX i ON t (id)"),
+            "CREATE UNIQUE INDEX IF NOT EXISTS i ON t (id)"
+        );
+    }
+}
@@ -0,0 +1,419 @@
+//! Periodic snapshots and point-in-time restore
+//!
+//! `SnapshotStore` writes a `DatabaseSnapshot` of the whole workspace to a
+//! snapshot directory (`snapshot-<created_at>.json`), periodically once `spawn`ed,
+//! and prunes old snapshots by its `SnapshotRetention`: the newest snapshots are
+//! kept, plus the newest one of each of the last days.
+//!
+//! `restore` rolls the workspace back to a snapshot. The operation log and audit
+//! trail are not rolled back: operations logged after the snapshot stay for
+//! inspection, marked `rolled_back` so they can no longer be undone.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::storage::snapshot::DatabaseSnapshot;
+use crate::storage::turso::TursoBackend;
+use crate::storage::types::{Result, StorageError};
+use holon_api::Value;
+use holon_core::operation_log::OperationStatus;
+
+/// Version of the snapshot file format
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Tables that keep their current rows when a snapshot is restored
+pub const PRESERVED_TABLES: [&str; 2] = ["operations", "operation_audit_log"];
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Which snapshots are kept when pruning
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotRetention {
+    /// Number of newest snapshots kept
+    pub keep_latest: usize,
+    /// Number of days (UTC, newest first) whose newest snapshot is kept
+    pub keep_daily: usize,
+}
+
+impl Default for SnapshotRetention {
+    fn default() -> Self {
+        Self {
+            keep_latest: 24,
+            keep_daily: 7,
+        }
+    }
+}
+
+impl SnapshotRetention {
+    /// Creation times of the snapshots to delete
+    pub fn expired(&self, created_at: &[i64]) -> Vec<i64> {
+        let mut newest_first = created_at.to_vec();
+        newest_first.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut days = HashSet::new();
+        newest_first
+            .into_iter()
+            .enumerate()
+            .filter(|(index, at)| {
+                let latest = *index < self.keep_latest;
+                let daily = days.len() < self.keep_daily && days.insert(at.div_euclid(DAY_MS));
+                !latest && !daily
+            })
+            .map(|(_, at)| at)
+            .collect()
+    }
+}
+
+/// Where and how often snapshots are taken
+#[derive(Debug, Clone)]
+pub struct SnapshotConfig {
+    pub directory: PathBuf,
+    pub interval: Duration,
+    pub retention: SnapshotRetention,
+}
+
+impl SnapshotConfig {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            interval: Duration::from_secs(60 * 60),
+            retention: SnapshotRetention::default(),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_retention(mut self, retention: SnapshotRetention) -> Self {
+        self.retention = retention;
+        self
+    }
+}
+
+/// A snapshot file in the snapshot directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotInfo {
+    /// When the snapshot was taken (Unix timestamp in milliseconds); identifies it
+    pub created_at: i64,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+}
+
+/// Result of restoring a snapshot
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreSummary {
+    pub created_at: i64,
+    pub tables: usize,
+    pub rows: usize,
+    /// Logged operations after the snapshot, now marked `rolled_back`
+    pub rolled_back_operations: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    version: u32,
+    created_at: i64,
+    database: DatabaseSnapshot,
+}
+
+/// Snapshots of the workspace in a directory
+pub struct SnapshotStore {
+    backend: Arc<RwLock<TursoBackend>>,
+    config: SnapshotConfig,
+}
+
+impl SnapshotStore {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>, config: SnapshotConfig) -> Self {
+        Self { backend, config }
+    }
+
+    pub fn config(&self) -> &SnapshotConfig {
+        &self.config
+    }
+
+    /// Snapshot the database now and prune expired snapshots
+    pub async fn take_snapshot(&self, now: i64) -> Result<SnapshotInfo> {
+        let database = {
+            let backend = self.backend.read().await;
+            DatabaseSnapshot::capture(&backend).await?
+        };
+        let file = SnapshotFile {
+            version: SNAPSHOT_FORMAT_VERSION,
+            created_at: now,
+            database,
+        };
+        let json = serde_json::to_string(&file)
+            .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+
+        std::fs::create_dir_all(&self.config.directory)?;
+        let path = self.path(now);
+        // Written aside and renamed, so a crash never leaves a truncated snapshot
+        let partial = path.with_extension("json.partial");
+        std::fs::write(&partial, json.as_bytes())?;
+        std::fs::rename(&partial, &path)?;
+        info!("[Snapshots] Wrote {}", path.display());
+
+        self.prune()?;
+        Ok(SnapshotInfo {
+            created_at: now,
+            path,
+            size_bytes: json.len() as u64,
+        })
+    }
+
+    /// Snapshots in the directory, newest first
+    pub fn snapshots(&self) -> Result<Vec<SnapshotInfo>> {
+        let entries = match std::fs::read_dir(&self.config.directory) {
+            Ok(entries) => entries,
+            // No snapshot taken yet
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut snapshots: Vec<SnapshotInfo> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let created_at = snapshot_created_at(&path)?;
+                let size_bytes = entry.metadata().ok()?.len();
+                Some(SnapshotInfo {
+                    created_at,
+                    path,
+                    size_bytes,
+                })
+            })
+            .collect();
+        snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(snapshots)
+    }
+
+    /// Delete the snapshots the retention policy doesn't keep; returns how many
+    pub fn prune(&self) -> Result<usize> {
+        let created_at: Vec<i64> = self.snapshots()?.iter().map(|s| s.created_at).collect();
+        let expired = self.config.retention.expired(&created_at);
+        for at in &expired {
+            std::fs::remove_file(self.path(*at))?;
+            debug!("[Snapshots] Pruned snapshot {}", at);
+        }
+        Ok(expired.len())
+    }
+
+    /// Roll the workspace back to the snapshot taken at `created_at`
+    ///
+    /// All tables get the snapshot's rows, except the operation log and audit
+    /// trail: operations logged after the snapshot are kept and marked
+    /// `rolled_back`.
+    pub async fn restore(&self, created_at: i64) -> Result<RestoreSummary> {
+        let json = std::fs::read_to_string(self.path(created_at)).map_err(|e| {
+            StorageError::BackendError(format!("Snapshot {} not found: {}", created_at, e))
+        })?;
+        let file: SnapshotFile = serde_json::from_str(&json)
+            .map_err(|e| StorageError::SerializationError(e.to_string()))?;
+        if file.version > SNAPSHOT_FORMAT_VERSION {
+            return Err(StorageError::SerializationError(format!(
+                "Snapshot format {} is newer than supported format {}",
+                file.version, SNAPSHOT_FORMAT_VERSION
+            )));
+        }
+
+        let backend = self.backend.read().await;
+        file.database.replace(&backend, &PRESERVED_TABLES).await?;
+        let rolled_back_operations = mark_rolled_back(&backend, file.created_at).await?;
+
+        let restored: Vec<_> = file
+            .database
+            .tables
+            .iter()
+            .filter(|table| !PRESERVED_TABLES.contains(&table.name.as_str()))
+            .collect();
+        let summary = RestoreSummary {
+            created_at: file.created_at,
+            tables: restored.len(),
+            rows: restored.iter().map(|table| table.rows.len()).sum(),
+            rolled_back_operations,
+        };
+        info!(
+            "[Snapshots] Restored snapshot {} ({} rows, {} operations rolled back)",
+            summary.created_at, summary.rows, summary.rolled_back_operations
+        );
+        Ok(summary)
+    }
+
+    /// Take a snapshot every `interval` until the store is dropped
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let store = Arc::downgrade(&self);
+        let interval = self.config.interval;
+        drop(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            // The first tick completes immediately; the first snapshot is due after one interval
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(store) = store.upgrade() else {
+                    break;
+                };
+                if let Err(e) = store
+                    .take_snapshot(chrono::Utc::now().timestamp_millis())
+                    .await
+                {
+                    warn!("[Snapshots] Failed to take snapshot: {}", e);
+                }
+            }
+        })
+    }
+
+    fn path(&self, created_at: i64) -> PathBuf {
+        self.config
+            .directory
+            .join(format!("snapshot-{}.json", created_at))
+    }
+}
+
+/// Creation time of a `snapshot-<created_at>.json` file
+fn snapshot_created_at(path: &Path) -> Option<i64> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("snapshot-")?
+        .strip_suffix(".json")?
+        .parse()
+        .ok()
+}
+
+/// Mark operations logged after `created_at` as rolled back; returns how many
+async fn mark_rolled_back(backend: &TursoBackend, created_at: i64) -> Result<usize> {
+    let params = HashMap::from([
+        ("created_at".to_string(), Value::Integer(created_at)),
+        (
+            "status".to_string(),
+            Value::String(OperationStatus::RolledBack.as_str().to_string()),
+        ),
+    ]);
+    let rows = backend
+        .execute_sql(
+            "SELECT COUNT(*) AS count FROM operations WHERE created_at > $created_at AND status != $status",
+            params.clone(),
+        )
+        .await?;
+    let count = rows
+        .first()
+        .and_then(|row| row.get("count"))
+        .and_then(Value::as_i64)
+        .unwrap_or(0);
+    if count > 0 {
+        backend
+            .execute_sql(
+                "UPDATE operations SET status = $status WHERE created_at > $created_at",
+                params,
+            )
+            .await?;
+    }
+    Ok(count as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    const HOUR_MS: i64 = 60 * 60 * 1000;
+
+    #[test]
+    fn test_retention_keeps_latest_and_daily() {
+        let retention = SnapshotRetention {
+            keep_latest: 2,
+            keep_daily: 2,
+        };
+        // Hourly snapshots over three days
+        let created_at: Vec<i64> = (0..72).map(|hour| hour * HOUR_MS).collect();
+        let expired = retention.expired(&created_at);
+        let kept: Vec<i64> = created_at
+            .iter()
+            .copied()
+            .filter(|at| !expired.contains(at))
+            .collect();
+        // The two newest, and the newest of the day before
+        assert_eq!(kept, vec![47 * HOUR_MS, 70 * HOUR_MS, 71 * HOUR_MS]);
+    }
+
+    #[tokio::test]
+    async fn test_restore_rolls_back_tables_but_keeps_later_operations() {
+        let backend = memory_backend().await;
+        let log = crate::core::operation_log::OperationLogStore::new(backend.clone());
+        log.initialize_schema().await.unwrap();
+        {
+            let backend = backend.read().await;
+            for sql in [
+                "CREATE TABLE notes (id TEXT PRIMARY KEY, title TEXT)",
+                "INSERT INTO notes (id, title) VALUES ('n-1', 'Before')",
+            ] {
+                backend.execute_sql(sql, HashMap::new()).await.unwrap();
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::new(backend.clone(), SnapshotConfig::new(dir.path()));
+        let now = chrono::Utc::now().timestamp_millis();
+        let snapshot = store.take_snapshot(now - 1_000).await.unwrap();
+        assert_eq!(store.snapshots().unwrap(), vec![snapshot.clone()]);
+
+        {
+            let backend = backend.read().await;
+            for sql in [
+                "UPDATE notes SET title = 'After' WHERE id = 'n-1'",
+                "INSERT INTO notes (id, title) VALUES ('n-2', 'New')",
+            ] {
+                backend.execute_sql(sql, HashMap::new()).await.unwrap();
+            }
+        }
+        use holon_core::OperationLogOperations;
+        log.log_operation(
+            holon_api::Operation::new("notes", "set_field", "Edit", HashMap::new()),
+            holon_core::UndoAction::Irreversible,
+        )
+        .await
+        .unwrap();
+
+        let summary = store.restore(snapshot.created_at).await.unwrap();
+        assert_eq!(summary.rows, 1);
+        assert_eq!(summary.rolled_back_operations, 1);
+
+        let backend = backend.read().await;
+        let notes = backend
+            .execute_sql("SELECT * FROM notes", HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].get("title"), Some(&Value::String("Before".into())));
+        let operations = backend
+            .execute_sql("SELECT status FROM operations", HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(operations.len(), 1);
+        assert_eq!(
+            operations[0].get("status"),
+            Some(&Value::String("rolled_back".into()))
+        );
+    }
+
+    #[test]
+    fn test_snapshot_file_names() {
+        assert_eq!(
+            snapshot_created_at(Path::new("/s/snapshot-1700000000000.json")),
+            Some(1_700_000_000_000)
+        );
+        assert_eq!(
+            snapshot_created_at(Path::new("/s/snapshot-1.json.partial")),
+            None
+        );
+        assert_eq!(snapshot_created_at(Path::new("/s/notes.json")), None);
+    }
+}
@@ -0,0 +1,76 @@
+//! Soft delete (trash) support
+//!
+//! Tables created by `QueryableCache` carry a storage-managed `deleted_at` column.
+//! `trash` sets it, `restore` clears it, and compiled queries hide rows where it is
+//! set unless the query contains an `include_deleted` step. Trashed rows are
+//! hard-deleted by the purge job once they are older than `TrashConfig::retention`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Registry of tables that have a `deleted_at` column, mapped to their entity names
+///
+/// Cheap to clone; all clones share the same map.
+#[derive(Clone, Debug, Default)]
+pub struct SoftDeleteTables {
+    tables: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl SoftDeleteTables {
+    pub fn register(&self, table_name: &str, entity_name: &str) {
+        self.tables
+            .write()
+            .unwrap()
+            .insert(table_name.to_string(), entity_name.to_string());
+    }
+
+    pub fn contains(&self, table_name: &str) -> bool {
+        self.tables.read().unwrap().contains_key(table_name)
+    }
+
+    /// Names of all registered tables
+    pub fn table_names(&self) -> HashSet<String> {
+        self.tables.read().unwrap().keys().cloned().collect()
+    }
+
+    /// (table name, entity name) of all registered tables
+    pub fn entries(&self) -> Vec<(String, String)> {
+        self.tables
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(table, entity)| (table.clone(), entity.clone()))
+            .collect()
+    }
+}
+
+/// Configuration of the trash purge job
+#[derive(Clone, Debug)]
+pub struct TrashConfig {
+    /// Trashed entities older than this are deleted permanently
+    pub retention: Duration,
+    /// How often the purge job runs
+    pub purge_interval: Duration,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            retention: Duration::from_secs(30 * 24 * 60 * 60),
+            purge_interval: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl TrashConfig {
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    pub fn with_purge_interval(mut self, purge_interval: Duration) -> Self {
+        self.purge_interval = purge_interval;
+        self
+    }
+}
@@ -0,0 +1,650 @@
+//! Workspace statistics
+//!
+//! Daily aggregates of task tables, kept in tables that dashboards query directly
+//! instead of aggregating the full history on every render:
+//!
+//! ```text
+//! from stats_daily | filter entity_name == "todoist_tasks" | sort {-day} | take 30
+//! from stats_project_daily | group project_id (aggregate {created = sum created})
+//! ```
+//!
+//! - `stats_daily`: tasks created and completed per day, with the average time from
+//!   creation to completion of the tasks completed that day
+//! - `stats_project_daily`: tasks created and completed per project and day
+//!
+//! Days are UTC dates. Tables of entities with `created_at`, `completed` and
+//! `completed_at` fields are tracked when their schema is initialized. The aggregates
+//! are rebuilt on start and then maintained incrementally: a materialized view over
+//! each table reports changes, and the task's previous contribution is replaced by
+//! its new one.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+use holon_api::{BatchWithMetadata, HasSchema, Schema, Value};
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::references::content_source;
+use crate::storage::turso::{ChangeData, RowChange, TursoBackend};
+use crate::storage::types::{Result, StorageEntity};
+
+/// Prefix of the materialized views that report changes of tracked tables
+pub const STATS_SOURCE_VIEW_PREFIX: &str = "stats_src_";
+
+/// Tasks created and completed on a day
+///
+/// Table name: `stats_daily`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Entity)]
+#[entity(name = "stats_daily", short_name = "daily_stats")]
+pub struct DailyStats {
+    /// `{entity_name}:{day}`
+    #[primary_key]
+    pub id: String,
+    #[indexed]
+    pub entity_name: String,
+    /// UTC date, `YYYY-MM-DD`
+    #[indexed]
+    pub day: String,
+    pub created: i64,
+    pub completed: i64,
+    /// Completed tasks with a known creation time
+    pub latency_count: i64,
+    /// Sum of the time from creation to completion (milliseconds)
+    pub total_latency_ms: i64,
+    /// `total_latency_ms / latency_count`, NULL without completions
+    pub avg_completion_latency_ms: Option<i64>,
+}
+
+/// Tasks of a project created and completed on a day
+///
+/// Table name: `stats_project_daily`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Entity)]
+#[entity(name = "stats_project_daily", short_name = "project_daily_stats")]
+pub struct ProjectDailyStats {
+    /// `{entity_name}:{project_id}:{day}`
+    #[primary_key]
+    pub id: String,
+    #[indexed]
+    pub entity_name: String,
+    /// Empty for tasks without a project
+    #[indexed]
+    pub project_id: String,
+    /// UTC date, `YYYY-MM-DD`
+    #[indexed]
+    pub day: String,
+    pub created: i64,
+    pub completed: i64,
+}
+
+/// Definition of the statistics of a task table
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsSource {
+    pub table: String,
+    pub id_column: String,
+    /// Column holding the creation time (RFC 3339)
+    pub created_column: String,
+    /// Boolean column marking a task completed
+    pub completed_column: String,
+    /// Column holding the completion time (RFC 3339)
+    pub completed_at_column: String,
+    /// Column grouping tasks in `stats_project_daily`, if any
+    pub project_column: Option<String>,
+}
+
+impl StatsSource {
+    /// Statistics of `table` with the default `id`, `created_at`, `completed` and
+    /// `completed_at` columns, without projects
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            id_column: "id".to_string(),
+            created_column: "created_at".to_string(),
+            completed_column: "completed".to_string(),
+            completed_at_column: "completed_at".to_string(),
+            project_column: None,
+        }
+    }
+
+    pub fn with_id_column(mut self, id_column: impl Into<String>) -> Self {
+        self.id_column = id_column.into();
+        self
+    }
+
+    pub fn with_created_column(mut self, created_column: impl Into<String>) -> Self {
+        self.created_column = created_column.into();
+        self
+    }
+
+    pub fn with_completed_column(mut self, completed_column: impl Into<String>) -> Self {
+        self.completed_column = completed_column.into();
+        self
+    }
+
+    pub fn with_completed_at_column(mut self, completed_at_column: impl Into<String>) -> Self {
+        self.completed_at_column = completed_at_column.into();
+        self
+    }
+
+    pub fn with_project_column(mut self, project_column: impl Into<String>) -> Self {
+        self.project_column = Some(project_column.into());
+        self
+    }
+
+    /// The statistics of a schema with `created_at`, `completed` and `completed_at`
+    /// fields (grouped by `project_id` if present), if it has them
+    pub fn from_schema(schema: &Schema) -> Option<Self> {
+        let has_field = |name: &str| schema.fields.iter().any(|f| f.name == name);
+        if !has_field("created_at") || !has_field("completed") || !has_field("completed_at") {
+            return None;
+        }
+        let id_column = schema
+            .fields
+            .iter()
+            .find(|f| f.primary_key)
+            .map(|f| f.name.as_str())
+            .unwrap_or("id");
+        let source = Self::new(&schema.table_name).with_id_column(id_column);
+        Some(if has_field("project_id") {
+            source.with_project_column("project_id")
+        } else {
+            source
+        })
+    }
+
+    /// `SELECT id, created_at, completed, completed_at, project_id` of the table
+    fn source_select_sql(&self) -> String {
+        format!(
+            "SELECT {} AS id, {} AS created_at, {} AS completed, {} AS completed_at, {} AS project_id FROM {}",
+            self.id_column,
+            self.created_column,
+            self.completed_column,
+            self.completed_at_column,
+            self.project_column.as_deref().unwrap_or("NULL"),
+            self.table
+        )
+    }
+}
+
+/// What a task adds to the aggregates
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Contribution {
+    project_id: String,
+    created_day: Option<String>,
+    completed_day: Option<String>,
+    latency_ms: Option<i64>,
+}
+
+impl Contribution {
+    /// Contribution of a row of `StatsSource::source_select_sql`
+    fn of(row: &StorageEntity) -> Self {
+        let datetime = |column: &str| match row.get(column) {
+            Some(Value::String(s)) | Some(Value::DateTime(s)) => holon_api::parse_datetime(s),
+            _ => None,
+        };
+        let completed = match row.get("completed") {
+            Some(Value::Boolean(completed)) => *completed,
+            Some(value) => value.as_i64().unwrap_or(0) != 0,
+            None => false,
+        };
+        let created_at = datetime("created_at");
+        let completed_at = datetime("completed_at").filter(|_| completed);
+        let day = |dt: chrono::DateTime<chrono::Utc>| dt.format("%Y-%m-%d").to_string();
+        Self {
+            project_id: row
+                .get("project_id")
+                .and_then(|v| v.as_string_owned())
+                .unwrap_or_default(),
+            created_day: created_at.map(day),
+            completed_day: completed_at.map(day),
+            latency_ms: created_at
+                .zip(completed_at)
+                .map(|(created_at, completed_at)| {
+                    (completed_at - created_at).num_milliseconds().max(0)
+                }),
+        }
+    }
+}
+
+/// Changes of the counters of one aggregate row
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Counts {
+    created: i64,
+    completed: i64,
+    latency_count: i64,
+    total_latency_ms: i64,
+}
+
+impl Counts {
+    fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Changes of the aggregates of one table, by day and by (project, day)
+#[derive(Debug, Default)]
+struct Deltas {
+    daily: HashMap<String, Counts>,
+    project_daily: HashMap<(String, String), Counts>,
+}
+
+impl Deltas {
+    /// Add (`sign` 1) or remove (`sign` -1) a task's contribution
+    fn add(&mut self, contribution: &Contribution, sign: i64) {
+        if let Some(day) = &contribution.created_day {
+            self.daily.entry(day.clone()).or_default().created += sign;
+            self.project_daily
+                .entry((contribution.project_id.clone(), day.clone()))
+                .or_default()
+                .created += sign;
+        }
+        if let Some(day) = &contribution.completed_day {
+            let daily = self.daily.entry(day.clone()).or_default();
+            daily.completed += sign;
+            if let Some(latency_ms) = contribution.latency_ms {
+                daily.latency_count += sign;
+                daily.total_latency_ms += sign * latency_ms;
+            }
+            self.project_daily
+                .entry((contribution.project_id.clone(), day.clone()))
+                .or_default()
+                .completed += sign;
+        }
+    }
+}
+
+/// Registry of statistics sources by table, with the contribution of every tracked task
+#[derive(Clone, Debug, Default)]
+pub struct WorkspaceStats {
+    tables: Arc<RwLock<HashMap<String, StatsSource>>>,
+    contributions: Arc<Mutex<HashMap<(String, String), Contribution>>>,
+}
+
+impl WorkspaceStats {
+    /// Register a source, creating the statistics tables if missing
+    pub async fn register(&self, backend: &TursoBackend, source: StatsSource) -> Result<()> {
+        for schema in [DailyStats::schema(), ProjectDailyStats::schema()] {
+            backend
+                .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+                .await?;
+            for index_sql in schema.to_index_sql() {
+                backend.execute_sql(&index_sql, HashMap::new()).await?;
+            }
+        }
+
+        self.tables
+            .write()
+            .unwrap()
+            .insert(source.table.clone(), source);
+        Ok(())
+    }
+
+    /// Register the source of `schema`, if it describes tasks with creation and completion times
+    pub async fn register_schema(&self, backend: &TursoBackend, schema: &Schema) -> Result<()> {
+        match StatsSource::from_schema(schema) {
+            Some(source) => self.register(backend, source).await,
+            None => Ok(()),
+        }
+    }
+
+    pub fn table_names(&self) -> Vec<String> {
+        self.tables.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Source of `table`, if registered
+    pub fn source(&self, table: &str) -> Option<StatsSource> {
+        self.tables.read().unwrap().get(table).cloned()
+    }
+
+    /// Recompute the aggregates of `table` from all its tasks
+    ///
+    /// Returns the number of tracked tasks.
+    pub async fn rebuild(&self, backend: &TursoBackend, table: &str) -> Result<usize> {
+        let Some(source) = self.source(table) else {
+            return Ok(0);
+        };
+        let rows = backend
+            .execute_sql(&source.source_select_sql(), HashMap::new())
+            .await?;
+
+        let mut deltas = Deltas::default();
+        let mut contributions = HashMap::new();
+        for row in rows {
+            let Some(id) = row.get("id").and_then(|v| v.as_string_owned()) else {
+                continue;
+            };
+            let contribution = Contribution::of(&row);
+            deltas.add(&contribution, 1);
+            contributions.insert(id, contribution);
+        }
+        let tracked = contributions.len();
+
+        let params = HashMap::from([("entity_name".to_string(), Value::String(table.to_string()))]);
+        for stats_table in ["stats_daily", "stats_project_daily"] {
+            backend
+                .execute_sql(
+                    &format!(
+                        "DELETE FROM {} WHERE entity_name = $entity_name",
+                        stats_table
+                    ),
+                    params.clone(),
+                )
+                .await?;
+        }
+        write_deltas(backend, table, &deltas).await?;
+
+        let mut all = self.contributions.lock().unwrap();
+        all.retain(|(contribution_table, _), _| contribution_table != table);
+        all.extend(
+            contributions
+                .into_iter()
+                .map(|(id, contribution)| ((table.to_string(), id), contribution)),
+        );
+        Ok(tracked)
+    }
+
+    /// Recompute the aggregates of every registered table
+    pub async fn rebuild_all(&self, backend: &TursoBackend) -> Result<usize> {
+        let mut tracked = 0;
+        for table in self.table_names() {
+            tracked += self.rebuild(backend, &table).await?;
+        }
+        Ok(tracked)
+    }
+
+    /// Update the aggregates from a change batch of a `stats_src_` view
+    ///
+    /// Returns the number of tasks whose contribution changed.
+    pub async fn apply_batch(
+        &self,
+        backend: &TursoBackend,
+        batch: &BatchWithMetadata<RowChange>,
+    ) -> Result<usize> {
+        let Some(source) = batch
+            .metadata
+            .relation_name
+            .strip_prefix(STATS_SOURCE_VIEW_PREFIX)
+            .and_then(|table| self.source(table))
+        else {
+            return Ok(0);
+        };
+
+        let mut rows = Vec::new();
+        for row_change in &batch.inner.items {
+            let Some(id) = content_source::changed_entity_id(&row_change.change) else {
+                continue;
+            };
+            let contribution = match &row_change.change {
+                ChangeData::Created { data, .. } | ChangeData::Updated { data, .. } => {
+                    Some(Contribution::of(data))
+                }
+                ChangeData::ColumnChange { .. } => {
+                    content_source::load_view_row(backend, &source.source_select_sql(), id)
+                        .await?
+                        .as_ref()
+                        .map(Contribution::of)
+                }
+                ChangeData::Deleted { .. } => None,
+            };
+            rows.push((id.to_string(), contribution));
+        }
+
+        let mut deltas = Deltas::default();
+        let mut changed = 0;
+        {
+            let mut contributions = self.contributions.lock().unwrap();
+            for (id, contribution) in rows {
+                let key = (source.table.clone(), id);
+                let previous = match contribution {
+                    Some(contribution) => {
+                        let previous = contributions.insert(key, contribution.clone());
+                        if previous.as_ref() == Some(&contribution) {
+                            continue;
+                        }
+                        deltas.add(&contribution, 1);
+                        previous
+                    }
+                    None => contributions.remove(&key),
+                };
+                if let Some(previous) = previous {
+                    deltas.add(&previous, -1);
+                }
+                changed += 1;
+            }
+        }
+        write_deltas(backend, &source.table, &deltas).await?;
+        Ok(changed)
+    }
+
+    /// Rebuild all aggregates, then follow changes of the tracked tables in the background
+    ///
+    /// Only sources registered before this is called are watched.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self, backend: Arc<tokio::sync::RwLock<TursoBackend>>) {
+        use std::ops::ControlFlow;
+
+        tokio::spawn(async move {
+            let views = {
+                let backend = backend.read().await;
+                match self.rebuild_all(&backend).await {
+                    Ok(tracked) => {
+                        tracing::debug!("[WorkspaceStats] Aggregated {} tasks", tracked)
+                    }
+                    Err(e) => tracing::warn!("[WorkspaceStats] Failed to aggregate tasks: {}", e),
+                }
+                self.table_names()
+                    .into_iter()
+                    .filter_map(|table| {
+                        let source = self.source(&table)?;
+                        Some((
+                            format!("{}{}", STATS_SOURCE_VIEW_PREFIX, table),
+                            source.source_select_sql(),
+                        ))
+                    })
+                    .collect::<Vec<_>>()
+            };
+            if views.is_empty() {
+                return;
+            }
+
+            let watched = content_source::follow_views(&backend, &views, |batch| {
+                let (stats, backend) = (self.clone(), backend.clone());
+                async move {
+                    let backend = backend.read().await;
+                    if let Err(e) = stats.apply_batch(&backend, &batch).await {
+                        tracing::warn!("[WorkspaceStats] Failed to update statistics: {}", e);
+                    }
+                    ControlFlow::Continue(())
+                }
+            })
+            .await;
+            if let Err(e) = watched {
+                tracing::warn!("[WorkspaceStats] Failed to watch tasks: {}", e);
+            }
+        });
+    }
+}
+
+/// Add the counter changes of `table` to the statistics tables
+async fn write_deltas(backend: &TursoBackend, table: &str, deltas: &Deltas) -> Result<()> {
+    let daily_sql = "INSERT INTO stats_daily
+            (id, entity_name, day, created, completed, latency_count, total_latency_ms, avg_completion_latency_ms)
+        VALUES ($id, $entity_name, $day, $created, $completed, $latency_count, $total_latency_ms, $avg_completion_latency_ms)
+        ON CONFLICT(id) DO UPDATE SET
+            created = created + excluded.created,
+            completed = completed + excluded.completed,
+            latency_count = latency_count + excluded.latency_count,
+            total_latency_ms = total_latency_ms + excluded.total_latency_ms,
+            avg_completion_latency_ms = CASE
+                WHEN latency_count + excluded.latency_count > 0
+                THEN (total_latency_ms + excluded.total_latency_ms) / (latency_count + excluded.latency_count)
+            END";
+    for (day, counts) in &deltas.daily {
+        if counts.is_zero() {
+            continue;
+        }
+        let row = DailyStats {
+            id: format!("{}:{}", table, day),
+            entity_name: table.to_string(),
+            day: day.clone(),
+            created: counts.created,
+            completed: counts.completed,
+            latency_count: counts.latency_count,
+            total_latency_ms: counts.total_latency_ms,
+            avg_completion_latency_ms: (counts.latency_count > 0)
+                .then(|| counts.total_latency_ms / counts.latency_count),
+        };
+        backend
+            .execute_sql(daily_sql, row.to_entity().fields)
+            .await?;
+    }
+
+    let project_sql = "INSERT INTO stats_project_daily
+            (id, entity_name, project_id, day, created, completed)
+        VALUES ($id, $entity_name, $project_id, $day, $created, $completed)
+        ON CONFLICT(id) DO UPDATE SET
+            created = created + excluded.created,
+            completed = completed + excluded.completed";
+    for ((project_id, day), counts) in &deltas.project_daily {
+        if counts.is_zero() {
+            continue;
+        }
+        let row = ProjectDailyStats {
+            id: format!("{}:{}:{}", table, project_id, day),
+            entity_name: table.to_string(),
+            project_id: project_id.clone(),
+            day: day.clone(),
+            created: counts.created,
+            completed: counts.completed,
+        };
+        backend
+            .execute_sql(project_sql, row.to_entity().fields)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn tasks_backend() -> TursoBackend {
+        let backend = TursoBackend::new_in_memory().await.unwrap();
+        backend
+            .execute_sql(
+                "CREATE TABLE tasks (id TEXT PRIMARY KEY, project_id TEXT, created_at TEXT, completed INTEGER, completed_at TEXT)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+            .execute_sql(
+                "INSERT INTO tasks (id, project_id, created_at, completed, completed_at) VALUES \
+                 ('a', 'work', '2026-06-01T08:00:00Z', 1, '2026-06-02T08:00:00Z'), \
+                 ('b', 'work', '2026-06-01T10:00:00Z', 1, '2026-06-02T12:00:00Z'), \
+                 ('c', 'home', '2026-06-02T09:00:00Z', 0, NULL)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+    }
+
+    async fn daily(backend: &TursoBackend, day: &str) -> (Value, Value, Value) {
+        let rows = backend
+            .execute_sql(
+                "SELECT created, completed, avg_completion_latency_ms FROM stats_daily WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(format!("tasks:{}", day)))]),
+            )
+            .await
+            .unwrap();
+        let row = &rows[0];
+        (
+            row["created"].clone(),
+            row["completed"].clone(),
+            row["avg_completion_latency_ms"].clone(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_and_apply_changes() {
+        let backend = tasks_backend().await;
+        let stats = WorkspaceStats::default();
+        stats
+            .register(
+                &backend,
+                StatsSource::new("tasks").with_project_column("project_id"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(stats.rebuild_all(&backend).await.unwrap(), 3);
+
+        const HOUR_MS: i64 = 60 * 60 * 1000;
+        assert_eq!(
+            daily(&backend, "2026-06-01").await,
+            (Value::Integer(2), Value::Integer(0), Value::Null)
+        );
+        // Completed after 24 and 26 hours
+        assert_eq!(
+            daily(&backend, "2026-06-02").await,
+            (
+                Value::Integer(1),
+                Value::Integer(2),
+                Value::Integer(25 * HOUR_MS)
+            )
+        );
+
+        // "c" is completed on the next day
+        backend
+            .execute_sql(
+                "UPDATE tasks SET completed = 1, completed_at = '2026-06-03T09:00:00Z' WHERE id = 'c'",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        let change = RowChange {
+            relation_name: "stats_src_tasks".to_string(),
+            change: ChangeData::ColumnChange {
+                id: "c".to_string(),
+                columns: HashMap::from([("completed".to_string(), Value::Integer(1))]),
+                origin: holon_api::ChangeOrigin::remote_with_trace(None, None),
+            },
+        };
+        let batch = BatchWithMetadata {
+            inner: holon_api::Batch {
+                items: vec![change],
+            },
+            metadata: holon_api::BatchMetadata {
+                relation_name: "stats_src_tasks".to_string(),
+                trace_context: None,
+                sync_token: None,
+                full_snapshot: false,
+            },
+        };
+        assert_eq!(stats.apply_batch(&backend, &batch).await.unwrap(), 1);
+        assert_eq!(
+            daily(&backend, "2026-06-03").await,
+            (
+                Value::Integer(0),
+                Value::Integer(1),
+                Value::Integer(24 * HOUR_MS)
+            )
+        );
+        // Replaying the change doesn't count it twice
+        assert_eq!(stats.apply_batch(&backend, &batch).await.unwrap(), 0);
+
+        let rows = backend
+            .execute_sql(
+                "SELECT project_id, SUM(created) AS created, SUM(completed) AS completed \
+                 FROM stats_project_daily GROUP BY project_id ORDER BY project_id",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["project_id"], Value::String("home".to_string()));
+        assert_eq!(rows[0]["completed"], Value::Integer(1));
+        assert_eq!(rows[1]["created"], Value::Integer(2));
+        assert_eq!(rows[1]["completed"], Value::Integer(2));
+    }
+}
@@ -1,15 +1,17 @@
-use crate::core::datasource::{CrudOperations, DataSource, Result, UndoAction};
+use crate::core::datasource::{
+    CrudOperations, DataSource, HolonError, HolonResult, Result, UndoAction,
+};
 use crate::tasks::Task;
 use async_trait::async_trait;
-use holon_api::streaming::ChangeNotifications;
 use holon_api::Value;
+use holon_api::streaming::ChangeNotifications;
 use holon_api::{ApiError, Change, ChangeOrigin, StreamPosition};
 use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use tokio::sync::mpsc;
-use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
+use tokio_stream::{Stream, StreamExt, wrappers::ReceiverStream};
 
 #[derive(Clone)]
 pub struct InMemoryTaskStore {
@@ -123,6 +125,7 @@ impl ChangeNotifications<Task> for InMemoryTaskStore {
                             origin: ChangeOrigin::Remote {
                                 operation_id: None,
                                 trace_id: None,
+                                source: None,
                             },
                         })
                         .collect::<Vec<_>>()
@@ -188,7 +191,7 @@ impl DataSource<Task> for InMemoryTaskStore {
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl CrudOperations<Task> for InMemoryTaskStore {
-    async fn set_field(&self, id: &str, field: &str, value: Value) -> Result<UndoAction> {
+    async fn set_field(&self, id: &str, field: &str, value: Value) -> HolonResult<UndoAction> {
         let mut tasks = self
             .tasks
             .write()
@@ -239,6 +242,7 @@ impl CrudOperations<Task> for InMemoryTaskStore {
                 origin: ChangeOrigin::Local {
                     operation_id: None,
                     trace_id: None,
+                    source: None,
                 },
             });
 
@@ -251,11 +255,11 @@ impl CrudOperations<Task> for InMemoryTaskStore {
                 ),
             ))
         } else {
-            Err(format!("Task not found: {}", id).into())
+            Err(HolonError::not_found("task", id))
         }
     }
 
-    async fn create(&self, fields: HashMap<String, Value>) -> Result<(String, UndoAction)> {
+    async fn create(&self, fields: HashMap<String, Value>) -> HolonResult<(String, UndoAction)> {
         let id = fields
             .get("id")
             .and_then(|v| v.as_string().map(|s| s.to_string()))
@@ -305,6 +309,7 @@ impl CrudOperations<Task> for InMemoryTaskStore {
             origin: ChangeOrigin::Local {
                 operation_id: None,
                 trace_id: None,
+                source: None,
             },
         });
 
@@ -317,7 +322,7 @@ impl CrudOperations<Task> for InMemoryTaskStore {
         Ok((id, inverse))
     }
 
-    async fn delete(&self, id: &str) -> Result<UndoAction> {
+    async fn delete(&self, id: &str) -> HolonResult<UndoAction> {
         let mut tasks = self
             .tasks
             .write()
@@ -353,6 +358,7 @@ impl CrudOperations<Task> for InMemoryTaskStore {
                 origin: ChangeOrigin::Local {
                     operation_id: None,
                     trace_id: None,
+                    source: None,
                 },
             });
 
@@ -363,7 +369,7 @@ impl CrudOperations<Task> for InMemoryTaskStore {
                 create_fields,
             )))
         } else {
-            Err(format!("Task not found: {}", id).into())
+            Err(HolonError::not_found("task", id))
         }
     }
 }
@@ -0,0 +1,15 @@
+//! Fixtures shared by the tests of the database-backed stores
+
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use crate::storage::turso::TursoBackend;
+
+/// A new in-memory database, shareable between stores like the app's
+pub(crate) async fn memory_backend() -> Arc<RwLock<TursoBackend>> {
+    let backend = TursoBackend::new_in_memory()
+        .await
+        .expect("Failed to create backend");
+    Arc::new(RwLock::new(backend))
+}
@@ -2,24 +2,37 @@ use async_trait::async_trait;
 use serde_json;
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex, Semaphore};
 use tokio_stream::wrappers::ReceiverStream;
 #[cfg(target_family = "unix")]
 use turso_core::UnixIO;
-use turso_core::{Database, DatabaseOpts, MemoryIO, OpenFlags};
+use turso_core::{Database, DatabaseOpts, EncryptionOpts, MemoryIO, OpenFlags};
 
 use crate::api::{Change, ChangeOrigin};
 use crate::storage::{
     backend::StorageBackend,
+    computed::ComputedFields,
+    encryption::{DatabaseLock, EncryptionConfig, KeyFile},
+    rollups::Rollups,
     schema::{EntitySchema, FieldType},
+    soft_delete::SoftDeleteTables,
+    stats::WorkspaceStats,
     types::{Filter, Result, StorageEntity, StorageError},
+    urgency::UrgencyScores,
 };
+#[cfg(target_arch = "wasm32")]
+use crate::storage::{opfs::OpfsFile, snapshot::DatabaseSnapshot};
 use holon_api::{
-    Batch, BatchMetadata, BatchTraceContext, BatchWithMetadata, Value, CHANGE_ORIGIN_COLUMN,
+    changed_columns, Batch, BatchMetadata, BatchTraceContext, BatchWithMetadata, Value,
+    ARCHIVED_AT_COLUMN, CHANGE_ORIGIN_COLUMN, DELETED_AT_COLUMN,
 };
 
+/// Time `TursoBackend::schedule_persist` waits before saving the web target's database
+#[cfg(target_arch = "wasm32")]
+pub const PERSIST_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+
 /// Extract ChangeOrigin from row data's _change_origin column
 ///
 /// If the column is present and contains valid JSON, parse it as ChangeOrigin.
@@ -33,9 +46,18 @@ fn extract_change_origin_from_data(data: &StorageEntity) -> ChangeOrigin {
         .unwrap_or_else(|| ChangeOrigin::Remote {
             operation_id: None,
             trace_id: None,
+            source: None,
         })
 }
 
+/// Datetime text as stored in SQL: UTC (see `holon_api::format_datetime`), so that
+/// comparisons in SQL are chronological. Unparseable text is kept as is.
+fn datetime_text(s: &str) -> String {
+    holon_api::parse_datetime(s)
+        .map(|dt| holon_api::format_datetime(&dt))
+        .unwrap_or_else(|| s.to_string())
+}
+
 /// A change notification from a materialized view
 ///
 /// Note: The row_changes() method automatically coalesces DELETE+INSERT pairs
@@ -83,10 +105,16 @@ pub type ChangeData = Change<StorageEntity>;
 pub type RowChangeStream = ReceiverStream<BatchWithMetadata<RowChange>>;
 
 /// Batches and coalesces CDC events to prevent UI flicker from DELETE+INSERT pairs
+///
+/// When the deleted row's data is known, a DELETE+INSERT pair becomes a
+/// `ColumnChange` carrying only the columns that differ; otherwise it becomes
+/// a whole-row `Updated`.
 struct CdcCoalescer {
     changes: Vec<Option<RowChange>>,
     pending_deletes: HashMap<(String, String), usize>,
     pending_inserts: HashMap<(String, String), usize>,
+    /// Row data of deletes in this batch, keyed like `pending_deletes`
+    deleted_rows: HashMap<(String, String), StorageEntity>,
 }
 
 impl CdcCoalescer {
@@ -95,6 +123,7 @@ impl CdcCoalescer {
             changes: Vec::new(),
             pending_deletes: HashMap::new(),
             pending_inserts: HashMap::new(),
+            deleted_rows: HashMap::new(),
         }
     }
 
@@ -102,6 +131,15 @@ impl CdcCoalescer {
         self.changes.push(Some(change));
     }
 
+    /// Add a delete together with the row data it removed
+    fn add_delete_with_row(&mut self, change: RowChange, row: StorageEntity) {
+        if let ChangeData::Deleted { id, .. } = &change.change {
+            self.deleted_rows
+                .insert((change.relation_name.clone(), id.clone()), row);
+        }
+        self.add(change);
+    }
+
     fn flush(&mut self) -> Vec<RowChange> {
         for idx in 0..self.changes.len() {
             if let Some(change) = self.changes[idx].clone() {
@@ -127,7 +165,9 @@ impl CdcCoalescer {
                                 })
                                 .unwrap_or_else(|| "".to_string())
                         }
-                        ChangeData::Updated { id, .. } => id.clone(),
+                        ChangeData::Updated { id, .. } | ChangeData::ColumnChange { id, .. } => {
+                            id.clone()
+                        }
                     },
                 );
 
@@ -155,8 +195,28 @@ impl CdcCoalescer {
 
                         // Check if there's a pending DELETE for same key
                         if let Some(delete_idx) = self.pending_deletes.remove(&key) {
-                            // DELETE then INSERT → UPDATE
                             self.changes[delete_idx] = None;
+
+                            // DELETE then INSERT of a known row → only the changed columns
+                            if let Some(before) = self.deleted_rows.remove(&key) {
+                                let mut columns = changed_columns(&before, data);
+                                columns.remove("_rowid");
+                                self.changes[idx] = if columns.is_empty() {
+                                    None
+                                } else {
+                                    Some(RowChange {
+                                        relation_name: change.relation_name.clone(),
+                                        change: ChangeData::ColumnChange {
+                                            id: key.1.clone(),
+                                            columns,
+                                            origin: origin.clone(),
+                                        },
+                                    })
+                                };
+                                continue;
+                            }
+
+                            // DELETE then INSERT → UPDATE
                             self.changes[idx] = Some(RowChange {
                                 relation_name: change.relation_name.clone(),
                                 change: ChangeData::Updated {
@@ -170,13 +230,14 @@ impl CdcCoalescer {
                             self.pending_inserts.insert(key, idx);
                         }
                     }
-                    ChangeData::Updated { .. } => {}
+                    ChangeData::Updated { .. } | ChangeData::ColumnChange { .. } => {}
                 }
             }
         }
 
         self.pending_deletes.clear();
         self.pending_inserts.clear();
+        self.deleted_rows.clear();
         self.changes.drain(..).flatten().collect()
     }
 }
@@ -263,6 +324,13 @@ impl ConnectionPool {
             conn_id,
         })
     }
+
+    /// Close all connections waiting in the pool
+    fn close_idle(&self) {
+        if let Ok(mut available) = self.available.try_lock() {
+            while available.try_recv().is_ok() {}
+        }
+    }
 }
 
 /// A connection that returns itself to the pool when dropped
@@ -316,6 +384,34 @@ pub struct TursoBackend {
     db: Arc<Database>,
     /// Connection pool for reusing connections
     pool: Arc<ConnectionPool>,
+    /// Tables with a storage-managed `deleted_at` column
+    soft_delete_tables: SoftDeleteTables,
+    /// Derived columns kept up to date from their dependencies
+    computed_fields: ComputedFields,
+    /// Child and completion counts of tree tables
+    rollups: Rollups,
+    /// Urgency scores of task tables
+    urgency_scores: UrgencyScores,
+    /// Daily aggregates of task tables
+    stats: WorkspaceStats,
+    /// Key file of an encrypted database
+    encryption: Option<EncryptionState>,
+    /// While locked, no connections are handed out
+    lock: DatabaseLock,
+    /// Where `persist` saves the in-memory database of the web target
+    #[cfg(target_arch = "wasm32")]
+    snapshot_file: OpfsFile,
+    /// A `schedule_persist` save is pending
+    #[cfg(target_arch = "wasm32")]
+    persist_scheduled: std::sync::atomic::AtomicBool,
+}
+
+/// Key material of an open encrypted database
+#[derive(Debug, Clone)]
+struct EncryptionState {
+    key_path: PathBuf,
+    /// Fingerprint of the data key the database was opened with
+    key_fingerprint: String,
 }
 
 impl std::fmt::Debug for TursoBackend {
@@ -348,20 +444,71 @@ impl TursoBackend {
     /// The turso-core library currently does not export a public cross-platform IO implementation.
     /// Windows support will be added once turso-core exposes the necessary APIs.
     pub async fn new<P: AsRef<Path>>(db_path: P) -> Result<Self> {
+        Self::open(db_path.as_ref(), None).await
+    }
+
+    /// Open (or create) an encrypted file-based TursoBackend
+    ///
+    /// A new database gets a random data key, stored in `<db>.key` wrapped with the
+    /// passphrase. Fails if the passphrase is wrong or if the database already exists
+    /// without a key file (i.e. unencrypted).
+    pub async fn new_encrypted<P: AsRef<Path>>(
+        db_path: P,
+        config: &EncryptionConfig,
+    ) -> Result<Self> {
+        let db_path = db_path.as_ref();
+        let key_path = KeyFile::path_for(db_path);
+        let (key_file, key) = match KeyFile::load(&key_path)? {
+            Some(key_file) => {
+                let key = key_file.unwrap_key(config.passphrase())?;
+                (key_file, key)
+            }
+            None if db_path.exists() => {
+                return Err(StorageError::DatabaseError(format!(
+                    "Database {} exists but is not encrypted",
+                    db_path.display()
+                )));
+            }
+            None => {
+                let (key_file, key) = KeyFile::generate(config.passphrase(), config.cipher())?;
+                key_file.save(&key_path)?;
+                (key_file, key)
+            }
+        };
+
+        let encryption_opts = EncryptionOpts {
+            cipher: key_file.cipher.clone(),
+            hexkey: key.to_hex(),
+        };
+        let mut backend = Self::open(db_path, Some(encryption_opts)).await?;
+        backend.encryption = Some(EncryptionState {
+            key_path,
+            key_fingerprint: key.fingerprint(),
+        });
+        Ok(backend)
+    }
+
+    async fn open(db_path: &Path, encryption_opts: Option<EncryptionOpts>) -> Result<Self> {
         #[cfg(target_family = "unix")]
         {
             let io =
                 Arc::new(UnixIO::new().map_err(|e| StorageError::DatabaseError(e.to_string()))?);
-            let opts = DatabaseOpts::default().with_views(true);
+            let opts = DatabaseOpts::default()
+                .with_views(true)
+                .with_encryption(encryption_opts.is_some());
 
             let db_path_str = db_path
-                .as_ref()
                 .to_str()
                 .ok_or_else(|| StorageError::DatabaseError("Invalid path".to_string()))?;
 
-            let db =
-                Database::open_file_with_flags(io, db_path_str, OpenFlags::default(), opts, None)
-                    .map_err(|e| StorageError::DatabaseError(e.to_string()))?;
+            let db = Database::open_file_with_flags(
+                io,
+                db_path_str,
+                OpenFlags::default(),
+                opts,
+                encryption_opts,
+            )
+            .map_err(|e| StorageError::DatabaseError(e.to_string()))?;
 
             tracing::info!("Turso database opened at: {}", db_path_str);
 
@@ -374,15 +521,68 @@ impl TursoBackend {
             Ok(Self {
                 db: Arc::clone(&db_arc),
                 pool,
+                soft_delete_tables: SoftDeleteTables::default(),
+                computed_fields: ComputedFields::default(),
+                rollups: Rollups::default(),
+                urgency_scores: UrgencyScores::default(),
+                stats: WorkspaceStats::default(),
+                encryption: None,
+                lock: DatabaseLock::default(),
             })
         }
-        #[cfg(not(target_family = "unix"))]
+        #[cfg(target_arch = "wasm32")]
+        {
+            // Browsers: run on MemoryIO and persist snapshots in OPFS (see `persist`)
+            if encryption_opts.is_some() {
+                return Err(StorageError::DatabaseError(
+                    "Encrypted databases are not supported on the web".to_string(),
+                ));
+            }
+            let db_path_str = db_path
+                .to_str()
+                .ok_or_else(|| StorageError::DatabaseError("Invalid path".to_string()))?;
+            let io = Arc::new(MemoryIO::new());
+            let opts = DatabaseOpts::default().with_views(true);
+            let db =
+                Database::open_file_with_flags(io, db_path_str, OpenFlags::default(), opts, None)
+                    .map_err(|e| StorageError::DatabaseError(e.to_string()))?;
+
+            const DEFAULT_POOL_SIZE: usize = 10;
+            let db_arc = Arc::new(db);
+            let pool = Arc::new(ConnectionPool::new(Arc::clone(&db_arc), DEFAULT_POOL_SIZE));
+            let backend = Self {
+                db: db_arc,
+                pool,
+                soft_delete_tables: SoftDeleteTables::default(),
+                computed_fields: ComputedFields::default(),
+                rollups: Rollups::default(),
+                urgency_scores: UrgencyScores::default(),
+                stats: WorkspaceStats::default(),
+                encryption: None,
+                lock: DatabaseLock::default(),
+                snapshot_file: OpfsFile::for_database(db_path_str),
+                persist_scheduled: std::sync::atomic::AtomicBool::new(false),
+            };
+
+            if let Some(json) = backend.snapshot_file.read().await? {
+                DatabaseSnapshot::from_json(&json)?
+                    .restore(&backend)
+                    .await?;
+                tracing::info!(
+                    "Turso database {} restored from {}",
+                    db_path_str,
+                    backend.snapshot_file.name()
+                );
+            }
+            Ok(backend)
+        }
+        #[cfg(all(not(target_family = "unix"), not(target_arch = "wasm32")))]
         {
             // Windows/other platforms: fall back to in-memory until turso-core exports cross-platform IO
             eprintln!(
                 "Warning: File-based storage not yet supported on this platform. Using in-memory storage."
             );
-            let _ = db_path; // Suppress unused variable warning
+            let _ = (db_path, encryption_opts); // Suppress unused variable warning
             Self::new_in_memory().await
         }
     }
@@ -400,11 +600,178 @@ impl TursoBackend {
         ))
     }
 
+    /// Save the database `PERSIST_DELAY` from now, unless a save is already pending
+    ///
+    /// A burst of changes is saved in one snapshot. `BackendEngine` schedules this
+    /// after successful operations (including `sync`) and replica syncs; nothing is
+    /// saved automatically otherwise.
+    #[cfg(target_arch = "wasm32")]
+    pub async fn schedule_persist(backend: Arc<tokio::sync::RwLock<Self>>) {
+        use std::sync::atomic::Ordering;
+
+        if backend
+            .read()
+            .await
+            .persist_scheduled
+            .swap(true, Ordering::AcqRel)
+        {
+            return;
+        }
+        wasm_bindgen_futures::spawn_local(async move {
+            crate::storage::opfs::sleep(PERSIST_DELAY).await;
+            let backend = backend.read().await;
+            // Changes made while the snapshot is captured schedule the next save
+            backend.persist_scheduled.store(false, Ordering::Release);
+            if let Err(e) = backend.persist().await {
+                tracing::warn!("[TursoBackend] Failed to persist database: {}", e);
+            }
+        });
+    }
+
+    /// Save the in-memory database of the web target to its OPFS snapshot file now
+    #[cfg(target_arch = "wasm32")]
+    pub async fn persist(&self) -> Result<()> {
+        let snapshot = DatabaseSnapshot::capture(self).await?;
+        self.snapshot_file.write(&snapshot.to_json()?).await?;
+        tracing::debug!(
+            "[TursoBackend] Persisted {} tables to {}",
+            snapshot.tables.len(),
+            self.snapshot_file.name()
+        );
+        Ok(())
+    }
+
+    /// Add the `deleted_at` and `archived_at` columns to a table (if missing) and
+    /// register it for soft delete
+    ///
+    /// Registered tables get `deleted_at IS NULL` and `archived_at IS NULL` filters in
+    /// compiled queries, and the purge job deletes their expired rows through
+    /// `entity_name`'s operations.
+    pub async fn enable_soft_delete(&self, table_name: &str, entity_name: &str) -> Result<()> {
+        let rows = self
+            .execute_sql(
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = $name",
+                HashMap::from([("name".to_string(), Value::String(table_name.to_string()))]),
+            )
+            .await?;
+        let table_sql = rows
+            .first()
+            .and_then(|row| row.get("sql"))
+            .and_then(|sql| sql.as_string())
+            .unwrap_or_default()
+            .to_string();
+
+        for column in [DELETED_AT_COLUMN, ARCHIVED_AT_COLUMN] {
+            if !table_sql.contains(column) {
+                self.execute_sql(
+                    &format!("ALTER TABLE {} ADD COLUMN {} INTEGER", table_name, column),
+                    HashMap::new(),
+                )
+                .await?;
+            }
+        }
+
+        self.soft_delete_tables.register(table_name, entity_name);
+        Ok(())
+    }
+
+    /// Tables registered via `enable_soft_delete`
+    pub fn soft_delete_tables(&self) -> SoftDeleteTables {
+        self.soft_delete_tables.clone()
+    }
+
+    /// Registry of computed fields of all tables
+    pub fn computed_fields(&self) -> ComputedFields {
+        self.computed_fields.clone()
+    }
+
+    /// Registry of progress rollups of all tree tables
+    pub fn rollups(&self) -> Rollups {
+        self.rollups.clone()
+    }
+
+    /// Registry of urgency scorings of all task tables
+    pub fn urgency_scores(&self) -> UrgencyScores {
+        self.urgency_scores.clone()
+    }
+
+    /// Registry of the daily statistics of all task tables
+    pub fn stats(&self) -> WorkspaceStats {
+        self.stats.clone()
+    }
+
+    /// Whether the database is encrypted at rest
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+
+    /// Shared locked/unlocked state of this database
+    pub fn database_lock(&self) -> DatabaseLock {
+        self.lock.clone()
+    }
+
+    /// Refuse all connections until `unlock` is called with the passphrase
+    ///
+    /// Idle pooled connections are closed; connections already handed out (e.g. CDC
+    /// connections) stay open.
+    pub fn lock(&self) -> Result<()> {
+        if self.encryption.is_none() {
+            return Err(StorageError::DatabaseError(
+                "Only encrypted databases can be locked".to_string(),
+            ));
+        }
+        self.lock.lock();
+        self.pool.close_idle();
+        tracing::info!("[TursoBackend] Database locked");
+        Ok(())
+    }
+
+    /// Unlock the database; fails if the passphrase doesn't unwrap this database's key
+    pub fn unlock(&self, passphrase: &str) -> Result<()> {
+        let state = self.encryption_state()?;
+        let key = Self::load_key_file(&state.key_path)?.unwrap_key(passphrase)?;
+        if key.fingerprint() != state.key_fingerprint {
+            return Err(StorageError::DatabaseError(
+                "Key file does not belong to the open database".to_string(),
+            ));
+        }
+        self.lock.unlock();
+        tracing::info!("[TursoBackend] Database unlocked");
+        Ok(())
+    }
+
+    /// Change the passphrase of an encrypted database
+    ///
+    /// Only the wrapped data key is rewritten; the database itself is not re-encrypted.
+    pub fn rotate_passphrase(&self, old_passphrase: &str, new_passphrase: &str) -> Result<()> {
+        self.lock.check()?;
+        let state = self.encryption_state()?;
+        let rotated =
+            Self::load_key_file(&state.key_path)?.rewrap(old_passphrase, new_passphrase)?;
+        rotated.save(&state.key_path)?;
+        tracing::info!("[TursoBackend] Database passphrase rotated");
+        Ok(())
+    }
+
+    fn encryption_state(&self) -> Result<&EncryptionState> {
+        self.encryption
+            .as_ref()
+            .ok_or_else(|| StorageError::DatabaseError("Database is not encrypted".to_string()))
+    }
+
+    fn load_key_file(key_path: &Path) -> Result<KeyFile> {
+        KeyFile::load(key_path)?.ok_or_else(|| {
+            StorageError::DatabaseError(format!("Key file {} is missing", key_path.display()))
+        })
+    }
+
     /// Get a connection from the pool
     ///
     /// The connection will be automatically returned to the pool when dropped,
     /// unless `take()` is called on it (for long-lived connections like CDC).
+    /// Fails with `StorageError::Locked` while the database is locked.
     pub fn get_connection(&self) -> Result<PooledConnection> {
+        self.lock.check()?;
         self.pool.get_connection()
     }
 
@@ -413,6 +780,7 @@ impl TursoBackend {
     /// **Note**: This creates a new connection that is NOT pooled. Use `get_connection()`
     /// and call `take()` on the PooledConnection if you need a long-lived connection.
     pub fn get_raw_connection(&self) -> Result<turso::Connection> {
+        self.lock.check()?;
         use std::sync::atomic::{AtomicU64, Ordering};
         static CONNECTION_COUNTER: AtomicU64 = AtomicU64::new(0);
         let conn_id = CONNECTION_COUNTER.fetch_add(1, Ordering::SeqCst);
@@ -533,10 +901,22 @@ impl TursoBackend {
                                 batch_trace_context = origin.to_batch_trace_context();
                             }
 
-                            ChangeData::Deleted {
-                                id: entity_id,
-                                origin,
-                            }
+                            let mut data = data;
+                            data.insert("_rowid".to_string(), Value::String(change.id.to_string()));
+
+                            // Keep the old row so a following INSERT can be
+                            // reduced to the columns that actually changed
+                            coalescer.add_delete_with_row(
+                                RowChange {
+                                    relation_name: event.relation_name.clone(),
+                                    change: ChangeData::Deleted {
+                                        id: entity_id,
+                                        origin,
+                                    },
+                                },
+                                data,
+                            );
+                            continue;
                         } else {
                             // Fallback to rowid if parsing fails
                             ChangeData::Deleted {
@@ -544,6 +924,7 @@ impl TursoBackend {
                                 origin: ChangeOrigin::Remote {
                                     operation_id: None,
                                     trace_id: None,
+                                    source: None,
                                 },
                             }
                         }
@@ -575,6 +956,7 @@ impl TursoBackend {
                 relation_name: event.relation_name.clone(),
                 trace_context,
                 sync_token: None, // CDC batches don't carry sync tokens
+                full_snapshot: false,
             };
 
             // Wrap batch with metadata
@@ -671,7 +1053,7 @@ impl TursoBackend {
             Value::Integer(i) => i.to_string(),
             Value::Float(f) => f.to_string(),
             Value::Boolean(b) => if *b { "1" } else { "0" }.to_string(),
-            Value::DateTime(s) => format!("'{}'", s.replace('\'', "''")),
+            Value::DateTime(s) => format!("'{}'", datetime_text(s).replace('\'', "''")),
             Value::Json(s) => format!("'{}'", s.replace('\'', "''")),
             Value::Reference(r) => format!("'{}'", r.replace('\'', "''")),
             Value::Array(arr) => {
@@ -708,7 +1090,7 @@ impl TursoBackend {
             Value::Integer(i) => turso::Value::Integer(*i),
             Value::Float(f) => turso::Value::Real(*f),
             Value::Boolean(b) => turso::Value::Integer(if *b { 1 } else { 0 }),
-            Value::DateTime(s) => turso::Value::Text(s.clone()),
+            Value::DateTime(s) => turso::Value::Text(datetime_text(s)),
             Value::Json(s) => turso::Value::Text(s.clone()),
             Value::Reference(r) => turso::Value::Text(r.clone()),
             Value::Array(arr) => {
@@ -742,7 +1124,7 @@ impl TursoBackend {
                 .map(Value::Integer)
                 .map_err(|e| StorageError::SerializationError(e.to_string())),
             FieldType::Boolean => Ok(Value::Boolean(raw == "1")),
-            FieldType::DateTime => Ok(Value::DateTime(raw.to_string())),
+            FieldType::DateTime => Ok(Value::DateTime(datetime_text(raw))),
             FieldType::Json => serde_json::from_str(raw)
                 .map(Value::Json)
                 .map_err(|e| StorageError::SerializationError(e.to_string())),
@@ -883,6 +1265,52 @@ impl TursoBackend {
         Ok(results)
     }
 
+    /// Execute a SQL query, passing its rows to `on_row` as the cursor yields them
+    ///
+    /// Unlike `execute_sql`, rows aren't collected, so memory use doesn't grow with the
+    /// result. `on_row` gets the column names and the row's values in column order, and
+    /// returns false to stop early. Returns the column names.
+    pub async fn for_each_row(
+        &self,
+        sql: &str,
+        params: HashMap<String, Value>,
+        mut on_row: impl FnMut(&[String], Vec<Value>) -> Result<bool>,
+    ) -> Result<Vec<String>> {
+        let conn = self.get_connection()?;
+        let (sql_with_placeholders, param_values) = self.bind_parameters(sql, &params)?;
+        let mut stmt = conn
+            .prepare(&sql_with_placeholders)
+            .await
+            .map_err(|e| StorageError::DatabaseError(e.to_string()))?;
+        let columns: Vec<String> = stmt
+            .columns()
+            .iter()
+            .map(|column| column.name().to_string())
+            .collect();
+
+        let mut rows = stmt
+            .query(param_values)
+            .await
+            .map_err(|e| StorageError::QueryError(e.to_string()))?;
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::QueryError(e.to_string()))?
+        {
+            let values = (0..columns.len())
+                .map(|idx| {
+                    row.get_value(idx)
+                        .map(|value| self.turso_value_to_value(value.into()))
+                        .map_err(|e| StorageError::QueryError(e.to_string()))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if !on_row(&columns, values)? {
+                break;
+            }
+        }
+        Ok(columns)
+    }
+
     /// Bind named parameters in SQL ($param_name) to positional placeholders (?)
     ///
     /// Returns the modified SQL and a Vec of parameter values in the correct order.
@@ -1262,6 +1690,7 @@ mod cdc_coalescer_tests {
                 origin: ChangeOrigin::Remote {
                     operation_id: None,
                     trace_id: None,
+                    source: None,
                 },
             },
         }
@@ -1275,6 +1704,7 @@ mod cdc_coalescer_tests {
                 origin: ChangeOrigin::Remote {
                     operation_id: None,
                     trace_id: None,
+                    source: None,
                 },
             },
         }
@@ -1293,6 +1723,7 @@ mod cdc_coalescer_tests {
                 origin: ChangeOrigin::Remote {
                     operation_id: None,
                     trace_id: None,
+                    source: None,
                 },
             },
         }
@@ -1318,6 +1749,37 @@ mod cdc_coalescer_tests {
         }
     }
 
+    #[test]
+    fn test_coalesce_known_delete_insert_becomes_column_change() {
+        let mut before = StorageEntity::new();
+        before.insert("id".to_string(), Value::String("id1".to_string()));
+        before.insert("value".to_string(), Value::String("old_value".to_string()));
+        before.insert("_rowid".to_string(), Value::String("7".to_string()));
+
+        let mut coalescer = CdcCoalescer::new();
+        coalescer.add_delete_with_row(make_delete("view1", "id1"), before.clone());
+        coalescer.add(make_insert("view1", "id1", "new_value"));
+
+        let result = coalescer.flush();
+        assert_eq!(result.len(), 1);
+        match &result[0].change {
+            ChangeData::ColumnChange { id, columns, .. } => {
+                assert_eq!(id, "id1");
+                assert_eq!(
+                    columns,
+                    &HashMap::from([("value".to_string(), Value::String("new_value".to_string()))])
+                );
+            }
+            _ => panic!("Expected ColumnChange, got {:?}", result[0].change),
+        }
+
+        // Rewriting a row with identical values emits nothing
+        before.insert("value".to_string(), Value::String("same".to_string()));
+        coalescer.add_delete_with_row(make_delete("view1", "id1"), before);
+        coalescer.add(make_insert("view1", "id1", "same"));
+        assert!(coalescer.flush().is_empty());
+    }
+
     #[test]
     fn test_coalesce_standalone_delete_unchanged() {
         let mut coalescer = CdcCoalescer::new();
@@ -320,6 +320,7 @@ fn apply_to_reference(
                                 origin: ChangeOrigin::Remote {
                                     operation_id: None,
                                     trace_id: None,
+                                    source: None,
                                 },
                             },
                         };
@@ -364,6 +365,7 @@ fn apply_to_reference(
                                 origin: ChangeOrigin::Remote {
                                     operation_id: None,
                                     trace_id: None,
+                                    source: None,
                                 },
                             },
                         };
@@ -404,6 +406,7 @@ fn apply_to_reference(
                                 origin: ChangeOrigin::Remote {
                                     operation_id: None,
                                     trace_id: None,
+                                    source: None,
                                 },
                             },
                         };
@@ -470,6 +473,7 @@ fn apply_to_reference(
                                         origin: ChangeOrigin::Remote {
                                             operation_id: None,
                                             trace_id: None,
+                                            source: None,
                                         },
                                     },
                                 };
@@ -1281,6 +1285,7 @@ impl StateMachineTest for StorageTest {
                         // Look up entity ID from mapping
                         ref_rowid_to_entity.get(id).cloned()
                     }
+                    ChangeData::ColumnChange { id, .. } => Some(id.clone()),
                 };
                 let actual_entity_id = match &actual.change {
                     ChangeData::Created { data, .. } | ChangeData::Updated { data, .. } => {
@@ -1308,6 +1313,7 @@ impl StateMachineTest for StorageTest {
                         // Look up entity ID from mapping
                         actual_rowid_to_entity.get(id).cloned()
                     }
+                    ChangeData::ColumnChange { id, .. } => Some(id.clone()),
                 };
 
                 assert_eq!(
@@ -1359,6 +1365,23 @@ impl StateMachineTest for StorageTest {
                             i, view_name
                         );
                     }
+                    (
+                        ChangeData::Updated { data: exp_data, .. },
+                        ChangeData::ColumnChange { columns, .. },
+                    ) => {
+                        // Column changes only carry the changed columns; each must
+                        // match the expected full row
+                        for (column, value) in columns {
+                            assert_eq!(
+                                exp_data.get(column).unwrap_or(&Value::Null),
+                                value,
+                                "Column '{}' mismatch at index {} for view '{}'",
+                                column,
+                                i,
+                                view_name
+                            );
+                        }
+                    }
                     (ChangeData::Deleted { .. }, ChangeData::Deleted { .. }) => {
                         // IDs already matched, nothing more to check
                     }
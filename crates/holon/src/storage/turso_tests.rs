@@ -104,6 +104,14 @@ mod value_conversion_tests {
         assert!(result.ends_with('\''));
     }
 
+    #[tokio::test]
+    async fn test_value_to_sql_param_datetime_normalized_to_utc() {
+        let backend = create_test_backend().await;
+        let result =
+            backend.value_to_sql_param(&Value::DateTime("2024-03-01T09:30:00+01:00".to_string()));
+        assert_eq!(result, "'2024-03-01T08:30:00.000Z'");
+    }
+
     #[tokio::test]
     async fn test_value_to_sql_param_json() {
         let backend = create_test_backend().await;
@@ -38,6 +38,12 @@ pub enum StorageError {
 
     #[error("Database error: {0}")]
     DatabaseError(String),
+
+    #[error("Database is locked")]
+    Locked,
+
+    #[error("Database replica is still being downloaded from its remote")]
+    ReplicaBootstrapping,
 }
 
 pub type Result<T> = std::result::Result<T, StorageError>;
@@ -0,0 +1,535 @@
+//! Deadline-aware urgency scores of tasks
+//!
+//! Urgency scoring adds an `urgency` column (0-100) to a table of tasks, combining
+//! how close the due date is, the priority, and whether the task is blocked by an
+//! open dependency (see `DependencyStore`). It is stored in the table itself, so a
+//! "Today" view is just `sort {-urgency}`.
+//!
+//! Tables of entities with `due_date`, `priority` and `completed` fields are scored
+//! when their schema is initialized. Scores are maintained incrementally: materialized
+//! views over each table's scoring columns and over `task_dependencies` report
+//! changes, and only the changed tasks are rescored. Since a due date comes closer
+//! without any change, all tasks are also rescored every `URGENCY_RESCORE_INTERVAL`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use holon_api::{BatchWithMetadata, Schema, Value};
+
+use crate::references::content_source;
+use crate::storage::turso::{ChangeData, RowChange, TursoBackend};
+use crate::storage::types::{Result, StorageEntity};
+
+/// Column holding the urgency score (0-100)
+pub const URGENCY_COLUMN: &str = "urgency";
+
+/// Prefix of the materialized views that report changes of scored tables
+pub const URGENCY_SOURCE_VIEW_PREFIX: &str = "urgency_src_";
+
+/// Materialized view reporting dependency changes
+const URGENCY_DEPENDENCIES_VIEW: &str = "urgency_dependencies";
+
+/// How often all tasks are rescored as their due dates come closer
+pub const URGENCY_RESCORE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Share of the score given by the due date
+const DUE_WEIGHT: f64 = 0.6;
+
+/// Share of the score given by the priority
+const PRIORITY_WEIGHT: f64 = 0.4;
+
+/// Factor applied to the score of blocked tasks, ranking them below actionable ones
+const BLOCKED_FACTOR: f64 = 0.25;
+
+/// Definition of the urgency scoring of a task table
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrgencyScoring {
+    pub table: String,
+    pub id_column: String,
+    /// Column holding the due date (RFC 3339 or `YYYY-MM-DD`)
+    pub due_column: String,
+    /// Integer column holding the priority, 1 being the lowest
+    pub priority_column: String,
+    /// Boolean column marking a task completed
+    pub completed_column: String,
+    /// Highest priority (4 for Todoist's p1)
+    pub max_priority: i64,
+    /// Due dates further away than this don't add to the score
+    pub horizon: chrono::Duration,
+}
+
+impl UrgencyScoring {
+    /// Scoring of `table` with the default `id`, `due_date`, `priority` and `completed` columns
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            id_column: "id".to_string(),
+            due_column: "due_date".to_string(),
+            priority_column: "priority".to_string(),
+            completed_column: "completed".to_string(),
+            max_priority: 4,
+            horizon: chrono::Duration::days(14),
+        }
+    }
+
+    pub fn with_id_column(mut self, id_column: impl Into<String>) -> Self {
+        self.id_column = id_column.into();
+        self
+    }
+
+    pub fn with_due_column(mut self, due_column: impl Into<String>) -> Self {
+        self.due_column = due_column.into();
+        self
+    }
+
+    pub fn with_priority_column(mut self, priority_column: impl Into<String>) -> Self {
+        self.priority_column = priority_column.into();
+        self
+    }
+
+    pub fn with_completed_column(mut self, completed_column: impl Into<String>) -> Self {
+        self.completed_column = completed_column.into();
+        self
+    }
+
+    pub fn with_max_priority(mut self, max_priority: i64) -> Self {
+        self.max_priority = max_priority;
+        self
+    }
+
+    pub fn with_horizon(mut self, horizon: chrono::Duration) -> Self {
+        self.horizon = horizon;
+        self
+    }
+
+    /// The scoring of a schema with `due_date`, `priority` and `completed` fields, if it has them
+    pub fn from_schema(schema: &Schema) -> Option<Self> {
+        let has_field = |name: &str| schema.fields.iter().any(|f| f.name == name);
+        if !has_field("due_date") || !has_field("priority") || !has_field("completed") {
+            return None;
+        }
+        let id_column = schema
+            .fields
+            .iter()
+            .find(|f| f.primary_key)
+            .map(|f| f.name.as_str())
+            .unwrap_or("id");
+        Some(Self::new(&schema.table_name).with_id_column(id_column))
+    }
+
+    /// Urgency (0-100) of a row of `source_select_sql` at `now`
+    ///
+    /// Overdue tasks get the full due date share, tasks due beyond the horizon or
+    /// without a due date none. Completed tasks score 0.
+    pub fn score(&self, row: &StorageEntity, blocked: bool, now: DateTime<Utc>) -> i64 {
+        let completed = match row.get("completed") {
+            Some(Value::Boolean(completed)) => *completed,
+            Some(value) => value.as_i64().unwrap_or(0) != 0,
+            None => false,
+        };
+        if completed {
+            return 0;
+        }
+
+        let due = match row.get("due_date") {
+            Some(Value::String(s)) | Some(Value::DateTime(s)) => holon_api::parse_datetime(s),
+            _ => None,
+        };
+        let due_score = due.map_or(0.0, |due| {
+            let remaining = (due - now).num_minutes() as f64;
+            let horizon = self.horizon.num_minutes().max(1) as f64;
+            (1.0 - remaining / horizon).clamp(0.0, 1.0)
+        });
+        let priority_score = row
+            .get("priority")
+            .and_then(|v| v.as_i64())
+            .map_or(0.0, |priority| {
+                ((priority - 1) as f64 / (self.max_priority - 1).max(1) as f64).clamp(0.0, 1.0)
+            });
+
+        let mut score = DUE_WEIGHT * due_score + PRIORITY_WEIGHT * priority_score;
+        if blocked {
+            score *= BLOCKED_FACTOR;
+        }
+        (score * 100.0).round() as i64
+    }
+
+    /// `SELECT id, due_date, priority, completed` of the table, watched for changes
+    fn source_select_sql(&self) -> String {
+        format!(
+            "SELECT {} AS id, {} AS due_date, {} AS priority, {} AS completed FROM {}",
+            self.id_column,
+            self.due_column,
+            self.priority_column,
+            self.completed_column,
+            self.table
+        )
+    }
+}
+
+/// Registry of urgency scorings by table
+#[derive(Clone, Debug, Default)]
+pub struct UrgencyScores {
+    tables: Arc<RwLock<HashMap<String, UrgencyScoring>>>,
+}
+
+impl UrgencyScores {
+    /// Register a scoring, adding the urgency column to the table if missing
+    pub async fn register(&self, backend: &TursoBackend, scoring: UrgencyScoring) -> Result<()> {
+        let create_sql = table_sql(backend, &scoring.table)
+            .await?
+            .unwrap_or_default();
+        if !create_sql.contains(URGENCY_COLUMN) {
+            backend
+                .execute_sql(
+                    &format!(
+                        "ALTER TABLE {} ADD COLUMN {} INTEGER",
+                        scoring.table, URGENCY_COLUMN
+                    ),
+                    HashMap::new(),
+                )
+                .await?;
+        }
+
+        self.tables
+            .write()
+            .unwrap()
+            .insert(scoring.table.clone(), scoring);
+        Ok(())
+    }
+
+    /// Register the scoring of `schema`, if it describes tasks with due dates and priorities
+    pub async fn register_schema(&self, backend: &TursoBackend, schema: &Schema) -> Result<()> {
+        match UrgencyScoring::from_schema(schema) {
+            Some(scoring) => self.register(backend, scoring).await,
+            None => Ok(()),
+        }
+    }
+
+    pub fn table_names(&self) -> Vec<String> {
+        self.tables.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Scoring of `table`, if registered
+    pub fn scoring(&self, table: &str) -> Option<UrgencyScoring> {
+        self.tables.read().unwrap().get(table).cloned()
+    }
+
+    /// Rescore the given tasks of `table` at `now`
+    ///
+    /// Returns the number of tasks whose score changed.
+    pub async fn rescore(
+        &self,
+        backend: &TursoBackend,
+        table: &str,
+        ids: Option<&[String]>,
+        now: DateTime<Utc>,
+    ) -> Result<usize> {
+        let Some(scoring) = self.scoring(table) else {
+            return Ok(0);
+        };
+        let blocked = blocked_ids(backend, table).await?;
+        let select = format!(
+            "SELECT {} AS id, {} AS due_date, {} AS priority, {} AS completed, {} AS current FROM {}",
+            scoring.id_column,
+            scoring.due_column,
+            scoring.priority_column,
+            scoring.completed_column,
+            URGENCY_COLUMN,
+            table
+        );
+        let rows = match ids {
+            None => backend.execute_sql(&select, HashMap::new()).await?,
+            Some(ids) => {
+                let sql = format!("{} WHERE {} = $id", select, scoring.id_column);
+                let mut rows = Vec::new();
+                for id in ids {
+                    let params = HashMap::from([("id".to_string(), Value::String(id.clone()))]);
+                    rows.extend(backend.execute_sql(&sql, params).await?);
+                }
+                rows
+            }
+        };
+
+        let mut changed = 0;
+        for row in rows {
+            let Some(id) = row.get("id").and_then(|v| v.as_string_owned()) else {
+                continue;
+            };
+            let score = Value::Integer(scoring.score(&row, blocked.contains(&id), now));
+            if row.get("current") == Some(&score) {
+                continue;
+            }
+            backend
+                .execute_sql(
+                    &format!(
+                        "UPDATE {} SET {} = $score WHERE {} = $id",
+                        table, URGENCY_COLUMN, scoring.id_column
+                    ),
+                    HashMap::from([
+                        ("score".to_string(), score),
+                        ("id".to_string(), Value::String(id)),
+                    ]),
+                )
+                .await?;
+            changed += 1;
+        }
+        Ok(changed)
+    }
+
+    /// Rescore every task of every registered table at `now`
+    pub async fn rescore_all(&self, backend: &TursoBackend, now: DateTime<Utc>) -> Result<usize> {
+        let mut changed = 0;
+        for table in self.table_names() {
+            changed += self.rescore(backend, &table, None, now).await?;
+        }
+        Ok(changed)
+    }
+
+    /// Rescore the tasks touched by a change batch of an `urgency_src_` or the dependencies view
+    ///
+    /// A removed dependency is only reflected by the next periodic rescore, since
+    /// deletions don't report the task they blocked.
+    pub async fn apply_batch(
+        &self,
+        backend: &TursoBackend,
+        batch: &BatchWithMetadata<RowChange>,
+        now: DateTime<Utc>,
+    ) -> Result<usize> {
+        let relation = batch.metadata.relation_name.as_str();
+        let mut touched: HashMap<String, Vec<String>> = HashMap::new();
+        for row_change in &batch.inner.items {
+            let Some(id) = content_source::changed_entity_id(&row_change.change) else {
+                continue;
+            };
+            let table = match &row_change.change {
+                ChangeData::Deleted { .. } => continue,
+                ChangeData::Created { data, .. } | ChangeData::Updated { data, .. }
+                    if relation == URGENCY_DEPENDENCIES_VIEW =>
+                {
+                    data.get("entity_name").and_then(|v| v.as_string_owned())
+                }
+                _ => relation
+                    .strip_prefix(URGENCY_SOURCE_VIEW_PREFIX)
+                    .map(str::to_string),
+            };
+            if let Some(table) = table {
+                touched.entry(table).or_default().push(id.to_string());
+            }
+        }
+
+        let mut changed = 0;
+        for (table, mut ids) in touched {
+            ids.sort();
+            ids.dedup();
+            changed += self.rescore(backend, &table, Some(&ids), now).await?;
+        }
+        Ok(changed)
+    }
+
+    /// Score all tasks, then follow changes and rescore periodically in the background
+    ///
+    /// Only scorings registered before this is called are watched.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self, backend: Arc<tokio::sync::RwLock<TursoBackend>>) {
+        use std::ops::ControlFlow;
+
+        tokio::spawn(async move {
+            let views = {
+                let backend = backend.read().await;
+                self.rescore_all_logged(&backend).await;
+                let mut views = self
+                    .table_names()
+                    .into_iter()
+                    .filter_map(|table| {
+                        let scoring = self.scoring(&table)?;
+                        Some((
+                            format!("{}{}", URGENCY_SOURCE_VIEW_PREFIX, table),
+                            scoring.source_select_sql(),
+                        ))
+                    })
+                    .collect::<Vec<_>>();
+                if views.is_empty() {
+                    return;
+                }
+                if matches!(
+                    table_sql(&backend, TASK_DEPENDENCIES_TABLE).await,
+                    Ok(Some(_))
+                ) {
+                    views.push((
+                        URGENCY_DEPENDENCIES_VIEW.to_string(),
+                        format!(
+                            "SELECT entity_name, blocks_id AS id, blocker_completed FROM {}",
+                            TASK_DEPENDENCIES_TABLE
+                        ),
+                    ));
+                }
+                views
+            };
+
+            let periodic = {
+                let (scores, backend) = (self.clone(), backend.clone());
+                tokio::spawn(async move {
+                    let mut rescore = tokio::time::interval(URGENCY_RESCORE_INTERVAL);
+                    // The first tick completes immediately; all tasks were just scored
+                    rescore.tick().await;
+                    loop {
+                        rescore.tick().await;
+                        let backend = backend.read().await;
+                        scores.rescore_all_logged(&backend).await;
+                    }
+                })
+            };
+
+            let watched = content_source::follow_views(&backend, &views, |batch| {
+                let (scores, backend) = (self.clone(), backend.clone());
+                async move {
+                    let backend = backend.read().await;
+                    if let Err(e) = scores.apply_batch(&backend, &batch, Utc::now()).await {
+                        tracing::warn!("[UrgencyScores] Failed to rescore tasks: {}", e);
+                    }
+                    ControlFlow::Continue(())
+                }
+            })
+            .await;
+            if let Err(e) = watched {
+                tracing::warn!("[UrgencyScores] Failed to watch tasks: {}", e);
+            }
+            periodic.abort();
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn rescore_all_logged(&self, backend: &TursoBackend) {
+        match self.rescore_all(backend, Utc::now()).await {
+            Ok(0) => {}
+            Ok(changed) => tracing::debug!("[UrgencyScores] Rescored {} tasks", changed),
+            Err(e) => tracing::warn!("[UrgencyScores] Failed to score tasks: {}", e),
+        }
+    }
+}
+
+/// Table of `DependencyStore`, which may not exist
+const TASK_DEPENDENCIES_TABLE: &str = "task_dependencies";
+
+/// `CREATE TABLE` statement of `table`, None if it doesn't exist
+async fn table_sql(backend: &TursoBackend, table: &str) -> Result<Option<String>> {
+    let rows = backend
+        .execute_sql(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = $name",
+            HashMap::from([("name".to_string(), Value::String(table.to_string()))]),
+        )
+        .await?;
+    Ok(rows
+        .first()
+        .and_then(|row| row.get("sql"))
+        .and_then(|sql| sql.as_string_owned()))
+}
+
+/// IDs of the tasks of `table` with an open blocker
+async fn blocked_ids(backend: &TursoBackend, table: &str) -> Result<HashSet<String>> {
+    if table_sql(backend, TASK_DEPENDENCIES_TABLE).await?.is_none() {
+        return Ok(HashSet::new());
+    }
+    let rows = backend
+        .execute_sql(
+            &format!(
+                "SELECT DISTINCT blocks_id AS id FROM {} WHERE entity_name = $entity_name AND blocker_completed = 0",
+                TASK_DEPENDENCIES_TABLE
+            ),
+            HashMap::from([(
+                "entity_name".to_string(),
+                Value::String(table.to_string()),
+            )]),
+        )
+        .await?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| row.get("id").and_then(|v| v.as_string_owned()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn tasks_backend() -> TursoBackend {
+        let backend = TursoBackend::new_in_memory().await.unwrap();
+        backend
+            .execute_sql(
+                "CREATE TABLE tasks (id TEXT PRIMARY KEY, due_date TEXT, priority INTEGER, completed INTEGER)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+            .execute_sql(
+                "INSERT INTO tasks (id, due_date, priority, completed) VALUES \
+                 ('overdue', '2026-06-01', 1, 0), ('soon', '2026-06-15T12:00:00Z', 4, 0), \
+                 ('later', '2026-09-01', 4, 0), ('done', '2026-06-01', 4, 1), ('someday', NULL, 1, 0)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+    }
+
+    async fn urgency_of(backend: &TursoBackend) -> HashMap<String, Value> {
+        backend
+            .execute_sql("SELECT id, urgency FROM tasks", HashMap::new())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|row| (row["id"].as_string_owned().unwrap(), row["urgency"].clone()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_rescore_all() {
+        let backend = tasks_backend().await;
+        let scores = UrgencyScores::default();
+        scores
+            .register(&backend, UrgencyScoring::new("tasks"))
+            .await
+            .unwrap();
+        let now = "2026-06-14T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        assert_eq!(scores.rescore_all(&backend, now).await.unwrap(), 5);
+        let urgency = urgency_of(&backend).await;
+        assert_eq!(urgency["overdue"], Value::Integer(60));
+        // Due in a day of 14: 0.6 * 13/14 + 0.4
+        assert_eq!(urgency["soon"], Value::Integer(96));
+        assert_eq!(urgency["later"], Value::Integer(40));
+        assert_eq!(urgency["done"], Value::Integer(0));
+        assert_eq!(urgency["someday"], Value::Integer(0));
+
+        // Nothing changed, nothing is written
+        assert_eq!(scores.rescore_all(&backend, now).await.unwrap(), 0);
+
+        // A blocked task ranks below actionable ones
+        backend
+            .execute_sql(
+                "CREATE TABLE task_dependencies (id TEXT PRIMARY KEY, entity_name TEXT, blocker_id TEXT, blocks_id TEXT, blocker_completed INTEGER)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        backend
+            .execute_sql(
+                "INSERT INTO task_dependencies VALUES ('d', 'tasks', 'someday', 'soon', 0)",
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            scores
+                .rescore(&backend, "tasks", Some(&["soon".to_string()]), now)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(urgency_of(&backend).await["soon"], Value::Integer(24));
+    }
+}
@@ -0,0 +1,776 @@
+//! End-to-end encrypted sync blobs
+//!
+//! Devices of one user sync through opaque blobs kept in any untrusted place (a shared
+//! folder, an object store, ...). Every executed operation is recorded in the
+//! `sync_blob_log` table with the device's sequence number and a vector clock. Each
+//! device appends its own records to its own blob; importing another device's blob
+//! adds the records this device hasn't seen and applies them through the dispatcher.
+//!
+//! Blob layout (append-only, integers big-endian):
+//!
+//! ```text
+//! "HOLONSB1" | salt (16) | device_id length (u16) | device_id
+//! record*:   length (u32) | nonce (24) | XChaCha20-Poly1305(JSON SyncRecord)
+//! ```
+//!
+//! The record key is derived from the passphrase and the blob's salt; the header is
+//! authenticated with every record, so records can't be moved between blobs. A torn
+//! trailing record (an interrupted append) is ignored.
+//!
+//! Remote records are applied in `SyncRecord::cmp_merge_order`, a total order that
+//! extends the vector clocks' causal order, so every device merges the same set of
+//! records into the same log.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock as StdRwLock, Weak};
+
+use async_trait::async_trait;
+use chacha20poly1305::XNonce;
+use chacha20poly1305::aead::{Aead, Payload};
+use holon_macros::Entity;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::api::operation_dispatcher::OperationDispatcher;
+use crate::core::datasource::OperationObserver;
+use crate::storage::encryption::{DataKey, derive_key};
+use crate::storage::turso::TursoBackend;
+use holon_api::{CURRENT_CHANGE_SOURCE, ChangeSource, DynamicEntity, HasSchema, Operation, Value};
+use holon_core::UndoAction;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+const BLOB_MAGIC: &[u8; 8] = b"HOLONSB1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+tokio::task_local! {
+    /// Set while remote records are dispatched, so they aren't recorded as local again
+    static APPLYING_REMOTE: ();
+}
+
+/// Per-device operation counters; `a` happened before `b` iff `a < b` componentwise
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorClock(pub BTreeMap<String, u64>);
+
+impl VectorClock {
+    pub fn get(&self, device_id: &str) -> u64 {
+        self.0.get(device_id).copied().unwrap_or(0)
+    }
+
+    pub fn increment(&mut self, device_id: &str) -> u64 {
+        let counter = self.0.entry(device_id.to_string()).or_insert(0);
+        *counter += 1;
+        *counter
+    }
+
+    /// Componentwise maximum
+    pub fn merge(&mut self, other: &VectorClock) {
+        for (device_id, &counter) in &other.0 {
+            let own = self.0.entry(device_id.clone()).or_insert(0);
+            *own = (*own).max(counter);
+        }
+    }
+
+    /// Causal order; None if the clocks are concurrent
+    pub fn causal_cmp(&self, other: &VectorClock) -> Option<Ordering> {
+        let mut ordering = Ordering::Equal;
+        for device_id in self.0.keys().chain(other.0.keys()) {
+            let step = self.get(device_id).cmp(&other.get(device_id));
+            match (ordering, step) {
+                (_, Ordering::Equal) => {}
+                (Ordering::Equal, step) => ordering = step,
+                (ordering, step) if ordering != step => return None,
+                _ => {}
+            }
+        }
+        Some(ordering)
+    }
+
+    fn total(&self) -> u64 {
+        self.0.values().sum()
+    }
+}
+
+/// One operation of one device, as stored in a blob
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncRecord {
+    pub device_id: String,
+    /// 1-based, contiguous per device
+    pub seq: u64,
+    pub clock: VectorClock,
+    pub created_at: i64,
+    pub operation: Operation,
+}
+
+impl SyncRecord {
+    /// Deterministic merge order: causally earlier records first, ties broken by device/seq
+    ///
+    /// A record's clock total grows along every causal chain, so sorting by it first
+    /// never puts an effect before its cause.
+    pub fn cmp_merge_order(&self, other: &SyncRecord) -> Ordering {
+        self.clock
+            .total()
+            .cmp(&other.clock.total())
+            .then_with(|| self.device_id.cmp(&other.device_id))
+            .then_with(|| self.seq.cmp(&other.seq))
+    }
+}
+
+/// The records of a decrypted blob
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncBlob {
+    pub device_id: String,
+    pub records: Vec<SyncRecord>,
+}
+
+/// Header and record key of an existing or new blob
+struct BlobKey {
+    header: Vec<u8>,
+    key: DataKey,
+}
+
+impl BlobKey {
+    fn new(device_id: &str, passphrase: &str) -> Result<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let mut header = BLOB_MAGIC.to_vec();
+        header.extend_from_slice(&salt);
+        header.extend_from_slice(&(device_id.len() as u16).to_be_bytes());
+        header.extend_from_slice(device_id.as_bytes());
+        Ok(Self {
+            key: derive_key(passphrase, &salt)?,
+            header,
+        })
+    }
+
+    /// Parse the header of `blob`; returns the key and the blob's device ID
+    fn parse(blob: &[u8], passphrase: &str) -> Result<(Self, String)> {
+        let id_offset = BLOB_MAGIC.len() + SALT_LEN + 2;
+        if blob.len() < id_offset || &blob[..BLOB_MAGIC.len()] != BLOB_MAGIC {
+            return Err("Not a sync blob".into());
+        }
+        let salt = &blob[BLOB_MAGIC.len()..BLOB_MAGIC.len() + SALT_LEN];
+        let id_len = u16::from_be_bytes([blob[id_offset - 2], blob[id_offset - 1]]) as usize;
+        let device_id = blob
+            .get(id_offset..id_offset + id_len)
+            .ok_or("Truncated sync blob header")?;
+        let device_id = String::from_utf8(device_id.to_vec())
+            .map_err(|e| format!("Invalid device ID in sync blob: {}", e))?;
+        Ok((
+            Self {
+                key: derive_key(passphrase, salt)?,
+                header: blob[..id_offset + id_len].to_vec(),
+            },
+            device_id,
+        ))
+    }
+
+    fn seal(&self, record: &SyncRecord) -> Result<Vec<u8>> {
+        let json = serde_json::to_vec(record)?;
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .key
+            .aead()
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &json,
+                    aad: &self.header,
+                },
+            )
+            .map_err(|e| format!("Failed to encrypt sync record: {}", e))?;
+
+        let mut bytes = Vec::with_capacity(4 + NONCE_LEN + ciphertext.len());
+        bytes.extend_from_slice(&((NONCE_LEN + ciphertext.len()) as u32).to_be_bytes());
+        bytes.extend_from_slice(&nonce);
+        bytes.extend_from_slice(&ciphertext);
+        Ok(bytes)
+    }
+
+    fn open(&self, sealed: &[u8]) -> Result<SyncRecord> {
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let json = self
+            .key
+            .aead()
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &self.header,
+                },
+            )
+            .map_err(|_| "Failed to decrypt sync record (wrong passphrase or tampered blob)")?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
+
+/// Decrypt and validate a blob
+pub fn read_blob(blob: &[u8], passphrase: &str) -> Result<SyncBlob> {
+    let (blob_key, device_id) = BlobKey::parse(blob, passphrase)?;
+    read_records(blob, &blob_key, device_id)
+}
+
+fn read_records(blob: &[u8], blob_key: &BlobKey, device_id: String) -> Result<SyncBlob> {
+    let mut records = Vec::new();
+    let mut offset = blob_key.header.len();
+
+    while offset + 4 <= blob.len() {
+        let len = u32::from_be_bytes(blob[offset..offset + 4].try_into().unwrap()) as usize;
+        let Some(sealed) = blob.get(offset + 4..offset + 4 + len) else {
+            debug!("[SyncBlob] Ignoring torn record at offset {}", offset);
+            break;
+        };
+        if len < NONCE_LEN {
+            return Err(format!("Corrupt sync record at offset {}", offset).into());
+        }
+        let record = blob_key.open(sealed)?;
+        if record.device_id != device_id {
+            return Err(format!(
+                "Sync blob of {} contains a record of {}",
+                device_id, record.device_id
+            )
+            .into());
+        }
+        if record.seq != records.len() as u64 + 1 {
+            return Err(format!(
+                "Sync blob of {} skips from seq {} to {}",
+                device_id,
+                records.len(),
+                record.seq
+            )
+            .into());
+        }
+        records.push(record);
+        offset += 4 + len;
+    }
+
+    Ok(SyncBlob { device_id, records })
+}
+
+/// A record of the merged sync log
+///
+/// Table name: `sync_blob_log`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Entity)]
+#[entity(name = "sync_blob_log", short_name = "sync_log")]
+pub struct SyncLogRecord {
+    /// `{device_id}:{seq}`
+    #[primary_key]
+    pub id: String,
+    #[indexed]
+    pub device_id: String,
+    pub seq: i64,
+    /// Vector clock (JSON)
+    pub clock: String,
+    /// When the operation was executed on its device (Unix timestamp in milliseconds)
+    pub created_at: i64,
+    /// The operation (JSON)
+    pub operation: String,
+    /// When the record was applied locally; None for remote records not applied yet
+    pub applied_at: Option<i64>,
+    /// Why applying a remote record failed
+    pub error: Option<String>,
+}
+
+impl SyncLogRecord {
+    fn from_record(record: &SyncRecord, applied_at: Option<i64>) -> Result<Self> {
+        Ok(Self {
+            id: format!("{}:{}", record.device_id, record.seq),
+            device_id: record.device_id.clone(),
+            seq: record.seq as i64,
+            clock: serde_json::to_string(&record.clock)?,
+            created_at: record.created_at,
+            operation: serde_json::to_string(&record.operation)?,
+            applied_at,
+            error: None,
+        })
+    }
+
+    fn to_record(&self) -> Result<SyncRecord> {
+        Ok(SyncRecord {
+            device_id: self.device_id.clone(),
+            seq: self.seq as u64,
+            clock: serde_json::from_str(&self.clock)?,
+            created_at: self.created_at,
+            operation: serde_json::from_str(&self.operation)?,
+        })
+    }
+}
+
+/// Outcome of importing a blob
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncImportSummary {
+    /// Records not seen before
+    pub new_records: usize,
+    pub applied: usize,
+    pub failed: usize,
+}
+
+/// The merged sync log of this device
+pub struct SyncBlobLog {
+    backend: Arc<RwLock<TursoBackend>>,
+    device_id: StdRwLock<String>,
+    dispatcher: StdRwLock<Weak<OperationDispatcher>>,
+}
+
+impl SyncBlobLog {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self {
+            backend,
+            device_id: StdRwLock::new(String::new()),
+            dispatcher: StdRwLock::new(Weak::new()),
+        }
+    }
+
+    /// Apply remote records through `dispatcher`
+    pub fn bind_dispatcher(&self, dispatcher: &Arc<OperationDispatcher>) {
+        *self.dispatcher.write().unwrap() = Arc::downgrade(dispatcher);
+    }
+
+    /// Create the sync log tables and load (or generate) this device's ID
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = SyncLogRecord::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create sync_blob_log table: {}", e))?;
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create sync_blob_log index: {}", e))?;
+        }
+        backend
+            .execute_sql(
+                "CREATE TABLE IF NOT EXISTS sync_blob_device (id TEXT PRIMARY KEY)",
+                HashMap::new(),
+            )
+            .await
+            .map_err(|e| format!("Failed to create sync_blob_device table: {}", e))?;
+
+        let rows = backend
+            .execute_sql("SELECT id FROM sync_blob_device LIMIT 1", HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to load device ID: {}", e))?;
+        let device_id = match rows
+            .first()
+            .and_then(|row| row.get("id"))
+            .and_then(|v| v.as_string_owned())
+        {
+            Some(device_id) => device_id,
+            None => {
+                let device_id = uuid::Uuid::new_v4().to_string();
+                backend
+                    .execute_sql(
+                        "INSERT INTO sync_blob_device (id) VALUES ($id)",
+                        HashMap::from([("id".to_string(), Value::String(device_id.clone()))]),
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to store device ID: {}", e))?;
+                info!("[SyncBlobLog] Generated device ID {}", device_id);
+                device_id
+            }
+        };
+        *self.device_id.write().unwrap() = device_id;
+        Ok(())
+    }
+
+    pub fn device_id(&self) -> String {
+        self.device_id.read().unwrap().clone()
+    }
+
+    /// Append a locally executed operation to the log
+    pub async fn record_local(&self, operation: &Operation, created_at: i64) -> Result<SyncRecord> {
+        let device_id = self.device_id();
+        let mut clock = self.seen_clock().await?;
+        let seq = clock.increment(&device_id);
+        let record = SyncRecord {
+            device_id,
+            seq,
+            clock,
+            created_at,
+            operation: operation.clone(),
+        };
+        self.insert(&SyncLogRecord::from_record(&record, Some(created_at))?)
+            .await?;
+        Ok(record)
+    }
+
+    /// Bytes to append to this device's blob to bring it up to date
+    ///
+    /// `blob` is the blob's current content (empty if it doesn't exist yet); the result
+    /// includes the header in that case. Existing bytes are never rewritten.
+    pub async fn append_local(&self, blob: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+        let device_id = self.device_id();
+        let (blob_key, exported, mut bytes) = if blob.is_empty() {
+            let blob_key = BlobKey::new(&device_id, passphrase)?;
+            let header = blob_key.header.clone();
+            (blob_key, 0, header)
+        } else {
+            let (blob_key, blob_device_id) = BlobKey::parse(blob, passphrase)?;
+            if blob_device_id != device_id {
+                return Err(format!(
+                    "Sync blob belongs to device {}, not {}",
+                    blob_device_id, device_id
+                )
+                .into());
+            }
+            let existing = read_records(blob, &blob_key, blob_device_id)?;
+            (blob_key, existing.records.len() as i64, Vec::new())
+        };
+
+        let records = self
+            .load(
+                "SELECT * FROM sync_blob_log WHERE device_id = $device_id AND seq > $seq ORDER BY seq",
+                HashMap::from([
+                    ("device_id".to_string(), Value::String(device_id)),
+                    ("seq".to_string(), Value::Integer(exported)),
+                ]),
+            )
+            .await?;
+        for record in &records {
+            bytes.extend(blob_key.seal(&record.to_record()?)?);
+        }
+        debug!("[SyncBlobLog] Exported {} records", records.len());
+        Ok(bytes)
+    }
+
+    /// Merge another device's blob into the log and apply its new records
+    pub async fn import_blob(&self, blob: &[u8], passphrase: &str) -> Result<SyncImportSummary> {
+        let remote = read_blob(blob, passphrase)?;
+        if remote.device_id == self.device_id() {
+            return Ok(SyncImportSummary::default());
+        }
+
+        let known = self.max_seq(&remote.device_id).await?;
+        let mut summary = SyncImportSummary::default();
+        for record in remote.records.iter().filter(|r| r.seq > known) {
+            self.insert(&SyncLogRecord::from_record(record, None)?)
+                .await?;
+            summary.new_records += 1;
+        }
+
+        let (applied, failed) = self.apply_pending().await?;
+        summary.applied = applied;
+        summary.failed = failed;
+        info!(
+            "[SyncBlobLog] Imported {} records of {} ({} applied, {} failed)",
+            summary.new_records, remote.device_id, applied, failed
+        );
+        Ok(summary)
+    }
+
+    /// Apply all remote records that haven't been applied yet, in merge order
+    ///
+    /// Returns (applied, failed). Failed records are kept with their error and not retried.
+    pub async fn apply_pending(&self) -> Result<(usize, usize)> {
+        let dispatcher = self
+            .dispatcher
+            .read()
+            .unwrap()
+            .upgrade()
+            .ok_or("SyncBlobLog is not bound to a dispatcher")?;
+
+        let mut pending = Vec::new();
+        for row in self
+            .load(
+                "SELECT * FROM sync_blob_log WHERE applied_at IS NULL",
+                HashMap::new(),
+            )
+            .await?
+        {
+            pending.push(row.to_record()?);
+        }
+        pending.sort_by(SyncRecord::cmp_merge_order);
+
+        let (mut applied, mut failed) = (0, 0);
+        for record in pending {
+            let operation = &record.operation;
+            // Changes are attributed to the device that made them. The operation keeps
+            // its idempotency key, so applying a record again after a crash is skipped.
+            let source = ChangeSource::default().with_device_id(&record.device_id);
+            let result = CURRENT_CHANGE_SOURCE
+                .scope(
+                    source,
+                    APPLYING_REMOTE.scope((), dispatcher.execute(operation.clone())),
+                )
+                .await;
+            let error = match result {
+                Ok(_) => {
+                    applied += 1;
+                    None
+                }
+                Err(e) => {
+                    failed += 1;
+                    tracing::warn!(
+                        "[SyncBlobLog] Failed to apply {}:{} ({}.{}): {}",
+                        record.device_id,
+                        record.seq,
+                        operation.entity_name,
+                        operation.op_name,
+                        e
+                    );
+                    Some(e.to_string())
+                }
+            };
+            self.mark_applied(&record, error).await?;
+        }
+        Ok((applied, failed))
+    }
+
+    /// All records in merge order
+    pub async fn merged_log(&self) -> Result<Vec<SyncRecord>> {
+        let mut records = Vec::new();
+        for row in self
+            .load("SELECT * FROM sync_blob_log", HashMap::new())
+            .await?
+        {
+            records.push(row.to_record()?);
+        }
+        records.sort_by(SyncRecord::cmp_merge_order);
+        Ok(records)
+    }
+
+    /// Clock of everything this device has recorded or applied
+    async fn seen_clock(&self) -> Result<VectorClock> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT device_id, MAX(seq) AS seq FROM sync_blob_log \
+                 WHERE device_id = $device_id OR applied_at IS NOT NULL GROUP BY device_id",
+                HashMap::from([("device_id".to_string(), Value::String(self.device_id()))]),
+            )
+            .await
+            .map_err(|e| format!("Failed to load vector clock: {}", e))?;
+        let mut clock = VectorClock::default();
+        for row in rows {
+            if let (Some(device_id), Some(Value::Integer(seq))) = (
+                row.get("device_id").and_then(|v| v.as_string_owned()),
+                row.get("seq"),
+            ) {
+                clock.0.insert(device_id, *seq as u64);
+            }
+        }
+        Ok(clock)
+    }
+
+    async fn max_seq(&self, device_id: &str) -> Result<u64> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT MAX(seq) AS seq FROM sync_blob_log WHERE device_id = $device_id",
+                HashMap::from([(
+                    "device_id".to_string(),
+                    Value::String(device_id.to_string()),
+                )]),
+            )
+            .await
+            .map_err(|e| format!("Failed to load sequence number: {}", e))?;
+        Ok(match rows.first().and_then(|row| row.get("seq")) {
+            Some(Value::Integer(seq)) => *seq as u64,
+            _ => 0,
+        })
+    }
+
+    async fn insert(&self, record: &SyncLogRecord) -> Result<()> {
+        let backend = self.backend.read().await;
+        let params = HashMap::from([
+            ("id".to_string(), Value::String(record.id.clone())),
+            (
+                "device_id".to_string(),
+                Value::String(record.device_id.clone()),
+            ),
+            ("seq".to_string(), Value::Integer(record.seq)),
+            ("clock".to_string(), Value::String(record.clock.clone())),
+            ("created_at".to_string(), Value::Integer(record.created_at)),
+            (
+                "operation".to_string(),
+                Value::String(record.operation.clone()),
+            ),
+            (
+                "applied_at".to_string(),
+                record.applied_at.map(Value::Integer).unwrap_or(Value::Null),
+            ),
+        ]);
+        backend
+            .execute_sql(
+                "INSERT OR IGNORE INTO sync_blob_log (id, device_id, seq, clock, created_at, operation, applied_at) \
+                 VALUES ($id, $device_id, $seq, $clock, $created_at, $operation, $applied_at)",
+                params,
+            )
+            .await
+            .map_err(|e| format!("Failed to insert sync record: {}", e))?;
+        Ok(())
+    }
+
+    async fn mark_applied(&self, record: &SyncRecord, error: Option<String>) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "UPDATE sync_blob_log SET applied_at = $applied_at, error = $error WHERE id = $id",
+                HashMap::from([
+                    (
+                        "applied_at".to_string(),
+                        Value::Integer(chrono::Utc::now().timestamp_millis()),
+                    ),
+                    (
+                        "error".to_string(),
+                        error.map(Value::String).unwrap_or(Value::Null),
+                    ),
+                    (
+                        "id".to_string(),
+                        Value::String(format!("{}:{}", record.device_id, record.seq)),
+                    ),
+                ]),
+            )
+            .await
+            .map_err(|e| format!("Failed to mark sync record applied: {}", e))?;
+        Ok(())
+    }
+
+    async fn load(&self, sql: &str, params: HashMap<String, Value>) -> Result<Vec<SyncLogRecord>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(sql, params)
+            .await
+            .map_err(|e| format!("Failed to load sync records: {}", e))?;
+        let mut records = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut entity = DynamicEntity::new("sync_blob_log");
+            entity.fields = row;
+            records.push(SyncLogRecord::from_entity(entity)?);
+        }
+        Ok(records)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationObserver for SyncBlobLog {
+    fn entity_filter(&self) -> &str {
+        "*"
+    }
+
+    async fn on_operation_executed(&self, operation: &Operation, _undo_action: &UndoAction) {
+        // Remote records are already in the log; provider syncs aren't user operations
+        if APPLYING_REMOTE.try_with(|_| ()).is_ok() || operation.op_name == "sync" {
+            return;
+        }
+        if let Err(e) = self
+            .record_local(operation, chrono::Utc::now().timestamp_millis())
+            .await
+        {
+            tracing::error!("Failed to record operation for sync: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+
+    fn clock(entries: &[(&str, u64)]) -> VectorClock {
+        VectorClock(
+            entries
+                .iter()
+                .map(|(device_id, counter)| (device_id.to_string(), *counter))
+                .collect(),
+        )
+    }
+
+    fn record(device_id: &str, seq: u64, clock: VectorClock) -> SyncRecord {
+        SyncRecord {
+            device_id: device_id.to_string(),
+            seq,
+            clock,
+            created_at: 0,
+            operation: Operation::new(
+                "blocks",
+                "set_field",
+                "",
+                HashMap::from([("id".to_string(), Value::String(format!("b{}", seq)))]),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_vector_clock_causality() {
+        let a = clock(&[("a", 1)]);
+        let b = clock(&[("a", 1), ("b", 1)]);
+        let c = clock(&[("a", 2)]);
+        assert_eq!(a.causal_cmp(&b), Some(Ordering::Less));
+        assert_eq!(b.causal_cmp(&a), Some(Ordering::Greater));
+        assert_eq!(b.causal_cmp(&c), None);
+
+        let mut merged = b.clone();
+        merged.merge(&c);
+        assert_eq!(merged, clock(&[("a", 2), ("b", 1)]));
+    }
+
+    #[test]
+    fn test_merge_order_is_deterministic_and_causal() {
+        let first = record("b", 1, clock(&[("b", 1)]));
+        let concurrent = record("a", 1, clock(&[("a", 1)]));
+        let after_both = record("b", 2, clock(&[("a", 1), ("b", 2)]));
+
+        let mut one = vec![after_both.clone(), first.clone(), concurrent.clone()];
+        let mut two = vec![concurrent.clone(), after_both.clone(), first.clone()];
+        one.sort_by(SyncRecord::cmp_merge_order);
+        two.sort_by(SyncRecord::cmp_merge_order);
+        assert_eq!(one, two);
+        assert_eq!(one, vec![concurrent, first, after_both]);
+    }
+
+    #[test]
+    fn test_blob_roundtrip_and_append() {
+        let blob_key = BlobKey::new("a", "secret").unwrap();
+        let mut blob = blob_key.header.clone();
+        blob.extend(blob_key.seal(&record("a", 1, clock(&[("a", 1)]))).unwrap());
+        blob.extend(blob_key.seal(&record("a", 2, clock(&[("a", 2)]))).unwrap());
+
+        let read = read_blob(&blob, "secret").unwrap();
+        assert_eq!(read.device_id, "a");
+        assert_eq!(read.records.len(), 2);
+        assert!(read_blob(&blob, "wrong").is_err());
+
+        // A torn trailing record is ignored
+        let torn = blob[..blob.len() - 3].to_vec();
+        assert_eq!(read_blob(&torn, "secret").unwrap().records.len(), 1);
+    }
+
+    #[test]
+    fn test_records_cannot_move_between_blobs() {
+        let a = BlobKey::new("a", "secret").unwrap();
+        let b = BlobKey::new("b", "secret").unwrap();
+        let sealed = a.seal(&record("a", 1, clock(&[("a", 1)]))).unwrap();
+        let mut blob = b.header.clone();
+        blob.extend(sealed);
+        assert!(read_blob(&blob, "secret").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_local_records_get_sequence_and_clock() {
+        let log = SyncBlobLog::new(memory_backend().await);
+        log.initialize_schema().await.unwrap();
+        let device_id = log.device_id();
+
+        let op = record("x", 1, VectorClock::default()).operation;
+        let first = log.record_local(&op, 1).await.unwrap();
+        let second = log.record_local(&op, 2).await.unwrap();
+        assert_eq!((first.seq, second.seq), (1, 2));
+        assert_eq!(second.clock.get(&device_id), 2);
+
+        let blob = log.append_local(&[], "secret").await.unwrap();
+        assert_eq!(read_blob(&blob, "secret").unwrap().records.len(), 2);
+
+        // Appending again adds nothing until there are new records
+        assert!(log.append_local(&blob, "secret").await.unwrap().is_empty());
+    }
+}
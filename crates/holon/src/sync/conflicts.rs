@@ -0,0 +1,778 @@
+//! Field-level conflict resolution
+//!
+//! A remote change conflicts with local changes when it delivers a different value
+//! for a field that pending (not yet acknowledged) local operations changed.
+//! `MergeStrategies` maps `(entity, field)` to the `MergeStrategy` deciding such
+//! conflicts, and `SyncReconciler` applies them to incoming remote changes before
+//! they reach the cache. Conflicts a strategy leaves open keep the local value and
+//! are recorded in the `sync_conflicts` table, which queries can read like any
+//! other entity:
+//!
+//! ```prql
+//! from sync_conflicts
+//! filter entity_name == "todoist_tasks"
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock as StdRwLock};
+
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::core::queryable_cache::stored_value;
+use crate::storage::turso::TursoBackend;
+use crate::storage::types::StorageEntity;
+use crate::sync::dirty::SyncDirtyStore;
+use holon_api::{CHANGE_ORIGIN_COLUMN, Change, ChangeOrigin, DynamicEntity, HasSchema, Value};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Field the remote modification time is read from, for last-writer-wins
+///
+/// The field itself never conflicts: the remote value is always taken.
+pub const REMOTE_MODIFIED_AT_FIELD: &str = "updated_at";
+
+/// A field changed both locally and remotely
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldConflict {
+    pub entity_name: String,
+    pub entity_id: String,
+    pub field: String,
+    /// Value in the local cache
+    pub local: Value,
+    /// Value delivered by the provider
+    pub remote: Value,
+    /// When the newest pending local operation was executed (Unix timestamp in milliseconds)
+    pub local_modified_at: Option<i64>,
+    /// Remote modification time from `updated_at`, if the entity has one
+    pub remote_modified_at: Option<i64>,
+}
+
+/// Custom merge: the merged value, or `None` to leave the conflict unresolved
+pub type MergeFn = dyn Fn(&FieldConflict) -> Option<Value> + Send + Sync;
+
+/// How a field conflict is decided
+#[derive(Clone)]
+pub enum MergeStrategy {
+    /// The side changed last wins; remote wins if either time is unknown
+    LastWriterWins,
+    PreferLocal,
+    PreferRemote,
+    /// Keep the local value and record the conflict for the user
+    Manual,
+    Custom(Arc<MergeFn>),
+}
+
+impl MergeStrategy {
+    pub fn custom(merge: impl Fn(&FieldConflict) -> Option<Value> + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(merge))
+    }
+
+    /// Union of both sides' lists, local items first (e.g. labels)
+    ///
+    /// Lists are arrays, JSON arrays or comma-separated strings; the merged list
+    /// has the representation of the local value. Other values stay unresolved.
+    pub fn union() -> Self {
+        Self::custom(union_of_lists)
+    }
+
+    /// The merged value, or `None` if the conflict stays unresolved
+    pub fn resolve(&self, conflict: &FieldConflict) -> Option<Value> {
+        match self {
+            Self::LastWriterWins => {
+                match (conflict.local_modified_at, conflict.remote_modified_at) {
+                    (Some(local), Some(remote)) if local > remote => Some(conflict.local.clone()),
+                    _ => Some(conflict.remote.clone()),
+                }
+            }
+            Self::PreferLocal => Some(conflict.local.clone()),
+            Self::PreferRemote => Some(conflict.remote.clone()),
+            Self::Manual => None,
+            Self::Custom(merge) => merge(conflict),
+        }
+    }
+}
+
+impl fmt::Debug for MergeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LastWriterWins => f.write_str("LastWriterWins"),
+            Self::PreferLocal => f.write_str("PreferLocal"),
+            Self::PreferRemote => f.write_str("PreferRemote"),
+            Self::Manual => f.write_str("Manual"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+fn union_of_lists(conflict: &FieldConflict) -> Option<Value> {
+    fn items(value: &Value) -> Option<Vec<Value>> {
+        match value {
+            Value::Null => Some(Vec::new()),
+            Value::Array(items) => Some(items.clone()),
+            Value::Json(json) => Value::from_json_str(json).ok()?.as_array().cloned(),
+            Value::String(list) => Some(
+                list.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| Value::String(item.to_string()))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    let mut merged = items(&conflict.local)?;
+    for item in items(&conflict.remote)? {
+        if !merged.contains(&item) {
+            merged.push(item);
+        }
+    }
+
+    let shape = if conflict.local.is_null() {
+        &conflict.remote
+    } else {
+        &conflict.local
+    };
+    Some(match shape {
+        Value::String(_) => Value::String(
+            merged
+                .iter()
+                .map(|item| {
+                    item.as_string_owned()
+                        .unwrap_or_else(|| item.to_json_string())
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        Value::Json(_) => Value::Json(Value::Array(merged).to_json_string()),
+        _ => Value::Array(merged),
+    })
+}
+
+/// Merge strategies per entity and field
+///
+/// A field's strategy takes precedence over its entity's, which takes precedence
+/// over the default (last-writer-wins unless configured otherwise).
+pub struct MergeStrategies {
+    table: StdRwLock<StrategyTable>,
+}
+
+struct StrategyTable {
+    default: MergeStrategy,
+    entities: HashMap<String, MergeStrategy>,
+    fields: HashMap<(String, String), MergeStrategy>,
+}
+
+impl Default for MergeStrategies {
+    fn default() -> Self {
+        Self::new(MergeStrategy::LastWriterWins)
+    }
+}
+
+impl MergeStrategies {
+    pub fn new(default: MergeStrategy) -> Self {
+        Self {
+            table: StdRwLock::new(StrategyTable {
+                default,
+                entities: HashMap::new(),
+                fields: HashMap::new(),
+            }),
+        }
+    }
+
+    pub fn set_default(&self, strategy: MergeStrategy) {
+        self.table.write().unwrap().default = strategy;
+    }
+
+    /// Strategy for all fields of an entity without a field strategy
+    pub fn register_entity(&self, entity_name: &str, strategy: MergeStrategy) {
+        self.table
+            .write()
+            .unwrap()
+            .entities
+            .insert(entity_name.to_string(), strategy);
+    }
+
+    /// Strategy for one field of an entity
+    pub fn register(&self, entity_name: &str, field: &str, strategy: MergeStrategy) {
+        self.table
+            .write()
+            .unwrap()
+            .fields
+            .insert((entity_name.to_string(), field.to_string()), strategy);
+    }
+
+    pub fn strategy_for(&self, entity_name: &str, field: &str) -> MergeStrategy {
+        let table = self.table.read().unwrap();
+        table
+            .fields
+            .get(&(entity_name.to_string(), field.to_string()))
+            .or_else(|| table.entities.get(entity_name))
+            .unwrap_or(&table.default)
+            .clone()
+    }
+
+    /// Decide a conflict with the strategy registered for its field
+    pub fn resolve(&self, conflict: &FieldConflict) -> Option<Value> {
+        self.strategy_for(&conflict.entity_name, &conflict.field)
+            .resolve(conflict)
+    }
+}
+
+/// A conflict no strategy could resolve
+///
+/// Table name: `sync_conflicts`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Entity)]
+#[entity(name = "sync_conflicts", short_name = "conflict")]
+pub struct SyncConflict {
+    /// `{entity_name}:{entity_id}:{field}`
+    #[primary_key]
+    pub id: String,
+    #[indexed]
+    pub entity_name: String,
+    /// ID of the conflicting entity (join key for queries)
+    #[indexed]
+    pub entity_id: String,
+    pub field: String,
+    /// Local value as JSON (the value the cache keeps)
+    pub local_value: String,
+    /// Remote value as JSON
+    pub remote_value: String,
+    /// Unix timestamp in milliseconds of the latest detection
+    pub detected_at: i64,
+}
+
+impl SyncConflict {
+    pub fn key(entity_name: &str, entity_id: &str, field: &str) -> String {
+        format!("{}:{}:{}", entity_name, entity_id, field)
+    }
+
+    pub fn new(conflict: &FieldConflict, detected_at: i64) -> Self {
+        Self {
+            id: Self::key(&conflict.entity_name, &conflict.entity_id, &conflict.field),
+            entity_name: conflict.entity_name.clone(),
+            entity_id: conflict.entity_id.clone(),
+            field: conflict.field.clone(),
+            local_value: conflict.local.to_json_string(),
+            remote_value: conflict.remote.to_json_string(),
+            detected_at,
+        }
+    }
+
+    pub fn local(&self) -> Value {
+        Value::from_json_str(&self.local_value).unwrap_or(Value::Null)
+    }
+
+    pub fn remote(&self) -> Value {
+        Value::from_json_str(&self.remote_value).unwrap_or(Value::Null)
+    }
+}
+
+/// Applies merge strategies to remote changes of entities with pending local changes
+pub struct SyncReconciler {
+    backend: Arc<RwLock<TursoBackend>>,
+    sync_dirty: Arc<SyncDirtyStore>,
+    strategies: Arc<MergeStrategies>,
+}
+
+impl SyncReconciler {
+    pub fn new(
+        backend: Arc<RwLock<TursoBackend>>,
+        sync_dirty: Arc<SyncDirtyStore>,
+        strategies: Arc<MergeStrategies>,
+    ) -> Self {
+        Self {
+            backend,
+            sync_dirty,
+            strategies,
+        }
+    }
+
+    pub fn strategies(&self) -> &Arc<MergeStrategies> {
+        &self.strategies
+    }
+
+    /// Initialize the sync_conflicts table.
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = SyncConflict::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create sync_conflicts table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        info!("Sync conflicts schema initialized");
+        Ok(())
+    }
+
+    /// Resolve conflicts in a batch of changes before it is applied to the cache
+    ///
+    /// Only remote creates and updates of entities with pending local changes are
+    /// touched: conflicting fields get the merged value, or keep the local value
+    /// and are recorded in `sync_conflicts` if they stay unresolved.
+    pub async fn reconcile<T>(&self, changes: Vec<Change<T>>) -> Result<Vec<Change<T>>>
+    where
+        T: HasSchema + Clone,
+    {
+        let schema = T::schema();
+        let entity_name = schema.table_name.clone();
+        let id_field = schema
+            .fields
+            .iter()
+            .find(|f| f.primary_key)
+            .map(|f| f.name.clone())
+            .unwrap_or_else(|| "id".to_string());
+        let fields: Vec<String> = schema
+            .stored_fields()
+            .map(|f| f.name.clone())
+            .filter(|name| {
+                name != &id_field
+                    && name != CHANGE_ORIGIN_COLUMN
+                    && name != REMOTE_MODIFIED_AT_FIELD
+            })
+            .collect();
+
+        let mut unresolved = Vec::new();
+        let mut reconciled = Vec::with_capacity(changes.len());
+        for change in changes {
+            let change = match change {
+                Change::Created { data, origin } if is_remote(&origin) => {
+                    let mut entity = data.to_entity();
+                    let Some(id) = entity.get_string(&id_field) else {
+                        reconciled.push(Change::Created { data, origin });
+                        continue;
+                    };
+                    if self
+                        .merge(
+                            &entity_name,
+                            &id_field,
+                            &id,
+                            &fields,
+                            &mut entity.fields,
+                            &mut unresolved,
+                        )
+                        .await?
+                    {
+                        Change::Created {
+                            data: T::from_entity(entity)?,
+                            origin,
+                        }
+                    } else {
+                        Change::Created { data, origin }
+                    }
+                }
+                Change::Updated { id, data, origin } if is_remote(&origin) => {
+                    let mut entity = data.to_entity();
+                    if self
+                        .merge(
+                            &entity_name,
+                            &id_field,
+                            &id,
+                            &fields,
+                            &mut entity.fields,
+                            &mut unresolved,
+                        )
+                        .await?
+                    {
+                        Change::Updated {
+                            id,
+                            data: T::from_entity(entity)?,
+                            origin,
+                        }
+                    } else {
+                        Change::Updated { id, data, origin }
+                    }
+                }
+                Change::ColumnChange {
+                    id,
+                    mut columns,
+                    origin,
+                } if is_remote(&origin) => {
+                    self.merge(
+                        &entity_name,
+                        &id_field,
+                        &id,
+                        &fields,
+                        &mut columns,
+                        &mut unresolved,
+                    )
+                    .await?;
+                    Change::ColumnChange {
+                        id,
+                        columns,
+                        origin,
+                    }
+                }
+                change => change,
+            };
+            reconciled.push(change);
+        }
+
+        if !unresolved.is_empty() {
+            self.record(&unresolved).await?;
+        }
+        Ok(reconciled)
+    }
+
+    /// Merge the remote values of one entity in place; returns whether any changed
+    async fn merge(
+        &self,
+        entity_name: &str,
+        id_field: &str,
+        entity_id: &str,
+        fields: &[String],
+        remote: &mut StorageEntity,
+        unresolved: &mut Vec<SyncConflict>,
+    ) -> Result<bool> {
+        let Some(pending) = self
+            .sync_dirty
+            .pending_changes(entity_name, entity_id)
+            .await?
+        else {
+            return Ok(false);
+        };
+        let Some(local) = self.local_row(entity_name, id_field, entity_id).await? else {
+            return Ok(false);
+        };
+
+        let remote_modified_at = remote
+            .get(REMOTE_MODIFIED_AT_FIELD)
+            .and_then(timestamp_millis);
+        let detected_at = chrono::Utc::now().timestamp_millis();
+        let mut changed = false;
+        for field in fields.iter().filter(|f| pending.fields.contains(*f)) {
+            let Some(remote_value) = remote.get(field) else {
+                continue;
+            };
+            if stored_value(local.get(field)) == stored_value(Some(remote_value)) {
+                continue;
+            }
+
+            let conflict = FieldConflict {
+                entity_name: entity_name.to_string(),
+                entity_id: entity_id.to_string(),
+                field: field.clone(),
+                local: local.get(field).cloned().unwrap_or(Value::Null),
+                remote: remote_value.clone(),
+                local_modified_at: Some(pending.last_pending_at),
+                remote_modified_at,
+            };
+            let merged = match self.strategies.resolve(&conflict) {
+                Some(merged) => {
+                    debug!(
+                        "Merged conflicting {} of {}:{}",
+                        field, entity_name, entity_id
+                    );
+                    merged
+                }
+                None => {
+                    unresolved.push(SyncConflict::new(&conflict, detected_at));
+                    conflict.local.clone()
+                }
+            };
+            if stored_value(Some(&merged)) != stored_value(Some(&conflict.remote)) {
+                remote.insert(field.clone(), merged);
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+
+    async fn local_row(
+        &self,
+        table_name: &str,
+        id_field: &str,
+        entity_id: &str,
+    ) -> Result<Option<StorageEntity>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                &format!("SELECT * FROM {} WHERE {} = $id", table_name, id_field),
+                HashMap::from([("id".to_string(), Value::String(entity_id.to_string()))]),
+            )
+            .await
+            .map_err(|e| format!("Failed to read {} for reconciliation: {}", table_name, e))?;
+        Ok(rows.into_iter().next())
+    }
+
+    async fn record(&self, conflicts: &[SyncConflict]) -> Result<()> {
+        let sql = "INSERT INTO sync_conflicts (id, entity_name, entity_id, field, local_value, remote_value, detected_at)
+            VALUES ($id, $entity_name, $entity_id, $field, $local_value, $remote_value, $detected_at)
+            ON CONFLICT(id) DO UPDATE SET
+                local_value = excluded.local_value,
+                remote_value = excluded.remote_value,
+                detected_at = excluded.detected_at";
+
+        let backend = self.backend.read().await;
+        for conflict in conflicts {
+            backend
+                .execute_sql(sql, conflict.to_entity().fields)
+                .await
+                .map_err(|e| format!("Failed to record sync conflict: {}", e))?;
+        }
+        info!("Recorded {} unresolved sync conflicts", conflicts.len());
+        Ok(())
+    }
+
+    /// Unresolved conflicts, optionally restricted to one entity type
+    pub async fn conflicts(&self, entity_name: Option<&str>) -> Result<Vec<SyncConflict>> {
+        let backend = self.backend.read().await;
+        let rows = match entity_name {
+            Some(entity_name) => backend
+                .execute_sql(
+                    "SELECT * FROM sync_conflicts WHERE entity_name = $entity_name ORDER BY detected_at ASC",
+                    HashMap::from([(
+                        "entity_name".to_string(),
+                        Value::String(entity_name.to_string()),
+                    )]),
+                )
+                .await,
+            None => backend
+                .execute_sql(
+                    "SELECT * FROM sync_conflicts ORDER BY detected_at ASC",
+                    HashMap::new(),
+                )
+                .await,
+        }
+        .map_err(|e| format!("Failed to query sync_conflicts: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new("sync_conflicts");
+                entity.fields = row;
+                SyncConflict::from_entity(entity)
+            })
+            .collect()
+    }
+
+    pub async fn conflict(&self, id: &str) -> Result<Option<SyncConflict>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT * FROM sync_conflicts WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query sync_conflicts: {}", e))?;
+
+        rows.into_iter()
+            .next()
+            .map(|row| {
+                let mut entity = DynamicEntity::new("sync_conflicts");
+                entity.fields = row;
+                SyncConflict::from_entity(entity)
+            })
+            .transpose()
+    }
+
+    /// Remove a conflict once it is resolved
+    pub async fn dismiss(&self, id: &str) -> Result<()> {
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "DELETE FROM sync_conflicts WHERE id = $id",
+                HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+            )
+            .await
+            .map_err(|e| format!("Failed to delete sync conflict: {}", e))?;
+        Ok(())
+    }
+}
+
+fn is_remote(origin: &ChangeOrigin) -> bool {
+    matches!(origin, ChangeOrigin::Remote { .. })
+}
+
+/// Unix timestamp in milliseconds of an integer or RFC3339 value
+fn timestamp_millis(value: &Value) -> Option<i64> {
+    match value {
+        Value::Integer(millis) => Some(*millis),
+        Value::String(s) | Value::DateTime(s) => chrono::DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.timestamp_millis()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::operation_log::OperationLogStore;
+    use crate::storage::test_support::memory_backend;
+    use holon_api::Operation;
+    use holon_core::{OperationLogOperations, UndoAction};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Entity)]
+    #[entity(name = "remote_tasks")]
+    struct RemoteTask {
+        #[primary_key]
+        id: String,
+        content: String,
+        labels: Option<String>,
+        priority: i64,
+    }
+
+    fn task(content: &str, labels: &str, priority: i64) -> RemoteTask {
+        RemoteTask {
+            id: "t1".to_string(),
+            content: content.to_string(),
+            labels: Some(labels.to_string()),
+            priority,
+        }
+    }
+
+    fn remote_update(task: RemoteTask) -> Change<RemoteTask> {
+        Change::Updated {
+            id: task.id.clone(),
+            data: task,
+            origin: ChangeOrigin::Remote {
+                operation_id: None,
+                trace_id: None,
+                source: None,
+            },
+        }
+    }
+
+    async fn create_reconciler(strategies: MergeStrategies) -> (SyncReconciler, OperationLogStore) {
+        let backend = memory_backend().await;
+
+        let log = OperationLogStore::new(backend.clone());
+        log.initialize_schema()
+            .await
+            .expect("Failed to initialize operation log");
+        let sync_dirty = Arc::new(SyncDirtyStore::new(backend.clone()));
+        sync_dirty
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize dirty schema");
+
+        let guard = backend.read().await;
+        guard
+            .execute_sql(&RemoteTask::schema().to_create_table_sql(), HashMap::new())
+            .await
+            .unwrap();
+        guard
+            .execute_sql(
+                "INSERT INTO remote_tasks (id, content, labels, priority) VALUES ($id, $content, $labels, $priority)",
+                task("Local", "home", 1).to_entity().fields,
+            )
+            .await
+            .unwrap();
+        drop(guard);
+
+        let reconciler = SyncReconciler::new(backend, sync_dirty, Arc::new(strategies));
+        reconciler
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        (reconciler, log)
+    }
+
+    async fn set_field(log: &OperationLogStore, field: &str) {
+        let op = Operation::new(
+            "remote_tasks",
+            "set_field",
+            "Set field",
+            HashMap::from([
+                ("id".to_string(), Value::String("t1".to_string())),
+                ("field".to_string(), Value::String(field.to_string())),
+            ]),
+        );
+        log.log_operation(op, UndoAction::Irreversible)
+            .await
+            .unwrap();
+    }
+
+    fn conflict(local: Value, remote: Value) -> FieldConflict {
+        FieldConflict {
+            entity_name: "remote_tasks".to_string(),
+            entity_id: "t1".to_string(),
+            field: "labels".to_string(),
+            local,
+            remote,
+            local_modified_at: Some(2),
+            remote_modified_at: Some(1),
+        }
+    }
+
+    #[test]
+    fn test_strategies() {
+        let strategies = MergeStrategies::default();
+        strategies.register("remote_tasks", "labels", MergeStrategy::union());
+
+        let labels = conflict(
+            Value::String("home, errands".to_string()),
+            Value::String("errands,work".to_string()),
+        );
+        assert_eq!(
+            strategies.resolve(&labels),
+            Some(Value::String("home,errands,work".to_string()))
+        );
+
+        // Last-writer-wins: local was changed after the remote
+        let content = FieldConflict {
+            field: "content".to_string(),
+            ..conflict(
+                Value::String("a".to_string()),
+                Value::String("b".to_string()),
+            )
+        };
+        assert_eq!(
+            strategies.resolve(&content),
+            Some(Value::String("a".to_string()))
+        );
+
+        strategies.register_entity("remote_tasks", MergeStrategy::Manual);
+        assert_eq!(strategies.resolve(&content), None);
+        assert!(strategies.resolve(&labels).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_merges_and_records_conflicts() {
+        let strategies = MergeStrategies::new(MergeStrategy::Manual);
+        strategies.register("remote_tasks", "labels", MergeStrategy::union());
+        let (reconciler, log) = create_reconciler(strategies).await;
+
+        // Not dirty: remote changes pass through
+        let changes = reconciler
+            .reconcile(vec![remote_update(task("Remote", "work", 1))])
+            .await
+            .unwrap();
+        assert!(matches!(&changes[0], Change::Updated { data, .. } if data.content == "Remote"));
+
+        set_field(&log, "content").await;
+        set_field(&log, "labels").await;
+        let changes = reconciler
+            .reconcile(vec![remote_update(task("Remote", "work", 4))])
+            .await
+            .unwrap();
+        let Change::Updated { data, .. } = &changes[0] else {
+            panic!("expected update");
+        };
+        // Unresolved content keeps the local value, labels are merged, priority
+        // wasn't changed locally so the remote value is taken
+        assert_eq!(data.content, "Local");
+        assert_eq!(data.labels.as_deref(), Some("home,work"));
+        assert_eq!(data.priority, 4);
+
+        let conflicts = reconciler.conflicts(Some("remote_tasks")).await.unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].id, "remote_tasks:t1:content");
+        assert_eq!(conflicts[0].remote(), Value::String("Remote".to_string()));
+
+        reconciler.dismiss(&conflicts[0].id).await.unwrap();
+        assert!(reconciler.conflicts(None).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,609 @@
+//! Unsynced-changes ("dirty") tracking
+//!
+//! Local operations on a provider's entities stay `pending_sync` in the operation
+//! log until the provider's next successful sync acknowledges them. `SyncDirtyStore`
+//! projects these pending operations into the `sync_dirty` table (one row per
+//! changed entity), which queries can join on `entity_id` to render "pending sync"
+//! indicators, and summarizes them per provider.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, RwLock as StdRwLock};
+
+use async_trait::async_trait;
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::core::datasource::OperationObserver;
+use crate::storage::turso::TursoBackend;
+use holon_api::{DynamicEntity, HasSchema, Operation, Value};
+use holon_core::{OperationStatus, UndoAction};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// An entity with local changes the provider hasn't acknowledged yet
+///
+/// Table name: `sync_dirty`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Entity)]
+#[entity(name = "sync_dirty", short_name = "dirty")]
+pub struct DirtyEntity {
+    /// `{entity_name}:{entity_id}`
+    #[primary_key]
+    pub id: String,
+    #[indexed]
+    pub entity_name: String,
+    /// ID of the changed entity (join key for queries)
+    #[indexed]
+    pub entity_id: String,
+    #[indexed]
+    pub provider_name: String,
+    /// Number of pending operations on this entity
+    pub pending_count: i64,
+    /// When the oldest pending operation was executed (Unix timestamp in milliseconds)
+    pub first_pending_at: i64,
+    /// When the newest pending operation was executed (Unix timestamp in milliseconds)
+    pub last_pending_at: i64,
+}
+
+/// Unsynced changes of a single provider
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderDirtyStatus {
+    pub provider_name: String,
+    /// Number of entities with pending changes
+    pub dirty_entities: i64,
+    /// Number of pending operations, including ones without an entity ID (e.g. creates)
+    pub pending_operations: i64,
+    pub oldest_pending_at: Option<i64>,
+}
+
+/// Fields of one entity changed by its pending operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingChanges {
+    /// Changed fields: the `field` parameter of `set_field`, otherwise all parameters but `id`
+    pub fields: HashSet<String>,
+    /// When the newest pending operation was executed (Unix timestamp in milliseconds)
+    pub last_pending_at: i64,
+}
+
+/// A pending operation read from the operation log
+struct PendingOperation {
+    entity_name: String,
+    entity_id: Option<String>,
+    created_at: i64,
+}
+
+/// Tracks which entities have local changes that are not yet synced
+pub struct SyncDirtyStore {
+    backend: Arc<RwLock<TursoBackend>>,
+    provider_entities: StdRwLock<HashMap<String, Vec<String>>>,
+}
+
+impl SyncDirtyStore {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>) -> Self {
+        Self {
+            backend,
+            provider_entities: StdRwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Initialize the sync_dirty table.
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let schema = DirtyEntity::schema();
+        let backend = self.backend.read().await;
+
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create sync_dirty table: {}", e))?;
+
+        for index_sql in schema.to_index_sql() {
+            backend
+                .execute_sql(&index_sql, HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create index: {}", e))?;
+        }
+
+        info!("Sync dirty schema initialized");
+        Ok(())
+    }
+
+    /// Declare which entities belong to a provider.
+    ///
+    /// Without a registration, entities named `{provider}_*` or `{provider}-*` are
+    /// attributed to providers that have a persisted sync token.
+    pub fn register_provider_entities(&self, provider_name: &str, entity_names: Vec<String>) {
+        self.provider_entities
+            .write()
+            .unwrap()
+            .insert(provider_name.to_string(), entity_names);
+    }
+
+    /// Provider that syncs the given entity, if any
+    pub async fn provider_for_entity(&self, entity_name: &str) -> Option<String> {
+        let registered = self
+            .provider_entities
+            .read()
+            .unwrap()
+            .iter()
+            .find(|(_, entities)| entities.iter().any(|e| e == entity_name))
+            .map(|(provider, _)| provider.clone());
+        if registered.is_some() {
+            return registered;
+        }
+
+        self.known_providers().await.into_iter().find(|provider| {
+            entity_name
+                .strip_prefix(provider.as_str())
+                .is_some_and(|rest| rest.starts_with('_') || rest.starts_with('-'))
+        })
+    }
+
+    /// Mark an entity dirty after a local operation
+    ///
+    /// Operations on entities that no provider syncs, and `sync` operations
+    /// themselves, are ignored.
+    pub async fn record_operation(&self, operation: &Operation, at: i64) -> Result<()> {
+        if operation.op_name == "sync" {
+            return Ok(());
+        }
+        let Some(entity_id) = operation_entity_id(operation) else {
+            return Ok(());
+        };
+        let Some(provider_name) = self.provider_for_entity(&operation.entity_name).await else {
+            return Ok(());
+        };
+
+        let entry = DirtyEntity {
+            id: format!("{}:{}", operation.entity_name, entity_id),
+            entity_name: operation.entity_name.clone(),
+            entity_id,
+            provider_name,
+            pending_count: 1,
+            first_pending_at: at,
+            last_pending_at: at,
+        };
+
+        let sql = "INSERT INTO sync_dirty (id, entity_name, entity_id, provider_name, pending_count, first_pending_at, last_pending_at)
+            VALUES ($id, $entity_name, $entity_id, $provider_name, $pending_count, $first_pending_at, $last_pending_at)
+            ON CONFLICT(id) DO UPDATE SET
+                pending_count = pending_count + 1,
+                last_pending_at = excluded.last_pending_at";
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(sql, entry.to_entity().fields)
+            .await
+            .map_err(|e| format!("Failed to mark entity dirty: {}", e))?;
+
+        debug!("Marked {} dirty", entry.id);
+        Ok(())
+    }
+
+    /// Recompute the sync_dirty table from the pending operations in the log
+    pub async fn rebuild(&self) -> Result<()> {
+        let pending = self.pending_operations().await?;
+
+        let mut entries: BTreeMap<String, DirtyEntity> = BTreeMap::new();
+        for (provider_name, op) in pending {
+            let Some(entity_id) = op.entity_id else {
+                continue;
+            };
+            let id = format!("{}:{}", op.entity_name, entity_id);
+            entries
+                .entry(id.clone())
+                .and_modify(|entry| {
+                    entry.pending_count += 1;
+                    entry.first_pending_at = entry.first_pending_at.min(op.created_at);
+                    entry.last_pending_at = entry.last_pending_at.max(op.created_at);
+                })
+                .or_insert(DirtyEntity {
+                    id,
+                    entity_name: op.entity_name,
+                    entity_id,
+                    provider_name,
+                    pending_count: 1,
+                    first_pending_at: op.created_at,
+                    last_pending_at: op.created_at,
+                });
+        }
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql("DELETE FROM sync_dirty", HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to clear sync_dirty: {}", e))?;
+
+        let sql = "INSERT INTO sync_dirty (id, entity_name, entity_id, provider_name, pending_count, first_pending_at, last_pending_at)
+            VALUES ($id, $entity_name, $entity_id, $provider_name, $pending_count, $first_pending_at, $last_pending_at)";
+        for entry in entries.into_values() {
+            backend
+                .execute_sql(sql, entry.to_entity().fields)
+                .await
+                .map_err(|e| format!("Failed to insert dirty entity: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Acknowledge a provider's pending operations after a successful sync
+    ///
+    /// Operations executed up to `synced_at` are marked `synced` in the log and
+    /// the sync_dirty table is rebuilt. Returns the number of acknowledged operations.
+    pub async fn mark_provider_synced(&self, provider_name: &str, synced_at: i64) -> Result<usize> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT id, entity_name, created_at FROM operations WHERE status = $status AND op_name != 'sync'",
+                HashMap::from([(
+                    "status".to_string(),
+                    Value::String(OperationStatus::PendingSync.as_str().to_string()),
+                )]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query pending operations: {}", e))?;
+        drop(backend);
+
+        let mut acknowledged = Vec::new();
+        for row in rows {
+            let created_at = row.get("created_at").and_then(|v| v.as_i64()).unwrap_or(0);
+            let (Some(id), Some(entity_name)) = (
+                row.get("id").and_then(|v| v.as_i64()),
+                row.get("entity_name").and_then(|v| v.as_string()),
+            ) else {
+                continue;
+            };
+            if created_at <= synced_at
+                && self.provider_for_entity(entity_name).await.as_deref() == Some(provider_name)
+            {
+                acknowledged.push(id.to_string());
+            }
+        }
+
+        if !acknowledged.is_empty() {
+            let sql = format!(
+                "UPDATE operations SET status = $status WHERE id IN ({})",
+                acknowledged.join(", ")
+            );
+            let backend = self.backend.read().await;
+            backend
+                .execute_sql(
+                    &sql,
+                    HashMap::from([(
+                        "status".to_string(),
+                        Value::String(OperationStatus::Synced.as_str().to_string()),
+                    )]),
+                )
+                .await
+                .map_err(|e| format!("Failed to mark operations synced: {}", e))?;
+        }
+
+        self.rebuild().await?;
+        debug!(
+            "Acknowledged {} pending operations for {}",
+            acknowledged.len(),
+            provider_name
+        );
+        Ok(acknowledged.len())
+    }
+
+    /// Whether the entity has local changes not yet acknowledged by its provider
+    pub async fn is_dirty(&self, entity_name: &str, entity_id: &str) -> Result<bool> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT id FROM sync_dirty WHERE id = $id",
+                HashMap::from([(
+                    "id".to_string(),
+                    Value::String(format!("{}:{}", entity_name, entity_id)),
+                )]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query sync_dirty: {}", e))?;
+        Ok(!rows.is_empty())
+    }
+
+    /// Fields changed by the entity's pending operations, or `None` if it isn't dirty
+    pub async fn pending_changes(
+        &self,
+        entity_name: &str,
+        entity_id: &str,
+    ) -> Result<Option<PendingChanges>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT operation, created_at FROM operations WHERE status = $status AND entity_name = $entity_name AND op_name != 'sync' ORDER BY id ASC",
+                HashMap::from([
+                    (
+                        "status".to_string(),
+                        Value::String(OperationStatus::PendingSync.as_str().to_string()),
+                    ),
+                    (
+                        "entity_name".to_string(),
+                        Value::String(entity_name.to_string()),
+                    ),
+                ]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query pending operations: {}", e))?;
+
+        let mut pending: Option<PendingChanges> = None;
+        for row in rows {
+            let Some(operation) = row
+                .get("operation")
+                .and_then(|v| v.as_string())
+                .and_then(|json| serde_json::from_str::<Operation>(json).ok())
+            else {
+                continue;
+            };
+            if operation_entity_id(&operation).as_deref() != Some(entity_id) {
+                continue;
+            }
+
+            let created_at = row.get("created_at").and_then(|v| v.as_i64()).unwrap_or(0);
+            let changes = pending.get_or_insert_with(|| PendingChanges {
+                fields: HashSet::new(),
+                last_pending_at: created_at,
+            });
+            changes.last_pending_at = changes.last_pending_at.max(created_at);
+            match operation.params.get("field").and_then(|v| v.as_string()) {
+                Some(field) => {
+                    changes.fields.insert(field.to_string());
+                }
+                None => changes.fields.extend(
+                    operation
+                        .params
+                        .keys()
+                        .filter(|name| name.as_str() != "id")
+                        .cloned(),
+                ),
+            }
+        }
+        Ok(pending)
+    }
+
+    /// Dirty entities, optionally restricted to one provider
+    pub async fn dirty_entities(&self, provider_name: Option<&str>) -> Result<Vec<DirtyEntity>> {
+        let backend = self.backend.read().await;
+        let rows = match provider_name {
+            Some(provider_name) => backend
+                .execute_sql(
+                    "SELECT * FROM sync_dirty WHERE provider_name = $provider ORDER BY first_pending_at ASC",
+                    HashMap::from([(
+                        "provider".to_string(),
+                        Value::String(provider_name.to_string()),
+                    )]),
+                )
+                .await,
+            None => backend
+                .execute_sql(
+                    "SELECT * FROM sync_dirty ORDER BY first_pending_at ASC",
+                    HashMap::new(),
+                )
+                .await,
+        }
+        .map_err(|e| format!("Failed to query sync_dirty: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new("sync_dirty");
+                entity.fields = row;
+                DirtyEntity::from_entity(entity)
+            })
+            .collect()
+    }
+
+    /// Unsynced changes per provider (providers without pending changes are omitted)
+    pub async fn summary(&self) -> Result<Vec<ProviderDirtyStatus>> {
+        let mut summary: BTreeMap<String, ProviderDirtyStatus> = BTreeMap::new();
+        let mut dirty: HashMap<String, HashSet<String>> = HashMap::new();
+
+        for (provider_name, op) in self.pending_operations().await? {
+            let status =
+                summary
+                    .entry(provider_name.clone())
+                    .or_insert_with(|| ProviderDirtyStatus {
+                        provider_name: provider_name.clone(),
+                        dirty_entities: 0,
+                        pending_operations: 0,
+                        oldest_pending_at: None,
+                    });
+            status.pending_operations += 1;
+            status.oldest_pending_at = Some(
+                status
+                    .oldest_pending_at
+                    .map_or(op.created_at, |t| t.min(op.created_at)),
+            );
+            if let Some(entity_id) = op.entity_id {
+                dirty
+                    .entry(provider_name)
+                    .or_default()
+                    .insert(format!("{}:{}", op.entity_name, entity_id));
+            }
+        }
+
+        for (provider_name, entities) in dirty {
+            if let Some(status) = summary.get_mut(&provider_name) {
+                status.dirty_entities = entities.len() as i64;
+            }
+        }
+        Ok(summary.into_values().collect())
+    }
+
+    /// Pending operations of provider-synced entities, with their provider
+    async fn pending_operations(&self) -> Result<Vec<(String, PendingOperation)>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT entity_name, operation, created_at FROM operations WHERE status = $status AND op_name != 'sync' ORDER BY id ASC",
+                HashMap::from([(
+                    "status".to_string(),
+                    Value::String(OperationStatus::PendingSync.as_str().to_string()),
+                )]),
+            )
+            .await
+            .map_err(|e| format!("Failed to query pending operations: {}", e))?;
+        drop(backend);
+
+        let mut pending = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Some(entity_name) = row.get("entity_name").and_then(|v| v.as_string_owned()) else {
+                continue;
+            };
+            let Some(provider_name) = self.provider_for_entity(&entity_name).await else {
+                continue;
+            };
+            let entity_id = row
+                .get("operation")
+                .and_then(|v| v.as_string())
+                .and_then(|json| serde_json::from_str::<Operation>(json).ok())
+                .and_then(|op| operation_entity_id(&op));
+            pending.push((
+                provider_name,
+                PendingOperation {
+                    entity_name,
+                    entity_id,
+                    created_at: row.get("created_at").and_then(|v| v.as_i64()).unwrap_or(0),
+                },
+            ));
+        }
+        Ok(pending)
+    }
+
+    /// Providers known from registrations and persisted sync tokens
+    async fn known_providers(&self) -> Vec<String> {
+        let mut providers: Vec<String> = self
+            .provider_entities
+            .read()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+
+        let backend = self.backend.read().await;
+        // sync_states may not exist yet (no provider ever synced)
+        if let Ok(rows) = backend
+            .execute_sql("SELECT provider_name FROM sync_states", HashMap::new())
+            .await
+        {
+            providers.extend(rows.iter().filter_map(|row| {
+                row.get("provider_name")
+                    .and_then(|v| v.as_string())
+                    .map(|s| s.to_string())
+            }));
+        }
+        providers
+    }
+}
+
+/// ID of the entity an operation changes (its `id` parameter)
+fn operation_entity_id(operation: &Operation) -> Option<String> {
+    match operation.params.get("id")? {
+        Value::String(id) | Value::Reference(id) => Some(id.clone()),
+        Value::Integer(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl OperationObserver for SyncDirtyStore {
+    fn entity_filter(&self) -> &str {
+        "*"
+    }
+
+    async fn on_operation_executed(&self, operation: &Operation, _undo_action: &UndoAction) {
+        if let Err(e) = self
+            .record_operation(operation, chrono::Utc::now().timestamp_millis())
+            .await
+        {
+            tracing::error!("Failed to record unsynced change: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::operation_log::OperationLogStore;
+    use crate::storage::test_support::memory_backend;
+    use holon_core::OperationLogOperations;
+
+    async fn create_stores() -> (SyncDirtyStore, OperationLogStore) {
+        let backend = memory_backend().await;
+
+        let log = OperationLogStore::new(backend.clone());
+        log.initialize_schema()
+            .await
+            .expect("Failed to initialize operation log");
+        let store = SyncDirtyStore::new(backend);
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        store.register_provider_entities("todoist", vec!["todoist_tasks".to_string()]);
+        (store, log)
+    }
+
+    fn set_content(entity_name: &str, id: &str) -> Operation {
+        Operation::new(
+            entity_name,
+            "set_field",
+            "Set content",
+            HashMap::from([("id".to_string(), Value::String(id.to_string()))]),
+        )
+    }
+
+    async fn execute(store: &SyncDirtyStore, log: &OperationLogStore, op: Operation) {
+        log.log_operation(op.clone(), UndoAction::Irreversible)
+            .await
+            .unwrap();
+        store
+            .on_operation_executed(&op, &UndoAction::Irreversible)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_local_changes_mark_entities_dirty() {
+        let (store, log) = create_stores().await;
+        execute(&store, &log, set_content("todoist_tasks", "t1")).await;
+        execute(&store, &log, set_content("todoist_tasks", "t1")).await;
+        execute(&store, &log, set_content("todoist_tasks", "t2")).await;
+        // Local-only entities are never dirty
+        execute(&store, &log, set_content("blocks", "b1")).await;
+
+        assert!(store.is_dirty("todoist_tasks", "t1").await.unwrap());
+        assert!(!store.is_dirty("blocks", "b1").await.unwrap());
+
+        let dirty = store.dirty_entities(Some("todoist")).await.unwrap();
+        assert_eq!(dirty.len(), 2);
+        let t1 = dirty.iter().find(|d| d.entity_id == "t1").unwrap();
+        assert_eq!(t1.pending_count, 2);
+
+        let summary = store.summary().await.unwrap();
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].provider_name, "todoist");
+        assert_eq!(summary[0].dirty_entities, 2);
+        assert_eq!(summary[0].pending_operations, 3);
+    }
+
+    #[tokio::test]
+    async fn test_successful_sync_clears_dirty_state() {
+        let (store, log) = create_stores().await;
+        execute(&store, &log, set_content("todoist_tasks", "t1")).await;
+
+        let acknowledged = store
+            .mark_provider_synced("todoist", chrono::Utc::now().timestamp_millis())
+            .await
+            .unwrap();
+        assert_eq!(acknowledged, 1);
+        assert!(!store.is_dirty("todoist_tasks", "t1").await.unwrap());
+        assert!(store.summary().await.unwrap().is_empty());
+
+        // Changes after the sync are pending again, and survive a rebuild from the log
+        execute(&store, &log, set_content("todoist_tasks", "t2")).await;
+        store.rebuild().await.unwrap();
+        assert!(store.is_dirty("todoist_tasks", "t2").await.unwrap());
+        assert!(!store.is_dirty("todoist_tasks", "t1").await.unwrap());
+    }
+}
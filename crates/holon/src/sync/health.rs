@@ -0,0 +1,818 @@
+//! Sync health tracking and recurring self-check reports
+//!
+//! Every sync attempt is recorded as a `SyncEvent`. Once per report interval
+//! (weekly by default) a `SyncHealthReport` is generated from those events,
+//! stored in the `sync_health_reports` table and surfaced via `NotificationSink`s.
+//! This makes silent failures (e.g. an expired API token) visible within days.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, RwLock as StdRwLock};
+
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::core::notifications::{Notification, NotificationSeverity, NotificationSink};
+use crate::storage::turso::TursoBackend;
+use holon_api::{DynamicEntity, HasSchema, Value};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+const HOUR_MS: i64 = 60 * 60 * 1000;
+const DAY_MS: i64 = 24 * HOUR_MS;
+
+/// Entity name of stored reports
+pub const SYNC_HEALTH_REPORTS_ENTITY: &str = "sync_health_reports";
+
+/// Coarse classification of sync errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SyncErrorCategory {
+    /// Expired or revoked credentials
+    Auth,
+    /// Connection failures and timeouts
+    Network,
+    /// The remote system throttled us
+    RateLimit,
+    /// Local database errors
+    Storage,
+    /// Unexpected response payloads
+    Parse,
+    Other,
+}
+
+impl SyncErrorCategory {
+    /// Classify an error message using common keywords and HTTP status codes
+    pub fn classify(message: &str) -> Self {
+        let msg = message.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| msg.contains(n));
+
+        if has(&["401", "403", "unauthorized", "forbidden", "token", "auth"]) {
+            Self::Auth
+        } else if has(&["429", "rate limit", "too many requests"]) {
+            Self::RateLimit
+        } else if has(&[
+            "timeout",
+            "timed out",
+            "connect",
+            "dns",
+            "network",
+            "unreachable",
+        ]) {
+            Self::Network
+        } else if has(&["database", "sql", "constraint", "locked"]) {
+            Self::Storage
+        } else if has(&["parse", "deserialize", "invalid json", "unexpected"]) {
+            Self::Parse
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::Network => "network",
+            Self::RateLimit => "rate_limit",
+            Self::Storage => "storage",
+            Self::Parse => "parse",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// A single recorded sync attempt
+///
+/// Table name: `sync_events`
+#[derive(Debug, Clone, Serialize, Deserialize, Entity)]
+#[entity(name = "sync_events", short_name = "sync_event")]
+pub struct SyncEvent {
+    #[primary_key]
+    pub id: String,
+    /// Provider that was synced (e.g., "todoist")
+    #[indexed]
+    pub provider_name: String,
+    /// When the sync finished (Unix timestamp in milliseconds)
+    #[indexed]
+    pub occurred_at: i64,
+    pub success: bool,
+    /// `SyncErrorCategory::as_str()` for failed syncs
+    pub error_category: Option<String>,
+    pub error_message: Option<String>,
+    /// Number of changes applied by this sync
+    pub changes_applied: i64,
+    /// Number of conflicts resolved by this sync
+    pub conflicts_resolved: i64,
+}
+
+/// Overall health of a provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ProviderStatus {
+    Healthy,
+    /// Some syncs failed, but the most recent one succeeded
+    Degraded,
+    /// No successful sync within the stale threshold
+    Stale,
+    /// Every sync in the period failed
+    Failing,
+}
+
+impl ProviderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Degraded => "degraded",
+            Self::Stale => "stale",
+            Self::Failing => "failing",
+        }
+    }
+}
+
+/// Health summary for a single provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderHealth {
+    pub provider_name: String,
+    pub status: ProviderStatus,
+    /// Rows in the provider's tables
+    pub entity_count: i64,
+    pub last_success_at: Option<i64>,
+    pub last_attempt_at: Option<i64>,
+    pub successes: i64,
+    pub failures: i64,
+    pub conflicts_resolved: i64,
+    /// Failure counts keyed by `SyncErrorCategory::as_str()`
+    pub errors_by_category: BTreeMap<String, i64>,
+    pub last_error: Option<String>,
+}
+
+/// A generated health report
+///
+/// Table name: `sync_health_reports`
+#[derive(Debug, Clone, Serialize, Deserialize, Entity)]
+#[entity(name = "sync_health_reports", short_name = "sync_report")]
+pub struct SyncHealthReport {
+    #[primary_key]
+    pub id: String,
+    /// When the report was generated (Unix timestamp in milliseconds)
+    #[indexed]
+    pub generated_at: i64,
+    /// Start of the covered period (Unix timestamp in milliseconds)
+    pub period_start: i64,
+    /// Worst `ProviderStatus` across providers
+    pub status: String,
+    /// Human-readable Markdown summary
+    pub summary: String,
+    /// JSON-encoded `Vec<ProviderHealth>`
+    pub providers: String,
+}
+
+impl SyncHealthReport {
+    pub fn provider_health(&self) -> Vec<ProviderHealth> {
+        serde_json::from_str(&self.providers).unwrap_or_default()
+    }
+}
+
+/// Configuration for sync health reporting
+#[derive(Clone, Debug)]
+pub struct SyncHealthConfig {
+    /// How often a report is generated
+    pub report_interval_ms: i64,
+    /// A provider without a successful sync for this long is considered stale
+    pub stale_after_ms: i64,
+    /// Sync events older than this are pruned when a report is generated
+    pub retention_ms: i64,
+}
+
+impl Default for SyncHealthConfig {
+    fn default() -> Self {
+        Self {
+            report_interval_ms: 7 * DAY_MS,
+            stale_after_ms: 3 * DAY_MS,
+            retention_ms: 35 * DAY_MS,
+        }
+    }
+}
+
+/// Records sync attempts and generates recurring health reports
+pub struct SyncHealthStore {
+    backend: Arc<RwLock<TursoBackend>>,
+    config: SyncHealthConfig,
+    sinks: StdRwLock<Vec<Arc<dyn NotificationSink>>>,
+    provider_tables: StdRwLock<HashMap<String, Vec<String>>>,
+}
+
+impl SyncHealthStore {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>, config: SyncHealthConfig) -> Self {
+        Self {
+            backend,
+            config,
+            sinks: StdRwLock::new(Vec::new()),
+            provider_tables: StdRwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Initialize the sync_events and sync_health_reports tables.
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let backend = self.backend.read().await;
+
+        for schema in [SyncEvent::schema(), SyncHealthReport::schema()] {
+            backend
+                .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+                .await
+                .map_err(|e| format!("Failed to create {} table: {}", schema.table_name, e))?;
+
+            for index_sql in schema.to_index_sql() {
+                backend
+                    .execute_sql(&index_sql, HashMap::new())
+                    .await
+                    .map_err(|e| format!("Failed to create index: {}", e))?;
+            }
+        }
+
+        info!("Sync health schema initialized");
+        Ok(())
+    }
+
+    /// Add a sink that receives report notifications
+    pub fn add_notification_sink(&self, sink: Arc<dyn NotificationSink>) {
+        self.sinks.write().unwrap().push(sink);
+    }
+
+    /// Declare which tables hold a provider's entities.
+    ///
+    /// Without a registration, tables named `{provider}_*` are counted.
+    pub fn register_provider_tables(&self, provider_name: &str, tables: Vec<String>) {
+        self.provider_tables
+            .write()
+            .unwrap()
+            .insert(provider_name.to_string(), tables);
+    }
+
+    /// Record a successful sync
+    pub async fn record_success(
+        &self,
+        provider_name: &str,
+        changes_applied: i64,
+        conflicts_resolved: i64,
+    ) -> Result<()> {
+        self.record(SyncEvent {
+            id: String::new(),
+            provider_name: provider_name.to_string(),
+            occurred_at: chrono::Utc::now().timestamp_millis(),
+            success: true,
+            error_category: None,
+            error_message: None,
+            changes_applied,
+            conflicts_resolved,
+        })
+        .await
+    }
+
+    /// Record a failed sync; the error is classified automatically
+    pub async fn record_failure(&self, provider_name: &str, error: &str) -> Result<()> {
+        self.record(SyncEvent {
+            id: String::new(),
+            provider_name: provider_name.to_string(),
+            occurred_at: chrono::Utc::now().timestamp_millis(),
+            success: false,
+            error_category: Some(SyncErrorCategory::classify(error).as_str().to_string()),
+            error_message: Some(error.to_string()),
+            changes_applied: 0,
+            conflicts_resolved: 0,
+        })
+        .await
+    }
+
+    /// Persist a sync event (a fresh ID is assigned if `id` is empty)
+    pub async fn record(&self, mut event: SyncEvent) -> Result<()> {
+        if event.id.is_empty() {
+            event.id = uuid::Uuid::new_v4().to_string();
+        }
+
+        let schema = SyncEvent::schema();
+        let fields = event.to_entity().fields;
+        let columns: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+        let sql = format!(
+            "INSERT INTO sync_events ({}) VALUES ({})",
+            columns.join(", "),
+            columns
+                .iter()
+                .map(|c| format!("${}", c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(&sql, fields)
+            .await
+            .map_err(|e| format!("Failed to record sync event: {}", e))?;
+
+        debug!(
+            "Recorded sync event for {} (success={})",
+            event.provider_name, event.success
+        );
+        Ok(())
+    }
+
+    /// Most recently generated report
+    pub async fn latest_report(&self) -> Result<Option<SyncHealthReport>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT * FROM sync_health_reports ORDER BY generated_at DESC LIMIT 1",
+                HashMap::new(),
+            )
+            .await
+            .map_err(|e| format!("Failed to query sync health reports: {}", e))?;
+
+        rows.into_iter()
+            .next()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(SYNC_HEALTH_REPORTS_ENTITY);
+                entity.fields = row;
+                SyncHealthReport::from_entity(entity)
+            })
+            .transpose()
+    }
+
+    /// Whether a new report is due at `now`
+    pub async fn report_due(&self, now: i64) -> Result<bool> {
+        Ok(match self.latest_report().await? {
+            Some(report) => now - report.generated_at >= self.config.report_interval_ms,
+            None => true,
+        })
+    }
+
+    /// Generate a report if the last one is older than the report interval
+    pub async fn generate_report_if_due(&self, now: i64) -> Result<Option<SyncHealthReport>> {
+        if self.report_due(now).await? {
+            Ok(Some(self.generate_report(now).await?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Generate, store and announce a report covering the last report interval
+    pub async fn generate_report(&self, now: i64) -> Result<SyncHealthReport> {
+        let period_start = now - self.config.report_interval_ms;
+        let events = self.events_since(period_start).await?;
+
+        let mut providers: Vec<String> = events
+            .iter()
+            .map(|e| e.provider_name.clone())
+            .chain(self.known_providers().await)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        providers.sort();
+
+        let mut health = Vec::with_capacity(providers.len());
+        for provider in providers {
+            let provider_events: Vec<&SyncEvent> = events
+                .iter()
+                .filter(|e| e.provider_name == provider)
+                .collect();
+            let last_success_at = match provider_events
+                .iter()
+                .filter(|e| e.success)
+                .map(|e| e.occurred_at)
+                .max()
+            {
+                Some(t) => Some(t),
+                None => self.last_success_before(&provider, period_start).await?,
+            };
+            let entity_count = self.count_entities(&provider).await;
+            health.push(summarize_provider(
+                &provider,
+                &provider_events,
+                last_success_at,
+                entity_count,
+                now,
+                self.config.stale_after_ms,
+            ));
+        }
+
+        let status = health
+            .iter()
+            .map(|h| h.status)
+            .max()
+            .unwrap_or(ProviderStatus::Healthy);
+
+        let report = SyncHealthReport {
+            id: format!("sync-health-{}", now),
+            generated_at: now,
+            period_start,
+            status: status.as_str().to_string(),
+            summary: render_summary(&health),
+            providers: serde_json::to_string(&health)?,
+        };
+
+        self.save_report(&report).await?;
+        self.prune_events(now - self.config.retention_ms).await?;
+        self.notify(&report, status, &health);
+
+        info!(
+            "Generated sync health report {} ({})",
+            report.id, report.status
+        );
+        Ok(report)
+    }
+
+    /// Generate reports in the background, checking hourly whether one is due.
+    ///
+    /// The last report time lives in the database, so the weekly cadence survives restarts.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn_recurring_reports(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                let now = chrono::Utc::now().timestamp_millis();
+                if let Err(e) = self.generate_report_if_due(now).await {
+                    warn!("Failed to generate sync health report: {}", e);
+                }
+            }
+        })
+    }
+
+    fn notify(&self, report: &SyncHealthReport, status: ProviderStatus, health: &[ProviderHealth]) {
+        let unhealthy: Vec<&str> = health
+            .iter()
+            .filter(|h| h.status != ProviderStatus::Healthy)
+            .map(|h| h.provider_name.as_str())
+            .collect();
+
+        let (severity, body) = match status {
+            ProviderStatus::Healthy => (
+                NotificationSeverity::Info,
+                format!("All {} sync providers are healthy", health.len()),
+            ),
+            ProviderStatus::Degraded => (
+                NotificationSeverity::Info,
+                format!("Intermittent sync errors: {}", unhealthy.join(", ")),
+            ),
+            ProviderStatus::Stale | ProviderStatus::Failing => (
+                NotificationSeverity::Warning,
+                format!("Sync is not working for: {}", unhealthy.join(", ")),
+            ),
+        };
+
+        let notification = Notification::new("Weekly sync health report", body, severity)
+            .with_entity(SYNC_HEALTH_REPORTS_ENTITY, report.id.clone());
+        for sink in self.sinks.read().unwrap().iter() {
+            sink.notify(notification.clone());
+        }
+    }
+
+    async fn events_since(&self, since: i64) -> Result<Vec<SyncEvent>> {
+        let mut params = HashMap::new();
+        params.insert("since".to_string(), Value::Integer(since));
+
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT * FROM sync_events WHERE occurred_at >= $since ORDER BY occurred_at ASC",
+                params,
+            )
+            .await
+            .map_err(|e| format!("Failed to query sync events: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new("sync_events");
+                entity.fields = row;
+                SyncEvent::from_entity(entity)
+            })
+            .collect()
+    }
+
+    async fn last_success_before(&self, provider_name: &str, before: i64) -> Result<Option<i64>> {
+        let mut params = HashMap::new();
+        params.insert(
+            "provider".to_string(),
+            Value::String(provider_name.to_string()),
+        );
+        params.insert("before".to_string(), Value::Integer(before));
+
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT MAX(occurred_at) AS last_success FROM sync_events
+                 WHERE provider_name = $provider AND success = 1 AND occurred_at < $before",
+                params,
+            )
+            .await
+            .map_err(|e| format!("Failed to query sync events: {}", e))?;
+
+        Ok(rows
+            .first()
+            .and_then(|row| row.get("last_success"))
+            .and_then(|v| v.as_i64()))
+    }
+
+    /// Providers known from registrations and persisted sync tokens
+    async fn known_providers(&self) -> Vec<String> {
+        let mut providers: Vec<String> = self
+            .provider_tables
+            .read()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+
+        let backend = self.backend.read().await;
+        // sync_states may not exist yet (no provider ever synced)
+        if let Ok(rows) = backend
+            .execute_sql("SELECT provider_name FROM sync_states", HashMap::new())
+            .await
+        {
+            providers.extend(rows.iter().filter_map(|row| {
+                row.get("provider_name")
+                    .and_then(|v| v.as_string())
+                    .map(|s| s.to_string())
+            }));
+        }
+        providers
+    }
+
+    async fn count_entities(&self, provider_name: &str) -> i64 {
+        let registered = self
+            .provider_tables
+            .read()
+            .unwrap()
+            .get(provider_name)
+            .cloned();
+
+        let backend = self.backend.read().await;
+        let tables = match registered {
+            Some(tables) => tables,
+            None => {
+                let mut params = HashMap::new();
+                params.insert(
+                    "pattern".to_string(),
+                    Value::String(format!("{}\\_%", provider_name)),
+                );
+                backend
+                    .execute_sql(
+                        "SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE $pattern ESCAPE '\\'",
+                        params,
+                    )
+                    .await
+                    .map(|rows| {
+                        rows.iter()
+                            .filter_map(|row| {
+                                row.get("name")
+                                    .and_then(|v| v.as_string())
+                                    .map(|s| s.to_string())
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            }
+        };
+
+        let mut total = 0;
+        for table in tables {
+            let sql = format!("SELECT COUNT(*) AS count FROM {}", table);
+            match backend.execute_sql(&sql, HashMap::new()).await {
+                Ok(rows) => {
+                    total += rows
+                        .first()
+                        .and_then(|row| row.get("count"))
+                        .and_then(|v| v.as_i64())
+                        .unwrap_or(0)
+                }
+                Err(e) => debug!("Could not count rows of {}: {}", table, e),
+            }
+        }
+        total
+    }
+
+    async fn save_report(&self, report: &SyncHealthReport) -> Result<()> {
+        let sql = "INSERT INTO sync_health_reports (id, generated_at, period_start, status, summary, providers)
+            VALUES ($id, $generated_at, $period_start, $status, $summary, $providers)
+            ON CONFLICT(id) DO UPDATE SET
+                status = excluded.status,
+                summary = excluded.summary,
+                providers = excluded.providers";
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(sql, report.to_entity().fields)
+            .await
+            .map_err(|e| format!("Failed to save sync health report: {}", e))?;
+        Ok(())
+    }
+
+    async fn prune_events(&self, before: i64) -> Result<()> {
+        let mut params = HashMap::new();
+        params.insert("before".to_string(), Value::Integer(before));
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(
+                "DELETE FROM sync_events WHERE occurred_at < $before",
+                params,
+            )
+            .await
+            .map_err(|e| format!("Failed to prune sync events: {}", e))?;
+        Ok(())
+    }
+}
+
+fn summarize_provider(
+    provider_name: &str,
+    events: &[&SyncEvent],
+    last_success_at: Option<i64>,
+    entity_count: i64,
+    now: i64,
+    stale_after_ms: i64,
+) -> ProviderHealth {
+    let successes = events.iter().filter(|e| e.success).count() as i64;
+    let failures = events.len() as i64 - successes;
+
+    let mut errors_by_category = BTreeMap::new();
+    for event in events.iter().filter(|e| !e.success) {
+        let category = event
+            .error_category
+            .clone()
+            .unwrap_or_else(|| SyncErrorCategory::Other.as_str().to_string());
+        *errors_by_category.entry(category).or_insert(0) += 1;
+    }
+
+    let last = events.last();
+    let status = if failures > 0 && successes == 0 {
+        ProviderStatus::Failing
+    } else if last_success_at.is_none_or(|t| now - t > stale_after_ms) {
+        ProviderStatus::Stale
+    } else if failures > 0 && last.is_some_and(|e| !e.success) {
+        ProviderStatus::Failing
+    } else if failures > 0 {
+        ProviderStatus::Degraded
+    } else {
+        ProviderStatus::Healthy
+    };
+
+    ProviderHealth {
+        provider_name: provider_name.to_string(),
+        status,
+        entity_count,
+        last_success_at,
+        last_attempt_at: last.map(|e| e.occurred_at),
+        successes,
+        failures,
+        conflicts_resolved: events.iter().map(|e| e.conflicts_resolved).sum(),
+        errors_by_category,
+        last_error: events
+            .iter()
+            .rev()
+            .find(|e| !e.success)
+            .and_then(|e| e.error_message.clone()),
+    }
+}
+
+fn format_timestamp(ms: Option<i64>) -> String {
+    ms.and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| "never".to_string())
+}
+
+fn render_summary(health: &[ProviderHealth]) -> String {
+    if health.is_empty() {
+        return "No sync providers configured.\n".to_string();
+    }
+
+    let mut out = String::from("# Sync health\n\n");
+    for h in health {
+        out.push_str(&format!(
+            "## {} ({})\n\n",
+            h.provider_name,
+            h.status.as_str()
+        ));
+        out.push_str(&format!("- Entities: {}\n", h.entity_count));
+        out.push_str(&format!(
+            "- Last successful sync: {}\n",
+            format_timestamp(h.last_success_at)
+        ));
+        out.push_str(&format!(
+            "- Syncs: {} ok, {} failed\n",
+            h.successes, h.failures
+        ));
+        out.push_str(&format!("- Conflicts resolved: {}\n", h.conflicts_resolved));
+        if !h.errors_by_category.is_empty() {
+            let errors: Vec<String> = h
+                .errors_by_category
+                .iter()
+                .map(|(category, count)| format!("{} {}", category, count))
+                .collect();
+            out.push_str(&format!("- Errors: {}\n", errors.join(", ")));
+        }
+        if let Some(error) = &h.last_error {
+            out.push_str(&format!("- Last error: {}\n", error));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct CollectingSink(Mutex<Vec<Notification>>);
+
+    impl NotificationSink for CollectingSink {
+        fn notify(&self, notification: Notification) {
+            self.0.lock().unwrap().push(notification);
+        }
+    }
+
+    async fn create_store() -> SyncHealthStore {
+        let store = SyncHealthStore::new(memory_backend().await, SyncHealthConfig::default());
+        store
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        store
+    }
+
+    #[test]
+    fn test_classify_errors() {
+        assert_eq!(
+            SyncErrorCategory::classify("HTTP 401 Unauthorized"),
+            SyncErrorCategory::Auth
+        );
+        assert_eq!(
+            SyncErrorCategory::classify("429 Too Many Requests"),
+            SyncErrorCategory::RateLimit
+        );
+        assert_eq!(
+            SyncErrorCategory::classify("operation timed out"),
+            SyncErrorCategory::Network
+        );
+        assert_eq!(
+            SyncErrorCategory::classify("something odd"),
+            SyncErrorCategory::Other
+        );
+    }
+
+    #[tokio::test]
+    async fn test_report_flags_failing_provider() {
+        let store = create_store().await;
+        let sink = Arc::new(CollectingSink::default());
+        store.add_notification_sink(sink.clone());
+
+        store.record_success("orgmode", 3, 1).await.unwrap();
+        store
+            .record_failure("todoist", "HTTP 401 Unauthorized: token expired")
+            .await
+            .unwrap();
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let report = store.generate_report(now).await.unwrap();
+        assert_eq!(report.status, "failing");
+
+        let health = report.provider_health();
+        assert_eq!(health.len(), 2);
+        assert_eq!(health[0].provider_name, "orgmode");
+        assert_eq!(health[0].status, ProviderStatus::Healthy);
+        assert_eq!(health[0].conflicts_resolved, 1);
+        assert_eq!(health[1].status, ProviderStatus::Failing);
+        assert_eq!(health[1].errors_by_category.get("auth"), Some(&1));
+
+        let notifications = sink.0.lock().unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].severity, NotificationSeverity::Warning);
+        assert!(notifications[0].body.contains("todoist"));
+    }
+
+    #[tokio::test]
+    async fn test_reports_are_generated_once_per_interval() {
+        let store = create_store().await;
+        let now = chrono::Utc::now().timestamp_millis();
+
+        assert!(store.generate_report_if_due(now).await.unwrap().is_some());
+        assert!(
+            store
+                .generate_report_if_due(now + DAY_MS)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            store
+                .generate_report_if_due(now + 7 * DAY_MS)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(
+            store.latest_report().await.unwrap().unwrap().generated_at,
+            now + 7 * DAY_MS
+        );
+    }
+}
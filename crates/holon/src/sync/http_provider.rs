@@ -0,0 +1,580 @@
+//! Toolkit for REST-backed sync providers
+//!
+//! `HttpSyncProviderBuilder` builds a `SyncableProvider` from just the
+//! endpoint-specific parts of a datasource: how to build the request for a sync
+//! cursor and how to map a response body to changes. The provider takes care of
+//! the rest, following the pattern established by `TodoistSyncProvider`:
+//! - loading the cursor from the `SyncTokenStore` and following pagination
+//! - emitting one batch per sync whose metadata carries the new cursor, so
+//!   `QueryableCache` persists it atomically with the data
+//! - flagging full snapshots so caches only apply what changed
+//! - HTTP error handling (`HttpStatusError`, including 429 `Retry-After`)
+//! - an optional polling loop with exponential backoff on failures
+//!
+//! ```ignore
+//! let provider = HttpSyncProviderBuilder::<Issue>::new("linear", "linear_issues")
+//!     .with_header("Authorization", &format!("Bearer {}", api_key))?
+//!     .with_request(|client, cursor| {
+//!         client.post("https://api.linear.app/graphql").json(&issues_query(cursor))
+//!     })
+//!     .with_parser(|body, cursor| parse_issue_page(body, cursor))
+//!     .build(token_store)?;
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use tokio::sync::broadcast;
+use tracing::{debug, info};
+
+use crate::core::datasource::{
+    Change, OperationDescriptor, OperationProvider, Result, StreamPosition, SyncTokenStore,
+    SyncableProvider, UndoAction, generate_sync_operation,
+};
+use crate::storage::types::StorageEntity;
+use holon_api::{BatchMetadata, BatchTraceContext, SyncTokenUpdate, WithMetadata};
+
+/// Changes wrapped with metadata for atomic sync token updates
+pub type ChangesWithMetadata<T> = WithMetadata<Vec<Change<T>>, BatchMetadata>;
+
+/// Wrap the changes of one sync in a batch that carries the new sync position
+///
+/// The position is saved by `QueryableCache` in the same transaction as the changes.
+pub fn sync_batch<T>(
+    provider_name: &str,
+    relation_name: &str,
+    changes: Vec<Change<T>>,
+    position: StreamPosition,
+    full_snapshot: bool,
+) -> ChangesWithMetadata<T> {
+    WithMetadata {
+        inner: changes,
+        metadata: BatchMetadata {
+            relation_name: relation_name.to_string(),
+            trace_context: BatchTraceContext::from_current_span(),
+            sync_token: Some(SyncTokenUpdate {
+                provider_name: provider_name.to_string(),
+                position,
+            }),
+            full_snapshot,
+        },
+    }
+}
+
+/// Number of created, updated and deleted entities in a batch of changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChangeCounts {
+    pub created: usize,
+    pub updated: usize,
+    pub deleted: usize,
+}
+
+impl ChangeCounts {
+    pub fn of<T>(changes: &[Change<T>]) -> Self {
+        let mut counts = Self::default();
+        for change in changes {
+            match change {
+                Change::Created { .. } => counts.created += 1,
+                Change::Updated { .. } | Change::ColumnChange { .. } => counts.updated += 1,
+                Change::Deleted { .. } => counts.deleted += 1,
+            }
+        }
+        counts
+    }
+}
+
+/// One page of a sync response, as mapped by the datasource's parser
+pub struct SyncPage<T> {
+    pub changes: Vec<Change<T>>,
+    /// Cursor for the next request; `None` keeps the current one
+    pub next_token: Option<String>,
+    /// The page lists every entity (not just changes since the cursor)
+    pub full_snapshot: bool,
+    /// More pages follow before the sync is complete
+    pub has_more: bool,
+}
+
+/// Non-success HTTP response from a sync endpoint
+#[derive(Debug, Clone)]
+pub struct HttpStatusError {
+    pub status: u16,
+    pub url: String,
+    pub body: String,
+    /// Back-off requested by the server (`Retry-After` header, in seconds)
+    pub retry_after: Option<Duration>,
+}
+
+impl std::fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "HTTP {} error from {}: {}",
+            self.status, self.url, self.body
+        )?;
+        if let Some(retry_after) = self.retry_after {
+            write!(f, " (retry after {}s)", retry_after.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+type RequestFn = dyn Fn(&reqwest::Client, Option<&str>) -> reqwest::RequestBuilder + Send + Sync;
+type ParseFn<T> = dyn Fn(&str, Option<&str>) -> Result<SyncPage<T>> + Send + Sync;
+
+/// Builder for `HttpSyncProvider`
+pub struct HttpSyncProviderBuilder<T> {
+    provider_name: String,
+    relation_name: String,
+    headers: HeaderMap,
+    timeout: Duration,
+    poll_interval: Duration,
+    max_backoff: Duration,
+    max_pages: usize,
+    request: Option<Arc<RequestFn>>,
+    parser: Option<Arc<ParseFn<T>>>,
+}
+
+impl<T: Clone + Send + Sync + 'static> HttpSyncProviderBuilder<T> {
+    /// `provider_name` keys the sync token and the `{provider_name}.sync` operation;
+    /// `relation_name` is the cache table the changes are meant for
+    pub fn new(provider_name: impl Into<String>, relation_name: impl Into<String>) -> Self {
+        Self {
+            provider_name: provider_name.into(),
+            relation_name: relation_name.into(),
+            headers: HeaderMap::new(),
+            timeout: Duration::from_secs(30),
+            poll_interval: Duration::from_secs(60),
+            max_backoff: Duration::from_secs(30 * 60),
+            max_pages: 100,
+            request: None,
+            parser: None,
+        }
+    }
+
+    /// Send `name: value` with every request (e.g. `Authorization`)
+    pub fn with_header(mut self, name: &str, value: &str) -> Result<Self> {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| format!("Invalid header name '{}': {}", name, e))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| format!("Invalid value for header '{}': {}", name, e))?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// Build the request fetching changes since `cursor` (`None` on the first sync)
+    pub fn with_request<F>(mut self, request: F) -> Self
+    where
+        F: Fn(&reqwest::Client, Option<&str>) -> reqwest::RequestBuilder + Send + Sync + 'static,
+    {
+        self.request = Some(Arc::new(request));
+        self
+    }
+
+    /// Map a response body (fetched with `cursor`) to changes and the next cursor
+    pub fn with_parser<F>(mut self, parser: F) -> Self
+    where
+        F: Fn(&str, Option<&str>) -> Result<SyncPage<T>> + Send + Sync + 'static,
+    {
+        self.parser = Some(Arc::new(parser));
+        self
+    }
+
+    /// Request timeout (default 30s)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Time between syncs of the polling loop (default 60s)
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Upper bound of the backoff after failed syncs (default 30min)
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Maximum pages followed in one sync, guarding against cursor loops (default 100)
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    pub fn build(self, token_store: Arc<dyn SyncTokenStore>) -> Result<HttpSyncProvider<T>> {
+        let request = self
+            .request
+            .ok_or_else(|| format!("HTTP sync provider '{}' has no request", self.provider_name))?;
+        let parser = self
+            .parser
+            .ok_or_else(|| format!("HTTP sync provider '{}' has no parser", self.provider_name))?;
+
+        let mut builder = reqwest::Client::builder().default_headers(self.headers);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            builder = builder.timeout(self.timeout);
+        }
+        let client = builder
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+        Ok(HttpSyncProvider {
+            provider_name: self.provider_name,
+            relation_name: self.relation_name,
+            client,
+            token_store,
+            request,
+            parser,
+            poll_interval: self.poll_interval,
+            max_backoff: self.max_backoff,
+            max_pages: self.max_pages,
+            tx: broadcast::channel(1000).0,
+        })
+    }
+}
+
+/// `SyncableProvider` for a REST endpoint, built with `HttpSyncProviderBuilder`
+///
+/// Each sync follows the pages from the stored cursor and emits all changes as
+/// one batch on the stream returned by `subscribe()`.
+pub struct HttpSyncProvider<T> {
+    provider_name: String,
+    relation_name: String,
+    client: reqwest::Client,
+    token_store: Arc<dyn SyncTokenStore>,
+    request: Arc<RequestFn>,
+    parser: Arc<ParseFn<T>>,
+    poll_interval: Duration,
+    max_backoff: Duration,
+    max_pages: usize,
+    tx: broadcast::Sender<ChangesWithMetadata<T>>,
+}
+
+impl<T: Clone + Send + Sync + 'static> HttpSyncProvider<T> {
+    /// Get a receiver for the synced changes (for wiring into a QueryableCache)
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangesWithMetadata<T>> {
+        self.tx.subscribe()
+    }
+
+    pub fn relation_name(&self) -> &str {
+        &self.relation_name
+    }
+
+    /// Fetch one page, mapping non-success responses to `HttpStatusError`
+    async fn fetch_page(&self, cursor: Option<&str>) -> Result<SyncPage<T>> {
+        let response = (self.request)(&self.client, cursor)
+            .send()
+            .await
+            .map_err(|e| format!("[{}] Sync request failed: {}", self.provider_name, e))?;
+
+        let status = response.status();
+        let url = response.url().to_string();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        let body = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read response body from {}: {}", url, e))?;
+
+        if !status.is_success() {
+            return Err(HttpStatusError {
+                status: status.as_u16(),
+                url,
+                body: body.chars().take(500).collect(),
+                retry_after,
+            }
+            .into());
+        }
+
+        (self.parser)(&body, cursor)
+    }
+
+    /// Delay before the next sync after `failures` consecutive failed syncs
+    pub fn backoff(&self, failures: u32, error: Option<&HttpStatusError>) -> Duration {
+        if let Some(retry_after) = error.and_then(|e| e.retry_after) {
+            return retry_after.max(self.poll_interval);
+        }
+        let factor = 2u32.saturating_pow(failures.min(16));
+        self.poll_interval
+            .saturating_mul(factor)
+            .min(self.max_backoff.max(self.poll_interval))
+    }
+
+    /// Sync every poll interval in the background, backing off while syncs fail
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn_polling(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut failures = 0;
+            loop {
+                let delay = match self.sync(StreamPosition::Beginning).await {
+                    Ok(_) => {
+                        failures = 0;
+                        self.poll_interval
+                    }
+                    Err(e) => {
+                        failures += 1;
+                        let delay = self.backoff(failures, e.downcast_ref::<HttpStatusError>());
+                        tracing::warn!(
+                            "[{}] Sync failed ({} in a row), retrying in {}s: {}",
+                            self.provider_name,
+                            failures,
+                            delay.as_secs(),
+                            e
+                        );
+                        delay
+                    }
+                };
+                tokio::time::sleep(delay).await;
+            }
+        })
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T: Clone + Send + Sync + 'static> SyncableProvider for HttpSyncProvider<T> {
+    fn provider_name(&self) -> &str {
+        &self.provider_name
+    }
+
+    /// Follow the pages from the stored cursor and emit them as one batch
+    ///
+    /// The passed position is ignored; the cursor comes from the token store.
+    async fn sync(&self, _position: StreamPosition) -> Result<StreamPosition> {
+        let position = self
+            .token_store
+            .load_token(&self.provider_name)
+            .await?
+            .unwrap_or(StreamPosition::Beginning);
+        let mut cursor = match &position {
+            StreamPosition::Beginning => None,
+            StreamPosition::Version(bytes) => Some(
+                String::from_utf8(bytes.clone())
+                    .map_err(|e| format!("[{}] Invalid sync token: {}", self.provider_name, e))?,
+            ),
+        };
+
+        let mut changes = Vec::new();
+        let mut full_snapshot = false;
+        for page_number in 1..=self.max_pages {
+            let page = self.fetch_page(cursor.as_deref()).await?;
+            debug!(
+                "[{}] Fetched page {}: {} changes, has_more={}",
+                self.provider_name,
+                page_number,
+                page.changes.len(),
+                page.has_more
+            );
+            // All pages of a full snapshot together list every entity
+            full_snapshot |= page.full_snapshot;
+            changes.extend(page.changes);
+            if page.next_token.is_some() {
+                cursor = page.next_token;
+            }
+            if !page.has_more {
+                break;
+            }
+            if page_number == self.max_pages {
+                return Err(format!(
+                    "[{}] Sync still has more pages after {}",
+                    self.provider_name, self.max_pages
+                )
+                .into());
+            }
+        }
+
+        let new_position = match cursor {
+            Some(cursor) => StreamPosition::Version(cursor.into_bytes()),
+            None => StreamPosition::Beginning,
+        };
+        let counts = ChangeCounts::of(&changes);
+        info!(
+            "[{}] Emitting {} changes (created={}, updated={}, deleted={}, full_snapshot={})",
+            self.provider_name,
+            changes.len(),
+            counts.created,
+            counts.updated,
+            counts.deleted,
+            full_snapshot
+        );
+
+        // Fire-and-forget: the cache persists the new position with the changes
+        let _ = self.tx.send(sync_batch(
+            &self.provider_name,
+            &self.relation_name,
+            changes,
+            new_position.clone(),
+            full_snapshot,
+        ));
+        Ok(new_position)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T: Clone + Send + Sync + 'static> OperationProvider for HttpSyncProvider<T> {
+    fn operations(&self) -> Vec<OperationDescriptor> {
+        vec![generate_sync_operation(&self.provider_name)]
+    }
+
+    async fn execute_operation(
+        &self,
+        entity_name: &str,
+        op_name: &str,
+        _params: StorageEntity,
+    ) -> Result<UndoAction> {
+        let expected_entity_name = format!("{}.sync", self.provider_name);
+        if entity_name != expected_entity_name || op_name != "sync" {
+            return Err(format!(
+                "Expected '{}' operation 'sync', got '{}' operation '{}'",
+                expected_entity_name, entity_name, op_name
+            )
+            .into());
+        }
+
+        self.sync(StreamPosition::Beginning).await?;
+        Ok(UndoAction::Irreversible)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::datasource::ChangeOrigin;
+    use std::collections::HashMap;
+    use std::sync::RwLock;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    struct MemoryTokenStore(RwLock<HashMap<String, StreamPosition>>);
+
+    #[async_trait]
+    impl SyncTokenStore for MemoryTokenStore {
+        async fn load_token(&self, provider_name: &str) -> Result<Option<StreamPosition>> {
+            Ok(self.0.read().unwrap().get(provider_name).cloned())
+        }
+        async fn save_token(&self, provider_name: &str, position: StreamPosition) -> Result<()> {
+            self.0
+                .write()
+                .unwrap()
+                .insert(provider_name.to_string(), position);
+            Ok(())
+        }
+    }
+
+    /// Serve canned responses: two pages of issues, and 429 on /limited
+    async fn serve() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                let (status, extra, body) = if request.starts_with("GET /limited") {
+                    ("429 Too Many Requests", "Retry-After: 7\r\n", "{}")
+                } else if request.starts_with("GET /issues?cursor=p2") {
+                    ("200 OK", "", r#"{"ids":["c"],"next":"t1","more":false}"#)
+                } else {
+                    ("200 OK", "", r#"{"ids":["a","b"],"next":"p2","more":true}"#)
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    extra,
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn provider(base_url: String, path: &'static str) -> HttpSyncProvider<String> {
+        HttpSyncProviderBuilder::<String>::new("issues", "issues")
+            .with_request(move |client, cursor| {
+                client.get(format!(
+                    "{}{}?cursor={}",
+                    base_url,
+                    path,
+                    cursor.unwrap_or("")
+                ))
+            })
+            .with_parser(|body, cursor| {
+                let json: serde_json::Value = serde_json::from_str(body)?;
+                let changes = json["ids"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| id.as_str())
+                    .map(|id| Change::Updated {
+                        id: id.to_string(),
+                        data: id.to_uppercase(),
+                        origin: ChangeOrigin::remote_with_current_span(),
+                    })
+                    .collect();
+                Ok(SyncPage {
+                    changes,
+                    next_token: json["next"].as_str().map(str::to_string),
+                    full_snapshot: cursor.is_none(),
+                    has_more: json["more"].as_bool().unwrap_or(false),
+                })
+            })
+            .build(Arc::new(MemoryTokenStore(RwLock::new(HashMap::new()))))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sync_follows_pages_into_one_batch() {
+        let provider = provider(serve().await, "/issues");
+        let mut rx = provider.subscribe();
+
+        let position = provider.sync(StreamPosition::Beginning).await.unwrap();
+        assert_eq!(position, StreamPosition::Version(b"t1".to_vec()));
+
+        let batch = rx.recv().await.unwrap();
+        let ids: Vec<&str> = batch
+            .inner
+            .iter()
+            .map(|change| match change {
+                Change::Updated { id, .. } => id.as_str(),
+                _ => panic!("unexpected change"),
+            })
+            .collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert!(batch.metadata.full_snapshot);
+        assert_eq!(batch.metadata.relation_name, "issues");
+        assert_eq!(
+            batch.metadata.sync_token.map(|token| token.position),
+            Some(position)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_sync_backs_off() {
+        let provider = provider(serve().await, "/limited");
+
+        let error = provider.sync(StreamPosition::Beginning).await.unwrap_err();
+        let status = error.downcast_ref::<HttpStatusError>().unwrap();
+        assert_eq!(status.status, 429);
+        assert_eq!(status.retry_after, Some(Duration::from_secs(7)));
+        assert_eq!(
+            provider.backoff(1, Some(status)),
+            Duration::from_secs(60),
+            "never polls faster than the poll interval"
+        );
+        assert_eq!(provider.backoff(3, None), Duration::from_secs(8 * 60));
+        assert_eq!(provider.backoff(20, None), Duration::from_secs(30 * 60));
+    }
+}
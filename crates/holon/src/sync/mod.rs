@@ -1,10 +1,34 @@
 //! Synchronization infrastructure
 //!
+//! - `blob`: End-to-end encrypted, append-only operation blobs for device-to-device sync
 //! - `collaborative_doc`: Loro-based real-time document collaboration
+//! - `conflicts`: Field-level merge strategies and unresolved-conflict tracking
 //! - `external_system`: External system integration with contract-based validation
+//! - `health`: Sync attempt tracking and recurring health reports
+//! - `dirty`: Per-entity and per-provider unsynced-changes tracking
+//! - `http_provider`: Builder for polling REST-backed sync providers
+//! - `orchestrator`: Bounded concurrent provider syncs with progress and cancellation
+//! - `scheduler`: Periodic per-provider syncs with pause/resume and offline mode
 
+pub mod blob;
 pub mod collaborative_doc;
+pub mod conflicts;
+pub mod dirty;
 pub mod external_system;
+pub mod health;
+pub mod http_provider;
+pub mod orchestrator;
+pub mod scheduler;
 
+pub use blob::{SyncBlob, SyncBlobLog, SyncImportSummary, SyncLogRecord, SyncRecord, VectorClock};
 pub use collaborative_doc::*;
+pub use conflicts::{FieldConflict, MergeStrategies, MergeStrategy, SyncConflict, SyncReconciler};
+pub use dirty::{DirtyEntity, PendingChanges, ProviderDirtyStatus, SyncDirtyStore};
 pub use external_system::*;
+pub use health::{SyncHealthConfig, SyncHealthReport, SyncHealthStore};
+pub use http_provider::{
+    ChangeCounts, ChangesWithMetadata, HttpStatusError, HttpSyncProvider, HttpSyncProviderBuilder,
+    SyncPage,
+};
+pub use orchestrator::{SyncOrchestrator, SyncProgress};
+pub use scheduler::{SyncAllSummary, SyncScheduler, SyncSchedulerConfig, SyncStatus};
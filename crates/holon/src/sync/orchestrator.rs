@@ -0,0 +1,254 @@
+//! Concurrent syncs of several providers
+//!
+//! `SyncOrchestrator` runs provider syncs concurrently, at most `max_concurrent` at
+//! a time. Every provider writes its changes through its own change stream, so
+//! batches of different sources can interleave freely, but two syncs of the same
+//! provider must not: a sync requested while one of the same provider is running
+//! waits for it, which keeps each source's batches in order.
+//!
+//! Syncs started while others are queued or running belong to the same round;
+//! `SyncProgress` aggregates the round's syncs for a progress indicator. After
+//! `shutdown` no new sync starts and running ones are cancelled at their next
+//! await point.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use tokio::sync::{Mutex, Semaphore, watch};
+use tracing::{debug, info};
+
+use crate::core::datasource::{Result, StreamPosition, SyncableProvider};
+
+/// Progress of the current round of syncs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncProgress {
+    /// Syncs of the round: queued, running or finished
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Providers syncing right now, in start order
+    pub running: Vec<String>,
+}
+
+impl SyncProgress {
+    pub fn finished(&self) -> usize {
+        self.succeeded + self.failed
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.finished() == self.total
+    }
+}
+
+/// Runs provider syncs concurrently with a bounded number of running syncs
+pub struct SyncOrchestrator {
+    permits: Semaphore,
+    /// One lock per provider, held while it syncs
+    locks: StdMutex<HashMap<String, Arc<Mutex<()>>>>,
+    progress: watch::Sender<SyncProgress>,
+    shutdown: watch::Sender<bool>,
+}
+
+impl SyncOrchestrator {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            permits: Semaphore::new(max_concurrent.max(1)),
+            locks: StdMutex::new(HashMap::new()),
+            progress: watch::channel(SyncProgress::default()).0,
+            shutdown: watch::channel(false).0,
+        }
+    }
+
+    /// Sync `provider` once a slot is free and no other sync of it is running
+    ///
+    /// Fails without syncing after `shutdown`, and with a cancellation error if the
+    /// orchestrator shuts down while waiting or syncing.
+    pub async fn run(
+        &self,
+        provider: &dyn SyncableProvider,
+        position: StreamPosition,
+    ) -> Result<StreamPosition> {
+        let name = provider.provider_name().to_string();
+        if self.is_shut_down() {
+            return Err(format!("Sync of {} cancelled: shutting down", name).into());
+        }
+
+        self.progress.send_modify(|progress| {
+            // A new round starts once the previous one is done
+            if progress.is_done() {
+                *progress = SyncProgress::default();
+            }
+            progress.total += 1;
+        });
+
+        let mut shutdown = self.shutdown.subscribe();
+        let result = tokio::select! {
+            result = self.run_exclusive(&name, provider, position) => result,
+            _ = shutdown.wait_for(|shut_down| *shut_down) => {
+                info!("[SyncOrchestrator] Cancelled sync of {}", name);
+                Err(format!("Sync of {} cancelled: shutting down", name).into())
+            }
+        };
+
+        self.progress.send_modify(|progress| {
+            if let Some(i) = progress.running.iter().position(|n| *n == name) {
+                progress.running.remove(i);
+            }
+            match result {
+                Ok(_) => progress.succeeded += 1,
+                Err(_) => progress.failed += 1,
+            }
+        });
+        result
+    }
+
+    async fn run_exclusive(
+        &self,
+        name: &str,
+        provider: &dyn SyncableProvider,
+        position: StreamPosition,
+    ) -> Result<StreamPosition> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|e| format!("Sync of {} cancelled: {}", name, e))?;
+        let lock = self.lock_for(name);
+        let _running = lock.lock().await;
+
+        debug!("[SyncOrchestrator] Syncing {}", name);
+        self.progress
+            .send_modify(|progress| progress.running.push(name.to_string()));
+        provider.sync(position).await
+    }
+
+    fn lock_for(&self, name: &str) -> Arc<Mutex<()>> {
+        self.locks
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_default()
+            .clone()
+    }
+
+    pub fn progress(&self) -> SyncProgress {
+        self.progress.borrow().clone()
+    }
+
+    /// Receive the progress whenever a sync is queued, starts or finishes
+    pub fn subscribe_progress(&self) -> watch::Receiver<SyncProgress> {
+        self.progress.subscribe()
+    }
+
+    /// Cancel running and queued syncs and refuse new ones
+    pub fn shutdown(&self) {
+        if !self.shutdown.send_replace(true) {
+            info!("[SyncOrchestrator] Shutting down");
+        }
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Provider whose syncs take `delay` and which tracks how many run at once
+    struct SlowProvider {
+        name: String,
+        delay: Duration,
+        running: Arc<AtomicUsize>,
+        max_running: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl SyncableProvider for SlowProvider {
+        fn provider_name(&self) -> &str {
+            &self.name
+        }
+
+        async fn sync(&self, position: StreamPosition) -> Result<StreamPosition> {
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(position)
+        }
+    }
+
+    fn providers(names: &[&str], delay: Duration) -> (Vec<SlowProvider>, Arc<AtomicUsize>) {
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let providers = names
+            .iter()
+            .map(|name| SlowProvider {
+                name: name.to_string(),
+                delay,
+                running: running.clone(),
+                max_running: max_running.clone(),
+            })
+            .collect();
+        (providers, max_running)
+    }
+
+    #[tokio::test]
+    async fn test_runs_concurrently_within_bound() {
+        let orchestrator = SyncOrchestrator::new(2);
+        let (providers, max_running) = providers(&["a", "b", "c", "d"], Duration::from_millis(30));
+
+        let results = futures::future::join_all(
+            providers
+                .iter()
+                .map(|p| orchestrator.run(p, StreamPosition::Beginning)),
+        )
+        .await;
+
+        assert!(results.iter().all(|r| r.is_ok()));
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+        let progress = orchestrator.progress();
+        assert_eq!((progress.total, progress.succeeded), (4, 4));
+        assert!(progress.running.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_same_provider_never_syncs_twice_at_once() {
+        let orchestrator = SyncOrchestrator::new(4);
+        let (providers, max_running) = providers(&["todoist"], Duration::from_millis(20));
+        let provider = &providers[0];
+
+        let (first, second) = tokio::join!(
+            orchestrator.run(provider, StreamPosition::Beginning),
+            orchestrator.run(provider, StreamPosition::Beginning)
+        );
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(max_running.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_cancels_syncs() {
+        let orchestrator = SyncOrchestrator::new(1);
+        let (providers, _) = providers(&["slow"], Duration::from_secs(60));
+
+        let (result, _) = tokio::join!(
+            orchestrator.run(&providers[0], StreamPosition::Beginning),
+            async {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                orchestrator.shutdown();
+            }
+        );
+        assert!(result.unwrap_err().to_string().contains("cancelled"));
+        assert_eq!(orchestrator.progress().failed, 1);
+        assert!(
+            orchestrator
+                .run(&providers[0], StreamPosition::Beginning)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,827 @@
+//! Periodic sync scheduling
+//!
+//! `SyncScheduler` runs every registered `SyncableProvider` on its own interval,
+//! randomized by a jitter so providers don't all hit the network at the same moment.
+//! Each provider's state (running, last success, last error, next run) is kept in the
+//! `sync_status` table, so queries can render it like any other entity.
+//!
+//! Scheduling can be paused per provider or for all providers. While the global
+//! offline flag is set nothing is synced; providers that became due in the meantime
+//! sync as soon as the app is back online or resumed.
+//!
+//! All syncs go through a `SyncOrchestrator`, which bounds how many providers sync
+//! at once and cancels running syncs on `shutdown`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::Duration;
+
+use holon_macros::Entity;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{RwLock, watch};
+use tracing::{debug, info, warn};
+
+use crate::core::datasource::{StreamPosition, SyncableProvider, UndoAction};
+use crate::storage::turso::TursoBackend;
+use crate::sync::dirty::SyncDirtyStore;
+use crate::sync::health::SyncHealthStore;
+use crate::sync::orchestrator::{SyncOrchestrator, SyncProgress};
+use holon_api::{DynamicEntity, HasSchema};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Entity name of the per-provider status rows
+pub const SYNC_STATUS_ENTITY: &str = "sync_status";
+
+/// Scheduling state of a provider, as shown in `SyncStatus::state`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleState {
+    /// Waiting for the next run
+    Idle,
+    Syncing,
+    /// Paused individually or via `pause_all`
+    Paused,
+    /// The global offline flag is set
+    Offline,
+    /// Waiting for the next run after the last sync failed
+    Failing,
+}
+
+impl ScheduleState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Syncing => "syncing",
+            Self::Paused => "paused",
+            Self::Offline => "offline",
+            Self::Failing => "failing",
+        }
+    }
+}
+
+/// Scheduling status of one provider
+///
+/// Table name: `sync_status`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Entity)]
+#[entity(name = "sync_status", short_name = "sync_status")]
+pub struct SyncStatus {
+    #[primary_key]
+    pub provider_name: String,
+    /// `ScheduleState::as_str()`
+    pub state: String,
+    pub in_progress: bool,
+    pub paused: bool,
+    /// Timestamps are Unix timestamps in milliseconds
+    pub last_started_at: Option<i64>,
+    pub last_success_at: Option<i64>,
+    pub last_error_at: Option<i64>,
+    pub last_error: Option<String>,
+    /// Failed syncs since the last successful one
+    pub consecutive_failures: i64,
+    /// When the next scheduled sync runs (None while paused or offline)
+    pub next_run_at: Option<i64>,
+    pub interval_ms: i64,
+}
+
+impl SyncStatus {
+    fn new(provider_name: &str, interval: Duration) -> Self {
+        Self {
+            provider_name: provider_name.to_string(),
+            state: ScheduleState::Idle.as_str().to_string(),
+            in_progress: false,
+            paused: false,
+            last_started_at: None,
+            last_success_at: None,
+            last_error_at: None,
+            last_error: None,
+            consecutive_failures: 0,
+            next_run_at: None,
+            interval_ms: interval.as_millis() as i64,
+        }
+    }
+}
+
+/// Configuration for scheduled syncs
+#[derive(Clone, Debug)]
+pub struct SyncSchedulerConfig {
+    /// Interval for providers without an explicit one
+    pub default_interval: Duration,
+    /// Per-provider intervals, keyed by provider name
+    pub intervals: HashMap<String, Duration>,
+    /// Each delay is randomized by up to this fraction of the interval (0.0 - 1.0)
+    pub jitter: f64,
+    /// Sync every provider right after the scheduler starts
+    pub run_on_start: bool,
+    /// Maximum number of providers syncing at the same time
+    pub max_concurrent: usize,
+}
+
+impl Default for SyncSchedulerConfig {
+    fn default() -> Self {
+        Self {
+            default_interval: Duration::from_secs(5 * 60),
+            intervals: HashMap::new(),
+            jitter: 0.1,
+            run_on_start: true,
+            max_concurrent: 4,
+        }
+    }
+}
+
+impl SyncSchedulerConfig {
+    pub fn with_default_interval(mut self, interval: Duration) -> Self {
+        self.default_interval = interval;
+        self
+    }
+
+    pub fn with_interval(mut self, provider_name: &str, interval: Duration) -> Self {
+        self.intervals.insert(provider_name.to_string(), interval);
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_run_on_start(mut self, run_on_start: bool) -> Self {
+        self.run_on_start = run_on_start;
+        self
+    }
+
+    pub fn with_max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent.max(1);
+        self
+    }
+
+    pub fn interval_for(&self, provider_name: &str) -> Duration {
+        self.intervals
+            .get(provider_name)
+            .copied()
+            .unwrap_or(self.default_interval)
+    }
+}
+
+/// `interval` randomized by up to `jitter` of itself; `unit` is a sample from [0, 1)
+pub fn jittered_interval(interval: Duration, jitter: f64, unit: f64) -> Duration {
+    let factor = 1.0 + jitter * (2.0 * unit - 1.0);
+    interval.mul_f64(factor.max(0.0))
+}
+
+/// Outcome of `SyncScheduler::sync_all`
+#[derive(Debug, Clone, Default)]
+pub struct SyncAllSummary {
+    pub synced: Vec<String>,
+    /// Provider name and error message of each failed sync
+    pub failed: Vec<(String, String)>,
+    /// Providers not synced because they are paused or offline
+    pub skipped: Vec<String>,
+}
+
+impl SyncAllSummary {
+    /// The outcome as an operation result: fails only if no provider synced
+    pub fn into_result(self) -> Result<UndoAction> {
+        if self.synced.is_empty() && !self.failed.is_empty() {
+            let errors: Vec<String> = self
+                .failed
+                .iter()
+                .map(|(name, e)| format!("{}: {}", name, e))
+                .collect();
+            return Err(format!(
+                "Sync failed on all {} providers: {}",
+                self.failed.len(),
+                errors.join("; ")
+            )
+            .into());
+        }
+        Ok(UndoAction::Irreversible)
+    }
+}
+
+/// Runs registered sync providers periodically
+pub struct SyncScheduler {
+    backend: Arc<RwLock<TursoBackend>>,
+    config: SyncSchedulerConfig,
+    providers: StdRwLock<Vec<Arc<dyn SyncableProvider>>>,
+    statuses: StdRwLock<HashMap<String, SyncStatus>>,
+    paused: StdRwLock<HashSet<String>>,
+    /// Providers to sync right away, regardless of their interval
+    requested: StdRwLock<HashSet<String>>,
+    all_paused: AtomicBool,
+    offline: AtomicBool,
+    /// Bumped on every pause/resume/offline/request change to wake the provider tasks
+    wake: watch::Sender<u64>,
+    sync_health: Option<Arc<SyncHealthStore>>,
+    sync_dirty: Option<Arc<SyncDirtyStore>>,
+    orchestrator: SyncOrchestrator,
+}
+
+impl SyncScheduler {
+    pub fn new(backend: Arc<RwLock<TursoBackend>>, config: SyncSchedulerConfig) -> Self {
+        Self {
+            backend,
+            orchestrator: SyncOrchestrator::new(config.max_concurrent),
+            config,
+            providers: StdRwLock::new(Vec::new()),
+            statuses: StdRwLock::new(HashMap::new()),
+            paused: StdRwLock::new(HashSet::new()),
+            requested: StdRwLock::new(HashSet::new()),
+            all_paused: AtomicBool::new(false),
+            offline: AtomicBool::new(false),
+            wake: watch::channel(0).0,
+            sync_health: None,
+            sync_dirty: None,
+        }
+    }
+
+    /// Record every scheduled sync as a success or classified failure
+    pub fn with_sync_health(mut self, sync_health: Arc<SyncHealthStore>) -> Self {
+        self.sync_health = Some(sync_health);
+        self
+    }
+
+    /// Acknowledge a provider's pending local changes after each successful sync
+    pub fn with_sync_dirty(mut self, sync_dirty: Arc<SyncDirtyStore>) -> Self {
+        self.sync_dirty = Some(sync_dirty);
+        self
+    }
+
+    /// Initialize the sync_status table
+    ///
+    /// Rows of a previous run are cleared; the in-memory state is authoritative.
+    pub async fn initialize_schema(&self) -> Result<()> {
+        let backend = self.backend.read().await;
+        let schema = SyncStatus::schema();
+        backend
+            .execute_sql(&schema.to_create_table_sql(), HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to create {} table: {}", schema.table_name, e))?;
+        backend
+            .execute_sql("DELETE FROM sync_status", HashMap::new())
+            .await
+            .map_err(|e| format!("Failed to clear sync_status: {}", e))?;
+
+        info!("Sync scheduler schema initialized");
+        Ok(())
+    }
+
+    /// Add a provider to the schedule (takes effect for tasks spawned afterwards)
+    pub fn register(&self, provider: Arc<dyn SyncableProvider>) {
+        let name = provider.provider_name().to_string();
+        let interval = self.config.interval_for(&name);
+        self.statuses
+            .write()
+            .unwrap()
+            .insert(name.clone(), SyncStatus::new(&name, interval));
+        let mut providers = self.providers.write().unwrap();
+        providers.retain(|p| p.provider_name() != name);
+        providers.push(provider);
+    }
+
+    pub fn provider_names(&self) -> Vec<String> {
+        self.providers
+            .read()
+            .unwrap()
+            .iter()
+            .map(|p| p.provider_name().to_string())
+            .collect()
+    }
+
+    /// Current status of all registered providers, sorted by name
+    pub fn statuses(&self) -> Vec<SyncStatus> {
+        let mut statuses: Vec<SyncStatus> =
+            self.statuses.read().unwrap().values().cloned().collect();
+        statuses.sort_by(|a, b| a.provider_name.cmp(&b.provider_name));
+        statuses
+    }
+
+    pub fn status(&self, provider_name: &str) -> Option<SyncStatus> {
+        self.statuses.read().unwrap().get(provider_name).cloned()
+    }
+
+    /// Set the global offline flag; while set, no provider is synced
+    pub fn set_offline(&self, offline: bool) {
+        if self.offline.swap(offline, Ordering::SeqCst) != offline {
+            info!(
+                "[SyncScheduler] {}",
+                if offline { "Offline" } else { "Online" }
+            );
+            self.wake_tasks();
+        }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::SeqCst)
+    }
+
+    /// Stop scheduling syncs for one provider (a running sync finishes)
+    pub fn pause(&self, provider_name: &str) {
+        self.paused
+            .write()
+            .unwrap()
+            .insert(provider_name.to_string());
+        self.wake_tasks();
+    }
+
+    pub fn resume(&self, provider_name: &str) {
+        self.paused.write().unwrap().remove(provider_name);
+        self.wake_tasks();
+    }
+
+    /// Stop scheduling syncs for all providers
+    pub fn pause_all(&self) {
+        self.all_paused.store(true, Ordering::SeqCst);
+        self.wake_tasks();
+    }
+
+    /// Undo `pause_all` (individually paused providers stay paused)
+    pub fn resume_all(&self) {
+        self.all_paused.store(false, Ordering::SeqCst);
+        self.wake_tasks();
+    }
+
+    pub fn is_paused(&self, provider_name: &str) -> bool {
+        self.all_paused.load(Ordering::SeqCst)
+            || self.paused.read().unwrap().contains(provider_name)
+    }
+
+    /// Sync a provider as soon as possible instead of waiting for its interval
+    ///
+    /// Honors pause and the offline flag: the sync runs once those are lifted.
+    pub fn request_sync(&self, provider_name: &str) {
+        self.requested
+            .write()
+            .unwrap()
+            .insert(provider_name.to_string());
+        self.wake_tasks();
+    }
+
+    fn wake_tasks(&self) {
+        self.wake.send_modify(|generation| *generation += 1);
+    }
+
+    fn blocked_state(&self, provider_name: &str) -> Option<ScheduleState> {
+        if self.is_offline() {
+            Some(ScheduleState::Offline)
+        } else if self.is_paused(provider_name) {
+            Some(ScheduleState::Paused)
+        } else {
+            None
+        }
+    }
+
+    /// Delay until the next scheduled run of a provider
+    pub fn next_delay(&self, provider_name: &str) -> Duration {
+        jittered_interval(
+            self.config.interval_for(provider_name),
+            self.config.jitter,
+            rand::random::<f64>(),
+        )
+    }
+
+    /// Update a provider's status in memory and in the sync_status table
+    async fn update_status(&self, provider_name: &str, update: impl FnOnce(&mut SyncStatus)) {
+        let status = {
+            let mut statuses = self.statuses.write().unwrap();
+            let status = statuses
+                .entry(provider_name.to_string())
+                .or_insert_with(|| {
+                    SyncStatus::new(provider_name, self.config.interval_for(provider_name))
+                });
+            update(status);
+            status.paused = self.is_paused(provider_name);
+            status.clone()
+        };
+        if let Err(e) = self.save_status(&status).await {
+            warn!("[SyncScheduler] Failed to save sync status: {}", e);
+        }
+    }
+
+    async fn save_status(&self, status: &SyncStatus) -> Result<()> {
+        let schema = SyncStatus::schema();
+        let columns: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+        let sql = format!(
+            "INSERT INTO sync_status ({}) VALUES ({}) ON CONFLICT(provider_name) DO UPDATE SET {}",
+            columns.join(", "),
+            columns
+                .iter()
+                .map(|c| format!("${}", c))
+                .collect::<Vec<_>>()
+                .join(", "),
+            columns
+                .iter()
+                .filter(|c| **c != "provider_name")
+                .map(|c| format!("{} = excluded.{}", c, c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        let backend = self.backend.read().await;
+        backend
+            .execute_sql(&sql, status.to_entity().fields)
+            .await
+            .map_err(|e| format!("Failed to save sync status: {}", e))?;
+        Ok(())
+    }
+
+    /// Load the persisted status rows (for debugging and tests)
+    pub async fn load_statuses(&self) -> Result<Vec<SyncStatus>> {
+        let backend = self.backend.read().await;
+        let rows = backend
+            .execute_sql(
+                "SELECT * FROM sync_status ORDER BY provider_name",
+                HashMap::new(),
+            )
+            .await
+            .map_err(|e| format!("Failed to query sync status: {}", e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let mut entity = DynamicEntity::new(SYNC_STATUS_ENTITY);
+                entity.fields = row;
+                SyncStatus::from_entity(entity)
+            })
+            .collect()
+    }
+
+    /// Run one sync of `provider` now, updating its status
+    pub async fn run_provider(&self, provider: &dyn SyncableProvider) -> Result<()> {
+        let name = provider.provider_name().to_string();
+        if self.is_shut_down() {
+            return Err(format!("Sync of {} cancelled: shutting down", name).into());
+        }
+        let started_at = chrono::Utc::now().timestamp_millis();
+        self.update_status(&name, |status| {
+            status.state = ScheduleState::Syncing.as_str().to_string();
+            status.in_progress = true;
+            status.last_started_at = Some(started_at);
+            status.next_run_at = None;
+        })
+        .await;
+
+        debug!("[SyncScheduler] Syncing {}", name);
+        let result = self
+            .orchestrator
+            .run(provider, StreamPosition::Beginning)
+            .await;
+        let finished_at = chrono::Utc::now().timestamp_millis();
+
+        if let Some(sync_dirty) = &self.sync_dirty
+            && result.is_ok()
+            && let Err(e) = sync_dirty.mark_provider_synced(&name, started_at).await
+        {
+            warn!(
+                "[SyncScheduler] Failed to acknowledge synced changes: {}",
+                e
+            );
+        }
+
+        if let Some(sync_health) = &self.sync_health {
+            let recorded = match &result {
+                Ok(_) => sync_health.record_success(&name, 0, 0).await,
+                Err(e) => sync_health.record_failure(&name, &e.to_string()).await,
+            };
+            if let Err(e) = recorded {
+                warn!("[SyncScheduler] Failed to record sync health: {}", e);
+            }
+        }
+
+        match result {
+            Ok(_) => {
+                self.update_status(&name, |status| {
+                    status.state = ScheduleState::Idle.as_str().to_string();
+                    status.in_progress = false;
+                    status.last_success_at = Some(finished_at);
+                    status.consecutive_failures = 0;
+                })
+                .await;
+                Ok(())
+            }
+            Err(e) => {
+                warn!("[SyncScheduler] Sync of {} failed: {}", name, e);
+                let message = e.to_string();
+                self.update_status(&name, |status| {
+                    status.state = ScheduleState::Failing.as_str().to_string();
+                    status.in_progress = false;
+                    status.last_error_at = Some(finished_at);
+                    status.last_error = Some(message);
+                    status.consecutive_failures += 1;
+                })
+                .await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Progress of the current round of syncs
+    pub fn progress(&self) -> SyncProgress {
+        self.orchestrator.progress()
+    }
+
+    pub fn subscribe_progress(&self) -> watch::Receiver<SyncProgress> {
+        self.orchestrator.subscribe_progress()
+    }
+
+    /// Cancel running syncs and stop the provider tasks
+    ///
+    /// Called when the app shuts down; the scheduler syncs nothing afterwards.
+    pub fn shutdown(&self) {
+        self.orchestrator.shutdown();
+        self.wake_tasks();
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        self.orchestrator.is_shut_down()
+    }
+
+    /// Sync all registered providers concurrently and wait for them
+    ///
+    /// Paused providers and, while offline, all providers are skipped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn sync_all(self: &Arc<Self>) -> SyncAllSummary {
+        let providers = self.providers.read().unwrap().clone();
+        let mut summary = SyncAllSummary::default();
+        let mut syncs = tokio::task::JoinSet::new();
+        for provider in providers {
+            let name = provider.provider_name().to_string();
+            if self.blocked_state(&name).is_some() {
+                summary.skipped.push(name);
+                continue;
+            }
+            let scheduler = self.clone();
+            syncs.spawn(async move {
+                let result = scheduler.run_provider(provider.as_ref()).await;
+                (name, result.map_err(|e| e.to_string()))
+            });
+        }
+
+        while let Some(joined) = syncs.join_next().await {
+            match joined {
+                Ok((name, Ok(()))) => summary.synced.push(name),
+                Ok((name, Err(e))) => summary.failed.push((name, e)),
+                Err(e) => warn!("[SyncScheduler] Sync task failed: {}", e),
+            }
+        }
+        summary.synced.sort();
+        summary.failed.sort();
+        summary
+    }
+
+    /// Start one background task per registered provider
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self: Arc<Self>) -> Vec<tokio::task::JoinHandle<()>> {
+        let providers = self.providers.read().unwrap().clone();
+        info!(
+            "[SyncScheduler] Scheduling {} sync providers",
+            providers.len()
+        );
+        providers
+            .into_iter()
+            .map(|provider| tokio::spawn(self.clone().run_schedule(provider)))
+            .collect()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn run_schedule(self: Arc<Self>, provider: Arc<dyn SyncableProvider>) {
+        use tokio::time::Instant;
+
+        let name = provider.provider_name().to_string();
+        let mut wake = self.wake.subscribe();
+        let mut next = if self.config.run_on_start {
+            Instant::now()
+        } else {
+            Instant::now() + self.next_delay(&name)
+        };
+
+        loop {
+            if self.is_shut_down() {
+                break;
+            }
+
+            // Blocked providers wait for a state change; once unblocked, an overdue
+            // sync runs right away
+            if let Some(state) = self.blocked_state(&name) {
+                self.update_status(&name, |status| {
+                    status.state = state.as_str().to_string();
+                    status.next_run_at = None;
+                })
+                .await;
+                if wake.changed().await.is_err() {
+                    break;
+                }
+                continue;
+            }
+
+            if self.requested.read().unwrap().contains(&name) {
+                next = Instant::now();
+            }
+            let delay = next.saturating_duration_since(Instant::now());
+            let next_run_at = chrono::Utc::now().timestamp_millis() + delay.as_millis() as i64;
+            self.update_status(&name, |status| {
+                if status.state != ScheduleState::Failing.as_str() {
+                    status.state = ScheduleState::Idle.as_str().to_string();
+                }
+                status.next_run_at = Some(next_run_at);
+            })
+            .await;
+
+            tokio::select! {
+                _ = tokio::time::sleep_until(next) => {}
+                changed = wake.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    if !self.requested.read().unwrap().contains(&name) {
+                        continue;
+                    }
+                }
+            }
+
+            if self.blocked_state(&name).is_some() {
+                continue;
+            }
+            self.requested.write().unwrap().remove(&name);
+            let _ = self.run_provider(provider.as_ref()).await;
+            next = Instant::now() + self.next_delay(&name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::memory_backend;
+    use async_trait::async_trait;
+    use std::sync::atomic::AtomicUsize;
+
+    struct CountingProvider {
+        name: &'static str,
+        syncs: AtomicUsize,
+        fail: AtomicBool,
+    }
+
+    impl CountingProvider {
+        fn new(name: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                syncs: AtomicUsize::new(0),
+                fail: AtomicBool::new(false),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl SyncableProvider for CountingProvider {
+        fn provider_name(&self) -> &str {
+            self.name
+        }
+
+        async fn sync(
+            &self,
+            position: StreamPosition,
+        ) -> crate::core::datasource::Result<StreamPosition> {
+            self.syncs.fetch_add(1, Ordering::SeqCst);
+            if self.fail.load(Ordering::SeqCst) {
+                return Err("HTTP 401 Unauthorized".into());
+            }
+            Ok(position)
+        }
+    }
+
+    async fn create_scheduler(config: SyncSchedulerConfig) -> Arc<SyncScheduler> {
+        let scheduler = SyncScheduler::new(memory_backend().await, config);
+        scheduler
+            .initialize_schema()
+            .await
+            .expect("Failed to initialize schema");
+        Arc::new(scheduler)
+    }
+
+    async fn wait_for(condition: impl Fn() -> bool) {
+        for _ in 0..200 {
+            if condition() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("condition not reached");
+    }
+
+    #[test]
+    fn test_jittered_interval_bounds() {
+        let interval = Duration::from_secs(100);
+        assert_eq!(
+            jittered_interval(interval, 0.2, 0.0),
+            Duration::from_secs(80)
+        );
+        assert_eq!(
+            jittered_interval(interval, 0.2, 0.5),
+            Duration::from_secs(100)
+        );
+        assert_eq!(jittered_interval(interval, 0.0, 0.9), interval);
+    }
+
+    #[tokio::test]
+    async fn test_run_provider_tracks_status() {
+        let scheduler = create_scheduler(SyncSchedulerConfig::default()).await;
+        let provider = CountingProvider::new("todoist");
+        scheduler.register(provider.clone());
+
+        scheduler.run_provider(provider.as_ref()).await.unwrap();
+        let status = scheduler.status("todoist").unwrap();
+        assert!(status.last_success_at.is_some());
+        assert!(!status.in_progress);
+        assert_eq!(status.state, "idle");
+
+        provider.fail.store(true, Ordering::SeqCst);
+        assert!(scheduler.run_provider(provider.as_ref()).await.is_err());
+        let stored = scheduler.load_statuses().await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].state, "failing");
+        assert_eq!(stored[0].consecutive_failures, 1);
+        assert_eq!(
+            stored[0].last_error.as_deref(),
+            Some("HTTP 401 Unauthorized")
+        );
+        assert!(stored[0].last_success_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_offline_and_pause_hold_back_syncs() {
+        let scheduler = create_scheduler(
+            SyncSchedulerConfig::default().with_default_interval(Duration::from_secs(3600)),
+        )
+        .await;
+        let provider = CountingProvider::new("orgmode");
+        scheduler.register(provider.clone());
+        scheduler.set_offline(true);
+        let _tasks = scheduler.clone().spawn();
+
+        wait_for(|| scheduler.status("orgmode").unwrap().state == "offline").await;
+        assert_eq!(provider.syncs.load(Ordering::SeqCst), 0);
+
+        // Back online, the overdue startup sync runs
+        scheduler.set_offline(false);
+        wait_for(|| provider.syncs.load(Ordering::SeqCst) == 1).await;
+
+        scheduler.pause("orgmode");
+        wait_for(|| scheduler.status("orgmode").unwrap().state == "paused").await;
+        scheduler.request_sync("orgmode");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(provider.syncs.load(Ordering::SeqCst), 1);
+
+        // The pending request runs once resumed
+        scheduler.resume("orgmode");
+        wait_for(|| provider.syncs.load(Ordering::SeqCst) == 2).await;
+        assert!(scheduler.status("orgmode").unwrap().next_run_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sync_all_reports_each_provider() {
+        let scheduler = create_scheduler(SyncSchedulerConfig::default()).await;
+        let todoist = CountingProvider::new("todoist");
+        let orgmode = CountingProvider::new("orgmode");
+        let caldav = CountingProvider::new("caldav");
+        orgmode.fail.store(true, Ordering::SeqCst);
+        scheduler.register(todoist.clone());
+        scheduler.register(orgmode.clone());
+        scheduler.register(caldav.clone());
+        scheduler.pause("caldav");
+
+        let summary = scheduler.sync_all().await;
+        assert_eq!(summary.synced, vec!["todoist".to_string()]);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.failed[0].0, "orgmode");
+        assert_eq!(summary.skipped, vec!["caldav".to_string()]);
+        assert_eq!(caldav.syncs.load(Ordering::SeqCst), 0);
+
+        let progress = scheduler.progress();
+        assert_eq!(
+            (progress.total, progress.succeeded, progress.failed),
+            (2, 1, 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_scheduled_syncs() {
+        let scheduler = create_scheduler(
+            SyncSchedulerConfig::default().with_default_interval(Duration::from_secs(3600)),
+        )
+        .await;
+        let provider = CountingProvider::new("todoist");
+        scheduler.register(provider.clone());
+        let tasks = scheduler.clone().spawn();
+        wait_for(|| provider.syncs.load(Ordering::SeqCst) == 1).await;
+
+        scheduler.shutdown();
+        for task in tasks {
+            tokio::time::timeout(Duration::from_secs(1), task)
+                .await
+                .expect("provider task did not stop")
+                .unwrap();
+        }
+        assert!(scheduler.run_provider(provider.as_ref()).await.is_err());
+        assert_eq!(provider.syncs.load(Ordering::SeqCst), 1);
+    }
+}
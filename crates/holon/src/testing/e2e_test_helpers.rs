@@ -235,7 +235,8 @@ impl ChangeType {
     fn matches(&self, change: &ChangeData) -> bool {
         match (self, change) {
             (ChangeType::Created, ChangeData::Created { .. }) => true,
-            (ChangeType::Updated, ChangeData::Updated { .. }) => true,
+            (ChangeType::Updated, ChangeData::Updated { .. })
+            | (ChangeType::Updated, ChangeData::ColumnChange { .. }) => true,
             (ChangeType::Deleted, ChangeData::Deleted { .. }) => true,
             _ => false,
         }
@@ -247,7 +248,7 @@ impl ChangeType {
 /// # Arguments
 /// * `batches` - Collected stream events (from `collect_stream_events`)
 /// * `expected_type` - Expected change type
-/// * `entity_id` - Optional entity ID to filter by (checks `data.get("id")` for Created/Updated, or `id` field for Deleted)
+/// * `entity_id` - Optional entity ID to filter by (checks `data.get("id")` for Created/Updated, or `id` field for Deleted/ColumnChange)
 ///
 /// # Returns
 /// Ok(()) if the change was found, Err with descriptive message otherwise
@@ -272,7 +273,9 @@ pub fn assert_change_type(
                             .and_then(|v| v.as_string())
                             .map(|id| id == expected_id)
                             .unwrap_or(false),
-                        ChangeData::Deleted { id, .. } => id.as_str() == expected_id,
+                        ChangeData::Deleted { id, .. } | ChangeData::ColumnChange { id, .. } => {
+                            id.as_str() == expected_id
+                        }
                     };
 
                     if matches_id {
@@ -304,6 +307,7 @@ pub fn assert_change_type(
                         .unwrap_or_default(),
                 ),
                 ChangeData::Deleted { id, .. } => ("Deleted", id.clone()),
+                ChangeData::ColumnChange { id, .. } => ("ColumnChange", id.clone()),
             };
             found_changes.push(format!("{}({})", change_type, entity_id_found));
         }
@@ -370,7 +374,8 @@ pub async fn wait_for_change(
                                     .and_then(|v| v.as_string())
                                     .map(|id| id == expected_id)
                                     .unwrap_or(false),
-                                ChangeData::Deleted { id, .. } => id.as_str() == expected_id,
+                                ChangeData::Deleted { id, .. }
+                                | ChangeData::ColumnChange { id, .. } => id.as_str() == expected_id,
                             };
 
                             if matches_id {
@@ -420,7 +425,7 @@ pub fn assert_change_sequence(
         for row_change in &batch.inner.items {
             let change_type = match &row_change.change {
                 ChangeData::Created { .. } => ChangeType::Created,
-                ChangeData::Updated { .. } => ChangeType::Updated,
+                ChangeData::Updated { .. } | ChangeData::ColumnChange { .. } => ChangeType::Updated,
                 ChangeData::Deleted { .. } => ChangeType::Deleted,
             };
 
@@ -433,7 +438,7 @@ pub fn assert_change_sequence(
                     .get("id")
                     .and_then(|v| v.as_string_owned())
                     .unwrap_or_default(),
-                ChangeData::Deleted { id, .. } => id.clone(),
+                ChangeData::Deleted { id, .. } | ChangeData::ColumnChange { id, .. } => id.clone(),
             };
 
             found_changes.push((change_type, entity_id));
@@ -500,7 +505,9 @@ pub fn filter_changes_by_entity(
                     .and_then(|v| v.as_string())
                     .map(|id| id == entity_id)
                     .unwrap_or(false),
-                ChangeData::Deleted { id, .. } => id == entity_id,
+                ChangeData::Deleted { id, .. } | ChangeData::ColumnChange { id, .. } => {
+                    id == entity_id
+                }
             };
 
             if matches {
@@ -534,7 +541,7 @@ pub fn extract_entity_ids(batches: &[BatchWithMetadata<RowChange>]) -> Vec<Strin
                         ids.insert(id.clone());
                     }
                 }
-                ChangeData::Deleted { id, .. } => {
+                ChangeData::Deleted { id, .. } | ChangeData::ColumnChange { id, .. } => {
                     ids.insert(id.clone());
                 }
             }
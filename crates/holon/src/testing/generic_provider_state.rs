@@ -288,6 +288,7 @@ mod tests {
                     affected_fields: vec![],
                     param_mappings: vec![],
                     precondition: None,
+                    simulation: None,
                 },
                 OperationDescriptor {
                     entity_name: "task".to_string(),
@@ -306,6 +307,7 @@ mod tests {
                     affected_fields: vec![],
                     param_mappings: vec![],
                     precondition: None,
+                    simulation: None,
                 },
             ],
         };
@@ -337,6 +339,7 @@ mod tests {
                     affected_fields: vec![],
                     param_mappings: vec![],
                     precondition: None,
+                    simulation: None,
                 },
                 OperationDescriptor {
                     entity_name: "task".to_string(),
@@ -355,6 +358,7 @@ mod tests {
                     affected_fields: vec![],
                     param_mappings: vec![],
                     precondition: None,
+                    simulation: None,
                 },
             ],
         };
@@ -0,0 +1,236 @@
+//! Mapping of webhook payloads to operations
+//!
+//! A `WebhookMapping` matches deliveries by headers and payload values and describes
+//! the operation to run for them. Parameters are templates over the JSON payload,
+//! e.g. `{{/issue/title}}`, or fixed values. For payloads referring to an entity of
+//! the service (a GitHub issue), the operation runs on the entities linked to it
+//! (see `BackendEngine::link_identities`).
+//!
+//! ```rust,ignore
+//! WebhookMapping::new("todoist_tasks", "set_field")
+//!     .when_header("X-GitHub-Event", "issues")
+//!     .when("/action", "closed")
+//!     .linked_to("github_issues", "{{/issue/html_url}}")
+//!     .with_value("field", "completed")
+//!     .with_value("value", true)
+//! ```
+
+use std::collections::HashMap;
+
+use holon_api::Value;
+use serde_json::Value as JsonValue;
+
+use crate::webhooks::WebhookError;
+
+/// Entity of the external service a payload refers to
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkedEntity {
+    pub entity_name: String,
+    /// Template of its ID
+    pub external_id: String,
+}
+
+/// Operation run for the deliveries of an endpoint that match its conditions
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookMapping {
+    /// Headers the delivery must have, e.g. `X-GitHub-Event: issues`
+    pub headers: Vec<(String, String)>,
+    /// Values the payload must have at JSON pointers, e.g. `"closed"` at `/action`
+    pub conditions: Vec<(String, JsonValue)>,
+    pub entity_name: String,
+    pub op_name: String,
+    /// Parameter templates (see `render_template`)
+    pub params: Vec<(String, String)>,
+    /// Fixed parameters
+    pub values: HashMap<String, Value>,
+    /// Run the operation on each entity linked to this one, with its ID as `id`
+    pub linked: Option<LinkedEntity>,
+}
+
+impl WebhookMapping {
+    pub fn new(entity_name: impl Into<String>, op_name: impl Into<String>) -> Self {
+        Self {
+            headers: Vec::new(),
+            conditions: Vec::new(),
+            entity_name: entity_name.into(),
+            op_name: op_name.into(),
+            params: Vec::new(),
+            values: HashMap::new(),
+            linked: None,
+        }
+    }
+
+    /// Only map deliveries with the header `name` set to `value`
+    pub fn when_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Only map payloads with `value` at the JSON pointer `pointer`
+    pub fn when(mut self, pointer: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        self.conditions.push((pointer.into(), value.into()));
+        self
+    }
+
+    pub fn with_param(mut self, name: impl Into<String>, template: impl Into<String>) -> Self {
+        self.params.push((name.into(), template.into()));
+        self
+    }
+
+    pub fn with_value(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.values.insert(name.into(), value.into());
+        self
+    }
+
+    /// Run the operation on the entities linked to the `entity_name` with the templated ID
+    pub fn linked_to(
+        mut self,
+        entity_name: impl Into<String>,
+        external_id: impl Into<String>,
+    ) -> Self {
+        self.linked = Some(LinkedEntity {
+            entity_name: entity_name.into(),
+            external_id: external_id.into(),
+        });
+        self
+    }
+
+    /// Whether a delivery with `headers` (lowercase names) and `payload` is mapped
+    pub fn matches(&self, headers: &HashMap<String, String>, payload: &JsonValue) -> bool {
+        self.headers
+            .iter()
+            .all(|(name, value)| headers.get(&name.to_lowercase()) == Some(value))
+            && self
+                .conditions
+                .iter()
+                .all(|(pointer, value)| payload.pointer(pointer) == Some(value))
+    }
+
+    /// The operation for a matching payload
+    pub fn action(&self, payload: &JsonValue) -> Result<WebhookAction, WebhookError> {
+        let mut params = self.values.clone();
+        for (name, template) in &self.params {
+            params.insert(name.clone(), render_template(template, payload)?);
+        }
+        let linked = match &self.linked {
+            Some(linked) => {
+                let id = render_template(&linked.external_id, payload)?;
+                Some((
+                    linked.entity_name.clone(),
+                    id.as_string_owned().unwrap_or_else(|| id.to_json_string()),
+                ))
+            }
+            None => None,
+        };
+        Ok(WebhookAction {
+            entity_name: self.entity_name.clone(),
+            op_name: self.op_name.clone(),
+            params,
+            linked,
+        })
+    }
+}
+
+/// Operation a delivery maps to
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookAction {
+    pub entity_name: String,
+    pub op_name: String,
+    pub params: HashMap<String, Value>,
+    /// Entity name and ID of the linked entity whose links get the operation
+    pub linked: Option<(String, String)>,
+}
+
+/// Render a parameter template over a JSON payload
+///
+/// `{{/json/pointer}}` placeholders are replaced by the payload's values. A template
+/// that is a single placeholder keeps the value's type (numbers stay numbers); others
+/// render to a string. A placeholder without a value in the payload is an error.
+pub fn render_template(template: &str, payload: &JsonValue) -> Result<Value, WebhookError> {
+    let lookup = |pointer: &str| {
+        payload.pointer(pointer).ok_or_else(|| {
+            WebhookError::Unmappable(format!("No value at {} for `{}`", pointer, template))
+        })
+    };
+
+    if let Some(pointer) = template
+        .strip_prefix("{{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+        .filter(|pointer| !pointer.contains("{{"))
+    {
+        return Ok(Value::from_json_value(lookup(pointer.trim())?.clone()));
+    }
+
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        match lookup(rest[start + 2..start + end].trim())? {
+            JsonValue::String(s) => rendered.push_str(s),
+            other => rendered.push_str(&other.to_string()),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(Value::String(rendered))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_template() {
+        let payload = json!({"issue": {"number": 42, "title": "Crash", "labels": []}});
+        assert_eq!(
+            render_template("{{/issue/number}}", &payload).unwrap(),
+            Value::Integer(42)
+        );
+        assert_eq!(
+            render_template("#{{/issue/number}}: {{ /issue/title }}", &payload).unwrap(),
+            Value::String("#42: Crash".to_string())
+        );
+        assert_eq!(
+            render_template("no placeholders", &payload).unwrap(),
+            Value::String("no placeholders".to_string())
+        );
+        assert!(matches!(
+            render_template("{{/issue/body}}", &payload),
+            Err(WebhookError::Unmappable(_))
+        ));
+    }
+
+    #[test]
+    fn test_mapping_matches_and_maps() {
+        let mapping = WebhookMapping::new("todoist_tasks", "set_field")
+            .when_header("X-GitHub-Event", "issues")
+            .when("/action", "closed")
+            .linked_to("github_issues", "{{/issue/html_url}}")
+            .with_param("value", "{{/issue/state}}")
+            .with_value("field", "state");
+        let headers = HashMap::from([("x-github-event".to_string(), "issues".to_string())]);
+        let closed = json!({
+            "action": "closed",
+            "issue": {"html_url": "https://github.com/o/r/issues/1", "state": "closed"}
+        });
+
+        assert!(mapping.matches(&headers, &closed));
+        assert!(!mapping.matches(&HashMap::new(), &closed));
+        assert!(!mapping.matches(&headers, &json!({"action": "opened"})));
+
+        let action = mapping.action(&closed).unwrap();
+        assert_eq!(
+            action.linked,
+            Some((
+                "github_issues".to_string(),
+                "https://github.com/o/r/issues/1".to_string()
+            ))
+        );
+        assert_eq!(action.params["value"], Value::String("closed".to_string()));
+        assert_eq!(action.params["field"], Value::String("state".to_string()));
+    }
+}
@@ -0,0 +1,59 @@
+//! Inbound webhooks
+//!
+//! An HTTP listener (feature `webhooks`) receiving the webhooks of external services
+//! and turning them into operations, e.g. "GitHub issue closed" into completing the
+//! task linked to the issue.
+//!
+//! - `signature`: verification that a delivery comes from the service
+//! - `mapping`: which deliveries map to which operations, with parameters templated
+//!   from the payload
+//! - `server`: the listener, executing mapped operations through the engine
+//!
+//! Operations are executed with the `ChangeSource` agent `webhook:<endpoint>`, so the
+//! operation log and the audit trail attribute them to the webhook.
+
+pub mod mapping;
+pub mod server;
+pub mod signature;
+
+pub use mapping::{LinkedEntity, WebhookAction, WebhookMapping, render_template};
+pub use server::{GITHUB_DELIVERY_HEADER, WebhookConfig, WebhookEndpoint, WebhookReceiver};
+pub use signature::SignatureScheme;
+
+/// Why a webhook delivery was rejected, or an endpoint can't be configured
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    /// The endpoint configuration is unsafe (e.g. it has no secret)
+    #[error("Invalid webhook endpoint: {0}")]
+    InvalidEndpoint(String),
+
+    #[error("Unknown webhook endpoint: {0}")]
+    UnknownEndpoint(String),
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    #[error("Invalid payload: {0}")]
+    InvalidPayload(String),
+
+    /// A matching mapping can't be applied to the payload (e.g. a templated value is missing)
+    #[error("Cannot map payload: {0}")]
+    Unmappable(String),
+
+    #[error("Operation failed: {0}")]
+    OperationFailed(String),
+}
+
+impl WebhookError {
+    /// HTTP status of the response
+    pub fn status(&self) -> u16 {
+        match self {
+            WebhookError::InvalidEndpoint(_) => 500,
+            WebhookError::UnknownEndpoint(_) => 404,
+            WebhookError::InvalidSignature => 401,
+            WebhookError::InvalidPayload(_) => 400,
+            WebhookError::Unmappable(_) => 422,
+            WebhookError::OperationFailed(_) => 500,
+        }
+    }
+}
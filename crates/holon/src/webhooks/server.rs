@@ -0,0 +1,378 @@
+//! HTTP listener for webhook deliveries
+//!
+//! Services POST deliveries to `/webhooks/<endpoint>`. The response status tells
+//! them what happened:
+//!
+//! - 200: the body is the number of executed operations (0 if no mapping matched,
+//!   e.g. for GitHub's ping)
+//! - 401: the signature is invalid
+//! - 404: there is no such endpoint
+//! - 400: the payload isn't JSON
+//! - 422: a matching mapping can't be applied to the payload
+//! - 500: an operation failed; operations of earlier mappings stay applied
+//!
+//! Operations of a delivery are executed under idempotency keys derived from its
+//! delivery ID (e.g. GitHub's `X-GitHub-Delivery`), so a redelivery doesn't apply
+//! them twice: only operations that failed before are executed again.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use serde_json::Value as JsonValue;
+use tracing::{info, warn};
+
+use crate::api::backend_engine::BackendEngine;
+use crate::webhooks::{SignatureScheme, WebhookError, WebhookMapping};
+use holon_api::{CURRENT_CHANGE_SOURCE, ChangeSource, Value};
+
+/// Header carrying GitHub's delivery ID, the default `delivery_header`
+pub const GITHUB_DELIVERY_HEADER: &str = "X-GitHub-Delivery";
+
+/// A service delivering webhooks, e.g. one GitHub repository
+#[derive(Debug, Clone)]
+pub struct WebhookEndpoint {
+    /// Path segment of the endpoint; executed operations are attributed to `webhook:<name>`
+    pub name: String,
+    /// Secret shared with the service; deliveries to an endpoint without one are rejected
+    pub secret: String,
+    pub signature: SignatureScheme,
+    /// Header carrying the ID of a delivery, kept on redeliveries
+    ///
+    /// Deliveries without it can't be recognised when redelivered.
+    pub delivery_header: Option<String>,
+    /// Mappings applied in order; every matching one runs
+    pub mappings: Vec<WebhookMapping>,
+}
+
+impl WebhookEndpoint {
+    /// Fails if `secret` is empty, since anyone could then sign deliveries
+    pub fn new(
+        name: impl Into<String>,
+        secret: impl Into<String>,
+        signature: SignatureScheme,
+    ) -> Result<Self, WebhookError> {
+        let name = name.into();
+        let secret = secret.into();
+        if secret.is_empty() {
+            return Err(WebhookError::InvalidEndpoint(format!(
+                "Webhook endpoint '{}' has no secret",
+                name
+            )));
+        }
+        Ok(Self {
+            name,
+            secret,
+            signature,
+            delivery_header: Some(GITHUB_DELIVERY_HEADER.to_string()),
+            mappings: Vec::new(),
+        })
+    }
+
+    /// Recognise redeliveries by the delivery ID in `header`
+    pub fn with_delivery_header(mut self, header: impl Into<String>) -> Self {
+        self.delivery_header = Some(header.into());
+        self
+    }
+
+    pub fn with_mapping(mut self, mapping: WebhookMapping) -> Self {
+        self.mappings.push(mapping);
+        self
+    }
+}
+
+/// Configuration of the webhook listener
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// Address the listener binds (port 0 picks a free port)
+    pub addr: SocketAddr,
+    pub endpoints: Vec<WebhookEndpoint>,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            addr: SocketAddr::from(([127, 0, 0, 1], 8787)),
+            endpoints: Vec::new(),
+        }
+    }
+}
+
+impl WebhookConfig {
+    pub fn with_addr(mut self, addr: SocketAddr) -> Self {
+        self.addr = addr;
+        self
+    }
+
+    pub fn with_endpoint(mut self, endpoint: WebhookEndpoint) -> Self {
+        self.endpoints.push(endpoint);
+        self
+    }
+}
+
+/// Verifies deliveries and executes the operations they map to
+pub struct WebhookReceiver {
+    engine: Arc<BackendEngine>,
+    endpoints: HashMap<String, WebhookEndpoint>,
+}
+
+impl WebhookReceiver {
+    pub fn new(engine: Arc<BackendEngine>, endpoints: Vec<WebhookEndpoint>) -> Self {
+        Self {
+            engine,
+            endpoints: endpoints
+                .into_iter()
+                .map(|endpoint| (endpoint.name.clone(), endpoint))
+                .collect(),
+        }
+    }
+
+    /// Handle a delivery to `endpoint`, returning the number of executed operations
+    ///
+    /// `headers` have lowercase names.
+    pub async fn receive(
+        &self,
+        endpoint: &str,
+        headers: &HashMap<String, String>,
+        body: &[u8],
+    ) -> Result<usize, WebhookError> {
+        let endpoint = self
+            .endpoints
+            .get(endpoint)
+            .ok_or_else(|| WebhookError::UnknownEndpoint(endpoint.to_string()))?;
+        if endpoint.secret.is_empty() || !endpoint.signature.verify(&endpoint.secret, headers, body)
+        {
+            return Err(WebhookError::InvalidSignature);
+        }
+        let payload: JsonValue = serde_json::from_slice(body)
+            .map_err(|e| WebhookError::InvalidPayload(e.to_string()))?;
+        let delivery = endpoint
+            .delivery_header
+            .as_ref()
+            .and_then(|header| headers.get(&header.to_lowercase()))
+            .filter(|delivery| !delivery.is_empty());
+
+        let source = ChangeSource::default().with_agent(format!("webhook:{}", endpoint.name));
+        let mut executed = 0;
+        for mapping in &endpoint.mappings {
+            if !mapping.matches(headers, &payload) {
+                continue;
+            }
+            let action = mapping.action(&payload)?;
+            let ids = match &action.linked {
+                Some((entity_name, external_id)) => Some(
+                    self.engine
+                        .resolve_identity(entity_name, external_id, &action.entity_name)
+                        .await
+                        .map_err(|e| WebhookError::OperationFailed(e.to_string()))?,
+                ),
+                None => None,
+            };
+
+            let params = match ids {
+                Some(ids) => ids
+                    .into_iter()
+                    .map(|id| {
+                        let mut params = action.params.clone();
+                        params.insert("id".to_string(), Value::String(id));
+                        params
+                    })
+                    .collect(),
+                None => vec![action.params.clone()],
+            };
+            for params in params {
+                // The n-th operation of a delivery keeps its key when redelivered
+                let idempotency_key = delivery
+                    .map(|delivery| format!("webhook:{}:{}:{}", endpoint.name, delivery, executed));
+                CURRENT_CHANGE_SOURCE
+                    .scope(
+                        source.clone(),
+                        self.engine.execute_operation_with_key(
+                            &action.entity_name,
+                            &action.op_name,
+                            params,
+                            idempotency_key,
+                        ),
+                    )
+                    .await
+                    .map_err(|e| WebhookError::OperationFailed(e.to_string()))?;
+                executed += 1;
+            }
+        }
+
+        info!(
+            "[Webhooks] Delivery to {} executed {} operations",
+            endpoint.name, executed
+        );
+        Ok(executed)
+    }
+
+    /// Serve deliveries on `listener` until it fails
+    pub async fn serve(self: Arc<Self>, listener: tokio::net::TcpListener) -> std::io::Result<()> {
+        let app = Router::new()
+            .route("/webhooks/:endpoint", post(handle_delivery))
+            .with_state(self);
+        axum::serve(listener, app).await
+    }
+}
+
+async fn handle_delivery(
+    State(receiver): State<Arc<WebhookReceiver>>,
+    Path(endpoint): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, String) {
+    let headers: HashMap<String, String> = headers
+        .iter()
+        .filter_map(|(name, value)| {
+            Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+        })
+        .collect();
+    match receiver.receive(&endpoint, &headers, &body).await {
+        Ok(executed) => (StatusCode::OK, executed.to_string()),
+        Err(e) => {
+            warn!("[Webhooks] Rejected delivery to {}: {}", endpoint, e);
+            let status =
+                StatusCode::from_u16(e.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            (status, e.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::datasource::{OperationDescriptor, OperationProvider, UndoAction};
+    use crate::di::test_helpers::{create_test_engine, create_test_engine_with_providers};
+    use crate::storage::types::StorageEntity;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_rejects_unauthenticated_deliveries() {
+        let engine = create_test_engine().await.unwrap();
+        let endpoint = WebhookEndpoint::new("github", "secret", SignatureScheme::github())
+            .unwrap()
+            .with_mapping(
+                WebhookMapping::new("todoist_tasks", "delete").when("/action", "deleted"),
+            );
+        let receiver = WebhookReceiver::new(engine, vec![endpoint]);
+
+        let body = br#"{"zen": "Keep it simple."}"#;
+        let signed = HashMap::from([(
+            "x-hub-signature-256".to_string(),
+            SignatureScheme::github().sign("secret", body),
+        )]);
+        assert!(matches!(
+            receiver.receive("gitlab", &signed, body).await,
+            Err(WebhookError::UnknownEndpoint(_))
+        ));
+        assert!(matches!(
+            receiver.receive("github", &HashMap::new(), body).await,
+            Err(WebhookError::InvalidSignature)
+        ));
+        // No mapping matches the ping
+        assert_eq!(receiver.receive("github", &signed, body).await.unwrap(), 0);
+
+        let body = b"not json";
+        let signed = HashMap::from([(
+            "x-hub-signature-256".to_string(),
+            SignatureScheme::github().sign("secret", body),
+        )]);
+        let err = receiver.receive("github", &signed, body).await.unwrap_err();
+        assert_eq!(err.status(), 400);
+
+        assert!(matches!(
+            WebhookEndpoint::new("github", "", SignatureScheme::github()),
+            Err(WebhookError::InvalidEndpoint(_))
+        ));
+    }
+
+    /// Provider of `tickets.close`, counting its executions
+    struct TicketProvider {
+        closed: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl OperationProvider for TicketProvider {
+        fn operations(&self) -> Vec<OperationDescriptor> {
+            vec![OperationDescriptor {
+                entity_name: "tickets".to_string(),
+                entity_short_name: "ticket".to_string(),
+                id_column: "id".to_string(),
+                name: "close".to_string(),
+                display_name: "Close".to_string(),
+                description: "Close a ticket".to_string(),
+                required_params: vec![],
+                affected_fields: vec![],
+                param_mappings: vec![],
+                precondition: None,
+                simulation: None,
+            }]
+        }
+
+        async fn execute_operation(
+            &self,
+            _entity_name: &str,
+            _op_name: &str,
+            _params: StorageEntity,
+        ) -> crate::core::datasource::Result<UndoAction> {
+            self.closed.fetch_add(1, Ordering::SeqCst);
+            Ok(UndoAction::Irreversible)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redelivery_is_not_applied_twice() {
+        let provider = Arc::new(TicketProvider {
+            closed: AtomicUsize::new(0),
+        });
+        let registered = provider.clone();
+        let engine = create_test_engine_with_providers(":memory:".into(), |module| {
+            module.with_operation_provider(registered)
+        })
+        .await
+        .unwrap();
+        let endpoint = WebhookEndpoint::new("github", "secret", SignatureScheme::github())
+            .unwrap()
+            .with_mapping(WebhookMapping::new("tickets", "close").when("/action", "closed"));
+        let receiver = WebhookReceiver::new(engine, vec![endpoint]);
+
+        let body = br#"{"action": "closed"}"#;
+        let delivery = |id: &str| {
+            HashMap::from([
+                (
+                    "x-hub-signature-256".to_string(),
+                    SignatureScheme::github().sign("secret", body),
+                ),
+                ("x-github-delivery".to_string(), id.to_string()),
+            ])
+        };
+        assert_eq!(
+            receiver
+                .receive("github", &delivery("d1"), body)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            receiver
+                .receive("github", &delivery("d1"), body)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(provider.closed.load(Ordering::SeqCst), 1);
+
+        // A new delivery with the same payload is applied
+        receiver
+            .receive("github", &delivery("d2"), body)
+            .await
+            .unwrap();
+        assert_eq!(provider.closed.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,117 @@
+//! Verification of webhook deliveries
+//!
+//! Services authenticate deliveries with a secret shared with the endpoint: either
+//! an HMAC of the body (GitHub, Stripe-like schemes) or the secret itself in a header
+//! (GitLab). Comparisons run in constant time.
+
+use std::collections::HashMap;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// How a service signs its deliveries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureScheme {
+    /// Hex HMAC-SHA256 of the body, keyed with the secret, in `header` after `prefix`
+    HmacSha256 { header: String, prefix: String },
+    /// The secret itself in `header`
+    Token { header: String },
+}
+
+impl SignatureScheme {
+    /// GitHub's `X-Hub-Signature-256: sha256=<hex>`
+    pub fn github() -> Self {
+        Self::hmac_sha256("X-Hub-Signature-256", "sha256=")
+    }
+
+    pub fn hmac_sha256(header: impl Into<String>, prefix: impl Into<String>) -> Self {
+        Self::HmacSha256 {
+            header: header.into(),
+            prefix: prefix.into(),
+        }
+    }
+
+    pub fn token(header: impl Into<String>) -> Self {
+        Self::Token {
+            header: header.into(),
+        }
+    }
+
+    /// Header carrying the signature
+    pub fn header(&self) -> &str {
+        match self {
+            Self::HmacSha256 { header, .. } | Self::Token { header } => header,
+        }
+    }
+
+    /// Header value the service sends for `body`
+    pub fn sign(&self, secret: &str, body: &[u8]) -> String {
+        match self {
+            Self::HmacSha256 { prefix, .. } => {
+                format!(
+                    "{}{}",
+                    prefix,
+                    hex::encode(hmac(secret, body).finalize().into_bytes())
+                )
+            }
+            Self::Token { .. } => secret.to_string(),
+        }
+    }
+
+    /// Whether `headers` (with lowercase names) carry a valid signature of `body`
+    pub fn verify(&self, secret: &str, headers: &HashMap<String, String>, body: &[u8]) -> bool {
+        let Some(value) = headers.get(&self.header().to_lowercase()) else {
+            return false;
+        };
+        match self {
+            Self::HmacSha256 { prefix, .. } => {
+                let Some(signature) = value
+                    .strip_prefix(prefix.as_str())
+                    .and_then(|hex_signature| hex::decode(hex_signature).ok())
+                else {
+                    return false;
+                };
+                hmac(secret, body).verify_slice(&signature).is_ok()
+            }
+            Self::Token { .. } => constant_time_eq(value.as_bytes(), secret.as_bytes()),
+        }
+    }
+}
+
+fn hmac(secret: &str, body: &[u8]) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_signatures() {
+        let body = br#"{"action": "closed"}"#;
+        let github = SignatureScheme::github();
+        let headers = HashMap::from([(
+            "x-hub-signature-256".to_string(),
+            github.sign("secret", body),
+        )]);
+        assert!(headers["x-hub-signature-256"].starts_with("sha256="));
+        assert!(github.verify("secret", &headers, body));
+        assert!(!github.verify("other secret", &headers, body));
+        assert!(!github.verify("secret", &headers, br#"{"action": "opened"}"#));
+        assert!(!github.verify("secret", &HashMap::new(), body));
+
+        let gitlab = SignatureScheme::token("X-Gitlab-Token");
+        let headers = HashMap::from([("x-gitlab-token".to_string(), "secret".to_string())]);
+        assert!(gitlab.verify("secret", &headers, body));
+        assert!(!gitlab.verify("secret2", &headers, body));
+    }
+}
@@ -79,6 +79,7 @@ impl OperationProvider for SqlOperationProvider {
                 affected_fields: vec![],
                 param_mappings: vec![],
                 precondition: None,
+                simulation: None,
             },
             OperationDescriptor {
                 entity_name: self.entity_name.clone(),
@@ -91,6 +92,7 @@ impl OperationProvider for SqlOperationProvider {
                 affected_fields: vec![],
                 param_mappings: vec![],
                 precondition: None,
+                simulation: None,
             },
             OperationDescriptor {
                 entity_name: self.entity_name.clone(),
@@ -107,6 +109,7 @@ impl OperationProvider for SqlOperationProvider {
                 affected_fields: vec![],
                 param_mappings: vec![],
                 precondition: None,
+                simulation: None,
             },
         ]
     }
@@ -353,6 +356,12 @@ async fn test_query_and_watch_stream() -> Result<()> {
                 Some("Updated content")
             );
         }
+        ChangeData::ColumnChange { columns, .. } => {
+            assert_eq!(
+                columns.get("content").unwrap().as_string(),
+                Some("Updated content")
+            );
+        }
         _ => panic!("Expected Updated change"),
     }
 
@@ -509,7 +518,12 @@ async fn test_multiple_operations_sequence() -> Result<()> {
     let update_count = changes
         .iter()
         .flat_map(|batch| &batch.inner.items)
-        .filter(|change| matches!(change.change, ChangeData::Updated { .. }))
+        .filter(|change| {
+            matches!(
+                change.change,
+                ChangeData::Updated { .. } | ChangeData::ColumnChange { .. }
+            )
+        })
         .count();
 
     assert!(
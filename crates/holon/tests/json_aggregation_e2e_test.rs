@@ -18,6 +18,7 @@ use holon::core::transform::{
     ColumnPreservationTransformer, JsonAggregationTransformer, TransformPipeline,
 };
 use holon::storage::turso::TursoBackend;
+use holon_api::Value;
 
 /// Create a unique database path for testing
 fn unique_db_path() -> PathBuf {
@@ -117,6 +118,7 @@ async fn setup_test_schema(engine: &Arc<BackendEngine>) -> Result<()> {
             content TEXT NOT NULL,
             parent_id TEXT,
             project_id TEXT,
+            section_id TEXT,
             priority INTEGER DEFAULT 1,
             completed INTEGER DEFAULT 0,
             is_deleted INTEGER DEFAULT 0
@@ -126,6 +128,21 @@ async fn setup_test_schema(engine: &Arc<BackendEngine>) -> Result<()> {
     )
     .await?;
 
+    // Create todoist_sections table
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS todoist_sections (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            project_id TEXT NOT NULL,
+            sort_order INTEGER,
+            is_archived INTEGER DEFAULT 0
+        )
+        "#,
+        (),
+    )
+    .await?;
+
     Ok(())
 }
 
@@ -281,6 +298,22 @@ async fn test_production_todoist_query_via_backend_engine() -> Result<()> {
     setup_test_schema(&engine).await?;
     insert_test_data(&engine).await?;
 
+    {
+        let backend = engine.get_backend();
+        let backend_guard = backend.write().await;
+        let conn = backend_guard.get_connection()?;
+        conn.execute(
+            "INSERT INTO todoist_sections (id, name, project_id, sort_order, is_archived) VALUES ('sec-1', 'Section 1', 'proj-1', 1, 0)",
+            (),
+        )
+        .await?;
+        conn.execute(
+            "INSERT INTO todoist_tasks (id, content, parent_id, project_id, section_id, priority, completed, is_deleted) VALUES ('task-3', 'Task 3', NULL, 'proj-1', 'sec-1', 1, 0, 0)",
+            (),
+        )
+        .await?;
+    }
+
     // Load the actual production query
     let prql = include_str!("../../holon-todoist/queries/todoist_hierarchy.prql");
     println!("Testing production query:\n{}\n", prql);
@@ -311,8 +344,17 @@ async fn test_production_todoist_query_via_backend_engine() -> Result<()> {
                     }
                     assert_eq!(
                         results.len(),
-                        4,
-                        "Should have 4 rows (2 projects + 2 tasks)"
+                        6,
+                        "Should have 6 rows (2 projects + 1 section + 3 tasks)"
+                    );
+                    let task_3 = results
+                        .iter()
+                        .find(|row| row.get("id") == Some(&Value::String("task-3".to_string())))
+                        .expect("task-3 should be in the results");
+                    assert_eq!(
+                        task_3.get("parent_id"),
+                        Some(&Value::String("sec-1".to_string())),
+                        "Tasks in a section are nested under it"
                     );
                 }
                 Err(e) => {
@@ -0,0 +1,55 @@
+//! Benchmarks of PRQL compilation
+//!
+//! Run with `cargo bench -p query-render`; `scripts/check-bench-budget.sh` compares
+//! the results with the budget in `scripts/bench-budget.txt`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use query_render::{compiler, parse_query_render, parse_query_render_to_rq, parser};
+
+/// The outliner's main query: a filtered block tree with a nested render template
+const OUTLINER_QUERY: &str = r#"
+from blocks
+filter completed == false
+derive {
+    has_children = true,
+    is_collapsed = false
+}
+select {id, content, parent_id, depth, sort_key, has_children, is_collapsed, completed}
+render (list sort_by:[parent_id, sort_key] item_template:(block
+    indent:depth
+    draggable:true
+    content:(row
+        (collapse_button visible:this.has_children collapsed:this.is_collapsed)
+        (checkbox checked:this.completed)
+        (editable_text content:this.content style:(style dim:true when:this.completed))
+    )
+))
+"#;
+
+fn compile_prql(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prql_compile");
+    group.bench_function("parse_query_render", |b| {
+        b.iter(|| parse_query_render(black_box(OUTLINER_QUERY)).unwrap())
+    });
+    group.bench_function("parse_query_render_to_rq", |b| {
+        b.iter(|| parse_query_render_to_rq(black_box(OUTLINER_QUERY)).unwrap())
+    });
+    group.finish();
+}
+
+fn compile_render_spec(c: &mut Criterion) {
+    let split = parser::split_prql_at_render(OUTLINER_QUERY).unwrap();
+    let render_json = parser::prql_ast_to_json(&split.render_ast).unwrap();
+
+    let mut group = c.benchmark_group("render_spec");
+    group.bench_function("prql_ast_to_json", |b| {
+        b.iter(|| parser::prql_ast_to_json(black_box(&split.render_ast)).unwrap())
+    });
+    group.bench_function("compile_render_spec", |b| {
+        b.iter(|| compiler::compile_render_spec(black_box(&render_json)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, compile_prql, compile_render_spec);
+criterion_main!(benches);
@@ -1,9 +1,69 @@
 use crate::types::*;
+use crate::widgets::WidgetRegistry;
 use anyhow::{bail, Context, Result};
-use holon_api::Value;
+use holon_api::{Value, NAMED_COLORS};
 use std::collections::HashMap;
 
+/// Function building a style rule in `style:` arguments
+pub const STYLE_FUNCTION: &str = "style";
+
+/// Argument of collection widgets ordering their rows
+pub const SORT_BY_ARG: &str = "sort_by";
+
+/// Argument of collection widgets grouping their rows under section headers
+pub const GROUP_BY_ARG: &str = "group_by";
+
+/// Argument naming the column shown in group headers
+pub const GROUP_LABEL_ARG: &str = "group_label";
+
+/// Argument of the root widget naming the view whose UI state is persisted
+pub const VIEW_ID_ARG: &str = "view_id";
+
+/// Argument of the root widget naming the time zone datetimes are shown in
+pub const TIMEZONE_ARG: &str = "timezone";
+
+/// Argument of the root widget naming the column matched against collaborators' cursors
+pub const PRESENCE_ARG: &str = "presence";
+
+/// Root widget showing rows as cards in one lane per `group_by:` value
+pub const BOARD_WIDGET: &str = "board";
+
+/// Argument of `board` listing its lanes in display order
+pub const LANES_ARG: &str = "lanes";
+
+/// Root widget showing rows in day/week/month buckets of a datetime column
+pub const CALENDAR_WIDGET: &str = "calendar";
+
+/// Argument of `calendar` naming the datetime column placing rows
+pub const DATE_ARG: &str = "date";
+
+/// Argument of `calendar` setting the bucket length (`"day"`, `"week"` or `"month"`)
+pub const PERIOD_ARG: &str = "period";
+
+/// Function formatting a datetime as a date, e.g. `(format_date this.due_date style:"long")`
+pub const FORMAT_DATE_FUNCTION: &str = "format_date";
+
+/// Function formatting a number with grouped digits, e.g. `(format_number this.amount decimals:2)`
+pub const FORMAT_NUMBER_FUNCTION: &str = "format_number";
+
+/// Function formatting a datetime relative to now, e.g. `(relative_time this.updated_at)`
+pub const RELATIVE_TIME_FUNCTION: &str = "relative_time";
+
+/// Widget filtering rows by a boolean column
+pub const TOGGLE_FILTER_FUNCTION: &str = "toggle_filter";
+
+/// Widget filtering rows by a range of a date column
+pub const DATE_FILTER_FUNCTION: &str = "date_filter";
+
 pub fn compile_render_spec(render_call: &Value) -> Result<RenderSpec> {
+    compile_render_spec_with_widgets(render_call, None)
+}
+
+/// Compile a render spec, checking its widget calls against `widgets` if given.
+pub fn compile_render_spec_with_widgets(
+    render_call: &Value,
+    widgets: Option<&WidgetRegistry>,
+) -> Result<RenderSpec> {
     let ui_expr = if let Some(obj) = render_call.as_object() {
         if obj.get("__fn").and_then(|v| v.as_string_owned()) == Some("render".to_string()) {
             obj.get("arg0")
@@ -16,15 +76,274 @@ pub fn compile_render_spec(render_call: &Value) -> Result<RenderSpec> {
     };
 
     let root = compile_render_expr(ui_expr)?;
+    if let Some(widgets) = widgets {
+        widgets.validate(&root)?;
+    }
+    let selection = root.selection();
+    let sort = sort_keys(&root)?;
+    let group_by = grouping(&root)?;
+    let view_id = view_id(&root)?;
+    let timezone = timezone(&root)?;
+    let board = board(&root, group_by.as_ref())?;
+    let calendar = calendar(&root)?;
+    let presence = presence(&root)?;
+    let mut filters = Vec::new();
+    collect_filters(&root, &mut filters)?;
 
     Ok(RenderSpec {
         root,
         nested_queries: vec![],
         operations: HashMap::new(), // Removed - not used anymore
         row_templates: vec![],      // Populated by parser for derive { ui = (render ...) } queries
+        selection,
+        sort,
+        group_by,
+        view_id,
+        view_state: ViewState::default(), // Filled in by the backend from its view-state store
+        filters,
+        timezone,
+        board,
+        calendar,
+        presence,
+    })
+}
+
+fn named_arg<'a>(expr: &'a RenderExpr, name: &str) -> Option<&'a RenderExpr> {
+    let RenderExpr::FunctionCall { args, .. } = expr else {
+        return None;
+    };
+    args.iter()
+        .find(|arg| arg.name.as_deref() == Some(name))
+        .map(|arg| &arg.value)
+}
+
+/// `sort_by:` of the root widget, e.g. `sort_by:[priority, -created_at]`
+fn sort_keys(root: &RenderExpr) -> Result<Vec<SortKey>> {
+    let items = match named_arg(root, SORT_BY_ARG) {
+        None => return Ok(vec![]),
+        Some(RenderExpr::Array { items }) => items.iter().collect(),
+        Some(item) => vec![item],
+    };
+    items
+        .into_iter()
+        .map(|item| {
+            sort_key(item)
+                .context("sort_by must list columns, e.g. sort_by:[priority, -created_at]")
+        })
+        .collect()
+}
+
+/// `group_by:` and `group_label:` of the root widget
+fn grouping(root: &RenderExpr) -> Result<Option<GroupSpec>> {
+    let Some(group_by) = named_arg(root, GROUP_BY_ARG) else {
+        return Ok(None);
+    };
+    let key = sort_key(group_by).context("group_by must be a column, e.g. group_by:project_id")?;
+    let label_column = match named_arg(root, GROUP_LABEL_ARG) {
+        None => None,
+        Some(RenderExpr::ColumnRef { name }) => Some(name.clone()),
+        Some(_) => bail!("group_label must be a column, e.g. group_label:project_name"),
+    };
+    Ok(Some(GroupSpec {
+        column: key.column,
+        descending: key.descending,
+        label_column,
+    }))
+}
+
+/// `view_id:` of the root widget, e.g. `view_id:"inbox"`
+fn view_id(root: &RenderExpr) -> Result<Option<String>> {
+    match named_arg(root, VIEW_ID_ARG) {
+        None => Ok(None),
+        Some(RenderExpr::Literal {
+            value: Value::String(id),
+        }) if !id.is_empty() => Ok(Some(id.clone())),
+        Some(_) => bail!("view_id must be a non-empty string, e.g. view_id:\"inbox\""),
+    }
+}
+
+/// `timezone:` of the root widget, e.g. `timezone:"Europe/Berlin"`
+fn timezone(root: &RenderExpr) -> Result<Option<String>> {
+    match named_arg(root, TIMEZONE_ARG) {
+        None => Ok(None),
+        Some(RenderExpr::Literal {
+            value: Value::String(tz),
+        }) if !tz.is_empty() && !tz.contains(char::is_whitespace) => Ok(Some(tz.clone())),
+        Some(_) => bail!("timezone must be a time zone name, e.g. timezone:\"Europe/Berlin\""),
+    }
+}
+
+/// `presence:` of the root widget, e.g. `presence:this.id`
+fn presence(root: &RenderExpr) -> Result<Option<PresenceSpec>> {
+    match named_arg(root, PRESENCE_ARG) {
+        None => Ok(None),
+        Some(RenderExpr::ColumnRef { name }) => Ok(Some(PresenceSpec {
+            column: name.clone(),
+        })),
+        Some(_) => bail!("presence must be a column, e.g. presence:this.id"),
+    }
+}
+
+/// Lanes of a `board` root widget, e.g. `board group_by:status lanes:["todo", "done"]`
+fn board(root: &RenderExpr, group_by: Option<&GroupSpec>) -> Result<Option<BoardSpec>> {
+    let RenderExpr::FunctionCall { name, .. } = root else {
+        return Ok(None);
+    };
+    if name != BOARD_WIDGET {
+        return Ok(None);
+    }
+    let Some(group_by) = group_by else {
+        bail!("board requires a lane column, e.g. (board group_by:status item_template:(text content))");
+    };
+    let items = match named_arg(root, LANES_ARG) {
+        None => &[][..],
+        Some(RenderExpr::Array { items }) => items.as_slice(),
+        Some(item) => std::slice::from_ref(item),
+    };
+    let lanes = items
+        .iter()
+        .map(|item| match item {
+            RenderExpr::Literal { value } => Some(value.clone()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .context("lanes must list values, e.g. lanes:[\"todo\", \"doing\", \"done\"]")?;
+    Ok(Some(BoardSpec {
+        column: group_by.column.clone(),
+        lanes,
+    }))
+}
+
+/// Buckets of a `calendar` root widget, e.g. `calendar date:this.due_date period:"week"`
+fn calendar(root: &RenderExpr) -> Result<Option<CalendarSpec>> {
+    let RenderExpr::FunctionCall { name, .. } = root else {
+        return Ok(None);
+    };
+    if name != CALENDAR_WIDGET {
+        return Ok(None);
+    }
+    let column = match named_arg(root, DATE_ARG) {
+        Some(RenderExpr::ColumnRef { name }) => name.clone(),
+        _ => bail!(
+            "calendar requires a date column, e.g. (calendar date:this.due_date item_template:(text content))"
+        ),
+    };
+    let period = match named_arg(root, PERIOD_ARG) {
+        None => Some(CalendarPeriod::default()),
+        Some(RenderExpr::Literal {
+            value: Value::String(period),
+        }) => CalendarPeriod::parse(period),
+        Some(_) => None,
+    }
+    .context("period must be \"day\", \"week\" or \"month\"")?;
+    Ok(Some(CalendarSpec { column, period }))
+}
+
+/// Filters declared by `toggle_filter`/`date_filter` calls in `expr`
+fn collect_filters(expr: &RenderExpr, filters: &mut Vec<FilterSpec>) -> Result<()> {
+    match expr {
+        RenderExpr::FunctionCall { name, args, .. } => {
+            let kind = match name.as_str() {
+                TOGGLE_FILTER_FUNCTION => Some(FilterKind::Boolean),
+                DATE_FILTER_FUNCTION => Some(FilterKind::DateRange),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                let filter = filter_spec(expr, name, kind)?;
+                if filters.iter().any(|f| f.name == filter.name) {
+                    bail!(
+                        "Filter '{}' is declared twice; set a different name: on one of them",
+                        filter.name
+                    );
+                }
+                filters.push(filter);
+            }
+            for arg in args {
+                collect_filters(&arg.value, filters)?;
+            }
+        }
+        RenderExpr::BinaryOp { left, right, .. } => {
+            collect_filters(left, filters)?;
+            collect_filters(right, filters)?;
+        }
+        RenderExpr::Array { items } => {
+            for item in items {
+                collect_filters(item, filters)?;
+            }
+        }
+        RenderExpr::Object { fields } => {
+            for value in fields.values() {
+                collect_filters(value, filters)?;
+            }
+        }
+        RenderExpr::ColumnRef { .. }
+        | RenderExpr::Literal { .. }
+        | RenderExpr::Style { .. }
+        | RenderExpr::Format { .. }
+        | RenderExpr::Error { .. } => {}
+    }
+    Ok(())
+}
+
+/// `column:`, `name:` and `default:` of a filter widget call
+fn filter_spec(call: &RenderExpr, function: &str, kind: FilterKind) -> Result<FilterSpec> {
+    let column = match named_arg(call, "column") {
+        Some(RenderExpr::ColumnRef { name }) => name.clone(),
+        _ => bail!("{} requires a column, e.g. column:due_date", function),
+    };
+    let name = match named_arg(call, "name") {
+        None => column.clone(),
+        Some(RenderExpr::Literal {
+            value: Value::String(name),
+        }) if !name.is_empty() => name.clone(),
+        Some(_) => bail!("name of {} must be a non-empty string", function),
+    };
+    let default = match (named_arg(call, "default"), kind) {
+        (None, _) => None,
+        (
+            Some(RenderExpr::Literal {
+                value: Value::Boolean(b),
+            }),
+            FilterKind::Boolean,
+        ) => Some(FilterValue::Boolean(*b)),
+        (Some(_), FilterKind::Boolean) => bail!("default of {} must be true or false", function),
+        (Some(_), FilterKind::DateRange) => bail!("{} doesn't take a default", function),
+    };
+    Ok(FilterSpec {
+        name,
+        column,
+        kind,
+        default,
     })
 }
 
+/// A column, or a negated column (`-x`, compiled to `0 - x`) for descending order
+fn sort_key(expr: &RenderExpr) -> Option<SortKey> {
+    match expr {
+        RenderExpr::ColumnRef { name } => Some(SortKey {
+            column: name.clone(),
+            descending: false,
+        }),
+        RenderExpr::BinaryOp {
+            op: BinaryOperator::Sub,
+            left,
+            right,
+        } => match (left.as_ref(), right.as_ref()) {
+            (
+                RenderExpr::Literal {
+                    value: Value::Integer(0),
+                },
+                RenderExpr::ColumnRef { name },
+            ) => Some(SortKey {
+                column: name.clone(),
+                descending: true,
+            }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 /// Compile a render expression from JSON, handling the render() wrapper if present.
 ///
 /// This is used for compiling row templates extracted from derive { ui = (render ...) }.
@@ -75,6 +394,10 @@ fn compile_render_expr(value: &Value) -> Result<RenderExpr> {
         }
         Value::Object(obj) => {
             if let Some(func_name) = obj.get("__fn").and_then(|v| v.as_string_owned()) {
+                if let Some(format) = compile_format(&func_name, obj)? {
+                    return Ok(format);
+                }
+
                 let mut args = vec![];
 
                 for i in 0.. {
@@ -91,9 +414,15 @@ fn compile_render_expr(value: &Value) -> Result<RenderExpr> {
 
                 for (key, value) in obj.iter() {
                     if key != "__fn" && !key.starts_with("arg") {
+                        let value = if key == STYLE_ARG && is_style(value) {
+                            compile_style(value)
+                                .with_context(|| format!("Invalid style of {}", func_name))?
+                        } else {
+                            compile_render_expr(value)?
+                        };
                         args.push(Arg {
                             name: Some(key.clone()),
-                            value: compile_render_expr(value)?,
+                            value,
                         });
                     }
                 }
@@ -141,6 +470,148 @@ fn compile_render_expr(value: &Value) -> Result<RenderExpr> {
     }
 }
 
+/// Compile a `format_date`/`format_number`/`relative_time` call into a
+/// `RenderExpr::Format` (None for other functions)
+fn compile_format(function: &str, call: &HashMap<String, Value>) -> Result<Option<RenderExpr>> {
+    if ![
+        FORMAT_DATE_FUNCTION,
+        FORMAT_NUMBER_FUNCTION,
+        RELATIVE_TIME_FUNCTION,
+    ]
+    .contains(&function)
+    {
+        return Ok(None);
+    }
+    let value = call.get("arg0").with_context(|| {
+        format!(
+            "{} requires a value, e.g. ({} this.due_date)",
+            function, function
+        )
+    })?;
+    if call.contains_key("arg1") {
+        bail!("{} takes a single value", function);
+    }
+
+    let mut style = DateStyle::default();
+    let mut time = false;
+    let mut decimals = None;
+    for (key, arg) in call {
+        match (function, key.as_str()) {
+            (_, "__fn" | "arg0") => {}
+            (FORMAT_DATE_FUNCTION, "style") => {
+                style = match arg.as_string() {
+                    Some("short") => DateStyle::Short,
+                    Some("medium") => DateStyle::Medium,
+                    Some("long") => DateStyle::Long,
+                    _ => bail!(
+                        "style of {} must be \"short\", \"medium\" or \"long\"",
+                        function
+                    ),
+                }
+            }
+            (FORMAT_DATE_FUNCTION, "time") => {
+                time = arg
+                    .as_bool()
+                    .with_context(|| format!("time of {} must be true or false", function))?
+            }
+            (FORMAT_NUMBER_FUNCTION, "decimals") => {
+                decimals = match arg {
+                    Value::Integer(n) if (0..=20).contains(n) => Some(*n as u32),
+                    _ => bail!("decimals of {} must be an integer from 0 to 20", function),
+                }
+            }
+            (_, other) => bail!("{} doesn't take an argument `{}`", function, other),
+        }
+    }
+
+    let format = match function {
+        FORMAT_DATE_FUNCTION => Format::Date { style, time },
+        FORMAT_NUMBER_FUNCTION => Format::Number { decimals },
+        _ => Format::RelativeTime,
+    };
+    Ok(Some(RenderExpr::Format {
+        format,
+        value: Box::new(compile_render_expr(value)?),
+    }))
+}
+
+/// Whether a `style:` argument is written as `(style ...)` calls
+///
+/// Other values (like `progress style:"bar"`) are widget-specific arguments and
+/// compiled as plain expressions.
+fn is_style(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => items.iter().any(is_style),
+        _ => value
+            .as_object()
+            .is_some_and(|obj| obj.contains_key("__fn")),
+    }
+}
+
+/// Compile the `style:` argument of a widget: a `(style ...)` call or a list of them
+fn compile_style(value: &Value) -> Result<RenderExpr> {
+    let calls = match value {
+        Value::Array(items) => items.iter().collect(),
+        _ => vec![value],
+    };
+    let rules = calls
+        .into_iter()
+        .map(compile_style_rule)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(RenderExpr::Style { rules })
+}
+
+fn compile_style_rule(value: &Value) -> Result<StyleRule> {
+    let obj = value
+        .as_object()
+        .filter(|obj| obj.get("__fn").and_then(|v| v.as_string()) == Some(STYLE_FUNCTION))
+        .context("expected (style ...) or a list of (style ...)")?;
+
+    let mut style = Style::default();
+    let mut when = None;
+    for (key, value) in obj.iter() {
+        let flag = || match value {
+            Value::Boolean(b) => Ok(Some(*b)),
+            _ => bail!("style attribute `{}` must be true or false", key),
+        };
+        let color = || match value.as_string() {
+            Some(color) if Style::rgb(color).is_some() => Ok(Some(color.to_string())),
+            _ => bail!(
+                "style attribute `{}` must be #rrggbb or one of: {}",
+                key,
+                NAMED_COLORS
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        match key.as_str() {
+            "__fn" => {}
+            "color" => style.color = color()?,
+            "background" => style.background = color()?,
+            "bold" => style.bold = flag()?,
+            "italic" => style.italic = flag()?,
+            "underline" => style.underline = flag()?,
+            "strikethrough" => style.strikethrough = flag()?,
+            "dim" => style.dim = flag()?,
+            "padding" => {
+                style.padding = match value {
+                    Value::Integer(n) if *n >= 0 => Some(u32::try_from(*n)?),
+                    _ => bail!("style attribute `padding` must be a non-negative integer"),
+                }
+            }
+            "when" => when = Some(compile_render_expr(value)?),
+            other => bail!(
+                "unknown style attribute `{}` (expected color, background, bold, italic, \
+                 underline, strikethrough, dim, padding or when)",
+                other
+            ),
+        }
+    }
+    Ok(StyleRule { style, when })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -310,4 +781,47 @@ mod tests {
             _ => panic!("Expected object"),
         }
     }
+
+    #[test]
+    fn test_compile_format() {
+        let json = json_to_value(serde_json::json!({
+            "__fn": "format_date",
+            "arg0": "$col:this.due_date",
+            "style": "long",
+            "time": true
+        }));
+        match compile_render_expr(&json).unwrap() {
+            RenderExpr::Format { format, value } => {
+                assert_eq!(
+                    format,
+                    Format::Date {
+                        style: DateStyle::Long,
+                        time: true
+                    }
+                );
+                assert!(matches!(*value, RenderExpr::ColumnRef { name } if name == "due_date"));
+            }
+            other => panic!("Expected format, got {:?}", other),
+        }
+
+        let json = json_to_value(serde_json::json!({
+            "__fn": "format_number",
+            "arg0": "$col:amount",
+            "decimals": -1
+        }));
+        let error = compile_render_expr(&json).unwrap_err().to_string();
+        assert!(error.contains("decimals of format_number"), "{}", error);
+
+        let json = json_to_value(serde_json::json!({
+            "__fn": "relative_time",
+            "arg0": "$col:updated_at",
+            "style": "short"
+        }));
+        let error = compile_render_expr(&json).unwrap_err().to_string();
+        assert!(
+            error.contains("relative_time doesn't take an argument `style`"),
+            "{}",
+            error
+        );
+    }
 }
@@ -0,0 +1,494 @@
+//! Completion candidates for query editors
+//!
+//! `QueryCompleter::complete` takes a (usually incomplete) render query and a cursor
+//! position and returns what could be typed there:
+//!
+//! - table names after `from` and `join`, from the registered `EntitySchema`s
+//! - column names in pipeline steps and `render` arguments, from the lineage of the
+//!   steps before the cursor (so `derive`d and `select`ed columns are included)
+//! - widget names after `(` in the `render` expression, and the parameters of the
+//!   widget whose arguments are being typed, if a `WidgetRegistry` is set
+//!
+//! The query doesn't need to compile: the context is taken from the text around the
+//! cursor, and if the steps before it can't be analyzed, the columns of the `from`
+//! table are offered instead.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use holon_api::{EntitySchema, WidgetArgType, STYLE_ARG};
+use prqlc::internal::pl_to_lineage;
+use prqlc::ir::pl::LineageColumn;
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::Span;
+use crate::widgets::WidgetRegistry;
+
+/// Keyword starting the `render` expression
+const RENDER_KEYWORD: &str = "render";
+
+/// Transforms whose first argument is a table
+const TABLE_TRANSFORMS: &[&str] = &["from", "join"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompletionKind {
+    Table,
+    Column,
+    Widget,
+    /// Named argument of the enclosing widget
+    WidgetParam,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Completion {
+    /// Shown in the completion list
+    pub label: String,
+    pub kind: CompletionKind,
+    /// Text replacing `Completions::replace` (e.g. `checked:` for a widget parameter)
+    pub insert_text: String,
+    /// Table of a column, signature of a widget or type of a parameter
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Completions {
+    /// The partial name around the cursor that a completion replaces
+    pub replace: Span,
+    /// Candidates starting with the partial name (ignoring case), sorted by label
+    pub items: Vec<Completion>,
+}
+
+/// Completes render queries from the known tables and widgets
+#[derive(Debug, Clone, Default)]
+pub struct QueryCompleter {
+    tables: BTreeMap<String, BTreeSet<String>>,
+    widgets: Option<WidgetRegistry>,
+}
+
+impl QueryCompleter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Offer `schema`'s table and its columns
+    pub fn with_schema(self, schema: &EntitySchema) -> Self {
+        let columns: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+        self.with_table(&schema.name, columns)
+    }
+
+    /// Offer `table` and its `columns`
+    pub fn with_table(
+        mut self,
+        table: impl Into<String>,
+        columns: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.tables
+            .entry(table.into())
+            .or_default()
+            .extend(columns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Offer widgets and their parameters in `render` expressions
+    pub fn with_widget_registry(mut self, registry: WidgetRegistry) -> Self {
+        self.widgets = Some(registry);
+        self
+    }
+
+    /// Candidates for the name at byte offset `cursor` of `source`
+    pub fn complete(&self, source: &str, cursor: usize) -> Completions {
+        let mut cursor = cursor.min(source.len());
+        while !source.is_char_boundary(cursor) {
+            cursor -= 1;
+        }
+        let start = source[..cursor]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_name_char(*c))
+            .last()
+            .map_or(cursor, |(i, _)| i);
+        let end = source[cursor..]
+            .char_indices()
+            .find(|(_, c)| !is_name_char(*c))
+            .map_or(source.len(), |(i, _)| cursor + i);
+        let prefix = &source[start..cursor];
+
+        let mut items = self.candidates(source, start);
+        let lowercase_prefix = prefix.to_lowercase();
+        items.retain(|item| item.label.to_lowercase().starts_with(&lowercase_prefix));
+        items.sort_by(|a, b| {
+            a.label
+                .cmp(&b.label)
+                .then(a.insert_text.cmp(&b.insert_text))
+        });
+        items.dedup();
+
+        Completions {
+            replace: Span::new(source, start, end),
+            items,
+        }
+    }
+
+    /// All candidates for a name starting at byte offset `start`
+    fn candidates(&self, source: &str, start: usize) -> Vec<Completion> {
+        let before = &source[..start];
+        let render_start = render_keyword_end(before);
+
+        // `this.` always refers to a column
+        if before.ends_with("this.") {
+            let query_end =
+                render_start.map_or_else(|| line_start(before), |end| end - RENDER_KEYWORD.len());
+            return self.columns(&source[..query_end]);
+        }
+
+        match render_start {
+            Some(render_start) => self.render_candidates(source, render_start, start),
+            None => match previous_word(before) {
+                Some(word) if TABLE_TRANSFORMS.contains(&word) => self.tables(),
+                _ => self.columns(&source[..line_start(before)]),
+            },
+        }
+    }
+
+    /// Candidates inside the `render` expression starting at `render_start`
+    fn render_candidates(
+        &self,
+        source: &str,
+        render_start: usize,
+        start: usize,
+    ) -> Vec<Completion> {
+        let query = &source[..render_start - RENDER_KEYWORD.len()];
+        let expr = &source[render_start..start];
+        if expr.ends_with('(') {
+            return self.widget_names();
+        }
+
+        let mut items = self.columns(query);
+        if let (Some(widgets), Some((name, given))) = (&self.widgets, enclosing_call(expr)) {
+            if let Some(widget) = widgets.get(&name) {
+                items.extend(
+                    widget
+                        .params
+                        .iter()
+                        .filter(|param| !given.contains(&param.name))
+                        .map(|param| Completion {
+                            label: param.name.clone(),
+                            kind: CompletionKind::WidgetParam,
+                            insert_text: format!("{}:", param.name),
+                            detail: Some(param_detail(&param.arg_type, param.required)),
+                        }),
+                );
+                if !given.iter().any(|arg| arg == STYLE_ARG) {
+                    items.push(Completion {
+                        label: STYLE_ARG.to_string(),
+                        kind: CompletionKind::WidgetParam,
+                        insert_text: format!("{}:", STYLE_ARG),
+                        detail: None,
+                    });
+                }
+            }
+        }
+        items
+    }
+
+    fn tables(&self) -> Vec<Completion> {
+        self.tables
+            .keys()
+            .map(|table| Completion {
+                label: table.clone(),
+                kind: CompletionKind::Table,
+                insert_text: table.clone(),
+                detail: None,
+            })
+            .collect()
+    }
+
+    fn widget_names(&self) -> Vec<Completion> {
+        let Some(widgets) = &self.widgets else {
+            return Vec::new();
+        };
+        widgets
+            .names()
+            .filter_map(|name| widgets.get(name))
+            .map(|widget| {
+                let params: Vec<String> = widget
+                    .params
+                    .iter()
+                    .map(|param| format!("{}:{:?}", param.name, param.arg_type))
+                    .chain(
+                        widget
+                            .variadic
+                            .iter()
+                            .map(|arg_type| format!("{:?}...", arg_type)),
+                    )
+                    .collect();
+                Completion {
+                    label: widget.name.clone(),
+                    kind: CompletionKind::Widget,
+                    insert_text: widget.name.clone(),
+                    detail: Some(
+                        format!("{} {}", widget.name, params.join(" "))
+                            .trim_end()
+                            .to_string(),
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    /// Columns of the result of `query` (the pipeline steps before the cursor)
+    ///
+    /// Uses the query's lineage; if it can't be computed (e.g. a step is incomplete),
+    /// falls back to the columns of the `from` table.
+    fn columns(&self, query: &str) -> Vec<Completion> {
+        let columns = self
+            .lineage_columns(query)
+            .or_else(|| {
+                let table = from_table(query)?;
+                let columns = self.tables.get(&table)?;
+                Some(
+                    columns
+                        .iter()
+                        .map(|column| (column.clone(), Some(table.clone())))
+                        .collect(),
+                )
+            })
+            .unwrap_or_default();
+
+        columns
+            .into_iter()
+            .map(|(column, table)| Completion {
+                label: column.clone(),
+                kind: CompletionKind::Column,
+                insert_text: column,
+                detail: table,
+            })
+            .collect()
+    }
+
+    /// (column, table) pairs of the last lineage frame of `query`
+    fn lineage_columns(&self, query: &str) -> Option<Vec<(String, Option<String>)>> {
+        if query.trim().is_empty() {
+            return None;
+        }
+        let module = prqlc::prql_to_pl(query).ok()?;
+        let frames = pl_to_lineage(module).ok()?;
+        let lineage = &frames.frames.last()?.1;
+
+        let mut columns = Vec::new();
+        for column in &lineage.columns {
+            match column {
+                LineageColumn::Single {
+                    name: Some(name),
+                    target_id,
+                    ..
+                } => {
+                    let table = lineage
+                        .inputs
+                        .iter()
+                        .find(|input| input.id == *target_id)
+                        .map(|input| input.table.name.clone());
+                    columns.push((name.name.clone(), table));
+                }
+                LineageColumn::Single { name: None, .. } => {}
+                LineageColumn::All { input_id, except } => {
+                    let Some(input) = lineage.inputs.iter().find(|input| input.id == *input_id)
+                    else {
+                        continue;
+                    };
+                    let table = &input.table.name;
+                    if let Some(table_columns) = self.tables.get(table) {
+                        columns.extend(
+                            table_columns
+                                .iter()
+                                .filter(|column| !except.contains(*column))
+                                .map(|column| (column.clone(), Some(table.clone()))),
+                        );
+                    }
+                }
+            }
+        }
+        Some(columns)
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Byte offset of the start of the line containing the end of `text`
+fn line_start(text: &str) -> usize {
+    text.rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Byte offset after the `render` keyword, if `text` contains one at the start of a step
+fn render_keyword_end(text: &str) -> Option<usize> {
+    text.match_indices(RENDER_KEYWORD)
+        .filter(|(i, _)| {
+            let line = &text[line_start(&text[..*i])..*i];
+            let after = text[i + RENDER_KEYWORD.len()..].chars().next();
+            line.trim().is_empty() && after.is_none_or(|c| !is_name_char(c))
+        })
+        .last()
+        .map(|(i, _)| i + RENDER_KEYWORD.len())
+}
+
+/// The word before the partial name at the end of `text`, on the same line
+fn previous_word(text: &str) -> Option<&str> {
+    let line = &text[line_start(text)..];
+    line.split(|c: char| !is_name_char(c))
+        .filter(|word| !word.is_empty())
+        .last()
+        .filter(|_| line.ends_with(char::is_whitespace))
+}
+
+/// Name and given named arguments of the innermost unclosed call in `expr`
+fn enclosing_call(expr: &str) -> Option<(String, Vec<String>)> {
+    let mut depth = 0usize;
+    let mut open = None;
+    for (i, c) in expr.char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' if depth == 0 => {
+                open = Some(i);
+                break;
+            }
+            '(' => depth -= 1,
+            _ => {}
+        }
+    }
+    let args = &expr[open? + 1..];
+    let name: String = args.chars().take_while(|c| is_name_char(*c)).collect();
+    if name.is_empty() {
+        return None;
+    }
+
+    // Named arguments at the call's own nesting level
+    let mut given = Vec::new();
+    let mut depth = 0usize;
+    let mut word = String::new();
+    for c in args[name.len()..].chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ':' if depth == 0 && !word.is_empty() => given.push(word.clone()),
+            _ => {}
+        }
+        if is_name_char(c) {
+            word.push(c);
+        } else {
+            word.clear();
+        }
+    }
+    Some((name, given))
+}
+
+/// Table name of the first `from` step of `query`
+fn from_table(query: &str) -> Option<String> {
+    query.lines().find_map(|line| {
+        let rest = line.trim_start().strip_prefix("from")?;
+        let table: String = rest
+            .trim_start()
+            .chars()
+            .take_while(|c| is_name_char(*c))
+            .collect();
+        (rest.starts_with(char::is_whitespace) && !table.is_empty()).then_some(table)
+    })
+}
+
+fn param_detail(arg_type: &WidgetArgType, required: bool) -> String {
+    if required {
+        format!("{:?} (required)", arg_type)
+    } else {
+        format!("{:?}", arg_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holon_api::{WidgetParam, WidgetSpec};
+
+    fn completer() -> QueryCompleter {
+        QueryCompleter::new()
+            .with_table("todoist_tasks", ["id", "content", "priority", "completed"])
+            .with_table("todoist_projects", ["id", "name"])
+            .with_widget_registry(WidgetRegistry::new([
+                WidgetSpec::new("list").with_param(WidgetParam::required(
+                    "item_template",
+                    WidgetArgType::Widget,
+                )),
+                WidgetSpec::new("text")
+                    .with_param(WidgetParam::required("content", WidgetArgType::String)),
+                WidgetSpec::new("checkbox")
+                    .with_param(WidgetParam::optional("checked", WidgetArgType::Bool)),
+            ]))
+    }
+
+    fn labels(completions: &Completions) -> Vec<&str> {
+        completions.items.iter().map(|c| c.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_tables_after_from() {
+        let source = "from todoist_t";
+        let completions = completer().complete(source, source.len());
+        assert_eq!(
+            labels(&completions),
+            vec!["todoist_projects", "todoist_tasks"]
+        );
+        assert_eq!(
+            (completions.replace.start, completions.replace.end),
+            (5, 14)
+        );
+        assert_eq!(completions.items[0].kind, CompletionKind::Table);
+    }
+
+    #[test]
+    fn test_columns_include_derived() {
+        let source = "from todoist_tasks\nderive { label = content }\nsort pr";
+        let completions = completer().complete(source, source.len());
+        assert_eq!(labels(&completions), vec!["priority"]);
+
+        let source = "from todoist_tasks\nderive { label = content }\nfilter la";
+        let completions = completer().complete(source, source.len());
+        assert_eq!(labels(&completions), vec!["label"]);
+    }
+
+    #[test]
+    fn test_widgets_and_params_in_render() {
+        let source = "from todoist_tasks\nrender (list item_template:(ch";
+        let completions = completer().complete(source, source.len());
+        assert_eq!(labels(&completions), vec!["checkbox"]);
+        assert_eq!(
+            completions.items[0].detail.as_deref(),
+            Some("checkbox checked:Bool")
+        );
+
+        let source = "from todoist_tasks\nrender (list item_template:(checkbox c";
+        let completions = completer().complete(source, source.len());
+        let checked = completions
+            .items
+            .iter()
+            .find(|c| c.kind == CompletionKind::WidgetParam)
+            .unwrap();
+        assert_eq!(checked.insert_text, "checked:");
+        // Columns can be passed positionally
+        assert!(labels(&completions).contains(&"completed"));
+        assert!(labels(&completions).contains(&"content"));
+
+        let source = "from todoist_tasks\nrender (checkbox checked:this.completed ";
+        let completions = completer().complete(source, source.len());
+        assert!(!labels(&completions).contains(&"checked"));
+    }
+
+    #[test]
+    fn test_cursor_inside_name() {
+        let source = "from todoist_tasks\nsort prio";
+        let completions = completer().complete(source, source.len() - 2);
+        assert_eq!(labels(&completions), vec!["priority"]);
+        assert_eq!(
+            &source[completions.replace.start..completions.replace.end],
+            "prio"
+        );
+    }
+}
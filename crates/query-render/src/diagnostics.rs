@@ -0,0 +1,575 @@
+//! Diagnostics for PRQL render queries
+//!
+//! `QueryLinter::lint` checks a query without running it and reports every problem
+//! it finds as a `Diagnostic` with a source span, so editors can underline it:
+//!
+//! - PRQL syntax and compile errors (with the hints prqlc gives)
+//! - errors in the `render` expression
+//! - column names that don't exist in the queried table, if its `EntitySchema` is
+//!   registered
+//! - widgets the frontend doesn't know and arguments they don't take, if a
+//!   `WidgetRegistry` is set
+//!
+//! Unknown names come with suggestions of similarly spelled known names.
+
+use std::collections::{HashMap, HashSet};
+
+use holon_api::{EntitySchema, DELETED_AT_COLUMN, STYLE_ARG};
+use prqlc::pr::*;
+use serde::{Deserialize, Serialize};
+
+use crate::compiler::{
+    FORMAT_DATE_FUNCTION, FORMAT_NUMBER_FUNCTION, RELATIVE_TIME_FUNCTION, STYLE_FUNCTION,
+};
+use crate::parser;
+use crate::widgets::WidgetRegistry;
+
+/// Transforms after which the columns of a pipeline no longer match its `from` table
+const MULTI_TABLE_TRANSFORMS: &[&str] = &["join", "append", "union", "loop"];
+
+/// Render function wrapping the UI expression (not a widget)
+const RENDER_FUNCTION: &str = "render";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    /// The query can't be compiled or rendered
+    Error,
+    /// The query compiles, but likely doesn't do what was intended
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiagnosticKind {
+    /// Reported by the PRQL compiler
+    Prql,
+    /// Invalid `render` expression
+    Render,
+    UnknownColumn,
+    UnknownWidget,
+    /// Named argument the widget doesn't take, or missing required argument
+    InvalidArgument,
+}
+
+/// Location of a diagnostic in the query source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    /// Byte offset of the start
+    pub start: usize,
+    /// Byte offset after the end
+    pub end: usize,
+    /// 1-based line of `start`
+    pub line: usize,
+    /// 1-based column (in characters) of `start`
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(source: &str, start: usize, end: usize) -> Self {
+        let start = start.min(source.len());
+        let before = source.get(..start).unwrap_or_default();
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            start,
+            end: end.clamp(start, source.len()),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    pub message: String,
+    /// `None` if the problem can't be attributed to a part of the source
+    pub span: Option<Span>,
+    /// Possible replacements or hints, best first
+    pub suggestions: Vec<String>,
+}
+
+/// Checks render queries against the known tables and widgets
+#[derive(Debug, Clone, Default)]
+pub struct QueryLinter {
+    tables: HashMap<String, HashSet<String>>,
+    widgets: Option<WidgetRegistry>,
+}
+
+impl QueryLinter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check column names of queries reading `schema`'s table
+    pub fn with_schema(self, schema: &EntitySchema) -> Self {
+        let columns: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+        self.with_table(&schema.name, columns)
+    }
+
+    /// Check column names of queries reading `table` against `columns`
+    pub fn with_table(
+        mut self,
+        table: impl Into<String>,
+        columns: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.tables
+            .entry(table.into())
+            .or_default()
+            .extend(columns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Check widget names in `render` expressions against `registry`
+    pub fn with_widget_registry(mut self, registry: WidgetRegistry) -> Self {
+        self.widgets = Some(registry);
+        self
+    }
+
+    /// All problems found in `source`, in source order
+    pub fn lint(&self, source: &str) -> Vec<Diagnostic> {
+        let split = match parser::split_prql_at_render(source) {
+            Ok(split) => split,
+            Err(e) => return error_diagnostics(source, &e),
+        };
+
+        let mut diagnostics = Vec::new();
+        if let Err(e) = crate::parse_query_render_to_rq(source) {
+            diagnostics.extend(error_diagnostics(source, &e));
+        }
+
+        let Some(main) = main_expr(&split.query_module) else {
+            return diagnostics;
+        };
+        let mut aliases = HashSet::new();
+        collect_aliases(main, &mut aliases);
+
+        let mut lint = Lint {
+            linter: self,
+            source,
+            aliases: &aliases,
+            diagnostics: &mut diagnostics,
+        };
+
+        let table = single_table(main);
+        if let Some(table) = &table {
+            lint.check_pipeline(main, table);
+        }
+        lint.check_render(&split.render_ast, table.as_deref());
+
+        let mut module = split.query_module.clone();
+        if let Ok(templates) = parser::extract_row_templates_from_module(&mut module) {
+            for template in &templates {
+                lint.check_render(&template.render_expr, Some(&template.entity_name));
+            }
+        }
+
+        diagnostics.sort_by_key(|d| d.span.map_or(0, |s| s.start));
+        diagnostics
+    }
+}
+
+/// State of a single `lint` run
+struct Lint<'a> {
+    linter: &'a QueryLinter,
+    source: &'a str,
+    /// Columns added by the query itself (`derive`, `select`, `aggregate`, …)
+    aliases: &'a HashSet<String>,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl Lint<'_> {
+    /// Check the column references in the steps of a single-table pipeline
+    fn check_pipeline(&mut self, main: &Expr, table: &str) {
+        let ExprKind::Pipeline(pipeline) = &main.kind else {
+            return;
+        };
+        for step in pipeline.exprs.iter().skip(1) {
+            if let ExprKind::FuncCall(call) = &step.kind {
+                for arg in call.args.iter().chain(call.named_args.values()) {
+                    self.check_columns(arg, table);
+                }
+            }
+        }
+    }
+
+    fn check_columns(&mut self, expr: &Expr, table: &str) {
+        match &expr.kind {
+            ExprKind::Ident(ident) => self.check_column(ident, expr, table),
+            ExprKind::FuncCall(call) if !is_call_to(expr, RENDER_FUNCTION) => {
+                for arg in call.args.iter().chain(call.named_args.values()) {
+                    self.check_columns(arg, table);
+                }
+            }
+            ExprKind::Pipeline(Pipeline { exprs: items })
+            | ExprKind::Tuple(items)
+            | ExprKind::Array(items) => {
+                for item in items {
+                    self.check_columns(item, table);
+                }
+            }
+            ExprKind::Binary(binary) => {
+                self.check_columns(&binary.left, table);
+                self.check_columns(&binary.right, table);
+            }
+            ExprKind::Unary(unary) => self.check_columns(&unary.expr, table),
+            _ => {}
+        }
+    }
+
+    /// Check widget names and column references of a `render` expression
+    fn check_render(&mut self, expr: &Expr, table: Option<&str>) {
+        match &expr.kind {
+            ExprKind::Ident(ident) => {
+                if let Some(table) = table {
+                    self.check_column(ident, expr, table);
+                }
+            }
+            ExprKind::FuncCall(call) => {
+                self.check_widget(call);
+                for arg in call.args.iter().chain(call.named_args.values()) {
+                    self.check_render(arg, table);
+                }
+            }
+            ExprKind::Tuple(items) | ExprKind::Array(items) => {
+                for item in items {
+                    self.check_render(item, table);
+                }
+            }
+            ExprKind::Binary(binary) => {
+                self.check_render(&binary.left, table);
+                self.check_render(&binary.right, table);
+            }
+            _ => {}
+        }
+    }
+
+    fn check_column(&mut self, ident: &Ident, expr: &Expr, table: &str) {
+        // Only plain (or `this.`) names refer to the table's columns
+        let plain = ident.path.is_empty() || ident.path == ["this"];
+        if !plain || matches!(ident.name.as_str(), "this" | "that" | "*") {
+            return;
+        }
+        let Some(columns) = self.linter.tables.get(table) else {
+            return;
+        };
+        let name = ident.name.as_str();
+        if columns.contains(name) || self.aliases.contains(name) || name == DELETED_AT_COLUMN {
+            return;
+        }
+
+        let candidates = columns
+            .iter()
+            .chain(self.aliases.iter())
+            .map(String::as_str);
+        self.push(
+            Severity::Warning,
+            DiagnosticKind::UnknownColumn,
+            format!("Unknown column `{}` in `{}`", name, table),
+            expr,
+            similar_names(name, candidates),
+        );
+    }
+
+    fn check_widget(&mut self, call: &FuncCall) {
+        let Some(widgets) = &self.linter.widgets else {
+            return;
+        };
+        let ExprKind::Ident(ident) = &call.name.kind else {
+            return;
+        };
+        let name = ident.name.as_str();
+        if [
+            RENDER_FUNCTION,
+            STYLE_FUNCTION,
+            FORMAT_DATE_FUNCTION,
+            FORMAT_NUMBER_FUNCTION,
+            RELATIVE_TIME_FUNCTION,
+        ]
+        .contains(&name)
+        {
+            return;
+        }
+        let Some(widget) = widgets.get(name) else {
+            self.push(
+                Severity::Error,
+                DiagnosticKind::UnknownWidget,
+                format!("Unknown widget `{}`", name),
+                &call.name,
+                similar_names(name, widgets.names()),
+            );
+            return;
+        };
+
+        for (arg_name, value) in &call.named_args {
+            if arg_name != STYLE_ARG && widget.param(arg_name).is_none() {
+                let params = widget.params.iter().map(|p| p.name.as_str());
+                self.push(
+                    Severity::Error,
+                    DiagnosticKind::InvalidArgument,
+                    format!("Widget `{}` has no parameter `{}`", name, arg_name),
+                    value,
+                    similar_names(arg_name, params),
+                );
+            }
+        }
+        let positional = call.args.len();
+        let missing: Vec<&str> = widget
+            .params
+            .iter()
+            .enumerate()
+            .filter(|(i, p)| {
+                p.required && *i >= positional && !call.named_args.contains_key(&p.name)
+            })
+            .map(|(_, p)| p.name.as_str())
+            .collect();
+        if !missing.is_empty() {
+            self.push(
+                Severity::Error,
+                DiagnosticKind::InvalidArgument,
+                format!(
+                    "Widget `{}` requires parameter `{}`",
+                    name,
+                    missing.join("`, `")
+                ),
+                &call.name,
+                Vec::new(),
+            );
+        }
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        kind: DiagnosticKind,
+        message: String,
+        expr: &Expr,
+        suggestions: Vec<String>,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            kind,
+            message,
+            span: expr
+                .span
+                .as_ref()
+                .map(|s| Span::new(self.source, s.start, s.end)),
+            suggestions,
+        });
+    }
+}
+
+/// Diagnostics for an error returned while compiling `source`
+fn error_diagnostics(source: &str, error: &anyhow::Error) -> Vec<Diagnostic> {
+    match error.downcast_ref::<prqlc::ErrorMessages>() {
+        Some(messages) => messages
+            .inner
+            .iter()
+            .map(|message| Diagnostic {
+                severity: Severity::Error,
+                kind: DiagnosticKind::Prql,
+                message: message.reason.clone(),
+                span: message
+                    .span
+                    .as_ref()
+                    .map(|s| Span::new(source, s.start, s.end)),
+                suggestions: message.hints.clone(),
+            })
+            .collect(),
+        None => vec![Diagnostic {
+            severity: Severity::Error,
+            kind: DiagnosticKind::Render,
+            message: format!("{:#}", error),
+            span: None,
+            suggestions: Vec::new(),
+        }],
+    }
+}
+
+pub(crate) fn main_expr(module: &ModuleDef) -> Option<&Expr> {
+    module.stmts.iter().find_map(|stmt| match &stmt.kind {
+        StmtKind::VarDef(var_def) if matches!(var_def.kind, VarDefKind::Main) => {
+            var_def.value.as_deref()
+        }
+        _ => None,
+    })
+}
+
+/// Table the main pipeline reads, if its columns are that table's columns
+fn single_table(main: &Expr) -> Option<String> {
+    let ExprKind::Pipeline(pipeline) = &main.kind else {
+        return None;
+    };
+    let mut steps = pipeline.exprs.iter();
+    let table = match &steps.next()?.kind {
+        ExprKind::FuncCall(call) if is_ident(&call.name, "from") => {
+            match &call.args.first()?.kind {
+                ExprKind::Ident(table) => table.name.clone(),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    let multi_table = steps.any(|step| {
+        MULTI_TABLE_TRANSFORMS
+            .iter()
+            .any(|transform| is_call_to(step, transform))
+    });
+    (!multi_table).then_some(table)
+}
+
+/// Names of all aliased expressions (derived/selected columns)
+fn collect_aliases(expr: &Expr, aliases: &mut HashSet<String>) {
+    if let Some(alias) = &expr.alias {
+        aliases.insert(alias.clone());
+    }
+    match &expr.kind {
+        ExprKind::FuncCall(call) => {
+            for arg in call.args.iter().chain(call.named_args.values()) {
+                collect_aliases(arg, aliases);
+            }
+        }
+        ExprKind::Pipeline(Pipeline { exprs: items })
+        | ExprKind::Tuple(items)
+        | ExprKind::Array(items) => {
+            for item in items {
+                collect_aliases(item, aliases);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub(crate) fn is_ident(expr: &Expr, name: &str) -> bool {
+    matches!(&expr.kind, ExprKind::Ident(ident) if ident.name == name)
+}
+
+pub(crate) fn is_call_to(expr: &Expr, function: &str) -> bool {
+    match &expr.kind {
+        ExprKind::FuncCall(call) => is_ident(&call.name, function),
+        _ => false,
+    }
+}
+
+/// Candidates within a small edit distance of `name`, closest first
+fn similar_names<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut similar: Vec<(usize, &str)> = candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    similar.sort();
+    similar.dedup();
+    similar
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            current.push(substitution.min(prev[j + 1] + 1).min(current[j] + 1));
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holon_api::{WidgetArgType, WidgetParam, WidgetSpec};
+
+    fn linter() -> QueryLinter {
+        QueryLinter::new()
+            .with_table("todoist_tasks", ["id", "content", "priority", "completed"])
+            .with_widget_registry(WidgetRegistry::new([
+                WidgetSpec::new("list").with_param(WidgetParam::required(
+                    "item_template",
+                    WidgetArgType::Widget,
+                )),
+                WidgetSpec::new("text")
+                    .with_param(WidgetParam::required("content", WidgetArgType::String)),
+                WidgetSpec::new("row").with_variadic(WidgetArgType::Widget),
+                WidgetSpec::new("checkbox")
+                    .with_param(WidgetParam::optional("checked", WidgetArgType::Bool)),
+            ]))
+    }
+
+    #[test]
+    fn test_valid_query() {
+        let source = r#"
+from todoist_tasks
+filter completed == false
+derive { label = content }
+render (list item_template:(row (checkbox checked:this.completed) (text label)))
+"#;
+        assert_eq!(linter().lint(source), vec![]);
+    }
+
+    #[test]
+    fn test_unknown_column_and_widget() {
+        let source = "from todoist_tasks\nsort priorty\nrender (list item_template:(txt contnet))";
+        let diagnostics = linter().lint(source);
+        assert_eq!(diagnostics.len(), 3, "{:?}", diagnostics);
+
+        let column = &diagnostics[0];
+        assert_eq!(column.kind, DiagnosticKind::UnknownColumn);
+        assert_eq!(column.severity, Severity::Warning);
+        assert_eq!(column.suggestions, vec!["priority"]);
+        let span = column.span.unwrap();
+        assert_eq!(&source[span.start..span.end], "priorty");
+        assert_eq!((span.line, span.column), (2, 6));
+
+        assert_eq!(diagnostics[1].kind, DiagnosticKind::UnknownWidget);
+        assert_eq!(diagnostics[1].suggestions, vec!["text"]);
+        assert_eq!(diagnostics[2].suggestions, vec!["content"]);
+    }
+
+    #[test]
+    fn test_syntax_error_has_span() {
+        let diagnostics =
+            linter().lint("from todoist_tasks\nfilter (content ==\nrender (text content)");
+        assert!(!diagnostics.is_empty());
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::Prql);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert!(diagnostics[0].span.is_some());
+    }
+
+    #[test]
+    fn test_invalid_widget_arguments() {
+        let source = "from todoist_tasks\nrender (list item_templat:(text content))";
+        let diagnostics = linter().lint(source);
+        let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiagnosticKind::InvalidArgument,
+                DiagnosticKind::InvalidArgument
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "Widget `list` requires parameter `item_template`"
+        );
+        assert_eq!(diagnostics[1].suggestions, vec!["item_template"]);
+    }
+
+    #[test]
+    fn test_unregistered_table_is_not_checked() {
+        let diagnostics = linter().lint("from blocks\nrender (text anything)");
+        assert_eq!(diagnostics, vec![]);
+    }
+
+    #[test]
+    fn test_span_line_and_column() {
+        let span = Span::new("ab\ncäd", 6, 7);
+        assert_eq!((span.line, span.column), (2, 3));
+    }
+}
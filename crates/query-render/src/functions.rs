@@ -0,0 +1,796 @@
+//! Built-in PRQL functions available in every query
+//!
+//! The agenda functions filter org headlines by their `scheduled` and `deadline`
+//! columns, which hold the org timestamp text (`<2024-01-15 Mon 10:00>`). Dates
+//! are compared as ISO date strings (`"2024-01-15"`), so arguments may be string
+//! literals or query parameters:
+//!
+//! ```prql
+//! from org_headlines
+//! scheduled_between "2024-01-15" "2024-01-21"
+//! render (list item_template:(text content:this.title))
+//! ```
+//!
+//! `resolve <entity> <column>` translates IDs between datasources with their own
+//! ID schemes. It is rewritten into a join against the `entity_identities` table
+//! and adds an `<entity>_id` column (null for rows without a linked ID):
+//!
+//! ```prql
+//! from todoist_tasks
+//! resolve logseq_blocks id
+//! join logseq_blocks (this.logseq_blocks_id == that.id)
+//! render (list item_template:(text content:this.content))
+//! ```
+//!
+//! `recently_viewed <n>` and `recently_modified <n>` keep the `n` rows of the
+//! pipeline's table that were viewed or modified last, newest first. They join
+//! against the `entity_access` table (kept by holon's `EntityAccessStore`) on the
+//! `id` column and add a `last_viewed_at`/`last_modified_at` column:
+//!
+//! ```prql
+//! from todoist_tasks
+//! recently_modified 20
+//! render (list item_template:(text content:this.content))
+//! ```
+//!
+//! `custom <relation> "<field>"` reads a user-defined field (kept by holon's
+//! `CustomFieldStore`) of the rows of the pipeline's table. It compiles into a
+//! subquery on the `custom_field_values` table and is null where the field isn't set:
+//!
+//! ```prql
+//! from todoist_tasks
+//! filter (custom this "energy") >= 3
+//! derive {context = (custom this "context")}
+//! render (list item_template:(text content:this.content))
+//! ```
+//!
+//! `limit_after <n> sortkey:@cursor` pages through a tree by its parent chain and
+//! sort keys (see `holon_api::TreeCursor`). It keeps the rows after the cursor
+//! passed in the `cursor` parameter, deepest level first, and then the first `n`
+//! of them (all of them without `n`). Without a cursor, the page starts at the
+//! first root row:
+//!
+//! ```prql
+//! from blocks
+//! limit_after 100 sortkey:@cursor
+//! render (tree parent_id:parent_id sortkey:sort_key item_template:(text content:this.content))
+//! ```
+//!
+//! A query defining a function of the same name uses its own definition.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Context, Result};
+use holon_api::{TreeCursor, Value, TREE_PARENT_COLUMN, TREE_SORT_COLUMN};
+use prqlc::pr::*;
+
+/// PRQL source of the built-in functions
+pub const BUILTIN_FUNCTIONS: &str = r#"
+# ISO date (YYYY-MM-DD) of an org timestamp such as `<2024-01-15 Mon 10:00>`
+let org_date = func timestamp -> s"substr(ltrim({timestamp}, '<['), 1, 10)"
+
+# Headlines scheduled from `first_day` to `last_day`, both inclusive
+let scheduled_between = func first_day last_day rel -> (
+    rel
+    filter (org_date scheduled) >= first_day && (org_date scheduled) <= last_day
+)
+
+# Headlines with a deadline in the next `days` days, including overdue ones
+let deadline_within = func days rel -> (
+    rel
+    filter (org_date deadline) <= s"date('now', '+' || {days} || ' days')"
+)
+
+# Whether an org timestamp falls in the seven days starting today
+let in_agenda_week = func timestamp -> (
+    (org_date timestamp) >= s"date('now')" && (org_date timestamp) <= s"date('now', '+6 days')"
+)
+
+# Headlines scheduled or due in the seven days starting today
+let agenda_week = func rel -> (
+    rel
+    filter (in_agenda_week scheduled) || (in_agenda_week deadline)
+)
+"#;
+
+/// Add the built-in functions to a parsed query, after its `prql` header
+///
+/// Functions the query defines itself are not added.
+/// flutter_rust_bridge:ignore
+pub fn add_builtin_functions(module: &mut ModuleDef) -> Result<()> {
+    let defined = defined_names(module);
+    let builtins = prqlc::prql_to_pl(BUILTIN_FUNCTIONS)?
+        .stmts
+        .into_iter()
+        .filter(|stmt| match &stmt.kind {
+            StmtKind::VarDef(var_def) => !defined.contains(&var_def.name),
+            _ => false,
+        });
+
+    let position = module
+        .stmts
+        .iter()
+        .take_while(|stmt| matches!(stmt.kind, StmtKind::QueryDef(_)))
+        .count();
+    module.stmts.splice(position..position, builtins);
+    Ok(())
+}
+
+/// Pipeline step translating IDs into another entity's IDs
+pub const RESOLVE_FUNCTION: &str = "resolve";
+
+/// Table linking entity IDs to shared identities (kept by holon's `EntityIdentityStore`)
+pub const IDENTITIES_TABLE: &str = "entity_identities";
+
+/// Rewrite `resolve <entity> <column>` steps into joins against `entity_identities`
+///
+/// The source entity is the table of the pipeline's `from`. Not applied if the
+/// query defines `resolve` itself.
+/// flutter_rust_bridge:ignore
+pub fn apply_resolve(module: &mut ModuleDef) -> Result<()> {
+    if defined_names(module).contains(RESOLVE_FUNCTION) {
+        return Ok(());
+    }
+    for stmt in &mut module.stmts {
+        if let StmtKind::VarDef(var_def) = &mut stmt.kind {
+            if let Some(value) = &mut var_def.value {
+                resolve_in_expr(value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn resolve_in_expr(expr: &mut Expr) -> Result<()> {
+    match &mut expr.kind {
+        ExprKind::Pipeline(pipeline) => {
+            let mut source: Option<String> = None;
+            for step in &mut pipeline.exprs {
+                if let Some(table) = from_table(step) {
+                    source = Some(table);
+                }
+                if is_call(step, RESOLVE_FUNCTION) {
+                    let Some(source) = &source else {
+                        bail!("`resolve` needs a pipeline starting with `from <table>`");
+                    };
+                    *step = resolve_step(source, step)?;
+                } else if let ExprKind::FuncCall(call) = &mut step.kind {
+                    // Nested pipelines, e.g. `join (from other | resolve ...)`
+                    for arg in &mut call.args {
+                        resolve_in_expr(arg)?;
+                    }
+                }
+            }
+        }
+        ExprKind::FuncCall(call) => {
+            for arg in &mut call.args {
+                resolve_in_expr(arg)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// `join side:left` step adding `<target>_id` for a `resolve <target> <column>` step
+fn resolve_step(source: &str, step: &Expr) -> Result<Expr> {
+    let ExprKind::FuncCall(call) = &step.kind else {
+        bail!("`resolve` expects an entity and an ID column");
+    };
+    let (target, column) = match call.args.as_slice() {
+        [target, column] => (target, column),
+        _ => bail!("`resolve` expects an entity and an ID column, e.g. `resolve logseq_blocks id`"),
+    };
+    let target = match &target.kind {
+        ExprKind::Ident(ident) if ident.path.is_empty() => ident.name.clone(),
+        ExprKind::Literal(Literal::String(name)) => name.clone(),
+        _ => bail!("`resolve` expects an entity name as first argument"),
+    };
+    for name in [source, target.as_str()] {
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            bail!("`resolve` can't be used with entity `{}`", name);
+        }
+    }
+    let column = match &column.kind {
+        ExprKind::Ident(ident) => {
+            let mut parts = if ident.path.is_empty() {
+                vec!["this".to_string()]
+            } else {
+                ident.path.clone()
+            };
+            parts.push(ident.name.clone());
+            parts
+                .iter()
+                .map(|part| format!("`{}`", part))
+                .collect::<Vec<_>>()
+                .join(".")
+        }
+        _ => bail!("`resolve` expects an ID column as second argument"),
+    };
+
+    let source_text = format!(
+        r#"
+join side:left {target}_identity = (
+    from source_identity = {table}
+    join target_identity = {table} (source_identity.identity_id == target_identity.identity_id)
+    filter source_identity.entity_name == "{source}" && target_identity.entity_name == "{target}"
+    select {{{target}_source_id = source_identity.external_id, {target}_id = target_identity.external_id}}
+) ({column} == that.{target}_source_id)
+"#,
+        table = IDENTITIES_TABLE,
+    );
+    let mut steps = parse_steps(&source_text)?;
+    if steps.len() != 1 {
+        bail!("Failed to build resolve step");
+    }
+    Ok(steps.remove(0))
+}
+
+/// Pipeline step keeping the most recently viewed rows
+pub const RECENTLY_VIEWED_FUNCTION: &str = "recently_viewed";
+
+/// Pipeline step keeping the most recently modified rows
+pub const RECENTLY_MODIFIED_FUNCTION: &str = "recently_modified";
+
+/// Table of entity access timestamps (kept by holon's `EntityAccessStore`)
+pub const ACCESS_TABLE: &str = "entity_access";
+
+/// Rewrite `recently_viewed <n>`/`recently_modified <n>` steps into joins against `entity_access`
+///
+/// The entity is the table of the pipeline's `from`. A function the query
+/// defines itself is not rewritten.
+/// flutter_rust_bridge:ignore
+pub fn apply_recency(module: &mut ModuleDef) -> Result<()> {
+    let defined = defined_names(module);
+    let functions: Vec<(&str, &str)> = [
+        (RECENTLY_VIEWED_FUNCTION, "last_viewed_at"),
+        (RECENTLY_MODIFIED_FUNCTION, "last_modified_at"),
+    ]
+    .into_iter()
+    .filter(|(function, _)| !defined.contains(*function))
+    .collect();
+    if functions.is_empty() {
+        return Ok(());
+    }
+    for stmt in &mut module.stmts {
+        if let StmtKind::VarDef(var_def) = &mut stmt.kind {
+            if let Some(value) = &mut var_def.value {
+                recency_in_expr(value, &functions)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn recency_in_expr(expr: &mut Expr, functions: &[(&str, &str)]) -> Result<()> {
+    match &mut expr.kind {
+        ExprKind::Pipeline(pipeline) => {
+            let mut source: Option<String> = None;
+            let mut exprs = Vec::with_capacity(pipeline.exprs.len());
+            for mut step in std::mem::take(&mut pipeline.exprs) {
+                if let Some(table) = from_table(&step) {
+                    source = Some(table);
+                }
+                let recency = functions
+                    .iter()
+                    .find(|(function, _)| is_call(&step, function));
+                if let Some((function, column)) = recency {
+                    let Some(source) = &source else {
+                        bail!(
+                            "`{}` needs a pipeline starting with `from <table>`",
+                            function
+                        );
+                    };
+                    exprs.extend(recency_steps(source, function, column, &step)?);
+                    continue;
+                }
+                if let ExprKind::FuncCall(call) = &mut step.kind {
+                    // Nested pipelines, e.g. `join (from other | recently_viewed 5)`
+                    for arg in &mut call.args {
+                        recency_in_expr(arg, functions)?;
+                    }
+                }
+                exprs.push(step);
+            }
+            pipeline.exprs = exprs;
+        }
+        ExprKind::FuncCall(call) => {
+            for arg in &mut call.args {
+                recency_in_expr(arg, functions)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// `join` and `sort` steps for a `recently_viewed <n>`/`recently_modified <n>` step
+fn recency_steps(source: &str, function: &str, column: &str, step: &Expr) -> Result<Vec<Expr>> {
+    let count = match &step.kind {
+        ExprKind::FuncCall(call) => match call.args.as_slice() {
+            [count] => match &count.kind {
+                ExprKind::Literal(Literal::Integer(count)) if *count > 0 => Some(*count),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    };
+    let Some(count) = count else {
+        bail!(
+            "`{}` expects a positive number of rows, e.g. `{} 20`",
+            function,
+            function
+        );
+    };
+    if !source
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        bail!("`{}` can't be used with entity `{}`", function, source);
+    }
+
+    let source_text = format!(
+        r#"
+join side:inner {function}_access = (
+    from {table}
+    filter entity_name == "{source}" && {column} != null
+    sort {{-{column}}}
+    take {count}
+    select {{{function}_id = entity_id, {column}}}
+) (this.id == that.{function}_id)
+sort {{-{function}_access.{column}}}
+"#,
+        table = ACCESS_TABLE,
+    );
+    let steps = parse_steps(&source_text)?;
+    if steps.len() != 2 {
+        bail!("Failed to build {} steps", function);
+    }
+    Ok(steps)
+}
+
+/// Accessor of a user-defined field
+pub const CUSTOM_FUNCTION: &str = "custom";
+
+/// Table of custom field values (kept by holon's `CustomFieldStore`)
+pub const CUSTOM_FIELD_VALUES_TABLE: &str = "custom_field_values";
+
+/// Rewrite `custom <relation> "<field>"` calls into subqueries on `custom_field_values`
+///
+/// The entity is the table of the pipeline's `from`. Not applied if the query
+/// defines `custom` itself.
+/// flutter_rust_bridge:ignore
+pub fn apply_custom_fields(module: &mut ModuleDef) -> Result<()> {
+    if defined_names(module).contains(CUSTOM_FUNCTION) {
+        return Ok(());
+    }
+    for stmt in &mut module.stmts {
+        if let StmtKind::VarDef(var_def) = &mut stmt.kind {
+            if let Some(value) = &mut var_def.value {
+                custom_in_expr(value, None)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn custom_in_expr(expr: &mut Expr, source: Option<&str>) -> Result<()> {
+    if is_call(expr, CUSTOM_FUNCTION) {
+        let Some(source) = source else {
+            bail!("`custom` needs a pipeline starting with `from <table>`");
+        };
+        *expr = custom_field_expr(source, expr)?;
+        return Ok(());
+    }
+    match &mut expr.kind {
+        ExprKind::Pipeline(pipeline) => {
+            let mut source = source.map(str::to_string);
+            for step in &mut pipeline.exprs {
+                if let Some(table) = from_table(step) {
+                    source = Some(table);
+                }
+                // render() arguments are evaluated by the frontends, not in SQL
+                if !is_call(step, "render") {
+                    custom_in_expr(step, source.as_deref())?;
+                }
+            }
+        }
+        ExprKind::FuncCall(call) => {
+            // Arguments of `from`, `join` etc. may be pipelines on other tables
+            for arg in call.args.iter_mut().chain(call.named_args.values_mut()) {
+                custom_in_expr(arg, source)?;
+            }
+        }
+        ExprKind::Tuple(items) | ExprKind::Array(items) => {
+            for item in items {
+                custom_in_expr(item, source)?;
+            }
+        }
+        ExprKind::Binary(binary) => {
+            custom_in_expr(&mut binary.left, source)?;
+            custom_in_expr(&mut binary.right, source)?;
+        }
+        ExprKind::Unary(unary) => custom_in_expr(&mut unary.expr, source)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Subquery reading the value of a `custom <relation> "<field>"` call
+fn custom_field_expr(source: &str, call: &Expr) -> Result<Expr> {
+    let ExprKind::FuncCall(call) = &call.kind else {
+        bail!("`custom` expects a relation and a field name");
+    };
+    let (relation, field) = match call.args.as_slice() {
+        [relation, field] => (relation, field),
+        _ => bail!("`custom` expects a relation and a field name, e.g. `custom this \"energy\"`"),
+    };
+    let relation = match &relation.kind {
+        ExprKind::Ident(ident) if ident.path.is_empty() => ident.name.clone(),
+        _ => bail!("`custom` expects a relation such as `this` as first argument"),
+    };
+    let field = match &field.kind {
+        ExprKind::Literal(Literal::String(field)) => field.clone(),
+        _ => bail!("`custom` expects a field name string as second argument"),
+    };
+    for name in [source, relation.as_str(), field.as_str()] {
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            bail!("`custom` can't be used with `{}`", name);
+        }
+    }
+
+    let source_text = format!(
+        r#"filter s"(SELECT json_extract(value, '$') FROM {table} WHERE entity_name = '{source}' AND entity_id = {{{relation}.id}} AND field = '{field}')""#,
+        table = CUSTOM_FIELD_VALUES_TABLE,
+    );
+    let steps = parse_steps(&source_text)?;
+    match steps.into_iter().next().map(|step| step.kind) {
+        Some(ExprKind::FuncCall(mut filter)) if filter.args.len() == 1 => Ok(filter.args.remove(0)),
+        _ => bail!("Failed to build custom field accessor"),
+    }
+}
+
+/// Pipeline step keeping the page of a tree after a cursor
+pub const LIMIT_AFTER_FUNCTION: &str = "limit_after";
+
+/// Column added by `limit_after`: the cursor level of a row, 0 for the deepest
+pub const PAGE_LEVEL_COLUMN: &str = "_page_level";
+
+/// Make `sortkey:@cursor` arguments refer to the `cursor` query parameter
+///
+/// PRQL reads `@` as the start of a date, so they are turned into `sortkey:$cursor`
+/// before the query is parsed.
+/// flutter_rust_bridge:ignore
+pub fn cursor_params(source: &str) -> String {
+    let pattern = regex::Regex::new(r"(\bsortkey\s*:\s*)@([A-Za-z_][A-Za-z0-9_]*)")
+        .expect("valid cursor parameter pattern");
+    pattern.replace_all(source, "${1}$$${2}").into_owned()
+}
+
+/// Rewrite `limit_after [<n>] sortkey:<cursor>` steps into keyset filters on the tree
+///
+/// The cursor is a `TreeCursor` token, given literally or as a query parameter
+/// looked up in `params`; a missing or null parameter starts at the first root
+/// row. Not applied if the query defines `limit_after` itself.
+/// flutter_rust_bridge:ignore
+pub fn apply_limit_after(module: &mut ModuleDef, params: &HashMap<String, Value>) -> Result<()> {
+    if defined_names(module).contains(LIMIT_AFTER_FUNCTION) {
+        return Ok(());
+    }
+    for stmt in &mut module.stmts {
+        if let StmtKind::VarDef(var_def) = &mut stmt.kind {
+            if let Some(value) = &mut var_def.value {
+                limit_after_in_expr(value, params)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn limit_after_in_expr(expr: &mut Expr, params: &HashMap<String, Value>) -> Result<()> {
+    match &mut expr.kind {
+        ExprKind::Pipeline(pipeline) => {
+            let mut exprs = Vec::with_capacity(pipeline.exprs.len());
+            for mut step in std::mem::take(&mut pipeline.exprs) {
+                if is_call(&step, LIMIT_AFTER_FUNCTION) {
+                    exprs.extend(limit_after_steps(&step, params)?);
+                    continue;
+                }
+                if let ExprKind::FuncCall(call) = &mut step.kind {
+                    // Nested pipelines, e.g. `append (from other | limit_after ...)`
+                    for arg in &mut call.args {
+                        limit_after_in_expr(arg, params)?;
+                    }
+                }
+                exprs.push(step);
+            }
+            pipeline.exprs = exprs;
+        }
+        ExprKind::FuncCall(call) => {
+            for arg in &mut call.args {
+                limit_after_in_expr(arg, params)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// `filter`, `derive`, `sort` and `take` steps for a `limit_after` step
+fn limit_after_steps(step: &Expr, params: &HashMap<String, Value>) -> Result<Vec<Expr>> {
+    const USAGE: &str = "`limit_after` expects an optional number of rows and a cursor, e.g. `limit_after 100 sortkey:@cursor`";
+    let ExprKind::FuncCall(call) = &step.kind else {
+        bail!(USAGE);
+    };
+    let count = match call.args.as_slice() {
+        [] => None,
+        [count] => match &count.kind {
+            ExprKind::Literal(Literal::Integer(count)) if *count > 0 => Some(*count),
+            _ => bail!(USAGE),
+        },
+        _ => bail!(USAGE),
+    };
+    let token = match call.named_args.get("sortkey").map(|cursor| &cursor.kind) {
+        Some(ExprKind::Param(name)) => match params.get(name.as_str()) {
+            Some(Value::String(token)) => Some(token.clone()),
+            None | Some(Value::Null) => None,
+            Some(other) => bail!(
+                "Cursor parameter `{}` must be a string, got {:?}",
+                name,
+                other
+            ),
+        },
+        Some(ExprKind::Literal(Literal::String(token))) => Some(token.clone()),
+        _ => bail!(USAGE),
+    };
+    let cursor = match token {
+        Some(token) => TreeCursor::from_token(&token).context("Invalid tree cursor")?,
+        None => TreeCursor::default(),
+    };
+    if cursor.levels.is_empty() {
+        bail!("Tree cursor has no levels");
+    }
+
+    // Deepest level first; its rows come right after the last row read
+    let parent = format!("`{}`", TREE_PARENT_COLUMN);
+    let sort_key = format!("`{}`", TREE_SORT_COLUMN);
+    let mut conditions = Vec::new();
+    let mut levels = Vec::new();
+    for (index, level) in cursor.levels.iter().rev().enumerate() {
+        let parent_condition = match &level.parent_id {
+            Some(parent_id) => format!("{} == {}", parent, prql_string(parent_id)),
+            None => format!("{} == null", parent),
+        };
+        conditions.push(format!(
+            "({} && {} > {})",
+            parent_condition,
+            sort_key,
+            prql_string(&level.sort_key)
+        ));
+        levels.push(format!("{} => {}", parent_condition, index));
+    }
+
+    let mut source_text = format!(
+        "filter ({})\nderive {{{} = case [{}]}}\nsort {{{}, {}}}\n",
+        conditions.join(" || "),
+        PAGE_LEVEL_COLUMN,
+        levels.join(", "),
+        PAGE_LEVEL_COLUMN,
+        sort_key,
+    );
+    if let Some(count) = count {
+        source_text.push_str(&format!("take {}\n", count));
+    }
+    parse_steps(&source_text)
+}
+
+/// A PRQL string literal of `value`
+fn prql_string(value: &str) -> String {
+    // JSON escapes are valid PRQL escapes
+    serde_json::to_string(value).expect("strings serialize to JSON")
+}
+
+/// Pipeline steps of PRQL source text, parsed so we don't build PL nodes by hand
+fn parse_steps(steps: &str) -> Result<Vec<Expr>> {
+    let module = prqlc::prql_to_pl(&format!("from t\n{}", steps))?;
+    for stmt in module.stmts {
+        if let StmtKind::VarDef(var_def) = stmt.kind {
+            if let Some(value) = var_def.value {
+                if let ExprKind::Pipeline(mut pipeline) = value.kind {
+                    pipeline.exprs.remove(0);
+                    return Ok(pipeline.exprs);
+                }
+            }
+        }
+    }
+    bail!("Failed to parse pipeline steps")
+}
+
+/// Table read by a `from <table>` step
+fn from_table(expr: &Expr) -> Option<String> {
+    let ExprKind::FuncCall(call) = &expr.kind else {
+        return None;
+    };
+    if !matches!(&call.name.kind, ExprKind::Ident(ident) if ident.name == "from") {
+        return None;
+    }
+    match &call.args.first()?.kind {
+        ExprKind::Ident(table) => Some(table.name.clone()),
+        _ => None,
+    }
+}
+
+fn is_call(expr: &Expr, function: &str) -> bool {
+    matches!(&expr.kind, ExprKind::FuncCall(call)
+        if matches!(&call.name.kind, ExprKind::Ident(ident) if ident.name == function))
+}
+
+/// Names of the functions and variables a query defines
+fn defined_names(module: &ModuleDef) -> HashSet<String> {
+    module
+        .stmts
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::VarDef(var_def) => Some(var_def.name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_query_render;
+
+    fn sql(query: &str) -> String {
+        let source = format!(
+            "from org_headlines\n{}\nrender (list item_template:(text content:this.title))",
+            query
+        );
+        parse_query_render(&source).unwrap().0
+    }
+
+    #[test]
+    fn test_scheduled_between() {
+        let sql = sql(r#"scheduled_between "2024-01-15" "2024-01-21""#);
+        assert!(sql.contains("substr(ltrim(scheduled, '<['), 1, 10)"));
+        assert!(sql.contains("'2024-01-15'") && sql.contains("'2024-01-21'"));
+    }
+
+    #[test]
+    fn test_deadline_within() {
+        let sql = sql("deadline_within 3");
+        assert!(sql.contains("substr(ltrim(deadline, '<['), 1, 10)"));
+        assert!(sql.contains("date('now', '+' || 3 || ' days')"));
+    }
+
+    #[test]
+    fn test_agenda_week() {
+        let sql = sql("agenda_week");
+        assert!(sql.contains("substr(ltrim(scheduled, '<['), 1, 10)"));
+        assert!(sql.contains("substr(ltrim(deadline, '<['), 1, 10)"));
+        assert!(sql.contains("date('now', '+6 days')"));
+    }
+
+    #[test]
+    fn test_query_definition_overrides_builtin() {
+        let source = r#"
+let deadline_within = func days rel -> (rel | filter priority > days)
+from org_headlines
+deadline_within 2
+render (list item_template:(text content:this.title))
+"#;
+        let (sql, _) = parse_query_render(source).unwrap();
+        assert!(sql.contains("priority > 2"));
+        assert!(!sql.contains("deadline"));
+    }
+
+    #[test]
+    fn test_resolve_joins_identity_mapping() {
+        let source = r#"
+from todoist_tasks
+resolve logseq_blocks id
+join logseq_blocks (this.logseq_blocks_id == that.id)
+render (list item_template:(text content:this.content))
+"#;
+        let (sql, _) = parse_query_render(source).unwrap();
+        assert!(sql.contains("entity_identities"));
+        assert!(sql.contains("LEFT JOIN"));
+        assert!(sql.contains("'todoist_tasks'"));
+        assert!(sql.contains("'logseq_blocks'"));
+        assert!(sql.contains("logseq_blocks_id"));
+    }
+
+    #[test]
+    fn test_recently_viewed_joins_access_table() {
+        let source = r#"
+from todoist_tasks
+recently_viewed 20
+render (list item_template:(text content:this.content))
+"#;
+        let (sql, _) = parse_query_render(source).unwrap();
+        assert!(sql.contains("entity_access"));
+        assert!(sql.contains("'todoist_tasks'"));
+        assert!(sql.contains("last_viewed_at DESC"));
+        assert!(sql.contains("20"));
+
+        let modified = source.replace("recently_viewed", "recently_modified");
+        let (sql, _) = parse_query_render(&modified).unwrap();
+        assert!(sql.contains("last_modified_at DESC"));
+        assert!(!sql.contains("last_viewed_at"));
+
+        let invalid = source.replace("20", "\"20\"");
+        assert!(parse_query_render(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_custom_reads_field_values() {
+        let source = r#"
+from todoist_tasks
+filter (custom this "energy") >= 3
+derive {context = (custom this "context")}
+render (list item_template:(text content:this.context))
+"#;
+        let (sql, _) = parse_query_render(source).unwrap();
+        assert!(sql.contains("custom_field_values"));
+        assert!(sql.contains("json_extract(value, '$')"));
+        assert!(sql.contains("entity_name = 'todoist_tasks'"));
+        assert!(sql.contains("field = 'energy'") && sql.contains("field = 'context'"));
+        assert!(sql.contains(">= 3"));
+
+        let invalid = source.replace("\"energy\"", "\"energy' OR 1=1 --\"");
+        assert!(parse_query_render(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_limit_after_pages_by_cursor() {
+        use holon_api::{TreeCursor, Value};
+        use std::collections::{HashMap, HashSet};
+
+        let source = r#"
+from blocks
+limit_after 50 sortkey:@cursor
+render (tree parent_id:parent_id sortkey:sort_key item_template:(text content))
+"#;
+        // Without cursor the page starts at the first root row
+        let (sql, _) = parse_query_render(source).unwrap();
+        assert!(sql.contains("parent_id IS NULL"));
+        assert!(sql.contains("_page_level"));
+        assert!(sql.contains("LIMIT 50"));
+
+        let mut cursor = TreeCursor::children_of(Some("doc"));
+        cursor.advance(Some("doc"), "a1");
+        let cursor = cursor.descend("it's");
+        let params = HashMap::from([("cursor".to_string(), Value::String(cursor.to_token()))]);
+        let parsed = crate::parse_query_render_to_rq_with_params(
+            source,
+            &HashSet::new(),
+            None,
+            &HashMap::new(),
+            &params,
+        )
+        .unwrap();
+        let sql = parsed.to_sql().unwrap();
+        assert!(sql.contains("'doc'") && sql.contains("'a1'"));
+        assert!(sql.contains("'it''s'"));
+        assert!(!sql.contains("$cursor"));
+        assert!(sql.contains("CASE"));
+
+        let invalid = HashMap::from([("cursor".to_string(), Value::String("x".to_string()))]);
+        assert!(crate::parse_query_render_to_rq_with_params(
+            source,
+            &HashSet::new(),
+            None,
+            &HashMap::new(),
+            &invalid,
+        )
+        .is_err());
+        assert!(parse_query_render(&source.replace("50", "\"50\"")).is_err());
+    }
+
+    #[test]
+    fn test_resolve_requires_entity_and_column() {
+        let source = "from todoist_tasks\nresolve logseq_blocks\nrender (list item_template:(text content:this.content))";
+        assert!(parse_query_render(source).is_err());
+    }
+}
@@ -1,33 +1,57 @@
 pub mod compiler;
+pub mod completion;
+pub mod diagnostics;
+pub mod functions;
 pub mod lineage;
 pub mod parser;
 pub mod types;
+pub mod usage;
+pub mod widgets;
 
 pub use compiler::compile_render_spec;
+pub use completion::{Completion, CompletionKind, Completions, QueryCompleter};
+pub use diagnostics::{Diagnostic, DiagnosticKind, QueryLinter, Severity, Span};
 pub use lineage::{LineagePreprocessor, WidgetOperationMapping};
-pub use parser::QueryRenderSplit;
+pub use parser::{QueryRenderSplit, INCLUDE_ARCHIVED, INCLUDE_DELETED};
 // Re-export prqlc types needed for RQ transformation
 pub use prqlc::ir::rq::RelationalQuery;
 // Re-export Number from types module (which re-exports from holon-api)
 pub use types::Number;
+pub use usage::{query_usages, QueryUsage, UsageKind};
+pub use widgets::WidgetRegistry;
 // Re-export render types from types module (which re-exports from holon-api)
 pub use types::{
-    Arg, BinaryOperator, OperationDescriptor, OperationParam, OperationWiring, PreconditionChecker,
-    RenderExpr, RenderSpec, RowTemplate, TypeHint,
+    Arg, BinaryOperator, BoardSpec, CalendarPeriod, CalendarSpec, DateStyle, FilterKind,
+    FilterSpec, FilterValue, Format, GroupSpec, OperationDescriptor, OperationParam,
+    OperationWiring, PreconditionChecker, RenderExpr, RenderLocale, RenderSpec, RowTemplate,
+    SelectionSpec, SortKey, Style, StyleRule, TypeHint, ViewState, WidgetArgType, WidgetParam,
+    WidgetSpec, STYLE_ARG,
 };
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
 
 /// Main entry point: Parse PRQL with render(), split into SQL query + UI instructions
+///
+/// The SQL orders rows as declared by `sort_by:`/`group_by:` (see `RenderSpec::row_order`).
 pub fn parse_query_render(prql_source: &str) -> Result<(String, RenderSpec)> {
-    let split = parser::split_prql_at_render(prql_source)?;
-
-    let rq = prqlc::pl_to_rq(split.query_module)?;
-    let sql = prqlc::rq_to_sql(rq, &prqlc::Options::default())?;
+    let mut split = parser::split_prql_at_render(prql_source)?;
+    parser::apply_soft_delete_filter(&mut split.query_module, &HashSet::new())?;
+    parser::apply_archive_filter(&mut split.query_module, &HashSet::new())?;
+    functions::apply_limit_after(&mut split.query_module, &HashMap::new())?;
 
     let render_json = parser::prql_ast_to_json(&split.render_ast)?;
 
     let render_spec = compiler::compile_render_spec(&render_json)?;
+    parser::apply_filters(
+        &mut split.query_module,
+        &render_spec.filters,
+        &HashMap::new(),
+    )?;
+    parser::apply_sort(&mut split.query_module, &render_spec.row_order())?;
+
+    let rq = prqlc::pl_to_rq(split.query_module)?;
+    let sql = prqlc::rq_to_sql(rq, &prqlc::Options::default())?;
 
     Ok((sql, render_spec))
 }
@@ -82,6 +106,9 @@ pub fn parse_query_render_with_operations(
 /// This allows callers to apply transformations (e.g., adding `_change_origin` column)
 /// to the RQ AST before generating SQL.
 ///
+/// The query is watched through a materialized view, which can't sort, so rows are not
+/// ordered by `sort_by:`/`group_by:` here; frontends order them by `RenderSpec::row_order`.
+///
 /// # Example
 /// ```ignore
 /// let parsed = parse_query_render_to_rq(prql)?;
@@ -89,10 +116,107 @@ pub fn parse_query_render_with_operations(
 /// let sql = ParsedQueryRender::to_sql_from_rq(&transformed_rq)?;
 /// ```
 pub fn parse_query_render_to_rq(prql_source: &str) -> Result<ParsedQueryRender> {
+    parse_query_render_to_rq_with_soft_delete(prql_source, &HashSet::new())
+}
+
+/// Parse PRQL to RQ AST, excluding soft-deleted and archived rows of `soft_delete_tables`.
+///
+/// Every read of one of these tables gets a `filter deleted_at == null`, unless its
+/// pipeline contains an `include_deleted` step, and a `filter archived_at == null`,
+/// unless it contains an `include_archived` step:
+///
+/// ```ignore
+/// from todoist_tasks | include_deleted | render (list item_template:(text content))
+/// ```
+pub fn parse_query_render_to_rq_with_soft_delete(
+    prql_source: &str,
+    soft_delete_tables: &HashSet<String>,
+) -> Result<ParsedQueryRender> {
+    parse_query_render_to_rq_with_widgets(prql_source, soft_delete_tables, None)
+}
+
+/// Parse PRQL to RQ AST, also checking all widget calls against `widgets` if given.
+///
+/// Queries using a widget that isn't registered, or passing a widget arguments it
+/// doesn't accept, fail here instead of when the frontend renders them. A row
+/// template failing the check becomes a `RenderExpr::Error` instead, so the rows of
+/// the other templates still render.
+pub fn parse_query_render_to_rq_with_widgets(
+    prql_source: &str,
+    soft_delete_tables: &HashSet<String>,
+    widgets: Option<&WidgetRegistry>,
+) -> Result<ParsedQueryRender> {
+    parse_query_render_to_rq_with_filters(prql_source, soft_delete_tables, widgets, &HashMap::new())
+}
+
+/// Parse PRQL to RQ AST, keeping only the rows that pass the query's filters.
+///
+/// Filters are declared by `toggle_filter`/`date_filter` widgets (see `FilterSpec`).
+/// `filter_values` holds the values their widgets set, by filter name; filters
+/// without one use their `default:`, and a `None` value turns a filter off.
+pub fn parse_query_render_to_rq_with_filters(
+    prql_source: &str,
+    soft_delete_tables: &HashSet<String>,
+    widgets: Option<&WidgetRegistry>,
+    filter_values: &HashMap<String, Option<FilterValue>>,
+) -> Result<ParsedQueryRender> {
+    parse_query_render_to_rq_with_params(
+        prql_source,
+        soft_delete_tables,
+        widgets,
+        filter_values,
+        &HashMap::new(),
+    )
+}
+
+/// Parse PRQL to RQ AST, reading the cursors of `limit_after` steps from `params`
+///
+/// `limit_after 100 sortkey:@cursor` keeps the page of a tree after the
+/// `holon_api::TreeCursor` token in the `cursor` parameter (see `functions`).
+/// The other parameters are bound when the SQL is executed.
+pub fn parse_query_render_to_rq_with_params(
+    prql_source: &str,
+    soft_delete_tables: &HashSet<String>,
+    widgets: Option<&WidgetRegistry>,
+    filter_values: &HashMap<String, Option<FilterValue>>,
+    params: &HashMap<String, holon_api::Value>,
+) -> Result<ParsedQueryRender> {
+    parse_query_render_to_rq_with_row_filters(
+        prql_source,
+        soft_delete_tables,
+        widgets,
+        filter_values,
+        params,
+        &HashMap::new(),
+    )
+}
+
+/// Parse PRQL to RQ AST, keeping only the rows of each table that pass its row filter
+///
+/// `row_filters` maps table names to PRQL filter expressions (row-level security,
+/// e.g. `assignee == "alice"`). Every read of these tables is filtered, including
+/// joined and appended ones, and no pipeline step opts out.
+pub fn parse_query_render_to_rq_with_row_filters(
+    prql_source: &str,
+    soft_delete_tables: &HashSet<String>,
+    widgets: Option<&WidgetRegistry>,
+    filter_values: &HashMap<String, Option<FilterValue>>,
+    params: &HashMap<String, holon_api::Value>,
+    row_filters: &HashMap<String, String>,
+) -> Result<ParsedQueryRender> {
     // Step 1: Split query and render (removes final render() call from pipeline)
     let split = parser::split_prql_at_render(prql_source)?;
     let mut query_module = split.query_module;
 
+    // Step 1.5: Hide soft-deleted and archived rows (and strip `include_deleted`/`include_archived` steps)
+    parser::apply_soft_delete_filter(&mut query_module, soft_delete_tables)?;
+    parser::apply_archive_filter(&mut query_module, soft_delete_tables)?;
+    // Rows other users may not see
+    parser::apply_row_filters(&mut query_module, row_filters)?;
+
+    // Step 1.6: Keep the page of the tree after the cursors of `limit_after` steps
+    functions::apply_limit_after(&mut query_module, params)?;
+
     // Step 2: Extract row templates from derive { ui = (render ...) } patterns
     // This modifies query_module in place, replacing render() calls with integer literals
     let extracted_templates = parser::extract_row_templates_from_module(&mut query_module)?;
@@ -100,19 +224,27 @@ pub fn parse_query_render_to_rq(prql_source: &str) -> Result<ParsedQueryRender>
     // Step 3: Extract table name from the main query (for single-table queries)
     let table_name = extract_table_name(&query_module)?;
 
+    let render_json = parser::prql_ast_to_json(&split.render_ast)?;
+    let mut render_spec = compiler::compile_render_spec_with_widgets(&render_json, widgets)?;
+
+    // Step 3.5: Keep the rows passing the filters of the render tree's filter widgets
+    parser::apply_filters(&mut query_module, &render_spec.filters, filter_values)?;
+
     // Step 4: Convert PL to RQ
     let rq = prqlc::pl_to_rq(query_module)?;
 
     // Step 4.5: Extract available columns from RQ (for operation filtering)
     let available_columns = extract_columns_from_rq(&rq);
 
-    let render_json = parser::prql_ast_to_json(&split.render_ast)?;
-    let mut render_spec = compiler::compile_render_spec(&render_json)?;
-
     // Step 5: Compile extracted row templates and populate row_templates in RenderSpec
+    // A template that fails to compile is replaced by an error placeholder, so the
+    // rows of the other templates still render
     for template in extracted_templates {
-        let template_json = parser::prql_ast_to_json(&template.render_expr)?;
-        let template_expr = compiler::compile_render_expr_from_json(&template_json)?;
+        let template_expr = compile_row_template(&template, widgets)
+            .with_context(|| format!("Row template of {}", template.entity_name))
+            .unwrap_or_else(|e| RenderExpr::Error {
+                message: format!("{:#}", e),
+            });
 
         render_spec.row_templates.push(RowTemplate {
             index: template.index,
@@ -136,6 +268,19 @@ pub fn parse_query_render_to_rq(prql_source: &str) -> Result<ParsedQueryRender>
     })
 }
 
+/// Compile the render expression of a row template, checking it against `widgets`
+fn compile_row_template(
+    template: &parser::ExtractedRowTemplate,
+    widgets: Option<&WidgetRegistry>,
+) -> Result<RenderExpr> {
+    let template_json = parser::prql_ast_to_json(&template.render_expr)?;
+    let template_expr = compiler::compile_render_expr_from_json(&template_json)?;
+    if let Some(widgets) = widgets {
+        widgets.validate(&template_expr)?;
+    }
+    Ok(template_expr)
+}
+
 /// Extract the table name from the main query pipeline
 fn extract_table_name(module: &prqlc::pr::ModuleDef) -> Result<String> {
     use prqlc::pr::*;
@@ -253,7 +398,9 @@ fn annotate_tree_with_operations(expr: &mut RenderExpr, table_name: &str) {
                                 affected_fields: vec![field_name.to_string()], // set_field affects the specified field
                                 param_mappings: vec![], // set_field doesn't use param mappings
                                 precondition: None,
+                                simulation: None,
                             },
+                            accepts_multiple: false, // Placeholder, rewired by OperationProvider
                         });
                     }
                 }
@@ -381,6 +528,292 @@ render (block indent:depth content:(text title))
         }
     }
 
+    #[test]
+    fn test_selectable_list() {
+        let prql = r#"
+from todoist_tasks
+render (list selectable:true select_all:false item_template:(text content))
+        "#;
+
+        let (_sql, spec) = parse_query_render(prql).unwrap();
+        let selection = spec.selection.expect("list should be selectable");
+        assert_eq!(selection.id_column, "id");
+        assert!(selection.checkbox_column);
+        assert!(!selection.select_all);
+
+        let prql = r#"
+from todoist_tasks
+render (list item_template:(text content))
+        "#;
+        let (_sql, spec) = parse_query_render(prql).unwrap();
+        assert!(spec.selection.is_none());
+    }
+
+    #[test]
+    fn test_style_argument() {
+        let prql = r#"
+from todoist_tasks
+render (text content style:[(style color:"red" bold:true), (style dim:true when:this.completed)])
+        "#;
+
+        let (_sql, spec) = parse_query_render(prql).unwrap();
+        let RenderExpr::FunctionCall { args, .. } = &spec.root else {
+            panic!("Expected function call");
+        };
+        let style = args.iter().find(|a| a.name.as_deref() == Some(STYLE_ARG));
+        let Some(RenderExpr::Style { rules }) = style.map(|a| &a.value) else {
+            panic!("Expected style argument");
+        };
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].style.color.as_deref(), Some("red"));
+        assert_eq!(rules[0].style.bold, Some(true));
+        assert!(matches!(
+            &rules[1].when,
+            Some(RenderExpr::ColumnRef { name }) if name == "completed"
+        ));
+
+        let completed = spec.root.resolve_style(|_| true).expect("text has a style");
+        assert_eq!(completed.dim, Some(true));
+        assert_eq!(spec.root.resolve_style(|_| false).unwrap().dim, None);
+    }
+
+    #[test]
+    fn test_invalid_style_argument() {
+        let prql = r#"
+from todoist_tasks
+render (text content style:(style color:"reddish"))
+        "#;
+        let error = format!("{:#}", parse_query_render(prql).unwrap_err());
+        assert!(error.contains("Invalid style of text"), "{}", error);
+        assert!(error.contains("must be #rrggbb or one of"), "{}", error);
+
+        let prql = r#"
+from todoist_tasks
+render (text content style:(style blink:true))
+        "#;
+        let error = format!("{:#}", parse_query_render(prql).unwrap_err());
+        assert!(
+            error.contains("unknown style attribute `blink`"),
+            "{}",
+            error
+        );
+
+        // Widgets with a style argument of their own keep it
+        let prql = r#"
+from todoist_tasks
+render (progress value:this.priority style:"bar")
+        "#;
+        parse_query_render(prql).unwrap();
+    }
+
+    #[test]
+    fn test_sort_and_group_by() {
+        let prql = r#"
+from todoist_tasks
+render (list sort_by:[priority, -created_at] group_by:project_id group_label:project_name item_template:(text content))
+        "#;
+        let (sql, spec) = parse_query_render(prql).unwrap();
+        assert_eq!(
+            spec.sort,
+            vec![
+                SortKey {
+                    column: "priority".to_string(),
+                    descending: false,
+                },
+                SortKey {
+                    column: "created_at".to_string(),
+                    descending: true,
+                },
+            ]
+        );
+        assert_eq!(
+            spec.group_by,
+            Some(GroupSpec {
+                column: "project_id".to_string(),
+                descending: false,
+                label_column: Some("project_name".to_string()),
+            })
+        );
+        assert!(
+            sql.contains("ORDER BY\n  project_id,\n  priority,\n  created_at DESC"),
+            "{}",
+            sql
+        );
+
+        // Watched queries leave ordering to the frontend
+        let parsed = parse_query_render_to_rq(prql).unwrap();
+        assert!(!parsed.to_sql().unwrap().contains("ORDER BY"));
+        assert_eq!(parsed.render_spec.row_order().len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_sort_by() {
+        let prql = r#"
+from todoist_tasks
+render (list sort_by:["priority"] item_template:(text content))
+        "#;
+        let error = format!("{:#}", parse_query_render(prql).unwrap_err());
+        assert!(error.contains("sort_by must list columns"), "{}", error);
+    }
+
+    #[test]
+    fn test_view_id() {
+        let prql = r#"
+from blocks
+render (tree view_id:"outline" parent_id:parent_id sortkey:sort_key item_template:(text content))
+        "#;
+        let (_, spec) = parse_query_render(prql).unwrap();
+        assert_eq!(spec.view_id.as_deref(), Some("outline"));
+        assert_eq!(spec.view_state, ViewState::default());
+
+        let prql = "from blocks\nrender (tree view_id:parent_id item_template:(text content))";
+        let error = format!("{:#}", parse_query_render(prql).unwrap_err());
+        assert!(
+            error.contains("view_id must be a non-empty string"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_timezone() {
+        let prql = r#"
+from tasks
+render (list timezone:"Europe/Berlin" item_template:(text due_date))
+        "#;
+        let (_, spec) = parse_query_render(prql).unwrap();
+        assert_eq!(spec.timezone.as_deref(), Some("Europe/Berlin"));
+
+        let prql = "from tasks\nrender (list timezone:1 item_template:(text due_date))";
+        let error = format!("{:#}", parse_query_render(prql).unwrap_err());
+        assert!(
+            error.contains("timezone must be a time zone name"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_presence() {
+        let prql = r#"
+from todoist_tasks
+render (list view_id:"inbox" presence:this.id item_template:(text content))
+        "#;
+        let (_, spec) = parse_query_render(prql).unwrap();
+        assert_eq!(
+            spec.presence,
+            Some(holon_api::PresenceSpec {
+                column: "id".to_string(),
+            })
+        );
+
+        let prql = "from todoist_tasks\nrender (list presence:true item_template:(text content))";
+        let error = format!("{:#}", parse_query_render(prql).unwrap_err());
+        assert!(error.contains("presence must be a column"), "{}", error);
+    }
+
+    #[test]
+    fn test_board() {
+        let prql = r#"
+from todoist_tasks
+render (board group_by:status lanes:["todo", "doing", "done"] item_template:(text content))
+        "#;
+        let (_, spec) = parse_query_render(prql).unwrap();
+        let board = spec.board.unwrap();
+        assert_eq!(board.column, "status");
+        assert_eq!(
+            board.lanes,
+            ["todo", "doing", "done"]
+                .map(|lane| holon_api::Value::String(lane.to_string()))
+                .to_vec()
+        );
+
+        let prql = "from todoist_tasks\nrender (board item_template:(text content))";
+        let error = format!("{:#}", parse_query_render(prql).unwrap_err());
+        assert!(error.contains("board requires a lane column"), "{}", error);
+
+        let prql = "from todoist_tasks\nrender (board group_by:status lanes:[status] item_template:(text content))";
+        let error = format!("{:#}", parse_query_render(prql).unwrap_err());
+        assert!(error.contains("lanes must list values"), "{}", error);
+    }
+
+    #[test]
+    fn test_calendar() {
+        let prql = r#"
+from todoist_tasks
+render (calendar date:this.due_date period:"week" item_template:(text content))
+        "#;
+        let (_, spec) = parse_query_render(prql).unwrap();
+        assert_eq!(
+            spec.calendar,
+            Some(CalendarSpec {
+                column: "due_date".to_string(),
+                period: CalendarPeriod::Week,
+            })
+        );
+
+        let prql = "from todoist_tasks\nrender (calendar item_template:(text content))";
+        let error = format!("{:#}", parse_query_render(prql).unwrap_err());
+        assert!(
+            error.contains("calendar requires a date column"),
+            "{}",
+            error
+        );
+
+        let prql = "from todoist_tasks\nrender (calendar date:this.due_date period:\"year\" item_template:(text content))";
+        let error = format!("{:#}", parse_query_render(prql).unwrap_err());
+        assert!(error.contains("period must be"), "{}", error);
+    }
+
+    #[test]
+    fn test_filters() {
+        let prql = r#"
+from todoist_tasks
+select {id, content, completed, due_date}
+render (row (toggle_filter column:completed default:false) (date_filter name:"due" column:due_date) (list item_template:(text content)))
+        "#;
+        let parsed = parse_query_render_to_rq(prql).unwrap();
+        assert_eq!(parsed.render_spec.filters.len(), 2);
+        assert_eq!(parsed.render_spec.filters[1].name, "due");
+        assert_eq!(parsed.render_spec.filters[1].kind, FilterKind::DateRange);
+        // The default hides completed tasks
+        let sql = parsed.to_sql().unwrap();
+        assert!(sql.contains("false"), "{}", sql);
+
+        let values = HashMap::from([
+            ("completed".to_string(), None),
+            (
+                "due".to_string(),
+                Some(FilterValue::DateRange {
+                    from: Some("2024-01-01".to_string()),
+                    to: Some("2024-01-31".to_string()),
+                }),
+            ),
+        ]);
+        let sql = parse_query_render_to_rq_with_filters(prql, &HashSet::new(), None, &values)
+            .and_then(|parsed| parsed.to_sql())
+            .unwrap();
+        assert!(!sql.contains("false"), "{}", sql);
+        assert!(sql.contains("2024-01-01"), "{}", sql);
+        assert!(sql.contains("2024-02-01"), "{}", sql);
+
+        let invalid = HashMap::from([(
+            "due".to_string(),
+            Some(FilterValue::DateRange {
+                from: Some("2024-01-01\" || true || \"".to_string()),
+                to: None,
+            }),
+        )]);
+        assert!(
+            parse_query_render_to_rq_with_filters(prql, &HashSet::new(), None, &invalid).is_err()
+        );
+        let mismatched = HashMap::from([("due".to_string(), Some(FilterValue::Boolean(true)))]);
+        assert!(
+            parse_query_render_to_rq_with_filters(prql, &HashSet::new(), None, &mismatched)
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_helper_function_expansion() {
         let prql = r#"
@@ -636,6 +1069,48 @@ render (tree parent_id:parent_id sortkey:sort_key item_template:this.ui)
             _ => panic!("Expected tree function call as root"),
         }
     }
+
+    #[test]
+    fn test_failed_row_template_becomes_error_placeholder() {
+        use holon_api::{WidgetArgType, WidgetParam, WidgetSpec};
+
+        let prql = r#"
+from todoist_tasks
+derive { ui = (render (row (text this.content))) }
+append (
+  from todoist_projects
+  derive { ui = (render (row (txt this.name))) }
+)
+render (tree parent_id:parent_id sortkey:sort_key item_template:this.ui)
+"#;
+        let widgets = super::WidgetRegistry::new([
+            WidgetSpec::new("tree")
+                .with_param(WidgetParam::required("parent_id", WidgetArgType::Any))
+                .with_param(WidgetParam::required("sortkey", WidgetArgType::Any))
+                .with_param(WidgetParam::required("item_template", WidgetArgType::Any)),
+            WidgetSpec::new("row").with_variadic(WidgetArgType::Widget),
+            WidgetSpec::new("text")
+                .with_param(WidgetParam::required("content", WidgetArgType::String)),
+        ]);
+
+        // The typo in the projects template doesn't fail the query
+        let parsed =
+            super::parse_query_render_to_rq_with_widgets(prql, &HashSet::new(), Some(&widgets))
+                .unwrap();
+        let templates = &parsed.render_spec.row_templates;
+        assert!(matches!(
+            &templates[0].expr,
+            super::RenderExpr::FunctionCall { name, .. } if name == "row"
+        ));
+        match &templates[1].expr {
+            super::RenderExpr::Error { message } => {
+                assert!(message.starts_with("Row template of todoist_projects"));
+                assert!(message.contains("unknown widget `txt`"), "{}", message);
+            }
+            other => panic!("Expected an error placeholder, got {:?}", other),
+        }
+        assert!(parsed.to_sql().unwrap().contains("UNION"));
+    }
 }
 
 #[cfg(test)]
@@ -1294,4 +1769,125 @@ append (
 
         // The Wildcard in RQ should indicate all columns are preserved
     }
+
+    #[test]
+    fn test_soft_deleted_rows_excluded() {
+        let tables = HashSet::from(["tasks".to_string()]);
+        let prql = r#"
+from tasks
+select {id, content}
+render (list item_template:(text content))
+        "#;
+
+        let sql = parse_query_render_to_rq_with_soft_delete(prql, &tables)
+            .and_then(|parsed| parsed.to_sql())
+            .unwrap();
+        assert!(sql.contains("deleted_at IS NULL"), "SQL: {}", sql);
+
+        // Tables without soft delete are left alone
+        let sql = parse_query_render_to_rq(prql)
+            .and_then(|parsed| parsed.to_sql())
+            .unwrap();
+        assert!(!sql.contains("deleted_at"), "SQL: {}", sql);
+    }
+
+    #[test]
+    fn test_include_deleted_keeps_soft_deleted_rows() {
+        let tables = HashSet::from(["tasks".to_string(), "projects".to_string()]);
+        let prql = r#"
+from tasks
+include_deleted
+join side:left projects (==project_id)
+select {tasks.id, tasks.content}
+render (list item_template:(text content))
+        "#;
+
+        let sql = parse_query_render_to_rq_with_soft_delete(prql, &tables)
+            .and_then(|parsed| parsed.to_sql())
+            .unwrap();
+        assert!(!sql.contains("deleted_at"), "SQL: {}", sql);
+        assert!(!sql.contains(INCLUDE_DELETED), "SQL: {}", sql);
+    }
+
+    #[test]
+    fn test_archived_rows_excluded_unless_included() {
+        let tables = HashSet::from(["tasks".to_string()]);
+        let prql = r#"
+from tasks
+select {id, content}
+render (list item_template:(text content))
+        "#;
+
+        let sql = parse_query_render_to_rq_with_soft_delete(prql, &tables)
+            .and_then(|parsed| parsed.to_sql())
+            .unwrap();
+        assert!(sql.contains("archived_at IS NULL"), "SQL: {}", sql);
+        assert!(sql.contains("deleted_at IS NULL"), "SQL: {}", sql);
+
+        // `include_archived` keeps archived rows, but still hides trashed ones
+        let prql = r#"
+from tasks
+include_archived
+select {id, content}
+render (list item_template:(text content))
+        "#;
+        let sql = parse_query_render_to_rq_with_soft_delete(prql, &tables)
+            .and_then(|parsed| parsed.to_sql())
+            .unwrap();
+        assert!(!sql.contains("archived_at"), "SQL: {}", sql);
+        assert!(!sql.contains(INCLUDE_ARCHIVED), "SQL: {}", sql);
+        assert!(sql.contains("deleted_at IS NULL"), "SQL: {}", sql);
+    }
+
+    #[test]
+    fn test_row_filters_apply_to_every_read() {
+        let row_filters =
+            HashMap::from([("tasks".to_string(), r#"assignee == "alice""#.to_string())]);
+        let prql = r#"
+from projects
+select {id, name}
+append (from tasks | select {id, name = content})
+render (list item_template:(text name))
+        "#;
+
+        let sql = parse_query_render_to_rq_with_row_filters(
+            prql,
+            &HashSet::new(),
+            None,
+            &HashMap::new(),
+            &HashMap::new(),
+            &row_filters,
+        )
+        .and_then(|parsed| parsed.to_sql())
+        .unwrap();
+        assert_eq!(sql.matches("assignee = 'alice'").count(), 1, "SQL: {}", sql);
+
+        // Invalid filters fail the query instead of being skipped
+        let row_filters = HashMap::from([("tasks".to_string(), "assignee ==".to_string())]);
+        assert!(parse_query_render_to_rq_with_row_filters(
+            prql,
+            &HashSet::new(),
+            None,
+            &HashMap::new(),
+            &HashMap::new(),
+            &row_filters,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_soft_delete_filter_in_appended_pipelines() {
+        let tables = HashSet::from(["tasks".to_string()]);
+        let prql = r#"
+from projects
+select {id, name}
+append (from tasks | select {id, name = content})
+render (list item_template:(text name))
+        "#;
+
+        let sql = parse_query_render_to_rq_with_soft_delete(prql, &tables)
+            .and_then(|parsed| parsed.to_sql())
+            .unwrap();
+        assert_eq!(sql.matches("deleted_at IS NULL").count(), 1, "SQL: {}", sql);
+    }
 }
@@ -1,7 +1,15 @@
-use anyhow::{bail, Result};
-use holon_api::Value;
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Context, Result};
+use holon_api::{FilterSpec, FilterValue, SortKey, Value, ARCHIVED_AT_COLUMN, DELETED_AT_COLUMN};
 use prqlc::pr::*;
 
+/// Pipeline step that keeps soft-deleted rows, e.g. `from todoist_tasks | include_deleted`
+pub const INCLUDE_DELETED: &str = "include_deleted";
+
+/// Pipeline step that keeps archived rows, e.g. `from todoist_tasks | include_archived`
+pub const INCLUDE_ARCHIVED: &str = "include_archived";
+
 #[derive(Debug, Clone)]
 /// flutter_rust_bridge:ignore
 pub struct QueryRenderSplit {
@@ -25,7 +33,11 @@ pub struct ExtractedRowTemplate {
 /// flutter_rust_bridge:ignore
 pub fn split_prql_at_render(source: &str) -> Result<QueryRenderSplit> {
     // Parse using PRQL's parser
-    let mut module = prqlc::prql_to_pl(source)?;
+    let mut module = prqlc::prql_to_pl(&crate::functions::cursor_params(source))?;
+    crate::functions::add_builtin_functions(&mut module)?;
+    crate::functions::apply_resolve(&mut module)?;
+    crate::functions::apply_recency(&mut module)?;
+    crate::functions::apply_custom_fields(&mut module)?;
 
     // Find and extract the render() call from the last statement
     let mut render_ast = extract_render_from_module(&mut module)?;
@@ -366,6 +378,380 @@ fn extract_row_templates_from_expr(
     }
 }
 
+/// Exclude soft-deleted rows from every pipeline reading a soft-delete table.
+///
+/// Each `from <table>` (and `join <table>`) of a table in `soft_delete_tables` is
+/// followed by `filter deleted_at == null`, except in pipelines containing an
+/// `include_deleted` step. `include_deleted` steps are removed from all pipelines,
+/// so call this even when no table supports soft delete.
+/// flutter_rust_bridge:ignore
+pub fn apply_soft_delete_filter(
+    module: &mut ModuleDef,
+    soft_delete_tables: &HashSet<String>,
+) -> Result<()> {
+    apply_table_filter(
+        module,
+        soft_delete_tables,
+        &format!("{} == null", DELETED_AT_COLUMN),
+        Some(INCLUDE_DELETED),
+    )
+}
+
+/// Exclude archived rows from every pipeline reading a soft-delete table.
+///
+/// Works like `apply_soft_delete_filter` with `filter archived_at == null` and
+/// `include_archived` steps; the tables registered for soft delete also carry the
+/// `archived_at` column.
+/// flutter_rust_bridge:ignore
+pub fn apply_archive_filter(
+    module: &mut ModuleDef,
+    soft_delete_tables: &HashSet<String>,
+) -> Result<()> {
+    apply_table_filter(
+        module,
+        soft_delete_tables,
+        &format!("{} == null", ARCHIVED_AT_COLUMN),
+        Some(INCLUDE_ARCHIVED),
+    )
+}
+
+/// Restrict every pipeline reading a table of `row_filters` to the rows passing its filter.
+///
+/// `row_filters` maps table names to PRQL filter expressions, e.g.
+/// `assignee == "alice"`. Unlike soft delete, pipelines can't opt out of these.
+/// flutter_rust_bridge:ignore
+pub fn apply_row_filters(
+    module: &mut ModuleDef,
+    row_filters: &HashMap<String, String>,
+) -> Result<()> {
+    for (table, filter) in row_filters {
+        apply_table_filter(module, &HashSet::from([table.clone()]), filter, None)
+            .with_context(|| format!("Invalid row filter of {}: {}", table, filter))?;
+    }
+    Ok(())
+}
+
+/// Follow each read of `tables` by `filter <filter>`, except in pipelines containing
+/// `include_step` (if any)
+fn apply_table_filter(
+    module: &mut ModuleDef,
+    tables: &HashSet<String>,
+    filter: &str,
+    include_step: Option<&str>,
+) -> Result<()> {
+    let template = filter_template(filter)?;
+    for stmt in &mut module.stmts {
+        if let StmtKind::VarDef(var_def) = &mut stmt.kind {
+            if let Some(value) = &mut var_def.value {
+                apply_table_filter_to_expr(value, tables, &template, include_step, false);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `from t | filter (<filter>)`, parsed so we don't build PL nodes by hand
+fn filter_template(filter: &str) -> Result<Expr> {
+    let source = format!("from t | filter ({})", filter);
+    let module = prqlc::prql_to_pl(&source)?;
+    for stmt in module.stmts {
+        if let StmtKind::VarDef(var_def) = stmt.kind {
+            if let Some(value) = var_def.value {
+                if matches!(&value.kind, ExprKind::Pipeline(p) if p.exprs.len() == 2) {
+                    return Ok(*value);
+                }
+            }
+        }
+    }
+    bail!("Failed to build filter {}", filter)
+}
+
+/// The `filter <filter>` step of the template
+fn template_filter(template: &Expr) -> Expr {
+    match &template.kind {
+        ExprKind::Pipeline(pipeline) => pipeline.exprs[1].clone(),
+        _ => unreachable!("filter template is a pipeline"),
+    }
+}
+
+/// `(<from> | filter <filter>)` with the template's `from t` replaced
+fn filtered_pipeline(template: &Expr, from: Expr) -> Expr {
+    let mut pipeline = template.clone();
+    if let ExprKind::Pipeline(p) = &mut pipeline.kind {
+        p.exprs[0] = from;
+    }
+    pipeline
+}
+
+fn apply_table_filter_to_expr(
+    expr: &mut Expr,
+    tables: &HashSet<String>,
+    template: &Expr,
+    include_step: Option<&str>,
+    include_hidden: bool,
+) {
+    if is_soft_delete_call(expr, "from", tables) {
+        // A bare `from <table>`, e.g. the argument of `append`
+        if !include_hidden {
+            let alias = expr.alias.take();
+            *expr = filtered_pipeline(template, expr.clone());
+            expr.alias = alias;
+        }
+        return;
+    }
+
+    match &mut expr.kind {
+        ExprKind::Pipeline(pipeline) => {
+            let include_hidden = include_hidden
+                || pipeline
+                    .exprs
+                    .iter()
+                    .any(|e| include_step.is_some_and(|step| is_include_step(e, step)));
+            if let Some(step) = include_step {
+                pipeline.exprs.retain(|e| !is_include_step(e, step));
+            }
+
+            let mut index = 0;
+            while index < pipeline.exprs.len() {
+                let step = &mut pipeline.exprs[index];
+                let reads_table = is_soft_delete_call(step, "from", tables);
+
+                if !include_hidden && is_soft_delete_call(step, "join", tables) {
+                    // `join <table>` becomes `join (from <table> | filter ...)`
+                    if let ExprKind::FuncCall(func_call) = &mut step.kind {
+                        let table = &mut func_call.args[0];
+                        let alias = table.alias.take();
+                        let mut from = template_from(template);
+                        if let ExprKind::FuncCall(from_call) = &mut from.kind {
+                            from_call.args[0] = table.clone();
+                        }
+                        *table = filtered_pipeline(template, from);
+                        table.alias = alias;
+                    }
+                } else if let ExprKind::FuncCall(func_call) = &mut step.kind {
+                    // Nested pipelines, e.g. `append (from other | ...)`
+                    for arg in &mut func_call.args {
+                        apply_table_filter_to_expr(
+                            arg,
+                            tables,
+                            template,
+                            include_step,
+                            include_hidden,
+                        );
+                    }
+                }
+
+                if reads_table && !include_hidden {
+                    pipeline.exprs.insert(index + 1, template_filter(template));
+                    index += 1;
+                }
+                index += 1;
+            }
+        }
+        ExprKind::FuncCall(func_call) => {
+            for arg in &mut func_call.args {
+                apply_table_filter_to_expr(arg, tables, template, include_step, include_hidden);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The `from t` step of the template
+fn template_from(template: &Expr) -> Expr {
+    match &template.kind {
+        ExprKind::Pipeline(pipeline) => pipeline.exprs[0].clone(),
+        _ => unreachable!("filter template is a pipeline"),
+    }
+}
+
+/// Check if `expr` is `<function> <table>` for a table that supports soft delete
+fn is_soft_delete_call(expr: &Expr, function: &str, tables: &HashSet<String>) -> bool {
+    let ExprKind::FuncCall(func_call) = &expr.kind else {
+        return false;
+    };
+    let is_function =
+        matches!(&func_call.name.kind, ExprKind::Ident(ident) if ident.name == function);
+    is_function
+        && matches!(
+            func_call.args.first().map(|arg| &arg.kind),
+            Some(ExprKind::Ident(table)) if tables.contains(&table.name)
+        )
+}
+
+/// Check if a pipeline step is `step`, e.g. `include_deleted`
+fn is_include_step(expr: &Expr, step: &str) -> bool {
+    match &expr.kind {
+        ExprKind::Ident(ident) => ident.name == step,
+        ExprKind::FuncCall(func_call) => {
+            matches!(&func_call.name.kind, ExprKind::Ident(ident) if ident.name == step)
+        }
+        _ => false,
+    }
+}
+
+/// Append a `sort` step ordering the main query's rows by `keys`
+///
+/// Materialized views can't sort, so this is only used for queries that are run
+/// once; watched queries leave ordering to the frontend (see `RenderSpec::row_order`).
+/// flutter_rust_bridge:ignore
+pub fn apply_sort(module: &mut ModuleDef, keys: &[SortKey]) -> Result<()> {
+    if keys.is_empty() {
+        return Ok(());
+    }
+    let columns: Vec<String> = keys
+        .iter()
+        .map(|key| {
+            let column = quoted_column(&key.column);
+            if key.descending {
+                format!("-{}", column)
+            } else {
+                column
+            }
+        })
+        .collect();
+    let step = parse_step(&format!("sort {{{}}}", columns.join(", ")))?;
+    if !push_main_step(module, step) {
+        bail!("No main query to sort");
+    }
+    Ok(())
+}
+
+/// Append a `filter` step keeping the main query's rows that pass `filters`
+///
+/// `values` holds the values widgets set, by filter name; filters without one use
+/// their default, and a `None` value turns the filter off.
+/// flutter_rust_bridge:ignore
+pub fn apply_filters(
+    module: &mut ModuleDef,
+    filters: &[FilterSpec],
+    values: &HashMap<String, Option<FilterValue>>,
+) -> Result<()> {
+    if let Some(name) = values
+        .keys()
+        .find(|name| !filters.iter().any(|f| &f.name == *name))
+    {
+        bail!("Unknown filter '{}'", name);
+    }
+
+    let mut conditions = Vec::new();
+    for filter in filters {
+        let value = match values.get(&filter.name) {
+            Some(value) => value.as_ref(),
+            None => filter.default.as_ref(),
+        };
+        let Some(value) = value else {
+            continue;
+        };
+        if value.kind() != filter.kind {
+            bail!(
+                "Filter '{}' takes {:?} values, got {:?}",
+                filter.name,
+                filter.kind,
+                value.kind()
+            );
+        }
+        conditions.extend(filter_conditions(&filter.column, value)?);
+    }
+    if conditions.is_empty() {
+        return Ok(());
+    }
+
+    let step = parse_step(&format!("filter ({})", conditions.join(" && ")))?;
+    if !push_main_step(module, step) {
+        bail!("No main query to filter");
+    }
+    Ok(())
+}
+
+/// PRQL conditions of a filter value on `column`
+fn filter_conditions(column: &str, value: &FilterValue) -> Result<Vec<String>> {
+    let column = quoted_column(column);
+    Ok(match value {
+        FilterValue::Boolean(b) => vec![format!("{} == {}", column, b)],
+        FilterValue::DateRange { from, to } => {
+            let mut conditions = Vec::new();
+            if let Some(from) = from {
+                conditions.push(format!("{} >= \"{}\"", column, date_bound(from)?));
+            }
+            if let Some(to) = to {
+                // A plain date includes the whole day
+                match chrono::NaiveDate::parse_from_str(to, "%Y-%m-%d") {
+                    Ok(date) => {
+                        let next = date.succ_opt().unwrap_or(date);
+                        conditions.push(format!("{} < \"{}\"", column, next.format("%Y-%m-%d")));
+                    }
+                    Err(_) => conditions.push(format!("{} <= \"{}\"", column, date_bound(to)?)),
+                }
+            }
+            conditions
+        }
+    })
+}
+
+/// A date range bound, checked to be RFC3339 or `YYYY-MM-DD`
+///
+/// RFC3339 bounds are normalized to UTC, the format datetime values are stored in.
+fn date_bound(bound: &str) -> Result<String> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(bound) {
+        return Ok(holon_api::format_datetime(&dt.with_timezone(&chrono::Utc)));
+    }
+    if chrono::NaiveDate::parse_from_str(bound, "%Y-%m-%d").is_err() {
+        bail!("Invalid date '{}', expected RFC3339 or YYYY-MM-DD", bound);
+    }
+    Ok(bound.to_string())
+}
+
+/// A column name quoted for PRQL, e.g. `` `t`.`due date` ``
+fn quoted_column(column: &str) -> String {
+    column
+        .split('.')
+        .map(|part| format!("`{}`", part))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// A single pipeline step parsed from `from t | <step>`, so we don't build PL nodes by hand
+fn parse_step(step: &str) -> Result<Expr> {
+    let module = prqlc::prql_to_pl(&format!("from t | {}", step))?;
+    for stmt in module.stmts {
+        if let StmtKind::VarDef(var_def) = stmt.kind {
+            if let Some(value) = var_def.value {
+                if let ExprKind::Pipeline(mut pipeline) = value.kind {
+                    if pipeline.exprs.len() == 2 {
+                        return Ok(pipeline.exprs.remove(1));
+                    }
+                }
+            }
+        }
+    }
+    bail!("Failed to build pipeline step: {}", step)
+}
+
+/// Append `step` to the main query's pipeline; false if there is no main query
+fn push_main_step(module: &mut ModuleDef, step: Expr) -> bool {
+    for stmt in &mut module.stmts {
+        if let StmtKind::VarDef(var_def) = &mut stmt.kind {
+            if !matches!(var_def.kind, VarDefKind::Main) {
+                continue;
+            }
+            if let Some(value) = &mut var_def.value {
+                match &mut value.kind {
+                    ExprKind::Pipeline(pipeline) => pipeline.exprs.push(step),
+                    _ => {
+                        let query = (**value).clone();
+                        **value = Expr::new(ExprKind::Pipeline(Pipeline {
+                            exprs: vec![query, step],
+                        }));
+                    }
+                }
+                return true;
+            }
+        }
+    }
+    false
+}
+
 /// Convert PRQL PR AST expression to JSON for easier processing
 /// flutter_rust_bridge:ignore
 pub fn prql_ast_to_json(expr: &Expr) -> Result<Value> {
@@ -425,6 +811,14 @@ pub fn prql_ast_to_json(expr: &Expr) -> Result<Value> {
             obj.insert("right".to_string(), prql_ast_to_json(&binary.right)?);
             Ok(Value::Object(obj))
         }
+        ExprKind::Unary(unary) if unary.op == UnOp::Neg => {
+            // `-x` is `0 - x`, which also marks descending columns in `sort_by:[-x]`
+            let mut obj = std::collections::HashMap::new();
+            obj.insert("__op".to_string(), Value::String("Sub".to_string()));
+            obj.insert("left".to_string(), Value::Integer(0));
+            obj.insert("right".to_string(), prql_ast_to_json(&unary.expr)?);
+            Ok(Value::Object(obj))
+        }
         _ => bail!("Unsupported expression type for render: {:?}", expr.kind),
     }
 }
@@ -3,6 +3,9 @@ pub use holon_api::Number;
 
 // Re-export render types from holon-api
 pub use holon_api::{
-    Arg, BinaryOperator, OperationDescriptor, OperationParam, OperationWiring, PreconditionChecker,
-    RenderExpr, RenderSpec, RowTemplate, TypeHint,
+    Arg, BinaryOperator, BoardSpec, CalendarPeriod, CalendarSpec, DateStyle, FilterKind,
+    FilterSpec, FilterValue, Format, GroupSpec, OperationDescriptor, OperationParam,
+    OperationWiring, PreconditionChecker, PresenceSpec, RenderExpr, RenderLocale, RenderSpec,
+    RowTemplate, SelectionSpec, SortKey, Style, StyleRule, TypeHint, ViewState, WidgetArgType,
+    WidgetParam, WidgetSpec, STYLE_ARG,
 };
@@ -0,0 +1,237 @@
+//! Tables and columns a query reads
+//!
+//! `query_usages` reports the tables of a query's `from` and `join` steps and the
+//! columns its `filter`, `sort`, `group` and `join` steps use, each with its source
+//! span. Query plans and index suggestions are mapped back to the PRQL source with it.
+//!
+//! Columns are attributed to the `from` table, or to the joined table when written
+//! as `that.column`. Steps inside nested pipelines aren't analyzed.
+
+use anyhow::Result;
+use prqlc::pr::*;
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::{is_call_to, main_expr, Span};
+use crate::parser;
+
+/// Step of the query a table or column is used in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UsageKind {
+    From,
+    Join,
+    Filter,
+    Sort,
+    Group,
+}
+
+/// A table (with `column: None`) or column used by a query step
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryUsage {
+    pub kind: UsageKind,
+    pub table: String,
+    pub column: Option<String>,
+    pub span: Option<Span>,
+}
+
+/// Tables and columns used by the main pipeline of `source`, in source order
+pub fn query_usages(source: &str) -> Result<Vec<QueryUsage>> {
+    let split = parser::split_prql_at_render(source)?;
+    let Some(ExprKind::Pipeline(pipeline)) = main_expr(&split.query_module).map(|e| &e.kind) else {
+        return Ok(Vec::new());
+    };
+
+    let mut usages = Vec::new();
+    let mut table: Option<String> = None;
+    for step in &pipeline.exprs {
+        let ExprKind::FuncCall(call) = &step.kind else {
+            continue;
+        };
+        let push = |usages: &mut Vec<QueryUsage>, kind, table: &str, column, expr: &Expr| {
+            usages.push(QueryUsage {
+                kind,
+                table: table.to_string(),
+                column,
+                span: expr
+                    .span
+                    .as_ref()
+                    .map(|s| Span::new(source, s.start, s.end)),
+            })
+        };
+
+        if is_call_to(step, "from") {
+            if let Some((name, arg)) = call
+                .args
+                .first()
+                .and_then(|arg| Some((table_name(arg)?, arg)))
+            {
+                push(&mut usages, UsageKind::From, name.as_str(), None, arg);
+                table = Some(name);
+            }
+            continue;
+        }
+        let Some(from) = table.clone() else {
+            continue;
+        };
+        let kind = if is_call_to(step, "join") {
+            UsageKind::Join
+        } else if is_call_to(step, "filter") {
+            UsageKind::Filter
+        } else if is_call_to(step, "sort") {
+            UsageKind::Sort
+        } else if is_call_to(step, "group") {
+            UsageKind::Group
+        } else {
+            continue;
+        };
+
+        let mut columns = Vec::new();
+        match kind {
+            UsageKind::Join => {
+                let joined = call
+                    .args
+                    .first()
+                    .and_then(|arg| Some((table_name(arg)?, arg)));
+                if let Some((name, arg)) = &joined {
+                    push(&mut usages, UsageKind::Join, name.as_str(), None, arg);
+                }
+                if let Some(condition) = call.args.get(1) {
+                    collect_columns(condition, &mut columns);
+                }
+                for (qualifier, column, expr) in columns {
+                    let joined_name = joined.as_ref().map(|(name, _)| name.as_str());
+                    let tables: Vec<&str> = match qualifier {
+                        // `(==id)` compares the column of both tables
+                        Qualifier::Both => [Some(from.as_str()), joined_name]
+                            .into_iter()
+                            .flatten()
+                            .collect(),
+                        Qualifier::That => joined_name.into_iter().collect(),
+                        Qualifier::This => vec![from.as_str()],
+                    };
+                    for table in tables {
+                        push(&mut usages, kind, table, Some(column.clone()), expr);
+                    }
+                }
+            }
+            _ => {
+                // `group` takes the columns first and a pipeline second
+                let args = match kind {
+                    UsageKind::Group => &call.args[..call.args.len().min(1)],
+                    _ => &call.args[..],
+                };
+                for arg in args {
+                    collect_columns(arg, &mut columns);
+                }
+                for (qualifier, column, expr) in columns {
+                    if qualifier != Qualifier::That {
+                        push(&mut usages, kind, from.as_str(), Some(column), expr);
+                    }
+                }
+            }
+        }
+    }
+    Ok(usages)
+}
+
+/// Which side of a join a column reference is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Qualifier {
+    This,
+    That,
+    Both,
+}
+
+fn table_name(expr: &Expr) -> Option<String> {
+    match &expr.kind {
+        ExprKind::Ident(ident) if ident.path.is_empty() => Some(ident.name.clone()),
+        _ => None,
+    }
+}
+
+/// Plain, `this.` and `that.` column references in `expr`
+fn collect_columns<'a>(expr: &'a Expr, columns: &mut Vec<(Qualifier, String, &'a Expr)>) {
+    match &expr.kind {
+        ExprKind::Ident(ident) => {
+            let qualifier = match ident.path.as_slice() {
+                [] => Qualifier::This,
+                [path] if path == "this" => Qualifier::This,
+                [path] if path == "that" => Qualifier::That,
+                _ => return,
+            };
+            if !matches!(ident.name.as_str(), "this" | "that" | "*") {
+                columns.push((qualifier, ident.name.clone(), expr));
+            }
+        }
+        ExprKind::Unary(unary) if unary.op == UnOp::EqSelf => {
+            if let ExprKind::Ident(ident) = &unary.expr.kind {
+                columns.push((Qualifier::Both, ident.name.clone(), &unary.expr));
+            }
+        }
+        ExprKind::Unary(unary) => collect_columns(&unary.expr, columns),
+        ExprKind::Binary(binary) => {
+            collect_columns(&binary.left, columns);
+            collect_columns(&binary.right, columns);
+        }
+        ExprKind::FuncCall(call) => {
+            for arg in call.args.iter().chain(call.named_args.values()) {
+                collect_columns(arg, columns);
+            }
+        }
+        ExprKind::Tuple(items) | ExprKind::Array(items) => {
+            for item in items {
+                collect_columns(item, columns);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(usages: &[QueryUsage], kind: UsageKind) -> Vec<(String, String)> {
+        usages
+            .iter()
+            .filter(|usage| usage.kind == kind)
+            .filter_map(|usage| Some((usage.table.clone(), usage.column.clone()?)))
+            .collect()
+    }
+
+    #[test]
+    fn test_query_usages() {
+        let source = r#"
+from tasks
+join projects (==project_id)
+filter completed == false
+sort {-priority, due_date}
+render (list item_template:(text content:this.content))
+"#;
+        let usages = query_usages(source).unwrap();
+
+        let from = &usages[0];
+        assert_eq!((from.kind, from.table.as_str()), (UsageKind::From, "tasks"));
+        let span = from.span.unwrap();
+        assert_eq!(&source[span.start..span.end], "tasks");
+        assert_eq!((span.line, span.column), (2, 6));
+
+        assert_eq!(
+            columns(&usages, UsageKind::Join),
+            vec![
+                ("tasks".to_string(), "project_id".to_string()),
+                ("projects".to_string(), "project_id".to_string()),
+            ]
+        );
+        assert_eq!(
+            columns(&usages, UsageKind::Filter),
+            vec![("tasks".to_string(), "completed".to_string())]
+        );
+        assert_eq!(
+            columns(&usages, UsageKind::Sort),
+            vec![
+                ("tasks".to_string(), "priority".to_string()),
+                ("tasks".to_string(), "due_date".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Widgets supported by a frontend
+//!
+//! Frontends register a `WidgetSpec` for every widget they can render. Render
+//! expressions compiled with a `WidgetRegistry` are checked against it, so a query
+//! using an unsupported widget or passing it wrong arguments fails to compile with a
+//! message naming the widget and argument, instead of failing when it is rendered.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use holon_api::{RenderExpr, Value, WidgetArgType, WidgetSpec, STYLE_ARG};
+
+/// Widgets a frontend can render
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WidgetRegistry {
+    widgets: BTreeMap<String, WidgetSpec>,
+}
+
+impl WidgetRegistry {
+    pub fn new(widgets: impl IntoIterator<Item = WidgetSpec>) -> Self {
+        let mut registry = Self::default();
+        for widget in widgets {
+            registry.register(widget);
+        }
+        registry
+    }
+
+    /// Add a widget, replacing a previously registered one of the same name
+    pub fn register(&mut self, widget: WidgetSpec) {
+        self.widgets.insert(widget.name.clone(), widget);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&WidgetSpec> {
+        self.widgets.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.widgets.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.widgets.keys().map(String::as_str)
+    }
+
+    /// Check every widget call in `expr`
+    ///
+    /// The error lists all problems found, not only the first one.
+    pub fn validate(&self, expr: &RenderExpr) -> Result<()> {
+        let mut problems = Vec::new();
+        self.collect_problems(expr, &mut problems);
+        if !problems.is_empty() {
+            bail!("Invalid render expression: {}", problems.join("; "));
+        }
+        Ok(())
+    }
+
+    fn collect_problems(&self, expr: &RenderExpr, problems: &mut Vec<String>) {
+        match expr {
+            RenderExpr::FunctionCall { name, args, .. } => {
+                match self.widgets.get(name) {
+                    Some(widget) => check_args(widget, args, problems),
+                    None => problems.push(format!(
+                        "unknown widget `{}` (supported: {})",
+                        name,
+                        self.names().collect::<Vec<_>>().join(", ")
+                    )),
+                }
+                for arg in args {
+                    self.collect_problems(&arg.value, problems);
+                }
+            }
+            RenderExpr::Array { items } => {
+                for item in items {
+                    self.collect_problems(item, problems);
+                }
+            }
+            RenderExpr::BinaryOp { left, right, .. } => {
+                self.collect_problems(left, problems);
+                self.collect_problems(right, problems);
+            }
+            RenderExpr::Object { fields } => {
+                for value in fields.values() {
+                    self.collect_problems(value, problems);
+                }
+            }
+            RenderExpr::Format { value, .. } => self.collect_problems(value, problems),
+            RenderExpr::ColumnRef { .. }
+            | RenderExpr::Literal { .. }
+            | RenderExpr::Style { .. }
+            | RenderExpr::Error { .. } => {}
+        }
+    }
+}
+
+fn check_args(widget: &WidgetSpec, args: &[holon_api::Arg], problems: &mut Vec<String>) {
+    let mut positional = widget.params.iter();
+    let mut given = Vec::new();
+    for arg in args {
+        // Every widget can be styled
+        if arg.name.as_deref() == Some(STYLE_ARG) {
+            continue;
+        }
+        let arg_type = match &arg.name {
+            Some(name) => match widget.param(name) {
+                Some(param) => {
+                    given.push(name.as_str());
+                    param.arg_type
+                }
+                None => {
+                    problems.push(format!(
+                        "widget `{}` has no parameter `{}`",
+                        widget.name, name
+                    ));
+                    continue;
+                }
+            },
+            None => match (positional.next(), widget.variadic) {
+                (Some(param), _) => {
+                    given.push(param.name.as_str());
+                    param.arg_type
+                }
+                (None, Some(arg_type)) => arg_type,
+                (None, None) => {
+                    problems.push(format!(
+                        "widget `{}` takes at most {} positional arguments",
+                        widget.name,
+                        widget.params.len()
+                    ));
+                    continue;
+                }
+            },
+        };
+
+        if !accepts(arg_type, &arg.value) {
+            problems.push(format!(
+                "argument {} of widget `{}` must be {}, got {}",
+                arg.name
+                    .as_deref()
+                    .map_or_else(|| "(positional)".to_string(), |n| format!("`{}`", n)),
+                widget.name,
+                type_name(arg_type),
+                describe(&arg.value)
+            ));
+        }
+    }
+
+    for param in &widget.params {
+        if param.required && !given.contains(&param.name.as_str()) {
+            problems.push(format!(
+                "widget `{}` requires parameter `{}`",
+                widget.name, param.name
+            ));
+        }
+    }
+}
+
+/// Whether `value` can be passed for an argument of type `arg_type`
+///
+/// Columns and computed values are only known when rendering, so they are accepted
+/// wherever a plain value is.
+fn accepts(arg_type: WidgetArgType, value: &RenderExpr) -> bool {
+    match (arg_type, value) {
+        (WidgetArgType::Any, _) => true,
+        (WidgetArgType::Widget, RenderExpr::FunctionCall { .. }) => true,
+        (WidgetArgType::Widget, _) => false,
+        (_, RenderExpr::ColumnRef { .. } | RenderExpr::BinaryOp { .. }) => true,
+        // Formatted values are text
+        (WidgetArgType::String, RenderExpr::Format { .. }) => true,
+        (_, RenderExpr::Literal { value: Value::Null }) => true,
+        (WidgetArgType::String, RenderExpr::Literal { value }) => matches!(
+            value,
+            Value::String(_) | Value::DateTime(_) | Value::Reference(_)
+        ),
+        (WidgetArgType::Bool, RenderExpr::Literal { value }) => {
+            matches!(value, Value::Boolean(_))
+        }
+        (WidgetArgType::Number, RenderExpr::Literal { value }) => {
+            matches!(value, Value::Integer(_) | Value::Float(_))
+        }
+        _ => false,
+    }
+}
+
+fn type_name(arg_type: WidgetArgType) -> &'static str {
+    match arg_type {
+        WidgetArgType::Any => "any value",
+        WidgetArgType::String => "a string",
+        WidgetArgType::Bool => "a bool",
+        WidgetArgType::Number => "a number",
+        WidgetArgType::Widget => "a widget",
+    }
+}
+
+fn describe(value: &RenderExpr) -> String {
+    match value {
+        RenderExpr::FunctionCall { name, .. } => format!("widget `{}`", name),
+        RenderExpr::ColumnRef { name } => format!("column `{}`", name),
+        RenderExpr::Literal { value } => match value {
+            Value::String(s) => format!("string {:?}", s),
+            Value::Boolean(b) => format!("bool {}", b),
+            Value::Integer(i) => format!("number {}", i),
+            Value::Float(f) => format!("number {}", f),
+            _ => "a literal".to_string(),
+        },
+        RenderExpr::BinaryOp { .. } => "an expression".to_string(),
+        RenderExpr::Array { .. } => "an array".to_string(),
+        RenderExpr::Object { .. } => "an object".to_string(),
+        RenderExpr::Style { .. } => "a style".to_string(),
+        RenderExpr::Format { .. } => "a formatted value".to_string(),
+        RenderExpr::Error { .. } => "an error".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holon_api::WidgetParam;
+
+    fn registry() -> WidgetRegistry {
+        WidgetRegistry::new([
+            WidgetSpec::new("text")
+                .with_param(WidgetParam::required("content", WidgetArgType::String)),
+            WidgetSpec::new("row").with_variadic(WidgetArgType::Widget),
+            WidgetSpec::new("list")
+                .with_param(WidgetParam::required(
+                    "item_template",
+                    WidgetArgType::Widget,
+                ))
+                .with_param(WidgetParam::optional("selectable", WidgetArgType::Bool)),
+        ])
+    }
+
+    fn compile(render: &str) -> Result<holon_api::RenderSpec> {
+        let prql = format!("from tasks\nrender {}", render);
+        let split = crate::parser::split_prql_at_render(&prql)?;
+        let json = crate::parser::prql_ast_to_json(&split.render_ast)?;
+        crate::compiler::compile_render_spec_with_widgets(&json, Some(&registry()))
+    }
+
+    #[test]
+    fn test_valid_calls() {
+        compile("(list selectable:true item_template:(row (text content) (text \"x\")))").unwrap();
+    }
+
+    #[test]
+    fn test_invalid_calls() {
+        let error = compile("(list selectable:\"yes\" item_template:(txt content))")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains(
+                "argument `selectable` of widget `list` must be a bool, got string \"yes\""
+            ),
+            "{}",
+            error
+        );
+        assert!(
+            error.contains("unknown widget `txt` (supported: list, row, text)"),
+            "{}",
+            error
+        );
+
+        let error = compile("(list (text content) extra:1)")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("widget `list` has no parameter `extra`"),
+            "{}",
+            error
+        );
+        assert!(!error.contains("requires parameter"), "{}", error);
+
+        let error = compile("(list selectable:true)").unwrap_err().to_string();
+        assert!(
+            error.contains("widget `list` requires parameter `item_template`"),
+            "{}",
+            error
+        );
+    }
+}
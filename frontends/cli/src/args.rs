@@ -0,0 +1,271 @@
+//! Command-line argument parsing
+
+use std::path::PathBuf;
+
+use holon::export::ExportFormat;
+
+pub const USAGE: &str = "\
+Usage: holon-cli [--db <path>] [--json] <command>
+
+Commands:
+  query <prql|-> [--param name=value]...        Run a PRQL query (`-` reads stdin) and print the rows
+  ops [<entity>]                                List the operations of all or one entity
+  run <entity> <operation> [--param name=value]...
+                                                Execute an operation
+  sync                                          Sync all providers
+  export <dir> [--format markdown|org]          Export all block trees as outline files
+  import <dir>                                  Import outline files written by `export`
+  mcp [--read-only] [--allow <pattern>]... [--max-calls-per-minute <n>] [--agent <name>]
+                                                Serve operations and queries to LLM assistants
+                                                over MCP on stdin/stdout
+
+Options:
+  --db <path>                Database file (default: blocks.db)
+  --json                     Print JSON instead of a table
+  -p, --param <name=value>   Query or operation parameter
+  --read-only                MCP: only offer the query tool
+  --allow <pattern>          MCP: only offer operations of an entity or `entity.operation`
+  --max-calls-per-minute <n> MCP: tool call limit (default: 60)
+  --agent <name>             MCP: actor recorded in the audit log as `mcp:<name>`
+                             (default: assistant)
+  -h, --help                 Show this help
+
+Environment:
+  TODOIST_API_KEY            Enables the Todoist provider
+  HOLON_DB_PASSPHRASE        Passphrase of an encrypted database";
+
+/// How query results and listings are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Markdown table for humans
+    Table,
+    /// JSON for scripts
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Query {
+        prql: String,
+        params: Vec<(String, String)>,
+    },
+    Operations {
+        entity: Option<String>,
+    },
+    Run {
+        entity: String,
+        operation: String,
+        params: Vec<(String, String)>,
+    },
+    Sync,
+    Export {
+        dir: PathBuf,
+        format: ExportFormat,
+    },
+    Import {
+        dir: PathBuf,
+    },
+    Mcp {
+        read_only: bool,
+        allowed: Vec<String>,
+        max_calls_per_minute: usize,
+        agent: String,
+    },
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Args {
+    pub db_path: PathBuf,
+    pub output: OutputFormat,
+    pub command: Command,
+}
+
+impl Args {
+    /// Parse the arguments after the program name
+    ///
+    /// Options may appear anywhere; the first positional argument is the command.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        let mut db_path = PathBuf::from("blocks.db");
+        let mut output = OutputFormat::Table;
+        let mut format = None;
+        let mut params = Vec::new();
+        let mut positional = Vec::new();
+        let mut help = false;
+        let mut read_only = false;
+        let mut allowed = Vec::new();
+        let mut max_calls_per_minute = 60;
+        let mut agent = "assistant".to_string();
+
+        while let Some(arg) = args.next() {
+            let mut value_of = |option: &str| {
+                args.next()
+                    .ok_or_else(|| format!("{} requires a value", option))
+            };
+            match arg.as_str() {
+                "--db" => db_path = PathBuf::from(value_of("--db")?),
+                "--json" => output = OutputFormat::Json,
+                "--param" | "-p" => params.push(split_param(&value_of("--param")?)?),
+                "--format" => format = Some(parse_export_format(&value_of("--format")?)?),
+                "--read-only" => read_only = true,
+                "--allow" => allowed.push(value_of("--allow")?),
+                "--max-calls-per-minute" => {
+                    let value = value_of("--max-calls-per-minute")?;
+                    max_calls_per_minute = value.parse().map_err(|_| {
+                        format!("--max-calls-per-minute must be a number, got '{}'", value)
+                    })?;
+                }
+                "--agent" => agent = value_of("--agent")?,
+                "--help" | "-h" => help = true,
+                _ if arg.starts_with('-') && arg != "-" => {
+                    return Err(format!("Unknown option '{}'", arg));
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        let command = match positional.next().as_deref() {
+            _ if help => Command::Help,
+            Some("query") => Command::Query {
+                prql: positional.next().ok_or("query requires a PRQL query")?,
+                params,
+            },
+            Some("ops") => Command::Operations {
+                entity: positional.next(),
+            },
+            Some("run") => Command::Run {
+                entity: positional.next().ok_or("run requires an entity name")?,
+                operation: positional.next().ok_or("run requires an operation name")?,
+                params,
+            },
+            Some("sync") => Command::Sync,
+            Some("export") => Command::Export {
+                dir: positional
+                    .next()
+                    .ok_or("export requires a directory")?
+                    .into(),
+                format: format.unwrap_or(ExportFormat::Markdown),
+            },
+            Some("import") => Command::Import {
+                dir: positional
+                    .next()
+                    .ok_or("import requires a directory")?
+                    .into(),
+            },
+            Some("mcp") => Command::Mcp {
+                read_only,
+                allowed,
+                max_calls_per_minute,
+                agent,
+            },
+            Some("help") | None => Command::Help,
+            Some(other) => return Err(format!("Unknown command '{}'", other)),
+        };
+        if let Some(extra) = positional.next().filter(|_| !help) {
+            return Err(format!("Unexpected argument '{}'", extra));
+        }
+
+        Ok(Self {
+            db_path,
+            output,
+            command,
+        })
+    }
+}
+
+/// Split a `name=value` parameter
+pub fn split_param(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("Expected a parameter as name=value, got '{}'", arg)),
+    }
+}
+
+fn parse_export_format(format: &str) -> Result<ExportFormat, String> {
+    match format.to_lowercase().as_str() {
+        "md" | "markdown" => Ok(ExportFormat::Markdown),
+        "org" => Ok(ExportFormat::Org),
+        _ => Err(format!(
+            "Unknown export format '{}' (expected markdown or org)",
+            format
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_run_with_params() {
+        let args = parse(&[
+            "--db",
+            "tasks.db",
+            "run",
+            "blocks",
+            "set_field",
+            "-p",
+            "id=b1",
+            "--param",
+            "value=a=b",
+            "--json",
+        ])
+        .unwrap();
+        assert_eq!(args.db_path, PathBuf::from("tasks.db"));
+        assert_eq!(args.output, OutputFormat::Json);
+        assert_eq!(
+            args.command,
+            Command::Run {
+                entity: "blocks".to_string(),
+                operation: "set_field".to_string(),
+                params: vec![
+                    ("id".to_string(), "b1".to_string()),
+                    ("value".to_string(), "a=b".to_string()),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(&["run", "blocks"]).is_err());
+        assert!(parse(&["query", "from blocks", "extra"]).is_err());
+        assert!(parse(&["query", "from blocks", "--param", "id"]).is_err());
+        assert!(parse(&["export", "out", "--format", "pdf"]).is_err());
+        assert!(parse(&["--verbose", "sync"]).is_err());
+        assert!(parse(&["mcp", "--max-calls-per-minute", "many"]).is_err());
+        assert_eq!(parse(&[]).unwrap().command, Command::Help);
+        assert_eq!(
+            parse(&[
+                "mcp",
+                "--allow",
+                "blocks",
+                "--allow",
+                "tasks.set_completion"
+            ])
+            .unwrap()
+            .command,
+            Command::Mcp {
+                read_only: false,
+                allowed: vec!["blocks".to_string(), "tasks.set_completion".to_string()],
+                max_calls_per_minute: 60,
+                agent: "assistant".to_string(),
+            }
+        );
+        assert_eq!(
+            parse(&["export", "out", "--format", "org"])
+                .unwrap()
+                .command,
+            Command::Export {
+                dir: PathBuf::from("out"),
+                format: ExportFormat::Org,
+            }
+        );
+    }
+}
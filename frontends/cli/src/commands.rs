@@ -0,0 +1,118 @@
+//! Running a parsed command against the backend engine
+
+use std::io::Read;
+use std::sync::Arc;
+
+use anyhow::Result;
+use holon::api::backend_engine::BackendEngine;
+
+use crate::args::{Command, OutputFormat, USAGE};
+use crate::mcp::{McpScope, McpServer, RateLimiter};
+use crate::output::{format_operations, format_rows};
+use crate::params::{operation_params, query_params};
+
+/// Run `command` and print its result to stdout
+///
+/// Fails if the command failed, e.g. a sync where a provider could not sync, so
+/// scripts can rely on the exit status.
+pub async fn run(engine: Arc<BackendEngine>, command: Command, output: OutputFormat) -> Result<()> {
+    match command {
+        Command::Query { prql, params } => {
+            let prql = if prql == "-" {
+                let mut prql = String::new();
+                std::io::stdin().read_to_string(&mut prql)?;
+                prql
+            } else {
+                prql
+            };
+            let (sql, _render_spec) = engine.compile_query(prql)?;
+            let rows = engine.execute_query(sql, query_params(&params)).await?;
+            print!("{}", format_rows(&rows, output));
+        }
+        Command::Operations { entity } => {
+            let operations = match entity {
+                Some(entity) => engine.available_operations(&entity).await,
+                None => engine.all_operations().await,
+            };
+            print!("{}", format_operations(&operations, output));
+        }
+        Command::Run {
+            entity,
+            operation,
+            params,
+        } => {
+            let descriptor = engine
+                .available_operations(&entity)
+                .await
+                .into_iter()
+                .find(|op| op.name == operation)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Unknown operation {}.{} (see `holon-cli ops {}`)",
+                        entity,
+                        operation,
+                        entity
+                    )
+                })?;
+            let params = operation_params(&descriptor, &params).map_err(anyhow::Error::msg)?;
+            engine
+                .execute_operation(&entity, &operation, params)
+                .await?;
+        }
+        Command::Sync => {
+            let summary = engine.sync_all().await?;
+            for provider in &summary.synced {
+                println!("synced  {}", provider);
+            }
+            for provider in &summary.skipped {
+                println!("skipped {}", provider);
+            }
+            for (provider, error) in &summary.failed {
+                println!("failed  {}: {}", provider, error);
+            }
+            if !summary.failed.is_empty() {
+                anyhow::bail!("{} provider(s) failed to sync", summary.failed.len());
+            }
+        }
+        Command::Export { dir, format } => {
+            let summary = engine.export_workspace(&dir, format).await?;
+            println!(
+                "Exported {} file(s) to {} ({} unchanged)",
+                summary.written.len(),
+                dir.display(),
+                summary.unchanged
+            );
+            for (path, error) in &summary.failed {
+                println!("failed  {}: {}", path.display(), error);
+            }
+            if !summary.failed.is_empty() {
+                anyhow::bail!("{} file(s) failed to export", summary.failed.len());
+            }
+        }
+        Command::Import { dir } => {
+            let summary = engine.import_workspace(&dir, |_| {}).await?;
+            println!(
+                "Imported {} block(s) from {}: {} created, {} updated, {} unchanged",
+                summary.blocks(),
+                dir.display(),
+                summary.created,
+                summary.updated,
+                summary.unchanged
+            );
+        }
+        Command::Mcp {
+            read_only,
+            allowed,
+            max_calls_per_minute,
+            agent: _,
+        } => {
+            let scope = McpScope { read_only, allowed };
+            let rate_limiter = RateLimiter::per_minute(max_calls_per_minute);
+            McpServer::new(engine, scope, rate_limiter)
+                .serve_stdio()
+                .await?;
+        }
+        Command::Help => println!("{}", USAGE),
+    }
+    Ok(())
+}
@@ -0,0 +1,14 @@
+//! Command-line interface for scripting and cron jobs
+//!
+//! Runs PRQL queries, operations, syncs and workspace exports/imports against the
+//! same database and backend engine as the other frontends, without a UI, or serves
+//! them to LLM assistants over MCP (see `mcp`).
+
+pub mod args;
+pub mod commands;
+pub mod mcp;
+pub mod output;
+pub mod params;
+
+pub use args::{Args, Command, OutputFormat};
+pub use commands::run;
@@ -0,0 +1,73 @@
+use std::process::ExitCode;
+
+use ferrous_di::ServiceCollectionModuleExt;
+use holon::core::operation_log::AuditActor;
+use holon_api::ChangeSource;
+use holon_cli::{args::USAGE, Args, Command};
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Logs go to stderr so they don't mix with query output; quiet unless RUST_LOG is set
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("warn"))
+        .add_directive("turso_core=warn".parse().unwrap());
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    if args.command == Command::Help {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let todoist_api_key = std::env::var("TODOIST_API_KEY").ok();
+    let db_passphrase = std::env::var("HOLON_DB_PASSPHRASE").ok();
+    let engine = holon::di::create_backend_engine(args.db_path.clone(), |services| {
+        // Operations of assistants are attributed to them in the audit log and change origins
+        let source = ChangeSource::default().with_frontend("cli");
+        if let Command::Mcp { agent, .. } = &args.command {
+            let agent = format!("mcp:{}", agent);
+            services.add_singleton(AuditActor::new(agent.clone()));
+            services.add_singleton(source.with_agent(agent));
+        } else {
+            services.add_singleton(source);
+        }
+        if let Some(passphrase) = &db_passphrase {
+            services.add_singleton(holon::storage::EncryptionConfig::new(passphrase.clone()));
+        }
+        if let Some(api_key) = &todoist_api_key {
+            services.add_singleton(holon_todoist::di::TodoistConfig::new(Some(api_key.clone())));
+            services
+                .add_module_mut(holon_todoist::di::TodoistModule)
+                .map_err(|e| anyhow::anyhow!("Failed to register TodoistModule: {}", e))?;
+        }
+        Ok(())
+    })
+    .await;
+    let engine = match engine {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("error: Failed to open {}: {:#}", args.db_path.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = holon_cli::run(engine.clone(), args.command, args.output).await;
+    engine.shutdown_sync();
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,411 @@
+//! Model Context Protocol server
+//!
+//! Lets LLM assistants work with the database over MCP (JSON-RPC 2.0, one message
+//! per line on stdin/stdout). Every operation an `OperationDescriptor` describes is
+//! a tool taking the operation's parameters; the `query` tool runs a read-only
+//! PRQL query.
+//!
+//! What an assistant may do is limited by an `McpScope`, and how often by a
+//! `RateLimiter`. Operations are executed through the backend engine like those of
+//! any other frontend, so they land in the audit trail, attributed to the actor the
+//! process registered (see `AuditActor`).
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use holon::api::backend_engine::BackendEngine;
+use holon_api::{OperationDescriptor, Value};
+use serde_json::{json, Map, Value as JsonValue};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::args::OutputFormat;
+use crate::output::format_rows;
+use crate::params::{json_operation_params, json_type};
+
+/// MCP revision this server speaks
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Name of the tool running PRQL queries
+pub const QUERY_TOOL: &str = "query";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Which operations an assistant may see and execute
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct McpScope {
+    /// Only the `query` tool is offered
+    pub read_only: bool,
+    /// `entity` or `entity.operation` patterns of allowed operations; empty allows all
+    pub allowed: Vec<String>,
+}
+
+impl McpScope {
+    pub fn allows(&self, op: &OperationDescriptor) -> bool {
+        if self.read_only {
+            return false;
+        }
+        self.allowed.is_empty()
+            || self.allowed.iter().any(|pattern| {
+                pattern == &op.entity_name || *pattern == format!("{}.{}", op.entity_name, op.name)
+            })
+    }
+}
+
+/// Sliding-window limit on tool calls
+#[derive(Debug)]
+pub struct RateLimiter {
+    max_calls: usize,
+    window: Duration,
+    calls: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(max_calls: usize, window: Duration) -> Self {
+        Self {
+            max_calls,
+            window,
+            calls: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn per_minute(max_calls: usize) -> Self {
+        Self::new(max_calls, Duration::from_secs(60))
+    }
+
+    /// Count a call at `now`, or return false if the window is full
+    pub fn try_acquire(&self, now: Instant) -> bool {
+        let mut calls = self.calls.lock().unwrap();
+        while calls
+            .front()
+            .is_some_and(|call| now.duration_since(*call) >= self.window)
+        {
+            calls.pop_front();
+        }
+        if calls.len() >= self.max_calls {
+            return false;
+        }
+        calls.push_back(now);
+        true
+    }
+}
+
+/// Name of the tool of an operation, e.g. `blocks__set_completion`
+///
+/// Wildcard operations (entity `*`) are named `all__sync` etc. MCP tool names may
+/// only contain letters, digits, `_` and `-`, so other characters become `_`.
+pub fn tool_name(op: &OperationDescriptor) -> String {
+    let entity_name = match op.entity_name.as_str() {
+        "*" => "all",
+        entity_name => entity_name,
+    };
+    format!("{}__{}", entity_name, op.name)
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// MCP tool definition of an operation
+pub fn operation_tool(op: &OperationDescriptor) -> JsonValue {
+    let properties: Map<String, JsonValue> = op
+        .required_params
+        .iter()
+        .map(|param| {
+            (
+                param.name.clone(),
+                json!({ "type": json_type(&param.type_hint), "description": param.description }),
+            )
+        })
+        .collect();
+    let description = match op.description.as_str() {
+        "" => op.display_name.clone(),
+        description => format!("{}: {}", op.display_name, description),
+    };
+    json!({
+        "name": tool_name(op),
+        "description": format!("{} ({} operation)", description, op.entity_name),
+        "inputSchema": {
+            "type": "object",
+            "properties": properties,
+            "required": op.required_params.iter().map(|param| &param.name).collect::<Vec<_>>(),
+        },
+    })
+}
+
+fn query_tool() -> JsonValue {
+    json!({
+        "name": QUERY_TOOL,
+        "description": "Run a read-only PRQL query, ending in a render(...) clause, and return the rows as JSON",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "prql": { "type": "string", "description": "The PRQL query" },
+                "params": { "type": "object", "description": "Values of $name placeholders" },
+            },
+            "required": ["prql"],
+        },
+    })
+}
+
+/// Whether compiled SQL only reads
+///
+/// PRQL compiles to a single SELECT, but `s"..."` strings can embed arbitrary SQL.
+/// Any `;` is rejected, even inside a string literal, to rule out a second statement.
+pub fn is_read_only_sql(sql: &str) -> bool {
+    let sql = sql.trim_start().to_lowercase();
+    (sql.starts_with("select") || sql.starts_with("with")) && !sql.trim_end().contains(';')
+}
+
+pub struct McpServer {
+    engine: Arc<BackendEngine>,
+    scope: McpScope,
+    rate_limiter: RateLimiter,
+}
+
+impl McpServer {
+    pub fn new(engine: Arc<BackendEngine>, scope: McpScope, rate_limiter: RateLimiter) -> Self {
+        Self {
+            engine,
+            scope,
+            rate_limiter,
+        }
+    }
+
+    /// Serve requests from stdin until it closes
+    pub async fn serve_stdio(&self) -> Result<()> {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        let mut stdout = tokio::io::stdout();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_message(&line).await {
+                stdout
+                    .write_all(format!("{}\n", response).as_bytes())
+                    .await?;
+                stdout.flush().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Response to one JSON-RPC message; None for notifications
+    pub async fn handle_message(&self, message: &str) -> Option<JsonValue> {
+        let request: JsonValue = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(JsonValue::Null, PARSE_ERROR, e.to_string())),
+        };
+        let id = request.get("id").cloned();
+        let Some(method) = request.get("method").and_then(|m| m.as_str()) else {
+            return Some(error_response(
+                id.unwrap_or(JsonValue::Null),
+                INVALID_REQUEST,
+                "Missing method".to_string(),
+            ));
+        };
+        // Notifications (e.g. `notifications/initialized`) get no response
+        let id = id?;
+        let params = request.get("params").cloned().unwrap_or(json!({}));
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "holon", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": self.tools().await })),
+            "tools/call" => self.call_tool(&params).await,
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+        };
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, message),
+        })
+    }
+
+    /// The query tool and the tools of all operations in scope
+    async fn tools(&self) -> Vec<JsonValue> {
+        let mut operations: Vec<OperationDescriptor> = self
+            .engine
+            .all_operations()
+            .await
+            .into_iter()
+            .filter(|op| self.scope.allows(op))
+            .collect();
+        operations.sort_by(|a, b| (&a.entity_name, &a.name).cmp(&(&b.entity_name, &b.name)));
+        std::iter::once(query_tool())
+            .chain(operations.iter().map(operation_tool))
+            .collect()
+    }
+
+    /// Result of a `tools/call`; failures of the tool itself are results with `isError`
+    async fn call_tool(&self, params: &JsonValue) -> std::result::Result<JsonValue, (i64, String)> {
+        let Some(name) = params.get("name").and_then(|n| n.as_str()) else {
+            return Err((INVALID_PARAMS, "Missing tool name".to_string()));
+        };
+        let arguments = params
+            .get("arguments")
+            .and_then(|a| a.as_object())
+            .cloned()
+            .unwrap_or_default();
+
+        if !self.rate_limiter.try_acquire(Instant::now()) {
+            return Ok(tool_result(
+                format!(
+                    "Rate limit exceeded: at most {} tool calls per {} seconds",
+                    self.rate_limiter.max_calls,
+                    self.rate_limiter.window.as_secs()
+                ),
+                true,
+            ));
+        }
+
+        let outcome = if name == QUERY_TOOL {
+            self.run_query(&arguments).await
+        } else {
+            let op = self
+                .engine
+                .all_operations()
+                .await
+                .into_iter()
+                .find(|op| tool_name(op) == name && self.scope.allows(op));
+            match op {
+                Some(op) => self.run_operation(&op, &arguments).await,
+                None => return Err((INVALID_PARAMS, format!("Unknown tool '{}'", name))),
+            }
+        };
+        Ok(match outcome {
+            Ok(text) => tool_result(text, false),
+            Err(e) => tool_result(format!("{:#}", e), true),
+        })
+    }
+
+    async fn run_query(&self, arguments: &Map<String, JsonValue>) -> Result<String> {
+        let prql = arguments
+            .get("prql")
+            .and_then(|p| p.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing prql"))?;
+        let params: HashMap<String, Value> = arguments
+            .get("params")
+            .and_then(|p| p.as_object())
+            .map(|params| {
+                params
+                    .iter()
+                    .map(|(name, value)| (name.clone(), Value::from_json_value(value.clone())))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let (sql, _render_spec) = self.engine.compile_query(prql.to_string())?;
+        if !is_read_only_sql(&sql) {
+            anyhow::bail!("Only read-only queries are allowed");
+        }
+        let rows = self.engine.execute_query(sql, params).await?;
+        Ok(format_rows(&rows, OutputFormat::Json))
+    }
+
+    async fn run_operation(
+        &self,
+        op: &OperationDescriptor,
+        arguments: &Map<String, JsonValue>,
+    ) -> Result<String> {
+        let params = json_operation_params(op, arguments).map_err(anyhow::Error::msg)?;
+        self.engine
+            .execute_operation(&op.entity_name, &op.name, params)
+            .await?;
+        Ok(format!("{}.{} done", op.entity_name, op.name))
+    }
+}
+
+fn tool_result(text: String, is_error: bool) -> JsonValue {
+    json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+}
+
+fn error_response(id: JsonValue, code: i64, message: String) -> JsonValue {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holon_api::{OperationParam, TypeHint};
+
+    fn op(entity_name: &str, name: &str) -> OperationDescriptor {
+        OperationDescriptor {
+            entity_name: entity_name.to_string(),
+            entity_short_name: String::new(),
+            id_column: "id".to_string(),
+            name: name.to_string(),
+            display_name: "Set completion".to_string(),
+            description: String::new(),
+            required_params: vec![OperationParam {
+                name: "completed".to_string(),
+                type_hint: TypeHint::Bool,
+                description: "Whether the task is completed".to_string(),
+            }],
+            affected_fields: vec![],
+            param_mappings: vec![],
+            precondition: None,
+            simulation: None,
+        }
+    }
+
+    #[test]
+    fn test_scope() {
+        let scope = McpScope {
+            read_only: false,
+            allowed: vec!["blocks".to_string(), "tasks.set_completion".to_string()],
+        };
+        assert!(scope.allows(&op("blocks", "delete")));
+        assert!(scope.allows(&op("tasks", "set_completion")));
+        assert!(!scope.allows(&op("tasks", "delete")));
+        assert!(McpScope::default().allows(&op("tasks", "delete")));
+        let read_only = McpScope {
+            read_only: true,
+            allowed: vec![],
+        };
+        assert!(!read_only.allows(&op("blocks", "delete")));
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        assert!(limiter.try_acquire(start));
+        assert!(limiter.try_acquire(start + Duration::from_secs(1)));
+        assert!(!limiter.try_acquire(start + Duration::from_secs(2)));
+        // The first call left the window
+        assert!(limiter.try_acquire(start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_operation_tool() {
+        let tool = operation_tool(&op("*", "sync"));
+        assert_eq!(tool["name"], "all__sync");
+        assert_eq!(
+            tool_name(&op("todoist-tasks", "set.due")),
+            "todoist-tasks__set_due"
+        );
+        assert_eq!(
+            tool["inputSchema"]["properties"]["completed"]["type"],
+            "boolean"
+        );
+        assert_eq!(tool["inputSchema"]["required"], json!(["completed"]));
+    }
+
+    #[test]
+    fn test_read_only_sql() {
+        assert!(is_read_only_sql("SELECT * FROM blocks"));
+        assert!(is_read_only_sql("WITH t AS (SELECT 1) SELECT * FROM t"));
+        assert!(!is_read_only_sql("DELETE FROM blocks"));
+        assert!(!is_read_only_sql("SELECT 1; DELETE FROM blocks"));
+    }
+}
@@ -0,0 +1,74 @@
+//! Printing query results and listings as tables or JSON
+
+use std::collections::{BTreeMap, HashMap};
+
+use holon::export::markdown::render_table;
+use holon_api::{OperationDescriptor, Value};
+
+use crate::args::OutputFormat;
+use crate::params::type_name;
+
+/// Query result rows
+///
+/// Internal columns starting with `_` (e.g. `_change_origin`) are left out. JSON
+/// objects have their keys sorted so the output is stable between runs.
+pub fn format_rows(rows: &[HashMap<String, Value>], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Table => render_table(rows),
+        OutputFormat::Json => {
+            let rows: Vec<BTreeMap<&String, &Value>> = rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .filter(|(key, _)| !key.starts_with('_'))
+                        .collect()
+                })
+                .collect();
+            serde_json::to_string_pretty(&rows).unwrap_or_default()
+        }
+    }
+}
+
+/// Operations with the parameters `run` expects, one per line
+pub fn format_operations(operations: &[OperationDescriptor], format: OutputFormat) -> String {
+    let mut operations: Vec<&OperationDescriptor> = operations.iter().collect();
+    operations.sort_by(|a, b| (&a.entity_name, &a.name).cmp(&(&b.entity_name, &b.name)));
+
+    match format {
+        OutputFormat::Table => operations
+            .iter()
+            .map(|op| {
+                let params: Vec<String> = op
+                    .required_params
+                    .iter()
+                    .map(|param| format!(" -p {}=<{}>", param.name, type_name(&param.type_hint)))
+                    .collect();
+                format!(
+                    "{} {}{}  # {}\n",
+                    op.entity_name,
+                    op.name,
+                    params.concat(),
+                    op.display_name
+                )
+            })
+            .collect(),
+        OutputFormat::Json => serde_json::to_string_pretty(&operations).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_rows_skip_internal_columns() {
+        let rows = vec![HashMap::from([
+            ("id".to_string(), Value::String("b1".to_string())),
+            ("completed".to_string(), Value::Boolean(true)),
+            ("_change_origin".to_string(), Value::Null),
+        ])];
+        let json: serde_json::Value =
+            serde_json::from_str(&format_rows(&rows, OutputFormat::Json)).unwrap();
+        assert_eq!(json, serde_json::json!([{ "completed": true, "id": "b1" }]));
+    }
+}
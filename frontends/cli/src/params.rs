@@ -0,0 +1,244 @@
+//! Typed values of `--param name=value` arguments
+
+use std::collections::HashMap;
+
+use holon_api::{OperationDescriptor, OperationParam, TypeHint, Value};
+
+/// Value of a parameter without a declared type
+///
+/// JSON literals (`3`, `true`, `null`, `[1, 2]`) become the matching value; anything
+/// else is a string, so `--param content=Buy milk` needs no quoting.
+pub fn infer_value(raw: &str) -> Value {
+    serde_json::from_str::<serde_json::Value>(raw)
+        .map(Value::from_json_value)
+        .unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// Value of a parameter declared as `type_hint`, or an error if it isn't of that type
+pub fn typed_value(name: &str, raw: &str, type_hint: &TypeHint) -> Result<Value, String> {
+    match type_hint {
+        TypeHint::Bool => match raw.to_lowercase().as_str() {
+            "true" | "1" | "yes" => Ok(Value::Boolean(true)),
+            "false" | "0" | "no" => Ok(Value::Boolean(false)),
+            _ => Err(format!(
+                "Parameter '{}' must be true or false, got '{}'",
+                name, raw
+            )),
+        },
+        TypeHint::Number => raw
+            .parse::<i64>()
+            .map(Value::Integer)
+            .map_err(|_| format!("Parameter '{}' must be a number, got '{}'", name, raw)),
+        TypeHint::String | TypeHint::EntityId { .. } => Ok(Value::String(raw.to_string())),
+    }
+}
+
+/// Parameters of a query; PRQL `$name` placeholders have no declared type
+pub fn query_params(raw: &[(String, String)]) -> HashMap<String, Value> {
+    raw.iter()
+        .map(|(name, value)| (name.clone(), infer_value(value)))
+        .collect()
+}
+
+/// Parameters of an operation, checked against its descriptor
+///
+/// Declared parameters are converted to their type and must all be given. Others
+/// (e.g. the fields of `create`) are passed through with an inferred type.
+pub fn operation_params(
+    descriptor: &OperationDescriptor,
+    raw: &[(String, String)],
+) -> Result<HashMap<String, Value>, String> {
+    let mut params = HashMap::new();
+    for (name, value) in raw {
+        let value = match descriptor.required_params.iter().find(|p| &p.name == name) {
+            Some(param) => typed_value(name, value, &param.type_hint)?,
+            None => infer_value(value),
+        };
+        params.insert(name.clone(), value);
+    }
+
+    check_required(descriptor, &params, |param| {
+        format!("--param {}=<{}>", param.name, type_name(&param.type_hint))
+    })?;
+    Ok(params)
+}
+
+/// Parameters of an operation from JSON arguments, checked against its descriptor
+///
+/// Like `operation_params`, but declared parameters must already have the JSON type
+/// of their declared type (e.g. `true`, not `"true"`).
+pub fn json_operation_params(
+    descriptor: &OperationDescriptor,
+    arguments: &serde_json::Map<String, serde_json::Value>,
+) -> Result<HashMap<String, Value>, String> {
+    let mut params = HashMap::new();
+    for (name, value) in arguments {
+        let value = match descriptor.required_params.iter().find(|p| &p.name == name) {
+            Some(param) => {
+                let matches = match param.type_hint {
+                    TypeHint::Bool => value.is_boolean(),
+                    TypeHint::Number => value.is_i64(),
+                    TypeHint::String | TypeHint::EntityId { .. } => value.is_string(),
+                };
+                if !matches {
+                    return Err(format!(
+                        "Parameter '{}' must be a {}, got {}",
+                        name,
+                        json_type(&param.type_hint),
+                        value
+                    ));
+                }
+                Value::from_json_value(value.clone())
+            }
+            None => Value::from_json_value(value.clone()),
+        };
+        params.insert(name.clone(), value);
+    }
+
+    check_required(descriptor, &params, |param| param.name.clone())?;
+    Ok(params)
+}
+
+/// Fail with the declared parameters missing from `params`, listed by `describe`
+fn check_required(
+    descriptor: &OperationDescriptor,
+    params: &HashMap<String, Value>,
+    describe: impl Fn(&OperationParam) -> String,
+) -> Result<(), String> {
+    let missing: Vec<String> = descriptor
+        .required_params
+        .iter()
+        .filter(|param| !params.contains_key(&param.name))
+        .map(describe)
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "{}.{} is missing {}",
+        descriptor.entity_name,
+        descriptor.name,
+        missing.join(" ")
+    ))
+}
+
+/// JSON Schema type of a parameter type
+pub fn json_type(type_hint: &TypeHint) -> &'static str {
+    match type_hint {
+        TypeHint::Bool => "boolean",
+        TypeHint::Number => "integer",
+        TypeHint::String | TypeHint::EntityId { .. } => "string",
+    }
+}
+
+/// Name of a parameter type as shown in help and error messages
+pub fn type_name(type_hint: &TypeHint) -> String {
+    match type_hint {
+        TypeHint::Bool => "bool".to_string(),
+        TypeHint::String => "string".to_string(),
+        TypeHint::Number => "number".to_string(),
+        TypeHint::EntityId { entity_name } => format!("{} id", entity_name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_completion() -> OperationDescriptor {
+        let param = |name: &str, type_hint: TypeHint| OperationParam {
+            name: name.to_string(),
+            type_hint,
+            description: String::new(),
+        };
+        OperationDescriptor {
+            entity_name: "blocks".to_string(),
+            entity_short_name: "block".to_string(),
+            id_column: "id".to_string(),
+            name: "set_completion".to_string(),
+            display_name: "Set completion".to_string(),
+            description: String::new(),
+            required_params: vec![
+                param(
+                    "id",
+                    TypeHint::EntityId {
+                        entity_name: "block".to_string(),
+                    },
+                ),
+                param("completed", TypeHint::Bool),
+            ],
+            affected_fields: vec!["completed".to_string()],
+            param_mappings: vec![],
+            precondition: None,
+            simulation: None,
+        }
+    }
+
+    fn raw(params: &[(&str, &str)]) -> Vec<(String, String)> {
+        params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_operation_params_are_typed() {
+        let params = operation_params(
+            &set_completion(),
+            &raw(&[("id", "42"), ("completed", "yes"), ("note", "7")]),
+        )
+        .unwrap();
+        // Declared as an entity ID, so "42" stays a string
+        assert_eq!(params["id"], Value::String("42".to_string()));
+        assert_eq!(params["completed"], Value::Boolean(true));
+        assert_eq!(params["note"], Value::Integer(7));
+    }
+
+    #[test]
+    fn test_operation_params_are_validated() {
+        let err = operation_params(&set_completion(), &raw(&[("completed", "true")])).unwrap_err();
+        assert_eq!(
+            err,
+            "blocks.set_completion is missing --param id=<block id>"
+        );
+        assert!(operation_params(
+            &set_completion(),
+            &raw(&[("id", "b1"), ("completed", "maybe")])
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_json_operation_params_check_types() {
+        let arguments = |json: serde_json::Value| json.as_object().unwrap().clone();
+        let params = json_operation_params(
+            &set_completion(),
+            &arguments(serde_json::json!({ "id": "b1", "completed": false })),
+        )
+        .unwrap();
+        assert_eq!(params["completed"], Value::Boolean(false));
+        assert_eq!(
+            json_operation_params(
+                &set_completion(),
+                &arguments(serde_json::json!({ "id": "b1", "completed": "false" })),
+            )
+            .unwrap_err(),
+            "Parameter 'completed' must be a boolean, got \"false\""
+        );
+        assert!(json_operation_params(
+            &set_completion(),
+            &arguments(serde_json::json!({ "completed": true })),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_infer_value() {
+        assert_eq!(infer_value("3"), Value::Integer(3));
+        assert_eq!(infer_value("null"), Value::Null);
+        assert_eq!(
+            infer_value("Buy milk"),
+            Value::String("Buy milk".to_string())
+        );
+    }
+}
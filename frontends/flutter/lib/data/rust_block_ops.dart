@@ -112,6 +112,11 @@ class RustBlockOps implements BlockOps<RustBlock> {
       invalidOperation: (msg) => debugPrint('Invalid operation: $msg'),
       networkError: (msg) => debugPrint('Network error: $msg'),
       internalError: (msg) => debugPrint('Internal error: $msg'),
+      notFound: (entity, id) => debugPrint('$entity not found: $id'),
+      remoteError: (status, msg) => debugPrint('Remote error ($status): $msg'),
+      conflict: (msg) => debugPrint('Conflict: $msg'),
+      preconditionViolated: (operation, clause, msg, params) =>
+          debugPrint('Precondition of $operation failed: $msg'),
     );
   }
 
@@ -587,6 +587,46 @@ class MainScreen extends HookConsumerWidget {
       };
     }, [backendService, context]);
 
+    // Batch callback for selectable lists - one undoable batch per call
+    final batchOperationCallback = useMemoized(() {
+      return (
+        String entityName,
+        String operationName,
+        String displayName,
+        List<Map<String, dynamic>> rows,
+      ) async {
+        try {
+          log.debug(
+            'Executing batch operation: entity=$entityName, op=$operationName, rows=${rows.length}',
+          );
+
+          final executed = await backendService.executeBatchOperation(
+            entityName: entityName,
+            opName: operationName,
+            displayName: displayName,
+            rows: rows.map(dynamicToValueMap).toList(),
+          );
+
+          log.debug('Batch "$displayName" executed on $executed rows');
+        } catch (e, stackTrace) {
+          log.error(
+            'Batch operation failed',
+            error: e,
+            stackTrace: stackTrace,
+          );
+          if (context.mounted) {
+            final colors = ref.read(appColorsProvider);
+            ScaffoldMessenger.of(context).showSnackBar(
+              SnackBar(
+                content: Text('$displayName failed: ${e.toString()}'),
+                backgroundColor: colors.error,
+              ),
+            );
+          }
+        }
+      };
+    }, [backendService, context]);
+
     // Create scaffold key
     final scaffoldKey = useMemoized(() => GlobalKey<ScaffoldState>());
 
@@ -607,6 +647,7 @@ class MainScreen extends HookConsumerWidget {
           changeStream: changeStream,
           initialData: initialData,
           onOperation: operationCallback,
+          onBatchOperation: batchOperationCallback,
         );
       },
       loading: () {
@@ -8,7 +8,8 @@ import 'package:holon/src/rust/third_party/holon_api/streaming.dart'
         MapChange,
         MapChange_Created,
         MapChange_Updated,
-        MapChange_Deleted;
+        MapChange_Deleted,
+        MapChange_ColumnChange;
 import '../services/backend_service.dart';
 import '../services/mock_backend_service.dart';
 import '../services/mcp_backend_wrapper.dart';
@@ -123,7 +124,8 @@ final queryResultProvider =
             for (final change in batchWithMetadata.inner.items) {
               if (change is MapChange_Created) {
                 createdCount++;
-              } else if (change is MapChange_Updated) {
+              } else if (change is MapChange_Updated ||
+                  change is MapChange_ColumnChange) {
                 updatedCount++;
               } else if (change is MapChange_Deleted) {
                 deletedCount++;
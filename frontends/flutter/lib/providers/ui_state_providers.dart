@@ -242,3 +242,35 @@ class CaptureOverlayNotifier extends Notifier<bool> {
 final captureOverlayProvider = NotifierProvider<CaptureOverlayNotifier, bool>(
   CaptureOverlayNotifier.new,
 );
+
+/// Provider for the multi-selection of a selectable list.
+///
+/// Keyed by the query key of the list; holds the values of the selection ID
+/// column (`SelectionSpec.idColumn`) of the selected rows.
+class SelectedRowIdsNotifier extends Notifier<Set<String>> {
+  SelectedRowIdsNotifier(this.queryKey);
+
+  final String queryKey;
+
+  @override
+  Set<String> build() => const {};
+
+  void toggle(String id) {
+    state = state.contains(id) ? ({...state}..remove(id)) : {...state, id};
+  }
+
+  /// Select all [ids], or clear the selection if all are already selected.
+  void toggleAll(Iterable<String> ids) {
+    final all = ids.toSet();
+    state = state.containsAll(all) ? const {} : all;
+  }
+
+  void clear() {
+    state = const {};
+  }
+}
+
+final selectedRowIdsProvider =
+    NotifierProvider.family<SelectedRowIdsNotifier, Set<String>, String>(
+      SelectedRowIdsNotifier.new,
+    );
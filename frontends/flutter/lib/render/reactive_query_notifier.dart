@@ -305,6 +305,17 @@ class ReactiveQueryStateNotifier extends _$ReactiveQueryStateNotifier {
           updatedCount++;
           break;
 
+        case RowEventType.patched:
+          // Only the changed columns are sent; rows not in the cache aren't
+          // part of this query's result
+          final existing = newCache[event.rowId];
+          if (existing == null || event.data == null) continue;
+          final patched = {...existing, ...event.data!};
+          newCache[event.rowId] = patched;
+          currentState.blockOps?.updateRowCache(event.rowId, patched);
+          updatedCount++;
+          break;
+
         case RowEventType.removed:
           newCache.remove(event.rowId);
           orderSet.remove(event.rowId);
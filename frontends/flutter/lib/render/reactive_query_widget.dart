@@ -6,17 +6,24 @@ import '../src/rust/third_party/holon_api.dart' show Value;
 import '../src/rust/third_party/holon_api/render_types.dart';
 import '../utils/value_converter.dart' show valueToDynamic;
 import 'package:flutter_riverpod/flutter_riverpod.dart';
+import 'package:intl/intl.dart' show DateFormat;
 import 'package:outliner_view/outliner_view.dart';
 import '../src/rust/third_party/holon_api/streaming.dart'
     show BatchMapChangeWithMetadata, MapChange, MapChangePatterns;
 import 'render_interpreter.dart';
+import 'operation_matcher.dart';
 import '../data/row_data_block_ops.dart';
 import 'reactive_query_notifier.dart';
 import '../providers/settings_provider.dart';
+import '../providers/ui_state_providers.dart' show selectedRowIdsProvider;
+import 'selection_action_bar.dart';
 import '../styles/app_styles.dart';
 
 /// Event type for CDC (Change Data Capture) streaming.
-enum RowEventType { added, updated, removed }
+///
+/// `patched` carries only the changed columns, which are merged into the
+/// cached row instead of replacing it.
+enum RowEventType { added, updated, patched, removed }
 
 class RowEvent {
   final RowEventType type;
@@ -62,6 +69,17 @@ RowEvent rowChangeToRowEvent(
       debugPrint('[rowChangeToRowEvent] Deleted event: id=$id');
       return RowEvent(type: RowEventType.removed, rowId: id, data: null);
     },
+    columnChange: (id, columns, origin) {
+      final convertedColumns = valueConverter(columns);
+      debugPrint(
+        '[rowChangeToRowEvent] ColumnChange event: id=$id, columns=${convertedColumns.keys.toList()}',
+      );
+      return RowEvent(
+        type: RowEventType.patched,
+        rowId: id,
+        data: convertedColumns,
+      );
+    },
   );
 }
 
@@ -109,6 +127,16 @@ class ReactiveQueryWidget extends ConsumerWidget {
   )?
   onOperation;
 
+  /// Callback for running one operation on all selected rows of a selectable list.
+  /// Parameters: entityName, operationName, displayName, one params map per row
+  final Future<void> Function(
+    String entityName,
+    String operationName,
+    String displayName,
+    List<Map<String, dynamic>> rows,
+  )?
+  onBatchOperation;
+
   /// Callback for syncing providers (e.g., Todoist).
   final Future<void> Function()? onSync;
 
@@ -120,6 +148,7 @@ class ReactiveQueryWidget extends ConsumerWidget {
     this.changeStream,
     this.initialData,
     this.onOperation,
+    this.onBatchOperation,
     this.onSync,
   });
 
@@ -151,6 +180,7 @@ class ReactiveQueryWidget extends ConsumerWidget {
       queryParams: queryParams,
       renderSpec: renderSpec,
       onOperation: onOperation,
+      onBatchOperation: onBatchOperation,
       onSync: onSync,
     );
   }
@@ -163,6 +193,13 @@ class _ReactiveQueryWidgetContent extends ConsumerWidget {
   final RenderSpec renderSpec;
   final Future<void> Function(String, String, Map<String, dynamic>)?
   onOperation;
+  final Future<void> Function(
+    String,
+    String,
+    String,
+    List<Map<String, dynamic>>,
+  )?
+  onBatchOperation;
   final Future<void> Function()? onSync;
 
   const _ReactiveQueryWidgetContent({
@@ -170,6 +207,7 @@ class _ReactiveQueryWidgetContent extends ConsumerWidget {
     required this.queryParams,
     required this.renderSpec,
     this.onOperation,
+    this.onBatchOperation,
     this.onSync,
   });
 
@@ -201,10 +239,28 @@ class _ReactiveQueryWidgetContent extends ConsumerWidget {
 
     // If root is a list(), outline(), or tree() function, build appropriate view
     return rootExpr.when(
-      functionCall: (name, args, _) {
+      functionCall: (name, args, operations) {
         if (name == 'list') {
           return _buildListView(ref, queryState, args, interpreter, colors);
         }
+        if (name == 'board') {
+          return _buildBoardView(
+            queryState,
+            args,
+            operations,
+            interpreter,
+            colors,
+          );
+        }
+        if (name == 'calendar') {
+          return _buildCalendarView(
+            queryState,
+            args,
+            operations,
+            interpreter,
+            colors,
+          );
+        }
         if (name == 'outline') {
           return _buildOutlineView(ref, queryState, args, interpreter, colors);
         }
@@ -223,6 +279,7 @@ class _ReactiveQueryWidgetContent extends ConsumerWidget {
       array: (_) => _buildSingleItem(queryState, rootExpr, interpreter, colors),
       object: (_) =>
           _buildSingleItem(queryState, rootExpr, interpreter, colors),
+      style: (_) => _buildSingleItem(queryState, rootExpr, interpreter, colors),
     );
   }
 
@@ -279,11 +336,19 @@ class _ReactiveQueryWidgetContent extends ConsumerWidget {
         )
         .value;
 
-    return ListView.builder(
+    final selection = renderSpec.selection;
+    final selectedIds = selection != null
+        ? ref.watch(selectedRowIdsProvider(queryKey))
+        : const <String>{};
+
+    final rowIds = _orderedRowIds(queryState);
+    final group = renderSpec.groupBy;
+
+    final listView = ListView.builder(
       padding: const EdgeInsets.symmetric(horizontal: 16, vertical: 8),
-      itemCount: queryState.rowOrder.length,
+      itemCount: rowIds.length,
       itemBuilder: (context, index) {
-        final rowId = queryState.rowOrder[index];
+        final rowId = rowIds[index];
         final rowData = queryState.rowCache[rowId];
         if (rowData == null) {
           return const SizedBox.shrink();
@@ -301,7 +366,7 @@ class _ReactiveQueryWidgetContent extends ConsumerWidget {
         // Get previous row data for operations that need context
         Map<String, dynamic>? previousRowData;
         if (index > 0) {
-          final previousRowId = queryState.rowOrder[index - 1];
+          final previousRowId = rowIds[index - 1];
           previousRowData = queryState.rowCache[previousRowId];
         }
 
@@ -314,22 +379,609 @@ class _ReactiveQueryWidgetContent extends ConsumerWidget {
           colors: colors,
         );
 
-        return KeyedSubtree(
-          key: key,
-          child: MouseRegion(
-            cursor: SystemMouseCursors.text,
-            child: Container(
-              padding: const EdgeInsets.symmetric(vertical: 2),
-              decoration: BoxDecoration(
-                borderRadius: BorderRadius.circular(4),
-                color: Colors.transparent,
+        Widget item = interpreter.build(itemExpr, renderContext);
+        final selectionId = selection != null
+            ? rowData[selection.idColumn]?.toString()
+            : null;
+        if (selection != null &&
+            selection.checkboxColumn &&
+            selectionId != null) {
+          item = Row(
+            crossAxisAlignment: CrossAxisAlignment.start,
+            children: [
+              Checkbox(
+                value: selectedIds.contains(selectionId),
+                onChanged: (_) => ref
+                    .read(selectedRowIdsProvider(queryKey).notifier)
+                    .toggle(selectionId),
               ),
-              child: interpreter.build(itemExpr, renderContext),
+              Expanded(child: item),
+            ],
+          );
+        }
+
+        Widget entry = MouseRegion(
+          cursor: SystemMouseCursors.text,
+          child: Container(
+            padding: const EdgeInsets.symmetric(vertical: 2),
+            decoration: BoxDecoration(
+              borderRadius: BorderRadius.circular(4),
+              color: Colors.transparent,
             ),
+            child: item,
           ),
         );
+
+        // The first row of each group gets the group's header
+        if (group != null &&
+            (previousRowData == null ||
+                previousRowData[group.column] != rowData[group.column])) {
+          final label = rowData[group.labelColumn ?? group.column];
+          entry = Column(
+            crossAxisAlignment: CrossAxisAlignment.stretch,
+            children: [
+              Padding(
+                padding: EdgeInsets.only(top: index > 0 ? 16 : 0, bottom: 4),
+                child: Text(
+                  label?.toString() ?? '(none)',
+                  style: TextStyle(
+                    fontSize: 13,
+                    fontWeight: FontWeight.w600,
+                    color: colors.textSecondary,
+                  ),
+                ),
+              ),
+              entry,
+            ],
+          );
+        }
+
+        return KeyedSubtree(key: key, child: entry);
       },
     );
+
+    if (selection == null) {
+      return listView;
+    }
+
+    final selectionNotifier = ref.read(
+      selectedRowIdsProvider(queryKey).notifier,
+    );
+    final allIds = queryState.rowOrder
+        .map((rowId) => queryState.rowCache[rowId]?[selection.idColumn])
+        .whereType<Object>()
+        .map((id) => id.toString())
+        .toList();
+
+    return Column(
+      children: [
+        SelectionActionBar(
+          selection: selection,
+          selectedCount: selectedIds.length,
+          totalCount: allIds.length,
+          onToggleAll: () => selectionNotifier.toggleAll(allIds),
+          onClear: selectionNotifier.clear,
+          actions: _batchActions(
+            queryState,
+            itemExpr,
+            selection,
+            selectedIds,
+            selectionNotifier.clear,
+          ),
+          colors: colors,
+        ),
+        Expanded(child: listView),
+      ],
+    );
+  }
+
+  /// Build a kanban board: one column per lane, cards dragged between lanes.
+  ///
+  /// Declared `lanes:` come first, even when empty; other values of the lane
+  /// column follow in row order. Dropping a card on another lane runs the
+  /// operation [_setColumn] picks.
+  Widget _buildBoardView(
+    ReactiveQueryState queryState,
+    List<Arg> boardArgs,
+    List<OperationWiring> operations,
+    RenderInterpreter interpreter,
+    AppColors colors,
+  ) {
+    final board = renderSpec.board;
+    if (board == null) {
+      throw ArgumentError('board() requires a "group_by" argument');
+    }
+    final itemExpr = boardArgs
+        .firstWhere(
+          (arg) => arg.name == 'item_template',
+          orElse: () =>
+              throw ArgumentError('board() requires "item_template" argument'),
+        )
+        .value;
+
+    final rowIds = _orderedRowIds(queryState);
+    final lanes = board.lanes.map(valueToDynamic).toList();
+    for (final rowId in rowIds) {
+      final lane = queryState.rowCache[rowId]?[board.column];
+      if (!lanes.contains(lane)) {
+        lanes.add(lane);
+      }
+    }
+    final labelColumn = renderSpec.groupBy?.labelColumn;
+
+    Widget buildCard(Map<String, dynamic> rowData, int index) {
+      return Card(
+        margin: const EdgeInsets.only(bottom: 8),
+        child: Padding(
+          padding: const EdgeInsets.all(8),
+          child: interpreter.build(
+            itemExpr,
+            RenderContext(
+              rowData: rowData,
+              rowTemplates: renderSpec.rowTemplates,
+              onOperation: onOperation,
+              rowIndex: index,
+              colors: colors,
+            ),
+          ),
+        ),
+      );
+    }
+
+    Widget buildLane(dynamic lane) {
+      final cardIds = rowIds
+          .where((rowId) => queryState.rowCache[rowId]?[board.column] == lane)
+          .toList();
+      final firstRow = cardIds.isEmpty
+          ? null
+          : queryState.rowCache[cardIds.first];
+      final title = labelColumn != null && firstRow != null
+          ? firstRow[labelColumn]
+          : lane;
+
+      return DragTarget<String>(
+        onWillAcceptWithDetails: (details) =>
+            queryState.rowCache[details.data]?[board.column] != lane,
+        onAcceptWithDetails: (details) =>
+            _setColumn(
+              operations,
+              source: board.column,
+              column: board.column,
+              rowId: details.data,
+              value: lane,
+            ),
+        builder: (context, candidates, _) => Container(
+          width: 280,
+          margin: const EdgeInsets.only(right: 12),
+          padding: const EdgeInsets.all(8),
+          decoration: BoxDecoration(
+            color: colors.backgroundSecondary,
+            borderRadius: BorderRadius.circular(8),
+            border: Border.all(
+              color: candidates.isNotEmpty ? colors.borderFocus : colors.border,
+            ),
+          ),
+          child: Column(
+            crossAxisAlignment: CrossAxisAlignment.stretch,
+            children: [
+              Padding(
+                padding: const EdgeInsets.only(bottom: 8),
+                child: Text(
+                  '${title ?? '(none)'} (${cardIds.length})',
+                  style: TextStyle(
+                    fontSize: 13,
+                    fontWeight: FontWeight.w600,
+                    color: colors.textSecondary,
+                  ),
+                ),
+              ),
+              Expanded(
+                child: ListView.builder(
+                  itemCount: cardIds.length,
+                  itemBuilder: (context, index) {
+                    final rowId = cardIds[index];
+                    final rowData = queryState.rowCache[rowId];
+                    if (rowData == null) {
+                      return const SizedBox.shrink();
+                    }
+                    final card = buildCard(rowData, index);
+                    return LongPressDraggable<String>(
+                      key: ValueKey(rowId),
+                      data: rowId,
+                      feedback: SizedBox(
+                        width: 264,
+                        child: Material(elevation: 4, child: card),
+                      ),
+                      childWhenDragging: Opacity(opacity: 0.3, child: card),
+                      child: card,
+                    );
+                  },
+                ),
+              ),
+            ],
+          ),
+        ),
+      );
+    }
+
+    return ListView(
+      scrollDirection: Axis.horizontal,
+      padding: const EdgeInsets.all(16),
+      children: lanes.map(buildLane).toList(),
+    );
+  }
+
+  /// Build a calendar: rows in day/week/month buckets of their date column.
+  ///
+  /// Buckets are in the device's time zone, in chronological order; rows
+  /// without a date come last. Dropping a row on a bucket moves it to the
+  /// bucket's first day via the operation [_setColumn] picks (e.g.
+  /// `set_due_date`, triggered by `calendar_day`).
+  Widget _buildCalendarView(
+    ReactiveQueryState queryState,
+    List<Arg> calendarArgs,
+    List<OperationWiring> operations,
+    RenderInterpreter interpreter,
+    AppColors colors,
+  ) {
+    final calendar = renderSpec.calendar;
+    if (calendar == null) {
+      throw ArgumentError('calendar() requires a "date" argument');
+    }
+    final itemExpr = calendarArgs
+        .firstWhere(
+          (arg) => arg.name == 'item_template',
+          orElse: () => throw ArgumentError(
+            'calendar() requires "item_template" argument',
+          ),
+        )
+        .value;
+
+    DateTime? bucketOf(Map<String, dynamic>? row) {
+      final value = row?[calendar.column];
+      final date = value is String ? DateTime.tryParse(value)?.toLocal() : null;
+      if (date == null) {
+        return null;
+      }
+      return switch (calendar.period) {
+        CalendarPeriod.day => DateTime(date.year, date.month, date.day),
+        CalendarPeriod.week => DateTime(
+          date.year,
+          date.month,
+          date.day - (date.weekday - DateTime.monday),
+        ),
+        CalendarPeriod.month => DateTime(date.year, date.month),
+      };
+    }
+
+    String titleOf(DateTime? start) {
+      if (start == null) {
+        return 'No date';
+      }
+      return switch (calendar.period) {
+        CalendarPeriod.day => DateFormat.yMMMMEEEEd().format(start),
+        CalendarPeriod.week => 'Week of ${DateFormat.yMMMd().format(start)}',
+        CalendarPeriod.month => DateFormat.yMMMM().format(start),
+      };
+    }
+
+    final buckets = <DateTime?, List<String>>{};
+    for (final rowId in _orderedRowIds(queryState)) {
+      buckets
+          .putIfAbsent(bucketOf(queryState.rowCache[rowId]), () => [])
+          .add(rowId);
+    }
+    final starts = buckets.keys.whereType<DateTime>().toList()..sort();
+    if (buckets.containsKey(null)) {
+      starts.add(null);
+    }
+
+    Widget buildItem(String rowId, Map<String, dynamic> rowData, int index) {
+      final item = interpreter.build(
+        itemExpr,
+        RenderContext(
+          rowData: rowData,
+          rowTemplates: renderSpec.rowTemplates,
+          onOperation: onOperation,
+          rowIndex: index,
+          colors: colors,
+        ),
+      );
+      return LongPressDraggable<String>(
+        key: ValueKey(rowId),
+        data: rowId,
+        feedback: Material(
+          elevation: 4,
+          child: ConstrainedBox(
+            constraints: const BoxConstraints(maxWidth: 400),
+            child: item,
+          ),
+        ),
+        childWhenDragging: Opacity(opacity: 0.3, child: item),
+        child: item,
+      );
+    }
+
+    Widget buildBucket(DateTime? start) {
+      final rowIds = buckets[start]!;
+      // Dropping on "No date" clears the date
+      final day = start?.toUtc().toIso8601String();
+
+      return DragTarget<String>(
+        onWillAcceptWithDetails: (details) =>
+            bucketOf(queryState.rowCache[details.data]) != start,
+        onAcceptWithDetails: (details) => _setColumn(
+          operations,
+          source: 'calendar_day', // CALENDAR_DAY_PARAM in holon_api
+          column: calendar.column,
+          rowId: details.data,
+          value: day,
+        ),
+        builder: (context, candidates, _) => Container(
+          margin: const EdgeInsets.only(bottom: 12),
+          padding: const EdgeInsets.all(8),
+          decoration: BoxDecoration(
+            borderRadius: BorderRadius.circular(8),
+            border: Border.all(
+              color: candidates.isNotEmpty
+                  ? colors.borderFocus
+                  : Colors.transparent,
+            ),
+          ),
+          child: Column(
+            crossAxisAlignment: CrossAxisAlignment.stretch,
+            children: [
+              Padding(
+                padding: const EdgeInsets.only(bottom: 4),
+                child: Text(
+                  titleOf(start),
+                  style: TextStyle(
+                    fontSize: 13,
+                    fontWeight: FontWeight.w600,
+                    color: colors.textSecondary,
+                  ),
+                ),
+              ),
+              for (final (index, rowId) in rowIds.indexed)
+                if (queryState.rowCache[rowId] case final rowData?)
+                  buildItem(rowId, rowData, index),
+            ],
+          ),
+        ),
+      );
+    }
+
+    return ListView(
+      padding: const EdgeInsets.symmetric(horizontal: 16, vertical: 8),
+      children: starts.map(buildBucket).toList(),
+    );
+  }
+
+  /// Set [column] of row [rowId] to [value] for a board or calendar move, like
+  /// `BoardSpec::move_operation` and `CalendarSpec::drop_operation` in Rust.
+  ///
+  /// Prefers operations with a param mapping from [source] (the lane column of
+  /// a board, `calendar_day` for calendars) or taking [column] as a parameter
+  /// (e.g. `set_status`), falling back to `set_field`.
+  Future<void> _setColumn(
+    List<OperationWiring> operations, {
+    required String source,
+    required String column,
+    required String rowId,
+    required dynamic value,
+  }) async {
+    final callback = onOperation;
+    if (callback == null) {
+      return;
+    }
+    final descriptors = operations.map((op) => op.descriptor).toList();
+    final candidates = descriptors
+        .where(
+          (op) =>
+              op.paramMappings.any(
+                (m) =>
+                    m.from == source &&
+                    (source == column || m.provides.contains(column)),
+              ) ||
+              op.requiredParams.any((p) => p.name == column),
+        )
+        .toList();
+    final match = OperationMatcher.findBestMatch(candidates, {
+      'id': rowId,
+      source: value,
+      column: value,
+    });
+    if (match != null && match.isFullySatisfied) {
+      await callback(
+        match.entityName,
+        match.operationName,
+        match.resolvedParams,
+      );
+      return;
+    }
+
+    final setField = descriptors.where((op) => op.name == 'set_field');
+    if (setField.isEmpty) {
+      debugPrint('[ReactiveQueryWidget] No operation sets $column');
+      return;
+    }
+    await callback(setField.first.entityName, 'set_field', {
+      setField.first.idColumn: rowId,
+      'field': column,
+      'value': value,
+    });
+  }
+
+  /// Row IDs in the order declared by `sort_by:`/`group_by:`.
+  ///
+  /// Watched queries can't be sorted in SQL, so the rows are ordered here: by
+  /// group first, then by the sort keys, keeping the query's order for ties.
+  List<String> _orderedRowIds(ReactiveQueryState queryState) {
+    final group = renderSpec.groupBy;
+    final keys = [
+      if (group != null)
+        SortKey(column: group.column, descending: group.descending),
+      ...renderSpec.sort,
+    ];
+    if (keys.isEmpty) {
+      return queryState.rowOrder;
+    }
+
+    int compareValues(Object? a, Object? b) {
+      if (a == null || b == null) {
+        // NULL sorts before any value
+        return a == null ? (b == null ? 0 : -1) : 1;
+      }
+      if (a is num && b is num) return a.compareTo(b);
+      if (a is bool && b is bool) return a == b ? 0 : (a ? 1 : -1);
+      return a.toString().compareTo(b.toString());
+    }
+
+    final indexed = queryState.rowOrder.indexed.toList();
+    indexed.sort((a, b) {
+      final rowA = queryState.rowCache[a.$2];
+      final rowB = queryState.rowCache[b.$2];
+      for (final key in keys) {
+        final cmp = compareValues(rowA?[key.column], rowB?[key.column]);
+        if (cmp != 0) return key.descending ? -cmp : cmp;
+      }
+      return a.$1.compareTo(b.$1);
+    });
+    return indexed.map((entry) => entry.$2).toList();
+  }
+
+  /// Batch actions for the selected rows: "Complete all" for the item's checkbox,
+  /// plus every wired operation that accepts multiple IDs.
+  List<BatchAction> _batchActions(
+    ReactiveQueryState queryState,
+    RenderExpr itemExpr,
+    SelectionSpec selection,
+    Set<String> selectedIds,
+    VoidCallback onDone,
+  ) {
+    final batch = onBatchOperation;
+    if (batch == null || selectedIds.isEmpty) {
+      return const [];
+    }
+
+    // Selected rows in list order, with the previous row of each (for indent)
+    final rows = <Map<String, dynamic>>[];
+    final rowIndices = <int>[];
+    for (var i = 0; i < queryState.rowOrder.length; i++) {
+      final row = queryState.rowCache[queryState.rowOrder[i]];
+      if (row != null &&
+          selectedIds.contains(row[selection.idColumn]?.toString())) {
+        rows.add(row);
+        rowIndices.add(i);
+      }
+    }
+    if (rows.isEmpty) {
+      return const [];
+    }
+
+    final operations = findBatchOperations(itemExpr);
+    final fallbackEntity = operations.isNotEmpty
+        ? operations.first.descriptor.entityName
+        : _extractEntityName();
+    String? entityOf(Map<String, dynamic> row) =>
+        row['entity_name']?.toString() ?? fallbackEntity;
+
+    // Rows of UNION queries can belong to different entities: one batch each
+    Future<void> dispatch(
+      String opName,
+      String displayName,
+      List<Map<String, dynamic>> params,
+    ) async {
+      final byEntity = <String, List<Map<String, dynamic>>>{};
+      for (var i = 0; i < params.length; i++) {
+        final entityName = entityOf(rows[i]);
+        if (entityName == null) continue;
+        byEntity.putIfAbsent(entityName, () => []).add(params[i]);
+      }
+      for (final entry in byEntity.entries) {
+        await batch(entry.key, opName, displayName, entry.value);
+      }
+      onDone();
+    }
+
+    final actions = <BatchAction>[];
+
+    final checkboxField = findCheckboxField(itemExpr);
+    if (checkboxField != null) {
+      final allDone = rows.every(
+        (row) => row[checkboxField] == true || row[checkboxField] == 1,
+      );
+      actions.add(
+        BatchAction(
+          label: allDone ? 'Reopen all' : 'Complete all',
+          icon: allDone ? Icons.undo : Icons.done_all,
+          onPressed: () => dispatch(
+            'set_field',
+            allDone ? 'Reopen all' : 'Complete all',
+            [
+              for (final row in rows)
+                {
+                  'id': row['id'].toString(),
+                  'field': checkboxField,
+                  'value': !allDone,
+                },
+            ],
+          ),
+        ),
+      );
+    }
+
+    for (final op in operations) {
+      final descriptor = op.descriptor;
+      actions.add(
+        BatchAction(
+          label: '${descriptor.displayName} all',
+          icon: Icons.playlist_play,
+          onPressed: () => dispatch(
+            descriptor.name,
+            '${descriptor.displayName} (${rows.length})',
+            [
+              for (var i = 0; i < rows.length; i++)
+                descriptor.name == 'indent'
+                    ? {
+                        ...rows[i],
+                        'parent_id': _indentParent(
+                          queryState,
+                          rowIndices[i],
+                          selection,
+                          selectedIds,
+                        ),
+                      }
+                    : Map<String, dynamic>.from(rows[i]),
+            ],
+          ),
+        ),
+      );
+    }
+
+    return actions;
+  }
+
+  /// New parent of a selected row when indenting the selection: the closest
+  /// previous sibling that is not selected itself, so consecutive selected
+  /// siblings end up under the same parent.
+  String? _indentParent(
+    ReactiveQueryState queryState,
+    int index,
+    SelectionSpec selection,
+    Set<String> selectedIds,
+  ) {
+    final row = queryState.rowCache[queryState.rowOrder[index]]!;
+    for (var i = index - 1; i >= 0; i--) {
+      final previous = queryState.rowCache[queryState.rowOrder[i]];
+      if (previous == null || previous['parent_id'] != row['parent_id']) {
+        continue;
+      }
+      if (!selectedIds.contains(previous[selection.idColumn]?.toString())) {
+        return previous['id']?.toString();
+      }
+    }
+    return null;
   }
 
   /// Build AnimatedTreeView from tree() function.
@@ -503,6 +1155,7 @@ class _ReactiveQueryWidgetContent extends ConsumerWidget {
           throw ArgumentError('Column name cannot be a binary operation'),
       array: (_) => throw ArgumentError('Column name cannot be an array'),
       object: (_) => throw ArgumentError('Column name cannot be an object'),
+      style: (_) => throw ArgumentError('Column name cannot be a style'),
     );
   }
 
@@ -523,6 +1176,7 @@ class _ReactiveQueryWidgetContent extends ConsumerWidget {
       binaryOp: (_, __, ___) => null,
       array: (_) => null,
       object: (_) => null,
+      style: (_) => null,
     );
   }
 
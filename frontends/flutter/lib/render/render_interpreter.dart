@@ -1,3 +1,5 @@
+import 'dart:convert';
+
 import 'package:flutter/material.dart';
 import 'package:pie_menu/pie_menu.dart';
 import 'package:outliner_view/outliner_view.dart';
@@ -115,6 +117,8 @@ class RenderInterpreter {
       binaryOp: (op, left, right) => _buildBinaryOp(op, left, right, context),
       array: (items) => _buildArray(items, context),
       object: (fields) => _buildObject(fields, context),
+      // Styles are arguments of widgets, applied by the widget they belong to
+      style: (_) => const SizedBox.shrink(),
     );
   }
 
@@ -170,6 +174,27 @@ class RenderInterpreter {
       focusDepth: context.focusDepth,
     );
 
+    final style = _resolveStyle(namedArgs['style'], enrichedContext);
+    final widget = _buildWidget(
+      name,
+      args,
+      namedArgs,
+      positionalArgs,
+      style,
+      enrichedContext,
+    );
+    return style?.wrap(widget) ?? widget;
+  }
+
+  /// Build the widget for function [name] (see [_buildFunctionCall]).
+  Widget _buildWidget(
+    String name,
+    List<Arg> args,
+    Map<String, RenderExpr> namedArgs,
+    List<RenderExpr> positionalArgs,
+    _ResolvedStyle? style,
+    RenderContext enrichedContext,
+  ) {
     switch (name) {
       case 'list':
         return _buildList(namedArgs, enrichedContext);
@@ -184,7 +209,7 @@ class RenderInterpreter {
       case 'editable_text':
         return _buildEditableText(namedArgs, enrichedContext);
       case 'text':
-        return _buildText(namedArgs, positionalArgs, enrichedContext);
+        return _buildText(namedArgs, positionalArgs, style, enrichedContext);
       case 'drop_zone':
         return _buildDropZone(namedArgs, enrichedContext);
       case 'collapse_button':
@@ -196,7 +221,11 @@ class RenderInterpreter {
       case 'checkbox':
         return _buildCheckbox(namedArgs, enrichedContext);
       case 'badge':
-        return _buildBadge(namedArgs, enrichedContext);
+        return _buildBadge(namedArgs, style, enrichedContext);
+      case 'attachment_chip':
+        return _buildAttachmentChip(namedArgs, style, enrichedContext);
+      case 'comments':
+        return _buildComments(style, enrichedContext);
       case 'bullet':
         return _buildBullet(namedArgs, positionalArgs, enrichedContext);
       case 'pie_menu':
@@ -255,6 +284,21 @@ class RenderInterpreter {
     }
   }
 
+  /// Resolve the `style:` argument of a widget for the current row.
+  ///
+  /// Rules apply in order, later ones overriding earlier ones; a rule with a
+  /// `when` condition only applies if it is true for the row.
+  _ResolvedStyle? _resolveStyle(RenderExpr? styleExpr, RenderContext context) {
+    if (styleExpr is! RenderExpr_Style) return null;
+    final style = _ResolvedStyle();
+    for (final rule in styleExpr.rules) {
+      final condition = rule.when;
+      if (condition != null && !_evaluateToBool(condition, context)) continue;
+      style.merge(rule.style);
+    }
+    return style;
+  }
+
   /// Automatically attach pie menu to a widget based on field interests.
   ///
   /// If operations are available that affect any of the specified fields,
@@ -813,6 +857,7 @@ class RenderInterpreter {
   Widget _buildText(
     Map<String, RenderExpr> namedArgs,
     List<RenderExpr> positionalArgs,
+    _ResolvedStyle? style,
     RenderContext context,
   ) {
     String text;
@@ -824,15 +869,13 @@ class RenderInterpreter {
       text = '';
     }
 
-    return Text(
-      text,
-      style: TextStyle(
-        fontSize: 16,
-        height: 1.5,
-        color: context.colors.textSecondary,
-        letterSpacing: 0,
-      ),
+    final textStyle = TextStyle(
+      fontSize: 16,
+      height: 1.5,
+      color: context.colors.textSecondary,
+      letterSpacing: 0,
     );
+    return Text(text, style: style?.applyTo(textStyle) ?? textStyle);
   }
 
   /// Build Spacer widget from spacer() function.
@@ -1212,7 +1255,11 @@ class RenderInterpreter {
 
   /// Build Badge/Chip widget from badge() function.
   /// LogSeq-style: more subtle badges.
-  Widget _buildBadge(Map<String, RenderExpr> args, RenderContext context) {
+  Widget _buildBadge(
+    Map<String, RenderExpr> args,
+    _ResolvedStyle? style,
+    RenderContext context,
+  ) {
     final contentExpr = args['content'];
     final content = contentExpr != null
         ? _evaluateToString(contentExpr, context)
@@ -1235,6 +1282,13 @@ class RenderInterpreter {
         _ => null,
       };
     }
+    badgeColor = style?.color ?? badgeColor;
+    final textStyle = TextStyle(
+      fontSize: 11,
+      color: badgeColor ?? context.colors.textSecondary,
+      fontWeight: FontWeight.w500,
+      letterSpacing: 0.2,
+    );
 
     return Container(
       padding: const EdgeInsets.symmetric(horizontal: 6, vertical: 2),
@@ -1246,12 +1300,122 @@ class RenderInterpreter {
       ),
       child: Text(
         content,
-        style: TextStyle(
-          fontSize: 11,
-          color: badgeColor ?? context.colors.textSecondary,
-          fontWeight: FontWeight.w500,
-          letterSpacing: 0.2,
-        ),
+        style: style?.applyTo(textStyle) ?? textStyle,
+      ),
+    );
+  }
+
+  /// Build attachment chip from attachment_chip() function.
+  /// Shows the file name and, when known, its size (e.g. "report.pdf · 1.5 KB").
+  Widget _buildAttachmentChip(
+    Map<String, RenderExpr> args,
+    _ResolvedStyle? style,
+    RenderContext context,
+  ) {
+    final nameExpr = args['name'];
+    final name = nameExpr != null ? _evaluateToString(nameExpr, context) : '';
+
+    final sizeExpr = args['size'];
+    final size = sizeExpr != null ? _evaluateGeneric(sizeExpr, context) : null;
+    final label = size is num ? '$name · ${_formatSize(size.toInt())}' : name;
+
+    final color = style?.color ?? context.colors.textSecondary;
+    final textStyle = TextStyle(
+      fontSize: 11,
+      color: color,
+      fontWeight: FontWeight.w500,
+      letterSpacing: 0.2,
+    );
+
+    return Container(
+      padding: const EdgeInsets.symmetric(horizontal: 6, vertical: 2),
+      decoration: BoxDecoration(
+        color: color.withValues(alpha: 0.1),
+        borderRadius: BorderRadius.circular(4),
+      ),
+      child: Row(
+        mainAxisSize: MainAxisSize.min,
+        children: [
+          Icon(Icons.attach_file, size: 12, color: color),
+          const SizedBox(width: 2),
+          Flexible(
+            child: Text(
+              label,
+              overflow: TextOverflow.ellipsis,
+              style: style?.applyTo(textStyle) ?? textStyle,
+            ),
+          ),
+        ],
+      ),
+    );
+  }
+
+  /// Format a file size like the backend's format_size (512 B, 1.5 KB, 12.0 MB).
+  String _formatSize(int bytes) {
+    if (bytes < 1024) return '${bytes < 0 ? 0 : bytes} B';
+    const units = ['KB', 'MB', 'GB', 'TB'];
+    var size = bytes / 1024;
+    var unit = 0;
+    while (size >= 1024 && unit < units.length - 1) {
+      size /= 1024;
+      unit++;
+    }
+    return '${size.toStringAsFixed(1)} ${units[unit]}';
+  }
+
+  /// Build the comments of a row from comments() function.
+  /// The backend denormalizes them into the row's `_comments` column (a JSON array,
+  /// oldest first); they are shown as a nested list, resolved ones struck through.
+  Widget _buildComments(_ResolvedStyle? style, RenderContext context) {
+    final json = context.rowData['_comments'];
+    final comments = json is String
+        ? (jsonDecode(json) as List).cast<Map<String, dynamic>>()
+        : const <Map<String, dynamic>>[];
+    if (comments.isEmpty) {
+      return const SizedBox.shrink();
+    }
+
+    final color = style?.color ?? context.colors.textSecondary;
+    final textStyle = TextStyle(fontSize: 12, color: color);
+    return Padding(
+      padding: const EdgeInsets.only(left: 16, top: 2, bottom: 2),
+      child: Column(
+        crossAxisAlignment: CrossAxisAlignment.start,
+        mainAxisSize: MainAxisSize.min,
+        children: [
+          for (final comment in comments)
+            Row(
+              crossAxisAlignment: CrossAxisAlignment.start,
+              children: [
+                Icon(
+                  comment['resolved'] == true
+                      ? Icons.check_circle_outline
+                      : Icons.chat_bubble_outline,
+                  size: 12,
+                  color: color,
+                ),
+                const SizedBox(width: 4),
+                Flexible(
+                  child: Text.rich(
+                    TextSpan(
+                      children: [
+                        TextSpan(
+                          text: '${comment['author']}: ',
+                          style: const TextStyle(fontWeight: FontWeight.w600),
+                        ),
+                        TextSpan(text: comment['text']?.toString() ?? ''),
+                      ],
+                    ),
+                    style: (style?.applyTo(textStyle) ?? textStyle).copyWith(
+                      decoration: comment['resolved'] == true
+                          ? TextDecoration.lineThrough
+                          : null,
+                    ),
+                  ),
+                ),
+              ],
+            ),
+        ],
       ),
     );
   }
@@ -1449,6 +1613,7 @@ class RenderInterpreter {
           throw ArgumentError('Cannot evaluate function call to int'),
       array: (_) => throw ArgumentError('Cannot evaluate array to int'),
       object: (_) => throw ArgumentError('Cannot evaluate object to int'),
+      style: (_) => throw ArgumentError('Cannot evaluate style to int'),
     );
   }
 
@@ -1479,6 +1644,7 @@ class RenderInterpreter {
           throw ArgumentError('Cannot evaluate function call to string'),
       array: (_) => throw ArgumentError('Cannot evaluate array to string'),
       object: (_) => throw ArgumentError('Cannot evaluate object to string'),
+      style: (_) => throw ArgumentError('Cannot evaluate style to string'),
     );
   }
 
@@ -1520,6 +1686,7 @@ class RenderInterpreter {
           throw ArgumentError('Cannot evaluate function call to bool'),
       array: (_) => throw ArgumentError('Cannot evaluate array to bool'),
       object: (_) => throw ArgumentError('Cannot evaluate object to bool'),
+      style: (_) => throw ArgumentError('Cannot evaluate style to bool'),
     );
   }
 
@@ -1597,6 +1764,7 @@ class RenderInterpreter {
           throw ArgumentError('Cannot evaluate function call to num'),
       array: (_) => throw ArgumentError('Cannot evaluate array to num'),
       object: (_) => throw ArgumentError('Cannot evaluate object to num'),
+      style: (_) => throw ArgumentError('Cannot evaluate style to num'),
     );
   }
 
@@ -1614,6 +1782,7 @@ class RenderInterpreter {
       object: (fields) => fields.map(
         (key, value) => MapEntry(key, _evaluateGeneric(value, context)),
       ),
+      style: (_) => throw ArgumentError('Cannot evaluate style generically'),
     );
   }
 
@@ -2448,3 +2617,86 @@ class _PulseWidgetState extends State<_PulseWidget>
     return FadeTransition(opacity: _animation, child: widget.child);
   }
 }
+
+/// The `style:` argument of a widget, resolved for one row.
+///
+/// Colors are names from holon_api's `NAMED_COLORS` or `#rrggbb`, so a style
+/// looks the same here as in the TUI. Padding is in spacing units of 4px.
+class _ResolvedStyle {
+  static const _namedColors = {
+    'red': Color(0xFFEF4444),
+    'orange': Color(0xFFF59E0B),
+    'yellow': Color(0xFFEAB308),
+    'green': Color(0xFF10B981),
+    'cyan': Color(0xFF06B6D4),
+    'blue': Color(0xFF3B82F6),
+    'purple': Color(0xFF8B5CF6),
+    'gray': Color(0xFF6B7280),
+    'grey': Color(0xFF6B7280),
+    'white': Color(0xFFFFFFFF),
+    'black': Color(0xFF000000),
+  };
+
+  Color? color;
+  Color? background;
+  bool bold = false;
+  bool italic = false;
+  bool underline = false;
+  bool strikethrough = false;
+  bool dim = false;
+  int padding = 0;
+
+  /// Apply the attributes set in [style] on top of this one.
+  void merge(Style style) {
+    color = _parseColor(style.color) ?? color;
+    background = _parseColor(style.background) ?? background;
+    bold = style.bold ?? bold;
+    italic = style.italic ?? italic;
+    underline = style.underline ?? underline;
+    strikethrough = style.strikethrough ?? strikethrough;
+    dim = style.dim ?? dim;
+    padding = style.padding ?? padding;
+  }
+
+  static Color? _parseColor(String? color) {
+    if (color == null) return null;
+    if (color.startsWith('#') && color.length == 7) {
+      final rgb = int.tryParse(color.substring(1), radix: 16);
+      return rgb != null ? Color(0xFF000000 | rgb) : null;
+    }
+    return _namedColors[color.toLowerCase()];
+  }
+
+  /// Text style of a widget with these attributes on top of [base].
+  TextStyle applyTo(TextStyle base) {
+    final textColor = color ?? base.color;
+    return base.copyWith(
+      color: dim ? textColor?.withValues(alpha: 0.5) : textColor,
+      fontWeight: bold ? FontWeight.bold : null,
+      fontStyle: italic ? FontStyle.italic : null,
+      decoration: underline || strikethrough
+          ? TextDecoration.combine([
+              if (underline) TextDecoration.underline,
+              if (strikethrough) TextDecoration.lineThrough,
+            ])
+          : null,
+    );
+  }
+
+  /// Wrap [child] with the background and padding, and make the text
+  /// attributes the default for text inside it.
+  Widget wrap(Widget child) {
+    Widget result = DefaultTextStyle.merge(
+      style: applyTo(const TextStyle()),
+      child: child,
+    );
+    if (background != null || padding > 0) {
+      result = Container(
+        padding: EdgeInsets.all(padding * 4.0),
+        color: background,
+        child: result,
+      );
+    }
+    return result;
+  }
+}
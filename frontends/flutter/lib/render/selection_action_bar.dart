@@ -0,0 +1,129 @@
+import 'package:flutter/material.dart';
+import '../src/rust/third_party/holon_api/render_types.dart';
+import '../styles/app_styles.dart';
+
+/// An action applied to all selected rows at once.
+class BatchAction {
+  final String label;
+  final IconData icon;
+  final VoidCallback onPressed;
+
+  const BatchAction({
+    required this.label,
+    required this.icon,
+    required this.onPressed,
+  });
+}
+
+/// Header of a selectable list: select-all toggle, selection count and batch actions.
+///
+/// Shown above lists whose RenderSpec declares a `selection` (`selectable:true`).
+class SelectionActionBar extends StatelessWidget {
+  final SelectionSpec selection;
+  final int selectedCount;
+  final int totalCount;
+  final VoidCallback onToggleAll;
+  final VoidCallback onClear;
+  final List<BatchAction> actions;
+  final AppColors colors;
+
+  const SelectionActionBar({
+    super.key,
+    required this.selection,
+    required this.selectedCount,
+    required this.totalCount,
+    required this.onToggleAll,
+    required this.onClear,
+    required this.actions,
+    required this.colors,
+  });
+
+  @override
+  Widget build(BuildContext context) {
+    final allSelected = totalCount > 0 && selectedCount == totalCount;
+
+    return Padding(
+      padding: const EdgeInsets.symmetric(horizontal: 16, vertical: 4),
+      child: Row(
+        children: [
+          if (selection.selectAll)
+            Checkbox(
+              // Tristate: partially selected lists show a dash
+              tristate: true,
+              value: allSelected ? true : (selectedCount == 0 ? false : null),
+              onChanged: (_) => onToggleAll(),
+            ),
+          Text(
+            selectedCount == 0 ? 'None selected' : '$selectedCount selected',
+            style: TextStyle(fontSize: 13, color: colors.textSecondary),
+          ),
+          const Spacer(),
+          if (selectedCount > 0) ...[
+            for (final action in actions)
+              TextButton.icon(
+                onPressed: action.onPressed,
+                icon: Icon(action.icon, size: 16),
+                label: Text(action.label),
+              ),
+            IconButton(
+              tooltip: 'Clear selection',
+              icon: const Icon(Icons.close, size: 16),
+              onPressed: onClear,
+            ),
+          ],
+        ],
+      ),
+    );
+  }
+}
+
+/// Column bound to the first `checkbox(checked: <column>)` in a template, if any.
+String? findCheckboxField(RenderExpr expr) {
+  if (expr case RenderExpr_FunctionCall(:final name, :final args)) {
+    if (name == 'checkbox') {
+      for (final arg in args) {
+        if (arg.name == 'checked' && arg.value is RenderExpr_ColumnRef) {
+          return (arg.value as RenderExpr_ColumnRef).name;
+        }
+      }
+    }
+    for (final arg in args) {
+      final field = findCheckboxField(arg.value);
+      if (field != null) return field;
+    }
+  }
+  return null;
+}
+
+/// Operations wired into a template that can run on every selected row.
+///
+/// Only operations whose parameters all come from the row itself are offered
+/// (plus `indent`, whose new parent is derived from the list order).
+List<OperationWiring> findBatchOperations(RenderExpr expr) {
+  final byName = <String, OperationWiring>{};
+
+  void visit(RenderExpr expr) {
+    if (expr case RenderExpr_FunctionCall(:final args, :final operations)) {
+      for (final op in operations) {
+        final descriptor = op.descriptor;
+        final extraParams = descriptor.requiredParams
+            .map((p) => p.name)
+            .where((name) => name != descriptor.idColumn)
+            .where(
+              (name) => !(descriptor.name == 'indent' && name == 'parent_id'),
+            );
+        if (op.acceptsMultiple &&
+            descriptor.name != 'set_field' &&
+            extraParams.isEmpty) {
+          byName.putIfAbsent(descriptor.name, () => op);
+        }
+      }
+      for (final arg in args) {
+        visit(arg.value);
+      }
+    }
+  }
+
+  visit(expr);
+  return byName.values.toList();
+}
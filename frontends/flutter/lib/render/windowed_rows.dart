@@ -0,0 +1,84 @@
+import 'package:flutter/foundation.dart';
+import 'package:holon/src/rust/third_party/holon_api/streaming.dart'
+    show
+        WindowChangeBatch,
+        WindowChange_Reset,
+        WindowChange_Inserted,
+        WindowChange_Updated,
+        WindowChange_Removed;
+import '../services/backend_service.dart';
+import '../src/rust/third_party/holon_api.dart' show Value;
+
+/// Rows of a windowed query (see [BackendService.queryWindowed]).
+///
+/// Holds only the rows of the current window plus the total row count, kept in
+/// sync by [apply]ing the batches of the query's sink in order. A virtualized
+/// list sizes itself by [total], reads rows with [rowAt] and calls
+/// [ensureVisible] with its visible range so the window follows scrolling.
+class WindowedRows extends ChangeNotifier {
+  WindowedRows({
+    required BackendService backend,
+    required this.queryId,
+    this.windowSize = 200,
+  }) : _backend = backend;
+
+  final BackendService _backend;
+  final BigInt queryId;
+
+  /// Number of rows requested per window
+  final int windowSize;
+
+  int _start = 0;
+  int _total = 0;
+  List<Map<String, Value>> _rows = [];
+
+  /// Index of the window's first row within the whole result
+  int get start => _start;
+
+  /// Number of rows of the whole result
+  int get total => _total;
+
+  /// The row at [index] of the whole result, or null if it is outside the window
+  Map<String, Value>? rowAt(int index) {
+    final offset = index - _start;
+    if (offset < 0 || offset >= _rows.length) return null;
+    return _rows[offset];
+  }
+
+  /// Apply a batch received from the query's sink.
+  void apply(WindowChangeBatch batch) {
+    _start = batch.start.toInt();
+    _total = batch.total.toInt();
+    for (final change in batch.changes) {
+      switch (change) {
+        case WindowChange_Reset(:final rows):
+          _rows = List.of(rows);
+        case WindowChange_Inserted(:final index, :final data):
+          _rows.insert(index.toInt(), data);
+        case WindowChange_Updated(:final index, :final data):
+          _rows[index.toInt()] = data;
+        case WindowChange_Removed(:final index):
+          _rows.removeAt(index.toInt());
+      }
+    }
+    notifyListeners();
+  }
+
+  /// Move the window if rows [first] to [last] are not all inside it.
+  ///
+  /// The new window is centered on the visible rows; its rows arrive through the
+  /// sink like any other change.
+  Future<void> ensureVisible(int first, int last) async {
+    final end = _start + windowSize;
+    if (first >= _start && (last < end || end >= _total)) return;
+    final start = ((first + last - windowSize) ~/ 2).clamp(0, _total);
+    await _backend.setQueryWindow(
+      queryId: queryId,
+      start: start,
+      len: windowSize,
+    );
+  }
+
+  /// Stop watching the query.
+  Future<void> close() => _backend.closeWindowedQuery(queryId: queryId);
+}
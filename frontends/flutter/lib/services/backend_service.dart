@@ -31,6 +31,37 @@ abstract class BackendService {
     TraceContext? traceContext,
   });
 
+  /// Compile a PRQL query and watch a window of its result.
+  ///
+  /// Only rows [start] to [start] + [len] are sent: the [sink] first receives
+  /// them as a `reset` change, then changes with indices relative to the
+  /// window. Used to virtualize lists over huge results (see `WindowedRows`).
+  ///
+  /// Returns:
+  /// A tuple containing:
+  /// - [RenderSpec]: UI rendering specification from the PRQL query
+  /// - [BigInt]: Id of the windowed query, for [setQueryWindow]
+  Future<(RenderSpec, BigInt)> queryWindowed({
+    required String prql,
+    required Map<String, Value> params,
+    required int start,
+    required int len,
+    required ffi.WindowChangeSink sink,
+    TraceContext? traceContext,
+  });
+
+  /// Move the window of a query opened with [queryWindowed].
+  ///
+  /// Its sink receives the rows of the new window.
+  Future<void> setQueryWindow({
+    required BigInt queryId,
+    required int start,
+    required int len,
+  });
+
+  /// Stop watching a query opened with [queryWindowed].
+  Future<void> closeWindowedQuery({required BigInt queryId});
+
   /// Get available operations for an entity.
   ///
   /// Returns a list of operation descriptors available for the given entityName.
@@ -48,6 +79,8 @@ abstract class BackendService {
   /// - [entityName]: Name of the entity (e.g., "blocks")
   /// - [opName]: Name of the operation (e.g., "indent", "outdent")
   /// - [params]: Operation parameters
+  ///
+  /// Throws an [ApiError] when the operation fails (e.g. `ApiError.notFound`).
   Future<void> executeOperation({
     required String entityName,
     required String opName,
@@ -55,6 +88,20 @@ abstract class BackendService {
     TraceContext? traceContext,
   });
 
+  /// Execute an operation once per row as a single undoable batch.
+  ///
+  /// Used for batch actions on multi-selections (e.g. "Complete all").
+  /// Stops at the first failing row.
+  ///
+  /// Returns:
+  /// The number of rows the operation was executed on
+  Future<int> executeBatchOperation({
+    required String entityName,
+    required String opName,
+    required String displayName,
+    required List<Map<String, Value>> rows,
+  });
+
   /// Check if an operation is available for an entity.
   ///
   /// Returns:
@@ -106,6 +153,43 @@ class RustBackendService implements BackendService {
     );
   }
 
+  @override
+  Future<(RenderSpec, BigInt)> queryWindowed({
+    required String prql,
+    required Map<String, Value> params,
+    required int start,
+    required int len,
+    required ffi.WindowChangeSink sink,
+    TraceContext? traceContext,
+  }) async {
+    return await ffi.queryWindowed(
+      prql: prql,
+      params: params,
+      start: BigInt.from(start),
+      len: BigInt.from(len),
+      sink: sink,
+      traceContext: traceContext,
+    );
+  }
+
+  @override
+  Future<void> setQueryWindow({
+    required BigInt queryId,
+    required int start,
+    required int len,
+  }) async {
+    return await ffi.setQueryWindow(
+      queryId: queryId,
+      start: BigInt.from(start),
+      len: BigInt.from(len),
+    );
+  }
+
+  @override
+  Future<void> closeWindowedQuery({required BigInt queryId}) async {
+    await ffi.closeWindowedQuery(queryId: queryId);
+  }
+
   @override
   Future<List<OperationDescriptor>> availableOperations({
     required String entityName,
@@ -128,6 +212,21 @@ class RustBackendService implements BackendService {
     );
   }
 
+  @override
+  Future<int> executeBatchOperation({
+    required String entityName,
+    required String opName,
+    required String displayName,
+    required List<Map<String, Value>> rows,
+  }) async {
+    return await ffi.executeBatchOperation(
+      entityName: entityName,
+      opName: opName,
+      displayName: displayName,
+      rows: rows,
+    );
+  }
+
   @override
   Future<bool> hasOperation({
     required String entityName,
@@ -268,6 +268,39 @@ class McpBackendWrapper implements BackendService {
     );
   }
 
+  @override
+  Future<(RenderSpec, BigInt)> queryWindowed({
+    required String prql,
+    required Map<String, Value> params,
+    required int start,
+    required int len,
+    required ffi.WindowChangeSink sink,
+    TraceContext? traceContext,
+  }) {
+    return _delegate.queryWindowed(
+      prql: prql,
+      params: params,
+      start: start,
+      len: len,
+      sink: sink,
+      traceContext: traceContext,
+    );
+  }
+
+  @override
+  Future<void> setQueryWindow({
+    required BigInt queryId,
+    required int start,
+    required int len,
+  }) {
+    return _delegate.setQueryWindow(queryId: queryId, start: start, len: len);
+  }
+
+  @override
+  Future<void> closeWindowedQuery({required BigInt queryId}) {
+    return _delegate.closeWindowedQuery(queryId: queryId);
+  }
+
   @override
   Future<List<OperationDescriptor>> availableOperations({
     required String entityName,
@@ -290,6 +323,21 @@ class McpBackendWrapper implements BackendService {
     );
   }
 
+  @override
+  Future<int> executeBatchOperation({
+    required String entityName,
+    required String opName,
+    required String displayName,
+    required List<Map<String, Value>> rows,
+  }) {
+    return _delegate.executeBatchOperation(
+      entityName: entityName,
+      opName: opName,
+      displayName: displayName,
+      rows: rows,
+    );
+  }
+
   @override
   Future<bool> hasOperation({
     required String entityName,
@@ -7,7 +7,8 @@ import '../src/rust/api/types.dart' show TraceContext;
 import '../src/rust/third_party/holon_api.dart' show Value;
 import '../src/rust/third_party/holon_api/render_types.dart'
     show OperationDescriptor, RenderSpec, RenderExpr, Arg, RowTemplate;
-import '../src/rust/api/ffi_bridge.dart' as ffi show MapChangeSink;
+import '../src/rust/api/ffi_bridge.dart' as ffi
+    show MapChangeSink, WindowChangeSink;
 
 /// Mock implementation of BackendService for testing.
 ///
@@ -347,6 +348,31 @@ class MockBackendService implements BackendService {
     );
   }
 
+  @override
+  Future<(RenderSpec, BigInt)> queryWindowed({
+    required String prql,
+    required Map<String, Value> params,
+    required int start,
+    required int len,
+    required ffi.WindowChangeSink sink,
+    TraceContext? traceContext,
+  }) async {
+    // Like queryAndWatch, the mock can't push the window's rows into the
+    // opaque sink, so only the render spec is returned
+    final (renderSpec, _) = getMockQueryResult();
+    return (renderSpec, BigInt.zero);
+  }
+
+  @override
+  Future<void> setQueryWindow({
+    required BigInt queryId,
+    required int start,
+    required int len,
+  }) async {}
+
+  @override
+  Future<void> closeWindowedQuery({required BigInt queryId}) async {}
+
   /// Get the change stream for testing purposes.
   /// This allows tests to listen to changes without going through the sink.
   Stream<MapChange> get changeStream => _changeStreamController.stream;
@@ -395,6 +421,28 @@ class MockBackendService implements BackendService {
     await Future.delayed(const Duration(milliseconds: 10));
   }
 
+  @override
+  Future<int> executeBatchOperation({
+    required String entityName,
+    required String opName,
+    required String displayName,
+    required List<Map<String, Value>> rows,
+  }) async {
+    // Record one operation call per row, like the real batch
+    for (final params in rows) {
+      _operationCalls.add(
+        OperationCall(
+          entityName: entityName,
+          opName: opName,
+          params: Map.from(params),
+        ),
+      );
+    }
+
+    await Future.delayed(const Duration(milliseconds: 10));
+    return rows.length;
+  }
+
   @override
   Future<bool> hasOperation({
     required String entityName,
@@ -3,11 +3,21 @@
 //! This module provides a minimal FFI surface exposing only BackendEngine and essential types.
 //! Low-level query_render types (Expr, ModuleDef, Lineage) are hidden as implementation details.
 
-use crate::api::types::TraceContext;
+use crate::api::render_wire::WireRenderTree;
+use crate::api::types::{
+    Diagnostics, LogFilter, LogRecord, MaintenanceStatus, Notification, OperationLogEntry,
+    TraceContext, ViewUpdate,
+};
 use crate::frb_generated::StreamSink;
 use ferrous_di::ServiceCollectionModuleExt;
-use holon_api::{BatchMapChange, BatchMapChangeWithMetadata, MapChange};
-use holon_api::{OperationDescriptor, RenderSpec, Value};
+use holon::api::Window;
+use holon::core::datasource::HolonError;
+use holon::core::log_buffer::{LogBuffer, DEFAULT_LOG_CAPACITY};
+use holon_api::{
+    ApiError, ChangeOrigin, ChangeSource, FilterValue, Format, OperationDescriptor, Presence,
+    RenderLocale, RenderSpec, Value, ViewState,
+};
+use holon_api::{BatchMapChange, BatchMapChangeWithMetadata, MapChange, WindowChangeBatch};
 use once_cell::sync::OnceCell;
 use opentelemetry::global;
 use opentelemetry::trace::{Span, Tracer};
@@ -23,6 +33,10 @@ pub use holon_api::Change;
 // This prevents Flutter Rust Bridge from disposing the engine during async operations
 static GLOBAL_ENGINE: OnceCell<Arc<BackendEngine>> = OnceCell::new();
 
+// Recent backend log events for the in-app log viewer
+// Shared by the tracing subscriber and the engine, so it outlives re-initialization
+static LOG_BUFFER: OnceCell<LogBuffer> = OnceCell::new();
+
 /// Create an OpenTelemetry span from optional trace context
 ///
 /// If trace_context is provided, creates a child span. Otherwise creates a new root span.
@@ -58,7 +72,7 @@ fn create_span_from_context(
 /// Sets up OTLP and stdout exporters based on environment variables.
 /// Bridges tracing to OpenTelemetry so existing tracing spans appear in traces.
 /// Also bridges tracing logs to OpenTelemetry logs for log export.
-async fn init_opentelemetry() -> anyhow::Result<()> {
+async fn init_opentelemetry(log_buffer: &LogBuffer) -> anyhow::Result<()> {
     use opentelemetry::global;
     use opentelemetry::KeyValue;
     use opentelemetry_sdk::Resource;
@@ -155,6 +169,7 @@ async fn init_opentelemetry() -> anyhow::Result<()> {
         let subscriber = Registry::default()
             .with(telemetry_layer)
             .with(log_bridge)
+            .with(log_buffer.layer())
             .with(
                 tracing_subscriber::fmt::layer()
                     .with_writer(std::io::stderr)
@@ -232,6 +247,7 @@ async fn init_opentelemetry() -> anyhow::Result<()> {
         let subscriber = Registry::default()
             .with(telemetry_layer)
             .with(log_bridge)
+            .with(log_buffer.layer())
             .with(
                 tracing_subscriber::fmt::layer()
                     .with_writer(std::io::stderr)
@@ -271,7 +287,8 @@ async fn init_opentelemetry() -> anyhow::Result<()> {
 ///
 /// # Parameters
 /// * `db_path` - Path to the database file
-/// * `config` - Configuration map (e.g., API keys like "TODOIST_API_KEY", paths like "ORGMODE_ROOT_DIRECTORY")
+/// * `config` - Configuration map (e.g., API keys like "TODOIST_API_KEY", paths like "ORGMODE_ROOT_DIRECTORY"
+///   or "VIEW_DIRECTORY" for views defined by `.prql` files)
 pub async fn init_render_engine(
     db_path: String,
     config: HashMap<String, String>,
@@ -281,8 +298,19 @@ pub async fn init_render_engine(
     use std::path::PathBuf;
     use std::println;
 
+    // Keep recent log events in memory (LOG_BUFFER_SIZE events, default 1000)
+    let log_buffer = LOG_BUFFER
+        .get_or_init(|| {
+            let capacity = config
+                .get("LOG_BUFFER_SIZE")
+                .and_then(|size| size.parse().ok())
+                .unwrap_or(DEFAULT_LOG_CAPACITY);
+            LogBuffer::new(capacity)
+        })
+        .clone();
+
     // Initialize OpenTelemetry (includes tracing subscriber with OpenTelemetry bridge)
-    init_opentelemetry().await?;
+    init_opentelemetry(&log_buffer).await?;
 
     // Also print a message to confirm logging is initialized
     println!("[FFI] Tracing subscriber initialized - Rust logs will appear below");
@@ -291,6 +319,9 @@ pub async fn init_render_engine(
     // Use shared DI setup function
     // Register modules based on config
     let engine = holon::di::create_backend_engine(db_path.into(), |services| {
+        services.add_singleton(log_buffer);
+        services.add_singleton(ChangeSource::default().with_frontend(FRONTEND_NAME));
+
         // Check for Todoist API key in config
         if let Some(api_key) = config.get("TODOIST_API_KEY") {
             println!("[FFI] Registering TodoistConfig with API key");
@@ -328,10 +359,49 @@ pub async fn init_render_engine(
             println!("[FFI] No ORGMODE_ROOT_DIRECTORY in config, skipping OrgMode integration");
         }
 
+        // Views defined by .prql files, reloaded when they change
+        if let Some(view_dir) = config.get("VIEW_DIRECTORY") {
+            println!("[FFI] Loading views from: {}", view_dir);
+            services.add_singleton(holon::api::ViewLoaderConfig::new(view_dir));
+        }
+
+        // Periodic snapshots for point-in-time restore
+        if let Some(snapshot_dir) = config.get("SNAPSHOT_DIRECTORY") {
+            println!("[FFI] Writing snapshots to: {}", snapshot_dir);
+            services.add_singleton(holon::storage::SnapshotConfig::new(snapshot_dir));
+        }
+
+        // Presence of collaborators on the local network, shown under this user name
+        if let Some(user_name) = config.get("PRESENCE_USER_NAME") {
+            use holon::core::presence::{MulticastPresenceChannel, PresenceConfig};
+            match MulticastPresenceChannel::join(MulticastPresenceChannel::DEFAULT_GROUP) {
+                Ok(channel) => {
+                    println!("[FFI] Sharing presence as: {}", user_name);
+                    services.add_singleton(PresenceConfig::new(user_name, Arc::new(channel)));
+                }
+                Err(e) => eprintln!("[FFI] Presence disabled, failed to join channel: {}", e),
+            }
+        }
+
         Ok(())
     })
     .await?;
 
+    // Load view files and watch them for changes (no-op without VIEW_DIRECTORY)
+    engine.start_view_loader();
+    // Fire reminders of due dates (shown once the app calls watch_notifications)
+    engine.start_reminders();
+    // Snapshot the workspace periodically (no-op without SNAPSHOT_DIRECTORY)
+    engine.start_snapshots();
+    // Checkpoint, vacuum and analyze the database while the app is idle
+    engine.start_maintenance();
+    // Persist collapsed nodes, selection and scroll position of views
+    engine.start_view_state_flush();
+    // Archive entities matching the archival rules (e.g. archived Todoist projects)
+    engine.start_archival();
+    // Receive collaborators' presence (no-op without PRESENCE_USER_NAME)
+    engine.start_presence();
+
     // Store in global singleton to prevent Flutter Rust Bridge from disposing it
     GLOBAL_ENGINE
         .set(engine.clone())
@@ -363,6 +433,7 @@ pub struct MapChangeSink {
 /// 1. Subscribe to the RowChangeStream using StreamBuilder in Flutter
 /// 2. Key widgets by entity ID from data.get("id"), NOT by rowid
 /// 3. Handle Added/Updated/Removed events to update UI
+/// 4. Handle ColumnChange events by patching only the listed fields of the row
 ///
 pub async fn query_and_watch(
     prql: String,
@@ -410,7 +481,9 @@ pub async fn query_and_watch(
             for row_change in &batch_with_metadata.inner.items {
                 match &row_change.change {
                     MapChange::Created { .. } => created_count += 1,
-                    MapChange::Updated { .. } => updated_count += 1,
+                    MapChange::Updated { .. } | MapChange::ColumnChange { .. } => {
+                        updated_count += 1
+                    }
                     MapChange::Deleted { .. } => deleted_count += 1,
                 }
             }
@@ -499,6 +572,284 @@ pub async fn query_and_watch(
     Ok((render_spec, data))
 }
 
+/// flutter_rust_bridge:non_opaque
+pub struct WindowChangeSink {
+    pub sink: StreamSink<WindowChangeBatch>,
+}
+
+/// Compile a PRQL query and watch a window of its result
+///
+/// For lists over huge results: instead of all rows, only rows `start..start + len`
+/// are sent. The sink first receives a batch with the window's rows (a `Reset`
+/// change), then batches of changes whose indices are relative to the window.
+/// Changes to rows outside the window only update the batch's `total`.
+///
+/// # Returns
+/// A tuple containing:
+/// - `RenderSpec`: UI rendering specification from the PRQL query
+/// - `u64`: Id of the query, for `set_query_window` and `close_windowed_query`
+///
+/// # UI Usage
+/// Size the list by the `total` of the latest batch and call `set_query_window`
+/// when the visible range leaves the window. Batches arrive in order, so the UI
+/// can apply them as they come.
+pub async fn query_windowed(
+    prql: String,
+    params: HashMap<String, Value>,
+    start: usize,
+    len: usize,
+    sink: WindowChangeSink,
+    trace_context: Option<TraceContext>,
+) -> anyhow::Result<(RenderSpec, u64)> {
+    let mut span = create_span_from_context("ffi.query_windowed", trace_context);
+    span.set_attribute(opentelemetry::KeyValue::new("prql.query", prql.clone()));
+
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    let (render_spec, query_id, mut stream) = engine
+        .query_windowed(prql, params, Window::new(start, len))
+        .await?;
+
+    tokio::spawn(async move {
+        while let Some(batch) = stream.next().await {
+            if sink.sink.add(batch).is_err() {
+                tracing::warn!("[FFI] Window sink closed, closing windowed query");
+                engine.close_windowed_query(query_id);
+                break;
+            }
+        }
+    });
+
+    span.end();
+    Ok((render_spec, query_id))
+}
+
+/// Move the window of a query opened with `query_windowed`
+///
+/// The query's sink receives the rows of the new window, followed by changes
+/// relative to it.
+pub async fn set_query_window(query_id: u64, start: usize, len: usize) -> anyhow::Result<()> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine
+        .set_query_window(query_id, Window::new(start, len))
+        .await
+}
+
+/// Stop watching a query opened with `query_windowed`
+pub async fn close_windowed_query(query_id: u64) -> anyhow::Result<bool> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    Ok(engine.close_windowed_query(query_id))
+}
+
+/// Compile a PRQL query and watch its result, re-running it when its filters change
+///
+/// Like `query_and_watch`, but the filter widgets listed in `RenderSpec.filters`
+/// are set with `set_query_filter` instead of submitting a new query. The sink then
+/// receives the rows that left, entered or changed in the result, followed by
+/// changes of the re-filtered query.
+///
+/// # Returns
+/// A tuple containing:
+/// - `RenderSpec`: UI rendering specification from the PRQL query
+/// - `Vec<HashMap<String, Value>>`: Current query results, with filter defaults applied
+/// - `u64`: Id of the query, for `set_query_filter` and `close_filtered_query`
+pub async fn query_filtered(
+    prql: String,
+    params: HashMap<String, Value>,
+    sink: MapChangeSink,
+    trace_context: Option<TraceContext>,
+) -> anyhow::Result<(RenderSpec, Vec<HashMap<String, Value>>, u64)> {
+    let mut span = create_span_from_context("ffi.query_filtered", trace_context);
+    span.set_attribute(opentelemetry::KeyValue::new("prql.query", prql.clone()));
+
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    let (render_spec, data, query_id, mut stream) = engine.query_filtered(prql, params).await?;
+
+    tokio::spawn(async move {
+        while let Some(batch) = stream.next().await {
+            let batch = BatchMapChangeWithMetadata {
+                inner: BatchMapChange {
+                    items: batch.inner.items.into_iter().map(|c| c.change).collect(),
+                },
+                metadata: batch.metadata,
+            };
+            if sink.sink.add(batch).is_err() {
+                tracing::warn!("[FFI] Sink closed, closing filtered query");
+                engine.close_filtered_query(query_id);
+                break;
+            }
+        }
+    });
+
+    span.end();
+    Ok((render_spec, data, query_id))
+}
+
+/// Set a filter of a query opened with `query_filtered`
+///
+/// `name` is the filter's name from `RenderSpec.filters`; `None` turns it off.
+pub async fn set_query_filter(
+    query_id: u64,
+    name: String,
+    value: Option<FilterValue>,
+) -> anyhow::Result<()> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine.set_query_filter(query_id, &name, value).await
+}
+
+/// Stop watching a query opened with `query_filtered`
+pub async fn close_filtered_query(query_id: u64) -> anyhow::Result<bool> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    Ok(engine.close_filtered_query(query_id))
+}
+
+/// Compile a search query and run it, for search-as-you-type
+///
+/// `text_param` names the query parameter bound to the search text (e.g. `search`
+/// for `filter (content ~= $search)`). Call `live_search` as the user types; the
+/// sink receives only the rows entering, leaving or changing in the result.
+///
+/// # Returns
+/// A tuple containing:
+/// - `RenderSpec`: UI rendering specification from the PRQL query
+/// - `Vec<HashMap<String, Value>>`: Current search results
+/// - `u64`: Id of the search, for `live_search` and `close_live_search`
+pub async fn open_live_search(
+    prql: String,
+    params: HashMap<String, Value>,
+    text_param: String,
+    sink: MapChangeSink,
+) -> anyhow::Result<(RenderSpec, Vec<HashMap<String, Value>>, u64)> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    let (render_spec, data, query_id, mut stream) =
+        engine.open_live_search(prql, params, &text_param).await?;
+
+    tokio::spawn(async move {
+        while let Some(batch) = stream.next().await {
+            let batch = BatchMapChangeWithMetadata {
+                inner: BatchMapChange {
+                    items: batch.inner.items.into_iter().map(|c| c.change).collect(),
+                },
+                metadata: batch.metadata,
+            };
+            if sink.sink.add(batch).is_err() {
+                tracing::warn!("[FFI] Sink closed, closing live search");
+                engine.close_live_search(query_id);
+                break;
+            }
+        }
+    });
+
+    Ok((render_spec, data, query_id))
+}
+
+/// Re-run a search opened with `open_live_search` for the text typed so far
+///
+/// Cheap to call on every keystroke: the search runs once typing pauses and
+/// newer text cancels older searches.
+pub async fn live_search(query_id: u64, text: String) -> anyhow::Result<()> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine.live_search(query_id, &text).await
+}
+
+/// Stop a search opened with `open_live_search`
+pub async fn close_live_search(query_id: u64) -> anyhow::Result<bool> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    Ok(engine.close_live_search(query_id))
+}
+
+/// Resolve a drop position in a windowed query into `tree_position` params
+///
+/// `drop_index` is the index in the whole result (window `start` plus the index in
+/// the window) the dragged row is dropped at. Returns `parent_id` and
+/// `after_block_id`, to commit as the gesture's `tree_position` so operations like
+/// `move_block` resolve their params through their param mappings.
+///
+/// # FFI Function
+/// This is exposed to Flutter via flutter_rust_bridge
+pub async fn resolve_tree_position(
+    query_id: u64,
+    drop_index: usize,
+) -> anyhow::Result<HashMap<String, Value>> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine.resolve_tree_position(query_id, drop_index).await
+}
+
+/// Flatten the render expressions of a `RenderSpec` for decoding in Dart
+///
+/// See `render_wire` for the node layout and its versioning.
+///
+/// # FFI Function
+/// This is exposed to Flutter via flutter_rust_bridge
+#[flutter_rust_bridge::frb(sync)]
+pub fn render_spec_wire(spec: RenderSpec) -> WireRenderTree {
+    WireRenderTree::from_render_spec(&spec)
+}
+
+/// Text of a `Format` node's value in the device's locale
+///
+/// Frontends format with the same rules as the TUI by calling this for every
+/// `Format` node of a render tree.
+///
+/// # FFI Function
+/// This is exposed to Flutter via flutter_rust_bridge
+#[flutter_rust_bridge::frb(sync)]
+pub fn format_render_value(format: Format, value: Value, locale: RenderLocale) -> String {
+    format.apply(&value, &locale, chrono::Utc::now())
+}
+
+/// Name of this frontend in the `ChangeSource` of changes made through it
+const FRONTEND_NAME: &str = "flutter";
+
+/// Badge text for a change made outside this app (by a sync provider, an agent or
+/// another device), or None for the app's own changes
+///
+/// # FFI Function
+/// This is exposed to Flutter via flutter_rust_bridge
+#[flutter_rust_bridge::frb(sync)]
+pub fn change_badge(origin: ChangeOrigin) -> Option<String> {
+    origin.badge(&ChangeSource::default().with_frontend(FRONTEND_NAME))
+}
+
 /// Get available operations for an entity
 ///
 /// Returns a list of operation descriptors available for the given entity_name.
@@ -526,12 +877,16 @@ pub async fn available_operations(entity_name: String) -> anyhow::Result<Vec<Ope
 /// # Note
 /// This function does NOT return new data. Changes propagate through:
 /// Operation → DB mutation → CDC event → watch_query stream → UI update
+///
+/// # Errors
+/// Missing entities, rejected preconditions and remote failures arrive as the
+/// matching `ApiError` variant; anything else as `ApiError::InternalError`.
 pub async fn execute_operation(
     entity_name: String,
     op_name: String,
     params: HashMap<String, Value>,
     trace_context: Option<TraceContext>,
-) -> anyhow::Result<()> {
+) -> Result<(), ApiError> {
     use opentelemetry::trace::TraceContextExt;
     use tracing::info;
     use tracing::Instrument;
@@ -641,16 +996,40 @@ pub async fn execute_operation(
         }
     }
 
-    result.map_err(|e| {
-        anyhow::anyhow!(
-            "Operation '{}' on entity '{}' failed: {}",
-            op_name,
-            entity_name,
-            e
-        )
+    // Structured errors of the operation traits reach Dart as their ApiError variant
+    result.map_err(|e| match e.downcast_ref::<HolonError>() {
+        Some(holon_error) => ApiError::from(holon_error.clone()),
+        None => ApiError::InternalError {
+            message: e.to_string(),
+        },
     })
 }
 
+/// Execute an operation once per selected entity (batch action over a selection)
+///
+/// # FFI Function
+/// This is exposed to Flutter via flutter_rust_bridge
+///
+/// `rows` holds the parameters for each selected entity. All executed operations
+/// are undone as one step named `display_name`. Returns the number of executed
+/// operations; fails at the first entity whose operation fails.
+pub async fn execute_batch_operation(
+    entity_name: String,
+    op_name: String,
+    display_name: String,
+    rows: Vec<HashMap<String, Value>>,
+) -> anyhow::Result<u32> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    let executed = engine
+        .execute_batch_operation(&entity_name, &op_name, &display_name, rows)
+        .await?;
+    Ok(executed as u32)
+}
+
 /// Check if an operation is available for an entity
 ///
 /// # FFI Function
@@ -712,3 +1091,245 @@ pub async fn can_redo() -> anyhow::Result<bool> {
 
     Ok(engine.can_redo().await)
 }
+
+/// Get the most recent operations from the operation log, newest first
+///
+/// Includes undone operations (status "undone"), so the history panel can show
+/// what redo would re-apply.
+pub async fn operation_history(limit: u32) -> anyhow::Result<Vec<OperationLogEntry>> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine.operation_history(limit as usize).await
+}
+
+/// Get a health report of the backend (database integrity, pending sync
+/// operations per provider, last syncs, schema version and query cache stats)
+pub async fn diagnostics() -> anyhow::Result<Diagnostics> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine.diagnostics().await
+}
+
+/// Get up to `limit` recent backend log events matching `filter`, newest first
+pub async fn recent_logs(filter: LogFilter, limit: u32) -> anyhow::Result<Vec<LogRecord>> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    Ok(engine.recent_logs(&filter, limit as usize))
+}
+
+/// Optimize the database now (WAL checkpoint, incremental vacuum, ANALYZE)
+///
+/// The backend also does this by itself while the app is idle. Fails if a run
+/// is already in progress; failed steps are listed in `last_errors`.
+pub async fn run_maintenance() -> anyhow::Result<MaintenanceStatus> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine.run_maintenance().await
+}
+
+/// Get the state of database maintenance (`running` while "optimizing database")
+pub async fn maintenance_status() -> anyhow::Result<Option<MaintenanceStatus>> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    Ok(engine.maintenance_status())
+}
+
+/// Get the persisted UI state of a view (also delivered in `RenderSpec.view_state`)
+pub async fn view_state(view_id: String) -> anyhow::Result<ViewState> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine.view_state(&view_id).await
+}
+
+/// Record that a tree node of a view was collapsed or expanded
+pub async fn set_view_collapsed(
+    view_id: String,
+    entity_id: String,
+    collapsed: bool,
+) -> anyhow::Result<()> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine
+        .set_view_collapsed(&view_id, &entity_id, collapsed)
+        .await
+}
+
+/// Record the selected rows of a view
+pub async fn set_view_selection(view_id: String, entity_ids: Vec<String>) -> anyhow::Result<()> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine.set_view_selection(&view_id, &entity_ids).await
+}
+
+/// Record the first visible row of a view (`None` when scrolled to the top)
+pub async fn set_view_scroll_anchor(
+    view_id: String,
+    entity_id: Option<String>,
+) -> anyhow::Result<()> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine
+        .set_view_scroll_anchor(&view_id, entity_id.as_deref())
+        .await
+}
+
+/// Tell collaborators which view the user shows, the row the cursor is on and the
+/// selected rows (requires PRESENCE_USER_NAME)
+pub async fn publish_presence(
+    view_id: Option<String>,
+    focused_id: Option<String>,
+    selection: Vec<String>,
+) -> anyhow::Result<()> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine
+        .publish_presence(view_id.as_deref(), focused_id.as_deref(), &selection)
+        .await
+}
+
+/// Presence of the collaborators showing `view_id` (all collaborators if None), for
+/// marking their rows in views declaring `RenderSpec.presence`
+pub async fn presence(view_id: Option<String>) -> anyhow::Result<Vec<Presence>> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine.presence(view_id.as_deref())
+}
+
+/// Record that the user opened an entity, for `recently_viewed` queries
+///
+/// Modifications are recorded by the backend when operations succeed.
+pub async fn record_entity_viewed(entity_name: String, entity_id: String) -> anyhow::Result<()> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine.record_entity_viewed(&entity_name, &entity_id).await
+}
+
+/// flutter_rust_bridge:non_opaque
+pub struct ViewUpdateSink {
+    pub sink: StreamSink<ViewUpdate>,
+}
+
+/// Names of the views loaded from the view directory
+///
+/// Fails unless `VIEW_DIRECTORY` was set in `init_render_engine`.
+pub async fn list_views() -> anyhow::Result<Vec<String>> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    Ok(engine.views()?.into_iter().map(|view| view.name).collect())
+}
+
+/// PRQL source of a view, to run with `query_and_watch`
+pub async fn view_prql(name: String) -> anyhow::Result<String> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    Ok(engine.view(&name)?.prql)
+}
+
+/// Receive changes to the view files
+///
+/// # UI Usage
+/// When the open view is `Updated`, re-run its `prql` to pick up the new
+/// definition; show `Failed` errors without closing the view.
+pub async fn watch_views(sink: ViewUpdateSink) -> anyhow::Result<()> {
+    use tokio::sync::broadcast::error::RecvError;
+
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    let mut events = engine.subscribe_views()?;
+    tokio::spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("[FFI] Missed {} view updates", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            if sink.sink.add(ViewUpdate::from(event)).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+
+/// flutter_rust_bridge:non_opaque
+pub struct NotificationStreamSink {
+    pub sink: StreamSink<Notification>,
+}
+
+/// Forwards backend notifications to Dart
+struct FlutterNotificationSink {
+    sink: StreamSink<Notification>,
+}
+
+impl holon::core::notifications::NotificationSink for FlutterNotificationSink {
+    fn notify(&self, notification: Notification) {
+        if self.sink.add(notification).is_err() {
+            tracing::debug!("[FFI] Notification sink closed, dropping notification");
+        }
+    }
+}
+
+/// Receive notifications of the backend (due reminders, sync health reports)
+///
+/// # UI Usage
+/// Show them as local notifications. `entity` names the entity to open when one
+/// is tapped. Reminder notifications refer to their `reminders` row (which has
+/// the reminded entity's `entity_name` and `entity_id`); offer its `snooze` and
+/// `dismiss` operations as notification actions.
+pub async fn watch_notifications(sink: NotificationStreamSink) -> anyhow::Result<()> {
+    let engine = GLOBAL_ENGINE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Engine not initialized. Call init_render_engine first."))?
+        .clone();
+
+    engine.add_notification_sink(Arc::new(FlutterNotificationSink { sink: sink.sink }));
+    Ok(())
+}
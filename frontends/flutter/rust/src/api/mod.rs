@@ -3,14 +3,21 @@ pub mod flutter_pbt_backend;
 pub mod flutter_pbt_runner;
 pub mod flutter_pbt_state_machine;
 pub mod pbt_proptest;
+pub mod render_wire;
 pub mod types;
 
 pub use holon::api::types::{NewBlock, Traversal};
 pub use holon::api::BackendEngine;
+pub use holon::api::{Diagnostics, ProviderDiagnostics, QueryCacheStats};
+pub use holon::core::log_buffer::{LogFilter, LogRecord};
+pub use holon::core::notifications::{Notification, NotificationSeverity};
+pub use holon::core::operation_log::OperationLogEntry;
 use holon::core::DynamicEntity;
+pub use holon::storage::maintenance::MaintenanceStatus;
+pub use holon::storage::replica::ReplicaStatus;
 pub use holon::storage::turso::RowChangeStream;
 pub use holon::storage::types::StorageEntity;
 pub use holon_api::ApiError;
 pub use holon_api::{Block, BlockChange, BlockMetadata};
-pub use holon_api::{Change, ChangeOrigin, MapChange, StreamPosition};
+pub use holon_api::{Change, ChangeOrigin, ChangeSource, MapChange, StreamPosition};
 pub use holon_api::{OperationDescriptor, OperationParam, RenderSpec};
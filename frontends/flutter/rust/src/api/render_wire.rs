@@ -0,0 +1,564 @@
+//! Flattened wire format of render expressions for the Dart side
+//!
+//! `RenderExpr` is a recursive enum, which Dart receives as deeply nested
+//! classes that have to be pattern matched level by level. `WireRenderTree`
+//! sends the same tree as a flat list of nodes in pre-order: every node carries
+//! a type tag and the index of its parent, and parents always come before their
+//! children. Dart builds its widget tree in a single pass over the list.
+//!
+//! The format is versioned by `RENDER_WIRE_VERSION`. Changes must stay
+//! backwards compatible within a version: new node fields are optional (and
+//! `#[serde(default)]`), new node kinds are only added together with a
+//! version bump, so older decoders can reject trees they do not understand.
+
+use flutter_rust_bridge::frb;
+use holon_api::{
+    Arg, BinaryOperator, Format, OperationWiring, RenderExpr, RenderSpec, Style, StyleRule, Value,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Version of the node layout produced by `WireRenderTree::from_render_spec`
+///
+/// Version 2 added `Format` nodes, version 3 `Error` nodes.
+pub const RENDER_WIRE_VERSION: u32 = 3;
+
+/// Type tag of a `WireNode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WireNodeKind {
+    /// Widget or function call; children are its arguments
+    FunctionCall,
+    ColumnRef,
+    Literal,
+    /// Children are the left and the right operand
+    BinaryOp,
+    Array,
+    /// Children are the fields, with the field name as `key`
+    Object,
+    /// `style:` argument; children are its rules
+    Style,
+    /// One rule of a `Style` node; its only child, if any, is the condition
+    StyleRule,
+    /// Formatted value; its only child is the value
+    Format,
+    /// Template that failed to compile; `value` is the message
+    Error,
+}
+
+/// One node of a flattened render expression
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WireNode {
+    pub kind: WireNodeKind,
+    /// Index of the parent node (None for roots)
+    pub parent: Option<u32>,
+    /// Argument name (named arguments), field name (objects), or
+    /// "left"/"right" (binary operands)
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Function name (`FunctionCall`) or column name (`ColumnRef`)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Value of a `Literal`
+    #[serde(default)]
+    pub value: Option<Value>,
+    /// Operator of a `BinaryOp`
+    #[serde(default)]
+    pub op: Option<BinaryOperator>,
+    /// Style of a `StyleRule`
+    #[serde(default)]
+    pub style: Option<Style>,
+    /// Formatting function of a `Format` node
+    #[serde(default)]
+    pub format: Option<Format>,
+    /// Indices into `WireRenderTree::operations` wired to a `FunctionCall`
+    #[serde(default)]
+    pub operations: Vec<u32>,
+}
+
+impl WireNode {
+    fn new(kind: WireNodeKind, parent: Option<u32>, key: Option<String>) -> Self {
+        Self {
+            kind,
+            parent,
+            key,
+            name: None,
+            value: None,
+            op: None,
+            style: None,
+            format: None,
+            operations: vec![],
+        }
+    }
+}
+
+/// Row template of a heterogeneous query, pointing to its root node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireRowTemplate {
+    pub index: usize,
+    pub entity_name: String,
+    pub entity_short_name: String,
+    pub root: u32,
+}
+
+/// Render expressions of a `RenderSpec` as one flat node list
+///
+/// The root expression and all row template expressions share `nodes`; the
+/// operation wirings referenced by nodes are stored once in `operations`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WireRenderTree {
+    pub version: u32,
+    pub nodes: Vec<WireNode>,
+    /// Index of the root expression's node
+    pub root: u32,
+    #[serde(default)]
+    pub row_templates: Vec<WireRowTemplate>,
+    #[serde(default)]
+    pub operations: Vec<OperationWiring>,
+}
+
+impl WireRenderTree {
+    /// Flatten the render expressions of `spec`
+    pub fn from_render_spec(spec: &RenderSpec) -> Self {
+        let mut tree = Self::empty();
+        tree.root = tree.push_expr(&spec.root, None, None);
+        tree.row_templates = spec
+            .row_templates
+            .iter()
+            .map(|template| WireRowTemplate {
+                index: template.index,
+                entity_name: template.entity_name.clone(),
+                entity_short_name: template.entity_short_name.clone(),
+                root: tree.push_expr(&template.expr, None, None),
+            })
+            .collect();
+        tree
+    }
+
+    /// Flatten a single expression
+    pub fn from_render_expr(expr: &RenderExpr) -> Self {
+        let mut tree = Self::empty();
+        tree.root = tree.push_expr(expr, None, None);
+        tree
+    }
+
+    /// Rebuild the expression rooted at node `index`
+    ///
+    /// This is what the Dart decoder does; it is used to check that the format
+    /// is lossless.
+    #[frb(ignore)]
+    pub fn to_render_expr(&self, index: u32) -> anyhow::Result<RenderExpr> {
+        if self.version > RENDER_WIRE_VERSION {
+            anyhow::bail!(
+                "Unsupported render wire version {} (expected at most {})",
+                self.version,
+                RENDER_WIRE_VERSION
+            );
+        }
+        let mut children: Vec<Vec<u32>> = vec![vec![]; self.nodes.len()];
+        for (i, node) in self.nodes.iter().enumerate() {
+            if let Some(parent) = node.parent {
+                if parent as usize >= i {
+                    anyhow::bail!("Node {} comes before its parent {}", i, parent);
+                }
+                children[parent as usize].push(i as u32);
+            }
+        }
+        self.decode(index, &children)
+    }
+
+    fn empty() -> Self {
+        Self {
+            version: RENDER_WIRE_VERSION,
+            nodes: vec![],
+            root: 0,
+            row_templates: vec![],
+            operations: vec![],
+        }
+    }
+
+    fn push(&mut self, node: WireNode) -> u32 {
+        self.nodes.push(node);
+        (self.nodes.len() - 1) as u32
+    }
+
+    fn push_expr(&mut self, expr: &RenderExpr, parent: Option<u32>, key: Option<String>) -> u32 {
+        match expr {
+            RenderExpr::FunctionCall {
+                name,
+                args,
+                operations,
+            } => {
+                let mut node = WireNode::new(WireNodeKind::FunctionCall, parent, key);
+                node.name = Some(name.clone());
+                for operation in operations {
+                    self.operations.push(operation.clone());
+                    node.operations.push((self.operations.len() - 1) as u32);
+                }
+                let index = self.push(node);
+                for arg in args {
+                    self.push_expr(&arg.value, Some(index), arg.name.clone());
+                }
+                index
+            }
+            RenderExpr::ColumnRef { name } => {
+                let mut node = WireNode::new(WireNodeKind::ColumnRef, parent, key);
+                node.name = Some(name.clone());
+                self.push(node)
+            }
+            RenderExpr::Literal { value } => {
+                let mut node = WireNode::new(WireNodeKind::Literal, parent, key);
+                node.value = Some(value.clone());
+                self.push(node)
+            }
+            RenderExpr::BinaryOp { op, left, right } => {
+                let mut node = WireNode::new(WireNodeKind::BinaryOp, parent, key);
+                node.op = Some(op.clone());
+                let index = self.push(node);
+                self.push_expr(left, Some(index), Some("left".to_string()));
+                self.push_expr(right, Some(index), Some("right".to_string()));
+                index
+            }
+            RenderExpr::Array { items } => {
+                let index = self.push(WireNode::new(WireNodeKind::Array, parent, key));
+                for item in items {
+                    self.push_expr(item, Some(index), None);
+                }
+                index
+            }
+            RenderExpr::Object { fields } => {
+                let index = self.push(WireNode::new(WireNodeKind::Object, parent, key));
+                // Sorted so that the same object always produces the same nodes
+                let mut fields: Vec<_> = fields.iter().collect();
+                fields.sort_by(|a, b| a.0.cmp(b.0));
+                for (name, value) in fields {
+                    self.push_expr(value, Some(index), Some(name.clone()));
+                }
+                index
+            }
+            RenderExpr::Style { rules } => {
+                let index = self.push(WireNode::new(WireNodeKind::Style, parent, key));
+                for rule in rules {
+                    let mut node = WireNode::new(WireNodeKind::StyleRule, Some(index), None);
+                    node.style = Some(rule.style.clone());
+                    let rule_index = self.push(node);
+                    if let Some(when) = &rule.when {
+                        self.push_expr(when, Some(rule_index), Some("when".to_string()));
+                    }
+                }
+                index
+            }
+            RenderExpr::Format { format, value } => {
+                let mut node = WireNode::new(WireNodeKind::Format, parent, key);
+                node.format = Some(format.clone());
+                let index = self.push(node);
+                self.push_expr(value, Some(index), None);
+                index
+            }
+            RenderExpr::Error { message } => {
+                let mut node = WireNode::new(WireNodeKind::Error, parent, key);
+                node.value = Some(Value::String(message.clone()));
+                self.push(node)
+            }
+        }
+    }
+
+    fn decode(&self, index: u32, children: &[Vec<u32>]) -> anyhow::Result<RenderExpr> {
+        let node = self
+            .nodes
+            .get(index as usize)
+            .ok_or_else(|| anyhow::anyhow!("Node {} does not exist", index))?;
+        let child_nodes = &children[index as usize];
+        let decode_children = || -> anyhow::Result<Vec<(Option<String>, RenderExpr)>> {
+            child_nodes
+                .iter()
+                .map(|&child| {
+                    Ok((
+                        self.nodes[child as usize].key.clone(),
+                        self.decode(child, children)?,
+                    ))
+                })
+                .collect()
+        };
+        let name = || {
+            node.name
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("{:?} node {} has no name", node.kind, index))
+        };
+
+        Ok(match node.kind {
+            WireNodeKind::FunctionCall => RenderExpr::FunctionCall {
+                name: name()?,
+                args: decode_children()?
+                    .into_iter()
+                    .map(|(name, value)| Arg { name, value })
+                    .collect(),
+                operations: node
+                    .operations
+                    .iter()
+                    .map(|&i| {
+                        self.operations.get(i as usize).cloned().ok_or_else(|| {
+                            anyhow::anyhow!("Operation {} of node {} does not exist", i, index)
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+            },
+            WireNodeKind::ColumnRef => RenderExpr::ColumnRef { name: name()? },
+            WireNodeKind::Literal => RenderExpr::Literal {
+                value: node.value.clone().unwrap_or(Value::Null),
+            },
+            WireNodeKind::BinaryOp => {
+                let op = node
+                    .op
+                    .clone()
+                    .ok_or_else(|| anyhow::anyhow!("BinaryOp node {} has no operator", index))?;
+                let mut operands = decode_children()?.into_iter().map(|(_, expr)| expr);
+                match (operands.next(), operands.next()) {
+                    (Some(left), Some(right)) => RenderExpr::BinaryOp {
+                        op,
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    },
+                    _ => anyhow::bail!("BinaryOp node {} needs two operands", index),
+                }
+            }
+            WireNodeKind::Array => RenderExpr::Array {
+                items: decode_children()?
+                    .into_iter()
+                    .map(|(_, expr)| expr)
+                    .collect(),
+            },
+            WireNodeKind::Object => RenderExpr::Object {
+                fields: decode_children()?
+                    .into_iter()
+                    .map(|(key, expr)| {
+                        let key = key.ok_or_else(|| {
+                            anyhow::anyhow!("Field of object node {} has no key", index)
+                        })?;
+                        Ok((key, expr))
+                    })
+                    .collect::<anyhow::Result<HashMap<_, _>>>()?,
+            },
+            WireNodeKind::Style => RenderExpr::Style {
+                rules: child_nodes
+                    .iter()
+                    .map(|&rule| {
+                        let rule_node = &self.nodes[rule as usize];
+                        let when = match children[rule as usize].first() {
+                            Some(&condition) => Some(self.decode(condition, children)?),
+                            None => None,
+                        };
+                        Ok(StyleRule {
+                            style: rule_node.style.clone().unwrap_or_default(),
+                            when,
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+            },
+            WireNodeKind::StyleRule => {
+                anyhow::bail!("StyleRule node {} outside of a Style node", index)
+            }
+            WireNodeKind::Format => {
+                let format = node
+                    .format
+                    .clone()
+                    .ok_or_else(|| anyhow::anyhow!("Format node {} has no format", index))?;
+                let value = match child_nodes.first() {
+                    Some(&value) => self.decode(value, children)?,
+                    None => anyhow::bail!("Format node {} has no value", index),
+                };
+                RenderExpr::Format {
+                    format,
+                    value: Box::new(value),
+                }
+            }
+            WireNodeKind::Error => RenderExpr::Error {
+                message: node
+                    .value
+                    .as_ref()
+                    .and_then(Value::as_string)
+                    .unwrap_or_default()
+                    .to_string(),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, args: Vec<Arg>) -> RenderExpr {
+        RenderExpr::FunctionCall {
+            name: name.to_string(),
+            args,
+            operations: vec![],
+        }
+    }
+
+    fn arg(name: Option<&str>, value: RenderExpr) -> Arg {
+        Arg {
+            name: name.map(String::from),
+            value,
+        }
+    }
+
+    fn column(name: &str) -> RenderExpr {
+        RenderExpr::ColumnRef {
+            name: name.to_string(),
+        }
+    }
+
+    /// `list item_template:(row (checkbox checked:this.completed) (text content:this.content
+    /// style:(style dim:true when:(this.completed == true))))`
+    fn sample_expr() -> RenderExpr {
+        call(
+            "list",
+            vec![arg(
+                Some("item_template"),
+                call(
+                    "row",
+                    vec![
+                        arg(
+                            None,
+                            call("checkbox", vec![arg(Some("checked"), column("completed"))]),
+                        ),
+                        arg(
+                            None,
+                            call(
+                                "text",
+                                vec![
+                                    arg(Some("content"), column("content")),
+                                    arg(
+                                        Some("style"),
+                                        RenderExpr::Style {
+                                            rules: vec![StyleRule {
+                                                style: Style {
+                                                    dim: Some(true),
+                                                    ..Default::default()
+                                                },
+                                                when: Some(RenderExpr::BinaryOp {
+                                                    op: BinaryOperator::Eq,
+                                                    left: Box::new(column("completed")),
+                                                    right: Box::new(RenderExpr::Literal {
+                                                        value: Value::Boolean(true),
+                                                    }),
+                                                }),
+                                            }],
+                                        },
+                                    ),
+                                ],
+                            ),
+                        ),
+                    ],
+                ),
+            )],
+        )
+    }
+
+    fn json(expr: &RenderExpr) -> serde_json::Value {
+        serde_json::to_value(expr).unwrap()
+    }
+
+    #[test]
+    fn test_parents_precede_children() {
+        let tree = WireRenderTree::from_render_expr(&sample_expr());
+        assert_eq!(tree.version, RENDER_WIRE_VERSION);
+        assert_eq!(tree.root, 0);
+        assert_eq!(tree.nodes[0].parent, None);
+        for (i, node) in tree.nodes.iter().enumerate().skip(1) {
+            assert!((node.parent.unwrap() as usize) < i);
+        }
+        let kinds: Vec<_> = tree.nodes.iter().map(|node| node.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                WireNodeKind::FunctionCall,
+                WireNodeKind::FunctionCall,
+                WireNodeKind::FunctionCall,
+                WireNodeKind::ColumnRef,
+                WireNodeKind::FunctionCall,
+                WireNodeKind::ColumnRef,
+                WireNodeKind::Style,
+                WireNodeKind::StyleRule,
+                WireNodeKind::BinaryOp,
+                WireNodeKind::ColumnRef,
+                WireNodeKind::Literal,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let expr = RenderExpr::Array {
+            items: vec![
+                sample_expr(),
+                RenderExpr::Object {
+                    fields: HashMap::from([
+                        ("b".to_string(), column("b")),
+                        (
+                            "c".to_string(),
+                            RenderExpr::Format {
+                                format: Format::Number { decimals: Some(2) },
+                                value: Box::new(column("amount")),
+                            },
+                        ),
+                        (
+                            "a".to_string(),
+                            RenderExpr::Literal {
+                                value: Value::Integer(1),
+                            },
+                        ),
+                    ]),
+                },
+                RenderExpr::Error {
+                    message: "unknown widget `txt`".to_string(),
+                },
+            ],
+        };
+        let tree = WireRenderTree::from_render_expr(&expr);
+        let json_tree: WireRenderTree =
+            serde_json::from_str(&serde_json::to_string(&tree).unwrap()).unwrap();
+        assert_eq!(
+            json(&json_tree.to_render_expr(tree.root).unwrap()),
+            json(&expr)
+        );
+    }
+
+    #[test]
+    fn test_decodes_version_1_fixture() {
+        // Written by version 1; optional node fields may be missing
+        let fixture = r#"{
+            "version": 1,
+            "root": 0,
+            "nodes": [
+                {"kind": "function_call", "parent": null, "name": "text"},
+                {"kind": "binary_op", "parent": 0, "key": "content", "op": "add"},
+                {"kind": "column_ref", "parent": 1, "key": "left", "name": "title"},
+                {"kind": "literal", "parent": 1, "key": "right", "value": "!"}
+            ]
+        }"#;
+        let tree: WireRenderTree = serde_json::from_str(fixture).unwrap();
+        let expected = call(
+            "text",
+            vec![arg(
+                Some("content"),
+                RenderExpr::BinaryOp {
+                    op: BinaryOperator::Add,
+                    left: Box::new(column("title")),
+                    right: Box::new(RenderExpr::Literal {
+                        value: Value::String("!".to_string()),
+                    }),
+                },
+            )],
+        );
+        assert_eq!(json(&tree.to_render_expr(0).unwrap()), json(&expected));
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let mut tree = WireRenderTree::from_render_expr(&column("title"));
+        tree.version = RENDER_WIRE_VERSION + 1;
+        assert!(tree.to_render_expr(0).is_err());
+    }
+}
@@ -3,6 +3,8 @@
 //! This module re-exports opaque types and defines enums for proper Dart pattern matching.
 
 use flutter_rust_bridge::frb;
+use holon::api::ViewEvent;
+use holon_api::{RenderSpec, Value};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 // Re-export SpanContext for generated code
@@ -13,6 +15,21 @@ pub use opentelemetry::trace::SpanContext;
 // Fields are accessed via BlockOps methods (getId, getContent, etc.)
 pub use super::{Block, BlockMetadata, NewBlock, Traversal};
 
+// Re-export the operation log entry (mirrored below for the history panel)
+pub use super::OperationLogEntry;
+
+// Re-export the diagnostics report (mirrored below for the settings page)
+pub use super::{Diagnostics, ProviderDiagnostics, QueryCacheStats, ReplicaStatus};
+
+// Re-export captured log events (mirrored below for the log viewer)
+pub use super::{LogFilter, LogRecord};
+
+// Re-export the database maintenance state (mirrored below for the status indicator)
+pub use super::MaintenanceStatus;
+
+// Re-export backend notifications (mirrored below for local notifications)
+pub use super::{Notification, NotificationSeverity};
+
 // Re-export Change from holon-api (moved from holon)
 pub use holon_api::Change;
 
@@ -58,8 +75,145 @@ pub enum _StreamPosition {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[frb(mirror(holon_api::ChangeOrigin))]
 pub enum _ChangeOrigin {
-    Local { operation_id: Option<String> },
-    Remote { operation_id: Option<String> },
+    Local {
+        operation_id: Option<String>,
+        trace_id: Option<String>,
+        source: Option<holon_api::ChangeSource>,
+    },
+    Remote {
+        operation_id: Option<String>,
+        trace_id: Option<String>,
+        source: Option<holon_api::ChangeSource>,
+    },
+}
+
+/// Who made a change (device, frontend, sync provider, agent), for badges.
+/// Mirrored from holon-api
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[frb(mirror(holon_api::ChangeSource))]
+pub struct _ChangeSource {
+    pub device_id: Option<String>,
+    pub frontend: Option<String>,
+    pub provider: Option<String>,
+    pub agent: Option<String>,
+}
+
+/// A logged operation, as shown in the operation history panel.
+/// Mirrored from holon-core
+#[frb(mirror(OperationLogEntry))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct _OperationLogEntry {
+    pub id: i64,
+    /// The executed operation (JSON)
+    pub operation: String,
+    /// The inverse operation for undo (JSON, None if not undoable)
+    pub inverse: Option<String>,
+    /// pending_sync, synced, undone or cancelled
+    pub status: String,
+    /// Unix timestamp in milliseconds
+    pub created_at: i64,
+    pub display_name: String,
+    pub entity_name: String,
+    pub op_name: String,
+}
+
+/// Backend health report, as shown on the diagnostics settings page.
+/// Mirrored from holon
+#[frb(mirror(Diagnostics))]
+#[derive(Debug, Clone)]
+pub struct _Diagnostics {
+    /// Output of `PRAGMA quick_check` ("ok" when the database is intact)
+    pub integrity: String,
+    pub integrity_ok: bool,
+    pub schema_version: Option<i64>,
+    pub providers: Vec<ProviderDiagnostics>,
+    pub query_cache: QueryCacheStats,
+    pub replica: Option<ReplicaStatus>,
+    /// Unix timestamp in milliseconds
+    pub generated_at: i64,
+}
+
+/// Sync state of one provider in the diagnostics report.
+/// Mirrored from holon
+#[frb(mirror(ProviderDiagnostics))]
+#[derive(Debug, Clone)]
+pub struct _ProviderDiagnostics {
+    pub provider_name: String,
+    pub pending_operations: i64,
+    pub dirty_entities: i64,
+    /// Unix timestamp in milliseconds
+    pub last_sync_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Remote replica state in the diagnostics report.
+/// Mirrored from holon
+#[frb(mirror(ReplicaStatus))]
+#[derive(Debug, Clone)]
+pub struct _ReplicaStatus {
+    pub sync_url: String,
+    /// Local writes are refused until the replica is downloaded from its remote
+    pub bootstrapped: bool,
+    pub syncing: bool,
+    /// Unix timestamp in milliseconds
+    pub last_sync_at: Option<i64>,
+    /// Unix timestamp in milliseconds
+    pub last_error_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+    pub syncs: u64,
+}
+
+/// Query cache counters in the diagnostics report.
+/// Mirrored from holon
+#[frb(mirror(QueryCacheStats))]
+#[derive(Debug, Clone, Copy)]
+pub struct _QueryCacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// A captured backend log event, as shown in the log viewer.
+/// Mirrored from holon
+#[frb(mirror(LogRecord))]
+#[derive(Debug, Clone)]
+pub struct _LogRecord {
+    /// Unix timestamp in milliseconds
+    pub timestamp: i64,
+    /// ERROR, WARN, INFO, DEBUG or TRACE
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    pub fields: HashMap<String, String>,
+}
+
+/// Which log events to return from recent_logs.
+/// Mirrored from holon
+#[frb(mirror(LogFilter))]
+#[derive(Debug, Clone, Default)]
+pub struct _LogFilter {
+    /// Least severe level to include (e.g. "warn")
+    pub min_level: Option<String>,
+    /// Target prefix (e.g. "holon_todoist")
+    pub target: Option<String>,
+    /// Case-insensitive substring of the message or a field value
+    pub contains: Option<String>,
+}
+
+/// State of database maintenance, for the "optimizing database" indicator.
+/// Mirrored from holon
+#[frb(mirror(MaintenanceStatus))]
+#[derive(Debug, Clone, Default)]
+pub struct _MaintenanceStatus {
+    pub running: bool,
+    /// Unix timestamps in milliseconds
+    pub last_started_at: Option<i64>,
+    pub last_finished_at: Option<i64>,
+    pub last_duration_ms: Option<i64>,
+    /// Failed steps of the last run
+    pub last_errors: Vec<String>,
+    pub runs: u64,
 }
 
 /// Structured error types for API operations.
@@ -83,6 +237,78 @@ pub enum _ApiError {
 
     #[error("Internal error: {message}")]
     InternalError { message: String },
+
+    #[error("{entity} not found: {id}")]
+    NotFound { entity: String, id: String },
+
+    #[error("Remote error ({status}): {message}")]
+    RemoteError { status: u16, message: String },
+
+    #[error("Conflict: {message}")]
+    Conflict { message: String },
+
+    #[error("Precondition failed: {message}")]
+    PreconditionViolated {
+        operation: String,
+        clause: String,
+        message: String,
+        params: HashMap<String, Value>,
+    },
+}
+
+/// How urgently a notification should be surfaced.
+/// Mirrored from holon
+#[frb(mirror(NotificationSeverity))]
+#[derive(Debug, Clone, Copy)]
+pub enum _NotificationSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// A notification raised by the backend (due reminder, sync health report).
+/// Mirrored from holon
+#[frb(mirror(Notification))]
+#[derive(Debug, Clone)]
+pub struct _Notification {
+    pub title: String,
+    pub body: String,
+    pub severity: NotificationSeverity,
+    /// Entity the notification refers to, as (entity_name, id)
+    pub entity: Option<(String, String)>,
+}
+
+/// Change to a view defined by a `.prql` file in the view directory
+#[derive(Debug, Clone)]
+pub enum ViewUpdate {
+    /// The view was added or its file changed; re-run `prql` to show it
+    Updated {
+        name: String,
+        prql: String,
+        render_spec: RenderSpec,
+    },
+    /// The view's file does not compile; the last valid definition stays in use
+    Failed {
+        name: String,
+        error: String,
+    },
+    Removed {
+        name: String,
+    },
+}
+
+impl From<ViewEvent> for ViewUpdate {
+    fn from(event: ViewEvent) -> Self {
+        match event {
+            ViewEvent::Updated(view) => ViewUpdate::Updated {
+                name: view.name,
+                prql: view.prql,
+                render_spec: view.render_spec,
+            },
+            ViewEvent::Failed { name, error } => ViewUpdate::Failed { name, error },
+            ViewEvent::Removed { name } => ViewUpdate::Removed { name },
+        }
+    }
 }
 
 /// Trace context for propagating OpenTelemetry trace information across FFI boundary.
@@ -0,0 +1,48 @@
+import 'package:flutter_test/flutter_test.dart';
+import 'package:holon/render/windowed_rows.dart';
+import 'package:holon/services/mock_backend_service.dart';
+import 'package:holon/src/rust/third_party/holon_api.dart'
+    show Value, Value_String;
+import 'package:holon/src/rust/third_party/holon_api/streaming.dart'
+    show WindowChange, WindowChangeBatch;
+
+Map<String, Value> row(String id) => {'id': Value_String(id)};
+
+String? idAt(WindowedRows rows, int index) =>
+    (rows.rowAt(index)?['id'] as Value_String?)?.field0;
+
+void main() {
+  test('applies window-relative changes', () {
+    final rows = WindowedRows(
+      backend: MockBackendService(),
+      queryId: BigInt.zero,
+      windowSize: 2,
+    );
+
+    rows.apply(
+      WindowChangeBatch(
+        start: BigInt.from(10),
+        total: BigInt.from(50),
+        changes: [
+          WindowChange.reset(rows: [row('a'), row('b')]),
+        ],
+      ),
+    );
+    expect([idAt(rows, 10), idAt(rows, 11)], ['a', 'b']);
+    expect(rows.rowAt(12), isNull);
+
+    // A row inserted above the window shifts it down by one
+    rows.apply(
+      WindowChangeBatch(
+        start: BigInt.from(10),
+        total: BigInt.from(51),
+        changes: [
+          WindowChange.removed(index: BigInt.one),
+          WindowChange.inserted(index: BigInt.zero, data: row('z')),
+        ],
+      ),
+    );
+    expect([idAt(rows, 10), idAt(rows, 11)], ['z', 'a']);
+    expect(rows.total, 51);
+  });
+}
@@ -1,16 +1,18 @@
 use super::components::{BlockListComponent, ComponentId};
+use super::operations_screen::OperationsScreen;
 use super::render_interpreter::RenderInterpreter;
 use super::state::{AppSignal, State};
 use super::stylesheet::{self, StyleId};
 use super::ui_element::UIElement;
+use holon::core::log_buffer::LogFilter;
 use r3bl_tui::{
     box_end, box_start, col, height, new_style, render_component_in_current_box,
     render_tui_styled_texts_into, req_size_pc, row, surface, throws, throws_with_return, tui_color,
     tui_styled_text, tui_styled_texts, App, BoxedSafeApp, CommonResult, ComponentRegistry,
     ComponentRegistryMap, EventPropagation, FlexBoxId, GlobalData, HasFocus, InputEvent, Key,
     KeyPress, LayoutDirection, LayoutManagement, LengthOps, PerformPositioningAndSizing, Pos,
-    RenderOpCommon, RenderOpIR, RenderOpIRVec, RenderPipeline, Size, Surface, SurfaceProps,
-    SurfaceRender, ZOrder, SPACER_GLYPH,
+    RenderOpCommon, RenderOpIR, RenderOpIRVec, RenderPipeline, Size, SpecialKey, Surface,
+    SurfaceProps, SurfaceRender, ZOrder, SPACER_GLYPH,
 };
 use std::marker::PhantomData;
 
@@ -105,6 +107,197 @@ impl App for AppMain {
                 }
             }
 
+            // Ctrl+d toggles the diagnostics debug screen; Esc closes it
+            if global_data.state.diagnostics.is_some() {
+                let closes = matches!(
+                    input_event,
+                    InputEvent::Keyboard(KeyPress::Plain {
+                        key: Key::SpecialKey(SpecialKey::Esc),
+                    }) | InputEvent::Keyboard(KeyPress::WithModifiers {
+                        key: Key::Character('d'),
+                        ..
+                    })
+                );
+                if closes {
+                    global_data.state.diagnostics = None;
+                }
+                // The block list is hidden, so it gets no input
+                return Ok(EventPropagation::ConsumedRender);
+            }
+            if let InputEvent::Keyboard(KeyPress::WithModifiers {
+                key: Key::Character('d'),
+                mask,
+            }) = input_event
+            {
+                if mask.ctrl_key_state == r3bl_tui::KeyState::Pressed
+                    && global_data.state.editing_block_index.is_none()
+                {
+                    let engine = global_data.state.engine.clone();
+                    let sender_opt = global_data
+                        .state
+                        .main_thread_sender_channel
+                        .lock()
+                        .unwrap()
+                        .clone();
+
+                    tokio::spawn(async move {
+                        let mut lines = match engine.diagnostics().await {
+                            Ok(diagnostics) => diagnostics.summary_lines(),
+                            Err(e) => vec![format!("Failed to load diagnostics: {}", e)],
+                        };
+                        let warnings = LogFilter {
+                            min_level: Some("warn".to_string()),
+                            ..Default::default()
+                        };
+                        let recent = engine.recent_logs(&warnings, 10);
+                        if !recent.is_empty() {
+                            lines.push(String::new());
+                            lines.push("Recent warnings and errors:".to_string());
+                        }
+                        lines.extend(recent.iter().map(|record| {
+                            format!("{} {}: {}", record.level, record.target, record.message)
+                        }));
+                        if let Some(sender) = sender_opt {
+                            let _ = sender
+                                .send(r3bl_tui::TerminalWindowMainThreadSignal::ApplyAppSignal(
+                                    AppSignal::DiagnosticsLoaded { lines },
+                                ))
+                                .await;
+                        }
+                    });
+
+                    global_data.state.diagnostics =
+                        Some(vec!["Loading diagnostics...".to_string()]);
+                    return Ok(EventPropagation::ConsumedRender);
+                }
+            }
+
+            // Ctrl+p toggles the operations debug screen
+            if let Some(screen) = &mut global_data.state.operations_screen {
+                let operation = match input_event {
+                    InputEvent::Keyboard(KeyPress::Plain {
+                        key: Key::SpecialKey(SpecialKey::Esc),
+                    }) if screen.is_prompting() => {
+                        screen.cancel_prompt();
+                        None
+                    }
+                    InputEvent::Keyboard(KeyPress::Plain {
+                        key: Key::SpecialKey(SpecialKey::Esc),
+                    })
+                    | InputEvent::Keyboard(KeyPress::WithModifiers {
+                        key: Key::Character('p'),
+                        ..
+                    }) => {
+                        global_data.state.operations_screen = None;
+                        None
+                    }
+                    InputEvent::Keyboard(KeyPress::Plain {
+                        key: Key::SpecialKey(SpecialKey::Up),
+                    }) => {
+                        screen.select_previous();
+                        None
+                    }
+                    InputEvent::Keyboard(KeyPress::Plain {
+                        key: Key::SpecialKey(SpecialKey::Down),
+                    }) => {
+                        screen.select_next();
+                        None
+                    }
+                    InputEvent::Keyboard(KeyPress::Plain {
+                        key: Key::SpecialKey(SpecialKey::Enter),
+                    }) => {
+                        if screen.is_prompting() {
+                            screen.submit()
+                        } else {
+                            screen.start_prompt()
+                        }
+                    }
+                    InputEvent::Keyboard(KeyPress::Plain {
+                        key: Key::SpecialKey(SpecialKey::Backspace),
+                    }) => {
+                        screen.backspace();
+                        None
+                    }
+                    InputEvent::Keyboard(KeyPress::Plain {
+                        key: Key::Character(c),
+                    }) => {
+                        screen.type_char(c);
+                        None
+                    }
+                    _ => None,
+                };
+
+                if let Some(operation) = operation {
+                    let engine = global_data.state.engine.clone();
+                    let sender_opt = global_data
+                        .state
+                        .main_thread_sender_channel
+                        .lock()
+                        .unwrap()
+                        .clone();
+                    let operation_name = format!("{}.{}", operation.entity_name, operation.op_name);
+
+                    tracing::info!(
+                        "[TUI] Running {} from the operations screen",
+                        operation_name
+                    );
+                    global_data.state.status_message = format!("Running {}...", operation_name);
+                    tokio::spawn(async move {
+                        let result = engine
+                            .execute_operation(
+                                &operation.entity_name,
+                                &operation.op_name,
+                                operation.params,
+                            )
+                            .await;
+                        if let Some(sender) = sender_opt {
+                            let _ = sender
+                                .send(r3bl_tui::TerminalWindowMainThreadSignal::ApplyAppSignal(
+                                    AppSignal::OperationResult {
+                                        operation_name,
+                                        success: result.is_ok(),
+                                        error_message: result.err().map(|e| e.to_string()),
+                                    },
+                                ))
+                                .await;
+                        }
+                    });
+                }
+                // The block list is hidden, so it gets no input
+                return Ok(EventPropagation::ConsumedRender);
+            }
+            if let InputEvent::Keyboard(KeyPress::WithModifiers {
+                key: Key::Character('p'),
+                mask,
+            }) = input_event
+            {
+                if mask.ctrl_key_state == r3bl_tui::KeyState::Pressed
+                    && global_data.state.editing_block_index.is_none()
+                {
+                    let engine = global_data.state.engine.clone();
+                    let sender_opt = global_data
+                        .state
+                        .main_thread_sender_channel
+                        .lock()
+                        .unwrap()
+                        .clone();
+
+                    tokio::spawn(async move {
+                        let operations = engine.all_operations().await;
+                        if let Some(sender) = sender_opt {
+                            let _ = sender
+                                .send(r3bl_tui::TerminalWindowMainThreadSignal::ApplyAppSignal(
+                                    AppSignal::OperationsLoaded { operations },
+                                ))
+                                .await;
+                        }
+                    });
+
+                    global_data.state.operations_screen = Some(OperationsScreen::loading());
+                    return Ok(EventPropagation::ConsumedRender);
+                }
+            }
+
             // Skip app-level shortcuts when editing (let component handle all input)
             if global_data.state.editing_block_index.is_some() {
                 // Route all events to the focused component when editing
@@ -116,6 +309,47 @@ impl App for AppMain {
                 )?);
             }
 
+            // Ctrl+o optimizes the database now instead of waiting for an idle period
+            if let InputEvent::Keyboard(KeyPress::WithModifiers {
+                key: Key::Character('o'),
+                mask,
+            }) = input_event
+            {
+                if mask.ctrl_key_state == r3bl_tui::KeyState::Pressed {
+                    let engine = global_data.state.engine.clone();
+                    let sender_opt = global_data
+                        .state
+                        .main_thread_sender_channel
+                        .lock()
+                        .unwrap()
+                        .clone();
+
+                    tracing::info!("[TUI] Database optimization triggered by user (Ctrl+o)");
+                    tokio::spawn(async move {
+                        let result = engine.run_maintenance().await;
+                        let error_message = match result {
+                            Ok(status) if status.last_errors.is_empty() => None,
+                            Ok(status) => Some(status.last_errors.join("; ")),
+                            Err(e) => Some(e.to_string()),
+                        };
+                        if let Some(sender) = sender_opt {
+                            let _ = sender
+                                .send(r3bl_tui::TerminalWindowMainThreadSignal::ApplyAppSignal(
+                                    AppSignal::OperationResult {
+                                        operation_name: "Database optimization".to_string(),
+                                        success: error_message.is_none(),
+                                        error_message,
+                                    },
+                                ))
+                                .await;
+                        }
+                    });
+
+                    global_data.state.status_message = "Database optimization started".to_string();
+                    return Ok(EventPropagation::ConsumedRender);
+                }
+            }
+
             // Handle app-level shortcuts (like Ctrl+r for sync)
             // Only when NOT editing
             if let InputEvent::Keyboard(KeyPress::WithModifiers {
@@ -292,6 +526,32 @@ impl App for AppMain {
                         global_data.state.status_message =
                             format!("{} failed: {}", operation_name, error_msg);
                     }
+                    if let Some(screen) = &mut global_data.state.operations_screen {
+                        screen.set_result(global_data.state.status_message.clone());
+                    }
+                }
+                AppSignal::Notify { message } => {
+                    global_data.state.status_message = message.clone();
+                }
+                AppSignal::RevertBlockMove {
+                    id,
+                    parent_id,
+                    sort_key,
+                } => {
+                    global_data
+                        .state
+                        .revert_block_move(id, parent_id.clone(), sort_key.clone());
+                }
+                AppSignal::DiagnosticsLoaded { lines } => {
+                    // Ignore reports arriving after the screen was closed
+                    if global_data.state.diagnostics.is_some() {
+                        global_data.state.diagnostics = Some(lines.clone());
+                    }
+                }
+                AppSignal::OperationsLoaded { operations } => {
+                    if let Some(screen) = &mut global_data.state.operations_screen {
+                        screen.set_operations(operations.clone());
+                    }
                 }
                 AppSignal::Noop => {}
             }
@@ -349,12 +609,39 @@ impl App for AppMain {
                 it
             };
 
-            // Render status bar (last row)
-            render_status_bar(
-                &mut surface.render_pipeline,
-                window_size,
-                &global_data.state.status_message,
-            );
+            // Debug screens cover the block list
+            if let Some(lines) = &global_data.state.diagnostics {
+                render_debug_screen(
+                    &mut surface.render_pipeline,
+                    window_size,
+                    "Diagnostics (Ctrl+d or Esc: close)",
+                    lines,
+                );
+            } else if let Some(screen) = &global_data.state.operations_screen {
+                let lines = screen.lines(debug_screen_rows(window_size));
+                render_debug_screen(
+                    &mut surface.render_pipeline,
+                    window_size,
+                    "Operations (↑/↓: select | Enter: run | Esc: cancel/close)",
+                    &lines,
+                );
+            }
+
+            // Render status bar (last row), noting background maintenance runs
+            let optimizing = global_data
+                .state
+                .engine
+                .maintenance_status()
+                .is_some_and(|status| status.running);
+            let status_message = if optimizing {
+                format!(
+                    "Optimizing database... {}",
+                    global_data.state.status_message
+                )
+            } else {
+                global_data.state.status_message.clone()
+            };
+            render_status_bar(&mut surface.render_pipeline, window_size, &status_message);
 
             surface.render_pipeline
         });
@@ -399,7 +686,7 @@ fn render_status_bar(pipeline: &mut RenderPipeline, size: Size, status_msg: &str
     let color_bg = tui_color!(hex "#076DEB");
     let color_fg = tui_color!(hex "#E9C940");
 
-    let help_text = format!("Ctrl+q: Exit | ↑/↓: Navigate/Edit | Ctrl+x: Toggle | Ctrl+r: Sync | Ctrl+→/←: Indent/Outdent | Ctrl+↑/↓: Move | Alt+Enter: Split | {}", status_msg);
+    let help_text = format!("Ctrl+q: Exit | ↑/↓: Navigate/Edit | Ctrl+x: Toggle | Ctrl+r: Sync | Ctrl+d: Diagnostics | Ctrl+p: Operations | Ctrl+o: Optimize DB | Ctrl+→/←: Indent/Outdent | Ctrl+↑/↓: Move | Alt+Enter: Split | {}", status_msg);
 
     // Use stylesheet for status bar styling
     let styled_texts = tui_styled_texts! {
@@ -430,6 +717,51 @@ fn render_status_bar(pipeline: &mut RenderPipeline, size: Size, status_msg: &str
     pipeline.push(ZOrder::Normal, render_ops);
 }
 
+/// Number of lines a debug screen shows below its heading
+fn debug_screen_rows(size: Size) -> usize {
+    size.row_height.as_usize().saturating_sub(4)
+}
+
+/// Render a debug screen between the title bar and the status bar
+fn render_debug_screen(pipeline: &mut RenderPipeline, size: Size, heading: &str, lines: &[String]) {
+    let blank = SPACER_GLYPH.repeat(size.col_width.as_usize());
+    let last_row = size.row_height.as_usize().saturating_sub(1);
+    let heading = heading.to_string();
+
+    let mut render_ops = RenderOpIRVec::new();
+    for row_index in 1..last_row {
+        render_ops += RenderOpIR::Common(RenderOpCommon::MoveCursorPositionAbs(Pos::from((
+            col(0),
+            row(row_index),
+        ))));
+        render_ops += RenderOpCommon::ResetColor;
+        render_ops += RenderOpIR::PaintTextWithAttributes(blank.clone().into(), None);
+    }
+    for (row_index, line) in std::iter::once(&heading)
+        .chain(lines)
+        .enumerate()
+        .take(last_row.saturating_sub(2))
+    {
+        let color_fg = if row_index == 0 {
+            tui_color!(hex "#00AAFF")
+        } else {
+            tui_color!(hex "#E0E0E0")
+        };
+        let styled_texts = tui_styled_texts! {
+            tui_styled_text! {
+                @style: new_style!(color_fg:{color_fg}),
+                @text: line.as_str()
+            },
+        };
+        render_ops += RenderOpIR::Common(RenderOpCommon::MoveCursorPositionAbs(Pos::from((
+            col(2),
+            row(row_index + 2),
+        ))));
+        render_tui_styled_texts_into(&styled_texts, &mut render_ops);
+    }
+    pipeline.push(ZOrder::Glass, render_ops);
+}
+
 /// Helper function to save the currently editing block when exiting the app
 /// This rebuilds the element tree and saves the block content synchronously
 /// to ensure the save completes before the app exits
@@ -442,6 +774,7 @@ fn save_editing_block_on_exit(global_data: &mut GlobalData<State, AppSignal>) {
                 &global_data.state.render_spec,
                 &global_data.state.data,
                 global_data.state.selected_index,
+                &global_data.state.selected_ids,
             );
 
             // Extract operation info from the EditableText
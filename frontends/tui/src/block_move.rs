@@ -0,0 +1,170 @@
+//! Keyboard-driven block moves computed from the rendered tree
+//!
+//! The TUI keeps `State::data` in visual (depth-first) order, so moving a block
+//! up/down or indenting/outdenting it only needs its visible siblings: the new
+//! parent, the anchor block to insert after, and a fractional sort key between the
+//! new neighbours. The sort key is used for the optimistic local reorder; the
+//! backend computes its own when it executes the move.
+
+use holon::storage::fractional_index::gen_key_between;
+use holon::storage::types::StorageEntity;
+use holon_api::Value;
+
+/// Direction of a keyboard block move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveDirection {
+    Up,
+    Down,
+    Indent,
+    Outdent,
+}
+
+impl MoveDirection {
+    /// Map a key binding action name to a move direction
+    pub fn from_action(name: &str) -> Option<Self> {
+        match name {
+            "move_up" | "move_block_up" => Some(Self::Up),
+            "move_down" | "move_block_down" => Some(Self::Down),
+            "indent" | "indent_block" => Some(Self::Indent),
+            "outdent" | "outdent_block" => Some(Self::Outdent),
+            _ => None,
+        }
+    }
+
+    /// Status message shown while the move is in flight
+    pub fn status(&self) -> &'static str {
+        match self {
+            Self::Up => "Moving up...",
+            Self::Down => "Moving down...",
+            Self::Indent => "Indenting...",
+            Self::Outdent => "Outdenting...",
+        }
+    }
+}
+
+/// Target position of a moved block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockMove {
+    pub id: String,
+    pub parent_id: String,
+    /// Sibling to insert after (None = first child of `parent_id`)
+    pub after_block_id: Option<String>,
+    /// Sort key between the new neighbours (for the optimistic reorder)
+    pub sort_key: String,
+}
+
+fn field<'a>(row: &'a StorageEntity, name: &str) -> Option<&'a str> {
+    row.get(name).and_then(|v| v.as_string())
+}
+
+fn id_of(row: &StorageEntity) -> Result<&str, String> {
+    field(row, "id").ok_or_else(|| "Block has no id".to_string())
+}
+
+/// Indices of the visible children of `parent_id`, in visual order
+fn siblings(rows: &[StorageEntity], parent_id: Option<&str>) -> Vec<usize> {
+    rows.iter()
+        .enumerate()
+        .filter(|(_, row)| field(row, "parent_id") == parent_id)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn key_between(
+    rows: &[StorageEntity],
+    prev: Option<usize>,
+    next: Option<usize>,
+) -> Result<String, String> {
+    let prev_key = prev.and_then(|i| field(&rows[i], "sort_key"));
+    let next_key = next.and_then(|i| field(&rows[i], "sort_key"));
+    gen_key_between(prev_key, next_key).map_err(|e| e.to_string())
+}
+
+/// Compute where the block at `index` goes when moved in `direction`
+///
+/// `rows` must be in visual order (as produced by `State::sort_hierarchically`).
+pub fn compute_block_move(
+    rows: &[StorageEntity],
+    index: usize,
+    direction: MoveDirection,
+) -> Result<BlockMove, String> {
+    let row = rows
+        .get(index)
+        .ok_or_else(|| "No block selected".to_string())?;
+    let id = id_of(row)?.to_string();
+    let parent_id = field(row, "parent_id");
+    let sibs = siblings(rows, parent_id);
+    let pos = sibs
+        .iter()
+        .position(|&i| i == index)
+        .expect("block is among its parent's children");
+
+    let (new_parent, after, sort_key) = match direction {
+        MoveDirection::Up => {
+            if pos == 0 {
+                return Err("Block is already the first of its siblings".to_string());
+            }
+            let before = pos.checked_sub(2).map(|p| sibs[p]);
+            let sort_key = key_between(rows, before, Some(sibs[pos - 1]))?;
+            (parent_id, before, sort_key)
+        }
+        MoveDirection::Down => {
+            let next = *sibs
+                .get(pos + 1)
+                .ok_or_else(|| "Block is already the last of its siblings".to_string())?;
+            let sort_key = key_between(rows, Some(next), sibs.get(pos + 2).copied())?;
+            (parent_id, Some(next), sort_key)
+        }
+        MoveDirection::Indent => {
+            if pos == 0 {
+                return Err("No previous sibling to indent under".to_string());
+            }
+            let new_parent = id_of(&rows[sibs[pos - 1]])?;
+            let last_child = siblings(rows, Some(new_parent)).last().copied();
+            let sort_key = key_between(rows, last_child, None)?;
+            (Some(new_parent), last_child, sort_key)
+        }
+        MoveDirection::Outdent => {
+            let parent_id = parent_id.ok_or_else(|| "Block is already top-level".to_string())?;
+            let parent = rows
+                .iter()
+                .position(|r| field(r, "id") == Some(parent_id))
+                .ok_or_else(|| "Parent block is not visible".to_string())?;
+            let grandparent = field(&rows[parent], "parent_id");
+            let parent_sibs = siblings(rows, grandparent);
+            let parent_pos = parent_sibs
+                .iter()
+                .position(|&i| i == parent)
+                .expect("parent is among its parent's children");
+            let sort_key =
+                key_between(rows, Some(parent), parent_sibs.get(parent_pos + 1).copied())?;
+            (grandparent, Some(parent), sort_key)
+        }
+    };
+
+    let parent_id = new_parent
+        .ok_or_else(|| "Cannot move a block to the top level".to_string())?
+        .to_string();
+    let after_block_id = match after {
+        Some(i) => Some(id_of(&rows[i])?.to_string()),
+        None => None,
+    };
+    Ok(BlockMove {
+        id,
+        parent_id,
+        after_block_id,
+        sort_key,
+    })
+}
+
+/// Apply a move to the local rows; returns the previous `(parent_id, sort_key)` for rollback
+pub fn apply_block_move(rows: &mut [StorageEntity], mv: &BlockMove) -> Option<(Value, Value)> {
+    let row = rows.iter_mut().find(|r| field(r, "id") == Some(&mv.id))?;
+    let previous_parent = row
+        .insert("parent_id".to_string(), Value::String(mv.parent_id.clone()))
+        .unwrap_or(Value::Null);
+    let previous_key = row
+        .insert("sort_key".to_string(), Value::String(mv.sort_key.clone()))
+        .unwrap_or(Value::Null);
+    Some((previous_parent, previous_key))
+}
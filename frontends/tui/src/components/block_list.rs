@@ -1,3 +1,4 @@
+use crate::block_move::MoveDirection;
 use crate::config::{Action, BindingContext};
 use crate::render_interpreter::RenderInterpreter;
 use crate::state::{AppSignal, State};
@@ -361,6 +362,196 @@ fn handle_split_block(
     });
 }
 
+/// Move the selected block (up/down/indent/outdent) with an optimistic reorder
+fn move_selected_block(
+    component: &mut BlockListComponent,
+    global_data: &mut GlobalData<State, AppSignal>,
+    direction: MoveDirection,
+) {
+    let selected_index = global_data.state.selected_index;
+    let entity_name = match extract_operation_info(&component.element_tree, selected_index) {
+        Some((_, entity_name, _, _)) => entity_name,
+        None => {
+            global_data.state.status_message =
+                format!("No movable block at index {}", selected_index);
+            return;
+        }
+    };
+
+    // The reorder changes row indices, so leave edit mode (saving) first
+    if let Some(editing_idx) = global_data.state.editing_block_index {
+        save_current_block_without_exit(component, global_data, editing_idx);
+        exit_edit_mode(component, global_data);
+    }
+
+    global_data.state.status_message = match global_data
+        .state
+        .move_selected_block(direction, &entity_name)
+    {
+        Ok(()) => direction.status().to_string(),
+        Err(e) => format!("Move failed: {}", e),
+    };
+}
+
+/// Direction of a board lane move action (true for the next lane)
+fn lane_move(action: &str) -> Option<bool> {
+    match action {
+        "move_to_next_lane" => Some(true),
+        "move_to_previous_lane" => Some(false),
+        _ => None,
+    }
+}
+
+/// Update the multi-selection for a selection key binding
+fn update_selection(global_data: &mut GlobalData<State, AppSignal>, action: &str) {
+    let state = &mut global_data.state;
+    let result = match action {
+        "toggle_selection" => state.toggle_selection(),
+        "select_all" => state.toggle_select_all(),
+        _ => {
+            state.clear_selection();
+            Ok(0)
+        }
+    };
+    state.status_message = match result {
+        Ok(0) => "Selection cleared".to_string(),
+        Ok(count) => format!("{} selected", count),
+        Err(e) => e,
+    };
+}
+
+/// Find an operation on any selected block that can be applied to all of them
+fn find_batch_operation(
+    component: &BlockListComponent,
+    global_data: &mut GlobalData<State, AppSignal>,
+    op_name: &str,
+) -> Option<query_render::OperationWiring> {
+    let wiring = global_data
+        .state
+        .selected_indices()
+        .into_iter()
+        .filter_map(|index| component.element_tree.get(index))
+        .find_map(|element| element.find_operation_wiring(op_name))
+        .cloned();
+    match wiring {
+        Some(wiring) if wiring.accepts_multiple => Some(wiring),
+        Some(_) => {
+            global_data.state.status_message =
+                format!("'{}' cannot be applied to multiple blocks", op_name);
+            None
+        }
+        None => {
+            global_data.state.status_message =
+                format!("Operation '{}' not found on selected blocks", op_name);
+            None
+        }
+    }
+}
+
+/// Set the completion checkbox of all selected blocks
+///
+/// Completes all of them unless all are already completed, in which case all are
+/// un-completed (like toggling a single block).
+fn complete_selection(
+    component: &BlockListComponent,
+    global_data: &mut GlobalData<State, AppSignal>,
+) {
+    let indices = global_data.state.selected_indices();
+    let Some(operation) = indices
+        .iter()
+        .filter_map(|&index| component.element_tree.get(index))
+        .find_map(|element| element.get_operation())
+        .cloned()
+    else {
+        global_data.state.status_message = "No checkbox on selected blocks".to_string();
+        return;
+    };
+    if !operation.accepts_multiple {
+        global_data.state.status_message = format!(
+            "'{}' cannot be applied to multiple blocks",
+            operation.descriptor.name
+        );
+        return;
+    }
+
+    let field = get_field_name(&operation);
+    let all_done = indices
+        .iter()
+        .all(|&index| match global_data.state.data[index].get(&field) {
+            Some(holon_api::Value::Boolean(b)) => *b,
+            Some(holon_api::Value::Integer(i)) => *i != 0,
+            _ => false,
+        });
+    let rows = indices
+        .iter()
+        .filter_map(|&index| {
+            let id = global_data.state.data[index].get(&operation.descriptor.id_column)?;
+            Some(holon::storage::types::StorageEntity::from([
+                (operation.descriptor.id_column.clone(), id.clone()),
+                ("field".to_string(), holon_api::Value::String(field.clone())),
+                ("value".to_string(), holon_api::Value::Boolean(!all_done)),
+            ]))
+        })
+        .collect();
+
+    global_data.state.status_message = match global_data
+        .state
+        .execute_batch_operation(&operation.descriptor, rows)
+    {
+        Ok(()) if all_done => format!("Reopening {} blocks...", indices.len()),
+        Ok(()) => format!("Completing {} blocks...", indices.len()),
+        Err(e) => format!("Complete all failed: {}", e),
+    };
+}
+
+/// Apply a key binding action to every selected block
+///
+/// Returns false for actions that only make sense for the block under the cursor
+/// (editing, splitting), which are then handled as usual.
+fn execute_on_selection(
+    component: &BlockListComponent,
+    global_data: &mut GlobalData<State, AppSignal>,
+    action: &str,
+) -> bool {
+    let op_name = match MoveDirection::from_action(action) {
+        Some(MoveDirection::Indent) => "indent",
+        Some(MoveDirection::Outdent) => "outdent",
+        Some(MoveDirection::Up | MoveDirection::Down) => {
+            global_data.state.status_message =
+                "Moving a selection up/down is not supported; clear the selection first"
+                    .to_string();
+            return true;
+        }
+        None => match action {
+            "toggle_completion" => {
+                complete_selection(component, global_data);
+                return true;
+            }
+            "start_editing" | "save_and_exit" | "split_block" => return false,
+            op_name => op_name,
+        },
+    };
+
+    let Some(wiring) = find_batch_operation(component, global_data, op_name) else {
+        return true;
+    };
+    let result = global_data
+        .state
+        .selection_params(&wiring.descriptor)
+        .and_then(|rows| {
+            let count = rows.len();
+            global_data
+                .state
+                .execute_batch_operation(&wiring.descriptor, rows)
+                .map(|()| count)
+        });
+    global_data.state.status_message = match result {
+        Ok(count) => format!("{} ({} blocks)...", wiring.descriptor.display_name, count),
+        Err(e) => format!("{} failed: {}", op_name, e),
+    };
+    true
+}
+
 /// Component that displays and manages the block list with hierarchical structure
 pub struct BlockListComponent {
     id: FlexBoxId,
@@ -407,6 +598,7 @@ impl BlockListComponent {
             &global_data.state.render_spec,
             &global_data.state.data,
             global_data.state.selected_index,
+            &global_data.state.selected_ids,
         );
     }
 
@@ -426,6 +618,39 @@ impl BlockListComponent {
         global_data: &mut GlobalData<State, AppSignal>,
     ) -> CommonResult<EventPropagation> {
         throws_with_return!({
+            // Block moves are computed from the rendered tree rather than dispatched as-is
+            let (Action::Operation(name) | Action::Special(name)) = action;
+            if matches!(
+                name.as_str(),
+                "toggle_selection" | "select_all" | "clear_selection"
+            ) {
+                update_selection(global_data, name);
+                return Ok(EventPropagation::ConsumedRender);
+            }
+
+            // With a multi-selection, actions apply to all selected blocks
+            if !global_data.state.selected_ids.is_empty()
+                && execute_on_selection(self, global_data, name)
+            {
+                return Ok(EventPropagation::ConsumedRender);
+            }
+
+            if let Some(forward) = lane_move(name) {
+                global_data.state.status_message = match global_data
+                    .state
+                    .move_selected_card(forward)
+                {
+                    Ok(lane) => format!("Moved to {}", RenderInterpreter::value_to_string(&lane)),
+                    Err(e) => format!("Move failed: {}", e),
+                };
+                return Ok(EventPropagation::ConsumedRender);
+            }
+
+            if let Some(direction) = MoveDirection::from_action(name) {
+                move_selected_block(self, global_data, direction);
+                return Ok(EventPropagation::ConsumedRender);
+            }
+
             match action {
                 Action::Operation(op_name) => {
                     // Execute operation on selected block
@@ -733,23 +958,11 @@ impl Component<State, AppSignal> for BlockListComponent {
                             // 'x' is now handled via keybindings (Ctrl+x), so removed from fallback
                             ']' => {
                                 event_consumed = true;
-                                execute_operation_with_status(
-                                    self,
-                                    global_data,
-                                    "indent",
-                                    "Indenting...",
-                                    None,
-                                );
+                                move_selected_block(self, global_data, MoveDirection::Indent);
                             }
                             '[' => {
                                 event_consumed = true;
-                                execute_operation_with_status(
-                                    self,
-                                    global_data,
-                                    "outdent",
-                                    "Outdenting...",
-                                    None,
-                                );
+                                move_selected_block(self, global_data, MoveDirection::Outdent);
                             }
                             _ => {}
                         }
@@ -808,13 +1021,7 @@ impl Component<State, AppSignal> for BlockListComponent {
                             }
                             SpecialKey::Tab => {
                                 event_consumed = true;
-                                execute_operation_with_status(
-                                    self,
-                                    global_data,
-                                    "indent",
-                                    "Indenting...",
-                                    None,
-                                );
+                                move_selected_block(self, global_data, MoveDirection::Indent);
                             }
                             _ => {}
                         }
@@ -914,53 +1121,35 @@ impl Component<State, AppSignal> for BlockListComponent {
                         match special_key {
                             SpecialKey::Tab if mask.shift_key_state == KeyState::Pressed => {
                                 event_consumed = true;
-                                execute_operation_with_status(
-                                    self,
-                                    global_data,
-                                    "outdent",
-                                    "Outdenting...",
-                                    None,
-                                );
+                                move_selected_block(self, global_data, MoveDirection::Outdent);
                             }
-                            SpecialKey::Up if mask.ctrl_key_state == KeyState::Pressed => {
+                            SpecialKey::Up
+                                if mask.ctrl_key_state == KeyState::Pressed
+                                    || mask.alt_key_state == KeyState::Pressed =>
+                            {
                                 event_consumed = true;
-                                execute_operation_with_status(
-                                    self,
-                                    global_data,
-                                    "move_up",
-                                    "Moving up...",
-                                    None,
-                                );
+                                move_selected_block(self, global_data, MoveDirection::Up);
                             }
-                            SpecialKey::Down if mask.ctrl_key_state == KeyState::Pressed => {
+                            SpecialKey::Down
+                                if mask.ctrl_key_state == KeyState::Pressed
+                                    || mask.alt_key_state == KeyState::Pressed =>
+                            {
                                 event_consumed = true;
-                                execute_operation_with_status(
-                                    self,
-                                    global_data,
-                                    "move_down",
-                                    "Moving down...",
-                                    None,
-                                );
+                                move_selected_block(self, global_data, MoveDirection::Down);
                             }
-                            SpecialKey::Right if mask.ctrl_key_state == KeyState::Pressed => {
+                            SpecialKey::Right
+                                if mask.ctrl_key_state == KeyState::Pressed
+                                    || mask.alt_key_state == KeyState::Pressed =>
+                            {
                                 event_consumed = true;
-                                execute_operation_with_status(
-                                    self,
-                                    global_data,
-                                    "indent",
-                                    "Indenting...",
-                                    None,
-                                );
+                                move_selected_block(self, global_data, MoveDirection::Indent);
                             }
-                            SpecialKey::Left if mask.ctrl_key_state == KeyState::Pressed => {
+                            SpecialKey::Left
+                                if mask.ctrl_key_state == KeyState::Pressed
+                                    || mask.alt_key_state == KeyState::Pressed =>
+                            {
                                 event_consumed = true;
-                                execute_operation_with_status(
-                                    self,
-                                    global_data,
-                                    "outdent",
-                                    "Outdenting...",
-                                    None,
-                                );
+                                move_selected_block(self, global_data, MoveDirection::Outdent);
                             }
                             _ => {}
                         }
@@ -1,16 +1,46 @@
-use super::{app_main::AppMain, config::KeyBindingConfig, state::State};
+use super::{
+    app_main::AppMain, config::KeyBindingConfig, notifications::TuiNotificationSink, state::State,
+};
 use ferrous_di::ServiceCollectionModuleExt;
+use holon::core::log_buffer::LogBuffer;
+use holon_api::ChangeSource;
 use r3bl_tui::{ok, CommonResult, InputEvent, Key, KeyPress, KeyState, TerminalWindow};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-pub async fn run_app(db_path: PathBuf, keybindings_path: Option<PathBuf>) -> CommonResult<()> {
+/// Name of this frontend in the `ChangeSource` of changes made through it
+pub const FRONTEND_NAME: &str = "tui";
+
+pub async fn run_app(
+    db_path: PathBuf,
+    keybindings_path: Option<PathBuf>,
+    log_buffer: LogBuffer,
+) -> CommonResult<()> {
     let app = AppMain::new_boxed();
 
     // Use shared DI setup function
     let todoist_api_key = std::env::var("TODOIST_API_KEY").ok();
+    let db_passphrase = std::env::var("HOLON_DB_PASSPHRASE").ok();
+    let sync_url = std::env::var("HOLON_SYNC_URL").ok();
+    let sync_auth_token = std::env::var("HOLON_SYNC_AUTH_TOKEN").unwrap_or_default();
     let engine = holon::di::create_backend_engine(db_path.clone(), |services| {
+        services.add_singleton(log_buffer);
+        services.add_singleton(ChangeSource::default().with_frontend(FRONTEND_NAME));
+
+        // Encrypt the database at rest if a passphrase is set
+        if let Some(passphrase) = &db_passphrase {
+            services.add_singleton(holon::storage::EncryptionConfig::new(passphrase.clone()));
+        }
+
+        // Keep the database in sync with a remote database if a sync URL is set
+        if let Some(url) = &sync_url {
+            services.add_singleton(holon::storage::ReplicaConfig::new(
+                url.clone(),
+                sync_auth_token.clone(),
+            ));
+        }
+
         // Register Todoist module if API key is present
         if let Some(api_key) = &todoist_api_key {
             services.add_singleton(holon_todoist::di::TodoistConfig::new(Some(api_key.clone())));
@@ -23,6 +53,51 @@ pub async fn run_app(db_path: PathBuf, keybindings_path: Option<PathBuf>) -> Com
     .await
     .map_err(|e| miette::miette!("Failed to create backend engine: {}", e))?;
 
+    // Bootstrap the database replica and sync it with its remote (no-op without a sync URL)
+    engine.start_replica_sync();
+
+    // Weekly sync health self-check (no-op until a report is due)
+    engine.start_sync_health_reports();
+
+    // Periodic background sync of all registered providers
+    engine.start_sync_scheduler();
+
+    // Checkpoint, vacuum and analyze the database while the app is idle
+    engine.start_maintenance();
+
+    // Persist collapsed nodes, selection and scroll position of views
+    engine.start_view_state_flush();
+
+    // Permanently delete entities that stayed in the trash past their retention
+    engine.start_trash_purge();
+
+    // Archive entities matching the archival rules (e.g. archived Todoist projects)
+    engine.start_archival();
+
+    // Keep the backlinks table in sync with block content
+    engine.start_backlink_index();
+
+    // Keep the tags table in sync with block content
+    engine.start_tag_index();
+
+    // Recompute derived entity fields when their source fields change
+    engine.start_computed_fields().await;
+
+    // Keep child counts and percent done of parent blocks up to date
+    engine.start_rollups().await;
+
+    // Rank tasks by due date, priority and open blockers in the urgency column
+    engine.start_urgency_scores().await;
+
+    // Aggregate tasks created and completed per day for dashboards
+    engine.start_stats().await;
+
+    // Show comments in the rows they annotate, also after tables were recreated
+    engine.start_annotations().await;
+
+    // Run the operations of automation rules when watched entities change
+    engine.start_automation_rules();
+
     // TODO: Make queries user-configurable
     let prql_query = if todoist_api_key.is_some() {
         // Query Todoist tasks
@@ -94,8 +169,15 @@ render (list hierarchical_sort:[parent_id, sort_key] item_template:(row (checkbo
     let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
     let cdc_receiver = Arc::new(std::sync::Mutex::new(rx));
 
+    let sync_engine = engine.clone();
     let initial_state = State::new(engine, render_spec, initial_data, cdc_receiver, keybindings);
 
+    // Show due reminders and sync health reports in the status bar
+    sync_engine.add_notification_sink(Arc::new(TuiNotificationSink::new(
+        initial_state.main_thread_sender_channel.clone(),
+    )));
+    sync_engine.start_reminders();
+
     // Spawn background task to forward CDC stream to channel and set pending flag
     let pending_flag = initial_state.has_pending_cdc_changes.clone();
     tokio::spawn(async move {
@@ -168,5 +250,8 @@ render (list hierarchical_sort:[parent_id, sort_key] item_template:(row (checkbo
 
     TerminalWindow::main_event_loop(app, exit_keys, initial_state)?.await?;
 
+    // Cancel syncs still running in the background
+    sync_engine.shutdown_sync();
+
     ok!()
 }
@@ -2,9 +2,12 @@
 // Exposes modules for testing and reuse
 
 pub mod app_main;
+pub mod block_move;
 pub mod components;
 pub mod config;
 pub mod launcher;
+pub mod notifications;
+pub mod operations_screen;
 pub mod render_interpreter;
 pub mod state;
 pub mod stylesheet;
@@ -1,12 +1,16 @@
 mod app_main;
+mod block_move;
 mod components;
 mod config;
 mod launcher;
+mod notifications;
+mod operations_screen;
 mod render_interpreter;
 mod state;
 mod stylesheet;
 mod ui_element;
 
+use holon::core::log_buffer::LogBuffer;
 use launcher::run_app;
 use r3bl_tui::{log::try_initialize_logging_global, CommonResult};
 use std::fs::OpenOptions;
@@ -55,11 +59,15 @@ async fn main() -> CommonResult<()> {
         .add_directive("turso_core::storage=warn".parse().unwrap())
         .add_directive("turso_core::vdbe=warn".parse().unwrap());
 
+    // Recent events are also kept in memory for the in-app log viewer
+    let log_buffer = LogBuffer::default();
+
     tracing_subscriber::registry()
         .with(filter)
         .with(
             fmt::layer().with_writer(log_file).with_ansi(false), // Disable ANSI colors for file output
         )
+        .with(log_buffer.layer())
         .init();
 
     // Parse command-line arguments
@@ -99,5 +107,5 @@ async fn main() -> CommonResult<()> {
         }
     }
 
-    run_app(db_path, keybindings_path).await
+    run_app(db_path, keybindings_path, log_buffer).await
 }
@@ -0,0 +1,47 @@
+//! Notifications of background subsystems (reminders, sync health reports)
+//!
+//! `TuiNotificationSink` shows them in the status bar. Notifications raised
+//! before the main event loop started are dropped.
+
+use crate::state::AppSignal;
+use holon::core::notifications::{Notification, NotificationSeverity, NotificationSink};
+use r3bl_tui::TerminalWindowMainThreadSignal;
+use std::sync::{Arc, Mutex};
+
+type MainThreadSender = tokio::sync::mpsc::Sender<TerminalWindowMainThreadSignal<AppSignal>>;
+
+/// Sends notifications to the app as `AppSignal::Notify`
+pub struct TuiNotificationSink {
+    main_thread_sender_channel: Arc<Mutex<Option<MainThreadSender>>>,
+}
+
+impl TuiNotificationSink {
+    pub fn new(main_thread_sender_channel: Arc<Mutex<Option<MainThreadSender>>>) -> Self {
+        Self {
+            main_thread_sender_channel,
+        }
+    }
+}
+
+impl NotificationSink for TuiNotificationSink {
+    fn notify(&self, notification: Notification) {
+        let sender = self.main_thread_sender_channel.lock().unwrap().clone();
+        if let Some(sender) = sender {
+            let signal = AppSignal::Notify {
+                message: status_line(&notification),
+            };
+            // Must not block: drop the notification if the UI is backed up
+            let _ = sender.try_send(TerminalWindowMainThreadSignal::ApplyAppSignal(signal));
+        }
+    }
+}
+
+/// Status bar text of a notification
+pub fn status_line(notification: &Notification) -> String {
+    let prefix = match notification.severity {
+        NotificationSeverity::Info => "",
+        NotificationSeverity::Warning => "! ",
+        NotificationSeverity::Critical => "!! ",
+    };
+    format!("{}{}: {}", prefix, notification.title, notification.body)
+}
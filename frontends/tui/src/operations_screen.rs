@@ -0,0 +1,279 @@
+//! Operations debug screen (Ctrl+p)
+//!
+//! Lists every operation registered with the `BackendEngine`, grouped by entity,
+//! with its required parameters and whether it has a precondition. Enter on an
+//! operation prompts for its parameters one after another and then runs it ad
+//! hoc, which is handy when developing a new datasource.
+
+use holon_api::{OperationDescriptor, TypeHint, Value};
+use std::collections::HashMap;
+
+/// An operation ready to run, with the parameters entered for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdHocOperation {
+    pub entity_name: String,
+    pub op_name: String,
+    pub params: HashMap<String, Value>,
+}
+
+/// Parameters entered so far for the selected operation
+#[derive(Debug, Clone, Default)]
+struct ParamPrompt {
+    values: HashMap<String, Value>,
+    /// Index of the parameter being entered
+    param_index: usize,
+    input: String,
+    error: Option<String>,
+}
+
+/// State of the operations debug screen
+#[derive(Debug, Clone, Default)]
+pub struct OperationsScreen {
+    /// Sorted by entity, then by name
+    operations: Vec<OperationDescriptor>,
+    loaded: bool,
+    selected: usize,
+    prompt: Option<ParamPrompt>,
+    /// Outcome of the last ad hoc execution
+    result: Option<String>,
+}
+
+impl OperationsScreen {
+    /// Screen shown while the operations are loading
+    pub fn loading() -> Self {
+        Self::default()
+    }
+
+    pub fn set_operations(&mut self, mut operations: Vec<OperationDescriptor>) {
+        operations.sort_by(|a, b| {
+            (a.entity_name.as_str(), a.name.as_str())
+                .cmp(&(b.entity_name.as_str(), b.name.as_str()))
+        });
+        self.operations = operations;
+        self.loaded = true;
+        self.selected = 0;
+        self.prompt = None;
+    }
+
+    pub fn selected_operation(&self) -> Option<&OperationDescriptor> {
+        self.operations.get(self.selected)
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.prompt.is_none() {
+            self.selected = self.selected.saturating_sub(1);
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if self.prompt.is_none() && self.selected + 1 < self.operations.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn is_prompting(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    /// Start entering the selected operation's parameters
+    ///
+    /// Returns the operation right away if it has no parameters.
+    pub fn start_prompt(&mut self) -> Option<AdHocOperation> {
+        self.selected_operation()?;
+        self.prompt = Some(ParamPrompt::default());
+        self.finish_if_complete()
+    }
+
+    pub fn cancel_prompt(&mut self) {
+        self.prompt = None;
+    }
+
+    pub fn type_char(&mut self, c: char) {
+        if let Some(prompt) = &mut self.prompt {
+            prompt.input.push(c);
+            prompt.error = None;
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(prompt) = &mut self.prompt {
+            prompt.input.pop();
+            prompt.error = None;
+        }
+    }
+
+    /// Accept the input for the current parameter
+    ///
+    /// Returns the operation once all parameters are entered and its
+    /// precondition holds. Invalid input or a violated precondition is shown
+    /// on the screen and the parameter is prompted for again.
+    pub fn submit(&mut self) -> Option<AdHocOperation> {
+        let param = {
+            let op = self.selected_operation()?;
+            let prompt = self.prompt.as_ref()?;
+            op.required_params.get(prompt.param_index)?.clone()
+        };
+        let prompt = self.prompt.as_mut()?;
+        match parse_param(&param.type_hint, &prompt.input) {
+            Ok(value) => {
+                prompt.values.insert(param.name, value);
+                prompt.param_index += 1;
+                prompt.input.clear();
+                prompt.error = None;
+                self.finish_if_complete()
+            }
+            Err(e) => {
+                prompt.error = Some(e);
+                None
+            }
+        }
+    }
+
+    /// Record the outcome of the last execution
+    pub fn set_result(&mut self, result: String) {
+        self.result = Some(result);
+    }
+
+    /// Lines to render, at most `max_rows`, keeping the selected operation visible
+    pub fn lines(&self, max_rows: usize) -> Vec<String> {
+        if !self.loaded {
+            return vec!["Loading operations...".to_string()];
+        }
+
+        let mut footer = Vec::new();
+        if let Some(result) = &self.result {
+            footer.push(String::new());
+            footer.push(format!("Last run: {}", result));
+        }
+        if let (Some(op), Some(prompt)) = (self.selected_operation(), &self.prompt) {
+            footer.push(String::new());
+            footer.push(format!("Run {}.{}", op.entity_name, op.name));
+            if let Some(param) = op.required_params.get(prompt.param_index) {
+                footer.push(format!(
+                    "  {} ({}){}: {}_",
+                    param.name,
+                    type_name(&param.type_hint),
+                    if param.description.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" - {}", param.description)
+                    },
+                    prompt.input
+                ));
+            }
+            if let Some(error) = &prompt.error {
+                footer.push(format!("  {}", error));
+            }
+        }
+
+        let mut lines = Vec::new();
+        let mut selected_line = 0;
+        let mut entity: Option<&str> = None;
+        for (index, op) in self.operations.iter().enumerate() {
+            if entity != Some(op.entity_name.as_str()) {
+                entity = Some(&op.entity_name);
+                lines.push(format!("{}:", op.entity_name));
+            }
+            if index == self.selected {
+                selected_line = lines.len();
+            }
+            let params: Vec<_> = op
+                .required_params
+                .iter()
+                .map(|p| format!("{}: {}", p.name, type_name(&p.type_hint)))
+                .collect();
+            lines.push(format!(
+                "{} {}({}){}",
+                if index == self.selected { ">" } else { " " },
+                op.name,
+                params.join(", "),
+                if op.precondition.is_some() {
+                    "  [precondition]"
+                } else {
+                    ""
+                }
+            ));
+        }
+        if self.operations.is_empty() {
+            lines.push("No operations registered".to_string());
+        }
+
+        let visible = max_rows.saturating_sub(footer.len()).max(1);
+        let skip = (selected_line + 1).saturating_sub(visible);
+        lines
+            .into_iter()
+            .skip(skip)
+            .take(visible)
+            .chain(footer)
+            .collect()
+    }
+
+    /// The operation, once all its parameters are entered
+    fn finish_if_complete(&mut self) -> Option<AdHocOperation> {
+        let op = self.selected_operation()?;
+        let prompt = self.prompt.as_ref()?;
+        if prompt.param_index < op.required_params.len() {
+            return None;
+        }
+
+        let operation = AdHocOperation {
+            entity_name: op.entity_name.clone(),
+            op_name: op.name.clone(),
+            params: prompt.values.clone(),
+        };
+        match op.check_precondition(&operation.params) {
+            Ok(None) => {
+                self.prompt = None;
+                Some(operation)
+            }
+            Ok(Some(violation)) => {
+                self.restart_prompt(format!("Precondition failed: {}", violation));
+                None
+            }
+            Err(e) => {
+                self.restart_prompt(format!("Precondition could not be checked: {}", e));
+                None
+            }
+        }
+    }
+
+    fn restart_prompt(&mut self, error: String) {
+        self.prompt = Some(ParamPrompt {
+            error: Some(error),
+            ..Default::default()
+        });
+    }
+}
+
+/// Parse prompted input for a parameter of type `type_hint`
+pub fn parse_param(type_hint: &TypeHint, input: &str) -> Result<Value, String> {
+    let input = input.trim();
+    match type_hint {
+        TypeHint::Bool => match input.to_lowercase().as_str() {
+            "true" | "yes" | "1" => Ok(Value::Boolean(true)),
+            "false" | "no" | "0" => Ok(Value::Boolean(false)),
+            _ => Err(format!("'{}' is not a boolean (true/false)", input)),
+        },
+        TypeHint::Number => input
+            .parse::<i64>()
+            .map(Value::Integer)
+            .map_err(|_| format!("'{}' is not a number", input)),
+        TypeHint::String => Ok(Value::String(input.to_string())),
+        TypeHint::EntityId { entity_name } => {
+            if input.is_empty() {
+                Err(format!("Enter the ID of a {}", entity_name))
+            } else {
+                Ok(Value::String(input.to_string()))
+            }
+        }
+    }
+}
+
+fn type_name(type_hint: &TypeHint) -> String {
+    match type_hint {
+        TypeHint::Bool => "bool".to_string(),
+        TypeHint::String => "string".to_string(),
+        TypeHint::Number => "number".to_string(),
+        TypeHint::EntityId { entity_name } => format!("{} id", entity_name),
+    }
+}
@@ -1,11 +1,32 @@
+use crate::launcher::FRONTEND_NAME;
+use crate::stylesheet::{self, TextAttributes};
 use crate::ui_element::UIElement;
-use holon_api::Value;
-use query_render::{Arg, BinaryOperator, RenderExpr, RenderSpec};
+use holon::core::annotations::{parse_comments, COMMENTS_COLUMN};
+use holon::core::attachments::format_size;
+use holon_api::{
+    CalendarPeriod, ChangeOrigin, ChangeSource, DateStyle, Format, RenderLocale, Value,
+    CHANGE_ORIGIN_COLUMN,
+};
+use query_render::{Arg, BinaryOperator, GroupSpec, RenderExpr, RenderSpec, SortKey};
 use r3bl_tui::{
     col, new_style, render_tui_styled_texts_into, row, tui_color, tui_styled_text,
     tui_styled_texts, Pos, RenderOpCommon, RenderOpIRVec, TuiColor, DEFAULT_CURSOR_CHAR,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Locale of the terminal (`LC_ALL`, `LC_TIME` or `LANG`) and its current UTC offset
+fn locale() -> &'static RenderLocale {
+    static LOCALE: OnceLock<RenderLocale> = OnceLock::new();
+    LOCALE.get_or_init(|| {
+        let name = ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+            .unwrap_or_default();
+        let offset = chrono::Local::now().offset().local_minus_utc() / 60;
+        RenderLocale::from_posix(&name, offset)
+    })
+}
 
 /// Interprets generic RenderExpr AST into R3BL TUI render operations.
 ///
@@ -16,13 +37,24 @@ pub struct RenderInterpreter;
 impl RenderInterpreter {
     /// Build element tree from RenderSpec with operations attached.
     /// This separates interpretation from rendering.
+    ///
+    /// `selected_ids` is the multi-selection; rows whose selection ID is in it are
+    /// marked when the spec declares a selection checkbox column.
     pub fn build_element_tree(
         spec: &RenderSpec,
         data: &[HashMap<String, Value>],
         selected_index: usize,
+        selected_ids: &HashSet<String>,
     ) -> Vec<UIElement> {
         let mut elements = Vec::new();
-        Self::build_elements_from_expr(&spec.root, data, selected_index, &mut elements, spec);
+        Self::build_elements_from_expr(
+            &spec.root,
+            data,
+            selected_index,
+            selected_ids,
+            &mut elements,
+            spec,
+        );
         elements
     }
 
@@ -31,6 +63,7 @@ impl RenderInterpreter {
         expr: &RenderExpr,
         data: &[HashMap<String, Value>],
         selected_index: usize,
+        selected_ids: &HashSet<String>,
         elements: &mut Vec<UIElement>,
         spec: &RenderSpec,
     ) {
@@ -41,7 +74,20 @@ impl RenderInterpreter {
                 operations: _,
             } => {
                 match name.as_str() {
-                    "list" => Self::build_list_elements(args, data, selected_index, elements, spec),
+                    "list" => Self::build_list_elements(
+                        args,
+                        data,
+                        selected_index,
+                        selected_ids,
+                        elements,
+                        spec,
+                    ),
+                    "board" => {
+                        Self::build_board_elements(args, data, selected_index, elements, spec)
+                    }
+                    "calendar" => {
+                        Self::build_calendar_elements(args, data, selected_index, elements, spec)
+                    }
                     _ => {
                         // For now, other function calls aren't converted to elements
                     }
@@ -56,6 +102,7 @@ impl RenderInterpreter {
         args: &[Arg],
         data: &[HashMap<String, Value>],
         selected_index: usize,
+        selected_ids: &HashSet<String>,
         elements: &mut Vec<UIElement>,
         spec: &RenderSpec,
     ) {
@@ -70,22 +117,20 @@ impl RenderInterpreter {
             .find(|arg| arg.name.as_deref() == Some("hierarchical_sort"))
             .and_then(|arg| Self::extract_sort_columns(&arg.value));
 
-        let sort_columns = args
-            .iter()
-            .find(|arg| arg.name.as_deref() == Some("sort_by"))
-            .and_then(|arg| Self::extract_sort_columns(&arg.value))
-            .unwrap_or_default();
+        // Declared with sort_by:/group_by:, see RenderSpec::row_order
+        let sort_keys = spec.row_order();
 
         let sorted_data: Vec<&HashMap<String, Value>> =
             if let Some(hier_cols) = hierarchical_columns {
                 Self::hierarchical_sort(data, &hier_cols)
-            } else if !sort_columns.is_empty() {
+            } else if !sort_keys.is_empty() {
                 let mut data_refs: Vec<_> = data.iter().collect();
-                data_refs.sort_by(|a, b| Self::compare_rows(a, b, &sort_columns));
+                data_refs.sort_by(|a, b| Self::compare_rows(a, b, &sort_keys));
                 data_refs
             } else {
                 data.iter().collect()
             };
+        let mut current_group: Option<Value> = None;
 
         // Build element for each row
         for (idx, row_data) in sorted_data.iter().enumerate() {
@@ -94,11 +139,209 @@ impl RenderInterpreter {
             if let Some(template) = item_template {
                 let element =
                     Self::build_element_from_template(template, row_data, is_selected, spec);
+                let element = match Self::change_badge(row_data) {
+                    Some(badge) => UIElement::Row {
+                        children: vec![element, badge],
+                    },
+                    None => element,
+                };
+                let element = match &spec.selection {
+                    Some(selection) if selection.checkbox_column => {
+                        let selected = row_data
+                            .get(&selection.id_column)
+                            .and_then(|v| v.as_string())
+                            .is_some_and(|id| selected_ids.contains(id));
+                        UIElement::Row {
+                            children: vec![UIElement::SelectionMark { selected }, element],
+                        }
+                    }
+                    _ => element,
+                };
+                let element = match &spec.group_by {
+                    Some(group) => {
+                        let value = row_data.get(&group.column).cloned().unwrap_or(Value::Null);
+                        if current_group.as_ref() == Some(&value) {
+                            element
+                        } else {
+                            current_group = Some(value);
+                            UIElement::Section {
+                                title: Self::group_title(row_data, group),
+                                child: Box::new(element),
+                            }
+                        }
+                    }
+                    None => element,
+                };
                 elements.push(element);
             }
         }
     }
 
+    /// Badge for rows last changed outside this frontend (by a sync provider, an
+    /// agent or another device), labeled with who changed them
+    fn change_badge(row: &HashMap<String, Value>) -> Option<UIElement> {
+        let origin = row
+            .get(CHANGE_ORIGIN_COLUMN)
+            .and_then(|v| v.as_string())
+            .and_then(ChangeOrigin::from_json)?;
+        let label = origin.badge(&ChangeSource::default().with_frontend(FRONTEND_NAME))?;
+        Some(UIElement::Badge {
+            content: format!(" ⇄ {} ", label),
+            color: tui_color!(hex "#808080"),
+            attributes: TextAttributes::default(),
+        })
+    }
+
+    /// Build board elements: one card per row, lane by lane, each lane under a header
+    ///
+    /// Lanes without cards are not shown, so elements stay one per row.
+    fn build_board_elements(
+        args: &[Arg],
+        data: &[HashMap<String, Value>],
+        selected_index: usize,
+        elements: &mut Vec<UIElement>,
+        spec: &RenderSpec,
+    ) {
+        let (Some(board), Some(group)) = (&spec.board, &spec.group_by) else {
+            return;
+        };
+        let Some(item_template) = args
+            .iter()
+            .find(|arg| arg.name.as_deref() == Some("item_template"))
+            .map(|arg| &arg.value)
+        else {
+            return;
+        };
+
+        let mut current_lane: Option<Value> = None;
+        for (position, idx) in Self::board_order(spec, data).into_iter().enumerate() {
+            let row_data = &data[idx];
+            let element = Self::build_element_from_template(
+                item_template,
+                row_data,
+                position == selected_index,
+                spec,
+            );
+            let lane = board.lane_of(row_data);
+            let element = if current_lane.as_ref() == Some(&lane) {
+                element
+            } else {
+                current_lane = Some(lane);
+                UIElement::Section {
+                    title: Self::group_title(row_data, group),
+                    child: Box::new(element),
+                }
+            };
+            elements.push(element);
+        }
+    }
+
+    /// Build calendar elements: rows bucket by bucket, each bucket under a header
+    ///
+    /// Buckets are in the terminal's time zone; rows without a date come last.
+    fn build_calendar_elements(
+        args: &[Arg],
+        data: &[HashMap<String, Value>],
+        selected_index: usize,
+        elements: &mut Vec<UIElement>,
+        spec: &RenderSpec,
+    ) {
+        let Some(calendar) = &spec.calendar else {
+            return;
+        };
+        let Some(item_template) = args
+            .iter()
+            .find(|arg| arg.name.as_deref() == Some("item_template"))
+            .map(|arg| &arg.value)
+        else {
+            return;
+        };
+
+        let tz = locale().offset();
+        let mut buckets: Vec<(Option<Value>, Vec<usize>)> = calendar
+            .buckets(data, &tz)
+            .into_iter()
+            .map(|(start, rows)| (Some(start), rows))
+            .collect();
+        let undated: Vec<usize> = (0..data.len())
+            .filter(|&idx| calendar.bucket_of(&data[idx], &tz).is_none())
+            .collect();
+        if !undated.is_empty() {
+            buckets.push((None, undated));
+        }
+
+        let mut position = 0;
+        for (start, mut rows) in buckets {
+            rows.sort_by(|&a, &b| Self::compare_rows(&data[a], &data[b], &spec.sort));
+            let title = match &start {
+                Some(start) => Self::calendar_title(start, calendar.period, &tz),
+                None => "No date".to_string(),
+            };
+            for (i, idx) in rows.into_iter().enumerate() {
+                let element = Self::build_element_from_template(
+                    item_template,
+                    &data[idx],
+                    position == selected_index,
+                    spec,
+                );
+                position += 1;
+                elements.push(if i == 0 {
+                    UIElement::Section {
+                        title: title.clone(),
+                        child: Box::new(element),
+                    }
+                } else {
+                    element
+                });
+            }
+        }
+    }
+
+    /// Header text of the calendar bucket starting at `start`
+    fn calendar_title(start: &Value, period: CalendarPeriod, tz: &chrono::FixedOffset) -> String {
+        let date =
+            |style| Format::Date { style, time: false }.apply(start, locale(), chrono::Utc::now());
+        match period {
+            CalendarPeriod::Day => date(DateStyle::Long),
+            CalendarPeriod::Week => format!("Week of {}", date(DateStyle::Medium)),
+            CalendarPeriod::Month => start
+                .as_datetime()
+                .map(|dt| dt.with_timezone(tz).format("%B %Y").to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Indices of `data` in the order a `board` root shows them
+    ///
+    /// Cards are ordered lane by lane (see `BoardSpec::lanes_for`), then by
+    /// `sort_by:` within a lane. Without a board, `data` keeps its order.
+    pub fn board_order(spec: &RenderSpec, data: &[HashMap<String, Value>]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..data.len()).collect();
+        let Some(board) = &spec.board else {
+            return order;
+        };
+        let lanes = board.lanes_for(data);
+        let lane_index = |row: &HashMap<String, Value>| {
+            let lane = board.lane_of(row);
+            lanes.iter().position(|l| *l == lane).unwrap_or(lanes.len())
+        };
+        order.sort_by(|&a, &b| {
+            lane_index(&data[a])
+                .cmp(&lane_index(&data[b]))
+                .then_with(|| Self::compare_rows(&data[a], &data[b], &spec.sort))
+        });
+        order
+    }
+
+    /// Header text of the group `row` starts
+    fn group_title(row: &HashMap<String, Value>, group: &GroupSpec) -> String {
+        let column = group.label_column.as_ref().unwrap_or(&group.column);
+        match row.get(column) {
+            None | Some(Value::Null) => "(none)".to_string(),
+            Some(value) => Self::value_to_string(value),
+        }
+    }
+
     /// Build a single UIElement from a template expression
     fn build_element_from_template(
         expr: &RenderExpr,
@@ -111,137 +354,203 @@ impl RenderInterpreter {
                 name,
                 args,
                 operations,
-            } => match name.as_str() {
-                "row" => {
-                    let mut children = Vec::new();
-                    for arg in args {
-                        let child = Self::build_element_from_template(
-                            &arg.value,
-                            row_data,
-                            is_selected,
-                            spec,
-                        );
-                        children.push(child);
+            } => {
+                let style = expr
+                    .resolve_style(|condition| {
+                        Self::eval_expr(condition, row_data)
+                            .and_then(|v| Self::value_to_bool(&v))
+                            .unwrap_or(false)
+                    })
+                    .map(|style| stylesheet::resolve_style(&style))
+                    .unwrap_or_default();
+                let element = match name.as_str() {
+                    "row" => {
+                        let mut children = Vec::new();
+                        for arg in args {
+                            let child = Self::build_element_from_template(
+                                &arg.value,
+                                row_data,
+                                is_selected,
+                                spec,
+                            );
+                            children.push(child);
+                        }
+                        UIElement::Row { children }
                     }
-                    UIElement::Row { children }
-                }
-                "text" => {
-                    let content_expr = args
-                        .iter()
-                        .find(|arg| arg.name.as_deref() == Some("content"))
-                        .map(|arg| &arg.value);
-
-                    let content = if let Some(content) = content_expr {
-                        Self::eval_expr(content, row_data)
-                            .map(|v| Self::value_to_string(&v))
-                            .unwrap_or_default()
-                    } else {
-                        String::new()
-                    };
+                    "text" => {
+                        let content_expr = args
+                            .iter()
+                            .find(|arg| arg.name.as_deref() == Some("content"))
+                            .map(|arg| &arg.value);
+
+                        let content = if let Some(content) = content_expr {
+                            Self::eval_expr(content, row_data)
+                                .map(|v| Self::value_to_string(&v))
+                                .unwrap_or_default()
+                        } else {
+                            String::new()
+                        };
 
-                    let bg_color = if is_selected {
-                        Some(tui_color!(hex "#333333"))
-                    } else {
-                        None
-                    };
+                        // The selection highlight wins over a styled background
+                        let bg_color = if is_selected {
+                            Some(tui_color!(hex "#333333"))
+                        } else {
+                            style.bg_color
+                        };
 
-                    UIElement::Text {
-                        content,
-                        fg_color: None,
-                        bg_color,
+                        UIElement::Text {
+                            content,
+                            fg_color: style.fg_color,
+                            bg_color,
+                            attributes: style.attributes,
+                        }
                     }
-                }
-                "checkbox" => {
-                    let checked_expr = args
-                        .iter()
-                        .find(|arg| arg.name.as_deref() == Some("checked"))
-                        .map(|arg| &arg.value);
-
-                    let is_checked = if let Some(checked) = checked_expr {
-                        Self::eval_expr(checked, row_data)
-                            .and_then(|v| Self::value_to_bool(&v))
-                            .unwrap_or(false)
-                    } else {
-                        false
-                    };
+                    "checkbox" => {
+                        let checked_expr = args
+                            .iter()
+                            .find(|arg| arg.name.as_deref() == Some("checked"))
+                            .map(|arg| &arg.value);
+
+                        let is_checked = if let Some(checked) = checked_expr {
+                            Self::eval_expr(checked, row_data)
+                                .and_then(|v| Self::value_to_bool(&v))
+                                .unwrap_or(false)
+                        } else {
+                            false
+                        };
 
-                    UIElement::Checkbox {
-                        checked: is_checked,
-                        operations: operations.clone(),
+                        UIElement::Checkbox {
+                            checked: is_checked,
+                            operations: operations.clone(),
+                        }
                     }
-                }
-                "editable_text" => {
-                    let content_expr = args
-                        .iter()
-                        .find(|arg| arg.name.as_deref() == Some("content"))
-                        .map(|arg| &arg.value);
+                    "editable_text" => {
+                        let content_expr = args
+                            .iter()
+                            .find(|arg| arg.name.as_deref() == Some("content"))
+                            .map(|arg| &arg.value);
+
+                        let content = if let Some(content) = content_expr {
+                            Self::eval_expr(content, row_data)
+                                .map(|v| Self::value_to_string(&v))
+                                .unwrap_or_default()
+                        } else {
+                            String::new()
+                        };
 
-                    let content = if let Some(content) = content_expr {
-                        Self::eval_expr(content, row_data)
-                            .map(|v| Self::value_to_string(&v))
-                            .unwrap_or_default()
-                    } else {
-                        String::new()
-                    };
+                        let bg_color = if is_selected {
+                            Some(tui_color!(hex "#333333"))
+                        } else {
+                            style.bg_color
+                        };
 
-                    let bg_color = if is_selected {
-                        Some(tui_color!(hex "#333333"))
-                    } else {
-                        None
-                    };
+                        UIElement::EditableText {
+                            content,
+                            operations: operations.clone(),
+                            fg_color: style.fg_color,
+                            bg_color,
+                            attributes: style.attributes,
+                        }
+                    }
+                    "badge" => {
+                        let content_expr = args
+                            .iter()
+                            .find(|arg| arg.name.as_deref() == Some("content"))
+                            .map(|arg| &arg.value);
+
+                        let content = if let Some(content) = content_expr {
+                            Self::eval_expr(content, row_data)
+                                .map(|v| format!(" [{}] ", Self::value_to_string(&v)))
+                                .unwrap_or_default()
+                        } else {
+                            String::new()
+                        };
 
-                    UIElement::EditableText {
-                        content,
-                        operations: operations.clone(),
-                        fg_color: None,
-                        bg_color,
+                        UIElement::Badge {
+                            content,
+                            color: style.fg_color.unwrap_or(tui_color!(hex "#FFFF00")),
+                            attributes: style.attributes,
+                        }
                     }
-                }
-                "badge" => {
-                    let content_expr = args
-                        .iter()
-                        .find(|arg| arg.name.as_deref() == Some("content"))
-                        .map(|arg| &arg.value);
+                    "attachment_chip" => {
+                        let arg_value = |name: &str| {
+                            args.iter()
+                                .find(|arg| arg.name.as_deref() == Some(name))
+                                .and_then(|arg| Self::eval_expr(&arg.value, row_data))
+                        };
 
-                    let content = if let Some(content) = content_expr {
-                        Self::eval_expr(content, row_data)
-                            .map(|v| format!(" [{}] ", Self::value_to_string(&v)))
-                            .unwrap_or_default()
-                    } else {
-                        String::new()
-                    };
+                        let name = arg_value("name")
+                            .map(|v| Self::value_to_string(&v))
+                            .unwrap_or_default();
+                        let content = match arg_value("size").and_then(|v| v.as_i64()) {
+                            Some(size) => format!(" 📎 {} ({}) ", name, format_size(size)),
+                            None => format!(" 📎 {} ", name),
+                        };
 
-                    UIElement::Badge {
-                        content,
-                        color: tui_color!(hex "#FFFF00"),
+                        UIElement::Badge {
+                            content,
+                            color: style.fg_color.unwrap_or(tui_color!(hex "#87CEEB")),
+                            attributes: style.attributes,
+                        }
                     }
-                }
-                "icon" => {
-                    let source_expr = args
-                        .iter()
-                        .find(|arg| arg.name.as_deref() == Some("source"))
-                        .map(|arg| &arg.value);
+                    "comments" => {
+                        // The comments are denormalized into the annotated row
+                        let comments = row_data
+                            .get(COMMENTS_COLUMN)
+                            .and_then(|v| v.as_string())
+                            .map(parse_comments)
+                            .unwrap_or_default();
+
+                        // A count, with one nested line per comment below it
+                        let content = if comments.is_empty() {
+                            String::new()
+                        } else {
+                            let open = comments.iter().filter(|c| !c.resolved).count();
+                            let mut lines = vec![format!(" 💬 {}/{} ", open, comments.len())];
+                            lines.extend(comments.iter().map(|comment| {
+                                let mark = if comment.resolved { "✓" } else { "•" };
+                                let text = comment.text.replace('\n', " ");
+                                format!("   {} {}: {}", mark, comment.author, text)
+                            }));
+                            lines.join("\n")
+                        };
 
-                    let symbol = if let Some(source) = source_expr {
-                        Self::eval_expr(source, row_data)
-                            .and_then(|v| v.as_string().map(String::from))
-                            .unwrap_or_else(|| "●".to_string())
-                    } else {
-                        "●".to_string()
-                    };
+                        UIElement::Text {
+                            content,
+                            fg_color: style.fg_color.or(Some(tui_color!(hex "#AAAAAA"))),
+                            bg_color: style.bg_color,
+                            attributes: style.attributes,
+                        }
+                    }
+                    "icon" => {
+                        let source_expr = args
+                            .iter()
+                            .find(|arg| arg.name.as_deref() == Some("source"))
+                            .map(|arg| &arg.value);
+
+                        let symbol = if let Some(source) = source_expr {
+                            Self::eval_expr(source, row_data)
+                                .and_then(|v| v.as_string().map(String::from))
+                                .unwrap_or_else(|| "●".to_string())
+                        } else {
+                            "●".to_string()
+                        };
 
-                    UIElement::Icon { symbol }
-                }
-                _ => UIElement::Text {
-                    content: format!("[{}]", name),
-                    fg_color: Some(tui_color!(hex "#FF0000")),
-                    bg_color: if is_selected {
-                        Some(tui_color!(hex "#333333"))
-                    } else {
-                        None
+                        UIElement::Icon { symbol }
+                    }
+                    _ => UIElement::Text {
+                        content: format!("[{}]", name),
+                        fg_color: Some(tui_color!(hex "#FF0000")),
+                        bg_color: if is_selected {
+                            Some(tui_color!(hex "#333333"))
+                        } else {
+                            None
+                        },
+                        attributes: TextAttributes::default(),
                     },
-                },
-            },
+                };
+                Self::pad(element, style.padding)
+            }
             RenderExpr::Literal { value } => {
                 let converted_value = value.clone();
                 let text = Self::value_to_string(&converted_value);
@@ -254,10 +563,24 @@ impl RenderInterpreter {
                     } else {
                         None
                     },
+                    attributes: TextAttributes::default(),
                 }
             }
             RenderExpr::ColumnRef { name } => {
                 let value = row_data.get(name).cloned().unwrap_or(Value::Null);
+                // UNION queries pick each row's template by its index in the `ui` column
+                if let Some(template) = value.as_i64().and_then(|index| {
+                    spec.row_templates
+                        .iter()
+                        .find(|template| template.index as i64 == index)
+                }) {
+                    return Self::build_element_from_template(
+                        &template.expr,
+                        row_data,
+                        is_selected,
+                        spec,
+                    );
+                }
                 let text = Self::value_to_string(&value);
 
                 UIElement::Text {
@@ -268,8 +591,15 @@ impl RenderInterpreter {
                     } else {
                         None
                     },
+                    attributes: TextAttributes::default(),
                 }
             }
+            // Inline error card for a template that failed to compile
+            RenderExpr::Error { message } => UIElement::Badge {
+                content: format!(" ⚠ {} ", message),
+                color: tui_color!(hex "#FF5555"),
+                attributes: TextAttributes::default(),
+            },
             _ => UIElement::Text {
                 content: format!("{:?}", expr),
                 fg_color: None,
@@ -278,10 +608,27 @@ impl RenderInterpreter {
                 } else {
                     None
                 },
+                attributes: TextAttributes::default(),
             },
         }
     }
 
+    /// Surround `element` with `padding` columns of space
+    fn pad(element: UIElement, padding: usize) -> UIElement {
+        if padding == 0 {
+            return element;
+        }
+        let space = || UIElement::Text {
+            content: " ".repeat(padding),
+            fg_color: None,
+            bg_color: None,
+            attributes: TextAttributes::default(),
+        };
+        UIElement::Row {
+            children: vec![space(), element, space()],
+        }
+    }
+
     /// Render element tree to RenderOpIRVec with layout information
     pub fn render_element_tree(
         elements: &[UIElement],
@@ -344,6 +691,7 @@ impl RenderInterpreter {
                 content,
                 fg_color,
                 bg_color,
+                attributes,
             } => {
                 // Dim text if component doesn't have focus
                 let adjusted_fg = if !is_focused {
@@ -361,7 +709,7 @@ impl RenderInterpreter {
                             row(start_row + line_idx),
                         )));
                     }
-                    Self::render_text_simple(render_ops, line, adjusted_fg, *bg_color);
+                    Self::render_text_styled(render_ops, line, adjusted_fg, *bg_color, *attributes);
                 }
                 let ending_col = start_col + content.lines().next().map(|l| l.len()).unwrap_or(0);
                 (lines.len().max(1), ending_col) // Return rows consumed and ending column
@@ -377,9 +725,23 @@ impl RenderInterpreter {
                 Self::render_text_simple(render_ops, checkbox_text, Some(fg_color), None);
                 (1, start_col + 4) // Return rows consumed and ending column (checkbox is 4 chars)
             }
-            UIElement::Badge { content, color } => {
+            UIElement::SelectionMark { selected } => {
+                let mark_text = if *selected { "◉ " } else { "○ " };
+                let fg_color = if is_focused {
+                    tui_color!(hex "#00BFFF")
+                } else {
+                    tui_color!(hex "#006080")
+                };
+                Self::render_text_simple(render_ops, mark_text, Some(fg_color), None);
+                (1, start_col + 2)
+            }
+            UIElement::Badge {
+                content,
+                color,
+                attributes,
+            } => {
                 // Badge color unchanged (or could dim if needed)
-                Self::render_text_simple(render_ops, content, Some(*color), None);
+                Self::render_text_styled(render_ops, content, Some(*color), None, *attributes);
                 (1, start_col + content.len()) // Return rows consumed and ending column
             }
             UIElement::Icon { symbol } => {
@@ -391,6 +753,7 @@ impl RenderInterpreter {
                 content,
                 fg_color,
                 bg_color,
+                attributes,
                 ..
             } => {
                 if is_editing {
@@ -514,13 +877,50 @@ impl RenderInterpreter {
                                 row(start_row + line_idx),
                             )));
                         }
-                        Self::render_text_simple(render_ops, line, adjusted_fg, *bg_color);
+                        Self::render_text_styled(
+                            render_ops,
+                            line,
+                            adjusted_fg,
+                            *bg_color,
+                            *attributes,
+                        );
                     }
                     let ending_col =
                         start_col + content.lines().next().map(|l| l.len()).unwrap_or(0);
                     (lines.len().max(1), ending_col) // Return rows consumed and ending column
                 }
             }
+            UIElement::Section { title, child } => {
+                let fg_color = if is_focused {
+                    tui_color!(hex "#00AAFF")
+                } else {
+                    tui_color!(hex "#006080")
+                };
+                Self::render_text_styled(
+                    render_ops,
+                    title,
+                    Some(fg_color),
+                    None,
+                    TextAttributes {
+                        bold: true,
+                        ..TextAttributes::default()
+                    },
+                );
+                *render_ops += RenderOpCommon::MoveCursorPositionAbs(Pos::from((
+                    col(start_col),
+                    row(start_row + 1),
+                )));
+                let (rows, ending_col) = Self::render_element(
+                    child,
+                    render_ops,
+                    is_focused,
+                    is_editing,
+                    editing_buffer,
+                    start_col,
+                    start_row + 1,
+                );
+                (rows + 1, ending_col)
+            }
             UIElement::Row { children } => {
                 let mut max_rows = 1;
                 let mut current_col = start_col;
@@ -551,13 +951,30 @@ impl RenderInterpreter {
         text: &str,
         fg_color: Option<TuiColor>,
         bg_color: Option<TuiColor>,
+    ) {
+        Self::render_text_styled(
+            render_ops,
+            text,
+            fg_color,
+            bg_color,
+            TextAttributes::default(),
+        );
+    }
+
+    /// Render text like `render_text_simple`, with text attributes from a widget style
+    fn render_text_styled(
+        render_ops: &mut RenderOpIRVec,
+        text: &str,
+        fg_color: Option<TuiColor>,
+        bg_color: Option<TuiColor>,
+        attributes: TextAttributes,
     ) {
         let fg = fg_color.unwrap_or_else(|| tui_color!(hex "#CCCCCC"));
         let bg = bg_color.unwrap_or_else(|| tui_color!(hex "#000000"));
 
         let styled_texts = tui_styled_texts! {
             tui_styled_text! {
-                @style: new_style!(color_fg: {fg} color_bg: {bg}),
+                @style: attributes.apply(new_style!(color_fg: {fg} color_bg: {bg})),
                 @text: text
             },
         };
@@ -573,6 +990,14 @@ impl RenderInterpreter {
                 row.get(name).cloned()
             }
             RenderExpr::Literal { value } => Some(value.clone()),
+            RenderExpr::Format { format, value } => {
+                let value = Self::eval_expr(value, row).unwrap_or(Value::Null);
+                Some(Value::String(format.apply(
+                    &value,
+                    locale(),
+                    chrono::Utc::now(),
+                )))
+            }
             RenderExpr::BinaryOp { op, left, right } => {
                 let left_val = Self::eval_expr(left, row)?;
                 let right_val = Self::eval_expr(right, row)?;
@@ -661,7 +1086,7 @@ impl RenderInterpreter {
     }
 
     /// Convert Value to String
-    fn value_to_string(value: &Value) -> String {
+    pub(crate) fn value_to_string(value: &Value) -> String {
         match value {
             Value::String(s) => s.clone(),
             Value::Integer(n) => n.to_string(),
@@ -685,7 +1110,7 @@ impl RenderInterpreter {
         }
     }
 
-    /// Extract column names from sort_by array expression
+    /// Extract column names from hierarchical_sort array expression
     fn extract_sort_columns(expr: &RenderExpr) -> Option<Vec<String>> {
         match expr {
             RenderExpr::Array { items } => {
@@ -707,13 +1132,13 @@ impl RenderInterpreter {
     fn compare_rows(
         a: &HashMap<String, Value>,
         b: &HashMap<String, Value>,
-        columns: &[String],
+        keys: &[SortKey],
     ) -> std::cmp::Ordering {
         use std::cmp::Ordering;
 
-        for col in columns {
-            let a_val = a.get(col);
-            let b_val = b.get(col);
+        for key in keys {
+            let a_val = a.get(&key.column);
+            let b_val = b.get(&key.column);
 
             let cmp = match (a_val, b_val) {
                 (None, None) => Ordering::Equal,
@@ -721,6 +1146,7 @@ impl RenderInterpreter {
                 (Some(_), None) => Ordering::Greater, // any value > NULL
                 (Some(a_v), Some(b_v)) => Self::compare_values(a_v, b_v),
             };
+            let cmp = if key.descending { cmp.reverse() } else { cmp };
 
             if cmp != Ordering::Equal {
                 return cmp;
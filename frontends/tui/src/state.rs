@@ -1,11 +1,14 @@
+use crate::block_move::{apply_block_move, compute_block_move, MoveDirection};
 use crate::config::KeyBindingConfig;
+use crate::operations_screen::OperationsScreen;
+use crate::render_interpreter::RenderInterpreter;
 use holon::api::backend_engine::BackendEngine;
 use holon::storage::turso::{ChangeData, RowChange};
 use holon::storage::types::StorageEntity; // StorageEntity is HashMap<String, Value>
 use holon_api::Value;
-use query_render::RenderSpec;
+use query_render::{RenderExpr, RenderSpec};
 use r3bl_tui::{row, DialogBuffer, EditorBuffer, FlexBoxId, HasDialogBuffers, HasEditorBuffers};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::{Arc, Mutex};
 
@@ -15,6 +18,8 @@ pub struct State {
     pub render_spec: RenderSpec,
     pub data: Vec<StorageEntity>, // Generic StorageEntity (no Todoist-specific types)
     pub selected_index: usize,
+    /// IDs of the rows picked for batch operations (independent of the cursor)
+    pub selected_ids: HashSet<String>,
     pub status_message: String,
     pub cdc_receiver: Arc<Mutex<tokio::sync::mpsc::UnboundedReceiver<RowChange>>>,
     /// Track the ID of the currently selected block to maintain selection after re-sorting
@@ -41,6 +46,12 @@ pub struct State {
 
     /// Keybindings configuration
     pub keybindings: Arc<KeyBindingConfig>,
+
+    /// Lines of the diagnostics debug screen (Ctrl+d); None while it is hidden
+    pub diagnostics: Option<Vec<String>>,
+
+    /// Operations debug screen (Ctrl+p); None while it is hidden
+    pub operations_screen: Option<OperationsScreen>,
 }
 
 impl fmt::Debug for State {
@@ -48,6 +59,7 @@ impl fmt::Debug for State {
         f.debug_struct("State")
             .field("data_len", &self.data.len())
             .field("selected_index", &self.selected_index)
+            .field("selected_ids", &self.selected_ids.len())
             .field("status_message", &self.status_message)
             .finish()
     }
@@ -76,6 +88,7 @@ impl State {
             render_spec,
             data: initial_data,
             selected_index: 0,
+            selected_ids: HashSet::new(),
             status_message: "Ready".to_string(),
             cdc_receiver,
             selected_block_id_cache: None,
@@ -86,6 +99,8 @@ impl State {
             editing_block_index: None,
             editing_buffer: None,
             keybindings,
+            diagnostics: None,
+            operations_screen: None,
         };
 
         // Sort initial data hierarchically to match renderer's visual order
@@ -251,6 +266,332 @@ impl State {
         Ok(())
     }
 
+    /// Value of the selection ID column for a row
+    fn selection_id(&self, row: &StorageEntity) -> Option<String> {
+        let spec = self.render_spec.selection.as_ref()?;
+        row.get(&spec.id_column)
+            .and_then(|v| v.as_string())
+            .map(|s| s.to_string())
+    }
+
+    /// Add the block under the cursor to the multi-selection, or remove it
+    ///
+    /// Returns the number of selected blocks.
+    pub fn toggle_selection(&mut self) -> Result<usize, String> {
+        if self.render_spec.selection.is_none() {
+            return Err("This view does not support selection".to_string());
+        }
+        let id = self
+            .data
+            .get(self.selected_index)
+            .and_then(|row| self.selection_id(row))
+            .ok_or_else(|| "Block has no selection id".to_string())?;
+        if !self.selected_ids.remove(&id) {
+            self.selected_ids.insert(id);
+        }
+        Ok(self.selected_ids.len())
+    }
+
+    /// Select all blocks, or clear the selection if all are already selected
+    pub fn toggle_select_all(&mut self) -> Result<usize, String> {
+        match &self.render_spec.selection {
+            Some(spec) if spec.select_all => {}
+            Some(_) => return Err("This view does not offer select all".to_string()),
+            None => return Err("This view does not support selection".to_string()),
+        }
+        let all: HashSet<String> = self
+            .data
+            .iter()
+            .filter_map(|row| self.selection_id(row))
+            .collect();
+        self.selected_ids = if all.is_subset(&self.selected_ids) {
+            HashSet::new()
+        } else {
+            all
+        };
+        Ok(self.selected_ids.len())
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selected_ids.clear();
+    }
+
+    /// Indices of the selected blocks, in visual order
+    pub fn selected_indices(&self) -> Vec<usize> {
+        self.data
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| {
+                self.selection_id(row)
+                    .is_some_and(|id| self.selected_ids.contains(&id))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Parameters for running `operation_descriptor` on every selected block
+    ///
+    /// Like `execute_operation_on_selected`, `indent` gets a `parent_id` injected:
+    /// the closest previous sibling that is not selected itself, so that
+    /// consecutive selected siblings all end up under the same parent.
+    pub fn selection_params(
+        &self,
+        operation_descriptor: &holon_api::OperationDescriptor,
+    ) -> Result<Vec<StorageEntity>, String> {
+        let indices = self.selected_indices();
+        if indices.is_empty() {
+            return Err("No blocks selected".to_string());
+        }
+
+        indices
+            .into_iter()
+            .map(|index| {
+                let mut row_data = self.data[index].clone();
+                if operation_descriptor.name == "indent" {
+                    let parent_id = row_data.get("parent_id").cloned();
+                    let new_parent_id = self.data[..index]
+                        .iter()
+                        .rev()
+                        .filter(|row| row.get("parent_id").cloned() == parent_id)
+                        .find(|row| {
+                            !self
+                                .selection_id(row)
+                                .is_some_and(|id| self.selected_ids.contains(&id))
+                        })
+                        .and_then(|row| row.get("id"))
+                        .and_then(|v| v.as_string())
+                        .ok_or_else(|| "No previous block to indent under".to_string())?;
+                    row_data.insert(
+                        "parent_id".to_string(),
+                        Value::String(new_parent_id.to_string()),
+                    );
+                }
+                Ok(row_data)
+            })
+            .collect()
+    }
+
+    /// Execute an operation once per row of `rows` as one undoable batch
+    ///
+    /// Like `execute_operation_on_selected`, the batch runs in the background and
+    /// reports back via `AppSignal::OperationResult`.
+    pub fn execute_batch_operation(
+        &mut self,
+        operation_descriptor: &holon_api::OperationDescriptor,
+        rows: Vec<StorageEntity>,
+    ) -> Result<(), String> {
+        if rows.is_empty() {
+            return Err("No blocks selected".to_string());
+        }
+
+        let engine = self.engine.clone();
+        let sender_opt = self.main_thread_sender_channel.lock().unwrap().clone();
+        self.selected_block_id_cache = self.selected_block_id();
+
+        let entity_name = operation_descriptor.entity_name.clone();
+        let op_name = operation_descriptor.name.clone();
+        let display_name = format!(
+            "{} ({} blocks)",
+            operation_descriptor.display_name,
+            rows.len()
+        );
+
+        tokio::spawn(async move {
+            let result = engine
+                .execute_batch_operation(&entity_name, &op_name, &display_name, rows)
+                .await;
+
+            if let Some(sender) = sender_opt {
+                let signal = AppSignal::OperationResult {
+                    operation_name: display_name,
+                    success: result.is_ok(),
+                    error_message: result.err().map(|e| e.to_string()),
+                };
+                let _ = sender
+                    .send(r3bl_tui::TerminalWindowMainThreadSignal::ApplyAppSignal(
+                        signal,
+                    ))
+                    .await;
+            } else if let Err(e) = result {
+                eprintln!("Batch '{}' failed: {}", display_name, e);
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Move the selected block up/down or indent/outdent it
+    ///
+    /// The new position is computed from the rendered tree and applied locally right
+    /// away (optimistic reorder); the move is then dispatched to the backend as
+    /// `move_block` (BlockOperations) or `move_entity` (MoveOperations), whichever
+    /// `entity_name` supports. If the backend rejects it, the local change is reverted
+    /// via `AppSignal::RevertBlockMove`.
+    pub fn move_selected_block(
+        &mut self,
+        direction: MoveDirection,
+        entity_name: &str,
+    ) -> Result<(), String> {
+        let mv = compute_block_move(&self.data, self.selected_index, direction)?;
+        let (previous_parent_id, previous_sort_key) = apply_block_move(&mut self.data, &mv)
+            .ok_or_else(|| format!("Block {} not found", mv.id))?;
+
+        // Selection follows the moved block
+        self.selected_block_id_cache = Some(mv.id.clone());
+        self.sort_hierarchically();
+
+        let engine = self.engine.clone();
+        let entity_name = entity_name.to_string();
+        let sender_opt = self.main_thread_sender_channel.lock().unwrap().clone();
+
+        tokio::spawn(async move {
+            let after = mv
+                .after_block_id
+                .clone()
+                .map(Value::String)
+                .unwrap_or(Value::Null);
+            let mut params = HashMap::from([
+                ("id".to_string(), Value::String(mv.id.clone())),
+                ("parent_id".to_string(), Value::String(mv.parent_id.clone())),
+            ]);
+
+            let result = if engine.has_operation(&entity_name, "move_block").await {
+                params.insert("after_block_id".to_string(), after);
+                engine
+                    .execute_operation(&entity_name, "move_block", params)
+                    .await
+            } else if engine.has_operation(&entity_name, "move_entity").await {
+                params.insert("after_id".to_string(), after);
+                engine
+                    .execute_operation(&entity_name, "move_entity", params)
+                    .await
+            } else {
+                Err(anyhow::anyhow!(
+                    "'{}' supports neither move_block nor move_entity",
+                    entity_name
+                ))
+            };
+
+            let Some(sender) = sender_opt else {
+                if let Err(e) = result {
+                    eprintln!("Block move failed: {}", e);
+                }
+                return;
+            };
+            if result.is_err() {
+                let revert = AppSignal::RevertBlockMove {
+                    id: mv.id.clone(),
+                    parent_id: previous_parent_id,
+                    sort_key: previous_sort_key,
+                };
+                let _ = sender
+                    .send(r3bl_tui::TerminalWindowMainThreadSignal::ApplyAppSignal(
+                        revert,
+                    ))
+                    .await;
+            }
+            let signal = AppSignal::OperationResult {
+                operation_name: "move_block".to_string(),
+                success: result.is_ok(),
+                error_message: result.err().map(|e| e.to_string()),
+            };
+            let _ = sender
+                .send(r3bl_tui::TerminalWindowMainThreadSignal::ApplyAppSignal(
+                    signal,
+                ))
+                .await;
+        });
+
+        Ok(())
+    }
+
+    /// Move the selected card of a `board` to the next (or previous) lane
+    ///
+    /// The move runs the operation `BoardSpec::move_operation` picks among the
+    /// operations wired to the board, in the background like
+    /// `execute_operation_on_selected`. Returns the target lane.
+    pub fn move_selected_card(&mut self, forward: bool) -> Result<Value, String> {
+        let board = self
+            .render_spec
+            .board
+            .as_ref()
+            .ok_or_else(|| "Not a board".to_string())?;
+        let row = RenderInterpreter::board_order(&self.render_spec, &self.data)
+            .get(self.selected_index)
+            .map(|&idx| &self.data[idx])
+            .ok_or_else(|| "No card selected".to_string())?;
+        let id = row
+            .get("id")
+            .and_then(|v| v.as_string())
+            .ok_or_else(|| "Card has no id".to_string())?
+            .to_string();
+
+        let lanes = board.lanes_for(&self.data);
+        let current = lanes
+            .iter()
+            .position(|lane| *lane == board.lane_of(row))
+            .unwrap_or_default();
+        let target = if forward {
+            lanes.get(current + 1)
+        } else {
+            current.checked_sub(1).and_then(|idx| lanes.get(idx))
+        }
+        .cloned()
+        .ok_or_else(|| "No lane to move to".to_string())?;
+
+        let descriptors: Vec<_> = match &self.render_spec.root {
+            RenderExpr::FunctionCall { operations, .. } => operations
+                .iter()
+                .map(|wiring| wiring.descriptor.clone())
+                .collect(),
+            _ => vec![],
+        };
+        let operation = board
+            .move_operation(&descriptors, &id, &target)
+            .ok_or_else(|| format!("No operation sets {}", board.column))?;
+
+        let engine = self.engine.clone();
+        let sender_opt = self.main_thread_sender_channel.lock().unwrap().clone();
+        self.selected_block_id_cache = Some(id);
+
+        tokio::spawn(async move {
+            let result = engine
+                .execute_operation(&operation.entity_name, &operation.op_name, operation.params)
+                .await;
+
+            if let Some(sender) = sender_opt {
+                let signal = AppSignal::OperationResult {
+                    operation_name: operation.display_name,
+                    success: result.is_ok(),
+                    error_message: result.err().map(|e| e.to_string()),
+                };
+                let _ = sender
+                    .send(r3bl_tui::TerminalWindowMainThreadSignal::ApplyAppSignal(
+                        signal,
+                    ))
+                    .await;
+            } else if let Err(e) = result {
+                eprintln!("Card move failed: {}", e);
+            }
+        });
+
+        Ok(target)
+    }
+
+    /// Undo an optimistic block move that the backend rejected
+    pub fn revert_block_move(&mut self, id: &str, parent_id: Value, sort_key: Value) {
+        if let Some(row) = self
+            .data
+            .iter_mut()
+            .find(|row| row.get("id").and_then(|v| v.as_string()) == Some(id))
+        {
+            row.insert("parent_id".to_string(), parent_id);
+            row.insert("sort_key".to_string(), sort_key);
+            self.selected_block_id_cache = self.selected_block_id();
+            self.sort_hierarchically();
+        }
+    }
+
     /// Calculate cursor offset from the editing buffer
     ///
     /// Helper method for operations that need cursor position (like split).
@@ -442,6 +783,9 @@ impl State {
             ChangeData::Deleted { id, .. } => {
                 self.apply_delete(&id);
             }
+            ChangeData::ColumnChange { id, columns, .. } => {
+                self.apply_column_change(&id, columns);
+            }
         }
     }
 
@@ -469,6 +813,18 @@ impl State {
         }
     }
 
+    /// Apply a column change - patch only the changed fields in place
+    fn apply_column_change(&mut self, entity_id: &str, columns: HashMap<String, Value>) {
+        if let Some(row) = self.data.iter_mut().find(|row| {
+            row.get("id")
+                .and_then(|v| v.as_string())
+                .map(|id| id == entity_id)
+                .unwrap_or(false)
+        }) {
+            row.extend(columns);
+        }
+    }
+
     /// Apply a delete
     fn apply_delete(&mut self, _id: &str) {
         // Note: The id parameter from CDC is SQLite ROWID, not the entity ID
@@ -500,6 +856,24 @@ pub enum AppSignal {
         success: bool,
         error_message: Option<String>,
     },
+    /// Diagnostics report loaded for the debug screen
+    DiagnosticsLoaded {
+        lines: Vec<String>,
+    },
+    /// Registered operations loaded for the operations debug screen
+    OperationsLoaded {
+        operations: Vec<holon_api::OperationDescriptor>,
+    },
+    /// Notification of a background subsystem (e.g. a due reminder)
+    Notify {
+        message: String,
+    },
+    /// Restore a block's position after a failed optimistic move
+    RevertBlockMove {
+        id: String,
+        parent_id: holon_api::Value,
+        sort_key: holon_api::Value,
+    },
 }
 
 impl Default for AppSignal {
@@ -1,6 +1,7 @@
+use holon_api::Style;
 use r3bl_tui::{
-    new_style, throws_with_return, tui_color, tui_stylesheet, CommonResult, FlexBoxId,
-    TuiStylesheet,
+    new_style, throws_with_return, tui_color, tui_stylesheet, CommonResult, FlexBoxId, TuiColor,
+    TuiStyle, TuiStylesheet,
 };
 
 /// Style IDs for consistent styling across the application
@@ -124,3 +125,69 @@ pub fn create_stylesheet() -> CommonResult<TuiStylesheet> {
         }
     })
 }
+
+/// Text attributes set by the `style` argument of a widget
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TextAttributes {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub dim: bool,
+}
+
+impl TextAttributes {
+    /// Add the attributes to `style`
+    pub fn apply(self, mut style: TuiStyle) -> TuiStyle {
+        if self.bold {
+            style = style + new_style!(bold);
+        }
+        if self.italic {
+            style = style + new_style!(italic);
+        }
+        if self.underline {
+            style = style + new_style!(underline);
+        }
+        if self.strikethrough {
+            style = style + new_style!(strikethrough);
+        }
+        if self.dim {
+            style = style + new_style!(dim);
+        }
+        style
+    }
+}
+
+/// A widget style from a render expression, in terminal terms
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ResolvedStyle {
+    pub fg_color: Option<TuiColor>,
+    pub bg_color: Option<TuiColor>,
+    pub attributes: TextAttributes,
+    /// Columns of space on either side
+    pub padding: usize,
+}
+
+/// Translate a render expression style for the terminal
+///
+/// Colors resolve like in every other frontend (see `Style::rgb`), so a query
+/// renders with the same colors in the TUI and in Flutter.
+pub fn resolve_style(style: &Style) -> ResolvedStyle {
+    ResolvedStyle {
+        fg_color: style.color.as_deref().and_then(style_color),
+        bg_color: style.background.as_deref().and_then(style_color),
+        attributes: TextAttributes {
+            bold: style.bold.unwrap_or(false),
+            italic: style.italic.unwrap_or(false),
+            underline: style.underline.unwrap_or(false),
+            strikethrough: style.strikethrough.unwrap_or(false),
+            dim: style.dim.unwrap_or(false),
+        },
+        padding: style.padding.unwrap_or(0) as usize,
+    }
+}
+
+/// Terminal color of a `#rrggbb` or named style color
+pub fn style_color(color: &str) -> Option<TuiColor> {
+    Style::rgb(color).map(|(r, g, b)| tui_color!(r, g, b))
+}
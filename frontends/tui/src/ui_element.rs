@@ -1,3 +1,4 @@
+use crate::stylesheet::TextAttributes;
 use query_render::OperationWiring;
 use r3bl_tui::TuiColor;
 use tracing::debug;
@@ -11,20 +12,27 @@ pub enum UIElement {
         content: String,
         fg_color: Option<TuiColor>,
         bg_color: Option<TuiColor>,
+        attributes: TextAttributes,
     },
     EditableText {
         content: String,
         operations: Vec<OperationWiring>,
         fg_color: Option<TuiColor>,
         bg_color: Option<TuiColor>,
+        attributes: TextAttributes,
     },
     Checkbox {
         checked: bool,
         operations: Vec<OperationWiring>,
     },
+    /// Multi-selection marker shown in front of a row
+    SelectionMark {
+        selected: bool,
+    },
     Badge {
         content: String,
         color: TuiColor,
+        attributes: TextAttributes,
     },
     Icon {
         symbol: String,
@@ -32,6 +40,11 @@ pub enum UIElement {
     Row {
         children: Vec<UIElement>,
     },
+    /// First row of a group (see `group_by:`), shown below the group's header
+    Section {
+        title: String,
+        child: Box<UIElement>,
+    },
 }
 
 impl UIElement {
@@ -50,6 +63,7 @@ impl UIElement {
                 }
                 None
             }
+            UIElement::Section { child, .. } => child.get_operation(),
             _ => None,
         }
     }
@@ -66,6 +80,7 @@ impl UIElement {
                 }
                 None
             }
+            UIElement::Section { child, .. } => child.find_editable_text(),
             _ => None,
         }
     }
@@ -75,6 +90,11 @@ impl UIElement {
         &self,
         op_name: &str,
     ) -> Option<&holon_api::OperationDescriptor> {
+        self.find_operation_wiring(op_name).map(|op| &op.descriptor)
+    }
+
+    /// Find an operation wiring by operation name, searching children recursively
+    pub fn find_operation_wiring(&self, op_name: &str) -> Option<&OperationWiring> {
         match self {
             UIElement::Checkbox { operations, .. } | UIElement::EditableText { operations, .. } => {
                 debug!(
@@ -88,10 +108,7 @@ impl UIElement {
                         op.descriptor.name, op.descriptor.entity_name
                     );
                 }
-                operations
-                    .iter()
-                    .find(|op| op.descriptor.name == op_name)
-                    .map(|op| &op.descriptor)
+                operations.iter().find(|op| op.descriptor.name == op_name)
             }
             UIElement::Row { children } => {
                 debug!(
@@ -101,8 +118,9 @@ impl UIElement {
                 );
                 children
                     .iter()
-                    .find_map(|child| child.find_operation_descriptor(op_name))
+                    .find_map(|child| child.find_operation_wiring(op_name))
             }
+            UIElement::Section { child, .. } => child.find_operation_wiring(op_name),
             _ => {
                 debug!(
                     "Element type {:?} has no operations",
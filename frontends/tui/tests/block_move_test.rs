@@ -0,0 +1,86 @@
+/// Tests for computing keyboard block moves from the rendered tree
+use holon::storage::fractional_index::gen_key_between;
+use holon::storage::types::StorageEntity;
+use holon_api::Value;
+use tui_r3bl_frontend::block_move::{apply_block_move, compute_block_move, MoveDirection};
+
+fn block(id: &str, parent_id: &str, sort_key: &str) -> StorageEntity {
+    StorageEntity::from([
+        ("id".to_string(), Value::String(id.to_string())),
+        (
+            "parent_id".to_string(),
+            Value::String(parent_id.to_string()),
+        ),
+        ("sort_key".to_string(), Value::String(sort_key.to_string())),
+    ])
+}
+
+/// root
+/// ├── a
+/// │   └── a1
+/// ├── b
+/// └── c
+fn sample_rows() -> Vec<StorageEntity> {
+    let k1 = gen_key_between(None, None).unwrap();
+    let k2 = gen_key_between(Some(&k1), None).unwrap();
+    let k3 = gen_key_between(Some(&k2), None).unwrap();
+    vec![
+        block("a", "root", &k1),
+        block("a1", "a", &k1),
+        block("b", "root", &k2),
+        block("c", "root", &k3),
+    ]
+}
+
+fn sort_key(rows: &[StorageEntity], id: &str) -> String {
+    rows.iter()
+        .find(|r| r.get("id").and_then(|v| v.as_string()) == Some(id))
+        .and_then(|r| r.get("sort_key").and_then(|v| v.as_string()))
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_move_up_and_down_between_siblings() {
+    let rows = sample_rows();
+
+    let up = compute_block_move(&rows, 3, MoveDirection::Up).unwrap();
+    assert_eq!(up.parent_id, "root");
+    assert_eq!(up.after_block_id.as_deref(), Some("a"));
+    assert!(up.sort_key > sort_key(&rows, "a") && up.sort_key < sort_key(&rows, "b"));
+
+    let down = compute_block_move(&rows, 0, MoveDirection::Down).unwrap();
+    assert_eq!(down.after_block_id.as_deref(), Some("b"));
+    assert!(down.sort_key > sort_key(&rows, "b") && down.sort_key < sort_key(&rows, "c"));
+
+    assert!(compute_block_move(&rows, 0, MoveDirection::Up).is_err());
+    assert!(compute_block_move(&rows, 3, MoveDirection::Down).is_err());
+}
+
+#[test]
+fn test_indent_and_outdent() {
+    let mut rows = sample_rows();
+
+    // b becomes the last child of a
+    let indent = compute_block_move(&rows, 2, MoveDirection::Indent).unwrap();
+    assert_eq!(indent.parent_id, "a");
+    assert_eq!(indent.after_block_id.as_deref(), Some("a1"));
+    assert!(indent.sort_key > sort_key(&rows, "a1"));
+    assert!(compute_block_move(&rows, 0, MoveDirection::Indent).is_err());
+
+    // a1 goes right after its former parent
+    let outdent = compute_block_move(&rows, 1, MoveDirection::Outdent).unwrap();
+    assert_eq!(outdent.parent_id, "root");
+    assert_eq!(outdent.after_block_id.as_deref(), Some("a"));
+
+    let (previous_parent, previous_key) = apply_block_move(&mut rows, &indent).unwrap();
+    assert_eq!(previous_parent, Value::String("root".to_string()));
+    assert_eq!(
+        previous_key.as_string(),
+        Some(sort_key(&sample_rows(), "b").as_str())
+    );
+    assert_eq!(
+        rows[2].get("parent_id"),
+        Some(&Value::String("a".to_string()))
+    );
+}
@@ -0,0 +1,69 @@
+/// Tests for showing rows as cards in the lanes of a board
+use std::collections::{HashMap, HashSet};
+
+use holon_api::Value;
+use query_render::parse_query_render;
+use tui_r3bl_frontend::render_interpreter::RenderInterpreter;
+use tui_r3bl_frontend::UIElement;
+
+fn rows() -> Vec<HashMap<String, Value>> {
+    [
+        ("a", "done", 1),
+        ("b", "todo", 2),
+        ("c", "blocked", 1),
+        ("d", "todo", 1),
+    ]
+    .iter()
+    .map(|(id, status, priority)| {
+        HashMap::from([
+            ("id".to_string(), Value::String(id.to_string())),
+            ("content".to_string(), Value::String(id.to_uppercase())),
+            ("status".to_string(), Value::String(status.to_string())),
+            ("priority".to_string(), Value::Integer(*priority)),
+        ])
+    })
+    .collect()
+}
+
+/// (lane title, card content) of each element
+fn outline(elements: &[UIElement]) -> Vec<(Option<String>, String)> {
+    fn content(element: &UIElement) -> String {
+        match element {
+            UIElement::Text { content, .. } => content.clone(),
+            other => panic!("expected text, got {:?}", other),
+        }
+    }
+    elements
+        .iter()
+        .map(|element| match element {
+            UIElement::Section { title, child } => (Some(title.clone()), content(child)),
+            other => (None, content(other)),
+        })
+        .collect()
+}
+
+#[test]
+fn test_cards_in_declared_lanes() {
+    let prql = r#"
+from tasks
+render (board group_by:status lanes:["todo", "doing", "done"] sort_by:priority item_template:(text content))
+    "#;
+    let (_sql, spec) = parse_query_render(prql).unwrap();
+    let rows = rows();
+    let elements = RenderInterpreter::build_element_tree(&spec, &rows, 0, &HashSet::new());
+
+    // Empty "doing" lane is skipped; the undeclared "blocked" lane comes last
+    assert_eq!(
+        outline(&elements),
+        vec![
+            (Some("todo".to_string()), "D".to_string()),
+            (None, "B".to_string()),
+            (Some("done".to_string()), "A".to_string()),
+            (Some("blocked".to_string()), "C".to_string()),
+        ]
+    );
+    assert_eq!(
+        RenderInterpreter::board_order(&spec, &rows),
+        vec![3, 1, 0, 2]
+    );
+}
@@ -0,0 +1,65 @@
+/// Tests for showing rows in the day/week/month buckets of a calendar
+use std::collections::{HashMap, HashSet};
+
+use holon_api::Value;
+use query_render::parse_query_render;
+use tui_r3bl_frontend::render_interpreter::RenderInterpreter;
+use tui_r3bl_frontend::UIElement;
+
+/// (whether the element starts a bucket, text content) of each element
+fn outline(elements: &[UIElement]) -> Vec<(bool, String)> {
+    fn content(element: &UIElement) -> String {
+        match element {
+            UIElement::Text { content, .. } => content.clone(),
+            other => panic!("expected text, got {:?}", other),
+        }
+    }
+    elements
+        .iter()
+        .map(|element| match element {
+            UIElement::Section { child, .. } => (true, content(child)),
+            other => (false, content(other)),
+        })
+        .collect()
+}
+
+#[test]
+fn test_rows_in_week_buckets() {
+    // Noon UTC, so the days are the same in any terminal time zone
+    let rows: Vec<HashMap<String, Value>> = [
+        ("a", Some("2024-03-06T12:00:00Z")),
+        ("b", None),
+        ("c", Some("2024-02-28T12:00:00Z")),
+        ("d", Some("2024-03-04T12:00:00Z")),
+    ]
+    .iter()
+    .map(|(content, due)| {
+        HashMap::from([
+            ("content".to_string(), Value::String(content.to_string())),
+            (
+                "due_date".to_string(),
+                due.map(|due| Value::DateTime(due.to_string()))
+                    .unwrap_or(Value::Null),
+            ),
+        ])
+    })
+    .collect();
+
+    let prql = r#"
+from tasks
+render (calendar date:this.due_date period:"week" sort_by:due_date item_template:(text content))
+    "#;
+    let (_sql, spec) = parse_query_render(prql).unwrap();
+    let elements = RenderInterpreter::build_element_tree(&spec, &rows, 0, &HashSet::new());
+
+    assert_eq!(
+        outline(&elements),
+        vec![
+            (true, "c".to_string()),
+            (true, "d".to_string()),
+            (false, "a".to_string()),
+            // Rows without a date come last
+            (true, "b".to_string()),
+        ]
+    );
+}
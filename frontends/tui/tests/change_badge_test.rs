@@ -0,0 +1,69 @@
+/// Tests for badging rows last changed outside the TUI
+use std::collections::{HashMap, HashSet};
+
+use holon_api::{ChangeOrigin, ChangeSource, Value, CHANGE_ORIGIN_COLUMN};
+use query_render::parse_query_render;
+use tui_r3bl_frontend::render_interpreter::RenderInterpreter;
+use tui_r3bl_frontend::UIElement;
+
+fn row(id: &str, origin: Option<ChangeOrigin>) -> HashMap<String, Value> {
+    let mut row = HashMap::from([
+        ("id".to_string(), Value::String(id.to_string())),
+        ("content".to_string(), Value::String(id.to_uppercase())),
+    ]);
+    if let Some(origin) = origin {
+        row.insert(
+            CHANGE_ORIGIN_COLUMN.to_string(),
+            Value::String(origin.to_json()),
+        );
+    }
+    row
+}
+
+fn badge(element: &UIElement) -> Option<String> {
+    match element {
+        UIElement::Row { children } => children.iter().find_map(|child| match child {
+            UIElement::Badge { content, .. } => Some(content.trim().to_string()),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_external_changes_are_badged() {
+    let (_sql, spec) =
+        parse_query_render("from blocks\nrender (list item_template:(text content))").unwrap();
+    let local =
+        |source: ChangeSource| ChangeOrigin::local_with_trace(None, None).with_source(source);
+    let rows = vec![
+        row("a", None),
+        row(
+            "b",
+            Some(local(ChangeSource::default().with_frontend("tui"))),
+        ),
+        row(
+            "c",
+            Some(
+                ChangeOrigin::remote_with_trace(None, None)
+                    .with_source(ChangeSource::default().with_provider("todoist")),
+            ),
+        ),
+        row(
+            "d",
+            Some(local(ChangeSource::default().with_agent("mcp:assistant"))),
+        ),
+    ];
+
+    let elements = RenderInterpreter::build_element_tree(&spec, &rows, 0, &HashSet::new());
+    let badges: Vec<Option<String>> = elements.iter().map(badge).collect();
+    assert_eq!(
+        badges,
+        vec![
+            None,
+            None,
+            Some("⇄ todoist".to_string()),
+            Some("⇄ mcp:assistant".to_string())
+        ]
+    );
+}
@@ -0,0 +1,53 @@
+/// Tests for showing the comments of a row below it
+use std::collections::{HashMap, HashSet};
+
+use holon::core::annotations::{Annotation, COMMENTS_COLUMN};
+use holon_api::Value;
+use query_render::parse_query_render;
+use tui_r3bl_frontend::render_interpreter::RenderInterpreter;
+use tui_r3bl_frontend::UIElement;
+
+fn task(id: &str, comments: &[Annotation]) -> HashMap<String, Value> {
+    let comments = match comments {
+        [] => Value::Null,
+        comments => Value::String(serde_json::to_string(comments).unwrap()),
+    };
+    HashMap::from([
+        ("id".to_string(), Value::String(id.to_string())),
+        ("content".to_string(), Value::String(format!("Task {}", id))),
+        (COMMENTS_COLUMN.to_string(), comments),
+    ])
+}
+
+/// Text of the `comments` widget in a row
+fn comments_text(element: &UIElement) -> String {
+    match element {
+        UIElement::Row { children } => match &children[1] {
+            UIElement::Text { content, .. } => content.clone(),
+            other => panic!("expected the comments text, got {:?}", other),
+        },
+        other => panic!("expected a row, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_comments_expand_below_the_count() {
+    let mut resolved = Annotation::new("tasks", "t1", "alice", "Needs numbers", 1);
+    resolved.resolved = true;
+    let open = Annotation::new("tasks", "t1", "bob", "See\nthe Q3 report", 2);
+    let rows = vec![task("t1", &[resolved, open]), task("t2", &[])];
+
+    let prql = r#"
+from tasks
+render (list item_template:(row (text content:this.content) (comments this.id)))
+    "#;
+    let (_sql, spec) = parse_query_render(prql).unwrap();
+    let elements = RenderInterpreter::build_element_tree(&spec, &rows, 0, &HashSet::new());
+
+    assert_eq!(
+        comments_text(&elements[0]),
+        " 💬 1/2 \n   ✓ alice: Needs numbers\n   • bob: See the Q3 report"
+    );
+    // Rows without comments show nothing
+    assert_eq!(comments_text(&elements[1]), "");
+}
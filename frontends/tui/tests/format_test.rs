@@ -0,0 +1,43 @@
+/// Tests for the locale-aware formatting functions in render expressions
+use std::collections::{HashMap, HashSet};
+
+use holon_api::Value;
+use query_render::parse_query_render;
+use tui_r3bl_frontend::render_interpreter::RenderInterpreter;
+use tui_r3bl_frontend::UIElement;
+
+fn texts(prql: &str, row: HashMap<String, Value>) -> Vec<String> {
+    let (_sql, spec) = parse_query_render(prql).unwrap();
+    RenderInterpreter::build_element_tree(&spec, &[row], 0, &HashSet::new())
+        .into_iter()
+        .map(|element| match element {
+            UIElement::Text { content, .. } => content,
+            other => panic!("expected text, got {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_formatted_text() {
+    // Without grouping or decimals, numbers read the same in every locale
+    let row = HashMap::from([("count".to_string(), Value::Integer(42))]);
+    assert_eq!(
+        texts(
+            "from tasks\nrender (list item_template:(text content:(format_number this.count decimals:0)))",
+            row,
+        ),
+        vec!["42"]
+    );
+}
+
+#[test]
+fn test_unformattable_value_is_shown_as_is() {
+    let row = HashMap::from([("due_date".to_string(), Value::String("someday".to_string()))]);
+    assert_eq!(
+        texts(
+            "from tasks\nrender (list item_template:(text content:(format_date this.due_date style:\"long\")))",
+            row,
+        ),
+        vec!["someday"]
+    );
+}
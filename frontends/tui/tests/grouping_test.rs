@@ -0,0 +1,74 @@
+/// Tests for ordering and grouping list rows with sort_by:/group_by:
+use std::collections::{HashMap, HashSet};
+
+use holon_api::Value;
+use query_render::parse_query_render;
+use tui_r3bl_frontend::render_interpreter::RenderInterpreter;
+use tui_r3bl_frontend::UIElement;
+
+fn rows() -> Vec<HashMap<String, Value>> {
+    [("a", "Home", 1), ("b", "Work", 3), ("c", "Home", 2)]
+        .iter()
+        .map(|(id, project, priority)| {
+            HashMap::from([
+                ("id".to_string(), Value::String(id.to_string())),
+                ("content".to_string(), Value::String(id.to_uppercase())),
+                ("project".to_string(), Value::String(project.to_string())),
+                ("priority".to_string(), Value::Integer(*priority)),
+            ])
+        })
+        .collect()
+}
+
+/// (section title, text content) of each element
+fn outline(elements: &[UIElement]) -> Vec<(Option<String>, String)> {
+    fn content(element: &UIElement) -> String {
+        match element {
+            UIElement::Text { content, .. } => content.clone(),
+            other => panic!("expected text, got {:?}", other),
+        }
+    }
+    elements
+        .iter()
+        .map(|element| match element {
+            UIElement::Section { title, child } => (Some(title.clone()), content(child)),
+            other => (None, content(other)),
+        })
+        .collect()
+}
+
+#[test]
+fn test_sort_by_descending() {
+    let (_sql, spec) = parse_query_render(
+        "from blocks\nrender (list sort_by:[-priority] item_template:(text content:this.content))",
+    )
+    .unwrap();
+
+    let elements = RenderInterpreter::build_element_tree(&spec, &rows(), 0, &HashSet::new());
+    assert_eq!(
+        outline(&elements),
+        vec![
+            (None, "B".to_string()),
+            (None, "C".to_string()),
+            (None, "A".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_group_by_adds_section_headers() {
+    let (_sql, spec) = parse_query_render(
+        "from blocks\nrender (list group_by:project sort_by:priority item_template:(text content:this.content))",
+    )
+    .unwrap();
+
+    let elements = RenderInterpreter::build_element_tree(&spec, &rows(), 0, &HashSet::new());
+    assert_eq!(
+        outline(&elements),
+        vec![
+            (Some("Home".to_string()), "A".to_string()),
+            (None, "C".to_string()),
+            (Some("Work".to_string()), "B".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,17 @@
+/// Tests for showing backend notifications in the status bar
+use holon::core::notifications::{Notification, NotificationSeverity};
+use tui_r3bl_frontend::notifications::status_line;
+
+#[test]
+fn test_status_line() {
+    let reminder = Notification::new("File taxes", "Due in 1 hour", NotificationSeverity::Info)
+        .with_entity("todoist_tasks", "t1");
+    assert_eq!(status_line(&reminder), "File taxes: Due in 1 hour");
+
+    let overdue = Notification::new(
+        "File taxes",
+        "Overdue by 2 days",
+        NotificationSeverity::Warning,
+    );
+    assert_eq!(status_line(&overdue), "! File taxes: Overdue by 2 days");
+}
@@ -0,0 +1,161 @@
+/// Tests for the operations debug screen: listing, prompting and parsing params
+use holon_api::{
+    OperationDescriptor, OperationParam, PreconditionChecker, PreconditionViolation, TypeHint,
+    Value,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tui_r3bl_frontend::operations_screen::{parse_param, OperationsScreen};
+
+fn descriptor(entity_name: &str, name: &str, params: &[(&str, TypeHint)]) -> OperationDescriptor {
+    OperationDescriptor {
+        entity_name: entity_name.to_string(),
+        entity_short_name: entity_name.to_string(),
+        id_column: "id".to_string(),
+        name: name.to_string(),
+        display_name: name.to_string(),
+        description: String::new(),
+        required_params: params
+            .iter()
+            .map(|(name, type_hint)| OperationParam {
+                name: name.to_string(),
+                type_hint: type_hint.clone(),
+                description: String::new(),
+            })
+            .collect(),
+        affected_fields: vec![],
+        param_mappings: vec![],
+        precondition: None,
+        simulation: None,
+    }
+}
+
+fn screen() -> OperationsScreen {
+    let mut screen = OperationsScreen::loading();
+    screen.set_operations(vec![
+        descriptor(
+            "todoist_tasks",
+            "set_completion",
+            &[("id", TypeHint::String), ("completed", TypeHint::Bool)],
+        ),
+        descriptor("logseq_blocks", "indent", &[("id", TypeHint::String)]),
+        descriptor("*", "sync", &[]),
+    ]);
+    screen
+}
+
+fn type_text(screen: &mut OperationsScreen, text: &str) {
+    for c in text.chars() {
+        screen.type_char(c);
+    }
+}
+
+#[test]
+fn test_lists_operations_grouped_by_entity() {
+    let lines = screen().lines(20);
+    assert_eq!(
+        lines,
+        vec![
+            "*:",
+            "> sync()",
+            "logseq_blocks:",
+            "  indent(id: string)",
+            "todoist_tasks:",
+            "  set_completion(id: string, completed: bool)",
+        ]
+    );
+}
+
+#[test]
+fn test_operation_without_params_runs_immediately() {
+    let mut screen = screen();
+    let operation = screen.start_prompt().unwrap();
+    assert_eq!(operation.entity_name, "*");
+    assert_eq!(operation.op_name, "sync");
+    assert!(operation.params.is_empty());
+    assert!(!screen.is_prompting());
+}
+
+#[test]
+fn test_prompts_for_each_param() {
+    let mut screen = screen();
+    screen.select_next();
+    screen.select_next();
+    assert!(screen.start_prompt().is_none());
+
+    type_text(&mut screen, "task-1");
+    assert!(screen.submit().is_none());
+    type_text(&mut screen, "maybe");
+    assert!(screen.submit().is_none());
+    assert!(screen
+        .lines(20)
+        .iter()
+        .any(|line| line.contains("'maybe' is not a boolean")));
+
+    for _ in 0..5 {
+        screen.backspace();
+    }
+    type_text(&mut screen, "yes");
+    let operation = screen.submit().unwrap();
+    assert_eq!(operation.op_name, "set_completion");
+    assert_eq!(
+        operation.params,
+        HashMap::from([
+            ("id".to_string(), Value::String("task-1".to_string())),
+            ("completed".to_string(), Value::Boolean(true)),
+        ])
+    );
+}
+
+#[test]
+fn test_violated_precondition_prompts_again() {
+    let mut op = descriptor("logseq_blocks", "indent", &[("id", TypeHint::String)]);
+    let checker: Box<PreconditionChecker> = Box::new(|_params| {
+        Ok(Some(PreconditionViolation {
+            operation: "indent".to_string(),
+            clause: "has_previous_sibling".to_string(),
+            message: Some("Block has no previous sibling".to_string()),
+            params: vec![],
+        }))
+    });
+    op.precondition = Some(Arc::new(checker));
+    let mut screen = OperationsScreen::loading();
+    screen.set_operations(vec![op]);
+    assert!(screen.lines(20)[1].ends_with("[precondition]"));
+
+    screen.start_prompt();
+    type_text(&mut screen, "block-1");
+    assert!(screen.submit().is_none());
+    assert!(screen.is_prompting());
+    assert!(screen
+        .lines(20)
+        .iter()
+        .any(|line| line.contains("Precondition failed: Block has no previous sibling")));
+}
+
+#[test]
+fn test_parse_param() {
+    assert_eq!(
+        parse_param(&TypeHint::Number, " 42 "),
+        Ok(Value::Integer(42))
+    );
+    assert!(parse_param(&TypeHint::Number, "forty").is_err());
+    assert_eq!(parse_param(&TypeHint::Bool, "0"), Ok(Value::Boolean(false)));
+    assert!(parse_param(
+        &TypeHint::EntityId {
+            entity_name: "project".to_string()
+        },
+        ""
+    )
+    .is_err());
+}
+
+#[test]
+fn test_selected_operation_stays_visible() {
+    let mut screen = screen();
+    screen.select_next();
+    screen.select_next();
+    let lines = screen.lines(2);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].starts_with("> set_completion"));
+}
@@ -0,0 +1,55 @@
+/// Tests for rendering the rows of a UNION query whose templates partly failed to compile
+use std::collections::{HashMap, HashSet};
+
+use holon_api::{Value, WidgetArgType, WidgetParam, WidgetSpec};
+use query_render::{parse_query_render_to_rq_with_widgets, WidgetRegistry};
+use tui_r3bl_frontend::render_interpreter::RenderInterpreter;
+use tui_r3bl_frontend::UIElement;
+
+fn row(ui: i64, name: &str) -> HashMap<String, Value> {
+    HashMap::from([
+        ("ui".to_string(), Value::Integer(ui)),
+        ("content".to_string(), Value::String(name.to_string())),
+        ("name".to_string(), Value::String(name.to_string())),
+    ])
+}
+
+#[test]
+fn test_failed_template_renders_error_card() {
+    let prql = r#"
+from todoist_tasks
+derive { ui = (render (row (text content:this.content))) }
+append (
+  from todoist_projects
+  derive { ui = (render (row (txt this.name))) }
+)
+render (list item_template:this.ui)
+"#;
+    let widgets = WidgetRegistry::new([
+        WidgetSpec::new("list")
+            .with_param(WidgetParam::required("item_template", WidgetArgType::Any)),
+        WidgetSpec::new("row").with_variadic(WidgetArgType::Widget),
+        WidgetSpec::new("text").with_param(WidgetParam::required("content", WidgetArgType::String)),
+    ]);
+    let parsed =
+        parse_query_render_to_rq_with_widgets(prql, &HashSet::new(), Some(&widgets)).unwrap();
+
+    let rows = vec![row(0, "Buy milk"), row(1, "Groceries")];
+    let elements =
+        RenderInterpreter::build_element_tree(&parsed.render_spec, &rows, 0, &HashSet::new());
+
+    // The task still renders with its template, the project as an error card
+    match &elements[0] {
+        UIElement::Row { children } => assert!(matches!(
+            &children[0],
+            UIElement::Text { content, .. } if content == "Buy milk"
+        )),
+        other => panic!("expected the task's row, got {:?}", other),
+    }
+    match &elements[1] {
+        UIElement::Badge { content, .. } => {
+            assert!(content.contains("unknown widget `txt`"), "{}", content)
+        }
+        other => panic!("expected an error card, got {:?}", other),
+    }
+}
@@ -0,0 +1,61 @@
+/// Tests for rendering the multi-selection of selectable lists
+use std::collections::{HashMap, HashSet};
+
+use holon_api::Value;
+use query_render::parse_query_render;
+use tui_r3bl_frontend::render_interpreter::RenderInterpreter;
+use tui_r3bl_frontend::UIElement;
+
+fn rows() -> Vec<HashMap<String, Value>> {
+    ["a", "b", "c"]
+        .iter()
+        .map(|id| {
+            HashMap::from([
+                ("id".to_string(), Value::String(id.to_string())),
+                ("content".to_string(), Value::String(id.to_uppercase())),
+            ])
+        })
+        .collect()
+}
+
+fn selection_marks(elements: &[UIElement]) -> Vec<Option<bool>> {
+    elements
+        .iter()
+        .map(|element| match element {
+            UIElement::Row { children } => match children.first() {
+                Some(UIElement::SelectionMark { selected }) => Some(*selected),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_selectable_list_marks_selected_rows() {
+    let (_sql, spec) = parse_query_render(
+        "from blocks\nrender (list selectable:true item_template:(text content))",
+    )
+    .unwrap();
+    let selected = HashSet::from(["b".to_string()]);
+
+    let elements = RenderInterpreter::build_element_tree(&spec, &rows(), 0, &selected);
+    assert_eq!(
+        selection_marks(&elements),
+        vec![Some(false), Some(true), Some(false)]
+    );
+}
+
+#[test]
+fn test_selection_checkbox_column_can_be_hidden() {
+    let selected = HashSet::from(["b".to_string()]);
+
+    for prql in [
+        "from blocks\nrender (list item_template:(text content))",
+        "from blocks\nrender (list selectable:true selection_checkbox:false item_template:(text content))",
+    ] {
+        let (_sql, spec) = parse_query_render(prql).unwrap();
+        let elements = RenderInterpreter::build_element_tree(&spec, &rows(), 0, &selected);
+        assert_eq!(selection_marks(&elements), vec![None, None, None]);
+    }
+}
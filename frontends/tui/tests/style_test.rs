@@ -0,0 +1,70 @@
+/// Tests for resolving the `style` argument of widgets
+use std::collections::{HashMap, HashSet};
+
+use holon_api::Value;
+use query_render::parse_query_render;
+use tui_r3bl_frontend::render_interpreter::RenderInterpreter;
+use tui_r3bl_frontend::stylesheet::{self, TextAttributes};
+use tui_r3bl_frontend::UIElement;
+
+fn rows() -> Vec<HashMap<String, Value>> {
+    [("a", false), ("b", true)]
+        .iter()
+        .map(|(id, done)| {
+            HashMap::from([
+                ("id".to_string(), Value::String(id.to_string())),
+                ("content".to_string(), Value::String(id.to_uppercase())),
+                ("done".to_string(), Value::Boolean(*done)),
+            ])
+        })
+        .collect()
+}
+
+#[test]
+fn test_text_style_is_resolved_per_row() {
+    let (_sql, spec) = parse_query_render(
+        "from blocks\nrender (list item_template:(text content:this.content style:[(style color:\"red\" bold:true), (style dim:true when:this.done)]))",
+    )
+    .unwrap();
+
+    let elements = RenderInterpreter::build_element_tree(&spec, &rows(), 0, &HashSet::new());
+    let styles: Vec<_> = elements
+        .iter()
+        .map(|element| match element {
+            UIElement::Text {
+                fg_color,
+                attributes,
+                ..
+            } => (*fg_color, *attributes),
+            other => panic!("expected text, got {:?}", other),
+        })
+        .collect();
+
+    let red = stylesheet::style_color("red");
+    assert!(red.is_some());
+    let bold = TextAttributes {
+        bold: true,
+        ..TextAttributes::default()
+    };
+    assert_eq!(
+        styles,
+        vec![(red, bold), (red, TextAttributes { dim: true, ..bold })]
+    );
+}
+
+#[test]
+fn test_padding_surrounds_widget() {
+    let (_sql, spec) = parse_query_render(
+        "from blocks\nrender (list item_template:(badge content:this.content style:(style padding:2)))",
+    )
+    .unwrap();
+
+    let elements = RenderInterpreter::build_element_tree(&spec, &rows(), 0, &HashSet::new());
+    match &elements[0] {
+        UIElement::Row { children } => {
+            assert!(matches!(&children[0], UIElement::Text { content, .. } if content == "  "));
+            assert!(matches!(&children[1], UIElement::Badge { content, .. } if content == " [A] "));
+        }
+        other => panic!("expected padded badge, got {:?}", other),
+    }
+}
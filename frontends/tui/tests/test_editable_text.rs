@@ -1,5 +1,6 @@
 use holon_api::{OperationDescriptor, OperationParam, OperationWiring, TypeHint};
 /// Integration tests for editable_text widget functionality
+use tui_r3bl_frontend::stylesheet::TextAttributes;
 use tui_r3bl_frontend::UIElement;
 
 // Helper function to extract field name from OperationWiring
@@ -36,7 +37,9 @@ fn test_editable_text_creation() {
                 },
             ],
             precondition: None,
+            simulation: None,
         },
+        accepts_multiple: false,
     }];
 
     let editable = UIElement::EditableText {
@@ -44,6 +47,7 @@ fn test_editable_text_creation() {
         operations: operations.clone(),
         fg_color: None,
         bg_color: None,
+        attributes: TextAttributes::default(),
     };
 
     assert!(editable.is_editable());
@@ -79,7 +83,9 @@ fn test_editable_text_get_operation() {
                 },
             ],
             precondition: None,
+            simulation: None,
         },
+        accepts_multiple: false,
     }];
 
     let editable = UIElement::EditableText {
@@ -87,6 +93,7 @@ fn test_editable_text_get_operation() {
         operations: operations.clone(),
         fg_color: None,
         bg_color: None,
+        attributes: TextAttributes::default(),
     };
 
     let op = editable.get_operation();
@@ -123,7 +130,9 @@ fn test_editable_text_in_row() {
                 },
             ],
             precondition: None,
+            simulation: None,
         },
+        accepts_multiple: false,
     }];
 
     let row = UIElement::Row {
@@ -132,12 +141,14 @@ fn test_editable_text_in_row() {
                 content: "Prefix: ".to_string(),
                 fg_color: None,
                 bg_color: None,
+                attributes: TextAttributes::default(),
             },
             UIElement::EditableText {
                 content: "Editable content".to_string(),
                 operations: operations.clone(),
                 fg_color: None,
                 bg_color: None,
+                attributes: TextAttributes::default(),
             },
         ],
     };
@@ -155,6 +166,7 @@ fn test_is_editable() {
         operations: vec![],
         fg_color: None,
         bg_color: None,
+        attributes: TextAttributes::default(),
     };
 
     assert!(editable.is_editable());
@@ -163,6 +175,7 @@ fn test_is_editable() {
         content: "Test".to_string(),
         fg_color: None,
         bg_color: None,
+        attributes: TextAttributes::default(),
     };
 
     assert!(!text.is_editable());
@@ -0,0 +1,64 @@
+#!/bin/bash
+# Run the query pipeline benchmarks and check them against the performance budget
+#
+# Usage:
+#   ./scripts/check-bench-budget.sh            # run benchmarks, then check
+#   ./scripts/check-bench-budget.sh --no-run   # only check the last results
+#
+# Budgets are listed in scripts/bench-budget.txt. The script fails if a benchmark's
+# mean time exceeds its budget or a budgeted benchmark has no results.
+# To compare two revisions in detail, use criterion's baselines instead:
+#   cargo bench -p holon -p query-render -- --save-baseline before
+#   cargo bench -p holon -p query-render -- --baseline before
+#
+# Prerequisites:
+#   - jq
+
+set -e
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+BUDGET_FILE="$SCRIPT_DIR/bench-budget.txt"
+CRITERION_DIR="$PROJECT_ROOT/target/criterion"
+
+if ! command -v jq &> /dev/null; then
+    echo "❌ jq is required: https://jqlang.github.io/jq/download/"
+    exit 1
+fi
+
+if [ "$1" != "--no-run" ]; then
+    echo "⏱️  Running benchmarks..."
+    (cd "$PROJECT_ROOT" && cargo bench -p query-render -p holon --bench query_pipeline)
+    echo ""
+fi
+
+echo "📊 Checking results against $BUDGET_FILE"
+echo ""
+
+FAILED=0
+while read -r BENCH BUDGET_US; do
+    # Skip comments and blank lines
+    [[ -z "$BENCH" || "$BENCH" == \#* ]] && continue
+
+    ESTIMATES="$CRITERION_DIR/$BENCH/new/estimates.json"
+    if [ ! -f "$ESTIMATES" ]; then
+        echo "❌ $BENCH: no results (expected $ESTIMATES)"
+        FAILED=1
+        continue
+    fi
+
+    MEAN_US=$(jq '.mean.point_estimate / 1000' "$ESTIMATES")
+    if jq -e --argjson mean "$MEAN_US" --argjson budget "$BUDGET_US" -n '$mean > $budget' > /dev/null; then
+        printf "❌ %-42s %12.1f µs (budget %s µs)\n" "$BENCH" "$MEAN_US" "$BUDGET_US"
+        FAILED=1
+    else
+        printf "✅ %-42s %12.1f µs (budget %s µs)\n" "$BENCH" "$MEAN_US" "$BUDGET_US"
+    fi
+done < "$BUDGET_FILE"
+
+echo ""
+if [ "$FAILED" -ne 0 ]; then
+    echo "❌ Performance budget exceeded"
+    exit 1
+fi
+echo "✅ All benchmarks within budget"